@@ -0,0 +1,27 @@
+// fuzz/fuzz_targets/callback_query.rs
+
+//! oauth2-client callback query parsing
+//!
+//! The `/callback` query string arrives from a browser redirect and is fully
+//! attacker-controlled. Runs it through axum's `Query` extractor exactly as the
+//! handler receives it.
+
+#![no_main]
+
+use axum::{extract::Query, http::Uri};
+use libfuzzer_sys::fuzz_target;
+use oauth2_client::CallbackQuery;
+
+// ---
+
+fuzz_target!(|data: &[u8]| {
+    // ---
+    let Ok(query) = std::str::from_utf8(data) else {
+        return;
+    };
+    let Ok(uri) = format!("/callback?{query}").parse::<Uri>() else {
+        return;
+    };
+
+    let _ = Query::<CallbackQuery>::try_from_uri(&uri);
+});
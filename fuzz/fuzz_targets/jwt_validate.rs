@@ -0,0 +1,40 @@
+// fuzz/fuzz_targets/jwt_validate.rs
+
+//! JWT parsing and validation on attacker-controlled input
+//!
+//! Feeds arbitrary bytes through the same path jwt-service and oauth2-server
+//! use for incoming credentials: `Authorization` header parsing followed by
+//! signature/claims validation. Any panic is a bug; every input must map to a
+//! `TokenError` or `AuthHeaderError` (or, astronomically unlikely, valid claims).
+
+#![no_main]
+
+use http::{header::AUTHORIZATION, HeaderMap, HeaderValue};
+use libfuzzer_sys::fuzz_target;
+use tokn_core::{bearer_token, validate_token, SystemClock};
+
+// ---
+
+const SECRET: &str = "fuzzing-secret-key-at-least-32-characters";
+
+// ---
+
+fuzz_target!(|data: &[u8]| {
+    // ---
+    // Raw token, as posted to /v1/auth/validate, /v1/auth/revoke
+    if let Ok(token) = std::str::from_utf8(data) {
+        let _ = validate_token(token, SECRET, &SystemClock);
+    }
+
+    // ---
+    // Header path, as seen by protected routes and /v1/oauth/userinfo
+    let Ok(value) = HeaderValue::from_bytes(data) else {
+        return;
+    };
+    let mut headers = HeaderMap::new();
+    headers.insert(AUTHORIZATION, value);
+
+    if let Ok(token) = bearer_token(&headers) {
+        let _ = validate_token(token, SECRET, &SystemClock);
+    }
+});
@@ -0,0 +1,23 @@
+// fuzz/fuzz_targets/token_request.rs
+
+//! oauth2-server token endpoint body parsing
+//!
+//! `token_handler` takes the raw `application/x-www-form-urlencoded` body and
+//! deserializes it into `TokenRequest` before touching the database, so this
+//! is the first code an unauthenticated client reaches.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use oauth2_server::TokenRequest;
+
+// ---
+
+fuzz_target!(|data: &[u8]| {
+    // ---
+    let Ok(body) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    let _ = serde_urlencoded::from_str::<TokenRequest>(body);
+});
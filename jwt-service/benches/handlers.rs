@@ -0,0 +1,189 @@
+// jwt-service/benches/handlers.rs
+
+//! Full handler-path benchmarks
+//!
+//! Drives the real router (routing, JSON extraction, signing, Redis I/O,
+//! serialization) in-process via `tower::ServiceExt::oneshot`. Requires a Redis
+//! at `REDIS_URL` (default `redis://127.0.0.1:6379`); the benchmarks are
+//! skipped with a notice when Redis is unreachable.
+//!
+//! ```bash
+//! docker compose up -d redis
+//! cargo bench -p jwt-service --bench handlers
+//! ```
+
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, Method, Request, StatusCode},
+    Router,
+};
+use criterion::{criterion_group, criterion_main, Criterion};
+use jwt_service::{
+    build_router, create_redis_client, AppState, Config, JwtConfig, RedisConfig, RedisConnection,
+    ServerConfig, SystemClock,
+};
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+use tower::ServiceExt;
+
+// ---
+
+const SECRET: &str = "benchmark-secret-key-at-least-32-characters";
+
+// ---
+
+/// Send a JSON POST through the router and return the decoded response body.
+async fn post_json(app: &Router, uri: &str, body: Value) -> (StatusCode, Value) {
+    // ---
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+    (
+        status,
+        serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+    )
+}
+
+// ---
+
+/// Issue an access/refresh token pair through `POST /v1/auth/token`.
+async fn issue(app: &Router) -> (String, String) {
+    // ---
+    let (_, body) = post_json(
+        app,
+        "/v1/auth/token",
+        json!({ "user_id": "user_bench", "email": "bench@example.com" }),
+    )
+    .await;
+
+    (
+        body["access_token"].as_str().unwrap().to_string(),
+        body["refresh_token"].as_str().unwrap().to_string(),
+    )
+}
+
+// ---
+
+fn bench_handlers(c: &mut Criterion) {
+    // ---
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let redis_url =
+        std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
+
+    let redis = match runtime.block_on(create_redis_client(&redis_url)) {
+        Ok(conn) => conn,
+        Err(e) => {
+            eprintln!("Skipping handler benchmarks: Redis unavailable at {redis_url}: {e}");
+            return;
+        }
+    };
+
+    let config = Config {
+        profile: Default::default(),
+        server: ServerConfig {
+            host: "127.0.0.1".to_string(),
+            port: 0,
+            bind: None,
+            socket_mode: None,
+            tls: None,
+            compression: Default::default(),
+            grpc_addr: None,
+            drain_timeout_seconds: 30,
+        },
+        redis: RedisConfig { url: redis_url },
+        jwt: JwtConfig {
+            algorithm: Default::default(),
+            secret: Some(SECRET.into()),
+            previous_secret: None,
+            secret_rotated_at: None,
+            private_key_path: None,
+            public_key_path: None,
+            access_token_expiry_seconds: 900,
+            refresh_token_expiry_seconds: 604800,
+            refresh_token_sliding: true,
+            max_session_seconds: None,
+            stateless: false,
+            keys: Vec::new(),
+            current_kid: None,
+        },
+        startup: Default::default(),
+        circuit_breaker: Default::default(),
+        log: Default::default(),
+        admin: Default::default(),
+        api: Default::default(),
+        events: Default::default(),
+        audit: Default::default(),
+        mail: Default::default(),
+    };
+    let redis = RedisConnection::new(redis, config.circuit_breaker);
+    let app = build_router(AppState {
+        keys: config.jwt.keys().unwrap().into(),
+        config: config.into(),
+        redis: Some(redis),
+        events: Default::default(),
+        audit: Default::default(),
+        mail: Default::default(),
+        clock: SystemClock::shared(),
+    });
+
+    let (access_token, _) = runtime.block_on(issue(&app));
+
+    // ---
+    c.bench_function("handler/generate", |b| {
+        b.to_async(&runtime).iter(|| issue(&app))
+    });
+
+    c.bench_function("handler/validate", |b| {
+        b.to_async(&runtime)
+            .iter(|| post_json(&app, "/v1/auth/validate", json!({ "token": access_token })))
+    });
+
+    c.bench_function("handler/protected", |b| {
+        b.to_async(&runtime).iter(|| async {
+            let request = Request::builder()
+                .uri("/v1/protected")
+                .header(header::AUTHORIZATION, format!("Bearer {access_token}"))
+                .body(Body::empty())
+                .unwrap();
+            app.clone().oneshot(request).await.unwrap()
+        })
+    });
+
+    // Refresh consumes its input, so issue a fresh pair per iteration outside
+    // the timed section.
+    c.bench_function("handler/refresh", |b| {
+        b.to_async(&runtime).iter_custom(|iters| {
+            let app = app.clone();
+            async move {
+                // ---
+                let mut elapsed = Duration::ZERO;
+                for _ in 0..iters {
+                    let (_, refresh_token) = issue(&app).await;
+                    let start = Instant::now();
+                    let (status, _) = post_json(
+                        &app,
+                        "/v1/auth/refresh",
+                        json!({ "refresh_token": refresh_token }),
+                    )
+                    .await;
+                    elapsed += start.elapsed();
+                    assert_eq!(status, StatusCode::OK);
+                }
+                elapsed
+            }
+        })
+    });
+}
+
+// ---
+
+criterion_group!(benches, bench_handlers);
+criterion_main!(benches);
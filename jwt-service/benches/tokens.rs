@@ -0,0 +1,214 @@
+// jwt-service/benches/tokens.rs
+
+//! Token generation, validation, and refresh rotation benchmarks
+//!
+//! Refresh rotation runs against an in-memory Redis stand-in so the numbers
+//! reflect our own code (serialization, key handling, signing) rather than
+//! network latency. Run with:
+//!
+//! ```bash
+//! cargo bench -p jwt-service --bench tokens
+//! ```
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use jwt_service::{
+    generate_refresh_token, generate_token, validate_refresh_token, validate_token, Claims,
+    SystemClock,
+};
+use redis::{aio::ConnectionLike, Arg, Cmd, Pipeline, RedisFuture, Value};
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::sync::{Arc, Mutex};
+
+// ---
+
+const SECRET: &str = "benchmark-secret-key-at-least-32-characters";
+
+// ---
+
+/// Minimal in-memory Redis supporting the commands the token modules issue
+/// (`SETEX`, `GET`, `DEL`, `EXISTS`, `TTL`), alone or pipelined. TTLs are
+/// accepted and ignored; `TTL` reports a fixed 60 seconds for any existing
+/// key.
+#[derive(Clone, Default)]
+struct MemoryRedis {
+    // ---
+    data: Arc<Mutex<HashMap<Vec<u8>, Vec<u8>>>>,
+}
+
+// ---
+
+impl MemoryRedis {
+    // ---
+    /// Store a refresh token directly, bypassing the async API (for untimed setup).
+    fn seed_refresh_token(&self) -> String {
+        // ---
+        let token = uuid::Uuid::new_v4().to_string();
+        let value = serde_json::json!({ "user_id": "user_bench", "email": "bench@example.com" });
+
+        self.data.lock().unwrap().insert(
+            tokn_core::keys::refresh_token(&token).into_bytes(),
+            value.to_string().into_bytes(),
+        );
+
+        token
+    }
+
+    // ---
+    fn execute(&self, cmd: &Cmd) -> Value {
+        // ---
+        let args: Vec<&[u8]> = cmd
+            .args_iter()
+            .filter_map(|arg| match arg {
+                Arg::Simple(bytes) => Some(bytes),
+                Arg::Cursor => None,
+            })
+            .collect();
+        let mut data = self.data.lock().unwrap();
+
+        match args.as_slice() {
+            [name, key, _ttl, value] if name.eq_ignore_ascii_case(b"SETEX") => {
+                data.insert(key.to_vec(), value.to_vec());
+                Value::Okay
+            }
+            [name, key] if name.eq_ignore_ascii_case(b"GET") => data
+                .get(*key)
+                .map(|v| Value::BulkString(v.clone()))
+                .unwrap_or(Value::Nil),
+            [name, key] if name.eq_ignore_ascii_case(b"DEL") => {
+                Value::Int(data.remove(*key).is_some() as i64)
+            }
+            [name, key] if name.eq_ignore_ascii_case(b"EXISTS") => {
+                Value::Int(data.contains_key(*key) as i64)
+            }
+            [name, key] if name.eq_ignore_ascii_case(b"TTL") => {
+                Value::Int(if data.contains_key(*key) { 60 } else { -2 })
+            }
+            _ => panic!("MemoryRedis: unsupported command"),
+        }
+    }
+}
+
+// ---
+
+impl ConnectionLike for MemoryRedis {
+    // ---
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
+        // ---
+        let value = self.execute(cmd);
+        Box::pin(async move { Ok(value) })
+    }
+
+    fn req_packed_commands<'a>(
+        &'a mut self,
+        cmd: &'a Pipeline,
+        offset: usize,
+        count: usize,
+    ) -> RedisFuture<'a, Vec<Value>> {
+        // ---
+        let values: Vec<Value> = cmd.cmd_iter().map(|cmd| self.execute(cmd)).collect();
+
+        // Only a transaction skips replies: those to `MULTI` and each queued
+        // command, leaving the results `EXEC` returns
+        let replies = if offset > 0 {
+            let queued = values
+                .iter()
+                .map(|_| Value::SimpleString("QUEUED".to_string()));
+            std::iter::once(Value::Okay)
+                .chain(queued)
+                .chain(std::iter::once(Value::Array(values)))
+                .collect()
+        } else {
+            values
+        };
+        let replies = replies.into_iter().skip(offset).take(count).collect();
+        Box::pin(async move { Ok(replies) })
+    }
+
+    fn get_db(&self) -> i64 {
+        // ---
+        0
+    }
+}
+
+// ---
+
+fn bench_generate(c: &mut Criterion) {
+    // ---
+    let claims = Claims::new(
+        "user_bench".into(),
+        "bench@example.com".into(),
+        900,
+        &SystemClock,
+    );
+
+    c.bench_function("token/generate", |b| {
+        b.iter(|| generate_token(black_box(&claims), black_box(SECRET)).unwrap())
+    });
+}
+
+// ---
+
+fn bench_validate(c: &mut Criterion) {
+    // ---
+    let claims = Claims::new(
+        "user_bench".into(),
+        "bench@example.com".into(),
+        900,
+        &SystemClock,
+    );
+    let token = generate_token(&claims, SECRET).unwrap();
+
+    c.bench_function("token/validate", |b| {
+        b.iter(|| validate_token(black_box(&token), black_box(SECRET), &SystemClock).unwrap())
+    });
+
+    let tampered = format!("{}x", token);
+    c.bench_function("token/validate_rejected", |b| {
+        b.iter(|| {
+            validate_token(black_box(&tampered), black_box(SECRET), &SystemClock).unwrap_err()
+        })
+    });
+}
+
+// ---
+
+fn bench_refresh_rotation(c: &mut Criterion) {
+    // ---
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let store = MemoryRedis::default();
+
+    c.bench_function("refresh/rotation", |b| {
+        b.to_async(&runtime).iter_batched(
+            || store.seed_refresh_token(),
+            |refresh_token| {
+                let mut conn = store.clone();
+                async move {
+                    // ---
+                    let user = validate_refresh_token(&mut conn, &refresh_token)
+                        .await
+                        .unwrap();
+                    let claims =
+                        Claims::new(user.user_id.clone(), user.email.clone(), 900, &SystemClock);
+                    let access_token = generate_token(&claims, SECRET).unwrap();
+                    let next_refresh = generate_refresh_token(&mut conn, &user, 604800)
+                        .await
+                        .unwrap();
+
+                    black_box((access_token, next_refresh))
+                }
+            },
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+// ---
+
+criterion_group!(
+    benches,
+    bench_generate,
+    bench_validate,
+    bench_refresh_rotation
+);
+criterion_main!(benches);
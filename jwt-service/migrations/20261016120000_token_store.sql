@@ -0,0 +1,61 @@
+-- Token store tables (TOKEN_STORE=postgres)
+--
+-- Prefixed so they can share a database with oauth2-server. IF NOT EXISTS
+-- adopts the tables earlier releases created on connect.
+
+CREATE TABLE IF NOT EXISTS jwt_refresh_tokens (
+    token TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    session_id TEXT,
+    data TEXT NOT NULL,
+    expires_at BIGINT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS jwt_refresh_tokens_user_id ON jwt_refresh_tokens (user_id);
+
+CREATE TABLE IF NOT EXISTS jwt_used_refresh_tokens (
+    token TEXT PRIMARY KEY,
+    data TEXT NOT NULL,
+    expires_at BIGINT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS jwt_refresh_successors (
+    token TEXT PRIMARY KEY,
+    successor TEXT NOT NULL,
+    expires_at BIGINT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS jwt_revoked_tokens (
+    jti TEXT PRIMARY KEY,
+    expires_at BIGINT NOT NULL
+);
+
+-- Token versions and revocation cutoffs never expire
+CREATE TABLE IF NOT EXISTS jwt_token_versions (
+    user_id TEXT PRIMARY KEY,
+    version BIGINT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS jwt_revoked_before (
+    user_id TEXT PRIMARY KEY,
+    revoked_before BIGINT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS jwt_tickets (
+    ticket TEXT PRIMARY KEY,
+    claims TEXT NOT NULL,
+    expires_at BIGINT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS jwt_magic_links (
+    token TEXT PRIMARY KEY,
+    claims TEXT NOT NULL,
+    expires_at BIGINT NOT NULL
+);
+
+-- For the expired row cleanup job
+CREATE INDEX IF NOT EXISTS jwt_refresh_tokens_expires_at ON jwt_refresh_tokens (expires_at);
+CREATE INDEX IF NOT EXISTS jwt_used_refresh_tokens_expires_at ON jwt_used_refresh_tokens (expires_at);
+CREATE INDEX IF NOT EXISTS jwt_refresh_successors_expires_at ON jwt_refresh_successors (expires_at);
+CREATE INDEX IF NOT EXISTS jwt_revoked_tokens_expires_at ON jwt_revoked_tokens (expires_at);
+CREATE INDEX IF NOT EXISTS jwt_tickets_expires_at ON jwt_tickets (expires_at);
+CREATE INDEX IF NOT EXISTS jwt_magic_links_expires_at ON jwt_magic_links (expires_at);
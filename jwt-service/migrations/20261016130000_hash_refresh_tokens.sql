@@ -0,0 +1,12 @@
+-- Key refresh tokens by their hex SHA-256, as the Redis store does, so read
+-- access to the database yields no token that can be replayed
+
+UPDATE jwt_refresh_tokens SET token = encode(sha256(convert_to(token, 'UTF8')), 'hex');
+ALTER TABLE jwt_refresh_tokens RENAME COLUMN token TO token_hash;
+
+UPDATE jwt_used_refresh_tokens SET token = encode(sha256(convert_to(token, 'UTF8')), 'hex');
+ALTER TABLE jwt_used_refresh_tokens RENAME COLUMN token TO token_hash;
+
+-- Successors stay sealed with the rotated token itself, not its hash
+UPDATE jwt_refresh_successors SET token = encode(sha256(convert_to(token, 'UTF8')), 'hex');
+ALTER TABLE jwt_refresh_successors RENAME COLUMN token TO token_hash;
@@ -0,0 +1,9 @@
+-- Used single-use tokens (email verification, password reset), kept apart
+-- from jwt_revoked_tokens so the blacklist endpoints neither list nor delete
+-- them
+
+CREATE TABLE jwt_consumed_tokens (
+    jti TEXT PRIMARY KEY,
+    expires_at BIGINT NOT NULL
+);
+CREATE INDEX jwt_consumed_tokens_expires_at ON jwt_consumed_tokens (expires_at);
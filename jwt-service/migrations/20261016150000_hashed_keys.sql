@@ -0,0 +1,11 @@
+-- API keys and issuer keys, by the SHA-256 of the key, with the user an API
+-- key acts as; they never expire
+
+CREATE TABLE jwt_keys (
+    kind TEXT NOT NULL,
+    id TEXT NOT NULL,
+    owner TEXT,
+    entry TEXT NOT NULL,
+    PRIMARY KEY (kind, id)
+);
+CREATE INDEX jwt_keys_owner ON jwt_keys (kind, owner);
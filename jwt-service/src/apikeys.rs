@@ -0,0 +1,126 @@
+// jwt-service/src/apikeys.rs
+
+//! Long-lived API keys for machine clients
+//!
+//! Service accounts that cannot keep an access token fresh through
+//! `POST /v1/auth/refresh` authenticate with an API key instead, sent as
+//! `X-Api-Key` to the routes behind the JWT middleware. A key is created by
+//! a user through `POST /v1/auth/apikeys` and acts as that user, with the
+//! scope it was given, until it is deleted or the user's tokens are revoked.
+//! Keys are kept in the token store by their SHA-256 (see
+//! [`HashedKey`](crate::HashedKey)); a key is shown once, when it is
+//! created.
+//!
+//! Not to be confused with the token endpoint's
+//! [issuer keys](crate::IssuerConfig), which travel in `X-Issuer-Key` and
+//! only to `POST /v1/auth/token`.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokn_core::{Claims, Clock};
+
+// ---
+
+use crate::hashed_keys::generate_key;
+use crate::{HashedKey, KeyKind};
+
+// ---
+
+/// Custom claim naming the API key a request authenticated with, by ID.
+pub const API_KEY_ID_CLAIM: &str = "api_key_id";
+
+/// Prefix of generated keys, telling them apart from issuer keys and making
+/// leaked ones easy to spot.
+const API_KEY_PREFIX: &str = "tokn_";
+
+/// Length of generated keys after the prefix.
+const GENERATED_KEY_LEN: usize = 40;
+
+/// Lifetime of the claims an API key stands for: they only live for the
+/// request, but handlers expect an `exp`.
+const API_KEY_CLAIMS_SECONDS: i64 = 60;
+
+// ---
+
+/// A stored API key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiKey {
+    // ---
+    /// What the key is for (e.g. `nightly-export`)
+    pub name: String,
+
+    /// The user the key acts as (`sub`)
+    pub user_id: String,
+
+    /// Email of the user, as in their access tokens (`email`)
+    pub email: String,
+
+    /// Space-delimited scopes granted to the key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+
+    /// Audiences of the access token the key was created with (`aud`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub audience: Vec<String>,
+
+    /// The user's token version when the key was created (`ver`); bumping
+    /// it revokes the key along with the user's tokens
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_version: Option<u64>,
+
+    /// When the key was created (`iat`); a revocation cutoff after it
+    /// revokes the key
+    pub created_at: DateTime<Utc>,
+}
+
+impl ApiKey {
+    // ---
+    /// The claims a request authenticated with the key with ID `id` is
+    /// handled with: the key's user, scope, and audience, and the key's ID
+    /// as `jti` and in [`API_KEY_ID_CLAIM`].
+    ///
+    /// They are checked for revocation like the claims of an access token
+    /// issued when the key was created, under the user's token version then.
+    pub fn claims(&self, id: &str, clock: &dyn Clock) -> Claims {
+        // ---
+        let mut claims = Claims::new(
+            self.user_id.clone(),
+            self.email.clone(),
+            API_KEY_CLAIMS_SECONDS,
+            clock,
+        )
+        .with_access(Vec::new(), self.scope.clone())
+        .with_audience(self.audience.clone())
+        .with_version(self.token_version);
+        claims.iat = self.created_at.timestamp() as usize;
+        claims.jti = id.to_string();
+        claims
+            .custom
+            .insert(API_KEY_ID_CLAIM.to_string(), Value::from(id));
+        claims
+    }
+}
+
+impl HashedKey for ApiKey {
+    // ---
+    const KIND: KeyKind = KeyKind::Api;
+
+    fn owner(&self) -> Option<&str> {
+        // ---
+        Some(&self.user_id)
+    }
+
+    fn created_at(&self) -> DateTime<Utc> {
+        // ---
+        self.created_at
+    }
+}
+
+// ---
+
+/// A new random key: `tokn_` and 40 alphanumeric characters.
+pub fn generate_api_key() -> String {
+    // ---
+    generate_key(API_KEY_PREFIX, GENERATED_KEY_LEN)
+}
@@ -0,0 +1,470 @@
+// jwt-service/src/audit.rs
+
+//! Audit log
+//!
+//! Records security-relevant actions (token issuance, refresh, revocation,
+//! and failed validation) with the user, token ID, client address, and time,
+//! for incident forensics. Entries go to a Redis stream or an append-only
+//! JSON lines file, written by a background task so a slow sink never delays
+//! a request.
+//!
+//! Unlike auth events (`tokn-events`), which feed analytics pipelines and may
+//! be sampled or dropped downstream, the audit log is the service's own
+//! record: every entry carries the client address, and failed validations
+//! are included.
+
+use anyhow::{bail, Context, Result};
+use axum::extract::{ConnectInfo, FromRequestParts};
+use axum::http::request::Parts;
+use axum::http::{Extensions, HeaderMap};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokn_core::Clock;
+
+// ---
+
+/// Where audit entries are written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditSink {
+    // ---
+    /// Entries are discarded (default)
+    #[default]
+    None,
+
+    /// Appended to a Redis stream (`XADD`), capped at `stream_max_len`
+    Redis,
+
+    /// Appended to `file`, one JSON object per line
+    File,
+}
+
+impl fmt::Display for AuditSink {
+    // ---
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // ---
+        f.write_str(match self {
+            AuditSink::None => "none",
+            AuditSink::Redis => "redis",
+            AuditSink::File => "file",
+        })
+    }
+}
+
+// ---
+
+/// Service configuration section for the audit log.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct AuditConfig {
+    // ---
+    /// Where entries go (env `AUDIT_SINK`: `none`, `redis`, or `file`)
+    pub sink: AuditSink,
+
+    /// Redis stream key (env `AUDIT_STREAM`, default: `tokn:audit`)
+    pub stream: String,
+
+    /// Approximate number of entries the stream keeps; older ones are
+    /// trimmed (env `AUDIT_STREAM_MAX_LEN`, default: 1000000)
+    pub stream_max_len: usize,
+
+    /// File to append to; required with the `file` sink (env `AUDIT_FILE`)
+    pub file: Option<PathBuf>,
+
+    /// Entries queued while the sink is slow; further entries are dropped
+    /// (default: 1024)
+    pub buffer: usize,
+}
+
+impl Default for AuditConfig {
+    // ---
+    fn default() -> Self {
+        // ---
+        Self {
+            sink: AuditSink::None,
+            stream: "tokn:audit".to_string(),
+            stream_max_len: 1_000_000,
+            file: None,
+            buffer: 1024,
+        }
+    }
+}
+
+// ---
+
+/// Config rule for the `audit` section: the file sink needs a `file`, and the
+/// stream must keep at least one entry.
+///
+/// # Errors
+///
+/// Returns a message for `tokn_config::ConfigLoader::rule` naming the first
+/// invalid setting.
+pub fn validate_audit_config(config: &AuditConfig) -> Result<(), String> {
+    // ---
+    if config.sink == AuditSink::File && config.file.is_none() {
+        return Err("sink 'file' requires a path (AUDIT_FILE)".to_string());
+    }
+    if config.stream_max_len == 0 {
+        return Err("AUDIT_STREAM_MAX_LEN must be positive".to_string());
+    }
+    Ok(())
+}
+
+// ---
+
+/// Type of audited action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEventKind {
+    // ---
+    /// Access (and refresh) tokens were issued by `POST /v1/auth/token`
+    TokenIssued,
+
+    /// A refresh token was exchanged for new tokens
+    TokenRefreshed,
+
+    /// An access or refresh token, or a whole session, was revoked
+    TokenRevoked,
+
+    /// A presented access or refresh token was refused
+    ValidationFailed,
+}
+
+impl AuditEventKind {
+    // ---
+    /// Wire name, as used in the `kind` field.
+    pub fn as_str(self) -> &'static str {
+        // ---
+        match self {
+            AuditEventKind::TokenIssued => "token_issued",
+            AuditEventKind::TokenRefreshed => "token_refreshed",
+            AuditEventKind::TokenRevoked => "token_revoked",
+            AuditEventKind::ValidationFailed => "validation_failed",
+        }
+    }
+}
+
+// ---
+
+/// One audit log entry.
+///
+/// # Security
+///
+/// Entries identify users and tokens but never carry token values; `jti` is
+/// the access token's ID claim.
+///
+/// # Example
+///
+/// ```json
+/// {
+///   "kind": "token_issued",
+///   "occurred_at": "2026-01-05T12:00:00Z",
+///   "user_id": "user_12345",
+///   "jti": "f47ac10b-58cc-4372-a567-0e02b2c3d479",
+///   "client_ip": "203.0.113.7"
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEvent {
+    // ---
+    /// What happened
+    pub kind: AuditEventKind,
+
+    /// When it happened
+    pub occurred_at: DateTime<Utc>,
+
+    /// User the action concerns, when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+
+    /// ID claim of the access token involved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
+
+    /// Peer address of the client
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_ip: Option<String>,
+
+    /// `X-Forwarded-For` as sent, when behind a proxy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forwarded_for: Option<String>,
+
+    /// Why a validation failed, or which token type was revoked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl AuditEvent {
+    // ---
+    /// New entry of `kind`, timestamped by `clock`.
+    pub fn new(kind: AuditEventKind, clock: &dyn Clock) -> Self {
+        // ---
+        Self {
+            kind,
+            occurred_at: clock.now(),
+            user_id: None,
+            jti: None,
+            client_ip: None,
+            forwarded_for: None,
+            reason: None,
+        }
+    }
+
+    /// Set the user the action concerns.
+    pub fn user_id(mut self, user_id: impl Into<String>) -> Self {
+        // ---
+        self.user_id = Some(user_id.into());
+        self
+    }
+
+    /// Set the access token's ID claim.
+    pub fn jti(mut self, jti: impl Into<String>) -> Self {
+        // ---
+        self.jti = Some(jti.into());
+        self
+    }
+
+    /// Set the client's addresses.
+    pub fn client(mut self, client: &ClientInfo) -> Self {
+        // ---
+        self.client_ip.clone_from(&client.ip);
+        self.forwarded_for.clone_from(&client.forwarded_for);
+        self
+    }
+
+    /// Set why a validation failed, or what was revoked.
+    pub fn reason(mut self, reason: impl Into<String>) -> Self {
+        // ---
+        self.reason = Some(reason.into());
+        self
+    }
+
+    /// Field/value pairs for a Redis stream entry (unset fields omitted).
+    #[cfg(feature = "redis")]
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        // ---
+        let mut fields = vec![
+            ("kind", self.kind.as_str().to_string()),
+            (
+                "occurred_at",
+                self.occurred_at
+                    .to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            ),
+        ];
+        let optional = [
+            ("user_id", &self.user_id),
+            ("jti", &self.jti),
+            ("client_ip", &self.client_ip),
+            ("forwarded_for", &self.forwarded_for),
+            ("reason", &self.reason),
+        ];
+        for (name, value) in optional {
+            if let Some(value) = value {
+                fields.push((name, value.clone()));
+            }
+        }
+        fields
+    }
+}
+
+// ---
+
+/// Addresses of the client making a request, for audit entries.
+///
+/// As an extractor it never fails: addresses that are unknown (e.g. over a
+/// Unix socket, or without a proxy) are `None`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientInfo {
+    // ---
+    /// Peer IP address (requires serving with connect info, as
+    /// `tokn_server::serve` does on TCP)
+    pub ip: Option<String>,
+
+    /// `X-Forwarded-For` as sent; recorded, not trusted
+    pub forwarded_for: Option<String>,
+}
+
+impl ClientInfo {
+    // ---
+    /// The client of a request with `headers` and `extensions`.
+    pub fn from_request(headers: &HeaderMap, extensions: &Extensions) -> Self {
+        // ---
+        Self {
+            ip: extensions
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| addr.ip().to_string()),
+            forwarded_for: headers
+                .get("x-forwarded-for")
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string),
+        }
+    }
+}
+
+impl<S> FromRequestParts<S> for ClientInfo
+where
+    S: Send + Sync,
+{
+    // ---
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        // ---
+        Ok(Self::from_request(&parts.headers, &parts.extensions))
+    }
+}
+
+// ---
+
+/// Handle for recording audit entries; cheap to clone into handler state.
+///
+/// The default handle is disabled and discards every entry.
+#[derive(Clone, Default)]
+pub struct AuditLog {
+    // ---
+    queue: Option<mpsc::Sender<AuditEvent>>,
+}
+
+impl AuditLog {
+    // ---
+    /// A handle that discards every entry.
+    pub fn disabled() -> Self {
+        // ---
+        Self::default()
+    }
+
+    // ---
+    /// Open the sink selected by `config` and start writing to it; a
+    /// disabled handle for [`AuditSink::None`]. The Redis sink connects to
+    /// `redis_url`.
+    ///
+    /// Must be called within a Tokio runtime.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened for appending, Redis
+    /// cannot be reached, or the Redis sink is compiled out (no `redis`
+    /// feature).
+    #[cfg_attr(not(feature = "redis"), allow(unused_variables))]
+    pub async fn open(config: &AuditConfig, redis_url: &str) -> Result<Self> {
+        // ---
+        let writer = match config.sink {
+            AuditSink::None => return Ok(Self::disabled()),
+            AuditSink::File => {
+                let Some(path) = &config.file else {
+                    bail!("The audit file sink requires AUDIT_FILE");
+                };
+                let file = tokio::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .await
+                    .with_context(|| format!("Failed to open audit file {}", path.display()))?;
+                Writer::File(file)
+            }
+            #[cfg(feature = "redis")]
+            AuditSink::Redis => Writer::Redis {
+                redis: crate::create_redis_client(redis_url).await?,
+                stream: config.stream.clone(),
+                max_len: config.stream_max_len,
+            },
+            #[cfg(not(feature = "redis"))]
+            AuditSink::Redis => bail!("The audit Redis sink requires the `redis` feature"),
+        };
+
+        let (queue, entries) = mpsc::channel(config.buffer.max(1));
+        tokio::spawn(write_loop(writer, entries));
+        Ok(Self { queue: Some(queue) })
+    }
+
+    // ---
+    /// Whether entries are written anywhere.
+    pub fn is_enabled(&self) -> bool {
+        // ---
+        self.queue.is_some()
+    }
+
+    // ---
+    /// Queue `event` for writing.
+    ///
+    /// Never blocks: when the queue is full the entry is dropped and an error
+    /// is logged.
+    pub fn record(&self, event: AuditEvent) {
+        // ---
+        let Some(queue) = &self.queue else {
+            return;
+        };
+        let kind = event.kind.as_str();
+
+        if let Err(e) = queue.try_send(event) {
+            let reason = match e {
+                TrySendError::Full(_) => "queue full",
+                TrySendError::Closed(_) => "writer stopped",
+            };
+            tracing::error!("Dropped {kind} audit entry ({reason})");
+        }
+    }
+}
+
+// ---
+
+/// An open audit sink.
+enum Writer {
+    // ---
+    File(tokio::fs::File),
+    #[cfg(feature = "redis")]
+    Redis {
+        redis: redis::aio::ConnectionManager,
+        stream: String,
+        max_len: usize,
+    },
+}
+
+impl Writer {
+    // ---
+    async fn write(&mut self, event: &AuditEvent) -> Result<()> {
+        // ---
+        match self {
+            Writer::File(file) => {
+                let mut line = serde_json::to_vec(event)?;
+                line.push(b'\n');
+                file.write_all(&line).await?;
+                file.flush().await?;
+            }
+            #[cfg(feature = "redis")]
+            Writer::Redis {
+                redis,
+                stream,
+                max_len,
+            } => {
+                let mut cmd = redis::cmd("XADD");
+                cmd.arg(&*stream)
+                    .arg("MAXLEN")
+                    .arg("~")
+                    .arg(*max_len)
+                    .arg("*");
+                for (name, value) in event.fields() {
+                    cmd.arg(name).arg(value);
+                }
+                let _: () = cmd.query_async(redis).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Write queued entries until every [`AuditLog`] handle is dropped.
+async fn write_loop(mut writer: Writer, mut entries: mpsc::Receiver<AuditEvent>) {
+    // ---
+    while let Some(event) = entries.recv().await {
+        if let Err(e) = writer.write(&event).await {
+            tracing::error!("Failed to write {} audit entry: {e:#}", event.kind.as_str());
+        }
+    }
+}
@@ -0,0 +1,201 @@
+// jwt-service/src/check.rs
+
+//! `--check-config`: load and validate the configuration, prove the signing
+//! keys round-trip a token, then probe the token store, Redis, the event
+//! broker, and mail
+
+use anyhow::Context;
+use tokn_config::Secret;
+use tokn_events::{Events, EventsBackend};
+use tokn_mail::Mail;
+use tokn_ratelimit::RateLimiter;
+use tokn_server::CheckReport;
+
+// ---
+
+use crate::{Claims, Config, JwtKeys, StoreBackend, SystemClock};
+
+// ---
+
+/// Run every startup check jwt-service can make without serving.
+pub async fn check_config() -> CheckReport {
+    // ---
+    let mut report = CheckReport::new("jwt-service");
+
+    let config = match Config::load_with_args() {
+        Ok(config) => config,
+        Err(e) => {
+            report.fail("config", format!("{e:#}"));
+            return report;
+        }
+    };
+    report.pass("config", format!("loaded (profile {})", config.profile));
+
+    report.secret("jwt.secret", config.jwt.secret.as_ref().map(Secret::expose));
+    if let Some(previous) = &config.jwt.previous_secret {
+        report.secret("jwt.previous_secret", Some(previous.expose()));
+        if let Some(stale) = config.jwt.stale_previous_secret(chrono::Utc::now()) {
+            report.warn("jwt.secret_rotated_at", stale);
+        }
+    }
+    for key in &config.jwt.keys {
+        if !key.algorithm.is_asymmetric() {
+            report.secret(
+                &format!("jwt.keys.{}.secret", key.kid),
+                key.secret.as_ref().map(Secret::expose),
+            );
+        }
+    }
+    match config
+        .jwt
+        .keys()
+        .and_then(|keys| sign_and_verify(&keys).map(|()| keys))
+    {
+        Ok(keys) => report.pass(
+            "jwt.keys",
+            match keys.current_kid() {
+                Some(kid) => format!(
+                    "{} token signed with '{kid}' and validated (ring: {})",
+                    keys.algorithm(),
+                    keys.kids().join(", ")
+                ),
+                None => format!("{} token issued and validated", keys.algorithm()),
+            },
+        ),
+        Err(e) => report.fail("jwt.keys", format!("{e:#}")),
+    }
+    report.secret(
+        "admin.token",
+        config.admin.token.as_ref().map(Secret::expose),
+    );
+    report.secret(
+        "service_auth.key",
+        config.service_auth.key.as_ref().map(Secret::expose),
+    );
+    for (i, key) in config.issuer.static_keys().into_iter().enumerate() {
+        report.secret(&format!("issuer.keys.{}", i + 1), Some(key));
+    }
+    if config.issuer.require_key {
+        report.pass("issuer", "POST /v1/auth/token requires an issuer key");
+    } else {
+        report.warn(
+            "issuer",
+            "POST /v1/auth/token issues tokens to any caller; set TOKEN_ISSUER_REQUIRE_KEY=true",
+        );
+    }
+    report.tls(config.server.tls.as_ref()).await;
+
+    // ---
+    if config.is_stateless() {
+        report.skip("redis", "stateless: no refresh tokens or revocation");
+    } else {
+        match config.store.backend {
+            StoreBackend::Redis => {
+                #[cfg(feature = "redis")]
+                report
+                    .probe("redis", async {
+                        let mut conn = crate::create_redis_client(&config.redis.url).await?;
+                        let _: String = redis::cmd("PING")
+                            .query_async(&mut conn)
+                            .await
+                            .context("Redis did not answer PING")?;
+                        Ok("connected".to_string())
+                    })
+                    .await;
+            }
+            StoreBackend::Postgres => check_postgres_store(&mut report, &config).await,
+            StoreBackend::Memory => report.warn(
+                "store",
+                "TOKEN_STORE=memory: tokens are lost on restart and not shared between replicas",
+            ),
+        }
+    }
+
+    if config.rate_limit.enabled {
+        report
+            .probe("redis (rate limiting)", async {
+                RateLimiter::connect(
+                    "jwt-service",
+                    &config.rate_limit,
+                    &config.redis.url,
+                    config.circuit_breaker,
+                    SystemClock::shared(),
+                )
+                .await
+                .context("Failed to connect to Redis (rate limiting)")?;
+                Ok("connected".to_string())
+            })
+            .await;
+    } else {
+        report.skip("redis (rate limiting)", "rate limiting disabled");
+    }
+
+    if config.events.backend == EventsBackend::None {
+        report.skip("events", "publishing disabled");
+    } else {
+        report
+            .probe("events", async {
+                Events::connect(&config.events, "jwt-service")
+                    .await
+                    .context("Failed to connect to the event broker")?;
+                Ok(format!("connected ({})", config.events.backend))
+            })
+            .await;
+    }
+
+    report
+        .probe("mail", async {
+            Mail::connect(&config.mail)
+                .await
+                .context("Failed to set up mail delivery")?;
+            Ok(format!("{} backend ready", config.mail.backend))
+        })
+        .await;
+
+    report
+}
+
+// ---
+
+/// Issue a short-lived token with `keys` and validate it again.
+fn sign_and_verify(keys: &JwtKeys) -> anyhow::Result<()> {
+    // ---
+    let claims = Claims::new(
+        "check-config".to_string(),
+        "check-config@localhost".to_string(),
+        60,
+        &SystemClock,
+    );
+    let token = keys.sign(&claims).context("Failed to sign a token")?;
+    keys.verify(&token, &SystemClock)
+        .context("Failed to validate a freshly signed token")?;
+
+    Ok(())
+}
+
+// ---
+
+/// Connect to the Postgres token store, applying its migrations.
+#[cfg(feature = "postgres")]
+async fn check_postgres_store(report: &mut CheckReport, config: &Config) {
+    // ---
+    let Some(url) = &config.store.database_url else {
+        // Refused by the config rules already
+        return;
+    };
+    report
+        .probe("postgres", async {
+            crate::PostgresStore::connect(url.expose(), SystemClock::shared()).await?;
+            Ok("connected; tables ready".to_string())
+        })
+        .await;
+}
+
+#[cfg(not(feature = "postgres"))]
+async fn check_postgres_store(report: &mut CheckReport, _config: &Config) {
+    // ---
+    report.fail(
+        "postgres",
+        "TOKEN_STORE=postgres requires the `postgres` feature",
+    );
+}
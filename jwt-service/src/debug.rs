@@ -0,0 +1,66 @@
+// jwt-service/src/debug.rs
+
+//! Sections of the `/debug` report
+//!
+//! The token store backend, Redis connection state, and the rate limiter's
+//! breaker, on top of the build info every service reports.
+
+use serde_json::{json, Value};
+use tokn_ratelimit::RateLimiter;
+use tokn_server::DebugInfo;
+
+// ---
+
+use crate::AppState;
+
+// ---
+
+/// The `/debug` report for jwt-service over `state` and `limiter`.
+pub fn debug_info(state: &AppState, limiter: &RateLimiter) -> DebugInfo {
+    // ---
+    let backend = state.store.as_ref().map(|store| store.backend().as_str());
+    let mut debug = DebugInfo::new("jwt-service", env!("CARGO_PKG_VERSION"))
+        .section("store", move || async move {
+            json!({ "backend": backend.unwrap_or("none") })
+        });
+
+    #[cfg(feature = "redis")]
+    {
+        let redis = state.redis.clone();
+        debug = debug.section("redis", move || redis_status(redis.clone()));
+    }
+
+    let limiter = limiter.clone();
+    debug.section("ratelimit", move || {
+        let status = rate_limit_status(&limiter);
+        async move { status }
+    })
+}
+
+// ---
+
+/// Breaker state and a `PING` round trip, or `{"stateless": true}`.
+#[cfg(feature = "redis")]
+async fn redis_status(redis: Option<crate::RedisConnection>) -> Value {
+    // ---
+    let Some(mut redis) = redis else {
+        return json!({ "stateless": true });
+    };
+
+    let started = std::time::Instant::now();
+    let pong: redis::RedisResult<String> = redis::cmd("PING").query_async(&mut redis).await;
+    json!({
+        "breaker": redis.breaker().state().as_str(),
+        "connected": pong.is_ok(),
+        "ping_ms": pong.is_ok().then(|| started.elapsed().as_secs_f64() * 1000.0),
+        "error": pong.err().map(|e| e.to_string()),
+    })
+}
+
+fn rate_limit_status(limiter: &RateLimiter) -> Value {
+    // ---
+    json!({
+        "enabled": limiter.is_enabled(),
+        "breaker": limiter.breaker().map(|breaker| breaker.state().as_str()),
+    })
+}
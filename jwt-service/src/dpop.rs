@@ -0,0 +1,40 @@
+// jwt-service/src/dpop.rs
+
+//! DPoP proof replay detection (RFC 9449 §11.1)
+//!
+//! Every DPoP proof carries a unique `jti`. The token endpoints and the JWT
+//! middleware record it in Redis until the proof expires, so a proof
+//! captured in transit cannot be presented again, on this replica or any
+//! other.
+
+use anyhow::{Context, Result};
+use redis::aio::ConnectionLike;
+use tokn_core::{keys, DpopProof};
+
+// ---
+
+/// Record `proof` as used at `now`, returning whether it had been used
+/// before.
+///
+/// # Errors
+///
+/// Returns an error if Redis cannot be written.
+pub async fn record_dpop_proof<C>(redis_conn: &mut C, proof: &DpopProof, now: i64) -> Result<bool>
+where
+    C: ConnectionLike + Send,
+{
+    // ---
+    // Only the first SET NX of a jti succeeds
+    let ttl = (proof.expires_at() - now).max(1);
+    let stored: Option<String> = redis::cmd("SET")
+        .arg(keys::dpop_proof(&proof.jti))
+        .arg("used")
+        .arg("NX")
+        .arg("EX")
+        .arg(ttl)
+        .query_async(redis_conn)
+        .await
+        .context("Failed to record DPoP proof in Redis")?;
+
+    Ok(stored.is_none())
+}
@@ -0,0 +1,172 @@
+// jwt-service/src/error.rs
+
+//! Errors of jwt-service's HTTP API
+//!
+//! Handlers return an [`ApiError`], sent as RFC 7807 problem details
+//! ([`Problem`], `application/problem+json`). Each variant is one problem
+//! type with a stable code: its `type` is `https://tokn.dev/problems/{code}`
+//! and its `title` is fixed, so clients can branch on either while `detail`
+//! explains the occurrence.
+//!
+//! | Variant               | Status | Code                 |
+//! |-----------------------|--------|----------------------|
+//! | `InvalidRequest`      | 400    | `invalid-request`    |
+//! | `InvalidDpopProof`    | 400    | `invalid-dpop-proof` |
+//! | `Unauthenticated`     | 401    | `unauthenticated`    |
+//! | `InvalidToken`        | 401    | `invalid-token`      |
+//! | `TokenRevoked`        | 401    | `token-revoked`      |
+//! | `Forbidden`           | 403    | `forbidden`          |
+//! | `InsufficientScope`   | 403    | `insufficient-scope` |
+//! | `NotFound`            | 404    | `not-found`          |
+//! | `NoSuchEntry`         | 404    | `no-such-entry`      |
+//! | `Internal`            | 500    | `internal-error`     |
+//! | `StoreUnavailable`    | 503    | `store-unavailable`  |
+
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use tokn_core::Problem;
+
+// ---
+
+/// Base of every [`ApiError`] problem `type`; the code follows it.
+pub const PROBLEM_TYPE_BASE: &str = "https://tokn.dev/problems/";
+
+/// Why a jwt-service request failed.
+#[derive(Debug, thiserror::Error)]
+pub enum ApiError {
+    // ---
+    /// The request body, query, or a header is malformed or incomplete.
+    #[error("{0}")]
+    InvalidRequest(String),
+
+    /// The request's DPoP proof is invalid or replayed; the body also
+    /// carries the RFC 9449 `"error": "invalid_dpop_proof"`.
+    #[error("{0}")]
+    InvalidDpopProof(String),
+
+    /// The caller sent no credential the endpoint accepts, or an unknown one.
+    #[error("{0}")]
+    Unauthenticated(String),
+
+    /// A token, link, or ticket that is malformed, expired, already used,
+    /// or not the caller's to use.
+    #[error("{0}")]
+    InvalidToken(String),
+
+    /// A token (or what it grants) that has been revoked.
+    #[error("{0}")]
+    TokenRevoked(String),
+
+    /// The caller is authenticated but may not do this.
+    #[error("{0}")]
+    Forbidden(String),
+
+    /// The caller's token lacks the named scope; the body also carries it
+    /// as `required_scope`.
+    #[error("Token lacks required scope '{0}'")]
+    InsufficientScope(String),
+
+    /// The endpoint is not served by this deployment (e.g. a stateful
+    /// endpoint of a stateless service).
+    #[error("Not found")]
+    NotFound,
+
+    /// The session, key, or entry the request names does not exist.
+    #[error("{0}")]
+    NoSuchEntry(String),
+
+    /// Signing a token or checking a request failed inside the service.
+    #[error("{0}")]
+    Internal(String),
+
+    /// The token store could not be read or written.
+    #[error("{0}")]
+    StoreUnavailable(String),
+}
+
+impl ApiError {
+    // ---
+    /// The stable code naming this problem type.
+    pub fn code(&self) -> &'static str {
+        // ---
+        match self {
+            ApiError::InvalidRequest(_) => "invalid-request",
+            ApiError::InvalidDpopProof(_) => "invalid-dpop-proof",
+            ApiError::Unauthenticated(_) => "unauthenticated",
+            ApiError::InvalidToken(_) => "invalid-token",
+            ApiError::TokenRevoked(_) => "token-revoked",
+            ApiError::Forbidden(_) => "forbidden",
+            ApiError::InsufficientScope(_) => "insufficient-scope",
+            ApiError::NotFound => "not-found",
+            ApiError::NoSuchEntry(_) => "no-such-entry",
+            ApiError::Internal(_) => "internal-error",
+            ApiError::StoreUnavailable(_) => "store-unavailable",
+        }
+    }
+
+    /// The problem type's title, the same for every occurrence.
+    pub fn title(&self) -> &'static str {
+        // ---
+        match self {
+            ApiError::InvalidRequest(_) => "Invalid request",
+            ApiError::InvalidDpopProof(_) => "Invalid DPoP proof",
+            ApiError::Unauthenticated(_) => "Authentication required",
+            ApiError::InvalidToken(_) => "Invalid token",
+            ApiError::TokenRevoked(_) => "Token revoked",
+            ApiError::Forbidden(_) => "Forbidden",
+            ApiError::InsufficientScope(_) => "Insufficient scope",
+            ApiError::NotFound => "Not found",
+            ApiError::NoSuchEntry(_) => "No such entry",
+            ApiError::Internal(_) => "Internal error",
+            ApiError::StoreUnavailable(_) => "Token store unavailable",
+        }
+    }
+
+    /// The response status.
+    pub fn status(&self) -> StatusCode {
+        // ---
+        match self {
+            ApiError::InvalidRequest(_) | ApiError::InvalidDpopProof(_) => StatusCode::BAD_REQUEST,
+            ApiError::Unauthenticated(_)
+            | ApiError::InvalidToken(_)
+            | ApiError::TokenRevoked(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden(_) | ApiError::InsufficientScope(_) => StatusCode::FORBIDDEN,
+            ApiError::NotFound | ApiError::NoSuchEntry(_) => StatusCode::NOT_FOUND,
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ApiError::StoreUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+
+    /// The problem details body, for callers adding extension members.
+    pub fn into_problem(self) -> Problem {
+        // ---
+        let problem = Problem::new(self.status())
+            .with_type(format!("{PROBLEM_TYPE_BASE}{}", self.code()), self.title());
+        match self {
+            ApiError::NotFound => problem,
+            ApiError::InvalidDpopProof(detail) => problem
+                .detail(detail)
+                .extension("error", "invalid_dpop_proof"),
+            ApiError::InsufficientScope(ref scope) => problem
+                .detail(self.to_string())
+                .extension("required_scope", scope.as_str()),
+            _ => problem.detail(self.to_string()),
+        }
+    }
+}
+
+impl From<ApiError> for Problem {
+    // ---
+    fn from(error: ApiError) -> Self {
+        // ---
+        error.into_problem()
+    }
+}
+
+impl IntoResponse for ApiError {
+    // ---
+    fn into_response(self) -> Response {
+        // ---
+        self.into_problem().into_response()
+    }
+}
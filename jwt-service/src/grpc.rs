@@ -0,0 +1,115 @@
+// jwt-service/src/grpc.rs
+
+//! gRPC token introspection (`tokn.introspection.v1.TokenIntrospection`)
+//!
+//! Same checks as `POST /v1/auth/validate` (signature, expiry, revocation
+//! blacklist), answered in the shared introspection contract so resource
+//! services can treat jwt-service and oauth2-server alike.
+
+use anyhow::{Context, Result};
+use std::net::SocketAddr;
+use tokn_proto::{
+    IntrospectRequest, IntrospectResponse, TokenIntrospection, TokenIntrospectionServer,
+};
+use tokn_server::Shutdown;
+use tonic::{Request, Response, Status};
+
+// ---
+
+use crate::AppState;
+
+// ---
+
+/// [`TokenIntrospection`] implementation backed by the service state.
+#[derive(Clone)]
+pub struct IntrospectionService {
+    // ---
+    state: AppState,
+}
+
+impl IntrospectionService {
+    // ---
+    /// Introspect tokens against `state`'s keys and revocation blacklist.
+    pub fn new(state: AppState) -> Self {
+        // ---
+        Self { state }
+    }
+}
+
+// ---
+
+#[tonic::async_trait]
+impl TokenIntrospection for IntrospectionService {
+    // ---
+    /// Report whether a JWT access token is active.
+    ///
+    /// # Errors
+    ///
+    /// Returns `UNAVAILABLE` if the revocation blacklist cannot be checked;
+    /// invalid, expired, and revoked tokens are `active: false`, not errors.
+    async fn introspect(
+        &self,
+        request: Request<IntrospectRequest>,
+    ) -> Result<Response<IntrospectResponse>, Status> {
+        // ---
+        let token = request.into_inner().token;
+
+        let claims = match self
+            .state
+            .keys
+            .get()
+            .verify(&token, self.state.clock.as_ref())
+        {
+            Ok(claims) => claims,
+            Err(e) => {
+                tracing::debug!("Introspected token is not active: {}", e);
+                return Ok(Response::new(IntrospectResponse::inactive()));
+            }
+        };
+
+        let revoked = self.state.is_revoked(&claims).await.map_err(|e| {
+            tracing::error!("Revocation check failed: {:#}", e);
+            Status::unavailable("revocation check unavailable")
+        })?;
+        if revoked {
+            return Ok(Response::new(IntrospectResponse::inactive()));
+        }
+
+        Ok(Response::new(IntrospectResponse {
+            active: true,
+            sub: Some(claims.sub),
+            email: Some(claims.email),
+            exp: Some(claims.exp as i64),
+            iat: Some(claims.iat as i64),
+            jti: Some(claims.jti),
+            scope: claims.scope,
+            iss: Some("jwt-service".to_string()),
+            token_type: Some("Bearer".to_string()),
+            ..Default::default()
+        }))
+    }
+}
+
+// ---
+
+/// Serve the introspection RPC on `addr` until `shutdown`, draining
+/// in-flight calls like the HTTP listener.
+///
+/// # Errors
+///
+/// Returns an error if `addr` cannot be bound or the server fails.
+pub async fn serve_grpc(addr: SocketAddr, state: AppState, shutdown: &Shutdown) -> Result<()> {
+    // ---
+    tracing::info!("Serving gRPC token introspection on {addr}");
+
+    let signal = shutdown.clone();
+    let server = tonic::transport::Server::builder()
+        .add_service(TokenIntrospectionServer::new(IntrospectionService::new(
+            state,
+        )))
+        .serve_with_shutdown(addr, async move { signal.signalled().await });
+    shutdown
+        .drain(server)
+        .await
+        .with_context(|| format!("gRPC server on {addr} failed"))
+}
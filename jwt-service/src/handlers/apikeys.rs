@@ -0,0 +1,303 @@
+// jwt-service/src/handlers/apikeys.rs
+
+//! API key management endpoints
+//!
+//! Handles `/v1/auth/apikeys`: users create, list, and delete the long-lived
+//! API keys their service accounts send in `X-Api-Key` (see
+//! [`ApiKey`](crate::ApiKey)).
+
+use super::protected::jwt_auth_layer;
+use crate::{generate_api_key, ApiError, ApiKey, AppState, Claims, TokenStore, API_KEY_ID_CLAIM};
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Json},
+    routing::{delete, get},
+    Router,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokn_auth::AuthenticatedUser;
+
+// ---
+
+/// Scope allowing a caller to manage the API keys of every user.
+const ADMIN_SCOPE: &str = "admin";
+
+// ---
+
+/// Request body of `POST /v1/auth/apikeys`.
+#[derive(Debug, Deserialize)]
+pub struct CreateApiKeyRequest {
+    // ---
+    /// What the key is for (e.g. `nightly-export`)
+    pub name: String,
+
+    /// Space-delimited scopes for the key, each held by the caller; none
+    /// when omitted
+    #[serde(default)]
+    pub scope: Option<String>,
+
+    /// The user the key acts as; defaults to the caller, and only callers
+    /// with the `admin` scope may name another
+    #[serde(default)]
+    pub user_id: Option<String>,
+}
+
+/// A stored key, by ID; the key itself is only in [`CreatedApiKey`].
+#[derive(Debug, Serialize)]
+pub struct ApiKeyInfo {
+    // ---
+    /// SHA-256 of the key, for `DELETE /v1/auth/apikeys/{id}`
+    id: String,
+
+    /// What the key is for
+    name: String,
+
+    /// The user the key acts as
+    user_id: String,
+
+    /// Scopes granted to the key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scope: Option<String>,
+
+    /// When the key was created
+    created_at: DateTime<Utc>,
+}
+
+impl ApiKeyInfo {
+    // ---
+    fn new(id: String, key: ApiKey) -> Self {
+        // ---
+        Self {
+            id,
+            name: key.name,
+            user_id: key.user_id,
+            scope: key.scope,
+            created_at: key.created_at,
+        }
+    }
+}
+
+/// Response of `POST /v1/auth/apikeys`: the only time the key is shown.
+#[derive(Debug, Serialize)]
+pub struct CreatedApiKey {
+    // ---
+    #[serde(flatten)]
+    info: ApiKeyInfo,
+
+    /// The key, for the service account's `X-Api-Key` header
+    api_key: String,
+}
+
+// ---
+
+/// Build the `/v1/auth/apikeys` routes, guarded by the JWT middleware.
+///
+/// Routes:
+/// - `POST /v1/auth/apikeys` - create a key; 201 with the key, which is not
+///   stored and cannot be shown again
+/// - `GET /v1/auth/apikeys` - list the caller's keys (every user's with the
+///   `admin` scope), oldest first
+/// - `DELETE /v1/auth/apikeys/{id}` - delete a key; 204, or 404 if the caller
+///   has none with that ID
+pub fn api_key_routes(state: AppState) -> Router<AppState> {
+    // ---
+    Router::new()
+        .route(
+            "/auth/apikeys",
+            get(list_api_keys_handler).post(create_api_key_handler),
+        )
+        .route("/auth/apikeys/{id}", delete(delete_api_key_handler))
+        .route_layer(jwt_auth_layer(&state))
+}
+
+// ---
+
+/// Create an API key acting as the caller, or as `user_id` for an admin.
+///
+/// Keys never expire; they are revoked by deleting them, and along with the
+/// user's tokens: the key records the user's token version, and when it was
+/// created, so `POST /v1/auth/invalidate-user` and revocation cutoffs reach
+/// it too. The key is limited to the audience of the access token it was
+/// created with. A request made with an API key cannot create another, so a
+/// leaked key cannot be used to outlive its own deletion.
+///
+/// # Request
+///
+/// ```json
+/// {
+///   "name": "nightly-export",
+///   "scope": "orders:read"
+/// }
+/// ```
+///
+/// # Response (201 Created)
+///
+/// ```json
+/// {
+///   "id": "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08",
+///   "name": "nightly-export",
+///   "user_id": "user_12345",
+///   "scope": "orders:read",
+///   "created_at": "2025-01-15T09:30:00Z",
+///   "api_key": "tokn_Xq4Lm8Tz2Wd7Kp1Vb5Nr9Hj3Lc6Pf0Gs8Yt2Ma4"
+/// }
+/// ```
+///
+/// # Errors
+///
+/// Returns 400 Bad Request without a `name`, 403 Forbidden when called with
+/// an API key, for another user without the `admin` scope, or for a scope the
+/// caller lacks, and 503 Service Unavailable if the token store cannot be
+/// reached.
+async fn create_api_key_handler(
+    State(state): State<AppState>,
+    AuthenticatedUser(claims): AuthenticatedUser,
+    Json(req): Json<CreateApiKeyRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    // ---
+    let name = req.name.trim();
+    if name.is_empty() {
+        return Err(ApiError::InvalidRequest("name is required".into()));
+    }
+    if claims.custom.contains_key(API_KEY_ID_CLAIM) {
+        return Err(ApiError::Forbidden(
+            "API keys cannot be created with an API key".into(),
+        ));
+    }
+    let Some(store) = &state.store else {
+        return Err(ApiError::NotFound);
+    };
+    let unavailable = |e: anyhow::Error| {
+        tracing::error!("API key creation failed: {:#}", e);
+        ApiError::StoreUnavailable("Failed to store API key".into())
+    };
+
+    let is_admin = claims.has_scope(ADMIN_SCOPE);
+    let user_id = req.user_id.unwrap_or_else(|| claims.sub.clone());
+    if user_id != claims.sub && !is_admin {
+        return Err(ApiError::Forbidden(
+            "Creating API keys for other users requires the 'admin' scope".into(),
+        ));
+    }
+    let scope = req.scope.filter(|scope| !scope.trim().is_empty());
+    if let Some(missing) = scope
+        .iter()
+        .flat_map(|scope| scope.split_whitespace())
+        .find(|scope| !is_admin && !claims.has_scope(scope))
+    {
+        return Err(ApiError::Forbidden(format!(
+            "Token lacks scope '{missing}' requested for the key"
+        )));
+    }
+
+    // Keys made for another user carry no email; the caller's is not theirs
+    let email = if user_id == claims.sub {
+        claims.email.clone()
+    } else {
+        String::new()
+    };
+    // Revoking the user's tokens from here on revokes the key too
+    let token_version = store.token_version(&user_id).await.map_err(unavailable)?;
+    let api_key = generate_api_key();
+    let entry = ApiKey {
+        name: name.to_string(),
+        user_id,
+        email,
+        scope,
+        audience: claims.aud.clone(),
+        token_version: Some(token_version),
+        created_at: state.clock.now(),
+    };
+    let id = store
+        .store_key(&api_key, &entry)
+        .await
+        .map_err(unavailable)?;
+
+    tracing::info!(
+        name = %entry.name,
+        user_id = %entry.user_id,
+        created_by = %claims.sub,
+        id = %id,
+        "Created API key"
+    );
+    let created = CreatedApiKey {
+        info: ApiKeyInfo::new(id, entry),
+        api_key,
+    };
+    Ok((
+        StatusCode::CREATED,
+        [(header::CACHE_CONTROL, "no-store")],
+        Json(created),
+    ))
+}
+
+/// List the caller's API keys, or every user's for an admin, without the
+/// keys themselves.
+///
+/// # Errors
+///
+/// Returns 503 Service Unavailable if the token store cannot be queried.
+async fn list_api_keys_handler(
+    State(state): State<AppState>,
+    AuthenticatedUser(claims): AuthenticatedUser,
+) -> Result<Json<Vec<ApiKeyInfo>>, ApiError> {
+    // ---
+    let Some(store) = &state.store else {
+        return Err(ApiError::NotFound);
+    };
+
+    let keys = store.list_keys::<ApiKey>().await.map_err(|e| {
+        tracing::error!("API key listing failed: {:#}", e);
+        ApiError::StoreUnavailable("Failed to list API keys".into())
+    })?;
+    Ok(Json(
+        keys.into_iter()
+            .filter(|(_, key)| may_manage(&claims, key))
+            .map(|(id, key)| ApiKeyInfo::new(id, key))
+            .collect(),
+    ))
+}
+
+/// Delete one of the caller's API keys (any user's for an admin).
+///
+/// # Errors
+///
+/// Returns 404 Not Found if the caller has no key with that ID, and 503
+/// Service Unavailable if the token store cannot be reached.
+async fn delete_api_key_handler(
+    State(state): State<AppState>,
+    AuthenticatedUser(claims): AuthenticatedUser,
+    Path(id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    // ---
+    let Some(store) = &state.store else {
+        return Err(ApiError::NotFound);
+    };
+    let unavailable = |e: anyhow::Error| {
+        tracing::error!("API key deletion failed: {:#}", e);
+        ApiError::StoreUnavailable("Failed to delete API key".into())
+    };
+
+    // Other users' keys are as good as absent
+    let owned = store
+        .find_key::<ApiKey>(&id)
+        .await
+        .map_err(unavailable)?
+        .is_some_and(|key| may_manage(&claims, &key));
+    if !owned || !store.delete_key::<ApiKey>(&id).await.map_err(unavailable)? {
+        return Err(ApiError::NoSuchEntry("No such API key".into()));
+    }
+
+    tracing::info!(id = %id, deleted_by = %claims.sub, "Deleted API key");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ---
+
+/// Whether the caller with `claims` may see and delete `key`.
+fn may_manage(claims: &Claims, key: &ApiKey) -> bool {
+    // ---
+    key.user_id == claims.sub || claims.has_scope(ADMIN_SCOPE)
+}
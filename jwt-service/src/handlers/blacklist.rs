@@ -0,0 +1,170 @@
+// jwt-service/src/handlers/blacklist.rs
+
+//! Admin API for the access token blacklist
+//!
+//! Handles `/admin/blacklist`: list the revoked access tokens with their
+//! TTLs, look one up, and take one off the blacklist, in whichever token
+//! store is configured.
+
+use crate::{ApiError, AppState, RevokedToken, TokenStore};
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Json},
+    routing::get,
+    Router,
+};
+use serde::{Deserialize, Serialize};
+
+// ---
+
+/// Entries listed by `GET /admin/blacklist` unless `limit` says otherwise.
+const DEFAULT_LIST_LIMIT: usize = 100;
+
+/// Most entries `GET /admin/blacklist` lists at once.
+const MAX_LIST_LIMIT: usize = 1000;
+
+// ---
+
+/// Query of `GET /admin/blacklist`.
+#[derive(Debug, Deserialize)]
+pub struct ListBlacklistQuery {
+    // ---
+    /// How many entries to list (default 100, at most 1000)
+    pub limit: Option<usize>,
+}
+
+/// Response of `GET /admin/blacklist`.
+#[derive(Debug, Serialize)]
+pub struct BlacklistResponse {
+    // ---
+    /// Revoked access tokens, by `jti`
+    revoked: Vec<RevokedToken>,
+
+    /// Whether more are blacklisted than listed
+    truncated: bool,
+}
+
+/// Response of `GET /admin/blacklist/{jti}`.
+#[derive(Debug, Serialize)]
+pub struct BlacklistEntryResponse {
+    // ---
+    /// The access token ID looked up
+    jti: String,
+
+    /// Whether it is blacklisted
+    revoked: bool,
+
+    /// Seconds until the entry expires, if blacklisted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ttl_seconds: Option<i64>,
+}
+
+// ---
+
+/// Build the `/admin/blacklist` routes, guarded by the admin token; empty
+/// when no admin token is configured (see [`tokn_server::admin_routes`]).
+///
+/// Routes:
+/// - `GET /admin/blacklist?limit=100` - list revoked access tokens and their
+///   TTLs, by `jti`
+/// - `GET /admin/blacklist/{jti}` - whether `jti` is revoked, and for how
+///   long; 200 either way
+/// - `DELETE /admin/blacklist/{jti}` - take `jti` off the blacklist, so the
+///   token is accepted again until it expires; 204, or 404 if it was not
+///   revoked
+///
+/// Tokens revoked by a [token version](crate::TokenStore::token_version)
+/// bump are not on the blacklist.
+pub fn blacklist_routes(state: &AppState) -> Router<AppState> {
+    // ---
+    let routes = Router::new()
+        .route("/admin/blacklist", get(list_blacklist_handler))
+        .route(
+            "/admin/blacklist/{jti}",
+            get(get_blacklist_entry_handler).delete(delete_blacklist_entry_handler),
+        );
+
+    tokn_server::admin_routes(&state.config.get().admin, routes)
+}
+
+/// The 503 problem for a failed blacklist `action`.
+fn unavailable(action: &str, e: anyhow::Error) -> ApiError {
+    // ---
+    tracing::error!("Blacklist {action} failed: {:#}", e);
+    ApiError::StoreUnavailable(format!("Failed to {action} blacklist"))
+}
+
+// ---
+
+async fn list_blacklist_handler(
+    State(state): State<AppState>,
+    Query(query): Query<ListBlacklistQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    // ---
+    let limit = query.limit.unwrap_or(DEFAULT_LIST_LIMIT);
+    if !(1..=MAX_LIST_LIMIT).contains(&limit) {
+        return Err(ApiError::InvalidRequest(format!(
+            "limit must be 1 to {MAX_LIST_LIMIT}"
+        )));
+    }
+    // Stateless services do not route this endpoint
+    let Some(store) = &state.store else {
+        return Err(ApiError::NotFound);
+    };
+
+    // One more than asked for tells whether the list was cut short
+    let mut revoked = store
+        .list_revoked_tokens(limit + 1)
+        .await
+        .map_err(|e| unavailable("read", e))?;
+    let truncated = revoked.len() > limit;
+    revoked.sort_by(|a, b| a.jti.cmp(&b.jti));
+    revoked.truncate(limit);
+
+    let response = BlacklistResponse { revoked, truncated };
+    Ok(([(header::CACHE_CONTROL, "no-store")], Json(response)))
+}
+
+async fn get_blacklist_entry_handler(
+    State(state): State<AppState>,
+    Path(jti): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    // ---
+    let Some(store) = &state.store else {
+        return Err(ApiError::NotFound);
+    };
+
+    let ttl_seconds = store
+        .revoked_token_ttl(&jti)
+        .await
+        .map_err(|e| unavailable("read", e))?;
+
+    let response = BlacklistEntryResponse {
+        jti,
+        revoked: ttl_seconds.is_some(),
+        ttl_seconds,
+    };
+    Ok(([(header::CACHE_CONTROL, "no-store")], Json(response)))
+}
+
+async fn delete_blacklist_entry_handler(
+    State(state): State<AppState>,
+    Path(jti): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    // ---
+    let Some(store) = &state.store else {
+        return Err(ApiError::NotFound);
+    };
+
+    let deleted = store
+        .unrevoke_token(&jti)
+        .await
+        .map_err(|e| unavailable("update", e))?;
+    if !deleted {
+        return Err(ApiError::NoSuchEntry("Token is not revoked".into()));
+    }
+
+    tracing::warn!(jti = %jti, "Removed access token from the blacklist");
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,75 @@
+// jwt-service/src/handlers/cookie.rs
+
+//! Token cookies (cookie mode)
+//!
+//! With `jwt.cookie_mode` set, `POST /v1/auth/token` and
+//! `POST /v1/auth/refresh` hand their tokens to the browser as `HttpOnly`
+//! cookies instead of in the JSON body, so page scripts never see them.
+//! Protected routes accept the access token cookie in place of an
+//! `Authorization` header, and the refresh endpoint reads the refresh token
+//! cookie when the body has none.
+
+use crate::JwtConfig;
+use axum::http::{header, HeaderMap, HeaderValue};
+use axum::response::Response;
+use tokn_core::cookie_value;
+
+// ---
+
+/// Cookie carrying the access token in cookie mode.
+pub const ACCESS_TOKEN_COOKIE: &str = "access_token";
+
+/// Cookie carrying the refresh token in cookie mode.
+pub const REFRESH_TOKEN_COOKIE: &str = "refresh_token";
+
+// ---
+
+/// Set `access_token` and the refresh token (if any) as cookies on
+/// `response`, each living as long as its token: `access_ttl` seconds and
+/// the refresh token's own TTL.
+pub(crate) fn with_token_cookies(
+    response: Response,
+    access_token: &str,
+    access_ttl: i64,
+    refresh: Option<(&str, i64)>,
+) -> Response {
+    // ---
+    let response = with_cookie(response, ACCESS_TOKEN_COOKIE, access_token, access_ttl);
+    match refresh {
+        Some((refresh_token, ttl)) => {
+            with_cookie(response, REFRESH_TOKEN_COOKIE, refresh_token, ttl)
+        }
+        None => response,
+    }
+}
+
+/// Append cookie `name` holding `value` for `ttl` seconds. The cookie is
+/// `Secure`, so browsers only send it over HTTPS, and `SameSite=Strict`, so
+/// requests other sites trigger do not carry it.
+fn with_cookie(mut response: Response, name: &str, value: &str, ttl: i64) -> Response {
+    // ---
+    let cookie =
+        format!("{name}={value}; Path=/; Max-Age={ttl}; HttpOnly; Secure; SameSite=Strict");
+    if let Ok(value) = HeaderValue::from_str(&cookie) {
+        response.headers_mut().append(header::SET_COOKIE, value);
+    }
+    response
+}
+
+/// The refresh token cookie of a request, in cookie mode.
+pub(crate) fn refresh_token_cookie<'a>(jwt: &JwtConfig, headers: &'a HeaderMap) -> Option<&'a str> {
+    // ---
+    jwt.cookie_mode
+        .then(|| cookie_value(headers, REFRESH_TOKEN_COOKIE))
+        .flatten()
+}
+
+/// Seconds a new session's refresh token lives: the refresh token expiry,
+/// capped by the maximum session length.
+pub(crate) fn refresh_cookie_ttl(jwt: &JwtConfig) -> i64 {
+    // ---
+    jwt.max_session_seconds
+        .map_or(jwt.refresh_token_expiry_seconds, |max| {
+            max.min(jwt.refresh_token_expiry_seconds)
+        })
+}
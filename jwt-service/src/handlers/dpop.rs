@@ -0,0 +1,68 @@
+// jwt-service/src/handlers/dpop.rs
+
+//! DPoP proofs at the token endpoints
+//!
+//! `POST /v1/auth/token` and `POST /v1/auth/refresh` bind the access tokens
+//! they issue to the key of a `DPoP` proof sent with the request (RFC 9449
+//! §5); without one they issue bearer tokens.
+
+use crate::{ApiError, AppState};
+use axum::http::{header, HeaderMap};
+use tokn_core::{verify_dpop_proof, DpopError, DpopProof, DpopRequest, DPOP_HEADER};
+
+// ---
+
+/// Verify the `DPoP` proof of a token request to `path`, if there is one,
+/// and record it against replay.
+///
+/// # Errors
+///
+/// Returns a 400 Bad Request problem for an invalid or replayed proof, or a
+/// 500 Internal Server Error problem if the proof cannot be recorded.
+pub(crate) async fn token_request_proof(
+    state: &AppState,
+    headers: &HeaderMap,
+    path: &str,
+) -> Result<Option<DpopProof>, ApiError> {
+    // ---
+    let Some(proof) = headers.get(DPOP_HEADER) else {
+        return Ok(None);
+    };
+    let invalid = |e: DpopError| {
+        tracing::warn!("Refused token request with DPoP proof: {e}");
+        ApiError::InvalidDpopProof(e.to_string())
+    };
+
+    let proof = proof
+        .to_str()
+        .map_err(|_| invalid(DpopError::Invalid("malformed")))?;
+    let request = DpopRequest {
+        method: "POST",
+        host: headers
+            .get(header::HOST)
+            .and_then(|value| value.to_str().ok()),
+        path,
+        access_token: None,
+    };
+    let proof = verify_dpop_proof(proof, &request, state.clock.as_ref()).map_err(invalid)?;
+
+    match state.dpop_proof_replayed(&proof).await {
+        Ok(false) => Ok(Some(proof)),
+        Ok(true) => Err(invalid(DpopError::Replayed)),
+        Err(e) => {
+            tracing::error!("Failed to record DPoP proof: {:#}", e);
+            Err(ApiError::Internal("Failed to verify DPoP proof".into()))
+        }
+    }
+}
+
+/// The `token_type` of a token response: `DPoP` for a DPoP-bound access
+/// token, else `Bearer`.
+pub(crate) fn token_type(proof: Option<&DpopProof>) -> String {
+    // ---
+    match proof {
+        Some(_) => "DPoP",
+        None => "Bearer",
+    }
+    .to_string()
+}
@@ -2,15 +2,23 @@
 
 //! Token generation endpoint
 //!
-//! Handles POST /auth/token - generates JWT access tokens and refresh tokens
+//! Handles POST /v1/auth/token - generates JWT access tokens and refresh tokens
 
-use crate::{generate_refresh_token, token::generate_token, AppState, Claims};
+use super::cookie::{refresh_cookie_ttl, with_token_cookies};
+use super::dpop::{token_request_proof, token_type};
+use crate::{
+    ApiError, AppState, AuditEvent, AuditEventKind, Claims, ClientDevice, ClientInfo, Config,
+    ISSUER_KEY_HEADER,
+};
 use axum::{
-    extract::State,
-    http::StatusCode,
-    response::{IntoResponse, Json},
+    extract::{OriginalUri, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
 };
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tokn_core::DpopProof;
+use tokn_events::{AuthEvent, AuthEventKind};
 
 // ---
 
@@ -25,6 +33,34 @@ pub struct TokenRequest {
 
     /// User email to encode in the token
     pub email: String,
+
+    /// Roles to grant, carried in the `roles` claim
+    #[serde(default)]
+    pub roles: Vec<String>,
+
+    /// Space-delimited scopes to grant, carried in the `scope` claim
+    #[serde(default)]
+    pub scope: Option<String>,
+
+    /// Services the token is meant for, carried in the `aud` claim: one as a
+    /// string, or several as an array
+    #[serde(default, with = "tokn_core::audience")]
+    pub audience: Vec<String>,
+
+    /// OAuth client the token is issued to, carried in the `client_id` claim
+    /// (default: the holder of the caller's issuer key, if any)
+    #[serde(default)]
+    pub client_id: Option<String>,
+
+    /// Access token lifetime in seconds, capped at
+    /// `jwt.access_token_expiry_seconds` (default: that setting)
+    #[serde(default)]
+    pub expires_in: Option<i64>,
+
+    /// Extra claims (tenant ID, plan, ...) added to the token payload; may
+    /// not set reserved claims such as `exp` or `jti`
+    #[serde(default)]
+    pub custom_claims: Map<String, Value>,
 }
 
 // ---
@@ -35,34 +71,67 @@ pub struct TokenRequest {
 #[derive(Debug, Serialize)]
 pub struct TokenResponse {
     // ---
-    /// The JWT access token
-    access_token: String,
+    /// The JWT access token (omitted in cookie mode)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    access_token: Option<String>,
 
-    /// Token type (always "Bearer" for JWTs)
+    /// Token type: "DPoP" for a token bound to the request's DPoP key, else
+    /// "Bearer"
     token_type: String,
 
-    /// Access token expiry in seconds
+    /// Access token expiry in seconds, as requested or capped
     expires_in: i64,
 
-    /// Refresh token for obtaining new access tokens
-    refresh_token: String,
+    /// Refresh token for obtaining new access tokens (omitted when stateless,
+    /// and in cookie mode)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    refresh_token: Option<String>,
 }
 
 // ---
 
 /// Generate new JWT access token and refresh token.
 ///
-/// This endpoint creates a signed JWT access token and a refresh token stored in Redis.
+/// This endpoint creates a signed JWT access token and a refresh token kept in the
+/// token store. A stateless service (`JWT_STATELESS=true`, or the Redis store
+/// compiled out) omits `refresh_token` from the response.
 ///
 /// # Request
 ///
 /// ```json
 /// {
 ///   "user_id": "user_12345",
-///   "email": "john@example.com"
+///   "email": "john@example.com",
+///   "roles": ["admin"],
+///   "scope": "orders:read orders:write",
+///   "audience": ["orders-api", "billing-api"],
+///   "client_id": "web-app",
+///   "custom_claims": { "tenant_id": "acme" },
+///   "expires_in": 60
 /// }
 /// ```
 ///
+/// `roles`, `scope`, `audience`, and `custom_claims` are optional. Their
+/// entries appear at the top level of the JWT payload (`audience` as `aud`)
+/// and carry over to tokens issued by refresh; routes check scopes with
+/// [`require_scope`](super::require_scope), and services named in `aud`
+/// accept the token with `POST /v1/auth/validate`'s `audience` or
+/// `tokn_auth::JwtAuth::audience`.
+///
+/// `client_id` names the application the token is issued to in the
+/// `client_id` claim; it defaults to the holder of the caller's issuer key when
+/// `TOKEN_ISSUER_REQUIRE_KEY` is set. With `JWT_ISSUER` set, tokens name it in
+/// `iss`.
+///
+/// With `JWT_COOKIE_MODE` set, the tokens are set as `HttpOnly` cookies
+/// (`access_token` and `refresh_token`) rather than returned, leaving
+/// `token_type` and `expires_in` in the body.
+///
+/// `expires_in` asks for a shorter-lived access token, e.g. 60 seconds for a
+/// download link. Longer requests are capped at
+/// `jwt.access_token_expiry_seconds`, and the response's `expires_in` gives
+/// the lifetime granted. Tokens issued by refresh get the configured lifetime.
+///
 /// # Response (200 OK)
 ///
 /// ```json
@@ -76,12 +145,30 @@ pub struct TokenResponse {
 ///
 /// # Security
 ///
-/// - Access tokens are signed with HS256 using JWT_SECRET
-/// - Access token expiry is configurable (default: 15 minutes)
+/// - With `TOKEN_ISSUER_REQUIRE_KEY` set, callers must send an issuer key in
+///   `X-Issuer-Key` (see [`IssuerConfig`](crate::IssuerConfig)); anyone else
+///   gets 401 and no token
+/// - Access tokens are signed with `JWT_ALGORITHM` (HS256, HS384, or HS512 with `JWT_SECRET`, or RS256/ES256/EdDSA with a key pair)
+/// - Access token expiry is configurable (default: 15 minutes); callers can
+///   only shorten it
 /// - Each access token has a unique `jti` for revocation tracking
-/// - Refresh tokens are random UUIDs stored in Redis
+/// - Refresh tokens are random UUIDs kept in the token store
 /// - Refresh tokens expire after configured duration (default: 7 days)
 /// - Refresh tokens are single-use (deleted on refresh)
+/// - With a `DPoP` proof header (RFC 9449), the access token is bound to the
+///   proof's key by its `cnf.jkt` claim and answered with
+///   `"token_type": "DPoP"`; the refresh token can then only be used with
+///   proofs from the same key. Proofs are single-use (remembered in Redis;
+///   not checked for replay when stateless)
+/// - The session records the client's `User-Agent`, IP address, and
+///   optional `X-Device-Id` header, listed by
+///   `GET /v1/auth/sessions/{user_id}`; with `JWT_REFRESH_BIND_DEVICE` set,
+///   only that device can refresh it
+/// - The access token carries the user's token version (`ver`), so
+///   `POST /v1/auth/invalidate-user` revokes it (not when stateless)
+/// - With `JWT_ACCESS_TOKEN_PROFILE` set, access tokens follow RFC 9068:
+///   they are typed `at+jwt` and always carry `iss`, `aud`, and
+///   `client_id`, so requests without an `audience` or client are refused
 ///
 /// # Token Workflow
 ///
@@ -91,58 +178,219 @@ pub struct TokenResponse {
 /// 4. Refresh token is consumed and new one issued (rotation)
 /// 5. After 7 days, refresh token expires and user must re-authenticate
 ///
-/// # TODO
-///
-/// - Add rate limiting to prevent token generation abuse
-/// - Add user authentication before issuing tokens (currently trusts request)
+/// The service trusts the caller to have authenticated `user_id`; the API
+/// key only establishes who the caller is.
 ///
 /// # Errors
 ///
-/// Returns 500 Internal Server Error if token generation or Redis storage fails.
+/// Returns a 401 Unauthorized problem for a missing or unknown issuer key when
+/// one is required, a 400 Bad Request problem if `expires_in` is not positive,
+/// the access token profile lacks an audience or client,
+/// or `custom_claims` sets a reserved claim
+/// (see [`tokn_core::RESERVED_CLAIMS`]), the DPoP proof is invalid or
+/// replayed, or `X-Device-Id` is empty or over 128 bytes, or a 500 Internal
+/// Server Error problem if token generation or the token store fails.
 pub async fn generate_token_handler(
     State(state): State<AppState>,
+    headers: HeaderMap,
+    OriginalUri(uri): OriginalUri,
+    client: ClientInfo,
     Json(req): Json<TokenRequest>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     // ---
-    // Create claims with configured expiry time
+    // Snapshot so a concurrent reload cannot change settings mid-request
+    let config = state.config.get();
+    let issuer = authorize_issuer(&state, &config, &headers).await?;
+    let proof = token_request_proof(&state, &headers, uri.path()).await?;
+    let device = ClientDevice::from_request(&headers, &client)?;
+
+    // Callers may shorten the token's lifetime, never extend it
+    let expiry_seconds = match req.expires_in {
+        Some(seconds) if seconds <= 0 => {
+            return Err(ApiError::InvalidRequest(
+                "expires_in must be a positive number of seconds".into(),
+            ));
+        }
+        Some(seconds) => seconds.min(config.jwt.access_token_expiry_seconds),
+        None => config.jwt.access_token_expiry_seconds,
+    };
+
+    // Stamp the user's token version, so invalidating the user revokes it
+    let version = state.token_version(&req.user_id).await.map_err(|e| {
+        tracing::error!("Token version lookup failed: {:#}", e);
+        ApiError::Internal("Failed to generate token".into())
+    })?;
+
+    // Create claims with the granted expiry time
     let claims = Claims::new(
         req.user_id.clone(),
         req.email.clone(),
-        state.config.jwt.access_token_expiry_seconds,
-    );
+        expiry_seconds,
+        state.clock.as_ref(),
+    )
+    .with_access(req.roles, req.scope)
+    .with_issuer(config.jwt.issuer.clone())
+    .with_audience(req.audience)
+    .with_client_id(req.client_id.or_else(|| issuer.clone()))
+    .with_dpop_key(proof.as_ref().map(|proof| proof.jkt.clone()))
+    .with_version(version)
+    .with_custom(req.custom_claims)
+    .map_err(|e| ApiError::InvalidRequest(format!("Invalid custom_claims: {e}")))?;
+    require_profile_claims(&config, &claims)?;
 
+    issue_tokens(
+        &state,
+        &claims,
+        proof.as_ref(),
+        &device,
+        &client,
+        issuer.as_deref().unwrap_or("-"),
+    )
+    .await
+}
+
+// ---
+
+/// Sign `claims` and store a refresh token starting a session on `device`,
+/// answering as `POST /v1/auth/token` does, with cookies in cookie mode.
+/// `issuer` names who the tokens were issued for in the log.
+///
+/// # Errors
+///
+/// Returns a 500 Internal Server Error problem if signing or the token store
+/// fails.
+pub(super) async fn issue_tokens(
+    state: &AppState,
+    claims: &Claims,
+    proof: Option<&DpopProof>,
+    device: &ClientDevice,
+    client: &ClientInfo,
+    issuer: &str,
+) -> Result<Response, ApiError> {
+    // ---
     // Generate signed JWT access token
-    let access_token = generate_token(&claims, &state.config.jwt.secret).map_err(|e| {
+    let access_token = state.keys.get().sign(claims).map_err(|e| {
         tracing::error!("Token generation failed: {}", e);
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Failed to generate token".to_string(),
-        )
+        ApiError::Internal("Failed to generate token".into())
     })?;
 
-    // Generate and store refresh token
-    let refresh_token = generate_refresh_token(
-        &mut state.redis.clone(),
-        &req.user_id,
-        &req.email,
-        state.config.jwt.refresh_token_expiry_seconds,
-    )
-    .await
-    .map_err(|e| {
-        tracing::error!("Refresh token generation failed: {}", e);
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Failed to generate refresh token".to_string(),
-        )
-    })?;
+    // Generate and store refresh token (unless stateless), recording the
+    // client for the sessions API
+    let refresh_token = state
+        .issue_refresh_token(claims, device)
+        .await
+        .map_err(|e| {
+            tracing::error!("Refresh token generation failed: {}", e);
+            ApiError::Internal("Failed to generate refresh token".into())
+        })?;
 
-    // Build response
+    tracing::info!(
+        event = "token_issued",
+        user_id = %claims.sub,
+        jti = %claims.jti,
+        issuer = issuer,
+        "Issued access token"
+    );
+    state.events.emit(
+        AuthEvent::new(AuthEventKind::TokenIssued)
+            .subject(&claims.sub)
+            .jti(&claims.jti),
+    );
+    state.audit.record(
+        AuditEvent::new(AuditEventKind::TokenIssued, state.clock.as_ref())
+            .user_id(&claims.sub)
+            .jti(&claims.jti)
+            .client(client),
+    );
+
+    // Build response; in cookie mode the tokens go in cookies instead
+    let expires_in = claims.exp.saturating_sub(claims.iat) as i64;
+    let config = state.config.get();
+    let cookie_mode = config.jwt.cookie_mode;
     let response = TokenResponse {
-        access_token,
-        token_type: "Bearer".to_string(),
-        expires_in: state.config.jwt.access_token_expiry_seconds,
-        refresh_token,
+        access_token: (!cookie_mode).then(|| access_token.clone()),
+        token_type: token_type(proof),
+        expires_in,
+        refresh_token: refresh_token.clone().filter(|_| !cookie_mode),
+    };
+
+    // Token responses must not be cached (or compressed, see tokn_server::compression_layer)
+    let response = (
+        StatusCode::OK,
+        [(header::CACHE_CONTROL, "no-store")],
+        Json(response),
+    )
+        .into_response();
+    if !cookie_mode {
+        return Ok(response);
+    }
+    let refresh_ttl = refresh_cookie_ttl(&config.jwt);
+    let refresh = refresh_token.as_deref().map(|token| (token, refresh_ttl));
+    Ok(with_token_cookies(
+        response,
+        &access_token,
+        expires_in,
+        refresh,
+    ))
+}
+
+// ---
+
+/// Under the access token profile (`jwt.access_token_profile`), refuse
+/// `claims` without the `aud` and `client_id` RFC 9068 requires; refreshed
+/// tokens inherit both from the session.
+///
+/// # Errors
+///
+/// Returns a 400 Bad Request problem naming the missing field.
+pub(super) fn require_profile_claims(config: &Config, claims: &Claims) -> Result<(), ApiError> {
+    // ---
+    if !config.jwt.access_token_profile {
+        return Ok(());
+    }
+    let missing = if claims.aud.is_empty() {
+        "audience"
+    } else if claims.client_id.is_none() {
+        "client_id"
+    } else {
+        return Ok(());
+    };
+    Err(ApiError::InvalidRequest(format!(
+        "{missing} is required for at+jwt access tokens"
+    )))
+}
+
+/// Check the caller's `X-Issuer-Key` when `issuer.require_key` is set,
+/// returning who holds the key.
+pub(super) async fn authorize_issuer(
+    state: &AppState,
+    config: &Config,
+    headers: &HeaderMap,
+) -> Result<Option<String>, ApiError> {
+    // ---
+    if !config.issuer.require_key {
+        return Ok(None);
+    }
+
+    let Some(key) = headers
+        .get(ISSUER_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+    else {
+        tracing::warn!("Refused token request without an issuer key");
+        return Err(ApiError::Unauthenticated(
+            "Issuer key required (X-Issuer-Key)".into(),
+        ));
     };
 
-    Ok((StatusCode::OK, Json(response)))
+    match state.issuer_key_name(key).await {
+        Ok(Some(name)) => Ok(Some(name)),
+        Ok(None) => {
+            tracing::warn!("Refused token request with an unknown issuer key");
+            Err(ApiError::Unauthenticated("Invalid issuer key".into()))
+        }
+        Err(e) => {
+            tracing::error!("Failed to check issuer key: {:#}", e);
+            Err(ApiError::Internal("Failed to verify issuer key".into()))
+        }
+    }
 }
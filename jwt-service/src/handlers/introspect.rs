@@ -0,0 +1,239 @@
+// jwt-service/src/handlers/introspect.rs
+
+//! Token introspection endpoint (RFC 7662)
+//!
+//! Handles POST /v1/auth/introspect - reports whether an access token is
+//! active, for resource servers that cannot verify JWTs themselves. Callers
+//! must authenticate (RFC 7662 §2.1), so the endpoint cannot be used to probe
+//! tokens anonymously.
+
+use crate::{ApiError, AppState, ISSUER_KEY_HEADER};
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json},
+    Extension, Form,
+};
+use serde::{Deserialize, Serialize};
+use tokn_core::{constant_time_eq, Confirmation};
+use tokn_server::CallingService;
+
+// ---
+
+/// Introspection request (RFC 7662 §2.1), form-encoded.
+#[derive(Debug, Deserialize)]
+pub struct IntrospectRequest {
+    // ---
+    /// The token to introspect
+    pub token: String,
+
+    /// Hint about the token's type; accepted and ignored, since only access
+    /// tokens are introspected
+    #[serde(default)]
+    pub token_type_hint: Option<String>,
+}
+
+// ---
+
+/// Introspection response (RFC 7662 §2.2).
+///
+/// An inactive token is reported as `{"active": false}` alone, without
+/// saying why.
+#[derive(Debug, Default, Serialize)]
+pub struct IntrospectResponse {
+    // ---
+    /// Whether the token is valid, unexpired, and not revoked
+    active: bool,
+
+    /// Subject (user ID)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sub: Option<String>,
+
+    /// Expiry (Unix timestamp)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exp: Option<usize>,
+
+    /// Issue time (Unix timestamp)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    iat: Option<usize>,
+
+    /// Space-delimited scopes granted to the token
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scope: Option<String>,
+
+    /// Services the token is meant for
+    #[serde(skip_serializing_if = "Vec::is_empty", with = "tokn_core::audience")]
+    aud: Vec<String>,
+
+    /// Issuer of the token
+    #[serde(skip_serializing_if = "Option::is_none")]
+    iss: Option<String>,
+
+    /// Client the token was issued to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_id: Option<String>,
+
+    /// JWT ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    jti: Option<String>,
+
+    /// Token type: "DPoP" for a DPoP-bound token, else "Bearer"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    token_type: Option<String>,
+
+    /// The DPoP key a bound token must be presented with (RFC 9449 §6.2)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cnf: Option<Confirmation>,
+}
+
+// ---
+
+/// Introspect an access token.
+///
+/// Makes the same checks as `POST /v1/auth/validate` (signature, expiry,
+/// revocation blacklist) and as the gRPC introspection service, answered in
+/// the RFC 7662 format.
+///
+/// The caller must be another tokn service (a request signed with
+/// `SERVICE_AUTH_KEY`, see [`tokn_server::verify_service_signature`]), a
+/// backend holding an issuer key (`X-Issuer-Key`), or an operator with the
+/// admin token (`Authorization: Bearer <ADMIN_TOKEN>`).
+///
+/// # Request
+///
+/// ```text
+/// POST /v1/auth/introspect
+/// X-Issuer-Key: Qm3Tz8Wd1Kx6Vb4Nr9Hj2Lc7Pf0Gs5Ya
+/// Content-Type: application/x-www-form-urlencoded
+///
+/// token=eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9...
+/// ```
+///
+/// # Response (200 OK)
+///
+/// ```json
+/// {
+///   "active": true,
+///   "sub": "user_12345",
+///   "exp": 1703001234,
+///   "iat": 1703000334,
+///   "scope": "orders:read",
+///   "jti": "f47ac10b-58cc-4372-a567-0e02b2c3d479",
+///   "token_type": "Bearer"
+/// }
+/// ```
+///
+/// A DPoP-bound token is reported with `"token_type": "DPoP"` and its
+/// `cnf` claim, for resource servers to check the proof against.
+///
+/// Invalid, expired, and revoked tokens are `{"active": false}` with 200 OK,
+/// not errors.
+///
+/// # Errors
+///
+/// Returns a 401 Unauthorized problem for a caller presenting none of the
+/// credentials above (or an unknown issuer key), a 500 Internal Server Error
+/// problem if the issuer key cannot be checked, and a 503 Service
+/// Unavailable problem if the revocation blacklist cannot be checked.
+pub async fn introspect_token_handler(
+    State(state): State<AppState>,
+    service: Option<Extension<CallingService>>,
+    headers: HeaderMap,
+    Form(req): Form<IntrospectRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    // ---
+    let service = service.map(|Extension(service)| service);
+    authorize_caller(&state, &headers, service.as_ref()).await?;
+
+    let response = match state.keys.get().verify(&req.token, state.clock.as_ref()) {
+        Ok(claims) => {
+            let revoked = state.is_revoked(&claims).await.map_err(|e| {
+                tracing::error!("Revocation check failed: {:#}", e);
+                ApiError::StoreUnavailable("Failed to verify token status".into())
+            })?;
+
+            if revoked {
+                IntrospectResponse::default()
+            } else {
+                IntrospectResponse {
+                    active: true,
+                    sub: Some(claims.sub),
+                    exp: Some(claims.exp),
+                    iat: Some(claims.iat),
+                    scope: claims.scope,
+                    aud: claims.aud,
+                    iss: claims.iss,
+                    client_id: claims.client_id,
+                    jti: Some(claims.jti),
+                    token_type: Some(
+                        if claims.cnf.is_some() {
+                            "DPoP"
+                        } else {
+                            "Bearer"
+                        }
+                        .to_string(),
+                    ),
+                    cnf: claims.cnf,
+                }
+            }
+        }
+        Err(e) => {
+            tracing::debug!("Introspected token is not active: {}", e);
+            IntrospectResponse::default()
+        }
+    };
+
+    // Introspection results must not be cached
+    Ok((
+        StatusCode::OK,
+        [(header::CACHE_CONTROL, "no-store")],
+        Json(response),
+    ))
+}
+
+/// Refuse callers that are neither a tokn service with a signed request, a
+/// backend with an issuer key, nor an operator with the admin token.
+async fn authorize_caller(
+    state: &AppState,
+    headers: &HeaderMap,
+    service: Option<&CallingService>,
+) -> Result<(), ApiError> {
+    // ---
+    if let Some(CallingService(service)) = service {
+        tracing::debug!(service, "Introspection requested by a tokn service");
+        return Ok(());
+    }
+
+    if let Some(key) = headers
+        .get(ISSUER_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+    {
+        return match state.issuer_key_name(key).await {
+            Ok(Some(_)) => Ok(()),
+            Ok(None) => {
+                tracing::warn!("Refused introspection with an unknown issuer key");
+                Err(ApiError::Unauthenticated("Invalid issuer key".into()))
+            }
+            Err(e) => {
+                tracing::error!("Failed to check issuer key: {:#}", e);
+                Err(ApiError::Internal("Failed to verify issuer key".into()))
+            }
+        };
+    }
+
+    let config = state.config.get();
+    let presented = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    if let (Some(token), Some(presented)) = (&config.admin.token, presented) {
+        if constant_time_eq(presented.as_bytes(), token.expose().as_bytes()) {
+            return Ok(());
+        }
+    }
+
+    tracing::warn!("Refused unauthenticated introspection request");
+    Err(ApiError::Unauthenticated(
+        "Introspection requires a signed request, an issuer key, or the admin token".into(),
+    ))
+}
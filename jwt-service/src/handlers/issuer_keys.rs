@@ -0,0 +1,168 @@
+// jwt-service/src/handlers/issuer_keys.rs
+
+//! Admin API for the token endpoint's issuer keys
+//!
+//! Handles `/admin/issuer-keys`: create, list, and delete the keys kept in
+//! the token store (see [`IssuerConfig`](crate::IssuerConfig)).
+
+use crate::{generate_issuer_key, ApiError, AppState, IssuerKey};
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Json},
+    routing::{delete, get},
+    Router,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+// ---
+
+/// Request body of `POST /admin/issuer-keys`.
+#[derive(Debug, Deserialize)]
+pub struct CreateIssuerKeyRequest {
+    // ---
+    /// Who the key is for (e.g. `billing-service`); logged with every token
+    /// it issues
+    pub name: String,
+}
+
+/// A stored key, by ID; the key itself is only in [`CreatedIssuerKey`].
+#[derive(Debug, Serialize)]
+pub struct IssuerKeyInfo {
+    // ---
+    /// SHA-256 of the key, for `DELETE /admin/issuer-keys/{id}`
+    id: String,
+
+    /// Who the key was created for
+    name: String,
+
+    /// When the key was created
+    created_at: DateTime<Utc>,
+}
+
+impl IssuerKeyInfo {
+    // ---
+    fn new(id: String, key: IssuerKey) -> Self {
+        // ---
+        Self {
+            id,
+            name: key.name,
+            created_at: key.created_at,
+        }
+    }
+}
+
+/// Response of `POST /admin/issuer-keys`: the only time the key is shown.
+#[derive(Debug, Serialize)]
+pub struct CreatedIssuerKey {
+    // ---
+    #[serde(flatten)]
+    info: IssuerKeyInfo,
+
+    /// The key, for the caller's `X-Issuer-Key` header
+    issuer_key: String,
+}
+
+// ---
+
+/// Build the `/admin/issuer-keys` routes, guarded by the admin token; empty
+/// when no admin token is configured (see [`tokn_server::admin_routes`]).
+///
+/// Routes:
+/// - `POST /admin/issuer-keys` - create a key for `{"name": ...}`; 201 with
+///   the key, which is not stored and cannot be shown again
+/// - `GET /admin/issuer-keys` - list the stored keys (IDs and names only),
+///   oldest first
+/// - `DELETE /admin/issuer-keys/{id}` - delete a key; 204, or 404 if there is
+///   none with that ID
+///
+/// Keys from `TOKEN_ISSUER_KEYS` are not listed and cannot be deleted here.
+pub fn issuer_key_routes(state: &AppState) -> Router<AppState> {
+    // ---
+    let routes = Router::new()
+        .route(
+            "/admin/issuer-keys",
+            get(list_issuer_keys_handler).post(create_issuer_key_handler),
+        )
+        .route("/admin/issuer-keys/{id}", delete(delete_issuer_key_handler));
+
+    tokn_server::admin_routes(&state.config.get().admin, routes)
+}
+
+// ---
+
+async fn create_issuer_key_handler(
+    State(state): State<AppState>,
+    Json(req): Json<CreateIssuerKeyRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    // ---
+    let name = req.name.trim();
+    if name.is_empty() {
+        return Err(ApiError::InvalidRequest("name is required".into()));
+    }
+    let Some(store) = &state.store else {
+        return Err(ApiError::NotFound);
+    };
+
+    let issuer_key = generate_issuer_key();
+    let entry = IssuerKey {
+        name: name.to_string(),
+        created_at: state.clock.now(),
+    };
+    let id = store.store_key(&issuer_key, &entry).await.map_err(|e| {
+        tracing::error!("Issuer key creation failed: {:#}", e);
+        ApiError::StoreUnavailable("Failed to store issuer key".into())
+    })?;
+
+    tracing::info!(name = %entry.name, id = %id, "Created issuer key");
+    let created = CreatedIssuerKey {
+        info: IssuerKeyInfo::new(id, entry),
+        issuer_key,
+    };
+    Ok((
+        StatusCode::CREATED,
+        [(header::CACHE_CONTROL, "no-store")],
+        Json(created),
+    ))
+}
+
+async fn list_issuer_keys_handler(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<IssuerKeyInfo>>, ApiError> {
+    // ---
+    let Some(store) = &state.store else {
+        return Err(ApiError::NotFound);
+    };
+
+    let keys = store.list_keys::<IssuerKey>().await.map_err(|e| {
+        tracing::error!("Issuer key listing failed: {:#}", e);
+        ApiError::StoreUnavailable("Failed to list issuer keys".into())
+    })?;
+    Ok(Json(
+        keys.into_iter()
+            .map(|(id, key)| IssuerKeyInfo::new(id, key))
+            .collect(),
+    ))
+}
+
+async fn delete_issuer_key_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    // ---
+    let Some(store) = &state.store else {
+        return Err(ApiError::NotFound);
+    };
+
+    let deleted = store.delete_key::<IssuerKey>(&id).await.map_err(|e| {
+        tracing::error!("Issuer key deletion failed: {:#}", e);
+        ApiError::StoreUnavailable("Failed to delete issuer key".into())
+    })?;
+    if !deleted {
+        return Err(ApiError::NoSuchEntry("No such issuer key".into()));
+    }
+
+    tracing::info!(id = %id, "Deleted issuer key");
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,275 @@
+// jwt-service/src/handlers/magic_link.rs
+
+//! Magic link endpoints
+//!
+//! Handles POST /v1/auth/magic-link - issues a single-use, short-lived login
+//! link for a user - and POST /v1/auth/magic-link/redeem - consumes the link
+//! for an access and refresh token pair
+
+use super::dpop::token_request_proof;
+use super::generate::{authorize_issuer, issue_tokens, require_profile_claims, TokenRequest};
+use crate::{ApiError, AppState, Claims, ClientDevice, ClientInfo, TokenStore};
+use axum::{
+    extract::{OriginalUri, State},
+    http::{header, HeaderMap},
+    response::{IntoResponse, Json},
+};
+use serde::{Deserialize, Serialize};
+use tokn_mail::Template;
+
+// ---
+
+/// Response of `POST /v1/auth/magic-link`.
+#[derive(Debug, Serialize)]
+pub struct MagicLinkResponse {
+    // ---
+    /// The link's token, for the caller to deliver; omitted when the link
+    /// was emailed (`JWT_MAGIC_LINK_URL` set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    token: Option<String>,
+
+    /// Seconds until the link expires unredeemed
+    expires_in: i64,
+}
+
+/// Request of `POST /v1/auth/magic-link/redeem`.
+#[derive(Debug, Deserialize)]
+pub struct RedeemMagicLinkRequest {
+    // ---
+    /// The token from the link
+    pub token: String,
+}
+
+// ---
+
+/// Issue a single-use login link for a user.
+///
+/// Takes the same request as `POST /v1/auth/token`, and the same `X-Issuer-Key`
+/// when `TOKEN_ISSUER_REQUIRE_KEY` is set, but issues no tokens: whoever
+/// redeems the link within `JWT_MAGIC_LINK_TTL_SECONDS` (default 900) gets
+/// them. With `JWT_MAGIC_LINK_URL` set, the link (that URL with `?token=`
+/// appended) is emailed to `email`; without it, the token is returned for the
+/// caller to deliver.
+///
+/// # Request
+///
+/// ```json
+/// {
+///   "user_id": "user_12345",
+///   "email": "john@example.com",
+///   "scope": "orders:read"
+/// }
+/// ```
+///
+/// # Response (200 OK)
+///
+/// ```json
+/// {
+///   "token": "2c9e7a41-8b3d-4f6e-a5c2-91d0e4b7f358",
+///   "expires_in": 900
+/// }
+/// ```
+///
+/// # Errors
+///
+/// Returns 401 Unauthorized for a missing or unknown API key when one is
+/// required, 400 Bad Request if `custom_claims` sets a reserved claim, the
+/// access token profile lacks an audience or client, or the link is to be
+/// emailed without an `email`, and 503 Service Unavailable if
+/// the link cannot be stored.
+pub async fn issue_magic_link_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<TokenRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    // ---
+    // Stateless services do not route this endpoint
+    let Some(store) = &state.store else {
+        return Err(ApiError::NotFound);
+    };
+    let config = state.config.get();
+    let issuer = authorize_issuer(&state, &config, &headers).await?;
+    if config.jwt.magic_link_url.is_some() && req.email.trim().is_empty() {
+        return Err(ApiError::InvalidRequest(
+            "email is required to send a magic link".into(),
+        ));
+    }
+    let unavailable = |e: anyhow::Error| {
+        tracing::error!("Magic link issuance failed: {:#}", e);
+        ApiError::StoreUnavailable("Failed to issue magic link".into())
+    };
+
+    // Stamp the version now, so invalidating the user also kills the link
+    let version = state
+        .token_version(&req.user_id)
+        .await
+        .map_err(unavailable)?;
+    let claims = Claims::new(
+        req.user_id,
+        req.email,
+        config.jwt.access_token_expiry_seconds,
+        state.clock.as_ref(),
+    )
+    .with_access(req.roles, req.scope)
+    .with_audience(req.audience)
+    .with_client_id(req.client_id.or_else(|| issuer.clone()))
+    .with_version(version)
+    .with_custom(req.custom_claims)
+    .map_err(|e| ApiError::InvalidRequest(format!("Invalid custom_claims: {e}")))?;
+    require_profile_claims(&config, &claims)?;
+
+    let ttl = config.jwt.magic_link_ttl_seconds;
+    let token = store
+        .issue_magic_link(&claims, ttl)
+        .await
+        .map_err(unavailable)?;
+
+    tracing::info!(
+        user_id = %claims.sub,
+        issuer = issuer.as_deref().unwrap_or("-"),
+        emailed = config.jwt.magic_link_url.is_some(),
+        "Issued magic link"
+    );
+    let token = match &config.jwt.magic_link_url {
+        Some(url) => {
+            state.mail.notify(
+                Template::MagicLink,
+                &claims.email,
+                &[
+                    ("link", &link_to(url, &token)),
+                    ("expires_in", &describe_seconds(ttl)),
+                ],
+            );
+            None
+        }
+        None => Some(token),
+    };
+
+    let response = MagicLinkResponse {
+        token,
+        expires_in: ttl,
+    };
+    Ok(([(header::CACHE_CONTROL, "no-store")], Json(response)))
+}
+
+/// Redeem a magic link for an access and refresh token pair.
+///
+/// Called by the page the link points to. The response is that of
+/// `POST /v1/auth/token`: the tokens are issued for the claims requested with
+/// the link, start a new session on the redeeming device, and may be bound to
+/// a `DPoP` proof sent with this request.
+///
+/// # Request
+///
+/// ```json
+/// {
+///   "token": "2c9e7a41-8b3d-4f6e-a5c2-91d0e4b7f358"
+/// }
+/// ```
+///
+/// # Response (200 OK)
+///
+/// ```json
+/// {
+///   "access_token": "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9...",
+///   "token_type": "Bearer",
+///   "expires_in": 900,
+///   "refresh_token": "f47ac10b-58cc-4372-a567-0e02b2c3d479"
+/// }
+/// ```
+///
+/// In cookie mode the tokens are set as cookies, as by `POST /v1/auth/token`.
+///
+/// # Errors
+///
+/// Returns 401 Unauthorized if the link is unknown, expired, or already
+/// redeemed, or its user was invalidated since it was issued, 400 Bad Request
+/// for an invalid DPoP proof or `X-Device-Id`, and 503 Service Unavailable if
+/// the token store cannot be reached.
+pub async fn redeem_magic_link_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    OriginalUri(uri): OriginalUri,
+    client: ClientInfo,
+    Json(req): Json<RedeemMagicLinkRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    // ---
+    // Stateless services do not route this endpoint
+    let Some(store) = &state.store else {
+        return Err(ApiError::NotFound);
+    };
+    let config = state.config.get();
+    let unavailable = |e: anyhow::Error| {
+        tracing::error!("Magic link redemption failed: {:#}", e);
+        ApiError::StoreUnavailable("Failed to redeem magic link".into())
+    };
+
+    // Check the request before the link is consumed, so a bad proof does not
+    // waste it
+    let proof = token_request_proof(&state, &headers, uri.path()).await?;
+    let device = ClientDevice::from_request(&headers, &client)?;
+
+    let Some(granted) = store
+        .redeem_magic_link(&req.token)
+        .await
+        .map_err(unavailable)?
+    else {
+        return Err(ApiError::InvalidToken(
+            "Invalid, expired, or already redeemed magic link".into(),
+        ));
+    };
+    if state.is_revoked(&granted).await.map_err(unavailable)? {
+        return Err(ApiError::TokenRevoked("Magic link has been revoked".into()));
+    }
+
+    // Fresh lifetime and ID; the user, access, client, and version are the
+    // link's
+    let claims = Claims::new(
+        granted.sub,
+        granted.email,
+        config.jwt.access_token_expiry_seconds,
+        state.clock.as_ref(),
+    )
+    .with_access(granted.roles, granted.scope)
+    .with_issuer(config.jwt.issuer.clone())
+    .with_audience(granted.aud)
+    .with_client_id(granted.client_id)
+    .with_dpop_key(proof.as_ref().map(|proof| proof.jkt.clone()))
+    .with_version(granted.ver)
+    .with_custom(granted.custom)
+    .map_err(|e| {
+        tracing::error!("Magic link claims refused: {e}");
+        ApiError::Internal("Failed to generate token".into())
+    })?;
+
+    issue_tokens(
+        &state,
+        &claims,
+        proof.as_ref(),
+        &device,
+        &client,
+        "magic-link",
+    )
+    .await
+}
+
+// ---
+
+/// The page at `url` with `token` appended as the `token` query parameter.
+pub(super) fn link_to(url: &str, token: &str) -> String {
+    // ---
+    let separator = if url.contains('?') { '&' } else { '?' };
+    format!("{url}{separator}token={token}")
+}
+
+/// `seconds` in words for an email, e.g. "15 minutes" or "1 hour".
+pub(super) fn describe_seconds(seconds: i64) -> String {
+    // ---
+    let (count, unit) = match seconds {
+        s if s % 3600 == 0 => (s / 3600, "hour"),
+        s if s % 60 == 0 => (s / 60, "minute"),
+        s => (s, "second"),
+    };
+    let plural = if count == 1 { "" } else { "s" };
+    format!("{count} {unit}{plural}")
+}
@@ -4,22 +4,68 @@
 //!
 //! # Endpoints
 //!
-//! - `POST /auth/token` - Generate JWT access and refresh tokens
-//! - `POST /auth/validate` - Validate JWT signature and expiration
-//! - `POST /auth/refresh` - Exchange refresh token for new access token
-//! - `POST /auth/revoke` - Revoke (blacklist) a JWT
-//! - `GET /protected` - Demo protected endpoint requiring valid JWT
+//! - `POST /v1/auth/token` - Generate JWT access and refresh tokens
+//! - `POST /v1/auth/validate` - Validate JWT signature and expiration
+//! - `POST /v1/auth/introspect` - RFC 7662 token introspection
+//! - `POST /v1/auth/refresh` - Exchange refresh token for new access token (token store)
+//! - `POST /v1/auth/revoke` - Revoke (blacklist) a JWT (token store)
+//! - `GET /v1/auth/sessions/{user_id}` - List a user's active sessions (token store)
+//! - `DELETE /v1/auth/sessions/{user_id}/{session_id}` - End a session (token store)
+//! - `POST /v1/auth/invalidate-user` - Revoke every token of a user (token store)
+//! - `POST /v1/auth/ticket` - Exchange an access token for a single-use WebSocket/SSE ticket (token store)
+//! - `POST /v1/auth/ticket/redeem` - Consume a ticket for its claims (token store)
+//! - `POST /v1/auth/magic-link` - Issue a single-use login link (token store)
+//! - `POST /v1/auth/magic-link/redeem` - Exchange a login link for tokens (token store)
+//! - `POST /v1/auth/verify-email/request` - Issue an email verification token (token store)
+//! - `POST /v1/auth/verify-email/confirm` - Consume an email verification token (token store)
+//! - `POST /v1/auth/password-reset/request` - Issue a password reset token (token store)
+//! - `POST /v1/auth/password-reset/confirm` - Consume a password reset token, ending every session (token store)
+//! - `/v1/auth/apikeys` - Manage the caller's API keys for machine clients (token store)
+//! - `GET /v1/protected` - Demo protected endpoint requiring valid JWT
+//! - `GET /v1/protected/admin` - Demo protected endpoint also requiring the `admin` scope
+//! - `/admin/issuer-keys` - Manage the token endpoint's issuer keys (token store, admin token)
+//! - `/admin/blacklist` - Inspect and edit the access token blacklist (token store, admin token)
+//! - `/admin/users/{user_id}/revoked-before` - Revoke every token issued to a user before a time (token store, admin token)
 
+mod apikeys;
+mod blacklist;
+mod cookie;
+mod dpop;
 mod generate;
+mod introspect;
+mod issuer_keys;
+mod magic_link;
+mod password_reset;
 mod protected;
 mod refresh;
 mod revoke;
+mod revoked_before;
+mod sessions;
+mod ticket;
 mod validate;
+mod verify_email;
 
 // ---
 
+pub use apikeys::api_key_routes;
+pub use blacklist::blacklist_routes;
+pub use cookie::{ACCESS_TOKEN_COOKIE, REFRESH_TOKEN_COOKIE};
 pub use generate::generate_token_handler;
-pub use protected::protected_routes;
+pub use introspect::introspect_token_handler;
+pub use issuer_keys::issuer_key_routes;
+pub use magic_link::{issue_magic_link_handler, redeem_magic_link_handler};
+pub use password_reset::{
+    confirm_password_reset_handler, request_password_reset_handler, PASSWORD_RESET_PURPOSE,
+};
+pub use protected::{protected_routes, require_scope, RequireScope, RequireScopeService};
 pub use refresh::refresh_token_handler;
 pub use revoke::revoke_token_handler;
+pub use revoked_before::revoked_before_routes;
+pub use sessions::{
+    delete_session_handler, invalidate_user_handler, list_sessions_handler, session_routes,
+};
+pub use ticket::{issue_ticket_handler, redeem_ticket_handler, ticket_routes};
 pub use validate::validate_token_handler;
+pub use verify_email::{
+    confirm_email_verification_handler, request_email_verification_handler, VERIFY_EMAIL_PURPOSE,
+};
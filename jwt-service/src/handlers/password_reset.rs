@@ -0,0 +1,286 @@
+// jwt-service/src/handlers/password_reset.rs
+
+//! Password reset endpoints
+//!
+//! Handles POST /v1/auth/password-reset/request - issues a short-lived,
+//! single-use token allowing a user's password to be reset - and
+//! POST /v1/auth/password-reset/confirm - consumes it and signs the user out
+//! everywhere. The token is a JWT with the `reset_password` purpose, which
+//! access-token validation refuses; the password itself is the caller's to
+//! store.
+
+use super::generate::authorize_issuer;
+use super::magic_link::{describe_seconds, link_to};
+use crate::{ApiError, AppState, AuditEvent, AuditEventKind, Claims, ClientInfo, TokenStore};
+use axum::{
+    extract::State,
+    http::{header, HeaderMap},
+    response::{IntoResponse, Json},
+};
+use serde::{Deserialize, Serialize};
+use tokn_events::{AuthEvent, AuthEventKind};
+use tokn_mail::Template;
+
+// ---
+
+/// `purpose` claim of password reset tokens.
+pub const PASSWORD_RESET_PURPOSE: &str = "reset_password";
+
+/// Security notification sent to the user once the password was reset.
+const RESET_SUMMARY: &str = "The password of your account was reset, and every device signed \
+                             in to it was signed out.";
+
+// ---
+
+/// Request of `POST /v1/auth/password-reset/request`.
+#[derive(Debug, Deserialize)]
+pub struct PasswordResetRequest {
+    // ---
+    /// The user whose password is to be reset
+    pub user_id: String,
+
+    /// The user's address, where the reset link is sent
+    pub email: String,
+}
+
+/// Response of `POST /v1/auth/password-reset/request`.
+#[derive(Debug, Serialize)]
+pub struct PasswordResetResponse {
+    // ---
+    /// The reset token, for the caller to deliver; omitted when it was
+    /// emailed (`JWT_PASSWORD_RESET_URL` set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    token: Option<String>,
+
+    /// Seconds until the token expires unused
+    expires_in: i64,
+}
+
+/// Request of `POST /v1/auth/password-reset/confirm`.
+#[derive(Debug, Deserialize)]
+pub struct ConfirmPasswordResetRequest {
+    // ---
+    /// The token from the reset link
+    pub token: String,
+}
+
+/// Response of `POST /v1/auth/password-reset/confirm`.
+#[derive(Debug, Serialize)]
+pub struct ConfirmPasswordResetResponse {
+    // ---
+    /// The user whose password may now be set
+    user_id: String,
+
+    /// The user's address, as the token was requested with
+    email: String,
+
+    /// The user's new token version; every older access token is revoked
+    token_version: u64,
+
+    /// Number of the user's sessions ended
+    sessions_ended: usize,
+
+    /// Number of the API keys acting as the user that were deleted
+    api_keys_deleted: usize,
+}
+
+// ---
+
+/// Issue a password reset token for a user.
+///
+/// Takes the same `X-Issuer-Key` as `POST /v1/auth/token` when
+/// `TOKEN_ISSUER_REQUIRE_KEY` is set. The token is bound to `user_id` and
+/// `email`, stamped with the user's token version, and lives
+/// `JWT_PASSWORD_RESET_TTL_SECONDS` (default 900). With
+/// `JWT_PASSWORD_RESET_URL` set, the link (that URL with `?token=` appended)
+/// is emailed to `email`; without it, the token is returned for the caller to
+/// deliver.
+///
+/// # Request
+///
+/// ```json
+/// {
+///   "user_id": "user_12345",
+///   "email": "john@example.com"
+/// }
+/// ```
+///
+/// # Response (200 OK)
+///
+/// ```json
+/// {
+///   "token": "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9...",
+///   "expires_in": 900
+/// }
+/// ```
+///
+/// # Errors
+///
+/// Returns 401 Unauthorized for a missing or unknown API key when one is
+/// required, 400 Bad Request without a `user_id` or `email`, 500 Internal
+/// Server Error if the token cannot be signed, and 503 Service Unavailable if
+/// the token store cannot be reached.
+pub async fn request_password_reset_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<PasswordResetRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    // ---
+    let config = state.config.get();
+    let issuer = authorize_issuer(&state, &config, &headers).await?;
+    if req.user_id.trim().is_empty() || req.email.trim().is_empty() {
+        return Err(ApiError::InvalidRequest(
+            "user_id and email are required".into(),
+        ));
+    }
+
+    // Stamp the version now, so a reset (or invalidation) kills the others
+    let version = state.token_version(&req.user_id).await.map_err(|e| {
+        tracing::error!("Password reset issuance failed: {:#}", e);
+        ApiError::StoreUnavailable("Failed to issue password reset".into())
+    })?;
+    let ttl = config.jwt.password_reset_ttl_seconds;
+    let claims = Claims::new(req.user_id, req.email, ttl, state.clock.as_ref())
+        .with_version(version)
+        .with_purpose(PASSWORD_RESET_PURPOSE);
+    let token = state.keys.get().sign(&claims).map_err(|e| {
+        tracing::error!("Password reset token generation failed: {}", e);
+        ApiError::Internal("Failed to generate password reset token".into())
+    })?;
+
+    tracing::info!(
+        user_id = %claims.sub,
+        issuer = issuer.as_deref().unwrap_or("-"),
+        emailed = config.jwt.password_reset_url.is_some(),
+        "Issued password reset token"
+    );
+    let token = match &config.jwt.password_reset_url {
+        Some(url) => {
+            state.mail.notify(
+                Template::PasswordReset,
+                &claims.email,
+                &[
+                    ("link", &link_to(url, &token)),
+                    ("expires_in", &describe_seconds(ttl)),
+                ],
+            );
+            None
+        }
+        None => Some(token),
+    };
+
+    let response = PasswordResetResponse {
+        token,
+        expires_in: ttl,
+    };
+    Ok(([(header::CACHE_CONTROL, "no-store")], Json(response)))
+}
+
+/// Consume a password reset token and sign its user out everywhere.
+///
+/// Called by the page the reset link points to, before it stores the new
+/// password for `user_id`. The token is marked used until it expires, and,
+/// as with `POST /v1/auth/invalidate-user`, the user's token version is bumped
+/// and every session and API key ended, which also kills the user's other
+/// reset tokens.
+/// The user is told by email.
+///
+/// # Request
+///
+/// ```json
+/// {
+///   "token": "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9..."
+/// }
+/// ```
+///
+/// # Response (200 OK)
+///
+/// ```json
+/// {
+///   "user_id": "user_12345",
+///   "email": "john@example.com",
+///   "token_version": 3,
+///   "sessions_ended": 2,
+///   "api_keys_deleted": 0
+/// }
+/// ```
+///
+/// # Errors
+///
+/// Returns 401 Unauthorized if the token is invalid, expired, already used,
+/// revoked, or not a password reset token, and 503 Service Unavailable if the
+/// token store cannot be reached.
+pub async fn confirm_password_reset_handler(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    Json(req): Json<ConfirmPasswordResetRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    // ---
+    // Stateless services do not route this endpoint
+    let Some(store) = &state.store else {
+        return Err(ApiError::NotFound);
+    };
+    let unavailable = |e: anyhow::Error| {
+        tracing::error!("Password reset failed: {:#}", e);
+        ApiError::StoreUnavailable("Failed to reset password".into())
+    };
+
+    let claims = state
+        .keys
+        .get()
+        .verify_purpose(&req.token, PASSWORD_RESET_PURPOSE, state.clock.as_ref())
+        .map_err(|e| ApiError::InvalidToken(e.to_string()))?;
+    if state.is_revoked(&claims).await.map_err(unavailable)? {
+        return Err(ApiError::TokenRevoked(
+            "Password reset token has been revoked".into(),
+        ));
+    }
+
+    let ttl = (claims.exp as i64 - state.clock.timestamp()).max(1);
+    if !store
+        .consume_token(&claims.jti, ttl)
+        .await
+        .map_err(unavailable)?
+    {
+        return Err(ApiError::InvalidToken(
+            "Password reset token has already been used".into(),
+        ));
+    }
+    let (token_version, sessions_ended, api_keys_deleted) = store
+        .invalidate_user(&claims.sub)
+        .await
+        .map_err(unavailable)?;
+
+    tracing::info!(
+        event = "token_revoked",
+        user_id = %claims.sub,
+        token_version,
+        sessions_ended,
+        api_keys_deleted,
+        "Password reset"
+    );
+    state
+        .events
+        .emit(AuthEvent::new(AuthEventKind::TokenRevoked).subject(&claims.sub));
+    state.audit.record(
+        AuditEvent::new(AuditEventKind::TokenRevoked, state.clock.as_ref())
+            .user_id(&claims.sub)
+            .client(&client)
+            .reason("password reset"),
+    );
+    let occurred_at = state.clock.now().to_rfc3339();
+    state.mail.notify(
+        Template::SecurityNotification,
+        &claims.email,
+        &[("summary", RESET_SUMMARY), ("occurred_at", &occurred_at)],
+    );
+
+    let response = ConfirmPasswordResetResponse {
+        user_id: claims.sub,
+        email: claims.email,
+        token_version,
+        sessions_ended,
+        api_keys_deleted,
+    };
+    Ok(([(header::CACHE_CONTROL, "no-store")], Json(response)))
+}
@@ -2,19 +2,29 @@
 
 //! Protected route demonstrating JWT authentication middleware
 //!
-//! This module showcases how to protect API endpoints using JWT tokens.
-//! Routes require valid, unexpired, non-revoked tokens with proper signatures.
+//! This module showcases how to protect API endpoints using JWT tokens, with
+//! the [`tokn_auth`] layer configured for this service.
+//! Routes require valid, unexpired, non-revoked tokens with proper signatures,
+//! and may also require scopes (see [`require_scope`]). Handlers take the
+//! validated claims as an [`AuthenticatedUser`].
 
-use crate::{is_token_revoked, token::validate_token, AppState, Claims};
+use super::cookie::ACCESS_TOKEN_COOKIE;
+use crate::{ApiError, AppState, AuditEvent, AuditEventKind, Claims, ClientInfo};
 use axum::{
-    extract::{Request, State},
-    http::StatusCode,
-    middleware::{self, Next},
+    extract::{FromRef, Request},
+    http::{header, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     routing::get,
     Json, Router,
 };
 use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokn_auth::{AuthError, AuthenticatedUser, JwtAuth, JwtAuthLayer, Verifier};
+use tower::{Layer, Service};
 
 // ---
 
@@ -27,24 +37,43 @@ pub struct UserProfile {
     pub email: String,
     pub token_issued_at: i64,
     pub token_expires_at: i64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roles: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
 }
 
 // ---
 
-/// Extract and validate JWT from Authorization header
+/// JWT authentication for the protected and session routes.
 ///
-/// This middleware intercepts all requests to protected routes and:
+/// Applied by [`JwtAuthLayer`] (or run by [`AuthenticatedUser`] on other
+/// routes), it:
 /// 1. Extracts the Bearer token from Authorization header
-/// 2. Validates the token signature
+/// 2. Validates the token signature with the current signing keys
 /// 3. Checks token expiration
 /// 4. Checks token revocation status
-/// 5. Injects validated claims into request extensions
+/// 5. For a DPoP-bound token, checks the request's proof of possession
+/// 6. Injects validated claims into request extensions
+///
+/// A machine client may instead send an API key from `POST /v1/auth/apikeys`
+/// in `X-Api-Key` (and no Authorization header); the request is handled with
+/// the key's user and scope (see [`ApiKey::claims`](crate::ApiKey::claims)).
+/// In cookie mode (`jwt.cookie_mode`), a browser may send its access token
+/// in the `access_token` cookie instead of an Authorization header.
 ///
 /// # Security
-/// - Tokens must use valid HS256 signature
+/// - Tokens must carry a valid signature in the configured algorithm
 /// - Tokens must not be expired (checked against server time)
-/// - Revoked tokens are rejected via Redis blacklist check
+/// - Revoked tokens are rejected via the token store's blacklist and the
+///   user's token version (skipped when stateless)
 /// - Malformed Authorization headers are rejected
+/// - DPoP-bound tokens need `Authorization: DPoP` and a fresh proof from
+///   their key; proofs are single-use (recorded in Redis, or in memory when
+///   stateless)
+/// - API keys are looked up by SHA-256 in the token store, and are not
+///   accepted when stateless; their claims are checked like a token's
+/// - Invalid and revoked tokens are recorded in the audit log
 ///
 /// # Header Format
 ///
@@ -54,62 +83,199 @@ pub struct UserProfile {
 ///
 /// # Errors
 ///
-/// Returns `401 Unauthorized` if:
+/// Responds with problem details (see [`Problem`](tokn_core::Problem)). Returns
+/// `401 Unauthorized` if:
 /// - Authorization header is missing
 /// - Header doesn't start with "Bearer "
 /// - Token signature is invalid
 /// - Token has expired
 /// - Token has been revoked
+/// - The DPoP proof of a bound token is missing, invalid, or replayed
+/// - The API key is unknown or has been deleted
 ///
 /// Returns `500 Internal Server Error` if:
-/// - Redis connection fails during revocation check
-async fn jwt_auth_middleware(
-    State(state): State<AppState>,
-    mut request: Request,
-    next: Next,
-) -> Result<Response, StatusCode> {
+/// - Redis connection fails during revocation, DPoP replay, or API key check
+impl FromRef<AppState> for JwtAuth {
     // ---
-    // Extract Authorization header
-    let auth_header = request
-        .headers()
-        .get("Authorization")
-        .and_then(|h| h.to_str().ok())
-        .ok_or(StatusCode::UNAUTHORIZED)?;
+    fn from_ref(state: &AppState) -> Self {
+        // ---
+        let revocation = state.clone();
+        let (audit, clock) = (state.audit.clone(), state.clock.clone());
+
+        let auth = JwtAuth::new(Verifier::from(state.keys.clone()))
+            .clock(state.clock.clone())
+            .check_claims_revocation(move |claims| {
+                let state = revocation.clone();
+                async move { state.is_revoked(&claims).await }
+            });
+
+        // Without Redis, the in-memory record of each JwtAuth is kept
+        let auth = if state.records_dpop_proofs() {
+            let replay = state.clone();
+            auth.check_dpop_replay(move |proof| {
+                let state = replay.clone();
+                async move { state.dpop_proof_replayed(&proof).await }
+            })
+        } else {
+            auth
+        };
 
+        // Browsers in cookie mode send the access token as a cookie
+        let auth = if state.config.get().jwt.cookie_mode {
+            auth.cookie(ACCESS_TOKEN_COOKIE)
+        } else {
+            auth
+        };
+
+        let auth = if state.accepts_api_keys() {
+            let api_keys = state.clone();
+            auth.check_api_key(move |key| {
+                let state = api_keys.clone();
+                async move { state.api_key_claims(&key).await }
+            })
+        } else {
+            auth
+        };
+
+        auth.on_refused(move |error, headers, extensions| {
+            let refused = AuditEvent::new(AuditEventKind::ValidationFailed, clock.as_ref());
+            let event = match error {
+                AuthError::Token(e) => refused.reason(e.to_string()),
+                AuthError::Dpop(e) => refused.reason(e.to_string()),
+                AuthError::ApiKey => refused.reason("unknown API key"),
+                AuthError::Revoked(claims) => refused
+                    .reason("token revoked")
+                    .user_id(&claims.sub)
+                    .jti(&claims.jti),
+                // No token was presented, or none was judged
+                _ => return,
+            };
+            let client = ClientInfo::from_request(headers, extensions);
+            audit.record(event.client(&client));
+        })
+    }
+}
+
+/// The [`JwtAuthLayer`] guarding routes of `state`.
+pub(crate) fn jwt_auth_layer(state: &AppState) -> JwtAuthLayer {
     // ---
-    // Verify Bearer token format
-    let token = auth_header
-        .strip_prefix("Bearer ")
-        .ok_or(StatusCode::UNAUTHORIZED)?;
+    JwtAuthLayer::new(JwtAuth::from_ref(state))
+}
+
+// ---
+
+/// Require the token to carry `scope` (one entry of its space-delimited
+/// `scope` claim) on routes behind the JWT middleware.
+///
+/// Add it to a method router so it runs after the JWT middleware has
+/// validated the token, as [`protected_routes`] does for `/protected/admin`:
+///
+/// ```no_run
+/// use axum::{routing::get, Router};
+/// use jwt_service::{require_scope, AppState};
+///
+/// async fn list_orders() -> &'static str {
+///     "[]"
+/// }
+///
+/// let orders: Router<AppState> = Router::new().route(
+///     "/orders",
+///     get(list_orders).route_layer(require_scope("orders:read")),
+/// );
+/// ```
+///
+/// A token without the scope gets `403 Forbidden` with
+/// `WWW-Authenticate: Bearer error="insufficient_scope"` (RFC 6750 §3.1); a
+/// request that reaches the layer without validated claims gets
+/// `401 Unauthorized`.
+pub fn require_scope(scope: &str) -> RequireScope {
+    // ---
+    RequireScope {
+        scope: Arc::from(scope),
+    }
+}
 
+/// Tower layer built by [`require_scope`].
+#[derive(Debug, Clone)]
+pub struct RequireScope {
     // ---
-    // Validate token and extract claims
-    let claims = validate_token(token, &state.config.jwt.secret).map_err(|e| {
-        tracing::warn!("Token validation failed: {:?}", e);
-        StatusCode::UNAUTHORIZED
-    })?;
+    scope: Arc<str>,
+}
 
+impl<S> Layer<S> for RequireScope {
     // ---
-    // Check if token is revoked
-    let is_revoked = is_token_revoked(&mut state.redis.clone(), &claims.jti)
-        .await
-        .map_err(|e| {
-            tracing::error!("Failed to check token revocation status: {}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
-
-    if is_revoked {
-        tracing::warn!("Revoked token attempted access (jti={})", claims.jti);
-        return Err(StatusCode::UNAUTHORIZED);
+    type Service = RequireScopeService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        // ---
+        RequireScopeService {
+            inner,
+            scope: self.scope.clone(),
+        }
     }
+}
+
+// ---
 
+/// Service produced by [`RequireScope`].
+#[derive(Debug, Clone)]
+pub struct RequireScopeService<S> {
     // ---
-    // Token is valid - inject claims into request extensions
-    request.extensions_mut().insert(claims);
+    inner: S,
+    scope: Arc<str>,
+}
 
+impl<S> Service<Request> for RequireScopeService<S>
+where
+    S: Service<Request, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
     // ---
-    // Continue to the actual handler
-    Ok(next.run(request).await)
+    type Response = Response;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // ---
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        // ---
+        // Keep the instance that was polled ready; leave a fresh clone behind
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        let Some(claims) = req.extensions().get::<Claims>() else {
+            tracing::warn!("require_scope reached without validated claims");
+            let error = ApiError::Unauthenticated("Token required".into());
+            return Box::pin(async move { Ok(error.into_response()) });
+        };
+
+        if claims.has_scope(&self.scope) {
+            return Box::pin(inner.call(req));
+        }
+
+        tracing::debug!(
+            user_id = %claims.sub,
+            required = %self.scope,
+            "Token lacks required scope"
+        );
+        let response = insufficient_scope(&self.scope);
+        Box::pin(async move { Ok(response) })
+    }
+}
+
+/// `403 Forbidden` for a token lacking `scope`.
+fn insufficient_scope(scope: &str) -> Response {
+    // ---
+    let problem = ApiError::InsufficientScope(scope.to_string()).into_problem();
+
+    let challenge = format!(r#"Bearer error="insufficient_scope", scope="{scope}""#);
+    match HeaderValue::from_str(&challenge) {
+        Ok(challenge) => ([(header::WWW_AUTHENTICATE, challenge)], problem).into_response(),
+        Err(_) => problem.into_response(),
+    }
 }
 
 // ---
@@ -118,13 +284,13 @@ async fn jwt_auth_middleware(
 ///
 /// Demonstrates how to access extracted claims from middleware.
 /// The JWT middleware runs first, validates the token, and injects
-/// the `Claims` into request extensions.
+/// the `Claims` that [`AuthenticatedUser`] hands to the handler.
 ///
 /// # Security
 ///
 /// This endpoint is only reachable if:
 /// - Valid Authorization header is present
-/// - Token signature is valid (HS256)
+/// - Token signature is valid (configured algorithm)
 /// - Token has not expired
 /// - Token has not been revoked
 ///
@@ -136,12 +302,14 @@ async fn jwt_auth_middleware(
 ///   "user_id": "user_12345",
 ///   "email": "john@example.com",
 ///   "token_issued_at": 1703000334,
-///   "token_expires_at": 1703001234
+///   "token_expires_at": 1703001234,
+///   "roles": ["admin"],
+///   "scope": "orders:read"
 /// }
 /// ```
-async fn protected_handler(
-    axum::extract::Extension(claims): axum::extract::Extension<Claims>,
-) -> impl IntoResponse {
+///
+/// `roles` and `scope` are omitted when the token has none.
+async fn protected_handler(AuthenticatedUser(claims): AuthenticatedUser) -> impl IntoResponse {
     // ---
     // Claims are already validated and extracted by middleware
     let profile = UserProfile {
@@ -150,6 +318,8 @@ async fn protected_handler(
         email: claims.email.clone(),
         token_issued_at: claims.iat as i64,
         token_expires_at: claims.exp as i64,
+        roles: claims.roles.clone(),
+        scope: claims.scope.clone(),
     };
 
     (StatusCode::OK, Json(profile))
@@ -166,18 +336,25 @@ async fn protected_handler(
 ///
 /// ```bash
 /// # Get a token first
-/// TOKEN=$(curl -X POST http://localhost:8083/auth/token \
+/// TOKEN=$(curl -X POST http://localhost:8083/v1/auth/token \
 ///   -H "Content-Type: application/json" \
 ///   -d '{"user_id": "test_user", "email": "test@example.com"}' \
 ///   | jq -r '.access_token')
 ///
 /// # Access protected endpoint
-/// curl http://localhost:8083/protected \
+/// curl http://localhost:8083/v1/protected \
 ///   -H "Authorization: Bearer $TOKEN"
 /// ```
+///
+/// `/protected/admin` additionally requires the `admin` scope (request it
+/// with `"scope": "admin"` when generating the token), answering 403 without.
 pub fn protected_routes(state: AppState) -> Router<AppState> {
     // ---
     Router::new()
         .route("/protected", get(protected_handler))
-        .layer(middleware::from_fn_with_state(state, jwt_auth_middleware))
+        .route(
+            "/protected/admin",
+            get(protected_handler).route_layer(require_scope("admin")),
+        )
+        .route_layer(jwt_auth_layer(&state))
 }
@@ -2,17 +2,30 @@
 
 //! Refresh token endpoint
 //!
-//! Handles POST /auth/refresh - exchanges refresh tokens for new access tokens
+//! Handles POST /v1/auth/refresh - exchanges refresh tokens for new access tokens
 
+use super::cookie::{refresh_cookie_ttl, refresh_token_cookie, with_token_cookies};
+use super::dpop::{token_request_proof, token_type};
 use crate::{
-    generate_refresh_token, token::generate_token, validate_refresh_token, AppState, Claims,
+    ApiError, AppState, AuditEvent, AuditEventKind, ClientDevice, ClientInfo, RefreshTokenData,
+    Store, TokenStore,
 };
 use axum::{
-    extract::State,
-    http::StatusCode,
+    extract::{OriginalUri, State},
+    http::{header, HeaderMap, StatusCode},
     response::{IntoResponse, Json},
 };
 use serde::{Deserialize, Serialize};
+use tokn_events::{AuthEvent, AuthEventKind};
+use tokn_mail::Template;
+
+// ---
+
+/// What the security notification tells a user whose refresh token was
+/// replayed.
+const REUSE_SUMMARY: &str = "A sign-in session was refreshed with a token that had already \
+                             been used. This can mean the token was copied from one of your \
+                             devices.";
 
 // ---
 
@@ -22,7 +35,9 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Deserialize)]
 pub struct RefreshRequest {
     // ---
-    /// The refresh token to exchange
+    /// The refresh token to exchange; in cookie mode it may be left out to
+    /// use the `refresh_token` cookie
+    #[serde(default)]
     pub refresh_token: String,
 }
 
@@ -34,17 +49,20 @@ pub struct RefreshRequest {
 #[derive(Debug, Serialize)]
 pub struct RefreshResponse {
     // ---
-    /// New JWT access token
-    access_token: String,
+    /// New JWT access token (omitted in cookie mode)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    access_token: Option<String>,
 
-    /// Token type (always "Bearer" for JWTs)
+    /// Token type: "DPoP" for a token bound to the request's DPoP key, else
+    /// "Bearer"
     token_type: String,
 
     /// Access token expiry in seconds
     expires_in: i64,
 
-    /// New refresh token (rotation)
-    refresh_token: String,
+    /// New refresh token (rotation; omitted in cookie mode)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    refresh_token: Option<String>,
 }
 
 // ---
@@ -73,11 +91,22 @@ pub struct RefreshResponse {
 /// }
 /// ```
 ///
+/// # Cookie Mode
+///
+/// With `jwt.cookie_mode`, a request without `refresh_token` in its body
+/// (e.g. `{}`) exchanges the `refresh_token` cookie, and the new tokens are
+/// set as cookies rather than returned, as at `POST /v1/auth/token`.
+///
 /// # Response (401 Unauthorized) - Invalid/Expired Token
 ///
 /// ```json
 /// {
-///   "error": "Invalid or expired refresh token"
+///   "type": "https://tokn.dev/problems/invalid-token",
+///   "title": "Invalid token",
+///   "status": 401,
+///   "detail": "Invalid or expired refresh token",
+///   "instance": "/v1/auth/refresh",
+///   "request_id": "0b9c6f5e-3c1a-4d9e-9a43-1f0d3c8e7a21"
 /// }
 /// ```
 ///
@@ -85,7 +114,7 @@ pub struct RefreshResponse {
 ///
 /// This endpoint implements **refresh token rotation** to prevent token replay attacks:
 ///
-/// 1. Validates the refresh token exists in Redis
+/// 1. Validates the refresh token exists in the token store
 /// 2. Retrieves the user data
 /// 3. **Deletes the old refresh token** (one-time use)
 /// 4. Generates new access token
@@ -96,98 +125,373 @@ pub struct RefreshResponse {
 /// - The next legitimate refresh attempt will fail
 /// - User is alerted to revoke all sessions
 /// - Prevents long-lived stolen tokens from being reused
+/// - A replayed token is logged, published as a `refresh_reuse` event, and
+///   reported to the user by a security notification email
+///
+/// # Lifetime
+///
+/// With `jwt.refresh_token_sliding` (the default) the new refresh token gets
+/// the full `jwt.refresh_token_expiry_seconds`; otherwise it keeps the old
+/// token's expiry. `jwt.max_session_seconds` caps the whole chain, counted
+/// from the first token's issue. The new token keeps the session ID listed by
+/// `GET /v1/auth/sessions/{user_id}`.
+///
+/// # Grace Window
+///
+/// With `jwt.refresh_grace_seconds` set, a refresh token rotated less than
+/// that long ago is not treated as reused: it gets a fresh access token and
+/// the refresh token it was rotated into, so a client refreshing twice in
+/// parallel keeps its session. Nothing is rotated, and the DPoP, device, and
+/// revocation checks still apply. A request racing the rotation itself, before
+/// it has completed, is still refused.
+///
+/// # DPoP
+///
+/// With a `DPoP` proof header (RFC 9449) the new access token is bound to
+/// the proof's key, as at `POST /v1/auth/token`. A refresh token issued with
+/// a DPoP-bound token requires a proof from the same key: without one it is
+/// refused (and consumed, so a thief cannot retry it).
+///
+/// # Device Binding
+///
+/// The session records the `User-Agent`, IP address, and `X-Device-Id` of
+/// each refresh. With `jwt.refresh_bind_device`, a refresh from a device
+/// other than the session's (by `X-Device-Id` if it was started with one,
+/// else by `User-Agent`) is refused and consumes the token, as for DPoP.
 ///
 /// # Errors
 ///
 /// Returns 401 Unauthorized if:
-/// - Refresh token doesn't exist in Redis (expired or already used)
+/// - Refresh token doesn't exist in the token store (expired or already used)
 /// - Refresh token format is invalid
+/// - The session has reached `jwt.max_session_seconds`
+/// - The refresh token is DPoP-bound and the request has no proof from its key
+/// - Device binding is on and the request comes from another device
+/// - The refresh token was issued before its user's revocation cutoff
+///
+/// Returns 400 Bad Request if the DPoP proof is invalid or replayed, or
+/// `X-Device-Id` is empty or over 128 bytes.
 ///
 /// Returns 500 Internal Server Error if:
 /// - Token generation fails
-/// - Redis operations fail
+/// - Token store operations fail
 ///
 /// # Example Flow
 ///
 /// ```text
 /// 1. User logs in → Gets access token (15 min) + refresh token (7 days)
 /// 2. After 15 min → Access token expires
-/// 3. Client sends refresh token to /auth/refresh
+/// 3. Client sends refresh token to /v1/auth/refresh
 /// 4. Old refresh token deleted, new tokens issued
 /// 5. Repeat until refresh token expires (7 days)
 /// 6. User must re-authenticate
 /// ```
 pub async fn refresh_token_handler(
     State(state): State<AppState>,
+    headers: HeaderMap,
+    OriginalUri(uri): OriginalUri,
+    client: ClientInfo,
     Json(req): Json<RefreshRequest>,
 ) -> impl IntoResponse {
     // ---
-    // Validate and consume refresh token (deletes it from Redis)
-    let user_data = match validate_refresh_token(&mut state.redis.clone(), &req.refresh_token).await
-    {
-        Ok(data) => data,
+    // Stateless services do not route this endpoint
+    let Some(store) = &state.store else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    // Snapshot so a concurrent reload cannot change settings mid-request
+    let config = state.config.get();
+    let refused = |reason: &str| {
+        AuditEvent::new(AuditEventKind::ValidationFailed, state.clock.as_ref())
+            .client(&client)
+            .reason(reason)
+    };
+
+    // Checked before the token is consumed: a bad proof costs no session
+    let proof = match token_request_proof(&state, &headers, uri.path()).await {
+        Ok(proof) => proof,
+        Err(problem) => return problem.into_response(),
+    };
+    let device = match ClientDevice::from_request(&headers, &client) {
+        Ok(device) => device,
+        Err(problem) => return problem.into_response(),
+    };
+
+    // In cookie mode the browser sends the token as a cookie
+    let refresh_token = match refresh_token_cookie(&config.jwt, &headers) {
+        Some(cookie) if req.refresh_token.is_empty() => cookie,
+        _ => req.refresh_token.as_str(),
+    };
+
+    // Validate and consume refresh token (deletes it from the store)
+    let (user_data, successor) = match store.validate_refresh_token(refresh_token).await {
+        Ok(data) => (data, None),
         Err(e) => {
             tracing::debug!("Refresh token validation failed: {}", e);
-            return (
-                StatusCode::UNAUTHORIZED,
-                Json(serde_json::json!({
-                    "error": "Invalid or expired refresh token"
-                })),
+            // A token a parallel request just rotated stands for its successor
+            let grace_seconds = config.jwt.refresh_grace_seconds;
+            let Some((successor, data)) =
+                grace_successor(store, grace_seconds, refresh_token).await
+            else {
+                // A rotated token presented again was most likely stolen
+                match store.refresh_token_reused(refresh_token).await {
+                    Ok(Some(owner)) => {
+                        state
+                            .audit
+                            .record(refused("refresh token reused").user_id(&owner.user_id));
+                        tracing::warn!(
+                            event = "refresh_reuse",
+                            user_id = %owner.user_id,
+                            "Rotated refresh token reused"
+                        );
+                        if !owner.email.is_empty() {
+                            let occurred_at = state.clock.now().to_rfc3339();
+                            state.mail.notify(
+                                Template::SecurityNotification,
+                                &owner.email,
+                                &[("summary", REUSE_SUMMARY), ("occurred_at", &occurred_at)],
+                            );
+                        }
+                        state.events.emit(
+                            AuthEvent::new(AuthEventKind::RefreshReuse).subject(owner.user_id),
+                        );
+                    }
+                    Ok(None) => state.audit.record(refused("invalid refresh token")),
+                    Err(e) => {
+                        tracing::warn!("Refresh token reuse check failed: {e:#}");
+                        state.audit.record(refused("invalid refresh token"));
+                    }
+                }
+                return ApiError::InvalidToken("Invalid or expired refresh token".into())
+                    .into_response();
+            };
+            (data, Some(successor))
+        }
+    };
+
+    // A DPoP-bound session only continues with proofs from its key
+    if let Some(jkt) = &user_data.dpop_jkt {
+        if proof.as_ref().map(|proof| &proof.jkt) != Some(jkt) {
+            tracing::warn!(
+                user_id = %user_data.user_id,
+                "Refresh refused: DPoP-bound token without a proof from its key"
+            );
+            state
+                .audit
+                .record(refused("DPoP key mismatch").user_id(&user_data.user_id));
+            return ApiError::InvalidToken(
+                "Refresh token requires a DPoP proof from the key it is bound to".into(),
             )
+            .into_response();
+        }
+    }
+
+    // A device-bound session only continues on its own device
+    if config.jwt.refresh_bind_device && !user_data.same_device(&device) {
+        tracing::warn!(
+            user_id = %user_data.user_id,
+            "Refresh refused: token presented from another device"
+        );
+        state
+            .audit
+            .record(refused("device mismatch").user_id(&user_data.user_id));
+        return ApiError::InvalidToken("Refresh token was issued to another device".into())
+            .into_response();
+    }
+
+    // Tokens issued before the user's revocation cutoff are dead
+    let cutoff = match store.revoked_before(&user_data.user_id).await {
+        Ok(cutoff) => cutoff,
+        Err(e) => {
+            tracing::error!("Revocation cutoff lookup failed: {:#}", e);
+            return ApiError::Internal("Failed to generate access token".into()).into_response();
+        }
+    };
+    let issued_at = user_data
+        .issued_at
+        .or(user_data.session_started_at)
+        .unwrap_or(0);
+    if cutoff.is_some_and(|cutoff| issued_at < cutoff) {
+        tracing::warn!(
+            user_id = %user_data.user_id,
+            "Refresh refused: token issued before the user's revocation cutoff"
+        );
+        state
+            .audit
+            .record(refused("refresh token revoked").user_id(&user_data.user_id));
+        return ApiError::TokenRevoked("Refresh token has been revoked".into()).into_response();
+    }
+
+    // The next token's lifetime; none left once the session reaches its cap.
+    // A successor handed out again keeps its own
+    let now = state.clock.timestamp();
+    let (next_data, next_ttl) = if successor.is_some() {
+        (user_data.clone(), 0)
+    } else {
+        let Some((mut next_data, next_ttl)) = user_data.renew(&config.jwt, now) else {
+            tracing::info!(
+                event = "session_expired",
+                user_id = %user_data.user_id,
+                "Refresh refused: maximum session lifetime reached"
+            );
+            state
+                .audit
+                .record(refused("session expired").user_id(&user_data.user_id));
+            return ApiError::InvalidToken("Session has expired; sign in again".into())
                 .into_response();
+        };
+
+        // The session continues on whichever client refreshed it
+        next_data.record_device(&device);
+        next_data.rotation_count = user_data.rotation_count.saturating_add(1);
+        (next_data, next_ttl)
+    };
+
+    // Stamp the user's current token version
+    let version = match store.token_version(&user_data.user_id).await {
+        Ok(version) => version,
+        Err(e) => {
+            tracing::error!("Token version lookup failed: {:#}", e);
+            return ApiError::Internal("Failed to generate access token".into()).into_response();
         }
     };
 
-    // Generate new access token
-    let claims = Claims::new(
-        user_data.user_id.clone(),
-        user_data.email.clone(),
-        state.config.jwt.access_token_expiry_seconds,
-    );
+    // Generate new access token, with the roles, scope, audience, client,
+    // and custom claims checked at issue
+    let claims = user_data
+        .claims(config.jwt.access_token_expiry_seconds, state.clock.as_ref())
+        .with_issuer(config.jwt.issuer.clone())
+        .with_dpop_key(proof.as_ref().map(|proof| proof.jkt.clone()))
+        .with_version(Some(version));
 
-    let access_token = match generate_token(&claims, &state.config.jwt.secret) {
+    let access_token = match state.keys.get().sign(&claims) {
         Ok(token) => token,
         Err(e) => {
             tracing::error!("Access token generation failed: {}", e);
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({
-                    "error": "Failed to generate access token"
-                })),
-            )
-                .into_response();
+            return ApiError::Internal("Failed to generate access token".into()).into_response();
         }
     };
 
-    // Generate new refresh token (rotation)
-    let new_refresh_token = match generate_refresh_token(
-        &mut state.redis.clone(),
-        &user_data.user_id,
-        &user_data.email,
-        state.config.jwt.refresh_token_expiry_seconds,
-    )
-    .await
-    {
-        Ok(token) => token,
-        Err(e) => {
-            tracing::error!("Refresh token generation failed: {}", e);
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({
-                    "error": "Failed to generate refresh token"
-                })),
-            )
-                .into_response();
+    // Generate new refresh token (rotation), unless handing out the
+    // successor again
+    let new_refresh_token = match successor {
+        Some(successor) => {
+            tracing::info!(
+                event = "refresh_grace",
+                user_id = %user_data.user_id,
+                "Rotated refresh token presented within the grace window"
+            );
+            successor
         }
+        None => match store.issue_refresh_token(&next_data, next_ttl).await {
+            Ok(token) => {
+                let grace_seconds = config.jwt.refresh_grace_seconds;
+                remember_successor(store, grace_seconds, refresh_token, &token).await;
+                token
+            }
+            Err(e) => {
+                tracing::error!("Refresh token generation failed: {}", e);
+                return ApiError::Internal("Failed to generate refresh token".into())
+                    .into_response();
+            }
+        },
     };
 
-    // Build response
+    tracing::info!(
+        event = "token_issued",
+        user_id = %claims.sub,
+        jti = %claims.jti,
+        grant_type = "refresh_token",
+        session_id = next_data.session_id.as_deref().unwrap_or("-"),
+        rotation_count = next_data.rotation_count,
+        session_age_seconds = now - next_data.session_started_at.unwrap_or(now),
+        "Issued access token"
+    );
+    state.events.emit(
+        AuthEvent::new(AuthEventKind::TokenIssued)
+            .subject(&claims.sub)
+            .jti(&claims.jti)
+            .grant_type("refresh_token"),
+    );
+    state.audit.record(
+        AuditEvent::new(AuditEventKind::TokenRefreshed, state.clock.as_ref())
+            .user_id(&claims.sub)
+            .jti(&claims.jti)
+            .client(&client),
+    );
+
+    // Build response; in cookie mode the tokens go in cookies instead
+    let cookie_mode = config.jwt.cookie_mode;
+    let expires_in = config.jwt.access_token_expiry_seconds;
     let response = RefreshResponse {
-        access_token,
-        token_type: "Bearer".to_string(),
-        expires_in: state.config.jwt.access_token_expiry_seconds,
-        refresh_token: new_refresh_token,
+        access_token: (!cookie_mode).then(|| access_token.clone()),
+        token_type: token_type(proof.as_ref()),
+        expires_in,
+        refresh_token: (!cookie_mode).then(|| new_refresh_token.clone()),
+    };
+
+    // Token responses must not be cached (or compressed, see tokn_server::compression_layer)
+    let response = (
+        StatusCode::OK,
+        [(header::CACHE_CONTROL, "no-store")],
+        Json(response),
+    )
+        .into_response();
+    if !cookie_mode {
+        return response;
+    }
+    // A successor handed out again has no new TTL; its cookie gets the
+    // configured one
+    let refresh_ttl = match next_ttl {
+        0 => refresh_cookie_ttl(&config.jwt),
+        ttl => ttl,
     };
+    with_token_cookies(
+        response,
+        &access_token,
+        expires_in,
+        Some((&new_refresh_token, refresh_ttl)),
+    )
+}
+
+// ---
+
+/// The successor `refresh_token` was rotated into, if that was less than
+/// `grace_seconds` ago; a failed lookup counts as none.
+async fn grace_successor(
+    store: &Store,
+    grace_seconds: u64,
+    refresh_token: &str,
+) -> Option<(String, RefreshTokenData)> {
+    // ---
+    if grace_seconds == 0 {
+        return None;
+    }
+    match store.refresh_successor(refresh_token).await {
+        Ok(successor) => successor,
+        Err(e) => {
+            tracing::warn!("Refresh token successor lookup failed: {e:#}");
+            None
+        }
+    }
+}
 
-    (StatusCode::OK, Json(response)).into_response()
+/// Remember that `refresh_token` was rotated into `successor`, for
+/// `grace_seconds`. A failure only costs the grace window.
+async fn remember_successor(
+    store: &Store,
+    grace_seconds: u64,
+    refresh_token: &str,
+    successor: &str,
+) {
+    // ---
+    if grace_seconds == 0 {
+        return;
+    }
+    if let Err(e) = store
+        .store_refresh_successor(refresh_token, successor, grace_seconds as i64)
+        .await
+    {
+        tracing::warn!("Failed to remember refresh token successor: {e:#}");
+    }
 }
@@ -2,16 +2,19 @@
 
 //! Token revocation endpoint
 //!
-//! Handles POST /auth/revoke - revokes (blacklists) JWT tokens
+//! Handles POST /v1/auth/revoke - revokes (blacklists) JWT tokens, or with a
+//! form-encoded body, revokes access or refresh tokens as RFC 7009 describes
 
-use crate::{revoke_token, token::validate_token, AppState};
+use crate::{ApiError, AppState, AuditEvent, AuditEventKind, ClientInfo, Store, TokenStore};
 use axum::{
-    extract::State,
-    http::StatusCode,
-    response::{IntoResponse, Json},
+    extract::{FromRequest, Request, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+    Form,
 };
-use chrono::Utc;
 use serde::{Deserialize, Serialize};
+use tokn_core::Claims;
+use tokn_events::{AuthEvent, AuthEventKind};
 
 // ---
 
@@ -27,6 +30,21 @@ pub struct RevokeRequest {
 
 // ---
 
+/// RFC 7009 revocation request (§2.1), form-encoded.
+#[derive(Debug, Deserialize)]
+pub struct RevocationRequest {
+    // ---
+    /// The access or refresh token to revoke
+    pub token: String,
+
+    /// `access_token` or `refresh_token`: which kind to try first. Other
+    /// values are ignored, as the token is looked up as both kinds anyway.
+    #[serde(default)]
+    pub token_type_hint: Option<String>,
+}
+
+// ---
+
 /// Response confirming token revocation.
 #[derive(Debug, Serialize)]
 pub struct RevokeResponse {
@@ -42,9 +60,13 @@ pub struct RevokeResponse {
 
 /// Revoke (blacklist) a JWT token.
 ///
-/// This endpoint validates the token and adds its JTI to the Redis blacklist.
+/// This endpoint validates the token and adds its JTI to the token store's
+/// blacklist.
 /// The token will be rejected by future validation attempts.
 ///
+/// A form-encoded request (`application/x-www-form-urlencoded`) is handled
+/// as RFC 7009 describes instead; see [RFC 7009 mode](#rfc-7009-mode).
+///
 /// # Request
 ///
 /// ```json
@@ -66,16 +88,37 @@ pub struct RevokeResponse {
 ///
 /// ```json
 /// {
-///   "error": "Invalid token"
+///   "type": "https://tokn.dev/problems/invalid-token",
+///   "title": "Invalid token",
+///   "status": 401,
+///   "detail": "Invalid token",
+///   "instance": "/v1/auth/revoke",
+///   "request_id": "0b9c6f5e-3c1a-4d9e-9a43-1f0d3c8e7a21"
 /// }
 /// ```
 ///
+/// # RFC 7009 Mode
+///
+/// ```text
+/// POST /v1/auth/revoke
+/// Content-Type: application/x-www-form-urlencoded
+///
+/// token=f47ac10b-58cc-4372-a567-0e02b2c3d479&token_type_hint=refresh_token
+/// ```
+///
+/// `token` may be an access token (blacklisted as above) or a refresh token
+/// (deleted from the token store); `token_type_hint` says which to try first.
+/// The response is `200 OK` with an empty body whether or not the token was
+/// found, so invalid, expired, and unknown tokens are not errors. Only a
+/// store failure is: `503 Service Unavailable`, after which the client may
+/// retry.
+///
 /// # Security
 ///
 /// - Token must be valid (signature + expiry) to be revoked
-/// - JTI is stored in Redis with TTL matching token expiry
+/// - JTI is stored with a TTL matching token expiry
 /// - Already-expired tokens cannot be revoked (no point)
-/// - Revoked tokens are rejected by POST /auth/validate
+/// - Revoked tokens are rejected by POST /v1/auth/validate
 ///
 /// # Use Cases
 ///
@@ -88,7 +131,7 @@ pub struct RevokeResponse {
 ///
 /// This implements **JWT blacklisting**:
 /// - Tokens are stateless (can't be "deleted")
-/// - Instead, we track revoked tokens in Redis
+/// - Instead, we track revoked tokens in the token store
 /// - Validation checks blacklist before accepting token
 /// - Blacklist entries expire when token would expire anyway
 ///
@@ -101,61 +144,84 @@ pub struct RevokeResponse {
 ///
 /// # Errors
 ///
-/// Returns 401 Unauthorized if:
+/// For JSON requests, returns 401 Unauthorized if:
 /// - Token signature is invalid
 /// - Token has expired
 /// - Token format is malformed
 ///
-/// Returns 500 Internal Server Error if Redis storage fails.
+/// Returns 400 Bad Request if the token has already expired, and 500 Internal
+/// Server Error if the token store fails.
 pub async fn revoke_token_handler(
     State(state): State<AppState>,
-    Json(req): Json<RevokeRequest>,
-) -> impl IntoResponse {
+    client: ClientInfo,
+    request: Request,
+) -> Response {
+    // ---
+    // Stateless services do not route this endpoint
+    let Some(store) = state.store.clone() else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    if is_form(request.headers()) {
+        return match Form::<RevocationRequest>::from_request(request, &state).await {
+            Ok(Form(req)) => revoke_rfc7009(&state, &client, &store, req).await,
+            Err(rejection) => rejection.into_response(),
+        };
+    }
+    match Json::<RevokeRequest>::from_request(request, &state).await {
+        Ok(Json(req)) => revoke_json(&state, &client, &store, req).await,
+        Err(rejection) => rejection.into_response(),
+    }
+}
+
+// ---
+
+/// Whether the request body is form-encoded (RFC 7009 mode).
+fn is_form(headers: &HeaderMap) -> bool {
+    // ---
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .is_some_and(|mime| {
+            mime.trim()
+                .eq_ignore_ascii_case("application/x-www-form-urlencoded")
+        })
+}
+
+/// Blacklist a valid access token, refusing invalid and expired ones.
+async fn revoke_json(
+    state: &AppState,
+    client: &ClientInfo,
+    store: &Store,
+    req: RevokeRequest,
+) -> Response {
     // ---
     // Validate token first (must be valid to revoke)
-    let claims = match validate_token(&req.token, &state.config.jwt.secret) {
+    let claims = match state.keys.get().verify(&req.token, state.clock.as_ref()) {
         Ok(claims) => claims,
         Err(e) => {
             tracing::debug!("Cannot revoke invalid token: {}", e);
-            return (
-                StatusCode::UNAUTHORIZED,
-                Json(serde_json::json!({
-                    "error": "Invalid token"
-                })),
-            )
-                .into_response();
+            return ApiError::InvalidToken("Invalid token".into()).into_response();
         }
     };
 
     // Calculate remaining TTL (time until expiration)
-    let now = Utc::now().timestamp() as usize;
+    let now = state.clock.timestamp() as usize;
     let remaining_ttl = if claims.exp > now {
         (claims.exp - now) as i64
     } else {
         // Token already expired, no need to revoke
         tracing::debug!("Token already expired, not revoking");
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({
-                "error": "Token already expired"
-            })),
-        )
-            .into_response();
+        return ApiError::InvalidRequest("Token already expired".into()).into_response();
     };
 
     // Revoke the token (add JTI to blacklist)
-    if let Err(e) = revoke_token(&mut state.redis.clone(), &claims.jti, remaining_ttl).await {
+    if let Err(e) = store.revoke_token(&claims.jti, remaining_ttl).await {
         tracing::error!("Failed to revoke token: {}", e);
-        return (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(serde_json::json!({
-                "error": "Failed to revoke token"
-            })),
-        )
-            .into_response();
+        return ApiError::Internal("Failed to revoke token".into()).into_response();
     }
-
-    tracing::info!("Token revoked: jti={}", claims.jti);
+    access_token_revoked(state, client, &claims);
 
     let response = RevokeResponse {
         message: "Token revoked successfully".to_string(),
@@ -164,3 +230,119 @@ pub async fn revoke_token_handler(
 
     (StatusCode::OK, Json(response)).into_response()
 }
+
+// ---
+
+/// Revoke an access or refresh token as RFC 7009 describes: `200 OK` with an
+/// empty body whether or not the token was found.
+async fn revoke_rfc7009(
+    state: &AppState,
+    client: &ClientInfo,
+    store: &Store,
+    req: RevocationRequest,
+) -> Response {
+    // ---
+    let refresh_first = req.token_type_hint.as_deref() == Some("refresh_token");
+
+    let result = if refresh_first {
+        match revoke_refresh(state, client, store, &req.token).await {
+            Ok(false) => revoke_access(state, client, store, &req.token).await,
+            other => other,
+        }
+    } else {
+        match revoke_access(state, client, store, &req.token).await {
+            Ok(false) => revoke_refresh(state, client, store, &req.token).await,
+            other => other,
+        }
+    };
+
+    match result {
+        Ok(found) => {
+            if !found {
+                tracing::debug!("Nothing to revoke: token is invalid, expired, or unknown");
+            }
+            StatusCode::OK.into_response()
+        }
+        Err(e) => {
+            tracing::error!("Failed to revoke token: {e:#}");
+            ApiError::StoreUnavailable("Failed to revoke token".into()).into_response()
+        }
+    }
+}
+
+/// Blacklist `token` if it is a live access token; `false` if it is not one.
+async fn revoke_access(
+    state: &AppState,
+    client: &ClientInfo,
+    store: &Store,
+    token: &str,
+) -> anyhow::Result<bool> {
+    // ---
+    let Ok(claims) = state.keys.get().verify(token, state.clock.as_ref()) else {
+        return Ok(false);
+    };
+
+    let now = state.clock.timestamp() as usize;
+    if claims.exp <= now {
+        return Ok(false);
+    }
+    store
+        .revoke_token(&claims.jti, (claims.exp - now) as i64)
+        .await?;
+
+    access_token_revoked(state, client, &claims);
+    Ok(true)
+}
+
+/// Delete `token` if it is a stored refresh token; `false` if it is not one.
+async fn revoke_refresh(
+    state: &AppState,
+    client: &ClientInfo,
+    store: &Store,
+    token: &str,
+) -> anyhow::Result<bool> {
+    // ---
+    let Some(data) = store.revoke_refresh_token(token).await? else {
+        return Ok(false);
+    };
+
+    tracing::info!(
+        event = "token_revoked",
+        user_id = %data.user_id,
+        token_type = "refresh_token",
+        "Token revoked"
+    );
+    state
+        .events
+        .emit(AuthEvent::new(AuthEventKind::TokenRevoked).subject(&data.user_id));
+    state.audit.record(
+        AuditEvent::new(AuditEventKind::TokenRevoked, state.clock.as_ref())
+            .user_id(&data.user_id)
+            .client(client)
+            .reason("refresh_token"),
+    );
+    Ok(true)
+}
+
+/// Log and publish the revocation of the access token with `claims`.
+fn access_token_revoked(state: &AppState, client: &ClientInfo, claims: &Claims) {
+    // ---
+    tracing::info!(
+        event = "token_revoked",
+        user_id = %claims.sub,
+        jti = %claims.jti,
+        "Token revoked"
+    );
+    state.events.emit(
+        AuthEvent::new(AuthEventKind::TokenRevoked)
+            .subject(&claims.sub)
+            .jti(&claims.jti),
+    );
+    state.audit.record(
+        AuditEvent::new(AuditEventKind::TokenRevoked, state.clock.as_ref())
+            .user_id(&claims.sub)
+            .jti(&claims.jti)
+            .client(client)
+            .reason("access_token"),
+    );
+}
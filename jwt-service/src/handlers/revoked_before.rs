@@ -0,0 +1,150 @@
+// jwt-service/src/handlers/revoked_before.rs
+
+//! Admin API for per-user revocation cutoffs
+//!
+//! Handles `/admin/users/{user_id}/revoked-before`: read or set the time
+//! before which every token issued to a user is refused, killing all of a
+//! compromised account's tokens with one write, without listing their jtis.
+
+use crate::{ApiError, AppState, AuditEvent, AuditEventKind, ClientInfo, TokenStore};
+use axum::{
+    extract::{Path, State},
+    http::header,
+    response::{IntoResponse, Json},
+    routing::get,
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use tokn_events::{AuthEvent, AuthEventKind};
+
+// ---
+
+/// Request of `PUT /admin/users/{user_id}/revoked-before`.
+#[derive(Debug, Deserialize)]
+pub struct SetRevokedBeforeRequest {
+    // ---
+    /// The cutoff (Unix timestamp); now when omitted, so `{}` revokes every
+    /// token issued so far
+    pub revoked_before: Option<i64>,
+}
+
+/// Response of `/admin/users/{user_id}/revoked-before`.
+#[derive(Debug, Serialize)]
+pub struct RevokedBeforeResponse {
+    // ---
+    /// The user looked up
+    user_id: String,
+
+    /// Tokens issued before this (Unix timestamp) are refused; `null` if
+    /// never set
+    revoked_before: Option<i64>,
+}
+
+// ---
+
+/// Build the `/admin/users/{user_id}/revoked-before` routes, guarded by the
+/// admin token; empty when no admin token is configured (see
+/// [`tokn_server::admin_routes`]).
+///
+/// Routes:
+/// - `GET /admin/users/{user_id}/revoked-before` - the user's cutoff, if any
+/// - `PUT /admin/users/{user_id}/revoked-before` - set it, to the body's
+///   `revoked_before` or, for `{}`, now; access and refresh tokens issued
+///   before it are refused from then on
+pub fn revoked_before_routes(state: &AppState) -> Router<AppState> {
+    // ---
+    let routes = Router::new().route(
+        "/admin/users/{user_id}/revoked-before",
+        get(get_revoked_before_handler).put(set_revoked_before_handler),
+    );
+
+    tokn_server::admin_routes(&state.config.get().admin, routes)
+}
+
+/// The 503 problem for a failed cutoff `action`.
+fn unavailable(action: &str, e: anyhow::Error) -> ApiError {
+    // ---
+    tracing::error!("Revocation cutoff {action} failed: {:#}", e);
+    ApiError::StoreUnavailable(format!("Failed to {action} revocation cutoff"))
+}
+
+// ---
+
+async fn get_revoked_before_handler(
+    State(state): State<AppState>,
+    Path(user_id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    // ---
+    // Stateless services do not route this endpoint
+    let Some(store) = &state.store else {
+        return Err(ApiError::NotFound);
+    };
+
+    let revoked_before = store
+        .revoked_before(&user_id)
+        .await
+        .map_err(|e| unavailable("read", e))?;
+
+    let response = RevokedBeforeResponse {
+        user_id,
+        revoked_before,
+    };
+    Ok(([(header::CACHE_CONTROL, "no-store")], Json(response)))
+}
+
+/// Set a user's revocation cutoff.
+///
+/// A cutoff in the future is refused: it would also refuse the tokens of
+/// the user's next sign-in. Tokens are compared by their issue time in whole
+/// seconds, so ones issued in the same second as the cutoff are kept.
+///
+/// # Errors
+///
+/// Returns 400 Bad Request for a cutoff in the future, and 503 Service
+/// Unavailable if the token store cannot be written.
+async fn set_revoked_before_handler(
+    State(state): State<AppState>,
+    Path(user_id): Path<String>,
+    client: ClientInfo,
+    Json(req): Json<SetRevokedBeforeRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    // ---
+    let Some(store) = &state.store else {
+        return Err(ApiError::NotFound);
+    };
+
+    let now = state.clock.timestamp();
+    let revoked_before = req.revoked_before.unwrap_or(now);
+    if revoked_before > now {
+        return Err(ApiError::InvalidRequest(
+            "revoked_before must not be in the future".into(),
+        ));
+    }
+
+    store
+        .set_revoked_before(&user_id, revoked_before)
+        .await
+        .map_err(|e| unavailable("update", e))?;
+
+    tracing::warn!(
+        event = "token_revoked",
+        user_id = %user_id,
+        revoked_before,
+        "Revoked every token issued to the user before the cutoff"
+    );
+    state
+        .events
+        .emit(AuthEvent::new(AuthEventKind::TokenRevoked).subject(&user_id));
+    state.audit.record(
+        AuditEvent::new(AuditEventKind::TokenRevoked, state.clock.as_ref())
+            .user_id(&user_id)
+            .client(&client)
+            .reason("revocation cutoff set"),
+    );
+
+    let response = RevokedBeforeResponse {
+        user_id,
+        revoked_before: Some(revoked_before),
+    };
+    Ok(([(header::CACHE_CONTROL, "no-store")], Json(response)))
+}
@@ -2,15 +2,16 @@
 
 //! Token validation endpoint
 //!
-//! Handles POST /auth/validate - validates JWT tokens and returns claims
+//! Handles POST /v1/auth/validate - validates JWT tokens and returns claims
 
-use crate::{is_token_revoked, token::validate_token, AppState, Claims};
+use crate::{ApiError, AppState, AuditEvent, AuditEventKind, Claims, ClientInfo};
 use axum::{
     extract::State,
     http::StatusCode,
     response::{IntoResponse, Json},
 };
 use serde::{Deserialize, Serialize};
+use tokn_core::{check_audience, Problem};
 
 // ---
 
@@ -22,6 +23,11 @@ pub struct ValidateRequest {
     // ---
     /// The JWT token to validate
     pub token: String,
+
+    /// The calling service's audience: when given, the token's `aud` claim
+    /// must list it
+    #[serde(default)]
+    pub audience: Option<String>,
 }
 
 // ---
@@ -41,19 +47,6 @@ pub struct ValidateResponse {
 
 // ---
 
-/// Error response for invalid tokens.
-#[derive(Debug, Serialize)]
-pub struct ValidateErrorResponse {
-    // ---
-    /// Indicates the token is invalid
-    valid: bool,
-
-    /// Error message describing why validation failed
-    error: String,
-}
-
-// ---
-
 /// Validate a JWT token.
 ///
 /// This endpoint verifies the token signature, checks expiration, and checks
@@ -63,10 +56,14 @@ pub struct ValidateErrorResponse {
 ///
 /// ```json
 /// {
-///   "token": "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9..."
+///   "token": "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9...",
+///   "audience": "orders-api"
 /// }
 /// ```
 ///
+/// `audience` is optional: a service passing its own name refuses tokens
+/// minted for other services, and tokens without `aud`.
+///
 /// # Response (200 OK) - Valid Token
 ///
 /// ```json
@@ -84,10 +81,18 @@ pub struct ValidateErrorResponse {
 ///
 /// # Response (401 Unauthorized) - Invalid Token
 ///
+/// A problem details body (`application/problem+json`) that also carries
+/// `"valid": false`:
+///
 /// ```json
 /// {
-///   "valid": false,
-///   "error": "Token has expired"
+///   "type": "https://tokn.dev/problems/invalid-token",
+///   "title": "Invalid token",
+///   "status": 401,
+///   "detail": "Token has expired",
+///   "instance": "/v1/auth/validate",
+///   "request_id": "0b9c6f5e-3c1a-4d9e-9a43-1f0d3c8e7a21",
+///   "valid": false
 /// }
 /// ```
 ///
@@ -107,50 +112,67 @@ pub struct ValidateErrorResponse {
 /// # Validation Failures
 ///
 /// Returns 401 Unauthorized if:
-/// - Token signature is invalid (tampered or wrong secret)
+/// - Token signature is invalid (tampered or wrong key)
 /// - Token has expired
 /// - Token format is malformed
-/// - Algorithm is not HS256
+/// - Algorithm is not the configured one
+/// - Token is a single-purpose token (e.g. email verification), not an
+///   access token
+/// - `audience` is given and the token's `aud` does not list it
+/// - `JWT_ACCESS_TOKEN_PROFILE` is set and the token is not typed `at+jwt`
 /// - **Token has been revoked** (in blacklist)
 ///
 /// # TODO
 ///
-/// - Add rate limiting to prevent validation abuse
 /// - Consider caching valid tokens briefly
 pub async fn validate_token_handler(
     State(state): State<AppState>,
+    client: ClientInfo,
     Json(req): Json<ValidateRequest>,
 ) -> impl IntoResponse {
     // ---
-    // Validate the token (signature + expiry)
-    let claims = match validate_token(&req.token, &state.config.jwt.secret) {
+    // Validate the token (signature + expiry, and audience when asked)
+    let verified = state
+        .keys
+        .get()
+        .verify(&req.token, state.clock.as_ref())
+        .and_then(|claims| match &req.audience {
+            Some(audience) => check_audience(&claims, audience).map(|()| claims),
+            None => Ok(claims),
+        });
+    let claims = match verified {
         Ok(claims) => claims,
         Err(e) => {
             // Token is invalid
             tracing::debug!("Token validation failed: {}", e);
+            state.audit.record(
+                AuditEvent::new(AuditEventKind::ValidationFailed, state.clock.as_ref())
+                    .client(&client)
+                    .reason(e.to_string()),
+            );
 
-            let error_response = ValidateErrorResponse {
-                valid: false,
-                error: e.to_string(),
-            };
-            return (StatusCode::UNAUTHORIZED, Json(error_response)).into_response();
+            return invalid(ApiError::InvalidToken(e.to_string())).into_response();
         }
     };
 
-    // Check if token is revoked
-    match is_token_revoked(&mut state.redis.clone(), &claims.jti).await {
+    // Check if token is revoked (always false when stateless)
+    match state.is_revoked(&claims).await {
         Ok(true) => {
             // Token is revoked
             tracing::debug!(
                 "Token validation failed: token revoked (jti={})",
                 claims.jti
             );
+            state.audit.record(
+                AuditEvent::new(AuditEventKind::ValidationFailed, state.clock.as_ref())
+                    .user_id(&claims.sub)
+                    .jti(&claims.jti)
+                    .client(&client)
+                    .reason("token revoked"),
+            );
 
-            let error_response = ValidateErrorResponse {
-                valid: false,
-                error: "Token has been revoked".to_string(),
-            };
-            return (StatusCode::UNAUTHORIZED, Json(error_response)).into_response();
+            return invalid(ApiError::TokenRevoked("Token has been revoked".into()))
+                .into_response();
         }
         Ok(false) => {
             // Token is not revoked, proceed
@@ -159,11 +181,8 @@ pub async fn validate_token_handler(
             // Redis error - fail secure (reject token)
             tracing::error!("Failed to check token revocation status: {}", e);
 
-            let error_response = ValidateErrorResponse {
-                valid: false,
-                error: "Failed to verify token status".to_string(),
-            };
-            return (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)).into_response();
+            return invalid(ApiError::Internal("Failed to verify token status".into()))
+                .into_response();
         }
     }
 
@@ -174,3 +193,12 @@ pub async fn validate_token_handler(
     };
     (StatusCode::OK, Json(response)).into_response()
 }
+
+// ---
+
+/// A problem response that, like the success response, says whether the
+/// token is valid.
+fn invalid(error: ApiError) -> Problem {
+    // ---
+    error.into_problem().extension("valid", false)
+}
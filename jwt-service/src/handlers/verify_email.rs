@@ -0,0 +1,221 @@
+// jwt-service/src/handlers/verify_email.rs
+
+//! Email verification endpoints
+//!
+//! Handles POST /v1/auth/verify-email/request - issues a single-use token
+//! proving a user controls an address - and POST /v1/auth/verify-email/confirm
+//! - consumes it. The token is a JWT with the `verify_email` purpose, which
+//! access-token validation refuses.
+
+use super::generate::authorize_issuer;
+use super::magic_link::{describe_seconds, link_to};
+use crate::{ApiError, AppState, Claims, TokenStore};
+use axum::{
+    extract::State,
+    http::{header, HeaderMap},
+    response::{IntoResponse, Json},
+};
+use serde::{Deserialize, Serialize};
+use tokn_mail::Template;
+
+// ---
+
+/// `purpose` claim of email verification tokens.
+pub const VERIFY_EMAIL_PURPOSE: &str = "verify_email";
+
+// ---
+
+/// Request of `POST /v1/auth/verify-email/request`.
+#[derive(Debug, Deserialize)]
+pub struct VerifyEmailRequest {
+    // ---
+    /// The user the address belongs to
+    pub user_id: String,
+
+    /// The address to verify
+    pub email: String,
+}
+
+/// Response of `POST /v1/auth/verify-email/request`.
+#[derive(Debug, Serialize)]
+pub struct VerifyEmailResponse {
+    // ---
+    /// The verification token, for the caller to deliver; omitted when it
+    /// was emailed (`JWT_VERIFY_EMAIL_URL` set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    token: Option<String>,
+
+    /// Seconds until the token expires unconfirmed
+    expires_in: i64,
+}
+
+/// Request of `POST /v1/auth/verify-email/confirm`.
+#[derive(Debug, Deserialize)]
+pub struct ConfirmEmailRequest {
+    // ---
+    /// The token from the verification link
+    pub token: String,
+}
+
+/// Response of `POST /v1/auth/verify-email/confirm`.
+#[derive(Debug, Serialize)]
+pub struct ConfirmEmailResponse {
+    // ---
+    /// The user the address belongs to
+    user_id: String,
+
+    /// The verified address
+    email: String,
+
+    /// Always `true`; a token that does not verify is an error
+    verified: bool,
+}
+
+// ---
+
+/// Issue an email verification token for a user's address.
+///
+/// Takes the same `X-Issuer-Key` as `POST /v1/auth/token` when
+/// `TOKEN_ISSUER_REQUIRE_KEY` is set. The token is bound to `user_id` and
+/// `email` and lives `JWT_VERIFY_EMAIL_TTL_SECONDS` (default 86400). With
+/// `JWT_VERIFY_EMAIL_URL` set, the link (that URL with `?token=` appended) is
+/// emailed to `email`; without it, the token is returned for the caller to
+/// deliver.
+///
+/// # Request
+///
+/// ```json
+/// {
+///   "user_id": "user_12345",
+///   "email": "john@example.com"
+/// }
+/// ```
+///
+/// # Response (200 OK)
+///
+/// ```json
+/// {
+///   "token": "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9...",
+///   "expires_in": 86400
+/// }
+/// ```
+///
+/// # Errors
+///
+/// Returns 401 Unauthorized for a missing or unknown API key when one is
+/// required, 400 Bad Request without a `user_id` or `email`, and 500 Internal
+/// Server Error if the token cannot be signed.
+pub async fn request_email_verification_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<VerifyEmailRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    // ---
+    let config = state.config.get();
+    let issuer = authorize_issuer(&state, &config, &headers).await?;
+    if req.user_id.trim().is_empty() || req.email.trim().is_empty() {
+        return Err(ApiError::InvalidRequest(
+            "user_id and email are required".into(),
+        ));
+    }
+
+    let ttl = config.jwt.verify_email_ttl_seconds;
+    let claims = Claims::new(req.user_id, req.email, ttl, state.clock.as_ref())
+        .with_purpose(VERIFY_EMAIL_PURPOSE);
+    let token = state.keys.get().sign(&claims).map_err(|e| {
+        tracing::error!("Verification token generation failed: {}", e);
+        ApiError::Internal("Failed to generate verification token".into())
+    })?;
+
+    tracing::info!(
+        user_id = %claims.sub,
+        issuer = issuer.as_deref().unwrap_or("-"),
+        emailed = config.jwt.verify_email_url.is_some(),
+        "Issued email verification token"
+    );
+    let token = match &config.jwt.verify_email_url {
+        Some(url) => {
+            state.mail.notify(
+                Template::Verification,
+                &claims.email,
+                &[
+                    ("link", &link_to(url, &token)),
+                    ("expires_in", &describe_seconds(ttl)),
+                ],
+            );
+            None
+        }
+        None => Some(token),
+    };
+
+    let response = VerifyEmailResponse {
+        token,
+        expires_in: ttl,
+    };
+    Ok(([(header::CACHE_CONTROL, "no-store")], Json(response)))
+}
+
+/// Confirm an email address with its verification token.
+///
+/// Called by the page the verification link points to. The token is
+/// consumed: it is marked used until it expires, so it confirms the address
+/// once.
+///
+/// # Request
+///
+/// ```json
+/// {
+///   "token": "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9..."
+/// }
+/// ```
+///
+/// # Response (200 OK)
+///
+/// ```json
+/// {
+///   "user_id": "user_12345",
+///   "email": "john@example.com",
+///   "verified": true
+/// }
+/// ```
+///
+/// # Errors
+///
+/// Returns 401 Unauthorized if the token is invalid, expired, already used,
+/// or not an email verification token, and 503 Service Unavailable if the
+/// token store cannot be reached.
+pub async fn confirm_email_verification_handler(
+    State(state): State<AppState>,
+    Json(req): Json<ConfirmEmailRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    // ---
+    // Stateless services do not route this endpoint
+    let Some(store) = &state.store else {
+        return Err(ApiError::NotFound);
+    };
+
+    let claims = state
+        .keys
+        .get()
+        .verify_purpose(&req.token, VERIFY_EMAIL_PURPOSE, state.clock.as_ref())
+        .map_err(|e| ApiError::InvalidToken(e.to_string()))?;
+
+    let ttl = (claims.exp as i64 - state.clock.timestamp()).max(1);
+    let consumed = store.consume_token(&claims.jti, ttl).await.map_err(|e| {
+        tracing::error!("Email verification failed: {:#}", e);
+        ApiError::StoreUnavailable("Failed to verify email".into())
+    })?;
+    if !consumed {
+        return Err(ApiError::InvalidToken(
+            "Verification token has already been used".into(),
+        ));
+    }
+
+    tracing::info!(user_id = %claims.sub, "Verified email address");
+    let response = ConfirmEmailResponse {
+        user_id: claims.sub,
+        email: claims.email,
+        verified: true,
+    };
+    Ok(([(header::CACHE_CONTROL, "no-store")], Json(response)))
+}
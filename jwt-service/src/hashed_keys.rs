@@ -0,0 +1,241 @@
+// jwt-service/src/hashed_keys.rs
+
+//! Long-lived keys stored by their hash
+//!
+//! Machine clients' [API keys](crate::ApiKey) and the token endpoint's
+//! [issuer keys](crate::IssuerKey) are both random strings shown once, when
+//! they are created, and kept in the [`TokenStore`](crate::TokenStore) by
+//! their SHA-256 only, so read access to the store yields no usable key.
+//! They unlock different things and travel in different headers: API keys
+//! in `X-Api-Key`, issuer keys in `X-Issuer-Key`.
+//!
+//! In Redis, each kind is a hash of ID → JSON entry (`api_keys`,
+//! `issuer_keys`), with a `user_keys:{hash}:{user_id}` set indexing the
+//! keys that act as a user.
+
+use chrono::{DateTime, Utc};
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+#[cfg(feature = "redis")]
+use anyhow::{Context, Result};
+#[cfg(feature = "redis")]
+use redis::aio::ConnectionLike;
+#[cfg(feature = "redis")]
+use redis::AsyncCommands;
+#[cfg(feature = "redis")]
+use tokn_core::keys;
+
+// ---
+
+/// Which kind of key a stored entry is; the kinds are kept apart, so a key
+/// of one kind is unknown as the other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyKind {
+    // ---
+    /// A machine client's API key, acting as a user
+    Api,
+
+    /// A key for `POST /v1/auth/token`
+    Issuer,
+}
+
+impl KeyKind {
+    // ---
+    /// The kind's name, as the Postgres store records it.
+    pub fn as_str(self) -> &'static str {
+        // ---
+        match self {
+            KeyKind::Api => "api",
+            KeyKind::Issuer => "issuer",
+        }
+    }
+
+    /// The Redis hash keeping keys of this kind.
+    #[cfg(feature = "redis")]
+    fn redis_hash(self) -> &'static str {
+        // ---
+        match self {
+            KeyKind::Api => keys::API_KEYS,
+            KeyKind::Issuer => keys::ISSUER_KEYS,
+        }
+    }
+}
+
+impl fmt::Display for KeyKind {
+    // ---
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // ---
+        f.write_str(self.as_str())
+    }
+}
+
+// ---
+
+/// The stored entry of a key, kept by [`Store::store_key`](crate::Store::store_key)
+/// under the key's [`key_id`].
+pub trait HashedKey: Serialize + DeserializeOwned + Send + Sync {
+    // ---
+    /// Which kind of key the entry is for.
+    const KIND: KeyKind;
+
+    /// The user the key acts as, whose keys
+    /// [`Store::invalidate_user`](crate::Store::invalidate_user) deletes;
+    /// `None` for a key acting as no user.
+    fn owner(&self) -> Option<&str>;
+
+    /// When the key was created; keys are listed oldest first.
+    fn created_at(&self) -> DateTime<Utc>;
+}
+
+// ---
+
+/// ID of `key`: its SHA-256, hex-encoded. Stored keys are indexed by ID, so
+/// the store never holds a usable key.
+pub fn key_id(key: &str) -> String {
+    // ---
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+/// A new random key: `prefix` and `len` alphanumeric characters.
+pub(crate) fn generate_key(prefix: &str, len: usize) -> String {
+    // ---
+    let random: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(len)
+        .map(char::from)
+        .collect();
+    format!("{prefix}{random}")
+}
+
+// ---
+
+/// Store the JSON `entry` of a `kind` key under its `id`, indexed under the
+/// user `owner` it acts as, if any.
+///
+/// # Errors
+///
+/// Returns an error if Redis cannot be written.
+#[cfg(feature = "redis")]
+pub(crate) async fn store_key_entry<C>(
+    conn: &mut C,
+    kind: KeyKind,
+    id: &str,
+    owner: Option<&str>,
+    entry: &str,
+) -> Result<()>
+where
+    C: ConnectionLike + Send,
+{
+    // ---
+    let mut pipe = redis::pipe();
+    pipe.atomic().hset(kind.redis_hash(), id, entry).ignore();
+    if let Some(owner) = owner {
+        pipe.sadd(keys::user_keys(kind.redis_hash(), owner), id)
+            .ignore();
+    }
+    let _: () = pipe
+        .query_async(conn)
+        .await
+        .with_context(|| format!("Failed to store {kind} key in Redis"))?;
+    Ok(())
+}
+
+/// The JSON entry of the `kind` key with ID `id`, if there is one.
+///
+/// # Errors
+///
+/// Returns an error if Redis cannot be queried.
+#[cfg(feature = "redis")]
+pub(crate) async fn find_key_entry<C>(
+    conn: &mut C,
+    kind: KeyKind,
+    id: &str,
+) -> Result<Option<String>>
+where
+    C: ConnectionLike + Send,
+{
+    // ---
+    conn.hget(kind.redis_hash(), id)
+        .await
+        .with_context(|| format!("Failed to look up {kind} key"))
+}
+
+/// Every `kind` key's ID and JSON entry.
+///
+/// # Errors
+///
+/// Returns an error if Redis cannot be queried.
+#[cfg(feature = "redis")]
+pub(crate) async fn list_key_entries<C>(
+    conn: &mut C,
+    kind: KeyKind,
+) -> Result<Vec<(String, String)>>
+where
+    C: ConnectionLike + Send,
+{
+    // ---
+    conn.hgetall(kind.redis_hash())
+        .await
+        .with_context(|| format!("Failed to list {kind} keys"))
+}
+
+/// Delete the `kind` key with ID `id`; `false` if there was none. Its user
+/// index keeps naming it, which is harmless: the index only drives
+/// [`delete_user_key_entries`].
+///
+/// # Errors
+///
+/// Returns an error if Redis cannot be written.
+#[cfg(feature = "redis")]
+pub(crate) async fn delete_key_entry<C>(conn: &mut C, kind: KeyKind, id: &str) -> Result<bool>
+where
+    C: ConnectionLike + Send,
+{
+    // ---
+    let deleted: u32 = conn
+        .hdel(kind.redis_hash(), id)
+        .await
+        .with_context(|| format!("Failed to delete {kind} key"))?;
+    Ok(deleted > 0)
+}
+
+/// Delete every `kind` key acting as `user_id`, with its index, returning
+/// how many there were.
+///
+/// # Errors
+///
+/// Returns an error if Redis cannot be queried or written.
+#[cfg(feature = "redis")]
+pub(crate) async fn delete_user_key_entries<C>(
+    conn: &mut C,
+    kind: KeyKind,
+    user_id: &str,
+) -> Result<usize>
+where
+    C: ConnectionLike + Send,
+{
+    // ---
+    let index_key = keys::user_keys(kind.redis_hash(), user_id);
+    let ids: Vec<String> = conn
+        .smembers(&index_key)
+        .await
+        .with_context(|| format!("Failed to list the user's {kind} keys"))?;
+    if ids.is_empty() {
+        return Ok(0);
+    }
+
+    let (deleted,): (usize,) = redis::pipe()
+        .atomic()
+        .hdel(kind.redis_hash(), &ids)
+        .del(&index_key)
+        .ignore()
+        .query_async(conn)
+        .await
+        .with_context(|| format!("Failed to delete the user's {kind} keys"))?;
+    Ok(deleted)
+}
@@ -0,0 +1,45 @@
+// jwt-service/src/health.rs
+
+//! Dependencies probed by `/health/ready`
+//!
+//! Redis when connected, and Postgres when it is the token store
+//! (`TOKEN_STORE=postgres`); a stateless service, or one keeping tokens in
+//! memory, has no dependencies and is ready while it is live.
+//! Redis that answers while its circuit breaker is not closed is reported as
+//! degraded.
+
+use tokn_server::HealthChecks;
+
+// ---
+
+use crate::{AppState, StoreBackend, TokenStore};
+
+// ---
+
+/// The readiness checks for jwt-service over `state`.
+pub fn health_checks(state: &AppState) -> HealthChecks {
+    // ---
+    let mut checks = HealthChecks::new("jwt-service");
+
+    #[cfg(feature = "redis")]
+    if let Some(redis) = state.redis.clone() {
+        let probed = redis.clone();
+        checks = checks
+            .check("redis", move || {
+                let redis = probed.clone();
+                async move { redis.ping().await }
+            })
+            .degraded_when("redis", move || redis.degraded());
+    }
+
+    if let Some(store) = state.store.clone() {
+        if store.backend() == StoreBackend::Postgres {
+            checks = checks.check("postgres", move || {
+                let store = store.clone();
+                async move { store.ping().await }
+            });
+        }
+    }
+
+    checks
+}
@@ -0,0 +1,143 @@
+// jwt-service/src/issuer_keys.rs
+
+//! Issuer keys for the token endpoint
+//!
+//! `POST /v1/auth/token` issues tokens for whichever `user_id` it is given,
+//! so only trusted backends should reach it. With `issuer.require_key` set,
+//! callers must send one of these keys in `X-Issuer-Key`: a key listed in
+//! `TOKEN_ISSUER_KEYS`, or one created through `/admin/issuer-keys` and
+//! kept in the token store. Stored keys are kept by their SHA-256 only (see
+//! [`HashedKey`](crate::HashedKey)); a key is shown once, when it is
+//! created.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokn_config::Secret;
+
+// ---
+
+use crate::hashed_keys::generate_key;
+use crate::{HashedKey, KeyKind};
+
+// ---
+
+/// Header carrying the caller's issuer key; machine clients' API keys travel
+/// in `X-Api-Key` instead.
+pub const ISSUER_KEY_HEADER: &str = "x-issuer-key";
+
+/// Minimum key length, matching the 256-bit floor used for JWT secrets.
+const MIN_ISSUER_KEY_LEN: usize = 32;
+
+/// Length of keys created through the admin API.
+const GENERATED_KEY_LEN: usize = 40;
+
+// ---
+
+/// Service configuration section for caller authentication on
+/// `POST /v1/auth/token`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct IssuerConfig {
+    // ---
+    /// Refuse token requests without a valid `X-Issuer-Key` (env
+    /// `TOKEN_ISSUER_REQUIRE_KEY`, default: false; required when
+    /// `TOKN_ENV=prod`)
+    pub require_key: bool,
+
+    /// Comma-separated keys accepted besides those stored through the admin
+    /// API (env `TOKEN_ISSUER_KEYS`)
+    pub keys: Option<Secret>,
+}
+
+impl IssuerConfig {
+    // ---
+    /// The keys listed in `keys`.
+    pub fn static_keys(&self) -> Vec<&str> {
+        // ---
+        let Some(keys) = &self.keys else {
+            return Vec::new();
+        };
+        keys.expose()
+            .split(',')
+            .map(str::trim)
+            .filter(|key| !key.is_empty())
+            .collect()
+    }
+}
+
+// ---
+
+/// Config rule for the `issuer` section: listed keys must be long enough not
+/// to be guessed.
+///
+/// # Errors
+///
+/// Returns a message for `tokn_config::ConfigLoader::rule` when a key is
+/// shorter than 32 characters.
+pub fn validate_issuer_config(config: &IssuerConfig) -> Result<(), String> {
+    // ---
+    let short = config
+        .static_keys()
+        .iter()
+        .filter(|key| key.len() < MIN_ISSUER_KEY_LEN)
+        .count();
+    if short > 0 {
+        return Err(format!(
+            "{short} TOKEN_ISSUER_KEYS entries are shorter than {MIN_ISSUER_KEY_LEN} characters \
+             (256 bits)"
+        ));
+    }
+    Ok(())
+}
+
+/// Config rule refusing an open token endpoint, for `ConfigLoader::prod_rule`.
+///
+/// # Errors
+///
+/// Returns a message when `require_key` is off.
+pub fn require_issuer_key(config: &IssuerConfig) -> Result<(), String> {
+    // ---
+    if !config.require_key {
+        return Err(
+            "must require an issuer key for POST /v1/auth/token (TOKEN_ISSUER_REQUIRE_KEY=true)"
+                .to_string(),
+        );
+    }
+    Ok(())
+}
+
+// ---
+
+/// A key stored through the admin API.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IssuerKey {
+    // ---
+    /// Who the key was created for (e.g. `billing-service`)
+    pub name: String,
+
+    /// When the key was created
+    pub created_at: DateTime<Utc>,
+}
+
+impl HashedKey for IssuerKey {
+    // ---
+    const KIND: KeyKind = KeyKind::Issuer;
+
+    fn owner(&self) -> Option<&str> {
+        // ---
+        None
+    }
+
+    fn created_at(&self) -> DateTime<Utc> {
+        // ---
+        self.created_at
+    }
+}
+
+// ---
+
+/// A new random key: 40 alphanumeric characters.
+pub fn generate_issuer_key() -> String {
+    // ---
+    generate_key("", GENERATED_KEY_LEN)
+}
@@ -0,0 +1,47 @@
+// jwt-service/src/jobs.rs
+
+//! Recurring housekeeping jobs
+//!
+//! Registered on a [`Scheduler`] by [`scheduler`]; each job's schedule can be
+//! overridden in the `scheduler` config section. Cleanup deletes are
+//! idempotent, so every replica runs them and no leader is elected.
+
+use tokn_core::SharedClock;
+use tokn_scheduler::{JobResult, Schedule, Scheduler, SchedulerConfig};
+
+// ---
+
+use crate::PostgresStore;
+
+// ---
+
+/// Name of the job deleting expired rows from the Postgres token store.
+pub const TOKEN_STORE_CLEANUP: &str = "token_store_cleanup";
+
+/// Default schedule for [`TOKEN_STORE_CLEANUP`].
+const TOKEN_STORE_CLEANUP_SCHEDULE: &str = "*/15 * * * *";
+
+// ---
+
+/// The jwt-service's scheduler, with the cleanup of `store` registered.
+/// Call [`Scheduler::start`] to run it.
+pub fn scheduler(config: &SchedulerConfig, store: &PostgresStore, clock: SharedClock) -> Scheduler {
+    // ---
+    let store = store.clone();
+    let cleanup = move || {
+        let store = store.clone();
+        async move {
+            let purged = store.purge_expired().await?;
+            tracing::info!("Deleted {purged} expired token store rows");
+            JobResult::Ok(())
+        }
+    };
+
+    Scheduler::new(config, clock).job(
+        TOKEN_STORE_CLEANUP,
+        TOKEN_STORE_CLEANUP_SCHEDULE
+            .parse::<Schedule>()
+            .expect("default schedule is valid"),
+        cleanup,
+    )
+}
@@ -3,45 +3,353 @@
 //! JWT token service library
 //!
 //! Provides JWT generation, validation, refresh, and revocation functionality.
+//!
+//! Refresh tokens and revocation live in a [`TokenStore`] selected by
+//! `TOKEN_STORE`: Redis (the default `redis` feature), Postgres (`postgres`
+//! feature), or process memory. With `JWT_STATELESS=true`, or the Redis store
+//! compiled out, the service is a stateless mint/validator: `/v1/auth/token`
+//! issues access tokens only, `/v1/auth/validate` checks signature and expiry
+//! only, and `/v1/auth/refresh` and `/v1/auth/revoke` are not routed.
 
-mod claims;
+mod apikeys;
+mod audit;
+mod check;
 mod config;
+mod debug;
+#[cfg(feature = "redis")]
+mod dpop;
+mod error;
+mod grpc;
 mod handlers;
+mod hashed_keys;
+mod health;
+mod issuer_keys;
+#[cfg(feature = "postgres")]
+mod jobs;
+#[cfg(feature = "redis")]
+mod magic_link;
+#[cfg(feature = "redis")]
 mod redis_client;
+#[cfg(feature = "redis")]
 mod refresh;
+mod reload;
+#[cfg(feature = "redis")]
 mod revoke;
-mod token;
-
-use std::sync::Arc;
+mod router;
+mod session;
+mod store;
+#[cfg(feature = "redis")]
+mod ticket;
 
-// ---
-
-/// Type alias for Redis connection manager.
-///
-/// Provides async connection pooling for Redis operations.
-pub type RedisConnection = redis::aio::ConnectionManager;
+use anyhow::Result;
+use axum::http::HeaderMap;
+use tokn_config::Reloadable;
+use tokn_core::DpopProof;
+use tokn_events::Events;
+use tokn_mail::Mail;
 
 // ---
 
 /// Application state shared across all handlers.
 ///
-/// Contains configuration and Redis connection.
+/// Contains configuration and, unless running stateless, the token store.
 #[derive(Clone)]
 pub struct AppState {
     // ---
-    pub config: Arc<Config>,
-    pub redis: RedisConnection,
+    /// Current configuration; swapped on reload (see [`reloader`])
+    pub config: Reloadable<Config>,
+    /// Refresh-token store and revocation blacklist; `None` when stateless
+    pub store: Option<Store>,
+    /// Redis, for DPoP replay records (and the store with
+    /// `TOKEN_STORE=redis`); `None` when stateless or storing elsewhere
+    #[cfg(feature = "redis")]
+    pub redis: Option<RedisConnection>,
+    /// Auth event publisher (disabled unless `EVENTS_BACKEND` is set)
+    pub events: Events,
+    /// Audit log for incident forensics (disabled unless `AUDIT_SINK` is set)
+    pub audit: AuditLog,
+    /// Email delivery for security notifications (logged unless
+    /// `MAIL_BACKEND` is set)
+    pub mail: Mail,
+    /// Time source for issuing and expiring tokens
+    pub clock: SharedClock,
+    /// Access-token signing keys, loaded from `config.jwt`; the `jwt.keys`
+    /// ring is swapped on reload
+    pub keys: Reloadable<JwtKeys>,
+}
+
+// ---
+
+impl AppState {
+    // ---
+    /// Stateless state for `config`: no token store, Redis, events, audit
+    /// log, or email delivery.
+    ///
+    /// Builds the same with or without the `redis` feature, for embedding
+    /// jwt-service in another process (e.g. `tokn-demo`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the signing keys cannot be loaded (see
+    /// [`JwtConfig::keys`]).
+    pub fn stateless(config: Config, clock: SharedClock) -> Result<Self> {
+        // ---
+        let keys = config.jwt.keys()?.into();
+
+        Ok(Self {
+            config: config.into(),
+            store: None,
+            #[cfg(feature = "redis")]
+            redis: None,
+            events: Events::disabled(),
+            audit: AuditLog::disabled(),
+            mail: Mail::default(),
+            clock,
+            keys,
+        })
+    }
+
+    // ---
+    /// Whether refresh tokens and revocation are available.
+    pub fn is_stateful(&self) -> bool {
+        // ---
+        self.store.is_some()
+    }
+
+    // ---
+    /// Whether DPoP proofs are recorded in Redis, and so shared between
+    /// replicas (see [`dpop_proof_replayed`](Self::dpop_proof_replayed)).
+    pub fn records_dpop_proofs(&self) -> bool {
+        // ---
+        #[cfg(feature = "redis")]
+        return self.redis.is_some();
+
+        #[cfg(not(feature = "redis"))]
+        return false;
+    }
+
+    // ---
+    /// The user a request's access token (bearer, DPoP, or the cookie in
+    /// cookie mode) was issued to, if the token's signature and expiry check
+    /// out; neither revocation nor the DPoP proof is checked. Identifies
+    /// users for `RATE_LIMIT_KEY_BY`.
+    pub fn authenticated_user(&self, headers: &HeaderMap) -> Option<String> {
+        // ---
+        let token = match tokn_core::authorization_token(headers) {
+            Ok((_, token)) => token,
+            Err(tokn_core::AuthHeaderError::Missing) if self.config.get().jwt.cookie_mode => {
+                tokn_core::cookie_value(headers, ACCESS_TOKEN_COOKIE)?
+            }
+            Err(_) => return None,
+        };
+        let claims = self.keys.get().verify(token, self.clock.as_ref()).ok()?;
+        Some(claims.sub)
+    }
+
+    // ---
+    /// Whether the token with `claims` is revoked: its `jti` is blacklisted,
+    /// its `ver` is older than its user's token version (an unversioned
+    /// token counts as version 0), or it was issued before its user's
+    /// revocation cutoff.
+    ///
+    /// Always `false` when stateless: there is no blacklist, so a token stays
+    /// valid until it expires.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the token store cannot be queried.
+    pub async fn is_revoked(&self, claims: &Claims) -> Result<bool> {
+        // ---
+        let Some(store) = &self.store else {
+            return Ok(false);
+        };
+        // One round trip on the hot path, rather than one per check
+        let status = store.revocation_status(&claims.jti, &claims.sub).await?;
+        Ok(status.revokes(claims))
+    }
+
+    /// The current token version of `user_id`, to stamp into its new access
+    /// tokens as `ver`; `None` when stateless.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the token store cannot be queried.
+    pub async fn token_version(&self, user_id: &str) -> Result<Option<u64>> {
+        // ---
+        match &self.store {
+            Some(store) => Ok(Some(store.token_version(user_id).await?)),
+            None => Ok(None),
+        }
+    }
+
+    // ---
+    /// Record a DPoP `proof` as used, returning whether it was used before.
+    ///
+    /// Always `false` without Redis: proofs are not shared between
+    /// requests, so replays are only caught by the JWT middleware's
+    /// in-memory check.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if Redis cannot be written.
+    #[cfg_attr(not(feature = "redis"), allow(unused_variables))]
+    pub async fn dpop_proof_replayed(&self, proof: &DpopProof) -> Result<bool> {
+        // ---
+        #[cfg(feature = "redis")]
+        if let Some(redis) = &self.redis {
+            return record_dpop_proof(&mut redis.clone(), proof, self.clock.timestamp()).await;
+        }
+
+        Ok(false)
+    }
+
+    // ---
+    /// Whether machine clients can authenticate with API keys, which are
+    /// kept in the token store (see [`api_key_claims`](Self::api_key_claims)).
+    pub fn accepts_api_keys(&self) -> bool {
+        // ---
+        self.is_stateful()
+    }
+
+    // ---
+    /// The claims a request sending API `key` in `X-Api-Key` is handled
+    /// with, or `None` for an unknown or deleted key (see [`ApiKey::claims`]).
+    /// The JWT middleware checks them for revocation like a token's.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the token store cannot be queried.
+    pub async fn api_key_claims(&self, key: &str) -> Result<Option<Claims>> {
+        // ---
+        let Some(store) = &self.store else {
+            return Ok(None);
+        };
+        let id = key_id(key);
+        let entry: Option<ApiKey> = store.find_key(&id).await?;
+        Ok(entry.map(|entry| entry.claims(&id, self.clock.as_ref())))
+    }
+
+    // ---
+    /// Who holds the token endpoint's issuer `key`: `TOKEN_ISSUER_KEYS` for
+    /// a configured key, the stored name for one created through
+    /// `/admin/issuer-keys`, or `None` for an unknown key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the token store cannot be queried.
+    pub async fn issuer_key_name(&self, key: &str) -> Result<Option<String>> {
+        // ---
+        // Compare digests so the comparison time says nothing about the key
+        let id = key_id(key);
+        let config = self.config.get();
+        if config
+            .issuer
+            .static_keys()
+            .iter()
+            .any(|configured| key_id(configured) == id)
+        {
+            return Ok(Some("TOKEN_ISSUER_KEYS".to_string()));
+        }
+
+        let Some(store) = &self.store else {
+            return Ok(None);
+        };
+        let entry: Option<IssuerKey> = store.find_key(&id).await?;
+        Ok(entry.map(|entry| entry.name))
+    }
+
+    // ---
+    /// Issue and store a refresh token for `claims`, starting a new session
+    /// on `device`; `None` when stateless.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the token cannot be stored.
+    pub async fn issue_refresh_token(
+        &self,
+        claims: &Claims,
+        device: &ClientDevice,
+    ) -> Result<Option<String>> {
+        // ---
+        let Some(store) = &self.store else {
+            return Ok(None);
+        };
+
+        let config = self.config.get();
+        let now = self.clock.timestamp();
+        let Some((mut data, ttl)) = RefreshTokenData::from(claims).renew(&config.jwt, now) else {
+            // Only possible with a maximum session shorter than a second
+            return Ok(None);
+        };
+        data.record_device(device);
+        let token = store.issue_refresh_token(&data, ttl).await?;
+        Ok(Some(token))
+    }
 }
 
 // ---
 
-pub use claims::Claims;
-pub use config::Config;
+pub use apikeys::{generate_api_key, ApiKey, API_KEY_ID_CLAIM};
+pub use audit::{
+    validate_audit_config, AuditConfig, AuditEvent, AuditEventKind, AuditLog, AuditSink, ClientInfo,
+};
+pub use check::check_config;
+pub use config::{Config, JwtConfig, JwtKeyConfig, RedisConfig, ServerConfig};
+pub use debug::debug_info;
+#[cfg(feature = "redis")]
+pub use dpop::record_dpop_proof;
+pub use error::{ApiError, PROBLEM_TYPE_BASE};
+pub use grpc::{serve_grpc, IntrospectionService};
 pub use handlers::{
-    generate_token_handler, protected_routes, refresh_token_handler, revoke_token_handler,
-    validate_token_handler,
+    api_key_routes, blacklist_routes, confirm_email_verification_handler,
+    confirm_password_reset_handler, delete_session_handler, generate_token_handler,
+    introspect_token_handler, invalidate_user_handler, issue_magic_link_handler,
+    issue_ticket_handler, issuer_key_routes, list_sessions_handler, protected_routes,
+    redeem_magic_link_handler, redeem_ticket_handler, refresh_token_handler,
+    request_email_verification_handler, request_password_reset_handler, require_scope,
+    revoke_token_handler, revoked_before_routes, session_routes, ticket_routes,
+    validate_token_handler, RequireScope, RequireScopeService, ACCESS_TOKEN_COOKIE,
+    PASSWORD_RESET_PURPOSE, REFRESH_TOKEN_COOKIE, VERIFY_EMAIL_PURPOSE,
+};
+pub use hashed_keys::{key_id, HashedKey, KeyKind};
+pub use health::health_checks;
+pub use issuer_keys::{
+    generate_issuer_key, require_issuer_key, validate_issuer_config, IssuerConfig, IssuerKey,
+    ISSUER_KEY_HEADER,
+};
+#[cfg(feature = "postgres")]
+pub use jobs::{scheduler, TOKEN_STORE_CLEANUP};
+#[cfg(feature = "redis")]
+pub use magic_link::{redeem_magic_link, store_magic_link};
+#[cfg(feature = "redis")]
+pub use redis_client::{create_redis_client, RedisConnection};
+#[cfg(feature = "redis")]
+pub use refresh::{
+    delete_user_session, generate_refresh_token, list_refresh_tokens, list_user_sessions,
+    refresh_successor, refresh_token_reused, revoke_refresh_token, store_refresh_successor,
+    store_refresh_token, validate_refresh_token,
+};
+pub use reload::reloader;
+#[cfg(feature = "redis")]
+pub use revoke::{
+    bump_token_version, consume_token, is_token_revoked, list_revoked_tokens, revocation_status,
+    revoke_token, revoked_before, revoked_token_ttl, set_revoked_before, token_version,
+    unrevoke_token,
+};
+pub use router::{build_router, with_service_layers};
+pub use session::{ClientDevice, RefreshTokenData, RefreshTokenEntry, DEVICE_ID_HEADER};
+#[cfg(feature = "postgres")]
+pub use store::PostgresStore;
+#[cfg(feature = "redis")]
+pub use store::RedisStore;
+pub use store::{
+    validate_store_config, MemoryStore, RevocationStatus, RevokedToken, Store, StoreBackend,
+    StoreConfig, TokenStore, DEFAULT_SWEEP_INTERVAL_SECONDS,
+};
+#[cfg(feature = "redis")]
+pub use ticket::{redeem_ticket, store_ticket};
+pub use tokn_auth::{AuthenticatedUser, JwtAuth};
+pub use tokn_core::{
+    generate_token, validate_token, Claims, Clock, JwtKeys, SharedClock, SigningAlgorithm,
+    SystemClock, TokenError,
 };
-pub use redis_client::create_redis_client;
-pub use refresh::{generate_refresh_token, validate_refresh_token};
-pub use revoke::{is_token_revoked, revoke_token};
-pub use token::{generate_token, validate_token};
@@ -0,0 +1,74 @@
+// jwt-service/src/magic_link.rs
+
+//! Single-use magic login links in Redis
+//!
+//! A magic link carries a token standing for the claims of a login: whoever
+//! follows it within its lifetime gets an access and refresh token pair for
+//! them, once. [`RedisStore`](crate::RedisStore) serves these to the
+//! handlers as a [`TokenStore`](crate::TokenStore).
+
+use anyhow::{Context, Result};
+use redis::aio::ConnectionLike;
+use redis::AsyncCommands;
+use tokn_core::{keys, Claims};
+
+// ---
+
+/// Store the `claims` the magic link `token` grants.
+///
+/// # Storage Format
+///
+/// - Key: `magic_link:{token}`
+/// - Value: JSON-serialized [`Claims`] the redeemed link's tokens are issued
+///   for
+/// - TTL: `ttl_seconds`
+///
+/// # Errors
+///
+/// Returns an error if the claims cannot be serialized or Redis cannot be
+/// written.
+pub async fn store_magic_link<C>(
+    redis_conn: &mut C,
+    token: &str,
+    claims: &Claims,
+    ttl_seconds: i64,
+) -> Result<()>
+where
+    C: ConnectionLike + Send,
+{
+    // ---
+    let json = serde_json::to_string(claims).context("Failed to serialize magic link claims")?;
+
+    redis_conn
+        .set_ex::<_, _, ()>(keys::magic_link(token), json, ttl_seconds as u64)
+        .await
+        .context("Failed to store magic link")
+}
+
+/// Consume the magic link `token` and return its claims.
+///
+/// Read and deleted in one `GETDEL` (Redis 6.2+), so of concurrent
+/// redemptions only one gets the claims.
+///
+/// # Returns
+///
+/// The claims, or `None` if the link was never issued, has expired, or was
+/// already redeemed.
+///
+/// # Errors
+///
+/// Returns an error if Redis cannot be reached or the stored claims do not
+/// parse.
+pub async fn redeem_magic_link<C>(redis_conn: &mut C, token: &str) -> Result<Option<Claims>>
+where
+    C: ConnectionLike + Send,
+{
+    // ---
+    let json: Option<String> = redis_conn
+        .get_del(keys::magic_link(token))
+        .await
+        .context("Failed to redeem magic link")?;
+
+    json.map(|json| serde_json::from_str(&json).context("Invalid magic link claims format"))
+        .transpose()
+}
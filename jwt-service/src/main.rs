@@ -10,75 +10,327 @@
 //! - Protected route demonstration
 
 use anyhow::Result;
-use axum::{
-    routing::{get, post},
-    Router,
-};
+use axum::Router;
 use jwt_service::{
-    create_redis_client, generate_token_handler, protected_routes, refresh_token_handler,
-    revoke_token_handler, validate_token_handler, AppState, Config,
+    build_router, with_service_layers, AppState, AuditLog, AuditSink, Config, MemoryStore, Store,
+    StoreBackend, SystemClock,
 };
-use std::sync::Arc;
-use tracing::info;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use std::time::Duration;
+use tokn_config::Reloadable;
+use tokn_events::{Events, LiveEvents};
+use tokn_mail::Mail;
+use tokn_ratelimit::{RateLimitLayer, RateLimiter};
+use tokn_server::{CheckFormat, Shutdown};
+use tokn_telemetry::TelemetryConfig;
+use tracing::{info, warn};
 
 // ---
 
 #[tokio::main]
 async fn main() -> Result<()> {
     // ---
-    // Initialize tracing
-    tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "jwt_service=debug,tower_http=debug".into()),
-        )
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    // `--check-config`: validate and probe, print the report, and exit
+    if let Some(format) = CheckFormat::from_args() {
+        jwt_service::check_config().await.exit(format);
+    }
 
-    // Load configuration
-    dotenvy::dotenv().ok();
-    let config = Arc::new(Config::from_env()?);
+    // Initialize tracing, OTLP export, and metrics
+    let telemetry = tokn_telemetry::init(&TelemetryConfig::from_env("jwt-service")?)?;
 
-    info!(
-        "Starting JWT service on {}:{}",
-        config.server.host, config.server.port
-    );
+    // Load configuration; `--dev` keeps tokens in memory instead of Redis
+    let config = Config::load_with_args()?;
+    telemetry.log_filter().apply(&config.log);
 
-    // Create Redis connection
-    let redis_conn = create_redis_client(&config.redis.url).await?;
-    info!("Connected to Redis at {}", config.redis.url);
+    // Fault injection (no-op unless built with `--features chaos`)
+    tokn_resilience::install_faults(&config.chaos);
+
+    let bind_addr = config.bind_address();
+    info!("Starting JWT service on {}", bind_addr);
+
+    // Stop accepting and drain in-flight requests on SIGTERM/SIGINT
+    let shutdown = Shutdown::on_signals(Duration::from_secs(config.server.drain_timeout_seconds))?;
+
+    // Signing keys; a `jwt.keys` ring is reloaded on SIGHUP
+    let keys = Reloadable::new(config.jwt.keys()?);
+    match keys.get().current_kid() {
+        Some(kid) => info!(
+            "Signing access tokens with {} key '{kid}' ({} keys in ring)",
+            keys.get().algorithm(),
+            config.jwt.keys.len()
+        ),
+        None => info!("Signing access tokens with {}", keys.get().algorithm()),
+    }
+    if config.jwt.previous_secret.is_some() {
+        info!("Also accepting access tokens signed with JWT_SECRET_PREVIOUS");
+    }
+    if let Some(stale) = config.jwt.stale_previous_secret(chrono::Utc::now()) {
+        warn!("{stale}");
+    }
+
+    if config.issuer.require_key {
+        info!(
+            "Requiring an issuer key on POST /v1/auth/token ({} in TOKEN_ISSUER_KEYS)",
+            config.issuer.static_keys().len()
+        );
+    } else {
+        warn!("POST /v1/auth/token issues tokens to any caller; set TOKEN_ISSUER_REQUIRE_KEY=true");
+    }
 
     // Create application state
+    #[cfg(feature = "redis")]
+    let redis = connect_redis(&config).await?;
+    let store = connect_store(&config).await?;
+    #[cfg(feature = "redis")]
+    let store = store.or_else(|| redis.clone().map(Store::redis));
+    let live = LiveEvents::new("jwt-service", config.events.live_buffer);
+    let events = connect_events(&config).await?.with_live(live.clone());
+    let audit = open_audit_log(&config).await?;
+    let mail = Mail::connect(&config.mail).await?;
+    info!("Sending email via {} backend", config.mail.backend);
+    let limiter = connect_rate_limiter(&config).await?;
+    let reloadable = Reloadable::new(config.clone());
     let state = AppState {
-        config: config.clone(),
-        redis: redis_conn,
+        config: reloadable.clone(),
+        store,
+        #[cfg(feature = "redis")]
+        redis,
+        events,
+        audit,
+        mail,
+        clock: SystemClock::shared(),
+        keys: keys.clone(),
+    };
+
+    // Reload on SIGHUP and POST /admin/reload
+    let reload = jwt_service::reloader(reloadable, keys, telemetry.log_filter());
+    tokn_server::reload_on_sighup(reload.clone())?;
+
+    // gRPC introspection runs alongside HTTP when configured
+    let grpc_state = state.clone();
+    let grpc = async {
+        match config.server.grpc_addr {
+            Some(addr) => jwt_service::serve_grpc(addr, grpc_state, &shutdown).await,
+            None => Ok(()),
+        }
     };
 
+    // Requests signed by the other tokn services may skip the admin token
+    if config.service_auth.key.is_some() {
+        info!("Accepting signed requests from tokn services");
+    }
+
     // Build application router
-    let app = Router::new()
-        .route("/", get(|| async { "JWT Service - Ready" }))
-        .route("/health", get(|| async { "OK" }))
-        .route("/auth/token", post(generate_token_handler))
-        .route("/auth/validate", post(validate_token_handler))
-        .route("/auth/refresh", post(refresh_token_handler))
-        .route("/auth/revoke", post(revoke_token_handler))
-        .merge(protected_routes(state.clone()))
-        .with_state(state);
+    let state_is_stateful = state.is_stateful();
+    let debug = jwt_service::debug_info(&state, &limiter);
+    let users = state.clone();
+    let rate_limit = RateLimitLayer::new(limiter)
+        .exempt("/health")
+        .exempt("/health/live")
+        .exempt("/health/ready")
+        .identify_users(move |headers| users.authenticated_user(headers));
+    let operator = Router::new()
+        .merge(tokn_server::admin_router(&config.admin, reload))
+        .merge(tokn_server::admin_events_router(&config.admin, live))
+        .merge(tokn_server::debug_router(&config.admin, debug));
+    let operator = with_service_layers(operator, &state);
+    let app = build_router(state).layer(rate_limit).merge(operator);
 
     // Start server
-    let addr = format!("{}:{}", config.server.host, config.server.port);
-    let listener = tokio::net::TcpListener::bind(&addr).await?;
-
-    info!("JWT service listening on {}", addr);
+    if !state_is_stateful {
+        info!("Running stateless: no refresh tokens or revocation");
+    }
     info!("Endpoints:");
-    info!("  POST /auth/token - Generate JWT and refresh tokens");
-    info!("  POST /auth/validate - Validate JWT token");
-    info!("  POST /auth/refresh - Refresh access token");
-    info!("  POST /auth/revoke - Revoke (blacklist) JWT token");
-    info!("  GET  /protected - Demo protected endpoint (requires valid JWT)");
+    info!("  POST /v1/auth/token - Generate JWT and refresh tokens");
+    info!("  POST /v1/auth/validate - Validate JWT token");
+    info!("  POST /v1/auth/introspect - RFC 7662 token introspection (authenticated callers)");
+    if state_is_stateful {
+        info!("  POST /v1/auth/refresh - Refresh access token");
+        info!("  POST /v1/auth/revoke - Revoke (blacklist) JWT token");
+        info!("  GET  /v1/auth/sessions/{{user_id}} - List a user's sessions");
+        info!("  DELETE /v1/auth/sessions/{{user_id}}/{{session_id}} - End a session");
+        info!("  POST /v1/auth/invalidate-user - Revoke every token of a user");
+        info!("  POST /v1/auth/ticket - Issue a single-use WebSocket/SSE ticket");
+        info!("  POST /v1/auth/ticket/redeem - Redeem a ticket for its claims");
+        info!("  POST /v1/auth/magic-link - Issue a single-use login link");
+        info!("  POST /v1/auth/magic-link/redeem - Redeem a login link for tokens");
+        info!("  POST /v1/auth/verify-email/request - Issue an email verification token");
+        info!("  POST /v1/auth/verify-email/confirm - Confirm an email address");
+        info!("  POST /v1/auth/password-reset/request - Issue a password reset token");
+        info!("  POST /v1/auth/password-reset/confirm - Consume a password reset token");
+        info!("  /v1/auth/apikeys - API keys for machine clients (requires valid JWT)");
+    }
+    info!("  GET  /v1/protected - Demo protected endpoint (requires valid JWT)");
+    if config.admin.token.is_some() {
+        info!("  POST /admin/reload - Reload configuration (requires ADMIN_TOKEN)");
+        info!("  GET  /admin/events - Live auth event stream (requires ADMIN_TOKEN)");
+        info!("  GET  /debug - Runtime diagnostics (requires ADMIN_TOKEN)");
+        if state_is_stateful {
+            info!("  /admin/blacklist - Revoked access tokens (requires ADMIN_TOKEN)");
+            info!("  /admin/issuer-keys - Token endpoint issuer keys (requires ADMIN_TOKEN)");
+        }
+    }
 
-    axum::serve(listener, app).await?;
+    let http = tokn_server::serve(
+        app,
+        &bind_addr,
+        config.server.tls.as_ref(),
+        config.server.socket_mode,
+        &shutdown,
+    );
+    tokio::try_join!(http, grpc)?;
 
+    // The router, and with it the Redis connections, is dropped by now
+    info!("Shutdown complete");
     Ok(())
 }
+
+// ---
+
+/// Connect the auth event publisher, waiting for the broker if it is still
+/// starting; disabled unless `EVENTS_BACKEND` is set.
+async fn connect_events(config: &Config) -> Result<Events> {
+    // ---
+    let events = tokn_resilience::retry(&config.startup, "event broker", || {
+        Events::connect(&config.events, "jwt-service")
+    })
+    .await?;
+    if events.is_enabled() {
+        info!(
+            "Publishing auth events to {} topic '{}'",
+            config.events.backend, config.events.topic
+        );
+    }
+
+    Ok(events)
+}
+
+// ---
+
+/// Open the audit log, waiting for Redis if it is still starting; disabled
+/// unless `AUDIT_SINK` is set.
+async fn open_audit_log(config: &Config) -> Result<AuditLog> {
+    // ---
+    let audit = tokn_resilience::retry(&config.startup, "audit log", || {
+        AuditLog::open(&config.audit, &config.redis.url)
+    })
+    .await?;
+    match (&config.audit.sink, &config.audit.file) {
+        (AuditSink::File, Some(path)) => info!("Writing audit log to {}", path.display()),
+        (AuditSink::Redis, _) => {
+            info!(
+                "Writing audit log to Redis stream '{}'",
+                config.audit.stream
+            );
+        }
+        _ => {}
+    }
+
+    Ok(audit)
+}
+
+// ---
+
+/// Connect the request rate limiter, waiting for Redis if it is still
+/// starting; disabled unless `RATE_LIMIT_ENABLED` is set.
+async fn connect_rate_limiter(config: &Config) -> Result<RateLimiter> {
+    // ---
+    let limiter = tokn_resilience::retry(&config.startup, "Redis (rate limiting)", || {
+        RateLimiter::connect(
+            "jwt-service",
+            &config.rate_limit,
+            &config.redis.url,
+            config.circuit_breaker,
+            SystemClock::shared(),
+        )
+    })
+    .await?;
+    if limiter.is_enabled() {
+        info!(
+            "Rate limiting to {} requests per {}s per {} ({})",
+            config.rate_limit.requests,
+            config.rate_limit.window_seconds,
+            config.rate_limit.key_by,
+            config.rate_limit.algorithm
+        );
+        for (path, requests) in &config.rate_limit.endpoints {
+            info!("  {path}: {requests} requests per window");
+        }
+    }
+
+    Ok(limiter)
+}
+
+// ---
+
+/// Open the token store `TOKEN_STORE` selects, waiting for Postgres if it is
+/// still starting; `None` when configured stateless, and for the Redis store,
+/// which is built over the connection from [`connect_redis`].
+async fn connect_store(config: &Config) -> Result<Option<Store>> {
+    // ---
+    if config.is_stateless() {
+        return Ok(None);
+    }
+
+    let store = match config.store.backend {
+        StoreBackend::Redis => return Ok(None),
+        StoreBackend::Memory => {
+            warn!("TOKEN_STORE=memory: tokens are lost on restart and not shared between replicas");
+            let store = MemoryStore::new(SystemClock::shared());
+            store.spawn_sweeper(Duration::from_secs(config.store.sweep_interval_seconds));
+            Store::new(store, StoreBackend::Memory)
+        }
+        StoreBackend::Postgres => connect_postgres_store(config).await?,
+    };
+    info!(
+        "Keeping refresh tokens and revocations in {}",
+        store.backend()
+    );
+
+    Ok(Some(store))
+}
+
+/// Connect to the Postgres token store, applying its migrations, and start
+/// the job deleting its expired rows.
+#[cfg(feature = "postgres")]
+async fn connect_postgres_store(config: &Config) -> Result<Store> {
+    // ---
+    let Some(url) = &config.store.database_url else {
+        anyhow::bail!("TOKEN_STORE=postgres requires TOKEN_STORE_DATABASE_URL");
+    };
+    let store = tokn_resilience::retry(&config.startup, "Postgres", || {
+        jwt_service::PostgresStore::connect(url.expose(), SystemClock::shared())
+    })
+    .await?;
+    jwt_service::scheduler(&config.scheduler, &store, SystemClock::shared()).start();
+
+    Ok(Store::new(store, StoreBackend::Postgres))
+}
+
+#[cfg(not(feature = "postgres"))]
+async fn connect_postgres_store(_config: &Config) -> Result<Store> {
+    // ---
+    anyhow::bail!("TOKEN_STORE=postgres requires the `postgres` feature")
+}
+
+// ---
+
+/// Connect to Redis, waiting for it if it is still starting; `None` when
+/// configured stateless or keeping tokens in another store.
+#[cfg(feature = "redis")]
+async fn connect_redis(config: &Config) -> Result<Option<jwt_service::RedisConnection>> {
+    // ---
+    if config.is_stateless() || config.store.backend != StoreBackend::Redis {
+        return Ok(None);
+    }
+
+    let conn = tokn_resilience::retry(&config.startup, "Redis", || {
+        jwt_service::create_redis_client(&config.redis.url)
+    })
+    .await?;
+    info!("Connected to Redis at {}", config.redis.url);
+
+    Ok(Some(
+        jwt_service::RedisConnection::new(conn, config.circuit_breaker)
+            .with_retry(config.redis_retry),
+    ))
+}
@@ -2,11 +2,49 @@
 
 //! Redis client for refresh token storage
 //!
-//! Manages Redis connections for storing and retrieving refresh tokens.
+//! Manages Redis connections for storing and retrieving refresh tokens, with
+//! bounded retries and a circuit breaker around every command.
 
 use anyhow::{Context, Result};
-use redis::aio::ConnectionManager;
-use redis::Client;
+use redis::aio::{ConnectionLike, ConnectionManager};
+use redis::{Arg, Client, Cmd, ErrorKind, Pipeline, RedisError, RedisFuture, Value};
+use tokn_resilience::{
+    retry_call, BreakerState, CallRetryPolicy, CircuitBreaker, CircuitBreakerConfig,
+    CircuitBreakerError,
+};
+
+// ---
+
+/// Commands that only read, so sending one again after a dropped connection
+/// or a timeout cannot apply anything twice.
+const READ_ONLY_COMMANDS: &[&str] = &[
+    "EXISTS",
+    "GET",
+    "HGET",
+    "HGETALL",
+    "HLEN",
+    "HMGET",
+    "HSCAN",
+    "LLEN",
+    "LRANGE",
+    "MGET",
+    "PING",
+    "PTTL",
+    "SCAN",
+    "SCARD",
+    "SISMEMBER",
+    "SMEMBERS",
+    "SSCAN",
+    "TTL",
+    "TYPE",
+    "XLEN",
+    "XRANGE",
+    "XREVRANGE",
+    "ZCARD",
+    "ZRANGE",
+    "ZRANGEBYSCORE",
+    "ZSCORE",
+];
 
 // ---
 
@@ -47,3 +85,166 @@ pub async fn create_redis_client(redis_url: &str) -> Result<ConnectionManager> {
         .await
         .context("Failed to connect to Redis")
 }
+
+// ---
+
+/// Redis connection guarded by retries and a circuit breaker.
+///
+/// Every command goes through a [`CircuitBreaker`] named `redis`: once Redis has
+/// failed (or exceeded the call timeout) `failure_threshold` times in a row,
+/// commands fail immediately with an I/O error instead of waiting on a dead
+/// server. Before that, a failed command is retried with backoff
+/// ([`CallRetryPolicy`]) when sending it again is safe: it never reached Redis
+/// (connection refused), or it only reads. Writes that may have been applied
+/// are not repeated, so a refresh token is never consumed twice.
+///
+/// Implements [`ConnectionLike`], so the refresh and revocation helpers use it
+/// exactly like a `ConnectionManager`; clones share the connection and the
+/// breaker.
+#[derive(Clone)]
+pub struct RedisConnection {
+    // ---
+    inner: ConnectionManager,
+    breaker: CircuitBreaker,
+    retry: CallRetryPolicy,
+}
+
+// ---
+
+impl RedisConnection {
+    // ---
+    /// Wrap `inner` with a breaker configured by `config`, retrying with the
+    /// default [`CallRetryPolicy`].
+    pub fn new(inner: ConnectionManager, config: CircuitBreakerConfig) -> Self {
+        // ---
+        Self {
+            inner,
+            breaker: CircuitBreaker::new("redis", config),
+            retry: CallRetryPolicy::default(),
+        }
+    }
+
+    /// Retry failed commands with `policy` instead of the default.
+    pub fn with_retry(mut self, policy: CallRetryPolicy) -> Self {
+        // ---
+        self.retry = policy;
+        self
+    }
+
+    // ---
+    /// The breaker guarding this connection.
+    pub fn breaker(&self) -> &CircuitBreaker {
+        // ---
+        &self.breaker
+    }
+
+    /// Why Redis counts as degraded, if it does: the breaker is failing
+    /// commands fast (open) or trying Redis again (half-open).
+    pub fn degraded(&self) -> Option<String> {
+        // ---
+        match self.breaker.state() {
+            BreakerState::Closed => None,
+            state => Some(format!("circuit breaker {}", state.as_str())),
+        }
+    }
+
+    /// `PING` Redis directly, bypassing the breaker and retries, so health
+    /// checks see Redis itself rather than the breaker's view of it.
+    ///
+    /// # Errors
+    ///
+    /// Returns the Redis error if the server does not answer.
+    pub async fn ping(&self) -> redis::RedisResult<()> {
+        // ---
+        let mut conn = self.inner.clone();
+        let _pong: String = redis::cmd("PING").query_async(&mut conn).await?;
+        Ok(())
+    }
+}
+
+// ---
+
+impl ConnectionLike for RedisConnection {
+    // ---
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
+        // ---
+        let (inner, breaker, retry) = (&self.inner, &self.breaker, self.retry);
+        let read_only = is_read_only(cmd);
+        Box::pin(async move {
+            let retryable =
+                |error: &CircuitBreakerError<RedisError>| is_transient(error, read_only);
+            retry_call(&retry, "redis", retryable, || {
+                let mut conn = inner.clone();
+                async move { breaker.call(conn.req_packed_command(cmd)).await }
+            })
+            .await
+            .map_err(into_redis_error)
+        })
+    }
+
+    fn req_packed_commands<'a>(
+        &'a mut self,
+        cmd: &'a Pipeline,
+        offset: usize,
+        count: usize,
+    ) -> RedisFuture<'a, Vec<Value>> {
+        // ---
+        let (inner, breaker, retry) = (&self.inner, &self.breaker, self.retry);
+        Box::pin(async move {
+            // A pipeline may mix reads and writes; only resend what never arrived
+            let retryable = |error: &CircuitBreakerError<RedisError>| is_transient(error, false);
+            retry_call(&retry, "redis", retryable, || {
+                let mut conn = inner.clone();
+                async move {
+                    breaker
+                        .call(conn.req_packed_commands(cmd, offset, count))
+                        .await
+                }
+            })
+            .await
+            .map_err(into_redis_error)
+        })
+    }
+
+    fn get_db(&self) -> i64 {
+        // ---
+        self.inner.get_db()
+    }
+}
+
+// ---
+
+/// Whether `cmd` is one of [`READ_ONLY_COMMANDS`].
+fn is_read_only(cmd: &Cmd) -> bool {
+    // ---
+    match cmd.args_iter().next() {
+        Some(Arg::Simple(name)) => READ_ONLY_COMMANDS
+            .iter()
+            .any(|read| name.eq_ignore_ascii_case(read.as_bytes())),
+        _ => false,
+    }
+}
+
+/// Whether a command that failed with `error` may be sent again: it never
+/// reached Redis, or it is `read_only` and the failure was transient. An open
+/// breaker is not retried; failing fast is its point.
+fn is_transient(error: &CircuitBreakerError<RedisError>, read_only: bool) -> bool {
+    // ---
+    match error {
+        CircuitBreakerError::Open(_) => false,
+        CircuitBreakerError::Injected(_) => true,
+        CircuitBreakerError::Timeout { .. } => read_only,
+        CircuitBreakerError::Inner(e) if e.is_connection_refusal() => true,
+        CircuitBreakerError::Inner(e) => {
+            read_only && (e.is_timeout() || e.is_connection_dropped() || e.is_io_error())
+        }
+    }
+}
+
+fn into_redis_error(error: CircuitBreakerError<RedisError>) -> RedisError {
+    // ---
+    match error {
+        CircuitBreakerError::Inner(error) => error,
+        other => RedisError::from((ErrorKind::IoError, "Redis unavailable", other.to_string())),
+    }
+}
@@ -1,29 +1,23 @@
 // jwt-service/src/refresh.rs
 
-//! Refresh token generation and management
+//! Refresh token generation and management in Redis
 //!
 //! Handles creation, storage, validation, and rotation of refresh tokens.
+//! [`RedisStore`](crate::RedisStore) serves these to the handlers as a
+//! [`TokenStore`](crate::TokenStore).
 
-use anyhow::{Context, Result};
-use redis::aio::ConnectionManager;
-use redis::{AsyncCommands, RedisError};
-use serde::{Deserialize, Serialize};
+use anyhow::{bail, Context, Result};
+use redis::aio::ConnectionLike;
+use redis::AsyncCommands;
+use serde_json::Map;
+use std::collections::HashMap;
+use tokn_core::keys;
 use uuid::Uuid;
 
 // ---
 
-/// Refresh token metadata stored in Redis.
-///
-/// Contains information needed to issue a new access token.
-#[derive(Debug, Serialize, Deserialize)]
-pub struct RefreshTokenData {
-    // ---
-    /// User ID this refresh token belongs to
-    pub user_id: String,
-
-    /// User email for claims generation
-    pub email: String,
-}
+use crate::store::{open_successor, seal_successor};
+use crate::{RefreshTokenData, RefreshTokenEntry};
 
 // ---
 
@@ -33,9 +27,10 @@ pub struct RefreshTokenData {
 ///
 /// # Arguments
 ///
-/// - `redis_conn` - Multiplexed Redis connection
-/// - `user_id` - User identifier
-/// - `email` - User email address
+/// - `redis_conn` - Redis connection (typically a `ConnectionManager`)
+/// - `token_data` - User, roles, scope, and custom claims to carry into
+///   refreshed access tokens, and the session's timing (usually from
+///   [`RefreshTokenData::renew`])
 /// - `expiry_seconds` - Token expiry duration (e.g., 604800 = 7 days)
 ///
 /// # Returns
@@ -44,13 +39,24 @@ pub struct RefreshTokenData {
 ///
 /// # Storage Format
 ///
-/// - Key: `refresh_token:{uuid}`
-/// - Value: JSON `{ "user_id": "...", "email": "..." }`
+/// - Key: `refresh_token:{sha256(uuid)}`, hex; the token itself is never
+///   stored
+/// - Value: JSON `RefreshTokenData`, e.g. `{ "user_id": "...", "email": "...",
+///   "roles": [...], "scope": "...", "custom_claims": {...}, "audience": [...],
+///   "session_started_at": 1703000334, "expires_at": 1703605134,
+///   "session_id": "...", "issued_at": 1703000334, "rotation_count": 2,
+///   "user_agent": "..." }`
+///   (empty and unset fields omitted)
 /// - TTL: `expiry_seconds`
+/// - Index: with a `session_id`, the token's hash is also recorded in the
+///   `user_sessions:{user_id}` hash under it, and the hash lives as long as
+///   the user's longest-lived token
 ///
 /// # Security
 ///
 /// - Tokens are cryptographically random UUIDs (v4)
+/// - Only their SHA-256 reaches Redis, so a Redis dump or read-only access
+///   does not yield tokens to replay
 /// - Tokens automatically expire via Redis TTL
 /// - Tokens are single-use (deleted on successful refresh)
 ///
@@ -61,38 +67,57 @@ pub struct RefreshTokenData {
 /// # Example
 ///
 /// ```no_run
-/// use jwt_service::{create_redis_client, generate_refresh_token};
+/// use jwt_service::{create_redis_client, generate_refresh_token, Claims, RefreshTokenData};
+/// use tokn_core::SystemClock;
 ///
 /// # async fn example() -> anyhow::Result<()> {
 /// let mut redis_conn = create_redis_client("redis://127.0.0.1:6379").await?;
+/// let claims = Claims::new("user_123".into(), "user@example.com".into(), 900, &SystemClock);
 /// let refresh_token = generate_refresh_token(
 ///     &mut redis_conn,
-///     "user_123",
-///     "user@example.com",
+///     &RefreshTokenData::from(&claims),
 ///     604800  // 7 days
 /// ).await?;
 /// # Ok(())
 /// # }
 /// ```
-pub async fn generate_refresh_token(
-    redis_conn: &mut ConnectionManager,
-    user_id: &str,
-    email: &str,
+pub async fn generate_refresh_token<C>(
+    redis_conn: &mut C,
+    token_data: &RefreshTokenData,
     expiry_seconds: i64,
-) -> Result<String> {
+) -> Result<String>
+where
+    C: ConnectionLike + Send,
+{
     // ---
     // Generate cryptographically random UUID
     let refresh_token = Uuid::new_v4().to_string();
-    let redis_key = format!("refresh_token:{}", refresh_token);
+    store_refresh_token(redis_conn, &refresh_token, token_data, expiry_seconds).await?;
 
-    // Store user data
-    let token_data = RefreshTokenData {
-        user_id: user_id.to_string(),
-        email: email.to_string(),
-    };
+    Ok(refresh_token)
+}
 
+/// Store `token_data` under `refresh_token` in Redis, as
+/// [`generate_refresh_token`] does with a token it generates.
+///
+/// # Errors
+///
+/// Returns error if Redis storage fails.
+pub async fn store_refresh_token<C>(
+    redis_conn: &mut C,
+    refresh_token: &str,
+    token_data: &RefreshTokenData,
+    expiry_seconds: i64,
+) -> Result<()>
+where
+    C: ConnectionLike + Send,
+{
+    // ---
+    let redis_key = keys::refresh_token(refresh_token);
+
+    // Store user data
     let token_json =
-        serde_json::to_string(&token_data).context("Failed to serialize refresh token data")?;
+        serde_json::to_string(token_data).context("Failed to serialize refresh token data")?;
 
     // Store with TTL
     redis_conn
@@ -100,7 +125,91 @@ pub async fn generate_refresh_token(
         .await
         .context("Failed to store refresh token in Redis")?;
 
-    Ok(refresh_token)
+    if let Some(session_id) = &token_data.session_id {
+        let index_key = keys::user_sessions(&token_data.user_id);
+        redis_conn
+            .hset::<_, _, _, ()>(
+                &index_key,
+                session_id,
+                keys::refresh_token_hash(refresh_token),
+            )
+            .await
+            .context("Failed to index refresh token")?;
+
+        // A fresh hash has no TTL (-1), so it always gets one here
+        let index_ttl: i64 = redis_conn
+            .ttl(&index_key)
+            .await
+            .context("Failed to read session index TTL")?;
+        if index_ttl < expiry_seconds {
+            redis_conn
+                .expire::<_, ()>(&index_key, expiry_seconds)
+                .await
+                .context("Failed to extend session index TTL")?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Drop the session of `token_data` from its user's index.
+async fn unindex_session<C>(redis_conn: &mut C, token_data: &RefreshTokenData) -> Result<()>
+where
+    C: ConnectionLike + Send,
+{
+    // ---
+    if let Some(session_id) = &token_data.session_id {
+        redis_conn
+            .hdel::<_, _, ()>(keys::user_sessions(&token_data.user_id), session_id)
+            .await
+            .context("Failed to unindex refresh token")?;
+    }
+    Ok(())
+}
+
+/// Find the stored entry of `refresh_token`: under its hash, or under the
+/// token itself if it was stored before tokens were hashed.
+///
+/// # Returns
+///
+/// The entry's Redis key and JSON data, or `None` if there is none.
+async fn find_refresh_token<C>(
+    redis_conn: &mut C,
+    refresh_token: &str,
+) -> Result<Option<(String, String)>>
+where
+    C: ConnectionLike + Send,
+{
+    // ---
+    let legacy = keys::legacy_refresh_token(refresh_token);
+    for redis_key in std::iter::once(keys::refresh_token(refresh_token)).chain(legacy) {
+        let token_json: Option<String> = redis_conn
+            .get(&redis_key)
+            .await
+            .context("Failed to read refresh token")?;
+        if let Some(token_json) = token_json {
+            return Ok(Some((redis_key, token_json)));
+        }
+    }
+    Ok(None)
+}
+
+/// Redis key of the entry a `user_sessions` index value points to: a token
+/// hash, or a token indexed before tokens were hashed.
+fn indexed_refresh_token(indexed: &str) -> String {
+    // ---
+    format!("{}{indexed}", keys::REFRESH_TOKEN_PREFIX)
+}
+
+/// The token hash of the entry stored under `redis_key`.
+fn stored_token_hash(redis_key: &str) -> String {
+    // ---
+    let stored = &redis_key[keys::REFRESH_TOKEN_PREFIX.len()..];
+    if keys::is_refresh_token_hash(stored) {
+        stored.to_string()
+    } else {
+        keys::refresh_token_hash(stored)
+    }
 }
 
 // ---
@@ -111,7 +220,7 @@ pub async fn generate_refresh_token(
 ///
 /// # Arguments
 ///
-/// - `redis_conn` - Multiplexed Redis connection
+/// - `redis_conn` - Redis connection (typically a `ConnectionManager`)
 /// - `refresh_token` - The refresh token UUID to validate
 ///
 /// # Returns
@@ -121,9 +230,12 @@ pub async fn generate_refresh_token(
 /// # Security - Token Rotation
 ///
 /// This function implements **refresh token rotation**:
-/// 1. Validates the token exists in Redis
+/// 1. Validates the token exists in Redis, under its hash or, if it was
+///    issued before tokens were hashed, under the token itself
 /// 2. Retrieves the user data
-/// 3. **Deletes the token** (prevents reuse)
+/// 3. **Deletes the token** (prevents reuse) and drops it from the user's
+///    session index
+/// 4. Records it as used, so [`refresh_token_reused`] can flag a replay
 ///
 /// This prevents replay attacks - if an attacker steals a refresh token,
 /// it can only be used once. The next legitimate refresh will fail,
@@ -151,18 +263,22 @@ pub async fn generate_refresh_token(
 /// # Ok(())
 /// # }
 /// ```
-pub async fn validate_refresh_token(
-    redis_conn: &mut ConnectionManager,
+pub async fn validate_refresh_token<C>(
+    redis_conn: &mut C,
     refresh_token: &str,
-) -> Result<RefreshTokenData> {
+) -> Result<RefreshTokenData>
+where
+    C: ConnectionLike + Send,
+{
     // ---
-    let redis_key = format!("refresh_token:{}", refresh_token);
+    let Some((redis_key, token_json)) = find_refresh_token(redis_conn, refresh_token).await? else {
+        bail!("Invalid or expired refresh token");
+    };
 
-    // Get token data
-    let token_json: String = redis_conn
-        .get(&redis_key)
+    let ttl_seconds: i64 = redis_conn
+        .ttl(&redis_key)
         .await
-        .map_err(|e: RedisError| anyhow::anyhow!("Invalid or expired refresh token: {}", e))?;
+        .context("Failed to read refresh token TTL")?;
 
     // Delete token (one-time use - rotation)
     redis_conn
@@ -174,5 +290,386 @@ pub async fn validate_refresh_token(
     let token_data: RefreshTokenData =
         serde_json::from_str(&token_json).context("Invalid refresh token data format")?;
 
+    // The caller re-indexes the session if it issues a successor
+    unindex_session(redis_conn, &token_data).await?;
+
+    // Remember the token was used until it would have expired, so a replay
+    // can be detected (see `refresh_token_reused`)
+    if ttl_seconds > 0 {
+        redis_conn
+            .set_ex::<_, _, ()>(
+                keys::used_refresh_token(refresh_token),
+                &token_json,
+                ttl_seconds as u64,
+            )
+            .await
+            .context("Failed to mark refresh token as used")?;
+    }
+
     Ok(token_data)
 }
+
+// ---
+
+/// Revoke a refresh token by deleting it from Redis.
+///
+/// Unlike [`validate_refresh_token`], no used-token marker is left behind: a
+/// revoked token presented again is simply unknown, not a replay.
+///
+/// # Returns
+///
+/// The user data of the revoked token, or `None` if it was never issued, has
+/// expired, or was already consumed.
+///
+/// # Errors
+///
+/// Returns error if a Redis command fails.
+pub async fn revoke_refresh_token<C>(
+    redis_conn: &mut C,
+    refresh_token: &str,
+) -> Result<Option<RefreshTokenData>>
+where
+    C: ConnectionLike + Send,
+{
+    // ---
+    match find_refresh_token(redis_conn, refresh_token).await? {
+        Some((redis_key, token_json)) => {
+            delete_refresh_token(redis_conn, &redis_key, &token_json).await
+        }
+        None => Ok(None),
+    }
+}
+
+/// Delete the refresh token entry under `redis_key`, holding `token_json`,
+/// and drop it from its user's session index.
+async fn delete_refresh_token<C>(
+    redis_conn: &mut C,
+    redis_key: &str,
+    token_json: &str,
+) -> Result<Option<RefreshTokenData>>
+where
+    C: ConnectionLike + Send,
+{
+    // ---
+    redis_conn
+        .del::<_, ()>(redis_key)
+        .await
+        .context("Failed to delete refresh token")?;
+
+    let token_data =
+        serde_json::from_str(token_json).context("Invalid refresh token data format")?;
+    unindex_session(redis_conn, &token_data).await?;
+    Ok(Some(token_data))
+}
+
+// ---
+
+/// Check whether `refresh_token` was already consumed by rotation.
+///
+/// Call after [`validate_refresh_token`] rejects a token: a rotated token
+/// being presented again means two parties hold it, typically because it was
+/// stolen. Tokens are remembered until they would have expired.
+///
+/// # Returns
+///
+/// The user the token belonged to, or `None` if the token was never issued or
+/// has expired. Tokens rotated before markers carried the email come back
+/// with an empty `email`. Markers of tokens rotated before tokens were hashed
+/// are found too.
+///
+/// # Errors
+///
+/// Returns error if Redis cannot be queried.
+pub async fn refresh_token_reused<C>(
+    redis_conn: &mut C,
+    refresh_token: &str,
+) -> Result<Option<RefreshTokenData>>
+where
+    C: ConnectionLike + Send,
+{
+    // ---
+    let mut marker: Option<String> = None;
+    let legacy = keys::legacy_used_refresh_token(refresh_token);
+    for redis_key in std::iter::once(keys::used_refresh_token(refresh_token)).chain(legacy) {
+        marker = redis_conn
+            .get(redis_key)
+            .await
+            .context("Failed to check refresh token reuse")?;
+        if marker.is_some() {
+            break;
+        }
+    }
+
+    // Older markers hold only the user ID
+    Ok(marker.map(|marker| {
+        serde_json::from_str(&marker).unwrap_or(RefreshTokenData {
+            user_id: marker,
+            email: String::new(),
+            roles: Vec::new(),
+            scope: None,
+            custom_claims: Map::new(),
+            audience: Vec::new(),
+            client_id: None,
+            session_started_at: None,
+            expires_at: None,
+            session_id: None,
+            issued_at: None,
+            rotation_count: 0,
+            user_agent: None,
+            ip: None,
+            device_id: None,
+            dpop_jkt: None,
+        })
+    }))
+}
+
+// ---
+
+/// Remember for `ttl_seconds` that `refresh_token` was rotated into
+/// `successor`, for the refresh grace window (see [`refresh_successor`]).
+///
+/// The successor is stored sealed with `refresh_token`, of which Redis only
+/// holds the hash, so the entry yields nothing without the rotated token.
+///
+/// # Errors
+///
+/// Returns error if Redis storage fails.
+pub async fn store_refresh_successor<C>(
+    redis_conn: &mut C,
+    refresh_token: &str,
+    successor: &str,
+    ttl_seconds: i64,
+) -> Result<()>
+where
+    C: ConnectionLike + Send,
+{
+    // ---
+    redis_conn
+        .set_ex::<_, _, ()>(
+            keys::refresh_successor(refresh_token),
+            seal_successor(refresh_token, successor)?,
+            ttl_seconds as u64,
+        )
+        .await
+        .context("Failed to store refresh token successor")
+}
+
+/// The token `refresh_token` was rotated into and its data, while the grace
+/// window set by [`store_refresh_successor`] lasts.
+///
+/// # Returns
+///
+/// `None` once the window has passed, or if the successor was consumed or
+/// revoked since.
+///
+/// # Errors
+///
+/// Returns error if Redis cannot be queried.
+pub async fn refresh_successor<C>(
+    redis_conn: &mut C,
+    refresh_token: &str,
+) -> Result<Option<(String, RefreshTokenData)>>
+where
+    C: ConnectionLike + Send,
+{
+    // ---
+    let sealed: Option<String> = redis_conn
+        .get(keys::refresh_successor(refresh_token))
+        .await
+        .context("Failed to read refresh token successor")?;
+    let Some(successor) = sealed.and_then(|sealed| open_successor(refresh_token, &sealed)) else {
+        return Ok(None);
+    };
+    let Some((_, token_json)) = find_refresh_token(redis_conn, &successor).await? else {
+        return Ok(None);
+    };
+
+    let data = serde_json::from_str(&token_json).context("Invalid refresh token data format")?;
+    Ok(Some((successor, data)))
+}
+
+// ---
+
+/// List the live refresh tokens belonging to `user_id`.
+///
+/// Refresh tokens are keyed by token, not by user, so this walks every
+/// `refresh_token:*` key with `SCAN`. Meant for occasional operator use
+/// (`tokn-admin sessions list`), not request paths; unlike
+/// [`list_user_sessions`] it also finds tokens stored before sessions were
+/// indexed.
+///
+/// Tokens that expire or are consumed during the scan are skipped.
+///
+/// # Errors
+///
+/// Returns error if a Redis command fails.
+///
+/// # Example
+///
+/// ```no_run
+/// use jwt_service::{create_redis_client, list_refresh_tokens};
+///
+/// # async fn example() -> anyhow::Result<()> {
+/// let mut redis_conn = create_redis_client("redis://127.0.0.1:6379").await?;
+/// for entry in list_refresh_tokens(&mut redis_conn, "user_123").await? {
+///     println!("{} expires in {}s", entry.token_hash, entry.ttl_seconds);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub async fn list_refresh_tokens<C>(
+    redis_conn: &mut C,
+    user_id: &str,
+) -> Result<Vec<RefreshTokenEntry>>
+where
+    C: ConnectionLike + Send,
+{
+    // ---
+    let pattern = format!("{}*", keys::REFRESH_TOKEN_PREFIX);
+    let mut redis_keys = Vec::new();
+    {
+        let mut iter = redis_conn
+            .scan_match::<_, String>(&pattern)
+            .await
+            .context("Failed to scan refresh tokens")?;
+        while let Some(key) = iter.next_item().await {
+            redis_keys.push(key);
+        }
+    }
+
+    let mut entries = Vec::new();
+    for redis_key in redis_keys {
+        let token_json: Option<String> = redis_conn
+            .get(&redis_key)
+            .await
+            .context("Failed to read refresh token")?;
+        let Some(token_json) = token_json else {
+            continue;
+        };
+
+        let data: RefreshTokenData =
+            serde_json::from_str(&token_json).context("Invalid refresh token data format")?;
+        if data.user_id != user_id {
+            continue;
+        }
+
+        let ttl_seconds: i64 = redis_conn
+            .ttl(&redis_key)
+            .await
+            .context("Failed to read refresh token TTL")?;
+
+        entries.push(RefreshTokenEntry {
+            token_hash: stored_token_hash(&redis_key),
+            data,
+            ttl_seconds,
+        });
+    }
+
+    Ok(entries)
+}
+
+// ---
+
+/// List the sessions of `user_id` from its `user_sessions:{user_id}` index.
+///
+/// One entry per session with its current refresh token, oldest session
+/// first. Index entries whose token has expired are removed along the way.
+///
+/// # Errors
+///
+/// Returns error if a Redis command fails.
+pub async fn list_user_sessions<C>(
+    redis_conn: &mut C,
+    user_id: &str,
+) -> Result<Vec<RefreshTokenEntry>>
+where
+    C: ConnectionLike + Send,
+{
+    // ---
+    let index_key = keys::user_sessions(user_id);
+    let index: HashMap<String, String> = redis_conn
+        .hgetall(&index_key)
+        .await
+        .context("Failed to read session index")?;
+
+    let mut entries = Vec::new();
+    for (session_id, indexed) in index {
+        let redis_key = indexed_refresh_token(&indexed);
+        let token_json: Option<String> = redis_conn
+            .get(&redis_key)
+            .await
+            .context("Failed to read refresh token")?;
+        let Some(token_json) = token_json else {
+            redis_conn
+                .hdel::<_, _, ()>(&index_key, &session_id)
+                .await
+                .context("Failed to unindex refresh token")?;
+            continue;
+        };
+
+        let data: RefreshTokenData =
+            serde_json::from_str(&token_json).context("Invalid refresh token data format")?;
+        let ttl_seconds: i64 = redis_conn
+            .ttl(&redis_key)
+            .await
+            .context("Failed to read refresh token TTL")?;
+
+        entries.push(RefreshTokenEntry {
+            token_hash: stored_token_hash(&redis_key),
+            data,
+            ttl_seconds,
+        });
+    }
+
+    entries.sort_by_key(|entry| entry.data.session_started_at);
+    Ok(entries)
+}
+
+// ---
+
+/// End session `session_id` of `user_id` by revoking its current refresh
+/// token (see [`revoke_refresh_token`]).
+///
+/// # Returns
+///
+/// `true` if a live session was ended, `false` if the user has no such
+/// session or its token had already expired.
+///
+/// # Errors
+///
+/// Returns error if a Redis command fails.
+pub async fn delete_user_session<C>(
+    redis_conn: &mut C,
+    user_id: &str,
+    session_id: &str,
+) -> Result<bool>
+where
+    C: ConnectionLike + Send,
+{
+    // ---
+    let index_key = keys::user_sessions(user_id);
+    let indexed: Option<String> = redis_conn
+        .hget(&index_key, session_id)
+        .await
+        .context("Failed to read session index")?;
+    let Some(indexed) = indexed else {
+        return Ok(false);
+    };
+
+    let redis_key = indexed_refresh_token(&indexed);
+    let token_json: Option<String> = redis_conn
+        .get(&redis_key)
+        .await
+        .context("Failed to read refresh token")?;
+    let revoked = match token_json {
+        Some(token_json) => delete_refresh_token(redis_conn, &redis_key, &token_json).await?,
+        None => None,
+    };
+
+    // Also drops entries whose token expired on its own
+    redis_conn
+        .hdel::<_, _, ()>(&index_key, session_id)
+        .await
+        .context("Failed to unindex refresh token")?;
+    Ok(revoked.is_some())
+}
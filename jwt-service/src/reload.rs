@@ -0,0 +1,210 @@
+// jwt-service/src/reload.rs
+
+//! Configuration reload
+//!
+//! Runs on `SIGHUP` and `POST /admin/reload`. Applies `log.filter`, the
+//! token lifetimes, device binding, the validation leeway, the ticket
+//! lifetime, the magic link, email verification, and password reset
+//! settings, and the `jwt.keys` ring; every other changed section is reported as needing a
+//! restart and keeps its running value.
+
+use std::sync::Arc;
+use tokn_config::Reloadable;
+use tokn_core::JwtKeys;
+use tokn_server::{ReloadFn, ReloadReport};
+use tokn_telemetry::LogFilter;
+
+// ---
+
+use crate::{Config, JwtConfig};
+
+// ---
+
+/// Build the reload callback for `config` and the signing `keys` built
+/// from it.
+///
+/// New token lifetimes apply to tokens issued after the reload; tokens
+/// already issued keep their original expiry. A changed `jwt.keys` ring or
+/// `jwt.current_kid` replaces `keys`: new tokens are signed with the new
+/// current key, and tokens signed with any key still in the ring stay valid.
+/// A changed `jwt.validation_leeway_seconds` also replaces `keys`, applying
+/// to every validation after the reload.
+///
+/// # Errors
+///
+/// The returned callback fails, leaving everything unchanged, if the
+/// configuration no longer loads, the new key ring does not load, or the new
+/// log filter does not parse.
+pub fn reloader(
+    config: Reloadable<Config>,
+    keys: Reloadable<JwtKeys>,
+    log_filter: LogFilter,
+) -> ReloadFn {
+    // ---
+    Arc::new(move || {
+        // ---
+        let new = Config::load_with_args()?;
+        let old = config.get();
+        let mut report = ReloadReport::default();
+
+        let jwt = JwtConfig {
+            access_token_expiry_seconds: new.jwt.access_token_expiry_seconds,
+            refresh_token_expiry_seconds: new.jwt.refresh_token_expiry_seconds,
+            refresh_token_sliding: new.jwt.refresh_token_sliding,
+            max_session_seconds: new.jwt.max_session_seconds,
+            refresh_bind_device: new.jwt.refresh_bind_device,
+            refresh_grace_seconds: new.jwt.refresh_grace_seconds,
+            validation_leeway_seconds: new.jwt.validation_leeway_seconds,
+            ticket_ttl_seconds: new.jwt.ticket_ttl_seconds,
+            magic_link_ttl_seconds: new.jwt.magic_link_ttl_seconds,
+            magic_link_url: new.jwt.magic_link_url.clone(),
+            verify_email_ttl_seconds: new.jwt.verify_email_ttl_seconds,
+            verify_email_url: new.jwt.verify_email_url.clone(),
+            password_reset_ttl_seconds: new.jwt.password_reset_ttl_seconds,
+            password_reset_url: new.jwt.password_reset_url.clone(),
+            keys: new.jwt.keys.clone(),
+            current_kid: new.jwt.current_kid.clone(),
+            ..old.jwt.clone()
+        };
+
+        // Load the ring first so a bad key file changes nothing
+        let ring_changed = old.jwt.keys != jwt.keys || old.jwt.current_kid != jwt.current_kid;
+        let leeway_changed = old.jwt.validation_leeway_seconds != jwt.validation_leeway_seconds;
+        let new_keys = (ring_changed || leeway_changed)
+            .then(|| jwt.keys())
+            .transpose()?;
+
+        if report.reloadable("log.filter", &old.log.filter, &new.log.filter) {
+            log_filter.set(new.log.filter.as_deref())?;
+        }
+        if let Some(new_keys) = new_keys {
+            report.reloadable(
+                "jwt.current_kid",
+                &old.jwt.current_kid,
+                &new.jwt.current_kid,
+            );
+            report.reloadable("jwt.keys", &old.jwt.key_ids(), &new.jwt.key_ids());
+            report.reloadable(
+                "jwt.validation_leeway_seconds",
+                &old.jwt.validation_leeway_seconds,
+                &new.jwt.validation_leeway_seconds,
+            );
+            keys.set(new_keys);
+        }
+        report.reloadable(
+            "jwt.access_token_expiry_seconds",
+            &old.jwt.access_token_expiry_seconds,
+            &new.jwt.access_token_expiry_seconds,
+        );
+        report.reloadable(
+            "jwt.refresh_token_expiry_seconds",
+            &old.jwt.refresh_token_expiry_seconds,
+            &new.jwt.refresh_token_expiry_seconds,
+        );
+        report.reloadable(
+            "jwt.refresh_token_sliding",
+            &old.jwt.refresh_token_sliding,
+            &new.jwt.refresh_token_sliding,
+        );
+        report.reloadable(
+            "jwt.max_session_seconds",
+            &old.jwt.max_session_seconds,
+            &new.jwt.max_session_seconds,
+        );
+        report.reloadable(
+            "jwt.refresh_bind_device",
+            &old.jwt.refresh_bind_device,
+            &new.jwt.refresh_bind_device,
+        );
+        report.reloadable(
+            "jwt.refresh_grace_seconds",
+            &old.jwt.refresh_grace_seconds,
+            &new.jwt.refresh_grace_seconds,
+        );
+        report.reloadable(
+            "jwt.ticket_ttl_seconds",
+            &old.jwt.ticket_ttl_seconds,
+            &new.jwt.ticket_ttl_seconds,
+        );
+        report.reloadable(
+            "jwt.magic_link_ttl_seconds",
+            &old.jwt.magic_link_ttl_seconds,
+            &new.jwt.magic_link_ttl_seconds,
+        );
+        report.reloadable(
+            "jwt.magic_link_url",
+            &old.jwt.magic_link_url,
+            &new.jwt.magic_link_url,
+        );
+        report.reloadable(
+            "jwt.verify_email_ttl_seconds",
+            &old.jwt.verify_email_ttl_seconds,
+            &new.jwt.verify_email_ttl_seconds,
+        );
+        report.reloadable(
+            "jwt.verify_email_url",
+            &old.jwt.verify_email_url,
+            &new.jwt.verify_email_url,
+        );
+        report.reloadable(
+            "jwt.password_reset_ttl_seconds",
+            &old.jwt.password_reset_ttl_seconds,
+            &new.jwt.password_reset_ttl_seconds,
+        );
+        report.reloadable(
+            "jwt.password_reset_url",
+            &old.jwt.password_reset_url,
+            &new.jwt.password_reset_url,
+        );
+
+        report.restart_required("server", &old.server, &new.server);
+        report.restart_required("redis", &old.redis, &new.redis);
+        report.restart_required("jwt.algorithm", &old.jwt.algorithm, &new.jwt.algorithm);
+        report.restart_required("jwt.format", &old.jwt.format, &new.jwt.format);
+        report.restart_required(
+            "jwt.encryption_key",
+            &old.jwt.encryption_key,
+            &new.jwt.encryption_key,
+        );
+        report.restart_required("jwt.secret", &old.jwt.secret, &new.jwt.secret);
+        report.restart_required(
+            "jwt.previous_secret",
+            &old.jwt.previous_secret,
+            &new.jwt.previous_secret,
+        );
+        report.restart_required(
+            "jwt.secret_rotated_at",
+            &old.jwt.secret_rotated_at,
+            &new.jwt.secret_rotated_at,
+        );
+        report.restart_required(
+            "jwt.private_key_path",
+            &old.jwt.private_key_path,
+            &new.jwt.private_key_path,
+        );
+        report.restart_required(
+            "jwt.public_key_path",
+            &old.jwt.public_key_path,
+            &new.jwt.public_key_path,
+        );
+        report.restart_required("jwt.stateless", &old.jwt.stateless, &new.jwt.stateless);
+        report.restart_required("store", &old.store, &new.store);
+        report.restart_required("startup", &old.startup, &new.startup);
+        report.restart_required(
+            "circuit_breaker",
+            &old.circuit_breaker,
+            &new.circuit_breaker,
+        );
+        report.restart_required("redis_retry", &old.redis_retry, &new.redis_retry);
+        report.restart_required("admin", &old.admin, &new.admin);
+        report.restart_required("api", &old.api, &new.api);
+
+        config.set(Config {
+            jwt,
+            log: new.log,
+            ..(*old).clone()
+        });
+
+        Ok(report)
+    })
+}
@@ -2,11 +2,17 @@
 
 //! Token revocation (blacklisting)
 //!
-//! Manages token revocation by storing JWT IDs in Redis.
+//! Manages token revocation by storing JWT IDs in Redis, and per-user token
+//! versions that revoke every older token of a user at once.
 
 use anyhow::{Context, Result};
-use redis::aio::ConnectionManager;
+use redis::aio::ConnectionLike;
 use redis::AsyncCommands;
+use tokn_core::keys;
+
+// ---
+
+use crate::{RevocationStatus, RevokedToken};
 
 // ---
 
@@ -18,7 +24,7 @@ use redis::AsyncCommands;
 ///
 /// # Arguments
 ///
-/// - `redis_conn` - Redis connection manager
+/// - `redis_conn` - Redis connection (typically a `ConnectionManager`)
 /// - `jti` - JWT ID (unique identifier from token claims)
 /// - `expiry_seconds` - Token's remaining TTL (time until expiration)
 ///
@@ -52,13 +58,12 @@ use redis::AsyncCommands;
 /// # Ok(())
 /// # }
 /// ```
-pub async fn revoke_token(
-    redis_conn: &mut ConnectionManager,
-    jti: &str,
-    expiry_seconds: i64,
-) -> Result<()> {
+pub async fn revoke_token<C>(redis_conn: &mut C, jti: &str, expiry_seconds: i64) -> Result<()>
+where
+    C: ConnectionLike + Send,
+{
     // ---
-    let redis_key = format!("blacklist:jti:{}", jti);
+    let redis_key = keys::blacklisted_jti(jti);
 
     // Store with TTL matching token expiry
     redis_conn
@@ -77,7 +82,7 @@ pub async fn revoke_token(
 ///
 /// # Arguments
 ///
-/// - `redis_conn` - Redis connection manager
+/// - `redis_conn` - Redis connection (typically a `ConnectionManager`)
 /// - `jti` - JWT ID to check
 ///
 /// # Returns
@@ -103,9 +108,12 @@ pub async fn revoke_token(
 /// # Ok(())
 /// # }
 /// ```
-pub async fn is_token_revoked(redis_conn: &mut ConnectionManager, jti: &str) -> Result<bool> {
+pub async fn is_token_revoked<C>(redis_conn: &mut C, jti: &str) -> Result<bool>
+where
+    C: ConnectionLike + Send,
+{
     // ---
-    let redis_key = format!("blacklist:jti:{}", jti);
+    let redis_key = keys::blacklisted_jti(jti);
 
     // Check if key exists
     let exists: bool = redis_conn
@@ -115,3 +123,235 @@ pub async fn is_token_revoked(redis_conn: &mut ConnectionManager, jti: &str) ->
 
     Ok(exists)
 }
+
+// ---
+
+/// Seconds until the blacklist entry of `jti` expires; `None` if it is not
+/// blacklisted.
+///
+/// # Errors
+///
+/// Returns an error if Redis cannot be queried.
+pub async fn revoked_token_ttl<C>(redis_conn: &mut C, jti: &str) -> Result<Option<i64>>
+where
+    C: ConnectionLike + Send,
+{
+    // ---
+    let ttl: i64 = redis_conn
+        .ttl(keys::blacklisted_jti(jti))
+        .await
+        .context("Failed to read revoked token TTL")?;
+
+    // -2: no such key; -1: no expiry, which `revoke_token` never stores
+    Ok((ttl != -2).then_some(ttl))
+}
+
+/// Up to `limit` blacklisted access tokens, found by scanning the
+/// `blacklist:jti:*` keys with `SCAN`, in no particular order. Meant for
+/// operators, not request paths.
+///
+/// Entries that expire during the scan are skipped.
+///
+/// # Errors
+///
+/// Returns an error if a Redis command fails.
+pub async fn list_revoked_tokens<C>(redis_conn: &mut C, limit: usize) -> Result<Vec<RevokedToken>>
+where
+    C: ConnectionLike + Send,
+{
+    // ---
+    let pattern = format!("{}*", keys::BLACKLIST_JTI_PREFIX);
+    let mut redis_keys = Vec::new();
+    {
+        let mut iter = redis_conn
+            .scan_match::<_, String>(&pattern)
+            .await
+            .context("Failed to scan revoked tokens")?;
+        while let Some(key) = iter.next_item().await {
+            redis_keys.push(key);
+            if redis_keys.len() == limit {
+                break;
+            }
+        }
+    }
+
+    let mut revoked = Vec::with_capacity(redis_keys.len());
+    for redis_key in redis_keys {
+        let jti = &redis_key[keys::BLACKLIST_JTI_PREFIX.len()..];
+        if let Some(ttl_seconds) = revoked_token_ttl(redis_conn, jti).await? {
+            revoked.push(RevokedToken {
+                jti: jti.to_string(),
+                ttl_seconds,
+            });
+        }
+    }
+    Ok(revoked)
+}
+
+/// Take the access token `jti` off the blacklist; `false` if it was not on
+/// it.
+///
+/// # Errors
+///
+/// Returns an error if Redis cannot be written.
+pub async fn unrevoke_token<C>(redis_conn: &mut C, jti: &str) -> Result<bool>
+where
+    C: ConnectionLike + Send,
+{
+    // ---
+    let deleted: u64 = redis_conn
+        .del(keys::blacklisted_jti(jti))
+        .await
+        .context("Failed to unrevoke token")?;
+
+    Ok(deleted > 0)
+}
+
+/// Mark the single-use token `jti` used for `expiry_seconds` unless it
+/// already is, returning whether this call did. Of concurrent calls, only
+/// the first `SET NX` succeeds.
+///
+/// The mark lives under `consumed:jti:{jti}`, not on the blacklist, so
+/// [`list_revoked_tokens`] does not show it and [`unrevoke_token`] cannot
+/// make the token usable again.
+///
+/// # Errors
+///
+/// Returns an error if Redis cannot be written.
+pub async fn consume_token<C>(redis_conn: &mut C, jti: &str, expiry_seconds: i64) -> Result<bool>
+where
+    C: ConnectionLike + Send,
+{
+    // ---
+    let stored: Option<String> = redis::cmd("SET")
+        .arg(keys::consumed_jti(jti))
+        .arg("consumed")
+        .arg("NX")
+        .arg("EX")
+        .arg(expiry_seconds.max(1))
+        .query_async(redis_conn)
+        .await
+        .context("Failed to consume token in Redis")?;
+
+    Ok(stored.is_some())
+}
+
+// ---
+
+/// The token version of `user_id`: access tokens stamped with an older `ver`
+/// claim are revoked. `0` until first bumped.
+///
+/// # Storage Format
+///
+/// - Key: `token_version:{user_id}`
+/// - Value: the version, incremented by [`bump_token_version`]
+/// - TTL: none, so older tokens stay revoked for their whole lifetime
+///
+/// # Errors
+///
+/// Returns an error if Redis cannot be queried.
+pub async fn token_version<C>(redis_conn: &mut C, user_id: &str) -> Result<u64>
+where
+    C: ConnectionLike + Send,
+{
+    // ---
+    let version: Option<u64> = redis_conn
+        .get(keys::token_version(user_id))
+        .await
+        .context("Failed to read token version")?;
+
+    Ok(version.unwrap_or(0))
+}
+
+/// Bump the token version of `user_id`, revoking every access token issued
+/// to the user so far, and return the new version.
+///
+/// # Errors
+///
+/// Returns an error if Redis cannot be written.
+pub async fn bump_token_version<C>(redis_conn: &mut C, user_id: &str) -> Result<u64>
+where
+    C: ConnectionLike + Send,
+{
+    // ---
+    redis_conn
+        .incr(keys::token_version(user_id), 1u64)
+        .await
+        .context("Failed to bump token version")
+}
+
+// ---
+
+/// The revocation cutoff of `user_id`: tokens issued (`iat`) before it are
+/// revoked. `None` until set.
+///
+/// # Storage Format
+///
+/// - Key: `revoked_before:{user_id}`
+/// - Value: Unix timestamp, set by [`set_revoked_before`]
+/// - TTL: none, so older tokens stay revoked for their whole lifetime
+///
+/// # Errors
+///
+/// Returns an error if Redis cannot be queried.
+pub async fn revoked_before<C>(redis_conn: &mut C, user_id: &str) -> Result<Option<i64>>
+where
+    C: ConnectionLike + Send,
+{
+    // ---
+    redis_conn
+        .get(keys::revoked_before(user_id))
+        .await
+        .context("Failed to read revocation cutoff")
+}
+
+/// Set the revocation cutoff of `user_id` to `timestamp`, replacing any
+/// earlier one.
+///
+/// # Errors
+///
+/// Returns an error if Redis cannot be written.
+pub async fn set_revoked_before<C>(redis_conn: &mut C, user_id: &str, timestamp: i64) -> Result<()>
+where
+    C: ConnectionLike + Send,
+{
+    // ---
+    redis_conn
+        .set(keys::revoked_before(user_id), timestamp)
+        .await
+        .context("Failed to set revocation cutoff")
+}
+
+// ---
+
+/// Whether `jti` is blacklisted, with the token version and revocation
+/// cutoff of `user_id`, read in one pipelined round trip: the checks every
+/// validation makes.
+///
+/// # Errors
+///
+/// Returns an error if Redis cannot be queried.
+pub async fn revocation_status<C>(
+    redis_conn: &mut C,
+    jti: &str,
+    user_id: &str,
+) -> Result<RevocationStatus>
+where
+    C: ConnectionLike + Send,
+{
+    // ---
+    let (blacklisted, token_version, revoked_before): (bool, Option<u64>, Option<i64>) =
+        redis::pipe()
+            .exists(keys::blacklisted_jti(jti))
+            .get(keys::token_version(user_id))
+            .get(keys::revoked_before(user_id))
+            .query_async(redis_conn)
+            .await
+            .context("Failed to check token revocation status")?;
+
+    Ok(RevocationStatus {
+        blacklisted,
+        token_version: token_version.unwrap_or(0),
+        revoked_before,
+    })
+}
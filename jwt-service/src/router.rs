@@ -0,0 +1,163 @@
+// jwt-service/src/router.rs
+
+//! HTTP router construction
+//!
+//! Builds the complete jwt-service route table so the binary and in-process
+//! test harnesses serve exactly the same application.
+
+use crate::{
+    generate_token_handler, introspect_token_handler, protected_routes, validate_token_handler,
+    AppState,
+};
+use axum::{
+    middleware,
+    routing::{get, post},
+    Router,
+};
+use tokn_server::{SecurityHeaders, ServiceAuth};
+
+// ---
+
+/// Build the jwt-service application router.
+///
+/// # Routes
+///
+/// - `GET  /` - Service banner
+/// - `GET  /health` - Liveness check (plain `OK`)
+/// - `GET  /health/live` - Liveness check (JSON)
+/// - `GET  /health/ready` - Readiness: probes Redis or Postgres when
+///   stateful (see [`tokn_server::health_router`])
+/// - `POST /v1/auth/token` - Generate JWT and refresh tokens
+/// - `POST /v1/auth/validate` - Validate JWT token
+/// - `POST /v1/auth/introspect` - RFC 7662 token introspection (requires a signed
+///   request, an issuer key, or `ADMIN_TOKEN`)
+/// - `POST /v1/auth/refresh` - Refresh access token (not routed when stateless)
+/// - `POST /v1/auth/revoke` - Revoke (blacklist) JWT token (not routed when stateless)
+/// - `GET  /v1/auth/sessions/{user_id}` - List a user's sessions (requires valid JWT;
+///   not routed when stateless)
+/// - `DELETE /v1/auth/sessions/{user_id}/{session_id}` - End a session (requires valid
+///   JWT; not routed when stateless)
+/// - `POST /v1/auth/invalidate-user` - Revoke every token of a user (requires valid
+///   JWT; not routed when stateless)
+/// - `POST /v1/auth/ticket` - Issue a single-use WebSocket/SSE ticket (requires valid
+///   JWT; not routed when stateless)
+/// - `POST /v1/auth/ticket/redeem` - Redeem a ticket for its claims (not routed when
+///   stateless)
+/// - `POST /v1/auth/magic-link` - Issue a single-use login link (not routed when
+///   stateless)
+/// - `POST /v1/auth/magic-link/redeem` - Redeem a login link for tokens (not routed
+///   when stateless)
+/// - `POST /v1/auth/verify-email/request` - Issue an email verification token (not
+///   routed when stateless)
+/// - `POST /v1/auth/verify-email/confirm` - Confirm an address with its token (not
+///   routed when stateless)
+/// - `POST /v1/auth/password-reset/request` - Issue a password reset token (not
+///   routed when stateless)
+/// - `POST /v1/auth/password-reset/confirm` - Consume a reset token, ending every
+///   session of its user (not routed when stateless)
+/// - `/v1/auth/apikeys` - Create, list, and delete API keys for machine clients
+///   (requires valid JWT; not routed when stateless)
+/// - `GET  /v1/protected` - Demo protected endpoint (requires valid JWT)
+/// - `/admin/issuer-keys` - Create, list, and delete the issuer keys
+///   `POST /v1/auth/token` accepts (requires `ADMIN_TOKEN`; not routed
+///   without it or when stateless)
+/// - `/admin/blacklist` - List, look up, and remove revoked access tokens
+///   (requires `ADMIN_TOKEN`; not routed without it or when stateless)
+///
+/// While `api.legacy_paths` is set, the `/v1` routes are also served without
+/// the prefix, marked deprecated; see [`tokn_server::versioned`]. Errors are
+/// RFC 7807 problem details (see [`tokn_server::problem_details`]), and every
+/// response carries the request's `X-Request-Id` (see
+/// [`tokn_server::request_id`]). Browser apps on the origins listed in
+/// `server.cors` may call every route (see [`tokn_server::cors_layer`]).
+/// Every route is wrapped in [`with_service_layers`].
+pub fn build_router(state: AppState) -> Router {
+    // ---
+    let api = Router::new()
+        .route("/auth/token", post(generate_token_handler))
+        .route("/auth/validate", post(validate_token_handler))
+        .route("/auth/introspect", post(introspect_token_handler))
+        .merge(protected_routes(state.clone()));
+
+    let api = if state.is_stateful() {
+        api.route("/auth/refresh", post(crate::refresh_token_handler))
+            .route("/auth/revoke", post(crate::revoke_token_handler))
+            .route("/auth/magic-link", post(crate::issue_magic_link_handler))
+            .route(
+                "/auth/magic-link/redeem",
+                post(crate::redeem_magic_link_handler),
+            )
+            .route(
+                "/auth/verify-email/request",
+                post(crate::request_email_verification_handler),
+            )
+            .route(
+                "/auth/verify-email/confirm",
+                post(crate::confirm_email_verification_handler),
+            )
+            .route(
+                "/auth/password-reset/request",
+                post(crate::request_password_reset_handler),
+            )
+            .route(
+                "/auth/password-reset/confirm",
+                post(crate::confirm_password_reset_handler),
+            )
+            .merge(crate::session_routes(state.clone()))
+            .merge(crate::ticket_routes(state.clone()))
+            .merge(crate::api_key_routes(state.clone()))
+    } else {
+        api
+    };
+
+    let config = state.config.get();
+    let app = Router::new()
+        .route("/", get(|| async { "JWT Service - Ready" }))
+        .route("/health", get(|| async { "OK" }))
+        .merge(tokn_server::health_router(crate::health_checks(&state)))
+        .merge(tokn_server::versioned(api, &config.api));
+
+    let app = if state.is_stateful() {
+        app.merge(crate::blacklist_routes(&state))
+            .merge(crate::revoked_before_routes(&state))
+            .merge(crate::issuer_key_routes(&state))
+    } else {
+        app
+    };
+
+    let app = app.layer(middleware::from_fn(tokn_server::problem_details));
+    let app = match tokn_server::cors_layer(&config.server.cors) {
+        Some(cors) => app.layer(cors),
+        None => app,
+    };
+
+    let app = app
+        .layer(middleware::from_fn(tokn_server::request_id))
+        .with_state(state.clone());
+    with_service_layers(app, &state)
+}
+
+/// Wrap `router` in the layers every jwt-service response passes through,
+/// innermost first: requests signed with the `service_auth` key are verified
+/// (see [`tokn_server::verify_service_signature`]), responses get the
+/// `server.security_headers` (see [`tokn_server::security_headers`]), and
+/// are compressed as `server.compression` configures (see
+/// [`tokn_server::compression_layer`]).
+///
+/// [`build_router`] applies it already; the binary applies it to the
+/// operator routes it merges in (admin, events, debug).
+pub fn with_service_layers(router: Router, state: &AppState) -> Router {
+    // ---
+    let config = state.config.get();
+    let service_auth = ServiceAuth::new("jwt-service", &config.service_auth, state.clock.clone());
+    router
+        .layer(middleware::from_fn_with_state(
+            service_auth,
+            tokn_server::verify_service_signature,
+        ))
+        .layer(middleware::from_fn_with_state(
+            SecurityHeaders::new(&config.server.security_headers),
+            tokn_server::security_headers,
+        ))
+        .layer(tokn_server::compression_layer(&config.server.compression))
+}
@@ -0,0 +1,289 @@
+// jwt-service/src/session.rs
+
+//! Refresh token sessions
+//!
+//! The data stored with each refresh token, whichever store keeps it: the
+//! user and access it carries into refreshed tokens, the session's timing
+//! across rotation, and the device it was issued to.
+
+use axum::http::{header, HeaderMap};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tokn_core::{Claims, Clock};
+use uuid::Uuid;
+
+// ---
+
+use crate::{ApiError, ClientInfo, JwtConfig};
+
+// ---
+
+/// Header carrying a client-chosen device ID, recorded with the session.
+pub const DEVICE_ID_HEADER: &str = "x-device-id";
+
+/// Longest accepted `X-Device-Id`, in bytes.
+const MAX_DEVICE_ID_LEN: usize = 128;
+
+// ---
+
+/// The client device a request comes from, as recorded with its session.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientDevice {
+    // ---
+    /// `User-Agent` header
+    pub user_agent: Option<String>,
+
+    /// Peer IP address
+    pub ip: Option<String>,
+
+    /// `X-Device-Id` header
+    pub device_id: Option<String>,
+}
+
+impl ClientDevice {
+    // ---
+    /// The device of a request with `headers` from `client`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a 400 Bad Request problem if `X-Device-Id` is empty or longer
+    /// than 128 bytes.
+    pub fn from_request(headers: &HeaderMap, client: &ClientInfo) -> Result<Self, ApiError> {
+        // ---
+        let text = |name| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+
+        let device_id = text(DEVICE_ID_HEADER);
+        if let Some(id) = &device_id {
+            if id.is_empty() || id.len() > MAX_DEVICE_ID_LEN {
+                return Err(ApiError::InvalidRequest(
+                    "X-Device-Id must be 1 to 128 characters".into(),
+                ));
+            }
+        }
+
+        Ok(Self {
+            user_agent: text(header::USER_AGENT.as_str()),
+            ip: client.ip.clone(),
+            device_id,
+        })
+    }
+}
+
+// ---
+
+/// Refresh token metadata kept by the [`TokenStore`](crate::TokenStore).
+///
+/// Contains information needed to issue a new access token.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefreshTokenData {
+    // ---
+    /// User ID this refresh token belongs to
+    pub user_id: String,
+
+    /// User email for claims generation
+    pub email: String,
+
+    /// Roles of the original token, copied into refreshed ones
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roles: Vec<String>,
+
+    /// Scope of the original token, copied into refreshed ones
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+
+    /// Custom claims of the original token, copied into refreshed ones
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub custom_claims: Map<String, Value>,
+
+    /// Audience (`aud`) of the original token, copied into refreshed ones
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub audience: Vec<String>,
+
+    /// Client (`client_id`) of the original token, copied into refreshed ones
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+
+    /// When the session began (Unix timestamp): the issue time of the first
+    /// token of the rotation chain. Unset for tokens stored before sessions
+    /// were tracked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_started_at: Option<i64>,
+
+    /// When this refresh token expires (Unix timestamp). Unset for tokens
+    /// stored before expiries were recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
+
+    /// Stable ID of the session, kept across rotation and indexed under
+    /// `user_sessions:{user_id}`. Unset for tokens stored before sessions
+    /// were indexed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+
+    /// When this refresh token was issued (Unix timestamp): the session's
+    /// last sign-in or refresh
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issued_at: Option<i64>,
+
+    /// How many times the session was refreshed: 0 for the token issued at
+    /// sign-in. Sessions begun before rotations were counted count from 0.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub rotation_count: u32,
+
+    /// `User-Agent` of the client that last signed in or refreshed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+
+    /// IP address of the client that last signed in or refreshed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ip: Option<String>,
+
+    /// `X-Device-Id` the session was started with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<String>,
+
+    /// Thumbprint of the DPoP key the session is bound to: refreshing
+    /// requires a proof signed by that key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dpop_jkt: Option<String>,
+}
+
+impl RefreshTokenData {
+    // ---
+    /// Claims for a new access token carrying this user, roles, scope,
+    /// audience, client, and custom claims (checked when the original token was
+    /// issued). The token
+    /// is not DPoP-bound; the refresh handler binds it to the proof's key.
+    pub fn claims(&self, expiry_seconds: i64, clock: &dyn Clock) -> Claims {
+        // ---
+        let mut claims = Claims::new(
+            self.user_id.clone(),
+            self.email.clone(),
+            expiry_seconds,
+            clock,
+        );
+        claims.roles = self.roles.clone();
+        claims.scope = self.scope.clone();
+        claims.aud = self.audience.clone();
+        claims.client_id = self.client_id.clone();
+        claims.custom = self.custom_claims.clone();
+        claims
+    }
+
+    /// The data and lifetime (seconds) of this session's next refresh token
+    /// at `now`, or `None` once the session is over.
+    ///
+    /// With `jwt.refresh_token_sliding` every token gets the full
+    /// `refresh_token_expiry_seconds`; without it a rotated token keeps the
+    /// expiry of the one it replaces. Either way `jwt.max_session_seconds`
+    /// caps the whole rotation chain.
+    ///
+    /// The session keeps its ID; one is assigned if it has none yet.
+    pub fn renew(&self, jwt: &JwtConfig, now: i64) -> Option<(Self, i64)> {
+        // ---
+        let mut ttl = jwt.refresh_token_expiry_seconds;
+        if let (false, Some(expires_at)) = (jwt.refresh_token_sliding, self.expires_at) {
+            ttl = expires_at - now;
+        }
+
+        let session_started_at = self.session_started_at.unwrap_or(now);
+        if let Some(max_session) = jwt.max_session_seconds {
+            ttl = ttl.min(session_started_at + max_session - now);
+        }
+        if ttl <= 0 {
+            return None;
+        }
+
+        let data = Self {
+            session_started_at: Some(session_started_at),
+            expires_at: Some(now + ttl),
+            session_id: Some(
+                self.session_id
+                    .clone()
+                    .unwrap_or_else(|| Uuid::new_v4().to_string()),
+            ),
+            issued_at: Some(now),
+            ..self.clone()
+        };
+        Some((data, ttl))
+    }
+
+    // ---
+    /// Record `device` as the one that last used the session. A device ID
+    /// given at sign-in is kept when later requests omit it.
+    pub fn record_device(&mut self, device: &ClientDevice) {
+        // ---
+        self.user_agent = device.user_agent.clone();
+        self.ip = device.ip.clone();
+        if device.device_id.is_some() {
+            self.device_id = device.device_id.clone();
+        }
+    }
+
+    /// Whether `device` is the one the session belongs to, by its device ID
+    /// if it was started with one, else by `User-Agent`. IP addresses are
+    /// not compared, as they change with the network. Sessions recorded
+    /// without either match any device.
+    pub fn same_device(&self, device: &ClientDevice) -> bool {
+        // ---
+        match (&self.device_id, &self.user_agent) {
+            (Some(device_id), _) => device.device_id.as_ref() == Some(device_id),
+            (None, Some(user_agent)) => device.user_agent.as_ref() == Some(user_agent),
+            (None, None) => true,
+        }
+    }
+}
+
+impl From<&Claims> for RefreshTokenData {
+    // ---
+    fn from(claims: &Claims) -> Self {
+        // ---
+        Self {
+            user_id: claims.sub.clone(),
+            email: claims.email.clone(),
+            roles: claims.roles.clone(),
+            scope: claims.scope.clone(),
+            custom_claims: claims.custom.clone(),
+            audience: claims.aud.clone(),
+            client_id: claims.client_id.clone(),
+            session_started_at: Some(claims.iat as i64),
+            expires_at: None,
+            session_id: None,
+            issued_at: None,
+            rotation_count: 0,
+            user_agent: None,
+            ip: None,
+            device_id: None,
+            dpop_jkt: claims.dpop_jkt().map(str::to_string),
+        }
+    }
+}
+
+/// Whether `n` is zero, for leaving unset counters out of stored data.
+fn is_zero(n: &u32) -> bool {
+    // ---
+    *n == 0
+}
+
+// ---
+
+/// A stored refresh token, as returned by
+/// [`TokenStore::list_user_sessions`](crate::TokenStore::list_user_sessions).
+#[derive(Debug, Serialize)]
+pub struct RefreshTokenEntry {
+    // ---
+    /// Hex SHA-256 of the refresh token, identifying it without making it
+    /// usable
+    pub token_hash: String,
+
+    /// User data stored with it
+    pub data: RefreshTokenData,
+
+    /// Seconds until the store expires it
+    pub ttl_seconds: i64,
+}
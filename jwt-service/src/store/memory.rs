@@ -0,0 +1,547 @@
+// jwt-service/src/store/memory.rs
+
+//! [`TokenStore`] in process memory, for a single instance, development
+//! (`--dev`), and tests
+
+use anyhow::{bail, Result};
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokn_core::{keys, SharedClock};
+
+// ---
+
+use super::{open_successor, seal_successor, RevokedToken, TokenStore};
+use crate::{Claims, KeyKind, RefreshTokenData, RefreshTokenEntry};
+
+// ---
+
+/// An entry and when it expires (Unix timestamp).
+struct Expiring<T> {
+    // ---
+    value: T,
+    expires_at: i64,
+}
+
+impl<T> Expiring<T> {
+    // ---
+    fn is_live(&self, now: i64) -> bool {
+        // ---
+        self.expires_at > now
+    }
+}
+
+/// Everything a [`MemoryStore`] holds.
+#[derive(Default)]
+struct Entries {
+    // ---
+    /// Live refresh tokens, by [`keys::refresh_token_hash`]
+    refresh: DashMap<String, Expiring<RefreshTokenData>>,
+
+    /// Refresh tokens consumed by rotation, for reuse detection, by hash
+    used: DashMap<String, Expiring<RefreshTokenData>>,
+
+    /// The tokens refresh tokens were rotated into, sealed, during the grace
+    /// window, by the hash of the rotated token
+    successors: DashMap<String, Expiring<String>>,
+
+    /// Blacklisted access token IDs and when they can be forgotten
+    revoked: DashMap<String, i64>,
+
+    /// IDs of used single-use tokens and when they can be forgotten
+    consumed: DashMap<String, i64>,
+
+    /// Token versions by user; never expire
+    versions: DashMap<String, u64>,
+
+    /// Revocation cutoffs by user; never expire
+    cutoffs: DashMap<String, i64>,
+
+    /// Unredeemed tickets
+    tickets: DashMap<String, Expiring<Claims>>,
+
+    /// Unredeemed magic links
+    magic_links: DashMap<String, Expiring<Claims>>,
+
+    /// Hashed keys by kind and ID, with the user each acts as; never expire
+    hashed_keys: DashMap<(KeyKind, String), StoredKey>,
+}
+
+/// A hashed key's JSON entry and the user it acts as.
+struct StoredKey {
+    // ---
+    owner: Option<String>,
+    entry: String,
+}
+
+impl Entries {
+    // ---
+    /// Drop every entry that has expired at `now`, returning how many.
+    fn purge(&self, now: i64) -> usize {
+        // ---
+        let before = self.len();
+        self.refresh.retain(|_, entry| entry.is_live(now));
+        self.used.retain(|_, entry| entry.is_live(now));
+        self.successors.retain(|_, entry| entry.is_live(now));
+        self.revoked.retain(|_, expires_at| *expires_at > now);
+        self.consumed.retain(|_, expires_at| *expires_at > now);
+        self.tickets.retain(|_, entry| entry.is_live(now));
+        self.magic_links.retain(|_, entry| entry.is_live(now));
+        before - self.len()
+    }
+
+    fn len(&self) -> usize {
+        // ---
+        self.refresh.len()
+            + self.used.len()
+            + self.successors.len()
+            + self.revoked.len()
+            + self.consumed.len()
+            + self.versions.len()
+            + self.cutoffs.len()
+            + self.tickets.len()
+            + self.magic_links.len()
+            + self.hashed_keys.len()
+    }
+}
+
+// ---
+
+/// Refresh tokens and the blacklist in process memory; clones share the same
+/// entries.
+///
+/// Refresh tokens are keyed by [`keys::refresh_token_hash`], as in Redis.
+/// Entries expire by `clock`: expired ones are never returned, and are
+/// dropped by [`sweep`](Self::sweep), which [`spawn_sweeper`](Self::spawn_sweeper)
+/// runs periodically; token versions, revocation cutoffs, and keys never
+/// expire. Everything is lost on
+/// restart and not shared between replicas, so this suits a single instance,
+/// development, and tests rather than production.
+#[derive(Clone)]
+pub struct MemoryStore {
+    // ---
+    entries: Arc<Entries>,
+    clock: SharedClock,
+}
+
+impl MemoryStore {
+    // ---
+    /// An empty store expiring entries by `clock`.
+    pub fn new(clock: SharedClock) -> Self {
+        // ---
+        Self {
+            entries: Arc::default(),
+            clock,
+        }
+    }
+
+    // ---
+    /// Drop the entries that have expired, returning how many.
+    pub fn sweep(&self) -> usize {
+        // ---
+        self.entries.purge(self.clock.timestamp())
+    }
+
+    /// Run [`sweep`](Self::sweep) every `interval` on the Tokio runtime,
+    /// until the last clone of this store is dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside a Tokio runtime.
+    pub fn spawn_sweeper(&self, interval: Duration) -> JoinHandle<()> {
+        // ---
+        let entries: Weak<Entries> = Arc::downgrade(&self.entries);
+        let clock = self.clock.clone();
+        tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(interval);
+            ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            // The first tick completes immediately
+            ticks.tick().await;
+            loop {
+                ticks.tick().await;
+                let Some(entries) = entries.upgrade() else {
+                    return;
+                };
+                let swept = entries.purge(clock.timestamp());
+                if swept > 0 {
+                    tracing::debug!(swept, "Dropped expired token store entries");
+                }
+            }
+        })
+    }
+
+    // ---
+    /// How many entries are held, expired or not.
+    pub fn len(&self) -> usize {
+        // ---
+        self.entries.len()
+    }
+
+    /// Whether no entries are held.
+    pub fn is_empty(&self) -> bool {
+        // ---
+        self.len() == 0
+    }
+}
+
+impl TokenStore for MemoryStore {
+    // ---
+    async fn store_refresh_token(
+        &self,
+        refresh_token: &str,
+        data: &RefreshTokenData,
+        ttl_seconds: i64,
+    ) -> Result<()> {
+        // ---
+        let now = self.clock.timestamp();
+        self.entries.refresh.insert(
+            keys::refresh_token_hash(refresh_token),
+            Expiring {
+                value: data.clone(),
+                expires_at: now + ttl_seconds,
+            },
+        );
+        Ok(())
+    }
+
+    async fn validate_refresh_token(&self, refresh_token: &str) -> Result<RefreshTokenData> {
+        // ---
+        let now = self.clock.timestamp();
+        let token_hash = keys::refresh_token_hash(refresh_token);
+        let Some((token_hash, entry)) = self.entries.refresh.remove(&token_hash) else {
+            bail!("Invalid or expired refresh token");
+        };
+        if !entry.is_live(now) {
+            bail!("Invalid or expired refresh token");
+        }
+
+        let data = entry.value.clone();
+        self.entries.used.insert(token_hash, entry);
+        Ok(data)
+    }
+
+    async fn revoke_refresh_token(&self, refresh_token: &str) -> Result<Option<RefreshTokenData>> {
+        // ---
+        let now = self.clock.timestamp();
+        Ok(self
+            .entries
+            .refresh
+            .remove(&keys::refresh_token_hash(refresh_token))
+            .filter(|(_, entry)| entry.is_live(now))
+            .map(|(_, entry)| entry.value))
+    }
+
+    async fn refresh_token_reused(&self, refresh_token: &str) -> Result<Option<RefreshTokenData>> {
+        // ---
+        let now = self.clock.timestamp();
+        Ok(self
+            .entries
+            .used
+            .get(&keys::refresh_token_hash(refresh_token))
+            .filter(|entry| entry.is_live(now))
+            .map(|entry| entry.value.clone()))
+    }
+
+    async fn store_refresh_successor(
+        &self,
+        refresh_token: &str,
+        successor: &str,
+        ttl_seconds: i64,
+    ) -> Result<()> {
+        // ---
+        let now = self.clock.timestamp();
+        self.entries.successors.insert(
+            keys::refresh_token_hash(refresh_token),
+            Expiring {
+                value: seal_successor(refresh_token, successor)?,
+                expires_at: now + ttl_seconds,
+            },
+        );
+        Ok(())
+    }
+
+    async fn refresh_successor(
+        &self,
+        refresh_token: &str,
+    ) -> Result<Option<(String, RefreshTokenData)>> {
+        // ---
+        let now = self.clock.timestamp();
+        let Some(successor) = self
+            .entries
+            .successors
+            .get(&keys::refresh_token_hash(refresh_token))
+            .filter(|entry| entry.is_live(now))
+            .and_then(|entry| open_successor(refresh_token, &entry.value))
+        else {
+            return Ok(None);
+        };
+        Ok(self
+            .entries
+            .refresh
+            .get(&keys::refresh_token_hash(&successor))
+            .filter(|entry| entry.is_live(now))
+            .map(|entry| (successor.clone(), entry.value.clone())))
+    }
+
+    async fn list_user_sessions(&self, user_id: &str) -> Result<Vec<RefreshTokenEntry>> {
+        // ---
+        let now = self.clock.timestamp();
+        let mut sessions: Vec<RefreshTokenEntry> = self
+            .entries
+            .refresh
+            .iter()
+            .filter(|entry| {
+                entry.is_live(now)
+                    && entry.value.user_id == user_id
+                    && entry.value.session_id.is_some()
+            })
+            .map(|entry| RefreshTokenEntry {
+                token_hash: entry.key().clone(),
+                data: entry.value.clone(),
+                ttl_seconds: entry.expires_at - now,
+            })
+            .collect();
+
+        sessions.sort_by_key(|entry| entry.data.session_started_at);
+        Ok(sessions)
+    }
+
+    async fn delete_user_session(&self, user_id: &str, session_id: &str) -> Result<bool> {
+        // ---
+        let now = self.clock.timestamp();
+        let in_session = |entry: &Expiring<RefreshTokenData>| {
+            entry.is_live(now)
+                && entry.value.user_id == user_id
+                && entry.value.session_id.as_deref() == Some(session_id)
+        };
+
+        // Collected first: removing while iterating would deadlock the shard
+        let token = self
+            .entries
+            .refresh
+            .iter()
+            .find(|entry| in_session(entry.value()))
+            .map(|entry| entry.key().clone());
+
+        Ok(token.is_some_and(|token| {
+            self.entries
+                .refresh
+                .remove_if(&token, |_, entry| in_session(entry))
+                .is_some()
+        }))
+    }
+
+    async fn revoke_token(&self, jti: &str, ttl_seconds: i64) -> Result<()> {
+        // ---
+        let expires_at = self.clock.timestamp() + ttl_seconds;
+        self.entries
+            .revoked
+            .entry(jti.to_string())
+            .and_modify(|current| *current = (*current).max(expires_at))
+            .or_insert(expires_at);
+        Ok(())
+    }
+
+    async fn is_token_revoked(&self, jti: &str) -> Result<bool> {
+        // ---
+        let now = self.clock.timestamp();
+        Ok(self
+            .entries
+            .revoked
+            .get(jti)
+            .is_some_and(|expires_at| *expires_at > now))
+    }
+
+    async fn revoked_token_ttl(&self, jti: &str) -> Result<Option<i64>> {
+        // ---
+        let now = self.clock.timestamp();
+        Ok(self
+            .entries
+            .revoked
+            .get(jti)
+            .map(|expires_at| *expires_at - now)
+            .filter(|ttl| *ttl > 0))
+    }
+
+    async fn list_revoked_tokens(&self, limit: usize) -> Result<Vec<RevokedToken>> {
+        // ---
+        let now = self.clock.timestamp();
+        Ok(self
+            .entries
+            .revoked
+            .iter()
+            .filter(|entry| *entry.value() > now)
+            .take(limit)
+            .map(|entry| RevokedToken {
+                jti: entry.key().clone(),
+                ttl_seconds: *entry.value() - now,
+            })
+            .collect())
+    }
+
+    async fn unrevoke_token(&self, jti: &str) -> Result<bool> {
+        // ---
+        let now = self.clock.timestamp();
+        Ok(self
+            .entries
+            .revoked
+            .remove(jti)
+            .is_some_and(|(_, expires_at)| expires_at > now))
+    }
+
+    async fn consume_token(&self, jti: &str, ttl_seconds: i64) -> Result<bool> {
+        // ---
+        let now = self.clock.timestamp();
+        match self.entries.consumed.entry(jti.to_string()) {
+            Entry::Occupied(entry) if *entry.get() > now => Ok(false),
+            entry => {
+                entry.insert(now + ttl_seconds);
+                Ok(true)
+            }
+        }
+    }
+
+    async fn token_version(&self, user_id: &str) -> Result<u64> {
+        // ---
+        Ok(self
+            .entries
+            .versions
+            .get(user_id)
+            .map_or(0, |version| *version))
+    }
+
+    async fn bump_token_version(&self, user_id: &str) -> Result<u64> {
+        // ---
+        let mut version = self
+            .entries
+            .versions
+            .entry(user_id.to_string())
+            .or_insert(0);
+        *version += 1;
+        Ok(*version)
+    }
+
+    async fn revoked_before(&self, user_id: &str) -> Result<Option<i64>> {
+        // ---
+        Ok(self.entries.cutoffs.get(user_id).map(|cutoff| *cutoff))
+    }
+
+    async fn set_revoked_before(&self, user_id: &str, timestamp: i64) -> Result<()> {
+        // ---
+        self.entries.cutoffs.insert(user_id.to_string(), timestamp);
+        Ok(())
+    }
+
+    async fn store_ticket(&self, ticket: &str, claims: &Claims, ttl_seconds: i64) -> Result<()> {
+        // ---
+        let now = self.clock.timestamp();
+        self.entries.tickets.insert(
+            ticket.to_string(),
+            Expiring {
+                value: claims.clone(),
+                expires_at: now + ttl_seconds,
+            },
+        );
+        Ok(())
+    }
+
+    async fn redeem_ticket(&self, ticket: &str) -> Result<Option<Claims>> {
+        // ---
+        let now = self.clock.timestamp();
+        Ok(self
+            .entries
+            .tickets
+            .remove(ticket)
+            .filter(|(_, entry)| entry.is_live(now))
+            .map(|(_, entry)| entry.value))
+    }
+
+    async fn store_magic_link(&self, token: &str, claims: &Claims, ttl_seconds: i64) -> Result<()> {
+        // ---
+        let now = self.clock.timestamp();
+        self.entries.magic_links.insert(
+            token.to_string(),
+            Expiring {
+                value: claims.clone(),
+                expires_at: now + ttl_seconds,
+            },
+        );
+        Ok(())
+    }
+
+    async fn redeem_magic_link(&self, token: &str) -> Result<Option<Claims>> {
+        // ---
+        let now = self.clock.timestamp();
+        Ok(self
+            .entries
+            .magic_links
+            .remove(token)
+            .filter(|(_, entry)| entry.is_live(now))
+            .map(|(_, entry)| entry.value))
+    }
+
+    async fn store_key_entry(
+        &self,
+        kind: KeyKind,
+        id: &str,
+        owner: Option<&str>,
+        entry: &str,
+    ) -> Result<()> {
+        // ---
+        self.entries.hashed_keys.insert(
+            (kind, id.to_string()),
+            StoredKey {
+                owner: owner.map(str::to_string),
+                entry: entry.to_string(),
+            },
+        );
+        Ok(())
+    }
+
+    async fn find_key_entry(&self, kind: KeyKind, id: &str) -> Result<Option<String>> {
+        // ---
+        Ok(self
+            .entries
+            .hashed_keys
+            .get(&(kind, id.to_string()))
+            .map(|key| key.entry.clone()))
+    }
+
+    async fn list_key_entries(&self, kind: KeyKind) -> Result<Vec<(String, String)>> {
+        // ---
+        Ok(self
+            .entries
+            .hashed_keys
+            .iter()
+            .filter(|key| key.key().0 == kind)
+            .map(|key| (key.key().1.clone(), key.entry.clone()))
+            .collect())
+    }
+
+    async fn delete_key_entry(&self, kind: KeyKind, id: &str) -> Result<bool> {
+        // ---
+        Ok(self
+            .entries
+            .hashed_keys
+            .remove(&(kind, id.to_string()))
+            .is_some())
+    }
+
+    async fn delete_user_key_entries(&self, kind: KeyKind, user_id: &str) -> Result<usize> {
+        // ---
+        let mut deleted = 0;
+        self.entries.hashed_keys.retain(|(key_kind, _), key| {
+            let owned = *key_kind == kind && key.owner.as_deref() == Some(user_id);
+            deleted += usize::from(owned);
+            !owned
+        });
+        Ok(deleted)
+    }
+
+    async fn ping(&self) -> Result<()> {
+        // ---
+        Ok(())
+    }
+}
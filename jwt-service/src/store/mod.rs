@@ -0,0 +1,1130 @@
+// jwt-service/src/store/mod.rs
+
+//! Refresh token and revocation storage
+//!
+//! Handlers reach refresh tokens, sessions, the access token blacklist,
+//! per-user token versions and revocation cutoffs, WebSocket tickets, magic
+//! links, and hashed API and issuer keys through a [`Store`] over the
+//! [`TokenStore`]
+//! selected by `TOKEN_STORE`: Redis (the default), Postgres, or in-process
+//! memory. Handlers do not depend on which one it is.
+
+mod memory;
+#[cfg(feature = "postgres")]
+mod postgres;
+#[cfg(feature = "redis")]
+mod redis;
+mod seal;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokn_config::Secret;
+use uuid::Uuid;
+
+// ---
+
+use crate::{key_id, Claims, HashedKey, KeyKind, RefreshTokenData, RefreshTokenEntry};
+
+#[cfg(feature = "redis")]
+pub use self::redis::RedisStore;
+pub use memory::MemoryStore;
+#[cfg(feature = "postgres")]
+pub use postgres::PostgresStore;
+pub(crate) use seal::{open_successor, seal_successor};
+
+// ---
+
+/// Which [`TokenStore`] keeps refresh tokens and revocations.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StoreBackend {
+    // ---
+    /// Redis at `REDIS_URL` (default; `redis` feature)
+    #[default]
+    Redis,
+
+    /// Postgres at `TOKEN_STORE_DATABASE_URL` (`postgres` feature)
+    Postgres,
+
+    /// In-process memory: lost on restart and not shared between replicas
+    Memory,
+}
+
+impl StoreBackend {
+    // ---
+    /// The backend's name, as `TOKEN_STORE` spells it.
+    pub fn as_str(self) -> &'static str {
+        // ---
+        match self {
+            StoreBackend::Redis => "redis",
+            StoreBackend::Postgres => "postgres",
+            StoreBackend::Memory => "memory",
+        }
+    }
+}
+
+impl fmt::Display for StoreBackend {
+    // ---
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // ---
+        f.write_str(self.as_str())
+    }
+}
+
+// ---
+
+/// Seconds between sweeps of expired entries from the `memory` store.
+pub const DEFAULT_SWEEP_INTERVAL_SECONDS: u64 = 60;
+
+/// Service configuration section for the token store.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct StoreConfig {
+    // ---
+    /// Where refresh tokens and revocations are kept (env `TOKEN_STORE`:
+    /// `redis`, `postgres`, or `memory`)
+    pub backend: StoreBackend,
+
+    /// Postgres connection URL; required with the `postgres` backend (env
+    /// `TOKEN_STORE_DATABASE_URL`)
+    pub database_url: Option<Secret>,
+
+    /// Seconds between sweeps of expired entries from the `memory` backend
+    /// (env `TOKEN_STORE_SWEEP_INTERVAL_SECONDS`)
+    pub sweep_interval_seconds: u64,
+}
+
+impl Default for StoreConfig {
+    // ---
+    fn default() -> Self {
+        // ---
+        Self {
+            backend: StoreBackend::default(),
+            database_url: None,
+            sweep_interval_seconds: DEFAULT_SWEEP_INTERVAL_SECONDS,
+        }
+    }
+}
+
+// ---
+
+/// Config rule for the `store` section: the Postgres backend needs a
+/// `database_url`, and sweeps need a non-zero interval.
+///
+/// # Errors
+///
+/// Returns a message for `tokn_config::ConfigLoader::rule` naming the missing
+/// setting.
+pub fn validate_store_config(config: &StoreConfig) -> Result<(), String> {
+    // ---
+    if config.backend == StoreBackend::Postgres && config.database_url.is_none() {
+        return Err("backend 'postgres' requires TOKEN_STORE_DATABASE_URL".to_string());
+    }
+    if config.sweep_interval_seconds == 0 {
+        return Err("sweep_interval_seconds must be at least 1".to_string());
+    }
+    Ok(())
+}
+
+// ---
+
+/// A blacklisted access token, as listed by
+/// [`TokenStore::list_revoked_tokens`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RevokedToken {
+    // ---
+    /// ID (`jti`) of the revoked access token
+    pub jti: String,
+
+    /// Seconds until the entry expires along with the token itself
+    pub ttl_seconds: i64,
+}
+
+/// What revokes an access token besides its expiry, read in one go by
+/// [`TokenStore::revocation_status`] on every validation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RevocationStatus {
+    // ---
+    /// Whether the token's `jti` is blacklisted
+    pub blacklisted: bool,
+
+    /// Token version of the token's user; older `ver` claims are revoked
+    pub token_version: u64,
+
+    /// Revocation cutoff of the token's user; tokens issued before it are
+    /// revoked
+    pub revoked_before: Option<i64>,
+}
+
+impl RevocationStatus {
+    // ---
+    /// Whether the token with `claims` is revoked: it is blacklisted, its
+    /// `ver` is older than its user's token version (an unversioned token
+    /// counts as version 0), or it was issued before its user's cutoff.
+    pub fn revokes(&self, claims: &Claims) -> bool {
+        // ---
+        self.blacklisted
+            || claims.ver.unwrap_or(0) < self.token_version
+            || self
+                .revoked_before
+                .is_some_and(|cutoff| (claims.iat as i64) < cutoff)
+    }
+}
+
+// ---
+
+/// Where refresh tokens, their sessions, revoked access tokens, tickets,
+/// magic links, and hashed keys are kept.
+///
+/// Implemented by [`RedisStore`], `PostgresStore` (`postgres` feature), and
+/// [`MemoryStore`]; implement it to keep them elsewhere and hand it to
+/// [`Store::new`]. Every entry expires on its own after the TTL it was
+/// stored with.
+pub trait TokenStore: Send + Sync + 'static {
+    // ---
+    /// Store `data` under `refresh_token` for `ttl_seconds`, listed under its
+    /// session when it has a `session_id`.
+    fn store_refresh_token(
+        &self,
+        refresh_token: &str,
+        data: &RefreshTokenData,
+        ttl_seconds: i64,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// Consume `refresh_token` and return its data. The token is remembered
+    /// as used until it would have expired, for [`refresh_token_reused`].
+    ///
+    /// Fails if the token is unknown, expired, or already consumed, as well
+    /// as when the store cannot be reached.
+    ///
+    /// [`refresh_token_reused`]: TokenStore::refresh_token_reused
+    fn validate_refresh_token(
+        &self,
+        refresh_token: &str,
+    ) -> impl Future<Output = Result<RefreshTokenData>> + Send;
+
+    /// Delete `refresh_token` without remembering it as used. `None` if it
+    /// was never issued, has expired, or was already consumed.
+    fn revoke_refresh_token(
+        &self,
+        refresh_token: &str,
+    ) -> impl Future<Output = Result<Option<RefreshTokenData>>> + Send;
+
+    /// The data of `refresh_token` if it was consumed by rotation and would
+    /// not have expired yet: presented again, it was most likely stolen.
+    fn refresh_token_reused(
+        &self,
+        refresh_token: &str,
+    ) -> impl Future<Output = Result<Option<RefreshTokenData>>> + Send;
+
+    /// Remember for `ttl_seconds` that `refresh_token` was rotated into
+    /// `successor`, for the refresh grace window.
+    fn store_refresh_successor(
+        &self,
+        refresh_token: &str,
+        successor: &str,
+        ttl_seconds: i64,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// The token `refresh_token` was rotated into and its data, while the
+    /// grace window set by [`store_refresh_successor`] lasts; `None` after
+    /// it, or if the successor was consumed or revoked since.
+    ///
+    /// [`store_refresh_successor`]: TokenStore::store_refresh_successor
+    fn refresh_successor(
+        &self,
+        refresh_token: &str,
+    ) -> impl Future<Output = Result<Option<(String, RefreshTokenData)>>> + Send;
+
+    /// The sessions of `user_id`, each with its current refresh token,
+    /// oldest first.
+    fn list_user_sessions(
+        &self,
+        user_id: &str,
+    ) -> impl Future<Output = Result<Vec<RefreshTokenEntry>>> + Send;
+
+    /// End session `session_id` of `user_id` by revoking its current refresh
+    /// token; `false` if the user has no such live session.
+    fn delete_user_session(
+        &self,
+        user_id: &str,
+        session_id: &str,
+    ) -> impl Future<Output = Result<bool>> + Send;
+
+    /// Blacklist the access token `jti` for `ttl_seconds`, its remaining
+    /// lifetime.
+    fn revoke_token(&self, jti: &str, ttl_seconds: i64) -> impl Future<Output = Result<()>> + Send;
+
+    /// Whether the access token `jti` is blacklisted.
+    fn is_token_revoked(&self, jti: &str) -> impl Future<Output = Result<bool>> + Send;
+
+    /// Seconds until the blacklist entry of `jti` expires; `None` if it is
+    /// not blacklisted.
+    fn revoked_token_ttl(&self, jti: &str) -> impl Future<Output = Result<Option<i64>>> + Send;
+
+    /// Up to `limit` blacklisted access tokens, in no particular order.
+    /// Meant for operators, not request paths.
+    fn list_revoked_tokens(
+        &self,
+        limit: usize,
+    ) -> impl Future<Output = Result<Vec<RevokedToken>>> + Send;
+
+    /// Take the access token `jti` off the blacklist; `false` if it was not
+    /// on it.
+    fn unrevoke_token(&self, jti: &str) -> impl Future<Output = Result<bool>> + Send;
+
+    /// Mark the single-use token `jti` used for `ttl_seconds`, its remaining
+    /// lifetime, unless it already is; `false` if it was. Of concurrent
+    /// calls, only one gets `true`. Kept apart from the blacklist: consumed
+    /// tokens are neither listed nor removable by the blacklist endpoints.
+    fn consume_token(
+        &self,
+        jti: &str,
+        ttl_seconds: i64,
+    ) -> impl Future<Output = Result<bool>> + Send;
+
+    /// The token version of `user_id`, `0` until first bumped. Access tokens
+    /// whose `ver` claim is older are revoked.
+    fn token_version(&self, user_id: &str) -> impl Future<Output = Result<u64>> + Send;
+
+    /// Bump the token version of `user_id`, revoking every access token
+    /// issued to the user so far, and return the new version. Versions never
+    /// expire.
+    fn bump_token_version(&self, user_id: &str) -> impl Future<Output = Result<u64>> + Send;
+
+    /// The revocation cutoff of `user_id` (Unix timestamp), `None` until
+    /// set. Tokens issued before it are revoked.
+    fn revoked_before(&self, user_id: &str) -> impl Future<Output = Result<Option<i64>>> + Send;
+
+    /// Set the revocation cutoff of `user_id` to `timestamp`, revoking every
+    /// token issued to the user before it. Replaces any earlier cutoff;
+    /// cutoffs never expire.
+    fn set_revoked_before(
+        &self,
+        user_id: &str,
+        timestamp: i64,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// Whether the access token `jti` is blacklisted, with the token version
+    /// and revocation cutoff of its user `user_id`: everything validation
+    /// checks. Looked up one after another unless a store overrides it to
+    /// read them in one round trip, as Redis and Postgres do.
+    fn revocation_status(
+        &self,
+        jti: &str,
+        user_id: &str,
+    ) -> impl Future<Output = Result<RevocationStatus>> + Send {
+        // ---
+        async move {
+            Ok(RevocationStatus {
+                blacklisted: self.is_token_revoked(jti).await?,
+                token_version: self.token_version(user_id).await?,
+                revoked_before: self.revoked_before(user_id).await?,
+            })
+        }
+    }
+
+    /// Store the `claims` a single-use `ticket` stands for, for
+    /// `ttl_seconds`.
+    fn store_ticket(
+        &self,
+        ticket: &str,
+        claims: &Claims,
+        ttl_seconds: i64,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// Consume `ticket` and return its claims; `None` if it was never issued,
+    /// has expired, or was already redeemed. Of concurrent redemptions, only
+    /// one gets the claims.
+    fn redeem_ticket(&self, ticket: &str) -> impl Future<Output = Result<Option<Claims>>> + Send;
+
+    /// Store the `claims` a single-use magic login link grants under its
+    /// `token`, for `ttl_seconds`.
+    fn store_magic_link(
+        &self,
+        token: &str,
+        claims: &Claims,
+        ttl_seconds: i64,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// Consume the magic link `token` and return the claims it grants;
+    /// `None` if it was never issued, has expired, or was already redeemed.
+    /// Of concurrent redemptions, only one gets the claims.
+    fn redeem_magic_link(&self, token: &str)
+        -> impl Future<Output = Result<Option<Claims>>> + Send;
+
+    /// Store the JSON `entry` of a `kind` key under its `id` (see
+    /// [`key_id`]), indexed under the user `owner` it acts as, if any. Keys
+    /// never expire.
+    fn store_key_entry(
+        &self,
+        kind: KeyKind,
+        id: &str,
+        owner: Option<&str>,
+        entry: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// The JSON entry of the `kind` key with ID `id`, if there is one.
+    fn find_key_entry(
+        &self,
+        kind: KeyKind,
+        id: &str,
+    ) -> impl Future<Output = Result<Option<String>>> + Send;
+
+    /// The ID and JSON entry of every `kind` key, in no particular order.
+    fn list_key_entries(
+        &self,
+        kind: KeyKind,
+    ) -> impl Future<Output = Result<Vec<(String, String)>>> + Send;
+
+    /// Delete the `kind` key with ID `id`; `false` if there was none.
+    fn delete_key_entry(
+        &self,
+        kind: KeyKind,
+        id: &str,
+    ) -> impl Future<Output = Result<bool>> + Send;
+
+    /// Delete every `kind` key acting as `user_id`, returning how many.
+    fn delete_user_key_entries(
+        &self,
+        kind: KeyKind,
+        user_id: &str,
+    ) -> impl Future<Output = Result<usize>> + Send;
+
+    /// Check the backend answers, for readiness probes.
+    fn ping(&self) -> impl Future<Output = Result<()>> + Send;
+}
+
+// ---
+
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Object-safe adapter over [`TokenStore`], so [`Store`] is not generic.
+trait DynTokenStore: Send + Sync {
+    // ---
+    fn store_refresh_token<'a>(
+        &'a self,
+        refresh_token: &'a str,
+        data: &'a RefreshTokenData,
+        ttl_seconds: i64,
+    ) -> BoxFuture<'a, Result<()>>;
+
+    fn validate_refresh_token<'a>(
+        &'a self,
+        refresh_token: &'a str,
+    ) -> BoxFuture<'a, Result<RefreshTokenData>>;
+
+    fn revoke_refresh_token<'a>(
+        &'a self,
+        refresh_token: &'a str,
+    ) -> BoxFuture<'a, Result<Option<RefreshTokenData>>>;
+
+    fn refresh_token_reused<'a>(
+        &'a self,
+        refresh_token: &'a str,
+    ) -> BoxFuture<'a, Result<Option<RefreshTokenData>>>;
+
+    fn store_refresh_successor<'a>(
+        &'a self,
+        refresh_token: &'a str,
+        successor: &'a str,
+        ttl_seconds: i64,
+    ) -> BoxFuture<'a, Result<()>>;
+
+    fn refresh_successor<'a>(
+        &'a self,
+        refresh_token: &'a str,
+    ) -> BoxFuture<'a, Result<Option<(String, RefreshTokenData)>>>;
+
+    fn list_user_sessions<'a>(
+        &'a self,
+        user_id: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<RefreshTokenEntry>>>;
+
+    fn delete_user_session<'a>(
+        &'a self,
+        user_id: &'a str,
+        session_id: &'a str,
+    ) -> BoxFuture<'a, Result<bool>>;
+
+    fn revoke_token<'a>(&'a self, jti: &'a str, ttl_seconds: i64) -> BoxFuture<'a, Result<()>>;
+
+    fn is_token_revoked<'a>(&'a self, jti: &'a str) -> BoxFuture<'a, Result<bool>>;
+
+    fn revoked_token_ttl<'a>(&'a self, jti: &'a str) -> BoxFuture<'a, Result<Option<i64>>>;
+
+    fn list_revoked_tokens(&self, limit: usize) -> BoxFuture<'_, Result<Vec<RevokedToken>>>;
+
+    fn unrevoke_token<'a>(&'a self, jti: &'a str) -> BoxFuture<'a, Result<bool>>;
+
+    fn consume_token<'a>(&'a self, jti: &'a str, ttl_seconds: i64) -> BoxFuture<'a, Result<bool>>;
+
+    fn token_version<'a>(&'a self, user_id: &'a str) -> BoxFuture<'a, Result<u64>>;
+
+    fn bump_token_version<'a>(&'a self, user_id: &'a str) -> BoxFuture<'a, Result<u64>>;
+
+    fn revoked_before<'a>(&'a self, user_id: &'a str) -> BoxFuture<'a, Result<Option<i64>>>;
+
+    fn set_revoked_before<'a>(
+        &'a self,
+        user_id: &'a str,
+        timestamp: i64,
+    ) -> BoxFuture<'a, Result<()>>;
+
+    fn revocation_status<'a>(
+        &'a self,
+        jti: &'a str,
+        user_id: &'a str,
+    ) -> BoxFuture<'a, Result<RevocationStatus>>;
+
+    fn store_ticket<'a>(
+        &'a self,
+        ticket: &'a str,
+        claims: &'a Claims,
+        ttl_seconds: i64,
+    ) -> BoxFuture<'a, Result<()>>;
+
+    fn redeem_ticket<'a>(&'a self, ticket: &'a str) -> BoxFuture<'a, Result<Option<Claims>>>;
+
+    fn store_magic_link<'a>(
+        &'a self,
+        token: &'a str,
+        claims: &'a Claims,
+        ttl_seconds: i64,
+    ) -> BoxFuture<'a, Result<()>>;
+
+    fn redeem_magic_link<'a>(&'a self, token: &'a str) -> BoxFuture<'a, Result<Option<Claims>>>;
+
+    fn store_key_entry<'a>(
+        &'a self,
+        kind: KeyKind,
+        id: &'a str,
+        owner: Option<&'a str>,
+        entry: &'a str,
+    ) -> BoxFuture<'a, Result<()>>;
+
+    fn find_key_entry<'a>(
+        &'a self,
+        kind: KeyKind,
+        id: &'a str,
+    ) -> BoxFuture<'a, Result<Option<String>>>;
+
+    fn list_key_entries(&self, kind: KeyKind) -> BoxFuture<'_, Result<Vec<(String, String)>>>;
+
+    fn delete_key_entry<'a>(&'a self, kind: KeyKind, id: &'a str) -> BoxFuture<'a, Result<bool>>;
+
+    fn delete_user_key_entries<'a>(
+        &'a self,
+        kind: KeyKind,
+        user_id: &'a str,
+    ) -> BoxFuture<'a, Result<usize>>;
+
+    fn ping(&self) -> BoxFuture<'_, Result<()>>;
+}
+
+impl<S: TokenStore> DynTokenStore for S {
+    // ---
+    fn store_refresh_token<'a>(
+        &'a self,
+        refresh_token: &'a str,
+        data: &'a RefreshTokenData,
+        ttl_seconds: i64,
+    ) -> BoxFuture<'a, Result<()>> {
+        // ---
+        Box::pin(TokenStore::store_refresh_token(
+            self,
+            refresh_token,
+            data,
+            ttl_seconds,
+        ))
+    }
+
+    fn validate_refresh_token<'a>(
+        &'a self,
+        refresh_token: &'a str,
+    ) -> BoxFuture<'a, Result<RefreshTokenData>> {
+        // ---
+        Box::pin(TokenStore::validate_refresh_token(self, refresh_token))
+    }
+
+    fn revoke_refresh_token<'a>(
+        &'a self,
+        refresh_token: &'a str,
+    ) -> BoxFuture<'a, Result<Option<RefreshTokenData>>> {
+        // ---
+        Box::pin(TokenStore::revoke_refresh_token(self, refresh_token))
+    }
+
+    fn refresh_token_reused<'a>(
+        &'a self,
+        refresh_token: &'a str,
+    ) -> BoxFuture<'a, Result<Option<RefreshTokenData>>> {
+        // ---
+        Box::pin(TokenStore::refresh_token_reused(self, refresh_token))
+    }
+
+    fn store_refresh_successor<'a>(
+        &'a self,
+        refresh_token: &'a str,
+        successor: &'a str,
+        ttl_seconds: i64,
+    ) -> BoxFuture<'a, Result<()>> {
+        // ---
+        Box::pin(TokenStore::store_refresh_successor(
+            self,
+            refresh_token,
+            successor,
+            ttl_seconds,
+        ))
+    }
+
+    fn refresh_successor<'a>(
+        &'a self,
+        refresh_token: &'a str,
+    ) -> BoxFuture<'a, Result<Option<(String, RefreshTokenData)>>> {
+        // ---
+        Box::pin(TokenStore::refresh_successor(self, refresh_token))
+    }
+
+    fn list_user_sessions<'a>(
+        &'a self,
+        user_id: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<RefreshTokenEntry>>> {
+        // ---
+        Box::pin(TokenStore::list_user_sessions(self, user_id))
+    }
+
+    fn delete_user_session<'a>(
+        &'a self,
+        user_id: &'a str,
+        session_id: &'a str,
+    ) -> BoxFuture<'a, Result<bool>> {
+        // ---
+        Box::pin(TokenStore::delete_user_session(self, user_id, session_id))
+    }
+
+    fn revoke_token<'a>(&'a self, jti: &'a str, ttl_seconds: i64) -> BoxFuture<'a, Result<()>> {
+        // ---
+        Box::pin(TokenStore::revoke_token(self, jti, ttl_seconds))
+    }
+
+    fn is_token_revoked<'a>(&'a self, jti: &'a str) -> BoxFuture<'a, Result<bool>> {
+        // ---
+        Box::pin(TokenStore::is_token_revoked(self, jti))
+    }
+
+    fn revoked_token_ttl<'a>(&'a self, jti: &'a str) -> BoxFuture<'a, Result<Option<i64>>> {
+        // ---
+        Box::pin(TokenStore::revoked_token_ttl(self, jti))
+    }
+
+    fn list_revoked_tokens(&self, limit: usize) -> BoxFuture<'_, Result<Vec<RevokedToken>>> {
+        // ---
+        Box::pin(TokenStore::list_revoked_tokens(self, limit))
+    }
+
+    fn unrevoke_token<'a>(&'a self, jti: &'a str) -> BoxFuture<'a, Result<bool>> {
+        // ---
+        Box::pin(TokenStore::unrevoke_token(self, jti))
+    }
+
+    fn consume_token<'a>(&'a self, jti: &'a str, ttl_seconds: i64) -> BoxFuture<'a, Result<bool>> {
+        // ---
+        Box::pin(TokenStore::consume_token(self, jti, ttl_seconds))
+    }
+
+    fn token_version<'a>(&'a self, user_id: &'a str) -> BoxFuture<'a, Result<u64>> {
+        // ---
+        Box::pin(TokenStore::token_version(self, user_id))
+    }
+
+    fn bump_token_version<'a>(&'a self, user_id: &'a str) -> BoxFuture<'a, Result<u64>> {
+        // ---
+        Box::pin(TokenStore::bump_token_version(self, user_id))
+    }
+
+    fn revoked_before<'a>(&'a self, user_id: &'a str) -> BoxFuture<'a, Result<Option<i64>>> {
+        // ---
+        Box::pin(TokenStore::revoked_before(self, user_id))
+    }
+
+    fn set_revoked_before<'a>(
+        &'a self,
+        user_id: &'a str,
+        timestamp: i64,
+    ) -> BoxFuture<'a, Result<()>> {
+        // ---
+        Box::pin(TokenStore::set_revoked_before(self, user_id, timestamp))
+    }
+
+    fn revocation_status<'a>(
+        &'a self,
+        jti: &'a str,
+        user_id: &'a str,
+    ) -> BoxFuture<'a, Result<RevocationStatus>> {
+        // ---
+        Box::pin(TokenStore::revocation_status(self, jti, user_id))
+    }
+
+    fn store_ticket<'a>(
+        &'a self,
+        ticket: &'a str,
+        claims: &'a Claims,
+        ttl_seconds: i64,
+    ) -> BoxFuture<'a, Result<()>> {
+        // ---
+        Box::pin(TokenStore::store_ticket(self, ticket, claims, ttl_seconds))
+    }
+
+    fn redeem_ticket<'a>(&'a self, ticket: &'a str) -> BoxFuture<'a, Result<Option<Claims>>> {
+        // ---
+        Box::pin(TokenStore::redeem_ticket(self, ticket))
+    }
+
+    fn store_magic_link<'a>(
+        &'a self,
+        token: &'a str,
+        claims: &'a Claims,
+        ttl_seconds: i64,
+    ) -> BoxFuture<'a, Result<()>> {
+        // ---
+        Box::pin(TokenStore::store_magic_link(
+            self,
+            token,
+            claims,
+            ttl_seconds,
+        ))
+    }
+
+    fn redeem_magic_link<'a>(&'a self, token: &'a str) -> BoxFuture<'a, Result<Option<Claims>>> {
+        // ---
+        Box::pin(TokenStore::redeem_magic_link(self, token))
+    }
+
+    fn store_key_entry<'a>(
+        &'a self,
+        kind: KeyKind,
+        id: &'a str,
+        owner: Option<&'a str>,
+        entry: &'a str,
+    ) -> BoxFuture<'a, Result<()>> {
+        // ---
+        Box::pin(TokenStore::store_key_entry(self, kind, id, owner, entry))
+    }
+
+    fn find_key_entry<'a>(
+        &'a self,
+        kind: KeyKind,
+        id: &'a str,
+    ) -> BoxFuture<'a, Result<Option<String>>> {
+        // ---
+        Box::pin(TokenStore::find_key_entry(self, kind, id))
+    }
+
+    fn list_key_entries(&self, kind: KeyKind) -> BoxFuture<'_, Result<Vec<(String, String)>>> {
+        // ---
+        Box::pin(TokenStore::list_key_entries(self, kind))
+    }
+
+    fn delete_key_entry<'a>(&'a self, kind: KeyKind, id: &'a str) -> BoxFuture<'a, Result<bool>> {
+        // ---
+        Box::pin(TokenStore::delete_key_entry(self, kind, id))
+    }
+
+    fn delete_user_key_entries<'a>(
+        &'a self,
+        kind: KeyKind,
+        user_id: &'a str,
+    ) -> BoxFuture<'a, Result<usize>> {
+        // ---
+        Box::pin(TokenStore::delete_user_key_entries(self, kind, user_id))
+    }
+
+    fn ping(&self) -> BoxFuture<'_, Result<()>> {
+        // ---
+        Box::pin(TokenStore::ping(self))
+    }
+}
+
+// ---
+
+/// Handle on the configured [`TokenStore`]; cheap to clone into handler
+/// state. Implements [`TokenStore`] itself by delegating to it.
+#[derive(Clone)]
+pub struct Store {
+    // ---
+    backend: StoreBackend,
+    inner: Arc<dyn DynTokenStore>,
+}
+
+impl Store {
+    // ---
+    /// Serve `store`, reported as `backend` by readiness and `/debug`.
+    pub fn new<S: TokenStore>(store: S, backend: StoreBackend) -> Self {
+        // ---
+        Self {
+            backend,
+            inner: Arc::new(store),
+        }
+    }
+
+    /// A [`RedisStore`] over `redis`.
+    #[cfg(feature = "redis")]
+    pub fn redis(redis: crate::RedisConnection) -> Self {
+        // ---
+        Self::new(RedisStore::new(redis), StoreBackend::Redis)
+    }
+
+    /// A [`MemoryStore`] expiring entries by `clock`.
+    pub fn memory(clock: tokn_core::SharedClock) -> Self {
+        // ---
+        Self::new(MemoryStore::new(clock), StoreBackend::Memory)
+    }
+
+    // ---
+    /// Which backend keeps the tokens.
+    pub fn backend(&self) -> StoreBackend {
+        // ---
+        self.backend
+    }
+
+    // ---
+    /// Generate a refresh token and store `data` under it for `ttl_seconds`.
+    ///
+    /// Tokens are random UUIDs (v4).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the token cannot be stored.
+    pub async fn issue_refresh_token(
+        &self,
+        data: &RefreshTokenData,
+        ttl_seconds: i64,
+    ) -> Result<String> {
+        // ---
+        let refresh_token = Uuid::new_v4().to_string();
+        self.inner
+            .store_refresh_token(&refresh_token, data, ttl_seconds)
+            .await?;
+        Ok(refresh_token)
+    }
+
+    /// Generate a single-use ticket and store `claims` under it for
+    /// `ttl_seconds`.
+    ///
+    /// Tickets are random UUIDs (v4), like refresh tokens.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the ticket cannot be stored.
+    pub async fn issue_ticket(&self, claims: &Claims, ttl_seconds: i64) -> Result<String> {
+        // ---
+        let ticket = Uuid::new_v4().to_string();
+        self.inner
+            .store_ticket(&ticket, claims, ttl_seconds)
+            .await?;
+        Ok(ticket)
+    }
+
+    /// Generate a single-use magic login link token and store the `claims`
+    /// it grants under it for `ttl_seconds`.
+    ///
+    /// Link tokens are random UUIDs (v4), like refresh tokens.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the link cannot be stored.
+    pub async fn issue_magic_link(&self, claims: &Claims, ttl_seconds: i64) -> Result<String> {
+        // ---
+        let token = Uuid::new_v4().to_string();
+        self.inner
+            .store_magic_link(&token, claims, ttl_seconds)
+            .await?;
+        Ok(token)
+    }
+
+    /// Store `entry` under the [`key_id`] of `key`, which itself is not
+    /// kept, returning the ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key cannot be stored.
+    pub async fn store_key<K: HashedKey>(&self, key: &str, entry: &K) -> Result<String> {
+        // ---
+        let id = key_id(key);
+        let json = serde_json::to_string(entry)
+            .with_context(|| format!("Failed to serialize {} key", K::KIND))?;
+        self.inner
+            .store_key_entry(K::KIND, &id, entry.owner(), &json)
+            .await?;
+        Ok(id)
+    }
+
+    /// The stored key with ID `id`, if there is one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be queried or the entry is
+    /// malformed.
+    pub async fn find_key<K: HashedKey>(&self, id: &str) -> Result<Option<K>> {
+        // ---
+        let json = self.inner.find_key_entry(K::KIND, id).await?;
+        json.map(|json| {
+            serde_json::from_str(&json).with_context(|| format!("Malformed {} key entry", K::KIND))
+        })
+        .transpose()
+    }
+
+    /// Every stored key of its kind with its ID, oldest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be queried or an entry is
+    /// malformed.
+    pub async fn list_keys<K: HashedKey>(&self) -> Result<Vec<(String, K)>> {
+        // ---
+        let mut keys = self
+            .inner
+            .list_key_entries(K::KIND)
+            .await?
+            .into_iter()
+            .map(|(id, json)| {
+                let entry: K = serde_json::from_str(&json)
+                    .with_context(|| format!("Malformed {} key entry", K::KIND))?;
+                Ok((id, entry))
+            })
+            .collect::<Result<Vec<(String, K)>>>()?;
+        keys.sort_by_key(|(_, key)| key.created_at());
+        Ok(keys)
+    }
+
+    /// Delete the stored key of kind `K` with ID `id`; `false` if there was
+    /// none.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be written.
+    pub async fn delete_key<K: HashedKey>(&self, id: &str) -> Result<bool> {
+        // ---
+        self.inner.delete_key_entry(K::KIND, id).await
+    }
+
+    /// Revoke every access token of `user_id` by bumping the user's token
+    /// version, end all of the user's sessions, and delete the API keys
+    /// acting as the user. Returns the new version, the number of sessions
+    /// ended, and the number of API keys deleted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be reached; sessions ended before
+    /// the failure stay ended.
+    pub async fn invalidate_user(&self, user_id: &str) -> Result<(u64, usize, usize)> {
+        // ---
+        let token_version = self.inner.bump_token_version(user_id).await?;
+
+        // Refresh tokens would otherwise mint access tokens under the new version
+        let mut sessions_ended = 0;
+        for entry in self.inner.list_user_sessions(user_id).await? {
+            let Some(session_id) = &entry.data.session_id else {
+                continue;
+            };
+            if self.inner.delete_user_session(user_id, session_id).await? {
+                sessions_ended += 1;
+            }
+        }
+
+        // The version bump already revokes them; deleting them unlists them too
+        let api_keys_deleted = self
+            .inner
+            .delete_user_key_entries(KeyKind::Api, user_id)
+            .await?;
+        Ok((token_version, sessions_ended, api_keys_deleted))
+    }
+}
+
+impl fmt::Debug for Store {
+    // ---
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // ---
+        f.debug_struct("Store")
+            .field("backend", &self.backend)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TokenStore for Store {
+    // ---
+    async fn store_refresh_token(
+        &self,
+        refresh_token: &str,
+        data: &RefreshTokenData,
+        ttl_seconds: i64,
+    ) -> Result<()> {
+        // ---
+        self.inner
+            .store_refresh_token(refresh_token, data, ttl_seconds)
+            .await
+    }
+
+    async fn validate_refresh_token(&self, refresh_token: &str) -> Result<RefreshTokenData> {
+        // ---
+        self.inner.validate_refresh_token(refresh_token).await
+    }
+
+    async fn revoke_refresh_token(&self, refresh_token: &str) -> Result<Option<RefreshTokenData>> {
+        // ---
+        self.inner.revoke_refresh_token(refresh_token).await
+    }
+
+    async fn refresh_token_reused(&self, refresh_token: &str) -> Result<Option<RefreshTokenData>> {
+        // ---
+        self.inner.refresh_token_reused(refresh_token).await
+    }
+
+    async fn store_refresh_successor(
+        &self,
+        refresh_token: &str,
+        successor: &str,
+        ttl_seconds: i64,
+    ) -> Result<()> {
+        // ---
+        self.inner
+            .store_refresh_successor(refresh_token, successor, ttl_seconds)
+            .await
+    }
+
+    async fn refresh_successor(
+        &self,
+        refresh_token: &str,
+    ) -> Result<Option<(String, RefreshTokenData)>> {
+        // ---
+        self.inner.refresh_successor(refresh_token).await
+    }
+
+    async fn list_user_sessions(&self, user_id: &str) -> Result<Vec<RefreshTokenEntry>> {
+        // ---
+        self.inner.list_user_sessions(user_id).await
+    }
+
+    async fn delete_user_session(&self, user_id: &str, session_id: &str) -> Result<bool> {
+        // ---
+        self.inner.delete_user_session(user_id, session_id).await
+    }
+
+    async fn revoke_token(&self, jti: &str, ttl_seconds: i64) -> Result<()> {
+        // ---
+        self.inner.revoke_token(jti, ttl_seconds).await
+    }
+
+    async fn is_token_revoked(&self, jti: &str) -> Result<bool> {
+        // ---
+        self.inner.is_token_revoked(jti).await
+    }
+
+    async fn revoked_token_ttl(&self, jti: &str) -> Result<Option<i64>> {
+        // ---
+        self.inner.revoked_token_ttl(jti).await
+    }
+
+    async fn list_revoked_tokens(&self, limit: usize) -> Result<Vec<RevokedToken>> {
+        // ---
+        self.inner.list_revoked_tokens(limit).await
+    }
+
+    async fn unrevoke_token(&self, jti: &str) -> Result<bool> {
+        // ---
+        self.inner.unrevoke_token(jti).await
+    }
+
+    async fn consume_token(&self, jti: &str, ttl_seconds: i64) -> Result<bool> {
+        // ---
+        self.inner.consume_token(jti, ttl_seconds).await
+    }
+
+    async fn token_version(&self, user_id: &str) -> Result<u64> {
+        // ---
+        self.inner.token_version(user_id).await
+    }
+
+    async fn bump_token_version(&self, user_id: &str) -> Result<u64> {
+        // ---
+        self.inner.bump_token_version(user_id).await
+    }
+
+    async fn revoked_before(&self, user_id: &str) -> Result<Option<i64>> {
+        // ---
+        self.inner.revoked_before(user_id).await
+    }
+
+    async fn set_revoked_before(&self, user_id: &str, timestamp: i64) -> Result<()> {
+        // ---
+        self.inner.set_revoked_before(user_id, timestamp).await
+    }
+
+    async fn revocation_status(&self, jti: &str, user_id: &str) -> Result<RevocationStatus> {
+        // ---
+        self.inner.revocation_status(jti, user_id).await
+    }
+
+    async fn store_ticket(&self, ticket: &str, claims: &Claims, ttl_seconds: i64) -> Result<()> {
+        // ---
+        self.inner.store_ticket(ticket, claims, ttl_seconds).await
+    }
+
+    async fn redeem_ticket(&self, ticket: &str) -> Result<Option<Claims>> {
+        // ---
+        self.inner.redeem_ticket(ticket).await
+    }
+
+    async fn store_magic_link(&self, token: &str, claims: &Claims, ttl_seconds: i64) -> Result<()> {
+        // ---
+        self.inner
+            .store_magic_link(token, claims, ttl_seconds)
+            .await
+    }
+
+    async fn redeem_magic_link(&self, token: &str) -> Result<Option<Claims>> {
+        // ---
+        self.inner.redeem_magic_link(token).await
+    }
+
+    async fn store_key_entry(
+        &self,
+        kind: KeyKind,
+        id: &str,
+        owner: Option<&str>,
+        entry: &str,
+    ) -> Result<()> {
+        // ---
+        self.inner.store_key_entry(kind, id, owner, entry).await
+    }
+
+    async fn find_key_entry(&self, kind: KeyKind, id: &str) -> Result<Option<String>> {
+        // ---
+        self.inner.find_key_entry(kind, id).await
+    }
+
+    async fn list_key_entries(&self, kind: KeyKind) -> Result<Vec<(String, String)>> {
+        // ---
+        self.inner.list_key_entries(kind).await
+    }
+
+    async fn delete_key_entry(&self, kind: KeyKind, id: &str) -> Result<bool> {
+        // ---
+        self.inner.delete_key_entry(kind, id).await
+    }
+
+    async fn delete_user_key_entries(&self, kind: KeyKind, user_id: &str) -> Result<usize> {
+        // ---
+        self.inner.delete_user_key_entries(kind, user_id).await
+    }
+
+    async fn ping(&self) -> Result<()> {
+        // ---
+        self.inner.ping().await
+    }
+}
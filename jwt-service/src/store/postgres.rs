@@ -0,0 +1,609 @@
+// jwt-service/src/store/postgres.rs
+
+//! [`TokenStore`] in Postgres (`postgres` feature)
+
+use anyhow::{anyhow, Context, Result};
+use sqlx::postgres::PgPoolOptions;
+use sqlx::{PgPool, Row};
+use std::time::Duration;
+use tokn_core::{keys, SharedClock};
+
+// ---
+
+use super::{open_successor, seal_successor, RevocationStatus, RevokedToken, TokenStore};
+use crate::{Claims, KeyKind, RefreshTokenData, RefreshTokenEntry};
+
+// ---
+
+/// Refresh tokens and the blacklist in Postgres tables; clones share the
+/// connection pool.
+///
+/// The tables come from the migrations in `jwt-service/migrations`, prefixed
+/// so they can share a database with oauth2-server. Refresh tokens are keyed
+/// by [`keys::refresh_token_hash`], so read access to the tables yields no
+/// token that can be replayed.
+///
+/// Rows carry their expiry (Unix timestamp, by `clock`): expired rows are
+/// ignored by every query and deleted by
+/// [`purge_expired`](Self::purge_expired), which the `token_store_cleanup`
+/// job runs periodically. Token versions, revocation cutoffs, and keys never
+/// expire. A refresh token is consumed by a single `DELETE ... RETURNING`,
+/// so concurrent refreshes with the same token cannot both succeed.
+#[derive(Clone)]
+pub struct PostgresStore {
+    // ---
+    pool: PgPool,
+    clock: SharedClock,
+}
+
+impl PostgresStore {
+    // ---
+    /// Connect to `database_url` and apply pending migrations.
+    ///
+    /// Waiting for a connection is capped at 5 seconds, so an unreachable
+    /// database fails fast (and startup can retry, see
+    /// `tokn_resilience::retry`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be reached or a migration
+    /// fails to apply.
+    pub async fn connect(database_url: &str, clock: SharedClock) -> Result<Self> {
+        // ---
+        let pool = PgPoolOptions::new()
+            .max_connections(5)
+            .acquire_timeout(Duration::from_secs(5))
+            .connect(database_url)
+            .await
+            .context("Failed to connect to the token store database")?;
+        Self::with_pool(pool, clock).await
+    }
+
+    /// Use `pool`, applying pending migrations.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a migration fails to apply.
+    pub async fn with_pool(pool: PgPool, clock: SharedClock) -> Result<Self> {
+        // ---
+        // oauth2-server's migrations share the history table when both use
+        // one database
+        let mut migrator = sqlx::migrate!("./migrations");
+        migrator.set_ignore_missing(true);
+        migrator
+            .run(&pool)
+            .await
+            .context("Failed to migrate the token store tables")?;
+        Ok(Self { pool, clock })
+    }
+
+    // ---
+    /// Delete the rows that have expired, returning how many.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a delete fails; earlier deletes stay committed.
+    pub async fn purge_expired(&self) -> Result<u64> {
+        // ---
+        let now = self.clock.timestamp();
+        let mut purged = 0;
+        for table in [
+            "jwt_refresh_tokens",
+            "jwt_used_refresh_tokens",
+            "jwt_refresh_successors",
+            "jwt_revoked_tokens",
+            "jwt_consumed_tokens",
+            "jwt_tickets",
+            "jwt_magic_links",
+        ] {
+            purged += sqlx::query(&format!("DELETE FROM {table} WHERE expires_at <= $1"))
+                .bind(now)
+                .execute(&self.pool)
+                .await
+                .with_context(|| format!("Failed to purge expired rows from {table}"))?
+                .rows_affected();
+        }
+        Ok(purged)
+    }
+}
+
+impl TokenStore for PostgresStore {
+    // ---
+    async fn store_refresh_token(
+        &self,
+        refresh_token: &str,
+        data: &RefreshTokenData,
+        ttl_seconds: i64,
+    ) -> Result<()> {
+        // ---
+        let now = self.clock.timestamp();
+        let json = serde_json::to_string(data).context("Failed to serialize refresh token data")?;
+        sqlx::query(
+            "INSERT INTO jwt_refresh_tokens (token_hash, user_id, session_id, data, expires_at)
+             VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(keys::refresh_token_hash(refresh_token))
+        .bind(&data.user_id)
+        .bind(&data.session_id)
+        .bind(json)
+        .bind(now + ttl_seconds)
+        .execute(&self.pool)
+        .await
+        .context("Failed to store refresh token")?;
+        Ok(())
+    }
+
+    async fn validate_refresh_token(&self, refresh_token: &str) -> Result<RefreshTokenData> {
+        // ---
+        let now = self.clock.timestamp();
+        let token_hash = keys::refresh_token_hash(refresh_token);
+        let row = sqlx::query(
+            "DELETE FROM jwt_refresh_tokens WHERE token_hash = $1 RETURNING data, expires_at",
+        )
+        .bind(&token_hash)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to consume refresh token")?;
+
+        let (json, expires_at): (String, i64) = match row {
+            Some(row) => (row.try_get("data")?, row.try_get("expires_at")?),
+            None => return Err(anyhow!("Invalid or expired refresh token")),
+        };
+        if expires_at <= now {
+            return Err(anyhow!("Invalid or expired refresh token"));
+        }
+        let data = serde_json::from_str(&json).context("Invalid refresh token data format")?;
+
+        // Remember the token was used until it would have expired
+        sqlx::query(
+            "INSERT INTO jwt_used_refresh_tokens (token_hash, data, expires_at)
+             VALUES ($1, $2, $3)
+             ON CONFLICT (token_hash) DO UPDATE SET data = $2, expires_at = $3",
+        )
+        .bind(token_hash)
+        .bind(&json)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await
+        .context("Failed to mark refresh token as used")?;
+        Ok(data)
+    }
+
+    async fn revoke_refresh_token(&self, refresh_token: &str) -> Result<Option<RefreshTokenData>> {
+        // ---
+        let now = self.clock.timestamp();
+        let json: Option<String> = sqlx::query_scalar(
+            "DELETE FROM jwt_refresh_tokens WHERE token_hash = $1 AND expires_at > $2
+             RETURNING data",
+        )
+        .bind(keys::refresh_token_hash(refresh_token))
+        .bind(now)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to delete refresh token")?;
+
+        json.map(|json| serde_json::from_str(&json).context("Invalid refresh token data format"))
+            .transpose()
+    }
+
+    async fn refresh_token_reused(&self, refresh_token: &str) -> Result<Option<RefreshTokenData>> {
+        // ---
+        let now = self.clock.timestamp();
+        let json: Option<String> = sqlx::query_scalar(
+            "SELECT data FROM jwt_used_refresh_tokens WHERE token_hash = $1 AND expires_at > $2",
+        )
+        .bind(keys::refresh_token_hash(refresh_token))
+        .bind(now)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to check refresh token reuse")?;
+
+        json.map(|json| serde_json::from_str(&json).context("Invalid refresh token data format"))
+            .transpose()
+    }
+
+    async fn store_refresh_successor(
+        &self,
+        refresh_token: &str,
+        successor: &str,
+        ttl_seconds: i64,
+    ) -> Result<()> {
+        // ---
+        let now = self.clock.timestamp();
+        sqlx::query(
+            "INSERT INTO jwt_refresh_successors (token_hash, successor, expires_at)
+             VALUES ($1, $2, $3)
+             ON CONFLICT (token_hash) DO UPDATE SET successor = $2, expires_at = $3",
+        )
+        .bind(keys::refresh_token_hash(refresh_token))
+        .bind(seal_successor(refresh_token, successor)?)
+        .bind(now + ttl_seconds)
+        .execute(&self.pool)
+        .await
+        .context("Failed to store refresh token successor")?;
+        Ok(())
+    }
+
+    async fn refresh_successor(
+        &self,
+        refresh_token: &str,
+    ) -> Result<Option<(String, RefreshTokenData)>> {
+        // ---
+        let now = self.clock.timestamp();
+        let sealed: Option<String> = sqlx::query_scalar(
+            "SELECT successor FROM jwt_refresh_successors
+             WHERE token_hash = $1 AND expires_at > $2",
+        )
+        .bind(keys::refresh_token_hash(refresh_token))
+        .bind(now)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to read refresh token successor")?;
+        let successor = sealed.and_then(|sealed| open_successor(refresh_token, &sealed));
+        let Some(successor) = successor else {
+            return Ok(None);
+        };
+
+        let json: Option<String> = sqlx::query_scalar(
+            "SELECT data FROM jwt_refresh_tokens WHERE token_hash = $1 AND expires_at > $2",
+        )
+        .bind(keys::refresh_token_hash(&successor))
+        .bind(now)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to read refresh token successor")?;
+        json.map(|json| {
+            let data = serde_json::from_str(&json).context("Invalid refresh token data format")?;
+            Ok((successor, data))
+        })
+        .transpose()
+    }
+
+    async fn list_user_sessions(&self, user_id: &str) -> Result<Vec<RefreshTokenEntry>> {
+        // ---
+        let now = self.clock.timestamp();
+        let rows = sqlx::query(
+            "SELECT token_hash, data, expires_at FROM jwt_refresh_tokens
+             WHERE user_id = $1 AND session_id IS NOT NULL AND expires_at > $2",
+        )
+        .bind(user_id)
+        .bind(now)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to list sessions")?;
+
+        let mut sessions = Vec::with_capacity(rows.len());
+        for row in rows {
+            let json: String = row.try_get("data")?;
+            let expires_at: i64 = row.try_get("expires_at")?;
+            sessions.push(RefreshTokenEntry {
+                token_hash: row.try_get("token_hash")?,
+                data: serde_json::from_str(&json).context("Invalid refresh token data format")?,
+                ttl_seconds: expires_at - now,
+            });
+        }
+
+        sessions.sort_by_key(|entry| entry.data.session_started_at);
+        Ok(sessions)
+    }
+
+    async fn delete_user_session(&self, user_id: &str, session_id: &str) -> Result<bool> {
+        // ---
+        let now = self.clock.timestamp();
+        let deleted = sqlx::query(
+            "DELETE FROM jwt_refresh_tokens
+             WHERE user_id = $1 AND session_id = $2 AND expires_at > $3",
+        )
+        .bind(user_id)
+        .bind(session_id)
+        .bind(now)
+        .execute(&self.pool)
+        .await
+        .context("Failed to end session")?;
+        Ok(deleted.rows_affected() > 0)
+    }
+
+    async fn revoke_token(&self, jti: &str, ttl_seconds: i64) -> Result<()> {
+        // ---
+        let now = self.clock.timestamp();
+        sqlx::query(
+            "INSERT INTO jwt_revoked_tokens (jti, expires_at) VALUES ($1, $2)
+             ON CONFLICT (jti) DO UPDATE
+             SET expires_at = GREATEST(jwt_revoked_tokens.expires_at, $2)",
+        )
+        .bind(jti)
+        .bind(now + ttl_seconds)
+        .execute(&self.pool)
+        .await
+        .context("Failed to revoke token")?;
+        Ok(())
+    }
+
+    async fn is_token_revoked(&self, jti: &str) -> Result<bool> {
+        // ---
+        let now = self.clock.timestamp();
+        let revoked: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM jwt_revoked_tokens WHERE jti = $1 AND expires_at > $2)",
+        )
+        .bind(jti)
+        .bind(now)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to check token revocation status")?;
+        Ok(revoked)
+    }
+
+    async fn revoked_token_ttl(&self, jti: &str) -> Result<Option<i64>> {
+        // ---
+        let now = self.clock.timestamp();
+        let expires_at: Option<i64> = sqlx::query_scalar(
+            "SELECT expires_at FROM jwt_revoked_tokens WHERE jti = $1 AND expires_at > $2",
+        )
+        .bind(jti)
+        .bind(now)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to read revoked token")?;
+        Ok(expires_at.map(|expires_at| expires_at - now))
+    }
+
+    async fn list_revoked_tokens(&self, limit: usize) -> Result<Vec<RevokedToken>> {
+        // ---
+        let now = self.clock.timestamp();
+        let rows = sqlx::query(
+            "SELECT jti, expires_at FROM jwt_revoked_tokens
+             WHERE expires_at > $1 ORDER BY jti LIMIT $2",
+        )
+        .bind(now)
+        .bind(i64::try_from(limit).unwrap_or(i64::MAX))
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to list revoked tokens")?;
+
+        rows.into_iter()
+            .map(|row| {
+                let expires_at: i64 = row.try_get("expires_at")?;
+                Ok(RevokedToken {
+                    jti: row.try_get("jti")?,
+                    ttl_seconds: expires_at - now,
+                })
+            })
+            .collect()
+    }
+
+    async fn unrevoke_token(&self, jti: &str) -> Result<bool> {
+        // ---
+        let now = self.clock.timestamp();
+        let deleted =
+            sqlx::query("DELETE FROM jwt_revoked_tokens WHERE jti = $1 AND expires_at > $2")
+                .bind(jti)
+                .bind(now)
+                .execute(&self.pool)
+                .await
+                .context("Failed to unrevoke token")?;
+        Ok(deleted.rows_affected() > 0)
+    }
+
+    async fn consume_token(&self, jti: &str, ttl_seconds: i64) -> Result<bool> {
+        // ---
+        // An expired entry is taken over; a live one wins the conflict
+        let now = self.clock.timestamp();
+        let consumed = sqlx::query(
+            "INSERT INTO jwt_consumed_tokens (jti, expires_at) VALUES ($1, $2)
+             ON CONFLICT (jti) DO UPDATE SET expires_at = $2
+             WHERE jwt_consumed_tokens.expires_at <= $3",
+        )
+        .bind(jti)
+        .bind(now + ttl_seconds)
+        .bind(now)
+        .execute(&self.pool)
+        .await
+        .context("Failed to consume token")?;
+        Ok(consumed.rows_affected() > 0)
+    }
+
+    async fn token_version(&self, user_id: &str) -> Result<u64> {
+        // ---
+        let version: Option<i64> =
+            sqlx::query_scalar("SELECT version FROM jwt_token_versions WHERE user_id = $1")
+                .bind(user_id)
+                .fetch_optional(&self.pool)
+                .await
+                .context("Failed to read token version")?;
+        Ok(version.map_or(0, |version| version as u64))
+    }
+
+    async fn bump_token_version(&self, user_id: &str) -> Result<u64> {
+        // ---
+        let version: i64 = sqlx::query_scalar(
+            "INSERT INTO jwt_token_versions (user_id, version) VALUES ($1, 1)
+             ON CONFLICT (user_id) DO UPDATE
+             SET version = jwt_token_versions.version + 1
+             RETURNING version",
+        )
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to bump token version")?;
+        Ok(version as u64)
+    }
+
+    async fn revoked_before(&self, user_id: &str) -> Result<Option<i64>> {
+        // ---
+        sqlx::query_scalar("SELECT revoked_before FROM jwt_revoked_before WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await
+            .context("Failed to read revocation cutoff")
+    }
+
+    async fn set_revoked_before(&self, user_id: &str, timestamp: i64) -> Result<()> {
+        // ---
+        sqlx::query(
+            "INSERT INTO jwt_revoked_before (user_id, revoked_before) VALUES ($1, $2)
+             ON CONFLICT (user_id) DO UPDATE SET revoked_before = EXCLUDED.revoked_before",
+        )
+        .bind(user_id)
+        .bind(timestamp)
+        .execute(&self.pool)
+        .await
+        .context("Failed to set revocation cutoff")?;
+        Ok(())
+    }
+
+    async fn revocation_status(&self, jti: &str, user_id: &str) -> Result<RevocationStatus> {
+        // ---
+        let now = self.clock.timestamp();
+        let row = sqlx::query(
+            "SELECT
+                 EXISTS (SELECT 1 FROM jwt_revoked_tokens WHERE jti = $1 AND expires_at > $3)
+                     AS blacklisted,
+                 (SELECT version FROM jwt_token_versions WHERE user_id = $2) AS version,
+                 (SELECT revoked_before FROM jwt_revoked_before WHERE user_id = $2)
+                     AS revoked_before",
+        )
+        .bind(jti)
+        .bind(user_id)
+        .bind(now)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to check token revocation status")?;
+
+        let version: Option<i64> = row.try_get("version")?;
+        Ok(RevocationStatus {
+            blacklisted: row.try_get("blacklisted")?,
+            token_version: version.map_or(0, |version| version as u64),
+            revoked_before: row.try_get("revoked_before")?,
+        })
+    }
+
+    async fn store_ticket(&self, ticket: &str, claims: &Claims, ttl_seconds: i64) -> Result<()> {
+        // ---
+        let now = self.clock.timestamp();
+        let json = serde_json::to_string(claims).context("Failed to serialize ticket claims")?;
+        sqlx::query("INSERT INTO jwt_tickets (ticket, claims, expires_at) VALUES ($1, $2, $3)")
+            .bind(ticket)
+            .bind(json)
+            .bind(now + ttl_seconds)
+            .execute(&self.pool)
+            .await
+            .context("Failed to store ticket")?;
+        Ok(())
+    }
+
+    async fn redeem_ticket(&self, ticket: &str) -> Result<Option<Claims>> {
+        // ---
+        let now = self.clock.timestamp();
+        let json: Option<String> = sqlx::query_scalar(
+            "DELETE FROM jwt_tickets WHERE ticket = $1 AND expires_at > $2 RETURNING claims",
+        )
+        .bind(ticket)
+        .bind(now)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to redeem ticket")?;
+
+        json.map(|json| serde_json::from_str(&json).context("Invalid ticket claims format"))
+            .transpose()
+    }
+
+    async fn store_magic_link(&self, token: &str, claims: &Claims, ttl_seconds: i64) -> Result<()> {
+        // ---
+        let now = self.clock.timestamp();
+        let json =
+            serde_json::to_string(claims).context("Failed to serialize magic link claims")?;
+        sqlx::query("INSERT INTO jwt_magic_links (token, claims, expires_at) VALUES ($1, $2, $3)")
+            .bind(token)
+            .bind(json)
+            .bind(now + ttl_seconds)
+            .execute(&self.pool)
+            .await
+            .context("Failed to store magic link")?;
+        Ok(())
+    }
+
+    async fn redeem_magic_link(&self, token: &str) -> Result<Option<Claims>> {
+        // ---
+        let now = self.clock.timestamp();
+        let json: Option<String> = sqlx::query_scalar(
+            "DELETE FROM jwt_magic_links WHERE token = $1 AND expires_at > $2 RETURNING claims",
+        )
+        .bind(token)
+        .bind(now)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to redeem magic link")?;
+
+        json.map(|json| serde_json::from_str(&json).context("Invalid magic link claims format"))
+            .transpose()
+    }
+
+    async fn store_key_entry(
+        &self,
+        kind: KeyKind,
+        id: &str,
+        owner: Option<&str>,
+        entry: &str,
+    ) -> Result<()> {
+        // ---
+        sqlx::query(
+            "INSERT INTO jwt_keys (kind, id, owner, entry) VALUES ($1, $2, $3, $4)
+             ON CONFLICT (kind, id) DO UPDATE SET owner = $3, entry = $4",
+        )
+        .bind(kind.as_str())
+        .bind(id)
+        .bind(owner)
+        .bind(entry)
+        .execute(&self.pool)
+        .await
+        .with_context(|| format!("Failed to store {kind} key"))?;
+        Ok(())
+    }
+
+    async fn find_key_entry(&self, kind: KeyKind, id: &str) -> Result<Option<String>> {
+        // ---
+        sqlx::query_scalar("SELECT entry FROM jwt_keys WHERE kind = $1 AND id = $2")
+            .bind(kind.as_str())
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .with_context(|| format!("Failed to look up {kind} key"))
+    }
+
+    async fn list_key_entries(&self, kind: KeyKind) -> Result<Vec<(String, String)>> {
+        // ---
+        sqlx::query_as("SELECT id, entry FROM jwt_keys WHERE kind = $1")
+            .bind(kind.as_str())
+            .fetch_all(&self.pool)
+            .await
+            .with_context(|| format!("Failed to list {kind} keys"))
+    }
+
+    async fn delete_key_entry(&self, kind: KeyKind, id: &str) -> Result<bool> {
+        // ---
+        let deleted = sqlx::query("DELETE FROM jwt_keys WHERE kind = $1 AND id = $2")
+            .bind(kind.as_str())
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .with_context(|| format!("Failed to delete {kind} key"))?;
+        Ok(deleted.rows_affected() > 0)
+    }
+
+    async fn delete_user_key_entries(&self, kind: KeyKind, user_id: &str) -> Result<usize> {
+        // ---
+        let deleted = sqlx::query("DELETE FROM jwt_keys WHERE kind = $1 AND owner = $2")
+            .bind(kind.as_str())
+            .bind(user_id)
+            .execute(&self.pool)
+            .await
+            .with_context(|| format!("Failed to delete the user's {kind} keys"))?;
+        Ok(deleted.rows_affected() as usize)
+    }
+
+    async fn ping(&self) -> Result<()> {
+        // ---
+        sqlx::query("SELECT 1").execute(&self.pool).await?;
+        Ok(())
+    }
+}
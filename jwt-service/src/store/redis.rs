@@ -0,0 +1,229 @@
+// jwt-service/src/store/redis.rs
+
+//! [`TokenStore`] in Redis: the refresh token, blacklist, ticket, magic
+//! link, and key helpers of `refresh.rs`, `revoke.rs`, `ticket.rs`,
+//! `magic_link.rs`, and `hashed_keys.rs`, over a [`RedisConnection`]
+
+use anyhow::Result;
+
+// ---
+
+use super::{RevocationStatus, RevokedToken, TokenStore};
+use crate::{
+    hashed_keys, magic_link, refresh, revoke, ticket, Claims, KeyKind, RedisConnection,
+    RefreshTokenData, RefreshTokenEntry,
+};
+
+// ---
+
+/// Refresh tokens and the blacklist in Redis, behind the connection's retries
+/// and circuit breaker. Entries expire by Redis TTL.
+#[derive(Clone)]
+pub struct RedisStore {
+    // ---
+    redis: RedisConnection,
+}
+
+impl RedisStore {
+    // ---
+    /// Keep tokens in Redis over `redis`.
+    pub fn new(redis: RedisConnection) -> Self {
+        // ---
+        Self { redis }
+    }
+}
+
+impl TokenStore for RedisStore {
+    // ---
+    async fn store_refresh_token(
+        &self,
+        refresh_token: &str,
+        data: &RefreshTokenData,
+        ttl_seconds: i64,
+    ) -> Result<()> {
+        // ---
+        let mut redis = self.redis.clone();
+        refresh::store_refresh_token(&mut redis, refresh_token, data, ttl_seconds).await
+    }
+
+    async fn validate_refresh_token(&self, refresh_token: &str) -> Result<RefreshTokenData> {
+        // ---
+        let mut redis = self.redis.clone();
+        refresh::validate_refresh_token(&mut redis, refresh_token).await
+    }
+
+    async fn revoke_refresh_token(&self, refresh_token: &str) -> Result<Option<RefreshTokenData>> {
+        // ---
+        let mut redis = self.redis.clone();
+        refresh::revoke_refresh_token(&mut redis, refresh_token).await
+    }
+
+    async fn refresh_token_reused(&self, refresh_token: &str) -> Result<Option<RefreshTokenData>> {
+        // ---
+        let mut redis = self.redis.clone();
+        refresh::refresh_token_reused(&mut redis, refresh_token).await
+    }
+
+    async fn store_refresh_successor(
+        &self,
+        refresh_token: &str,
+        successor: &str,
+        ttl_seconds: i64,
+    ) -> Result<()> {
+        // ---
+        let mut redis = self.redis.clone();
+        refresh::store_refresh_successor(&mut redis, refresh_token, successor, ttl_seconds).await
+    }
+
+    async fn refresh_successor(
+        &self,
+        refresh_token: &str,
+    ) -> Result<Option<(String, RefreshTokenData)>> {
+        // ---
+        let mut redis = self.redis.clone();
+        refresh::refresh_successor(&mut redis, refresh_token).await
+    }
+
+    async fn list_user_sessions(&self, user_id: &str) -> Result<Vec<RefreshTokenEntry>> {
+        // ---
+        let mut redis = self.redis.clone();
+        refresh::list_user_sessions(&mut redis, user_id).await
+    }
+
+    async fn delete_user_session(&self, user_id: &str, session_id: &str) -> Result<bool> {
+        // ---
+        let mut redis = self.redis.clone();
+        refresh::delete_user_session(&mut redis, user_id, session_id).await
+    }
+
+    async fn revoke_token(&self, jti: &str, ttl_seconds: i64) -> Result<()> {
+        // ---
+        let mut redis = self.redis.clone();
+        revoke::revoke_token(&mut redis, jti, ttl_seconds).await
+    }
+
+    async fn is_token_revoked(&self, jti: &str) -> Result<bool> {
+        // ---
+        let mut redis = self.redis.clone();
+        revoke::is_token_revoked(&mut redis, jti).await
+    }
+
+    async fn revoked_token_ttl(&self, jti: &str) -> Result<Option<i64>> {
+        // ---
+        let mut redis = self.redis.clone();
+        revoke::revoked_token_ttl(&mut redis, jti).await
+    }
+
+    async fn list_revoked_tokens(&self, limit: usize) -> Result<Vec<RevokedToken>> {
+        // ---
+        let mut redis = self.redis.clone();
+        revoke::list_revoked_tokens(&mut redis, limit).await
+    }
+
+    async fn unrevoke_token(&self, jti: &str) -> Result<bool> {
+        // ---
+        let mut redis = self.redis.clone();
+        revoke::unrevoke_token(&mut redis, jti).await
+    }
+
+    async fn consume_token(&self, jti: &str, ttl_seconds: i64) -> Result<bool> {
+        // ---
+        let mut redis = self.redis.clone();
+        revoke::consume_token(&mut redis, jti, ttl_seconds).await
+    }
+
+    async fn token_version(&self, user_id: &str) -> Result<u64> {
+        // ---
+        let mut redis = self.redis.clone();
+        revoke::token_version(&mut redis, user_id).await
+    }
+
+    async fn bump_token_version(&self, user_id: &str) -> Result<u64> {
+        // ---
+        let mut redis = self.redis.clone();
+        revoke::bump_token_version(&mut redis, user_id).await
+    }
+
+    async fn revoked_before(&self, user_id: &str) -> Result<Option<i64>> {
+        // ---
+        let mut redis = self.redis.clone();
+        revoke::revoked_before(&mut redis, user_id).await
+    }
+
+    async fn set_revoked_before(&self, user_id: &str, timestamp: i64) -> Result<()> {
+        // ---
+        let mut redis = self.redis.clone();
+        revoke::set_revoked_before(&mut redis, user_id, timestamp).await
+    }
+
+    async fn revocation_status(&self, jti: &str, user_id: &str) -> Result<RevocationStatus> {
+        // ---
+        let mut redis = self.redis.clone();
+        revoke::revocation_status(&mut redis, jti, user_id).await
+    }
+
+    async fn store_ticket(&self, ticket: &str, claims: &Claims, ttl_seconds: i64) -> Result<()> {
+        // ---
+        let mut redis = self.redis.clone();
+        ticket::store_ticket(&mut redis, ticket, claims, ttl_seconds).await
+    }
+
+    async fn redeem_ticket(&self, ticket: &str) -> Result<Option<Claims>> {
+        // ---
+        let mut redis = self.redis.clone();
+        ticket::redeem_ticket(&mut redis, ticket).await
+    }
+
+    async fn store_magic_link(&self, token: &str, claims: &Claims, ttl_seconds: i64) -> Result<()> {
+        // ---
+        let mut redis = self.redis.clone();
+        magic_link::store_magic_link(&mut redis, token, claims, ttl_seconds).await
+    }
+
+    async fn redeem_magic_link(&self, token: &str) -> Result<Option<Claims>> {
+        // ---
+        let mut redis = self.redis.clone();
+        magic_link::redeem_magic_link(&mut redis, token).await
+    }
+
+    async fn store_key_entry(
+        &self,
+        kind: KeyKind,
+        id: &str,
+        owner: Option<&str>,
+        entry: &str,
+    ) -> Result<()> {
+        // ---
+        let mut redis = self.redis.clone();
+        hashed_keys::store_key_entry(&mut redis, kind, id, owner, entry).await
+    }
+
+    async fn find_key_entry(&self, kind: KeyKind, id: &str) -> Result<Option<String>> {
+        // ---
+        let mut redis = self.redis.clone();
+        hashed_keys::find_key_entry(&mut redis, kind, id).await
+    }
+
+    async fn list_key_entries(&self, kind: KeyKind) -> Result<Vec<(String, String)>> {
+        // ---
+        let mut redis = self.redis.clone();
+        hashed_keys::list_key_entries(&mut redis, kind).await
+    }
+
+    async fn delete_key_entry(&self, kind: KeyKind, id: &str) -> Result<bool> {
+        // ---
+        let mut redis = self.redis.clone();
+        hashed_keys::delete_key_entry(&mut redis, kind, id).await
+    }
+
+    async fn delete_user_key_entries(&self, kind: KeyKind, user_id: &str) -> Result<usize> {
+        // ---
+        let mut redis = self.redis.clone();
+        hashed_keys::delete_user_key_entries(&mut redis, kind, user_id).await
+    }
+
+    async fn ping(&self) -> Result<()> {
+        // ---
+        Ok(self.redis.ping().await?)
+    }
+}
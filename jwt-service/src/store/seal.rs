@@ -0,0 +1,63 @@
+// jwt-service/src/store/seal.rs
+
+//! Sealed refresh token successors
+//!
+//! During the refresh grace window every store remembers which token a
+//! rotated refresh token became. The successor is a live refresh token, so
+//! it is kept sealed with AES-256-GCM under a key derived from the rotated
+//! token: the entry yields nothing without that token, and a tampered entry
+//! fails to open instead of naming another successor.
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Nonce};
+use anyhow::{anyhow, Result};
+use sha2::{Digest, Sha256};
+use tokn_core::keys;
+
+// ---
+
+/// Length of an AES-GCM nonce, in bytes.
+const NONCE_LEN: usize = 12;
+
+// ---
+
+/// `successor` sealed with `refresh_token`: a random nonce and the
+/// ciphertext, hex encoded.
+///
+/// # Errors
+///
+/// Returns error if encryption fails.
+pub(crate) fn seal_successor(refresh_token: &str, successor: &str) -> Result<String> {
+    // ---
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher(refresh_token)
+        .encrypt(&nonce, successor.as_bytes())
+        .map_err(|_| anyhow!("Failed to seal refresh token successor"))?;
+    Ok(hex::encode([nonce.as_slice(), &ciphertext].concat()))
+}
+
+/// The successor [`seal_successor`] sealed with `refresh_token`; `None` for
+/// another token's entry or a tampered one.
+pub(crate) fn open_successor(refresh_token: &str, sealed: &str) -> Option<String> {
+    // ---
+    let sealed = hex::decode(sealed).ok()?;
+    if sealed.len() < NONCE_LEN {
+        return None;
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    let successor = cipher(refresh_token)
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .ok()?;
+    String::from_utf8(successor).ok()
+}
+
+/// The cipher keyed by `refresh_token`. The key is domain-separated from the
+/// token's storage hash, which the stores keep in the clear.
+fn cipher(refresh_token: &str) -> Aes256Gcm {
+    // ---
+    let key = Sha256::new()
+        .chain_update(keys::REFRESH_SUCCESSOR_PREFIX)
+        .chain_update(refresh_token)
+        .finalize();
+    Aes256Gcm::new(&key)
+}
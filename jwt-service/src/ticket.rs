@@ -0,0 +1,75 @@
+// jwt-service/src/ticket.rs
+
+//! Single-use WebSocket/SSE tickets in Redis
+//!
+//! A ticket stands in for an access token where a client cannot set an
+//! `Authorization` header, such as a browser opening a WebSocket. It lives
+//! for seconds and is consumed by its first redemption.
+//! [`RedisStore`](crate::RedisStore) serves these to the handlers as a
+//! [`TokenStore`](crate::TokenStore).
+
+use anyhow::{Context, Result};
+use redis::aio::ConnectionLike;
+use redis::AsyncCommands;
+use tokn_core::{keys, Claims};
+
+// ---
+
+/// Store the `claims` a single-use `ticket` stands for.
+///
+/// # Storage Format
+///
+/// - Key: `ticket:{ticket}`
+/// - Value: JSON-serialized [`Claims`] of the access token the ticket was
+///   issued for
+/// - TTL: `ttl_seconds`
+///
+/// # Errors
+///
+/// Returns an error if the claims cannot be serialized or Redis cannot be
+/// written.
+pub async fn store_ticket<C>(
+    redis_conn: &mut C,
+    ticket: &str,
+    claims: &Claims,
+    ttl_seconds: i64,
+) -> Result<()>
+where
+    C: ConnectionLike + Send,
+{
+    // ---
+    let json = serde_json::to_string(claims).context("Failed to serialize ticket claims")?;
+
+    redis_conn
+        .set_ex::<_, _, ()>(keys::ticket(ticket), json, ttl_seconds as u64)
+        .await
+        .context("Failed to store ticket")
+}
+
+/// Consume `ticket` and return its claims.
+///
+/// Read and deleted in one `GETDEL` (Redis 6.2+), so of concurrent
+/// redemptions only one gets the claims.
+///
+/// # Returns
+///
+/// The claims, or `None` if the ticket was never issued, has expired, or was
+/// already redeemed.
+///
+/// # Errors
+///
+/// Returns an error if Redis cannot be reached or the stored claims do not
+/// parse.
+pub async fn redeem_ticket<C>(redis_conn: &mut C, ticket: &str) -> Result<Option<Claims>>
+where
+    C: ConnectionLike + Send,
+{
+    // ---
+    let json: Option<String> = redis_conn
+        .get_del(keys::ticket(ticket))
+        .await
+        .context("Failed to redeem ticket")?;
+
+    json.map(|json| serde_json::from_str(&json).context("Invalid ticket claims format"))
+        .transpose()
+}
@@ -0,0 +1,96 @@
+// oauth2-client/src/check.rs
+
+//! `--check-config`: load and validate the configuration, then probe the
+//! authorization server and Redis
+
+use anyhow::Context;
+use tokn_config::Secret;
+use tokn_core::SystemClock;
+use tokn_i18n::Localizer;
+use tokn_ratelimit::RateLimiter;
+use tokn_server::CheckReport;
+use tokn_theme::Themes;
+
+// ---
+
+use crate::Config;
+
+// ---
+
+/// Run every startup check oauth2-client can make without serving.
+pub async fn check_config() -> CheckReport {
+    // ---
+    let mut report = CheckReport::new("oauth2-client");
+
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            report.fail("config", format!("{e:#}"));
+            return report;
+        }
+    };
+    report.pass("config", format!("loaded (profile {})", config.profile));
+
+    report.secret(
+        "oauth2.client_secret",
+        Some(config.oauth2.client_secret.expose()),
+    );
+    report.secret(
+        "admin.token",
+        config.admin.token.as_ref().map(Secret::expose),
+    );
+    report.secret(
+        "service_auth.key",
+        config.service_auth.key.as_ref().map(Secret::expose),
+    );
+    report.tls(config.server.tls.as_ref()).await;
+
+    match Localizer::new(&config.i18n) {
+        Ok(_) => report.pass(
+            "i18n",
+            format!("default locale {}", config.i18n.default_locale),
+        ),
+        Err(e) => report.fail("i18n", e.to_string()),
+    }
+    match Themes::new(&config.theme) {
+        Ok(_) => report.pass("theme", format!("default theme {}", config.theme.name)),
+        Err(e) => report.fail("theme", e.to_string()),
+    }
+
+    // ---
+    // Any HTTP response proves the token endpoint is reachable; a GET is
+    // expected to be refused
+    report
+        .probe("authorization server", async {
+            let response = reqwest::get(&config.oauth2.token_url)
+                .await
+                .with_context(|| format!("Failed to reach {}", config.oauth2.token_url))?;
+            Ok(format!(
+                "{} answered {}",
+                config.oauth2.token_url,
+                response.status().as_u16()
+            ))
+        })
+        .await;
+
+    if config.rate_limit.enabled {
+        report
+            .probe("redis", async {
+                RateLimiter::connect(
+                    "oauth2-client",
+                    &config.rate_limit,
+                    &config.redis.url,
+                    config.circuit_breaker,
+                    SystemClock::shared(),
+                )
+                .await
+                .context("Failed to connect to Redis (rate limiting)")?;
+                Ok("connected (rate limiting)".to_string())
+            })
+            .await;
+    } else {
+        report.skip("redis", "rate limiting disabled");
+    }
+
+    report
+}
@@ -1,30 +1,90 @@
 // oauth2-client/src/config.rs
 
-use anyhow::{Context, Result};
+use anyhow::Result;
 use serde::Deserialize;
-use std::env;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use tokn_config::{ConfigLoader, Profile, Secret};
+use tokn_i18n::I18nConfig;
+use tokn_ratelimit::{Algorithm, KeyBy, RateLimitConfig};
+use tokn_resilience::{ChaosConfig, ChaosTargets, CircuitBreakerConfig};
+use tokn_server::{
+    AdminConfig, Bind, CompressionAlgorithms, CompressionConfig, CorsConfig, CorsList, SecurityHeadersConfig,
+    ServiceAuthConfig, SocketMode, TlsConfig,
+};
+use tokn_telemetry::LogConfig;
+use tokn_theme::{ClientThemes, ThemeConfig};
 
 // ---
 
 /// Application configuration for the OAuth2 client.
 ///
-/// Contains server, Redis, and OAuth2 provider settings loaded from environment variables.
-#[derive(Debug, Clone, Deserialize)]
+/// Contains server, Redis, and OAuth2 provider settings loaded from defaults, an
+/// optional config file, and environment variables.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
 pub struct Config {
     // ---
+    /// Deployment profile (`TOKN_ENV`); `staging` and `prod` refuse weak
+    /// secrets, `prod` also requires TLS
+    #[serde(default)]
+    pub profile: Profile,
     pub server: ServerConfig,
     pub redis: RedisConfig,
     pub oauth2: OAuth2Config,
+    /// Circuit breaker around calls to the authorization server
+    #[serde(default)]
+    pub circuit_breaker: CircuitBreakerConfig,
+    /// Log filter (reloadable)
+    #[serde(default)]
+    pub log: LogConfig,
+    /// `/admin` endpoints
+    #[serde(default)]
+    pub admin: AdminConfig,
+    /// Page localization (`Accept-Language`, locale overrides)
+    #[serde(default)]
+    pub i18n: I18nConfig,
+    /// Page theming (partials, static assets, per-client themes)
+    #[serde(default)]
+    pub theme: ThemeConfig,
+    /// Per-client request rate limiting (Redis)
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+    /// Signed requests between tokn services
+    #[serde(default)]
+    pub service_auth: ServiceAuthConfig,
+    /// Fault injection into dependency calls (`chaos` builds only)
+    #[serde(default)]
+    pub chaos: ChaosConfig,
 }
 
 // ---
 
 /// HTTP server configuration.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
 pub struct ServerConfig {
     // ---
     pub host: String,
     pub port: u16,
+    /// Listen address overriding `host`/`port`: `host:port` or `unix:/path/to.sock`
+    #[serde(default)]
+    pub bind: Option<Bind>,
+    /// Unix socket file permissions (octal, e.g. 660)
+    #[serde(default)]
+    pub socket_mode: Option<SocketMode>,
+    /// Serve HTTPS with this certificate/key pair (plain HTTP when unset)
+    #[serde(default)]
+    pub tls: Option<TlsConfig>,
+    /// Response compression (gzip/br; token responses are never compressed)
+    #[serde(default)]
+    pub compression: CompressionConfig,
+    /// CORS for browser apps on other origins (off until origins are listed)
+    #[serde(default)]
+    pub cors: CorsConfig,
+    /// Security headers added to every response
+    #[serde(default)]
+    pub security_headers: SecurityHeadersConfig,
+    /// Seconds in-flight requests get to finish after `SIGTERM`/`SIGINT`
+    pub drain_timeout_seconds: u64,
 }
 
 // ---
@@ -32,7 +92,7 @@ pub struct ServerConfig {
 /// Redis connection configuration.
 ///
 /// Used for session storage and CSRF token validation.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
 pub struct RedisConfig {
     // ---
     pub url: String,
@@ -43,11 +103,11 @@ pub struct RedisConfig {
 /// OAuth2 provider configuration.
 ///
 /// Contains OAuth2 client credentials and endpoint URLs for the authorization server.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
 pub struct OAuth2Config {
     // ---
     pub client_id: String,
-    pub client_secret: String,
+    pub client_secret: Secret,
     pub redirect_uri: String,
     pub authorize_url: String,
     pub token_url: String,
@@ -58,60 +118,192 @@ pub struct OAuth2Config {
 
 impl Config {
     // ---
-    /// Loads configuration from environment variables.
+    /// Loads configuration: defaults → config file (`TOKN_CONFIG`) → environment.
     ///
-    /// Reads configuration from .env file if present, then from environment variables.
+    /// Reads `.env` if present.
+    ///
+    /// # Environment Variables
+    ///
+    /// - `TOKN_ENV` → `profile` (default: "dev"; "staging" or "prod" makes weak secrets fatal, "prod" also requires TLS)
+    /// - `CLIENT_HOST` → `server.host` (default: "127.0.0.1")
+    /// - `CLIENT_PORT` → `server.port` (default: "8081")
+    /// - `CLIENT_BIND` → `server.bind` (optional; `host:port` or `unix:/path`, overrides host/port)
+    /// - `CLIENT_SOCKET_MODE` → `server.socket_mode` (optional; octal, Unix sockets only)
+    /// - `CLIENT_TLS_CERT_PATH` → `server.tls.cert_path` (optional; enables HTTPS)
+    /// - `CLIENT_TLS_KEY_PATH` → `server.tls.key_path` (required with the certificate)
+    /// - `CLIENT_COMPRESSION` → `server.compression.algorithms` (default: "gzip,br"; "off" disables)
+    /// - `CLIENT_CORS_ALLOWED_ORIGINS` → `server.cors.allowed_origins` (optional; comma-separated origins or "*", enables CORS)
+    /// - `CLIENT_CORS_ALLOWED_METHODS` → `server.cors.allowed_methods` (default: "GET,POST")
+    /// - `CLIENT_CORS_ALLOWED_HEADERS` → `server.cors.allowed_headers` (default: "authorization,content-type,x-request-id")
+    /// - `CLIENT_CORS_ALLOW_CREDENTIALS` → `server.cors.allow_credentials` (default: "false"; not with "*" origins)
+    /// - `CLIENT_CONTENT_SECURITY_POLICY` → `server.security_headers.content_security_policy` (default: same-origin resources, no framing; "" omits the header)
+    /// - `CLIENT_FRAME_OPTIONS` → `server.security_headers.frame_options` (default: "DENY"; or "SAMEORIGIN", "" omits the header)
+    /// - `CLIENT_REFERRER_POLICY` → `server.security_headers.referrer_policy` (default: "no-referrer"; "" omits the header)
+    /// - `CLIENT_CONTENT_TYPE_OPTIONS` → `server.security_headers.content_type_options` (default: "nosniff"; "" omits the header)
+    /// - `CLIENT_DRAIN_TIMEOUT_SECONDS` → `server.drain_timeout_seconds` (default: "30"; time in-flight requests get to finish on shutdown)
+    /// - `REDIS_URL` → `redis.url` (default: "redis://127.0.0.1:6379")
+    /// - `OAUTH2_CLIENT_ID` → `oauth2.client_id` (required)
+    /// - `OAUTH2_CLIENT_SECRET` → `oauth2.client_secret` (required; or `OAUTH2_CLIENT_SECRET_FILE` naming a file that holds it)
+    /// - `OAUTH2_REDIRECT_URI` → `oauth2.redirect_uri` (default: "http://127.0.0.1:8081/callback")
+    /// - `OAUTH2_AUTHORIZE_URL` → `oauth2.authorize_url` (default: "http://127.0.0.1:8082/v1/oauth/authorize")
+    /// - `OAUTH2_TOKEN_URL` → `oauth2.token_url` (default: "http://127.0.0.1:8082/v1/oauth/token")
+    /// - `OAUTH2_USERINFO_URL` → `oauth2.userinfo_url` (default: "http://127.0.0.1:8082/v1/oauth/userinfo")
+    /// - `CIRCUIT_BREAKER_FAILURE_THRESHOLD` → `circuit_breaker.failure_threshold` (default: "5")
+    /// - `CIRCUIT_BREAKER_OPEN_SECONDS` → `circuit_breaker.open_seconds` (default: "30")
+    /// - `CIRCUIT_BREAKER_CALL_TIMEOUT_MS` → `circuit_breaker.call_timeout_ms` (default: "5000")
+    /// - `RUST_LOG` → `log.filter` (optional; reloadable)
+    /// - `ADMIN_TOKEN` → `admin.token` (optional; enables `/admin`, at least 32 characters)
+    /// - `I18N_DEFAULT_LOCALE` → `i18n.default_locale` (default: "en"; used when `Accept-Language` matches no translation)
+    /// - `I18N_LOCALE` → `i18n.locale` (optional; serve every page in this locale)
+    /// - `I18N_DIR` → `i18n.dir` (optional; `<locale>/*.ftl` files overriding or adding translations)
+    /// - `THEME_DIR` → `theme.dir` (optional; directory of named themes)
+    /// - `THEME_NAME` → `theme.name` (default: "default"; theme for pages without a client-specific one)
+    /// - `THEME_CLIENTS` → `theme.clients` (optional; `client_id=theme` pairs, comma-separated)
+    /// - `RATE_LIMIT_ENABLED` → `rate_limit.enabled` (default: "false"; limit requests per client IP in Redis)
+    /// - `RATE_LIMIT_ALGORITHM` → `rate_limit.algorithm` (default: "sliding_window"; or "token_bucket")
+    /// - `RATE_LIMIT_REQUESTS` → `rate_limit.requests` (default: "100"; per window)
+    /// - `RATE_LIMIT_WINDOW_SECONDS` → `rate_limit.window_seconds` (default: "60")
+    /// - `RATE_LIMIT_BURST` → `rate_limit.burst` (optional; token bucket capacity, defaults to the request limit)
+    /// - `RATE_LIMIT_TRUST_FORWARDED_FOR` → `rate_limit.trust_forwarded_for` (default: "false"; identify clients by `X-Forwarded-For`)
+    /// - `RATE_LIMIT_KEY_BY` → `rate_limit.key_by` (default: "ip"; "user" or "ip_and_user" count authenticated users)
+    /// - `RATE_LIMIT_ENDPOINTS` → `rate_limit.endpoints` (optional; per-path limits as an inline table, usually set in the config file instead)
+    /// - `SERVICE_AUTH_KEY` → `service_auth.key` (optional; shared by all tokn services to sign requests to each other, at least 32 characters)
+    /// - `SERVICE_AUTH_MAX_AGE_SECONDS` → `service_auth.max_age_seconds` (default: "300"; accepted clock difference for signed requests)
+    /// - `CHAOS_FAILURE_PERCENT` → `chaos.failure_percent` (default: "0"; fail this share of dependency calls, `chaos` builds only)
+    /// - `CHAOS_DELAY_PERCENT` → `chaos.delay_percent` (default: "0"; delay this share of dependency calls, `chaos` builds only)
+    /// - `CHAOS_DELAY_MS` → `chaos.delay_ms` (default: "1000")
+    /// - `CHAOS_TARGETS` → `chaos.targets` (default: all; comma-separated breaker names)
+    ///
+    /// On reload (`SIGHUP` or `POST /admin/reload`) only `log.filter` is
+    /// applied; see [`crate::reloader`].
     ///
     /// # Errors
     ///
-    /// Returns an error if:
-    /// - Required environment variables are missing (OAUTH2_CLIENT_ID, OAUTH2_CLIENT_SECRET, DATABASE_URL)
-    /// - Port values cannot be parsed as u16
-    pub fn from_env() -> Result<Self> {
-        // ---
-        dotenvy::dotenv().ok();
-
+    /// Returns a [`tokn_config::ConfigError`] listing every missing or invalid key
+    /// (e.g. both OAuth2 credentials unset, `CLIENT_PORT` not a valid u16).
+    pub fn load() -> Result<Self> {
         // ---
-        let server = ServerConfig {
-            host: env::var("CLIENT_HOST").unwrap_or_else(|_| "127.0.0.1".to_string()),
-            port: env::var("CLIENT_PORT")
-                .unwrap_or_else(|_| "8081".to_string())
-                .parse()
-                .context("CLIENT_PORT must be a valid u16")?,
-        };
+        let config = ConfigLoader::new("oauth2-client")
+            .optional("server.host", "CLIENT_HOST", "127.0.0.1".to_string())
+            .optional("server.port", "CLIENT_PORT", 8081u16)
+            .key::<Bind>("server.bind", "CLIENT_BIND")
+            .key::<SocketMode>("server.socket_mode", "CLIENT_SOCKET_MODE")
+            .key::<PathBuf>("server.tls.cert_path", "CLIENT_TLS_CERT_PATH")
+            .key::<PathBuf>("server.tls.key_path", "CLIENT_TLS_KEY_PATH")
+            .key::<CompressionAlgorithms>("server.compression.algorithms", "CLIENT_COMPRESSION")
+            .key::<CorsList>("server.cors.allowed_origins", "CLIENT_CORS_ALLOWED_ORIGINS")
+            .key::<CorsList>("server.cors.allowed_methods", "CLIENT_CORS_ALLOWED_METHODS")
+            .key::<CorsList>("server.cors.allowed_headers", "CLIENT_CORS_ALLOWED_HEADERS")
+            .key::<bool>(
+                "server.cors.allow_credentials",
+                "CLIENT_CORS_ALLOW_CREDENTIALS",
+            )
+            .key::<String>("server.security_headers.content_security_policy", "CLIENT_CONTENT_SECURITY_POLICY")
+            .key::<String>("server.security_headers.frame_options", "CLIENT_FRAME_OPTIONS")
+            .key::<String>("server.security_headers.referrer_policy", "CLIENT_REFERRER_POLICY")
+            .key::<String>("server.security_headers.content_type_options", "CLIENT_CONTENT_TYPE_OPTIONS")
+            .optional(
+                "server.drain_timeout_seconds",
+                "CLIENT_DRAIN_TIMEOUT_SECONDS",
+                tokn_server::DEFAULT_DRAIN_TIMEOUT_SECONDS,
+            )
+            .optional(
+                "redis.url",
+                "REDIS_URL",
+                "redis://127.0.0.1:6379".to_string(),
+            )
+            .required::<String>("oauth2.client_id", "OAUTH2_CLIENT_ID")
+            .required::<String>("oauth2.client_secret", "OAUTH2_CLIENT_SECRET")
+            .optional(
+                "oauth2.redirect_uri",
+                "OAUTH2_REDIRECT_URI",
+                "http://127.0.0.1:8081/callback".to_string(),
+            )
+            .optional(
+                "oauth2.authorize_url",
+                "OAUTH2_AUTHORIZE_URL",
+                "http://127.0.0.1:8082/v1/oauth/authorize".to_string(),
+            )
+            .optional(
+                "oauth2.token_url",
+                "OAUTH2_TOKEN_URL",
+                "http://127.0.0.1:8082/v1/oauth/token".to_string(),
+            )
+            .optional(
+                "oauth2.userinfo_url",
+                "OAUTH2_USERINFO_URL",
+                "http://127.0.0.1:8082/v1/oauth/userinfo".to_string(),
+            )
+            .key::<u32>(
+                "circuit_breaker.failure_threshold",
+                "CIRCUIT_BREAKER_FAILURE_THRESHOLD",
+            )
+            .key::<u64>(
+                "circuit_breaker.open_seconds",
+                "CIRCUIT_BREAKER_OPEN_SECONDS",
+            )
+            .key::<u64>(
+                "circuit_breaker.call_timeout_ms",
+                "CIRCUIT_BREAKER_CALL_TIMEOUT_MS",
+            )
+            .key::<String>("log.filter", "RUST_LOG")
+            .key::<String>("admin.token", "ADMIN_TOKEN")
+            .key::<String>("i18n.default_locale", "I18N_DEFAULT_LOCALE")
+            .key::<String>("i18n.locale", "I18N_LOCALE")
+            .key::<PathBuf>("i18n.dir", "I18N_DIR")
+            .key::<PathBuf>("theme.dir", "THEME_DIR")
+            .key::<String>("theme.name", "THEME_NAME")
+            .key::<ClientThemes>("theme.clients", "THEME_CLIENTS")
+            .key::<bool>("rate_limit.enabled", "RATE_LIMIT_ENABLED")
+            .key::<Algorithm>("rate_limit.algorithm", "RATE_LIMIT_ALGORITHM")
+            .key::<u32>("rate_limit.requests", "RATE_LIMIT_REQUESTS")
+            .key::<u64>("rate_limit.window_seconds", "RATE_LIMIT_WINDOW_SECONDS")
+            .key::<u32>("rate_limit.burst", "RATE_LIMIT_BURST")
+            .key::<bool>(
+                "rate_limit.trust_forwarded_for",
+                "RATE_LIMIT_TRUST_FORWARDED_FOR",
+            )
+            .key::<KeyBy>("rate_limit.key_by", "RATE_LIMIT_KEY_BY")
+            .key::<BTreeMap<String, u32>>("rate_limit.endpoints", "RATE_LIMIT_ENDPOINTS")
+            .key::<String>("service_auth.key", "SERVICE_AUTH_KEY")
+            .key::<u64>(
+                "service_auth.max_age_seconds",
+                "SERVICE_AUTH_MAX_AGE_SECONDS",
+            )
+            .key::<u8>("chaos.failure_percent", "CHAOS_FAILURE_PERCENT")
+            .key::<u8>("chaos.delay_percent", "CHAOS_DELAY_PERCENT")
+            .key::<u64>("chaos.delay_ms", "CHAOS_DELAY_MS")
+            .key::<ChaosTargets>("chaos.targets", "CHAOS_TARGETS")
+            .secret("oauth2.client_secret")
+            .rule("admin.token", |token: &String| {
+                tokn_server::validate_admin_token(token)
+            })
+            .secret("admin.token")
+            .rule("rate_limit", tokn_ratelimit::validate_rate_limit_config)
+            .rule("service_auth", tokn_server::validate_service_auth_config)
+            .rule("server.cors", tokn_server::validate_cors_config)
+            .rule(
+                "server.security_headers",
+                tokn_server::validate_security_headers_config,
+            )
+            .rule("chaos", tokn_resilience::validate_chaos_config)
+            .prod_rule("chaos", tokn_resilience::forbid_chaos)
+            .prod_rule("server", |server: &ServerConfig| {
+                tokn_server::require_tls(server.tls.as_ref(), server.bind.as_ref())
+            })
+            .secret("service_auth.key")
+            .load()?;
 
-        // ---
-        let redis = RedisConfig {
-            url: env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string()),
-        };
-
-        // ---
-        let oauth2 = OAuth2Config {
-            client_id: env::var("OAUTH2_CLIENT_ID").context("OAUTH2_CLIENT_ID must be set")?,
-            client_secret: env::var("OAUTH2_CLIENT_SECRET")
-                .context("OAUTH2_CLIENT_SECRET must be set")?,
-            redirect_uri: env::var("OAUTH2_REDIRECT_URI")
-                .unwrap_or_else(|_| "http://127.0.0.1:8081/callback".to_string()),
-            authorize_url: env::var("OAUTH2_AUTHORIZE_URL")
-                .unwrap_or_else(|_| "http://127.0.0.1:8082/oauth/authorize".to_string()),
-            token_url: env::var("OAUTH2_TOKEN_URL")
-                .unwrap_or_else(|_| "http://127.0.0.1:8082/oauth/token".to_string()),
-            userinfo_url: env::var("OAUTH2_USERINFO_URL")
-                .unwrap_or_else(|_| "http://127.0.0.1:8082/oauth/userinfo".to_string()),
-        };
-
-        // ---
-        Ok(Self {
-            server,
-            redis,
-            oauth2,
-        })
+        Ok(config)
     }
 
     // ---
-    /// Returns the server bind address in "host:port" format.
-    pub fn bind_address(&self) -> String {
+    /// Returns where the server listens: `server.bind` if set, else `host:port`.
+    pub fn bind_address(&self) -> Bind {
         // ---
-        format!("{}:{}", self.server.host, self.server.port)
+        self.server
+            .bind
+            .clone()
+            .unwrap_or_else(|| Bind::Tcp(format!("{}:{}", self.server.host, self.server.port)))
     }
 }
@@ -0,0 +1,26 @@
+// oauth2-client/src/debug.rs
+
+//! Sections of the `/debug` report
+//!
+//! The rate limiter's breaker, on top of the build info every service
+//! reports.
+
+use serde_json::json;
+use tokn_ratelimit::RateLimiter;
+use tokn_server::DebugInfo;
+
+// ---
+
+/// The `/debug` report for oauth2-client over `limiter`.
+pub fn debug_info(limiter: &RateLimiter) -> DebugInfo {
+    // ---
+    let limiter = limiter.clone();
+
+    DebugInfo::new("oauth2-client", env!("CARGO_PKG_VERSION")).section("ratelimit", move || {
+        let status = json!({
+            "enabled": limiter.is_enabled(),
+            "breaker": limiter.breaker().map(|breaker| breaker.state().as_str()),
+        });
+        async move { status }
+    })
+}
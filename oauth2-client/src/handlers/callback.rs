@@ -2,14 +2,22 @@
 
 use axum::{
     extract::{Query, State},
-    response::{Html, IntoResponse, Redirect},
+    response::{IntoResponse, Redirect},
 };
 use oauth2::{
-    basic::BasicClient, reqwest::async_http_client, AuthUrl, AuthorizationCode, ClientId,
-    ClientSecret, RedirectUrl, TokenResponse, TokenUrl,
+    basic::BasicClient,
+    reqwest::async_http_client,
+    url::{Position, Url},
+    AuthUrl, AuthorizationCode, ClientId, ClientSecret, RedirectUrl, TokenResponse, TokenUrl,
 };
 use serde::Deserialize;
 use std::sync::Arc;
+use tokn_core::UserInfo;
+use tokn_i18n::Messages;
+use tokn_resilience::{CircuitBreaker, CircuitBreakerError, CircuitBreakerLayer};
+use tokn_server::{RequestId, ServiceAuth, REQUEST_ID};
+use tokn_theme::{Page, Themes};
+use tower::{service_fn, Layer, ServiceExt};
 
 // ---
 
@@ -39,6 +47,12 @@ pub struct CallbackQuery {
 /// - Exchanges the authorization code for an access token
 /// - Validates the token response from the authorization server
 /// - Fetches user information using the access token
+/// - Both calls to the authorization server go through the `oauth2-server`
+///   circuit breaker, so a down server fails the callback immediately
+/// - Both calls are signed as coming from oauth2-client when
+///   `SERVICE_AUTH_KEY` is set
+/// - Both calls carry this request's `X-Request-Id`, so the authorization
+///   server's logs can be matched to ours
 /// - TODO: Should validate CSRF state token from Redis
 ///
 /// # OAuth2 Flow
@@ -46,7 +60,8 @@ pub struct CallbackQuery {
 /// 1. Receives authorization code from redirect
 /// 2. Exchanges code for access token at token endpoint
 /// 3. Uses access token to fetch user info from userinfo endpoint
-/// 4. Displays user information to demonstrate successful authentication
+/// 4. Displays user information to demonstrate successful authentication, in
+///    the language negotiated from `Accept-Language`
 ///
 /// # Errors
 ///
@@ -56,6 +71,11 @@ pub struct CallbackQuery {
 /// - Userinfo response cannot be parsed
 pub async fn callback_handler(
     State(config): State<Arc<Config>>,
+    State(upstream): State<CircuitBreaker>,
+    State(service_auth): State<ServiceAuth>,
+    State(themes): State<Arc<Themes>>,
+    request_id: RequestId,
+    messages: Messages,
     Query(params): Query<CallbackQuery>,
 ) -> impl IntoResponse {
     // ---
@@ -65,7 +85,9 @@ pub async fn callback_handler(
     // Build OAuth2 client
     let client = BasicClient::new(
         ClientId::new(config.oauth2.client_id.clone()),
-        Some(ClientSecret::new(config.oauth2.client_secret.clone())),
+        Some(ClientSecret::new(
+            config.oauth2.client_secret.expose().to_string(),
+        )),
         AuthUrl::new(config.oauth2.authorize_url.clone()).expect("Invalid authorize URL"),
         Some(TokenUrl::new(config.oauth2.token_url.clone()).expect("Invalid token URL")),
     )
@@ -76,9 +98,28 @@ pub async fn callback_handler(
 
     // ---
     // Exchange authorization code for access token
+    let token_service =
+        CircuitBreakerLayer::new(upstream.clone()).layer(service_fn(async_http_client));
     let token_result = client
         .exchange_code(AuthorizationCode::new(params.code))
-        .request_async(async_http_client)
+        .request_async(|mut request| {
+            let mut headers = service_auth.sign(
+                request.method.as_str(),
+                path_and_query(&request.url),
+                &request.body,
+            );
+            headers.push((REQUEST_ID, request_id.as_str().to_string()));
+            // oauth2 builds requests with its own `http` version
+            for (name, value) in headers {
+                if let (Ok(name), Ok(value)) = (
+                    oauth2::http::HeaderName::from_bytes(name.as_str().as_bytes()),
+                    oauth2::http::HeaderValue::from_str(&value),
+                ) {
+                    request.headers.insert(name, value);
+                }
+            }
+            token_service.oneshot(request)
+        })
         .await;
 
     // ---
@@ -95,11 +136,26 @@ pub async fn callback_handler(
     // ---
     // Fetch user info from userinfo endpoint
     let http_client = reqwest::Client::new();
-    let userinfo_result = http_client
+    let userinfo_result = match http_client
         .get(&config.oauth2.userinfo_url)
         .bearer_auth(&access_token)
-        .send()
-        .await;
+        .header(REQUEST_ID, request_id.as_str())
+        .build()
+    {
+        Ok(mut request) => {
+            let signature = service_auth.sign("GET", path_and_query(request.url()), &[]);
+            for (name, value) in signature {
+                if let Ok(value) = value.parse() {
+                    request.headers_mut().insert(name, value);
+                }
+            }
+            CircuitBreakerLayer::new(upstream)
+                .layer(http_client)
+                .oneshot(request)
+                .await
+        }
+        Err(err) => Err(CircuitBreakerError::Inner(err)),
+    };
 
     let userinfo_response = match userinfo_result {
         Ok(resp) => resp,
@@ -110,39 +166,42 @@ pub async fn callback_handler(
     };
 
     // ---
-    let userinfo_json = match userinfo_response.json::<serde_json::Value>().await {
-        Ok(json) => json,
+    let userinfo = match userinfo_response.json::<UserInfo>().await {
+        Ok(info) => info,
         Err(err) => {
             tracing::error!("Failed to parse userinfo: {:?}", err);
             return Redirect::to("/?error=userinfo_parse_failed").into_response();
         }
     };
-
-    // ---
-    // Extract username from userinfo
-    let username = userinfo_json
-        .get("username")
-        .and_then(|v| v.as_str())
-        .unwrap_or("Unknown");
+    let username = userinfo.username;
 
     // ---
     // Display success page with user info
-    let html = format!(
+    let title = messages.text("client-callback-title");
+    let content = format!(
         r#"
-<!DOCTYPE html>
-<html>
-<head>
-    <title>Logged In - OAuth2 Client Demo</title>
-</head>
-<body>
-    <h1>Successfully Authenticated!</h1>
-    <p>Welcome, <strong>{username}</strong></p>
-    <p>Access Token: <code>{access_token}</code></p>
-    <a href="/">Back to Home</a>
-</body>
-</html>
-"#,
+    <h1>{heading}</h1>
+    <p>{welcome}</p>
+    <p>{token}</p>
+    <a href="/">{back}</a>"#,
+        heading = messages.text("client-callback-heading"),
+        welcome = messages.format("client-callback-welcome", &[("username", &username)]),
+        token = messages.format("client-callback-token", &[("token", &access_token)]),
+        back = messages.text("client-back-home"),
     );
 
-    Html(html).into_response()
+    let html = themes.default_theme().render(&Page {
+        lang: &messages.lang(),
+        title: &title,
+        content: &content,
+    });
+    messages.html(html)
+}
+
+// ---
+
+/// The part of `url` a signature covers, as the server sees it.
+fn path_and_query(url: &Url) -> &str {
+    // ---
+    &url[Position::BeforePath..Position::AfterQuery]
 }
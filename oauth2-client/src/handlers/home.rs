@@ -1,29 +1,33 @@
 // oauth2-client/src/handlers/home.rs
 
-use axum::response::Html;
+use axum::{extract::State, response::Response};
+use std::sync::Arc;
+use tokn_i18n::Messages;
+use tokn_theme::{Page, Themes};
 
 // ---
 
 /// Displays the OAuth2 client demo home page.
 ///
 /// Shows a simple landing page with a login button that initiates the OAuth2 authorization code flow.
-pub async fn home_handler() -> Html<&'static str> {
+pub async fn home_handler(State(themes): State<Arc<Themes>>, messages: Messages) -> Response {
     // ---
-    Html(
+    let title = messages.text("client-home-title");
+    let content = format!(
         r#"
-<!DOCTYPE html>
-<html>
-<head>
-    <title>OAuth2 Client Demo</title>
-</head>
-<body>
-    <h1>OAuth2 Client Demo</h1>
-    <p>This demo shows OAuth2 authorization code flow.</p>
+    <h1>{title}</h1>
+    <p>{intro}</p>
     <a href="/login">
-        <button>Login with OAuth2</button>
-    </a>
-</body>
-</html>
-"#,
-    )
+        <button>{login}</button>
+    </a>"#,
+        intro = messages.text("client-home-intro"),
+        login = messages.text("client-home-login"),
+    );
+
+    let html = themes.default_theme().render(&Page {
+        lang: &messages.lang(),
+        title: &title,
+        content: &content,
+    });
+    messages.html(html)
 }
@@ -41,7 +41,9 @@ pub async fn login_handler(State(config): State<Arc<Config>>) -> impl IntoRespon
     // Build OAuth2 client
     let client = oauth2::basic::BasicClient::new(
         ClientId::new(config.oauth2.client_id.clone()),
-        Some(ClientSecret::new(config.oauth2.client_secret.clone())),
+        Some(ClientSecret::new(
+            config.oauth2.client_secret.expose().to_string(),
+        )),
         AuthUrl::new(config.oauth2.authorize_url.clone()).expect("Invalid authorize URL"),
         Some(TokenUrl::new(config.oauth2.token_url.clone()).expect("Invalid token URL")),
     )
@@ -11,7 +11,7 @@ mod profile;
 
 // ---
 
-pub use callback::callback_handler;
+pub use callback::{callback_handler, CallbackQuery};
 pub use home::home_handler;
 pub use login::login_handler;
 pub use profile::profile_handler;
@@ -1,6 +1,9 @@
 // oauth2-client/src/handlers/profile.rs
 
-use axum::response::Html;
+use axum::{extract::State, response::Response};
+use std::sync::Arc;
+use tokn_i18n::Messages;
+use tokn_theme::{Page, Themes};
 
 // ---
 
@@ -13,27 +16,30 @@ use axum::response::Html;
 /// - Check for valid access token in Redis
 /// - Fetch user info from oauth2-server userinfo endpoint
 /// - Display actual user data instead of placeholder
-pub async fn profile_handler() -> Html<&'static str> {
+pub async fn profile_handler(State(themes): State<Arc<Themes>>, messages: Messages) -> Response {
     // ---
     // TODO: Check for valid access token in Redis
     // TODO: Fetch user info from oauth2-server userinfo endpoint
     // TODO: Display actual user data
 
     // ---
-    Html(
+    let title = messages.text("client-profile-title");
+    let content = format!(
         r#"
-<!DOCTYPE html>
-<html>
-<head>
-    <title>Profile - OAuth2 Client Demo</title>
-</head>
-<body>
-    <h1>Profile</h1>
-    <p>You are authenticated!</p>
-    <p><em>TODO: Display actual user information from userinfo endpoint</em></p>
-    <a href="/">Back to Home</a>
-</body>
-</html>
-"#,
-    )
+    <h1>{heading}</h1>
+    <p>{authenticated}</p>
+    <p>{todo}</p>
+    <a href="/">{back}</a>"#,
+        heading = messages.text("client-profile-heading"),
+        authenticated = messages.text("client-profile-authenticated"),
+        todo = messages.text("client-profile-todo"),
+        back = messages.text("client-back-home"),
+    );
+
+    let html = themes.default_theme().render(&Page {
+        lang: &messages.lang(),
+        title: &title,
+        content: &content,
+    });
+    messages.html(html)
 }
@@ -7,10 +7,86 @@
 
 // ---
 
+mod check;
 mod config;
+mod debug;
 mod handlers;
+mod reload;
+mod router;
+
+use axum::extract::FromRef;
+use std::sync::Arc;
+use tokn_i18n::Localizer;
+use tokn_resilience::CircuitBreaker;
+use tokn_server::ServiceAuth;
+use tokn_theme::Themes;
+
+// ---
+
+/// Application state shared across all handlers.
+///
+/// Handlers extract the parts they need (`State<Arc<Config>>`,
+/// `State<CircuitBreaker>`) via [`FromRef`]; pages take a [`tokn_i18n::Messages`]
+/// built from `i18n` and render through `State<Arc<Themes>>`.
+#[derive(Clone)]
+pub struct AppState {
+    // ---
+    pub config: Arc<Config>,
+    /// Breaker around outbound calls to the authorization server
+    pub upstream: CircuitBreaker,
+    /// Translations for the demo pages
+    pub i18n: Arc<Localizer>,
+    /// Page theme
+    pub theme: Arc<Themes>,
+    /// Signs calls to the authorization server as coming from oauth2-client
+    pub service_auth: ServiceAuth,
+}
+
+impl FromRef<AppState> for Arc<Config> {
+    // ---
+    fn from_ref(state: &AppState) -> Self {
+        // ---
+        state.config.clone()
+    }
+}
+
+impl FromRef<AppState> for CircuitBreaker {
+    // ---
+    fn from_ref(state: &AppState) -> Self {
+        // ---
+        state.upstream.clone()
+    }
+}
+
+impl FromRef<AppState> for ServiceAuth {
+    // ---
+    fn from_ref(state: &AppState) -> Self {
+        // ---
+        state.service_auth.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<Localizer> {
+    // ---
+    fn from_ref(state: &AppState) -> Self {
+        // ---
+        state.i18n.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<Themes> {
+    // ---
+    fn from_ref(state: &AppState) -> Self {
+        // ---
+        state.theme.clone()
+    }
+}
 
 // ---
 
-pub use config::Config;
-pub use handlers::{callback_handler, home_handler, login_handler, profile_handler};
+pub use check::check_config;
+pub use config::{Config, OAuth2Config, RedisConfig, ServerConfig};
+pub use debug::debug_info;
+pub use handlers::{callback_handler, home_handler, login_handler, profile_handler, CallbackQuery};
+pub use reload::reloader;
+pub use router::build_router;
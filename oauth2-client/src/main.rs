@@ -1,50 +1,112 @@
 // oauth2-client/src/main.rs
 
 use anyhow::Result;
-use axum::{routing::get, Router};
-use oauth2_client::Config;
+use axum::middleware;
+use oauth2_client::{build_router, Config};
 use std::sync::Arc;
-use tower_http::trace::TraceLayer;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use std::time::Duration;
+use tokn_config::Reloadable;
+use tokn_core::SystemClock;
+use tokn_ratelimit::{RateLimitLayer, RateLimiter};
+use tokn_resilience::RetryPolicy;
+use tokn_server::{CheckFormat, SecurityHeaders, ServiceAuth, Shutdown};
+use tokn_telemetry::TelemetryConfig;
 
 // ---
 
 #[tokio::main]
 async fn main() -> Result<()> {
     // ---
-    // Initialize tracing
-    tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "oauth2_client=debug,tower_http=debug".into()),
-        )
-        .with(tracing_subscriber::fmt::layer().with_ansi(false))
-        .init();
+    // `--check-config`: validate and probe, print the report, and exit
+    if let Some(format) = CheckFormat::from_args() {
+        oauth2_client::check_config().await.exit(format);
+    }
+
+    // ---
+    // Initialize tracing, OTLP export, and metrics
+    let telemetry = tokn_telemetry::init(&TelemetryConfig::from_env("oauth2-client")?)?;
 
     // ---
     // Load configuration
-    let config = Arc::new(Config::from_env()?);
+    let config = Arc::new(Config::load()?);
+    telemetry.log_filter().apply(&config.log);
+
+    // Fault injection (no-op unless built with `--features chaos`)
+    tokn_resilience::install_faults(&config.chaos);
+
     let bind_addr = config.bind_address();
 
+    // Stop accepting and drain in-flight requests on SIGTERM/SIGINT
+    let shutdown = Shutdown::on_signals(Duration::from_secs(config.server.drain_timeout_seconds))?;
+
     // ---
     tracing::info!("Starting oauth2-client on {}", bind_addr);
 
+    // ---
+    // Reload on SIGHUP and POST /admin/reload
+    let reload = oauth2_client::reloader(
+        Reloadable::new(Config::clone(&config)),
+        telemetry.log_filter(),
+    );
+    tokn_server::reload_on_sighup(reload.clone())?;
+
+    // ---
+    // Per-client rate limiting in Redis (disabled unless RATE_LIMIT_ENABLED is set)
+    let limiter = tokn_resilience::retry(&RetryPolicy::default(), "Redis (rate limiting)", || {
+        RateLimiter::connect(
+            "oauth2-client",
+            &config.rate_limit,
+            &config.redis.url,
+            config.circuit_breaker,
+            SystemClock::shared(),
+        )
+    })
+    .await?;
+    if limiter.is_enabled() {
+        tracing::info!(
+            "Rate limiting to {} requests per {}s per client ({})",
+            config.rate_limit.requests,
+            config.rate_limit.window_seconds,
+            config.rate_limit.algorithm
+        );
+    }
+
+    // ---
+    // Requests signed by the other tokn services may skip the admin token
+    let service_auth =
+        ServiceAuth::new("oauth2-client", &config.service_auth, SystemClock::shared());
+    if service_auth.is_enabled() {
+        tracing::info!("Accepting signed requests from tokn services");
+    }
+
     // ---
     // Build router
-    let app = Router::new()
-        .route("/", get(oauth2_client::home_handler))
-        .route("/login", get(oauth2_client::login_handler))
-        .route("/callback", get(oauth2_client::callback_handler))
-        .route("/profile", get(oauth2_client::profile_handler))
-        .layer(TraceLayer::new_for_http())
-        .with_state(config);
+    let debug = oauth2_client::debug_info(&limiter);
+    let app = build_router(config.clone())?
+        .layer(RateLimitLayer::new(limiter))
+        .merge(tokn_server::admin_router(&config.admin, reload))
+        .merge(tokn_server::debug_router(&config.admin, debug))
+        .layer(middleware::from_fn_with_state(
+            service_auth,
+            tokn_server::verify_service_signature,
+        ))
+        .layer(middleware::from_fn_with_state(
+            SecurityHeaders::new(&config.server.security_headers),
+            tokn_server::security_headers,
+        ))
+        .layer(tokn_server::compression_layer(&config.server.compression));
 
     // ---
     // Start server
-    let listener = tokio::net::TcpListener::bind(&bind_addr).await?;
-    tracing::info!("Listening on {}", bind_addr);
-
-    axum::serve(listener, app).await?;
+    tokn_server::serve(
+        app,
+        &bind_addr,
+        config.server.tls.as_ref(),
+        config.server.socket_mode,
+        &shutdown,
+    )
+    .await?;
 
+    tracing::info!("Shutdown complete");
     Ok(())
 }
@@ -0,0 +1,69 @@
+// oauth2-client/src/router.rs
+
+use anyhow::Result;
+use axum::{middleware, routing::get, Router};
+use std::sync::Arc;
+use tokn_core::SystemClock;
+use tokn_i18n::Localizer;
+use tokn_resilience::CircuitBreaker;
+use tokn_server::ServiceAuth;
+use tokn_theme::Themes;
+use tower_http::trace::TraceLayer;
+
+// ---
+
+use crate::handlers::{callback_handler, home_handler, login_handler, profile_handler};
+use crate::{AppState, Config};
+
+// ---
+
+/// Builds the oauth2-client application router.
+///
+/// Shared by the binary and in-process test harnesses so both serve the
+/// same routes and middleware. Calls to the authorization server run through
+/// a circuit breaker named `oauth2-server` configured by `config.circuit_breaker`
+/// and are signed with `config.service_auth.key` when it is set; they carry
+/// the `X-Request-Id` of the request that made them, which every response
+/// also returns (see [`tokn_server::request_id`]). Browser apps on the
+/// origins `config.server.cors` lists may call every route (see
+/// [`tokn_server::cors_layer`]).
+/// Pages are rendered with the translations `config.i18n` selects, in the
+/// theme `config.theme` selects; theme assets are served under
+/// `/theme/<name>/static/`.
+///
+/// # Errors
+///
+/// Returns an error when `config.i18n` names an unknown locale or an override
+/// directory that cannot be read or parsed, or `config.theme` names a theme
+/// that does not exist or cannot be read.
+pub fn build_router(config: Arc<Config>) -> Result<Router> {
+    // ---
+    let i18n = Arc::new(Localizer::new(&config.i18n)?);
+    let theme = Arc::new(Themes::new(&config.theme)?);
+
+    let app = Router::new()
+        .route("/", get(home_handler))
+        .route("/login", get(login_handler))
+        .route("/callback", get(callback_handler))
+        .route("/profile", get(profile_handler))
+        .merge(theme.static_router());
+    let app = match tokn_server::cors_layer(&config.server.cors) {
+        Some(cors) => app.layer(cors),
+        None => app,
+    };
+
+    Ok(app
+        .layer(TraceLayer::new_for_http())
+        .layer(middleware::from_fn(tokn_server::request_id))
+        .with_state(AppState {
+            upstream: CircuitBreaker::new("oauth2-server", config.circuit_breaker),
+            service_auth: ServiceAuth::new(
+                "oauth2-client",
+                &config.service_auth,
+                SystemClock::shared(),
+            ),
+            i18n,
+            theme,
+            config,
+        }))
+}
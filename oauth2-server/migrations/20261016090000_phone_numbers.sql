@@ -0,0 +1,17 @@
+-- Phone numbers and SMS one-time codes
+
+-- Set once the user confirms a code sent to the number
+ALTER TABLE users
+    ADD COLUMN phone_number VARCHAR(32),
+    ADD COLUMN phone_number_verified BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Outstanding code and send counters, one row per phone number
+CREATE TABLE phone_otps (
+    phone_number VARCHAR(32) PRIMARY KEY,
+    code_hash VARCHAR(64),
+    expires_at TIMESTAMP NOT NULL,
+    attempts INTEGER NOT NULL,
+    last_sent_at TIMESTAMP NOT NULL,
+    window_started_at TIMESTAMP NOT NULL,
+    sends_in_window INTEGER NOT NULL
+);
@@ -0,0 +1,22 @@
+-- Soft delete and change history for clients and users
+
+-- Set when a client or user is deleted; every lookup skips such rows, and
+-- clearing it restores the record
+ALTER TABLE clients ADD COLUMN deleted_at TIMESTAMP;
+ALTER TABLE users ADD COLUMN deleted_at TIMESTAMP;
+
+-- Who changed which client or user record, how, and when; secrets are
+-- recorded as changed without their values
+CREATE TABLE change_history (
+    id BIGSERIAL PRIMARY KEY,
+    entity VARCHAR(16) NOT NULL,
+    entity_id VARCHAR(255) NOT NULL,
+    action VARCHAR(32) NOT NULL,
+    field VARCHAR(64),
+    old_value TEXT,
+    new_value TEXT,
+    actor VARCHAR(255) NOT NULL,
+    changed_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX change_history_entity_idx ON change_history (entity, entity_id, changed_at);
@@ -0,0 +1,7 @@
+-- PKCE (RFC 7636) challenges of authorization codes
+
+-- Set when the authorization request carried a code_challenge; the token
+-- request must then send the code_verifier it was derived from
+ALTER TABLE authorization_codes
+    ADD COLUMN code_challenge VARCHAR(128),
+    ADD COLUMN code_challenge_method VARCHAR(16);
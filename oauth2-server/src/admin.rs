@@ -0,0 +1,707 @@
+// oauth2-server/src/admin.rs
+
+//! Client, user, and access token management
+//!
+//! Operator-side reads and writes of the `clients`, `users`, and
+//! `access_tokens` tables, shared by the `tokn-admin` CLI and the admin web
+//! UI (see [`crate::admin_ui_router`]), and the startup check on registered
+//! client secrets.
+//!
+//! Clients and users are never removed: deleting one sets its `deleted_at`,
+//! which every lookup honors, and revokes its tokens. Each write names an
+//! `actor` and is recorded in the change history (see [`crate::list_changes`]).
+
+use anyhow::{anyhow, Context, Result};
+use argon2::password_hash::{rand_core::OsRng, PasswordHasher, SaltString};
+use argon2::Argon2;
+use chrono::NaiveDateTime;
+use rand::{distributions::Alphanumeric, Rng};
+use sqlx::PgPool;
+use tokn_config::Profile;
+
+// ---
+
+use crate::history::{self, Action, Entity, REDACTED};
+use crate::query::timed;
+
+// ---
+
+/// Length of generated client secrets (alphanumeric, ~238 bits).
+const CLIENT_SECRET_LEN: usize = 40;
+
+// ---
+
+/// A registered client, without its secret.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientSummary {
+    // ---
+    pub client_id: String,
+    pub redirect_uri: String,
+    pub created_at: NaiveDateTime,
+    /// When the client was deleted, if it was
+    pub deleted_at: Option<NaiveDateTime>,
+}
+
+/// A user, without their password hash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserSummary {
+    // ---
+    pub user_id: String,
+    pub username: String,
+    /// Verified phone number, if any
+    pub phone_number: Option<String>,
+    pub created_at: NaiveDateTime,
+    /// When the user was deleted, if they were
+    pub deleted_at: Option<NaiveDateTime>,
+}
+
+/// An unexpired access token, identified by its fingerprint rather than the
+/// token itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessTokenSummary {
+    // ---
+    /// SHA-256 of the token (hex); what [`revoke_access_token`] takes
+    pub fingerprint: String,
+    pub client_id: String,
+    pub user_id: String,
+    pub scope: Option<String>,
+    pub expires_at: NaiveDateTime,
+    pub created_at: NaiveDateTime,
+}
+
+/// Who an access token revoked by [`revoke_access_token`] belonged to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RevokedToken {
+    // ---
+    pub client_id: String,
+    pub user_id: String,
+}
+
+// ---
+
+/// Register a new OAuth2 client on behalf of `actor`.
+///
+/// # Errors
+///
+/// Returns an error if `client_id` is already registered (deleted clients
+/// included; restore those instead) or the insert fails.
+pub async fn create_client(
+    pool: &PgPool,
+    actor: &str,
+    client_id: &str,
+    client_secret: &str,
+    redirect_uri: &str,
+) -> Result<()> {
+    // ---
+    let mut tx = pool.begin().await.context("Failed to start transaction")?;
+
+    timed(
+        "admin.create_client",
+        sqlx::query!(
+            "INSERT INTO clients (client_id, client_secret, redirect_uri) VALUES ($1, $2, $3)",
+            client_id,
+            client_secret,
+            redirect_uri
+        )
+        .execute(&mut *tx),
+    )
+    .await
+    .with_context(|| format!("Failed to create client '{client_id}'"))?;
+
+    let field = ("redirect_uri", None, Some(redirect_uri));
+    history::record(
+        &mut tx,
+        actor,
+        Entity::Client,
+        client_id,
+        Action::Created,
+        Some(field),
+    )
+    .await?;
+
+    tx.commit().await.context("Failed to commit new client")?;
+    Ok(())
+}
+
+// ---
+
+/// A new random client secret for [`create_client`] or
+/// [`reset_client_secret`]; 40 alphanumeric characters, so it passes
+/// [`tokn_config::secret_weakness`].
+pub fn generate_client_secret() -> String {
+    // ---
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(CLIENT_SECRET_LEN)
+        .map(char::from)
+        .collect()
+}
+
+// ---
+
+/// Registered clients, ordered by ID; deleted ones only with
+/// `include_deleted`.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn list_clients(pool: &PgPool, include_deleted: bool) -> Result<Vec<ClientSummary>> {
+    // ---
+    let clients = timed(
+        "admin.list_clients",
+        sqlx::query_as!(
+            ClientSummary,
+            r#"
+        SELECT client_id, redirect_uri, created_at, deleted_at
+        FROM clients
+        WHERE $1 OR deleted_at IS NULL
+        ORDER BY client_id
+        "#,
+            include_deleted
+        )
+        .fetch_all(pool),
+    )
+    .await
+    .context("Failed to list clients")?;
+
+    Ok(clients)
+}
+
+// ---
+
+/// Replace a client's secret on behalf of `actor`. The old secret stops
+/// working immediately.
+///
+/// Returns `false` if no active client has this `client_id`.
+///
+/// # Errors
+///
+/// Returns an error if the update fails.
+pub async fn reset_client_secret(
+    pool: &PgPool,
+    actor: &str,
+    client_id: &str,
+    client_secret: &str,
+) -> Result<bool> {
+    // ---
+    let mut tx = pool.begin().await.context("Failed to start transaction")?;
+
+    let result = timed(
+        "admin.reset_client_secret",
+        sqlx::query!(
+            "UPDATE clients SET client_secret = $2 WHERE client_id = $1 AND deleted_at IS NULL",
+            client_id,
+            client_secret
+        )
+        .execute(&mut *tx),
+    )
+    .await
+    .with_context(|| format!("Failed to reset secret for client '{client_id}'"))?;
+    if result.rows_affected() != 1 {
+        return Ok(false);
+    }
+
+    let field = ("client_secret", Some(REDACTED), Some(REDACTED));
+    history::record(
+        &mut tx,
+        actor,
+        Entity::Client,
+        client_id,
+        Action::Updated,
+        Some(field),
+    )
+    .await?;
+
+    tx.commit()
+        .await
+        .context("Failed to commit client secret")?;
+    Ok(true)
+}
+
+// ---
+
+/// Delete a client on behalf of `actor`: it can no longer exchange codes,
+/// its outstanding authorization codes are dropped, and its access tokens
+/// are revoked. The record is kept and can be restored.
+///
+/// Returns the tokens revoked, or `None` if no active client has this
+/// `client_id`.
+///
+/// # Errors
+///
+/// Returns an error if an update fails; nothing is changed then.
+pub async fn delete_client(
+    pool: &PgPool,
+    actor: &str,
+    client_id: &str,
+) -> Result<Option<Vec<RevokedToken>>> {
+    // ---
+    let mut tx = pool.begin().await.context("Failed to start transaction")?;
+
+    let result = timed(
+        "admin.delete_client",
+        sqlx::query!(
+            r#"
+        UPDATE clients SET deleted_at = CURRENT_TIMESTAMP
+        WHERE client_id = $1 AND deleted_at IS NULL
+        "#,
+            client_id
+        )
+        .execute(&mut *tx),
+    )
+    .await
+    .with_context(|| format!("Failed to delete client '{client_id}'"))?;
+    if result.rows_affected() != 1 {
+        return Ok(None);
+    }
+
+    timed(
+        "admin.delete_client.codes",
+        sqlx::query!(
+            "DELETE FROM authorization_codes WHERE client_id = $1",
+            client_id
+        )
+        .execute(&mut *tx),
+    )
+    .await
+    .with_context(|| format!("Failed to drop authorization codes of client '{client_id}'"))?;
+    let revoked = timed(
+        "admin.delete_client.tokens",
+        sqlx::query_as!(
+            RevokedToken,
+            "DELETE FROM access_tokens WHERE client_id = $1 RETURNING client_id, user_id",
+            client_id
+        )
+        .fetch_all(&mut *tx),
+    )
+    .await
+    .with_context(|| format!("Failed to revoke access tokens of client '{client_id}'"))?;
+
+    history::record(
+        &mut tx,
+        actor,
+        Entity::Client,
+        client_id,
+        Action::Deleted,
+        None,
+    )
+    .await?;
+
+    tx.commit()
+        .await
+        .context("Failed to commit client deletion")?;
+    Ok(Some(revoked))
+}
+
+// ---
+
+/// Undo [`delete_client`] on behalf of `actor`. Revoked tokens stay revoked.
+///
+/// Returns `false` if no deleted client has this `client_id`.
+///
+/// # Errors
+///
+/// Returns an error if the update fails.
+pub async fn restore_client(pool: &PgPool, actor: &str, client_id: &str) -> Result<bool> {
+    // ---
+    let mut tx = pool.begin().await.context("Failed to start transaction")?;
+
+    let result = timed(
+        "admin.restore_client",
+        sqlx::query!(
+            "UPDATE clients SET deleted_at = NULL WHERE client_id = $1 AND deleted_at IS NOT NULL",
+            client_id
+        )
+        .execute(&mut *tx),
+    )
+    .await
+    .with_context(|| format!("Failed to restore client '{client_id}'"))?;
+    if result.rows_affected() != 1 {
+        return Ok(false);
+    }
+
+    history::record(
+        &mut tx,
+        actor,
+        Entity::Client,
+        client_id,
+        Action::Restored,
+        None,
+    )
+    .await?;
+
+    tx.commit()
+        .await
+        .context("Failed to commit client restore")?;
+    Ok(true)
+}
+
+// ---
+
+/// Screen every registered client secret with
+/// [`tokn_config::secret_weakness`].
+///
+/// Under the `staging` and `prod` profiles a weak secret refuses startup;
+/// otherwise each one is logged as a warning. Rotate with `tokn-admin clients reset-secret`.
+///
+/// # Errors
+///
+/// Returns an error if the clients cannot be read, or if `profile` is strict
+/// and any client secret is weak (every such client is named).
+pub async fn check_client_secrets(pool: &PgPool, profile: Profile) -> Result<()> {
+    // ---
+    let weak = weak_client_secrets(pool).await?;
+
+    if profile.is_strict() && !weak.is_empty() {
+        return Err(anyhow!(
+            "Weak client secrets (refused when TOKN_ENV={profile}): {}",
+            weak.join("; ")
+        ));
+    }
+    for client in &weak {
+        tracing::warn!("Weak client secret: {client}; refused when TOKN_ENV is staging or prod");
+    }
+
+    Ok(())
+}
+
+/// Registered clients whose secret [`tokn_config::secret_weakness`] rejects,
+/// as `'client_id' reason`.
+///
+/// # Errors
+///
+/// Returns an error if the clients cannot be read.
+pub async fn weak_client_secrets(pool: &PgPool) -> Result<Vec<String>> {
+    // ---
+    let clients = timed(
+        "admin.check_client_secrets",
+        sqlx::query!(
+        "SELECT client_id, client_secret FROM clients WHERE deleted_at IS NULL ORDER BY client_id"
+    )
+        .fetch_all(pool),
+    )
+    .await
+    .context("Failed to read client secrets")?;
+
+    Ok(clients
+        .into_iter()
+        .filter_map(|client| {
+            let reason = tokn_config::secret_weakness(&client.client_secret)?;
+            Some(format!("'{}' {reason}", client.client_id))
+        })
+        .collect())
+}
+
+// ---
+
+/// Add a user with an Argon2id-hashed password on behalf of `actor`.
+///
+/// # Security
+///
+/// Only the hash is stored; `password` is not logged or persisted.
+///
+/// # Errors
+///
+/// Returns an error if `user_id` or `username` is taken (deleted users
+/// included), or the insert fails.
+pub async fn create_user(
+    pool: &PgPool,
+    actor: &str,
+    user_id: &str,
+    username: &str,
+    password: &str,
+) -> Result<()> {
+    // ---
+    let password_hash = hash_password(password)?;
+    let mut tx = pool.begin().await.context("Failed to start transaction")?;
+
+    timed(
+        "admin.create_user",
+        sqlx::query!(
+            "INSERT INTO users (user_id, username, password_hash) VALUES ($1, $2, $3)",
+            user_id,
+            username,
+            password_hash
+        )
+        .execute(&mut *tx),
+    )
+    .await
+    .with_context(|| format!("Failed to create user '{username}'"))?;
+
+    let field = ("username", None, Some(username));
+    history::record(
+        &mut tx,
+        actor,
+        Entity::User,
+        user_id,
+        Action::Created,
+        Some(field),
+    )
+    .await?;
+
+    tx.commit().await.context("Failed to commit new user")?;
+    Ok(())
+}
+
+// ---
+
+/// Delete a user on behalf of `actor`: they disappear from every lookup,
+/// their outstanding authorization codes are dropped, and their access
+/// tokens are revoked. The record is kept and can be restored.
+///
+/// Returns the tokens revoked, or `None` if no active user has this
+/// `user_id`.
+///
+/// # Errors
+///
+/// Returns an error if an update fails; nothing is changed then.
+pub async fn delete_user(
+    pool: &PgPool,
+    actor: &str,
+    user_id: &str,
+) -> Result<Option<Vec<RevokedToken>>> {
+    // ---
+    let mut tx = pool.begin().await.context("Failed to start transaction")?;
+
+    let result = timed(
+        "admin.delete_user",
+        sqlx::query!(
+            r#"
+        UPDATE users SET deleted_at = CURRENT_TIMESTAMP
+        WHERE user_id = $1 AND deleted_at IS NULL
+        "#,
+            user_id
+        )
+        .execute(&mut *tx),
+    )
+    .await
+    .with_context(|| format!("Failed to delete user '{user_id}'"))?;
+    if result.rows_affected() != 1 {
+        return Ok(None);
+    }
+
+    timed(
+        "admin.delete_user.codes",
+        sqlx::query!(
+            "DELETE FROM authorization_codes WHERE user_id = $1",
+            user_id
+        )
+        .execute(&mut *tx),
+    )
+    .await
+    .with_context(|| format!("Failed to drop authorization codes of user '{user_id}'"))?;
+    let revoked = timed(
+        "admin.delete_user.tokens",
+        sqlx::query_as!(
+            RevokedToken,
+            "DELETE FROM access_tokens WHERE user_id = $1 RETURNING client_id, user_id",
+            user_id
+        )
+        .fetch_all(&mut *tx),
+    )
+    .await
+    .with_context(|| format!("Failed to revoke access tokens of user '{user_id}'"))?;
+
+    history::record(&mut tx, actor, Entity::User, user_id, Action::Deleted, None).await?;
+
+    tx.commit()
+        .await
+        .context("Failed to commit user deletion")?;
+    Ok(Some(revoked))
+}
+
+// ---
+
+/// Undo [`delete_user`] on behalf of `actor`. Revoked tokens stay revoked.
+///
+/// Returns `false` if no deleted user has this `user_id`.
+///
+/// # Errors
+///
+/// Returns an error if the update fails.
+pub async fn restore_user(pool: &PgPool, actor: &str, user_id: &str) -> Result<bool> {
+    // ---
+    let mut tx = pool.begin().await.context("Failed to start transaction")?;
+
+    let result = timed(
+        "admin.restore_user",
+        sqlx::query!(
+            "UPDATE users SET deleted_at = NULL WHERE user_id = $1 AND deleted_at IS NOT NULL",
+            user_id
+        )
+        .execute(&mut *tx),
+    )
+    .await
+    .with_context(|| format!("Failed to restore user '{user_id}'"))?;
+    if result.rows_affected() != 1 {
+        return Ok(false);
+    }
+
+    history::record(
+        &mut tx,
+        actor,
+        Entity::User,
+        user_id,
+        Action::Restored,
+        None,
+    )
+    .await?;
+
+    tx.commit().await.context("Failed to commit user restore")?;
+    Ok(true)
+}
+
+// ---
+
+/// Hash `password` with Argon2id (default parameters, random salt) in PHC
+/// string format, as stored in `users.password_hash`.
+///
+/// # Errors
+///
+/// Returns an error if hashing fails.
+pub fn hash_password(password: &str) -> Result<String> {
+    // ---
+    let salt = SaltString::generate(&mut OsRng);
+    let hash = Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(|e| anyhow!("Failed to hash password: {e}"))?;
+
+    Ok(hash.to_string())
+}
+
+// ---
+
+/// Up to `limit` users ordered by username; with `search`, only those whose
+/// username contains it (case-insensitively) or whose ID equals it. Deleted
+/// users are listed only with `include_deleted`.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn list_users(
+    pool: &PgPool,
+    search: Option<&str>,
+    include_deleted: bool,
+    limit: i64,
+) -> Result<Vec<UserSummary>> {
+    // ---
+    let users = timed(
+        "admin.list_users",
+        sqlx::query_as!(
+            UserSummary,
+            r#"
+        SELECT user_id, username, phone_number, created_at, deleted_at
+        FROM users
+        WHERE ($1::TEXT IS NULL OR strpos(lower(username), lower($1)) > 0 OR user_id = $1)
+          AND ($2 OR deleted_at IS NULL)
+        ORDER BY username
+        LIMIT $3
+        "#,
+            search,
+            include_deleted,
+            limit
+        )
+        .fetch_all(pool),
+    )
+    .await
+    .context("Failed to list users")?;
+
+    Ok(users)
+}
+
+// ---
+
+/// Up to `limit` access tokens still valid at `now`, newest first; with
+/// `user_id`, only that user's.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn list_access_tokens(
+    pool: &PgPool,
+    user_id: Option<&str>,
+    now: NaiveDateTime,
+    limit: i64,
+) -> Result<Vec<AccessTokenSummary>> {
+    // ---
+    let tokens = timed(
+        "admin.list_access_tokens",
+        sqlx::query_as!(
+            AccessTokenSummary,
+            r#"
+        SELECT encode(sha256(token::bytea), 'hex') AS "fingerprint!",
+               client_id, user_id, scope, expires_at, created_at
+        FROM access_tokens
+        WHERE expires_at > $1 AND ($2::TEXT IS NULL OR user_id = $2)
+        ORDER BY created_at DESC
+        LIMIT $3
+        "#,
+            now,
+            user_id,
+            limit
+        )
+        .fetch_all(pool),
+    )
+    .await
+    .context("Failed to list access tokens")?;
+
+    Ok(tokens)
+}
+
+// ---
+
+/// Delete the access token with this fingerprint (see
+/// [`AccessTokenSummary::fingerprint`]); it stops working immediately.
+///
+/// Returns `None` if no token matches.
+///
+/// # Errors
+///
+/// Returns an error if the delete fails.
+pub async fn revoke_access_token(pool: &PgPool, fingerprint: &str) -> Result<Option<RevokedToken>> {
+    // ---
+    let revoked = timed(
+        "admin.revoke_access_token",
+        sqlx::query_as!(
+            RevokedToken,
+            r#"
+        DELETE FROM access_tokens
+        WHERE encode(sha256(token::bytea), 'hex') = $1
+        RETURNING client_id, user_id
+        "#,
+            fingerprint
+        )
+        .fetch_optional(pool),
+    )
+    .await
+    .context("Failed to revoke access token")?;
+
+    Ok(revoked)
+}
+
+// ---
+
+/// Delete every access token issued to `user_id`, signing them out of every
+/// client.
+///
+/// Returns the tokens deleted.
+///
+/// # Errors
+///
+/// Returns an error if the delete fails.
+pub async fn revoke_user_tokens(pool: &PgPool, user_id: &str) -> Result<Vec<RevokedToken>> {
+    // ---
+    let revoked = timed(
+        "admin.revoke_user_tokens",
+        sqlx::query_as!(
+            RevokedToken,
+            "DELETE FROM access_tokens WHERE user_id = $1 RETURNING client_id, user_id",
+            user_id
+        )
+        .fetch_all(pool),
+    )
+    .await
+    .with_context(|| format!("Failed to revoke access tokens for user '{user_id}'"))?;
+
+    Ok(revoked)
+}
@@ -0,0 +1,951 @@
+// oauth2-server/src/admin_ui.rs
+
+//! Admin web UI
+//!
+//! Server-rendered pages under `/admin/ui` for managing clients and users,
+//! viewing and revoking active access tokens, and reading the change history,
+//! over the same functions the `tokn-admin` CLI uses (see [`crate::admin`]).
+//! Changes made here are recorded with the actor `admin-ui`. Pages render
+//! through the deployment's default theme and are English only.
+
+use axum::{
+    extract::{Query, Request, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    middleware::{self, Next},
+    response::{Html, IntoResponse, Redirect, Response},
+    routing::{get, post},
+    Form, Router,
+};
+use chrono::NaiveDateTime;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use tokn_config::Secret;
+use tokn_core::constant_time_eq;
+use tokn_events::{AuthEvent, AuthEventKind};
+use tokn_server::AdminConfig;
+use tokn_theme::Page;
+
+// ---
+
+use crate::admin::{self, RevokedToken};
+use crate::history::{self, Entity};
+use crate::AppState;
+
+// ---
+
+/// Cookie holding the admin session.
+const SESSION_COOKIE: &str = "tokn_admin";
+
+/// Rows shown per table.
+const PAGE_LIMIT: i64 = 100;
+
+/// Actor recorded in the change history for changes made here.
+const ACTOR: &str = "admin-ui";
+
+// ---
+
+/// State for the admin pages: the server's state plus the values derived
+/// from `ADMIN_TOKEN` that authenticate a browser.
+#[derive(Clone)]
+struct AdminUi {
+    // ---
+    app: AppState,
+    token: Arc<Secret>,
+    /// Session cookie value
+    session: Arc<str>,
+    /// Anti-forgery value every form posts back
+    csrf: Arc<str>,
+}
+
+impl AdminUi {
+    // ---
+    fn new(app: AppState, token: Secret) -> Self {
+        // ---
+        let derive = |purpose: &str| -> Arc<str> {
+            let digest = Sha256::digest(format!("tokn-admin-ui-{purpose}:{}", token.expose()));
+            hex::encode(digest).into()
+        };
+
+        Self {
+            session: derive("session"),
+            csrf: derive("csrf"),
+            token: Arc::new(token),
+            app,
+        }
+    }
+
+    fn csrf_ok(&self, presented: &str) -> bool {
+        // ---
+        constant_time_eq(presented.as_bytes(), self.csrf.as_bytes())
+    }
+
+    /// A themed page with the admin navigation above `content`.
+    fn page(&self, title: &str, content: &str) -> Response {
+        // ---
+        let content = format!(
+            r#"
+    <nav class="tokn-admin-nav">
+        <a href="/admin/ui/clients">Clients</a>
+        <a href="/admin/ui/users">Users</a>
+        <a href="/admin/ui/sessions">Sessions</a>
+        <a href="/admin/ui/history">History</a>
+        <form method="POST" action="/admin/ui/logout">
+            <input type="hidden" name="csrf" value="{csrf}">
+            <button type="submit">Sign out</button>
+        </form>
+    </nav>
+    <h1>{title}</h1>{content}"#,
+            csrf = self.csrf,
+            title = escape_html(title),
+        );
+
+        no_store(self.render(title, &content))
+    }
+
+    fn render(&self, title: &str, content: &str) -> Response {
+        // ---
+        let html = self.app.theme.default_theme().render(&Page {
+            lang: "en",
+            title: &format!("tokn admin: {}", escape_html(title)),
+            content,
+        });
+        Html(html).into_response()
+    }
+}
+
+// ---
+
+/// Build the `/admin/ui` pages, or an empty router when no admin token is
+/// configured.
+///
+/// Routes:
+/// - `GET/POST /admin/ui/login` - sign in with `ADMIN_TOKEN`
+/// - `GET /admin/ui/clients` - registered clients (`?deleted=true` includes
+///   deleted ones); `POST` registers one
+/// - `POST /admin/ui/clients/reset-secret` - replace a client's secret
+/// - `POST /admin/ui/clients/delete`, `.../restore` - soft-delete or restore a
+///   client
+/// - `GET /admin/ui/users` - users, searchable by username or ID
+///   (`?deleted=true` includes deleted ones)
+/// - `POST /admin/ui/users/delete`, `.../restore` - soft-delete or restore a user
+/// - `GET /admin/ui/sessions` - unexpired access tokens, optionally for one user
+/// - `POST /admin/ui/sessions/revoke` - revoke one access token
+/// - `POST /admin/ui/sessions/revoke-user` - revoke all of a user's access tokens
+/// - `GET /admin/ui/history` - changes to clients and users, optionally for
+///   one record
+///
+/// # Security
+///
+/// - Signing in sets an `HttpOnly`, `Secure`, `SameSite=Strict` cookie
+///   derived from `ADMIN_TOKEN` (never the token itself); rotating the token
+///   signs every browser out
+/// - Every form carries an anti-forgery value checked in constant time
+/// - Generated client secrets are shown once and pages are sent `no-store`
+/// - As with `/admin/reload`, keep `/admin` off the public network
+pub fn admin_ui_router(config: &AdminConfig, state: AppState) -> Router {
+    // ---
+    let Some(token) = config.token.clone() else {
+        return Router::new();
+    };
+    let ui = AdminUi::new(state, token);
+
+    let pages = Router::new()
+        .route(
+            "/admin/ui",
+            get(|| async { Redirect::to("/admin/ui/clients") }),
+        )
+        .route("/admin/ui/clients", get(clients_page).post(create_client))
+        .route("/admin/ui/clients/reset-secret", post(reset_client_secret))
+        .route("/admin/ui/clients/delete", post(delete_client))
+        .route("/admin/ui/clients/restore", post(restore_client))
+        .route("/admin/ui/users", get(users_page))
+        .route("/admin/ui/users/delete", post(delete_user))
+        .route("/admin/ui/users/restore", post(restore_user))
+        .route("/admin/ui/sessions", get(sessions_page))
+        .route("/admin/ui/sessions/revoke", post(revoke_session))
+        .route("/admin/ui/sessions/revoke-user", post(revoke_user_sessions))
+        .route("/admin/ui/history", get(history_page))
+        .route("/admin/ui/logout", post(logout))
+        .route_layer(middleware::from_fn_with_state(ui.clone(), require_session));
+
+    Router::new()
+        .route("/admin/ui/login", get(login_page).post(login))
+        .merge(pages)
+        .with_state(ui)
+}
+
+// ---
+
+/// Sign-in form.
+#[derive(Debug, Deserialize)]
+struct LoginForm {
+    // ---
+    token: String,
+}
+
+/// Any form with nothing but the anti-forgery value.
+#[derive(Debug, Deserialize)]
+struct CsrfForm {
+    // ---
+    csrf: String,
+}
+
+/// Client registration form.
+#[derive(Debug, Deserialize)]
+struct CreateClientForm {
+    // ---
+    csrf: String,
+    client_id: String,
+    redirect_uri: String,
+}
+
+/// Form naming one client.
+#[derive(Debug, Deserialize)]
+struct ClientForm {
+    // ---
+    csrf: String,
+    client_id: String,
+}
+
+/// Form naming one access token by fingerprint.
+#[derive(Debug, Deserialize)]
+struct RevokeForm {
+    // ---
+    csrf: String,
+    fingerprint: String,
+    /// Sessions filter to return to
+    #[serde(default)]
+    user_id: Option<String>,
+}
+
+/// Form naming one user.
+#[derive(Debug, Deserialize)]
+struct UserForm {
+    // ---
+    csrf: String,
+    user_id: String,
+}
+
+/// `?deleted=true` on the clients page.
+#[derive(Debug, Default, Deserialize)]
+struct ClientsQuery {
+    // ---
+    #[serde(default)]
+    deleted: bool,
+}
+
+/// `?q=` search and `?deleted=true` on the users page.
+#[derive(Debug, Default, Deserialize)]
+struct UsersQuery {
+    // ---
+    #[serde(default)]
+    q: Option<String>,
+    #[serde(default)]
+    deleted: bool,
+}
+
+/// `?user_id=` filter on the sessions page.
+#[derive(Debug, Default, Deserialize)]
+struct SessionsQuery {
+    // ---
+    #[serde(default)]
+    user_id: Option<String>,
+}
+
+/// `?entity=` and `?id=` filters on the history page.
+#[derive(Debug, Default, Deserialize)]
+struct HistoryQuery {
+    // ---
+    #[serde(default)]
+    entity: Option<String>,
+    #[serde(default)]
+    id: Option<String>,
+}
+
+/// A message shown above a page's content.
+enum Notice {
+    // ---
+    /// Trusted HTML
+    Info(String),
+    Error(String),
+}
+
+impl Notice {
+    // ---
+    fn html(&self) -> String {
+        // ---
+        match self {
+            Notice::Info(html) => format!("\n    <p class=\"tokn-notice\">{html}</p>"),
+            Notice::Error(text) => {
+                format!("\n    <p class=\"tokn-error\">{}</p>", escape_html(text))
+            }
+        }
+    }
+}
+
+// ---
+
+async fn login_page(State(ui): State<AdminUi>) -> Response {
+    // ---
+    login_view(&ui, None)
+}
+
+async fn login(State(ui): State<AdminUi>, Form(form): Form<LoginForm>) -> Response {
+    // ---
+    if !constant_time_eq(form.token.as_bytes(), ui.token.expose().as_bytes()) {
+        tracing::warn!("Rejected admin UI sign-in with an invalid token");
+        let notice = Notice::Error("Invalid admin token".to_string());
+        return (StatusCode::UNAUTHORIZED, login_view(&ui, Some(notice))).into_response();
+    }
+
+    tracing::info!("Admin UI sign-in");
+    let cookie = format!(
+        "{SESSION_COOKIE}={}; Path=/admin/ui; HttpOnly; Secure; SameSite=Strict",
+        ui.session
+    );
+    with_cookie(Redirect::to("/admin/ui/clients").into_response(), &cookie)
+}
+
+async fn logout(State(ui): State<AdminUi>, Form(form): Form<CsrfForm>) -> Response {
+    // ---
+    if !ui.csrf_ok(&form.csrf) {
+        return forbidden();
+    }
+
+    let cookie =
+        format!("{SESSION_COOKIE}=; Path=/admin/ui; HttpOnly; Secure; SameSite=Strict; Max-Age=0");
+    with_cookie(Redirect::to("/admin/ui/login").into_response(), &cookie)
+}
+
+fn login_view(ui: &AdminUi, notice: Option<Notice>) -> Response {
+    // ---
+    let content = format!(
+        r#"
+    <h1>tokn admin</h1>{notice}
+    <form method="POST" action="/admin/ui/login">
+        <label>Admin token <input type="password" name="token" autocomplete="current-password" required></label>
+        <button type="submit">Sign in</button>
+    </form>"#,
+        notice = notice.map(|n| n.html()).unwrap_or_default(),
+    );
+
+    no_store(ui.render("Sign in", &content))
+}
+
+// ---
+
+async fn clients_page(State(ui): State<AdminUi>, Query(query): Query<ClientsQuery>) -> Response {
+    // ---
+    clients_view(&ui, query.deleted, None).await
+}
+
+async fn create_client(State(ui): State<AdminUi>, Form(form): Form<CreateClientForm>) -> Response {
+    // ---
+    if !ui.csrf_ok(&form.csrf) {
+        return forbidden();
+    }
+
+    let client_id = form.client_id.trim();
+    let redirect_uri = form.redirect_uri.trim();
+    if client_id.is_empty() {
+        let notice = Notice::Error("Client ID is required".to_string());
+        return (
+            StatusCode::BAD_REQUEST,
+            clients_view(&ui, false, Some(notice)).await,
+        )
+            .into_response();
+    }
+    if !(redirect_uri.starts_with("https://") || redirect_uri.starts_with("http://")) {
+        let notice = Notice::Error("Redirect URI must be an http(s) URL".to_string());
+        return (
+            StatusCode::BAD_REQUEST,
+            clients_view(&ui, false, Some(notice)).await,
+        )
+            .into_response();
+    }
+
+    let secret = admin::generate_client_secret();
+    if let Err(e) =
+        admin::create_client(&ui.app.pool, ACTOR, client_id, &secret, redirect_uri).await
+    {
+        tracing::warn!("Admin UI: {e:#}");
+        let notice = Notice::Error(format!("{e:#}"));
+        return (
+            StatusCode::CONFLICT,
+            clients_view(&ui, false, Some(notice)).await,
+        )
+            .into_response();
+    }
+
+    tracing::info!("Admin UI: created client '{client_id}'");
+    let notice = Notice::Info(format!(
+        "Created client <code>{}</code>. Client secret (shown once): <code>{secret}</code>",
+        escape_html(client_id)
+    ));
+    clients_view(&ui, false, Some(notice)).await
+}
+
+async fn reset_client_secret(State(ui): State<AdminUi>, Form(form): Form<ClientForm>) -> Response {
+    // ---
+    if !ui.csrf_ok(&form.csrf) {
+        return forbidden();
+    }
+
+    let secret = admin::generate_client_secret();
+    let notice =
+        match admin::reset_client_secret(&ui.app.pool, ACTOR, &form.client_id, &secret).await {
+            Ok(true) => {
+                tracing::info!("Admin UI: reset secret for client '{}'", form.client_id);
+                Notice::Info(format!(
+                    "Reset secret for <code>{}</code>; the old secret no longer works. \
+                 Client secret (shown once): <code>{secret}</code>",
+                    escape_html(&form.client_id)
+                ))
+            }
+            Ok(false) => Notice::Error(format!("No active client with ID '{}'", form.client_id)),
+            Err(e) => return internal_error(&e),
+        };
+    clients_view(&ui, false, Some(notice)).await
+}
+
+async fn delete_client(State(ui): State<AdminUi>, Form(form): Form<ClientForm>) -> Response {
+    // ---
+    if !ui.csrf_ok(&form.csrf) {
+        return forbidden();
+    }
+
+    let notice = match admin::delete_client(&ui.app.pool, ACTOR, &form.client_id).await {
+        Ok(Some(revoked)) => {
+            tracing::info!(
+                "Admin UI: deleted client '{}' and revoked {} access tokens",
+                form.client_id,
+                revoked.len()
+            );
+            for token in &revoked {
+                emit_revoked(&ui, token);
+            }
+            Notice::Info(format!(
+                "Deleted <code>{}</code> and revoked {} access tokens",
+                escape_html(&form.client_id),
+                revoked.len()
+            ))
+        }
+        Ok(None) => Notice::Error(format!("No active client with ID '{}'", form.client_id)),
+        Err(e) => return internal_error(&e),
+    };
+    clients_view(&ui, false, Some(notice)).await
+}
+
+async fn restore_client(State(ui): State<AdminUi>, Form(form): Form<ClientForm>) -> Response {
+    // ---
+    if !ui.csrf_ok(&form.csrf) {
+        return forbidden();
+    }
+
+    let notice = match admin::restore_client(&ui.app.pool, ACTOR, &form.client_id).await {
+        Ok(true) => {
+            tracing::info!("Admin UI: restored client '{}'", form.client_id);
+            Notice::Info(format!(
+                "Restored <code>{}</code>",
+                escape_html(&form.client_id)
+            ))
+        }
+        Ok(false) => Notice::Error(format!("No deleted client with ID '{}'", form.client_id)),
+        Err(e) => return internal_error(&e),
+    };
+    clients_view(&ui, false, Some(notice)).await
+}
+
+async fn clients_view(ui: &AdminUi, include_deleted: bool, notice: Option<Notice>) -> Response {
+    // ---
+    let clients = match admin::list_clients(&ui.app.pool, include_deleted).await {
+        Ok(clients) => clients,
+        Err(e) => return internal_error(&e),
+    };
+
+    let rows: String = clients
+        .iter()
+        .map(|client| {
+            let button =
+                |action, label| action_button(ui, action, label, "client_id", &client.client_id);
+            let (status, actions) = match client.deleted_at {
+                Some(deleted_at) => (
+                    format!("Deleted {}", timestamp(deleted_at)),
+                    button("/admin/ui/clients/restore", "Restore"),
+                ),
+                None => (
+                    String::new(),
+                    button("/admin/ui/clients/reset-secret", "Reset secret")
+                        + &button("/admin/ui/clients/delete", "Delete"),
+                ),
+            };
+            format!(
+                r#"
+        <tr>
+            <td><code>{id}</code></td>
+            <td>{redirect_uri}</td>
+            <td>{created}</td>
+            <td>{status}</td>
+            <td>{actions}
+                <a href="/admin/ui/history?{filter}">History</a>
+            </td>
+        </tr>"#,
+                id = escape_html(&client.client_id),
+                redirect_uri = escape_html(&client.redirect_uri),
+                created = timestamp(client.created_at),
+                filter = history_filter(Entity::Client, &client.client_id),
+            )
+        })
+        .collect();
+
+    let content = format!(
+        r#"{notice}{toggle}
+    <table>
+        <tr><th>Client ID</th><th>Redirect URI</th><th>Registered</th><th>Status</th><th></th></tr>{rows}
+    </table>
+    <h2>Register a client</h2>
+    <form method="POST" action="/admin/ui/clients">
+        <input type="hidden" name="csrf" value="{csrf}">
+        <label>Client ID <input name="client_id" required></label>
+        <label>Redirect URI <input name="redirect_uri" type="url" required></label>
+        <button type="submit">Register</button>
+    </form>"#,
+        notice = notice.map(|n| n.html()).unwrap_or_default(),
+        toggle = deleted_toggle("/admin/ui/clients", include_deleted),
+        csrf = ui.csrf,
+    );
+
+    ui.page("Clients", &content)
+}
+
+// ---
+
+async fn users_page(State(ui): State<AdminUi>, Query(query): Query<UsersQuery>) -> Response {
+    // ---
+    let search = query.q.as_deref().map(str::trim).filter(|q| !q.is_empty());
+    let users = match admin::list_users(&ui.app.pool, search, query.deleted, PAGE_LIMIT).await {
+        Ok(users) => users,
+        Err(e) => return internal_error(&e),
+    };
+
+    let rows: String = users
+        .iter()
+        .map(|user| {
+            let button =
+                |action, label| action_button(&ui, action, label, "user_id", &user.user_id);
+            let (status, action) = match user.deleted_at {
+                Some(deleted_at) => (
+                    format!("Deleted {}", timestamp(deleted_at)),
+                    button("/admin/ui/users/restore", "Restore"),
+                ),
+                None => (String::new(), button("/admin/ui/users/delete", "Delete")),
+            };
+            format!(
+                r#"
+        <tr>
+            <td><code>{id}</code></td>
+            <td>{username}</td>
+            <td>{phone}</td>
+            <td>{created}</td>
+            <td>{status}</td>
+            <td>{action}
+                <a href="/admin/ui/sessions?{filter}">Sessions</a>
+                <a href="/admin/ui/history?{history}">History</a>
+            </td>
+        </tr>"#,
+                id = escape_html(&user.user_id),
+                username = escape_html(&user.username),
+                phone = escape_html(user.phone_number.as_deref().unwrap_or("")),
+                created = timestamp(user.created_at),
+                filter = user_filter(&user.user_id),
+                history = history_filter(Entity::User, &user.user_id),
+            )
+        })
+        .collect();
+
+    let content = format!(
+        r#"
+    <form method="GET" action="/admin/ui/users">
+        <label>Search <input name="q" value="{q}" placeholder="username or user ID"></label>
+        <label><input type="checkbox" name="deleted" value="true"{checked}> Include deleted</label>
+        <button type="submit">Search</button>
+    </form>
+    <table>
+        <tr><th>User ID</th><th>Username</th><th>Phone</th><th>Created</th><th>Status</th><th></th></tr>{rows}
+    </table>{more}"#,
+        q = escape_html(search.unwrap_or("")),
+        checked = if query.deleted { " checked" } else { "" },
+        more = more_rows(users.len()),
+    );
+
+    ui.page("Users", &content)
+}
+
+async fn delete_user(State(ui): State<AdminUi>, Form(form): Form<UserForm>) -> Response {
+    // ---
+    if !ui.csrf_ok(&form.csrf) {
+        return forbidden();
+    }
+
+    match admin::delete_user(&ui.app.pool, ACTOR, &form.user_id).await {
+        Ok(Some(revoked)) => {
+            tracing::info!(
+                "Admin UI: deleted user '{}' and revoked {} access tokens",
+                form.user_id,
+                revoked.len()
+            );
+            for token in &revoked {
+                emit_revoked(&ui, token);
+            }
+        }
+        // Already deleted; the list is simply refreshed
+        Ok(None) => {}
+        Err(e) => return internal_error(&e),
+    }
+
+    Redirect::to("/admin/ui/users?deleted=true").into_response()
+}
+
+async fn restore_user(State(ui): State<AdminUi>, Form(form): Form<UserForm>) -> Response {
+    // ---
+    if !ui.csrf_ok(&form.csrf) {
+        return forbidden();
+    }
+
+    match admin::restore_user(&ui.app.pool, ACTOR, &form.user_id).await {
+        Ok(true) => tracing::info!("Admin UI: restored user '{}'", form.user_id),
+        // Not deleted; the list is simply refreshed
+        Ok(false) => {}
+        Err(e) => return internal_error(&e),
+    }
+
+    Redirect::to("/admin/ui/users").into_response()
+}
+
+// ---
+
+async fn sessions_page(State(ui): State<AdminUi>, Query(query): Query<SessionsQuery>) -> Response {
+    // ---
+    let user_id = query.user_id.as_deref().filter(|id| !id.is_empty());
+    let now = ui.app.clock.now().naive_utc();
+    let tokens = match admin::list_access_tokens(&ui.app.pool, user_id, now, PAGE_LIMIT).await {
+        Ok(tokens) => tokens,
+        Err(e) => return internal_error(&e),
+    };
+
+    let rows: String = tokens
+        .iter()
+        .map(|token| {
+            format!(
+                r#"
+        <tr>
+            <td><code title="{fingerprint}">{short}</code></td>
+            <td>{client}</td>
+            <td><a href="/admin/ui/sessions?{filter}">{user}</a></td>
+            <td>{scope}</td>
+            <td>{issued}</td>
+            <td>{expires}</td>
+            <td>
+                <form method="POST" action="/admin/ui/sessions/revoke">
+                    <input type="hidden" name="csrf" value="{csrf}">
+                    <input type="hidden" name="fingerprint" value="{fingerprint}">
+                    <input type="hidden" name="user_id" value="{return_to}">
+                    <button type="submit">Revoke</button>
+                </form>
+            </td>
+        </tr>"#,
+                fingerprint = escape_html(&token.fingerprint),
+                short = escape_html(token.fingerprint.get(..12).unwrap_or(&token.fingerprint)),
+                client = escape_html(&token.client_id),
+                user = escape_html(&token.user_id),
+                filter = user_filter(&token.user_id),
+                scope = escape_html(token.scope.as_deref().unwrap_or("")),
+                issued = timestamp(token.created_at),
+                expires = timestamp(token.expires_at),
+                return_to = escape_html(user_id.unwrap_or("")),
+                csrf = ui.csrf,
+            )
+        })
+        .collect();
+
+    let filter = match user_id {
+        Some(user_id) => format!(
+            r#"
+    <p>Access tokens for <code>{id}</code> (<a href="/admin/ui/sessions">show all</a>)</p>
+    <form method="POST" action="/admin/ui/sessions/revoke-user">
+        <input type="hidden" name="csrf" value="{csrf}">
+        <input type="hidden" name="user_id" value="{id}">
+        <button type="submit">Revoke all for this user</button>
+    </form>"#,
+            id = escape_html(user_id),
+            csrf = ui.csrf,
+        ),
+        None => String::new(),
+    };
+
+    let content = format!(
+        r#"{filter}
+    <table>
+        <tr><th>Token</th><th>Client</th><th>User</th><th>Scope</th><th>Issued</th><th>Expires</th><th></th></tr>{rows}
+    </table>{more}"#,
+        more = more_rows(tokens.len()),
+    );
+
+    ui.page("Sessions", &content)
+}
+
+async fn revoke_session(State(ui): State<AdminUi>, Form(form): Form<RevokeForm>) -> Response {
+    // ---
+    if !ui.csrf_ok(&form.csrf) {
+        return forbidden();
+    }
+
+    match admin::revoke_access_token(&ui.app.pool, &form.fingerprint).await {
+        Ok(Some(revoked)) => {
+            tracing::info!(
+                "Admin UI: revoked an access token of user '{}' for client '{}'",
+                revoked.user_id,
+                revoked.client_id
+            );
+            emit_revoked(&ui, &revoked);
+        }
+        // Already expired or revoked; the list is simply refreshed
+        Ok(None) => {}
+        Err(e) => return internal_error(&e),
+    }
+
+    let user_id = form.user_id.as_deref().filter(|id| !id.is_empty());
+    Redirect::to(&sessions_url(user_id)).into_response()
+}
+
+async fn revoke_user_sessions(State(ui): State<AdminUi>, Form(form): Form<UserForm>) -> Response {
+    // ---
+    if !ui.csrf_ok(&form.csrf) {
+        return forbidden();
+    }
+
+    match admin::revoke_user_tokens(&ui.app.pool, &form.user_id).await {
+        Ok(revoked) => {
+            tracing::info!(
+                "Admin UI: revoked {} access tokens of user '{}'",
+                revoked.len(),
+                form.user_id
+            );
+            for token in &revoked {
+                emit_revoked(&ui, token);
+            }
+        }
+        Err(e) => return internal_error(&e),
+    }
+
+    Redirect::to(&sessions_url(Some(&form.user_id))).into_response()
+}
+
+fn emit_revoked(ui: &AdminUi, revoked: &RevokedToken) {
+    // ---
+    ui.app.events.emit(
+        AuthEvent::new(AuthEventKind::TokenRevoked)
+            .subject(&revoked.user_id)
+            .client_id(&revoked.client_id),
+    );
+}
+
+// ---
+
+async fn history_page(State(ui): State<AdminUi>, Query(query): Query<HistoryQuery>) -> Response {
+    // ---
+    let entity = match query.entity.as_deref().filter(|e| !e.is_empty()) {
+        Some(entity) => match entity.parse::<Entity>() {
+            Ok(entity) => Some(entity),
+            Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+        },
+        None => None,
+    };
+    let id = query
+        .id
+        .as_deref()
+        .filter(|id| !id.is_empty() && entity.is_some());
+    let changes = match history::list_changes(&ui.app.pool, entity, id, PAGE_LIMIT).await {
+        Ok(changes) => changes,
+        Err(e) => return internal_error(&e),
+    };
+
+    let rows: String = changes
+        .iter()
+        .map(|change| {
+            format!(
+                r#"
+        <tr>
+            <td>{at}</td>
+            <td>{actor}</td>
+            <td>{entity} <code>{id}</code></td>
+            <td>{action}</td>
+            <td>{field}</td>
+            <td>{old}</td>
+            <td>{new}</td>
+        </tr>"#,
+                at = timestamp(change.changed_at),
+                actor = escape_html(&change.actor),
+                entity = escape_html(&change.entity),
+                id = escape_html(&change.entity_id),
+                action = escape_html(&change.action),
+                field = escape_html(change.field.as_deref().unwrap_or("")),
+                old = escape_html(change.old_value.as_deref().unwrap_or("")),
+                new = escape_html(change.new_value.as_deref().unwrap_or("")),
+            )
+        })
+        .collect();
+
+    let filter = match (entity, id) {
+        (Some(entity), Some(id)) => format!(
+            "\n    <p>Changes to {entity} <code>{}</code> (<a href=\"/admin/ui/history\">show all</a>)</p>",
+            escape_html(id)
+        ),
+        _ => String::new(),
+    };
+
+    let content = format!(
+        r#"{filter}
+    <table>
+        <tr><th>When</th><th>Actor</th><th>Record</th><th>Action</th><th>Field</th><th>Old</th><th>New</th></tr>{rows}
+    </table>{more}"#,
+        more = more_rows(changes.len()),
+    );
+
+    ui.page("History", &content)
+}
+
+// ---
+
+/// Send requests without a valid session cookie to the sign-in page.
+async fn require_session(State(ui): State<AdminUi>, req: Request, next: Next) -> Response {
+    // ---
+    match session_cookie(req.headers()) {
+        Some(session) if constant_time_eq(session.as_bytes(), ui.session.as_bytes()) => {
+            next.run(req).await
+        }
+        _ => Redirect::to("/admin/ui/login").into_response(),
+    }
+}
+
+fn session_cookie(headers: &HeaderMap) -> Option<&str> {
+    // ---
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .find_map(|pair| pair.trim().strip_prefix(SESSION_COOKIE)?.strip_prefix('='))
+}
+
+fn with_cookie(mut response: Response, cookie: &str) -> Response {
+    // ---
+    if let Ok(value) = HeaderValue::from_str(cookie) {
+        response.headers_mut().insert(header::SET_COOKIE, value);
+    }
+    response
+}
+
+/// Keep pages (and the secrets some of them show) out of browser and proxy
+/// caches.
+fn no_store(mut response: Response) -> Response {
+    // ---
+    response
+        .headers_mut()
+        .insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    response
+}
+
+fn forbidden() -> Response {
+    // ---
+    tracing::warn!("Rejected admin UI form without a valid anti-forgery value");
+    (
+        StatusCode::FORBIDDEN,
+        "Invalid form; reload the page and retry",
+    )
+        .into_response()
+}
+
+fn internal_error(error: &anyhow::Error) -> Response {
+    // ---
+    tracing::error!("Admin UI: {error:#}");
+    (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error").into_response()
+}
+
+// ---
+
+fn sessions_url(user_id: Option<&str>) -> String {
+    // ---
+    match user_id {
+        Some(user_id) => format!("/admin/ui/sessions?{}", user_filter(user_id)),
+        None => "/admin/ui/sessions".to_string(),
+    }
+}
+
+/// `user_id=...` query string, URL-encoded (and safe inside an attribute).
+fn user_filter(user_id: &str) -> String {
+    // ---
+    serde_urlencoded::to_string([("user_id", user_id)]).unwrap_or_default()
+}
+
+/// `entity=...&id=...` query string for the history page.
+fn history_filter(entity: Entity, id: &str) -> String {
+    // ---
+    serde_urlencoded::to_string([("entity", entity.as_str()), ("id", id)]).unwrap_or_default()
+}
+
+/// A one-button form posting `name=value` to `action`.
+fn action_button(ui: &AdminUi, action: &str, label: &str, name: &str, value: &str) -> String {
+    // ---
+    format!(
+        r#"
+                <form method="POST" action="{action}">
+                    <input type="hidden" name="csrf" value="{csrf}">
+                    <input type="hidden" name="{name}" value="{value}">
+                    <button type="submit">{label}</button>
+                </form>"#,
+        csrf = ui.csrf,
+        value = escape_html(value),
+    )
+}
+
+/// Link switching a list between active and all records.
+fn deleted_toggle(path: &str, include_deleted: bool) -> String {
+    // ---
+    if include_deleted {
+        format!("\n    <p><a href=\"{path}\">Hide deleted</a></p>")
+    } else {
+        format!("\n    <p><a href=\"{path}?deleted=true\">Show deleted</a></p>")
+    }
+}
+
+fn more_rows(shown: usize) -> String {
+    // ---
+    if shown as i64 == PAGE_LIMIT {
+        format!("\n    <p>Showing the first {PAGE_LIMIT}; narrow the search to see others.</p>")
+    } else {
+        String::new()
+    }
+}
+
+fn timestamp(at: NaiveDateTime) -> String {
+    // ---
+    at.format("%Y-%m-%d %H:%M UTC").to_string()
+}
+
+fn escape_html(value: &str) -> String {
+    // ---
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,134 @@
+// oauth2-server/src/check.rs
+
+//! `--check-config`: load and validate the configuration, then probe
+//! Postgres (schema and registered client secrets) and Redis
+
+use anyhow::Context;
+use tokn_config::Secret;
+use tokn_core::SystemClock;
+use tokn_i18n::Localizer;
+use tokn_ratelimit::RateLimiter;
+use tokn_server::CheckReport;
+use tokn_theme::Themes;
+
+// ---
+
+use crate::{admin, database, Config};
+
+// ---
+
+/// Run every startup check oauth2-server can make without serving.
+pub async fn check_config() -> CheckReport {
+    // ---
+    let mut report = CheckReport::new("oauth2-server");
+
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            report.fail("config", format!("{e:#}"));
+            return report;
+        }
+    };
+    report.pass("config", format!("loaded (profile {})", config.profile));
+
+    report.secret(
+        "admin.token",
+        config.admin.token.as_ref().map(Secret::expose),
+    );
+    report.secret(
+        "service_auth.key",
+        config.service_auth.key.as_ref().map(Secret::expose),
+    );
+    report.tls(config.server.tls.as_ref()).await;
+
+    match Localizer::new(&config.i18n) {
+        Ok(_) => report.pass(
+            "i18n",
+            format!("default locale {}", config.i18n.default_locale),
+        ),
+        Err(e) => report.fail("i18n", e.to_string()),
+    }
+    match Themes::new(&config.theme) {
+        Ok(_) => report.pass("theme", format!("default theme {}", config.theme.name)),
+        Err(e) => report.fail("theme", e.to_string()),
+    }
+
+    // ---
+    let mut pool = None;
+    report
+        .probe("postgres", async {
+            let connected = database::create_pool(config.database.url.expose())
+                .await
+                .context("Failed to connect to Postgres")?;
+            sqlx::query("SELECT 1")
+                .execute(&connected)
+                .await
+                .context("Postgres did not answer a query")?;
+            pool = Some(connected);
+            Ok("connected".to_string())
+        })
+        .await;
+
+    match &pool {
+        None => {
+            report.skip("schema", "Postgres unreachable");
+            report.skip("clients", "Postgres unreachable");
+        }
+        Some(pool) => {
+            match database::pending_migrations(pool).await {
+                Ok(pending) if pending.is_empty() => report.pass("schema", "up to date"),
+                Ok(pending) => report.warn(
+                    "schema",
+                    format!(
+                        "{} migration(s) not applied ({}); apply them before starting",
+                        pending.len(),
+                        pending
+                            .iter()
+                            .map(i64::to_string)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                ),
+                Err(e) => report.fail("schema", format!("{e:#}")),
+            }
+
+            match admin::weak_client_secrets(pool).await {
+                Ok(weak) if weak.is_empty() => report.pass("clients", "client secrets strong"),
+                Ok(weak) if config.profile.is_strict() => report.fail(
+                    "clients",
+                    format!("weak client secrets: {}", weak.join("; ")),
+                ),
+                Ok(weak) => report.warn(
+                    "clients",
+                    format!(
+                        "weak client secrets: {}; refused when TOKN_ENV is staging or prod",
+                        weak.join("; ")
+                    ),
+                ),
+                Err(e) => report.fail("clients", format!("{e:#}")),
+            }
+        }
+    }
+
+    // ---
+    if config.rate_limit.enabled {
+        report
+            .probe("redis", async {
+                RateLimiter::connect(
+                    "oauth2-server",
+                    &config.rate_limit,
+                    &config.redis.url,
+                    config.circuit_breaker,
+                    SystemClock::shared(),
+                )
+                .await
+                .context("Failed to connect to Redis (rate limiting)")?;
+                Ok("connected (rate limiting)".to_string())
+            })
+            .await;
+    } else {
+        report.skip("redis", "rate limiting disabled");
+    }
+
+    report
+}
@@ -1,30 +1,120 @@
 // oauth2-server/src/config.rs
 
-use anyhow::{Context, Result};
+use anyhow::Result;
 use serde::Deserialize;
-use std::env;
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use tokn_config::{ConfigLoader, Profile, Secret};
+use tokn_events::{EventsBackend, EventsConfig};
+use tokn_i18n::I18nConfig;
+use tokn_portal::{PortalConfig, ServiceUrls};
+use tokn_ratelimit::{Algorithm, KeyBy, RateLimitConfig};
+use tokn_resilience::{ChaosConfig, ChaosTargets, CircuitBreakerConfig, RetryPolicy};
+use tokn_scheduler::{Schedule, SchedulerConfig};
+use tokn_server::{
+    AdminConfig, ApiConfig, Bind, CompressionAlgorithms, CompressionConfig, CorsConfig, CorsList, SecurityHeadersConfig,
+    ServiceAuthConfig, SocketMode, TlsConfig,
+};
+use tokn_sms::{SmsBackend, SmsConfig};
+use tokn_telemetry::LogConfig;
+use tokn_theme::{ClientThemes, ThemeConfig};
+
+// ---
+
+use crate::DEFAULT_SLOW_QUERY_MS;
 
 // ---
 
 /// Application configuration for the OAuth2 authorization server.
 ///
-/// Contains server, database, and Redis settings loaded from environment variables.
-#[derive(Debug, Clone, Deserialize)]
+/// Contains server, database, and Redis settings loaded from defaults, an optional
+/// config file, and environment variables.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
 pub struct Config {
     // ---
+    /// Deployment profile (`TOKN_ENV`); `staging` and `prod` refuse weak
+    /// secrets, `prod` also requires TLS
+    #[serde(default)]
+    pub profile: Profile,
     pub server: ServerConfig,
     pub database: DatabaseConfig,
     pub redis: RedisConfig,
+    /// Backoff while waiting for dependencies at startup
+    #[serde(default)]
+    pub startup: RetryPolicy,
+    /// Circuit breaker around database queries
+    #[serde(default)]
+    pub circuit_breaker: CircuitBreakerConfig,
+    /// Log filter (reloadable)
+    #[serde(default)]
+    pub log: LogConfig,
+    /// `/admin` endpoints
+    #[serde(default)]
+    pub admin: AdminConfig,
+    /// Public API routing (`/v1` and the deprecated unversioned paths)
+    #[serde(default)]
+    pub api: ApiConfig,
+    /// Page localization (`Accept-Language`, locale overrides)
+    #[serde(default)]
+    pub i18n: I18nConfig,
+    /// Page theming (partials, static assets, per-client themes)
+    #[serde(default)]
+    pub theme: ThemeConfig,
+    /// Auth event publishing (Kafka/NATS)
+    #[serde(default)]
+    pub events: EventsConfig,
+    /// SMS delivery and one-time codes for phone verification
+    #[serde(default)]
+    pub sms: SmsConfig,
+    /// Recurring housekeeping jobs (expired token cleanup)
+    #[serde(default)]
+    pub scheduler: SchedulerConfig,
+    /// Per-client request rate limiting (Redis)
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+    /// Developer portal (`/docs`)
+    #[serde(default)]
+    pub portal: PortalConfig,
+    /// Signed requests between tokn services
+    #[serde(default)]
+    pub service_auth: ServiceAuthConfig,
+    /// Fault injection into dependency calls (`chaos` builds only)
+    #[serde(default)]
+    pub chaos: ChaosConfig,
 }
 
 // ---
 
 /// HTTP server configuration.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
 pub struct ServerConfig {
     // ---
     pub host: String,
     pub port: u16,
+    /// Listen address overriding `host`/`port`: `host:port` or `unix:/path/to.sock`
+    #[serde(default)]
+    pub bind: Option<Bind>,
+    /// Unix socket file permissions (octal, e.g. 660)
+    #[serde(default)]
+    pub socket_mode: Option<SocketMode>,
+    /// Serve HTTPS with this certificate/key pair (plain HTTP when unset)
+    #[serde(default)]
+    pub tls: Option<TlsConfig>,
+    /// Response compression (gzip/br; token responses are never compressed)
+    #[serde(default)]
+    pub compression: CompressionConfig,
+    /// CORS for browser apps on other origins (off until origins are listed)
+    #[serde(default)]
+    pub cors: CorsConfig,
+    /// Security headers added to every response
+    #[serde(default)]
+    pub security_headers: SecurityHeadersConfig,
+    /// Serve gRPC token introspection on this address (off when unset)
+    #[serde(default)]
+    pub grpc_addr: Option<SocketAddr>,
+    /// Seconds in-flight requests get to finish after `SIGTERM`/`SIGINT`
+    pub drain_timeout_seconds: u64,
 }
 
 // ---
@@ -32,10 +122,23 @@ pub struct ServerConfig {
 /// PostgreSQL database configuration.
 ///
 /// Used for storing OAuth2 clients, authorization codes, and access tokens.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
 pub struct DatabaseConfig {
     // ---
-    pub url: String,
+    /// Connection URL, usually carrying the password; redacted in `Debug`
+    pub url: Secret,
+    /// Log queries taking at least this many milliseconds (env
+    /// `DATABASE_SLOW_QUERY_MS`, default: [`DEFAULT_SLOW_QUERY_MS`])
+    pub slow_query_ms: Option<u64>,
+}
+
+impl DatabaseConfig {
+    // ---
+    /// The slow-query threshold in effect.
+    pub fn slow_query_ms(&self) -> u64 {
+        // ---
+        self.slow_query_ms.unwrap_or(DEFAULT_SLOW_QUERY_MS)
+    }
 }
 
 // ---
@@ -43,7 +146,7 @@ pub struct DatabaseConfig {
 /// Redis connection configuration.
 ///
 /// Used for session storage and token caching.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
 pub struct RedisConfig {
     // ---
     pub url: String,
@@ -53,50 +156,215 @@ pub struct RedisConfig {
 
 impl Config {
     // ---
-    /// Loads configuration from environment variables.
+    /// Loads configuration: defaults → config file (`TOKN_CONFIG`) → environment.
+    ///
+    /// Reads `.env` if present.
     ///
-    /// Reads configuration from .env file if present, then from environment variables.
+    /// # Environment Variables
+    ///
+    /// - `TOKN_ENV` → `profile` (default: "dev"; "staging" or "prod" makes weak secrets fatal, "prod" also requires TLS)
+    /// - `SERVER_HOST` → `server.host` (default: "127.0.0.1")
+    /// - `SERVER_PORT` → `server.port` (default: "8082")
+    /// - `SERVER_BIND` → `server.bind` (optional; `host:port` or `unix:/path`, overrides host/port)
+    /// - `SERVER_SOCKET_MODE` → `server.socket_mode` (optional; octal, Unix sockets only)
+    /// - `SERVER_TLS_CERT_PATH` → `server.tls.cert_path` (optional; enables HTTPS)
+    /// - `SERVER_TLS_KEY_PATH` → `server.tls.key_path` (required with the certificate)
+    /// - `SERVER_COMPRESSION` → `server.compression.algorithms` (default: "gzip,br"; "off" disables)
+    /// - `SERVER_CORS_ALLOWED_ORIGINS` → `server.cors.allowed_origins` (optional; comma-separated origins or "*", enables CORS)
+    /// - `SERVER_CORS_ALLOWED_METHODS` → `server.cors.allowed_methods` (default: "GET,POST")
+    /// - `SERVER_CORS_ALLOWED_HEADERS` → `server.cors.allowed_headers` (default: "authorization,content-type,x-request-id")
+    /// - `SERVER_CORS_ALLOW_CREDENTIALS` → `server.cors.allow_credentials` (default: "false"; not with "*" origins)
+    /// - `SERVER_CONTENT_SECURITY_POLICY` → `server.security_headers.content_security_policy` (default: same-origin resources, no framing; "" omits the header)
+    /// - `SERVER_FRAME_OPTIONS` → `server.security_headers.frame_options` (default: "DENY"; or "SAMEORIGIN", "" omits the header)
+    /// - `SERVER_REFERRER_POLICY` → `server.security_headers.referrer_policy` (default: "no-referrer"; "" omits the header)
+    /// - `SERVER_CONTENT_TYPE_OPTIONS` → `server.security_headers.content_type_options` (default: "nosniff"; "" omits the header)
+    /// - `SERVER_GRPC_ADDR` → `server.grpc_addr` (optional; enables gRPC introspection)
+    /// - `SERVER_DRAIN_TIMEOUT_SECONDS` → `server.drain_timeout_seconds` (default: "30"; time in-flight requests get to finish on shutdown)
+    /// - `DATABASE_URL` → `database.url` (required; or `DATABASE_URL_FILE` naming a file that holds it)
+    /// - `DATABASE_SLOW_QUERY_MS` → `database.slow_query_ms` (default: "200"; log queries at least this slow, "0" logs every query; reloadable)
+    /// - `REDIS_URL` → `redis.url` (default: "redis://127.0.0.1:6379")
+    /// - `STARTUP_MAX_WAIT_SECONDS` → `startup.max_wait_seconds` (default: "60")
+    /// - `CIRCUIT_BREAKER_FAILURE_THRESHOLD` → `circuit_breaker.failure_threshold` (default: "5")
+    /// - `CIRCUIT_BREAKER_OPEN_SECONDS` → `circuit_breaker.open_seconds` (default: "30")
+    /// - `CIRCUIT_BREAKER_CALL_TIMEOUT_MS` → `circuit_breaker.call_timeout_ms` (default: "5000")
+    /// - `RUST_LOG` → `log.filter` (optional; reloadable)
+    /// - `ADMIN_TOKEN` → `admin.token` (optional; enables `/admin` and the `/admin/ui` pages, at least 32 characters)
+    /// - `API_LEGACY_PATHS` → `api.legacy_paths` (default: "true"; also serve the unversioned API paths, deprecated)
+    /// - `I18N_DEFAULT_LOCALE` → `i18n.default_locale` (default: "en"; used when `Accept-Language` matches no translation)
+    /// - `I18N_LOCALE` → `i18n.locale` (optional; serve every page in this locale)
+    /// - `I18N_DIR` → `i18n.dir` (optional; `<locale>/*.ftl` files overriding or adding translations)
+    /// - `THEME_DIR` → `theme.dir` (optional; directory of named themes)
+    /// - `THEME_NAME` → `theme.name` (default: "default"; theme for pages without a client-specific one)
+    /// - `THEME_CLIENTS` → `theme.clients` (optional; `client_id=theme` pairs, comma-separated)
+    /// - `EVENTS_BACKEND` → `events.backend` (default: "none"; "kafka" or "nats" publishes auth events)
+    /// - `EVENTS_URL` → `events.url` (required with a backend; Kafka brokers or NATS URL)
+    /// - `EVENTS_TOPIC` → `events.topic` (default: "tokn.auth"; Kafka topic or NATS subject)
+    /// - `EVENTS_LIVE_BUFFER` → `events.live_buffer` (default: "256"; events queued per `/admin/events` subscriber)
+    /// - `SMS_BACKEND` → `sms.backend` (default: "console"; "twilio" sends text messages)
+    /// - `SMS_TWILIO_ACCOUNT_SID` → `sms.twilio_account_sid` (required with "twilio")
+    /// - `SMS_TWILIO_AUTH_TOKEN` → `sms.twilio_auth_token` (required with "twilio")
+    /// - `SMS_TWILIO_FROM` → `sms.twilio_from` (required with "twilio"; E.164 number or `MG...` service SID)
+    /// - `OTP_TTL_SECONDS` → `sms.otp.ttl_seconds` (default: "300")
+    /// - `OTP_MAX_ATTEMPTS` → `sms.otp.max_attempts` (default: "5")
+    /// - `OTP_RESEND_SECONDS` → `sms.otp.resend_seconds` (default: "30")
+    /// - `OTP_MAX_SENDS_PER_HOUR` → `sms.otp.max_sends_per_hour` (default: "5")
+    /// - `SCHEDULER_ENABLED` → `scheduler.enabled` (default: "true"; "false" stops every scheduled job)
+    /// - `JOB_EXPIRED_TOKEN_CLEANUP` → `scheduler.jobs.expired_token_cleanup` (default: "*/15 * * * *"; cron, `@every 10m`, or "off")
+    /// - `RATE_LIMIT_ENABLED` → `rate_limit.enabled` (default: "false"; limit requests per client IP in Redis)
+    /// - `RATE_LIMIT_ALGORITHM` → `rate_limit.algorithm` (default: "sliding_window"; or "token_bucket")
+    /// - `RATE_LIMIT_REQUESTS` → `rate_limit.requests` (default: "100"; per window)
+    /// - `RATE_LIMIT_WINDOW_SECONDS` → `rate_limit.window_seconds` (default: "60")
+    /// - `RATE_LIMIT_BURST` → `rate_limit.burst` (optional; token bucket capacity, defaults to the request limit)
+    /// - `RATE_LIMIT_TRUST_FORWARDED_FOR` → `rate_limit.trust_forwarded_for` (default: "false"; identify clients by `X-Forwarded-For`)
+    /// - `RATE_LIMIT_KEY_BY` → `rate_limit.key_by` (default: "ip"; "user" or "ip_and_user" count authenticated users)
+    /// - `RATE_LIMIT_ENDPOINTS` → `rate_limit.endpoints` (optional; per-path limits as an inline table, usually set in the config file instead)
+    /// - `PORTAL_ENABLED` → `portal.enabled` (default: "true"; serve the API docs at `/docs`)
+    /// - `PORTAL_SERVERS` → `portal.servers` (optional; `service=url` pairs, comma-separated, replacing the local URLs in the docs)
+    /// - `SERVICE_AUTH_KEY` → `service_auth.key` (optional; shared by all tokn services to sign requests to each other, at least 32 characters)
+    /// - `SERVICE_AUTH_MAX_AGE_SECONDS` → `service_auth.max_age_seconds` (default: "300"; accepted clock difference for signed requests)
+    /// - `CHAOS_FAILURE_PERCENT` → `chaos.failure_percent` (default: "0"; fail this share of dependency calls, `chaos` builds only)
+    /// - `CHAOS_DELAY_PERCENT` → `chaos.delay_percent` (default: "0"; delay this share of dependency calls, `chaos` builds only)
+    /// - `CHAOS_DELAY_MS` → `chaos.delay_ms` (default: "1000")
+    /// - `CHAOS_TARGETS` → `chaos.targets` (default: all; comma-separated breaker names)
+    ///
+    /// On reload (`SIGHUP` or `POST /admin/reload`) only `log.filter` and
+    /// `database.slow_query_ms` are applied; see [`crate::reloader`].
     ///
     /// # Errors
     ///
-    /// Returns an error if:
-    /// - Required environment variables are missing (DATABASE_URL)
-    /// - Port values cannot be parsed as u16
-    pub fn from_env() -> Result<Self> {
+    /// Returns a [`tokn_config::ConfigError`] listing every missing or invalid key
+    /// (e.g. `DATABASE_URL` unset, `SERVER_PORT` not a valid u16).
+    pub fn load() -> Result<Self> {
         // ---
-        dotenvy::dotenv().ok();
+        let config = ConfigLoader::new("oauth2-server")
+            .optional("server.host", "SERVER_HOST", "127.0.0.1".to_string())
+            .optional("server.port", "SERVER_PORT", 8082u16)
+            .key::<Bind>("server.bind", "SERVER_BIND")
+            .key::<SocketMode>("server.socket_mode", "SERVER_SOCKET_MODE")
+            .key::<PathBuf>("server.tls.cert_path", "SERVER_TLS_CERT_PATH")
+            .key::<PathBuf>("server.tls.key_path", "SERVER_TLS_KEY_PATH")
+            .key::<CompressionAlgorithms>("server.compression.algorithms", "SERVER_COMPRESSION")
+            .key::<CorsList>("server.cors.allowed_origins", "SERVER_CORS_ALLOWED_ORIGINS")
+            .key::<CorsList>("server.cors.allowed_methods", "SERVER_CORS_ALLOWED_METHODS")
+            .key::<CorsList>("server.cors.allowed_headers", "SERVER_CORS_ALLOWED_HEADERS")
+            .key::<bool>(
+                "server.cors.allow_credentials",
+                "SERVER_CORS_ALLOW_CREDENTIALS",
+            )
+            .key::<String>("server.security_headers.content_security_policy", "SERVER_CONTENT_SECURITY_POLICY")
+            .key::<String>("server.security_headers.frame_options", "SERVER_FRAME_OPTIONS")
+            .key::<String>("server.security_headers.referrer_policy", "SERVER_REFERRER_POLICY")
+            .key::<String>("server.security_headers.content_type_options", "SERVER_CONTENT_TYPE_OPTIONS")
+            .key::<SocketAddr>("server.grpc_addr", "SERVER_GRPC_ADDR")
+            .optional(
+                "server.drain_timeout_seconds",
+                "SERVER_DRAIN_TIMEOUT_SECONDS",
+                tokn_server::DEFAULT_DRAIN_TIMEOUT_SECONDS,
+            )
+            .required::<String>("database.url", "DATABASE_URL")
+            .key::<u64>("database.slow_query_ms", "DATABASE_SLOW_QUERY_MS")
+            .optional(
+                "redis.url",
+                "REDIS_URL",
+                "redis://127.0.0.1:6379".to_string(),
+            )
+            .optional(
+                "startup.max_wait_seconds",
+                "STARTUP_MAX_WAIT_SECONDS",
+                60u64,
+            )
+            .key::<u32>(
+                "circuit_breaker.failure_threshold",
+                "CIRCUIT_BREAKER_FAILURE_THRESHOLD",
+            )
+            .key::<u64>(
+                "circuit_breaker.open_seconds",
+                "CIRCUIT_BREAKER_OPEN_SECONDS",
+            )
+            .key::<u64>(
+                "circuit_breaker.call_timeout_ms",
+                "CIRCUIT_BREAKER_CALL_TIMEOUT_MS",
+            )
+            .key::<EventsBackend>("events.backend", "EVENTS_BACKEND")
+            .key::<String>("events.url", "EVENTS_URL")
+            .key::<String>("events.topic", "EVENTS_TOPIC")
+            .key::<usize>("events.live_buffer", "EVENTS_LIVE_BUFFER")
+            .key::<String>("log.filter", "RUST_LOG")
+            .key::<String>("admin.token", "ADMIN_TOKEN")
+            .key::<bool>("api.legacy_paths", "API_LEGACY_PATHS")
+            .key::<String>("i18n.default_locale", "I18N_DEFAULT_LOCALE")
+            .key::<String>("i18n.locale", "I18N_LOCALE")
+            .key::<PathBuf>("i18n.dir", "I18N_DIR")
+            .key::<PathBuf>("theme.dir", "THEME_DIR")
+            .key::<String>("theme.name", "THEME_NAME")
+            .key::<ClientThemes>("theme.clients", "THEME_CLIENTS")
+            .key::<SmsBackend>("sms.backend", "SMS_BACKEND")
+            .key::<String>("sms.twilio_account_sid", "SMS_TWILIO_ACCOUNT_SID")
+            .key::<String>("sms.twilio_auth_token", "SMS_TWILIO_AUTH_TOKEN")
+            .key::<String>("sms.twilio_from", "SMS_TWILIO_FROM")
+            .key::<u64>("sms.otp.ttl_seconds", "OTP_TTL_SECONDS")
+            .key::<u32>("sms.otp.max_attempts", "OTP_MAX_ATTEMPTS")
+            .key::<u64>("sms.otp.resend_seconds", "OTP_RESEND_SECONDS")
+            .key::<u32>("sms.otp.max_sends_per_hour", "OTP_MAX_SENDS_PER_HOUR")
+            .key::<bool>("scheduler.enabled", "SCHEDULER_ENABLED")
+            .key::<Schedule>(
+                "scheduler.jobs.expired_token_cleanup",
+                "JOB_EXPIRED_TOKEN_CLEANUP",
+            )
+            .key::<bool>("rate_limit.enabled", "RATE_LIMIT_ENABLED")
+            .key::<Algorithm>("rate_limit.algorithm", "RATE_LIMIT_ALGORITHM")
+            .key::<u32>("rate_limit.requests", "RATE_LIMIT_REQUESTS")
+            .key::<u64>("rate_limit.window_seconds", "RATE_LIMIT_WINDOW_SECONDS")
+            .key::<u32>("rate_limit.burst", "RATE_LIMIT_BURST")
+            .key::<bool>(
+                "rate_limit.trust_forwarded_for",
+                "RATE_LIMIT_TRUST_FORWARDED_FOR",
+            )
+            .key::<KeyBy>("rate_limit.key_by", "RATE_LIMIT_KEY_BY")
+            .key::<BTreeMap<String, u32>>("rate_limit.endpoints", "RATE_LIMIT_ENDPOINTS")
+            .key::<bool>("portal.enabled", "PORTAL_ENABLED")
+            .key::<ServiceUrls>("portal.servers", "PORTAL_SERVERS")
+            .key::<String>("service_auth.key", "SERVICE_AUTH_KEY")
+            .key::<u64>(
+                "service_auth.max_age_seconds",
+                "SERVICE_AUTH_MAX_AGE_SECONDS",
+            )
+            .key::<u8>("chaos.failure_percent", "CHAOS_FAILURE_PERCENT")
+            .key::<u8>("chaos.delay_percent", "CHAOS_DELAY_PERCENT")
+            .key::<u64>("chaos.delay_ms", "CHAOS_DELAY_MS")
+            .key::<ChaosTargets>("chaos.targets", "CHAOS_TARGETS")
+            .rule("admin.token", |token: &String| {
+                tokn_server::validate_admin_token(token)
+            })
+            .secret("admin.token")
+            .rule("events", tokn_events::validate_events_config)
+            .rule("sms", tokn_sms::validate_sms_config)
+            .rule("rate_limit", tokn_ratelimit::validate_rate_limit_config)
+            .rule("portal", tokn_portal::validate_portal_config)
+            .rule("service_auth", tokn_server::validate_service_auth_config)
+            .rule("server.cors", tokn_server::validate_cors_config)
+            .rule(
+                "server.security_headers",
+                tokn_server::validate_security_headers_config,
+            )
+            .rule("chaos", tokn_resilience::validate_chaos_config)
+            .prod_rule("chaos", tokn_resilience::forbid_chaos)
+            .prod_rule("server", |server: &ServerConfig| {
+                tokn_server::require_tls(server.tls.as_ref(), server.bind.as_ref())
+            })
+            .secret("service_auth.key")
+            .load()?;
 
-        // ---
-        let server = ServerConfig {
-            host: env::var("SERVER_HOST").unwrap_or_else(|_| "127.0.0.1".to_string()),
-            port: env::var("SERVER_PORT")
-                .unwrap_or_else(|_| "8082".to_string())
-                .parse()
-                .context("SERVER_PORT must be a valid u16")?,
-        };
-
-        // ---
-        let database = DatabaseConfig {
-            url: env::var("DATABASE_URL").context("DATABASE_URL must be set")?,
-        };
-
-        // ---
-        let redis = RedisConfig {
-            url: env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string()),
-        };
-
-        // ---
-        Ok(Self {
-            server,
-            database,
-            redis,
-        })
+        Ok(config)
     }
 
     // ---
-    /// Returns the server bind address in "host:port" format.
-    pub fn bind_address(&self) -> String {
+    /// Returns where the server listens: `server.bind` if set, else `host:port`.
+    pub fn bind_address(&self) -> Bind {
         // ---
-        format!("{}:{}", self.server.host, self.server.port)
+        self.server
+            .bind
+            .clone()
+            .unwrap_or_else(|| Bind::Tcp(format!("{}:{}", self.server.host, self.server.port)))
     }
 }
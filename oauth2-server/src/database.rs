@@ -1,15 +1,19 @@
 // oauth2-server/src/database.rs
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use sqlx::postgres::PgPoolOptions;
 use sqlx::PgPool;
+use std::time::Duration;
 
 // ---
 
 /// Creates a PostgreSQL connection pool.
 ///
 /// Configures a connection pool with a maximum of 5 connections for storing
-/// OAuth2 authorization codes, access tokens, and user data.
+/// OAuth2 authorization codes, access tokens, and user data. Waiting for a
+/// connection is capped at 5 seconds, so an unreachable database fails fast
+/// (and startup can retry, see `tokn_resilience::retry`) instead of hanging
+/// for sqlx's 30-second default.
 ///
 /// # Errors
 ///
@@ -18,8 +22,53 @@ pub async fn create_pool(database_url: &str) -> Result<PgPool> {
     // ---
     let pool = PgPoolOptions::new()
         .max_connections(5)
+        .acquire_timeout(Duration::from_secs(5))
         .connect(database_url)
         .await?;
 
     Ok(pool)
 }
+
+// ---
+
+/// Applies the embedded schema migrations (`oauth2-server/migrations`).
+///
+/// Used by test harnesses and fresh deployments to bring a database up to the
+/// schema the compiled queries expect. Migrations applied by jwt-service's
+/// Postgres token store, which may share the database, are left alone.
+///
+/// # Errors
+///
+/// Returns an error if a migration fails to apply.
+pub async fn run_migrations(pool: &PgPool) -> Result<()> {
+    // ---
+    let mut migrator = sqlx::migrate!("./migrations");
+    migrator.set_ignore_missing(true);
+    migrator.run(pool).await?;
+
+    Ok(())
+}
+
+// ---
+
+/// Versions of the embedded migrations not yet applied to the database.
+///
+/// # Errors
+///
+/// Returns an error if the migration history cannot be read (including a
+/// database that has never been migrated).
+pub(crate) async fn pending_migrations(pool: &PgPool) -> Result<Vec<i64>> {
+    // ---
+    let applied: Vec<i64> =
+        sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success")
+            .fetch_all(pool)
+            .await
+            .context("Failed to read the migration history")?;
+
+    Ok(sqlx::migrate!("./migrations")
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+        .map(|migration| migration.version)
+        .filter(|version| !applied.contains(version))
+        .collect())
+}
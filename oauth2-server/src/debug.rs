@@ -0,0 +1,65 @@
+// oauth2-server/src/debug.rs
+
+//! Sections of the `/debug` report
+//!
+//! Postgres pool utilization, housekeeping job status, and the rate
+//! limiter's breaker, on top of the build info every service reports.
+
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use tokn_ratelimit::RateLimiter;
+use tokn_resilience::CircuitBreaker;
+use tokn_scheduler::Scheduler;
+use tokn_server::DebugInfo;
+
+// ---
+
+use crate::AppState;
+
+// ---
+
+/// The `/debug` report for oauth2-server over `state`, the `scheduler`
+/// running its jobs, and `limiter`.
+pub fn debug_info(state: &AppState, scheduler: &Scheduler, limiter: &RateLimiter) -> DebugInfo {
+    // ---
+    let (pool, breaker) = (state.pool.clone(), state.postgres.clone());
+    let scheduler = scheduler.clone();
+    let limiter = limiter.clone();
+
+    DebugInfo::new("oauth2-server", env!("CARGO_PKG_VERSION"))
+        .section("postgres", move || {
+            let status = pool_status(&pool, &breaker);
+            async move { status }
+        })
+        .section("jobs", move || {
+            let status = serde_json::to_value(scheduler.status()).unwrap_or_default();
+            async move { status }
+        })
+        .section("ratelimit", move || {
+            let status = json!({
+                "enabled": limiter.is_enabled(),
+                "breaker": limiter.breaker().map(|breaker| breaker.state().as_str()),
+            });
+            async move { status }
+        })
+}
+
+// ---
+
+/// Connections open, idle, and in use against the configured maximum.
+fn pool_status(pool: &PgPool, breaker: &CircuitBreaker) -> Value {
+    // ---
+    let size = pool.size();
+    let idle = u32::try_from(pool.num_idle()).unwrap_or(size);
+    let in_use = size.saturating_sub(idle);
+    let max = pool.options().get_max_connections();
+
+    json!({
+        "size": size,
+        "idle": idle,
+        "in_use": in_use,
+        "max_connections": max,
+        "utilization": f64::from(in_use) / f64::from(max.max(1)),
+        "breaker": breaker.state().as_str(),
+    })
+}
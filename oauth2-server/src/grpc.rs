@@ -0,0 +1,118 @@
+// oauth2-server/src/grpc.rs
+
+//! gRPC token introspection (`tokn.introspection.v1.TokenIntrospection`)
+//!
+//! Looks up opaque access tokens issued by `/v1/oauth/token`, answering in the
+//! shared introspection contract so resource services can treat
+//! oauth2-server and jwt-service alike.
+
+use anyhow::{Context, Result};
+use std::net::SocketAddr;
+use tokn_proto::{
+    IntrospectRequest, IntrospectResponse, TokenIntrospection, TokenIntrospectionServer,
+};
+use tokn_server::Shutdown;
+use tonic::{Request, Response, Status};
+
+// ---
+
+use crate::query::timed;
+use crate::AppState;
+
+// ---
+
+/// [`TokenIntrospection`] implementation backed by the `access_tokens` table.
+#[derive(Clone)]
+pub struct IntrospectionService {
+    // ---
+    state: AppState,
+}
+
+impl IntrospectionService {
+    // ---
+    /// Introspect tokens against `state`'s pool, through its circuit breaker.
+    pub fn new(state: AppState) -> Self {
+        // ---
+        Self { state }
+    }
+}
+
+// ---
+
+#[tonic::async_trait]
+impl TokenIntrospection for IntrospectionService {
+    // ---
+    /// Report whether an OAuth2 access token is active.
+    ///
+    /// # Errors
+    ///
+    /// Returns `UNAVAILABLE` if the database cannot be queried; unknown and
+    /// expired tokens are `active: false`, not errors.
+    async fn introspect(
+        &self,
+        request: Request<IntrospectRequest>,
+    ) -> Result<Response<IntrospectResponse>, Status> {
+        // ---
+        let token = request.into_inner().token;
+
+        let query = timed(
+            "grpc.find_token",
+            sqlx::query!(
+                r#"
+            SELECT client_id, user_id, scope, expires_at, created_at
+            FROM access_tokens
+            WHERE token = $1
+            "#,
+                token
+            )
+            .fetch_optional(self.state.pool.as_ref()),
+        );
+        let row = self.state.postgres.call(query).await.map_err(|e| {
+            tracing::error!("Database error introspecting token: {:?}", e);
+            Status::unavailable("token store unavailable")
+        })?;
+
+        let Some(row) = row else {
+            return Ok(Response::new(IntrospectResponse::inactive()));
+        };
+        if row.expires_at < self.state.clock.now().naive_utc() {
+            return Ok(Response::new(IntrospectResponse::inactive()));
+        }
+
+        Ok(Response::new(IntrospectResponse {
+            active: true,
+            sub: Some(row.user_id),
+            client_id: Some(row.client_id),
+            scope: row.scope,
+            exp: Some(row.expires_at.and_utc().timestamp()),
+            iat: Some(row.created_at.and_utc().timestamp()),
+            iss: Some("oauth2-server".to_string()),
+            token_type: Some("Bearer".to_string()),
+            ..Default::default()
+        }))
+    }
+}
+
+// ---
+
+/// Serve the introspection RPC on `addr` until `shutdown`, draining
+/// in-flight calls like the HTTP listener.
+///
+/// # Errors
+///
+/// Returns an error if `addr` cannot be bound or the server fails.
+pub async fn serve_grpc(addr: SocketAddr, state: AppState, shutdown: &Shutdown) -> Result<()> {
+    // ---
+    tracing::info!("Serving gRPC token introspection on {addr}");
+
+    let signal = shutdown.clone();
+    let server = tonic::transport::Server::builder()
+        .add_service(TokenIntrospectionServer::new(IntrospectionService::new(
+            state,
+        )))
+        .serve_with_shutdown(addr, async move { signal.signalled().await });
+    shutdown
+        .drain(server)
+        .await
+        .with_context(|| format!("gRPC server on {addr} failed"))
+}
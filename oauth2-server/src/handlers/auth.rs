@@ -0,0 +1,101 @@
+// oauth2-server/src/handlers/auth.rs
+
+use axum::{
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use sqlx::PgPool;
+use tokn_core::{bearer_token, Problem, SharedClock};
+use tokn_resilience::CircuitBreaker;
+
+// ---
+
+use crate::query::timed;
+
+// ---
+
+/// `status` as a problem details response explaining `detail`.
+pub fn error_response(status: StatusCode, detail: impl Into<String>) -> Response {
+    // ---
+    Problem::new(status).detail(detail).into_response()
+}
+
+// ---
+
+/// The user and scope an access token was issued for.
+#[derive(Debug, Clone)]
+pub struct Authenticated {
+    // ---
+    pub user_id: String,
+    /// Space-separated scopes granted with the token
+    pub scope: Option<String>,
+}
+
+impl Authenticated {
+    // ---
+    /// True when the token was granted `scope`.
+    pub fn has_scope(&self, scope: &str) -> bool {
+        // ---
+        self.scope
+            .as_deref()
+            .is_some_and(|granted| granted.split_whitespace().any(|s| s == scope))
+    }
+}
+
+// ---
+
+/// Resolve the Bearer access token in `headers` to the user it was issued for.
+///
+/// # Errors
+///
+/// Returns the response to send instead:
+/// - 401 UNAUTHORIZED: Missing/invalid Authorization header, unknown or expired token
+/// - 500 INTERNAL_SERVER_ERROR: Database errors
+pub async fn authenticate(
+    pool: &PgPool,
+    postgres: &CircuitBreaker,
+    clock: &SharedClock,
+    headers: &HeaderMap,
+) -> Result<Authenticated, Response> {
+    // ---
+    let token = bearer_token(headers)
+        .map_err(|e| error_response(StatusCode::UNAUTHORIZED, e.to_string()))?;
+
+    let query = timed(
+        "auth.find_token",
+        sqlx::query!(
+            r#"
+        SELECT user_id, scope, expires_at
+        FROM access_tokens
+        WHERE token = $1
+        "#,
+            token
+        )
+        .fetch_optional(pool),
+    );
+
+    let access_token = match postgres.call(query).await {
+        Ok(Some(t)) => t,
+        Ok(None) => return Err(error_response(StatusCode::UNAUTHORIZED, "Invalid token")),
+        Err(e) => {
+            tracing::error!("Database error fetching token: {:?}", e);
+            return Err(error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Internal server error",
+            ));
+        }
+    };
+
+    // Check token hasn't expired
+    if access_token.expires_at < clock.now().naive_utc() {
+        return Err(error_response(
+            StatusCode::UNAUTHORIZED,
+            "Token has expired",
+        ));
+    }
+
+    Ok(Authenticated {
+        user_id: access_token.user_id,
+        scope: access_token.scope,
+    })
+}
@@ -1,12 +1,17 @@
 // oauth2-server/src/handlers/authorize.rs
 
 use axum::{
-    extract::{Query, State},
-    response::{Html, IntoResponse},
+    extract::{rejection::QueryRejection, Query, State},
+    http::{StatusCode, Uri},
+    response::{IntoResponse, Response},
 };
 use serde::Deserialize;
 use sqlx::PgPool;
+use std::collections::HashMap;
 use std::sync::Arc;
+use tokn_core::check_code_challenge;
+use tokn_i18n::Messages;
+use tokn_theme::{Page, Themes};
 
 // ---
 
@@ -21,6 +26,9 @@ pub struct AuthorizeQuery {
     pub redirect_uri: String,
     pub scope: Option<String>,
     pub state: Option<String>,
+    /// PKCE challenge (RFC 7636), the S256 hash of the client's code verifier
+    pub code_challenge: Option<String>,
+    pub code_challenge_method: Option<String>,
 }
 
 // ---
@@ -44,48 +52,110 @@ pub struct AuthorizeQuery {
 ///
 /// # Current Implementation
 ///
-/// Shows a simple consent page with approve/deny buttons. The form submits to
-/// the authorize_post_handler which generates the authorization code.
+/// Shows a simple consent page with approve/deny buttons, in the language
+/// negotiated from `Accept-Language` (see [`tokn_i18n::Localizer`]) and the
+/// client's theme (see [`tokn_theme::Themes`]). The form submits to the
+/// authorize_post_handler which generates the authorization code.
+///
+/// # PKCE
+///
+/// A `code_challenge` (RFC 7636 §4.3) is carried through the consent form and
+/// stored with the code, whose exchange then requires the matching
+/// `code_verifier`. Only `code_challenge_method=S256` is accepted.
+///
+/// # Errors
+///
+/// A request missing `response_type`, `client_id`, or `redirect_uri`, or with
+/// a `code_challenge` that is not S256, gets a themed 400 error page.
 pub async fn authorize_handler(
     State(_pool): State<Arc<PgPool>>,
-    Query(params): Query<AuthorizeQuery>,
-) -> impl IntoResponse {
+    State(themes): State<Arc<Themes>>,
+    messages: Messages,
+    uri: Uri,
+    query: Result<Query<AuthorizeQuery>, QueryRejection>,
+) -> Response {
     // ---
+    let params = match query {
+        Ok(Query(params)) => params,
+        Err(rejection) => {
+            tracing::info!("Invalid authorization request: {rejection}");
+            let client_id = Query::<HashMap<String, String>>::try_from_uri(&uri)
+                .ok()
+                .and_then(|Query(query)| query.get("client_id").cloned());
+            return invalid_request_page(&themes, &messages, client_id.as_deref());
+        }
+    };
+    if let Some(challenge) = &params.code_challenge {
+        let method = params.code_challenge_method.as_deref();
+        if let Err(e) = check_code_challenge(challenge, method) {
+            tracing::info!("Invalid authorization request: {e}");
+            return invalid_request_page(&themes, &messages, Some(&params.client_id));
+        }
+    }
+
     // TODO: Validate client_id exists in database
     // TODO: Validate redirect_uri matches client registration
     // TODO: Show consent page with approve/deny buttons
 
     // ---
     // For now, return simple consent page
-    let html = format!(
+    let scope = params.scope.as_deref().unwrap_or("profile");
+    let title = messages.text("consent-title");
+    let content = format!(
         r#"
-<!DOCTYPE html>
-<html>
-<head>
-    <title>Authorization Request</title>
-</head>
-<body>
-    <h1>Authorization Request</h1>
-    <p>Application <strong>{}</strong> wants to access your account.</p>
-    <p>Scopes: {}</p>
-    <form method="POST" action="/oauth/authorize">
+    <h1>{title}</h1>
+    <p>{intro}</p>
+    <p>{scopes}</p>
+    <form method="POST" action="/v1/oauth/authorize">
         <input type="hidden" name="client_id" value="{}">
         <input type="hidden" name="redirect_uri" value="{}">
         <input type="hidden" name="scope" value="{}">
         <input type="hidden" name="state" value="{}">
-        <button type="submit" name="action" value="approve">Approve</button>
-        <button type="submit" name="action" value="deny">Deny</button>
-    </form>
-</body>
-</html>
-"#,
-        params.client_id,
-        params.scope.as_deref().unwrap_or("profile"),
+        <input type="hidden" name="code_challenge" value="{}">
+        <input type="hidden" name="code_challenge_method" value="{}">
+        <button type="submit" name="action" value="approve">{approve}</button>
+        <button type="submit" name="action" value="deny">{deny}</button>
+    </form>"#,
         params.client_id,
         params.redirect_uri,
-        params.scope.as_deref().unwrap_or("profile"),
+        scope,
         params.state.as_deref().unwrap_or(""),
+        params.code_challenge.as_deref().unwrap_or(""),
+        params.code_challenge_method.as_deref().unwrap_or(""),
+        intro = messages.format("consent-intro", &[("client", &params.client_id)]),
+        scopes = messages.format("consent-scopes", &[("scopes", scope)]),
+        approve = messages.text("consent-approve"),
+        deny = messages.text("consent-deny"),
+    );
+
+    let html = themes.for_client(Some(&params.client_id)).render(&Page {
+        lang: &messages.lang(),
+        title: &title,
+        content: &content,
+    });
+    messages.html(html)
+}
+
+// ---
+
+/// Themed 400 page for an authorization request that cannot be parsed.
+///
+/// There is no valid `redirect_uri` to send the error back to, so it is shown
+/// to the user (RFC 6749 §4.1.2.1), in the client's theme when its id is known.
+fn invalid_request_page(themes: &Themes, messages: &Messages, client_id: Option<&str>) -> Response {
+    // ---
+    let title = messages.text("error-title");
+    let content = format!(
+        r#"
+    <h1 class="tokn-error">{title}</h1>
+    <p>{message}</p>"#,
+        message = messages.text("error-invalid-authorize-request"),
     );
 
-    Html(html)
+    let html = themes.for_client(client_id).render(&Page {
+        lang: &messages.lang(),
+        title: &title,
+        content: &content,
+    });
+    (StatusCode::BAD_REQUEST, messages.html(html)).into_response()
 }
@@ -5,14 +5,21 @@ use axum::{
     response::{IntoResponse, Redirect},
     Form,
 };
-use chrono::{Duration, Utc};
+use chrono::Duration;
 use serde::Deserialize;
 use sqlx::PgPool;
 use std::sync::Arc;
+use tokn_core::{check_code_challenge, SharedClock};
+use tokn_events::{AuthEvent, AuthEventKind, Events};
+use tokn_resilience::CircuitBreaker;
 use uuid::Uuid;
 
 // ---
 
+use crate::query::timed;
+
+// ---
+
 /// Form data submitted from the authorization consent page.
 ///
 /// Contains the client's authorization request parameters and the user's decision (approve/deny).
@@ -24,6 +31,12 @@ pub struct AuthorizeForm {
     pub scope: String,
     pub state: String,
     pub action: String, // "approve" or "deny"
+    /// PKCE challenge carried over from the authorization request; empty when
+    /// the client sent none
+    #[serde(default)]
+    pub code_challenge: String,
+    #[serde(default)]
+    pub code_challenge_method: String,
 }
 
 // ---
@@ -38,6 +51,8 @@ pub struct AuthorizeForm {
 /// - Generates cryptographically random authorization code (UUID v4)
 /// - Sets 5-minute expiration on authorization codes
 /// - Stores code with associated client_id and redirect_uri for validation during token exchange
+/// - Stores the PKCE `code_challenge` (S256 only), if any, so the token exchange requires the
+///   matching `code_verifier` (RFC 7636 §4.4)
 /// - TODO: Validate client_id exists in database
 /// - TODO: Validate redirect_uri matches client registration
 /// - TODO: Get actual user_id from authenticated session instead of hardcoded value
@@ -54,14 +69,23 @@ pub struct AuthorizeForm {
 ///
 /// # Errors
 ///
-/// Returns redirect with error=server_error if database operations fail.
+/// Returns redirect with error=invalid_request if the PKCE challenge is not S256, and with
+/// error=server_error if database operations fail.
 pub async fn authorize_post_handler(
     State(pool): State<Arc<PgPool>>,
+    State(postgres): State<CircuitBreaker>,
+    State(events): State<Events>,
+    State(clock): State<SharedClock>,
     Form(form): Form<AuthorizeForm>,
 ) -> impl IntoResponse {
     // ---
     // If user denied, redirect with error
     if form.action == "deny" {
+        tracing::info!(
+            event = "consent_denied",
+            client_id = %form.client_id,
+            "User denied authorization"
+        );
         let error_url = format!(
             "{}?error=access_denied&state={}",
             form.redirect_uri, form.state
@@ -69,40 +93,78 @@ pub async fn authorize_post_handler(
         return Redirect::to(&error_url);
     }
 
+    // ---
+    // Check the PKCE challenge, if any
+    let code_challenge = (!form.code_challenge.is_empty()).then_some(form.code_challenge.as_str());
+    let code_challenge_method = code_challenge.map(|_| form.code_challenge_method.as_str());
+    if let Some(challenge) = code_challenge {
+        if let Err(e) = check_code_challenge(challenge, code_challenge_method) {
+            tracing::info!(client_id = %form.client_id, "Invalid PKCE challenge: {e}");
+            let error_url = format!(
+                "{}?error=invalid_request&state={}",
+                form.redirect_uri, form.state
+            );
+            return Redirect::to(&error_url);
+        }
+    }
+
     // ---
     // Generate authorization code
     let code = Uuid::new_v4().to_string();
-    let expires_at = Utc::now() + Duration::minutes(5);
+    let expires_at = clock.now() + Duration::minutes(5);
 
     // ---
     // Store authorization code in database
     // TODO: Get actual user_id from session (hardcoded for now)
     let user_id = "user_001";
 
-    let result = sqlx::query!(
-        r#"
-        INSERT INTO authorization_codes (code, client_id, user_id, redirect_uri, scope, expires_at)
-        VALUES ($1, $2, $3, $4, $5, $6)
+    let query = timed(
+        "authorize.insert_code",
+        sqlx::query!(
+            r#"
+        INSERT INTO authorization_codes
+            (code, client_id, user_id, redirect_uri, scope, expires_at,
+             code_challenge, code_challenge_method)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
         "#,
-        code,
-        form.client_id,
-        user_id,
-        form.redirect_uri,
-        form.scope,
-        expires_at.naive_utc()
-    )
-    .execute(pool.as_ref())
-    .await;
+            code,
+            form.client_id,
+            user_id,
+            form.redirect_uri,
+            form.scope,
+            expires_at.naive_utc(),
+            code_challenge,
+            code_challenge_method
+        )
+        .execute(pool.as_ref()),
+    );
+    let result = postgres.call(query).await;
 
     // ---
     match result {
         Ok(_) => {
+            tracing::info!(
+                event = "login",
+                client_id = %form.client_id,
+                user_id,
+                "User approved authorization"
+            );
+            events.emit(
+                AuthEvent::new(AuthEventKind::Login)
+                    .subject(user_id)
+                    .client_id(&form.client_id),
+            );
+
             // Redirect back to client with authorization code
             let callback_url = format!("{}?code={}&state={}", form.redirect_uri, code, form.state);
             Redirect::to(&callback_url)
         }
         Err(e) => {
-            tracing::error!("Failed to store authorization code: {:?}", e);
+            tracing::error!(
+                client_id = %form.client_id,
+                "Failed to store authorization code: {:?}",
+                e
+            );
             let error_url = format!(
                 "{}?error=server_error&state={}",
                 form.redirect_uri, form.state
@@ -4,13 +4,16 @@
 
 // ---
 
+mod auth;
 mod authorize;
 mod authorize_post;
+mod phone;
 mod token;
 mod userinfo;
 
 // ---
 pub use authorize::authorize_handler;
 pub use authorize_post::authorize_post_handler;
-pub use token::token_handler;
+pub use phone::{phone_handler, phone_verify_handler, PhoneRequest, PhoneVerifyRequest};
+pub use token::{token_handler, TokenRequest};
 pub use userinfo::userinfo_handler;
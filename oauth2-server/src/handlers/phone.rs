@@ -0,0 +1,236 @@
+// oauth2-server/src/handlers/phone.rs
+
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::sync::Arc;
+use tokn_core::SharedClock;
+use tokn_events::{AuthEvent, AuthEventKind, Events};
+use tokn_resilience::CircuitBreaker;
+use tokn_sms::{Otp, OtpError, PhoneNumber, SmsError};
+
+// ---
+
+use super::auth::{authenticate, error_response, Authenticated};
+use crate::query::timed;
+
+// ---
+
+/// Request body for `POST /oauth/phone`.
+#[derive(Debug, Deserialize)]
+pub struct PhoneRequest {
+    // ---
+    /// Number to verify, E.164 (`+14155550123`); spaces and dashes are ignored
+    pub phone_number: String,
+}
+
+/// Response body for `POST /oauth/phone`.
+#[derive(Debug, Serialize)]
+pub struct PhoneResponse {
+    // ---
+    /// Seconds until the code that was sent expires
+    pub expires_in: u64,
+}
+
+/// Request body for `POST /oauth/phone/verify`.
+#[derive(Debug, Deserialize)]
+pub struct PhoneVerifyRequest {
+    // ---
+    pub phone_number: String,
+    /// The code from the text message
+    pub code: String,
+}
+
+// ---
+
+/// Sends a one-time code to a phone number the user wants on their account.
+///
+/// # Security
+///
+/// - Requires a valid Bearer token granted the `phone` scope
+/// - Resend interval and hourly cap per number are enforced by [`Otp`]
+/// - The number is not stored on the user until the code is confirmed
+///
+/// # Errors
+///
+/// Returns JSON error response with appropriate HTTP status code:
+/// - 400 BAD_REQUEST: Malformed number, or the provider refused to deliver to it
+/// - 401 UNAUTHORIZED / 403 FORBIDDEN: Missing token / token lacks the `phone` scope
+/// - 429 TOO_MANY_REQUESTS: Sent too recently or too often (with `Retry-After`)
+/// - 503 SERVICE_UNAVAILABLE: SMS provider unreachable
+pub async fn phone_handler(
+    State(pool): State<Arc<PgPool>>,
+    State(postgres): State<CircuitBreaker>,
+    State(clock): State<SharedClock>,
+    State(otp): State<Otp>,
+    headers: HeaderMap,
+    Json(request): Json<PhoneRequest>,
+) -> Response {
+    // ---
+    if let Err(response) = authorize_phone(&pool, &postgres, &clock, &headers).await {
+        return response;
+    }
+
+    let phone = match request.phone_number.parse::<PhoneNumber>() {
+        Ok(phone) => phone,
+        Err(e) => return otp_error_response(e),
+    };
+
+    match otp.send_code(&phone).await {
+        Ok(()) => (
+            StatusCode::ACCEPTED,
+            Json(PhoneResponse {
+                expires_in: otp.policy().ttl_seconds,
+            }),
+        )
+            .into_response(),
+        Err(e) => otp_error_response(e),
+    }
+}
+
+// ---
+
+/// Confirms a code sent by [`phone_handler`] and records the number on the
+/// user as verified.
+///
+/// A try refused for too many wrong codes emits a `lockout` event.
+///
+/// # Errors
+///
+/// Returns JSON error response with appropriate HTTP status code:
+/// - 400 BAD_REQUEST: Malformed number; wrong, expired, or already-used code
+/// - 401 UNAUTHORIZED / 403 FORBIDDEN: Missing token / token lacks the `phone` scope
+/// - 429 TOO_MANY_REQUESTS: Too many wrong codes; a new one must be sent
+/// - 500 INTERNAL_SERVER_ERROR: Database errors
+pub async fn phone_verify_handler(
+    State(pool): State<Arc<PgPool>>,
+    State(postgres): State<CircuitBreaker>,
+    State(clock): State<SharedClock>,
+    State(otp): State<Otp>,
+    State(events): State<Events>,
+    headers: HeaderMap,
+    Json(request): Json<PhoneVerifyRequest>,
+) -> Response {
+    // ---
+    let user = match authorize_phone(&pool, &postgres, &clock, &headers).await {
+        Ok(user) => user,
+        Err(response) => return response,
+    };
+
+    let phone = match request.phone_number.parse::<PhoneNumber>() {
+        Ok(phone) => phone,
+        Err(e) => return otp_error_response(e),
+    };
+    if let Err(e) = otp.verify(&phone, &request.code).await {
+        if matches!(e, OtpError::TooManyAttempts) {
+            tracing::warn!(
+                "Phone verification locked for user {} ({})",
+                user.user_id,
+                phone.masked()
+            );
+            events.emit(AuthEvent::new(AuthEventKind::Lockout).subject(&user.user_id));
+        }
+        return otp_error_response(e);
+    }
+
+    // ---
+    // The code proves the user holds the number. The change is recorded in
+    // the history with the number masked.
+    let query = timed(
+        "phone.verify_number",
+        sqlx::query!(
+            r#"
+        WITH updated AS (
+            UPDATE users SET phone_number = $2, phone_number_verified = TRUE
+            WHERE user_id = $1 AND deleted_at IS NULL
+            RETURNING user_id
+        )
+        INSERT INTO change_history (entity, entity_id, action, field, new_value, actor)
+        SELECT 'user', user_id, 'updated', 'phone_number', $3, 'user:' || user_id
+        FROM updated
+        "#,
+            user.user_id,
+            phone.as_str(),
+            phone.masked()
+        )
+        .execute(pool.as_ref()),
+    );
+
+    match postgres.call(query).await {
+        Ok(result) if result.rows_affected() == 0 => {
+            error_response(StatusCode::NOT_FOUND, "User not found")
+        }
+        Ok(_) => {
+            tracing::info!(
+                "Verified phone {} for user {}",
+                phone.masked(),
+                user.user_id
+            );
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Err(e) => {
+            tracing::error!("Database error saving phone number: {:?}", e);
+            error_response(StatusCode::INTERNAL_SERVER_ERROR, "Internal server error")
+        }
+    }
+}
+
+// ---
+
+/// Authenticate the request and require the `phone` scope.
+async fn authorize_phone(
+    pool: &PgPool,
+    postgres: &CircuitBreaker,
+    clock: &SharedClock,
+    headers: &HeaderMap,
+) -> Result<Authenticated, Response> {
+    // ---
+    let user = authenticate(pool, postgres, clock, headers).await?;
+    if !user.has_scope("phone") {
+        return Err(error_response(
+            StatusCode::FORBIDDEN,
+            "Token lacks the 'phone' scope",
+        ));
+    }
+    Ok(user)
+}
+
+fn otp_error_response(error: OtpError) -> Response {
+    // ---
+    let retry_after = |seconds: u64, error: &OtpError| {
+        let mut response = error_response(StatusCode::TOO_MANY_REQUESTS, error.to_string());
+        response
+            .headers_mut()
+            .insert(header::RETRY_AFTER, seconds.into());
+        response
+    };
+
+    match error {
+        OtpError::ResendTooSoon { retry_after: s } | OtpError::RateLimited { retry_after: s } => {
+            retry_after(s, &error)
+        }
+        OtpError::TooManyAttempts => {
+            error_response(StatusCode::TOO_MANY_REQUESTS, error.to_string())
+        }
+        OtpError::InvalidPhoneNumber
+        | OtpError::NoPendingCode
+        | OtpError::Expired
+        | OtpError::Mismatch => error_response(StatusCode::BAD_REQUEST, error.to_string()),
+        OtpError::Send(SmsError::Rejected { .. }) => error_response(
+            StatusCode::BAD_REQUEST,
+            "Could not send a text message to this number",
+        ),
+        OtpError::Send(SmsError::Transport { .. }) => error_response(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Text messages cannot be sent right now",
+        ),
+        e => {
+            tracing::error!("Phone verification failed: {e}");
+            error_response(StatusCode::INTERNAL_SERVER_ERROR, "Internal server error")
+        }
+    }
+}
@@ -2,28 +2,62 @@
 
 use axum::{
     extract::State,
-    http::StatusCode,
+    http::{header, StatusCode},
     response::{IntoResponse, Json},
 };
-use chrono::{Duration, Utc};
+use chrono::Duration;
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use std::sync::Arc;
+use tokn_config::Secret;
+use tokn_core::{verify_code_verifier, SharedClock};
+use tokn_events::{AuthEvent, AuthEventKind, Events};
+use tokn_resilience::CircuitBreaker;
 use uuid::Uuid;
 
 // ---
 
+use crate::query::timed;
+
+// ---
+
 /// OAuth2 token request parameters.
 ///
 /// Sent by the client to exchange an authorization code for an access token (RFC 6749 §4.1.3).
-#[derive(Debug, Deserialize)]
+///
+/// `Debug` masks `code`, `client_secret`, and `code_verifier`, so the request can be logged.
+#[derive(Deserialize)]
 pub struct TokenRequest {
     // ---
     pub grant_type: String,
     pub code: String,
     pub redirect_uri: String,
     pub client_id: String,
-    pub client_secret: String,
+    pub client_secret: Secret,
+    /// PKCE verifier (RFC 7636 §4.5), required for codes issued with a challenge
+    #[serde(default)]
+    pub code_verifier: Option<String>,
+}
+
+impl std::fmt::Debug for TokenRequest {
+    // ---
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // ---
+        f.debug_struct("TokenRequest")
+            .field("grant_type", &self.grant_type)
+            .field("code", &tokn_telemetry::REDACTED)
+            .field("redirect_uri", &self.redirect_uri)
+            .field("client_id", &self.client_id)
+            .field("client_secret", &tokn_telemetry::REDACTED)
+            .field(
+                "code_verifier",
+                &self
+                    .code_verifier
+                    .as_ref()
+                    .map(|_| tokn_telemetry::REDACTED),
+            )
+            .finish()
+    }
 }
 
 // ---
@@ -65,6 +99,8 @@ pub struct TokenError {
 /// - Verifies authorization code exists and hasn't been used
 /// - Checks authorization code hasn't expired (5-minute TTL)
 /// - Validates redirect_uri matches the one used during authorization
+/// - Verifies the PKCE code_verifier against the code's S256 challenge (RFC 7636 §4.6); a
+///   verifier sent for a code issued without a challenge is refused too
 /// - Invalidates authorization code after successful exchange (one-time use)
 /// - Generates cryptographically random access token (UUID v4)
 /// - Sets 1-hour expiration on access tokens
@@ -74,7 +110,7 @@ pub struct TokenError {
 /// 1. Parse and validate token request parameters
 /// 2. Validate client credentials against database
 /// 3. Fetch and validate authorization code
-/// 4. Check code expiration and redirect_uri match
+/// 4. Check code expiration, redirect_uri match, and PKCE code_verifier
 /// 5. Generate access token with expiration
 /// 6. Store access token in database
 /// 7. Delete used authorization code
@@ -83,23 +119,29 @@ pub struct TokenError {
 /// # Errors
 ///
 /// Returns JSON error response with appropriate HTTP status code:
-/// - 400 BAD_REQUEST: Malformed request, unsupported grant type, invalid/expired code, redirect_uri mismatch
+/// - 400 BAD_REQUEST: Malformed request, unsupported grant type, invalid/expired code, redirect_uri mismatch,
+///   missing or mismatched code_verifier
 /// - 401 UNAUTHORIZED: Invalid client credentials, client not found
 /// - 500 INTERNAL_SERVER_ERROR: Database errors, token generation failures
 ///
 /// Error responses follow RFC 6749 §5.2 format with `error` and `error_description` fields.
 pub async fn token_handler(
     State(pool): State<Arc<PgPool>>,
+    State(postgres): State<CircuitBreaker>,
+    State(events): State<Events>,
+    State(clock): State<SharedClock>,
     body: String, // Capture raw body first
 ) -> impl IntoResponse {
     // ---
-    tracing::debug!("Raw token request body: {body}");
-
     // Then manually deserialize with better error context
     let params: TokenRequest = match serde_urlencoded::from_str(&body) {
         Ok(p) => p,
         Err(e) => {
-            tracing::error!("Failed to parse token request: {:?}\nBody was: {body}", e);
+            tracing::error!(
+                "Failed to parse token request: {:?}\nBody was: {}",
+                e,
+                tokn_telemetry::redact(&body)
+            );
             return (
                 StatusCode::BAD_REQUEST,
                 Json(TokenError {
@@ -127,16 +169,19 @@ pub async fn token_handler(
 
     // ---
     // Validate client credentials
-    let client_result = sqlx::query!(
-        r#"
+    let query = timed(
+        "token.find_client",
+        sqlx::query!(
+            r#"
         SELECT client_secret, redirect_uri
         FROM clients
-        WHERE client_id = $1
+        WHERE client_id = $1 AND deleted_at IS NULL
         "#,
-        params.client_id
-    )
-    .fetch_optional(pool.as_ref())
-    .await;
+            params.client_id
+        )
+        .fetch_optional(pool.as_ref()),
+    );
+    let client_result = postgres.call(query).await;
 
     let client = match client_result {
         Ok(Some(c)) => c,
@@ -165,7 +210,12 @@ pub async fn token_handler(
 
     // ---
     // Verify client secret
-    if client.client_secret != params.client_secret {
+    if client.client_secret != params.client_secret.expose() {
+        tracing::warn!(
+            event = "invalid_client",
+            client_id = %params.client_id,
+            "Rejected token request: invalid client secret"
+        );
         return (
             StatusCode::UNAUTHORIZED,
             Json(TokenError {
@@ -177,18 +227,22 @@ pub async fn token_handler(
     }
 
     // ---
-    // Fetch authorization code
-    let code_result = sqlx::query!(
-        r#"
-        SELECT user_id, redirect_uri, scope, expires_at
-        FROM authorization_codes
-        WHERE code = $1 AND client_id = $2
+    // Fetch authorization code (codes of deleted users are not honored)
+    let query = timed(
+        "token.find_code",
+        sqlx::query!(
+            r#"
+        SELECT c.user_id, c.redirect_uri, c.scope, c.expires_at, c.code_challenge
+        FROM authorization_codes c
+        JOIN users u ON u.user_id = c.user_id AND u.deleted_at IS NULL
+        WHERE c.code = $1 AND c.client_id = $2
         "#,
-        params.code,
-        params.client_id
-    )
-    .fetch_optional(pool.as_ref())
-    .await;
+            params.code,
+            params.client_id
+        )
+        .fetch_optional(pool.as_ref()),
+    );
+    let code_result = postgres.call(query).await;
 
     let auth_code = match code_result {
         Ok(Some(c)) => c,
@@ -217,7 +271,7 @@ pub async fn token_handler(
 
     // ---
     // Check code hasn't expired
-    if auth_code.expires_at < Utc::now().naive_utc() {
+    if auth_code.expires_at < clock.now().naive_utc() {
         return (
             StatusCode::BAD_REQUEST,
             Json(TokenError {
@@ -241,26 +295,57 @@ pub async fn token_handler(
             .into_response();
     }
 
+    // ---
+    // Verify the PKCE code_verifier
+    let pkce = match (&auth_code.code_challenge, &params.code_verifier) {
+        (Some(challenge), Some(verifier)) => {
+            verify_code_verifier(verifier, challenge).map_err(|e| e.to_string())
+        }
+        (Some(_), None) => Err("code_verifier is required".to_string()),
+        (None, Some(_)) => {
+            Err("Authorization code was issued without a code_challenge".to_string())
+        }
+        (None, None) => Ok(()),
+    };
+    if let Err(error_description) = pkce {
+        tracing::warn!(
+            event = "invalid_grant",
+            client_id = %params.client_id,
+            "Rejected token request: {error_description}"
+        );
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(TokenError {
+                error: "invalid_grant".to_string(),
+                error_description,
+            }),
+        )
+            .into_response();
+    }
+
     // ---
     // Generate access token
     let access_token = Uuid::new_v4().to_string();
-    let expires_at = Utc::now() + Duration::hours(1);
+    let expires_at = clock.now() + Duration::hours(1);
 
     // ---
     // Store access token
-    let insert_result = sqlx::query!(
-        r#"
+    let query = timed(
+        "token.insert_token",
+        sqlx::query!(
+            r#"
         INSERT INTO access_tokens (token, client_id, user_id, scope, expires_at)
         VALUES ($1, $2, $3, $4, $5)
         "#,
-        access_token,
-        params.client_id,
-        auth_code.user_id,
-        auth_code.scope,
-        expires_at.naive_utc()
-    )
-    .execute(pool.as_ref())
-    .await;
+            access_token,
+            params.client_id,
+            auth_code.user_id,
+            auth_code.scope,
+            expires_at.naive_utc()
+        )
+        .execute(pool.as_ref()),
+    );
+    let insert_result = postgres.call(query).await;
 
     if let Err(e) = insert_result {
         tracing::error!("Failed to store access token: {:?}", e);
@@ -276,21 +361,44 @@ pub async fn token_handler(
 
     // ---
     // Delete used authorization code
-    let _ = sqlx::query!(
-        r#"
+    let query = timed(
+        "token.delete_code",
+        sqlx::query!(
+            r#"
         DELETE FROM authorization_codes WHERE code = $1
         "#,
-        params.code
-    )
-    .execute(pool.as_ref())
-    .await;
+            params.code
+        )
+        .execute(pool.as_ref()),
+    );
+    let _ = postgres.call(query).await;
+
+    tracing::info!(
+        event = "token_issued",
+        client_id = %params.client_id,
+        user_id = %auth_code.user_id,
+        grant_type = "authorization_code",
+        "Issued access token"
+    );
+    events.emit(
+        AuthEvent::new(AuthEventKind::TokenIssued)
+            .subject(&auth_code.user_id)
+            .client_id(&params.client_id)
+            .grant_type("authorization_code"),
+    );
 
     // ---
-    // Return success
-    Json(TokenResponse {
-        access_token,
-        token_type: "Bearer".to_string(),
-        expires_in: 3600, // 1 hour
-    })
-    .into_response()
+    // Return success (RFC 6749 §5.1: token responses must not be cached)
+    (
+        [
+            (header::CACHE_CONTROL, "no-store"),
+            (header::PRAGMA, "no-cache"),
+        ],
+        Json(TokenResponse {
+            access_token,
+            token_type: "Bearer".to_string(),
+            expires_in: 3600, // 1 hour
+        }),
+    )
+        .into_response()
 }
@@ -5,31 +5,15 @@ use axum::{
     http::{HeaderMap, StatusCode},
     response::{IntoResponse, Json},
 };
-use chrono::Utc;
-use serde::Serialize;
 use sqlx::PgPool;
 use std::sync::Arc;
+use tokn_core::{SharedClock, UserInfo};
+use tokn_resilience::CircuitBreaker;
 
 // ---
 
-/// User information response.
-///
-/// Returns the authenticated user's profile information (OIDC UserInfo endpoint).
-#[derive(Debug, Serialize)]
-pub struct UserInfo {
-    // ---
-    sub: String, // User ID (subject)
-    username: String,
-}
-
-// ---
-
-/// Error response for userinfo endpoint.
-#[derive(Debug, Serialize)]
-pub struct ErrorResponse {
-    // ---
-    error: String,
-}
+use super::auth::{authenticate, error_response};
+use crate::query::timed;
 
 // ---
 
@@ -45,6 +29,8 @@ pub struct ErrorResponse {
 /// - Checks token hasn't expired (1-hour TTL)
 /// - Returns 401 UNAUTHORIZED for missing/invalid/expired tokens
 /// - Only returns user data associated with the token's user_id
+/// - Returns `phone_number` / `phone_number_verified` only when the token
+///   was granted the `phone` scope and the user has a verified number
 ///
 /// # OAuth2 Flow
 ///
@@ -61,126 +47,55 @@ pub struct ErrorResponse {
 /// - 500 INTERNAL_SERVER_ERROR: Database errors
 pub async fn userinfo_handler(
     State(pool): State<Arc<PgPool>>,
+    State(postgres): State<CircuitBreaker>,
+    State(clock): State<SharedClock>,
     headers: HeaderMap,
 ) -> impl IntoResponse {
-    // ---
-    // Extract Bearer token from Authorization header
-    let auth_header = match headers.get("Authorization") {
-        Some(h) => h.to_str().unwrap_or(""),
-        None => {
-            return (
-                StatusCode::UNAUTHORIZED,
-                Json(ErrorResponse {
-                    error: "Missing Authorization header".to_string(),
-                }),
-            )
-                .into_response();
-        }
-    };
-
-    // ---
-    let token = match auth_header.strip_prefix("Bearer ") {
-        Some(t) => t,
-        None => {
-            return (
-                StatusCode::UNAUTHORIZED,
-                Json(ErrorResponse {
-                    error: "Invalid Authorization header format".to_string(),
-                }),
-            )
-                .into_response();
-        }
-    };
-
     // ---
     // Validate token and fetch user_id
-    let token_result = sqlx::query!(
-        r#"
-        SELECT user_id, expires_at
-        FROM access_tokens
-        WHERE token = $1
-        "#,
-        token
-    )
-    .fetch_optional(pool.as_ref())
-    .await;
-
-    let access_token = match token_result {
-        Ok(Some(t)) => t,
-        Ok(None) => {
-            return (
-                StatusCode::UNAUTHORIZED,
-                Json(ErrorResponse {
-                    error: "Invalid token".to_string(),
-                }),
-            )
-                .into_response();
-        }
-        Err(e) => {
-            tracing::error!("Database error fetching token: {:?}", e);
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "Internal server error".to_string(),
-                }),
-            )
-                .into_response();
-        }
+    let access_token = match authenticate(&pool, &postgres, &clock, &headers).await {
+        Ok(t) => t,
+        Err(response) => return response,
     };
 
-    // ---
-    // Check token hasn't expired
-    if access_token.expires_at < Utc::now().naive_utc() {
-        return (
-            StatusCode::UNAUTHORIZED,
-            Json(ErrorResponse {
-                error: "Token has expired".to_string(),
-            }),
-        )
-            .into_response();
-    }
-
     // ---
     // Fetch user info
-    let user_result = sqlx::query!(
-        r#"
-        SELECT user_id, username
+    let query = timed(
+        "userinfo.find_user",
+        sqlx::query!(
+            r#"
+        SELECT user_id, username, phone_number, phone_number_verified
         FROM users
-        WHERE user_id = $1
+        WHERE user_id = $1 AND deleted_at IS NULL
         "#,
-        access_token.user_id
-    )
-    .fetch_optional(pool.as_ref())
-    .await;
+            access_token.user_id
+        )
+        .fetch_optional(pool.as_ref()),
+    );
+    let user_result = postgres.call(query).await;
 
     let user = match user_result {
         Ok(Some(u)) => u,
-        Ok(None) => {
-            return (
-                StatusCode::NOT_FOUND,
-                Json(ErrorResponse {
-                    error: "User not found".to_string(),
-                }),
-            )
-                .into_response();
-        }
+        Ok(None) => return error_response(StatusCode::NOT_FOUND, "User not found"),
         Err(e) => {
             tracing::error!("Database error fetching user: {:?}", e);
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "Internal server error".to_string(),
-                }),
-            )
-                .into_response();
+            return error_response(StatusCode::INTERNAL_SERVER_ERROR, "Internal server error");
         }
     };
 
+    // ---
+    // Phone claims are only released under the `phone` scope
+    let phone_number = user
+        .phone_number
+        .filter(|_| access_token.has_scope("phone") && user.phone_number_verified);
+
     // ---
     // Return user info
     Json(UserInfo {
         sub: user.user_id,
         username: user.username,
+        phone_number_verified: phone_number.as_ref().map(|_| true),
+        phone_number,
     })
     .into_response()
 }
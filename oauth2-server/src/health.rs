@@ -0,0 +1,30 @@
+// oauth2-server/src/health.rs
+
+//! Dependencies probed by `/health/ready`
+//!
+//! Postgres, through the same circuit breaker as every other query, so an
+//! open breaker reports the service unready without touching the database.
+
+use tokn_server::HealthChecks;
+
+// ---
+
+use crate::AppState;
+
+// ---
+
+/// The readiness checks for oauth2-server over `state`.
+pub fn health_checks(state: &AppState) -> HealthChecks {
+    // ---
+    let (pool, breaker) = (state.pool.clone(), state.postgres.clone());
+
+    HealthChecks::new("oauth2-server").check("postgres", move || {
+        let (pool, breaker) = (pool.clone(), breaker.clone());
+        async move {
+            breaker
+                .call(sqlx::query("SELECT 1").execute(&*pool))
+                .await
+                .map(|_| ())
+        }
+    })
+}
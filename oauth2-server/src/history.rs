@@ -0,0 +1,195 @@
+// oauth2-server/src/history.rs
+
+//! Change history for clients and users
+//!
+//! Every write to a client or user record through [`crate::admin`] (and the
+//! phone verification handler) adds rows to `change_history` in the same
+//! transaction, naming the actor, the action, and each field changed.
+//! Secrets are recorded as changed, never with their values.
+
+use anyhow::{Context, Result};
+use chrono::NaiveDateTime;
+use sqlx::{PgConnection, PgPool};
+use std::fmt;
+use std::str::FromStr;
+
+// ---
+
+use crate::query::timed;
+
+// ---
+
+/// Stands in for secret values in the history.
+pub(crate) const REDACTED: &str = "[redacted]";
+
+// ---
+
+/// Kind of record a [`Change`] applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Entity {
+    // ---
+    Client,
+    User,
+}
+
+impl Entity {
+    // ---
+    /// Value stored in `change_history.entity`.
+    pub fn as_str(self) -> &'static str {
+        // ---
+        match self {
+            Entity::Client => "client",
+            Entity::User => "user",
+        }
+    }
+}
+
+impl fmt::Display for Entity {
+    // ---
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // ---
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Entity {
+    // ---
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        // ---
+        match value {
+            "client" => Ok(Entity::Client),
+            "user" => Ok(Entity::User),
+            _ => Err(format!(
+                "unknown entity '{value}' (expected client or user)"
+            )),
+        }
+    }
+}
+
+// ---
+
+/// What happened to the record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Action {
+    // ---
+    Created,
+    Updated,
+    Deleted,
+    Restored,
+}
+
+impl Action {
+    // ---
+    fn as_str(self) -> &'static str {
+        // ---
+        match self {
+            Action::Created => "created",
+            Action::Updated => "updated",
+            Action::Deleted => "deleted",
+            Action::Restored => "restored",
+        }
+    }
+}
+
+// ---
+
+/// One recorded change to a client or user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    // ---
+    pub id: i64,
+    /// `client` or `user`
+    pub entity: String,
+    pub entity_id: String,
+    /// `created`, `updated`, `deleted`, or `restored`
+    pub action: String,
+    /// Field changed, for `created` and `updated`
+    pub field: Option<String>,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    /// Who made the change (`tokn-admin (<OS user>)`, `admin-ui`,
+    /// `user:<user_id>`)
+    pub actor: String,
+    pub changed_at: NaiveDateTime,
+}
+
+// ---
+
+/// Up to `limit` changes, newest first; with `entity`, only changes to that
+/// kind of record, and with `entity_id` as well, only to that record.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn list_changes(
+    pool: &PgPool,
+    entity: Option<Entity>,
+    entity_id: Option<&str>,
+    limit: i64,
+) -> Result<Vec<Change>> {
+    // ---
+    let changes = timed(
+        "history.list_changes",
+        sqlx::query_as!(
+            Change,
+            r#"
+        SELECT id, entity, entity_id, action, field, old_value, new_value, actor, changed_at
+        FROM change_history
+        WHERE ($1::TEXT IS NULL OR entity = $1) AND ($2::TEXT IS NULL OR entity_id = $2)
+        ORDER BY changed_at DESC, id DESC
+        LIMIT $3
+        "#,
+            entity.map(Entity::as_str),
+            entity_id,
+            limit
+        )
+        .fetch_all(pool),
+    )
+    .await
+    .context("Failed to read change history")?;
+
+    Ok(changes)
+}
+
+// ---
+
+/// Record `action` on a record, optionally naming the field changed and its
+/// old and new values. Call inside the transaction making the change.
+pub(crate) async fn record(
+    conn: &mut PgConnection,
+    actor: &str,
+    entity: Entity,
+    entity_id: &str,
+    action: Action,
+    field: Option<(&str, Option<&str>, Option<&str>)>,
+) -> Result<()> {
+    // ---
+    let (field, old_value, new_value) = match field {
+        Some((field, old_value, new_value)) => (Some(field), old_value, new_value),
+        None => (None, None, None),
+    };
+
+    timed(
+        "history.record",
+        sqlx::query!(
+            r#"
+        INSERT INTO change_history (entity, entity_id, action, field, old_value, new_value, actor)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        "#,
+            entity.as_str(),
+            entity_id,
+            action.as_str(),
+            field,
+            old_value,
+            new_value,
+            actor
+        )
+        .execute(conn),
+    )
+    .await
+    .with_context(|| format!("Failed to record change to {entity} '{entity_id}'"))?;
+
+    Ok(())
+}
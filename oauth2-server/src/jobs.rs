@@ -0,0 +1,133 @@
+// oauth2-server/src/jobs.rs
+
+//! Recurring housekeeping jobs
+//!
+//! Registered on a [`Scheduler`] by [`scheduler`]; each job's schedule can be
+//! overridden in the `scheduler` config section. Cleanup runs on one replica
+//! at a time, elected through a Postgres advisory lock
+//! ([`PgLeaderElection`]). Jobs query the pool
+//! directly rather than through the `postgres` circuit breaker, so a slow
+//! cleanup cannot open the breaker on request traffic.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgPool;
+use tokn_scheduler::{JobResult, Schedule, Scheduler, SchedulerConfig};
+
+// ---
+
+use crate::query::timed;
+use crate::{AppState, PgLeaderElection};
+
+// ---
+
+/// Name of the job deleting expired tokens, codes, and one-time code rows.
+pub const EXPIRED_TOKEN_CLEANUP: &str = "expired_token_cleanup";
+
+/// Default schedule for [`EXPIRED_TOKEN_CLEANUP`].
+const EXPIRED_TOKEN_CLEANUP_SCHEDULE: &str = "*/15 * * * *";
+
+/// One-time code rows are kept this long after their send window opened, so
+/// deleting one never resets a number's hourly send limit.
+const OTP_SEND_WINDOW_SECONDS: i64 = 3600;
+
+// ---
+
+/// Rows deleted by [`purge_expired`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Purged {
+    // ---
+    pub access_tokens: u64,
+    pub authorization_codes: u64,
+    pub phone_otps: u64,
+}
+
+// ---
+
+/// The oauth2-server's scheduler, with its housekeeping jobs registered over
+/// `state`'s pool and clock and leadership elected on the same database.
+/// Call [`Scheduler::start`] to run them.
+pub fn scheduler(config: &SchedulerConfig, state: &AppState) -> Scheduler {
+    // ---
+    let (pool, clock) = (state.pool.clone(), state.clock.clone());
+    let cleanup = move || {
+        let (pool, now) = (pool.clone(), clock.now());
+        async move {
+            let purged = purge_expired(&pool, now).await?;
+            tracing::info!(
+                "Deleted {} expired access tokens, {} authorization codes, {} one-time codes",
+                purged.access_tokens,
+                purged.authorization_codes,
+                purged.phone_otps
+            );
+            JobResult::Ok(())
+        }
+    };
+
+    Scheduler::new(config, state.clock.clone())
+        .with_election(PgLeaderElection::new(state.pool.clone()))
+        .singleton_job(
+            EXPIRED_TOKEN_CLEANUP,
+            EXPIRED_TOKEN_CLEANUP_SCHEDULE
+                .parse::<Schedule>()
+                .expect("default schedule is valid"),
+            cleanup,
+        )
+}
+
+// ---
+
+/// Delete access tokens and authorization codes that expired before `now`,
+/// and one-time code rows whose code and send window have both lapsed.
+///
+/// # Errors
+///
+/// Returns an error if a delete fails; earlier deletes stay committed.
+pub async fn purge_expired(pool: &PgPool, now: DateTime<Utc>) -> Result<Purged> {
+    // ---
+    let now_naive = now.naive_utc();
+    let window_start = (now - Duration::seconds(OTP_SEND_WINDOW_SECONDS)).naive_utc();
+
+    let access_tokens = timed(
+        "jobs.purge_tokens",
+        sqlx::query!(
+            "DELETE FROM access_tokens WHERE expires_at <= $1",
+            now_naive
+        )
+        .execute(pool),
+    )
+    .await
+    .context("Failed to delete expired access tokens")?
+    .rows_affected();
+
+    let authorization_codes = timed(
+        "jobs.purge_codes",
+        sqlx::query!(
+            "DELETE FROM authorization_codes WHERE expires_at <= $1",
+            now_naive
+        )
+        .execute(pool),
+    )
+    .await
+    .context("Failed to delete expired authorization codes")?
+    .rows_affected();
+
+    let phone_otps = timed(
+        "jobs.purge_otps",
+        sqlx::query!(
+            "DELETE FROM phone_otps WHERE expires_at <= $1 AND window_started_at <= $2",
+            now_naive,
+            window_start
+        )
+        .execute(pool),
+    )
+    .await
+    .context("Failed to delete expired one-time codes")?
+    .rows_affected();
+
+    Ok(Purged {
+        access_tokens,
+        authorization_codes,
+        phone_otps,
+    })
+}
@@ -0,0 +1,98 @@
+// oauth2-server/src/leader.rs
+
+use sqlx::{Connection, PgConnection, PgPool};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokn_scheduler::Election;
+
+// ---
+
+use crate::query::timed;
+
+// ---
+
+/// Advisory lock key held by the replica that runs singleton jobs
+/// (`"toknjobs"` as ASCII).
+const SCHEDULER_LOCK_KEY: i64 = 0x746f_6b6e_6a6f_6273;
+
+// ---
+
+/// [`Election`] over a Postgres session-level advisory lock, so exactly one
+/// oauth2-server replica runs the singleton housekeeping jobs.
+///
+/// The leader keeps the lock on a connection of its own, outside the pool.
+/// When the leader exits or its connection drops, Postgres releases the lock
+/// and the next replica to check takes over.
+#[derive(Clone)]
+pub struct PgLeaderElection {
+    // ---
+    pool: Arc<PgPool>,
+    key: i64,
+    /// The connection holding the lock, while this instance leads
+    held: Arc<Mutex<Option<PgConnection>>>,
+}
+
+impl PgLeaderElection {
+    // ---
+    /// Campaign for the scheduler lock on `pool`'s database.
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        // ---
+        Self::with_key(pool, SCHEDULER_LOCK_KEY)
+    }
+
+    /// Campaign for the advisory lock `key` instead, e.g. to keep separate
+    /// deployments sharing a database apart.
+    pub fn with_key(pool: Arc<PgPool>, key: i64) -> Self {
+        // ---
+        Self {
+            pool,
+            key,
+            held: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    // ---
+    /// Try the lock on a pooled connection, keeping the connection if it is
+    /// granted.
+    async fn campaign(&self) -> Result<Option<PgConnection>, sqlx::Error> {
+        // ---
+        let mut conn = self.pool.acquire().await?;
+        let locked = timed(
+            "leader.try_lock",
+            sqlx::query_scalar!(r#"SELECT pg_try_advisory_lock($1) AS "locked!""#, self.key)
+                .fetch_one(&mut *conn),
+        )
+        .await?;
+
+        // A session lock must not go back to the pool with its connection
+        Ok(locked.then(|| conn.detach()))
+    }
+}
+
+impl Election for PgLeaderElection {
+    // ---
+    async fn is_leader(&self) -> bool {
+        // ---
+        let mut held = self.held.lock().await;
+        if let Some(conn) = held.as_mut() {
+            if conn.ping().await.is_ok() {
+                return true;
+            }
+            tracing::warn!("Lost the scheduler lock connection; no longer leading");
+            *held = None;
+        }
+
+        match self.campaign().await {
+            Ok(Some(conn)) => {
+                tracing::info!("Elected to run singleton jobs on this instance");
+                *held = Some(conn);
+                true
+            }
+            Ok(None) => false,
+            Err(e) => {
+                tracing::warn!("Scheduler leader election failed: {e}");
+                false
+            }
+        }
+    }
+}
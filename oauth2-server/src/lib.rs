@@ -7,18 +7,270 @@
 
 // ---
 
+mod admin;
+mod admin_ui;
+mod check;
 mod config;
 mod database;
+mod debug;
+mod grpc;
 mod handlers;
+mod health;
+mod history;
+mod jobs;
+mod leader;
+mod otp_store;
+mod query;
+mod reload;
+mod router;
+
+use axum::extract::FromRef;
+use sqlx::PgPool;
+use std::sync::Arc;
+use tokn_core::{SharedClock, SystemClock};
+use tokn_events::Events;
+use tokn_i18n::Localizer;
+use tokn_resilience::{CircuitBreaker, CircuitBreakerConfig};
+use tokn_server::{ApiConfig, CompressionConfig, CorsConfig, SecurityHeadersConfig, ServiceAuth};
+use tokn_sms::{ConsoleSender, Otp, OtpPolicy, SmsBackend};
+use tokn_theme::Themes;
+
+// ---
+
+/// Application state shared across all handlers.
+///
+/// Handlers extract the parts they need (`State<Arc<PgPool>>`,
+/// `State<CircuitBreaker>`, `State<Events>`, `State<SharedClock>`,
+/// `State<Otp>`) via [`FromRef`];
+/// HTML pages take a [`tokn_i18n::Messages`] built from `i18n` and render
+/// through `State<Arc<Themes>>`.
+#[derive(Clone)]
+pub struct AppState {
+    // ---
+    pub pool: Arc<PgPool>,
+    /// Breaker around every database query
+    pub postgres: CircuitBreaker,
+    /// Auth event publisher (disabled unless `EVENTS_BACKEND` is set)
+    pub events: Events,
+    /// Time source for code and token expiry
+    pub clock: SharedClock,
+    /// Public API routing (`/v1` and the deprecated unversioned paths)
+    pub api: ApiConfig,
+    /// Origins allowed to call from the browser (none by default)
+    pub cors: CorsConfig,
+    /// Translations for the consent page
+    pub i18n: Arc<Localizer>,
+    /// Page themes (deployment-wide and per client)
+    pub theme: Arc<Themes>,
+    /// SMS one-time codes for phone verification
+    pub otp: Otp,
+    /// Headers guarding every response (CSP, `X-Frame-Options`, ...)
+    pub security_headers: SecurityHeadersConfig,
+    /// Response compression
+    pub compression: CompressionConfig,
+    /// Verifies requests signed by the other tokn services (off by default)
+    pub service_auth: ServiceAuth,
+}
+
+impl AppState {
+    // ---
+    /// State over `pool`, with a circuit breaker named `postgres` configured
+    /// by `circuit_breaker`, event publishing disabled, the system clock, the
+    /// default API routing, CORS off, the built-in translations and theme,
+    /// one-time codes logged to the console, the default security headers and
+    /// compression, and no service signatures accepted.
+    pub fn new(pool: Arc<PgPool>, circuit_breaker: CircuitBreakerConfig) -> Self {
+        // ---
+        let postgres = CircuitBreaker::new("postgres", circuit_breaker);
+        let clock = SystemClock::shared();
+        let otp = Otp::new(
+            ConsoleSender,
+            SmsBackend::Console,
+            PgOtpStore::new(pool.clone(), postgres.clone()),
+            OtpPolicy::default(),
+            clock.clone(),
+        );
+
+        Self {
+            pool,
+            postgres,
+            events: Events::disabled(),
+            clock,
+            api: ApiConfig::default(),
+            cors: CorsConfig::default(),
+            i18n: Arc::new(Localizer::builtin()),
+            theme: Arc::new(Themes::builtin()),
+            otp,
+            security_headers: SecurityHeadersConfig::default(),
+            compression: CompressionConfig::default(),
+            service_auth: ServiceAuth::disabled(),
+        }
+    }
+
+    // ---
+    /// Publish auth events through `events`.
+    pub fn with_events(mut self, events: Events) -> Self {
+        // ---
+        self.events = events;
+        self
+    }
+
+    /// Read the time from `clock` instead of the system clock.
+    ///
+    /// Does not reach an [`Otp`] already set with [`with_otp`](Self::with_otp);
+    /// build that one over the same clock.
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        // ---
+        self.clock = clock;
+        self
+    }
+
+    /// Route the public API as `api` configures.
+    pub fn with_api(mut self, api: ApiConfig) -> Self {
+        // ---
+        self.api = api;
+        self
+    }
+
+    /// Allow the browser apps `cors` lists to call the server.
+    pub fn with_cors(mut self, cors: CorsConfig) -> Self {
+        // ---
+        self.cors = cors;
+        self
+    }
+
+    /// Render HTML pages with `i18n`'s translations.
+    pub fn with_i18n(mut self, i18n: Localizer) -> Self {
+        // ---
+        self.i18n = Arc::new(i18n);
+        self
+    }
+
+    /// Render HTML pages with `theme`.
+    pub fn with_theme(mut self, theme: Themes) -> Self {
+        // ---
+        self.theme = Arc::new(theme);
+        self
+    }
+
+    /// Send and check phone verification codes through `otp` (usually built
+    /// over a [`PgOtpStore`] on this state's pool).
+    pub fn with_otp(mut self, otp: Otp) -> Self {
+        // ---
+        self.otp = otp;
+        self
+    }
+
+    /// Add the headers `security_headers` sets to every response.
+    pub fn with_security_headers(mut self, security_headers: SecurityHeadersConfig) -> Self {
+        // ---
+        self.security_headers = security_headers;
+        self
+    }
+
+    /// Compress responses as `compression` configures.
+    pub fn with_compression(mut self, compression: CompressionConfig) -> Self {
+        // ---
+        self.compression = compression;
+        self
+    }
+
+    /// Accept requests other tokn services sign for `service_auth` (usually
+    /// built over this state's clock).
+    pub fn with_service_auth(mut self, service_auth: ServiceAuth) -> Self {
+        // ---
+        self.service_auth = service_auth;
+        self
+    }
+}
+
+impl FromRef<AppState> for Arc<PgPool> {
+    // ---
+    fn from_ref(state: &AppState) -> Self {
+        // ---
+        state.pool.clone()
+    }
+}
+
+impl FromRef<AppState> for CircuitBreaker {
+    // ---
+    fn from_ref(state: &AppState) -> Self {
+        // ---
+        state.postgres.clone()
+    }
+}
+
+impl FromRef<AppState> for Events {
+    // ---
+    fn from_ref(state: &AppState) -> Self {
+        // ---
+        state.events.clone()
+    }
+}
+
+impl FromRef<AppState> for SharedClock {
+    // ---
+    fn from_ref(state: &AppState) -> Self {
+        // ---
+        state.clock.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<Localizer> {
+    // ---
+    fn from_ref(state: &AppState) -> Self {
+        // ---
+        state.i18n.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<Themes> {
+    // ---
+    fn from_ref(state: &AppState) -> Self {
+        // ---
+        state.theme.clone()
+    }
+}
+
+impl FromRef<AppState> for Otp {
+    // ---
+    fn from_ref(state: &AppState) -> Self {
+        // ---
+        state.otp.clone()
+    }
+}
 
 // ---
 
-pub use config::Config;
-pub use database::create_pool;
+pub use admin::{
+    check_client_secrets, create_client, create_user, delete_client, delete_user,
+    generate_client_secret, hash_password, list_access_tokens, list_clients, list_users,
+    reset_client_secret, restore_client, restore_user, revoke_access_token, revoke_user_tokens,
+    weak_client_secrets, AccessTokenSummary, ClientSummary, RevokedToken, UserSummary,
+};
+pub use admin_ui::admin_ui_router;
+pub use check::check_config;
+pub use config::{Config, DatabaseConfig, RedisConfig, ServerConfig};
+pub use database::{create_pool, run_migrations};
+pub use debug::debug_info;
+pub use grpc::{serve_grpc, IntrospectionService};
 pub use handlers::{
     //
     authorize_handler,
     authorize_post_handler,
+    phone_handler,
+    phone_verify_handler,
     token_handler,
     userinfo_handler,
+    PhoneRequest,
+    PhoneVerifyRequest,
+    TokenRequest,
 };
+pub use health::health_checks;
+pub use history::{list_changes, Change, Entity};
+pub use jobs::{purge_expired, scheduler, Purged, EXPIRED_TOKEN_CLEANUP};
+pub use leader::PgLeaderElection;
+pub use otp_store::PgOtpStore;
+pub use query::{set_slow_query_threshold, timed, RowCount, DEFAULT_SLOW_QUERY_MS};
+pub use reload::reloader;
+pub use router::{build_router, with_service_layers};
@@ -1,82 +1,184 @@
 // oauth2-server/src/main.rs
 
 use anyhow::Result;
-use axum::{
-    http::StatusCode,
-    routing::{get, post},
-    Router,
-};
-use oauth2_server::Config;
+use axum::Router;
+use oauth2_server::{build_router, with_service_layers, AppState, Config, PgOtpStore};
 use std::sync::Arc;
-use tower_http::trace::TraceLayer;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
-
-// ---
-
-/// Root endpoint - service info
-///
-/// **Security Note:** This endpoint reveals service information and available endpoints.
-/// In production, consider removing this or placing it behind authentication to avoid
-/// information disclosure to potential attackers.
-async fn root_handler() -> (StatusCode, &'static str) {
-    // ---
-    (
-        StatusCode::OK,
-        "oauth2-server v0.1.0\n\
-         \n\
-         Available endpoints:\n\
-         - GET/POST /oauth/authorize - Authorization endpoint\n\
-         - POST /oauth/token - Token exchange endpoint\n\
-         - GET /oauth/userinfo - User information endpoint\n",
-    )
-}
+use std::time::Duration;
+use tokn_config::Reloadable;
+use tokn_events::{Events, LiveEvents};
+use tokn_i18n::Localizer;
+use tokn_ratelimit::{RateLimitLayer, RateLimiter};
+use tokn_server::{CheckFormat, ServiceAuth, Shutdown};
+use tokn_sms::Otp;
+use tokn_telemetry::TelemetryConfig;
+use tokn_theme::Themes;
 
 // ---
 
 #[tokio::main]
 async fn main() -> Result<()> {
     // ---
-    // Initialize tracing
-    tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "oauth2_server=debug,tower_http=debug".into()),
-        )
-        .with(tracing_subscriber::fmt::layer().with_ansi(false))
-        .init();
+    // `--check-config`: validate and probe, print the report, and exit
+    if let Some(format) = CheckFormat::from_args() {
+        oauth2_server::check_config().await.exit(format);
+    }
+
+    // ---
+    // Initialize tracing, OTLP export, and metrics
+    let telemetry = tokn_telemetry::init(&TelemetryConfig::from_env("oauth2-server")?)?;
 
     // ---
     // Load configuration
-    let config = Arc::new(Config::from_env()?);
+    let config = Arc::new(Config::load()?);
+    telemetry.log_filter().apply(&config.log);
+
+    // Fault injection (no-op unless built with `--features chaos`)
+    tokn_resilience::install_faults(&config.chaos);
+
+    // Slow-query logging threshold
+    oauth2_server::set_slow_query_threshold(config.database.slow_query_ms());
+
     let bind_addr = config.bind_address();
 
+    // Stop accepting and drain in-flight requests on SIGTERM/SIGINT
+    let shutdown = Shutdown::on_signals(Duration::from_secs(config.server.drain_timeout_seconds))?;
+
+    // ---
+    // Create database pool, waiting for Postgres if it is still starting
+    let pool = tokn_resilience::retry(&config.startup, "Postgres", || {
+        oauth2_server::create_pool(config.database.url.expose())
+    })
+    .await?;
+    let pool = Arc::new(pool);
+    let closing = pool.clone();
+
+    // ---
+    // Registered client secrets get the same screening as configured ones
+    oauth2_server::check_client_secrets(&pool, config.profile).await?;
+
     // ---
-    // Create database pool
-    let pool = Arc::new(oauth2_server::create_pool(&config.database.url).await?);
+    // Connect the auth event publisher (disabled unless EVENTS_BACKEND is set);
+    // events also stream live to /admin/events
+    let live = LiveEvents::new("oauth2-server", config.events.live_buffer);
+    let events = tokn_resilience::retry(&config.startup, "event broker", || {
+        Events::connect(&config.events, "oauth2-server")
+    })
+    .await?
+    .with_live(live.clone());
+    if events.is_enabled() {
+        tracing::info!(
+            "Publishing auth events to {} topic '{}'",
+            config.events.backend,
+            config.events.topic
+        );
+    }
 
     // ---
     tracing::info!("Starting oauth2-server on {}", bind_addr);
 
+    // ---
+    // Reload on SIGHUP and POST /admin/reload
+    let reload = oauth2_server::reloader(
+        Reloadable::new(Config::clone(&config)),
+        telemetry.log_filter(),
+    );
+    tokn_server::reload_on_sighup(reload.clone())?;
+
     // ---
     // Build router
-    let app = Router::new()
-        .route("/", get(root_handler))
-        .route("/oauth/authorize", get(oauth2_server::authorize_handler))
-        .route(
-            "/oauth/authorize",
-            post(oauth2_server::authorize_post_handler),
+    let state = AppState::new(pool, config.circuit_breaker)
+        .with_events(events)
+        .with_api(config.api)
+        .with_cors(config.server.cors.clone())
+        .with_i18n(Localizer::new(&config.i18n)?)
+        .with_theme(Themes::new(&config.theme)?)
+        .with_security_headers(config.server.security_headers.clone())
+        .with_compression(config.server.compression.clone());
+
+    // ---
+    // Phone verification codes go out through SMS_BACKEND (console by default)
+    let otp_store = PgOtpStore::new(state.pool.clone(), state.postgres.clone());
+    let otp = Otp::connect(&config.sms, otp_store, state.clock.clone())?;
+    tracing::info!(
+        "Sending phone verification codes via {}",
+        config.sms.backend
+    );
+    let state = state.with_otp(otp);
+
+    // ---
+    // Housekeeping jobs (expired token cleanup) on their configured schedules
+    let scheduler = oauth2_server::scheduler(&config.scheduler, &state);
+    scheduler.start();
+
+    // ---
+    // Per-client rate limiting in Redis (disabled unless RATE_LIMIT_ENABLED is set)
+    let limiter = tokn_resilience::retry(&config.startup, "Redis (rate limiting)", || {
+        RateLimiter::connect(
+            "oauth2-server",
+            &config.rate_limit,
+            &config.redis.url,
+            config.circuit_breaker,
+            state.clock.clone(),
         )
-        .route("/oauth/token", post(oauth2_server::token_handler))
-        .route("/oauth/userinfo", get(oauth2_server::userinfo_handler))
-        .layer(TraceLayer::new_for_http())
-        .with_state(pool);
+    })
+    .await?;
+    if limiter.is_enabled() {
+        tracing::info!(
+            "Rate limiting to {} requests per {}s per client ({})",
+            config.rate_limit.requests,
+            config.rate_limit.window_seconds,
+            config.rate_limit.algorithm
+        );
+    }
 
     // ---
-    // Start server
-    let listener = tokio::net::TcpListener::bind(&bind_addr).await?;
-    tracing::info!("Listening on {}", bind_addr);
+    // Requests signed by the other tokn services may skip the admin token
+    let service_auth = ServiceAuth::new("oauth2-server", &config.service_auth, state.clock.clone());
+    if service_auth.is_enabled() {
+        tracing::info!("Accepting signed requests from tokn services");
+    }
+    let state = state.with_service_auth(service_auth);
 
-    axum::serve(listener, app).await?;
+    let debug = oauth2_server::debug_info(&state, &scheduler, &limiter);
+    let operator = Router::new()
+        .merge(tokn_server::admin_router(&config.admin, reload))
+        .merge(tokn_server::admin_events_router(&config.admin, live))
+        .merge(tokn_server::debug_router(&config.admin, debug))
+        .merge(oauth2_server::admin_ui_router(&config.admin, state.clone()))
+        .merge(tokn_portal::portal_router(&config.portal)?);
+    let operator = with_service_layers(operator, &state);
+    let app = build_router(state.clone())
+        .layer(
+            RateLimitLayer::new(limiter)
+                .exempt("/health/live")
+                .exempt("/health/ready"),
+        )
+        .merge(operator);
+
+    // ---
+    // gRPC introspection runs alongside HTTP when configured
+    let grpc = async {
+        match config.server.grpc_addr {
+            Some(addr) => oauth2_server::serve_grpc(addr, state, &shutdown).await,
+            None => Ok(()),
+        }
+    };
 
+    // ---
+    // Start server
+    let http = tokn_server::serve(
+        app,
+        &bind_addr,
+        config.server.tls.as_ref(),
+        config.server.socket_mode,
+        &shutdown,
+    );
+    tokio::try_join!(http, grpc)?;
+
+    // ---
+    // Requests have drained: close the database pool
+    closing.close().await;
+    tracing::info!("Shutdown complete");
     Ok(())
 }
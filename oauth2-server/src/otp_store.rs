@@ -0,0 +1,98 @@
+// oauth2-server/src/otp_store.rs
+
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use std::sync::Arc;
+use tokn_resilience::CircuitBreaker;
+use tokn_sms::{OtpError, OtpRecord, OtpStore, PhoneNumber};
+
+// ---
+
+use crate::query::timed;
+
+// ---
+
+/// [`OtpStore`] over the `phone_otps` table, so codes sent by one replica
+/// can be checked by another.
+#[derive(Clone)]
+pub struct PgOtpStore {
+    // ---
+    pool: Arc<PgPool>,
+    postgres: CircuitBreaker,
+}
+
+impl PgOtpStore {
+    // ---
+    /// Store over `pool`, querying through the `postgres` breaker.
+    pub fn new(pool: Arc<PgPool>, postgres: CircuitBreaker) -> Self {
+        // ---
+        Self { pool, postgres }
+    }
+}
+
+// ---
+
+impl OtpStore for PgOtpStore {
+    // ---
+    async fn load(&self, phone: &PhoneNumber) -> Result<Option<OtpRecord>, OtpError> {
+        // ---
+        let query = timed(
+            "otp_store.load",
+            sqlx::query!(
+                r#"
+            SELECT code_hash, expires_at, attempts, last_sent_at, window_started_at, sends_in_window
+            FROM phone_otps
+            WHERE phone_number = $1
+            "#,
+                phone.as_str()
+            )
+            .fetch_optional(self.pool.as_ref()),
+        );
+        let row = self
+            .postgres
+            .call(query)
+            .await
+            .map_err(|e| OtpError::Store(e.to_string()))?;
+
+        Ok(row.map(|row| OtpRecord {
+            code_hash: row.code_hash,
+            expires_at: row.expires_at.and_utc(),
+            attempts: row.attempts.max(0) as u32,
+            last_sent_at: row.last_sent_at.and_utc(),
+            window_started_at: row.window_started_at.and_utc(),
+            sends_in_window: row.sends_in_window.max(0) as u32,
+        }))
+    }
+
+    async fn save(&self, phone: &PhoneNumber, record: &OtpRecord) -> Result<(), OtpError> {
+        // ---
+        let naive = |at: DateTime<Utc>| at.naive_utc();
+        let query = timed("otp_store.save", sqlx::query!(
+            r#"
+            INSERT INTO phone_otps (phone_number, code_hash, expires_at, attempts, last_sent_at, window_started_at, sends_in_window)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (phone_number) DO UPDATE SET
+                code_hash = EXCLUDED.code_hash,
+                expires_at = EXCLUDED.expires_at,
+                attempts = EXCLUDED.attempts,
+                last_sent_at = EXCLUDED.last_sent_at,
+                window_started_at = EXCLUDED.window_started_at,
+                sends_in_window = EXCLUDED.sends_in_window
+            "#,
+            phone.as_str(),
+            record.code_hash,
+            naive(record.expires_at),
+            record.attempts as i32,
+            naive(record.last_sent_at),
+            naive(record.window_started_at),
+            record.sends_in_window as i32,
+        )
+        .execute(self.pool.as_ref()));
+        self.postgres
+            .call(query)
+            .await
+            .map_err(|e| OtpError::Store(e.to_string()))?;
+
+        Ok(())
+    }
+}
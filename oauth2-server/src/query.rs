@@ -0,0 +1,158 @@
+// oauth2-server/src/query.rs
+
+//! Query instrumentation
+//!
+//! Every sqlx call runs through [`timed`] under a stable name,
+//! `<module>.<operation>` (`token.find_client`, `jobs.purge_tokens`), so
+//! the load on Postgres can be traced back to the handler or job issuing it:
+//!
+//! - a `db.query` span with the name, `rows`, and `duration_ms`
+//! - `tokn_db_query_duration_seconds{query}`, a histogram of every run,
+//!   including failed and cancelled ones
+//! - `tokn_db_query_errors_total{query}` for runs that returned an error
+//! - a `Slow query` warning and `tokn_db_slow_queries_total{query}` for runs
+//!   at or over `database.slow_query_ms`
+
+use sqlx::postgres::PgQueryResult;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+use tracing::{field, Instrument, Span};
+
+// ---
+
+/// Queries taking this long or longer are logged, unless
+/// `database.slow_query_ms` says otherwise.
+pub const DEFAULT_SLOW_QUERY_MS: u64 = 200;
+
+static SLOW_QUERY_MS: AtomicU64 = AtomicU64::new(DEFAULT_SLOW_QUERY_MS);
+
+/// Log queries taking `ms` milliseconds or longer from now on (0 logs every
+/// query). Set at startup and on reload from `database.slow_query_ms`.
+pub fn set_slow_query_threshold(ms: u64) {
+    // ---
+    SLOW_QUERY_MS.store(ms, Ordering::Relaxed);
+}
+
+// ---
+
+/// Rows a query returned or changed, as recorded on its span.
+pub trait RowCount {
+    // ---
+    fn row_count(&self) -> u64;
+}
+
+impl<T> RowCount for Option<T> {
+    // ---
+    fn row_count(&self) -> u64 {
+        // ---
+        u64::from(self.is_some())
+    }
+}
+
+impl<T> RowCount for Vec<T> {
+    // ---
+    fn row_count(&self) -> u64 {
+        // ---
+        self.len() as u64
+    }
+}
+
+impl RowCount for PgQueryResult {
+    // ---
+    fn row_count(&self) -> u64 {
+        // ---
+        self.rows_affected()
+    }
+}
+
+/// A `fetch_one` of a scalar.
+impl RowCount for bool {
+    // ---
+    fn row_count(&self) -> u64 {
+        // ---
+        1
+    }
+}
+
+// ---
+
+/// Run `query`, a sqlx `fetch_*` or `execute` future, as the query named
+/// `name`, recording its span, duration, and rows (see the module docs).
+pub async fn timed<T, E, F>(name: &'static str, query: F) -> Result<T, E>
+where
+    T: RowCount,
+    F: Future<Output = Result<T, E>>,
+{
+    // ---
+    let span = tracing::info_span!(
+        "db.query",
+        query = name,
+        rows = field::Empty,
+        duration_ms = field::Empty
+    );
+    let mut timer = Timer {
+        name,
+        span: span.clone(),
+        started: Instant::now(),
+        outcome: Outcome::Cancelled,
+    };
+
+    let result = query.instrument(span).await;
+    timer.outcome = match &result {
+        Ok(rows) => Outcome::Rows(rows.row_count()),
+        Err(_) => Outcome::Failed,
+    };
+    result
+}
+
+// ---
+
+/// Records a query's duration when it ends, however it ends: a caller
+/// timing out (e.g. the circuit breaker) drops the query mid-flight, and
+/// those are the slow queries worth seeing.
+struct Timer {
+    // ---
+    name: &'static str,
+    span: Span,
+    started: Instant,
+    outcome: Outcome,
+}
+
+enum Outcome {
+    // ---
+    Rows(u64),
+    Failed,
+    Cancelled,
+}
+
+impl Drop for Timer {
+    // ---
+    fn drop(&mut self) {
+        // ---
+        let elapsed = self.started.elapsed();
+        let duration_ms = elapsed.as_millis() as u64;
+        let name = self.name;
+
+        self.span.record("duration_ms", duration_ms);
+        metrics::histogram!("tokn_db_query_duration_seconds", "query" => name)
+            .record(elapsed.as_secs_f64());
+
+        let outcome = match self.outcome {
+            Outcome::Rows(rows) => {
+                self.span.record("rows", rows);
+                "ok"
+            }
+            Outcome::Failed => {
+                metrics::counter!("tokn_db_query_errors_total", "query" => name).increment(1);
+                "error"
+            }
+            Outcome::Cancelled => "cancelled",
+        };
+
+        if duration_ms >= SLOW_QUERY_MS.load(Ordering::Relaxed) {
+            metrics::counter!("tokn_db_slow_queries_total", "query" => name).increment(1);
+            tracing::warn!(query = name, duration_ms, outcome, "Slow query");
+        }
+    }
+}
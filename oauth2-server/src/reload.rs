@@ -0,0 +1,72 @@
+// oauth2-server/src/reload.rs
+
+//! Configuration reload
+//!
+//! Runs on `SIGHUP` and `POST /admin/reload`. Only `log.filter` and
+//! `database.slow_query_ms` are applied; every other changed section is
+//! reported as needing a restart.
+
+use std::sync::Arc;
+use tokn_config::Reloadable;
+use tokn_server::{ReloadFn, ReloadReport};
+use tokn_telemetry::LogFilter;
+
+// ---
+
+use crate::{Config, DatabaseConfig};
+
+// ---
+
+/// Build the reload callback. `config` is the running configuration that new
+/// values are compared against; it is updated after each reload.
+///
+/// # Errors
+///
+/// The returned callback fails, leaving everything unchanged, if the
+/// configuration no longer loads or the new log filter does not parse.
+pub fn reloader(config: Reloadable<Config>, log_filter: LogFilter) -> ReloadFn {
+    // ---
+    Arc::new(move || {
+        // ---
+        let new = Config::load()?;
+        let old = config.get();
+        let mut report = ReloadReport::default();
+
+        if report.reloadable("log.filter", &old.log.filter, &new.log.filter) {
+            log_filter.set(new.log.filter.as_deref())?;
+        }
+
+        report.restart_required("server", &old.server, &new.server);
+        if report.reloadable(
+            "database.slow_query_ms",
+            &old.database.slow_query_ms,
+            &new.database.slow_query_ms,
+        ) {
+            crate::set_slow_query_threshold(new.database.slow_query_ms());
+        }
+
+        report.restart_required("database.url", &old.database.url, &new.database.url);
+        report.restart_required("redis", &old.redis, &new.redis);
+        report.restart_required("startup", &old.startup, &new.startup);
+        report.restart_required(
+            "circuit_breaker",
+            &old.circuit_breaker,
+            &new.circuit_breaker,
+        );
+        report.restart_required("admin", &old.admin, &new.admin);
+        report.restart_required("api", &old.api, &new.api);
+        report.restart_required("i18n", &old.i18n, &new.i18n);
+        report.restart_required("theme", &old.theme, &new.theme);
+
+        config.set(Config {
+            log: new.log,
+            database: DatabaseConfig {
+                slow_query_ms: new.database.slow_query_ms,
+                ..old.database.clone()
+            },
+            ..(*old).clone()
+        });
+
+        Ok(report)
+    })
+}
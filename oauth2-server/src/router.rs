@@ -0,0 +1,112 @@
+// oauth2-server/src/router.rs
+
+use axum::{
+    http::StatusCode,
+    middleware,
+    routing::{get, post},
+    Router,
+};
+use tokn_server::SecurityHeaders;
+use tower_http::trace::TraceLayer;
+
+// ---
+
+use crate::handlers::{
+    authorize_handler, authorize_post_handler, phone_handler, phone_verify_handler, token_handler,
+    userinfo_handler,
+};
+use crate::AppState;
+
+// ---
+
+/// Root endpoint - service info
+///
+/// **Security Note:** This endpoint reveals service information and available endpoints.
+/// In production, consider removing this or placing it behind authentication to avoid
+/// information disclosure to potential attackers.
+async fn root_handler() -> (StatusCode, &'static str) {
+    // ---
+    (
+        StatusCode::OK,
+        "oauth2-server v0.1.0\n\
+         \n\
+         Available endpoints:\n\
+         - GET/POST /v1/oauth/authorize - Authorization endpoint\n\
+         - POST /v1/oauth/token - Token exchange endpoint\n\
+         - GET /v1/oauth/userinfo - User information endpoint\n\
+         - POST /v1/oauth/phone - Send a phone verification code\n\
+         - POST /v1/oauth/phone/verify - Confirm a phone verification code\n\
+         - GET /docs - API documentation for every tokn service\n",
+    )
+}
+
+// ---
+
+/// Builds the oauth2-server application router.
+///
+/// Shared by the binary and in-process test harnesses so both serve the
+/// same routes and middleware. Database queries run through `state.postgres`,
+/// which the gRPC introspection service shares.
+///
+/// The OAuth2 endpoints are served under `/v1/oauth/...`; while
+/// `state.api.legacy_paths` is set they are also served at `/oauth/...`,
+/// marked deprecated (see [`tokn_server::versioned`]). Theme assets are served
+/// under `/theme/<name>/static/`. Bearer endpoint errors are RFC 7807 problem
+/// details (see [`tokn_server::problem_details`]); the token endpoint keeps
+/// RFC 6749 error bodies. Every response carries the request's
+/// `X-Request-Id` (see [`tokn_server::request_id`]), and browser apps on the
+/// origins `state.cors` lists may call every route (see
+/// [`tokn_server::cors_layer`]). `/health/live` and `/health/ready` report
+/// liveness and Postgres readiness (see [`tokn_server::health_router`]).
+/// Every route, the consent page included, is wrapped in
+/// [`with_service_layers`].
+pub fn build_router(state: AppState) -> Router {
+    // ---
+    let api = Router::new()
+        .route("/oauth/authorize", get(authorize_handler))
+        .route("/oauth/authorize", post(authorize_post_handler))
+        .route("/oauth/token", post(token_handler))
+        .route("/oauth/userinfo", get(userinfo_handler))
+        .route("/oauth/phone", post(phone_handler))
+        .route("/oauth/phone/verify", post(phone_verify_handler));
+
+    let app = Router::new()
+        .route("/", get(root_handler))
+        .merge(tokn_server::health_router(crate::health_checks(&state)))
+        .merge(tokn_server::versioned(api, &state.api))
+        .merge(state.theme.static_router())
+        .layer(middleware::from_fn(tokn_server::problem_details));
+    let app = match tokn_server::cors_layer(&state.cors) {
+        Some(cors) => app.layer(cors),
+        None => app,
+    };
+
+    let app = app
+        .layer(TraceLayer::new_for_http())
+        .layer(middleware::from_fn(tokn_server::request_id))
+        .with_state(state.clone());
+    with_service_layers(app, &state)
+}
+
+/// Wrap `router` in the layers every oauth2-server response passes through,
+/// innermost first: requests signed for `state.service_auth` are verified
+/// (see [`tokn_server::verify_service_signature`]), responses get
+/// `state.security_headers` (see [`tokn_server::security_headers`]), and are
+/// compressed as `state.compression` configures (see
+/// [`tokn_server::compression_layer`]).
+///
+/// [`build_router`] applies it already; the binary applies it to the
+/// operator routes it merges in (admin API and UI, events, debug, portal).
+pub fn with_service_layers(router: Router, state: &AppState) -> Router {
+    // ---
+    router
+        .layer(middleware::from_fn_with_state(
+            state.service_auth.clone(),
+            tokn_server::verify_service_signature,
+        ))
+        .layer(middleware::from_fn_with_state(
+            SecurityHeaders::new(&state.security_headers),
+            tokn_server::security_headers,
+        ))
+        .layer(tokn_server::compression_layer(&state.compression))
+}
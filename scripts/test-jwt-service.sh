@@ -119,7 +119,7 @@ echo ""
 
 # Test 1: Generate tokens
 echo "📝 Test 1: Token Generation"
-TOKENS=$(curl -s -X POST "$BASE_URL/auth/token" \
+TOKENS=$(curl -s -X POST "$BASE_URL/v1/auth/token" \
   -H "Content-Type: application/json" \
   -d '{"user_id": "test_user", "email": "test@example.com"}')
 
@@ -136,7 +136,7 @@ echo ""
 
 # Test 2: Validate valid token
 echo "🔍 Test 2: Token Validation (Valid)"
-VALIDATION=$(curl -s -X POST "$BASE_URL/auth/validate" \
+VALIDATION=$(curl -s -X POST "$BASE_URL/v1/auth/validate" \
   -H "Content-Type: application/json" \
   -d "{\"token\": \"$ACCESS_TOKEN\"}")
 
@@ -151,7 +151,7 @@ echo ""
 
 # Test 3: Validate invalid token
 echo "🔍 Test 3: Token Validation (Invalid)"
-INVALID=$(curl -s -X POST "$BASE_URL/auth/validate" \
+INVALID=$(curl -s -X POST "$BASE_URL/v1/auth/validate" \
   -H "Content-Type: application/json" \
   -d '{"token": "invalid.token.here"}')
 
@@ -166,7 +166,7 @@ echo ""
 
 # Test 4: Refresh token flow
 echo "🔄 Test 4: Refresh Token Flow"
-NEW_TOKENS=$(curl -s -X POST "$BASE_URL/auth/refresh" \
+NEW_TOKENS=$(curl -s -X POST "$BASE_URL/v1/auth/refresh" \
   -H "Content-Type: application/json" \
   -d "{\"refresh_token\": \"$REFRESH_TOKEN\"}")
 
@@ -183,7 +183,7 @@ echo ""
 
 # Test 5: Refresh token rotation (old token should fail)
 echo "🔄 Test 5: Refresh Token Rotation (One-Time Use)"
-OLD_REFRESH=$(curl -s -X POST "$BASE_URL/auth/refresh" \
+OLD_REFRESH=$(curl -s -X POST "$BASE_URL/v1/auth/refresh" \
   -H "Content-Type: application/json" \
   -d "{\"refresh_token\": \"$REFRESH_TOKEN\"}")
 
@@ -198,7 +198,7 @@ echo ""
 
 # Test 6: Token revocation
 echo "🚫 Test 6: Token Revocation"
-REVOKE=$(curl -s -X POST "$BASE_URL/auth/revoke" \
+REVOKE=$(curl -s -X POST "$BASE_URL/v1/auth/revoke" \
   -H "Content-Type: application/json" \
   -d "{\"token\": \"$NEW_ACCESS_TOKEN\"}")
 
@@ -213,7 +213,7 @@ echo ""
 
 # Test 7: Validate revoked token (should fail)
 echo "🔍 Test 7: Validate Revoked Token"
-REVOKED=$(curl -s -X POST "$BASE_URL/auth/validate" \
+REVOKED=$(curl -s -X POST "$BASE_URL/v1/auth/validate" \
   -H "Content-Type: application/json" \
   -d "{\"token\": \"$NEW_ACCESS_TOKEN\"}")
 
@@ -0,0 +1,498 @@
+// tests/src/contract.rs
+
+//! Consumer-driven contracts between tokn services
+//!
+//! A contract (`tests/contracts/<consumer>.<provider>.json`) lists the requests
+//! a consumer makes to a provider and the responses it relies on. Each side is
+//! checked against it on its own, so a breaking change on either side fails
+//! before an end-to-end run:
+//!
+//! - the consumer runs against a [`Stub`] that answers the contract's requests
+//!   with its example responses and records any request it does not recognise
+//! - [`verify_provider`] replays each contract request against the real
+//!   provider, after setting up its provider state, and checks the responses
+//!
+//! Request fields match exactly unless written `{"any": "<example>"}` (present
+//! with any value) or `{"prefix": "<text>"}`; query and form fields the
+//! contract does not list are mismatches, extra headers are not. Response
+//! bodies match by shape: every key of the example must be present with the
+//! same JSON type, and providers may add keys.
+
+use anyhow::{Context, Result};
+use axum::body::Body;
+use axum::extract::Request;
+use axum::response::{IntoResponse, Response};
+use axum::Router;
+use http::{HeaderMap, StatusCode};
+use reqwest::Url;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::future::Future;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+// ---
+
+/// The requests one service makes to another and the responses it relies on.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Contract {
+    // ---
+    pub consumer: String,
+    pub provider: String,
+    pub interactions: Vec<Interaction>,
+}
+
+/// One request and the response the consumer expects for it.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Interaction {
+    // ---
+    pub description: String,
+
+    /// What the provider must hold before the request, e.g. an issued code
+    #[serde(default)]
+    pub provider_state: Option<String>,
+
+    pub request: RequestSpec,
+    pub response: ResponseSpec,
+}
+
+/// Expected request; examples are what [`verify_provider`] sends.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RequestSpec {
+    // ---
+    pub method: String,
+    pub path: String,
+    #[serde(default)]
+    pub query: BTreeMap<String, Matcher>,
+    #[serde(default)]
+    pub headers: BTreeMap<String, Matcher>,
+    /// `application/x-www-form-urlencoded` body fields
+    #[serde(default)]
+    pub form: BTreeMap<String, Matcher>,
+}
+
+/// Expected response; examples are what a [`Stub`] answers with.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ResponseSpec {
+    // ---
+    pub status: u16,
+    #[serde(default)]
+    pub headers: BTreeMap<String, Matcher>,
+    /// JSON body, matched by shape
+    #[serde(default)]
+    pub body: Option<Value>,
+}
+
+/// How a request field or header value is matched.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Matcher {
+    // ---
+    /// Exactly this value
+    Exact(String),
+
+    /// Any value; the string is the example sent or answered
+    Any { any: String },
+
+    /// A value starting with this text, which doubles as the example
+    Prefix { prefix: String },
+}
+
+// ---
+
+impl Contract {
+    // ---
+    /// Load `tests/contracts/<name>.json`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file is missing or not a valid contract.
+    pub fn load(name: &str) -> Result<Self> {
+        // ---
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("contracts")
+            .join(format!("{name}.json"));
+        let json = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read contract {}", path.display()))?;
+        serde_json::from_str(&json).with_context(|| format!("Invalid contract {}", path.display()))
+    }
+
+    // ---
+    /// The interaction with this description.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the contract has no such interaction.
+    pub fn interaction(&self, description: &str) -> &Interaction {
+        // ---
+        self.interactions
+            .iter()
+            .find(|interaction| interaction.description == description)
+            .unwrap_or_else(|| panic!("no contract interaction '{description}'"))
+    }
+}
+
+// ---
+
+impl Matcher {
+    // ---
+    /// The value sent or answered for this field.
+    pub fn example(&self) -> &str {
+        // ---
+        match self {
+            Matcher::Exact(value)
+            | Matcher::Any { any: value }
+            | Matcher::Prefix { prefix: value } => value,
+        }
+    }
+
+    fn matches(&self, actual: &str) -> bool {
+        // ---
+        match self {
+            Matcher::Exact(value) => actual == value,
+            Matcher::Any { .. } => true,
+            Matcher::Prefix { prefix } => actual.starts_with(prefix.as_str()),
+        }
+    }
+}
+
+impl fmt::Display for Matcher {
+    // ---
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // ---
+        match self {
+            Matcher::Exact(value) => write!(f, "{value:?}"),
+            Matcher::Any { .. } => f.write_str("any value"),
+            Matcher::Prefix { prefix } => write!(f, "a value starting with {prefix:?}"),
+        }
+    }
+}
+
+// ---
+
+impl RequestSpec {
+    // ---
+    /// Every way a request differs from this spec; empty when it matches.
+    pub fn mismatches(
+        &self,
+        method: &str,
+        url: &Url,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> Vec<String> {
+        // ---
+        let mut problems = Vec::new();
+        if !method.eq_ignore_ascii_case(&self.method) {
+            problems.push(format!("method: expected {}, got {method}", self.method));
+        }
+        if url.path() != self.path {
+            problems.push(format!("path: expected {}, got {}", self.path, url.path()));
+        }
+
+        let query: BTreeMap<String, String> = url.query_pairs().into_owned().collect();
+        fields("query", &self.query, &query, &mut problems);
+
+        if !self.form.is_empty() {
+            match serde_urlencoded::from_bytes::<BTreeMap<String, String>>(body) {
+                Ok(form) => fields("form", &self.form, &form, &mut problems),
+                Err(e) => problems.push(format!("form: not a form body ({e})")),
+            }
+        }
+
+        header_mismatches(&self.headers, headers, &mut problems);
+        problems
+    }
+
+    // ---
+    /// Build the example request against `base_url`.
+    pub fn example(
+        &self,
+        http: &reqwest::Client,
+        base_url: &str,
+    ) -> Result<reqwest::RequestBuilder> {
+        // ---
+        let mut url = Url::parse(&format!("{base_url}{}", self.path))?;
+        if !self.query.is_empty() {
+            url.query_pairs_mut()
+                .extend_pairs(self.query.iter().map(|(k, v)| (k, v.example())));
+        }
+
+        let method = self.method.parse::<reqwest::Method>()?;
+        let mut request = http.request(method, url);
+        for (name, value) in &self.headers {
+            request = request.header(name, value.example());
+        }
+        if !self.form.is_empty() {
+            let form: BTreeMap<&str, &str> = self
+                .form
+                .iter()
+                .map(|(k, v)| (k.as_str(), v.example()))
+                .collect();
+            request = request.body(serde_urlencoded::to_string(form)?);
+        }
+        Ok(request)
+    }
+}
+
+// ---
+
+impl ResponseSpec {
+    // ---
+    /// Every way a response differs from this spec; empty when it matches.
+    pub fn mismatches(&self, status: u16, headers: &HeaderMap, body: &[u8]) -> Vec<String> {
+        // ---
+        let mut problems = Vec::new();
+        if status != self.status {
+            problems.push(format!("status: expected {}, got {status}", self.status));
+        }
+        header_mismatches(&self.headers, headers, &mut problems);
+
+        if let Some(expected) = &self.body {
+            match serde_json::from_slice::<Value>(body) {
+                Ok(actual) => shape("body", expected, &actual, &mut problems),
+                Err(e) => problems.push(format!("body: not JSON ({e})")),
+            }
+        }
+        problems
+    }
+
+    // ---
+    /// The example response.
+    fn example(&self) -> Response {
+        // ---
+        let mut response = match &self.body {
+            Some(body) => body.to_string().into_response(),
+            None => Response::new(Body::empty()),
+        };
+        *response.status_mut() = StatusCode::from_u16(self.status).unwrap_or(StatusCode::OK);
+        for (name, value) in &self.headers {
+            if let (Ok(name), Ok(value)) = (
+                http::HeaderName::from_bytes(name.as_bytes()),
+                value.example().parse(),
+            ) {
+                response.headers_mut().insert(name, value);
+            }
+        }
+        response
+    }
+}
+
+// ---
+
+/// Compare query or form fields: listed ones must match, unlisted ones must
+/// be absent.
+fn fields(
+    kind: &str,
+    expected: &BTreeMap<String, Matcher>,
+    actual: &BTreeMap<String, String>,
+    problems: &mut Vec<String>,
+) {
+    // ---
+    for (name, matcher) in expected {
+        match actual.get(name) {
+            Some(value) if matcher.matches(value) => {}
+            Some(value) => {
+                problems.push(format!("{kind} {name}: expected {matcher}, got {value:?}"))
+            }
+            None => problems.push(format!("{kind} {name}: missing")),
+        }
+    }
+    for name in actual.keys().filter(|name| !expected.contains_key(*name)) {
+        problems.push(format!("{kind} {name}: not in the contract"));
+    }
+}
+
+fn header_mismatches(
+    expected: &BTreeMap<String, Matcher>,
+    actual: &HeaderMap,
+    problems: &mut Vec<String>,
+) {
+    // ---
+    for (name, matcher) in expected {
+        match actual.get(name.as_str()).map(|value| value.to_str()) {
+            Some(Ok(value)) if matcher.matches(value) => {}
+            Some(Ok(value)) => {
+                problems.push(format!("header {name}: expected {matcher}, got {value:?}"))
+            }
+            Some(Err(_)) => problems.push(format!("header {name}: not text")),
+            None => problems.push(format!("header {name}: missing")),
+        }
+    }
+}
+
+/// Compare JSON by shape: same types, every expected key present.
+fn shape(path: &str, expected: &Value, actual: &Value, problems: &mut Vec<String>) {
+    // ---
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            for (key, expected) in expected {
+                match actual.get(key) {
+                    Some(actual) => shape(&format!("{path}.{key}"), expected, actual, problems),
+                    None => problems.push(format!("{path}.{key}: missing")),
+                }
+            }
+        }
+        (Value::Array(expected), Value::Array(actual)) => {
+            if let Some(expected) = expected.first() {
+                for (i, actual) in actual.iter().enumerate() {
+                    shape(&format!("{path}[{i}]"), expected, actual, problems);
+                }
+            }
+        }
+        (Value::Null, Value::Null)
+        | (Value::Bool(_), Value::Bool(_))
+        | (Value::Number(_), Value::Number(_))
+        | (Value::String(_), Value::String(_)) => {}
+        _ => problems.push(format!("{path}: expected {expected}, got {actual}")),
+    }
+}
+
+// ---
+
+/// Stand-in provider serving a contract's example responses.
+///
+/// Each request is answered by the first interaction it matches; requests
+/// matching none get a 500 and are listed by [`Stub::unmatched`].
+#[derive(Debug, Clone)]
+pub struct Stub {
+    // ---
+    /// Base URL to point the consumer at
+    pub url: String,
+    log: Arc<Mutex<StubLog>>,
+}
+
+#[derive(Debug, Default)]
+struct StubLog {
+    // ---
+    matched: Vec<String>,
+    unmatched: Vec<String>,
+}
+
+impl Stub {
+    // ---
+    /// Serve `contract`'s provider side on an ephemeral port.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no local port can be bound.
+    pub async fn start(contract: &Contract) -> Result<Self> {
+        // ---
+        let log = Arc::new(Mutex::new(StubLog::default()));
+        let interactions = Arc::new(contract.interactions.clone());
+
+        let app = Router::new().fallback({
+            let log = log.clone();
+            move |request: Request| answer(request, interactions.clone(), log.clone())
+        });
+
+        Ok(Self {
+            url: crate::serve(app).await?,
+            log,
+        })
+    }
+
+    // ---
+    /// Descriptions of the interactions served so far, in order.
+    pub fn matched(&self) -> Vec<String> {
+        // ---
+        self.log.lock().unwrap().matched.clone()
+    }
+
+    // ---
+    /// Requests that matched no interaction, with what was wrong with them.
+    pub fn unmatched(&self) -> Vec<String> {
+        // ---
+        self.log.lock().unwrap().unmatched.clone()
+    }
+}
+
+async fn answer(
+    request: Request,
+    interactions: Arc<Vec<Interaction>>,
+    log: Arc<Mutex<StubLog>>,
+) -> Response {
+    // ---
+    let (parts, body) = request.into_parts();
+    let body = axum::body::to_bytes(body, 1024 * 1024)
+        .await
+        .unwrap_or_default();
+    let url = Url::parse(&format!("http://stub{}", parts.uri)).expect("request URI");
+
+    let mut reasons = Vec::new();
+    for interaction in interactions.iter() {
+        let problems =
+            interaction
+                .request
+                .mismatches(parts.method.as_str(), &url, &parts.headers, &body);
+        if problems.is_empty() {
+            log.lock()
+                .unwrap()
+                .matched
+                .push(interaction.description.clone());
+            return interaction.response.example();
+        }
+        reasons.push(format!(
+            "{}: {}",
+            interaction.description,
+            problems.join(", ")
+        ));
+    }
+
+    let unmatched = format!(
+        "{} {} matched no interaction ({})",
+        parts.method,
+        parts.uri,
+        reasons.join("; ")
+    );
+    log.lock().unwrap().unmatched.push(unmatched.clone());
+    (StatusCode::INTERNAL_SERVER_ERROR, unmatched).into_response()
+}
+
+// ---
+
+/// Replay every interaction of `contract` against the provider at `base_url`,
+/// calling `setup` with each provider state first.
+///
+/// Returns the mismatches, each prefixed with its interaction; empty when the
+/// provider honours the contract.
+///
+/// # Errors
+///
+/// Returns an error if `setup` fails or a request cannot be sent.
+pub async fn verify_provider<F, Fut>(
+    contract: &Contract,
+    base_url: &str,
+    mut setup: F,
+) -> Result<Vec<String>>
+where
+    F: FnMut(&str) -> Fut,
+    Fut: Future<Output = Result<()>>,
+{
+    // ---
+    let http = crate::http_client();
+    let mut problems = Vec::new();
+
+    for interaction in &contract.interactions {
+        if let Some(state) = &interaction.provider_state {
+            setup(state)
+                .await
+                .with_context(|| format!("Failed to set up provider state '{state}'"))?;
+        }
+
+        let response = interaction.request.example(&http, base_url)?.send().await?;
+        let status = response.status().as_u16();
+        let headers = response.headers().clone();
+        let body = response.bytes().await?;
+
+        problems.extend(
+            interaction
+                .response
+                .mismatches(status, &headers, &body)
+                .into_iter()
+                .map(|problem| format!("{}: {problem}", interaction.description)),
+        );
+    }
+    Ok(problems)
+}
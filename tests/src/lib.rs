@@ -0,0 +1,710 @@
+// tests/src/lib.rs
+
+//! Integration test harness for the tokn workspace
+//!
+//! Starts disposable Postgres and Redis containers (via testcontainers), applies
+//! the oauth2-server migrations, and boots each service in-process on an
+//! ephemeral port using the same router builders as the production binaries.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # async fn example() -> anyhow::Result<()> {
+//! let env = tokn_tests::TestEnv::start().await?;
+//! let jwt_url = env.spawn_jwt_service().await?;
+//! let response = reqwest::get(format!("{jwt_url}/health")).await?;
+//! assert!(response.status().is_success());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`contract`] checks services against the consumer-driven contracts in
+//! `tests/contracts/` without starting the other side.
+
+pub mod contract;
+
+use anyhow::{Context, Result};
+use axum::Router;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use std::sync::{Arc, Mutex};
+use testcontainers_modules::testcontainers::{runners::AsyncRunner, ContainerAsync};
+use testcontainers_modules::{postgres::Postgres, redis::Redis};
+use tokn_core::{SharedClock, SystemClock};
+use tokn_events::{AuthEvent, EventPublisher, Events, EventsError};
+use tokn_mail::{Email, Mail, MailConfig, MailError, Mailer, Templates};
+use tokn_proto::TokenIntrospectionServer;
+use tokn_sms::{Otp, PhoneNumber, SmsError, SmsSender};
+use tonic::transport::server::TcpIncoming;
+
+// ---
+
+/// JWT secret used by every in-process jwt-service instance.
+pub const TEST_JWT_SECRET: &str = "integration-test-secret-at-least-32-characters";
+
+/// `ADMIN_TOKEN` for in-process services that mount admin routes.
+pub const TEST_ADMIN_TOKEN: &str = "integration-test-admin-token-at-least-32-characters";
+
+/// Demo client seeded by the oauth2-server migrations.
+pub const DEMO_CLIENT_ID: &str = "demo_client";
+
+/// Secret for [`DEMO_CLIENT_ID`] seeded by the oauth2-server migrations.
+pub const DEMO_CLIENT_SECRET: &str = "demo_secret";
+
+/// Redirect URI registered for [`DEMO_CLIENT_ID`].
+pub const DEMO_REDIRECT_URI: &str = "http://127.0.0.1:8081/callback";
+
+// ---
+
+/// Running backing services for one test.
+///
+/// Containers are stopped and removed when the environment is dropped, so each
+/// test gets a clean database and an empty Redis.
+pub struct TestEnv {
+    // ---
+    _postgres: ContainerAsync<Postgres>,
+    _redis: ContainerAsync<Redis>,
+
+    /// Connection URL for the migrated Postgres database
+    pub database_url: String,
+
+    /// Connection URL for the Redis instance
+    pub redis_url: String,
+
+    /// Shared pool against `database_url`
+    pub pool: Arc<PgPool>,
+}
+
+// ---
+
+/// Base URLs of a full in-process tokn deployment.
+#[derive(Debug, Clone)]
+pub struct Stack {
+    // ---
+    pub jwt_service: String,
+    pub oauth2_server: String,
+    pub oauth2_client: String,
+}
+
+// ---
+
+impl TestEnv {
+    // ---
+    /// Start Postgres and Redis containers and apply migrations.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if Docker is unavailable, a container fails to start,
+    /// or the migrations cannot be applied.
+    pub async fn start() -> Result<Self> {
+        // ---
+        let postgres = Postgres::default()
+            .start()
+            .await
+            .context("Failed to start Postgres container (is Docker running?)")?;
+        let redis = Redis::default()
+            .start()
+            .await
+            .context("Failed to start Redis container (is Docker running?)")?;
+
+        // ---
+        let pg_host = postgres.get_host().await?;
+        let pg_port = postgres.get_host_port_ipv4(5432).await?;
+        let database_url = format!("postgres://postgres:postgres@{pg_host}:{pg_port}/postgres");
+
+        let redis_host = redis.get_host().await?;
+        let redis_port = redis.get_host_port_ipv4(6379).await?;
+        let redis_url = format!("redis://{redis_host}:{redis_port}");
+
+        // ---
+        let pool = oauth2_server::create_pool(&database_url).await?;
+        oauth2_server::run_migrations(&pool).await?;
+
+        Ok(Self {
+            _postgres: postgres,
+            _redis: redis,
+            database_url,
+            redis_url,
+            pool: Arc::new(pool),
+        })
+    }
+
+    // ---
+    /// Boot jwt-service in-process and return its base URL.
+    pub async fn spawn_jwt_service(&self) -> Result<String> {
+        // ---
+        self.spawn_jwt_service_with_events(Events::disabled()).await
+    }
+
+    // ---
+    /// Boot jwt-service in-process, publishing auth events through `events`.
+    pub async fn spawn_jwt_service_with_events(&self, events: Events) -> Result<String> {
+        // ---
+        let state = self.jwt_state(events, SystemClock::shared()).await?;
+
+        serve(jwt_service::build_router(state)).await
+    }
+
+    // ---
+    /// Boot jwt-service in-process with `config` adjusted from
+    /// [`jwt_config`], reading the time from `clock`.
+    pub async fn spawn_jwt_service_with_config(
+        &self,
+        config: jwt_service::Config,
+        clock: SharedClock,
+    ) -> Result<String> {
+        // ---
+        let state = self
+            .jwt_state_with(config, Events::disabled(), clock)
+            .await?;
+
+        serve(jwt_service::build_router(state)).await
+    }
+
+    // ---
+    /// Boot jwt-service in-process, sending email through `mail`.
+    pub async fn spawn_jwt_service_with_mail(&self, mail: Mail) -> Result<String> {
+        // ---
+        let mut state = self
+            .jwt_state(Events::disabled(), SystemClock::shared())
+            .await?;
+        state.mail = mail;
+
+        serve(jwt_service::build_router(state)).await
+    }
+
+    // ---
+    /// Boot jwt-service in-process, reading the time from `clock`.
+    pub async fn spawn_jwt_service_with_clock(&self, clock: SharedClock) -> Result<String> {
+        // ---
+        let state = self.jwt_state(Events::disabled(), clock).await?;
+
+        serve(jwt_service::build_router(state)).await
+    }
+
+    // ---
+    /// Boot jwt-service's gRPC introspection in-process and return its
+    /// endpoint URL.
+    pub async fn spawn_jwt_grpc(&self) -> Result<String> {
+        // ---
+        let state = self
+            .jwt_state(Events::disabled(), SystemClock::shared())
+            .await?;
+        let service = jwt_service::IntrospectionService::new(state);
+
+        serve_grpc(TokenIntrospectionServer::new(service)).await
+    }
+
+    // ---
+    /// jwt-service state against the test Redis.
+    async fn jwt_state(&self, events: Events, clock: SharedClock) -> Result<jwt_service::AppState> {
+        // ---
+        self.jwt_state_with(jwt_config(&self.redis_url), events, clock)
+            .await
+    }
+
+    // ---
+    /// jwt-service state with `config` against the test Redis.
+    async fn jwt_state_with(
+        &self,
+        config: jwt_service::Config,
+        events: Events,
+        clock: SharedClock,
+    ) -> Result<jwt_service::AppState> {
+        // ---
+        let redis = jwt_service::create_redis_client(&self.redis_url).await?;
+        let redis = jwt_service::RedisConnection::new(redis, config.circuit_breaker)
+            .with_retry(config.redis_retry);
+        let audit = jwt_service::AuditLog::open(&config.audit, &self.redis_url).await?;
+        Ok(jwt_service::AppState {
+            keys: config.jwt.keys()?.into(),
+            config: config.into(),
+            store: Some(jwt_service::Store::redis(redis.clone())),
+            redis: Some(redis),
+            events,
+            audit,
+            mail: Mail::default(),
+            clock,
+        })
+    }
+
+    // ---
+    /// Boot oauth2-server in-process and return its base URL.
+    pub async fn spawn_oauth2_server(&self) -> Result<String> {
+        // ---
+        self.spawn_oauth2_server_with_events(Events::disabled())
+            .await
+    }
+
+    // ---
+    /// Boot oauth2-server in-process, publishing auth events through `events`.
+    pub async fn spawn_oauth2_server_with_events(&self, events: Events) -> Result<String> {
+        // ---
+        let state =
+            oauth2_server::AppState::new(self.pool.clone(), Default::default()).with_events(events);
+
+        serve(oauth2_server::build_router(state)).await
+    }
+
+    // ---
+    /// Boot oauth2-server in-process, reading the time from `clock`.
+    pub async fn spawn_oauth2_server_with_clock(&self, clock: SharedClock) -> Result<String> {
+        // ---
+        let state =
+            oauth2_server::AppState::new(self.pool.clone(), Default::default()).with_clock(clock);
+
+        serve(oauth2_server::build_router(state)).await
+    }
+
+    // ---
+    /// Boot oauth2-server in-process with the admin web UI mounted under
+    /// [`TEST_ADMIN_TOKEN`].
+    pub async fn spawn_oauth2_server_with_admin_ui(&self) -> Result<String> {
+        // ---
+        let state = oauth2_server::AppState::new(self.pool.clone(), Default::default());
+        let admin = tokn_server::AdminConfig {
+            token: Some(TEST_ADMIN_TOKEN.into()),
+        };
+
+        let app = oauth2_server::build_router(state.clone())
+            .merge(oauth2_server::admin_ui_router(&admin, state));
+        serve(app).await
+    }
+
+    // ---
+    /// Boot oauth2-server in-process, sending phone verification codes
+    /// through `sender` (codes are stored in Postgres as in production).
+    pub async fn spawn_oauth2_server_with_sms(&self, sender: RecordingSender) -> Result<String> {
+        // ---
+        let state = oauth2_server::AppState::new(self.pool.clone(), Default::default());
+        let store = oauth2_server::PgOtpStore::new(state.pool.clone(), state.postgres.clone());
+        let otp = Otp::new(
+            sender,
+            "recording",
+            store,
+            Default::default(),
+            state.clock.clone(),
+        );
+
+        serve(oauth2_server::build_router(state.with_otp(otp))).await
+    }
+
+    // ---
+    /// Boot oauth2-server's gRPC introspection in-process and return its
+    /// endpoint URL.
+    pub async fn spawn_oauth2_grpc(&self) -> Result<String> {
+        // ---
+        let state = oauth2_server::AppState::new(self.pool.clone(), Default::default());
+        let service = oauth2_server::IntrospectionService::new(state);
+
+        serve_grpc(TokenIntrospectionServer::new(service)).await
+    }
+
+    // ---
+    /// Boot oauth2-client in-process against the given oauth2-server base URL.
+    ///
+    /// The client's redirect URI is [`DEMO_REDIRECT_URI`] so it matches the
+    /// seeded client registration.
+    pub async fn spawn_oauth2_client(&self, server_url: &str) -> Result<String> {
+        // ---
+        let config = oauth2_client_config(server_url, &self.redis_url);
+
+        serve(oauth2_client::build_router(Arc::new(config))?).await
+    }
+
+    // ---
+    /// Boot all three services, with oauth2-client pointed at oauth2-server.
+    pub async fn spawn_stack(&self) -> Result<Stack> {
+        // ---
+        let jwt_service = self.spawn_jwt_service().await?;
+        let oauth2_server = self.spawn_oauth2_server().await?;
+        let oauth2_client = self.spawn_oauth2_client(&oauth2_server).await?;
+
+        Ok(Stack {
+            jwt_service,
+            oauth2_server,
+            oauth2_client,
+        })
+    }
+}
+
+// ---
+
+/// jwt-service configuration used by the in-process instances, signing with
+/// [`TEST_JWT_SECRET`] and storing state in the Redis at `redis_url`.
+pub fn jwt_config(redis_url: &str) -> jwt_service::Config {
+    // ---
+    jwt_service::Config {
+        profile: Default::default(),
+        server: jwt_service::ServerConfig {
+            host: "127.0.0.1".to_string(),
+            port: 0,
+            bind: None,
+            socket_mode: None,
+            tls: None,
+            compression: Default::default(),
+            cors: Default::default(),
+            security_headers: Default::default(),
+            grpc_addr: None,
+            drain_timeout_seconds: 30,
+        },
+        redis: jwt_service::RedisConfig {
+            url: redis_url.to_string(),
+        },
+        jwt: jwt_service::JwtConfig {
+            algorithm: Default::default(),
+            format: Default::default(),
+            issuer: None,
+            access_token_profile: false,
+            secret: Some(TEST_JWT_SECRET.into()),
+            previous_secret: None,
+            previous_algorithm: None,
+            secret_rotated_at: None,
+            allow_weak_secret: false,
+            private_key_path: None,
+            public_key_path: None,
+            access_token_expiry_seconds: 900,
+            refresh_token_expiry_seconds: 604800,
+            refresh_token_sliding: true,
+            max_session_seconds: None,
+            refresh_bind_device: false,
+            refresh_grace_seconds: 0,
+            cookie_mode: false,
+            validation_leeway_seconds: tokn_core::DEFAULT_LEEWAY_SECONDS,
+            ticket_ttl_seconds: 30,
+            magic_link_ttl_seconds: 900,
+            magic_link_url: None,
+            verify_email_ttl_seconds: 86_400,
+            verify_email_url: None,
+            password_reset_ttl_seconds: 900,
+            password_reset_url: None,
+            stateless: false,
+            keys: Vec::new(),
+            current_kid: None,
+            encryption_key: None,
+        },
+        store: Default::default(),
+        scheduler: Default::default(),
+        startup: Default::default(),
+        circuit_breaker: Default::default(),
+        redis_retry: Default::default(),
+        log: Default::default(),
+        admin: Default::default(),
+        api: Default::default(),
+        events: Default::default(),
+        audit: Default::default(),
+        mail: Default::default(),
+        rate_limit: Default::default(),
+        service_auth: Default::default(),
+        issuer: Default::default(),
+        chaos: Default::default(),
+    }
+}
+
+/// Stateful jwt-service state for `config` with tokens kept in memory, as
+/// `--dev` runs it: refresh, revocation, and sessions with no containers.
+///
+/// # Errors
+///
+/// Returns an error if the signing keys cannot be loaded.
+pub fn jwt_state_in_memory(
+    config: jwt_service::Config,
+    clock: SharedClock,
+) -> Result<jwt_service::AppState> {
+    // ---
+    let mut state = jwt_service::AppState::stateless(config.dev()?, clock)?;
+    state.store = Some(jwt_service::Store::memory(state.clock.clone()));
+    Ok(state)
+}
+
+/// Serve jwt-service from [`jwt_state_in_memory`] for `config` on `clock`
+/// and return its base URL.
+///
+/// # Errors
+///
+/// Returns an error if the signing keys cannot be loaded or the server
+/// cannot bind.
+pub async fn spawn_jwt_service_in_memory(
+    config: jwt_service::Config,
+    clock: SharedClock,
+) -> Result<String> {
+    // ---
+    let state = jwt_state_in_memory(config, clock)?;
+    serve(jwt_service::build_router(state)).await
+}
+
+// ---
+
+/// oauth2-client configuration used by the in-process instances: the seeded
+/// demo client against the oauth2-server at `server_url`.
+pub fn oauth2_client_config(server_url: &str, redis_url: &str) -> oauth2_client::Config {
+    // ---
+    oauth2_client::Config {
+        profile: Default::default(),
+        server: oauth2_client::ServerConfig {
+            host: "127.0.0.1".to_string(),
+            port: 0,
+            bind: None,
+            socket_mode: None,
+            tls: None,
+            compression: Default::default(),
+            cors: Default::default(),
+            security_headers: Default::default(),
+            drain_timeout_seconds: 30,
+        },
+        redis: oauth2_client::RedisConfig {
+            url: redis_url.to_string(),
+        },
+        oauth2: oauth2_client::OAuth2Config {
+            client_id: DEMO_CLIENT_ID.to_string(),
+            client_secret: DEMO_CLIENT_SECRET.into(),
+            redirect_uri: DEMO_REDIRECT_URI.to_string(),
+            authorize_url: format!("{server_url}/v1/oauth/authorize"),
+            token_url: format!("{server_url}/v1/oauth/token"),
+            userinfo_url: format!("{server_url}/v1/oauth/userinfo"),
+        },
+        circuit_breaker: Default::default(),
+        log: Default::default(),
+        admin: Default::default(),
+        i18n: Default::default(),
+        theme: Default::default(),
+        rate_limit: Default::default(),
+        service_auth: Default::default(),
+        chaos: Default::default(),
+    }
+}
+
+// ---
+
+/// Serve a router on an ephemeral localhost port and return its base URL.
+///
+/// As with `tokn_server::serve`, handlers see the peer address as
+/// `ConnectInfo<SocketAddr>`. The server runs on a background task for the
+/// remainder of the test.
+pub async fn serve(app: Router) -> Result<String> {
+    // ---
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+
+    tokio::spawn(async move {
+        // ---
+        let app = app.into_make_service_with_connect_info::<std::net::SocketAddr>();
+        if let Err(e) = axum::serve(listener, app).await {
+            eprintln!("in-process server on {addr} failed: {e}");
+        }
+    });
+
+    Ok(format!("http://{addr}"))
+}
+
+// ---
+
+/// [`EventPublisher`] that keeps every published event in memory, for
+/// asserting what a service emitted.
+#[derive(Clone, Default)]
+pub struct RecordingPublisher {
+    // ---
+    published: Arc<Mutex<Vec<AuthEvent>>>,
+}
+
+impl RecordingPublisher {
+    // ---
+    /// An [`Events`] handle publishing into this recorder as `service`.
+    pub fn events(&self, service: &str) -> Events {
+        // ---
+        Events::start(self.clone(), service, 64)
+    }
+
+    /// Events published so far, waiting briefly for queued ones to arrive.
+    pub async fn wait_for(&self, count: usize) -> Vec<AuthEvent> {
+        // ---
+        for _ in 0..50 {
+            if self.published.lock().unwrap().len() >= count {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        self.published.lock().unwrap().clone()
+    }
+}
+
+impl EventPublisher for RecordingPublisher {
+    // ---
+    async fn publish(&self, _key: Option<&str>, payload: Vec<u8>) -> Result<(), EventsError> {
+        // ---
+        let event = serde_json::from_slice(&payload)?;
+        self.published.lock().unwrap().push(event);
+        Ok(())
+    }
+}
+
+// ---
+
+/// [`Mailer`] that keeps every sent email in memory, for asserting what a
+/// service sent.
+#[derive(Clone, Default)]
+pub struct RecordingMailer {
+    // ---
+    sent: Arc<Mutex<Vec<Email>>>,
+}
+
+impl RecordingMailer {
+    // ---
+    /// A [`Mail`] handle sending into this recorder with the built-in
+    /// templates.
+    pub fn mail(&self) -> Mail {
+        // ---
+        Mail::with_mailer(
+            self.clone(),
+            "recording",
+            &MailConfig::default(),
+            Templates::builtin(),
+        )
+    }
+
+    /// Emails sent so far, waiting briefly for background sends to arrive.
+    pub async fn wait_for(&self, count: usize) -> Vec<Email> {
+        // ---
+        for _ in 0..50 {
+            if self.sent.lock().unwrap().len() >= count {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        self.sent.lock().unwrap().clone()
+    }
+}
+
+impl Mailer for RecordingMailer {
+    // ---
+    async fn send(&self, email: &Email) -> Result<(), MailError> {
+        // ---
+        self.sent.lock().unwrap().push(email.clone());
+        Ok(())
+    }
+}
+
+// ---
+
+/// [`SmsSender`] that keeps every sent text message in memory, for reading
+/// back the one-time codes a service sent.
+#[derive(Clone, Default)]
+pub struct RecordingSender {
+    // ---
+    sent: Arc<Mutex<Vec<(PhoneNumber, String)>>>,
+}
+
+impl RecordingSender {
+    // ---
+    /// Messages sent so far, as `(to, body)`.
+    pub fn sent(&self) -> Vec<(PhoneNumber, String)> {
+        // ---
+        self.sent.lock().unwrap().clone()
+    }
+
+    /// The code in the last message sent to `to`.
+    pub fn last_code(&self, to: &PhoneNumber) -> Option<String> {
+        // ---
+        let sent = self.sent.lock().unwrap();
+        let (_, body) = sent.iter().rev().find(|(phone, _)| phone == to)?;
+        body.split(|c: char| !c.is_ascii_digit())
+            .find(|word| word.len() >= 4)
+            .map(str::to_string)
+    }
+}
+
+impl SmsSender for RecordingSender {
+    // ---
+    async fn send(&self, to: &PhoneNumber, body: &str) -> Result<(), SmsError> {
+        // ---
+        self.sent
+            .lock()
+            .unwrap()
+            .push((to.clone(), body.to_string()));
+        Ok(())
+    }
+}
+
+// ---
+
+/// Serve a gRPC service on an ephemeral localhost port and return its URL.
+///
+/// The server runs on a background task for the remainder of the test.
+pub async fn serve_grpc<S>(service: S) -> Result<String>
+where
+    S: tonic::server::NamedService
+        + tower::Service<
+            http::Request<tonic::body::Body>,
+            Response = http::Response<tonic::body::Body>,
+            Error = std::convert::Infallible,
+        > + Clone
+        + Send
+        + Sync
+        + 'static,
+    S::Future: Send + 'static,
+{
+    // ---
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+
+    tokio::spawn(async move {
+        // ---
+        let result = tonic::transport::Server::builder()
+            .add_service(service)
+            .serve_with_incoming(TcpIncoming::from(listener))
+            .await;
+        if let Err(e) = result {
+            eprintln!("in-process gRPC server on {addr} failed: {e}");
+        }
+    });
+
+    Ok(format!("http://{addr}"))
+}
+
+// ---
+
+/// HTTP client that does not follow redirects, so tests can assert on them.
+pub fn http_client() -> reqwest::Client {
+    // ---
+    reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .expect("Failed to build HTTP client")
+}
+
+/// POST `body` as JSON to `path` on `base`, returning the response status and
+/// JSON body.
+pub async fn post_json(base: &str, path: &str, body: Value) -> Result<(StatusCode, Value)> {
+    // ---
+    let response = http_client()
+        .post(format!("{base}{path}"))
+        .json(&body)
+        .send()
+        .await?;
+    Ok((response.status(), response.json().await?))
+}
+
+/// Sign `user_id` in at the jwt-service at `base`, expecting 200 OK, and
+/// return the token response.
+pub async fn sign_in(base: &str, user_id: &str) -> Result<Value> {
+    // ---
+    let (status, tokens) = post_json(
+        base,
+        "/v1/auth/token",
+        json!({ "user_id": user_id, "email": "u@example.com" }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{tokens}");
+    Ok(tokens)
+}
+
+// ---
+
+/// Return the first value of a query parameter in a URL.
+pub fn query_param(url: &reqwest::Url, name: &str) -> Option<String> {
+    // ---
+    url.query_pairs()
+        .find(|(k, _)| k == name)
+        .map(|(_, v)| v.into_owned())
+}
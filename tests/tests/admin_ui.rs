@@ -0,0 +1,222 @@
+// tests/tests/admin_ui.rs
+
+//! oauth2-server's admin web UI: sign-in, anti-forgery checks, and managing
+//! clients and sessions against a real Postgres
+
+use anyhow::{Context, Result};
+use reqwest::header::{COOKIE, LOCATION, SET_COOKIE};
+use reqwest::StatusCode;
+use serde_json::Value;
+use sqlx::postgres::PgPoolOptions;
+use std::sync::Arc;
+use tokn_tests::{
+    http_client, query_param, serve, TestEnv, DEMO_CLIENT_ID, DEMO_CLIENT_SECRET,
+    DEMO_REDIRECT_URI, TEST_ADMIN_TOKEN,
+};
+
+// ---
+
+/// oauth2-server with the admin UI over a pool that is never connected; the
+/// requests under test are turned away before any query.
+async fn spawn_without_database(token: Option<&str>) -> Result<String> {
+    // ---
+    let pool = PgPoolOptions::new().connect_lazy("postgres://unused@127.0.0.1:1/unused")?;
+    let state = oauth2_server::AppState::new(Arc::new(pool), Default::default());
+    let admin = tokn_server::AdminConfig {
+        token: token.map(Into::into),
+    };
+    serve(oauth2_server::admin_ui_router(&admin, state)).await
+}
+
+/// Sign in and return the `Cookie` header value for the session.
+async fn sign_in(http: &reqwest::Client, base: &str) -> Result<String> {
+    // ---
+    let response = http
+        .post(format!("{base}/admin/ui/login"))
+        .form(&[("token", TEST_ADMIN_TOKEN)])
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+
+    let cookie = response.headers()[SET_COOKIE].to_str()?;
+    assert!(cookie.contains("HttpOnly"));
+    assert!(cookie.contains("SameSite=Strict"));
+    assert!(!cookie.contains(TEST_ADMIN_TOKEN));
+    Ok(cookie.split(';').next().unwrap().to_string())
+}
+
+/// Fetch an admin page and return its HTML.
+async fn page(http: &reqwest::Client, base: &str, path: &str, cookie: &str) -> Result<String> {
+    // ---
+    let response = http
+        .get(format!("{base}{path}"))
+        .header(COOKIE, cookie)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK, "{path}");
+    assert_eq!(response.headers()["cache-control"], "no-store");
+    Ok(response.text().await?)
+}
+
+/// The anti-forgery value embedded in a page's forms.
+fn csrf(html: &str) -> Result<String> {
+    // ---
+    let start = html
+        .find(r#"name="csrf" value=""#)
+        .context("page has a form")?
+        + r#"name="csrf" value=""#.len();
+    let end = html[start..].find('"').context("unterminated value")?;
+    Ok(html[start..start + end].to_string())
+}
+
+// ---
+
+#[tokio::test]
+async fn pages_require_sign_in() -> Result<()> {
+    // ---
+    let base = spawn_without_database(Some(TEST_ADMIN_TOKEN)).await?;
+    let http = http_client();
+
+    for path in ["/admin/ui", "/admin/ui/clients", "/admin/ui/sessions"] {
+        let response = http.get(format!("{base}{path}")).send().await?;
+        assert_eq!(response.status(), StatusCode::SEE_OTHER, "{path}");
+        assert_eq!(response.headers()[LOCATION], "/admin/ui/login");
+    }
+
+    // A forged cookie does not pass either
+    let response = http
+        .get(format!("{base}/admin/ui/clients"))
+        .header(COOKIE, "tokn_admin=forged")
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+
+    let response = http
+        .post(format!("{base}/admin/ui/login"))
+        .form(&[("token", "not-the-admin-token")])
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert!(response.headers().get(SET_COOKIE).is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn forms_require_anti_forgery_value() -> Result<()> {
+    // ---
+    let base = spawn_without_database(Some(TEST_ADMIN_TOKEN)).await?;
+    let http = http_client();
+    let cookie = sign_in(&http, &base).await?;
+
+    let response = http
+        .post(format!("{base}/admin/ui/sessions/revoke-user"))
+        .header(COOKIE, &cookie)
+        .form(&[("user_id", "user_001"), ("csrf", "forged")])
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    Ok(())
+}
+
+#[tokio::test]
+async fn ui_is_not_mounted_without_admin_token() -> Result<()> {
+    // ---
+    let base = spawn_without_database(None).await?;
+
+    let response = http_client()
+        .get(format!("{base}/admin/ui/login"))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    Ok(())
+}
+
+// ---
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn register_client_and_revoke_session() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let base = env.spawn_oauth2_server_with_admin_ui().await?;
+    let http = http_client();
+    let cookie = sign_in(&http, &base).await?;
+
+    // ---
+    // Register a client; its secret is shown once
+    let clients = page(&http, &base, "/admin/ui/clients", &cookie).await?;
+    assert!(clients.contains(DEMO_CLIENT_ID));
+    let created = http
+        .post(format!("{base}/admin/ui/clients"))
+        .header(COOKIE, &cookie)
+        .form(&[
+            ("csrf", csrf(&clients)?.as_str()),
+            ("client_id", "ui_client"),
+            ("redirect_uri", "https://app.example.com/callback"),
+        ])
+        .send()
+        .await?;
+    assert_eq!(created.status(), StatusCode::OK);
+    let created = created.text().await?;
+    assert!(created.contains("Client secret (shown once)"));
+    assert!(!page(&http, &base, "/admin/ui/clients", &cookie)
+        .await?
+        .contains("shown once"));
+
+    // ---
+    // Issue an access token for the demo user
+    let response = http
+        .post(format!("{base}/v1/oauth/authorize"))
+        .form(&[
+            ("client_id", DEMO_CLIENT_ID),
+            ("redirect_uri", DEMO_REDIRECT_URI),
+            ("scope", "profile"),
+            ("state", "xyz"),
+            ("action", "approve"),
+        ])
+        .send()
+        .await?;
+    let location = reqwest::Url::parse(response.headers()[LOCATION].to_str()?)?;
+    let code = query_param(&location, "code").unwrap();
+    let token: Value = http
+        .post(format!("{base}/v1/oauth/token"))
+        .form(&[
+            ("grant_type", "authorization_code"),
+            ("code", &code),
+            ("redirect_uri", DEMO_REDIRECT_URI),
+            ("client_id", DEMO_CLIENT_ID),
+            ("client_secret", DEMO_CLIENT_SECRET),
+        ])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let access_token = token["access_token"].as_str().unwrap();
+
+    // ---
+    // The session is listed by fingerprint, never by token
+    let sessions = page(&http, &base, "/admin/ui/sessions?user_id=user_001", &cookie).await?;
+    assert!(sessions.contains(DEMO_CLIENT_ID));
+    assert!(!sessions.contains(access_token));
+
+    let revoked = http
+        .post(format!("{base}/admin/ui/sessions/revoke-user"))
+        .header(COOKIE, &cookie)
+        .form(&[("csrf", csrf(&sessions)?.as_str()), ("user_id", "user_001")])
+        .send()
+        .await?;
+    assert_eq!(revoked.status(), StatusCode::SEE_OTHER);
+    assert_eq!(
+        revoked.headers()[LOCATION],
+        "/admin/ui/sessions?user_id=user_001"
+    );
+
+    let userinfo = http
+        .get(format!("{base}/v1/oauth/userinfo"))
+        .bearer_auth(access_token)
+        .send()
+        .await?;
+    assert_eq!(userinfo.status(), StatusCode::UNAUTHORIZED);
+    Ok(())
+}
@@ -0,0 +1,203 @@
+// tests/tests/api_keys.rs
+
+//! API keys for machine clients: created through `/v1/auth/apikeys` with an
+//! access token, then sent in `X-Api-Key` instead of one, until deleted or
+//! revoked with the user's tokens
+
+use anyhow::Result;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use tokn_core::{SystemClock, TestClock};
+use tokn_tests::{http_client, jwt_config, spawn_jwt_service_in_memory, TestEnv};
+
+// ---
+
+const NOW: i64 = 1_700_000_000;
+
+// ---
+
+/// An access token for `user_id` with `scope` from jwt-service at `base`.
+async fn access_token(base: &str, user_id: &str, scope: &str) -> Result<String> {
+    // ---
+    let issued: Value = http_client()
+        .post(format!("{base}/v1/auth/token"))
+        .json(&json!({ "user_id": user_id, "email": "u@example.com", "scope": scope }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(issued["access_token"].as_str().unwrap().to_string())
+}
+
+/// GET `/v1/protected` on `base` with `api_key`.
+async fn protected_with_key(base: &str, api_key: &str) -> Result<reqwest::Response> {
+    // ---
+    Ok(http_client()
+        .get(format!("{base}/v1/protected"))
+        .header("X-Api-Key", api_key)
+        .send()
+        .await?)
+}
+
+// ---
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn api_keys_authenticate_until_deleted() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let config = jwt_config(&env.redis_url);
+    let base = env
+        .spawn_jwt_service_with_config(config, SystemClock::shared())
+        .await?;
+    let http = http_client();
+    let token = access_token(&base, "user_1", "orders:read orders:write").await?;
+
+    let created: Value = http
+        .post(format!("{base}/v1/auth/apikeys"))
+        .bearer_auth(&token)
+        .json(&json!({ "name": "nightly-export", "scope": "orders:read" }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let api_key = created["api_key"].as_str().unwrap();
+    let id = created["id"].as_str().unwrap();
+    assert!(api_key.starts_with("tokn_"), "{created}");
+    assert_eq!(created["user_id"], "user_1");
+
+    // The key acts as its user, with its own scope
+    let response = protected_with_key(&base, api_key).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let profile: Value = response.json().await?;
+    assert_eq!(profile["user_id"], "user_1");
+    assert_eq!(profile["scope"], "orders:read");
+
+    // Keys cannot mint keys
+    let response = http
+        .post(format!("{base}/v1/auth/apikeys"))
+        .header("X-Api-Key", api_key)
+        .json(&json!({ "name": "escalation" }))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    // Listed without the key itself
+    let listed: Value = http
+        .get(format!("{base}/v1/auth/apikeys"))
+        .bearer_auth(&token)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    assert_eq!(listed[0]["id"], id);
+    assert!(!listed.to_string().contains(api_key));
+
+    // A deleted key authenticates nothing
+    let response = http
+        .delete(format!("{base}/v1/auth/apikeys/{id}"))
+        .bearer_auth(&token)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let response = protected_with_key(&base, api_key).await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn api_keys_are_limited_to_the_caller() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let config = jwt_config(&env.redis_url);
+    let base = env
+        .spawn_jwt_service_with_config(config, SystemClock::shared())
+        .await?;
+    let http = http_client();
+    let token = access_token(&base, "user_1", "orders:read").await?;
+
+    // No scope beyond the caller's, and no key for another user
+    for request in [
+        json!({ "name": "wider", "scope": "orders:write" }),
+        json!({ "name": "someone-else", "user_id": "user_2" }),
+    ] {
+        let response = http
+            .post(format!("{base}/v1/auth/apikeys"))
+            .bearer_auth(&token)
+            .json(&request)
+            .send()
+            .await?;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN, "{request}");
+    }
+
+    // Admins may do both, and the key is not the caller's to see
+    let admin = access_token(&base, "admin_1", "admin").await?;
+    let created: Value = http
+        .post(format!("{base}/v1/auth/apikeys"))
+        .bearer_auth(&admin)
+        .json(&json!({ "name": "for-user-2", "user_id": "user_2", "scope": "orders:write" }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let id = created["id"].as_str().unwrap();
+
+    let listed: Value = http
+        .get(format!("{base}/v1/auth/apikeys"))
+        .bearer_auth(&token)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    assert_eq!(listed, json!([]));
+    let response = http
+        .delete(format!("{base}/v1/auth/apikeys/{id}"))
+        .bearer_auth(&token)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    Ok(())
+}
+
+#[tokio::test]
+async fn invalidating_a_user_revokes_their_api_keys() -> Result<()> {
+    // ---
+    let clock = TestClock::at_timestamp(NOW);
+    let base = spawn_jwt_service_in_memory(jwt_config("redis://unused"), clock.shared()).await?;
+    let http = http_client();
+    let token = access_token(&base, "user_1", "orders:read").await?;
+
+    let created: Value = http
+        .post(format!("{base}/v1/auth/apikeys"))
+        .bearer_auth(&token)
+        .json(&json!({ "name": "nightly-export" }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let api_key = created["api_key"].as_str().unwrap();
+    let response = protected_with_key(&base, api_key).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let invalidated: Value = http
+        .post(format!("{base}/v1/auth/invalidate-user"))
+        .bearer_auth(&token)
+        .json(&json!({ "user_id": "user_1" }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    assert_eq!(invalidated["api_keys_deleted"], 1, "{invalidated}");
+
+    let response = protected_with_key(&base, api_key).await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    Ok(())
+}
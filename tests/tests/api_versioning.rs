@@ -0,0 +1,134 @@
+// tests/tests/api_versioning.rs
+
+//! `/v1` routing and the deprecated unversioned paths, against a stateless
+//! jwt-service (no containers needed)
+
+use anyhow::Result;
+use reqwest::{Response, StatusCode};
+use serde_json::{json, Value};
+use tokn_core::SystemClock;
+use tokn_events::Events;
+use tokn_tests::{http_client, jwt_config, serve};
+
+// ---
+
+/// Serve a stateless jwt-service, with or without the legacy paths.
+async fn spawn_stateless(legacy_paths: bool) -> Result<String> {
+    // ---
+    let mut config = jwt_config("redis://unused");
+    config.jwt.stateless = true;
+    config.api.legacy_paths = legacy_paths;
+
+    let state = jwt_service::AppState {
+        keys: config.jwt.keys()?.into(),
+        config: config.into(),
+        store: None,
+        redis: None,
+        events: Events::disabled(),
+        audit: Default::default(),
+        mail: Default::default(),
+        clock: SystemClock::shared(),
+    };
+    serve(jwt_service::build_router(state)).await
+}
+
+async fn issue(base: &str, path: &str) -> Result<Response> {
+    // ---
+    Ok(http_client()
+        .post(format!("{base}{path}"))
+        .json(&json!({ "user_id": "user_1", "email": "u@example.com" }))
+        .send()
+        .await?)
+}
+
+// ---
+
+#[tokio::test]
+async fn versioned_paths_are_not_deprecated() -> Result<()> {
+    // ---
+    let base = spawn_stateless(true).await?;
+
+    let issued = issue(&base, "/v1/auth/token").await?;
+    assert_eq!(issued.status(), StatusCode::OK);
+    assert!(issued.headers().get("deprecation").is_none());
+    let token = issued.json::<Value>().await?["access_token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let protected = http_client()
+        .get(format!("{base}/v1/protected"))
+        .bearer_auth(&token)
+        .send()
+        .await?;
+    assert_eq!(protected.status(), StatusCode::OK);
+    assert!(protected.headers().get("deprecation").is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn legacy_paths_still_work_and_point_at_v1() -> Result<()> {
+    // ---
+    let base = spawn_stateless(true).await?;
+
+    let issued = issue(&base, "/auth/token").await?;
+    assert_eq!(issued.status(), StatusCode::OK);
+    assert_eq!(issued.headers()["deprecation"], "true");
+    assert_eq!(
+        issued.headers()["link"],
+        r#"</v1/auth/token>; rel="successor-version""#
+    );
+    let token = issued.json::<Value>().await?["access_token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    // Tokens are interchangeable between the two paths
+    let validated = http_client()
+        .post(format!("{base}/v1/auth/validate"))
+        .json(&json!({ "token": token }))
+        .send()
+        .await?;
+    assert_eq!(validated.status(), StatusCode::OK);
+
+    let protected = http_client()
+        .get(format!("{base}/protected"))
+        .bearer_auth(&token)
+        .send()
+        .await?;
+    assert_eq!(protected.status(), StatusCode::OK);
+    assert_eq!(protected.headers()["deprecation"], "true");
+
+    // Rejections on legacy paths are marked too
+    let missing = http_client()
+        .get(format!("{base}/protected"))
+        .send()
+        .await?;
+    assert_eq!(missing.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(missing.headers()["deprecation"], "true");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn legacy_paths_can_be_switched_off() -> Result<()> {
+    // ---
+    let base = spawn_stateless(false).await?;
+
+    assert_eq!(
+        issue(&base, "/auth/token").await?.status(),
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        issue(&base, "/v1/auth/token").await?.status(),
+        StatusCode::OK
+    );
+
+    // Unversioned service endpoints are unaffected
+    let health = http_client().get(format!("{base}/health")).send().await?;
+    assert_eq!(health.status(), StatusCode::OK);
+    assert!(health.headers().get("deprecation").is_none());
+
+    Ok(())
+}
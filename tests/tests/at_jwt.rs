@@ -0,0 +1,156 @@
+// tests/tests/at_jwt.rs
+
+//! JWT access token profile (RFC 9068): with `jwt.access_token_profile` set,
+//! access tokens are typed `at+jwt` and carry `iss`, `aud`, and `client_id`,
+//! and validation refuses tokens of another type (in-memory store)
+
+use anyhow::Result;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use tokn_core::{Claims, JwtKeys, SystemClock, TestClock, TokenError};
+use tokn_tests::{jwt_config, post_json, spawn_jwt_service_in_memory, TEST_JWT_SECRET};
+
+// ---
+
+const NOW: i64 = 1_700_000_000;
+
+const ISSUER: &str = "https://auth.example.com";
+
+// ---
+
+/// [`jwt_config`] issuing `at+jwt` access tokens.
+fn config() -> jwt_service::Config {
+    // ---
+    let mut config = jwt_config("redis://unused");
+    config.jwt.issuer = Some(ISSUER.into());
+    config.jwt.access_token_profile = true;
+    config
+}
+
+/// The decoded header of JWT `token`.
+fn header(token: &str) -> Result<Value> {
+    // ---
+    let encoded = token.split('.').next().unwrap_or_default();
+    Ok(serde_json::from_slice(&URL_SAFE_NO_PAD.decode(encoded)?)?)
+}
+
+// ---
+
+#[test]
+fn profile_keys_type_access_tokens_and_refuse_others() -> Result<()> {
+    // ---
+    let keys = JwtKeys::hs256(TEST_JWT_SECRET).with_access_token_profile();
+    let claims = Claims::new("user_1".into(), "u@example.com".into(), 900, &SystemClock);
+
+    let token = keys.sign(&claims)?;
+    assert_eq!(header(&token)?["typ"], "at+jwt");
+    assert_eq!(keys.verify(&token, &SystemClock)?.sub, "user_1");
+
+    // A plain JWT signed with the same key is not an access token
+    let plain = JwtKeys::hs256(TEST_JWT_SECRET).sign(&claims)?;
+    assert_eq!(header(&plain)?["typ"], "JWT");
+    assert!(matches!(
+        keys.verify(&plain, &SystemClock),
+        Err(TokenError::WrongType)
+    ));
+
+    // Single-purpose tokens keep the plain type
+    let verification = keys.sign(&claims.with_purpose("verify_email"))?;
+    assert_eq!(header(&verification)?["typ"], "JWT");
+    assert!(keys
+        .verify_purpose(&verification, "verify_email", &SystemClock)
+        .is_ok());
+    Ok(())
+}
+
+#[tokio::test]
+async fn issued_tokens_carry_the_profile_claims() -> Result<()> {
+    // ---
+    let clock = TestClock::at_timestamp(NOW);
+    let base = spawn_jwt_service_in_memory(config(), clock.shared()).await?;
+    let (status, tokens) = post_json(
+        &base,
+        "/v1/auth/token",
+        json!({
+            "user_id": "user_1",
+            "email": "u@example.com",
+            "audience": "orders-api",
+            "client_id": "web-app",
+        }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{tokens}");
+    let token = tokens["access_token"].as_str().unwrap();
+    assert_eq!(header(token)?["typ"], "at+jwt");
+
+    let (status, body) = post_json(&base, "/v1/auth/validate", json!({ "token": token })).await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let claims = &body["claims"];
+    assert_eq!(claims["iss"], ISSUER);
+    assert_eq!(claims["aud"], "orders-api");
+    assert_eq!(claims["client_id"], "web-app");
+    for claim in ["sub", "exp", "iat", "jti"] {
+        assert!(claims.get(claim).is_some(), "{claim} missing: {claims}");
+    }
+
+    // Refreshed tokens keep the client and audience
+    let (status, refreshed) = post_json(
+        &base,
+        "/v1/auth/refresh",
+        json!({ "refresh_token": tokens["refresh_token"] }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{refreshed}");
+    let (status, body) = post_json(
+        &base,
+        "/v1/auth/validate",
+        json!({ "token": refreshed["access_token"] }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["claims"]["iss"], ISSUER);
+    assert_eq!(body["claims"]["client_id"], "web-app");
+    Ok(())
+}
+
+#[tokio::test]
+async fn requests_without_audience_or_client_are_refused() -> Result<()> {
+    // ---
+    let clock = TestClock::at_timestamp(NOW);
+    let base = spawn_jwt_service_in_memory(config(), clock.shared()).await?;
+    for (request, missing) in [
+        (
+            json!({ "user_id": "user_1", "email": "u@example.com", "client_id": "web-app" }),
+            "audience",
+        ),
+        (
+            json!({ "user_id": "user_1", "email": "u@example.com", "audience": "orders-api" }),
+            "client_id",
+        ),
+    ] {
+        let (status, body) = post_json(&base, "/v1/auth/token", request).await?;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body["detail"],
+            format!("{missing} is required for at+jwt access tokens")
+        );
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn validation_refuses_tokens_not_typed_at_jwt() -> Result<()> {
+    // ---
+    let clock = TestClock::at_timestamp(NOW);
+    let base = spawn_jwt_service_in_memory(config(), clock.shared()).await?;
+    let claims = Claims::new("user_1".into(), "u@example.com".into(), 900, &clock)
+        .with_audience(vec!["orders-api".into()]);
+    let plain = JwtKeys::hs256(TEST_JWT_SECRET).sign(&claims)?;
+
+    let (status, body) = post_json(&base, "/v1/auth/validate", json!({ "token": plain })).await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["detail"], "Token type is not at+jwt", "{body}");
+    Ok(())
+}
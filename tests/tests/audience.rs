@@ -0,0 +1,170 @@
+// tests/tests/audience.rs
+
+//! Audience-restricted tokens: jwt-service issues `aud` as a string or an
+//! array, keeps it across refresh, and `POST /v1/auth/validate` and the
+//! `tokn-auth` layer refuse tokens minted for other services (in-memory
+//! store)
+
+use anyhow::Result;
+use axum::{routing::get, Router};
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use tokn_auth::{AuthenticatedUser, JwtAuth, JwtAuthLayer, Verifier};
+use tokn_core::{check_audience, Claims, JwtKeys, SystemClock, TestClock, TokenError};
+use tokn_tests::{
+    http_client, jwt_config, post_json, serve, spawn_jwt_service_in_memory, TEST_JWT_SECRET,
+};
+
+// ---
+
+const NOW: i64 = 1_700_000_000;
+
+// ---
+
+/// Sign `user_1` in for `audience`, returning the token response.
+async fn sign_in_for(base: &str, audience: Value) -> Result<Value> {
+    // ---
+    let (status, tokens) = post_json(
+        base,
+        "/v1/auth/token",
+        json!({ "user_id": "user_1", "email": "u@example.com", "audience": audience }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{tokens}");
+    Ok(tokens)
+}
+
+// ---
+
+#[test]
+fn aud_is_a_string_for_one_audience_and_an_array_for_several() -> Result<()> {
+    // ---
+    let one = Claims::new("user_1".into(), "u@example.com".into(), 900, &SystemClock)
+        .with_audience(vec!["orders-api".into()]);
+    assert_eq!(serde_json::to_value(&one)?["aud"], "orders-api");
+
+    let many = one
+        .clone()
+        .with_audience(vec!["orders-api".into(), "billing-api".into()]);
+    assert_eq!(
+        serde_json::to_value(&many)?["aud"],
+        json!(["orders-api", "billing-api"])
+    );
+
+    // Both forms survive signing and verification
+    let keys = JwtKeys::hs256(TEST_JWT_SECRET);
+    let verified = keys.verify(&keys.sign(&many)?, &SystemClock)?;
+    assert_eq!(verified.aud, ["orders-api", "billing-api"]);
+    assert!(check_audience(&verified, "billing-api").is_ok());
+    assert!(matches!(
+        check_audience(&verified, "admin-api"),
+        Err(TokenError::WrongAudience)
+    ));
+
+    let unrestricted = Claims::new("user_1".into(), "u@example.com".into(), 900, &SystemClock);
+    assert!(serde_json::to_value(&unrestricted)?.get("aud").is_none());
+    assert!(check_audience(&unrestricted, "orders-api").is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn validation_refuses_tokens_for_other_audiences() -> Result<()> {
+    // ---
+    let clock = TestClock::at_timestamp(NOW);
+    let base = spawn_jwt_service_in_memory(jwt_config("redis://unused"), clock.shared()).await?;
+    let tokens = sign_in_for(&base, json!(["orders-api", "billing-api"])).await?;
+    let token = &tokens["access_token"];
+
+    let (status, body) = post_json(
+        &base,
+        "/v1/auth/validate",
+        json!({ "token": token, "audience": "orders-api" }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["claims"]["aud"], json!(["orders-api", "billing-api"]));
+
+    let (status, body) = post_json(
+        &base,
+        "/v1/auth/validate",
+        json!({ "token": token, "audience": "admin-api" }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        body["detail"], "Token is not valid for this audience",
+        "{body}"
+    );
+
+    // Without an expected audience, any valid token passes
+    let (status, _) = post_json(&base, "/v1/auth/validate", json!({ "token": token })).await?;
+    assert_eq!(status, StatusCode::OK);
+
+    // Tokens without `aud` are for no audience in particular
+    let unrestricted = sign_in_for(&base, Value::Null).await?;
+    let (status, _) = post_json(
+        &base,
+        "/v1/auth/validate",
+        json!({ "token": unrestricted["access_token"], "audience": "orders-api" }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    Ok(())
+}
+
+#[tokio::test]
+async fn refreshed_tokens_keep_their_audience() -> Result<()> {
+    // ---
+    let clock = TestClock::at_timestamp(NOW);
+    let base = spawn_jwt_service_in_memory(jwt_config("redis://unused"), clock.shared()).await?;
+    let tokens = sign_in_for(&base, json!("orders-api")).await?;
+
+    let (status, refreshed) = post_json(
+        &base,
+        "/v1/auth/refresh",
+        json!({ "refresh_token": tokens["refresh_token"] }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{refreshed}");
+    let (status, body) = post_json(
+        &base,
+        "/v1/auth/validate",
+        json!({ "token": refreshed["access_token"], "audience": "orders-api" }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["claims"]["aud"], "orders-api");
+    Ok(())
+}
+
+#[tokio::test]
+async fn the_auth_layer_refuses_tokens_for_other_audiences() -> Result<()> {
+    // ---
+    async fn whoami(user: AuthenticatedUser) -> String {
+        user.sub.clone()
+    }
+
+    let auth = JwtAuth::new(Verifier::secret(TEST_JWT_SECRET)).audience("orders-api");
+    let app = Router::new()
+        .route("/whoami", get(whoami))
+        .route_layer(JwtAuthLayer::new(auth.clone()))
+        .with_state(auth);
+    let base = serve(app).await?;
+
+    let keys = JwtKeys::hs256(TEST_JWT_SECRET);
+    let claims = Claims::new("user_1".into(), "u@example.com".into(), 900, &SystemClock);
+    for (audience, expected) in [
+        (vec!["orders-api".to_string()], StatusCode::OK),
+        (vec!["billing-api".to_string()], StatusCode::UNAUTHORIZED),
+        (vec![], StatusCode::UNAUTHORIZED),
+    ] {
+        let token = keys.sign(&claims.clone().with_audience(audience.clone()))?;
+        let response = http_client()
+            .get(format!("{base}/whoami"))
+            .bearer_auth(token)
+            .send()
+            .await?;
+        assert_eq!(response.status(), expected, "{audience:?}");
+    }
+    Ok(())
+}
@@ -0,0 +1,177 @@
+// tests/tests/audit.rs
+
+//! jwt-service audit log: config validation, entries appended to a JSON lines
+//! file by a stateless service, and to a Redis stream against real Redis
+
+use anyhow::Result;
+use jwt_service::{validate_audit_config, AuditConfig, AuditLog, AuditSink};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokn_core::SystemClock;
+use tokn_tests::{http_client, jwt_config, serve, TestEnv};
+
+// ---
+
+/// A fresh audit file path for test `name`.
+fn audit_file(name: &str) -> PathBuf {
+    // ---
+    let path = std::env::temp_dir().join(format!("tokn-audit-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+/// The entries in `path`, waiting until there are at least `count`.
+async fn entries(path: &Path, count: usize) -> Result<Vec<Value>> {
+    // ---
+    for _ in 0..50 {
+        let text = tokio::fs::read_to_string(path).await.unwrap_or_default();
+        let lines: Vec<&str> = text.lines().collect();
+        if lines.len() >= count {
+            return lines
+                .into_iter()
+                .map(|line| Ok(serde_json::from_str(line)?))
+                .collect();
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    anyhow::bail!("fewer than {count} audit entries in {}", path.display())
+}
+
+// ---
+
+#[test]
+fn file_sink_requires_a_path() {
+    // ---
+    let config = AuditConfig {
+        sink: AuditSink::File,
+        ..Default::default()
+    };
+    assert!(validate_audit_config(&config).is_err());
+
+    let config = AuditConfig {
+        sink: AuditSink::File,
+        file: Some(audit_file("config")),
+        ..Default::default()
+    };
+    assert!(validate_audit_config(&config).is_ok());
+
+    let config = AuditConfig {
+        stream_max_len: 0,
+        ..Default::default()
+    };
+    assert!(validate_audit_config(&config).is_err());
+}
+
+#[tokio::test]
+async fn file_sink_records_issued_tokens_and_failed_validations() -> Result<()> {
+    // ---
+    let path = audit_file("stateless");
+    let mut config = jwt_config("redis://unused");
+    config.jwt.stateless = true;
+    config.audit = AuditConfig {
+        sink: AuditSink::File,
+        file: Some(path.clone()),
+        ..Default::default()
+    };
+
+    let audit = AuditLog::open(&config.audit, "redis://unused").await?;
+    let mut state = jwt_service::AppState::stateless(config, SystemClock::shared())?;
+    state.audit = audit;
+    let base = serve(jwt_service::build_router(state)).await?;
+    let http = http_client();
+
+    http.post(format!("{base}/v1/auth/token"))
+        .json(&json!({ "user_id": "user_audit", "email": "a@example.com" }))
+        .send()
+        .await?
+        .error_for_status()?;
+    let response = http
+        .post(format!("{base}/v1/auth/validate"))
+        .header("x-forwarded-for", "203.0.113.7")
+        .json(&json!({ "token": "not-a-jwt" }))
+        .send()
+        .await?;
+    assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+
+    let entries = entries(&path, 2).await?;
+    let (issued, failed) = (&entries[0], &entries[1]);
+    assert_eq!(issued["kind"], "token_issued");
+    assert_eq!(issued["user_id"], "user_audit");
+    assert_eq!(issued["client_ip"], "127.0.0.1");
+    assert!(issued["occurred_at"].is_string());
+    assert!(issued["jti"].is_string());
+
+    assert_eq!(failed["kind"], "validation_failed");
+    assert_eq!(failed["forwarded_for"], "203.0.113.7");
+    assert!(failed["reason"].is_string());
+    assert!(failed.get("user_id").is_none());
+
+    let _ = std::fs::remove_file(&path);
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn redis_sink_records_refresh_and_revocation() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let mut config = jwt_config(&env.redis_url);
+    config.audit.sink = AuditSink::Redis;
+    config.audit.stream = "tokn:audit:it".to_string();
+    let base = env
+        .spawn_jwt_service_with_config(config, SystemClock::shared())
+        .await?;
+    let http = http_client();
+
+    let tokens: Value = http
+        .post(format!("{base}/v1/auth/token"))
+        .json(&json!({ "user_id": "user_it", "email": "it@example.com" }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let refreshed: Value = http
+        .post(format!("{base}/v1/auth/refresh"))
+        .json(&json!({ "refresh_token": tokens["refresh_token"] }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    http.post(format!("{base}/v1/auth/revoke"))
+        .json(&json!({ "token": refreshed["access_token"] }))
+        .send()
+        .await?
+        .error_for_status()?;
+
+    let mut redis = redis::Client::open(env.redis_url.as_str())?
+        .get_multiplexed_async_connection()
+        .await?;
+    let mut stream = Vec::new();
+    for _ in 0..50 {
+        stream = redis::cmd("XRANGE")
+            .arg("tokn:audit:it")
+            .arg("-")
+            .arg("+")
+            .query_async::<Vec<(String, Vec<String>)>>(&mut redis)
+            .await?;
+        if stream.len() >= 3 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    let kinds: Vec<&str> = stream
+        .iter()
+        .map(|(_, fields)| fields[1].as_str())
+        .collect();
+    assert_eq!(kinds, ["token_issued", "token_refreshed", "token_revoked"]);
+    for (_, fields) in &stream {
+        let user = fields.iter().position(|field| field == "user_id").unwrap();
+        assert_eq!(fields[user + 1], "user_it");
+        assert!(fields.iter().any(|field| field == "client_ip"));
+    }
+    Ok(())
+}
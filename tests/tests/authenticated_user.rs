@@ -0,0 +1,66 @@
+// tests/tests/authenticated_user.rs
+
+//! The `AuthenticatedUser` extractor on a downstream route outside the JWT
+//! middleware, on a stateless jwt-service (no containers needed)
+
+use anyhow::Result;
+use axum::{routing::get, Router};
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use tokn_core::{generate_token, Claims, SystemClock};
+use tokn_tests::{http_client, jwt_config, serve};
+
+// ---
+
+async fn whoami(user: jwt_service::AuthenticatedUser) -> String {
+    // ---
+    format!("{} <{}>", user.sub, user.email)
+}
+
+// ---
+
+#[tokio::test]
+async fn handlers_take_validated_claims() -> Result<()> {
+    // ---
+    let mut config = jwt_config("redis://unused");
+    config.jwt.stateless = true;
+    let state = jwt_service::AppState::stateless(config, SystemClock::shared())?;
+    let app = Router::new()
+        .route("/whoami", get(whoami))
+        .with_state(state.clone())
+        .merge(jwt_service::build_router(state));
+    let base = serve(app).await?;
+    let http = http_client();
+
+    let tokens: Value = http
+        .post(format!("{base}/v1/auth/token"))
+        .json(&json!({ "user_id": "user_1", "email": "u@example.com" }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let response = http
+        .get(format!("{base}/whoami"))
+        .bearer_auth(tokens["access_token"].as_str().unwrap())
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text().await?, "user_1 <u@example.com>");
+
+    // Missing and forged tokens are refused with problem details
+    let response = http.get(format!("{base}/whoami")).send().await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let problem: Value = response.json().await?;
+    assert_eq!(problem["status"], 401, "{problem}");
+
+    let claims = Claims::new("user_1".into(), "u@example.com".into(), 900, &SystemClock);
+    let forged = generate_token(&claims, "Zr8Kd2Wq6Vn1Xt5Mb9Hc3Lp7Fj0Gs4Ya")?;
+    let response = http
+        .get(format!("{base}/whoami"))
+        .bearer_auth(forged)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    Ok(())
+}
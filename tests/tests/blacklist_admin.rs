@@ -0,0 +1,151 @@
+// tests/tests/blacklist_admin.rs
+
+//! `/admin/blacklist` on jwt-service: operators list revoked access tokens
+//! with their TTLs, look one up, and take one off the blacklist, behind the
+//! admin token (in-memory store)
+
+use anyhow::Result;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use tokn_core::TestClock;
+use tokn_tests::{http_client, jwt_config, spawn_jwt_service_in_memory, TEST_ADMIN_TOKEN};
+
+// ---
+
+const NOW: i64 = 1_700_000_000;
+
+// ---
+
+/// Serve jwt-service with the admin API from an in-memory store.
+async fn start() -> Result<String> {
+    // ---
+    let mut config = jwt_config("redis://unused");
+    config.admin.token = Some(TEST_ADMIN_TOKEN.into());
+    spawn_jwt_service_in_memory(config, TestClock::at_timestamp(NOW).shared()).await
+}
+
+/// Issue an access token at `base` and revoke it, returning the token and
+/// its `jti`.
+async fn issue_and_revoke(base: &str) -> Result<(String, String)> {
+    // ---
+    let http = http_client();
+    let issued: Value = http
+        .post(format!("{base}/v1/auth/token"))
+        .json(&json!({ "user_id": "user_1", "email": "u@example.com" }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let token = issued["access_token"].as_str().unwrap().to_string();
+
+    let validated: Value = http
+        .post(format!("{base}/v1/auth/validate"))
+        .json(&json!({ "token": token }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let jti = validated["claims"]["jti"].as_str().unwrap().to_string();
+
+    http.post(format!("{base}/v1/auth/revoke"))
+        .json(&json!({ "token": token }))
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok((token, jti))
+}
+
+// ---
+
+#[tokio::test]
+async fn admin_api_lists_looks_up_and_removes_revoked_tokens() -> Result<()> {
+    // ---
+    let base = start().await?;
+    let http = http_client();
+    let (token, jti) = issue_and_revoke(&base).await?;
+    issue_and_revoke(&base).await?;
+
+    // The admin API needs the admin token
+    let response = http.get(format!("{base}/admin/blacklist")).send().await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let listed: Value = http
+        .get(format!("{base}/admin/blacklist"))
+        .bearer_auth(TEST_ADMIN_TOKEN)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    assert_eq!(listed["revoked"].as_array().unwrap().len(), 2, "{listed}");
+    assert_eq!(listed["truncated"], false, "{listed}");
+    let entry = listed["revoked"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|entry| entry["jti"] == jti.as_str())
+        .unwrap();
+    assert_eq!(entry["ttl_seconds"], 900, "{listed}");
+
+    let listed: Value = http
+        .get(format!("{base}/admin/blacklist?limit=1"))
+        .bearer_auth(TEST_ADMIN_TOKEN)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    assert_eq!(listed["revoked"].as_array().unwrap().len(), 1, "{listed}");
+    assert_eq!(listed["truncated"], true, "{listed}");
+
+    let looked_up: Value = http
+        .get(format!("{base}/admin/blacklist/{jti}"))
+        .bearer_auth(TEST_ADMIN_TOKEN)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    assert_eq!(looked_up["revoked"], true, "{looked_up}");
+    assert_eq!(looked_up["ttl_seconds"], 900, "{looked_up}");
+
+    // Removed, the token is accepted again
+    let response = http
+        .delete(format!("{base}/admin/blacklist/{jti}"))
+        .bearer_auth(TEST_ADMIN_TOKEN)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let response = http
+        .get(format!("{base}/v1/protected"))
+        .bearer_auth(&token)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = http
+        .delete(format!("{base}/admin/blacklist/{jti}"))
+        .bearer_auth(TEST_ADMIN_TOKEN)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let looked_up: Value = http
+        .get(format!("{base}/admin/blacklist/{jti}"))
+        .bearer_auth(TEST_ADMIN_TOKEN)
+        .send()
+        .await?
+        .json()
+        .await?;
+    assert_eq!(looked_up["revoked"], false, "{looked_up}");
+    assert!(looked_up.get("ttl_seconds").is_none(), "{looked_up}");
+
+    let response = http
+        .get(format!("{base}/admin/blacklist?limit=0"))
+        .bearer_auth(TEST_ADMIN_TOKEN)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    Ok(())
+}
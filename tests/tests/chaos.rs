@@ -0,0 +1,154 @@
+// tests/tests/chaos.rs
+
+//! Fault injection: injected failures and delays open circuit breakers like
+//! real outages, only for targeted breakers, and bounded retries ride out
+//! transient ones (no containers needed)
+
+use std::convert::Infallible;
+use std::sync::atomic::{AtomicU32, Ordering};
+use tokn_resilience::{
+    forbid_chaos, install_faults, retry_call, validate_chaos_config, BreakerState, CallRetryPolicy,
+    ChaosConfig, ChaosTargets, CircuitBreaker, CircuitBreakerConfig, CircuitBreakerError,
+};
+
+// ---
+
+async fn ok() -> Result<(), Infallible> {
+    // ---
+    Ok(())
+}
+
+fn breaker(name: &'static str) -> CircuitBreaker {
+    // ---
+    CircuitBreaker::new(
+        name,
+        CircuitBreakerConfig {
+            failure_threshold: 2,
+            open_seconds: 60,
+            call_timeout_ms: 50,
+        },
+    )
+}
+
+// ---
+
+#[test]
+fn config_validation() {
+    // ---
+    assert!(validate_chaos_config(&ChaosConfig::default()).is_ok());
+    assert!(forbid_chaos(&ChaosConfig::default()).is_ok());
+
+    let failing = ChaosConfig {
+        failure_percent: 10,
+        ..Default::default()
+    };
+    assert!(validate_chaos_config(&failing).is_ok());
+    assert!(forbid_chaos(&failing).is_err());
+
+    let too_much = ChaosConfig {
+        delay_percent: 101,
+        ..Default::default()
+    };
+    assert!(validate_chaos_config(&too_much).is_err());
+
+    let targets: ChaosTargets = " redis, postgres ,".parse().unwrap();
+    assert_eq!(targets.0, ["redis", "postgres"]);
+    assert!(targets.includes("redis") && !targets.includes("ratelimit"));
+
+    let all: ChaosTargets = "all".parse().unwrap();
+    assert!(all.includes("oauth2-server"));
+    assert!(ChaosTargets::default().includes("ratelimit"));
+}
+
+// Fault injection is process-wide, so every scenario runs in this one test
+#[tokio::test]
+async fn injected_faults_trip_targeted_breakers_only() {
+    // ---
+    let failing = breaker("chaos-fail");
+    let spared = breaker("chaos-spared");
+
+    install_faults(&ChaosConfig {
+        failure_percent: 100,
+        targets: "chaos-fail".parse().unwrap(),
+        ..Default::default()
+    });
+
+    for _ in 0..2 {
+        let err = failing.call(ok()).await.unwrap_err();
+        assert!(
+            matches!(err, CircuitBreakerError::Injected("chaos-fail")),
+            "{err}"
+        );
+    }
+    assert_eq!(failing.state(), BreakerState::Open);
+    assert!(failing.call(ok()).await.unwrap_err().is_open());
+
+    assert!(spared.call(ok()).await.is_ok());
+    assert_eq!(spared.state(), BreakerState::Closed);
+
+    // A delay past the breaker's call timeout becomes a timeout
+    let slow = breaker("chaos-slow");
+    install_faults(&ChaosConfig {
+        delay_percent: 100,
+        delay_ms: 200,
+        targets: "chaos-slow".parse().unwrap(),
+        ..Default::default()
+    });
+    let err = slow.call(ok()).await.unwrap_err();
+    assert!(matches!(err, CircuitBreakerError::Timeout { .. }), "{err}");
+
+    // Installing an inactive config stops injection
+    install_faults(&ChaosConfig::default());
+    assert!(slow.call(ok()).await.is_ok());
+}
+
+#[tokio::test]
+async fn retries_stop_at_the_limit_or_a_permanent_error() {
+    // ---
+    let policy = CallRetryPolicy {
+        max_attempts: 3,
+        initial_delay_ms: 1,
+        max_delay_ms: 2,
+    };
+    let calls = AtomicU32::new(0);
+    let flaky = || async {
+        match calls.fetch_add(1, Ordering::SeqCst) {
+            0 | 1 => Err("transient"),
+            _ => Ok("value"),
+        }
+    };
+
+    // Two transient failures, then success on the third attempt
+    let value = retry_call(&policy, "flaky", |_| true, flaky).await;
+    assert_eq!(value, Ok("value"));
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+    // A permanent error is returned at once
+    calls.store(0, Ordering::SeqCst);
+    let error = retry_call(&policy, "flaky", |_| false, flaky).await;
+    assert_eq!(error, Err("transient"));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    // Never more than max_attempts
+    let calls = AtomicU32::new(0);
+    let error = retry_call(
+        &policy,
+        "down",
+        |_| true,
+        || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err::<(), _>("down")
+        },
+    )
+    .await;
+    assert_eq!(error, Err("down"));
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn call_retry_policy_defaults() {
+    // ---
+    let policy: CallRetryPolicy = serde_json::from_str("{}").unwrap();
+    assert_eq!(policy, CallRetryPolicy::default());
+    assert_eq!(policy.max_attempts, 3);
+}
@@ -0,0 +1,109 @@
+// tests/tests/check_config.rs
+
+//! `--check-config` reports: flag parsing, check outcomes, and the exit
+//! decision (no containers needed)
+
+use anyhow::anyhow;
+use tokn_server::{CheckFormat, CheckReport, CheckStatus, TlsConfig};
+
+// ---
+
+const STRONG: &str = "Jx4q9Lr2vTz7Wm1Kp8Ns3Hd6Bf0Gc5Ye";
+
+// ---
+
+fn statuses(report: &CheckReport) -> Vec<(&str, CheckStatus)> {
+    // ---
+    report
+        .checks
+        .iter()
+        .map(|check| (check.name.as_str(), check.status))
+        .collect()
+}
+
+// ---
+
+#[test]
+fn flag_selects_the_report_format() {
+    // ---
+    assert_eq!(
+        CheckFormat::parse(["--check-config"]),
+        Some(CheckFormat::Text)
+    );
+    assert_eq!(
+        CheckFormat::parse(["-v", "--check-config=json"]),
+        Some(CheckFormat::Json)
+    );
+    // A typo in the format still checks rather than starting the server
+    assert_eq!(
+        CheckFormat::parse(["--check-config=jsn"]),
+        Some(CheckFormat::Text)
+    );
+    assert_eq!(CheckFormat::parse(["--check-configs"]), None);
+    assert_eq!(CheckFormat::parse(Vec::<String>::new()), None);
+}
+
+#[tokio::test]
+async fn any_failed_check_fails_the_report() {
+    // ---
+    let mut report = CheckReport::new("test-service");
+    report.pass("config", "loaded (profile dev)");
+    report.secret("jwt.secret", Some(STRONG));
+    report.secret("admin.token", Some("changeme-changeme-changeme-changeme"));
+    report.secret("service_auth.key", None);
+    report
+        .probe("redis", async { Ok("connected".to_string()) })
+        .await;
+    assert!(report.passed(), "warnings and skips do not fail: {report}");
+
+    report
+        .probe("postgres", async { Err(anyhow!("connection refused")) })
+        .await;
+    assert!(!report.passed());
+
+    assert_eq!(
+        statuses(&report),
+        [
+            ("config", CheckStatus::Pass),
+            ("jwt.secret", CheckStatus::Pass),
+            ("admin.token", CheckStatus::Warn),
+            ("service_auth.key", CheckStatus::Skip),
+            ("redis", CheckStatus::Pass),
+            ("postgres", CheckStatus::Fail),
+        ]
+    );
+
+    // Secret values never appear in the report
+    let text = report.to_string();
+    assert!(!text.contains(STRONG) && !text.contains("changeme-changeme"));
+    assert!(text.contains("[FAIL] postgres"), "{text}");
+    assert!(text.ends_with("FAILED: 1 of 6 checks failed\n"), "{text}");
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["service"], "test-service");
+    assert_eq!(json["checks"][5]["status"], "fail");
+    assert_eq!(json["checks"][5]["detail"], "connection refused");
+}
+
+#[tokio::test]
+async fn unreadable_tls_files_fail_the_check() {
+    // ---
+    let missing = std::env::temp_dir().join(format!("tokn-check-{}", std::process::id()));
+    let tls = TlsConfig {
+        cert_path: missing.join("cert.pem"),
+        key_path: missing.join("key.pem"),
+    };
+
+    let mut report = CheckReport::new("test-service");
+    report.tls(None).await;
+    report.tls(Some(&tls)).await;
+
+    assert_eq!(
+        statuses(&report),
+        [
+            ("server.tls", CheckStatus::Skip),
+            ("server.tls", CheckStatus::Fail)
+        ]
+    );
+    assert!(report.checks[1].detail.contains("cert.pem"));
+}
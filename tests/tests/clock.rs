@@ -0,0 +1,94 @@
+// tests/tests/clock.rs
+
+//! Token issuance and expiry follow the injected clock, with configurable
+//! leeway (no containers needed)
+
+use chrono::Duration;
+use tokn_core::{generate_token, validate_token, Claims, JwtKeys, TestClock, TokenError};
+use tokn_tests::jwt_config;
+
+// ---
+
+const SECRET: &str = "clock-test-secret-at-least-32-characters";
+
+// ---
+
+#[test]
+fn claims_are_stamped_from_the_clock() {
+    // ---
+    let clock = TestClock::at_timestamp(1_700_000_000);
+    let claims = Claims::new("user_1".into(), "u@example.com".into(), 900, &clock);
+
+    assert_eq!(claims.iat, 1_700_000_000);
+    assert_eq!(claims.exp, 1_700_000_900);
+}
+
+#[test]
+fn tokens_expire_after_leeway_without_sleeping() {
+    // ---
+    let clock = TestClock::default();
+    let claims = Claims::new("user_1".into(), "u@example.com".into(), 900, &clock);
+    let token = generate_token(&claims, SECRET).unwrap();
+
+    assert!(validate_token(&token, SECRET, &clock).is_ok());
+
+    // 60 seconds of leeway past `exp`
+    clock.advance(Duration::seconds(960));
+    assert!(validate_token(&token, SECRET, &clock).is_ok());
+
+    clock.advance(Duration::seconds(1));
+    assert!(matches!(
+        validate_token(&token, SECRET, &clock),
+        Err(TokenError::Expired)
+    ));
+}
+
+#[test]
+fn tokens_from_the_past_clock_are_expired_now() {
+    // ---
+    let past = TestClock::default();
+    past.advance(-Duration::hours(1));
+    let claims = Claims::new("user_1".into(), "u@example.com".into(), 900, &past);
+    let token = generate_token(&claims, SECRET).unwrap();
+
+    assert!(matches!(
+        validate_token(&token, SECRET, &tokn_core::SystemClock),
+        Err(TokenError::Expired)
+    ));
+}
+
+#[test]
+fn leeway_is_configurable() {
+    // ---
+    let clock = TestClock::default();
+    let claims = Claims::new("user_1".into(), "u@example.com".into(), 900, &clock);
+    let strict = JwtKeys::hs256(SECRET).with_leeway(0);
+    let lenient = JwtKeys::hs256(SECRET).with_leeway(120);
+    let token = strict.sign(&claims).unwrap();
+
+    clock.advance(Duration::seconds(901));
+    assert!(matches!(
+        strict.verify(&token, &clock),
+        Err(TokenError::Expired)
+    ));
+    assert!(lenient.verify(&token, &clock).is_ok());
+
+    clock.advance(Duration::seconds(120));
+    assert!(matches!(
+        lenient.verify(&token, &clock),
+        Err(TokenError::Expired)
+    ));
+}
+
+#[test]
+fn jwt_service_keys_use_the_configured_leeway() {
+    // ---
+    let mut config = jwt_config("redis://unused");
+    assert_eq!(
+        config.jwt.keys().unwrap().leeway(),
+        tokn_core::DEFAULT_LEEWAY_SECONDS
+    );
+
+    config.jwt.validation_leeway_seconds = 5;
+    assert_eq!(config.jwt.keys().unwrap().leeway(), 5);
+}
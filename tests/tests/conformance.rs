@@ -0,0 +1,76 @@
+// tests/tests/conformance.rs
+
+//! tokn-conformance against an in-process oauth2-server
+
+use anyhow::Result;
+use tokn_conformance::{Group, Target};
+use tokn_tests::{TestEnv, DEMO_CLIENT_ID, DEMO_CLIENT_SECRET, DEMO_REDIRECT_URI};
+
+// ---
+
+/// Checks oauth2-server is known to fail today. Kept exact so that both a
+/// regression and a fix show up here; remove an entry when its gap is closed.
+const KNOWN_GAPS: &[&str] = &[
+    "authorize.unknown-client",
+    "authorize.unregistered-redirect",
+    "authorize.consent-unregistered-redirect",
+    "authorize.unsupported-response-type",
+    "authorize.state-round-trip",
+];
+
+// ---
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn oauth2_server_conformance_matches_known_gaps() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let server = env.spawn_oauth2_server().await?;
+
+    let target = Target {
+        client_id: DEMO_CLIENT_ID.to_string(),
+        client_secret: DEMO_CLIENT_SECRET.to_string(),
+        redirect_uri: DEMO_REDIRECT_URI.to_string(),
+        pkce: true,
+        ..Target::new(server)
+    };
+    let report = tokn_conformance::run(&target).await?;
+
+    let failed: Vec<&str> = report.failed().map(|result| result.check.id).collect();
+    assert_eq!(failed, KNOWN_GAPS, "\n{report}");
+
+    // Revocation and introspection are not served over HTTP yet
+    let skipped: Vec<Group> = report.skipped().map(|result| result.check.group).collect();
+    assert!(
+        skipped.iter().all(|group| *group != Group::Rfc6749),
+        "\n{report}"
+    );
+    assert_eq!(skipped.len(), 6, "\n{report}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn unselected_and_unconfigured_groups_send_no_requests() -> Result<()> {
+    // ---
+    // Nothing listens here; any request would be reported as a failure
+    let target = Target {
+        groups: vec![Group::Rfc7009],
+        ..Target::new("http://127.0.0.1:1")
+    };
+    let report = tokn_conformance::run(&target).await?;
+
+    assert!(
+        report
+            .results()
+            .iter()
+            .all(|result| result.check.group == Group::Rfc7009),
+        "\n{report}"
+    );
+    assert_eq!(
+        report.skipped().count(),
+        report.results().len(),
+        "\n{report}"
+    );
+    assert!(!report.results().is_empty());
+    Ok(())
+}
@@ -0,0 +1,171 @@
+// tests/tests/contract.rs
+
+//! Contract between oauth2-client and oauth2-server: the client against a
+//! stub answering from the contract (no containers needed), and the server
+//! against the contract's requests
+
+use anyhow::{bail, Result};
+use reqwest::{header::LOCATION, StatusCode, Url};
+use sqlx::PgPool;
+use std::sync::Arc;
+use tokn_tests::contract::{verify_provider, Contract, Stub};
+use tokn_tests::{
+    http_client, oauth2_client_config, serve, TestEnv, DEMO_CLIENT_ID, DEMO_REDIRECT_URI,
+};
+
+// ---
+
+const CONTRACT: &str = "oauth2-client.oauth2-server";
+
+/// oauth2-client only needs Redis for features these tests do not use.
+const UNUSED_REDIS_URL: &str = "redis://127.0.0.1:1";
+
+// ---
+
+/// Boot oauth2-client against the contract's stub oauth2-server.
+async fn spawn_client(stub: &Stub, client_secret: &str) -> Result<String> {
+    // ---
+    let mut config = oauth2_client_config(&stub.url, UNUSED_REDIS_URL);
+    config.oauth2.client_secret = client_secret.into();
+    serve(oauth2_client::build_router(Arc::new(config))?).await
+}
+
+/// Set up one of the contract's provider states in the server's database.
+async fn provider_state(pool: Arc<PgPool>, state: String) -> Result<()> {
+    // ---
+    match state.as_str() {
+        "authorization code contract-code issued to demo_client" => {
+            sqlx::query(
+                "INSERT INTO authorization_codes (code, client_id, user_id, redirect_uri, scope, expires_at)
+                 VALUES ('contract-code', $1, 'user_001', $2, 'profile',
+                         (NOW() AT TIME ZONE 'UTC') + INTERVAL '5 minutes')",
+            )
+            .bind(DEMO_CLIENT_ID)
+            .bind(DEMO_REDIRECT_URI)
+            .execute(pool.as_ref())
+            .await?;
+        }
+        "access token contract-access-token issued to demo_client for demo" => {
+            sqlx::query(
+                "INSERT INTO access_tokens (token, client_id, user_id, scope, expires_at)
+                 VALUES ('contract-access-token', $1, 'user_001', 'profile',
+                         (NOW() AT TIME ZONE 'UTC') + INTERVAL '1 hour')",
+            )
+            .bind(DEMO_CLIENT_ID)
+            .execute(pool.as_ref())
+            .await?;
+        }
+        other => bail!("unknown provider state '{other}'"),
+    }
+    Ok(())
+}
+
+// ---
+
+#[tokio::test]
+async fn oauth2_client_makes_only_contract_requests() -> Result<()> {
+    // ---
+    let contract = Contract::load(CONTRACT)?;
+    let stub = Stub::start(&contract).await?;
+    let client = spawn_client(&stub, "demo_secret").await?;
+    let http = http_client();
+
+    // The browser makes the authorization request, so check where the client
+    // sends it
+    let login = http.get(format!("{client}/login")).send().await?;
+    let location = Url::parse(login.headers()[LOCATION].to_str()?)?;
+    let authorize = &contract
+        .interaction("authorization request from the demo client")
+        .request;
+    assert_eq!(
+        authorize.mismatches("GET", &location, &Default::default(), b""),
+        Vec::<String>::new()
+    );
+
+    // The callback exchanges the code and fetches the user from the stub
+    let page = http
+        .get(format!(
+            "{client}/callback?code=contract-code&state=contract-state"
+        ))
+        .send()
+        .await?;
+    assert_eq!(page.status(), StatusCode::OK);
+    let html = page.text().await?;
+    assert!(html.contains("demo"), "{html}");
+    assert!(html.contains("contract-access-token"), "{html}");
+
+    // A refused exchange sends the user home with an error
+    let refused = http
+        .get(format!(
+            "{client}/callback?code=unknown-code&state=contract-state"
+        ))
+        .send()
+        .await?;
+    assert_eq!(refused.headers()[LOCATION], "/?error=token_exchange_failed");
+
+    assert_eq!(stub.unmatched(), Vec::<String>::new());
+    assert_eq!(
+        stub.matched(),
+        [
+            "token exchange for an issued code",
+            "userinfo for an issued access token",
+            "token exchange for an unknown code",
+        ]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn requests_outside_the_contract_are_reported() -> Result<()> {
+    // ---
+    let contract = Contract::load(CONTRACT)?;
+    let stub = Stub::start(&contract).await?;
+    let client = spawn_client(&stub, "rotated_secret").await?;
+
+    let response = http_client()
+        .get(format!(
+            "{client}/callback?code=contract-code&state=contract-state"
+        ))
+        .send()
+        .await?;
+    assert_eq!(
+        response.headers()[LOCATION],
+        "/?error=token_exchange_failed"
+    );
+
+    let unmatched = stub.unmatched();
+    assert_eq!(unmatched.len(), 1, "{unmatched:?}");
+    assert!(
+        unmatched[0].starts_with("POST /v1/oauth/token"),
+        "{unmatched:?}"
+    );
+    assert!(
+        unmatched[0]
+            .contains(r#"form client_secret: expected "demo_secret", got "rotated_secret""#),
+        "{unmatched:?}"
+    );
+    assert!(stub.matched().is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn oauth2_server_honours_the_client_contract() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let server = env.spawn_oauth2_server().await?;
+    let contract = Contract::load(CONTRACT)?;
+
+    let pool = env.pool.clone();
+    let problems = verify_provider(&contract, &server, |state| {
+        provider_state(pool.clone(), state.to_string())
+    })
+    .await?;
+    assert!(
+        problems.is_empty(),
+        "oauth2-server breaks the contract with {}:\n{}",
+        contract.consumer,
+        problems.join("\n")
+    );
+    Ok(())
+}
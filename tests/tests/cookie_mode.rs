@@ -0,0 +1,157 @@
+// tests/tests/cookie_mode.rs
+
+//! Cookie mode: with `jwt.cookie_mode` set, jwt-service sets issued tokens as
+//! `HttpOnly` cookies instead of returning them, protected routes accept the
+//! access token cookie, and refresh reads the refresh token cookie
+//! (in-memory store)
+
+use anyhow::Result;
+use reqwest::header::{COOKIE, SET_COOKIE};
+use reqwest::{RequestBuilder, StatusCode};
+use serde_json::{json, Value};
+use tokn_core::TestClock;
+use tokn_tests::{http_client, jwt_config, spawn_jwt_service_in_memory};
+
+// ---
+
+const NOW: i64 = 1_700_000_000;
+
+// ---
+
+/// Serve jwt-service from an in-memory store, with cookie mode `on` or off.
+async fn start(on: bool) -> Result<String> {
+    // ---
+    let mut config = jwt_config("redis://unused");
+    config.jwt.cookie_mode = on;
+    spawn_jwt_service_in_memory(config, TestClock::at_timestamp(NOW).shared()).await
+}
+
+/// Send `request`, returning the response status, its `Set-Cookie` headers,
+/// and its body.
+async fn send(request: RequestBuilder) -> Result<(StatusCode, Vec<String>, Value)> {
+    // ---
+    let response = request.send().await?;
+    let cookies = response
+        .headers()
+        .get_all(SET_COOKIE)
+        .iter()
+        .map(|value| value.to_str().map(str::to_string))
+        .collect::<Result<_, _>>()?;
+    Ok((response.status(), cookies, response.json().await?))
+}
+
+/// The value of cookie `name` among `set_cookies`.
+fn cookie<'a>(set_cookies: &'a [String], name: &str) -> Option<&'a str> {
+    // ---
+    set_cookies.iter().find_map(|set_cookie| {
+        let pair = set_cookie.split(';').next()?;
+        pair.strip_prefix(name)?.strip_prefix('=')
+    })
+}
+
+// ---
+
+#[tokio::test]
+async fn tokens_are_set_as_cookies_not_returned() -> Result<()> {
+    // ---
+    let base = start(true).await?;
+    let (status, set_cookies, body) = send(
+        http_client()
+            .post(format!("{base}/v1/auth/token"))
+            .json(&json!({ "user_id": "user_1", "email": "u@example.com" })),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert!(body.get("access_token").is_none(), "{body}");
+    assert!(body.get("refresh_token").is_none(), "{body}");
+    assert_eq!(body["token_type"], "Bearer");
+    assert_eq!(body["expires_in"], 900);
+
+    assert_eq!(set_cookies.len(), 2, "{set_cookies:?}");
+    for set_cookie in &set_cookies {
+        for attribute in ["HttpOnly", "Secure", "SameSite=Strict", "Path=/"] {
+            assert!(set_cookie.contains(attribute), "{set_cookie}");
+        }
+    }
+    assert!(set_cookies[0].starts_with("access_token="));
+    assert!(set_cookies[0].contains("Max-Age=900"));
+    assert!(set_cookies[1].starts_with("refresh_token="));
+    assert!(set_cookies[1].contains("Max-Age=604800"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn protected_routes_and_refresh_read_the_cookies() -> Result<()> {
+    // ---
+    let base = start(true).await?;
+    let (_, set_cookies, _) = send(
+        http_client()
+            .post(format!("{base}/v1/auth/token"))
+            .json(&json!({ "user_id": "user_1", "email": "u@example.com" })),
+    )
+    .await?;
+    let access_token = cookie(&set_cookies, "access_token").unwrap();
+    let refresh_token = cookie(&set_cookies, "refresh_token").unwrap();
+
+    let (status, _, body) = send(
+        http_client()
+            .get(format!("{base}/v1/protected"))
+            .header(COOKIE, format!("theme=dark; access_token={access_token}")),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["user_id"], "user_1");
+
+    // Without the cookie or an Authorization header the request is refused
+    let (status, _, _) = send(http_client().get(format!("{base}/v1/protected"))).await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // Refresh takes the refresh token cookie and rotates both cookies
+    let (status, rotated, body) = send(
+        http_client()
+            .post(format!("{base}/v1/auth/refresh"))
+            .header(COOKIE, format!("refresh_token={refresh_token}"))
+            .json(&json!({})),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert!(body.get("refresh_token").is_none(), "{body}");
+    let new_refresh_token = cookie(&rotated, "refresh_token").unwrap();
+    assert_ne!(new_refresh_token, refresh_token);
+    assert!(cookie(&rotated, "access_token").is_some());
+
+    // The rotated cookie is single-use like any refresh token
+    let (status, _, _) = send(
+        http_client()
+            .post(format!("{base}/v1/auth/refresh"))
+            .header(COOKIE, format!("refresh_token={refresh_token}"))
+            .json(&json!({})),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    Ok(())
+}
+
+#[tokio::test]
+async fn cookies_are_ignored_outside_cookie_mode() -> Result<()> {
+    // ---
+    let base = start(false).await?;
+    let (status, set_cookies, tokens) = send(
+        http_client()
+            .post(format!("{base}/v1/auth/token"))
+            .json(&json!({ "user_id": "user_1", "email": "u@example.com" })),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{tokens}");
+    assert!(set_cookies.is_empty(), "{set_cookies:?}");
+    let access_token = tokens["access_token"].as_str().unwrap();
+
+    let (status, _, _) = send(
+        http_client()
+            .get(format!("{base}/v1/protected"))
+            .header(COOKIE, format!("access_token={access_token}")),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    Ok(())
+}
@@ -0,0 +1,165 @@
+// tests/tests/cors.rs
+
+//! CORS: browser apps on the configured origins may call jwt-service, others
+//! keep the same-origin policy, and bad settings are refused at load
+
+use anyhow::Result;
+use reqwest::{header, Method, StatusCode};
+use serde_json::json;
+use tokn_core::TestClock;
+use tokn_server::{CorsConfig, REQUEST_ID};
+use tokn_tests::{http_client, jwt_config, spawn_jwt_service_in_memory};
+
+// ---
+
+const APP: &str = "https://app.example.com";
+
+// ---
+
+/// Serve jwt-service from an in-memory store with `cors`, returning its base
+/// URL.
+async fn start(cors: CorsConfig) -> Result<String> {
+    // ---
+    let mut config = jwt_config("redis://unused");
+    config.server.cors = cors;
+    spawn_jwt_service_in_memory(config, TestClock::at_timestamp(1_700_000_000).shared()).await
+}
+
+fn allowing(origins: &str) -> CorsConfig {
+    // ---
+    CorsConfig {
+        allowed_origins: origins.into(),
+        ..CorsConfig::default()
+    }
+}
+
+/// Send the preflight a browser on `origin` sends before a JSON POST to
+/// `/v1/auth/validate`.
+async fn preflight(base: &str, origin: &str) -> Result<reqwest::Response> {
+    // ---
+    let response = http_client()
+        .request(Method::OPTIONS, format!("{base}/v1/auth/validate"))
+        .header(header::ORIGIN, origin)
+        .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+        .header(header::ACCESS_CONTROL_REQUEST_HEADERS, "content-type")
+        .send()
+        .await?;
+    Ok(response)
+}
+
+// ---
+
+#[tokio::test]
+async fn listed_origins_may_call_the_service() -> Result<()> {
+    // ---
+    let base = start(allowing(APP)).await?;
+
+    let response = preflight(&base, APP).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let headers = response.headers();
+    assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_ORIGIN], APP);
+    let methods = headers[header::ACCESS_CONTROL_ALLOW_METHODS].to_str()?;
+    assert!(methods.contains("POST"), "{methods}");
+    let allowed = headers[header::ACCESS_CONTROL_ALLOW_HEADERS].to_str()?;
+    assert!(allowed.contains("content-type"), "{allowed}");
+    assert_eq!(headers[header::ACCESS_CONTROL_MAX_AGE], "600");
+
+    // The actual request, a problem response included, is readable by the app
+    let response = http_client()
+        .post(format!("{base}/v1/auth/validate"))
+        .header(header::ORIGIN, APP)
+        .json(&json!({ "token": "not-a-jwt" }))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let headers = response.headers();
+    assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_ORIGIN], APP);
+    let exposed = headers[header::ACCESS_CONTROL_EXPOSE_HEADERS].to_str()?;
+    assert!(exposed.contains(REQUEST_ID.as_str()), "{exposed}");
+    assert!(headers
+        .get(header::ACCESS_CONTROL_ALLOW_CREDENTIALS)
+        .is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn other_origins_are_not_allowed() -> Result<()> {
+    // ---
+    let base = start(allowing(APP)).await?;
+    let response = preflight(&base, "https://evil.example.com").await?;
+    assert!(response
+        .headers()
+        .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+        .is_none());
+
+    // Nor is anyone by default
+    let base = start(CorsConfig::default()).await?;
+    let response = preflight(&base, APP).await?;
+    assert!(response
+        .headers()
+        .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+        .is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn credentials_are_allowed_only_when_configured() -> Result<()> {
+    // ---
+    let base = start(CorsConfig {
+        allow_credentials: true,
+        ..allowing(APP)
+    })
+    .await?;
+    let response = preflight(&base, APP).await?;
+    assert_eq!(response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], APP);
+    assert_eq!(
+        response.headers()[header::ACCESS_CONTROL_ALLOW_CREDENTIALS],
+        "true"
+    );
+    Ok(())
+}
+
+#[test]
+fn invalid_cors_settings_are_refused() {
+    // ---
+    let valid = [
+        allowing(APP),
+        allowing("https://app.example.com, http://localhost:3000"),
+        allowing("*"),
+        CorsConfig {
+            allowed_methods: "get,post,delete".into(),
+            ..allowing(APP)
+        },
+    ];
+    for config in &valid {
+        assert_eq!(
+            tokn_server::validate_cors_config(config),
+            Ok(()),
+            "{config:?}"
+        );
+    }
+
+    let invalid = [
+        allowing("app.example.com"),
+        allowing("https://app.example.com/"),
+        allowing("*,https://app.example.com"),
+        CorsConfig {
+            allow_credentials: true,
+            ..allowing("*")
+        },
+        CorsConfig {
+            allowed_methods: "GET,NOT A METHOD".into(),
+            ..allowing(APP)
+        },
+        CorsConfig {
+            allowed_headers: "content-type,bad header".into(),
+            ..allowing(APP)
+        },
+    ];
+    for config in &invalid {
+        assert!(
+            tokn_server::validate_cors_config(config).is_err(),
+            "{config:?}"
+        );
+    }
+}
@@ -0,0 +1,102 @@
+// tests/tests/custom_claims.rs
+
+//! Caller-supplied claims in issued tokens, and refusal of reserved ones, on a
+//! stateless jwt-service (no containers needed)
+
+use anyhow::Result;
+use reqwest::StatusCode;
+use serde_json::{json, Map, Value};
+use tokn_core::{validate_token, Claims, ReservedClaim, SystemClock, RESERVED_CLAIMS};
+use tokn_tests::{http_client, jwt_config, serve, TEST_JWT_SECRET};
+
+// ---
+
+async fn spawn_stateless() -> Result<String> {
+    // ---
+    let mut config = jwt_config("redis://unused");
+    config.jwt.stateless = true;
+
+    let state = jwt_service::AppState::stateless(config, SystemClock::shared())?;
+    serve(jwt_service::build_router(state)).await
+}
+
+// ---
+
+#[tokio::test]
+async fn custom_claims_are_flattened_into_the_token() -> Result<()> {
+    // ---
+    let base = spawn_stateless().await?;
+    let tokens: Value = http_client()
+        .post(format!("{base}/v1/auth/token"))
+        .json(&json!({
+            "user_id": "user_1",
+            "email": "u@example.com",
+            "custom_claims": { "tenant_id": "acme", "plans": ["pro", "billing"] },
+        }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let token = tokens["access_token"].as_str().unwrap();
+
+    // Top-level payload claims, next to the standard ones
+    let claims = validate_token(token, TEST_JWT_SECRET, &SystemClock)?;
+    assert_eq!(claims.sub, "user_1");
+    assert_eq!(claims.custom["tenant_id"], "acme");
+    assert_eq!(claims.custom["plans"], json!(["pro", "billing"]));
+
+    let validation: Value = http_client()
+        .post(format!("{base}/v1/auth/validate"))
+        .json(&json!({ "token": token }))
+        .send()
+        .await?
+        .json()
+        .await?;
+    assert_eq!(validation["claims"]["tenant_id"], "acme");
+    Ok(())
+}
+
+#[tokio::test]
+async fn reserved_claims_are_refused() -> Result<()> {
+    // ---
+    let base = spawn_stateless().await?;
+    for name in ["exp", "jti", "sub", "scope"] {
+        let response = http_client()
+            .post(format!("{base}/v1/auth/token"))
+            .json(&json!({
+                "user_id": "user_1",
+                "email": "u@example.com",
+                "custom_claims": { "tenant_id": "acme", name: 0 },
+            }))
+            .send()
+            .await?;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{name}");
+
+        let problem: Value = response.json().await?;
+        let detail = problem["detail"].as_str().unwrap();
+        assert!(detail.contains(&format!("'{name}'")), "{detail}");
+    }
+
+    // Without custom claims the request is unchanged
+    http_client()
+        .post(format!("{base}/v1/auth/token"))
+        .json(&json!({ "user_id": "user_1", "email": "u@example.com" }))
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+#[test]
+fn every_reserved_claim_is_refused() {
+    // ---
+    for name in RESERVED_CLAIMS {
+        let custom = Map::from_iter([(name.to_string(), json!("x"))]);
+        let claims = Claims::new("user_1".into(), "u@example.com".into(), 900, &SystemClock);
+        assert_eq!(
+            claims.with_custom(custom).err(),
+            Some(ReservedClaim(name.to_string()))
+        );
+    }
+}
@@ -0,0 +1,94 @@
+// tests/tests/db_queries.rs
+
+//! oauth2-server's query instrumentation: slow-query warnings, including for
+//! queries cancelled mid-flight (no containers needed)
+
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing_subscriber::fmt::MakeWriter;
+
+// ---
+
+/// Collects formatted log output in memory.
+#[derive(Clone, Default)]
+struct Capture(Arc<Mutex<Vec<u8>>>);
+
+impl Capture {
+    // ---
+    /// Take the lines logged so far.
+    fn take(&self) -> Vec<String> {
+        // ---
+        let buf = std::mem::take(&mut *self.0.lock().unwrap());
+        String::from_utf8(buf)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+impl io::Write for Capture {
+    // ---
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // ---
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        // ---
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for Capture {
+    // ---
+    type Writer = Capture;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        // ---
+        self.clone()
+    }
+}
+
+// ---
+
+// One test, because the threshold is process-wide
+#[tokio::test]
+async fn queries_over_the_threshold_are_logged() {
+    // ---
+    let out = Capture::default();
+    let subscriber = tracing_subscriber::fmt()
+        .with_writer(out.clone())
+        .with_ansi(false)
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    // Under the default threshold: nothing logged
+    let rows = oauth2_server::timed("test.fast", async { Ok::<_, ()>(vec![1, 2, 3]) }).await;
+    assert_eq!(rows, Ok(vec![1, 2, 3]));
+    assert!(out.take().is_empty());
+
+    // Every query is slow at 0
+    oauth2_server::set_slow_query_threshold(0);
+    let _ = oauth2_server::timed("test.failing", async { Err::<Option<u8>, _>("boom") }).await;
+    let lines = out.take();
+    assert_eq!(lines.len(), 1, "{lines:?}");
+    assert!(lines[0].contains("Slow query"), "{}", lines[0]);
+    assert!(lines[0].contains("test.failing"), "{}", lines[0]);
+    assert!(lines[0].contains("error"), "{}", lines[0]);
+
+    // A query dropped by its caller's timeout is still reported
+    oauth2_server::set_slow_query_threshold(20);
+    let hung = oauth2_server::timed("test.hung", std::future::pending::<Result<bool, ()>>());
+    assert!(tokio::time::timeout(Duration::from_millis(50), hung)
+        .await
+        .is_err());
+    let lines = out.take();
+    assert_eq!(lines.len(), 1, "{lines:?}");
+    assert!(lines[0].contains("test.hung"), "{}", lines[0]);
+    assert!(lines[0].contains("cancelled"), "{}", lines[0]);
+
+    oauth2_server::set_slow_query_threshold(oauth2_server::DEFAULT_SLOW_QUERY_MS);
+}
@@ -0,0 +1,163 @@
+// tests/tests/debug.rs
+
+//! The `/debug` diagnostics routes: the report and its sections, cache hit
+//! rates, the admin token check, and oauth2-server's pool and job sections
+
+use anyhow::Result;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use tokn_ratelimit::RateLimiter;
+use tokn_scheduler::SchedulerConfig;
+use tokn_server::{AdminConfig, CacheStats, DebugInfo};
+use tokn_tests::{http_client, serve, TestEnv, TEST_ADMIN_TOKEN};
+
+// ---
+
+fn admin() -> AdminConfig {
+    // ---
+    AdminConfig {
+        token: Some(TEST_ADMIN_TOKEN.into()),
+    }
+}
+
+async fn get(url: &str) -> Result<(StatusCode, Value)> {
+    // ---
+    let response = http_client()
+        .get(url)
+        .bearer_auth(TEST_ADMIN_TOKEN)
+        .send()
+        .await?;
+    let status = response.status();
+    Ok((status, response.json().await.unwrap_or(Value::Null)))
+}
+
+// ---
+
+#[tokio::test]
+async fn report_has_build_info_sections_and_caches() -> Result<()> {
+    // ---
+    let probes = Arc::new(AtomicU32::new(0));
+    let sessions = CacheStats::default();
+    let debug = DebugInfo::new("test-service", "1.2.3")
+        .section("pool", {
+            let probes = probes.clone();
+            move || {
+                let count = probes.fetch_add(1, Ordering::SeqCst) + 1;
+                async move { json!({ "in_use": 3, "probes": count }) }
+            }
+        })
+        .cache("sessions", &sessions);
+    let base = serve(tokn_server::debug_router(&admin(), debug)).await?;
+
+    sessions.hit();
+    sessions.hit();
+    sessions.hit();
+    sessions.miss();
+
+    let (status, report) = get(&format!("{base}/debug")).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(report["build"]["service"], "test-service");
+    assert_eq!(report["build"]["version"], "1.2.3");
+    assert!(report["build"]["uptime_seconds"].is_u64());
+    assert_eq!(report["pool"], json!({ "in_use": 3, "probes": 1 }));
+    assert_eq!(
+        report["caches"]["sessions"],
+        json!({ "hits": 3, "misses": 1, "hit_rate": 0.75 })
+    );
+
+    // Probes run on every request, not once at startup
+    let (status, pool) = get(&format!("{base}/debug/pool")).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(pool["probes"], 2);
+
+    let (status, build) = get(&format!("{base}/debug/build")).await?;
+    assert_eq!(
+        (status, &build["service"]),
+        (StatusCode::OK, &json!("test-service"))
+    );
+
+    let (status, _) = get(&format!("{base}/debug/missing")).await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    Ok(())
+}
+
+#[test]
+fn unused_cache_has_no_hit_rate() {
+    // ---
+    let snapshot = CacheStats::default().snapshot();
+    assert_eq!(
+        (snapshot.hits, snapshot.misses, snapshot.hit_rate),
+        (0, 0, None)
+    );
+}
+
+#[tokio::test]
+async fn requires_the_admin_token() -> Result<()> {
+    // ---
+    let debug = DebugInfo::new("test-service", "1.2.3");
+    let base = serve(tokn_server::debug_router(&admin(), debug.clone())).await?;
+
+    let anonymous = http_client().get(format!("{base}/debug")).send().await?;
+    assert_eq!(anonymous.status(), StatusCode::UNAUTHORIZED);
+
+    let wrong = http_client()
+        .get(format!("{base}/debug/build"))
+        .bearer_auth("not-the-admin-token-not-the-admin-token")
+        .send()
+        .await?;
+    assert_eq!(wrong.status(), StatusCode::UNAUTHORIZED);
+
+    // Without an admin token the routes are not mounted at all
+    let unmounted = serve(tokn_server::debug_router(&AdminConfig::default(), debug)).await?;
+    let response = http_client()
+        .get(format!("{unmounted}/debug"))
+        .bearer_auth(TEST_ADMIN_TOKEN)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn oauth2_server_reports_pool_and_jobs() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let state = oauth2_server::AppState::new(env.pool.clone(), Default::default());
+    let scheduler = oauth2_server::scheduler(&SchedulerConfig::default(), &state);
+    let debug = oauth2_server::debug_info(&state, &scheduler, &RateLimiter::disabled());
+    let base = serve(tokn_server::debug_router(&admin(), debug)).await?;
+
+    scheduler
+        .run_now(oauth2_server::EXPIRED_TOKEN_CLEANUP)
+        .await?;
+
+    let (status, report) = get(&format!("{base}/debug")).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(report["build"]["service"], "oauth2-server");
+
+    let postgres = &report["postgres"];
+    assert_eq!(postgres["breaker"], "closed");
+    assert!(postgres["size"].as_u64() >= Some(1), "{postgres}");
+    assert!(postgres["max_connections"].as_u64() >= postgres["size"].as_u64());
+
+    let cleanup = &report["jobs"][0];
+    assert_eq!(cleanup["name"], oauth2_server::EXPIRED_TOKEN_CLEANUP);
+    assert_eq!(cleanup["last_result"], "success");
+    assert!(cleanup["next_run"].is_string());
+
+    assert_eq!(
+        report["ratelimit"],
+        json!({ "enabled": false, "breaker": null })
+    );
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "reserved or already registered")]
+fn section_names_are_unique() {
+    // ---
+    let _ = DebugInfo::new("test-service", "1.2.3").section("build", || async { Value::Null });
+}
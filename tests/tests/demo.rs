@@ -0,0 +1,98 @@
+// tests/tests/demo.rs
+
+//! The services as `tokn-demo` wires them: a stateless jwt-service, and
+//! oauth2-client signing in at oauth2-server as the seeded demo client
+
+use anyhow::{Context, Result};
+use reqwest::{header::LOCATION, StatusCode, Url};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tokn_tests::{http_client, query_param, serve, TestEnv};
+
+// ---
+
+#[tokio::test]
+async fn jwt_service_runs_without_redis() -> Result<()> {
+    // ---
+    let base = serve(tokn_demo::jwt_service()?).await?;
+    let http = http_client();
+
+    let tokens: Value = http
+        .post(format!("{base}/v1/auth/token"))
+        .json(&json!({ "user_id": "user_001", "email": "demo@example.com" }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    assert!(tokens.get("refresh_token").is_none(), "{tokens}");
+
+    let validate: Value = http
+        .post(format!("{base}/v1/auth/validate"))
+        .json(&json!({ "token": tokens["access_token"] }))
+        .send()
+        .await?
+        .json()
+        .await?;
+    assert_eq!(validate["valid"], true);
+
+    let refresh = http
+        .post(format!("{base}/v1/auth/refresh"))
+        .json(&json!({ "refresh_token": "anything" }))
+        .send()
+        .await?;
+    assert_eq!(refresh.status(), StatusCode::NOT_FOUND);
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn demo_client_signs_in_at_demo_server() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let server = serve(tokn_demo::oauth2_server(Arc::clone(&env.pool))?).await?;
+    let client = serve(tokn_demo::oauth2_client(&server)?).await?;
+    let http = http_client();
+
+    let login = http.get(format!("{client}/login")).send().await?;
+    let location = login
+        .headers()
+        .get(LOCATION)
+        .context("login must redirect")?;
+    let authorize = Url::parse(location.to_str()?)?;
+    assert!(authorize.as_str().starts_with(&server));
+    let state = query_param(&authorize, "state").context("login must send state")?;
+
+    let approve = http
+        .post(format!("{server}/v1/oauth/authorize"))
+        .form(&[
+            ("client_id", tokn_demo::DEMO_CLIENT_ID),
+            ("redirect_uri", tokn_demo::DEMO_REDIRECT_URI),
+            ("scope", "profile"),
+            ("state", state.as_str()),
+            ("action", "approve"),
+        ])
+        .send()
+        .await?;
+    let callback = Url::parse(
+        approve
+            .headers()
+            .get(LOCATION)
+            .context("approval must redirect")?
+            .to_str()?,
+    )?;
+    let code = query_param(&callback, "code").context("approval must issue a code")?;
+
+    let page = http
+        .get(format!("{client}/callback"))
+        .query(&[("code", code.as_str()), ("state", state.as_str())])
+        .send()
+        .await?;
+    assert_eq!(page.status(), StatusCode::OK);
+    assert!(page.text().await?.contains("<strong>demo</strong>"));
+
+    // The API docs are mounted too
+    let docs = http.get(format!("{server}/docs")).send().await?;
+    assert_eq!(docs.status(), StatusCode::OK);
+    Ok(())
+}
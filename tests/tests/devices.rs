@@ -0,0 +1,157 @@
+// tests/tests/devices.rs
+
+//! Refresh token sessions record the client device (`User-Agent`, IP, and
+//! `X-Device-Id`), list it in the sessions API, and with
+//! `JWT_REFRESH_BIND_DEVICE` refuse refreshes from any other device
+
+use anyhow::Result;
+use jwt_service::{ClientDevice, RefreshTokenData};
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use tokn_core::{Claims, TestClock};
+use tokn_tests::{http_client, jwt_config, spawn_jwt_service_in_memory};
+
+// ---
+
+const NOW: i64 = 1_700_000_000;
+
+// ---
+
+/// Serve jwt-service from an in-memory store, with device binding `bind`.
+async fn start(bind: bool) -> Result<String> {
+    // ---
+    let mut config = jwt_config("redis://unused");
+    config.jwt.refresh_bind_device = bind;
+    spawn_jwt_service_in_memory(config, TestClock::at_timestamp(NOW).shared()).await
+}
+
+/// A device sending `user_agent` and `device_id`.
+fn device(user_agent: Option<&str>, device_id: Option<&str>) -> ClientDevice {
+    // ---
+    ClientDevice {
+        user_agent: user_agent.map(str::to_string),
+        ip: Some("127.0.0.1".into()),
+        device_id: device_id.map(str::to_string),
+    }
+}
+
+// ---
+
+#[test]
+fn sessions_match_by_device_id_then_user_agent() {
+    // ---
+    let claims = Claims::new(
+        "user_1".into(),
+        "u@example.com".into(),
+        900,
+        &TestClock::at_timestamp(NOW),
+    );
+
+    let mut data = RefreshTokenData::from(&claims);
+    assert!(data.same_device(&device(Some("curl/8"), None)));
+
+    data.record_device(&device(Some("curl/8"), None));
+    assert!(data.same_device(&device(Some("curl/8"), Some("phone"))));
+    assert!(!data.same_device(&device(Some("wget/1"), None)));
+
+    // A device ID outranks the user agent, and is kept when later omitted
+    data.record_device(&device(Some("curl/8"), Some("laptop")));
+    data.record_device(&device(Some("curl/9"), None));
+    assert_eq!(data.device_id.as_deref(), Some("laptop"));
+    assert!(data.same_device(&device(Some("wget/1"), Some("laptop"))));
+    assert!(!data.same_device(&device(Some("curl/9"), Some("phone"))));
+    assert!(!data.same_device(&device(Some("curl/9"), None)));
+}
+
+#[tokio::test]
+async fn sessions_list_the_device_they_were_issued_to() -> Result<()> {
+    // ---
+    let base = start(false).await?;
+    let http = http_client();
+
+    let issued: Value = http
+        .post(format!("{base}/v1/auth/token"))
+        .header("user-agent", "tokn-tests/1.0")
+        .header("x-device-id", "laptop-5f4b")
+        .json(&json!({ "user_id": "user_1", "email": "u@example.com" }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    let listed: Value = http
+        .get(format!("{base}/v1/auth/sessions/user_1"))
+        .bearer_auth(issued["access_token"].as_str().unwrap())
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let session = &listed["sessions"][0];
+    assert_eq!(session["user_agent"], "tokn-tests/1.0", "{listed}");
+    assert_eq!(session["ip"], "127.0.0.1", "{listed}");
+    assert_eq!(session["device_id"], "laptop-5f4b", "{listed}");
+
+    // Device IDs are bounded
+    let response = http
+        .post(format!("{base}/v1/auth/token"))
+        .header("x-device-id", "x".repeat(129))
+        .json(&json!({ "user_id": "user_1", "email": "u@example.com" }))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    Ok(())
+}
+
+#[tokio::test]
+async fn bound_sessions_refuse_refresh_from_another_device() -> Result<()> {
+    // ---
+    let base = start(true).await?;
+    let http = http_client();
+    let sign_in = || async {
+        let issued: Value = http
+            .post(format!("{base}/v1/auth/token"))
+            .header("x-device-id", "laptop-5f4b")
+            .json(&json!({ "user_id": "user_1", "email": "u@example.com" }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        anyhow::Ok(json!({ "refresh_token": issued["refresh_token"] }))
+    };
+
+    // The same device keeps refreshing
+    let response = http
+        .post(format!("{base}/v1/auth/refresh"))
+        .header("x-device-id", "laptop-5f4b")
+        .json(&sign_in().await?)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Another device is refused, and the token is consumed
+    let refresh = sign_in().await?;
+    let response = http
+        .post(format!("{base}/v1/auth/refresh"))
+        .header("x-device-id", "phone-91ac")
+        .json(&refresh)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let problem: Value = response.json().await?;
+    assert_eq!(
+        problem["detail"], "Refresh token was issued to another device",
+        "{problem}"
+    );
+
+    let response = http
+        .post(format!("{base}/v1/auth/refresh"))
+        .header("x-device-id", "laptop-5f4b")
+        .json(&refresh)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    Ok(())
+}
@@ -0,0 +1,263 @@
+// tests/tests/dpop.rs
+
+//! DPoP-bound access tokens (RFC 9449): issuing against a proof, presenting
+//! bound tokens to the JWT middleware, and refreshing a bound session
+
+use anyhow::Result;
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use jsonwebtoken::jwk::Jwk;
+use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use tokn_core::{access_token_hash, jwk_thumbprint, Claims, Clock, SystemClock};
+use tokn_tests::{http_client, jwt_config, serve, TestEnv};
+use uuid::Uuid;
+
+// ---
+
+const EC_PRIVATE_PEM: &[u8] = include_bytes!("../fixtures/ec_private.pem");
+const EC_PUBLIC_PEM: &str = include_str!("../fixtures/ec_public.pem");
+const ED_PRIVATE_PEM: &[u8] = include_bytes!("../fixtures/ed25519_private.pem");
+const ED_PUBLIC_PEM: &str = include_str!("../fixtures/ed25519_public.pem");
+
+// ---
+
+/// A client's DPoP key pair.
+struct DpopKey {
+    // ---
+    algorithm: Algorithm,
+    encoding: EncodingKey,
+    jwk: Jwk,
+}
+
+impl DpopKey {
+    // ---
+    /// The P-256 fixture key.
+    fn es256() -> Result<Self> {
+        // ---
+        // The uncompressed point ends the SPKI: 0x04 || x || y
+        let point = public_key_bytes(EC_PUBLIC_PEM)?;
+        let (x, y) = point[point.len() - 64..].split_at(32);
+        let jwk = json!({
+            "kty": "EC",
+            "crv": "P-256",
+            "x": URL_SAFE_NO_PAD.encode(x),
+            "y": URL_SAFE_NO_PAD.encode(y),
+        });
+        Ok(Self {
+            algorithm: Algorithm::ES256,
+            encoding: EncodingKey::from_ec_pem(EC_PRIVATE_PEM)?,
+            jwk: serde_json::from_value(jwk)?,
+        })
+    }
+
+    /// The Ed25519 fixture key.
+    fn eddsa() -> Result<Self> {
+        // ---
+        let spki = public_key_bytes(ED_PUBLIC_PEM)?;
+        let jwk = json!({
+            "kty": "OKP",
+            "crv": "Ed25519",
+            "x": URL_SAFE_NO_PAD.encode(&spki[spki.len() - 32..]),
+        });
+        Ok(Self {
+            algorithm: Algorithm::EdDSA,
+            encoding: EncodingKey::from_ed_pem(ED_PRIVATE_PEM)?,
+            jwk: serde_json::from_value(jwk)?,
+        })
+    }
+
+    /// A fresh proof for `method` on `url`, sent with `access_token`.
+    fn proof(&self, method: &str, url: &str, access_token: Option<&str>) -> Result<String> {
+        // ---
+        let mut header = Header::new(self.algorithm);
+        header.typ = Some("dpop+jwt".to_string());
+        header.jwk = Some(self.jwk.clone());
+
+        let mut claims = json!({
+            "jti": Uuid::new_v4().to_string(),
+            "htm": method,
+            "htu": url,
+            "iat": SystemClock.timestamp(),
+        });
+        if let Some(token) = access_token {
+            claims["ath"] = access_token_hash(token).into();
+        }
+        Ok(encode(&header, &claims, &self.encoding)?)
+    }
+}
+
+/// The DER of a PEM public key.
+fn public_key_bytes(pem: &str) -> Result<Vec<u8>> {
+    // ---
+    let base64: String = pem
+        .lines()
+        .filter(|line| !line.starts_with("-----"))
+        .collect();
+    Ok(STANDARD.decode(base64)?)
+}
+
+/// The claims of `token`, unverified.
+fn payload(token: &str) -> Result<Claims> {
+    // ---
+    let payload = token.split('.').nth(1).unwrap_or_default();
+    Ok(serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload)?)?)
+}
+
+// ---
+
+#[tokio::test]
+async fn bound_tokens_need_a_proof_from_their_key() -> Result<()> {
+    // ---
+    let mut config = jwt_config("redis://unused");
+    config.jwt.stateless = true;
+    let state = jwt_service::AppState::stateless(config, SystemClock::shared())?;
+    let base = serve(jwt_service::build_router(state)).await?;
+    let http = http_client();
+    let key = DpopKey::es256()?;
+
+    // Issued against a proof, the token is bound to its key
+    let token_url = format!("{base}/v1/auth/token");
+    let tokens: Value = http
+        .post(&token_url)
+        .header("DPoP", key.proof("POST", &token_url, None)?)
+        .json(&json!({ "user_id": "user_1", "email": "u@example.com" }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    assert_eq!(tokens["token_type"], "DPoP");
+    let access_token = tokens["access_token"].as_str().unwrap();
+    let claims = payload(access_token)?;
+    assert_eq!(claims.dpop_jkt(), jwk_thumbprint(&key.jwk).as_deref());
+
+    let protected = format!("{base}/v1/protected");
+    let get = |scheme: &str, proof: Option<String>| {
+        let mut request = http
+            .get(&protected)
+            .header("Authorization", format!("{scheme} {access_token}"));
+        if let Some(proof) = proof {
+            request = request.header("DPoP", proof);
+        }
+        request.send()
+    };
+
+    let proof = key.proof("GET", &protected, Some(access_token))?;
+    let response = get("DPoP", Some(proof.clone())).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // A proof is single-use
+    let response = get("DPoP", Some(proof)).await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let problem: Value = response.json().await?;
+    assert_eq!(problem["detail"], "DPoP proof has already been used");
+
+    // Stolen, the token is useless: as a bearer token, without a proof, or
+    // with a proof from another key
+    let response = get("Bearer", None).await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let response = get("DPoP", None).await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let other = DpopKey::eddsa()?.proof("GET", &protected, Some(access_token))?;
+    let response = get("DPoP", Some(other)).await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    // A proof made for another URL or another token does not carry over
+    let elsewhere = key.proof(
+        "GET",
+        &format!("{base}/v1/protected/admin"),
+        Some(access_token),
+    )?;
+    let response = get("DPoP", Some(elsewhere)).await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let unbound = key.proof("GET", &protected, Some("another-token"))?;
+    let response = get("DPoP", Some(unbound)).await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    Ok(())
+}
+
+#[tokio::test]
+async fn invalid_proofs_are_refused_at_the_token_endpoint() -> Result<()> {
+    // ---
+    let mut config = jwt_config("redis://unused");
+    config.jwt.stateless = true;
+    let state = jwt_service::AppState::stateless(config, SystemClock::shared())?;
+    let base = serve(jwt_service::build_router(state)).await?;
+    let key = DpopKey::es256()?;
+
+    // Made for a different endpoint
+    let proof = key.proof("POST", &format!("{base}/v1/auth/refresh"), None)?;
+    let response = http_client()
+        .post(format!("{base}/v1/auth/token"))
+        .header("DPoP", proof)
+        .json(&json!({ "user_id": "user_1", "email": "u@example.com" }))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let problem: Value = response.json().await?;
+    assert_eq!(problem["error"], "invalid_dpop_proof", "{problem}");
+
+    // Unchanged without a proof: a bearer token
+    let tokens: Value = http_client()
+        .post(format!("{base}/v1/auth/token"))
+        .json(&json!({ "user_id": "user_1", "email": "u@example.com" }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    assert_eq!(tokens["token_type"], "Bearer");
+    assert!(payload(tokens["access_token"].as_str().unwrap())?
+        .cnf
+        .is_none());
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn bound_refresh_tokens_need_a_proof_from_their_key() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let base = env
+        .spawn_jwt_service_with_config(jwt_config(&env.redis_url), SystemClock::shared())
+        .await?;
+    let http = http_client();
+    let key = DpopKey::es256()?;
+
+    let token_url = format!("{base}/v1/auth/token");
+    let tokens: Value = http
+        .post(&token_url)
+        .header("DPoP", key.proof("POST", &token_url, None)?)
+        .json(&json!({ "user_id": "user_1", "email": "u@example.com" }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    // Refreshed with a proof from the same key: a new bound token
+    let refresh_url = format!("{base}/v1/auth/refresh");
+    let refreshed: Value = http
+        .post(&refresh_url)
+        .header("DPoP", key.proof("POST", &refresh_url, None)?)
+        .json(&json!({ "refresh_token": tokens["refresh_token"] }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    assert_eq!(refreshed["token_type"], "DPoP");
+    let claims = payload(refreshed["access_token"].as_str().unwrap())?;
+    assert_eq!(claims.dpop_jkt(), jwk_thumbprint(&key.jwk).as_deref());
+
+    // Without a proof the refresh token is refused
+    let response = http
+        .post(&refresh_url)
+        .json(&json!({ "refresh_token": refreshed["refresh_token"] }))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    Ok(())
+}
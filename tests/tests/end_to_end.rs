@@ -0,0 +1,275 @@
+// tests/tests/end_to_end.rs
+
+//! End-to-end flows spanning oauth2-client, oauth2-server, and jwt-service
+//!
+//! The browser is played by a non-redirecting HTTP client so every hop of the
+//! flow is asserted explicitly:
+//!
+//! ```text
+//! client /login ──> server /v1/oauth/authorize (consent) ──> POST approve
+//!      ──> redirect_uri?code ──> client /callback ──> server /v1/oauth/token
+//!      ──> server /v1/oauth/userinfo ──> jwt-service /v1/auth/* (session tokens)
+//! ```
+
+use anyhow::{Context, Result};
+use reqwest::{header::LOCATION, StatusCode, Url};
+use serde_json::{json, Value};
+use tokn_tests::{http_client, query_param, TestEnv, DEMO_CLIENT_ID, DEMO_REDIRECT_URI};
+
+// ---
+
+/// Read the `Location` header of a redirect response.
+fn location(response: &reqwest::Response) -> Result<Url> {
+    // ---
+    let value = response
+        .headers()
+        .get(LOCATION)
+        .context("redirect response has no Location header")?
+        .to_str()?;
+
+    Ok(Url::parse(value)?)
+}
+
+// ---
+
+/// Extract the access token the client demo page renders inside `<code>...</code>`.
+fn rendered_access_token(html: &str) -> Option<&str> {
+    // ---
+    let start = html.find("<code>")? + "<code>".len();
+    let end = html[start..].find("</code>")? + start;
+
+    Some(&html[start..end])
+}
+
+// ---
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn authorization_code_flow_then_jwt_session() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let stack = env.spawn_stack().await?;
+    let http = http_client();
+
+    // ---
+    // 1. Client login redirects to the authorization endpoint
+    let login = http
+        .get(format!("{}/login", stack.oauth2_client))
+        .send()
+        .await?;
+    assert!(login.status().is_redirection());
+
+    let authorize_url = location(&login)?;
+    assert!(authorize_url
+        .as_str()
+        .starts_with(&format!("{}/v1/oauth/authorize", stack.oauth2_server)));
+    assert_eq!(
+        query_param(&authorize_url, "response_type").as_deref(),
+        Some("code")
+    );
+    assert_eq!(
+        query_param(&authorize_url, "client_id").as_deref(),
+        Some(DEMO_CLIENT_ID)
+    );
+    assert_eq!(
+        query_param(&authorize_url, "redirect_uri").as_deref(),
+        Some(DEMO_REDIRECT_URI)
+    );
+    let state = query_param(&authorize_url, "state").context("login must send state")?;
+    let scope = query_param(&authorize_url, "scope").unwrap_or_default();
+
+    // ---
+    // 2. Server renders the consent page for this client
+    let consent = http.get(authorize_url.clone()).send().await?;
+    assert_eq!(consent.status(), StatusCode::OK);
+    let consent_html = consent.text().await?;
+    assert!(consent_html.contains(DEMO_CLIENT_ID));
+    assert!(consent_html.contains(r#"action="/v1/oauth/authorize""#));
+
+    // ---
+    // 3. User approves; server redirects to the client's redirect_uri with a code
+    let approve = http
+        .post(format!("{}/v1/oauth/authorize", stack.oauth2_server))
+        .form(&[
+            ("client_id", DEMO_CLIENT_ID),
+            ("redirect_uri", DEMO_REDIRECT_URI),
+            ("scope", scope.as_str()),
+            ("state", state.as_str()),
+            ("action", "approve"),
+        ])
+        .send()
+        .await?;
+    assert!(approve.status().is_redirection());
+
+    let callback_url = location(&approve)?;
+    assert!(callback_url.as_str().starts_with(DEMO_REDIRECT_URI));
+    assert_eq!(query_param(&callback_url, "state"), Some(state.clone()));
+    let code = query_param(&callback_url, "code").context("approval must issue a code")?;
+
+    // ---
+    // 4. Deliver the redirect to the in-process client, which exchanges the code
+    //    at /v1/oauth/token and fetches /v1/oauth/userinfo
+    let callback = http
+        .get(format!("{}/callback", stack.oauth2_client))
+        .query(&[("code", code.as_str()), ("state", state.as_str())])
+        .send()
+        .await?;
+    assert_eq!(callback.status(), StatusCode::OK);
+
+    let callback_html = callback.text().await?;
+    assert!(callback_html.contains("Successfully Authenticated!"));
+    assert!(callback_html.contains("<strong>demo</strong>"));
+
+    // ---
+    // 5. The access token the client received works against userinfo directly
+    let access_token =
+        rendered_access_token(&callback_html).context("callback page shows the access token")?;
+    let userinfo: tokn_core::UserInfo = http
+        .get(format!("{}/v1/oauth/userinfo", stack.oauth2_server))
+        .bearer_auth(access_token)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    assert_eq!(userinfo.username, "demo");
+
+    // ---
+    // 6. The authorization code was consumed by the client's exchange
+    let replay = http
+        .post(format!("{}/v1/oauth/token", stack.oauth2_server))
+        .form(&[
+            ("grant_type", "authorization_code"),
+            ("code", code.as_str()),
+            ("redirect_uri", DEMO_REDIRECT_URI),
+            ("client_id", DEMO_CLIENT_ID),
+            ("client_secret", tokn_tests::DEMO_CLIENT_SECRET),
+        ])
+        .send()
+        .await?;
+    assert_eq!(replay.status(), StatusCode::BAD_REQUEST);
+    let replay_body: Value = replay.json().await?;
+    assert_eq!(replay_body["error"], "invalid_grant");
+
+    // ---
+    // 7. Mint a jwt-service session for the authenticated user
+    let jwt = &stack.jwt_service;
+    let tokens: Value = http
+        .post(format!("{jwt}/v1/auth/token"))
+        .json(&json!({ "user_id": userinfo.sub, "email": "demo@example.com" }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let session_token = tokens["access_token"].as_str().unwrap().to_string();
+    let refresh_token = tokens["refresh_token"].as_str().unwrap().to_string();
+    assert_eq!(tokens["token_type"], "Bearer");
+
+    let protected: Value = http
+        .get(format!("{jwt}/v1/protected"))
+        .bearer_auth(&session_token)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    assert_eq!(protected["user_id"], userinfo.sub.as_str());
+
+    // ---
+    // 8. Refresh rotates the session; the old refresh token is single-use
+    let rotated: Value = http
+        .post(format!("{jwt}/v1/auth/refresh"))
+        .json(&json!({ "refresh_token": refresh_token }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let rotated_token = rotated["access_token"].as_str().unwrap().to_string();
+    assert_ne!(rotated["refresh_token"], refresh_token.as_str());
+
+    let replay = http
+        .post(format!("{jwt}/v1/auth/refresh"))
+        .json(&json!({ "refresh_token": refresh_token }))
+        .send()
+        .await?;
+    assert_eq!(replay.status(), StatusCode::UNAUTHORIZED);
+
+    // ---
+    // 9. Revocation takes effect on validate and on protected routes
+    let revoke = http
+        .post(format!("{jwt}/v1/auth/revoke"))
+        .json(&json!({ "token": rotated_token }))
+        .send()
+        .await?;
+    assert_eq!(revoke.status(), StatusCode::OK);
+
+    let validate: Value = http
+        .post(format!("{jwt}/v1/auth/validate"))
+        .json(&json!({ "token": rotated_token }))
+        .send()
+        .await?
+        .json()
+        .await?;
+    assert_eq!(validate["valid"], false);
+
+    let protected = http
+        .get(format!("{jwt}/v1/protected"))
+        .bearer_auth(&rotated_token)
+        .send()
+        .await?;
+    assert_eq!(protected.status(), StatusCode::UNAUTHORIZED);
+
+    // The first session token was never revoked and remains valid
+    let still_valid: Value = http
+        .post(format!("{jwt}/v1/auth/validate"))
+        .json(&json!({ "token": session_token }))
+        .send()
+        .await?
+        .json()
+        .await?;
+    assert_eq!(still_valid["valid"], true);
+
+    Ok(())
+}
+
+// ---
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn denied_consent_returns_access_denied_to_client() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let stack = env.spawn_stack().await?;
+    let http = http_client();
+
+    let login = http
+        .get(format!("{}/login", stack.oauth2_client))
+        .send()
+        .await?;
+    let state = query_param(&location(&login)?, "state").context("login must send state")?;
+
+    let deny = http
+        .post(format!("{}/v1/oauth/authorize", stack.oauth2_server))
+        .form(&[
+            ("client_id", DEMO_CLIENT_ID),
+            ("redirect_uri", DEMO_REDIRECT_URI),
+            ("scope", "profile"),
+            ("state", state.as_str()),
+            ("action", "deny"),
+        ])
+        .send()
+        .await?;
+    assert!(deny.status().is_redirection());
+
+    let callback_url = location(&deny)?;
+    assert_eq!(
+        query_param(&callback_url, "error").as_deref(),
+        Some("access_denied")
+    );
+    assert_eq!(query_param(&callback_url, "state"), Some(state));
+    assert!(query_param(&callback_url, "code").is_none());
+
+    Ok(())
+}
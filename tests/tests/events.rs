@@ -0,0 +1,134 @@
+// tests/tests/events.rs
+
+//! Auth events emitted by jwt-service and oauth2-server
+
+use anyhow::Result;
+use reqwest::{header::LOCATION, StatusCode};
+use serde_json::{json, Value};
+use tokn_events::AuthEventKind;
+use tokn_tests::{
+    http_client, query_param, RecordingPublisher, TestEnv, DEMO_CLIENT_ID, DEMO_CLIENT_SECRET,
+    DEMO_REDIRECT_URI,
+};
+
+// ---
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn jwt_service_emits_issue_reuse_and_revoke_events() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let recorder = RecordingPublisher::default();
+    let base = env
+        .spawn_jwt_service_with_events(recorder.events("jwt-service"))
+        .await?;
+    let http = http_client();
+
+    let tokens: Value = http
+        .post(format!("{base}/v1/auth/token"))
+        .json(&json!({ "user_id": "user_it", "email": "it@example.com" }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let access_token = tokens["access_token"].as_str().unwrap();
+    let refresh_token = tokens["refresh_token"].as_str().unwrap();
+
+    // ---
+    // Rotate once, then replay the rotated token
+    for expected in [StatusCode::OK, StatusCode::UNAUTHORIZED] {
+        let response = http
+            .post(format!("{base}/v1/auth/refresh"))
+            .json(&json!({ "refresh_token": refresh_token }))
+            .send()
+            .await?;
+        assert_eq!(response.status(), expected);
+    }
+
+    // A token that was never issued is not reuse
+    let unknown = http
+        .post(format!("{base}/v1/auth/refresh"))
+        .json(&json!({ "refresh_token": "never-issued" }))
+        .send()
+        .await?;
+    assert_eq!(unknown.status(), StatusCode::UNAUTHORIZED);
+
+    let revoked = http
+        .post(format!("{base}/v1/auth/revoke"))
+        .json(&json!({ "token": access_token }))
+        .send()
+        .await?;
+    assert_eq!(revoked.status(), StatusCode::OK);
+
+    // ---
+    let events = recorder.wait_for(4).await;
+    let kinds: Vec<_> = events.iter().map(|event| event.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            AuthEventKind::TokenIssued,
+            AuthEventKind::TokenIssued,
+            AuthEventKind::RefreshReuse,
+            AuthEventKind::TokenRevoked,
+        ]
+    );
+    assert!(events.iter().all(|event| event.service == "jwt-service"));
+    assert!(events
+        .iter()
+        .all(|event| event.subject.as_deref() == Some("user_it")));
+    assert_eq!(events[1].grant_type.as_deref(), Some("refresh_token"));
+
+    Ok(())
+}
+
+// ---
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn oauth2_server_emits_login_and_issue_events() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let recorder = RecordingPublisher::default();
+    let base = env
+        .spawn_oauth2_server_with_events(recorder.events("oauth2-server"))
+        .await?;
+    let http = http_client();
+
+    let response = http
+        .post(format!("{base}/v1/oauth/authorize"))
+        .form(&[
+            ("client_id", DEMO_CLIENT_ID),
+            ("redirect_uri", DEMO_REDIRECT_URI),
+            ("scope", "profile"),
+            ("state", "xyz"),
+            ("action", "approve"),
+        ])
+        .send()
+        .await?;
+    let location = reqwest::Url::parse(response.headers()[LOCATION].to_str()?)?;
+    let code = query_param(&location, "code").expect("redirect carries an authorization code");
+
+    http.post(format!("{base}/v1/oauth/token"))
+        .form(&[
+            ("grant_type", "authorization_code"),
+            ("code", &code),
+            ("redirect_uri", DEMO_REDIRECT_URI),
+            ("client_id", DEMO_CLIENT_ID),
+            ("client_secret", DEMO_CLIENT_SECRET),
+        ])
+        .send()
+        .await?
+        .error_for_status()?;
+
+    // ---
+    let events = recorder.wait_for(2).await;
+    let kinds: Vec<_> = events.iter().map(|event| event.kind).collect();
+    assert_eq!(kinds, [AuthEventKind::Login, AuthEventKind::TokenIssued]);
+    assert!(events
+        .iter()
+        .all(|event| event.client_id.as_deref() == Some(DEMO_CLIENT_ID)));
+    assert_eq!(events[1].grant_type.as_deref(), Some("authorization_code"));
+
+    Ok(())
+}
@@ -0,0 +1,75 @@
+// tests/tests/expires_in.rs
+
+//! Per-request access token lifetimes: `POST /v1/auth/token` takes an
+//! `expires_in` that shortens the token's lifetime but is capped at
+//! `jwt.access_token_expiry_seconds` (in-memory store)
+
+use anyhow::Result;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use tokn_core::TestClock;
+use tokn_tests::{jwt_config, post_json, spawn_jwt_service_in_memory};
+
+// ---
+
+const NOW: i64 = 1_700_000_000;
+
+// ---
+
+/// Sign `user_1` in asking for `expires_in`, returning the granted lifetime
+/// and the lifetime the access token's claims carry.
+async fn lifetime(base: &str, expires_in: Value) -> Result<(Value, Value)> {
+    // ---
+    let (status, tokens) = post_json(
+        base,
+        "/v1/auth/token",
+        json!({ "user_id": "user_1", "email": "u@example.com", "expires_in": expires_in }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{tokens}");
+
+    let (status, body) = post_json(
+        base,
+        "/v1/auth/validate",
+        json!({ "token": tokens["access_token"] }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let claims = &body["claims"];
+    let seconds = claims["exp"].as_i64().unwrap() - claims["iat"].as_i64().unwrap();
+    Ok((tokens["expires_in"].clone(), json!(seconds)))
+}
+
+// ---
+
+#[tokio::test]
+async fn callers_can_shorten_but_not_extend_token_lifetimes() -> Result<()> {
+    // ---
+    let clock = TestClock::at_timestamp(NOW);
+    let base = spawn_jwt_service_in_memory(jwt_config("redis://unused"), clock.shared()).await?;
+
+    assert_eq!(lifetime(&base, json!(60)).await?, (json!(60), json!(60)));
+    assert_eq!(
+        lifetime(&base, json!(86_400)).await?,
+        (json!(900), json!(900))
+    );
+    assert_eq!(
+        lifetime(&base, Value::Null).await?,
+        (json!(900), json!(900))
+    );
+
+    for expires_in in [0, -60] {
+        let (status, body) = post_json(
+            &base,
+            "/v1/auth/token",
+            json!({ "user_id": "user_1", "email": "u@example.com", "expires_in": expires_in }),
+        )
+        .await?;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+        assert_eq!(
+            body["detail"],
+            "expires_in must be a positive number of seconds"
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,101 @@
+// tests/tests/ffi.rs
+
+//! tokn-ffi's C ABI: claims of valid tokens, status codes for rejected ones,
+//! and freeing (no containers needed)
+
+use std::ffi::{CStr, CString};
+use std::ptr;
+use tokn_core::{generate_token, Claims, SystemClock, TestClock};
+use tokn_ffi::{
+    tokn_claims_free, tokn_status_message, tokn_validate_token, ToknClaims, TOKN_EXPIRED,
+    TOKN_INVALID_ARGUMENT, TOKN_INVALID_SIGNATURE, TOKN_MALFORMED, TOKN_OK,
+};
+
+// ---
+
+const SECRET: &str = "integration-test-secret-at-least-32-characters";
+
+// ---
+
+fn c(s: &str) -> CString {
+    // ---
+    CString::new(s).unwrap()
+}
+
+fn empty_claims() -> ToknClaims {
+    // ---
+    ToknClaims {
+        sub: ptr::null_mut(),
+        email: ptr::null_mut(),
+        jti: ptr::null_mut(),
+        exp: 0,
+        iat: 0,
+    }
+}
+
+fn validate(token: &str, secret: &str) -> i32 {
+    // ---
+    let (token, secret) = (c(token), c(secret));
+    unsafe { tokn_validate_token(token.as_ptr(), secret.as_ptr(), ptr::null_mut()) }
+}
+
+// ---
+
+#[test]
+fn valid_token_yields_its_claims() {
+    // ---
+    let claims = Claims::new("user_1".into(), "u@example.com".into(), 900, &SystemClock);
+    let token = c(&generate_token(&claims, SECRET).unwrap());
+    let secret = c(SECRET);
+    let mut out = empty_claims();
+
+    let status = unsafe { tokn_validate_token(token.as_ptr(), secret.as_ptr(), &mut out) };
+    assert_eq!(status, TOKN_OK);
+
+    unsafe {
+        assert_eq!(CStr::from_ptr(out.sub).to_str(), Ok("user_1"));
+        assert_eq!(CStr::from_ptr(out.email).to_str(), Ok("u@example.com"));
+        assert_eq!(CStr::from_ptr(out.jti).to_str(), Ok(claims.jti.as_str()));
+    }
+    assert_eq!(out.exp, claims.exp as i64);
+    assert_eq!(out.iat, claims.iat as i64);
+
+    // Freeing nulls the strings, so a second free is harmless
+    unsafe {
+        tokn_claims_free(&mut out);
+        assert!(out.sub.is_null() && out.email.is_null() && out.jti.is_null());
+        tokn_claims_free(&mut out);
+        tokn_claims_free(ptr::null_mut());
+    }
+}
+
+#[test]
+fn rejected_tokens_report_why() {
+    // ---
+    let past = TestClock::at_timestamp(1_700_000_000);
+    let claims = Claims::new("user_1".into(), "u@example.com".into(), 900, &past);
+    let expired = generate_token(&claims, SECRET).unwrap();
+    assert_eq!(validate(&expired, SECRET), TOKN_EXPIRED);
+
+    let claims = Claims::new("user_1".into(), "u@example.com".into(), 900, &SystemClock);
+    let token = generate_token(&claims, SECRET).unwrap();
+    assert_eq!(
+        validate(&token, "some-other-secret-at-least-32-characters"),
+        TOKN_INVALID_SIGNATURE
+    );
+    assert_eq!(validate("not.a.jwt", SECRET), TOKN_MALFORMED);
+
+    let secret = c(SECRET);
+    let status = unsafe { tokn_validate_token(ptr::null(), secret.as_ptr(), ptr::null_mut()) };
+    assert_eq!(status, TOKN_INVALID_ARGUMENT);
+}
+
+#[test]
+fn every_status_has_a_message() {
+    // ---
+    let message = |status| unsafe { CStr::from_ptr(tokn_status_message(status)) };
+
+    assert_eq!(message(TOKN_EXPIRED).to_str(), Ok("Token has expired"));
+    assert_eq!(message(TOKN_OK).to_str(), Ok("Token is valid"));
+    assert_eq!(message(99).to_str(), Ok("Unknown status"));
+}
@@ -0,0 +1,124 @@
+// tests/tests/grpc_introspection.rs
+
+//! gRPC token introspection against real Redis and Postgres
+
+use anyhow::Result;
+use reqwest::{header::LOCATION, StatusCode};
+use serde_json::{json, Value};
+use tokn_proto::{IntrospectRequest, TokenIntrospectionClient};
+use tokn_tests::{
+    http_client, query_param, TestEnv, DEMO_CLIENT_ID, DEMO_CLIENT_SECRET, DEMO_REDIRECT_URI,
+};
+
+// ---
+
+fn request(token: &str) -> IntrospectRequest {
+    // ---
+    IntrospectRequest {
+        token: token.to_string(),
+        token_type_hint: String::new(),
+    }
+}
+
+// ---
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn jwt_service_introspects_and_honors_revocation() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let base = env.spawn_jwt_service().await?;
+    let mut grpc = TokenIntrospectionClient::connect(env.spawn_jwt_grpc().await?).await?;
+    let http = http_client();
+
+    let tokens: Value = http
+        .post(format!("{base}/v1/auth/token"))
+        .json(&json!({ "user_id": "user_it", "email": "it@example.com" }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let access_token = tokens["access_token"].as_str().unwrap();
+
+    // ---
+    let active = grpc.introspect(request(access_token)).await?.into_inner();
+    assert!(active.active);
+    assert_eq!(active.sub.as_deref(), Some("user_it"));
+    assert_eq!(active.email.as_deref(), Some("it@example.com"));
+
+    // ---
+    // Revoked tokens are inactive, as are tokens this service did not sign
+    let revoked = http
+        .post(format!("{base}/v1/auth/revoke"))
+        .json(&json!({ "token": access_token }))
+        .send()
+        .await?;
+    assert_eq!(revoked.status(), StatusCode::OK);
+
+    let inactive = grpc.introspect(request(access_token)).await?.into_inner();
+    assert!(!inactive.active);
+    assert_eq!(inactive.sub, None);
+
+    let garbage = grpc.introspect(request("not-a-jwt")).await?.into_inner();
+    assert!(!garbage.active);
+
+    Ok(())
+}
+
+// ---
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn oauth2_server_introspects_opaque_tokens() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let base = env.spawn_oauth2_server().await?;
+    let mut grpc = TokenIntrospectionClient::connect(env.spawn_oauth2_grpc().await?).await?;
+    let http = http_client();
+
+    let response = http
+        .post(format!("{base}/v1/oauth/authorize"))
+        .form(&[
+            ("client_id", DEMO_CLIENT_ID),
+            ("redirect_uri", DEMO_REDIRECT_URI),
+            ("scope", "profile"),
+            ("state", "xyz"),
+            ("action", "approve"),
+        ])
+        .send()
+        .await?;
+    let location = reqwest::Url::parse(response.headers()[LOCATION].to_str()?)?;
+    let code = query_param(&location, "code").expect("redirect carries an authorization code");
+
+    let token: Value = http
+        .post(format!("{base}/v1/oauth/token"))
+        .form(&[
+            ("grant_type", "authorization_code"),
+            ("code", &code),
+            ("redirect_uri", DEMO_REDIRECT_URI),
+            ("client_id", DEMO_CLIENT_ID),
+            ("client_secret", DEMO_CLIENT_SECRET),
+        ])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let access_token = token["access_token"].as_str().unwrap();
+
+    // ---
+    let active = grpc.introspect(request(access_token)).await?.into_inner();
+    assert!(active.active);
+    assert_eq!(active.sub.as_deref(), Some("user_001"));
+    assert_eq!(active.client_id.as_deref(), Some(DEMO_CLIENT_ID));
+    assert_eq!(active.scope.as_deref(), Some("profile"));
+
+    let unknown = grpc
+        .introspect(request("not-a-real-token"))
+        .await?
+        .into_inner();
+    assert!(!unknown.active);
+
+    Ok(())
+}
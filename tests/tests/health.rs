@@ -0,0 +1,121 @@
+// tests/tests/health.rs
+
+//! Liveness and readiness endpoints: `tokn_server::health_router` reporting
+//! probe failures, timeouts, and degraded dependencies, and jwt-service and
+//! oauth2-server probing their real dependencies
+
+use anyhow::Result;
+use axum::Router;
+use reqwest::StatusCode;
+use serde_json::Value;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokn_core::SystemClock;
+use tokn_server::HealthChecks;
+use tokn_tests::{http_client, jwt_config, serve, TestEnv};
+
+// ---
+
+/// GET `path` on `base`, returning the status and JSON body.
+async fn probe(base: &str, path: &str) -> Result<(StatusCode, Value)> {
+    // ---
+    let response = http_client().get(format!("{base}{path}")).send().await?;
+    assert_eq!(response.headers()["cache-control"], "no-store");
+    Ok((response.status(), response.json().await?))
+}
+
+// ---
+
+#[tokio::test]
+async fn readiness_reports_each_dependency() -> Result<()> {
+    // ---
+    let checks = HealthChecks::new("test-service")
+        .timeout(Duration::from_millis(100))
+        .check("up", || async { Ok::<_, std::io::Error>(()) })
+        .check("down", || async { Err::<(), _>("connection refused") })
+        .check("slow", || async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok::<_, std::io::Error>(())
+        });
+    let base = serve(Router::new().merge(tokn_server::health_router(checks))).await?;
+
+    let (status, ready) = probe(&base, "/health/ready").await?;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(ready["status"], "fail");
+    assert_eq!(ready["service"], "test-service");
+    assert_eq!(ready["checks"]["up"]["status"], "ok");
+    assert!(ready["checks"]["up"]["latency_ms"].is_number());
+    assert!(ready["checks"]["up"].get("error").is_none());
+    assert_eq!(ready["checks"]["down"]["status"], "fail");
+    assert_eq!(ready["checks"]["down"]["error"], "connection refused");
+    assert_eq!(ready["checks"]["slow"]["error"], "no answer within 100ms");
+
+    // Liveness never probes dependencies
+    let (status, live) = probe(&base, "/health/live").await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(live["status"], "ok");
+    assert!(live.get("checks").is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn degraded_dependencies_keep_the_service_ready() -> Result<()> {
+    // ---
+    let degraded = Arc::new(AtomicBool::new(true));
+    let flag = degraded.clone();
+    let checks = HealthChecks::new("test-service")
+        .check("redis", || async { Ok::<_, std::io::Error>(()) })
+        .degraded_when("redis", move || {
+            flag.load(Ordering::SeqCst)
+                .then(|| "circuit breaker open".to_string())
+        });
+    let base = serve(Router::new().merge(tokn_server::health_router(checks))).await?;
+
+    let (status, ready) = probe(&base, "/health/ready").await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(ready["status"], "degraded");
+    assert_eq!(ready["checks"]["redis"]["status"], "degraded");
+    assert_eq!(ready["checks"]["redis"]["reason"], "circuit breaker open");
+
+    degraded.store(false, Ordering::SeqCst);
+    let (status, ready) = probe(&base, "/health/ready").await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(ready["status"], "ok");
+    assert!(ready["checks"]["redis"].get("reason").is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn stateless_jwt_service_is_ready_without_dependencies() -> Result<()> {
+    // ---
+    let mut config = jwt_config("redis://unused");
+    config.jwt.stateless = true;
+    let state = jwt_service::AppState::stateless(config, SystemClock::shared())?;
+    let base = serve(jwt_service::build_router(state)).await?;
+
+    let (status, ready) = probe(&base, "/health/ready").await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(ready["status"], "ok");
+    assert_eq!(ready["service"], "jwt-service");
+    assert!(ready.get("checks").is_none());
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn services_probe_redis_and_postgres() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+
+    let jwt = env.spawn_jwt_service().await?;
+    let (status, ready) = probe(&jwt, "/health/ready").await?;
+    assert_eq!(status, StatusCode::OK, "{ready}");
+    assert_eq!(ready["checks"]["redis"]["status"], "ok");
+
+    let oauth2 = env.spawn_oauth2_server().await?;
+    let (status, ready) = probe(&oauth2, "/health/ready").await?;
+    assert_eq!(status, StatusCode::OK, "{ready}");
+    assert_eq!(ready["checks"]["postgres"]["status"], "ok");
+    Ok(())
+}
@@ -0,0 +1,172 @@
+// tests/tests/history.rs
+
+//! Soft-deleted clients and users and the change history recorded for them,
+//! against a real Postgres
+
+use anyhow::Result;
+use reqwest::header::LOCATION;
+use reqwest::StatusCode;
+use serde_json::Value;
+use tokn_tests::{http_client, query_param, TestEnv, DEMO_CLIENT_ID, DEMO_REDIRECT_URI};
+
+// ---
+
+const ACTOR: &str = "tokn-admin (test)";
+const CLIENT_ID: &str = "history_client";
+
+// ---
+
+/// Approve the consent page for `client_id` and exchange the code, returning
+/// the token endpoint's status and body.
+async fn authorize_and_exchange(
+    base: &str,
+    client_id: &str,
+    client_secret: &str,
+) -> Result<(StatusCode, Value)> {
+    // ---
+    let http = http_client();
+    let response = http
+        .post(format!("{base}/v1/oauth/authorize"))
+        .form(&[
+            ("client_id", client_id),
+            ("redirect_uri", DEMO_REDIRECT_URI),
+            ("scope", "profile"),
+            ("state", "xyz"),
+            ("action", "approve"),
+        ])
+        .send()
+        .await?;
+    let location = reqwest::Url::parse(response.headers()[LOCATION].to_str()?)?;
+    let code = query_param(&location, "code").unwrap();
+
+    let response = http
+        .post(format!("{base}/v1/oauth/token"))
+        .form(&[
+            ("grant_type", "authorization_code"),
+            ("code", &code),
+            ("redirect_uri", DEMO_REDIRECT_URI),
+            ("client_id", client_id),
+            ("client_secret", client_secret),
+        ])
+        .send()
+        .await?;
+    Ok((response.status(), response.json().await?))
+}
+
+// ---
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn deleted_client_is_refused_until_restored() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let base = env.spawn_oauth2_server().await?;
+    let secret = oauth2_server::generate_client_secret();
+    oauth2_server::create_client(&env.pool, ACTOR, CLIENT_ID, &secret, DEMO_REDIRECT_URI).await?;
+
+    let (status, _) = authorize_and_exchange(&base, CLIENT_ID, &secret).await?;
+    assert_eq!(status, StatusCode::OK);
+
+    // ---
+    // Deleting revokes the client's tokens and hides it from every lookup
+    let revoked = oauth2_server::delete_client(&env.pool, ACTOR, CLIENT_ID).await?;
+    assert_eq!(revoked.map(|tokens| tokens.len()), Some(1));
+    assert!(oauth2_server::delete_client(&env.pool, ACTOR, CLIENT_ID)
+        .await?
+        .is_none());
+
+    let (status, body) = authorize_and_exchange(&base, CLIENT_ID, &secret).await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["error"], "invalid_client");
+
+    let listed = oauth2_server::list_clients(&env.pool, false).await?;
+    assert!(listed.iter().all(|c| c.client_id != CLIENT_ID));
+    let listed = oauth2_server::list_clients(&env.pool, true).await?;
+    let deleted = listed.iter().find(|c| c.client_id == CLIENT_ID).unwrap();
+    assert!(deleted.deleted_at.is_some());
+    assert!(!oauth2_server::reset_client_secret(&env.pool, ACTOR, CLIENT_ID, "x").await?);
+
+    // ---
+    // Restoring brings the client back with its secret
+    assert!(oauth2_server::restore_client(&env.pool, ACTOR, CLIENT_ID).await?);
+    assert!(!oauth2_server::restore_client(&env.pool, ACTOR, CLIENT_ID).await?);
+    let (status, _) = authorize_and_exchange(&base, CLIENT_ID, &secret).await?;
+    assert_eq!(status, StatusCode::OK);
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn deleted_user_loses_sessions_and_userinfo() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let base = env.spawn_oauth2_server().await?;
+    let secret = oauth2_server::generate_client_secret();
+    oauth2_server::create_client(&env.pool, ACTOR, CLIENT_ID, &secret, DEMO_REDIRECT_URI).await?;
+    let (_, token) = authorize_and_exchange(&base, CLIENT_ID, &secret).await?;
+    let access_token = token["access_token"].as_str().unwrap();
+
+    let revoked = oauth2_server::delete_user(&env.pool, ACTOR, "user_001").await?;
+    assert_eq!(revoked.map(|tokens| tokens.len()), Some(1));
+
+    let userinfo = http_client()
+        .get(format!("{base}/v1/oauth/userinfo"))
+        .bearer_auth(access_token)
+        .send()
+        .await?;
+    assert_eq!(userinfo.status(), StatusCode::UNAUTHORIZED);
+
+    // A code issued to the deleted user is not honored
+    let (status, body) = authorize_and_exchange(&base, CLIENT_ID, &secret).await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "invalid_grant");
+
+    let users = oauth2_server::list_users(&env.pool, Some("user_001"), false, 10).await?;
+    assert!(users.is_empty());
+    let users = oauth2_server::list_users(&env.pool, Some("user_001"), true, 10).await?;
+    assert_eq!(users.len(), 1);
+
+    assert!(oauth2_server::restore_user(&env.pool, ACTOR, "user_001").await?);
+    let (status, _) = authorize_and_exchange(&base, CLIENT_ID, &secret).await?;
+    assert_eq!(status, StatusCode::OK);
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn changes_are_recorded_with_actor_and_without_secrets() -> Result<()> {
+    // ---
+    use oauth2_server::Entity;
+
+    let env = TestEnv::start().await?;
+    let secret = oauth2_server::generate_client_secret();
+    oauth2_server::create_client(&env.pool, ACTOR, CLIENT_ID, &secret, DEMO_REDIRECT_URI).await?;
+    let new_secret = oauth2_server::generate_client_secret();
+    oauth2_server::reset_client_secret(&env.pool, "admin-ui", CLIENT_ID, &new_secret).await?;
+    oauth2_server::delete_client(&env.pool, ACTOR, CLIENT_ID).await?;
+    oauth2_server::restore_client(&env.pool, ACTOR, CLIENT_ID).await?;
+
+    let changes =
+        oauth2_server::list_changes(&env.pool, Some(Entity::Client), Some(CLIENT_ID), 10).await?;
+    let actions: Vec<_> = changes.iter().map(|c| c.action.as_str()).collect();
+    assert_eq!(actions, ["restored", "deleted", "updated", "created"]);
+
+    let reset = &changes[2];
+    assert_eq!(reset.actor, "admin-ui");
+    assert_eq!(reset.field.as_deref(), Some("client_secret"));
+    for change in &changes {
+        assert_eq!(change.entity, "client");
+        for value in [&change.old_value, &change.new_value].into_iter().flatten() {
+            assert_ne!(value, &secret);
+            assert_ne!(value, &new_secret);
+        }
+    }
+    assert_eq!(changes[3].new_value.as_deref(), Some(DEMO_REDIRECT_URI));
+
+    // Filters narrow to one record
+    let others =
+        oauth2_server::list_changes(&env.pool, Some(Entity::Client), Some(DEMO_CLIENT_ID), 10)
+            .await?;
+    assert!(others.is_empty());
+    Ok(())
+}
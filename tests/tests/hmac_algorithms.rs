@@ -0,0 +1,139 @@
+// tests/tests/hmac_algorithms.rs
+
+//! HS384 and HS512 access tokens: HMAC keys of each size, refusing tokens
+//! of another HMAC algorithm, and `JWT_ALGORITHM` selecting them in
+//! jwt-service (no containers needed)
+
+use anyhow::Result;
+use jsonwebtoken::decode_header;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use tokn_core::{Claims, JwtKeys, SigningAlgorithm, SystemClock, TestClock, TokenError};
+use tokn_tests::{http_client, jwt_config, spawn_jwt_service_in_memory};
+
+// ---
+
+/// 64 characters: long enough for HS512, and so for HS256 and HS384 too.
+const SECRET: &str = "Jx4q9Lr2vTz7Wm1Kp8Ns3Hd6Bf0Gc5YeQa2Wz7Rt4Yu1Io8Pl3Kj6Hg9Fd0Sx5Cv";
+
+const PRIVATE_PEM: &[u8] = include_bytes!("../fixtures/rsa_private.pem");
+const PUBLIC_PEM: &[u8] = include_bytes!("../fixtures/rsa_public.pem");
+
+// ---
+
+fn claims() -> Claims {
+    // ---
+    Claims::new("user_1".into(), "u@example.com".into(), 900, &SystemClock)
+}
+
+// ---
+
+#[test]
+fn hs384_and_hs512_sign_and_verify() -> Result<()> {
+    // ---
+    for algorithm in [SigningAlgorithm::Hs384, SigningAlgorithm::Hs512] {
+        let keys = JwtKeys::hmac(algorithm, SECRET)?;
+        assert_eq!(keys.algorithm(), algorithm);
+        let token = keys.sign(&claims())?;
+        assert_eq!(
+            format!("{:?}", decode_header(&token)?.alg),
+            algorithm.as_str()
+        );
+        assert_eq!(keys.verify(&token, &SystemClock)?.sub, "user_1");
+
+        // The same secret under another HMAC algorithm does not verify it
+        assert!(matches!(
+            JwtKeys::hs256(SECRET).verify(&token, &SystemClock),
+            Err(TokenError::InvalidAlgorithm)
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn hmac_algorithms_take_secrets_and_key_pairs_take_pems() {
+    // ---
+    assert_eq!(SigningAlgorithm::Hs256.min_secret_len(), Some(32));
+    assert_eq!(SigningAlgorithm::Hs384.min_secret_len(), Some(48));
+    assert_eq!(SigningAlgorithm::Hs512.min_secret_len(), Some(64));
+    assert_eq!(SigningAlgorithm::Rs256.min_secret_len(), None);
+    assert_eq!("hs512".parse(), Ok(SigningAlgorithm::Hs512));
+
+    assert!(matches!(
+        JwtKeys::hmac(SigningAlgorithm::Rs256, SECRET),
+        Err(TokenError::InvalidKey(_))
+    ));
+    assert!(matches!(
+        JwtKeys::from_public_pem(SigningAlgorithm::Hs384, b"not a pem"),
+        Err(TokenError::InvalidKey(_))
+    ));
+}
+
+#[test]
+fn previous_secrets_use_the_current_hmac_algorithm() -> Result<()> {
+    // ---
+    let old = JwtKeys::hmac(SigningAlgorithm::Hs512, SECRET)?;
+    let token = old.sign(&claims())?;
+
+    let rotated = JwtKeys::hmac(SigningAlgorithm::Hs512, &SECRET.replace('J', "K"))?
+        .with_previous_secret(SECRET);
+    assert_eq!(rotated.verify(&token, &SystemClock)?.sub, "user_1");
+    Ok(())
+}
+
+#[test]
+fn previous_secrets_verify_tokens_of_another_algorithm_than_the_current_key() -> Result<()> {
+    // ---
+    let old = JwtKeys::hs256(SECRET);
+    let token = old.sign(&claims())?;
+
+    // Moving from an HMAC secret to a key pair keeps old tokens valid
+    let rotated = JwtKeys::rs256(PRIVATE_PEM, PUBLIC_PEM)?.with_previous_secret(SECRET);
+    assert_eq!(rotated.verify(&token, &SystemClock)?.sub, "user_1");
+    let current = rotated.sign(&claims())?;
+    assert_eq!(rotated.verify(&current, &SystemClock)?.sub, "user_1");
+
+    // Without the previous secret the old token names the wrong algorithm
+    let unrotated = JwtKeys::rs256(PRIVATE_PEM, PUBLIC_PEM)?;
+    assert!(matches!(
+        unrotated.verify(&token, &SystemClock),
+        Err(TokenError::InvalidAlgorithm)
+    ));
+
+    // A different secret under the old algorithm is still refused
+    let forged = JwtKeys::hs256(&SECRET.replace('J', "K")).sign(&claims())?;
+    assert!(matches!(
+        rotated.verify(&forged, &SystemClock),
+        Err(TokenError::InvalidSignature)
+    ));
+    Ok(())
+}
+
+#[tokio::test]
+async fn jwt_service_signs_with_the_configured_hmac_algorithm() -> Result<()> {
+    // ---
+    let mut config = jwt_config("redis://unused");
+    config.jwt.algorithm = SigningAlgorithm::Hs512;
+    config.jwt.secret = Some(SECRET.into());
+    let clock = TestClock::at_timestamp(1_700_000_000).shared();
+    let base = spawn_jwt_service_in_memory(config, clock).await?;
+
+    let tokens: Value = http_client()
+        .post(format!("{base}/v1/auth/token"))
+        .json(&json!({ "user_id": "user_1", "email": "u@example.com" }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let token = tokens["access_token"].as_str().unwrap();
+    assert_eq!(format!("{:?}", decode_header(token)?.alg), "HS512");
+
+    let response = http_client()
+        .post(format!("{base}/v1/auth/validate"))
+        .json(&json!({ "token": token }))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    Ok(())
+}
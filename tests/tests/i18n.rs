@@ -0,0 +1,142 @@
+// tests/tests/i18n.rs
+
+//! Page localization: locale negotiation, deployment overrides, and the
+//! oauth2-client pages served per `Accept-Language` (no containers needed)
+
+use anyhow::Result;
+use reqwest::header::{ACCEPT_LANGUAGE, CONTENT_LANGUAGE, VARY};
+use std::sync::Arc;
+use tokn_i18n::{I18nConfig, I18nError, Localizer};
+use tokn_tests::{http_client, oauth2_client_config, serve};
+
+// ---
+
+fn negotiated(localizer: &Arc<Localizer>, accept_language: Option<&str>) -> String {
+    // ---
+    localizer.negotiate(accept_language).lang()
+}
+
+/// A fresh override directory under the system temp dir.
+fn override_dir(name: &str) -> std::path::PathBuf {
+    // ---
+    let dir = std::env::temp_dir().join(format!("tokn-i18n-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+// ---
+
+#[test]
+fn accept_language_picks_the_best_available_locale() {
+    // ---
+    let localizer = Arc::new(Localizer::builtin());
+
+    assert_eq!(negotiated(&localizer, Some("es")), "es");
+    // Region falls back to the language
+    assert_eq!(negotiated(&localizer, Some("de-AT")), "de");
+    // Weights win over header order
+    assert_eq!(negotiated(&localizer, Some("fr;q=0.4, es;q=0.9")), "es");
+    // Unsupported languages and q=0 are skipped
+    assert_eq!(negotiated(&localizer, Some("ja, de;q=0, fr;q=0.5")), "fr");
+    // Nothing usable falls back to the default
+    assert_eq!(negotiated(&localizer, Some("ja, *")), "en");
+    assert_eq!(negotiated(&localizer, None), "en");
+}
+
+#[test]
+fn deployments_can_force_and_default_locales() -> Result<()> {
+    // ---
+    let forced = Arc::new(Localizer::new(&I18nConfig {
+        locale: Some("de".into()),
+        ..Default::default()
+    })?);
+    assert_eq!(negotiated(&forced, Some("fr")), "de");
+
+    let spanish_default = Arc::new(Localizer::new(&I18nConfig {
+        default_locale: "es".into(),
+        ..Default::default()
+    })?);
+    assert_eq!(negotiated(&spanish_default, Some("ja")), "es");
+    assert_eq!(negotiated(&spanish_default, Some("fr")), "fr");
+
+    let unknown = Localizer::new(&I18nConfig {
+        default_locale: "ja".into(),
+        ..Default::default()
+    });
+    assert!(matches!(unknown, Err(I18nError::UnknownLocale(..))));
+
+    Ok(())
+}
+
+#[test]
+fn override_directory_replaces_and_adds_translations() -> Result<()> {
+    // ---
+    let dir = override_dir("overrides");
+    std::fs::create_dir_all(dir.join("en"))?;
+    std::fs::write(dir.join("en/branding.ftl"), "consent-approve = Allow\n")?;
+    std::fs::create_dir_all(dir.join("pt-BR"))?;
+    std::fs::write(dir.join("pt-BR/pages.ftl"), "consent-approve = Permitir\n")?;
+
+    let localizer = Arc::new(Localizer::new(&I18nConfig {
+        dir: Some(dir.clone()),
+        ..Default::default()
+    })?);
+
+    let english = localizer.negotiate(Some("en"));
+    assert_eq!(english.text("consent-approve"), "Allow");
+    assert_eq!(english.text("consent-deny"), "Deny");
+
+    // New locale; messages it lacks fall back to the default locale
+    let portuguese = localizer.negotiate(Some("pt-BR"));
+    assert_eq!(portuguese.lang(), "pt-BR");
+    assert_eq!(portuguese.text("consent-approve"), "Permitir");
+    assert_eq!(portuguese.text("consent-deny"), "Deny");
+
+    std::fs::write(dir.join("en/broken.ftl"), "consent-approve = {\n")?;
+    let broken = Localizer::new(&I18nConfig {
+        dir: Some(dir.clone()),
+        ..Default::default()
+    });
+    assert!(matches!(broken, Err(I18nError::Parse { .. })));
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[test]
+fn message_arguments_are_html_escaped() {
+    // ---
+    let localizer = Arc::new(Localizer::builtin());
+    let messages = localizer.negotiate(Some("en"));
+
+    assert_eq!(
+        messages.format("consent-intro", &[("client", "<script>x</script>")]),
+        "Application <strong>&lt;script&gt;x&lt;/script&gt;</strong> wants to access your account."
+    );
+}
+
+#[tokio::test]
+async fn client_pages_follow_accept_language() -> Result<()> {
+    // ---
+    let config = oauth2_client_config("http://127.0.0.1:1", "redis://unused");
+    let base = serve(oauth2_client::build_router(Arc::new(config))?).await?;
+    let http = http_client();
+
+    let french = http
+        .get(format!("{base}/"))
+        .header(ACCEPT_LANGUAGE, "fr-FR,fr;q=0.9,en;q=0.8")
+        .send()
+        .await?;
+    assert_eq!(french.headers()[CONTENT_LANGUAGE], "fr");
+    assert_eq!(french.headers()[VARY], "accept-language");
+    let body = french.text().await?;
+    assert!(body.contains(r#"<html lang="fr">"#));
+    assert!(body.contains("Se connecter avec OAuth2"));
+
+    let english = http.get(format!("{base}/profile")).send().await?;
+    assert_eq!(english.headers()[CONTENT_LANGUAGE], "en");
+    assert!(english.text().await?.contains("You are authenticated!"));
+
+    Ok(())
+}
@@ -0,0 +1,190 @@
+// tests/tests/introspection.rs
+
+//! RFC 7662 introspection on jwt-service: caller authentication, active and
+//! inactive tokens on a stateless service (no containers needed), and revoked
+//! tokens against real Redis
+
+use anyhow::Result;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use tokn_core::{generate_token, Claims, SystemClock};
+use tokn_server::{ServiceAuth, ServiceAuthConfig};
+use tokn_tests::{http_client, jwt_config, serve, TestEnv, TEST_ADMIN_TOKEN, TEST_JWT_SECRET};
+
+// ---
+
+/// Issuer key the resource server introspects with.
+const ISSUER_KEY: &str = "Qm3Tz8Wd1Kx6Vb4Nr9Hj2Lc7Pf0Gs5Ya";
+
+const SERVICE_AUTH_KEY: &str = "service-auth-test-key-at-least-32-characters";
+
+// ---
+
+/// [`jwt_config`] accepting [`ISSUER_KEY`].
+fn config() -> jwt_service::Config {
+    // ---
+    let mut config = jwt_config("redis://unused");
+    config.issuer.keys = Some(ISSUER_KEY.into());
+    config
+}
+
+async fn spawn_stateless() -> Result<String> {
+    // ---
+    let mut config = config();
+    config.jwt.stateless = true;
+
+    let state = jwt_service::AppState::stateless(config, SystemClock::shared())?;
+    serve(jwt_service::build_router(state)).await
+}
+
+/// Issue an access token from jwt-service at `base`.
+async fn issue(base: &str) -> Result<String> {
+    // ---
+    let tokens: Value = http_client()
+        .post(format!("{base}/v1/auth/token"))
+        .json(&json!({
+            "user_id": "user_1",
+            "email": "u@example.com",
+            "scope": "orders:read",
+        }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(tokens["access_token"].as_str().unwrap().to_string())
+}
+
+/// Introspect `token` at jwt-service at `base` with [`ISSUER_KEY`],
+/// expecting 200 OK.
+async fn introspect(base: &str, token: &str) -> Result<Value> {
+    // ---
+    let response = http_client()
+        .post(format!("{base}/v1/auth/introspect"))
+        .header("X-Issuer-Key", ISSUER_KEY)
+        .form(&[("token", token), ("token_type_hint", "access_token")])
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["cache-control"], "no-store");
+    Ok(response.json().await?)
+}
+
+// ---
+
+#[tokio::test]
+async fn active_tokens_report_their_claims() -> Result<()> {
+    // ---
+    let base = spawn_stateless().await?;
+    let token = issue(&base).await?;
+
+    let introspection = introspect(&base, &token).await?;
+    assert_eq!(introspection["active"], true, "{introspection}");
+    assert_eq!(introspection["sub"], "user_1");
+    assert_eq!(introspection["scope"], "orders:read");
+    assert_eq!(introspection["token_type"], "Bearer");
+    assert!(introspection["exp"].as_u64() > introspection["iat"].as_u64());
+    Ok(())
+}
+
+#[tokio::test]
+async fn invalid_and_expired_tokens_are_inactive() -> Result<()> {
+    // ---
+    let base = spawn_stateless().await?;
+    let expired = Claims::new("user_1".into(), "u@example.com".into(), -3600, &SystemClock);
+    let forged = Claims::new("user_1".into(), "u@example.com".into(), 900, &SystemClock);
+
+    for token in [
+        "not-a-jwt".to_string(),
+        generate_token(&expired, TEST_JWT_SECRET)?,
+        generate_token(&forged, "an-unrelated-secret-of-at-least-32-chars")?,
+    ] {
+        // Nothing but `active` is disclosed about an inactive token
+        assert_eq!(introspect(&base, &token).await?, json!({ "active": false }));
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn callers_must_authenticate() -> Result<()> {
+    // ---
+    let mut config = config();
+    config.jwt.stateless = true;
+    config.admin.token = Some(TEST_ADMIN_TOKEN.into());
+    config.service_auth = ServiceAuthConfig {
+        key: Some(SERVICE_AUTH_KEY.into()),
+        ..Default::default()
+    };
+    let service_auth = config.service_auth.clone();
+    let state = jwt_service::AppState::stateless(config, SystemClock::shared())?;
+    let base = serve(jwt_service::build_router(state)).await?;
+    let token = issue(&base).await?;
+    let url = format!("{base}/v1/auth/introspect");
+    let form = format!("token={token}");
+    let http = http_client();
+
+    // Anonymous callers learn nothing, not even whether the token is active
+    let response = http.post(&url).form(&[("token", &token)]).send().await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let problem: Value = response.json().await?;
+    assert_eq!(problem["type"], "https://tokn.dev/problems/unauthenticated");
+    assert!(problem.get("active").is_none());
+
+    for (name, value) in [
+        ("X-Issuer-Key", "not-a-configured-issuer-key"),
+        ("Authorization", "Bearer not-the-admin-token"),
+    ] {
+        let response = http
+            .post(&url)
+            .header(name, value)
+            .form(&[("token", &token)])
+            .send()
+            .await?;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED, "{name}");
+    }
+
+    // The admin token and a signed request from a tokn service are accepted
+    let response = http
+        .post(&url)
+        .bearer_auth(TEST_ADMIN_TOKEN)
+        .form(&[("token", &token)])
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let caller = ServiceAuth::new("resource-server", &service_auth, SystemClock::shared());
+    let mut request = http
+        .post(&url)
+        .header("content-type", "application/x-www-form-urlencoded")
+        .body(form.clone());
+    for (name, value) in caller.sign("POST", "/v1/auth/introspect", form.as_bytes()) {
+        request = request.header(name.as_str(), value);
+    }
+    let response = request.send().await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let introspection: Value = response.json().await?;
+    assert_eq!(introspection["active"], true, "{introspection}");
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn revoked_tokens_are_inactive() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let base = env
+        .spawn_jwt_service_with_config(config(), SystemClock::shared())
+        .await?;
+    let token = issue(&base).await?;
+    assert_eq!(introspect(&base, &token).await?["active"], true);
+
+    http_client()
+        .post(format!("{base}/v1/auth/revoke"))
+        .json(&json!({ "token": token }))
+        .send()
+        .await?
+        .error_for_status()?;
+
+    assert_eq!(introspect(&base, &token).await?, json!({ "active": false }));
+    Ok(())
+}
@@ -0,0 +1,144 @@
+// tests/tests/issuer_keys.rs
+
+//! Issuer keys on `POST /v1/auth/token`: configured keys on a stateless
+//! jwt-service, and keys managed through `/admin/issuer-keys` in Redis
+
+use anyhow::Result;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use tokn_core::SystemClock;
+use tokn_tests::{http_client, jwt_config, serve, TestEnv};
+
+// ---
+
+const ISSUER_KEY: &str = "Qm3Tz8Wd1Kx6Vb4Nr9Hj2Lc7Pf0Gs5Ya";
+const ADMIN_TOKEN: &str = "Vd7Kq2Xm9Lt4Wz1Hb6Nc3Rf8Jp0Gs5Ye";
+
+// ---
+
+/// POST a token request to `base` with `issuer_key` in `X-Issuer-Key`.
+async fn request_token(base: &str, issuer_key: Option<&str>) -> Result<reqwest::Response> {
+    // ---
+    let mut request = http_client()
+        .post(format!("{base}/v1/auth/token"))
+        .json(&json!({ "user_id": "user_1", "email": "u@example.com" }));
+    if let Some(issuer_key) = issuer_key {
+        request = request.header("X-Issuer-Key", issuer_key);
+    }
+    Ok(request.send().await?)
+}
+
+// ---
+
+#[tokio::test]
+async fn configured_keys_guard_token_issuance() -> Result<()> {
+    // ---
+    let mut config = jwt_config("redis://unused");
+    config.jwt.stateless = true;
+    config.issuer = jwt_service::IssuerConfig {
+        require_key: true,
+        keys: Some(format!("{ISSUER_KEY}, Zr8Kd2Wq6Vn1Xt5Mb9Hc3Lp7Fj0Gs4Ya").into()),
+    };
+    let state = jwt_service::AppState::stateless(config, SystemClock::shared())?;
+    let base = serve(jwt_service::build_router(state)).await?;
+
+    let response = request_token(&base, Some(ISSUER_KEY)).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = request_token(&base, None).await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let problem: Value = response.json().await?;
+    assert_eq!(
+        problem["detail"], "Issuer key required (X-Issuer-Key)",
+        "{problem}"
+    );
+
+    let response = request_token(&base, Some("not-a-configured-key")).await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    // X-Api-Key carries machine clients' API keys, not issuer keys
+    let response = http_client()
+        .post(format!("{base}/v1/auth/token"))
+        .header("X-Api-Key", ISSUER_KEY)
+        .json(&json!({ "user_id": "user_1", "email": "u@example.com" }))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    Ok(())
+}
+
+#[test]
+fn short_configured_keys_are_refused() {
+    // ---
+    let config = jwt_service::IssuerConfig {
+        require_key: true,
+        keys: Some(format!("{ISSUER_KEY},short").into()),
+    };
+    let err = jwt_service::validate_issuer_config(&config).unwrap_err();
+    assert!(err.contains("1 TOKEN_ISSUER_KEYS entries"), "{err}");
+
+    let open = jwt_service::IssuerConfig::default();
+    assert!(jwt_service::require_issuer_key(&open).is_err());
+}
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn admin_api_manages_stored_keys() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let mut config = jwt_config(&env.redis_url);
+    config.admin.token = Some(ADMIN_TOKEN.into());
+    config.issuer.require_key = true;
+    let base = env
+        .spawn_jwt_service_with_config(config, SystemClock::shared())
+        .await?;
+    let http = http_client();
+
+    // The admin API needs the admin token
+    let response = http
+        .post(format!("{base}/admin/issuer-keys"))
+        .json(&json!({ "name": "billing-service" }))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let created: Value = http
+        .post(format!("{base}/admin/issuer-keys"))
+        .bearer_auth(ADMIN_TOKEN)
+        .json(&json!({ "name": "billing-service" }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let issuer_key = created["issuer_key"].as_str().unwrap();
+    let id = created["id"].as_str().unwrap();
+    assert_eq!(created["name"], "billing-service");
+
+    let response = request_token(&base, Some(issuer_key)).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Listed without the key itself
+    let listed: Value = http
+        .get(format!("{base}/admin/issuer-keys"))
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    assert_eq!(listed[0]["id"], id);
+    assert!(listed[0].get("issuer_key").is_none(), "{listed}");
+    assert!(!listed.to_string().contains(issuer_key));
+
+    // A deleted key mints nothing
+    let response = http
+        .delete(format!("{base}/admin/issuer-keys/{id}"))
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let response = request_token(&base, Some(issuer_key)).await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    Ok(())
+}
@@ -0,0 +1,142 @@
+// tests/tests/jwe.rs
+
+//! Encrypted access tokens (`JWT_ENCRYPTION_KEY`): JWTs nested in a JWE so
+//! their claims cannot be read without the key, decrypted transparently on
+//! validation (no containers needed)
+
+use anyhow::Result;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use tokn_core::{Claims, JwtKeys, SystemClock, TokenError, JWE_KEY_LENGTH};
+use tokn_tests::{http_client, jwt_config, serve, TEST_JWT_SECRET};
+
+// ---
+
+const ENCRYPTION_KEY: [u8; JWE_KEY_LENGTH] = [7; JWE_KEY_LENGTH];
+
+// ---
+
+fn claims() -> Claims {
+    // ---
+    Claims::new("user_1".into(), "u@example.com".into(), 900, &SystemClock)
+}
+
+fn encrypting_keys() -> Result<JwtKeys> {
+    // ---
+    Ok(JwtKeys::hs256(TEST_JWT_SECRET).with_encryption_key(&ENCRYPTION_KEY)?)
+}
+
+// ---
+
+#[test]
+fn encrypted_tokens_hide_their_claims() -> Result<()> {
+    // ---
+    let keys = encrypting_keys()?;
+    assert!(keys.is_encrypted());
+    let token = keys.sign(&claims())?;
+
+    let parts: Vec<&str> = token.split('.').collect();
+    assert_eq!(parts.len(), 5, "{token}");
+    let header: Value = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(parts[0])?)?;
+    assert_eq!(
+        header,
+        json!({ "alg": "dir", "enc": "A256GCM", "cty": "JWT" })
+    );
+    for part in &parts[1..] {
+        let bytes = URL_SAFE_NO_PAD.decode(part)?;
+        assert!(!String::from_utf8_lossy(&bytes).contains("u@example.com"));
+    }
+
+    assert_eq!(keys.verify(&token, &SystemClock)?.email, "u@example.com");
+    Ok(())
+}
+
+#[test]
+fn decryption_needs_the_right_key() -> Result<()> {
+    // ---
+    let token = encrypting_keys()?.sign(&claims())?;
+
+    let other = JwtKeys::hs256(TEST_JWT_SECRET).with_encryption_key(&[9; JWE_KEY_LENGTH])?;
+    assert!(matches!(
+        other.verify(&token, &SystemClock),
+        Err(TokenError::InvalidSignature)
+    ));
+
+    // Without any encryption key the token does not even parse
+    assert!(matches!(
+        JwtKeys::hs256(TEST_JWT_SECRET).verify(&token, &SystemClock),
+        Err(TokenError::Malformed)
+    ));
+
+    // A tampered ciphertext fails authentication
+    let mut parts: Vec<String> = token.split('.').map(String::from).collect();
+    let mut ciphertext = URL_SAFE_NO_PAD.decode(&parts[3])?;
+    ciphertext[0] ^= 1;
+    parts[3] = URL_SAFE_NO_PAD.encode(ciphertext);
+    assert!(matches!(
+        encrypting_keys()?.verify(&parts.join("."), &SystemClock),
+        Err(TokenError::InvalidSignature)
+    ));
+    Ok(())
+}
+
+#[test]
+fn tokens_signed_before_encryption_stay_valid() -> Result<()> {
+    // ---
+    let plain = JwtKeys::hs256(TEST_JWT_SECRET).sign(&claims())?;
+    assert_eq!(
+        encrypting_keys()?.verify(&plain, &SystemClock)?.sub,
+        "user_1"
+    );
+    Ok(())
+}
+
+#[test]
+fn encryption_keys_must_be_256_bits() {
+    // ---
+    assert!(matches!(
+        JwtKeys::hs256(TEST_JWT_SECRET).with_encryption_key(b"too short"),
+        Err(TokenError::InvalidKey(_))
+    ));
+}
+
+#[tokio::test]
+async fn jwt_service_issues_encrypted_tokens() -> Result<()> {
+    // ---
+    let mut config = jwt_config("redis://unused");
+    config.jwt.stateless = true;
+    config.jwt.encryption_key = Some(hex::encode(ENCRYPTION_KEY).into());
+    let state = jwt_service::AppState::stateless(config, SystemClock::shared())?;
+    let base = serve(jwt_service::build_router(state)).await?;
+    let http = http_client();
+
+    let tokens: Value = http
+        .post(format!("{base}/v1/auth/token"))
+        .json(&json!({ "user_id": "user_1", "email": "u@example.com" }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let token = tokens["access_token"].as_str().unwrap();
+    assert_eq!(token.split('.').count(), 5, "{token}");
+
+    let validate: Value = http
+        .post(format!("{base}/v1/auth/validate"))
+        .json(&json!({ "token": token }))
+        .send()
+        .await?
+        .json()
+        .await?;
+    assert_eq!(validate["valid"], true, "{validate}");
+
+    let response = http
+        .get(format!("{base}/v1/protected"))
+        .bearer_auth(token)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    Ok(())
+}
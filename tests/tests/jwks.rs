@@ -0,0 +1,124 @@
+// tests/tests/jwks.rs
+
+//! Token validation against a JSON Web Key Set: key selection by `kid`,
+//! algorithm pinning, and expiry (no containers needed)
+
+use chrono::Duration;
+use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+use serde_json::json;
+use tokn_core::{generate_token, validate_token_with_jwks, Claims, JwkSet, TestClock, TokenError};
+
+// ---
+
+const SECRET: &str = "integration-test-secret-at-least-32-characters";
+const OTHER_SECRET: &str = "another-secret-for-a-second-key-32-chars-long";
+
+/// [`SECRET`] and [`OTHER_SECRET`], base64url-encoded as JWK `k` values.
+const SECRET_K: &str = "aW50ZWdyYXRpb24tdGVzdC1zZWNyZXQtYXQtbGVhc3QtMzItY2hhcmFjdGVycw";
+const OTHER_SECRET_K: &str = "YW5vdGhlci1zZWNyZXQtZm9yLWEtc2Vjb25kLWtleS0zMi1jaGFycy1sb25n";
+
+// ---
+
+fn jwks(keys: serde_json::Value) -> JwkSet {
+    // ---
+    serde_json::from_value(json!({ "keys": keys })).unwrap()
+}
+
+fn claims(clock: &TestClock) -> Claims {
+    // ---
+    Claims::new("user_1".into(), "u@example.com".into(), 900, clock)
+}
+
+/// A token signed with `secret` under `kid`.
+fn token_with_kid(claims: &Claims, secret: &str, kid: &str, alg: Algorithm) -> String {
+    // ---
+    let mut header = Header::new(alg);
+    header.kid = Some(kid.to_string());
+    encode(
+        &header,
+        claims,
+        &EncodingKey::from_secret(secret.as_bytes()),
+    )
+    .unwrap()
+}
+
+// ---
+
+#[test]
+fn single_key_validates_tokens_without_kid() {
+    // ---
+    let clock = TestClock::default();
+    let token = generate_token(&claims(&clock), SECRET).unwrap();
+    let set = jwks(json!([{ "kty": "oct", "k": SECRET_K }]));
+
+    let decoded = validate_token_with_jwks(&token, &set, &clock).unwrap();
+    assert_eq!(decoded.sub, "user_1");
+
+    // Two keys and no `kid`: ambiguous, refused
+    let two = jwks(json!([
+        { "kty": "oct", "kid": "a", "k": SECRET_K },
+        { "kty": "oct", "kid": "b", "k": OTHER_SECRET_K },
+    ]));
+    assert!(matches!(
+        validate_token_with_jwks(&token, &two, &clock),
+        Err(TokenError::UnknownKey)
+    ));
+}
+
+#[test]
+fn key_is_chosen_by_kid() {
+    // ---
+    let clock = TestClock::default();
+    let set = jwks(json!([
+        { "kty": "oct", "kid": "old", "alg": "HS256", "k": OTHER_SECRET_K },
+        { "kty": "oct", "kid": "new", "alg": "HS256", "k": SECRET_K },
+    ]));
+
+    let token = token_with_kid(&claims(&clock), SECRET, "new", Algorithm::HS256);
+    assert!(validate_token_with_jwks(&token, &set, &clock).is_ok());
+    let token = token_with_kid(&claims(&clock), OTHER_SECRET, "old", Algorithm::HS256);
+    assert!(validate_token_with_jwks(&token, &set, &clock).is_ok());
+
+    // Signed with one key but naming the other
+    let mislabeled = token_with_kid(&claims(&clock), SECRET, "old", Algorithm::HS256);
+    assert!(matches!(
+        validate_token_with_jwks(&mislabeled, &set, &clock),
+        Err(TokenError::InvalidSignature)
+    ));
+
+    let unknown = token_with_kid(&claims(&clock), SECRET, "gone", Algorithm::HS256);
+    assert!(matches!(
+        validate_token_with_jwks(&unknown, &set, &clock),
+        Err(TokenError::UnknownKey)
+    ));
+}
+
+#[test]
+fn algorithm_is_pinned_by_the_key() {
+    // ---
+    let clock = TestClock::default();
+    let set = jwks(json!([{ "kty": "oct", "kid": "k", "alg": "HS256", "k": SECRET_K }]));
+
+    let token = token_with_kid(&claims(&clock), SECRET, "k", Algorithm::HS512);
+    assert!(matches!(
+        validate_token_with_jwks(&token, &set, &clock),
+        Err(TokenError::InvalidAlgorithm)
+    ));
+}
+
+#[test]
+fn expiry_follows_the_clock_with_leeway() {
+    // ---
+    let clock = TestClock::default();
+    let token = generate_token(&claims(&clock), SECRET).unwrap();
+    let set = jwks(json!([{ "kty": "oct", "k": SECRET_K }]));
+
+    clock.advance(Duration::seconds(960));
+    assert!(validate_token_with_jwks(&token, &set, &clock).is_ok());
+
+    clock.advance(Duration::seconds(1));
+    assert!(matches!(
+        validate_token_with_jwks(&token, &set, &clock),
+        Err(TokenError::Expired)
+    ));
+}
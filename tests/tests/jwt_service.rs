@@ -0,0 +1,228 @@
+// tests/tests/jwt_service.rs
+
+//! jwt-service handlers against a real Redis
+
+use anyhow::Result;
+use chrono::Duration;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use tokn_core::TestClock;
+use tokn_tests::{http_client, TestEnv};
+
+// ---
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn token_lifecycle_against_redis() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let base = env.spawn_jwt_service().await?;
+    let http = http_client();
+
+    // ---
+    // Issue tokens
+    let tokens: Value = http
+        .post(format!("{base}/v1/auth/token"))
+        .json(&json!({ "user_id": "user_it", "email": "it@example.com" }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let access_token = tokens["access_token"].as_str().unwrap().to_string();
+    let refresh_token = tokens["refresh_token"].as_str().unwrap().to_string();
+
+    // ---
+    // Validate
+    let validation: Value = http
+        .post(format!("{base}/v1/auth/validate"))
+        .json(&json!({ "token": access_token }))
+        .send()
+        .await?
+        .json()
+        .await?;
+    assert_eq!(validation["valid"], true);
+    assert_eq!(validation["claims"]["sub"], "user_it");
+
+    // ---
+    // Protected route accepts the token
+    let protected = http
+        .get(format!("{base}/v1/protected"))
+        .bearer_auth(&access_token)
+        .send()
+        .await?;
+    assert_eq!(protected.status(), StatusCode::OK);
+
+    // ---
+    // Refresh rotates: first use succeeds, replay fails
+    let refreshed = http
+        .post(format!("{base}/v1/auth/refresh"))
+        .json(&json!({ "refresh_token": refresh_token }))
+        .send()
+        .await?;
+    assert_eq!(refreshed.status(), StatusCode::OK);
+
+    let replay = http
+        .post(format!("{base}/v1/auth/refresh"))
+        .json(&json!({ "refresh_token": refresh_token }))
+        .send()
+        .await?;
+    assert_eq!(replay.status(), StatusCode::UNAUTHORIZED);
+
+    // ---
+    // Revoke, then the token is rejected everywhere
+    let revoked = http
+        .post(format!("{base}/v1/auth/revoke"))
+        .json(&json!({ "token": access_token }))
+        .send()
+        .await?;
+    assert_eq!(revoked.status(), StatusCode::OK);
+
+    let validation = http
+        .post(format!("{base}/v1/auth/validate"))
+        .json(&json!({ "token": access_token }))
+        .send()
+        .await?;
+    assert_eq!(validation.status(), StatusCode::UNAUTHORIZED);
+
+    let protected = http
+        .get(format!("{base}/v1/protected"))
+        .bearer_auth(&access_token)
+        .send()
+        .await?;
+    assert_eq!(protected.status(), StatusCode::UNAUTHORIZED);
+
+    Ok(())
+}
+
+// ---
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn protected_route_requires_bearer_token() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let base = env.spawn_jwt_service().await?;
+    let http = http_client();
+
+    let missing = http.get(format!("{base}/v1/protected")).send().await?;
+    assert_eq!(missing.status(), StatusCode::UNAUTHORIZED);
+
+    let malformed = http
+        .get(format!("{base}/v1/protected"))
+        .header("Authorization", "Basic dXNlcjpwYXNz")
+        .send()
+        .await?;
+    assert_eq!(malformed.status(), StatusCode::UNAUTHORIZED);
+
+    // Refusals are problem details naming the request
+    assert_eq!(malformed.headers()["content-type"], tokn_core::PROBLEM_JSON);
+    let problem: Value = malformed.json().await?;
+    assert_eq!(problem["status"], 401);
+    assert_eq!(problem["title"], "Unauthorized");
+    assert_eq!(problem["instance"], "/v1/protected");
+    assert!(problem["request_id"].is_string());
+
+    // An invalid token is reported as such, like a valid one
+    let invalid = http
+        .post(format!("{base}/v1/auth/validate"))
+        .json(&json!({ "token": "not-a-jwt" }))
+        .send()
+        .await?;
+    assert_eq!(invalid.status(), StatusCode::UNAUTHORIZED);
+    let problem: Value = invalid.json().await?;
+    assert_eq!(problem["valid"], false);
+    assert_eq!(problem["detail"], "Malformed token");
+
+    Ok(())
+}
+
+// ---
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn access_token_expires_with_the_clock() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let clock = TestClock::default();
+    let base = env.spawn_jwt_service_with_clock(clock.shared()).await?;
+    let http = http_client();
+
+    let tokens: Value = http
+        .post(format!("{base}/v1/auth/token"))
+        .json(&json!({ "user_id": "user_it", "email": "it@example.com" }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let access_token = tokens["access_token"].as_str().unwrap();
+
+    // ---
+    // 900s expiry plus 60s leeway
+    clock.advance(Duration::seconds(960));
+    let protected = http
+        .get(format!("{base}/v1/protected"))
+        .bearer_auth(access_token)
+        .send()
+        .await?;
+    assert_eq!(protected.status(), StatusCode::OK);
+
+    clock.advance(Duration::seconds(1));
+    let protected = http
+        .get(format!("{base}/v1/protected"))
+        .bearer_auth(access_token)
+        .send()
+        .await?;
+    assert_eq!(protected.status(), StatusCode::UNAUTHORIZED);
+
+    Ok(())
+}
+
+// ---
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn roles_scope_and_custom_claims_survive_refresh() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let base = env.spawn_jwt_service().await?;
+    let http = http_client();
+
+    let tokens: Value = http
+        .post(format!("{base}/v1/auth/token"))
+        .json(&json!({
+            "user_id": "user_it",
+            "email": "it@example.com",
+            "roles": ["admin"],
+            "scope": "orders:read",
+            "custom_claims": { "tenant_id": "acme" },
+        }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    let refreshed: Value = http
+        .post(format!("{base}/v1/auth/refresh"))
+        .json(&json!({ "refresh_token": tokens["refresh_token"] }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    let validation: Value = http
+        .post(format!("{base}/v1/auth/validate"))
+        .json(&json!({ "token": refreshed["access_token"] }))
+        .send()
+        .await?
+        .json()
+        .await?;
+    assert_eq!(validation["claims"]["tenant_id"], "acme");
+    assert_eq!(validation["claims"]["roles"], json!(["admin"]));
+    assert_eq!(validation["claims"]["scope"], "orders:read");
+
+    Ok(())
+}
@@ -0,0 +1,307 @@
+// tests/tests/key_rotation.rs
+
+//! Signing key rotation: a ring signs with its current key, verifies by the
+//! `kid` header, and jwt-service keeps old tokens valid across a key switch
+//! (no containers needed)
+
+use anyhow::Result;
+use jsonwebtoken::decode_header;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use tokn_core::{generate_token, Claims, JwtKeys, SigningAlgorithm, SystemClock, TokenError};
+use tokn_tests::{http_client, jwt_config, serve, TEST_JWT_SECRET};
+
+// ---
+
+const OLD_SECRET: &str = "Jx4q9Lr2vTz7Wm1Kp8Ns3Hd6Bf0Gc5Ye";
+const NEW_SECRET: &str = "Qa7Zt3Mv9Xc1Rb5Kw2Ly8Pn4Hs6Dj0Fu";
+/// 64 characters, for HS512
+const HS512_SECRET: &str = "Qa7Zt3Mv9Xc1Rb5Kw2Ly8Pn4Hs6Dj0FuJx4q9Lr2vTz7Wm1Kp8Ns3Hd6Bf0Gc5Ye";
+const PRIVATE_PEM: &[u8] = include_bytes!("../fixtures/rsa_private.pem");
+const PUBLIC_PEM: &[u8] = include_bytes!("../fixtures/rsa_public.pem");
+
+// ---
+
+fn claims() -> Claims {
+    // ---
+    Claims::new("user_1".into(), "u@example.com".into(), 900, &SystemClock)
+}
+
+fn ring(current: &str) -> Result<JwtKeys> {
+    // ---
+    Ok(JwtKeys::ring(
+        current,
+        [
+            ("2025-01".to_string(), JwtKeys::hs256(OLD_SECRET)),
+            ("2025-02".to_string(), JwtKeys::hs256(NEW_SECRET)),
+        ],
+    )?)
+}
+
+fn kid(token: &str) -> Option<String> {
+    // ---
+    decode_header(token).unwrap().kid
+}
+
+fn ring_key(kid: &str, secret: &str) -> jwt_service::JwtKeyConfig {
+    // ---
+    jwt_service::JwtKeyConfig {
+        kid: kid.into(),
+        algorithm: SigningAlgorithm::Hs256,
+        secret: Some(secret.into()),
+        private_key_path: None,
+        public_key_path: None,
+    }
+}
+
+/// Issue an access token from jwt-service at `base`.
+async fn issue(base: &str) -> Result<String> {
+    // ---
+    let tokens: Value = http_client()
+        .post(format!("{base}/v1/auth/token"))
+        .json(&json!({ "user_id": "user_1", "email": "u@example.com" }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(tokens["access_token"].as_str().unwrap().to_string())
+}
+
+/// Whether jwt-service at `base` accepts `token`.
+async fn valid(base: &str, token: &str) -> Result<bool> {
+    // ---
+    let validate: Value = http_client()
+        .post(format!("{base}/v1/auth/validate"))
+        .json(&json!({ "token": token }))
+        .send()
+        .await?
+        .json()
+        .await?;
+    Ok(validate["valid"] == true)
+}
+
+// ---
+
+#[test]
+fn ring_signs_with_the_current_key_and_verifies_by_kid() -> Result<()> {
+    // ---
+    let before = ring("2025-01")?;
+    let old_token = before.sign(&claims())?;
+    assert_eq!(kid(&old_token).as_deref(), Some("2025-01"));
+
+    // After the switch, new tokens name the new key and old ones still verify
+    let after = ring("2025-02")?;
+    let new_token = after.sign(&claims())?;
+    assert_eq!(kid(&new_token).as_deref(), Some("2025-02"));
+    assert_eq!(after.verify(&old_token, &SystemClock)?.sub, "user_1");
+    assert_eq!(after.verify(&new_token, &SystemClock)?.sub, "user_1");
+    assert_eq!(after.kids(), ["2025-01", "2025-02"]);
+
+    // Once the old key is removed, its tokens are refused
+    let retired = JwtKeys::ring(
+        "2025-02",
+        [("2025-02".to_string(), JwtKeys::hs256(NEW_SECRET))],
+    )?;
+    assert!(matches!(
+        retired.verify(&old_token, &SystemClock),
+        Err(TokenError::UnknownKey)
+    ));
+    Ok(())
+}
+
+#[test]
+fn tokens_without_kid_use_the_current_key() -> Result<()> {
+    // ---
+    // Issued before rotation was set up
+    let legacy = generate_token(&claims(), OLD_SECRET)?;
+    assert_eq!(kid(&legacy), None);
+    assert_eq!(
+        ring("2025-01")?.verify(&legacy, &SystemClock)?.sub,
+        "user_1"
+    );
+    assert!(matches!(
+        ring("2025-02")?.verify(&legacy, &SystemClock),
+        Err(TokenError::InvalidSignature)
+    ));
+
+    // A single key ignores `kid`, so verifiers that only hold the current
+    // secret keep working after rotation is set up
+    let token = ring("2025-02")?.sign(&claims())?;
+    let verifier = JwtKeys::hs256(NEW_SECRET);
+    assert_eq!(verifier.verify(&token, &SystemClock)?.sub, "user_1");
+    Ok(())
+}
+
+#[test]
+fn ring_pins_each_key_to_its_algorithm() -> Result<()> {
+    // ---
+    let keys = JwtKeys::ring(
+        "rsa",
+        [
+            ("rsa".to_string(), JwtKeys::rs256(PRIVATE_PEM, PUBLIC_PEM)?),
+            ("hmac".to_string(), JwtKeys::hs256(OLD_SECRET)),
+        ],
+    )?;
+    assert_eq!(keys.algorithm(), SigningAlgorithm::Rs256);
+    assert_eq!(keys.current_kid(), Some("rsa"));
+
+    // An HS256 token claiming the RSA key's ID is refused
+    let forged = JwtKeys::ring("rsa", [("rsa".to_string(), JwtKeys::hs256(OLD_SECRET))])?;
+    assert!(matches!(
+        keys.verify(&forged.sign(&claims())?, &SystemClock),
+        Err(TokenError::InvalidAlgorithm)
+    ));
+    Ok(())
+}
+
+#[test]
+fn invalid_rings_are_refused() -> Result<()> {
+    // ---
+    let refused = |current: &str, keys: Vec<(&str, JwtKeys)>| {
+        let keys = keys.into_iter().map(|(kid, keys)| (kid.to_string(), keys));
+        matches!(JwtKeys::ring(current, keys), Err(TokenError::InvalidKey(_)))
+    };
+    let hs = || JwtKeys::hs256(OLD_SECRET);
+
+    assert!(refused("b", vec![("a", hs())]), "unknown current key");
+    assert!(refused("a", vec![("a", hs()), ("a", hs())]), "duplicate ID");
+    assert!(refused("", vec![("", hs())]), "empty ID");
+    assert!(
+        refused("a", vec![("a", JwtKeys::rs256_public(PUBLIC_PEM)?)]),
+        "current key cannot sign"
+    );
+
+    // A verify-only key may stay in the ring once it is retired
+    JwtKeys::ring(
+        "b",
+        [
+            ("a".to_string(), JwtKeys::rs256_public(PUBLIC_PEM)?),
+            ("b".to_string(), hs()),
+        ],
+    )?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn jwt_service_keeps_old_tokens_valid_across_a_key_switch() -> Result<()> {
+    // ---
+    let mut config = jwt_config("redis://unused");
+    config.jwt.stateless = true;
+    config.jwt.keys = vec![ring_key("2025-01", OLD_SECRET)];
+    config.jwt.current_kid = Some("2025-01".into());
+
+    let state = jwt_service::AppState::stateless(config.clone(), SystemClock::shared())?;
+    let keys = state.keys.clone();
+    let base = serve(jwt_service::build_router(state)).await?;
+    let old_token = issue(&base).await?;
+    assert_eq!(kid(&old_token).as_deref(), Some("2025-01"));
+
+    // Add the new key and sign with it, as a reload would
+    config.jwt.keys.push(ring_key("2025-02", NEW_SECRET));
+    config.jwt.current_kid = Some("2025-02".into());
+    keys.set(config.jwt.keys()?);
+
+    let new_token = issue(&base).await?;
+    assert_eq!(kid(&new_token).as_deref(), Some("2025-02"));
+    assert!(valid(&base, &old_token).await?);
+    assert!(valid(&base, &new_token).await?);
+
+    // Drop the old key once its tokens have expired
+    config.jwt.keys.remove(0);
+    keys.set(config.jwt.keys()?);
+    assert!(!valid(&base, &old_token).await?);
+    assert!(valid(&base, &new_token).await?);
+
+    // A token signed with an unrelated secret never validates
+    assert!(!valid(&base, &generate_token(&claims(), TEST_JWT_SECRET)?).await?);
+    Ok(())
+}
+
+#[tokio::test]
+async fn jwt_service_accepts_the_previous_secret_after_a_rotation() -> Result<()> {
+    // ---
+    let mut config = jwt_config("redis://unused");
+    config.jwt.stateless = true;
+    config.jwt.secret = Some(NEW_SECRET.into());
+    config.jwt.previous_secret = Some(OLD_SECRET.into());
+
+    let base = serve(jwt_service::build_router(jwt_service::AppState::stateless(
+        config,
+        SystemClock::shared(),
+    )?))
+    .await?;
+
+    // New tokens are signed with the new secret alone
+    let new_token = issue(&base).await?;
+    assert_eq!(kid(&new_token), None);
+    assert!(JwtKeys::hs256(NEW_SECRET)
+        .verify(&new_token, &SystemClock)
+        .is_ok());
+
+    assert!(valid(&base, &new_token).await?);
+    assert!(valid(&base, &generate_token(&claims(), OLD_SECRET)?).await?);
+    assert!(!valid(&base, &generate_token(&claims(), TEST_JWT_SECRET)?).await?);
+    Ok(())
+}
+
+#[test]
+fn previous_secret_is_flagged_after_the_rotation_window() {
+    // ---
+    let mut jwt = jwt_config("redis://unused").jwt;
+    let now = chrono::Utc::now();
+    assert_eq!(jwt.stale_previous_secret(now), None);
+
+    jwt.previous_secret = Some(OLD_SECRET.into());
+    let unknown = jwt.stale_previous_secret(now).unwrap();
+    assert!(unknown.contains("JWT_SECRET_ROTATED_AT"), "{unknown}");
+
+    // Tokens signed just before the rotation may still be live
+    jwt.secret_rotated_at = Some(now - chrono::Duration::seconds(600));
+    assert_eq!(jwt.stale_previous_secret(now), None);
+
+    jwt.secret_rotated_at = Some(now - chrono::Duration::hours(2));
+    let stale = jwt.stale_previous_secret(now).unwrap();
+    assert!(stale.contains("remove it"), "{stale}");
+}
+
+#[test]
+fn previous_secret_keeps_its_own_algorithm() -> Result<()> {
+    // ---
+    let old_token = generate_token(&claims(), OLD_SECRET)?;
+
+    // HS256 to HS512: the previous secret defaults to the new algorithm
+    let mut jwt = jwt_config("redis://unused").jwt;
+    jwt.algorithm = SigningAlgorithm::Hs512;
+    jwt.secret = Some(HS512_SECRET.into());
+    jwt.previous_secret = Some(OLD_SECRET.into());
+    assert!(matches!(
+        jwt.keys()?.verify(&old_token, &SystemClock),
+        Err(TokenError::InvalidAlgorithm)
+    ));
+    jwt.previous_algorithm = Some(SigningAlgorithm::Hs256);
+    assert_eq!(jwt.keys()?.verify(&old_token, &SystemClock)?.sub, "user_1");
+
+    // HS256 to a key pair: the previous secret defaults to HS256
+    let fixtures = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("fixtures");
+    jwt.algorithm = SigningAlgorithm::Rs256;
+    jwt.secret = None;
+    jwt.previous_algorithm = None;
+    jwt.private_key_path = Some(fixtures.join("rsa_private.pem"));
+    jwt.public_key_path = Some(fixtures.join("rsa_public.pem"));
+    assert_eq!(jwt.previous_secret_algorithm(), SigningAlgorithm::Hs256);
+    let keys = jwt.keys()?;
+    assert_eq!(keys.algorithm(), SigningAlgorithm::Rs256);
+    assert_eq!(keys.verify(&old_token, &SystemClock)?.sub, "user_1");
+    assert_eq!(
+        keys.verify(&keys.sign(&claims())?, &SystemClock)?.sub,
+        "user_1"
+    );
+
+    // Only an HMAC algorithm takes a secret
+    assert!(matches!(
+        JwtKeys::hs256(NEW_SECRET).with_previous_hmac_secret(SigningAlgorithm::Rs256, OLD_SECRET),
+        Err(TokenError::InvalidKey(_))
+    ));
+    Ok(())
+}
@@ -0,0 +1,117 @@
+// tests/tests/log_schema.rs
+
+//! JSON log lines follow the documented schema (no containers needed)
+
+use serde_json::Value;
+use std::io;
+use std::sync::{Arc, Mutex};
+use tokn_telemetry::{JsonLogLayer, LOG_SCHEMA_VERSION};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::SubscriberExt;
+
+// ---
+
+/// Collects written lines in memory.
+#[derive(Clone, Default)]
+struct Capture(Arc<Mutex<Vec<u8>>>);
+
+impl Capture {
+    // ---
+    fn lines(&self) -> Vec<Value> {
+        // ---
+        let buf = self.0.lock().unwrap();
+        buf.split(|b| *b == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_slice(line).expect("each line is one JSON object"))
+            .collect()
+    }
+}
+
+impl io::Write for Capture {
+    // ---
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // ---
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        // ---
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for Capture {
+    // ---
+    type Writer = Capture;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        // ---
+        self.clone()
+    }
+}
+
+/// Run `f` with a JSON log layer installed and return the lines it logged.
+fn capture(layer: impl FnOnce(JsonLogLayer) -> JsonLogLayer, f: impl FnOnce()) -> Vec<Value> {
+    // ---
+    let out = Capture::default();
+    let layer = layer(JsonLogLayer::new("jwt-service")).with_writer(out.clone());
+    tracing::subscriber::with_default(tracing_subscriber::registry().with(layer), f);
+    out.lines()
+}
+
+// ---
+
+#[test]
+fn json_lines_carry_schema_fields() {
+    // ---
+    let lines = capture(
+        |layer| layer,
+        || {
+            let span = tracing::info_span!("request", request_id = "req-1", client_id = "demo");
+            let _guard = span.enter();
+            tracing::info!(
+                event = "token_revoked",
+                user_id = "user_123",
+                jti = "abc",
+                "Token revoked"
+            );
+        },
+    );
+
+    let line = &lines[0];
+    assert_eq!(line["schema_version"], LOG_SCHEMA_VERSION);
+    assert_eq!(line["level"], "INFO");
+    assert_eq!(line["service"], "jwt-service");
+    assert_eq!(line["target"], "log_schema");
+    assert_eq!(line["message"], "Token revoked");
+    assert_eq!(line["event"], "token_revoked");
+    assert_eq!(line["request_id"], "req-1");
+    assert_eq!(line["client_id"], "demo");
+    assert_eq!(line["span"], "request");
+    assert_eq!(line["fields"]["jti"], "abc");
+    assert!(line["timestamp"].as_str().unwrap().ends_with('Z'));
+
+    // The user ID itself never reaches the log
+    let hash = line["user_hash"].as_str().unwrap();
+    assert_eq!(hash.len(), 16);
+    assert!(!line.to_string().contains("user_123"));
+}
+
+// ---
+
+#[test]
+fn optional_fields_are_omitted_and_user_hash_is_keyed() {
+    // ---
+    let plain = capture(|layer| layer, || tracing::warn!(user_id = "u", "hello"));
+    let keyed = capture(
+        |layer| layer.with_user_hash_key("log-hash-key"),
+        || tracing::warn!(user_id = "u", "hello"),
+    );
+
+    let line = plain[0].as_object().unwrap();
+    for absent in ["event", "request_id", "client_id", "span", "fields"] {
+        assert!(!line.contains_key(absent), "{absent} should be omitted");
+    }
+    assert_ne!(plain[0]["user_hash"], keyed[0]["user_hash"]);
+}
@@ -0,0 +1,169 @@
+// tests/tests/magic_links.rs
+
+//! Magic login links: `POST /v1/auth/magic-link` issues a single-use link,
+//! returned to the caller or emailed, and `POST /v1/auth/magic-link/redeem`
+//! exchanges it for a token pair (in-memory store)
+
+use anyhow::Result;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use tokn_core::TestClock;
+use tokn_tests::{http_client, jwt_config, jwt_state_in_memory, serve, RecordingMailer};
+
+// ---
+
+const NOW: i64 = 1_700_000_000;
+
+// ---
+
+/// Serve jwt-service from an in-memory store on `clock`, emailing links to
+/// `magic_link_url` through `mailer` when given, and return its base URL.
+async fn start(
+    clock: &TestClock,
+    magic_link_url: Option<&str>,
+    mailer: Option<&RecordingMailer>,
+) -> Result<String> {
+    // ---
+    let mut config = jwt_config("redis://unused");
+    config.jwt.magic_link_url = magic_link_url.map(str::to_string);
+    let mut state = jwt_state_in_memory(config, clock.shared())?;
+    if let Some(mailer) = mailer {
+        state.mail = mailer.mail();
+    }
+    serve(jwt_service::build_router(state)).await
+}
+
+/// Request a magic link for `user_1`.
+async fn request_link(base: &str) -> Result<Value> {
+    // ---
+    let issued = http_client()
+        .post(format!("{base}/v1/auth/magic-link"))
+        .json(&json!({ "user_id": "user_1", "email": "u@example.com", "scope": "orders:read" }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(issued)
+}
+
+/// Redeem the magic link `token`, returning the response status and body.
+async fn redeem(base: &str, token: &str) -> Result<(StatusCode, Value)> {
+    // ---
+    let response = http_client()
+        .post(format!("{base}/v1/auth/magic-link/redeem"))
+        .json(&json!({ "token": token }))
+        .send()
+        .await?;
+    Ok((response.status(), response.json().await?))
+}
+
+// ---
+
+#[tokio::test]
+async fn magic_links_are_redeemed_once_for_a_token_pair() -> Result<()> {
+    // ---
+    let clock = TestClock::at_timestamp(NOW);
+    let base = start(&clock, None, None).await?;
+
+    let issued = request_link(&base).await?;
+    assert_eq!(issued["expires_in"], 900, "{issued}");
+    let token = issued["token"].as_str().unwrap();
+
+    let (status, tokens) = redeem(&base, token).await?;
+    assert_eq!(status, StatusCode::OK, "{tokens}");
+    assert!(tokens["refresh_token"].is_string(), "{tokens}");
+
+    // The access token carries what the link was requested with
+    let validated: Value = http_client()
+        .post(format!("{base}/v1/auth/validate"))
+        .json(&json!({ "token": tokens["access_token"] }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    assert_eq!(validated["claims"]["sub"], "user_1");
+    assert_eq!(validated["claims"]["scope"], "orders:read");
+
+    let (status, body) = redeem(&base, token).await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        body["detail"], "Invalid, expired, or already redeemed magic link",
+        "{body}"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn magic_links_expire() -> Result<()> {
+    // ---
+    let clock = TestClock::at_timestamp(NOW);
+    let base = start(&clock, None, None).await?;
+    let issued = request_link(&base).await?;
+
+    clock.advance(chrono::Duration::seconds(901));
+    let (status, _) = redeem(&base, issued["token"].as_str().unwrap()).await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    Ok(())
+}
+
+#[tokio::test]
+async fn magic_links_die_with_an_invalidated_user() -> Result<()> {
+    // ---
+    let clock = TestClock::at_timestamp(NOW);
+    let base = start(&clock, None, None).await?;
+    let issued = request_link(&base).await?;
+
+    let tokens: Value = http_client()
+        .post(format!("{base}/v1/auth/token"))
+        .json(&json!({ "user_id": "user_1", "email": "u@example.com" }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    http_client()
+        .post(format!("{base}/v1/auth/invalidate-user"))
+        .bearer_auth(tokens["access_token"].as_str().unwrap())
+        .json(&json!({ "user_id": "user_1" }))
+        .send()
+        .await?
+        .error_for_status()?;
+
+    let (status, body) = redeem(&base, issued["token"].as_str().unwrap()).await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["detail"], "Magic link has been revoked", "{body}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn magic_links_are_emailed_with_a_link_url() -> Result<()> {
+    // ---
+    let clock = TestClock::at_timestamp(NOW);
+    let recorder = RecordingMailer::default();
+    let base = start(
+        &clock,
+        Some("https://app.example.com/login/magic"),
+        Some(&recorder),
+    )
+    .await?;
+
+    // The caller never sees the token
+    let issued = request_link(&base).await?;
+    assert!(issued.get("token").is_none(), "{issued}");
+
+    let sent = recorder.wait_for(1).await;
+    assert_eq!(sent[0].to, "u@example.com");
+    assert!(sent[0].text.contains("15 minutes"), "{}", sent[0].text);
+    let link = sent[0]
+        .text
+        .split_whitespace()
+        .find(|word| word.starts_with("https://app.example.com/login/magic?token="))
+        .expect("the email carries the link");
+    let token = link.rsplit('=').next().unwrap();
+
+    let (status, tokens) = redeem(&base, token).await?;
+    assert_eq!(status, StatusCode::OK, "{tokens}");
+    Ok(())
+}
@@ -0,0 +1,264 @@
+// tests/tests/mail.rs
+
+//! Email templates, delivery retries, and jwt-service's security
+//! notification on refresh-token reuse
+
+use anyhow::Result;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use tokn_mail::{
+    validate_mail_config, Email, Mail, MailBackend, MailConfig, MailError, Mailer, Template,
+    Templates,
+};
+use tokn_tests::{http_client, RecordingMailer, TestEnv};
+
+// ---
+
+/// Fails with a transport error `failures` times, then delivers; or rejects
+/// every message when `reject` is set.
+#[derive(Clone, Default)]
+struct FlakyMailer {
+    // ---
+    attempts: Arc<AtomicU32>,
+    failures: u32,
+    reject: bool,
+}
+
+impl Mailer for FlakyMailer {
+    // ---
+    async fn send(&self, _email: &Email) -> Result<(), MailError> {
+        // ---
+        let attempt = self.attempts.fetch_add(1, Ordering::SeqCst) + 1;
+        if self.reject {
+            return Err(MailError::Rejected {
+                backend: MailBackend::Smtp,
+                message: "550 mailbox unavailable".to_string(),
+            });
+        }
+        if attempt <= self.failures {
+            return Err(MailError::Transport {
+                backend: MailBackend::Smtp,
+                message: "connection refused".to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Mail config retrying quickly, for a bounded test.
+fn fast_retry() -> Result<MailConfig> {
+    // ---
+    Ok(serde_json::from_value(json!({
+        "retry": { "initial_delay_ms": 1, "max_delay_ms": 5, "max_wait_seconds": 2 }
+    }))?)
+}
+
+fn template_dir(name: &str) -> Result<PathBuf> {
+    // ---
+    let dir = std::env::temp_dir().join(format!("tokn-mail-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+// ---
+
+#[test]
+fn builtin_templates_escape_html_only() {
+    // ---
+    let rendered = Templates::builtin().render(
+        Template::Verification,
+        &[
+            ("to", "ann@example.com"),
+            ("link", "https://id.example.com/verify?a=1&b=<2>"),
+            ("expires_in", "24 hours"),
+        ],
+    );
+
+    assert_eq!(rendered.subject, "Confirm your email address");
+    assert!(rendered
+        .text
+        .contains("https://id.example.com/verify?a=1&b=<2>"));
+    assert!(rendered.text.contains("expires in 24 hours"));
+    let html = rendered.html.expect("built-in templates have HTML");
+    assert!(html.contains(r#"href="https://id.example.com/verify?a=1&amp;b=&lt;2&gt;""#));
+    assert!(!html.contains("{{"));
+}
+
+#[test]
+fn subject_values_cannot_add_header_lines() {
+    // ---
+    let dir = template_dir("subject").unwrap();
+    std::fs::write(
+        dir.join("security_notification.txt"),
+        "Subject: Alert: {{ summary }}\n\nBody\n",
+    )
+    .unwrap();
+
+    let templates = Templates::load(Some(&dir)).unwrap();
+    let rendered = templates.render(
+        Template::SecurityNotification,
+        &[("summary", "one\r\nBcc: attacker@example.com")],
+    );
+    assert!(!rendered.subject.contains('\n'));
+    assert!(!rendered.subject.contains('\r'));
+}
+
+#[test]
+fn text_override_without_html_sends_plain_text() -> Result<()> {
+    // ---
+    let dir = template_dir("override")?;
+    std::fs::write(
+        dir.join("password_reset.txt"),
+        "Subject: ACME password reset\n\nReset at {{ link }}\n",
+    )?;
+
+    let templates = Templates::load(Some(&dir))?;
+    let reset = templates.render(Template::PasswordReset, &[("link", "https://x/r")]);
+    assert_eq!(reset.subject, "ACME password reset");
+    assert_eq!(reset.text, "Reset at https://x/r\n");
+    assert_eq!(reset.html, None);
+
+    // Templates without overrides are untouched
+    let verify = templates.render(Template::Verification, &[]);
+    assert_eq!(verify.subject, "Confirm your email address");
+    assert!(verify.html.is_some());
+    Ok(())
+}
+
+#[test]
+fn text_override_requires_subject_line() -> Result<()> {
+    // ---
+    let dir = template_dir("no-subject")?;
+    std::fs::write(dir.join("verification.txt"), "Confirm at {{ link }}\n")?;
+
+    let error = Templates::load(Some(&dir)).unwrap_err();
+    assert!(matches!(error, MailError::Template { .. }), "{error}");
+    Ok(())
+}
+
+#[test]
+fn smtp_backend_requires_url() {
+    // ---
+    let config = MailConfig {
+        backend: MailBackend::Smtp,
+        ..MailConfig::default()
+    };
+    assert!(validate_mail_config(&config).is_err());
+
+    let config = MailConfig {
+        backend: MailBackend::Smtp,
+        smtp_url: Some("smtp://relay.example.com:587".into()),
+        ..MailConfig::default()
+    };
+    assert!(validate_mail_config(&config).is_ok());
+}
+
+// ---
+
+#[tokio::test]
+async fn transport_errors_are_retried() -> Result<()> {
+    // ---
+    let mailer = FlakyMailer {
+        failures: 2,
+        ..FlakyMailer::default()
+    };
+    let mail = Mail::with_mailer(
+        mailer.clone(),
+        "flaky",
+        &fast_retry()?,
+        Templates::builtin(),
+    );
+
+    mail.send(Template::Verification, "ann@example.com", &[])
+        .await?;
+    assert_eq!(mailer.attempts.load(Ordering::SeqCst), 3);
+    Ok(())
+}
+
+#[tokio::test]
+async fn rejections_are_not_retried() -> Result<()> {
+    // ---
+    let mailer = FlakyMailer {
+        reject: true,
+        ..FlakyMailer::default()
+    };
+    let mail = Mail::with_mailer(
+        mailer.clone(),
+        "flaky",
+        &fast_retry()?,
+        Templates::builtin(),
+    );
+
+    let error = mail
+        .send(Template::PasswordReset, "ann@example.com", &[])
+        .await
+        .unwrap_err();
+    assert!(error.is_permanent());
+    assert_eq!(mailer.attempts.load(Ordering::SeqCst), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn send_addresses_message_from_config() -> Result<()> {
+    // ---
+    let recorder = RecordingMailer::default();
+    recorder
+        .mail()
+        .send(
+            Template::SecurityNotification,
+            "ann@example.com",
+            &[("summary", "New sign-in"), ("occurred_at", "now")],
+        )
+        .await?;
+
+    let sent = recorder.wait_for(1).await;
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].from, MailConfig::default().from);
+    assert_eq!(sent[0].to, "ann@example.com");
+    assert!(sent[0].text.contains("ann@example.com"));
+    assert!(sent[0].text.contains("New sign-in"));
+    Ok(())
+}
+
+// ---
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn refresh_reuse_sends_security_notification() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let recorder = RecordingMailer::default();
+    let base = env.spawn_jwt_service_with_mail(recorder.mail()).await?;
+    let http = http_client();
+
+    let tokens: Value = http
+        .post(format!("{base}/v1/auth/token"))
+        .json(&json!({ "user_id": "user_mail", "email": "mail@example.com" }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let refresh_token = tokens["refresh_token"].as_str().unwrap();
+
+    // Rotate once, then replay the rotated token
+    for expected in [StatusCode::OK, StatusCode::UNAUTHORIZED] {
+        let response = http
+            .post(format!("{base}/v1/auth/refresh"))
+            .json(&json!({ "refresh_token": refresh_token }))
+            .send()
+            .await?;
+        assert_eq!(response.status(), expected);
+    }
+
+    let sent = recorder.wait_for(1).await;
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].to, "mail@example.com");
+    assert_eq!(sent[0].subject, "Security alert for your account");
+    assert!(sent[0].text.contains("already been used"));
+    Ok(())
+}
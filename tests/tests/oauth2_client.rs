@@ -0,0 +1,42 @@
+// tests/tests/oauth2_client.rs
+
+//! oauth2-client handlers wired to an in-process oauth2-server
+
+use anyhow::Result;
+use reqwest::{header::LOCATION, StatusCode};
+use tokn_tests::{http_client, query_param, TestEnv, DEMO_CLIENT_ID};
+
+// ---
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn login_redirects_to_authorization_server() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let server = env.spawn_oauth2_server().await?;
+    let client = env.spawn_oauth2_client(&server).await?;
+    let http = http_client();
+
+    // ---
+    let home = http.get(format!("{client}/")).send().await?;
+    assert_eq!(home.status(), StatusCode::OK);
+
+    // ---
+    let login = http.get(format!("{client}/login")).send().await?;
+    assert!(login.status().is_redirection());
+
+    let location = reqwest::Url::parse(login.headers()[LOCATION].to_str()?)?;
+    assert!(location
+        .as_str()
+        .starts_with(&format!("{server}/v1/oauth/authorize")));
+
+    let client_id = query_param(&location, "client_id");
+    assert_eq!(client_id.as_deref(), Some(DEMO_CLIENT_ID));
+
+    // ---
+    // The consent page renders for the client's request
+    let consent = http.get(location).send().await?;
+    assert_eq!(consent.status(), StatusCode::OK);
+
+    Ok(())
+}
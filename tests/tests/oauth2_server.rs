@@ -0,0 +1,184 @@
+// tests/tests/oauth2_server.rs
+
+//! oauth2-server handlers against a real Postgres
+
+use anyhow::Result;
+use chrono::Duration;
+use reqwest::{header::LOCATION, StatusCode};
+use serde_json::Value;
+use tokn_core::TestClock;
+use tokn_tests::{
+    http_client, query_param, TestEnv, DEMO_CLIENT_ID, DEMO_CLIENT_SECRET, DEMO_REDIRECT_URI,
+};
+
+// ---
+
+/// Approve the consent form and return the issued authorization code.
+async fn approve(http: &reqwest::Client, base: &str) -> Result<String> {
+    // ---
+    let response = http
+        .post(format!("{base}/v1/oauth/authorize"))
+        .form(&[
+            ("client_id", DEMO_CLIENT_ID),
+            ("redirect_uri", DEMO_REDIRECT_URI),
+            ("scope", "profile"),
+            ("state", "xyz"),
+            ("action", "approve"),
+        ])
+        .send()
+        .await?;
+    assert!(response.status().is_redirection());
+
+    let location = response.headers()[LOCATION].to_str()?.to_string();
+    let url = reqwest::Url::parse(&location)?;
+    let code = query_param(&url, "code").expect("redirect carries an authorization code");
+
+    Ok(code)
+}
+
+// ---
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn code_exchange_and_userinfo_against_postgres() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let base = env.spawn_oauth2_server().await?;
+    let http = http_client();
+
+    // ---
+    let code = approve(&http, &base).await?;
+
+    let token: Value = http
+        .post(format!("{base}/v1/oauth/token"))
+        .form(&[
+            ("grant_type", "authorization_code"),
+            ("code", &code),
+            ("redirect_uri", DEMO_REDIRECT_URI),
+            ("client_id", DEMO_CLIENT_ID),
+            ("client_secret", DEMO_CLIENT_SECRET),
+        ])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let access_token = token["access_token"].as_str().unwrap();
+    assert_eq!(token["token_type"], "Bearer");
+
+    // ---
+    let userinfo: tokn_core::UserInfo = http
+        .get(format!("{base}/v1/oauth/userinfo"))
+        .bearer_auth(access_token)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    assert_eq!(userinfo.sub, "user_001");
+    assert_eq!(userinfo.username, "demo");
+
+    // ---
+    // Codes are single-use
+    let replay = http
+        .post(format!("{base}/v1/oauth/token"))
+        .form(&[
+            ("grant_type", "authorization_code"),
+            ("code", &code),
+            ("redirect_uri", DEMO_REDIRECT_URI),
+            ("client_id", DEMO_CLIENT_ID),
+            ("client_secret", DEMO_CLIENT_SECRET),
+        ])
+        .send()
+        .await?;
+    assert_eq!(replay.status(), StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
+
+// ---
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn token_endpoint_rejects_bad_client_secret() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let base = env.spawn_oauth2_server().await?;
+    let http = http_client();
+
+    let code = approve(&http, &base).await?;
+
+    let response = http
+        .post(format!("{base}/v1/oauth/token"))
+        .form(&[
+            ("grant_type", "authorization_code"),
+            ("code", &code),
+            ("redirect_uri", DEMO_REDIRECT_URI),
+            ("client_id", DEMO_CLIENT_ID),
+            ("client_secret", "wrong"),
+        ])
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let body: Value = response.json().await?;
+    assert_eq!(body["error"], "invalid_client");
+
+    Ok(())
+}
+
+// ---
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn userinfo_rejects_unknown_token() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let base = env.spawn_oauth2_server().await?;
+    let http = http_client();
+
+    let response = http
+        .get(format!("{base}/v1/oauth/userinfo"))
+        .bearer_auth("not-a-real-token")
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    Ok(())
+}
+
+// ---
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn authorization_code_expires_after_five_minutes() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let clock = TestClock::default();
+    let base = env.spawn_oauth2_server_with_clock(clock.shared()).await?;
+    let http = http_client();
+
+    let code = approve(&http, &base).await?;
+    clock.advance(Duration::minutes(5) + Duration::seconds(1));
+
+    let response: Value = http
+        .post(format!("{base}/v1/oauth/token"))
+        .form(&[
+            ("grant_type", "authorization_code"),
+            ("code", &code),
+            ("redirect_uri", DEMO_REDIRECT_URI),
+            ("client_id", DEMO_CLIENT_ID),
+            ("client_secret", DEMO_CLIENT_SECRET),
+        ])
+        .send()
+        .await?
+        .json()
+        .await?;
+    assert_eq!(response["error"], "invalid_grant");
+    assert_eq!(
+        response["error_description"],
+        "Authorization code has expired"
+    );
+
+    Ok(())
+}
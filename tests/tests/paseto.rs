@@ -0,0 +1,188 @@
+// tests/tests/paseto.rs
+
+//! PASETO v4 access tokens (`TOKEN_FORMAT=paseto`): `v4.local` from the
+//! HS256 secret, `v4.public` from an Ed25519 key pair, and no crossing over
+//! between formats (no containers needed)
+
+use anyhow::Result;
+use chrono::Duration;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use tokn_core::{
+    Claims, JwtKeys, SigningAlgorithm, SystemClock, TestClock, TokenError, TokenFormat,
+};
+use tokn_tests::{http_client, jwt_config, serve, TEST_JWT_SECRET};
+
+// ---
+
+const ED_PRIVATE_PEM: &[u8] = include_bytes!("../fixtures/ed25519_private.pem");
+const ED_PUBLIC_PEM: &[u8] = include_bytes!("../fixtures/ed25519_public.pem");
+
+// ---
+
+fn fixture(name: &str) -> PathBuf {
+    // ---
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("fixtures")
+        .join(name)
+}
+
+fn claims() -> Claims {
+    // ---
+    Claims::new("user_1".into(), "u@example.com".into(), 900, &SystemClock)
+        .with_access(vec!["admin".into()], None)
+}
+
+// ---
+
+#[test]
+fn local_and_public_tokens_round_trip() -> Result<()> {
+    // ---
+    let local = JwtKeys::paseto_local(TEST_JWT_SECRET);
+    assert_eq!(local.format(), TokenFormat::Paseto);
+    let token = local.sign(&claims())?;
+    assert!(token.starts_with("v4.local."), "{token}");
+    let decoded = local.verify(&token, &SystemClock)?;
+    assert_eq!(decoded.sub, "user_1");
+    assert_eq!(decoded.roles, ["admin"]);
+
+    let public = JwtKeys::paseto_public(ED_PRIVATE_PEM, ED_PUBLIC_PEM)?;
+    let token = public.sign(&claims())?;
+    assert!(token.starts_with("v4.public."), "{token}");
+
+    // A resource server holding only the public key
+    let verifier = JwtKeys::paseto_public_verifier(ED_PUBLIC_PEM)?;
+    assert!(!verifier.can_sign());
+    assert_eq!(verifier.verify(&token, &SystemClock)?.sub, "user_1");
+    Ok(())
+}
+
+#[test]
+fn formats_do_not_cross_over() -> Result<()> {
+    // ---
+    let jwt = JwtKeys::hs256(TEST_JWT_SECRET);
+    let paseto = JwtKeys::paseto_local(TEST_JWT_SECRET);
+    let public = JwtKeys::paseto_public(ED_PRIVATE_PEM, ED_PUBLIC_PEM)?;
+
+    // A JWT with the same secret is not a PASETO, and vice versa
+    let jwt_token = jwt.sign(&claims())?;
+    assert!(matches!(
+        paseto.verify(&jwt_token, &SystemClock),
+        Err(TokenError::InvalidAlgorithm)
+    ));
+    assert!(jwt.verify(&paseto.sign(&claims())?, &SystemClock).is_err());
+
+    // Nor does one purpose pass for the other
+    assert!(matches!(
+        paseto.verify(&public.sign(&claims())?, &SystemClock),
+        Err(TokenError::InvalidAlgorithm)
+    ));
+
+    // A different secret cannot decrypt
+    let other = JwtKeys::paseto_local("a-different-secret-of-32-characters!");
+    assert!(matches!(
+        other.verify(&paseto.sign(&claims())?, &SystemClock),
+        Err(TokenError::InvalidSignature)
+    ));
+    Ok(())
+}
+
+#[test]
+fn expiry_has_the_jwt_leeway() -> Result<()> {
+    // ---
+    let keys = JwtKeys::paseto_local(TEST_JWT_SECRET);
+    let clock = TestClock::at_timestamp(1_700_000_000);
+    let token = keys.sign(&Claims::new(
+        "user_1".into(),
+        "u@example.com".into(),
+        900,
+        &clock,
+    ))?;
+
+    clock.advance(Duration::seconds(900 + 30));
+    assert!(keys.verify(&token, &clock).is_ok());
+    clock.advance(Duration::seconds(60));
+    assert!(matches!(
+        keys.verify(&token, &clock),
+        Err(TokenError::Expired)
+    ));
+    Ok(())
+}
+
+#[tokio::test]
+async fn jwt_service_issues_paseto_tokens() -> Result<()> {
+    // ---
+    let mut config = jwt_config("redis://unused");
+    config.jwt.stateless = true;
+    config.jwt.format = TokenFormat::Paseto;
+    config.jwt.algorithm = SigningAlgorithm::EdDsa;
+    config.jwt.secret = None;
+    config.jwt.private_key_path = Some(fixture("ed25519_private.pem"));
+    config.jwt.public_key_path = Some(fixture("ed25519_public.pem"));
+
+    let state = jwt_service::AppState::stateless(config, SystemClock::shared())?;
+    let base = serve(jwt_service::build_router(state)).await?;
+    let http = http_client();
+
+    let tokens: Value = http
+        .post(format!("{base}/v1/auth/token"))
+        .json(&json!({ "user_id": "user_1", "email": "u@example.com" }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let token = tokens["access_token"].as_str().unwrap();
+    assert!(token.starts_with("v4.public."), "{token}");
+
+    // Same endpoints, same middleware
+    let response = http
+        .get(format!("{base}/v1/protected"))
+        .bearer_auth(token)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let verifier = JwtKeys::paseto_public_verifier(ED_PUBLIC_PEM)?;
+    assert_eq!(verifier.verify(token, &SystemClock)?.sub, "user_1");
+
+    // A JWT is refused, even one signed with the same Ed25519 key
+    let jwt = JwtKeys::from_pem(SigningAlgorithm::EdDsa, ED_PRIVATE_PEM, ED_PUBLIC_PEM)?;
+    let response = http
+        .get(format!("{base}/v1/protected"))
+        .bearer_auth(jwt.sign(&claims())?)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    Ok(())
+}
+
+#[test]
+fn jwt_service_refuses_paseto_with_other_algorithms() {
+    // ---
+    let mut config = jwt_config("redis://unused");
+    config.jwt.stateless = true;
+    config.jwt.format = TokenFormat::Paseto;
+    config.jwt.algorithm = SigningAlgorithm::Es256;
+    config.jwt.secret = None;
+    config.jwt.private_key_path = Some(fixture("ec_private.pem"));
+    config.jwt.public_key_path = Some(fixture("ec_public.pem"));
+
+    let error = jwt_service::AppState::stateless(config, SystemClock::shared())
+        .err()
+        .expect("PASETO has no ES256 purpose");
+    assert!(
+        format!("{error:#}").contains("does not support ES256"),
+        "{error:#}"
+    );
+}
+
+#[test]
+fn token_formats_parse() {
+    // ---
+    assert_eq!("paseto".parse(), Ok(TokenFormat::Paseto));
+    assert_eq!("JWT".parse(), Ok(TokenFormat::Jwt));
+    assert!("jwe".parse::<TokenFormat>().is_err());
+    assert_eq!(TokenFormat::default(), TokenFormat::Jwt);
+}
@@ -0,0 +1,209 @@
+// tests/tests/password_reset.rs
+
+//! Password reset: `POST /v1/auth/password-reset/request` issues a
+//! `reset_password` purpose token, returned to the caller or emailed, and
+//! `POST /v1/auth/password-reset/confirm` consumes it once and signs the user
+//! out everywhere (in-memory store)
+
+use anyhow::Result;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use tokn_core::TestClock;
+use tokn_tests::{http_client, jwt_config, jwt_state_in_memory, post_json, serve, RecordingMailer};
+
+// ---
+
+const NOW: i64 = 1_700_000_000;
+
+// ---
+
+/// Serve jwt-service from an in-memory store on `clock`, emailing tokens to
+/// `password_reset_url` through `mailer` when given, and return its base URL.
+async fn start(
+    clock: &TestClock,
+    password_reset_url: Option<&str>,
+    mailer: Option<&RecordingMailer>,
+) -> Result<String> {
+    // ---
+    let mut config = jwt_config("redis://unused");
+    config.jwt.password_reset_url = password_reset_url.map(str::to_string);
+    let mut state = jwt_state_in_memory(config, clock.shared())?;
+    if let Some(mailer) = mailer {
+        state.mail = mailer.mail();
+    }
+    serve(jwt_service::build_router(state)).await
+}
+
+/// Request a password reset token for `user_1`.
+async fn request_reset(base: &str) -> Result<Value> {
+    // ---
+    let issued = http_client()
+        .post(format!("{base}/v1/auth/password-reset/request"))
+        .json(&json!({ "user_id": "user_1", "email": "u@example.com" }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(issued)
+}
+
+// ---
+
+#[tokio::test]
+async fn password_resets_sign_the_user_out_everywhere() -> Result<()> {
+    // ---
+    let clock = TestClock::at_timestamp(NOW);
+    let base = start(&clock, None, None).await?;
+    let (_, tokens) = post_json(
+        &base,
+        "/v1/auth/token",
+        json!({ "user_id": "user_1", "email": "u@example.com" }),
+    )
+    .await?;
+
+    let issued = request_reset(&base).await?;
+    assert_eq!(issued["expires_in"], 900, "{issued}");
+    let token = issued["token"].as_str().unwrap();
+
+    let (status, reset) = post_json(
+        &base,
+        "/v1/auth/password-reset/confirm",
+        json!({ "token": token }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{reset}");
+    assert_eq!(reset["user_id"], "user_1");
+    assert_eq!(reset["email"], "u@example.com");
+    assert_eq!(reset["sessions_ended"], 1);
+
+    // The user's access and refresh tokens died with the reset
+    let (status, _) = post_json(
+        &base,
+        "/v1/auth/validate",
+        json!({ "token": tokens["access_token"] }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = post_json(
+        &base,
+        "/v1/auth/refresh",
+        json!({ "refresh_token": tokens["refresh_token"] }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // The token is spent
+    let (status, body) = post_json(
+        &base,
+        "/v1/auth/password-reset/confirm",
+        json!({ "token": token }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        body["detail"], "Password reset token has been revoked",
+        "{body}"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn a_reset_revokes_the_users_other_reset_tokens() -> Result<()> {
+    // ---
+    let clock = TestClock::at_timestamp(NOW);
+    let base = start(&clock, None, None).await?;
+    let first = request_reset(&base).await?;
+    let second = request_reset(&base).await?;
+
+    // A reset token is neither an access token nor a verification token
+    for path in ["/v1/auth/validate", "/v1/auth/verify-email/confirm"] {
+        let (status, body) = post_json(&base, path, json!({ "token": second["token"] })).await?;
+        assert_eq!(status, StatusCode::UNAUTHORIZED, "{path}");
+        assert_eq!(
+            body["detail"], "Token is not valid for this purpose",
+            "{body}"
+        );
+    }
+
+    let (status, _) = post_json(
+        &base,
+        "/v1/auth/password-reset/confirm",
+        json!({ "token": first["token"] }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK);
+    let (status, body) = post_json(
+        &base,
+        "/v1/auth/password-reset/confirm",
+        json!({ "token": second["token"] }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        body["detail"], "Password reset token has been revoked",
+        "{body}"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn password_reset_tokens_expire() -> Result<()> {
+    // ---
+    let clock = TestClock::at_timestamp(NOW);
+    let base = start(&clock, None, None).await?;
+    let issued = request_reset(&base).await?;
+
+    clock.advance(chrono::Duration::seconds(900 + 120));
+    let (status, body) = post_json(
+        &base,
+        "/v1/auth/password-reset/confirm",
+        json!({ "token": issued["token"] }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["detail"], "Token has expired", "{body}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn password_resets_are_emailed_with_a_link_url() -> Result<()> {
+    // ---
+    let clock = TestClock::at_timestamp(NOW);
+    let recorder = RecordingMailer::default();
+    let base = start(
+        &clock,
+        Some("https://app.example.com/reset-password"),
+        Some(&recorder),
+    )
+    .await?;
+
+    // The caller never sees the token
+    let issued = request_reset(&base).await?;
+    assert!(issued.get("token").is_none(), "{issued}");
+
+    let sent = recorder.wait_for(1).await;
+    assert_eq!(sent[0].to, "u@example.com");
+    assert!(sent[0].text.contains("15 minutes"), "{}", sent[0].text);
+    let link = sent[0]
+        .text
+        .split_whitespace()
+        .find(|word| word.starts_with("https://app.example.com/reset-password?token="))
+        .expect("the email carries the link");
+    let token = link.rsplit('=').next().unwrap();
+
+    let (status, reset) = post_json(
+        &base,
+        "/v1/auth/password-reset/confirm",
+        json!({ "token": token }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{reset}");
+
+    // And the user hears about it
+    let sent = recorder.wait_for(2).await;
+    assert_eq!(sent.len(), 2);
+    assert_eq!(sent[1].to, "u@example.com");
+    assert!(sent[1].text.contains("was reset"), "{}", sent[1].text);
+    Ok(())
+}
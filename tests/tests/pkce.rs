@@ -0,0 +1,171 @@
+// tests/tests/pkce.rs
+
+//! PKCE (RFC 7636): the S256 helpers in tokn-core, and oauth2-server binding
+//! authorization codes to a `code_challenge` (real Postgres)
+
+use anyhow::Result;
+use reqwest::{header::LOCATION, StatusCode};
+use serde_json::Value;
+use tokn_core::{check_code_challenge, pkce_challenge, verify_code_verifier, PkceError};
+use tokn_tests::{
+    http_client, query_param, TestEnv, DEMO_CLIENT_ID, DEMO_CLIENT_SECRET, DEMO_REDIRECT_URI,
+};
+
+// ---
+
+/// RFC 7636 Appendix B
+const VERIFIER: &str = "dBjftJeZ4CVP-mJ92K1qW8-2y4Edry9xXNC6J5HJ0RQ";
+const CHALLENGE: &str = "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM";
+
+// ---
+
+/// Approve the consent form with `challenge` fields, returning the redirect.
+async fn approve(base: &str, challenge: &[(&str, &str)]) -> Result<reqwest::Url> {
+    // ---
+    let mut form = vec![
+        ("client_id", DEMO_CLIENT_ID),
+        ("redirect_uri", DEMO_REDIRECT_URI),
+        ("scope", "profile"),
+        ("state", "xyz"),
+        ("action", "approve"),
+    ];
+    form.extend_from_slice(challenge);
+    let response = http_client()
+        .post(format!("{base}/v1/oauth/authorize"))
+        .form(&form)
+        .send()
+        .await?;
+    assert!(response.status().is_redirection());
+    Ok(reqwest::Url::parse(response.headers()[LOCATION].to_str()?)?)
+}
+
+/// Exchange `code`, sending `verifier` if given; returns status and body.
+async fn exchange(base: &str, code: &str, verifier: Option<&str>) -> Result<(StatusCode, Value)> {
+    // ---
+    let mut form = vec![
+        ("grant_type", "authorization_code"),
+        ("code", code),
+        ("redirect_uri", DEMO_REDIRECT_URI),
+        ("client_id", DEMO_CLIENT_ID),
+        ("client_secret", DEMO_CLIENT_SECRET),
+    ];
+    if let Some(verifier) = verifier {
+        form.push(("code_verifier", verifier));
+    }
+    let response = http_client()
+        .post(format!("{base}/v1/oauth/token"))
+        .form(&form)
+        .send()
+        .await?;
+    Ok((response.status(), response.json().await?))
+}
+
+// ---
+
+#[test]
+fn s256_challenges_and_verifiers() {
+    // ---
+    assert_eq!(pkce_challenge(VERIFIER), CHALLENGE);
+    assert_eq!(check_code_challenge(CHALLENGE, Some("S256")), Ok(()));
+    assert_eq!(verify_code_verifier(VERIFIER, CHALLENGE), Ok(()));
+
+    // plain, spelled out or implied, is refused
+    assert_eq!(
+        check_code_challenge(CHALLENGE, Some("plain")),
+        Err(PkceError::UnsupportedMethod)
+    );
+    assert_eq!(
+        check_code_challenge(CHALLENGE, None),
+        Err(PkceError::UnsupportedMethod)
+    );
+    assert_eq!(
+        check_code_challenge("too-short", Some("S256")),
+        Err(PkceError::InvalidChallenge)
+    );
+
+    let reversed: String = VERIFIER.chars().rev().collect();
+    assert_eq!(
+        verify_code_verifier(&reversed, CHALLENGE),
+        Err(PkceError::Mismatch)
+    );
+    assert_eq!(
+        verify_code_verifier("short", CHALLENGE),
+        Err(PkceError::InvalidVerifier)
+    );
+    assert_eq!(
+        verify_code_verifier(&format!("{VERIFIER}+"), CHALLENGE),
+        Err(PkceError::InvalidVerifier)
+    );
+}
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn codes_with_a_challenge_require_the_matching_verifier() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let base = env.spawn_oauth2_server().await?;
+    let challenge = [
+        ("code_challenge", CHALLENGE),
+        ("code_challenge_method", "S256"),
+    ];
+    let wrong = "a".repeat(43);
+
+    for verifier in [None, Some(wrong.as_str())] {
+        let url = approve(&base, &challenge).await?;
+        let code = query_param(&url, "code").expect("redirect carries an authorization code");
+        let (status, body) = exchange(&base, &code, verifier).await?;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+        assert_eq!(body["error"], "invalid_grant");
+    }
+
+    let url = approve(&base, &challenge).await?;
+    let code = query_param(&url, "code").expect("redirect carries an authorization code");
+    let (status, body) = exchange(&base, &code, Some(VERIFIER)).await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["token_type"], "Bearer");
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn verifiers_for_codes_without_a_challenge_are_refused() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let base = env.spawn_oauth2_server().await?;
+
+    let url = approve(&base, &[]).await?;
+    let code = query_param(&url, "code").expect("redirect carries an authorization code");
+    let (status, body) = exchange(&base, &code, Some(VERIFIER)).await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+    assert_eq!(body["error"], "invalid_grant");
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn plain_challenges_are_refused() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let base = env.spawn_oauth2_server().await?;
+
+    let response = http_client()
+        .get(format!("{base}/v1/oauth/authorize"))
+        .query(&[
+            ("response_type", "code"),
+            ("client_id", DEMO_CLIENT_ID),
+            ("redirect_uri", DEMO_REDIRECT_URI),
+            ("code_challenge", VERIFIER),
+            ("code_challenge_method", "plain"),
+        ])
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let url = approve(&base, &[("code_challenge", VERIFIER)]).await?;
+    assert_eq!(
+        query_param(&url, "error").as_deref(),
+        Some("invalid_request")
+    );
+    assert_eq!(query_param(&url, "code"), None);
+    Ok(())
+}
@@ -0,0 +1,250 @@
+// tests/tests/portal.rs
+
+//! The developer portal: merging the service specs, per-endpoint auth
+//! requirements, server URL overrides, and the `/docs` routes
+
+use anyhow::Result;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use tokn_portal::{
+    builtin_specs, merge, portal_router, validate_portal_config, PortalConfig, PortalError,
+    ServiceUrls, Spec,
+};
+use tokn_tests::{http_client, serve};
+
+// ---
+
+/// The merged operation at `method` `path`.
+fn operation<'a>(merged: &'a Value, method: &str, path: &str) -> &'a Value {
+    // ---
+    &merged["paths"][path][method]
+}
+
+fn overrides(pairs: &[(&str, &str)]) -> ServiceUrls {
+    // ---
+    ServiceUrls(
+        pairs
+            .iter()
+            .map(|(service, url)| (service.to_string(), url.to_string()))
+            .collect(),
+    )
+}
+
+// ---
+
+#[test]
+fn builtin_specs_merge_into_one_platform_spec() -> Result<()> {
+    // ---
+    let specs = builtin_specs()?;
+    let merged = merge(&specs)?;
+    assert_eq!(merged["openapi"], "3.1.0");
+    assert_eq!(merged["info"]["title"], "tokn platform");
+
+    for path in [
+        "/v1/auth/token",
+        "/v1/protected",
+        "/v1/oauth/token",
+        "/v1/oauth/phone/verify",
+        "/callback",
+        "/admin/reload",
+        "/admin/events",
+    ] {
+        assert!(merged["paths"].get(path).is_some(), "{path} missing");
+    }
+
+    // Operations keep pointing at the service that serves them
+    let servers = &merged["paths"]["/v1/auth/token"]["servers"];
+    assert_eq!(servers[0]["description"], "jwt-service");
+    let servers = &merged["paths"]["/admin/reload"]["servers"];
+    assert_eq!(servers.as_array().map(Vec::len), Some(3));
+
+    // Every operation states its auth requirement
+    for (path, item) in merged["paths"].as_object().unwrap() {
+        for (method, operation) in item.as_object().unwrap() {
+            if method != "servers" {
+                assert!(operation["security"].is_array(), "{method} {path}");
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn auth_requirements_are_declared_per_endpoint() -> Result<()> {
+    // ---
+    let merged = merge(&builtin_specs()?)?;
+
+    assert_eq!(
+        operation(&merged, "post", "/v1/auth/token")["security"],
+        json!([])
+    );
+    assert_eq!(
+        operation(&merged, "get", "/v1/protected")["security"],
+        json!([{ "jwtBearer": [] }])
+    );
+    assert_eq!(
+        operation(&merged, "get", "/v1/oauth/userinfo")["security"],
+        json!([{ "oauth2": [] }])
+    );
+    assert_eq!(
+        operation(&merged, "post", "/v1/oauth/phone")["security"],
+        json!([{ "oauth2": ["phone"] }])
+    );
+    // The admin spec's top-level requirement moves onto its operations
+    assert_eq!(
+        operation(&merged, "get", "/admin/events")["security"],
+        json!([{ "adminToken": [] }, { "serviceSignature": [] }])
+    );
+
+    let schemes = &merged["components"]["securitySchemes"];
+    for scheme in ["jwtBearer", "oauth2", "adminToken", "serviceSignature"] {
+        assert!(schemes.get(scheme).is_some(), "{scheme} missing");
+    }
+    Ok(())
+}
+
+#[test]
+fn merge_rejects_conflicts_and_undeclared_security() -> Result<()> {
+    // ---
+    let spec = |name: &str, document: Value| Spec::parse(name, &document.to_string());
+    let public = json!({ "get": { "security": [], "responses": {} } });
+
+    let a = spec(
+        "a",
+        json!({ "openapi": "3.1.0", "info": {}, "paths": { "/x": public }}),
+    )?;
+    let b = spec(
+        "b",
+        json!({ "openapi": "3.1.0", "info": {}, "paths": { "/x": public }}),
+    )?;
+    assert!(matches!(
+        merge(&[a.clone(), b]),
+        Err(PortalError::Conflict { what: "path", .. })
+    ));
+
+    // The same component may appear twice only with the same contents
+    let with_schema = |name: &str, path: &str, schema: Value| {
+        spec(
+            name,
+            json!({
+                "openapi": "3.1.0",
+                "info": {},
+                "paths": { path: public },
+                "components": { "schemas": { "Error": schema } },
+            }),
+        )
+    };
+    let c = with_schema("c", "/c", json!({ "type": "object" }))?;
+    let d = with_schema("d", "/d", json!({ "type": "object" }))?;
+    let e = with_schema("e", "/e", json!({ "type": "string" }))?;
+    assert!(merge(&[c.clone(), d]).is_ok());
+    assert!(matches!(
+        merge(&[c, e]),
+        Err(PortalError::Conflict {
+            what: "component",
+            ..
+        })
+    ));
+
+    let undeclared = spec(
+        "f",
+        json!({ "openapi": "3.1.0", "info": {}, "paths": { "/f": { "post": {} }}}),
+    )?;
+    let err = merge(&[undeclared]).unwrap_err();
+    assert!(matches!(err, PortalError::MissingSecurity { .. }));
+    assert!(err.to_string().contains("POST /f"));
+
+    assert!(matches!(
+        Spec::parse("g", r#"{"openapi": "3.1.0"}"#),
+        Err(PortalError::InvalidSpec { .. })
+    ));
+    Ok(())
+}
+
+#[test]
+fn config_validation() {
+    // ---
+    assert!(validate_portal_config(&PortalConfig::default()).is_ok());
+
+    let valid = PortalConfig {
+        servers: overrides(&[("jwt-service", "https://jwt.example.com")]),
+        ..Default::default()
+    };
+    assert!(validate_portal_config(&valid).is_ok());
+
+    let unknown = PortalConfig {
+        servers: overrides(&[("billing", "https://billing.example.com")]),
+        ..Default::default()
+    };
+    assert!(validate_portal_config(&unknown).is_err());
+
+    let not_http = PortalConfig {
+        servers: overrides(&[("jwt-service", "jwt.example.com")]),
+        ..Default::default()
+    };
+    assert!(validate_portal_config(&not_http).is_err());
+}
+
+#[tokio::test]
+async fn portal_serves_swagger_ui_and_specs() -> Result<()> {
+    // ---
+    let config = PortalConfig {
+        servers: overrides(&[("jwt-service", "https://jwt.example.com")]),
+        ..Default::default()
+    };
+    let base = serve(portal_router(&config)?).await?;
+    let http = http_client();
+
+    let page = http.get(format!("{base}/docs")).send().await?;
+    assert_eq!(page.status(), StatusCode::OK);
+    let page = page.text().await?;
+    assert!(page.contains("SwaggerUIBundle"));
+    assert!(page.contains("/docs/openapi.json"));
+    assert!(page.contains("/docs/specs/oauth2-server.json"));
+
+    let merged = http.get(format!("{base}/docs/openapi.json")).send().await?;
+    assert_eq!(merged.status(), StatusCode::OK);
+    assert_eq!(merged.headers()[CONTENT_TYPE], "application/json");
+    let merged: Value = merged.json().await?;
+    assert_eq!(
+        merged["paths"]["/v1/protected"]["servers"][0]["url"],
+        "https://jwt.example.com"
+    );
+    // The admin endpoints on jwt-service move too; the others keep theirs
+    let admin_servers = &merged["paths"]["/admin/reload"]["servers"];
+    assert_eq!(admin_servers[0]["url"], "https://jwt.example.com");
+    assert_eq!(admin_servers[1]["url"], "http://127.0.0.1:8082");
+
+    let spec: Value = http
+        .get(format!("{base}/docs/specs/jwt-service.json"))
+        .send()
+        .await?
+        .json()
+        .await?;
+    assert_eq!(spec["info"]["title"], "jwt-service");
+    assert_eq!(spec["servers"][0]["url"], "https://jwt.example.com");
+    Ok(())
+}
+
+#[tokio::test]
+async fn disabled_portal_serves_nothing() -> Result<()> {
+    // ---
+    let config = PortalConfig {
+        enabled: false,
+        ..Default::default()
+    };
+    let base = serve(portal_router(&config)?).await?;
+    let response = http_client().get(format!("{base}/docs")).send().await?;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let unknown = PortalConfig {
+        servers: overrides(&[("billing", "https://billing.example.com")]),
+        ..Default::default()
+    };
+    assert!(matches!(
+        portal_router(&unknown),
+        Err(PortalError::UnknownService(service)) if service == "billing"
+    ));
+    Ok(())
+}
@@ -0,0 +1,179 @@
+// tests/tests/problem.rs
+
+//! RFC 7807 problem details: the body shape, the middleware that fills in
+//! the request path and ID, and jwt-service's typed errors
+
+use anyhow::Result;
+use axum::http::{header, StatusCode};
+use axum::middleware;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use jwt_service::ApiError;
+use serde_json::{json, Value};
+use tokn_core::{Problem, TestClock, PROBLEM_JSON};
+use tokn_tests::{http_client, jwt_config, serve, spawn_jwt_service_in_memory};
+
+// ---
+
+/// Serve a failing route, a rate-limited route, and a successful route
+/// behind the problem details middleware.
+async fn spawn() -> Result<String> {
+    // ---
+    let app = Router::new()
+        .route(
+            "/v1/fail",
+            get(|| async { Problem::new(StatusCode::NOT_FOUND).detail("User not found") }),
+        )
+        .route(
+            "/v1/slow-down",
+            get(|| async {
+                let mut response = Problem::new(StatusCode::TOO_MANY_REQUESTS).into_response();
+                response
+                    .headers_mut()
+                    .insert(header::RETRY_AFTER, 30.into());
+                response
+            }),
+        )
+        .route("/v1/ok", get(|| async { "ok" }))
+        .layer(middleware::from_fn(tokn_server::problem_details));
+    serve(app).await
+}
+
+// ---
+
+#[test]
+fn problem_serializes_per_rfc_7807() -> Result<()> {
+    // ---
+    let problem = Problem::new(StatusCode::UNAUTHORIZED)
+        .detail("Token has expired")
+        .extension("valid", false);
+    assert_eq!(
+        serde_json::to_value(&problem)?,
+        json!({
+            "type": "about:blank",
+            "title": "Unauthorized",
+            "status": 401,
+            "detail": "Token has expired",
+            "valid": false,
+        })
+    );
+
+    let typed = Problem::new(StatusCode::BAD_REQUEST)
+        .with_type("https://tokn.dev/problems/phone", "Invalid phone number")
+        .instance("/v1/oauth/phone")
+        .request_id("req-1");
+    let parsed: Problem = serde_json::from_slice(&typed.to_json())?;
+    assert_eq!(parsed, typed);
+    assert_eq!(parsed.status_code(), StatusCode::BAD_REQUEST);
+    Ok(())
+}
+
+#[tokio::test]
+async fn middleware_adds_the_request_path_and_id() -> Result<()> {
+    // ---
+    let base = spawn().await?;
+    let http = http_client();
+
+    let response = http.get(format!("{base}/v1/fail")).send().await?;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(response.headers()[header::CONTENT_TYPE], PROBLEM_JSON);
+    let generated = response.headers()[tokn_server::REQUEST_ID]
+        .to_str()?
+        .to_string();
+    let body: Value = response.json().await?;
+    assert_eq!(body["title"], "Not Found");
+    assert_eq!(body["detail"], "User not found");
+    assert_eq!(body["instance"], "/v1/fail");
+    assert_eq!(body["request_id"], generated.as_str());
+
+    // A caller's request ID is kept
+    let response = http
+        .get(format!("{base}/v1/fail"))
+        .header(tokn_server::REQUEST_ID, "req-from-proxy")
+        .send()
+        .await?;
+    assert_eq!(
+        response.headers()[tokn_server::REQUEST_ID],
+        "req-from-proxy"
+    );
+    let body: Value = response.json().await?;
+    assert_eq!(body["request_id"], "req-from-proxy");
+
+    // The handler's own headers survive
+    let response = http.get(format!("{base}/v1/slow-down")).send().await?;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.headers()[header::RETRY_AFTER], "30");
+    let body: Value = response.json().await?;
+    assert_eq!(body["title"], "Too Many Requests");
+    assert_eq!(body["instance"], "/v1/slow-down");
+
+    // Anything else passes through
+    let response = http.get(format!("{base}/v1/ok")).send().await?;
+    assert!(response.headers().get(tokn_server::REQUEST_ID).is_none());
+    assert_eq!(response.text().await?, "ok");
+    Ok(())
+}
+
+#[test]
+fn api_errors_have_stable_types() -> Result<()> {
+    // ---
+    let problem = ApiError::InsufficientScope("admin".into()).into_problem();
+    assert_eq!(
+        serde_json::to_value(&problem)?,
+        json!({
+            "type": "https://tokn.dev/problems/insufficient-scope",
+            "title": "Insufficient scope",
+            "status": 403,
+            "detail": "Token lacks required scope 'admin'",
+            "required_scope": "admin",
+        })
+    );
+
+    // The title is the type's, whatever the occurrence
+    let expired = ApiError::InvalidToken("Token has expired".into()).into_problem();
+    let used = ApiError::InvalidToken("Ticket has already been used".into()).into_problem();
+    assert_eq!(serde_json::to_value(&expired)?["title"], "Invalid token");
+    assert_eq!(serde_json::to_value(&used)?["title"], "Invalid token");
+
+    // An endpoint that is not served says no more than its status
+    let problem = serde_json::to_value(ApiError::NotFound.into_problem())?;
+    assert_eq!(problem["type"], "https://tokn.dev/problems/not-found");
+    assert_eq!(problem["status"], 404);
+    assert!(problem.get("detail").is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn jwt_service_errors_are_typed_problems() -> Result<()> {
+    // ---
+    let clock = TestClock::at_timestamp(1_700_000_000);
+    let base = spawn_jwt_service_in_memory(jwt_config("redis://unused"), clock.shared()).await?;
+    let http = http_client();
+
+    let response = http
+        .post(format!("{base}/v1/auth/validate"))
+        .json(&json!({ "token": "not-a-jwt" }))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(response.headers()[header::CONTENT_TYPE], PROBLEM_JSON);
+    let body: Value = response.json().await?;
+    assert_eq!(body["type"], "https://tokn.dev/problems/invalid-token");
+    assert_eq!(body["title"], "Invalid token");
+    assert_eq!(body["valid"], false);
+
+    let response = http
+        .post(format!("{base}/v1/auth/token"))
+        .json(&json!({ "user_id": "user_1", "email": "u@example.com", "expires_in": 0 }))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body: Value = response.json().await?;
+    assert_eq!(body["type"], "https://tokn.dev/problems/invalid-request");
+    assert_eq!(
+        body["detail"],
+        "expires_in must be a positive number of seconds"
+    );
+    Ok(())
+}
@@ -0,0 +1,176 @@
+// tests/tests/profiles.rs
+
+//! Deployment profiles: what `dev`, `staging`, and `prod` each refuse at
+//! startup (no containers needed)
+
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use tokn_config::{ConfigError, ConfigLoader, Profile, Secret};
+use tokn_server::{require_tls, Bind, TlsConfig};
+
+// ---
+
+const STRONG: &str = "Jx4q9Lr2vTz7Wm1Kp8Ns3Hd6Bf0Gc5Ye";
+
+// ---
+
+#[derive(Debug, Deserialize)]
+struct Config {
+    // ---
+    #[serde(default)]
+    profile: Profile,
+    secret: Secret,
+    #[serde(default)]
+    server: ServerConfig,
+    #[serde(default)]
+    debug_endpoints: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ServerConfig {
+    // ---
+    host: String,
+    #[serde(default)]
+    bind: Option<Bind>,
+    #[serde(default)]
+    tls: Option<TlsConfig>,
+}
+
+/// Write `contents` to a fresh TOML file for one test.
+fn config_file(name: &str, contents: &str) -> PathBuf {
+    // ---
+    let path =
+        std::env::temp_dir().join(format!("tokn-profile-{name}-{}.toml", std::process::id()));
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+/// Load `contents` the way a service would, with a defaulted secret, a TLS
+/// rule for production, and a development-only switch.
+fn load(name: &str, contents: &str) -> Result<Config, ConfigError> {
+    // ---
+    let path = config_file(name, contents);
+    let config = loader(&path).load();
+    std::fs::remove_file(&path).ok();
+    config
+}
+
+fn loader(path: &Path) -> ConfigLoader {
+    // ---
+    ConfigLoader::new("test")
+        .file(path)
+        .optional("server.host", "TOKN_TEST_HOST", "127.0.0.1".to_string())
+        .optional("secret", "TOKN_TEST_SECRET", STRONG.to_string())
+        .key::<bool>("debug_endpoints", "TOKN_TEST_DEBUG_ENDPOINTS")
+        .secret("secret")
+        .prod_rule("server", |server: &ServerConfig| {
+            require_tls(server.tls.as_ref(), server.bind.as_ref())
+        })
+        .forbid_in_prod("debug_endpoints")
+}
+
+// ---
+
+#[test]
+fn profiles_parse_with_aliases() {
+    // ---
+    for (value, profile) in [
+        ("dev", Profile::Dev),
+        ("development", Profile::Dev),
+        ("staging", Profile::Staging),
+        ("stage", Profile::Staging),
+        ("prod", Profile::Prod),
+        ("production", Profile::Prod),
+    ] {
+        let parsed: Profile = serde_json::from_value(serde_json::json!(value)).unwrap();
+        assert_eq!(parsed, profile, "{value}");
+    }
+    assert_eq!(Profile::Staging.to_string(), "staging");
+    assert!(Profile::Staging.is_strict() && !Profile::Staging.is_prod());
+    assert!(!Profile::Dev.is_strict());
+}
+
+#[test]
+fn dev_allows_defaults_and_development_switches() {
+    // ---
+    let config = load("dev", "debug_endpoints = true\n").unwrap();
+    assert_eq!(config.profile, Profile::Dev);
+    assert_eq!(config.secret.expose(), STRONG);
+    assert!(config.debug_endpoints);
+    assert_eq!(config.server.host, "127.0.0.1");
+    assert!(config.server.tls.is_none());
+}
+
+#[test]
+fn staging_refuses_default_and_weak_secrets_only() {
+    // ---
+    let err = load("staging-default", "profile = \"staging\"\n")
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains("secret (env TOKN_TEST_SECRET): is a weak secret: is the built-in default"),
+        "{err}"
+    );
+
+    let err = load(
+        "staging-weak",
+        "profile = \"staging\"\nsecret = \"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa\"\n",
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("is a weak secret"), "{err}");
+
+    // No TLS and development switches are fine short of production
+    let config = load(
+        "staging-ok",
+        "profile = \"staging\"\nsecret = \"3f9a1c7e52b8d046e1a9c3f7b2d5e8a04c6f1b9d7e3a5c2f\"\ndebug_endpoints = true\n",
+    )
+    .unwrap();
+    assert_eq!(config.profile, Profile::Staging);
+}
+
+#[test]
+fn prod_refuses_missing_tls_and_development_switches() {
+    // ---
+    let err = load(
+        "prod-insecure",
+        "profile = \"prod\"\nsecret = \"3f9a1c7e52b8d046e1a9c3f7b2d5e8a04c6f1b9d7e3a5c2f\"\ndebug_endpoints = true\n",
+    )
+    .unwrap_err();
+    let ConfigError::Invalid { problems, .. } = &err else {
+        panic!("unexpected error: {err}");
+    };
+    assert_eq!(problems.len(), 2, "{err}");
+
+    let err = err.to_string();
+    assert!(err.contains("server: must serve HTTPS"), "{err}");
+    assert!(err.contains("when TOKN_ENV=prod"), "{err}");
+    assert!(
+        err.contains(
+            "debug_endpoints (env TOKN_TEST_DEBUG_ENDPOINTS): must be off when TOKN_ENV=prod"
+        ),
+        "{err}"
+    );
+}
+
+#[test]
+fn prod_starts_with_tls_or_a_unix_socket() {
+    // ---
+    let config = load(
+        "prod-tls",
+        "profile = \"prod\"\nsecret = \"3f9a1c7e52b8d046e1a9c3f7b2d5e8a04c6f1b9d7e3a5c2f\"\n\n[server.tls]\ncert_path = \"cert.pem\"\nkey_path = \"key.pem\"\n",
+    )
+    .unwrap();
+    assert_eq!(config.profile, Profile::Prod);
+
+    let config = load(
+        "prod-unix",
+        "profile = \"prod\"\nsecret = \"3f9a1c7e52b8d046e1a9c3f7b2d5e8a04c6f1b9d7e3a5c2f\"\n\n[server]\nbind = \"unix:/run/tokn.sock\"\n",
+    )
+    .unwrap();
+    assert!(matches!(config.server.bind, Some(Bind::Unix(_))));
+
+    let tcp = Bind::Tcp("0.0.0.0:8083".to_string());
+    assert!(require_tls(None, Some(&tcp)).is_err());
+    assert!(require_tls(None, None).is_err());
+}
@@ -0,0 +1,399 @@
+// tests/tests/ratelimit.rs
+
+//! Distributed rate limiting: config validation, the sliding window and
+//! token bucket algorithms over Redis, client identification, per-endpoint
+//! and per-user limits, and failing open when Redis misbehaves
+
+use anyhow::Result;
+use axum::routing::get;
+use axum::Router;
+use chrono::Duration;
+use reqwest::{Response, StatusCode};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use tokn_core::TestClock;
+use tokn_ratelimit::{
+    validate_rate_limit_config, Algorithm, KeyBy, RateLimitConfig, RateLimitLayer, RateLimiter,
+};
+use tokn_tests::{http_client, serve, TestEnv};
+
+// ---
+
+/// Serve `/` and an exempt `/health` behind `limiter`.
+async fn limited_app(limiter: RateLimiter) -> Result<String> {
+    // ---
+    let app = Router::new()
+        .route("/", get(|| async { "ok" }))
+        .route("/health", get(|| async { "OK" }))
+        .layer(RateLimitLayer::new(limiter).exempt("/health"));
+    serve(app).await
+}
+
+/// Serve `/` and `/login` behind `limiter`, identifying users by an
+/// `X-User` header.
+async fn user_limited_app(limiter: RateLimiter) -> Result<String> {
+    // ---
+    let layer = RateLimitLayer::new(limiter).identify_users(|headers| {
+        let user = headers.get("x-user")?.to_str().ok()?;
+        Some(user.to_string())
+    });
+    let app = Router::new()
+        .route("/", get(|| async { "ok" }))
+        .route("/login", get(|| async { "ok" }))
+        .layer(layer);
+    serve(app).await
+}
+
+/// An enabled limiter for `service` against the environment's Redis.
+async fn connect(
+    env: &TestEnv,
+    service: &'static str,
+    config: RateLimitConfig,
+    clock: &TestClock,
+) -> Result<RateLimiter> {
+    // ---
+    let config = RateLimitConfig {
+        enabled: true,
+        ..config
+    };
+    Ok(RateLimiter::connect(
+        service,
+        &config,
+        &env.redis_url,
+        Default::default(),
+        clock.shared(),
+    )
+    .await?)
+}
+
+fn header(response: &Response, name: &str) -> Option<u64> {
+    // ---
+    response.headers().get(name)?.to_str().ok()?.parse().ok()
+}
+
+// ---
+
+#[test]
+fn config_validation() {
+    // ---
+    assert!(validate_rate_limit_config(&RateLimitConfig::default()).is_ok());
+
+    let zero_requests = RateLimitConfig {
+        requests: 0,
+        ..Default::default()
+    };
+    assert!(validate_rate_limit_config(&zero_requests).is_err());
+
+    let zero_window = RateLimitConfig {
+        window_seconds: 0,
+        ..Default::default()
+    };
+    assert!(validate_rate_limit_config(&zero_window).is_err());
+
+    // A burst only means something to the token bucket
+    let sliding_burst = RateLimitConfig {
+        burst: Some(10),
+        ..Default::default()
+    };
+    assert!(validate_rate_limit_config(&sliding_burst).is_err());
+
+    let bucket = RateLimitConfig {
+        algorithm: Algorithm::TokenBucket,
+        burst: Some(10),
+        ..Default::default()
+    };
+    assert!(validate_rate_limit_config(&bucket).is_ok());
+    assert_eq!(bucket.capacity(), 10);
+
+    let empty_bucket = RateLimitConfig {
+        burst: Some(0),
+        ..bucket
+    };
+    assert!(validate_rate_limit_config(&empty_bucket).is_err());
+
+    // Endpoint limits need a path and a positive limit
+    let endpoints = |path: &str, requests| RateLimitConfig {
+        endpoints: BTreeMap::from([(path.to_string(), requests)]),
+        ..Default::default()
+    };
+    assert!(validate_rate_limit_config(&endpoints("/v1/auth/token", 10)).is_ok());
+    assert!(validate_rate_limit_config(&endpoints("v1/auth/token", 10)).is_err());
+    assert!(validate_rate_limit_config(&endpoints("/v1/auth/token", 0)).is_err());
+    assert_eq!(endpoints("/login", 5).limit_for("/login"), (5, 5));
+    assert_eq!(endpoints("/login", 5).limit_for("/"), (100, 100));
+}
+
+#[tokio::test]
+async fn disabled_limiter_passes_everything_through() -> Result<()> {
+    // ---
+    // A disabled config never touches Redis, so the URL need not resolve
+    let limiter = RateLimiter::connect(
+        "disabled",
+        &RateLimitConfig::default(),
+        "redis://unreachable.invalid:6379",
+        Default::default(),
+        TestClock::default().shared(),
+    )
+    .await?;
+    assert!(!limiter.is_enabled());
+    assert_eq!(limiter.check("127.0.0.1").await?, None);
+
+    let base = limited_app(limiter).await?;
+    let http = http_client();
+    for _ in 0..5 {
+        let response = http.get(format!("{base}/")).send().await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().get("ratelimit-limit").is_none());
+    }
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn sliding_window_limits_and_recovers() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let clock = TestClock::default();
+    let config = RateLimitConfig {
+        requests: 3,
+        window_seconds: 60,
+        ..Default::default()
+    };
+    let base = limited_app(connect(&env, "sliding", config, &clock).await?).await?;
+    let http = http_client();
+
+    for remaining in [2, 1, 0] {
+        let response = http.get(format!("{base}/")).send().await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(header(&response, "ratelimit-limit"), Some(3));
+        assert_eq!(header(&response, "ratelimit-remaining"), Some(remaining));
+        assert_eq!(header(&response, "ratelimit-reset"), Some(60));
+    }
+
+    let limited = http.get(format!("{base}/")).send().await?;
+    assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(header(&limited, "retry-after"), Some(60));
+    assert_eq!(header(&limited, "ratelimit-remaining"), Some(0));
+    let body: Value = limited.json().await?;
+    assert_eq!(body["error"], "rate_limited");
+
+    // Health checks are never limited
+    let health = http.get(format!("{base}/health")).send().await?;
+    assert_eq!(health.status(), StatusCode::OK);
+    assert!(health.headers().get("ratelimit-limit").is_none());
+
+    // Refused requests do not count: the window still ends 60s after the first
+    clock.advance(Duration::seconds(30));
+    let limited = http.get(format!("{base}/")).send().await?;
+    assert_eq!(header(&limited, "retry-after"), Some(30));
+
+    clock.advance(Duration::seconds(30));
+    let response = http.get(format!("{base}/")).send().await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn token_bucket_allows_bursts_then_refills() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let clock = TestClock::default();
+    let config = RateLimitConfig {
+        algorithm: Algorithm::TokenBucket,
+        requests: 1,
+        window_seconds: 10,
+        burst: Some(3),
+        ..Default::default()
+    };
+    let base = limited_app(connect(&env, "bucket", config, &clock).await?).await?;
+    let http = http_client();
+
+    for remaining in [2, 1, 0] {
+        let response = http.get(format!("{base}/")).send().await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(header(&response, "ratelimit-limit"), Some(3));
+        assert_eq!(header(&response, "ratelimit-remaining"), Some(remaining));
+    }
+
+    let limited = http.get(format!("{base}/")).send().await?;
+    assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+    let retry_after = header(&limited, "retry-after").expect("Retry-After header");
+    assert!(
+        (10..=11).contains(&retry_after),
+        "retry after {retry_after}s"
+    );
+
+    // One token per 10 seconds comes back
+    clock.advance(Duration::seconds(retry_after as i64));
+    let response = http.get(format!("{base}/")).send().await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = http.get(format!("{base}/")).send().await?;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn clients_are_limited_separately() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let clock = TestClock::default();
+    let config = RateLimitConfig {
+        requests: 1,
+        ..Default::default()
+    };
+    let http = http_client();
+
+    // Trusted: the last X-Forwarded-For entry identifies the client
+    let trusting = RateLimitConfig {
+        trust_forwarded_for: true,
+        ..config.clone()
+    };
+    let base = limited_app(connect(&env, "trusting", trusting, &clock).await?).await?;
+    let send = |forwarded_for: &'static str| {
+        http.get(format!("{base}/"))
+            .header("X-Forwarded-For", forwarded_for)
+            .send()
+    };
+    assert_eq!(send("198.51.100.1").await?.status(), StatusCode::OK);
+    assert_eq!(
+        send("198.51.100.1").await?.status(),
+        StatusCode::TOO_MANY_REQUESTS
+    );
+    assert_eq!(
+        send("198.51.100.1, 198.51.100.2").await?.status(),
+        StatusCode::OK
+    );
+
+    // Untrusted: the header is ignored and every request comes from 127.0.0.1
+    let base = limited_app(connect(&env, "untrusting", config, &clock).await?).await?;
+    let send = |forwarded_for: &'static str| {
+        http.get(format!("{base}/"))
+            .header("X-Forwarded-For", forwarded_for)
+            .send()
+    };
+    assert_eq!(send("198.51.100.1").await?.status(), StatusCode::OK);
+    assert_eq!(
+        send("198.51.100.2").await?.status(),
+        StatusCode::TOO_MANY_REQUESTS
+    );
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn redis_errors_fail_open() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let clock = TestClock::default();
+    let config = RateLimitConfig {
+        requests: 1,
+        ..Default::default()
+    };
+    let limiter = connect(&env, "failing", config, &clock).await?;
+
+    // A key of the wrong type makes every check fail with WRONGTYPE
+    let mut redis = redis::Client::open(env.redis_url.as_str())?
+        .get_multiplexed_async_connection()
+        .await?;
+    redis::cmd("SET")
+        .arg("tokn:ratelimit:failing:sliding_window:127.0.0.1")
+        .arg("not a sorted set")
+        .query_async::<()>(&mut redis)
+        .await?;
+    assert!(limiter.check("127.0.0.1").await.is_err());
+
+    let base = limited_app(limiter).await?;
+    let http = http_client();
+    for _ in 0..3 {
+        let response = http.get(format!("{base}/")).send().await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().get("ratelimit-limit").is_none());
+    }
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn endpoints_have_limits_of_their_own() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let clock = TestClock::default();
+    let config = RateLimitConfig {
+        requests: 3,
+        endpoints: BTreeMap::from([("/login".to_string(), 1)]),
+        ..Default::default()
+    };
+    let base = user_limited_app(connect(&env, "endpoints", config, &clock).await?).await?;
+    let http = http_client();
+
+    let response = http.get(format!("{base}/login")).send().await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(header(&response, "ratelimit-limit"), Some(1));
+    let limited = http.get(format!("{base}/login")).send().await?;
+    assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(header(&limited, "retry-after"), Some(60));
+
+    // The rest of the service keeps its own count
+    for remaining in [2, 1, 0] {
+        let response = http.get(format!("{base}/")).send().await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(header(&response, "ratelimit-limit"), Some(3));
+        assert_eq!(header(&response, "ratelimit-remaining"), Some(remaining));
+    }
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn users_are_limited_by_identity() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let clock = TestClock::default();
+    let http = http_client();
+    let send = |base: &str, user: Option<&'static str>| {
+        let request = http.get(format!("{base}/"));
+        match user {
+            Some(user) => request.header("X-User", user),
+            None => request,
+        }
+        .send()
+    };
+
+    // By user: each user has a bucket, anonymous requests share the IP's
+    let config = RateLimitConfig {
+        requests: 1,
+        key_by: KeyBy::User,
+        ..Default::default()
+    };
+    let base = user_limited_app(connect(&env, "by-user", config.clone(), &clock).await?).await?;
+    assert_eq!(send(&base, Some("alice")).await?.status(), StatusCode::OK);
+    assert_eq!(
+        send(&base, Some("alice")).await?.status(),
+        StatusCode::TOO_MANY_REQUESTS
+    );
+    assert_eq!(send(&base, Some("bob")).await?.status(), StatusCode::OK);
+    assert_eq!(send(&base, None).await?.status(), StatusCode::OK);
+    assert_eq!(
+        send(&base, None).await?.status(),
+        StatusCode::TOO_MANY_REQUESTS
+    );
+
+    // By IP and user: a new user does not escape the address's limit
+    let config = RateLimitConfig {
+        requests: 2,
+        key_by: KeyBy::IpAndUser,
+        ..config
+    };
+    let base = user_limited_app(connect(&env, "by-both", config, &clock).await?).await?;
+    let response = send(&base, Some("alice")).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(header(&response, "ratelimit-remaining"), Some(1));
+    assert_eq!(send(&base, Some("bob")).await?.status(), StatusCode::OK);
+    assert_eq!(
+        send(&base, Some("carol")).await?.status(),
+        StatusCode::TOO_MANY_REQUESTS
+    );
+    Ok(())
+}
@@ -0,0 +1,96 @@
+// tests/tests/refresh_grace.rs
+
+//! Refresh grace window: with `jwt.refresh_grace_seconds` set, a refresh
+//! token presented again right after its rotation gets the same successor
+//! instead of 401, until the window closes (in-memory store)
+
+use anyhow::Result;
+use chrono::Duration;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use tokn_core::TestClock;
+use tokn_tests::{jwt_config, post_json, sign_in, spawn_jwt_service_in_memory};
+
+// ---
+
+const NOW: i64 = 1_700_000_000;
+
+// ---
+
+/// [`jwt_config`] with a refresh grace window of `grace_seconds`.
+fn config(grace_seconds: u64) -> jwt_service::Config {
+    // ---
+    let mut config = jwt_config("redis://unused");
+    config.jwt.refresh_grace_seconds = grace_seconds;
+    config
+}
+
+/// Refresh with `refresh_token`.
+async fn refresh(base: &str, refresh_token: &Value) -> Result<(StatusCode, Value)> {
+    // ---
+    post_json(
+        base,
+        "/v1/auth/refresh",
+        json!({ "refresh_token": refresh_token }),
+    )
+    .await
+}
+
+// ---
+
+#[tokio::test]
+async fn a_just_rotated_token_resolves_to_its_successor() -> Result<()> {
+    // ---
+    let clock = TestClock::at_timestamp(NOW);
+    let base = spawn_jwt_service_in_memory(config(10), clock.shared()).await?;
+    let refresh_token = sign_in(&base, "user_1").await?["refresh_token"].clone();
+
+    let (status, first) = refresh(&base, &refresh_token).await?;
+    assert_eq!(status, StatusCode::OK, "{first}");
+    clock.advance(Duration::seconds(2));
+    let (status, second) = refresh(&base, &refresh_token).await?;
+    assert_eq!(status, StatusCode::OK, "{second}");
+
+    // Both callers continue the same session
+    assert_eq!(second["refresh_token"], first["refresh_token"]);
+    let (status, access) = post_json(
+        &base,
+        "/v1/auth/validate",
+        json!({ "token": second["access_token"] }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{access}");
+    let (status, _) = refresh(&base, &second["refresh_token"]).await?;
+    assert_eq!(status, StatusCode::OK);
+    Ok(())
+}
+
+#[tokio::test]
+async fn rotated_tokens_are_refused_after_the_window() -> Result<()> {
+    // ---
+    let clock = TestClock::at_timestamp(NOW);
+    let base = spawn_jwt_service_in_memory(config(10), clock.shared()).await?;
+    let refresh_token = sign_in(&base, "user_1").await?["refresh_token"].clone();
+
+    let (status, _) = refresh(&base, &refresh_token).await?;
+    assert_eq!(status, StatusCode::OK);
+    clock.advance(Duration::seconds(11));
+    let (status, body) = refresh(&base, &refresh_token).await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["detail"], "Invalid or expired refresh token", "{body}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn without_a_window_rotated_tokens_are_refused_at_once() -> Result<()> {
+    // ---
+    let clock = TestClock::at_timestamp(NOW);
+    let base = spawn_jwt_service_in_memory(config(0), clock.shared()).await?;
+    let refresh_token = sign_in(&base, "user_1").await?["refresh_token"].clone();
+
+    let (status, _) = refresh(&base, &refresh_token).await?;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = refresh(&base, &refresh_token).await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    Ok(())
+}
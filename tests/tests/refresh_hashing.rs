@@ -0,0 +1,188 @@
+// tests/tests/refresh_hashing.rs
+
+//! Refresh tokens in Redis are kept only as their SHA-256: no key or session
+//! index entry holds a usable token, and tokens stored raw before hashing
+//! keep working until they expire (real Redis)
+
+use anyhow::Result;
+use jwt_service::{
+    create_redis_client, delete_user_session, generate_refresh_token, list_user_sessions,
+    refresh_successor, refresh_token_reused, store_refresh_successor, store_refresh_token,
+    validate_refresh_token, RefreshTokenData,
+};
+use redis::AsyncCommands;
+use tokn_core::{keys, Claims, SystemClock};
+use tokn_tests::{jwt_config, TestEnv};
+
+// ---
+
+/// Refresh token data for a fresh session of `user_id`.
+fn session_data(user_id: &str) -> RefreshTokenData {
+    // ---
+    let jwt = jwt_config("redis://unused").jwt;
+    let now = chrono::Utc::now().timestamp();
+    let claims = Claims::new(user_id.into(), "u@example.com".into(), 900, &SystemClock);
+    let (data, _) = RefreshTokenData::from(&claims).renew(&jwt, now).unwrap();
+    data
+}
+
+// ---
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn redis_holds_no_usable_refresh_token() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let mut redis = create_redis_client(&env.redis_url).await?;
+    let data = session_data("user_1");
+    let token = generate_refresh_token(&mut redis, &data, 3600).await?;
+
+    let stored: Vec<String> = redis.keys("refresh_token:*").await?;
+    assert_eq!(
+        stored,
+        [format!(
+            "refresh_token:{}",
+            keys::refresh_token_hash(&token)
+        )]
+    );
+    let index: Vec<String> = redis.hvals(keys::user_sessions("user_1")).await?;
+    assert_eq!(index, [keys::refresh_token_hash(&token)]);
+
+    let sessions = list_user_sessions(&mut redis, "user_1").await?;
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0].token_hash, keys::refresh_token_hash(&token));
+
+    // Looked up by hash, and its reuse marker is hashed too
+    assert_eq!(
+        validate_refresh_token(&mut redis, &token).await?.user_id,
+        "user_1"
+    );
+    assert!(validate_refresh_token(&mut redis, &token).await.is_err());
+    let used: Vec<String> = redis.keys("used_refresh_token:*").await?;
+    assert_eq!(used, [keys::used_refresh_token(&token)]);
+    assert!(refresh_token_reused(&mut redis, &token).await?.is_some());
+
+    // A hash read from Redis is no token
+    let other = generate_refresh_token(&mut redis, &data, 3600).await?;
+    let hash = keys::refresh_token_hash(&other);
+    assert!(validate_refresh_token(&mut redis, &hash).await.is_err());
+    assert!(
+        refresh_token_reused(&mut redis, &keys::refresh_token_hash(&token))
+            .await?
+            .is_none()
+    );
+    assert_eq!(
+        validate_refresh_token(&mut redis, &other).await?.user_id,
+        "user_1"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn grace_window_successors_are_sealed() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let mut redis = create_redis_client(&env.redis_url).await?;
+    let data = session_data("user_1");
+    let rotated = generate_refresh_token(&mut redis, &data, 3600).await?;
+    validate_refresh_token(&mut redis, &rotated).await?;
+    let successor = generate_refresh_token(&mut redis, &data, 3600).await?;
+    store_refresh_successor(&mut redis, &rotated, &successor, 10).await?;
+
+    // Neither token can be read back from the entry
+    let sealed: String = redis.get(keys::refresh_successor(&rotated)).await?;
+    assert!(!sealed.contains(&successor), "{sealed}");
+    assert!(!sealed.contains(&rotated), "{sealed}");
+    assert!(
+        redis
+            .ttl::<_, i64>(keys::refresh_successor(&rotated))
+            .await?
+            <= 10
+    );
+
+    // Only the rotated token opens it
+    let (found, found_data) = refresh_successor(&mut redis, &rotated).await?.unwrap();
+    assert_eq!(found, successor);
+    assert_eq!(found_data.session_id, data.session_id);
+    let hash = keys::refresh_token_hash(&rotated);
+    assert!(refresh_successor(&mut redis, &hash).await?.is_none());
+
+    // A tampered entry does not open to another token
+    let last = sealed.len() - 1;
+    let flipped = if sealed.ends_with('0') { "1" } else { "0" };
+    let tampered = format!("{}{flipped}", &sealed[..last]);
+    redis
+        .set_ex::<_, _, ()>(keys::refresh_successor(&rotated), &tampered, 10)
+        .await?;
+    assert!(refresh_successor(&mut redis, &rotated).await?.is_none());
+    redis
+        .set_ex::<_, _, ()>(keys::refresh_successor(&rotated), &sealed, 10)
+        .await?;
+
+    // A consumed successor is not handed out again
+    validate_refresh_token(&mut redis, &successor).await?;
+    assert!(refresh_successor(&mut redis, &rotated).await?.is_none());
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn tokens_stored_before_hashing_keep_working() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let mut redis = create_redis_client(&env.redis_url).await?;
+    let data = session_data("user_1");
+    let session_id = data.session_id.clone().unwrap();
+
+    // A token as stored before hashing: raw key, raw index entry
+    let legacy = "f47ac10b-58cc-4372-a567-0e02b2c3d479";
+    redis
+        .set_ex::<_, _, ()>(
+            keys::legacy_refresh_token(legacy).unwrap(),
+            serde_json::to_string(&data)?,
+            3600,
+        )
+        .await?;
+    redis
+        .hset::<_, _, _, ()>(keys::user_sessions("user_1"), &session_id, legacy)
+        .await?;
+
+    let sessions = list_user_sessions(&mut redis, "user_1").await?;
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0].token_hash, keys::refresh_token_hash(legacy));
+
+    // Rotation consumes the raw entry; its successor is stored hashed
+    let data = validate_refresh_token(&mut redis, legacy).await?;
+    assert!(
+        !redis
+            .exists::<_, bool>(keys::legacy_refresh_token(legacy).unwrap())
+            .await?
+    );
+    assert!(refresh_token_reused(&mut redis, legacy).await?.is_some());
+    let successor = "0b5e6a6c-8f1e-4a4e-9d0a-3f9c6f0e2b11";
+    store_refresh_token(&mut redis, successor, &data, 3600).await?;
+    assert!(
+        redis
+            .exists::<_, bool>(keys::refresh_token(successor))
+            .await?
+    );
+
+    // A raw entry can also be ended from its session
+    let second = session_data("user_2");
+    let second_session = second.session_id.clone().unwrap();
+    let raw = "5d3c2b1a-0000-4000-8000-000000000002";
+    redis
+        .set_ex::<_, _, ()>(
+            keys::legacy_refresh_token(raw).unwrap(),
+            serde_json::to_string(&second)?,
+            3600,
+        )
+        .await?;
+    redis
+        .hset::<_, _, _, ()>(keys::user_sessions("user_2"), &second_session, raw)
+        .await?;
+    assert!(delete_user_session(&mut redis, "user_2", &second_session).await?);
+    assert!(validate_refresh_token(&mut redis, raw).await.is_err());
+    Ok(())
+}
@@ -0,0 +1,142 @@
+// tests/tests/refresh_lifetime.rs
+
+//! Refresh token lifetimes across rotation: sliding and absolute expiry and
+//! the maximum session cap, computed directly and enforced by jwt-service
+//! against real Redis
+
+use anyhow::Result;
+use chrono::Duration;
+use jwt_service::{JwtConfig, RefreshTokenData};
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use tokn_core::{Claims, TestClock};
+use tokn_tests::{http_client, jwt_config, TestEnv};
+
+// ---
+
+const DAY: i64 = 86_400;
+const WEEK: i64 = 7 * DAY;
+
+// ---
+
+fn lifetimes(sliding: bool, max_session_seconds: Option<i64>) -> JwtConfig {
+    // ---
+    let mut jwt = jwt_config("redis://unused").jwt;
+    jwt.refresh_token_sliding = sliding;
+    jwt.max_session_seconds = max_session_seconds;
+    jwt
+}
+
+/// The data of a session's first refresh token, issued at `now`.
+fn first_token(jwt: &JwtConfig, now: i64) -> RefreshTokenData {
+    // ---
+    let clock = TestClock::at_timestamp(now);
+    let claims = Claims::new("user_1".into(), "u@example.com".into(), 900, &clock);
+    let (data, ttl) = RefreshTokenData::from(&claims).renew(jwt, now).unwrap();
+    assert_eq!(data.expires_at, Some(now + ttl));
+    data
+}
+
+// ---
+
+#[test]
+fn sliding_rotation_grants_the_full_lifetime() {
+    // ---
+    let jwt = lifetimes(true, None);
+    let start = 1_700_000_000;
+    let first = first_token(&jwt, start);
+
+    let (next, ttl) = first.renew(&jwt, start + 3 * DAY).unwrap();
+    assert_eq!(ttl, WEEK);
+    assert_eq!(next.session_started_at, Some(start));
+}
+
+#[test]
+fn absolute_rotation_keeps_the_original_expiry() {
+    // ---
+    let jwt = lifetimes(false, None);
+    let start = 1_700_000_000;
+    let first = first_token(&jwt, start);
+
+    let (next, ttl) = first.renew(&jwt, start + 3 * DAY).unwrap();
+    assert_eq!(ttl, 4 * DAY);
+    assert_eq!(next.expires_at, Some(start + WEEK));
+
+    let (_, ttl) = next.renew(&jwt, start + WEEK - 1).unwrap();
+    assert_eq!(ttl, 1);
+}
+
+#[test]
+fn max_session_caps_the_rotation_chain() {
+    // ---
+    let jwt = lifetimes(true, Some(10 * DAY));
+    let start = 1_700_000_000;
+    let first = first_token(&jwt, start);
+
+    let (next, ttl) = first.renew(&jwt, start + 6 * DAY).unwrap();
+    assert_eq!(ttl, 4 * DAY);
+    assert!(next.renew(&jwt, start + 10 * DAY).is_none());
+
+    // The cap also shortens the first token
+    let jwt = lifetimes(true, Some(DAY));
+    assert_eq!(first_token(&jwt, start).expires_at, Some(start + DAY));
+}
+
+#[test]
+fn tokens_stored_without_timestamps_start_a_session_on_rotation() -> Result<()> {
+    // ---
+    let legacy: RefreshTokenData =
+        serde_json::from_value(json!({ "user_id": "user_1", "email": "u@example.com" }))?;
+    let now = chrono::Utc::now().timestamp();
+
+    let (next, ttl) = legacy.renew(&lifetimes(false, Some(DAY)), now).unwrap();
+    assert_eq!(ttl, DAY);
+    assert_eq!(next.session_started_at, Some(now));
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn jwt_service_refuses_refresh_past_the_max_session() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let clock = TestClock::default();
+    let mut config = jwt_config(&env.redis_url);
+    config.jwt.max_session_seconds = Some(DAY);
+    let base = env
+        .spawn_jwt_service_with_config(config, clock.shared())
+        .await?;
+    let http = http_client();
+
+    let tokens: Value = http
+        .post(format!("{base}/v1/auth/token"))
+        .json(&json!({ "user_id": "user_it", "email": "it@example.com" }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    // Rotation works within the session
+    clock.advance(Duration::hours(12));
+    let tokens: Value = http
+        .post(format!("{base}/v1/auth/refresh"))
+        .json(&json!({ "refresh_token": tokens["refresh_token"] }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    // ... and is refused once the session reaches its cap
+    clock.advance(Duration::hours(12));
+    let response = http
+        .post(format!("{base}/v1/auth/refresh"))
+        .json(&json!({ "refresh_token": tokens["refresh_token"] }))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let problem: Value = response.json().await?;
+    assert_eq!(problem["detail"], "Session has expired; sign in again");
+    Ok(())
+}
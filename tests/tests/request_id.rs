@@ -0,0 +1,107 @@
+// tests/tests/request_id.rs
+
+//! Request IDs: the middleware that gives every request an `X-Request-Id`,
+//! hands it to handlers, and returns it with the response
+
+use anyhow::Result;
+use axum::http::StatusCode;
+use axum::middleware;
+use axum::routing::get;
+use axum::Router;
+use serde_json::Value;
+use tokn_core::{Problem, TestClock};
+use tokn_server::{RequestId, REQUEST_ID};
+use tokn_tests::{http_client, jwt_config, serve, spawn_jwt_service_in_memory};
+
+// ---
+
+/// Serve a route echoing the handler's request ID and a failing route,
+/// behind the problem details and request ID middleware.
+async fn spawn() -> Result<String> {
+    // ---
+    let app = Router::new()
+        .route(
+            "/v1/echo",
+            get(|request_id: RequestId| async move { request_id.as_str().to_string() }),
+        )
+        .route(
+            "/v1/fail",
+            get(|| async { Problem::new(StatusCode::NOT_FOUND).detail("User not found") }),
+        )
+        .layer(middleware::from_fn(tokn_server::problem_details))
+        .layer(middleware::from_fn(tokn_server::request_id));
+    serve(app).await
+}
+
+// ---
+
+#[tokio::test]
+async fn callers_request_ids_are_kept() -> Result<()> {
+    // ---
+    let base = spawn().await?;
+    let response = http_client()
+        .get(format!("{base}/v1/echo"))
+        .header(REQUEST_ID, "req-from-oauth2-client")
+        .send()
+        .await?;
+    assert_eq!(response.headers()[REQUEST_ID], "req-from-oauth2-client");
+    assert_eq!(response.text().await?, "req-from-oauth2-client");
+    Ok(())
+}
+
+#[tokio::test]
+async fn requests_without_a_usable_id_get_a_new_one() -> Result<()> {
+    // ---
+    let base = spawn().await?;
+    let http = http_client();
+
+    let response = http.get(format!("{base}/v1/echo")).send().await?;
+    let generated = response.headers()[REQUEST_ID].to_str()?.to_string();
+    uuid::Uuid::parse_str(&generated)?;
+    assert_eq!(response.text().await?, generated);
+
+    // Overlong or blank IDs are not trusted
+    for sent in ["x".repeat(129), String::new()] {
+        let response = http
+            .get(format!("{base}/v1/echo"))
+            .header(REQUEST_ID, sent.as_str())
+            .send()
+            .await?;
+        let replaced = response.headers()[REQUEST_ID].to_str()?.to_string();
+        assert_ne!(replaced, sent);
+        uuid::Uuid::parse_str(&replaced)?;
+        assert_eq!(response.text().await?, replaced);
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn problems_carry_the_request_id() -> Result<()> {
+    // ---
+    let base = spawn().await?;
+    let response = http_client().get(format!("{base}/v1/fail")).send().await?;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let id = response.headers()[REQUEST_ID].to_str()?.to_string();
+    let body: Value = response.json().await?;
+    assert_eq!(body["request_id"], id.as_str());
+    Ok(())
+}
+
+#[tokio::test]
+async fn jwt_service_returns_the_request_id() -> Result<()> {
+    // ---
+    let clock = TestClock::at_timestamp(1_700_000_000);
+    let base = spawn_jwt_service_in_memory(jwt_config("redis://unused"), clock.shared()).await?;
+
+    let response = http_client()
+        .post(format!("{base}/v1/auth/validate"))
+        .header(REQUEST_ID, "req-exchange-1")
+        .json(&serde_json::json!({ "token": "not-a-jwt" }))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(response.headers()[REQUEST_ID], "req-exchange-1");
+    let body: Value = response.json().await?;
+    assert_eq!(body["request_id"], "req-exchange-1");
+    Ok(())
+}
@@ -0,0 +1,112 @@
+// tests/tests/revocation.rs
+
+//! RFC 7009 revocation on jwt-service: form-encoded requests revoke access
+//! and refresh tokens and answer 200 for unknown ones, against real Redis
+
+use anyhow::Result;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use tokn_tests::{http_client, TestEnv};
+
+// ---
+
+/// Issue access and refresh tokens from jwt-service at `base`.
+async fn issue(base: &str) -> Result<(String, String)> {
+    // ---
+    let tokens: Value = http_client()
+        .post(format!("{base}/v1/auth/token"))
+        .json(&json!({ "user_id": "user_it", "email": "it@example.com" }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok((
+        tokens["access_token"].as_str().unwrap().to_string(),
+        tokens["refresh_token"].as_str().unwrap().to_string(),
+    ))
+}
+
+/// Revoke `token` at jwt-service at `base` in RFC 7009 mode.
+async fn revoke(base: &str, token: &str, hint: Option<&str>) -> Result<reqwest::Response> {
+    // ---
+    let mut form = vec![("token", token)];
+    if let Some(hint) = hint {
+        form.push(("token_type_hint", hint));
+    }
+    Ok(http_client()
+        .post(format!("{base}/v1/auth/revoke"))
+        .form(&form)
+        .send()
+        .await?)
+}
+
+// ---
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn form_requests_revoke_access_and_refresh_tokens() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let base = env.spawn_jwt_service().await?;
+    let http = http_client();
+    let (access_token, refresh_token) = issue(&base).await?;
+
+    // A refresh token is found with or without the hint
+    let response = revoke(&base, &refresh_token, None).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.text().await?.is_empty());
+
+    let refresh = http
+        .post(format!("{base}/v1/auth/refresh"))
+        .json(&json!({ "refresh_token": refresh_token }))
+        .send()
+        .await?;
+    assert_eq!(refresh.status(), StatusCode::UNAUTHORIZED);
+
+    // An access token is blacklisted even when the hint is wrong
+    let response = revoke(&base, &access_token, Some("refresh_token")).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let validation = http
+        .post(format!("{base}/v1/auth/validate"))
+        .json(&json!({ "token": access_token }))
+        .send()
+        .await?;
+    assert_eq!(validation.status(), StatusCode::UNAUTHORIZED);
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn unknown_tokens_are_not_errors_in_rfc7009_mode() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let base = env.spawn_jwt_service().await?;
+    let (_, refresh_token) = issue(&base).await?;
+
+    for hint in [
+        None,
+        Some("access_token"),
+        Some("refresh_token"),
+        Some("id_token"),
+    ] {
+        let response = revoke(&base, "not-a-token", hint).await?;
+        assert_eq!(response.status(), StatusCode::OK, "{hint:?}");
+    }
+
+    // Revoking twice is fine too
+    for _ in 0..2 {
+        let response = revoke(&base, &refresh_token, Some("refresh_token")).await?;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    // JSON requests keep refusing tokens that are not valid access tokens
+    let response = http_client()
+        .post(format!("{base}/v1/auth/revoke"))
+        .json(&json!({ "token": "not-a-token" }))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    Ok(())
+}
@@ -0,0 +1,135 @@
+// tests/tests/revoked_before.rs
+
+//! Per-user revocation cutoffs: `PUT /admin/users/{user_id}/revoked-before`
+//! revokes every access and refresh token issued to the user before the
+//! cutoff, behind the admin token, while later sign-ins work (in-memory
+//! store)
+
+use anyhow::Result;
+use chrono::Duration;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use tokn_core::TestClock;
+use tokn_tests::{
+    http_client, jwt_config, post_json, sign_in, spawn_jwt_service_in_memory, TEST_ADMIN_TOKEN,
+};
+
+// ---
+
+const NOW: i64 = 1_700_000_000;
+
+// ---
+
+/// [`jwt_config`] with the admin API.
+fn config() -> jwt_service::Config {
+    // ---
+    let mut config = jwt_config("redis://unused");
+    config.admin.token = Some(TEST_ADMIN_TOKEN.into());
+    config
+}
+
+/// Set the revocation cutoff of `user_1` with `body`, as the admin.
+async fn set_cutoff(base: &str, body: Value) -> Result<reqwest::Response> {
+    // ---
+    Ok(http_client()
+        .put(format!("{base}/admin/users/user_1/revoked-before"))
+        .bearer_auth(TEST_ADMIN_TOKEN)
+        .json(&body)
+        .send()
+        .await?)
+}
+
+// ---
+
+#[tokio::test]
+async fn tokens_issued_before_the_cutoff_are_revoked() -> Result<()> {
+    // ---
+    let clock = TestClock::at_timestamp(NOW);
+    let base = spawn_jwt_service_in_memory(config(), clock.shared()).await?;
+    let old = sign_in(&base, "user_1").await?;
+
+    clock.advance(Duration::seconds(10));
+    let response = set_cutoff(&base, json!({})).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let set: Value = response.json().await?;
+    assert_eq!(set["revoked_before"], NOW + 10, "{set}");
+
+    let (status, _) = post_json(
+        &base,
+        "/v1/auth/validate",
+        json!({ "token": old["access_token"] }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, body) = post_json(
+        &base,
+        "/v1/auth/refresh",
+        json!({ "refresh_token": old["refresh_token"] }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["detail"], "Refresh token has been revoked", "{body}");
+
+    // Signing in again works
+    clock.advance(Duration::seconds(1));
+    let new = sign_in(&base, "user_1").await?;
+    let (status, _) = post_json(
+        &base,
+        "/v1/auth/validate",
+        json!({ "token": new["access_token"] }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = post_json(
+        &base,
+        "/v1/auth/refresh",
+        json!({ "refresh_token": new["refresh_token"] }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK);
+
+    let cutoff: Value = http_client()
+        .get(format!("{base}/admin/users/user_1/revoked-before"))
+        .bearer_auth(TEST_ADMIN_TOKEN)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    assert_eq!(
+        cutoff,
+        json!({ "user_id": "user_1", "revoked_before": NOW + 10 })
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn cutoffs_need_the_admin_token_and_cannot_be_in_the_future() -> Result<()> {
+    // ---
+    let clock = TestClock::at_timestamp(NOW);
+    let base = spawn_jwt_service_in_memory(config(), clock.shared()).await?;
+
+    let response = http_client()
+        .put(format!("{base}/admin/users/user_1/revoked-before"))
+        .json(&json!({}))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = set_cutoff(&base, json!({ "revoked_before": NOW + 60 })).await?;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // Other users are untouched
+    let other = sign_in(&base, "user_2").await?;
+    clock.advance(Duration::seconds(5));
+    let response = set_cutoff(&base, json!({})).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let (status, _) = post_json(
+        &base,
+        "/v1/auth/validate",
+        json!({ "token": other["access_token"] }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK);
+    Ok(())
+}
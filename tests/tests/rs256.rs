@@ -0,0 +1,150 @@
+// tests/tests/rs256.rs
+
+//! RS256 access tokens: signing with a key pair, verifying with the public key
+//! alone, and refusing the other algorithm (no containers needed)
+
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use tokn_core::{
+    generate_token, Claims, JwtKeys, SigningAlgorithm, SystemClock, TestClock, TokenError,
+};
+use tokn_tests::{http_client, jwt_config, serve, TEST_JWT_SECRET};
+
+// ---
+
+const PRIVATE_PEM: &[u8] = include_bytes!("../fixtures/rsa_private.pem");
+const PUBLIC_PEM: &[u8] = include_bytes!("../fixtures/rsa_public.pem");
+const OTHER_PUBLIC_PEM: &[u8] = include_bytes!("../fixtures/rsa_other_public.pem");
+
+// ---
+
+fn fixture(name: &str) -> PathBuf {
+    // ---
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("fixtures")
+        .join(name)
+}
+
+fn claims() -> Claims {
+    // ---
+    Claims::new("user_1".into(), "u@example.com".into(), 900, &SystemClock)
+}
+
+// ---
+
+#[test]
+fn public_key_alone_verifies_but_cannot_sign() -> Result<()> {
+    // ---
+    let keys = JwtKeys::rs256(PRIVATE_PEM, PUBLIC_PEM)?;
+    assert_eq!(keys.algorithm(), SigningAlgorithm::Rs256);
+    let token = keys.sign(&claims())?;
+
+    let verifier = JwtKeys::rs256_public(PUBLIC_PEM)?;
+    assert_eq!(verifier.verify(&token, &SystemClock)?.sub, "user_1");
+    assert!(!verifier.can_sign());
+    assert!(matches!(
+        verifier.sign(&claims()),
+        Err(TokenError::InvalidKey(_))
+    ));
+
+    let past = TestClock::at_timestamp(1_700_000_000);
+    let expired = keys.sign(&Claims::new(
+        "user_1".into(),
+        "u@example.com".into(),
+        900,
+        &past,
+    ))?;
+    assert!(matches!(
+        verifier.verify(&expired, &SystemClock),
+        Err(TokenError::Expired)
+    ));
+    Ok(())
+}
+
+#[test]
+fn mismatched_or_unparseable_keys_are_refused() {
+    // ---
+    assert!(matches!(
+        JwtKeys::rs256(PRIVATE_PEM, OTHER_PUBLIC_PEM),
+        Err(TokenError::InvalidKey(reason)) if reason.contains("does not match")
+    ));
+    assert!(matches!(
+        JwtKeys::rs256(b"not a key", PUBLIC_PEM),
+        Err(TokenError::InvalidKey(_))
+    ));
+    assert!(matches!(
+        JwtKeys::rs256_public(PRIVATE_PEM),
+        Err(TokenError::InvalidKey(_))
+    ));
+}
+
+#[test]
+fn each_algorithm_refuses_the_other() -> Result<()> {
+    // ---
+    let rs256 = JwtKeys::rs256(PRIVATE_PEM, PUBLIC_PEM)?;
+    let hs256 = JwtKeys::hs256(TEST_JWT_SECRET);
+
+    let hs_token = generate_token(&claims(), TEST_JWT_SECRET)?;
+    assert!(matches!(
+        rs256.verify(&hs_token, &SystemClock),
+        Err(TokenError::InvalidAlgorithm)
+    ));
+
+    let rs_token = rs256.sign(&claims())?;
+    assert!(matches!(
+        hs256.verify(&rs_token, &SystemClock),
+        Err(TokenError::InvalidAlgorithm)
+    ));
+    Ok(())
+}
+
+#[tokio::test]
+async fn jwt_service_issues_rs256_tokens() -> Result<()> {
+    // ---
+    let mut config = jwt_config("redis://unused");
+    config.jwt.stateless = true;
+    config.jwt.algorithm = SigningAlgorithm::Rs256;
+    config.jwt.secret = None;
+    config.jwt.private_key_path = Some(fixture("rsa_private.pem"));
+    config.jwt.public_key_path = Some(fixture("rsa_public.pem"));
+
+    let state = jwt_service::AppState::stateless(config, SystemClock::shared())?;
+    let base = serve(jwt_service::build_router(state)).await?;
+    let http = http_client();
+
+    let tokens: Value = http
+        .post(format!("{base}/v1/auth/token"))
+        .json(&json!({ "user_id": "user_1", "email": "u@example.com" }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let token = tokens["access_token"].as_str().unwrap();
+
+    // A resource server with only the public key accepts it
+    let verifier = JwtKeys::rs256_public(PUBLIC_PEM)?;
+    assert_eq!(verifier.verify(token, &SystemClock)?.sub, "user_1");
+
+    let validate: Value = http
+        .post(format!("{base}/v1/auth/validate"))
+        .json(&json!({ "token": token }))
+        .send()
+        .await?
+        .json()
+        .await?;
+    assert_eq!(validate["valid"], true, "{validate}");
+
+    // An HS256 token is refused, even one signed with a known secret
+    let hs_token = generate_token(&claims(), TEST_JWT_SECRET)?;
+    let validate: Value = http
+        .post(format!("{base}/v1/auth/validate"))
+        .json(&json!({ "token": hs_token }))
+        .send()
+        .await?
+        .json()
+        .await?;
+    assert_eq!(validate["valid"], false, "{validate}");
+    Ok(())
+}
@@ -0,0 +1,415 @@
+// tests/tests/scheduler.rs
+
+//! Schedule parsing, overlap prevention, leader election for singleton jobs,
+//! and oauth2-server's expired token cleanup
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use oauth2_server::PgLeaderElection;
+use serde_json::json;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Arc;
+use tokio::sync::Notify;
+use tokn_core::{Clock, SystemClock, TestClock};
+use tokn_scheduler::{
+    Election, Job, JobResult, RunOutcome, Schedule, Scheduler, SchedulerConfig, SchedulerError,
+};
+use tokn_tests::{http_client, query_param, TestEnv, DEMO_CLIENT_ID, DEMO_REDIRECT_URI};
+
+// ---
+
+/// Leads while its flag is set.
+#[derive(Clone, Default)]
+struct FlagElection {
+    // ---
+    leader: Arc<AtomicBool>,
+}
+
+impl Election for FlagElection {
+    // ---
+    async fn is_leader(&self) -> bool {
+        // ---
+        self.leader.load(Ordering::SeqCst)
+    }
+}
+
+/// A job that counts its runs in `runs`.
+fn counting(runs: &Arc<AtomicU32>) -> impl Job {
+    // ---
+    let runs = runs.clone();
+    move || {
+        let runs = runs.clone();
+        async move {
+            runs.fetch_add(1, Ordering::SeqCst);
+            JobResult::Ok(())
+        }
+    }
+}
+
+fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+    // ---
+    Utc.with_ymd_and_hms(year, month, day, hour, minute, 0)
+        .unwrap()
+}
+
+fn next(expression: &str, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    // ---
+    expression.parse::<Schedule>().unwrap().next_after(after)
+}
+
+fn buggy() -> JobResult {
+    // ---
+    panic!("job bug")
+}
+
+fn scheduler() -> Scheduler {
+    // ---
+    Scheduler::new(&SchedulerConfig::default(), SystemClock::shared())
+}
+
+// ---
+
+#[test]
+fn cron_fields_steps_ranges_and_names() {
+    // ---
+    // 2026-10-16 is a Friday
+    let now = at(2026, 10, 16, 10, 7);
+
+    assert_eq!(next("*/15 * * * *", now), Some(at(2026, 10, 16, 10, 15)));
+    assert_eq!(next("0 3 * * *", now), Some(at(2026, 10, 17, 3, 0)));
+    assert_eq!(next("30 9-17/4 * * *", now), Some(at(2026, 10, 16, 13, 30)));
+    assert_eq!(next("0 0 1,15 * *", now), Some(at(2026, 11, 1, 0, 0)));
+    assert_eq!(next("0 12 * jan *", now), Some(at(2027, 1, 1, 12, 0)));
+    assert_eq!(next("0 8 * * mon-fri", now), Some(at(2026, 10, 19, 8, 0)));
+    // Sunday is 0 or 7
+    assert_eq!(next("0 0 * * 7", now), next("0 0 * * sun", now));
+}
+
+#[test]
+fn next_run_is_strictly_after() {
+    // ---
+    let on_the_hour = at(2026, 10, 16, 10, 0);
+    assert_eq!(
+        next("0 * * * *", on_the_hour),
+        Some(at(2026, 10, 16, 11, 0))
+    );
+
+    let mid_minute = on_the_hour + Duration::seconds(30);
+    assert_eq!(next("* * * * *", mid_minute), Some(at(2026, 10, 16, 10, 1)));
+}
+
+#[test]
+fn either_day_field_matches_when_both_are_restricted() {
+    // ---
+    // The 20th or any Monday, whichever comes first
+    let now = at(2026, 10, 16, 10, 7);
+    assert_eq!(next("0 0 20 * mon", now), Some(at(2026, 10, 19, 0, 0)));
+    assert_eq!(
+        next("0 0 20 * mon", at(2026, 10, 19, 0, 0)),
+        Some(at(2026, 10, 20, 0, 0))
+    );
+}
+
+#[test]
+fn shorthands_intervals_and_off() {
+    // ---
+    let now = at(2026, 10, 16, 10, 7);
+
+    assert_eq!(next("@hourly", now), Some(at(2026, 10, 16, 11, 0)));
+    assert_eq!(next("@daily", now), Some(at(2026, 10, 17, 0, 0)));
+    assert_eq!(next("@weekly", now), Some(at(2026, 10, 18, 0, 0)));
+    assert_eq!(next("@monthly", now), Some(at(2026, 11, 1, 0, 0)));
+    assert_eq!(next("@yearly", now), Some(at(2027, 1, 1, 0, 0)));
+    assert_eq!(next("@every 90s", now), Some(now + Duration::seconds(90)));
+    assert_eq!(next("@every 2h", now), Some(now + Duration::hours(2)));
+
+    let off: Schedule = "off".parse().unwrap();
+    assert!(off.is_off());
+    assert_eq!(off.next_after(now), None);
+    // Never matches: there is no 31 February
+    assert_eq!(next("0 0 31 2 *", now), None);
+}
+
+#[test]
+fn invalid_expressions_are_rejected() {
+    // ---
+    for expression in [
+        "* * * *",
+        "60 * * * *",
+        "* 24 * * *",
+        "* * 0 * *",
+        "* * * 13 *",
+        "* * * * 8",
+        "*/0 * * * *",
+        "5-1 * * * *",
+        "* * * smarch *",
+        "@fortnightly",
+        "@every",
+        "@every 0m",
+        "@every 10w",
+    ] {
+        let error = expression.parse::<Schedule>().unwrap_err();
+        assert!(
+            matches!(error, SchedulerError::InvalidSchedule { .. }),
+            "{expression}: {error}"
+        );
+    }
+}
+
+#[test]
+fn config_overrides_default_schedule() -> Result<()> {
+    // ---
+    let config: SchedulerConfig = serde_json::from_value(json!({
+        "jobs": { "cleanup": "@every 5m" }
+    }))?;
+    assert!(config.enabled);
+
+    let scheduler = Scheduler::new(&config, SystemClock::shared())
+        .job("cleanup", "@daily".parse()?, || async { JobResult::Ok(()) })
+        .job("report", "@daily".parse()?, || async { JobResult::Ok(()) });
+    let schedules: Vec<(String, String)> = scheduler
+        .jobs()
+        .map(|(name, schedule)| (name.to_string(), schedule.to_string()))
+        .collect();
+    assert_eq!(
+        schedules,
+        [
+            ("cleanup".to_string(), "@every 5m".to_string()),
+            ("report".to_string(), "@daily".to_string()),
+        ]
+    );
+
+    let invalid = serde_json::from_value::<SchedulerConfig>(json!({
+        "jobs": { "cleanup": "every five minutes" }
+    }));
+    assert!(invalid.is_err());
+    Ok(())
+}
+
+// ---
+
+#[tokio::test]
+async fn run_now_skips_while_previous_run_is_in_progress() -> Result<()> {
+    // ---
+    let (started, release) = (Arc::new(Notify::new()), Arc::new(Notify::new()));
+    let runs = Arc::new(AtomicU32::new(0));
+    let job = {
+        let (started, release, runs) = (started.clone(), release.clone(), runs.clone());
+        move || {
+            let (started, release, runs) = (started.clone(), release.clone(), runs.clone());
+            async move {
+                runs.fetch_add(1, Ordering::SeqCst);
+                started.notify_one();
+                release.notified().await;
+                JobResult::Ok(())
+            }
+        }
+    };
+    let scheduler = scheduler().job("slow", Schedule::off(), job);
+
+    let first = tokio::spawn({
+        let scheduler = scheduler.clone();
+        async move { scheduler.run_now("slow").await }
+    });
+    started.notified().await;
+
+    assert_eq!(scheduler.run_now("slow").await?, RunOutcome::Skipped);
+    release.notify_one();
+    assert_eq!(first.await??, RunOutcome::Completed);
+
+    // Free to run again once the first run has finished
+    release.notify_one();
+    assert_eq!(scheduler.run_now("slow").await?, RunOutcome::Completed);
+    assert_eq!(runs.load(Ordering::SeqCst), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn failures_and_panics_are_reported() -> Result<()> {
+    // ---
+    let scheduler = scheduler()
+        .job("failing", Schedule::off(), || async {
+            JobResult::Err("database unavailable".into())
+        })
+        .job("panicking", Schedule::off(), || async { buggy() });
+
+    let error = scheduler.run_now("failing").await.unwrap_err();
+    assert!(
+        matches!(&error, SchedulerError::Failed { job, message }
+            if job == "failing" && message == "database unavailable"),
+        "{error}"
+    );
+
+    let error = scheduler.run_now("panicking").await.unwrap_err();
+    assert!(matches!(error, SchedulerError::Panicked(ref job) if job == "panicking"));
+    // A panicked run does not leave the job marked as running
+    assert!(scheduler
+        .run_now("panicking")
+        .await
+        .is_err_and(|e| matches!(e, SchedulerError::Panicked(_))));
+
+    assert!(matches!(
+        scheduler.run_now("missing").await,
+        Err(SchedulerError::UnknownJob(_))
+    ));
+    Ok(())
+}
+
+#[tokio::test]
+async fn status_reports_each_jobs_last_run() -> Result<()> {
+    // ---
+    let clock = TestClock::at_timestamp(1_800_000_000);
+    let fail = Arc::new(AtomicBool::new(true));
+    let flaky = {
+        let fail = fail.clone();
+        move || {
+            let fail = fail.load(Ordering::SeqCst);
+            async move {
+                match fail {
+                    true => JobResult::Err("database unavailable".into()),
+                    false => JobResult::Ok(()),
+                }
+            }
+        }
+    };
+    let scheduler = Scheduler::new(&SchedulerConfig::default(), clock.shared())
+        .singleton_job("flaky", "@every 10m".parse()?, flaky)
+        .job("idle", Schedule::off(), || async { JobResult::Ok(()) });
+
+    let status = scheduler.status();
+    assert_eq!(status.len(), 2);
+    assert_eq!(status[0].name, "flaky");
+    assert_eq!(status[0].schedule, "@every 10m");
+    assert!(status[0].singleton && !status[0].running);
+    assert_eq!(status[0].last_run, None);
+    assert_eq!(
+        status[0].next_run,
+        Some(clock.now() + Duration::minutes(10))
+    );
+    assert_eq!(status[1].next_run, None, "an off job is never due");
+
+    let _ = scheduler.run_now("flaky").await;
+    let failed = &scheduler.status()[0];
+    assert_eq!(failed.last_run, Some(clock.now()));
+    assert_eq!(failed.last_result, Some("failure"));
+    assert_eq!(failed.last_success, None);
+    assert!(failed
+        .last_error
+        .as_deref()
+        .is_some_and(|e| e.contains("database unavailable")));
+
+    clock.advance(Duration::minutes(1));
+    fail.store(false, Ordering::SeqCst);
+    scheduler.run_now("flaky").await?;
+    let recovered = &scheduler.status()[0];
+    assert_eq!(recovered.last_result, Some("success"));
+    assert_eq!(recovered.last_success, Some(clock.now()));
+    assert_eq!(recovered.last_error, None);
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn singleton_jobs_run_only_on_the_leader() -> Result<()> {
+    // ---
+    let election = FlagElection::default();
+    let (everywhere, singleton) = (Arc::new(AtomicU32::new(0)), Arc::new(AtomicU32::new(0)));
+    // Only Tokio's paused timer moves; the frozen clock just anchors schedules
+    let scheduler = Scheduler::new(&SchedulerConfig::default(), TestClock::default().shared())
+        .with_election(election.clone())
+        .job("everywhere", "@every 10s".parse()?, counting(&everywhere))
+        .singleton_job("singleton", "@every 10s".parse()?, counting(&singleton));
+    scheduler.start();
+
+    tokio::time::sleep(std::time::Duration::from_secs(25)).await;
+    assert_eq!(everywhere.load(Ordering::SeqCst), 2);
+    assert_eq!(singleton.load(Ordering::SeqCst), 0);
+
+    election.leader.store(true, Ordering::SeqCst);
+    tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+    assert_eq!(everywhere.load(Ordering::SeqCst), 3);
+    assert_eq!(singleton.load(Ordering::SeqCst), 1);
+
+    // A follower can still run a singleton by hand
+    election.leader.store(false, Ordering::SeqCst);
+    assert_eq!(scheduler.run_now("singleton").await?, RunOutcome::Completed);
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn disabled_scheduler_runs_nothing() -> Result<()> {
+    // ---
+    let config = SchedulerConfig {
+        enabled: false,
+        ..SchedulerConfig::default()
+    };
+    let runs = Arc::new(AtomicU32::new(0));
+    Scheduler::new(&config, TestClock::default().shared())
+        .job("tick", "@every 1s".parse()?, counting(&runs))
+        .start();
+
+    tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+    assert_eq!(runs.load(Ordering::SeqCst), 0);
+    Ok(())
+}
+
+// ---
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn one_replica_leads_until_it_goes_away() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let first = PgLeaderElection::new(env.pool.clone());
+    let second = PgLeaderElection::new(env.pool.clone());
+
+    assert!(first.is_leader().await);
+    assert!(!second.is_leader().await);
+    // Leadership is sticky
+    assert!(first.is_leader().await);
+
+    // The lock goes with the leader's connection, once Postgres notices
+    drop(first);
+    let mut took_over = false;
+    for _ in 0..50 {
+        if second.is_leader().await {
+            took_over = true;
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    assert!(took_over);
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn cleanup_deletes_only_expired_rows() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let clock = TestClock::default();
+    let base = env.spawn_oauth2_server_with_clock(clock.shared()).await?;
+    let http = http_client();
+
+    let response = http
+        .post(format!("{base}/v1/oauth/authorize"))
+        .form(&[
+            ("client_id", DEMO_CLIENT_ID),
+            ("redirect_uri", DEMO_REDIRECT_URI),
+            ("scope", "profile"),
+            ("state", "xyz"),
+            ("action", "approve"),
+        ])
+        .send()
+        .await?;
+    let location = reqwest::Url::parse(response.headers()["location"].to_str()?)?;
+    assert!(query_param(&location, "code").is_some());
+
+    // Still valid: nothing to delete
+    let purged = oauth2_server::purge_expired(&env.pool, clock.now()).await?;
+    assert_eq!(purged, oauth2_server::Purged::default());
+
+    clock.advance(Duration::minutes(6));
+    let purged = oauth2_server::purge_expired(&env.pool, clock.now()).await?;
+    assert_eq!(purged.authorization_codes, 1);
+    Ok(())
+}
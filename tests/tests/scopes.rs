@@ -0,0 +1,166 @@
+// tests/tests/scopes.rs
+
+//! Role and scope claims in issued tokens, and `require_scope` refusing tokens
+//! without the scope, on a stateless jwt-service (no containers needed)
+
+use anyhow::Result;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use tokn_core::{validate_token, Claims, SystemClock};
+use tokn_tests::{http_client, jwt_config, serve, TEST_JWT_SECRET};
+
+// ---
+
+async fn spawn_stateless() -> Result<String> {
+    // ---
+    let mut config = jwt_config("redis://unused");
+    config.jwt.stateless = true;
+
+    let state = jwt_service::AppState::stateless(config, SystemClock::shared())?;
+    serve(jwt_service::build_router(state)).await
+}
+
+/// Issue an access token from jwt-service at `base` for `request`.
+async fn issue(base: &str, request: Value) -> Result<String> {
+    // ---
+    let tokens: Value = http_client()
+        .post(format!("{base}/v1/auth/token"))
+        .json(&request)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(tokens["access_token"].as_str().unwrap().to_string())
+}
+
+// ---
+
+#[tokio::test]
+async fn roles_and_scope_are_carried_in_the_token() -> Result<()> {
+    // ---
+    let base = spawn_stateless().await?;
+    let token = issue(
+        &base,
+        json!({
+            "user_id": "user_1",
+            "email": "u@example.com",
+            "roles": ["admin", "billing"],
+            "scope": "orders:read orders:write",
+        }),
+    )
+    .await?;
+
+    let claims = validate_token(&token, TEST_JWT_SECRET, &SystemClock)?;
+    assert_eq!(claims.roles, ["admin", "billing"]);
+    assert!(claims.has_role("billing"));
+    assert_eq!(
+        claims.scopes().collect::<Vec<_>>(),
+        ["orders:read", "orders:write"]
+    );
+
+    let profile: Value = http_client()
+        .get(format!("{base}/v1/protected"))
+        .bearer_auth(&token)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    assert_eq!(profile["roles"], json!(["admin", "billing"]));
+    assert_eq!(profile["scope"], "orders:read orders:write");
+
+    // Tokens without roles or scope leave the claims out of the payload
+    let token = issue(
+        &base,
+        json!({ "user_id": "user_1", "email": "u@example.com" }),
+    )
+    .await?;
+    let validation: Value = http_client()
+        .post(format!("{base}/v1/auth/validate"))
+        .json(&json!({ "token": token }))
+        .send()
+        .await?
+        .json()
+        .await?;
+    assert!(validation["claims"].get("roles").is_none(), "{validation}");
+    assert!(validation["claims"].get("scope").is_none(), "{validation}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn require_scope_refuses_tokens_without_the_scope() -> Result<()> {
+    // ---
+    let base = spawn_stateless().await?;
+    let admin = issue(
+        &base,
+        json!({
+            "user_id": "user_1",
+            "email": "u@example.com",
+            "scope": "orders:read admin",
+        }),
+    )
+    .await?;
+    let reader = issue(
+        &base,
+        json!({
+            "user_id": "user_1",
+            "email": "u@example.com",
+            "scope": "orders:read administrator",
+        }),
+    )
+    .await?;
+    let unscoped = issue(
+        &base,
+        json!({ "user_id": "user_1", "email": "u@example.com" }),
+    )
+    .await?;
+
+    let response = http_client()
+        .get(format!("{base}/v1/protected/admin"))
+        .bearer_auth(&admin)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Scopes match whole entries, not prefixes
+    for token in [&reader, &unscoped] {
+        let response = http_client()
+            .get(format!("{base}/v1/protected/admin"))
+            .bearer_auth(token)
+            .send()
+            .await?;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert!(response.headers()["www-authenticate"]
+            .to_str()?
+            .contains(r#"error="insufficient_scope""#));
+
+        let problem: Value = response.json().await?;
+        assert_eq!(problem["required_scope"], "admin", "{problem}");
+    }
+
+    // The JWT middleware still runs first, and unscoped routes are unchanged
+    let response = http_client()
+        .get(format!("{base}/v1/protected/admin"))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = http_client()
+        .get(format!("{base}/v1/protected"))
+        .bearer_auth(&unscoped)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    Ok(())
+}
+
+#[test]
+fn blank_scope_grants_nothing() {
+    // ---
+    let claims = Claims::new("user_1".into(), "u@example.com".into(), 900, &SystemClock)
+        .with_access(Vec::new(), Some("  ".into()));
+    assert_eq!(claims.scope, None);
+    assert!(!claims.has_scope(""));
+    assert_eq!(claims.scopes().count(), 0);
+}
@@ -0,0 +1,101 @@
+// tests/tests/secret_files.rs
+
+//! `_FILE` variables: declared settings read from mounted secret files, and
+//! the conflicts and unreadable files the loader refuses (no containers
+//! needed)
+
+use serde::Deserialize;
+use std::path::PathBuf;
+use tokn_config::{ConfigError, ConfigLoader, Secret};
+
+// ---
+
+#[derive(Debug, Deserialize)]
+struct Config {
+    // ---
+    secret: Secret,
+    port: u16,
+}
+
+/// Write `contents` to a fresh secret file for one test.
+fn secret_file(name: &str, contents: &str) -> PathBuf {
+    // ---
+    let path = std::env::temp_dir().join(format!("tokn-secret-{name}-{}", std::process::id()));
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+/// Load with `secret` and `port` declared under variables unique to the
+/// test, since tests in this file share the process environment.
+fn load(name: &str) -> Result<Config, ConfigError> {
+    // ---
+    let (secret_env, port_env) = env_names(name);
+    ConfigLoader::new("test")
+        .required::<String>("secret", secret_env)
+        .optional("port", port_env, 8080u16)
+        .load()
+}
+
+fn env_names(name: &str) -> (&'static str, &'static str) {
+    // ---
+    let secret = format!("TOKN_TEST_{name}_SECRET");
+    let port = format!("TOKN_TEST_{name}_PORT");
+    (secret.leak(), port.leak())
+}
+
+// ---
+
+#[test]
+fn file_variants_supply_values() {
+    // ---
+    let (secret_env, port_env) = env_names("READ");
+    let secret = secret_file("read", "Jx4q9Lr2vTz7Wm1Kp8Ns3Hd6Bf0Gc5Ye\n");
+    let port = secret_file("read-port", "9443\n");
+    std::env::set_var(format!("{secret_env}_FILE"), &secret);
+    std::env::set_var(format!("{port_env}_FILE"), &port);
+
+    let config = load("READ").unwrap();
+    assert_eq!(config.secret.expose(), "Jx4q9Lr2vTz7Wm1Kp8Ns3Hd6Bf0Gc5Ye");
+    assert_eq!(config.port, 9443);
+
+    std::fs::remove_file(&secret).ok();
+    std::fs::remove_file(&port).ok();
+}
+
+#[test]
+fn setting_both_variants_is_refused() {
+    // ---
+    let (secret_env, _) = env_names("BOTH");
+    let secret = secret_file("both", "from-the-file");
+    std::env::set_var(secret_env, "from-the-environment");
+    std::env::set_var(format!("{secret_env}_FILE"), &secret);
+
+    let err = load("BOTH").unwrap_err().to_string();
+    assert!(
+        err.contains(
+            "secret (env TOKN_TEST_BOTH_SECRET_FILE): conflicts with TOKN_TEST_BOTH_SECRET"
+        ),
+        "{err}"
+    );
+
+    std::fs::remove_file(&secret).ok();
+}
+
+#[test]
+fn unreadable_file_is_reported_once() {
+    // ---
+    let (secret_env, _) = env_names("MISSING");
+    std::env::set_var(format!("{secret_env}_FILE"), "/nonexistent/tokn-secret");
+
+    let err = load("MISSING").unwrap_err();
+    let ConfigError::Invalid { problems, .. } = &err else {
+        panic!("unexpected error: {err}");
+    };
+    assert_eq!(problems.len(), 1, "{err}");
+    assert!(
+        err.to_string().contains(
+            "secret (env TOKN_TEST_MISSING_SECRET_FILE): cannot read /nonexistent/tokn-secret"
+        ),
+        "{err}"
+    );
+}
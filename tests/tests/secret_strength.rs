@@ -0,0 +1,168 @@
+// tests/tests/secret_strength.rs
+
+//! Weak secrets are flagged and refused under the prod profile unless
+//! explicitly allowed (`JWT_ALLOW_WEAK_SECRET`), and loaded secrets stay out
+//! of `Debug` output (no containers needed)
+
+use serde::Deserialize;
+use std::path::PathBuf;
+use tokn_config::{secret_weakness, ConfigError, ConfigLoader, Profile, Secret};
+
+// ---
+
+#[derive(Debug, Deserialize)]
+struct Config {
+    // ---
+    #[serde(default)]
+    profile: Profile,
+    secret: Secret,
+}
+
+/// Write `contents` to a fresh TOML file for one test.
+fn config_file(name: &str, contents: &str) -> PathBuf {
+    // ---
+    let path = std::env::temp_dir().join(format!("tokn-{name}-{}.toml", std::process::id()));
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+fn load(path: &PathBuf) -> Result<Config, ConfigError> {
+    // ---
+    ConfigLoader::new("test")
+        .file(path)
+        .required::<String>("secret", "TOKN_TEST_SECRET")
+        .secret("secret")
+        .load()
+}
+
+/// [`load`], with weak secrets allowed by `allow_weak_secret` as jwt-service
+/// declares `JWT_ALLOW_WEAK_SECRET`, and a length rule beside.
+fn load_allowing_weak(path: &PathBuf) -> Result<Config, ConfigError> {
+    // ---
+    ConfigLoader::new("test")
+        .file(path)
+        .required::<String>("secret", "TOKN_TEST_SECRET")
+        .optional("allow_weak_secret", "TOKN_TEST_ALLOW_WEAK_SECRET", false)
+        .rule("secret", |secret: &String| {
+            if secret.len() < 32 {
+                return Err("must be at least 32 characters".into());
+            }
+            Ok(())
+        })
+        .secret_unless("secret", "allow_weak_secret")
+        .load()
+}
+
+// ---
+
+#[test]
+fn obviously_weak_secrets_are_flagged() {
+    // ---
+    for weak in [
+        "a".repeat(40),
+        "abab".repeat(10),
+        "0123456789abcdefghijklmnopqrstuvwxyz".to_string(),
+        "your-secret-key-must-be-at-least-32-characters-long-for-security".to_string(),
+        "correcthorsebatterystaplechangemeplease".to_string(),
+        "abcdefghij".repeat(4),
+        "Password1Password1Password1Password1".to_string(),
+    ] {
+        assert!(secret_weakness(&weak).is_some(), "{weak} passed");
+    }
+}
+
+#[test]
+fn random_secrets_pass() {
+    // ---
+    for strong in [
+        "Jx4q9Lr2vTz7Wm1Kp8Ns3Hd6Bf0Gc5Ye",
+        "3f9a1c7e52b8d046e1a9c3f7b2d5e8a04c6f1b9d7e3a5c2f",
+        "q2/Vn8+Lw5kZ0rT7yH4mX1sB9dF6gJ3pC0eA2uN8vK5=",
+    ] {
+        assert_eq!(secret_weakness(strong), None, "{strong} rejected");
+    }
+}
+
+#[test]
+fn prod_profile_refuses_weak_secret() {
+    // ---
+    let path = config_file(
+        "weak-prod",
+        "profile = \"prod\"\nsecret = \"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa\"\n",
+    );
+    let err = load(&path).unwrap_err().to_string();
+    std::fs::remove_file(&path).ok();
+
+    assert!(
+        err.contains("secret (env TOKN_TEST_SECRET): is a weak secret"),
+        "{err}"
+    );
+}
+
+#[test]
+fn dev_profile_accepts_weak_secret() {
+    // ---
+    let path = config_file(
+        "weak-dev",
+        "secret = \"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa\"\n",
+    );
+    let config = load(&path);
+    std::fs::remove_file(&path).ok();
+
+    let config = config.unwrap();
+    assert_eq!(config.profile, Profile::Dev);
+    assert_eq!(config.secret.expose().len(), 36);
+}
+
+#[test]
+fn prod_profile_accepts_strong_secret() {
+    // ---
+    let path = config_file(
+        "strong-prod",
+        "profile = \"production\"\nsecret = \"Jx4q9Lr2vTz7Wm1Kp8Ns3Hd6Bf0Gc5Ye\"\n",
+    );
+    let config = load(&path);
+    std::fs::remove_file(&path).ok();
+
+    assert_eq!(config.unwrap().profile, Profile::Prod);
+}
+
+#[test]
+fn allowed_weak_secrets_load_under_any_profile_but_keep_their_rules() {
+    // ---
+    let weak = "secret = \"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa\"\n";
+    let path = config_file("allowed-weak-prod", &format!("profile = \"prod\"\n{weak}"));
+    let refused = load_allowing_weak(&path).unwrap_err().to_string();
+    std::fs::write(
+        &path,
+        format!("profile = \"prod\"\nallow_weak_secret = true\n{weak}"),
+    )
+    .unwrap();
+    let allowed = load_allowing_weak(&path);
+    std::fs::write(
+        &path,
+        "profile = \"prod\"\nallow_weak_secret = true\nsecret = \"aaaa\"\n",
+    )
+    .unwrap();
+    let short = load_allowing_weak(&path).unwrap_err().to_string();
+    std::fs::remove_file(&path).ok();
+
+    assert!(refused.contains("is a weak secret"), "{refused}");
+    assert_eq!(allowed.unwrap().profile, Profile::Prod);
+    assert!(short.contains("must be at least 32 characters"), "{short}");
+    assert!(!short.contains("is a weak secret"), "{short}");
+}
+
+#[test]
+fn loaded_secrets_are_redacted_in_debug() {
+    // ---
+    let path = config_file("debug", "secret = \"Jx4q9Lr2vTz7Wm1Kp8Ns3Hd6Bf0Gc5Ye\"\n");
+    let config = load(&path);
+    std::fs::remove_file(&path).ok();
+
+    let config = config.unwrap();
+    let debug = format!("{config:?}");
+    assert!(!debug.contains("Jx4q9"), "{debug}");
+    assert!(debug.contains("Secret([REDACTED])"), "{debug}");
+    assert_eq!(config.secret.expose(), "Jx4q9Lr2vTz7Wm1Kp8Ns3Hd6Bf0Gc5Ye");
+}
@@ -0,0 +1,218 @@
+// tests/tests/security_headers.rs
+
+//! Security headers: the middleware adding CSP, `X-Frame-Options`,
+//! `Referrer-Policy`, and `X-Content-Type-Options` to every response, its
+//! settings, pages that bring their own policy, and the services' routers
+//! applying it (no containers needed)
+
+use anyhow::Result;
+use axum::http::header;
+use axum::middleware;
+use axum::response::Html;
+use axum::routing::get;
+use axum::Router;
+use reqwest::StatusCode;
+use sqlx::postgres::PgPoolOptions;
+use std::sync::Arc;
+use tokn_core::SystemClock;
+use tokn_portal::{portal_router, PortalConfig};
+use tokn_server::{SecurityHeaders, SecurityHeadersConfig, DEFAULT_CONTENT_SECURITY_POLICY};
+use tokn_tests::{http_client, jwt_config, serve};
+
+// ---
+
+/// Serve an HTML page, a page with its own policy, and the developer portal
+/// behind the security headers middleware configured by `config`.
+async fn spawn(config: &SecurityHeadersConfig) -> Result<String> {
+    // ---
+    let app = Router::new()
+        .route("/consent", get(|| async { Html("<h1>Allow access?</h1>") }))
+        .route(
+            "/embeddable",
+            get(|| async {
+                (
+                    [(header::X_FRAME_OPTIONS, "SAMEORIGIN")],
+                    Html("<h1>Widget</h1>"),
+                )
+            }),
+        )
+        .merge(portal_router(&PortalConfig::default())?)
+        .layer(middleware::from_fn_with_state(
+            SecurityHeaders::new(config),
+            tokn_server::security_headers,
+        ));
+    serve(app).await
+}
+
+/// oauth2-server's router over a pool that is never connected (the consent
+/// page does not query the database), adding `config`'s headers.
+async fn spawn_oauth2_server(config: SecurityHeadersConfig) -> Result<String> {
+    // ---
+    let pool = PgPoolOptions::new().connect_lazy("postgres://unused@127.0.0.1:1/unused")?;
+    let state = oauth2_server::AppState::new(Arc::new(pool), Default::default())
+        .with_security_headers(config);
+    serve(oauth2_server::build_router(state)).await
+}
+
+// ---
+
+#[tokio::test]
+async fn every_response_gets_the_default_headers() -> Result<()> {
+    // ---
+    let base = spawn(&SecurityHeadersConfig::default()).await?;
+    let http = http_client();
+
+    for path in ["/consent", "/missing"] {
+        let response = http.get(format!("{base}{path}")).send().await?;
+        let headers = response.headers();
+        assert_eq!(
+            headers[header::CONTENT_SECURITY_POLICY],
+            DEFAULT_CONTENT_SECURITY_POLICY,
+            "{path}"
+        );
+        assert_eq!(headers[header::X_FRAME_OPTIONS], "DENY", "{path}");
+        assert_eq!(headers[header::REFERRER_POLICY], "no-referrer", "{path}");
+        assert_eq!(headers[header::X_CONTENT_TYPE_OPTIONS], "nosniff", "{path}");
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn pages_may_set_their_own_headers() -> Result<()> {
+    // ---
+    let base = spawn(&SecurityHeadersConfig::default()).await?;
+    let http = http_client();
+
+    let response = http.get(format!("{base}/embeddable")).send().await?;
+    assert_eq!(response.headers()[header::X_FRAME_OPTIONS], "SAMEORIGIN");
+    assert_eq!(response.headers()[header::REFERRER_POLICY], "no-referrer");
+
+    // The portal loads Swagger UI from its CDN
+    let response = http.get(format!("{base}/docs")).send().await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let csp = response.headers()[header::CONTENT_SECURITY_POLICY].to_str()?;
+    assert!(csp.contains("https://unpkg.com"), "{csp}");
+    assert_eq!(response.headers()[header::X_FRAME_OPTIONS], "DENY");
+    Ok(())
+}
+
+#[tokio::test]
+async fn headers_are_configurable() -> Result<()> {
+    // ---
+    let config = SecurityHeadersConfig {
+        content_security_policy: "default-src 'self' https://cdn.example.com".to_string(),
+        frame_options: String::new(),
+        referrer_policy: "strict-origin-when-cross-origin".to_string(),
+        ..SecurityHeadersConfig::default()
+    };
+    let base = spawn(&config).await?;
+
+    let response = http_client().get(format!("{base}/consent")).send().await?;
+    let headers = response.headers();
+    assert_eq!(
+        headers[header::CONTENT_SECURITY_POLICY],
+        "default-src 'self' https://cdn.example.com"
+    );
+    assert!(headers.get(header::X_FRAME_OPTIONS).is_none());
+    assert_eq!(
+        headers[header::REFERRER_POLICY],
+        "strict-origin-when-cross-origin"
+    );
+    assert_eq!(headers[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+    Ok(())
+}
+
+#[tokio::test]
+async fn oauth2_server_consent_page_is_guarded() -> Result<()> {
+    // ---
+    let consent = "/v1/oauth/authorize?response_type=code&client_id=acme_web\
+                   &redirect_uri=http://localhost/cb";
+    let base = spawn_oauth2_server(SecurityHeadersConfig::default()).await?;
+
+    let response = http_client().get(format!("{base}{consent}")).send().await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let headers = response.headers();
+    let content_type = headers[header::CONTENT_TYPE].to_str()?;
+    assert!(content_type.starts_with("text/html"), "{content_type}");
+    assert_eq!(
+        headers[header::CONTENT_SECURITY_POLICY],
+        DEFAULT_CONTENT_SECURITY_POLICY
+    );
+    assert_eq!(headers[header::X_FRAME_OPTIONS], "DENY");
+    assert_eq!(headers[header::REFERRER_POLICY], "no-referrer");
+
+    // The deployment's settings reach the page too
+    let base = spawn_oauth2_server(SecurityHeadersConfig {
+        frame_options: "SAMEORIGIN".to_string(),
+        referrer_policy: "same-origin".to_string(),
+        ..SecurityHeadersConfig::default()
+    })
+    .await?;
+    let response = http_client().get(format!("{base}{consent}")).send().await?;
+    assert_eq!(response.headers()[header::X_FRAME_OPTIONS], "SAMEORIGIN");
+    assert_eq!(response.headers()[header::REFERRER_POLICY], "same-origin");
+    Ok(())
+}
+
+#[tokio::test]
+async fn jwt_service_responses_are_guarded() -> Result<()> {
+    // ---
+    let mut config = jwt_config("redis://unused");
+    config.jwt.stateless = true;
+    let state = jwt_service::AppState::stateless(config, SystemClock::shared())?;
+    let base = serve(jwt_service::build_router(state)).await?;
+
+    for path in ["/", "/v1/missing"] {
+        let response = http_client().get(format!("{base}{path}")).send().await?;
+        let headers = response.headers();
+        assert_eq!(
+            headers[header::CONTENT_SECURITY_POLICY],
+            DEFAULT_CONTENT_SECURITY_POLICY,
+            "{path}"
+        );
+        assert_eq!(headers[header::X_FRAME_OPTIONS], "DENY", "{path}");
+        assert_eq!(headers[header::REFERRER_POLICY], "no-referrer", "{path}");
+    }
+    Ok(())
+}
+
+#[test]
+fn invalid_security_header_settings_are_refused() {
+    // ---
+    let valid = [
+        SecurityHeadersConfig::default(),
+        SecurityHeadersConfig {
+            frame_options: "sameorigin".to_string(),
+            ..SecurityHeadersConfig::default()
+        },
+        SecurityHeadersConfig {
+            content_security_policy: String::new(),
+            frame_options: String::new(),
+            ..SecurityHeadersConfig::default()
+        },
+    ];
+    for config in &valid {
+        assert_eq!(
+            tokn_server::validate_security_headers_config(config),
+            Ok(()),
+            "{config:?}"
+        );
+    }
+
+    let invalid = [
+        SecurityHeadersConfig {
+            frame_options: "ALLOW-FROM https://example.com".to_string(),
+            ..SecurityHeadersConfig::default()
+        },
+        SecurityHeadersConfig {
+            referrer_policy: "no-referrer\nx-injected: 1".to_string(),
+            ..SecurityHeadersConfig::default()
+        },
+    ];
+    for config in &invalid {
+        assert!(
+            tokn_server::validate_security_headers_config(config).is_err(),
+            "{config:?}"
+        );
+    }
+}
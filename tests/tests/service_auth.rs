@@ -0,0 +1,249 @@
+// tests/tests/service_auth.rs
+
+//! Signed requests between tokn services: signing and verification, the
+//! replay window, and the middleware that lets a signed request through the
+//! admin endpoints (no containers needed)
+
+use anyhow::Result;
+use axum::http::{HeaderMap, StatusCode};
+use axum::middleware;
+use axum::routing::get;
+use axum::Router;
+use chrono::Duration;
+use serde_json::Value;
+use std::sync::Arc;
+use tokn_core::{TestClock, PROBLEM_JSON};
+use tokn_server::{
+    admin_router, validate_service_auth_config, verify_service_signature, AdminConfig,
+    CallingService, ReloadFn, ReloadReport, ServiceAuth, ServiceAuthConfig, ServiceAuthError,
+    NONCE_HEADER, SIGNATURE_HEADER,
+};
+use tokn_tests::{http_client, serve, TEST_ADMIN_TOKEN};
+
+// ---
+
+const KEY: &str = "service-auth-test-key-at-least-32-characters";
+
+// ---
+
+fn config() -> ServiceAuthConfig {
+    // ---
+    ServiceAuthConfig {
+        key: Some(KEY.into()),
+        ..Default::default()
+    }
+}
+
+/// `sign`'s output as request headers.
+fn headers(signature: Vec<(axum::http::HeaderName, String)>) -> HeaderMap {
+    // ---
+    signature
+        .into_iter()
+        .map(|(name, value)| (name, value.parse().unwrap()))
+        .collect()
+}
+
+/// Serve the admin routes and an internal-only route behind signature
+/// verification with `auth`.
+async fn spawn(auth: ServiceAuth) -> Result<String> {
+    // ---
+    let reload: ReloadFn = Arc::new(|| Ok::<_, anyhow::Error>(ReloadReport::default()));
+    let admin = AdminConfig {
+        token: Some(TEST_ADMIN_TOKEN.into()),
+    };
+    let app = Router::new()
+        .route(
+            "/internal/whoami",
+            get(|CallingService(service): CallingService| async move { service }),
+        )
+        .merge(admin_router(&admin, reload))
+        .layer(middleware::from_fn_with_state(
+            auth,
+            verify_service_signature,
+        ));
+    serve(app).await
+}
+
+// ---
+
+#[test]
+fn signed_requests_verify_only_unchanged() {
+    // ---
+    let clock = TestClock::at_timestamp(1_700_000_000);
+    let client = ServiceAuth::new("oauth2-client", &config(), clock.shared());
+    let server = ServiceAuth::new("oauth2-server", &config(), clock.shared());
+
+    let signed = headers(client.sign("post", "/admin/reload?dry_run=1", b"{}"));
+    assert_eq!(
+        server.verify("POST", "/admin/reload?dry_run=1", &signed, b"{}"),
+        Ok("oauth2-client".to_string())
+    );
+
+    let signed = headers(client.sign("POST", "/admin/reload", b"{}"));
+    assert_eq!(
+        server.verify("POST", "/admin/events", &signed, b"{}"),
+        Err(ServiceAuthError::BadSignature)
+    );
+    assert_eq!(
+        server.verify("POST", "/admin/reload", &signed, b"{\"a\":1}"),
+        Err(ServiceAuthError::BadSignature)
+    );
+    assert_eq!(
+        server.verify("GET", "/admin/reload", &signed, b"{}"),
+        Err(ServiceAuthError::BadSignature)
+    );
+
+    // A service with a different key is not trusted
+    let stranger = ServiceAuth::new(
+        "oauth2-client",
+        &ServiceAuthConfig {
+            key: Some("some-other-key-also-at-least-32-characters".into()),
+            ..Default::default()
+        },
+        clock.shared(),
+    );
+    let signed = headers(stranger.sign("GET", "/", b""));
+    assert_eq!(
+        server.verify("GET", "/", &signed, b""),
+        Err(ServiceAuthError::BadSignature)
+    );
+
+    let mut unsigned = headers(client.sign("GET", "/", b""));
+    unsigned.remove(NONCE_HEADER);
+    assert_eq!(
+        server.verify("GET", "/", &unsigned, b""),
+        Err(ServiceAuthError::MissingHeader(NONCE_HEADER))
+    );
+}
+
+#[test]
+fn old_and_replayed_requests_are_refused() {
+    // ---
+    let clock = TestClock::at_timestamp(1_700_000_000);
+    let auth = ServiceAuth::new("jwt-service", &config(), clock.shared());
+
+    let signed = headers(auth.sign("GET", "/", b""));
+    assert!(auth.verify("GET", "/", &signed, b"").is_ok());
+    assert_eq!(
+        auth.verify("GET", "/", &signed, b""),
+        Err(ServiceAuthError::Replayed)
+    );
+
+    // Five minutes of clock difference either way (the default window)
+    let signed = headers(auth.sign("GET", "/", b""));
+    clock.advance(Duration::seconds(300));
+    assert!(auth.verify("GET", "/", &signed, b"").is_ok());
+
+    let signed = headers(auth.sign("GET", "/", b""));
+    clock.advance(Duration::seconds(301));
+    assert_eq!(
+        auth.verify("GET", "/", &signed, b""),
+        Err(ServiceAuthError::Expired)
+    );
+
+    clock.advance(Duration::seconds(-602));
+    assert_eq!(
+        auth.verify("GET", "/", &signed, b""),
+        Err(ServiceAuthError::Expired)
+    );
+}
+
+#[test]
+fn disabled_service_auth_signs_nothing() {
+    // ---
+    let auth = ServiceAuth::new(
+        "oauth2-client",
+        &ServiceAuthConfig::default(),
+        TestClock::default().shared(),
+    );
+    assert!(!auth.is_enabled());
+    assert!(auth.sign("GET", "/", b"").is_empty());
+}
+
+#[test]
+fn config_validation() {
+    // ---
+    assert!(validate_service_auth_config(&ServiceAuthConfig::default()).is_ok());
+    assert!(validate_service_auth_config(&config()).is_ok());
+
+    let short_key = ServiceAuthConfig {
+        key: Some("too-short".into()),
+        ..Default::default()
+    };
+    assert!(validate_service_auth_config(&short_key).is_err());
+
+    let no_window = ServiceAuthConfig {
+        max_age_seconds: 0,
+        ..config()
+    };
+    assert!(validate_service_auth_config(&no_window).is_err());
+}
+
+#[tokio::test]
+async fn signed_requests_reach_admin_and_internal_routes() -> Result<()> {
+    // ---
+    let clock = TestClock::default();
+    let base = spawn(ServiceAuth::new("oauth2-server", &config(), clock.shared())).await?;
+    let caller = ServiceAuth::new("oauth2-client", &config(), clock.shared());
+    let http = http_client();
+
+    // No admin token needed from a tokn service
+    let response = http
+        .post(format!("{base}/admin/reload"))
+        .headers(headers(caller.sign("POST", "/admin/reload", b"")))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = http
+        .get(format!("{base}/internal/whoami"))
+        .headers(headers(caller.sign("GET", "/internal/whoami", b"")))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text().await?, "oauth2-client");
+
+    // Unsigned requests still need the admin token, and never reach
+    // internal routes
+    let response = http.post(format!("{base}/admin/reload")).send().await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let response = http
+        .post(format!("{base}/admin/reload"))
+        .bearer_auth(TEST_ADMIN_TOKEN)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = http.get(format!("{base}/internal/whoami")).send().await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(response.headers()["content-type"], PROBLEM_JSON);
+
+    // A bad signature is refused outright
+    let mut forged = headers(caller.sign("POST", "/admin/reload", b""));
+    forged.insert(SIGNATURE_HEADER, "00".repeat(32).parse()?);
+    let response = http
+        .post(format!("{base}/admin/reload"))
+        .headers(forged)
+        .bearer_auth(TEST_ADMIN_TOKEN)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let body: Value = response.json().await?;
+    assert_eq!(body["detail"], "Invalid request signature");
+    Ok(())
+}
+
+#[tokio::test]
+async fn disabled_verification_ignores_signatures() -> Result<()> {
+    // ---
+    let clock = TestClock::default();
+    let base = spawn(ServiceAuth::disabled()).await?;
+    let caller = ServiceAuth::new("oauth2-client", &config(), clock.shared());
+
+    let response = http_client()
+        .post(format!("{base}/admin/reload"))
+        .headers(headers(caller.sign("POST", "/admin/reload", b"")))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    Ok(())
+}
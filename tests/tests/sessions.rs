@@ -0,0 +1,192 @@
+// tests/tests/sessions.rs
+
+//! Session listing and deletion on jwt-service: sessions survive refresh
+//! token rotation under a stable ID, ending one revokes its refresh token,
+//! and users cannot manage each other's sessions, against real Redis
+
+use anyhow::Result;
+use jwt_service::RefreshTokenData;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use tokn_core::{Claims, TestClock};
+use tokn_tests::{http_client, jwt_config, TestEnv};
+
+// ---
+
+/// Sign `user_id` in at jwt-service at `base` from `user_agent`, returning
+/// the token response.
+async fn sign_in(base: &str, user_id: &str, user_agent: &str) -> Result<Value> {
+    // ---
+    Ok(http_client()
+        .post(format!("{base}/v1/auth/token"))
+        .header("user-agent", user_agent)
+        .json(&json!({ "user_id": user_id, "email": "it@example.com" }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?)
+}
+
+/// List the sessions of `user_id` with `access_token`, expecting 200 OK.
+async fn sessions(base: &str, user_id: &str, access_token: &Value) -> Result<Vec<Value>> {
+    // ---
+    let response: Value = http_client()
+        .get(format!("{base}/v1/auth/sessions/{user_id}"))
+        .bearer_auth(access_token.as_str().unwrap())
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(response["sessions"].as_array().unwrap().clone())
+}
+
+/// The session in `sessions` last used from `user_agent`.
+fn from_agent<'a>(sessions: &'a [Value], user_agent: &str) -> &'a Value {
+    // ---
+    sessions
+        .iter()
+        .find(|session| session["user_agent"] == user_agent)
+        .unwrap_or_else(|| panic!("no session from {user_agent}: {sessions:?}"))
+}
+
+// ---
+
+#[test]
+fn rotation_keeps_the_session_id() {
+    // ---
+    let jwt = jwt_config("redis://unused").jwt;
+    let now = 1_700_000_000;
+    let claims = Claims::new(
+        "user_1".into(),
+        "u@example.com".into(),
+        900,
+        &TestClock::at_timestamp(now),
+    );
+
+    let (first, _) = RefreshTokenData::from(&claims).renew(&jwt, now).unwrap();
+    let (next, _) = first.renew(&jwt, now + 3600).unwrap();
+    assert!(first.session_id.is_some());
+    assert_eq!(next.session_id, first.session_id);
+    assert_eq!(next.issued_at, Some(now + 3600));
+    assert_eq!(next.session_started_at, Some(now));
+}
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn sessions_are_listed_and_keep_their_id_across_refresh() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let base = env.spawn_jwt_service().await?;
+    let laptop = sign_in(&base, "user_it", "laptop/1.0").await?;
+    let phone = sign_in(&base, "user_it", "phone/2.0").await?;
+
+    let listed = sessions(&base, "user_it", &laptop["access_token"]).await?;
+    assert_eq!(listed.len(), 2, "{listed:?}");
+    let laptop_session = from_agent(&listed, "laptop/1.0");
+    let phone_session = from_agent(&listed, "phone/2.0");
+    assert_ne!(laptop_session["session_id"], phone_session["session_id"]);
+    assert!(laptop_session.get("refresh_token").is_none());
+
+    // Refreshing rotates the token but not the session
+    let refreshed: Value = http_client()
+        .post(format!("{base}/v1/auth/refresh"))
+        .header("user-agent", "phone/2.1")
+        .json(&json!({ "refresh_token": phone["refresh_token"] }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    let after = sessions(&base, "user_it", &refreshed["access_token"]).await?;
+    assert_eq!(after.len(), 2, "{after:?}");
+    let refreshed_session = from_agent(&after, "phone/2.1");
+    assert_eq!(refreshed_session["session_id"], phone_session["session_id"]);
+    assert_eq!(refreshed_session["created_at"], phone_session["created_at"]);
+    assert_eq!(phone_session["rotation_count"], 0);
+    assert_eq!(refreshed_session["rotation_count"], 1);
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn deleting_a_session_revokes_its_refresh_token() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let base = env.spawn_jwt_service().await?;
+    let http = http_client();
+    let laptop = sign_in(&base, "user_it", "laptop/1.0").await?;
+    let phone = sign_in(&base, "user_it", "phone/2.0").await?;
+
+    let listed = sessions(&base, "user_it", &laptop["access_token"]).await?;
+    let phone_session = from_agent(&listed, "phone/2.0")["session_id"]
+        .as_str()
+        .unwrap();
+    let url = format!("{base}/v1/auth/sessions/user_it/{phone_session}");
+
+    let response = http
+        .delete(&url)
+        .bearer_auth(laptop["access_token"].as_str().unwrap())
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let refresh = http
+        .post(format!("{base}/v1/auth/refresh"))
+        .json(&json!({ "refresh_token": phone["refresh_token"] }))
+        .send()
+        .await?;
+    assert_eq!(refresh.status(), StatusCode::UNAUTHORIZED);
+
+    let remaining = sessions(&base, "user_it", &laptop["access_token"]).await?;
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0]["user_agent"], "laptop/1.0");
+
+    // The session is gone now
+    let response = http
+        .delete(&url)
+        .bearer_auth(laptop["access_token"].as_str().unwrap())
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn users_cannot_manage_other_users_sessions() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let base = env.spawn_jwt_service().await?;
+    let http = http_client();
+    let victim = sign_in(&base, "user_victim", "laptop/1.0").await?;
+    let other = sign_in(&base, "user_other", "laptop/1.0").await?;
+
+    let listed = sessions(&base, "user_victim", &victim["access_token"]).await?;
+    let session_id = listed[0]["session_id"].as_str().unwrap();
+    let other_token = other["access_token"].as_str().unwrap();
+
+    let response = http
+        .get(format!("{base}/v1/auth/sessions/user_victim"))
+        .bearer_auth(other_token)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = http
+        .delete(format!("{base}/v1/auth/sessions/user_victim/{session_id}"))
+        .bearer_auth(other_token)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    // Both need a valid access token at all
+    let response = http
+        .get(format!("{base}/v1/auth/sessions/user_victim"))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    Ok(())
+}
@@ -0,0 +1,88 @@
+// tests/tests/shutdown.rs
+
+//! Graceful shutdown of `tokn_server::serve`: in-flight requests finish,
+//! new connections are refused, and the drain timeout bounds the wait
+
+use anyhow::Result;
+use axum::routing::get;
+use axum::Router;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tokn_server::{Bind, Shutdown};
+use tokn_tests::http_client;
+
+// ---
+
+/// Serve a `/slow` route answering after `delay` until `shutdown`, returning
+/// the base URL and the server task.
+async fn slow_app(
+    delay: Duration,
+    shutdown: &Shutdown,
+) -> Result<(String, JoinHandle<Result<()>>)> {
+    // ---
+    let addr = std::net::TcpListener::bind("127.0.0.1:0")?.local_addr()?;
+    let app = Router::new().route(
+        "/slow",
+        get(move || async move {
+            tokio::time::sleep(delay).await;
+            "done"
+        }),
+    );
+
+    let shutdown = shutdown.clone();
+    let server = tokio::spawn(async move {
+        tokn_server::serve(app, &Bind::Tcp(addr.to_string()), None, None, &shutdown).await
+    });
+
+    // Wait for the listener
+    let base = format!("http://{addr}");
+    for _ in 0..50 {
+        if tokio::net::TcpStream::connect(addr).await.is_ok() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    Ok((base, server))
+}
+
+// ---
+
+#[tokio::test]
+async fn in_flight_requests_finish_before_shutdown() -> Result<()> {
+    // ---
+    let shutdown = Shutdown::new(Duration::from_secs(5));
+    let (base, server) = slow_app(Duration::from_millis(300), &shutdown).await?;
+
+    let http = http_client();
+    let in_flight = tokio::spawn(http.get(format!("{base}/slow")).send());
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    shutdown.trigger();
+
+    let response = in_flight.await??;
+    assert_eq!(response.text().await?, "done");
+    server.await??;
+
+    // Nothing listens any more
+    assert!(http_client()
+        .get(format!("{base}/slow"))
+        .send()
+        .await
+        .is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn drain_timeout_bounds_the_wait() -> Result<()> {
+    // ---
+    let shutdown = Shutdown::new(Duration::from_millis(200));
+    let (base, server) = slow_app(Duration::from_secs(30), &shutdown).await?;
+
+    let _stuck = tokio::spawn(http_client().get(format!("{base}/slow")).send());
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let started = Instant::now();
+    shutdown.trigger();
+    server.await??;
+    assert!(started.elapsed() < Duration::from_secs(5));
+    Ok(())
+}
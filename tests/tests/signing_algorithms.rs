@@ -0,0 +1,185 @@
+// tests/tests/signing_algorithms.rs
+
+//! ES256 and EdDSA access tokens: key pairs of each type, refusing keys of
+//! the wrong type at load, and refusing tokens of another algorithm (no
+//! containers needed)
+
+use anyhow::Result;
+use jsonwebtoken::decode_header;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use tokn_core::{Claims, JwtKeys, SigningAlgorithm, SystemClock, TokenError};
+use tokn_tests::{http_client, jwt_config, serve};
+
+// ---
+
+const EC_PRIVATE_PEM: &[u8] = include_bytes!("../fixtures/ec_private.pem");
+const EC_PUBLIC_PEM: &[u8] = include_bytes!("../fixtures/ec_public.pem");
+const ED_PRIVATE_PEM: &[u8] = include_bytes!("../fixtures/ed25519_private.pem");
+const ED_PUBLIC_PEM: &[u8] = include_bytes!("../fixtures/ed25519_public.pem");
+const RSA_PRIVATE_PEM: &[u8] = include_bytes!("../fixtures/rsa_private.pem");
+const RSA_PUBLIC_PEM: &[u8] = include_bytes!("../fixtures/rsa_public.pem");
+
+// ---
+
+fn fixture(name: &str) -> PathBuf {
+    // ---
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("fixtures")
+        .join(name)
+}
+
+fn claims() -> Claims {
+    // ---
+    Claims::new("user_1".into(), "u@example.com".into(), 900, &SystemClock)
+}
+
+fn invalid_key<T>(result: Result<T, TokenError>) -> bool {
+    // ---
+    matches!(result, Err(TokenError::InvalidKey(_)))
+}
+
+// ---
+
+#[test]
+fn es256_and_eddsa_sign_and_verify_with_the_public_key() -> Result<()> {
+    // ---
+    for (algorithm, private_pem, public_pem) in [
+        (SigningAlgorithm::Es256, EC_PRIVATE_PEM, EC_PUBLIC_PEM),
+        (SigningAlgorithm::EdDsa, ED_PRIVATE_PEM, ED_PUBLIC_PEM),
+    ] {
+        let keys = JwtKeys::from_pem(algorithm, private_pem, public_pem)?;
+        assert_eq!(keys.algorithm(), algorithm);
+        let token = keys.sign(&claims())?;
+        assert_eq!(
+            format!("{:?}", decode_header(&token)?.alg),
+            algorithm.as_str()
+        );
+
+        let verifier = JwtKeys::from_public_pem(algorithm, public_pem)?;
+        assert!(!verifier.can_sign());
+        assert_eq!(verifier.verify(&token, &SystemClock)?.sub, "user_1");
+    }
+    Ok(())
+}
+
+#[test]
+fn keys_of_the_wrong_type_are_refused_at_load() {
+    // ---
+    use SigningAlgorithm::{EdDsa, Es256, Hs256, Rs256};
+
+    assert!(invalid_key(JwtKeys::from_pem(
+        Es256,
+        RSA_PRIVATE_PEM,
+        RSA_PUBLIC_PEM
+    )));
+    assert!(invalid_key(JwtKeys::from_pem(
+        EdDsa,
+        EC_PRIVATE_PEM,
+        EC_PUBLIC_PEM
+    )));
+    assert!(invalid_key(JwtKeys::from_pem(
+        Rs256,
+        ED_PRIVATE_PEM,
+        ED_PUBLIC_PEM
+    )));
+    assert!(invalid_key(JwtKeys::from_public_pem(Es256, ED_PUBLIC_PEM)));
+    assert!(invalid_key(JwtKeys::from_public_pem(Hs256, EC_PUBLIC_PEM)));
+
+    // A good private key does not excuse a broken public key
+    assert!(invalid_key(JwtKeys::from_pem(
+        Es256,
+        EC_PRIVATE_PEM,
+        b"not a key"
+    )));
+}
+
+#[test]
+fn each_algorithm_refuses_tokens_of_another() -> Result<()> {
+    // ---
+    let es256 = JwtKeys::from_pem(SigningAlgorithm::Es256, EC_PRIVATE_PEM, EC_PUBLIC_PEM)?;
+    let eddsa = JwtKeys::from_pem(SigningAlgorithm::EdDsa, ED_PRIVATE_PEM, ED_PUBLIC_PEM)?;
+    let rs256 = JwtKeys::rs256(RSA_PRIVATE_PEM, RSA_PUBLIC_PEM)?;
+
+    let es_token = es256.sign(&claims())?;
+    let ed_token = eddsa.sign(&claims())?;
+    for (keys, token) in [
+        (&eddsa, &es_token),
+        (&rs256, &es_token),
+        (&es256, &ed_token),
+    ] {
+        assert!(matches!(
+            keys.verify(token, &SystemClock),
+            Err(TokenError::InvalidAlgorithm)
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn algorithm_names_parse() {
+    // ---
+    assert_eq!("ES256".parse(), Ok(SigningAlgorithm::Es256));
+    assert_eq!("eddsa".parse(), Ok(SigningAlgorithm::EdDsa));
+    assert_eq!("Ed25519".parse(), Ok(SigningAlgorithm::EdDsa));
+    assert!("ES384".parse::<SigningAlgorithm>().is_err());
+    assert_eq!(SigningAlgorithm::EdDsa.to_string(), "EdDSA");
+    assert!(!SigningAlgorithm::Hs256.is_asymmetric());
+}
+
+#[tokio::test]
+async fn jwt_service_issues_es256_tokens() -> Result<()> {
+    // ---
+    let mut config = jwt_config("redis://unused");
+    config.jwt.stateless = true;
+    config.jwt.algorithm = SigningAlgorithm::Es256;
+    config.jwt.secret = None;
+    config.jwt.private_key_path = Some(fixture("ec_private.pem"));
+    config.jwt.public_key_path = Some(fixture("ec_public.pem"));
+
+    let state = jwt_service::AppState::stateless(config, SystemClock::shared())?;
+    let base = serve(jwt_service::build_router(state)).await?;
+    let http = http_client();
+
+    let tokens: Value = http
+        .post(format!("{base}/v1/auth/token"))
+        .json(&json!({ "user_id": "user_1", "email": "u@example.com" }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let token = tokens["access_token"].as_str().unwrap();
+
+    let verifier = JwtKeys::from_public_pem(SigningAlgorithm::Es256, EC_PUBLIC_PEM)?;
+    assert_eq!(verifier.verify(token, &SystemClock)?.sub, "user_1");
+
+    let validate: Value = http
+        .post(format!("{base}/v1/auth/validate"))
+        .json(&json!({ "token": token }))
+        .send()
+        .await?
+        .json()
+        .await?;
+    assert_eq!(validate["valid"], true, "{validate}");
+    Ok(())
+}
+
+#[test]
+fn jwt_service_refuses_a_key_pair_of_the_wrong_type() {
+    // ---
+    let mut config = jwt_config("redis://unused");
+    config.jwt.stateless = true;
+    config.jwt.algorithm = SigningAlgorithm::EdDsa;
+    config.jwt.secret = None;
+    config.jwt.private_key_path = Some(fixture("rsa_private.pem"));
+    config.jwt.public_key_path = Some(fixture("rsa_public.pem"));
+
+    let error = jwt_service::AppState::stateless(config, SystemClock::shared())
+        .err()
+        .expect("an RSA key pair must not load as EdDSA");
+    assert!(
+        format!("{error:#}").contains("JWT_ALGORITHM is EdDSA"),
+        "{error:#}"
+    );
+}
@@ -0,0 +1,325 @@
+// tests/tests/sms.rs
+
+//! One-time codes over SMS, and oauth2-server's phone verification
+//! endpoints and `phone` userinfo claims
+
+use anyhow::Result;
+use chrono::Duration;
+use reqwest::{header::LOCATION, StatusCode};
+use serde_json::{json, Value};
+use tokn_core::TestClock;
+use tokn_sms::{
+    validate_sms_config, MemoryOtpStore, Otp, OtpError, OtpPolicy, PhoneNumber, SmsBackend,
+    SmsConfig,
+};
+use tokn_tests::{
+    http_client, query_param, RecordingSender, TestEnv, DEMO_CLIENT_ID, DEMO_CLIENT_SECRET,
+    DEMO_REDIRECT_URI,
+};
+
+// ---
+
+const PHONE: &str = "+14155550123";
+
+/// An [`Otp`] over an in-memory store, sending into `sender` at `clock`'s
+/// time.
+fn otp(sender: &RecordingSender, clock: &TestClock) -> Otp {
+    // ---
+    Otp::new(
+        sender.clone(),
+        "recording",
+        MemoryOtpStore::default(),
+        OtpPolicy::default(),
+        clock.shared(),
+    )
+}
+
+fn phone() -> PhoneNumber {
+    // ---
+    PHONE.parse().unwrap()
+}
+
+// ---
+
+#[test]
+fn phone_numbers_normalize_to_e164() {
+    // ---
+    let phone: PhoneNumber = "+1 (415) 555-0123".parse().unwrap();
+    assert_eq!(phone.as_str(), PHONE);
+    assert_eq!(phone.masked(), "+*********23");
+
+    for invalid in ["4155550123", "+1415", "+0145555012345", "+1415555012a", ""] {
+        assert!(
+            matches!(
+                invalid.parse::<PhoneNumber>(),
+                Err(OtpError::InvalidPhoneNumber)
+            ),
+            "{invalid}"
+        );
+    }
+}
+
+#[test]
+fn twilio_backend_requires_credentials() {
+    // ---
+    let config = SmsConfig {
+        backend: SmsBackend::Twilio,
+        twilio_account_sid: Some("AC123".to_string()),
+        ..SmsConfig::default()
+    };
+    let error = validate_sms_config(&config).unwrap_err();
+    assert!(error.contains("SMS_TWILIO_AUTH_TOKEN"), "{error}");
+
+    let config = SmsConfig {
+        twilio_auth_token: Some("token".into()),
+        twilio_from: Some("+14155550100".to_string()),
+        ..config
+    };
+    assert!(validate_sms_config(&config).is_ok());
+}
+
+// ---
+
+#[tokio::test]
+async fn code_is_accepted_once() -> Result<()> {
+    // ---
+    let sender = RecordingSender::default();
+    let otp = otp(&sender, &TestClock::default());
+
+    otp.send_code(&phone()).await?;
+    let code = sender.last_code(&phone()).expect("a code was sent");
+    assert_eq!(code.len(), 6);
+
+    otp.verify(&phone(), &code).await?;
+    assert!(matches!(
+        otp.verify(&phone(), &code).await,
+        Err(OtpError::NoPendingCode)
+    ));
+    Ok(())
+}
+
+#[tokio::test]
+async fn code_expires() -> Result<()> {
+    // ---
+    let sender = RecordingSender::default();
+    let clock = TestClock::default();
+    let otp = otp(&sender, &clock);
+
+    otp.send_code(&phone()).await?;
+    let code = sender.last_code(&phone()).unwrap();
+    clock.advance(Duration::seconds(301));
+
+    assert!(matches!(
+        otp.verify(&phone(), &code).await,
+        Err(OtpError::Expired)
+    ));
+    Ok(())
+}
+
+#[tokio::test]
+async fn wrong_guesses_burn_the_code() -> Result<()> {
+    // ---
+    let sender = RecordingSender::default();
+    let otp = otp(&sender, &TestClock::default());
+
+    otp.send_code(&phone()).await?;
+    let code = sender.last_code(&phone()).unwrap();
+    let wrong = if code == "000000" { "111111" } else { "000000" };
+
+    for _ in 0..4 {
+        assert!(matches!(
+            otp.verify(&phone(), wrong).await,
+            Err(OtpError::Mismatch)
+        ));
+    }
+    assert!(matches!(
+        otp.verify(&phone(), wrong).await,
+        Err(OtpError::TooManyAttempts)
+    ));
+
+    // The right code no longer helps
+    assert!(matches!(
+        otp.verify(&phone(), &code).await,
+        Err(OtpError::TooManyAttempts)
+    ));
+    Ok(())
+}
+
+#[tokio::test]
+async fn resends_are_spaced_and_capped() -> Result<()> {
+    // ---
+    let sender = RecordingSender::default();
+    let clock = TestClock::default();
+    let otp = otp(&sender, &clock);
+
+    otp.send_code(&phone()).await?;
+    assert!(matches!(
+        otp.send_code(&phone()).await,
+        Err(OtpError::ResendTooSoon { retry_after: 30 })
+    ));
+
+    // Five sends per hour, however they are spaced
+    for _ in 0..4 {
+        clock.advance(Duration::seconds(30));
+        otp.send_code(&phone()).await?;
+    }
+    clock.advance(Duration::seconds(30));
+    let Err(OtpError::RateLimited { retry_after }) = otp.send_code(&phone()).await else {
+        panic!("sixth send within the hour should be rate limited");
+    };
+    assert_eq!(retry_after, 3600 - 150);
+    assert_eq!(sender.sent().len(), 5);
+
+    // A new window opens once the hour has passed
+    clock.advance(Duration::seconds(retry_after as i64));
+    otp.send_code(&phone()).await?;
+
+    // Limits are per number
+    otp.send_code(&"+442071838750".parse()?).await?;
+    assert_eq!(sender.sent().len(), 7);
+    Ok(())
+}
+
+#[tokio::test]
+async fn new_code_replaces_the_old_one() -> Result<()> {
+    // ---
+    let sender = RecordingSender::default();
+    let clock = TestClock::default();
+    let otp = otp(&sender, &clock);
+
+    otp.send_code(&phone()).await?;
+    let first = sender.last_code(&phone()).unwrap();
+    clock.advance(Duration::seconds(30));
+    otp.send_code(&phone()).await?;
+    let second = sender.last_code(&phone()).unwrap();
+
+    if first != second {
+        assert!(matches!(
+            otp.verify(&phone(), &first).await,
+            Err(OtpError::Mismatch)
+        ));
+    }
+    otp.verify(&phone(), &second).await?;
+    Ok(())
+}
+
+// ---
+
+/// Complete the authorization code flow for `scope` and return the access
+/// token.
+async fn access_token(http: &reqwest::Client, base: &str, scope: &str) -> Result<String> {
+    // ---
+    let response = http
+        .post(format!("{base}/v1/oauth/authorize"))
+        .form(&[
+            ("client_id", DEMO_CLIENT_ID),
+            ("redirect_uri", DEMO_REDIRECT_URI),
+            ("scope", scope),
+            ("state", "xyz"),
+            ("action", "approve"),
+        ])
+        .send()
+        .await?;
+    let location = response.headers()[LOCATION].to_str()?.to_string();
+    let code = query_param(&reqwest::Url::parse(&location)?, "code").unwrap();
+
+    let token: Value = http
+        .post(format!("{base}/v1/oauth/token"))
+        .form(&[
+            ("grant_type", "authorization_code"),
+            ("code", &code),
+            ("redirect_uri", DEMO_REDIRECT_URI),
+            ("client_id", DEMO_CLIENT_ID),
+            ("client_secret", DEMO_CLIENT_SECRET),
+        ])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    Ok(token["access_token"].as_str().unwrap().to_string())
+}
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn phone_verification_sets_userinfo_claims() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let sender = RecordingSender::default();
+    let base = env.spawn_oauth2_server_with_sms(sender.clone()).await?;
+    let http = http_client();
+
+    // ---
+    // Changing the phone number needs the `phone` scope
+    let profile_only = access_token(&http, &base, "profile").await?;
+    let response = http
+        .post(format!("{base}/v1/oauth/phone"))
+        .bearer_auth(&profile_only)
+        .json(&json!({ "phone_number": PHONE }))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    // ---
+    let token = access_token(&http, &base, "profile phone").await?;
+    let response = http
+        .post(format!("{base}/v1/oauth/phone"))
+        .bearer_auth(&token)
+        .json(&json!({ "phone_number": "+1 415 555 0123" }))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+    let resend = http
+        .post(format!("{base}/v1/oauth/phone"))
+        .bearer_auth(&token)
+        .json(&json!({ "phone_number": PHONE }))
+        .send()
+        .await?;
+    assert_eq!(resend.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(resend.headers().contains_key("retry-after"));
+
+    // ---
+    let wrong = http
+        .post(format!("{base}/v1/oauth/phone/verify"))
+        .bearer_auth(&token)
+        .json(&json!({ "phone_number": PHONE, "code": "not-a-code" }))
+        .send()
+        .await?;
+    assert_eq!(wrong.status(), StatusCode::BAD_REQUEST);
+
+    let code = sender.last_code(&phone()).expect("a code was sent");
+    let verified = http
+        .post(format!("{base}/v1/oauth/phone/verify"))
+        .bearer_auth(&token)
+        .json(&json!({ "phone_number": PHONE, "code": code }))
+        .send()
+        .await?;
+    assert_eq!(verified.status(), StatusCode::NO_CONTENT);
+
+    // ---
+    // The claims are released under the `phone` scope only
+    let userinfo: tokn_core::UserInfo = http
+        .get(format!("{base}/v1/oauth/userinfo"))
+        .bearer_auth(&token)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    assert_eq!(userinfo.phone_number.as_deref(), Some(PHONE));
+    assert_eq!(userinfo.phone_number_verified, Some(true));
+
+    let userinfo: Value = http
+        .get(format!("{base}/v1/oauth/userinfo"))
+        .bearer_auth(&profile_only)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    assert!(userinfo.get("phone_number").is_none());
+    assert!(userinfo.get("phone_number_verified").is_none());
+    Ok(())
+}
@@ -0,0 +1,208 @@
+// tests/tests/theming.rs
+
+//! Page theming: theme directories, per-client themes, and static assets on
+//! oauth2-server and oauth2-client (no containers needed)
+
+use anyhow::Result;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::StatusCode;
+use serde_json::json;
+use sqlx::postgres::PgPoolOptions;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokn_tests::{http_client, oauth2_client_config, serve};
+use tokn_theme::{Page, ThemeConfig, ThemeError, Themes};
+
+// ---
+
+/// A theme directory with a branded `default` theme and an `acme` theme
+/// that replaces the header and ships its own stylesheet and logo.
+fn theme_dir(name: &str) -> Result<PathBuf> {
+    // ---
+    let dir = std::env::temp_dir().join(format!("tokn-theme-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+
+    std::fs::create_dir_all(dir.join("default"))?;
+    std::fs::write(
+        dir.join("default/footer.html"),
+        "<footer>Operated by Example Corp</footer>\n",
+    )?;
+
+    std::fs::create_dir_all(dir.join("acme/static"))?;
+    std::fs::write(
+        dir.join("acme/header.html"),
+        r#"<header><img src="{{ static }}/logo.svg" alt="ACME"></header>"#,
+    )?;
+    std::fs::write(dir.join("acme/static/theme.css"), "body { color: red; }\n")?;
+    std::fs::write(dir.join("acme/static/logo.svg"), "<svg></svg>\n")?;
+
+    Ok(dir)
+}
+
+fn config(dir: &Path, clients: &str) -> Result<ThemeConfig> {
+    // ---
+    Ok(serde_json::from_value(json!({
+        "dir": dir,
+        "clients": clients,
+    }))?)
+}
+
+/// oauth2-server over a pool that is never connected; the pages under test
+/// do not query the database.
+async fn spawn_oauth2_server(themes: Themes) -> Result<String> {
+    // ---
+    let pool = PgPoolOptions::new().connect_lazy("postgres://unused@127.0.0.1:1/unused")?;
+    let state = oauth2_server::AppState::new(Arc::new(pool), Default::default()).with_theme(themes);
+    serve(oauth2_server::build_router(state)).await
+}
+
+// ---
+
+#[test]
+fn theme_directories_override_partials() -> Result<()> {
+    // ---
+    let dir = theme_dir("partials")?;
+    let themes = Themes::new(&config(&dir, "acme_web=acme")?)?;
+    let page = Page {
+        lang: "en",
+        title: "Consent",
+        content: "<p>{{ static }} stays literal</p>",
+    };
+
+    let acme = themes.for_client(Some("acme_web")).render(&page);
+    assert!(acme.contains(r#"<img src="/theme/acme/static/logo.svg" alt="ACME">"#));
+    assert!(acme.contains(r#"href="/theme/acme/static/theme.css""#));
+    // Partials the theme does not replace are the built-in ones
+    assert!(acme.contains(r#"<footer class="tokn-footer">"#));
+    // Page content is not expanded as a template
+    assert!(acme.contains("<p>{{ static }} stays literal</p>"));
+
+    let default = themes.for_client(Some("other_client")).render(&page);
+    assert!(default.contains("<footer>Operated by Example Corp</footer>"));
+    assert!(default.contains(r#"<header class="tokn-header">"#));
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[test]
+fn client_themes_parse_from_env_list_or_table() -> Result<()> {
+    // ---
+    let from_env: ThemeConfig = serde_json::from_value(json!({ "clients": "a=acme, b = beta" }))?;
+    let from_file: ThemeConfig =
+        serde_json::from_value(json!({ "clients": { "a": "acme", "b": "beta" } }))?;
+
+    assert_eq!(from_env.clients, from_file.clients);
+    assert_eq!(from_env.clients.0["b"], "beta");
+    assert!(serde_json::from_value::<ThemeConfig>(json!({ "clients": "a:acme" })).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn unknown_or_unsafe_themes_are_rejected() -> Result<()> {
+    // ---
+    let dir = theme_dir("unknown")?;
+
+    let missing = Themes::new(&config(&dir, "acme_web=nope")?);
+    assert!(matches!(missing, Err(ThemeError::UnknownTheme(..))));
+
+    let unsafe_name = Themes::new(&ThemeConfig {
+        name: "../etc".into(),
+        ..Default::default()
+    });
+    assert!(matches!(unsafe_name, Err(ThemeError::InvalidName(_))));
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn consent_and_error_pages_use_the_client_theme() -> Result<()> {
+    // ---
+    let dir = theme_dir("server")?;
+    let base = spawn_oauth2_server(Themes::new(&config(&dir, "acme_web=acme")?)?).await?;
+    let http = http_client();
+
+    let consent = http
+        .get(format!(
+            "{base}/v1/oauth/authorize?response_type=code&client_id=acme_web&redirect_uri=http://localhost/cb"
+        ))
+        .send()
+        .await?;
+    assert_eq!(consent.status(), StatusCode::OK);
+    let body = consent.text().await?;
+    assert!(body.contains(r#"alt="ACME""#));
+    assert!(body.contains(r#"action="/v1/oauth/authorize""#));
+
+    // Missing redirect_uri: themed 400 page instead of a plain-text rejection
+    let invalid = http
+        .get(format!("{base}/v1/oauth/authorize?client_id=acme_web"))
+        .send()
+        .await?;
+    assert_eq!(invalid.status(), StatusCode::BAD_REQUEST);
+    let body = invalid.text().await?;
+    assert!(body.contains(r#"alt="ACME""#));
+    assert!(body.contains("Something went wrong"));
+
+    // Theme assets, with the built-in stylesheet as a fallback
+    let logo = http
+        .get(format!("{base}/theme/acme/static/logo.svg"))
+        .send()
+        .await?;
+    assert_eq!(logo.status(), StatusCode::OK);
+    assert_eq!(logo.headers()[CONTENT_TYPE], "image/svg+xml");
+
+    let css = http
+        .get(format!("{base}/theme/acme/static/theme.css"))
+        .send()
+        .await?;
+    assert_eq!(css.text().await?, "body { color: red; }\n");
+
+    let builtin_css = http
+        .get(format!("{base}/theme/default/static/theme.css"))
+        .send()
+        .await?;
+    assert_eq!(builtin_css.status(), StatusCode::OK);
+    assert!(builtin_css.headers()[CONTENT_TYPE]
+        .to_str()?
+        .starts_with("text/css"));
+
+    let escape = http
+        .get(format!(
+            "{base}/theme/acme/static/../../default/footer.html"
+        ))
+        .send()
+        .await?;
+    assert_eq!(escape.status(), StatusCode::NOT_FOUND);
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn client_pages_use_the_deployment_theme() -> Result<()> {
+    // ---
+    let dir = theme_dir("client")?;
+    let mut config = oauth2_client_config("http://127.0.0.1:1", "redis://unused");
+    config.theme = serde_json::from_value(json!({ "dir": dir, "name": "acme" }))?;
+    let base = serve(oauth2_client::build_router(Arc::new(config))?).await?;
+
+    let home = http_client()
+        .get(format!("{base}/"))
+        .send()
+        .await?
+        .text()
+        .await?;
+    assert!(home.contains(r#"alt="ACME""#));
+    assert!(home.contains("Login with OAuth2"));
+
+    let css = http_client()
+        .get(format!("{base}/theme/acme/static/theme.css"))
+        .send()
+        .await?;
+    assert_eq!(css.status(), StatusCode::OK);
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
@@ -0,0 +1,135 @@
+// tests/tests/tickets.rs
+
+//! Single-use WebSocket/SSE tickets: `POST /v1/auth/ticket` exchanges an
+//! access token for a short-lived ticket, and `POST /v1/auth/ticket/redeem`
+//! consumes it for the token's claims
+
+use anyhow::Result;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use tokn_core::TestClock;
+use tokn_tests::{http_client, jwt_config, jwt_state_in_memory, serve};
+
+// ---
+
+const NOW: i64 = 1_700_000_000;
+
+// ---
+
+/// Serve jwt-service from an in-memory store on `clock`, issuing access
+/// tokens for `expiry_seconds`, and return its base URL and an access token
+/// of `user_1`.
+async fn start(clock: &TestClock, expiry_seconds: i64) -> Result<(String, String)> {
+    // ---
+    let mut config = jwt_config("redis://unused");
+    config.jwt.access_token_expiry_seconds = expiry_seconds;
+    let state = jwt_state_in_memory(config, clock.shared())?;
+    let base = serve(jwt_service::build_router(state)).await?;
+
+    let issued: Value = http_client()
+        .post(format!("{base}/v1/auth/token"))
+        .json(&json!({ "user_id": "user_1", "email": "u@example.com" }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let access_token = issued["access_token"].as_str().unwrap().to_string();
+    Ok((base, access_token))
+}
+
+/// Issue a ticket for `access_token`.
+async fn ticket(base: &str, access_token: &str) -> Result<Value> {
+    // ---
+    let issued = http_client()
+        .post(format!("{base}/v1/auth/ticket"))
+        .bearer_auth(access_token)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(issued)
+}
+
+/// Redeem `ticket`, returning the response status and body.
+async fn redeem(base: &str, ticket: &Value) -> Result<(StatusCode, Value)> {
+    // ---
+    let response = http_client()
+        .post(format!("{base}/v1/auth/ticket/redeem"))
+        .json(&json!({ "ticket": ticket["ticket"] }))
+        .send()
+        .await?;
+    Ok((response.status(), response.json().await?))
+}
+
+// ---
+
+#[tokio::test]
+async fn tickets_are_redeemed_once_for_the_token_claims() -> Result<()> {
+    // ---
+    let clock = TestClock::at_timestamp(NOW);
+    let (base, access_token) = start(&clock, 900).await?;
+
+    // Only a valid access token gets a ticket
+    let response = http_client()
+        .post(format!("{base}/v1/auth/ticket"))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let issued = ticket(&base, &access_token).await?;
+    assert_eq!(issued["expires_in"], 30, "{issued}");
+
+    let (status, body) = redeem(&base, &issued).await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["valid"], true);
+    assert_eq!(body["claims"]["sub"], "user_1");
+
+    // A second redemption is refused
+    let (status, body) = redeem(&base, &issued).await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        body["detail"], "Invalid, expired, or already redeemed ticket",
+        "{body}"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn tickets_expire_with_their_ttl_or_token() -> Result<()> {
+    // ---
+    let clock = TestClock::at_timestamp(NOW);
+    let (base, access_token) = start(&clock, 900).await?;
+    let issued = ticket(&base, &access_token).await?;
+    clock.advance(chrono::Duration::seconds(31));
+    let (status, _) = redeem(&base, &issued).await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // Never outlives the access token
+    let clock = TestClock::at_timestamp(NOW);
+    let (base, access_token) = start(&clock, 10).await?;
+    let issued = ticket(&base, &access_token).await?;
+    assert_eq!(issued["expires_in"], 10, "{issued}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn tickets_of_revoked_tokens_are_refused() -> Result<()> {
+    // ---
+    let clock = TestClock::at_timestamp(NOW);
+    let (base, access_token) = start(&clock, 900).await?;
+    let issued = ticket(&base, &access_token).await?;
+
+    http_client()
+        .post(format!("{base}/v1/auth/revoke"))
+        .json(&json!({ "token": access_token }))
+        .send()
+        .await?
+        .error_for_status()?;
+
+    let (status, body) = redeem(&base, &issued).await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["detail"], "Token has been revoked", "{body}");
+    Ok(())
+}
@@ -0,0 +1,409 @@
+// tests/tests/token_store.rs
+
+//! Pluggable token stores (`TOKEN_STORE`): the in-memory and Postgres stores
+//! keep refresh tokens, sessions, the blacklist, tickets, and magic links to
+//! the same contract as Redis, and jwt-service serves refresh, revocation,
+//! and sessions from the in-memory store without Redis (`--dev`)
+
+use anyhow::Result;
+use jwt_service::{
+    MemoryStore, PostgresStore, RefreshTokenData, RevocationStatus, Store, TokenStore,
+};
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use std::time::Duration;
+use tokn_config::Profile;
+use tokn_core::{keys, Claims, TestClock};
+use tokn_tests::{http_client, jwt_config, spawn_jwt_service_in_memory, TestEnv};
+
+// ---
+
+const NOW: i64 = 1_700_000_000;
+
+// ---
+
+/// Refresh token data for a fresh session of `user_id`, as issued at `NOW`.
+fn session_data(user_id: &str) -> RefreshTokenData {
+    // ---
+    let jwt = jwt_config("redis://unused").jwt;
+    let claims = Claims::new(
+        user_id.into(),
+        "u@example.com".into(),
+        900,
+        &TestClock::at_timestamp(NOW),
+    );
+    let (data, _) = RefreshTokenData::from(&claims).renew(&jwt, NOW).unwrap();
+    data
+}
+
+/// Exercise `store`, whose entries expire by `clock`, the way jwt-service
+/// uses it.
+async fn check_store_contract(store: &impl TokenStore, clock: &TestClock) -> Result<()> {
+    // ---
+    store.ping().await?;
+
+    // Listed under its session until consumed by rotation
+    let first = session_data("user_1");
+    store.store_refresh_token("token-a", &first, 3600).await?;
+    let listed = store.list_user_sessions("user_1").await?;
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].token_hash, keys::refresh_token_hash("token-a"));
+    assert_eq!(listed[0].ttl_seconds, 3600);
+    assert!(store.list_user_sessions("user_2").await?.is_empty());
+
+    let data = store.validate_refresh_token("token-a").await?;
+    assert_eq!(data.session_id, first.session_id);
+    assert!(store.validate_refresh_token("token-a").await.is_err());
+    let reused = store.refresh_token_reused("token-a").await?;
+    assert_eq!(reused.map(|data| data.user_id).as_deref(), Some("user_1"));
+    assert!(store.refresh_token_reused("token-unknown").await?.is_none());
+    assert!(store.list_user_sessions("user_1").await?.is_empty());
+
+    // During the grace window a rotated token leads to its live successor
+    store.store_refresh_token("token-a2", &first, 3600).await?;
+    store
+        .store_refresh_successor("token-a", "token-a2", 10)
+        .await?;
+    let (successor, data) = store.refresh_successor("token-a").await?.unwrap();
+    assert_eq!(successor, "token-a2");
+    assert_eq!(data.session_id, first.session_id);
+    assert!(store.refresh_successor("token-unknown").await?.is_none());
+    store.validate_refresh_token("token-a2").await?;
+    assert!(store.refresh_successor("token-a").await?.is_none());
+
+    // Ending the session deletes its token
+    let second = session_data("user_1");
+    let session_id = second.session_id.clone().unwrap();
+    store.store_refresh_token("token-b", &second, 3600).await?;
+    assert!(!store.delete_user_session("user_2", &session_id).await?);
+    assert!(store.delete_user_session("user_1", &session_id).await?);
+    assert!(!store.delete_user_session("user_1", &session_id).await?);
+    assert!(store.validate_refresh_token("token-b").await.is_err());
+
+    // Revocation returns the data once
+    store.store_refresh_token("token-c", &second, 3600).await?;
+    assert!(store.revoke_refresh_token("token-c").await?.is_some());
+    assert!(store.revoke_refresh_token("token-c").await?.is_none());
+
+    // Blacklist entries and refresh tokens expire after their TTL
+    store.revoke_token("jti-1", 60).await?;
+    store.store_refresh_token("token-d", &second, 30).await?;
+    assert!(store.is_token_revoked("jti-1").await?);
+    assert!(!store.is_token_revoked("jti-2").await?);
+
+    // The blacklist can be listed and edited by operators
+    store.revoke_token("jti-3", 60).await?;
+    assert_eq!(store.revoked_token_ttl("jti-1").await?, Some(60));
+    assert_eq!(store.revoked_token_ttl("jti-2").await?, None);
+    let mut listed = store.list_revoked_tokens(10).await?;
+    listed.sort_by(|a, b| a.jti.cmp(&b.jti));
+    let jtis: Vec<&str> = listed.iter().map(|entry| entry.jti.as_str()).collect();
+    assert_eq!(jtis, ["jti-1", "jti-3"]);
+    assert_eq!(store.list_revoked_tokens(1).await?.len(), 1);
+    assert!(store.unrevoke_token("jti-3").await?);
+    assert!(!store.unrevoke_token("jti-3").await?);
+    assert!(!store.is_token_revoked("jti-3").await?);
+
+    // Single-use tokens are consumed once, out of the blacklist's reach
+    assert!(store.consume_token("jti-4", 30).await?);
+    assert!(!store.consume_token("jti-4", 30).await?);
+    assert!(!store.is_token_revoked("jti-4").await?);
+    let listed = store.list_revoked_tokens(10).await?;
+    assert!(listed.iter().all(|entry| entry.jti != "jti-4"));
+    assert!(!store.unrevoke_token("jti-4").await?);
+    assert!(!store.consume_token("jti-4", 30).await?);
+
+    // Token versions count up per user and never expire
+    assert_eq!(store.token_version("user_1").await?, 0);
+    assert_eq!(store.bump_token_version("user_1").await?, 1);
+    assert_eq!(store.bump_token_version("user_1").await?, 2);
+    assert_eq!(store.token_version("user_2").await?, 0);
+
+    // Validation reads the blacklist, version, and cutoff together
+    assert_eq!(
+        store.revocation_status("jti-1", "user_1").await?,
+        RevocationStatus {
+            blacklisted: true,
+            token_version: 2,
+            revoked_before: None,
+        }
+    );
+    store.set_revoked_before("user_2", NOW).await?;
+    assert_eq!(
+        store.revocation_status("jti-2", "user_2").await?,
+        RevocationStatus {
+            blacklisted: false,
+            token_version: 0,
+            revoked_before: Some(NOW),
+        }
+    );
+
+    // Tickets are redeemed once
+    let claims = Claims::new(
+        "user_1".into(),
+        "u@example.com".into(),
+        900,
+        &TestClock::at_timestamp(NOW),
+    );
+    store.store_ticket("ticket-1", &claims, 30).await?;
+    store.store_ticket("ticket-2", &claims, 30).await?;
+    let redeemed = store.redeem_ticket("ticket-1").await?;
+    assert_eq!(redeemed.map(|claims| claims.jti), Some(claims.jti.clone()));
+    assert!(store.redeem_ticket("ticket-1").await?.is_none());
+    assert!(store.redeem_ticket("ticket-unknown").await?.is_none());
+
+    // So are magic links
+    store.store_magic_link("link-1", &claims, 30).await?;
+    store.store_magic_link("link-2", &claims, 30).await?;
+    let redeemed = store.redeem_magic_link("link-1").await?;
+    assert_eq!(redeemed.map(|claims| claims.jti), Some(claims.jti.clone()));
+    assert!(store.redeem_magic_link("link-1").await?.is_none());
+
+    clock.advance(chrono::Duration::seconds(61));
+    assert!(!store.is_token_revoked("jti-1").await?);
+    assert!(store.list_revoked_tokens(10).await?.is_empty());
+    assert!(store.validate_refresh_token("token-d").await.is_err());
+    assert!(store.list_user_sessions("user_1").await?.is_empty());
+    assert_eq!(store.token_version("user_1").await?, 2);
+    assert!(store.redeem_ticket("ticket-2").await?.is_none());
+    assert!(store.redeem_magic_link("link-2").await?.is_none());
+    assert!(store.consume_token("jti-4", 30).await?);
+    Ok(())
+}
+
+// ---
+
+#[tokio::test]
+async fn memory_store_keeps_the_token_store_contract() -> Result<()> {
+    // ---
+    let clock = TestClock::at_timestamp(NOW);
+    check_store_contract(&MemoryStore::new(clock.shared()), &clock).await
+}
+
+#[test]
+fn revocation_status_revokes_blacklisted_outdated_and_cut_off_tokens() {
+    // ---
+    let claims = Claims::new(
+        "user_1".into(),
+        "u@example.com".into(),
+        900,
+        &TestClock::at_timestamp(NOW),
+    )
+    .with_version(Some(1));
+    let live = RevocationStatus {
+        blacklisted: false,
+        token_version: 1,
+        revoked_before: Some(NOW),
+    };
+    assert!(!live.revokes(&claims));
+
+    for revoked in [
+        RevocationStatus {
+            blacklisted: true,
+            ..live
+        },
+        RevocationStatus {
+            token_version: 2,
+            ..live
+        },
+        RevocationStatus {
+            revoked_before: Some(NOW + 1),
+            ..live
+        },
+    ] {
+        assert!(revoked.revokes(&claims), "{revoked:?}");
+    }
+}
+
+#[tokio::test]
+async fn stores_are_usable_through_the_store_handle() -> Result<()> {
+    // ---
+    let clock = TestClock::at_timestamp(NOW);
+    let store = Store::memory(clock.shared());
+    assert_eq!(store.backend().as_str(), "memory");
+
+    // Issued tokens are random and stored
+    let data = session_data("user_3");
+    let token = store.issue_refresh_token(&data, 3600).await?;
+    assert_ne!(token, store.issue_refresh_token(&data, 3600).await?);
+    assert_eq!(
+        store.validate_refresh_token(&token).await?.user_id,
+        "user_3"
+    );
+
+    check_store_contract(&store, &clock).await
+}
+
+#[tokio::test]
+async fn memory_store_sweeps_expired_entries() -> Result<()> {
+    // ---
+    let clock = TestClock::at_timestamp(NOW);
+    let store = MemoryStore::new(clock.shared());
+    let data = session_data("user_1");
+    store.store_refresh_token("short", &data, 10).await?;
+    store.store_refresh_token("long", &data, 3600).await?;
+    store.revoke_token("jti-1", 10).await?;
+    assert_eq!(store.sweep(), 0);
+
+    clock.advance(chrono::Duration::seconds(11));
+    assert_eq!(store.sweep(), 2);
+    assert_eq!(store.len(), 1);
+    assert_eq!(
+        store.validate_refresh_token("long").await?.user_id,
+        "user_1"
+    );
+
+    // The sweeper stops once the store is gone
+    let sweeper = store.spawn_sweeper(Duration::from_millis(1));
+    drop(store);
+    tokio::time::timeout(Duration::from_secs(5), sweeper).await??;
+    Ok(())
+}
+
+#[test]
+fn dev_mode_keeps_tokens_in_memory_outside_production() {
+    // ---
+    let config = jwt_config("redis://unused").dev().unwrap();
+    assert_eq!(config.store.backend.as_str(), "memory");
+    assert!(!config.is_stateless());
+
+    let mut config = jwt_config("redis://unused");
+    config.profile = Profile::Prod;
+    assert!(config.dev().is_err());
+}
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn postgres_store_keeps_the_token_store_contract() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let clock = TestClock::at_timestamp(NOW);
+    let store = PostgresStore::with_pool((*env.pool).clone(), clock.shared()).await?;
+    check_store_contract(&store, &clock).await
+}
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn postgres_store_keys_refresh_tokens_by_hash() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let clock = TestClock::at_timestamp(NOW);
+    let store = PostgresStore::with_pool((*env.pool).clone(), clock.shared()).await?;
+    let data = session_data("user_1");
+    store.store_refresh_token("token-a", &data, 3600).await?;
+    store.store_refresh_token("token-a2", &data, 3600).await?;
+    store.validate_refresh_token("token-a").await?;
+    store
+        .store_refresh_successor("token-a", "token-a2", 10)
+        .await?;
+
+    let hash = keys::refresh_token_hash("token-a");
+    for table in [
+        "jwt_refresh_tokens",
+        "jwt_used_refresh_tokens",
+        "jwt_refresh_successors",
+    ] {
+        let stored: Vec<String> = sqlx::query_scalar(&format!("SELECT token_hash FROM {table}"))
+            .fetch_all(&*env.pool)
+            .await?;
+        assert!(!stored.is_empty(), "{table}");
+        for key in stored {
+            assert!(keys::is_refresh_token_hash(&key), "{table}: {key}");
+            assert!(!key.contains("token-a"), "{table}: {key}");
+        }
+    }
+    let used: String = sqlx::query_scalar("SELECT token_hash FROM jwt_used_refresh_tokens")
+        .fetch_one(&*env.pool)
+        .await?;
+    assert_eq!(used, hash);
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires Docker (testcontainers)"]
+async fn postgres_store_cleanup_job_deletes_expired_rows() -> Result<()> {
+    // ---
+    let env = TestEnv::start().await?;
+    let clock = TestClock::at_timestamp(NOW);
+    let store = PostgresStore::with_pool((*env.pool).clone(), clock.shared()).await?;
+    // Migrating again is a no-op, beside oauth2-server's migrations
+    PostgresStore::with_pool((*env.pool).clone(), clock.shared()).await?;
+    oauth2_server::run_migrations(&env.pool).await?;
+
+    let data = session_data("user_1");
+    store.store_refresh_token("short", &data, 10).await?;
+    store.store_refresh_token("long", &data, 3600).await?;
+    store.revoke_token("jti-1", 10).await?;
+    assert_eq!(store.purge_expired().await?, 0);
+
+    // Expired rows are not served before the job runs, nor kept after
+    clock.advance(chrono::Duration::seconds(11));
+    assert!(!store.is_token_revoked("jti-1").await?);
+    let scheduler = jwt_service::scheduler(&Default::default(), &store, clock.shared());
+    scheduler.run_now(jwt_service::TOKEN_STORE_CLEANUP).await?;
+    assert_eq!(store.purge_expired().await?, 0);
+    assert_eq!(store.list_user_sessions("user_1").await?.len(), 1);
+    assert_eq!(
+        store.validate_refresh_token("long").await?.user_id,
+        "user_1"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn jwt_service_serves_refresh_and_revocation_from_memory() -> Result<()> {
+    // ---
+    let clock = TestClock::at_timestamp(NOW).shared();
+    let base = spawn_jwt_service_in_memory(jwt_config("redis://unused"), clock).await?;
+    let http = http_client();
+
+    let issued: Value = http
+        .post(format!("{base}/v1/auth/token"))
+        .json(&json!({ "user_id": "user_1", "email": "u@example.com" }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let access_token = issued["access_token"].as_str().unwrap();
+
+    let listed: Value = http
+        .get(format!("{base}/v1/auth/sessions/user_1"))
+        .bearer_auth(access_token)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    assert_eq!(listed["sessions"].as_array().unwrap().len(), 1, "{listed}");
+
+    // Rotation consumes the refresh token
+    let refresh = json!({ "refresh_token": issued["refresh_token"] });
+    let refreshed = http
+        .post(format!("{base}/v1/auth/refresh"))
+        .json(&refresh)
+        .send()
+        .await?;
+    assert_eq!(refreshed.status(), StatusCode::OK);
+    let replayed = http
+        .post(format!("{base}/v1/auth/refresh"))
+        .json(&refresh)
+        .send()
+        .await?;
+    assert_eq!(replayed.status(), StatusCode::UNAUTHORIZED);
+
+    // A revoked access token is refused
+    let revoked = http
+        .post(format!("{base}/v1/auth/revoke"))
+        .json(&json!({ "token": access_token }))
+        .send()
+        .await?;
+    assert_eq!(revoked.status(), StatusCode::OK);
+    let response = http
+        .get(format!("{base}/v1/protected"))
+        .bearer_auth(access_token)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    Ok(())
+}
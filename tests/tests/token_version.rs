@@ -0,0 +1,116 @@
+// tests/tests/token_version.rs
+
+//! Per-user token versions: access tokens carry the user's version as `ver`,
+//! and `POST /v1/auth/invalidate-user` bumps it, revoking every token issued
+//! to the user before without blacklisting each `jti` (in-memory store)
+
+use anyhow::Result;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use tokn_core::TestClock;
+use tokn_tests::{http_client, jwt_config, post_json, sign_in, spawn_jwt_service_in_memory};
+
+// ---
+
+const NOW: i64 = 1_700_000_000;
+
+// ---
+
+/// The claims `POST /v1/auth/validate` reports for `token`, or `None` if it
+/// is refused.
+async fn validate(base: &str, token: &Value) -> Result<Option<Value>> {
+    // ---
+    let response = http_client()
+        .post(format!("{base}/v1/auth/validate"))
+        .json(&json!({ "token": token }))
+        .send()
+        .await?;
+    if response.status() != StatusCode::OK {
+        return Ok(None);
+    }
+    let body: Value = response.json().await?;
+    Ok(Some(body["claims"].clone()))
+}
+
+/// Invalidate `user_id` at jwt-service at `base`, as the holder of
+/// `access_token`.
+async fn invalidate(base: &str, user_id: &str, access_token: &Value) -> Result<reqwest::Response> {
+    // ---
+    Ok(http_client()
+        .post(format!("{base}/v1/auth/invalidate-user"))
+        .bearer_auth(access_token.as_str().unwrap())
+        .json(&json!({ "user_id": user_id }))
+        .send()
+        .await?)
+}
+
+// ---
+
+#[tokio::test]
+async fn invalidating_a_user_revokes_every_earlier_token() -> Result<()> {
+    // ---
+    let clock = TestClock::at_timestamp(NOW);
+    let base = spawn_jwt_service_in_memory(jwt_config("redis://unused"), clock.shared()).await?;
+    let first = sign_in(&base, "user_1").await?;
+    let second = sign_in(&base, "user_1").await?;
+    let other = sign_in(&base, "user_2").await?;
+
+    let claims = validate(&base, &first["access_token"]).await?.unwrap();
+    assert_eq!(claims["ver"], 0, "{claims}");
+
+    let response = invalidate(&base, "user_1", &first["access_token"]).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: Value = response.json().await?;
+    assert_eq!(body["token_version"], 1, "{body}");
+    assert_eq!(body["sessions_ended"], 2, "{body}");
+
+    // Every token issued to the user before is refused, including for refresh
+    for issued in [&first, &second] {
+        assert!(validate(&base, &issued["access_token"]).await?.is_none());
+        let response = http_client()
+            .get(format!("{base}/v1/protected"))
+            .bearer_auth(issued["access_token"].as_str().unwrap())
+            .send()
+            .await?;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = http_client()
+            .post(format!("{base}/v1/auth/refresh"))
+            .json(&json!({ "refresh_token": issued["refresh_token"] }))
+            .send()
+            .await?;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    // Other users and new sign-ins are unaffected
+    assert!(validate(&base, &other["access_token"]).await?.is_some());
+    let again = sign_in(&base, "user_1").await?;
+    let claims = validate(&base, &again["access_token"]).await?.unwrap();
+    assert_eq!(claims["ver"], 1, "{claims}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn only_the_user_or_an_admin_can_invalidate() -> Result<()> {
+    // ---
+    let clock = TestClock::at_timestamp(NOW);
+    let base = spawn_jwt_service_in_memory(jwt_config("redis://unused"), clock.shared()).await?;
+    let victim = sign_in(&base, "user_1").await?;
+    let other = sign_in(&base, "user_2").await?;
+    let (status, admin) = post_json(
+        &base,
+        "/v1/auth/token",
+        json!({ "user_id": "admin_1", "email": "u@example.com", "scope": "admin" }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{admin}");
+
+    let response = invalidate(&base, "user_1", &other["access_token"]).await?;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert!(validate(&base, &victim["access_token"]).await?.is_some());
+
+    let response = invalidate(&base, "user_1", &admin["access_token"]).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(validate(&base, &victim["access_token"]).await?.is_none());
+    Ok(())
+}
@@ -0,0 +1,189 @@
+// tests/tests/tokn_auth.rs
+
+//! The `tokn-auth` layer and extractor on a plain router: secret and JWKS
+//! verifiers, the revocation check, and API keys (no containers needed)
+
+use anyhow::Result;
+use axum::{routing::get, Json, Router};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use tokn_auth::{AuthenticatedUser, JwtAuth, JwtAuthLayer, Verifier};
+use tokn_core::{generate_token, Claims, SystemClock};
+use tokn_tests::{http_client, serve, TEST_JWT_SECRET};
+
+// ---
+
+async fn whoami(user: AuthenticatedUser) -> String {
+    // ---
+    user.sub.clone()
+}
+
+/// Serve `/whoami` behind `auth`, returning the base URL.
+async fn protected_app(auth: JwtAuth) -> Result<String> {
+    // ---
+    let app = Router::new()
+        .route("/whoami", get(whoami))
+        .route_layer(JwtAuthLayer::new(auth.clone()))
+        .with_state(auth);
+    serve(app).await
+}
+
+/// GET `/whoami` on `base` with `token`, returning the status and body.
+async fn whoami_with(base: &str, token: Option<&str>) -> Result<(StatusCode, String)> {
+    // ---
+    let mut request = http_client().get(format!("{base}/whoami"));
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    let response = request.send().await?;
+    Ok((response.status(), response.text().await?))
+}
+
+fn token(secret: &str) -> Result<String> {
+    // ---
+    let claims = Claims::new("user_1".into(), "u@example.com".into(), 900, &SystemClock);
+    Ok(generate_token(&claims, secret)?)
+}
+
+// ---
+
+#[tokio::test]
+async fn secret_verifier_guards_routes() -> Result<()> {
+    // ---
+    let base = protected_app(JwtAuth::new(Verifier::secret(TEST_JWT_SECRET))).await?;
+
+    let (status, body) = whoami_with(&base, Some(&token(TEST_JWT_SECRET)?)).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, "user_1");
+
+    let (status, body) = whoami_with(&base, None).await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let problem: Value = serde_json::from_str(&body)?;
+    assert_eq!(problem["status"], 401, "{problem}");
+
+    let forged = token("Zr8Kd2Wq6Vn1Xt5Mb9Hc3Lp7Fj0Gs4Ya")?;
+    let (status, _) = whoami_with(&base, Some(&forged)).await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    Ok(())
+}
+
+#[tokio::test]
+async fn revocation_check_refuses_listed_tokens() -> Result<()> {
+    // ---
+    let revoked = JwtAuth::new(Verifier::secret(TEST_JWT_SECRET))
+        .check_revocation(|_jti| async { Ok::<_, std::io::Error>(true) });
+    let base = protected_app(revoked).await?;
+    let (status, body) = whoami_with(&base, Some(&token(TEST_JWT_SECRET)?)).await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert!(body.contains("Token has been revoked"), "{body}");
+
+    // A failing check never lets the token through
+    let unavailable = JwtAuth::new(Verifier::secret(TEST_JWT_SECRET))
+        .check_revocation(|_jti| async { Err::<bool, _>("connection refused") });
+    let base = protected_app(unavailable).await?;
+    let (status, _) = whoami_with(&base, Some(&token(TEST_JWT_SECRET)?)).await?;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    Ok(())
+}
+
+#[tokio::test]
+async fn api_key_check_stands_in_for_a_token() -> Result<()> {
+    // ---
+    let auth = JwtAuth::new(Verifier::secret(TEST_JWT_SECRET)).check_api_key(|key| async move {
+        let claims = (key == "tokn_service_key")
+            .then(|| Claims::new("svc_1".into(), String::new(), 60, &SystemClock));
+        Ok::<_, std::io::Error>(claims)
+    });
+    let base = protected_app(auth).await?;
+    let whoami_with_key = |key: &'static str| {
+        http_client()
+            .get(format!("{base}/whoami"))
+            .header("X-Api-Key", key)
+    };
+
+    let response = whoami_with_key("tokn_service_key").send().await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text().await?, "svc_1");
+
+    let response = whoami_with_key("tokn_unknown_key").send().await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert!(response.text().await?.contains("Invalid API key"));
+
+    // A bearer token, when sent too, is what counts
+    let response = whoami_with_key("tokn_service_key")
+        .bearer_auth(token(TEST_JWT_SECRET)?)
+        .send()
+        .await?;
+    assert_eq!(response.text().await?, "user_1");
+
+    // Without a check the header means nothing
+    let base = protected_app(JwtAuth::new(Verifier::secret(TEST_JWT_SECRET))).await?;
+    let response = http_client()
+        .get(format!("{base}/whoami"))
+        .header("X-Api-Key", "tokn_service_key")
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    Ok(())
+}
+
+#[tokio::test]
+async fn api_key_claims_are_checked_like_a_token() -> Result<()> {
+    // ---
+    let auth = JwtAuth::new(Verifier::secret(TEST_JWT_SECRET))
+        .audience("orders-api")
+        .check_claims_revocation(|claims| async move {
+            Ok::<_, std::io::Error>(claims.sub == "svc_revoked")
+        })
+        .check_api_key(|key| async move {
+            let claims = Claims::new(key.clone(), String::new(), 60, &SystemClock);
+            let audience = if key == "svc_elsewhere" {
+                "billing-api"
+            } else {
+                "orders-api"
+            };
+            Ok::<_, std::io::Error>(Some(claims.with_audience(vec![audience.into()])))
+        });
+    let base = protected_app(auth).await?;
+
+    for (key, expected) in [
+        ("svc_1", StatusCode::OK),
+        ("svc_elsewhere", StatusCode::UNAUTHORIZED),
+        ("svc_revoked", StatusCode::UNAUTHORIZED),
+    ] {
+        let response = http_client()
+            .get(format!("{base}/whoami"))
+            .header("X-Api-Key", key)
+            .send()
+            .await?;
+        assert_eq!(response.status(), expected, "{key}");
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn jwks_verifier_fetches_keys() -> Result<()> {
+    // ---
+    let k = URL_SAFE_NO_PAD.encode(TEST_JWT_SECRET);
+    let jwks = json!({ "keys": [{ "kty": "oct", "alg": "HS256", "k": k }] });
+    let issuer = serve(Router::new().route(
+        "/.well-known/jwks.json",
+        get(move || async move { Json(jwks) }),
+    ))
+    .await?;
+
+    let auth = JwtAuth::new(Verifier::jwks_url(format!(
+        "{issuer}/.well-known/jwks.json"
+    )));
+    let base = protected_app(auth).await?;
+    let (status, body) = whoami_with(&base, Some(&token(TEST_JWT_SECRET)?)).await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+
+    // Without keys nothing can be validated
+    let auth = JwtAuth::new(Verifier::jwks_url("http://127.0.0.1:9/jwks.json"));
+    let base = protected_app(auth).await?;
+    let (status, _) = whoami_with(&base, Some(&token(TEST_JWT_SECRET)?)).await?;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    Ok(())
+}
@@ -0,0 +1,211 @@
+// tests/tests/verify_email.rs
+
+//! Email verification: `POST /v1/auth/verify-email/request` issues a
+//! `verify_email` purpose token, returned to the caller or emailed, and
+//! `POST /v1/auth/verify-email/confirm` consumes it once; the token is never
+//! an access token (in-memory store)
+
+use anyhow::Result;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use tokn_core::{Claims, JwtKeys, TestClock, TokenError};
+use tokn_tests::{
+    http_client, jwt_config, jwt_state_in_memory, serve, RecordingMailer, TEST_JWT_SECRET,
+};
+
+// ---
+
+const NOW: i64 = 1_700_000_000;
+
+// ---
+
+/// Serve jwt-service from an in-memory store on `clock`, emailing tokens to
+/// `verify_email_url` through `mailer` when given, and return its base URL.
+async fn start(
+    clock: &TestClock,
+    verify_email_url: Option<&str>,
+    mailer: Option<&RecordingMailer>,
+) -> Result<String> {
+    // ---
+    let mut config = jwt_config("redis://unused");
+    config.jwt.verify_email_url = verify_email_url.map(str::to_string);
+    let mut state = jwt_state_in_memory(config, clock.shared())?;
+    if let Some(mailer) = mailer {
+        state.mail = mailer.mail();
+    }
+    serve(jwt_service::build_router(state)).await
+}
+
+/// Request a verification token for `user_1`'s address.
+async fn request_token(base: &str) -> Result<Value> {
+    // ---
+    let issued = http_client()
+        .post(format!("{base}/v1/auth/verify-email/request"))
+        .json(&json!({ "user_id": "user_1", "email": "u@example.com" }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(issued)
+}
+
+/// POST `token` to `path` on `base`, returning the response status and body.
+async fn post_token(base: &str, path: &str, token: &str) -> Result<(StatusCode, Value)> {
+    // ---
+    let response = http_client()
+        .post(format!("{base}{path}"))
+        .json(&json!({ "token": token }))
+        .send()
+        .await?;
+    Ok((response.status(), response.json().await?))
+}
+
+// ---
+
+#[tokio::test]
+async fn verification_tokens_are_confirmed_once() -> Result<()> {
+    // ---
+    let clock = TestClock::at_timestamp(NOW);
+    let base = start(&clock, None, None).await?;
+
+    let issued = request_token(&base).await?;
+    assert_eq!(issued["expires_in"], 86_400, "{issued}");
+    let token = issued["token"].as_str().unwrap();
+
+    let (status, confirmed) = post_token(&base, "/v1/auth/verify-email/confirm", token).await?;
+    assert_eq!(status, StatusCode::OK, "{confirmed}");
+    assert_eq!(
+        confirmed,
+        json!({ "user_id": "user_1", "email": "u@example.com", "verified": true })
+    );
+
+    let (status, body) = post_token(&base, "/v1/auth/verify-email/confirm", token).await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        body["detail"], "Verification token has already been used",
+        "{body}"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn verification_tokens_expire() -> Result<()> {
+    // ---
+    let clock = TestClock::at_timestamp(NOW);
+    let base = start(&clock, None, None).await?;
+    let issued = request_token(&base).await?;
+
+    clock.advance(chrono::Duration::seconds(86_400 + 120));
+    let (status, body) = post_token(
+        &base,
+        "/v1/auth/verify-email/confirm",
+        issued["token"].as_str().unwrap(),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["detail"], "Token has expired", "{body}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn verification_and_access_tokens_are_not_interchangeable() -> Result<()> {
+    // ---
+    let clock = TestClock::at_timestamp(NOW);
+    let base = start(&clock, None, None).await?;
+    let issued = request_token(&base).await?;
+    let verification = issued["token"].as_str().unwrap();
+
+    // A verification token is no access token
+    let (status, body) = post_token(&base, "/v1/auth/validate", verification).await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        body["detail"], "Token is not valid for this purpose",
+        "{body}"
+    );
+    let response = http_client()
+        .get(format!("{base}/v1/protected"))
+        .bearer_auth(verification)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    // Nor does an access token verify an address
+    let tokens: Value = http_client()
+        .post(format!("{base}/v1/auth/token"))
+        .json(&json!({ "user_id": "user_1", "email": "u@example.com" }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let access = tokens["access_token"].as_str().unwrap();
+    let (status, body) = post_token(&base, "/v1/auth/verify-email/confirm", access).await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        body["detail"], "Token is not valid for this purpose",
+        "{body}"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn verification_tokens_are_emailed_with_a_link_url() -> Result<()> {
+    // ---
+    let clock = TestClock::at_timestamp(NOW);
+    let recorder = RecordingMailer::default();
+    let base = start(
+        &clock,
+        Some("https://app.example.com/verify-email"),
+        Some(&recorder),
+    )
+    .await?;
+
+    // The caller never sees the token
+    let issued = request_token(&base).await?;
+    assert!(issued.get("token").is_none(), "{issued}");
+
+    let sent = recorder.wait_for(1).await;
+    assert_eq!(sent[0].to, "u@example.com");
+    assert!(sent[0].text.contains("24 hours"), "{}", sent[0].text);
+    let link = sent[0]
+        .text
+        .split_whitespace()
+        .find(|word| word.starts_with("https://app.example.com/verify-email?token="))
+        .expect("the email carries the link");
+    let token = link.rsplit('=').next().unwrap();
+
+    let (status, confirmed) = post_token(&base, "/v1/auth/verify-email/confirm", token).await?;
+    assert_eq!(status, StatusCode::OK, "{confirmed}");
+    Ok(())
+}
+
+#[test]
+fn purpose_tokens_verify_only_for_their_purpose() -> Result<()> {
+    // ---
+    let clock = TestClock::at_timestamp(NOW);
+    let keys = JwtKeys::hs256(TEST_JWT_SECRET);
+    let claims = Claims::new("user_1".into(), "u@example.com".into(), 900, &clock);
+    let access = keys.sign(&claims)?;
+    let verification = keys.sign(&claims.clone().with_purpose("verify_email"))?;
+
+    assert_eq!(
+        keys.verify_purpose(&verification, "verify_email", &clock)?
+            .purpose
+            .as_deref(),
+        Some("verify_email")
+    );
+    assert!(matches!(
+        keys.verify(&verification, &clock),
+        Err(TokenError::WrongPurpose)
+    ));
+    assert!(matches!(
+        keys.verify_purpose(&verification, "reset_password", &clock),
+        Err(TokenError::WrongPurpose)
+    ));
+    assert!(matches!(
+        keys.verify_purpose(&access, "verify_email", &clock),
+        Err(TokenError::WrongPurpose)
+    ));
+    Ok(())
+}
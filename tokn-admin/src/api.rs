@@ -0,0 +1,84 @@
+// tokn-admin/src/api.rs
+
+//! API mode: operate through the running services
+
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Value};
+
+// ---
+
+use crate::cli::{Args, Target};
+
+// ---
+
+pub async fn revoke_token(args: &Args, token: &str) -> Result<()> {
+    // ---
+    let url = format!("{}/v1/auth/revoke", args.jwt_url);
+    let response = reqwest::Client::new()
+        .post(&url)
+        .json(&json!({ "token": token }))
+        .send()
+        .await
+        .with_context(|| format!("Failed to reach jwt-service at {url}"))?;
+
+    let status = response.status();
+    if status == reqwest::StatusCode::NOT_FOUND {
+        bail!("{url} returned 404; jwt-service runs stateless and cannot revoke tokens");
+    }
+    if !status.is_success() {
+        let body: Value = response.json().await.unwrap_or(Value::Null);
+        match body["error"].as_str() {
+            Some(error) => bail!("{url} returned {status}: {error}"),
+            None => bail!("{url} returned {status}"),
+        }
+    }
+
+    println!("Token revoked");
+    Ok(())
+}
+
+// ---
+
+pub async fn reload(args: &Args, service: Target) -> Result<()> {
+    // ---
+    let token = args
+        .admin_token
+        .as_deref()
+        .context("ADMIN_TOKEN (or --admin-token) is required for reload")?;
+
+    let url = format!("{}/admin/reload", args.service_url(service));
+    let response = reqwest::Client::new()
+        .post(&url)
+        .bearer_auth(token)
+        .send()
+        .await
+        .with_context(|| format!("Failed to reach {url}"))?;
+
+    let status = response.status();
+    if status == reqwest::StatusCode::NOT_FOUND {
+        bail!("{url} returned 404; is ADMIN_TOKEN set on the service?");
+    }
+    if !status.is_success() {
+        let text = response.text().await.unwrap_or_default();
+        match text.trim() {
+            "" => bail!("{url} returned {status}"),
+            error => bail!("{url} returned {status}: {error}"),
+        }
+    }
+
+    let report: Value = response.json().await.context("Invalid reload response")?;
+    for change in report["applied"].as_array().into_iter().flatten() {
+        println!("applied: {}", change.as_str().unwrap_or_default());
+    }
+    for key in report["restart_required"].as_array().into_iter().flatten() {
+        println!("restart required: {}", key.as_str().unwrap_or_default());
+    }
+    if report["applied"].as_array().is_some_and(Vec::is_empty)
+        && report["restart_required"]
+            .as_array()
+            .is_some_and(Vec::is_empty)
+    {
+        println!("No changes");
+    }
+    Ok(())
+}
@@ -0,0 +1,257 @@
+// tokn-admin/src/cli.rs
+
+//! Command-line options
+
+use clap::{Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+use tokn_core::SigningAlgorithm;
+
+// ---
+
+/// Operator CLI for tokn: clients, users, sessions, and tokens.
+///
+/// By default commands go through the running services' HTTP APIs. With
+/// `--direct` they read and write Postgres (`DATABASE_URL`) and Redis
+/// (`REDIS_URL`) instead, for operations that have no API yet or when the
+/// services are down.
+#[derive(Debug, Parser)]
+#[command(name = "tokn-admin", version)]
+pub struct Args {
+    // ---
+    /// Operate on Postgres/Redis directly instead of the service APIs
+    #[arg(long, global = true)]
+    pub direct: bool,
+
+    /// jwt-service base URL
+    #[arg(
+        long,
+        global = true,
+        env = "TOKN_JWT_URL",
+        default_value = "http://127.0.0.1:8083"
+    )]
+    pub jwt_url: String,
+
+    /// oauth2-server base URL
+    #[arg(
+        long,
+        global = true,
+        env = "TOKN_OAUTH2_URL",
+        default_value = "http://127.0.0.1:8082"
+    )]
+    pub oauth2_url: String,
+
+    /// oauth2-client base URL
+    #[arg(
+        long,
+        global = true,
+        env = "TOKN_CLIENT_URL",
+        default_value = "http://127.0.0.1:8081"
+    )]
+    pub client_url: String,
+
+    /// Bearer token for `/admin` endpoints
+    #[arg(long, global = true, env = "ADMIN_TOKEN", hide_env_values = true)]
+    pub admin_token: Option<String>,
+
+    /// Postgres URL (--direct)
+    #[arg(long, global = true, env = "DATABASE_URL", hide_env_values = true)]
+    pub database_url: Option<String>,
+
+    /// Redis URL (--direct)
+    #[arg(
+        long,
+        global = true,
+        env = "REDIS_URL",
+        default_value = "redis://127.0.0.1:6379"
+    )]
+    pub redis_url: String,
+
+    /// jwt-service signing secret, to read a token's `jti` and expiry (--direct)
+    #[arg(long, global = true, env = "JWT_SECRET", hide_env_values = true)]
+    pub jwt_secret: Option<String>,
+
+    /// jwt-service public key (PEM file), used instead of the secret when
+    /// jwt-service signs with a key pair (--direct)
+    #[arg(long, global = true, env = "JWT_PUBLIC_KEY_PATH")]
+    pub jwt_public_key: Option<PathBuf>,
+
+    /// Algorithm of --jwt-public-key: RS256 (default), ES256, or EdDSA; or of
+    /// --jwt-secret: HS256 (default), HS384, or HS512
+    #[arg(long, global = true, env = "JWT_ALGORITHM")]
+    pub jwt_algorithm: Option<SigningAlgorithm>,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+// ---
+
+/// Top-level command.
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    // ---
+    /// Manage OAuth2 clients (--direct)
+    #[command(subcommand)]
+    Clients(ClientsCommand),
+
+    /// Manage users (--direct)
+    #[command(subcommand)]
+    Users(UsersCommand),
+
+    /// Inspect refresh-token sessions (--direct)
+    #[command(subcommand)]
+    Sessions(SessionsCommand),
+
+    /// Manage access tokens
+    #[command(subcommand)]
+    Tokens(TokensCommand),
+
+    /// Show recorded changes to clients and users, newest first (--direct)
+    History {
+        // ---
+        /// Only changes to this client
+        #[arg(long, conflicts_with = "user")]
+        client: Option<String>,
+
+        /// Only changes to this user (by user ID)
+        #[arg(long)]
+        user: Option<String>,
+
+        /// Most changes to show
+        #[arg(long, default_value_t = 50)]
+        limit: i64,
+    },
+
+    /// Reload a running service's configuration (requires ADMIN_TOKEN)
+    Reload {
+        // ---
+        /// Service to reload
+        #[arg(value_enum)]
+        service: Target,
+    },
+}
+
+// ---
+
+/// `clients` subcommands.
+#[derive(Debug, Subcommand)]
+pub enum ClientsCommand {
+    // ---
+    /// Register a client; prints its generated secret once
+    Create {
+        // ---
+        /// Client ID
+        client_id: String,
+
+        /// Registered redirect URI
+        #[arg(long)]
+        redirect_uri: String,
+    },
+
+    /// Replace a client's secret; prints the new secret once
+    ResetSecret {
+        // ---
+        /// Client ID
+        client_id: String,
+    },
+
+    /// Soft-delete a client and revoke its access tokens
+    Delete {
+        // ---
+        /// Client ID
+        client_id: String,
+    },
+
+    /// Restore a deleted client
+    Restore {
+        // ---
+        /// Client ID
+        client_id: String,
+    },
+}
+
+// ---
+
+/// `users` subcommands.
+#[derive(Debug, Subcommand)]
+pub enum UsersCommand {
+    // ---
+    /// Add a user; the password is read from the first line of stdin
+    Add {
+        // ---
+        /// Login name
+        username: String,
+
+        /// User ID (default: random UUID)
+        #[arg(long)]
+        user_id: Option<String>,
+    },
+
+    /// Soft-delete a user and revoke their access tokens
+    Delete {
+        // ---
+        /// User ID
+        user_id: String,
+    },
+
+    /// Restore a deleted user
+    Restore {
+        // ---
+        /// User ID
+        user_id: String,
+    },
+}
+
+// ---
+
+/// `sessions` subcommands.
+#[derive(Debug, Subcommand)]
+pub enum SessionsCommand {
+    // ---
+    /// List a user's live refresh tokens
+    List {
+        // ---
+        /// User ID
+        user_id: String,
+    },
+}
+
+// ---
+
+/// `tokens` subcommands.
+#[derive(Debug, Subcommand)]
+pub enum TokensCommand {
+    // ---
+    /// Revoke (blacklist) an access token until it expires
+    Revoke {
+        // ---
+        /// The JWT access token
+        token: String,
+    },
+}
+
+// ---
+
+/// Service targeted by `reload`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Target {
+    // ---
+    JwtService,
+    Oauth2Server,
+    Oauth2Client,
+}
+
+// ---
+
+impl Args {
+    // ---
+    /// Base URL of `service`.
+    pub fn service_url(&self, service: Target) -> &str {
+        // ---
+        match service {
+            Target::JwtService => &self.jwt_url,
+            Target::Oauth2Server => &self.oauth2_url,
+            Target::Oauth2Client => &self.client_url,
+        }
+    }
+}
@@ -0,0 +1,258 @@
+// tokn-admin/src/direct.rs
+
+//! `--direct` mode: operate on Postgres and Redis without the services
+
+use anyhow::{bail, Context, Result};
+use oauth2_server::Entity;
+use sqlx::PgPool;
+use std::io::BufRead;
+use tokn_core::{Clock, SigningAlgorithm};
+
+// ---
+
+use crate::cli::Args;
+
+// ---
+
+pub async fn create_client(args: &Args, client_id: &str, redirect_uri: &str) -> Result<()> {
+    // ---
+    let pool = connect_postgres(args).await?;
+    let secret = oauth2_server::generate_client_secret();
+
+    oauth2_server::create_client(&pool, &actor(), client_id, &secret, redirect_uri).await?;
+
+    println!("Created client '{client_id}'");
+    println!("Client secret (shown once): {secret}");
+    Ok(())
+}
+
+// ---
+
+pub async fn reset_client_secret(args: &Args, client_id: &str) -> Result<()> {
+    // ---
+    let pool = connect_postgres(args).await?;
+    let secret = oauth2_server::generate_client_secret();
+
+    if !oauth2_server::reset_client_secret(&pool, &actor(), client_id, &secret).await? {
+        bail!("No active client with ID '{client_id}'");
+    }
+
+    println!("Reset secret for client '{client_id}'; the old secret no longer works");
+    println!("Client secret (shown once): {secret}");
+    Ok(())
+}
+
+// ---
+
+pub async fn delete_client(args: &Args, client_id: &str) -> Result<()> {
+    // ---
+    let pool = connect_postgres(args).await?;
+
+    let Some(revoked) = oauth2_server::delete_client(&pool, &actor(), client_id).await? else {
+        bail!("No active client with ID '{client_id}'");
+    };
+
+    println!(
+        "Deleted client '{client_id}' and revoked {} access tokens",
+        revoked.len()
+    );
+    Ok(())
+}
+
+// ---
+
+pub async fn restore_client(args: &Args, client_id: &str) -> Result<()> {
+    // ---
+    let pool = connect_postgres(args).await?;
+
+    if !oauth2_server::restore_client(&pool, &actor(), client_id).await? {
+        bail!("No deleted client with ID '{client_id}'");
+    }
+
+    println!("Restored client '{client_id}'");
+    Ok(())
+}
+
+// ---
+
+pub async fn add_user(args: &Args, username: &str, user_id: Option<&str>) -> Result<()> {
+    // ---
+    let password = read_password()?;
+    let user_id = user_id.map_or_else(|| uuid::Uuid::new_v4().to_string(), str::to_string);
+    let pool = connect_postgres(args).await?;
+
+    oauth2_server::create_user(&pool, &actor(), &user_id, username, &password).await?;
+
+    println!("Created user '{username}' with ID {user_id}");
+    Ok(())
+}
+
+// ---
+
+pub async fn delete_user(args: &Args, user_id: &str) -> Result<()> {
+    // ---
+    let pool = connect_postgres(args).await?;
+
+    let Some(revoked) = oauth2_server::delete_user(&pool, &actor(), user_id).await? else {
+        bail!("No active user with ID '{user_id}'");
+    };
+
+    println!(
+        "Deleted user '{user_id}' and revoked {} access tokens",
+        revoked.len()
+    );
+    Ok(())
+}
+
+// ---
+
+pub async fn restore_user(args: &Args, user_id: &str) -> Result<()> {
+    // ---
+    let pool = connect_postgres(args).await?;
+
+    if !oauth2_server::restore_user(&pool, &actor(), user_id).await? {
+        bail!("No deleted user with ID '{user_id}'");
+    }
+
+    println!("Restored user '{user_id}'");
+    Ok(())
+}
+
+// ---
+
+pub async fn history(
+    args: &Args,
+    client: Option<&str>,
+    user: Option<&str>,
+    limit: i64,
+) -> Result<()> {
+    // ---
+    let (entity, entity_id) = match (client, user) {
+        (Some(client_id), _) => (Some(Entity::Client), Some(client_id)),
+        (None, Some(user_id)) => (Some(Entity::User), Some(user_id)),
+        (None, None) => (None, None),
+    };
+    let pool = connect_postgres(args).await?;
+    let changes = oauth2_server::list_changes(&pool, entity, entity_id, limit).await?;
+
+    if changes.is_empty() {
+        println!("No recorded changes");
+        return Ok(());
+    }
+    for change in changes {
+        let detail = match &change.field {
+            Some(field) => format!(
+                "  {field}: {} -> {}",
+                change.old_value.as_deref().unwrap_or("-"),
+                change.new_value.as_deref().unwrap_or("-")
+            ),
+            None => String::new(),
+        };
+        println!(
+            "{}  {}  {} {} {}{detail}",
+            change.changed_at.format("%Y-%m-%d %H:%M:%S"),
+            change.actor,
+            change.action,
+            change.entity,
+            change.entity_id
+        );
+    }
+    Ok(())
+}
+
+// ---
+
+pub async fn list_sessions(args: &Args, user_id: &str) -> Result<()> {
+    // ---
+    let mut redis = jwt_service::create_redis_client(&args.redis_url).await?;
+    let sessions = jwt_service::list_refresh_tokens(&mut redis, user_id).await?;
+
+    if sessions.is_empty() {
+        println!("No active sessions for user '{user_id}'");
+        return Ok(());
+    }
+    for session in sessions {
+        println!(
+            "{}  {}  expires in {}s",
+            session.token_hash, session.data.email, session.ttl_seconds
+        );
+    }
+    Ok(())
+}
+
+// ---
+
+pub async fn revoke_token(args: &Args, token: &str) -> Result<()> {
+    // ---
+    let keys = match (&args.jwt_public_key, &args.jwt_secret) {
+        (Some(path), _) => {
+            let pem = std::fs::read(path)
+                .with_context(|| format!("Failed to read public key {}", path.display()))?;
+            let algorithm = args.jwt_algorithm.unwrap_or(SigningAlgorithm::Rs256);
+            tokn_core::JwtKeys::from_public_pem(algorithm, &pem)?
+        }
+        (None, Some(secret)) => {
+            let algorithm = args.jwt_algorithm.unwrap_or(SigningAlgorithm::Hs256);
+            tokn_core::JwtKeys::hmac(algorithm, secret)?
+        }
+        (None, None) => bail!(
+            "JWT_SECRET (or --jwt-secret), or JWT_PUBLIC_KEY_PATH for a key pair, is required to revoke with --direct"
+        ),
+    };
+
+    // Only a token that still validates needs blacklisting; the blacklist entry
+    // lives exactly as long as the token would have
+    let clock = tokn_core::SystemClock;
+    let claims = keys.verify(token, &clock).context("Token is not valid")?;
+    let now = clock.timestamp() as usize;
+    if claims.exp <= now {
+        bail!("Token already expired, nothing to revoke");
+    }
+    let remaining = (claims.exp - now) as i64;
+
+    let mut redis = jwt_service::create_redis_client(&args.redis_url).await?;
+    jwt_service::revoke_token(&mut redis, &claims.jti, remaining).await?;
+
+    println!("Revoked token {} for user '{}'", claims.jti, claims.sub);
+    Ok(())
+}
+
+// ---
+
+async fn connect_postgres(args: &Args) -> Result<PgPool> {
+    // ---
+    let url = args
+        .database_url
+        .as_deref()
+        .context("DATABASE_URL (or --database-url) is required with --direct")?;
+
+    oauth2_server::create_pool(url)
+        .await
+        .context("Failed to connect to Postgres")
+}
+
+/// Actor recorded in the change history: the OS user running the command.
+fn actor() -> String {
+    // ---
+    let user = std::env::var("USER").unwrap_or_else(|_| "unknown".to_string());
+    format!("tokn-admin ({user})")
+}
+
+/// Read the password from the first line of stdin, so it never appears in
+/// shell history or the process list.
+fn read_password() -> Result<String> {
+    // ---
+    let mut line = String::new();
+    std::io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .context("Failed to read password from stdin")?;
+
+    let password = line.trim_end_matches(['\r', '\n']).to_string();
+    if password.is_empty() {
+        bail!(
+            "Empty password; pipe it on stdin, e.g. `read -s pw && echo \"$pw\" | tokn-admin ...`"
+        );
+    }
+    Ok(password)
+}
@@ -0,0 +1,99 @@
+// tokn-admin/src/main.rs
+
+//! tokn-admin - Operator CLI for clients, users, sessions, and tokens
+//!
+//! Replaces ad-hoc `psql` and `redis-cli` sessions for routine operations.
+//! Commands use the services' HTTP APIs by default; `--direct` goes to
+//! Postgres and Redis instead. Client, user, session, and history commands
+//! have no API yet and require `--direct`.
+//!
+//! # Example
+//!
+//! ```bash
+//! # Register a client (prints the generated secret once)
+//! tokn-admin --direct clients create my_app --redirect-uri https://app.example.com/callback
+//!
+//! # Add a user, password on stdin
+//! read -s pw && echo "$pw" | tokn-admin --direct users add alice
+//!
+//! # Soft-delete a client, then review who changed it
+//! tokn-admin --direct clients delete my_app
+//! tokn-admin --direct history --client my_app
+//!
+//! # List a user's refresh tokens, then revoke an access token
+//! tokn-admin --direct sessions list user_001
+//! tokn-admin tokens revoke eyJhbGciOiJIUzI1NiJ9...
+//!
+//! # Reload jwt-service configuration
+//! ADMIN_TOKEN=... tokn-admin reload jwt-service
+//! ```
+
+mod api;
+mod cli;
+mod direct;
+
+use anyhow::{bail, Result};
+use clap::Parser;
+
+// ---
+
+use cli::{Args, ClientsCommand, Command, SessionsCommand, TokensCommand, UsersCommand};
+
+// ---
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // ---
+    let _ = dotenvy::dotenv();
+    let args = Args::parse();
+
+    match &args.command {
+        Command::Clients(_)
+        | Command::Users(_)
+        | Command::Sessions(_)
+        | Command::History { .. }
+            if !args.direct =>
+        {
+            bail!("This command has no admin API yet; rerun with --direct")
+        }
+        Command::Reload { .. } if args.direct => {
+            bail!("reload acts on a running service; rerun without --direct")
+        }
+
+        Command::Clients(ClientsCommand::Create {
+            client_id,
+            redirect_uri,
+        }) => direct::create_client(&args, client_id, redirect_uri).await,
+        Command::Clients(ClientsCommand::ResetSecret { client_id }) => {
+            direct::reset_client_secret(&args, client_id).await
+        }
+        Command::Clients(ClientsCommand::Delete { client_id }) => {
+            direct::delete_client(&args, client_id).await
+        }
+        Command::Clients(ClientsCommand::Restore { client_id }) => {
+            direct::restore_client(&args, client_id).await
+        }
+        Command::Users(UsersCommand::Add { username, user_id }) => {
+            direct::add_user(&args, username, user_id.as_deref()).await
+        }
+        Command::Users(UsersCommand::Delete { user_id }) => {
+            direct::delete_user(&args, user_id).await
+        }
+        Command::Users(UsersCommand::Restore { user_id }) => {
+            direct::restore_user(&args, user_id).await
+        }
+        Command::History {
+            client,
+            user,
+            limit,
+        } => direct::history(&args, client.as_deref(), user.as_deref(), *limit).await,
+        Command::Sessions(SessionsCommand::List { user_id }) => {
+            direct::list_sessions(&args, user_id).await
+        }
+        Command::Tokens(TokensCommand::Revoke { token }) if args.direct => {
+            direct::revoke_token(&args, token).await
+        }
+        Command::Tokens(TokensCommand::Revoke { token }) => api::revoke_token(&args, token).await,
+        Command::Reload { service } => api::reload(&args, *service).await,
+    }
+}
@@ -0,0 +1,396 @@
+// tokn-auth/src/auth.rs
+
+use axum::extract::OriginalUri;
+use axum::http::header::{AUTHORIZATION, HOST};
+use axum::http::request::Parts;
+use axum::http::{Extensions, HeaderMap};
+use std::fmt::Display;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokn_core::{
+    authorization_token, check_audience, cookie_value, verify_dpop_proof, AuthHeaderError,
+    AuthScheme, Claims, DpopError, DpopProof, DpopRequest, SharedClock, SystemClock, DPOP_HEADER,
+};
+
+// ---
+
+use crate::dpop::SeenProofs;
+use crate::{AuthError, Verifier};
+
+// ---
+
+/// Header carrying a machine client's API key, accepted in place of an
+/// access token when [`JwtAuth::check_api_key`] is set.
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+// ---
+
+/// The future a revocation check returns: whether the token ID is revoked,
+/// or why that could not be determined.
+pub type RevocationFuture = Pin<Box<dyn Future<Output = Result<bool, String>> + Send>>;
+
+/// Decides from a token's claims whether it was revoked.
+type RevocationCheck = Arc<dyn Fn(Claims) -> RevocationFuture + Send + Sync>;
+
+/// Records a DPoP proof, reporting whether it was seen before.
+type ReplayCheck = Arc<dyn Fn(DpopProof) -> RevocationFuture + Send + Sync>;
+
+/// The future an API key lookup returns: the claims the key stands for,
+/// `None` for an unknown key, or why the lookup failed.
+pub type ApiKeyFuture = Pin<Box<dyn Future<Output = Result<Option<Claims>, String>> + Send>>;
+
+/// Resolves an API key to the claims it stands for.
+type ApiKeyCheck = Arc<dyn Fn(String) -> ApiKeyFuture + Send + Sync>;
+
+/// Observes refused requests (e.g. for an audit log).
+type RefusalHook = Arc<dyn Fn(&AuthError, &HeaderMap, &Extensions) + Send + Sync>;
+
+// ---
+
+/// Access-token validation shared by a service's protected routes.
+///
+/// A token is accepted when:
+/// 1. The request carries `Authorization: Bearer <token>`
+/// 2. Its signature verifies with the [`Verifier`]'s key, in that key's
+///    algorithm
+/// 3. It has not expired (checked against the service clock, with leeway)
+/// 4. Its `aud` claim lists the service's [audience](Self::audience), if
+///    one is set
+/// 5. The revocation check, if any, does not report it revoked
+///
+/// A DPoP-bound token (one with a `cnf.jkt` claim) must instead be sent as
+/// `Authorization: DPoP <token>` with a `DPoP` header carrying a fresh,
+/// unused proof for this request, signed by the key the token is bound to
+/// (RFC 9449 §7).
+///
+/// With [`check_api_key`](Self::check_api_key), a request without an
+/// `Authorization` header may instead send an `X-Api-Key` header, accepted
+/// for the claims the lookup returns if they pass checks 4 and 5. With [`cookie`](Self::cookie), a
+/// request without one may carry its bearer token in that cookie instead.
+///
+/// Cloning is cheap; clones share the verifier and checks.
+///
+/// # Example
+///
+/// ```no_run
+/// use tokn_auth::{JwtAuth, Verifier};
+///
+/// # async fn example(parts: axum::http::request::Parts) -> Result<(), tokn_auth::AuthError> {
+/// let auth = JwtAuth::new(Verifier::secret("Jx4q9Lr2vTz7Wm1Kp8Ns3Hd6Bf0Gc5Ye"));
+/// let claims = auth.authenticate(&parts).await?;
+/// println!("Request from {}", claims.sub);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct JwtAuth {
+    // ---
+    verifier: Verifier,
+    clock: SharedClock,
+    audience: Option<String>,
+    revocation: Option<RevocationCheck>,
+    dpop_replay: Option<ReplayCheck>,
+    api_keys: Option<ApiKeyCheck>,
+    cookie: Option<String>,
+    seen_proofs: Arc<SeenProofs>,
+    on_refused: Option<RefusalHook>,
+}
+
+impl JwtAuth {
+    // ---
+    /// Validate tokens with `verifier` against the system clock, without a
+    /// revocation check, remembering DPoP proofs in memory.
+    pub fn new(verifier: Verifier) -> Self {
+        // ---
+        Self {
+            verifier,
+            clock: SystemClock::shared(),
+            audience: None,
+            revocation: None,
+            dpop_replay: None,
+            api_keys: None,
+            cookie: None,
+            seen_proofs: Arc::default(),
+            on_refused: None,
+        }
+    }
+
+    /// Check expiry against `clock` instead of the system clock.
+    pub fn clock(mut self, clock: SharedClock) -> Self {
+        // ---
+        self.clock = clock;
+        self
+    }
+
+    /// Accept only tokens minted for `audience`, this service's name in the
+    /// `aud` claim, refusing tokens issued to other services (and tokens
+    /// without `aud`) with 401.
+    pub fn audience(mut self, audience: impl Into<String>) -> Self {
+        // ---
+        self.audience = Some(audience.into());
+        self
+    }
+
+    /// Refuse tokens whose `jti` `check` reports revoked (e.g. jwt-service's
+    /// Redis blacklist). A failing check refuses the request with 500 rather
+    /// than letting a possibly revoked token through.
+    pub fn check_revocation<F, Fut, E>(self, check: F) -> Self
+    where
+        F: Fn(String) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<bool, E>> + Send + 'static,
+        E: Display,
+    {
+        // ---
+        self.check_claims_revocation(move |claims| check(claims.jti))
+    }
+
+    /// Refuse tokens `check` reports revoked from their whole claims, for
+    /// checks beyond the `jti` (e.g. jwt-service's per-user token version).
+    /// Replaces any [`check_revocation`](Self::check_revocation); a failing
+    /// check refuses the request with 500.
+    pub fn check_claims_revocation<F, Fut, E>(mut self, check: F) -> Self
+    where
+        F: Fn(Claims) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<bool, E>> + Send + 'static,
+        E: Display,
+    {
+        // ---
+        self.revocation = Some(Arc::new(move |claims| -> RevocationFuture {
+            let check = check(claims);
+            Box::pin(async move { check.await.map_err(|e| e.to_string()) })
+        }));
+        self
+    }
+
+    /// Record DPoP proofs with `check`, which reports whether a proof's
+    /// `jti` was seen before (remembering it until
+    /// [`DpopProof::expires_at`]), instead of in this process's memory. Use
+    /// a shared store when several replicas accept the same tokens. A
+    /// failing check refuses the request with 500.
+    pub fn check_dpop_replay<F, Fut, E>(mut self, check: F) -> Self
+    where
+        F: Fn(DpopProof) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<bool, E>> + Send + 'static,
+        E: Display,
+    {
+        // ---
+        self.dpop_replay = Some(Arc::new(move |proof| -> RevocationFuture {
+            let check = check(proof);
+            Box::pin(async move { check.await.map_err(|e| e.to_string()) })
+        }));
+        self
+    }
+
+    /// Accept requests carrying an `X-Api-Key` header and no `Authorization`
+    /// header for the claims `check` resolves the key to, for machine
+    /// clients that cannot refresh access tokens. Keys `check` does not know
+    /// are refused with 401 and a failing check with 500. The returned
+    /// claims go through the same [audience](Self::audience) and revocation
+    /// checks as a token's, so a key stamped with its user's `ver` and `iat`
+    /// is revoked along with the user's tokens; expiry is not checked.
+    pub fn check_api_key<F, Fut, E>(mut self, check: F) -> Self
+    where
+        F: Fn(String) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Option<Claims>, E>> + Send + 'static,
+        E: Display,
+    {
+        // ---
+        self.api_keys = Some(Arc::new(move |key| -> ApiKeyFuture {
+            let check = check(key);
+            Box::pin(async move { check.await.map_err(|e| e.to_string()) })
+        }));
+        self
+    }
+
+    /// Accept a bearer token from cookie `name` when the request has no
+    /// `Authorization` header, for browsers that hold their access token in
+    /// an `HttpOnly` cookie (e.g. jwt-service's cookie mode). A DPoP-bound
+    /// token cannot be sent this way.
+    pub fn cookie(mut self, name: impl Into<String>) -> Self {
+        // ---
+        self.cookie = Some(name.into());
+        self
+    }
+
+    /// Call `hook` with every refusal and the refused request's headers and
+    /// extensions, after it is logged.
+    pub fn on_refused<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&AuthError, &HeaderMap, &Extensions) + Send + Sync + 'static,
+    {
+        // ---
+        self.on_refused = Some(Arc::new(hook));
+        self
+    }
+
+    // ---
+    /// Validate the access token of the request with these `parts`, and its
+    /// DPoP proof when the token is bound to a key, returning its claims. A
+    /// request with an API key and no `Authorization` header gets the claims
+    /// of the key instead, when [`check_api_key`](Self::check_api_key) is set,
+    /// and one with neither may send its token in the [`cookie`](Self::cookie).
+    ///
+    /// # Errors
+    ///
+    /// Returns the [`AuthError`] the request is refused with.
+    pub async fn authenticate(&self, parts: &Parts) -> Result<Claims, AuthError> {
+        // ---
+        let api_key = parts
+            .headers
+            .get(API_KEY_HEADER)
+            .filter(|_| !parts.headers.contains_key(AUTHORIZATION));
+
+        let result = match (&self.api_keys, api_key) {
+            (Some(check), Some(key)) => match key.to_str() {
+                Ok(key) => self.authenticate_api_key(check, key).await,
+                Err(_) => Err(AuthError::ApiKey),
+            },
+            _ => match authorization_token(&parts.headers) {
+                Ok((scheme, token)) => self.authenticate_token(scheme, token, parts).await,
+                Err(AuthHeaderError::Missing) => match self.cookie_token(&parts.headers) {
+                    Some(token) => {
+                        self.authenticate_token(AuthScheme::Bearer, token, parts)
+                            .await
+                    }
+                    None => Err(AuthHeaderError::Missing.into()),
+                },
+                Err(e) => Err(e.into()),
+            },
+        };
+
+        if let Err(e) = &result {
+            e.log();
+            if let Some(hook) = &self.on_refused {
+                hook(e, &parts.headers, &parts.extensions);
+            }
+        }
+        result
+    }
+
+    /// The token in the configured [`cookie`](Self::cookie), if any.
+    fn cookie_token<'a>(&self, headers: &'a HeaderMap) -> Option<&'a str> {
+        // ---
+        cookie_value(headers, self.cookie.as_deref()?)
+    }
+
+    /// Validate `token`, returning its claims. The DPoP binding is not
+    /// checked; see [`authenticate`](Self::authenticate).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::Token`] for a bad signature, algorithm, or
+    /// audience or an expired token, [`AuthError::Revoked`] for a revoked one, and
+    /// [`AuthError::RevocationUnavailable`] or
+    /// [`AuthError::KeysUnavailable`] when this cannot be decided.
+    pub async fn verify(&self, token: &str) -> Result<Claims, AuthError> {
+        // ---
+        let claims = self.verifier.verify(token, self.clock.as_ref()).await?;
+        self.check_claims(claims).await
+    }
+
+    // ---
+    /// Refuse `claims` meant for another audience or reported revoked,
+    /// whether they came from a token or an API key.
+    async fn check_claims(&self, claims: Claims) -> Result<Claims, AuthError> {
+        // ---
+        if let Some(audience) = &self.audience {
+            check_audience(&claims, audience)?;
+        }
+
+        if let Some(check) = &self.revocation {
+            match check(claims.clone()).await {
+                Ok(false) => {}
+                Ok(true) => return Err(AuthError::Revoked(Box::new(claims))),
+                Err(e) => return Err(AuthError::RevocationUnavailable(e)),
+            }
+        }
+        Ok(claims)
+    }
+
+    /// The claims `check` resolves API `key` to, once checked like a
+    /// token's.
+    async fn authenticate_api_key(
+        &self,
+        check: &ApiKeyCheck,
+        key: &str,
+    ) -> Result<Claims, AuthError> {
+        // ---
+        let claims = match check(key.to_string()).await {
+            Ok(Some(claims)) => claims,
+            Ok(None) => return Err(AuthError::ApiKey),
+            Err(e) => return Err(AuthError::ApiKeyUnavailable(e)),
+        };
+        self.check_claims(claims).await
+    }
+
+    /// Verify `token`, sent with `scheme`, and the proof its binding calls
+    /// for.
+    async fn authenticate_token(
+        &self,
+        scheme: AuthScheme,
+        token: &str,
+        parts: &Parts,
+    ) -> Result<Claims, AuthError> {
+        // ---
+        let claims = self.verify(token).await?;
+
+        let jkt = match (claims.dpop_jkt(), scheme) {
+            (None, AuthScheme::Bearer) => return Ok(claims),
+            (Some(jkt), AuthScheme::Dpop) => jkt,
+            // A bound token must not be downgraded to a bearer token (RFC 9449 §7.2)
+            _ => return Err(DpopError::SchemeMismatch.into()),
+        };
+
+        let proof = self.verify_proof(parts, token).await?;
+        if proof.jkt != jkt {
+            return Err(DpopError::KeyMismatch.into());
+        }
+        Ok(claims)
+    }
+
+    /// Verify the `DPoP` header of the request with these `parts`, sent with
+    /// `token`, and record the proof against replay.
+    async fn verify_proof(&self, parts: &Parts, token: &str) -> Result<DpopProof, AuthError> {
+        // ---
+        let proof = parts
+            .headers
+            .get(DPOP_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .ok_or(DpopError::Missing)?;
+
+        // Nested routers see the path below their prefix; the proof names it all
+        let path = parts
+            .extensions
+            .get::<OriginalUri>()
+            .map_or(parts.uri.path(), |uri| uri.path());
+        let host = parts
+            .headers
+            .get(HOST)
+            .and_then(|value| value.to_str().ok())
+            .or_else(|| parts.uri.authority().map(|authority| authority.as_str()));
+        let request = DpopRequest {
+            method: parts.method.as_str(),
+            host,
+            path,
+            access_token: Some(token),
+        };
+        let proof = verify_dpop_proof(proof, &request, self.clock.as_ref())?;
+
+        let replayed = match &self.dpop_replay {
+            Some(check) => check(proof.clone())
+                .await
+                .map_err(AuthError::RevocationUnavailable)?,
+            None => {
+                let now = self.clock.timestamp();
+                !self
+                    .seen_proofs
+                    .first_use(&proof.jti, proof.expires_at(), now)
+            }
+        };
+        if replayed {
+            return Err(DpopError::Replayed.into());
+        }
+        Ok(proof)
+    }
+}
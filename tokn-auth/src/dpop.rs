@@ -0,0 +1,34 @@
+// tokn-auth/src/dpop.rs
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+// ---
+
+/// DPoP proof IDs seen by this process, until their proofs expire.
+///
+/// The replay check [`JwtAuth`](crate::JwtAuth) falls back to; services with
+/// several replicas should share one through
+/// [`JwtAuth::check_dpop_replay`](crate::JwtAuth::check_dpop_replay).
+#[derive(Debug, Default)]
+pub(crate) struct SeenProofs {
+    // ---
+    /// Proof `jti` → when its proof expires (Unix timestamp)
+    seen: Mutex<HashMap<String, i64>>,
+}
+
+impl SeenProofs {
+    // ---
+    /// Record `jti`, valid until `expires_at`; `false` if it was already
+    /// recorded. Expired entries are dropped on the way.
+    pub(crate) fn first_use(&self, jti: &str, expires_at: i64, now: i64) -> bool {
+        // ---
+        let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+        seen.retain(|_, expiry| *expiry >= now);
+        if seen.contains_key(jti) {
+            return false;
+        }
+        seen.insert(jti.to_string(), expires_at);
+        true
+    }
+}
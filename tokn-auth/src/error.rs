@@ -0,0 +1,96 @@
+// tokn-auth/src/error.rs
+
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use tokn_core::{AuthHeaderError, Claims, DpopError, Problem, TokenError};
+
+// ---
+
+/// Why a request's access token was refused.
+#[derive(Debug, thiserror::Error)]
+pub enum AuthError {
+    // ---
+    /// No usable `Authorization: Bearer` (or `DPoP`) header.
+    #[error(transparent)]
+    Header(#[from] AuthHeaderError),
+
+    /// The token failed its signature, algorithm, expiry, or audience check.
+    #[error(transparent)]
+    Token(#[from] TokenError),
+
+    /// The token is bound to a DPoP key and the request's proof of
+    /// possession is missing, invalid, or replayed.
+    #[error(transparent)]
+    Dpop(#[from] DpopError),
+
+    /// The token is valid but has been revoked.
+    #[error("Token has been revoked")]
+    Revoked(Box<Claims>),
+
+    /// The revocation check failed, so the token cannot be trusted.
+    #[error("Failed to verify token status")]
+    RevocationUnavailable(String),
+
+    /// The key set could not be fetched from its JWKS URL.
+    #[error("Token signing keys are unavailable")]
+    KeysUnavailable(String),
+
+    /// The request's `X-Api-Key` is unknown or has been deleted.
+    #[error("Invalid API key")]
+    ApiKey,
+
+    /// The API key lookup failed, so the key cannot be trusted.
+    #[error("Failed to verify API key")]
+    ApiKeyUnavailable(String),
+}
+
+impl AuthError {
+    // ---
+    /// The response status: 401 for a token the client must replace, 500 or
+    /// 503 when this service cannot decide.
+    pub fn status(&self) -> StatusCode {
+        // ---
+        match self {
+            AuthError::Header(_)
+            | AuthError::Token(_)
+            | AuthError::Dpop(_)
+            | AuthError::Revoked(_)
+            | AuthError::ApiKey => StatusCode::UNAUTHORIZED,
+            AuthError::RevocationUnavailable(_) | AuthError::ApiKeyUnavailable(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+            AuthError::KeysUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+
+    pub(crate) fn log(&self) {
+        // ---
+        match self {
+            AuthError::Header(e) => tracing::debug!("Rejected Authorization header: {e}"),
+            AuthError::Token(e) => tracing::warn!("Token validation failed: {e:?}"),
+            AuthError::Dpop(e) => tracing::warn!("DPoP proof refused: {e}"),
+            AuthError::Revoked(claims) => tracing::warn!(
+                event = "revoked_token_used",
+                user_id = %claims.sub,
+                jti = %claims.jti,
+                "Revoked token attempted access"
+            ),
+            AuthError::RevocationUnavailable(e) => {
+                tracing::error!("Failed to check token revocation status: {e}")
+            }
+            AuthError::KeysUnavailable(e) => tracing::error!("Failed to fetch signing keys: {e}"),
+            AuthError::ApiKey => tracing::warn!("Unknown API key refused"),
+            AuthError::ApiKeyUnavailable(e) => tracing::error!("Failed to look up API key: {e}"),
+        }
+    }
+}
+
+impl IntoResponse for AuthError {
+    // ---
+    fn into_response(self) -> Response {
+        // ---
+        Problem::new(self.status())
+            .detail(self.to_string())
+            .into_response()
+    }
+}
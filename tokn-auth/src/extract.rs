@@ -0,0 +1,62 @@
+// tokn-auth/src/extract.rs
+
+use axum::extract::{FromRef, FromRequestParts};
+use axum::http::request::Parts;
+use std::ops::Deref;
+use tokn_core::Claims;
+
+// ---
+
+use crate::{AuthError, JwtAuth};
+
+// ---
+
+/// The validated claims of the request's access token, for handlers.
+///
+/// Behind a [`JwtAuthLayer`](crate::JwtAuthLayer) this reuses the claims
+/// the layer validated. On any other route it validates the access token
+/// (and any DPoP proof) itself with the router state's [`JwtAuth`], so the
+/// state must provide one (`JwtAuth: FromRef<S>`; a router without state can
+/// use `.with_state(auth)`).
+///
+/// ```no_run
+/// use tokn_auth::AuthenticatedUser;
+///
+/// async fn whoami(user: AuthenticatedUser) -> String {
+///     format!("{} <{}>", user.sub, user.email)
+/// }
+/// ```
+///
+/// # Errors
+///
+/// Rejects the request with the [`AuthError`] as problem details.
+#[derive(Debug, Clone)]
+pub struct AuthenticatedUser(pub Claims);
+
+impl Deref for AuthenticatedUser {
+    // ---
+    type Target = Claims;
+
+    fn deref(&self) -> &Claims {
+        // ---
+        &self.0
+    }
+}
+
+impl<S> FromRequestParts<S> for AuthenticatedUser
+where
+    JwtAuth: FromRef<S>,
+    S: Send + Sync,
+{
+    // ---
+    type Rejection = AuthError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        // ---
+        if let Some(claims) = parts.extensions.get::<Claims>() {
+            return Ok(Self(claims.clone()));
+        }
+
+        JwtAuth::from_ref(state).authenticate(parts).await.map(Self)
+    }
+}
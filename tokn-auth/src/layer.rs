@@ -0,0 +1,107 @@
+// tokn-auth/src/layer.rs
+
+use axum::extract::Request;
+use axum::response::{IntoResponse, Response};
+use std::convert::Infallible;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+
+// ---
+
+use crate::JwtAuth;
+
+// ---
+
+/// Tower layer requiring a valid access token on every request.
+///
+/// Requests [`JwtAuth`] accepts reach the inner service with the token's
+/// `Claims` in their extensions (read them with
+/// [`AuthenticatedUser`](crate::AuthenticatedUser) or
+/// `Extension<Claims>`); the rest are answered with the
+/// [`AuthError`](crate::AuthError) as problem details.
+///
+/// Apply it with `route_layer` so unmatched paths still answer 404:
+///
+/// ```no_run
+/// use axum::{routing::get, Router};
+/// use tokn_auth::{JwtAuth, JwtAuthLayer, Verifier};
+///
+/// let auth = JwtAuth::new(Verifier::secret("Jx4q9Lr2vTz7Wm1Kp8Ns3Hd6Bf0Gc5Ye"));
+/// let app: Router = Router::new()
+///     .route("/orders", get(|| async { "[]" }))
+///     .route_layer(JwtAuthLayer::new(auth));
+/// ```
+#[derive(Clone)]
+pub struct JwtAuthLayer {
+    // ---
+    auth: JwtAuth,
+}
+
+impl JwtAuthLayer {
+    // ---
+    /// Authenticate every request with `auth`.
+    pub fn new(auth: JwtAuth) -> Self {
+        // ---
+        Self { auth }
+    }
+}
+
+impl<S> Layer<S> for JwtAuthLayer {
+    // ---
+    type Service = JwtAuthService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        // ---
+        JwtAuthService {
+            inner,
+            auth: self.auth.clone(),
+        }
+    }
+}
+
+// ---
+
+/// Service produced by [`JwtAuthLayer`].
+#[derive(Clone)]
+pub struct JwtAuthService<S> {
+    // ---
+    inner: S,
+    auth: JwtAuth,
+}
+
+impl<S> Service<Request> for JwtAuthService<S>
+where
+    S: Service<Request, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    // ---
+    type Response = Response;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // ---
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        // ---
+        // Keep the instance that was polled ready; leave a fresh clone behind
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let auth = self.auth.clone();
+
+        Box::pin(async move {
+            let (mut parts, body) = req.into_parts();
+            match auth.authenticate(&parts).await {
+                Ok(claims) => {
+                    parts.extensions.insert(claims);
+                    inner.call(Request::from_parts(parts, body)).await
+                }
+                Err(e) => Ok(e.into_response()),
+            }
+        })
+    }
+}
@@ -0,0 +1,47 @@
+// tokn-auth/src/lib.rs
+
+//! Access-token authentication for axum routes in any tokn service
+//!
+//! [`JwtAuth`] validates the `Authorization: Bearer` token of a request the
+//! same way everywhere: signature and algorithm against a [`Verifier`] (an
+//! HS256 secret, a public key or key ring, or a JWKS fetched from a URL),
+//! expiry against the service clock, and, when given a checker, revocation.
+//! Tokens bound to a client key (RFC 9449 DPoP, a `cnf.jkt` claim) must also
+//! come with a proof of possession of that key. Machine clients may send an
+//! `X-Api-Key` header instead, when the service resolves keys with
+//! [`JwtAuth::check_api_key`].
+//! [`JwtAuthLayer`] applies it to a router, answering refusals with problem
+//! details, and [`AuthenticatedUser`] hands the validated claims to
+//! handlers.
+//!
+//! ```no_run
+//! use axum::{routing::get, Router};
+//! use tokn_auth::{AuthenticatedUser, JwtAuth, JwtAuthLayer, Verifier};
+//!
+//! async fn whoami(user: AuthenticatedUser) -> String {
+//!     user.sub.clone()
+//! }
+//!
+//! let auth = JwtAuth::new(Verifier::jwks_url("https://auth.example.com/.well-known/jwks.json"))
+//!     // Look `jti` up in a revocation list
+//!     .check_revocation(|_jti| async move { Ok::<_, std::io::Error>(false) });
+//! let app: Router = Router::new()
+//!     .route("/whoami", get(whoami))
+//!     .route_layer(JwtAuthLayer::new(auth.clone()))
+//!     .with_state(auth);
+//! ```
+
+mod auth;
+mod dpop;
+mod error;
+mod extract;
+mod layer;
+mod verifier;
+
+// ---
+
+pub use auth::{ApiKeyFuture, JwtAuth, RevocationFuture, API_KEY_HEADER};
+pub use error::AuthError;
+pub use extract::AuthenticatedUser;
+pub use layer::{JwtAuthLayer, JwtAuthService};
+pub use verifier::{JwksClient, Verifier, DEFAULT_JWKS_MAX_AGE};
@@ -0,0 +1,203 @@
+// tokn-auth/src/verifier.rs
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tokn_config::Reloadable;
+use tokn_core::{validate_token_with_jwks, Claims, Clock, JwkSet, JwtKeys, TokenError};
+
+// ---
+
+use crate::AuthError;
+
+// ---
+
+/// How long a fetched key set is used before it is fetched again.
+pub const DEFAULT_JWKS_MAX_AGE: Duration = Duration::from_secs(300);
+
+/// Shortest time between fetches prompted by an unknown `kid`, so tokens
+/// naming made-up keys cannot flood the JWKS endpoint.
+const MIN_JWKS_REFETCH: Duration = Duration::from_secs(10);
+
+/// Longest a JWKS fetch may take.
+const JWKS_FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+// ---
+
+/// Where the keys that verify access tokens come from.
+#[derive(Clone)]
+pub enum Verifier {
+    // ---
+    /// Keys held in process: an HS256 secret, a public key, or a rotation
+    /// ring. Built from a service's [`Reloadable`] keys, a reloaded ring
+    /// applies to the next request.
+    Keys(Reloadable<JwtKeys>),
+
+    /// A JSON Web Key Set published at a URL, fetched and cached.
+    Jwks(JwksClient),
+}
+
+impl Verifier {
+    // ---
+    /// Verify HS256 tokens with a shared secret (at least 32 bytes).
+    pub fn secret(secret: &str) -> Self {
+        // ---
+        Self::keys(JwtKeys::hs256(secret))
+    }
+
+    /// Verify with `keys`; verify-only keys (a public key alone) suffice.
+    pub fn keys(keys: JwtKeys) -> Self {
+        // ---
+        Verifier::Keys(Reloadable::new(keys))
+    }
+
+    /// Verify with the key set published at `url` (see [`JwksClient`]).
+    pub fn jwks_url(url: impl Into<String>) -> Self {
+        // ---
+        Verifier::Jwks(JwksClient::new(url))
+    }
+
+    // ---
+    /// Check `token`'s signature, algorithm, and expiry.
+    pub(crate) async fn verify(&self, token: &str, clock: &dyn Clock) -> Result<Claims, AuthError> {
+        // ---
+        match self {
+            Verifier::Keys(keys) => Ok(keys.get().verify(token, clock)?),
+            Verifier::Jwks(jwks) => jwks.verify(token, clock).await,
+        }
+    }
+}
+
+impl From<Reloadable<JwtKeys>> for Verifier {
+    // ---
+    fn from(keys: Reloadable<JwtKeys>) -> Self {
+        // ---
+        Verifier::Keys(keys)
+    }
+}
+
+// ---
+
+/// A JSON Web Key Set fetched from a URL and cached; cloning is cheap and
+/// clones share the cache.
+///
+/// The set is fetched on first use and again once it is older than
+/// [`DEFAULT_JWKS_MAX_AGE`] (see [`max_age`](Self::max_age)). A token whose
+/// `kid` the cached set lacks prompts an early fetch, at most every 10
+/// seconds, so keys added by a rotation are picked up at once. If a fetch
+/// fails the last set fetched stays in use.
+#[derive(Clone)]
+pub struct JwksClient {
+    // ---
+    url: Arc<str>,
+    http: reqwest::Client,
+    max_age: Duration,
+    cache: Arc<RwLock<Option<CachedKeys>>>,
+}
+
+#[derive(Clone)]
+struct CachedKeys {
+    // ---
+    keys: Arc<JwkSet>,
+    fetched_at: Instant,
+}
+
+impl JwksClient {
+    // ---
+    /// A client for the key set at `url`; nothing is fetched until the first
+    /// token is verified.
+    pub fn new(url: impl Into<String>) -> Self {
+        // ---
+        Self {
+            url: url.into().into(),
+            http: reqwest::Client::new(),
+            max_age: DEFAULT_JWKS_MAX_AGE,
+            cache: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// Fetch the key set again once it is older than `max_age`.
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        // ---
+        self.max_age = max_age;
+        self
+    }
+
+    // ---
+    /// Validate `token` with a key from the set.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::KeysUnavailable`] if no key set has been fetched
+    /// yet and the fetch fails, or the errors of
+    /// [`validate_token_with_jwks`].
+    pub async fn verify(&self, token: &str, clock: &dyn Clock) -> Result<Claims, AuthError> {
+        // ---
+        let cached = self.key_set(false).await?;
+        match validate_token_with_jwks(token, &cached.keys, clock) {
+            // The key may have been published since the set was fetched
+            Err(TokenError::UnknownKey) if cached.fetched_at.elapsed() >= MIN_JWKS_REFETCH => {
+                let refreshed = self.key_set(true).await?;
+                Ok(validate_token_with_jwks(token, &refreshed.keys, clock)?)
+            }
+            result => Ok(result?),
+        }
+    }
+
+    /// The cached key set, fetched first if it is missing or stale (or, with
+    /// `refetch`, not fetched within [`MIN_JWKS_REFETCH`]).
+    async fn key_set(&self, refetch: bool) -> Result<CachedKeys, AuthError> {
+        // ---
+        let fresh_for = if refetch {
+            MIN_JWKS_REFETCH
+        } else {
+            self.max_age
+        };
+        if let Some(cached) = self.cache.read().await.as_ref() {
+            if cached.fetched_at.elapsed() < fresh_for {
+                return Ok(cached.clone());
+            }
+        }
+
+        let mut cache = self.cache.write().await;
+        // Another request may have fetched while this one waited
+        if let Some(cached) = cache.as_ref() {
+            if cached.fetched_at.elapsed() < fresh_for {
+                return Ok(cached.clone());
+            }
+        }
+
+        match self.fetch().await {
+            Ok(keys) => {
+                let cached = CachedKeys {
+                    keys: Arc::new(keys),
+                    fetched_at: Instant::now(),
+                };
+                *cache = Some(cached.clone());
+                Ok(cached)
+            }
+            Err(e) => match cache.as_ref() {
+                Some(stale) => {
+                    tracing::warn!("Failed to refresh JWKS from {}: {e}", self.url);
+                    Ok(stale.clone())
+                }
+                None => Err(AuthError::KeysUnavailable(format!(
+                    "fetching {}: {e}",
+                    self.url
+                ))),
+            },
+        }
+    }
+
+    async fn fetch(&self) -> reqwest::Result<JwkSet> {
+        // ---
+        self.http
+            .get(&*self.url)
+            .timeout(JWKS_FETCH_TIMEOUT)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+}
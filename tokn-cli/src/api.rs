@@ -0,0 +1,122 @@
+// tokn-cli/src/api.rs
+
+//! API mode: operate through a running jwt-service
+
+use anyhow::{anyhow, bail, Context, Result};
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde_json::{json, Value};
+
+// ---
+
+use crate::cli::Args;
+use crate::MintRequest;
+
+// ---
+
+pub async fn mint(args: &Args, request: &MintRequest<'_>) -> Result<()> {
+    // ---
+    let url = format!("{}/v1/auth/token", args.jwt_url);
+    let mut builder = reqwest::Client::new().post(&url).json(&json!({
+        "user_id": request.user_id,
+        "email": request.email,
+        "roles": request.roles,
+        "scope": request.scope,
+        "custom_claims": request.custom_claims,
+    }));
+    if let Some(key) = &args.api_key {
+        builder = builder.header(jwt_service::ISSUER_KEY_HEADER, key);
+    }
+
+    let response = send(builder, &url).await?;
+    if response.status() == StatusCode::UNAUTHORIZED && args.api_key.is_none() {
+        bail!("{url} returned 401; set TOKN_API_KEY (or --api-key) if jwt-service requires an issuer key");
+    }
+    let issued: Value = success(response, &url)
+        .await?
+        .json()
+        .await
+        .context("Invalid token response")?;
+
+    println!("{}", serde_json::to_string_pretty(&issued)?);
+    Ok(())
+}
+
+// ---
+
+pub async fn verify(args: &Args, token: &str) -> Result<()> {
+    // ---
+    let url = format!("{}/v1/auth/validate", args.jwt_url);
+    let builder = reqwest::Client::new()
+        .post(&url)
+        .json(&json!({ "token": token }));
+
+    let response = send(builder, &url).await?;
+    if response.status() == StatusCode::UNAUTHORIZED {
+        let detail = problem_detail(response).await;
+        bail!(
+            "Token is not valid: {}",
+            detail.as_deref().unwrap_or("rejected")
+        );
+    }
+    let validated: Value = success(response, &url)
+        .await?
+        .json()
+        .await
+        .context("Invalid validation response")?;
+
+    println!("{}", serde_json::to_string_pretty(&validated["claims"])?);
+    Ok(())
+}
+
+// ---
+
+pub async fn revoke_refresh_token(args: &Args, refresh_token: &str) -> Result<()> {
+    // ---
+    // RFC 7009 mode: the only form of the endpoint that takes refresh tokens
+    let url = format!("{}/v1/auth/revoke", args.jwt_url);
+    let builder = reqwest::Client::new().post(&url).form(&[
+        ("token", refresh_token),
+        ("token_type_hint", "refresh_token"),
+    ]);
+
+    let response = send(builder, &url).await?;
+    if response.status() == StatusCode::NOT_FOUND {
+        bail!("{url} returned 404; jwt-service runs stateless and has no refresh tokens");
+    }
+    success(response, &url).await?;
+
+    // RFC 7009 answers the same whether or not the token existed
+    println!("Refresh token revoked, if it was live");
+    Ok(())
+}
+
+// ---
+
+/// Send `builder`'s request to `url`.
+async fn send(builder: RequestBuilder, url: &str) -> Result<Response> {
+    // ---
+    builder
+        .send()
+        .await
+        .with_context(|| format!("Failed to reach jwt-service at {url}"))
+}
+
+/// `response` if it succeeded, else an error with its problem detail.
+async fn success(response: Response, url: &str) -> Result<Response> {
+    // ---
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    Err(match problem_detail(response).await {
+        Some(detail) => anyhow!("{url} returned {status}: {detail}"),
+        None => anyhow!("{url} returned {status}"),
+    })
+}
+
+/// The `detail` of an RFC 7807 problem response, if it has one.
+async fn problem_detail(response: Response) -> Option<String> {
+    // ---
+    let body: Value = response.json().await.ok()?;
+    body["detail"].as_str().map(str::to_string)
+}
@@ -0,0 +1,133 @@
+// tokn-cli/src/cli.rs
+
+//! Command-line options
+
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use serde_json::Value;
+
+// ---
+
+/// Developer CLI for tokn: mint, decode, and verify access tokens, and
+/// revoke refresh tokens.
+///
+/// By default commands go through a running jwt-service's HTTP API. With
+/// `--direct` they sign and verify with jwt-service's own key configuration
+/// (`JWT_SECRET`, `JWT_PRIVATE_KEY_PATH`, `JWT_KEYS`, ...) and reach Redis
+/// (`REDIS_URL`) themselves, so no service needs to be running.
+#[derive(Debug, Parser)]
+#[command(name = "tokn-cli", version)]
+pub struct Args {
+    // ---
+    /// Use the signing keys and Redis directly instead of jwt-service
+    #[arg(long, global = true)]
+    pub direct: bool,
+
+    /// jwt-service base URL
+    #[arg(
+        long,
+        global = true,
+        env = "TOKN_JWT_URL",
+        default_value = "http://127.0.0.1:8083"
+    )]
+    pub jwt_url: String,
+
+    /// Issuer key (`X-Issuer-Key`) for `POST /v1/auth/token`, when jwt-service
+    /// requires one
+    #[arg(long, global = true, env = "TOKN_API_KEY", hide_env_values = true)]
+    pub api_key: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+// ---
+
+/// Top-level command.
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    // ---
+    /// Mint, decode, and verify access tokens
+    #[command(subcommand)]
+    Token(TokenCommand),
+
+    /// Manage refresh tokens
+    #[command(subcommand)]
+    Refresh(RefreshCommand),
+}
+
+// ---
+
+/// `token` subcommands.
+#[derive(Debug, Subcommand)]
+pub enum TokenCommand {
+    // ---
+    /// Issue an access token (and, through the API, a refresh token)
+    Mint {
+        // ---
+        /// User ID, carried in `sub`
+        user_id: String,
+
+        /// Email claim (default: `<user_id>@example.com`)
+        #[arg(long)]
+        email: Option<String>,
+
+        /// Role to grant; repeat for several
+        #[arg(long = "role")]
+        roles: Vec<String>,
+
+        /// Space-delimited scopes to grant
+        #[arg(long)]
+        scope: Option<String>,
+
+        /// Extra claim as KEY=VALUE; VALUE is read as JSON if it parses, else
+        /// as a string. Repeat for several
+        #[arg(long = "claim", value_parser = parse_claim)]
+        claims: Vec<(String, Value)>,
+
+        /// Lifetime in seconds (--direct; default: JWT_ACCESS_TOKEN_EXPIRY_SECONDS)
+        #[arg(long)]
+        ttl: Option<i64>,
+    },
+
+    /// Print a JWT's header and claims without verifying it
+    Decode {
+        // ---
+        /// The JWT
+        token: String,
+    },
+
+    /// Check an access token's signature and expiry, printing its claims;
+    /// through the API, also whether it was revoked
+    Verify {
+        // ---
+        /// The access token
+        token: String,
+    },
+}
+
+// ---
+
+/// `refresh` subcommands.
+#[derive(Debug, Subcommand)]
+pub enum RefreshCommand {
+    // ---
+    /// Revoke a refresh token, ending its session
+    Revoke {
+        // ---
+        /// The refresh token
+        refresh_token: String,
+    },
+}
+
+// ---
+
+/// Parse a `--claim KEY=VALUE` argument.
+fn parse_claim(arg: &str) -> Result<(String, Value)> {
+    // ---
+    let (key, value) = arg
+        .split_once('=')
+        .context("expected KEY=VALUE, e.g. tenant_id=acme")?;
+    let value = serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()));
+    Ok((key.to_string(), value))
+}
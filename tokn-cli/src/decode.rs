@@ -0,0 +1,52 @@
+// tokn-cli/src/decode.rs
+
+//! `token decode`: read a JWT without any key
+
+use anyhow::{bail, Context, Result};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde_json::{json, Value};
+use tokn_core::{Clock, SystemClock};
+
+// ---
+
+/// Print `token`'s header and claims, unverified, and say when it expires.
+pub fn decode(token: &str) -> Result<()> {
+    // ---
+    let token = token.trim();
+    if token.starts_with("v4.local.") || token.starts_with("v4.public.") {
+        bail!("Token is PASETO, not a JWT; use `token verify`");
+    }
+    let (header, claims) = match token.split('.').collect::<Vec<_>>().as_slice() {
+        [header, claims, _signature] => (segment(header, "header")?, segment(claims, "claims")?),
+        [_, _, _, _, _] => {
+            bail!("Token is encrypted (JWE); only jwt-service's JWT_ENCRYPTION_KEY can read it")
+        }
+        _ => bail!("Not a JWT: expected three dot-separated segments"),
+    };
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&json!({ "header": header, "claims": claims }))?
+    );
+
+    if let Some(exp) = claims["exp"].as_i64() {
+        let remaining = exp - SystemClock.timestamp();
+        if remaining > 0 {
+            eprintln!("Expires in {remaining}s");
+        } else {
+            eprintln!("Expired {}s ago", -remaining);
+        }
+    }
+    eprintln!("Signature not checked; use `token verify` to check it");
+    Ok(())
+}
+
+/// Decode the base64url JSON segment `name`.
+fn segment(encoded: &str, name: &str) -> Result<Value> {
+    // ---
+    let bytes = URL_SAFE_NO_PAD
+        .decode(encoded)
+        .with_context(|| format!("Token {name} is not base64url"))?;
+    serde_json::from_slice(&bytes).with_context(|| format!("Token {name} is not JSON"))
+}
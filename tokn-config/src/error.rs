@@ -0,0 +1,101 @@
+// tokn-config/src/error.rs
+
+use std::fmt;
+use std::path::PathBuf;
+use thiserror::Error;
+
+// ---
+
+/// A single missing or invalid configuration key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigProblem {
+    // ---
+    /// A required key was not provided by any layer.
+    Missing { key: String, env: Option<String> },
+
+    /// A key was provided but has the wrong type or fails a validation rule.
+    Invalid {
+        key: String,
+        env: Option<String>,
+        reason: String,
+    },
+}
+
+impl ConfigProblem {
+    // ---
+    /// The dotted config key at fault.
+    pub fn key(&self) -> &str {
+        // ---
+        match self {
+            ConfigProblem::Missing { key, .. } | ConfigProblem::Invalid { key, .. } => key,
+        }
+    }
+}
+
+// ---
+
+impl fmt::Display for ConfigProblem {
+    // ---
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // ---
+        let (key, env) = match self {
+            ConfigProblem::Missing { key, env } | ConfigProblem::Invalid { key, env, .. } => {
+                (key, env)
+            }
+        };
+
+        write!(f, "{key}")?;
+        if let Some(env) = env {
+            write!(f, " (env {env})")?;
+        }
+
+        match self {
+            ConfigProblem::Missing { .. } => write!(f, ": required but not set"),
+            ConfigProblem::Invalid { reason, .. } => write!(f, ": {reason}"),
+        }
+    }
+}
+
+// ---
+
+/// Errors returned by [`ConfigLoader::load`](crate::ConfigLoader::load).
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    // ---
+    /// The config file named by `TOKN_CONFIG` (or [`ConfigLoader::file`](crate::ConfigLoader::file)) does not exist.
+    #[error("Config file not found: {}", .0.display())]
+    FileNotFound(PathBuf),
+
+    /// The config file exists but has an unsupported extension.
+    #[error("Unsupported config file format: {} (expected .toml, .yaml, or .yml)", .0.display())]
+    UnsupportedFormat(PathBuf),
+
+    /// The config file could not be parsed.
+    #[error("Failed to parse config: {0}")]
+    Parse(#[source] Box<figment::Error>),
+
+    /// One or more keys are missing or invalid. Every problem is listed, not
+    /// just the first.
+    #[error("{}", render(.service, .problems))]
+    Invalid {
+        service: String,
+        problems: Vec<ConfigProblem>,
+    },
+}
+
+// ---
+
+fn render(service: &str, problems: &[ConfigProblem]) -> String {
+    // ---
+    let mut message = format!(
+        "Invalid {service} configuration ({} problem{}):",
+        problems.len(),
+        if problems.len() == 1 { "" } else { "s" }
+    );
+    for problem in problems {
+        message.push_str("\n  - ");
+        message.push_str(&problem.to_string());
+    }
+
+    message
+}
@@ -0,0 +1,30 @@
+// tokn-config/src/lib.rs
+
+//! Layered configuration loading for tokn services
+//!
+//! Every service resolves its settings the same way:
+//! defaults → optional TOML/YAML file (`TOKN_CONFIG`) → environment variables.
+//! Misconfiguration is reported all at once: a single [`ConfigError::Invalid`]
+//! lists every missing or invalid key, with the environment variable that sets it.
+//! Any declared variable may be read from a file instead (`JWT_SECRET_FILE`).
+//! Settings that may change at runtime are held in a [`Reloadable`].
+//!
+//! Secrets declared with [`ConfigLoader::secret`] are screened for weak values
+//! ([`secret_weakness`]); the deployment [`Profile`] (`TOKN_ENV`) decides
+//! whether a weak one is a warning (`dev`) or refuses startup (`staging`,
+//! `prod`), and whether production-only rules apply (`prod`).
+//! Secret values are held in a [`Secret`], zeroed on drop and redacted in `Debug`.
+
+mod error;
+mod loader;
+mod profile;
+mod reloadable;
+mod secret;
+
+// ---
+
+pub use error::{ConfigError, ConfigProblem};
+pub use loader::{ConfigLoader, CONFIG_FILE_ENV, FILE_ENV_SUFFIX};
+pub use profile::{Profile, PROFILE_ENV};
+pub use reloadable::Reloadable;
+pub use secret::{secret_weakness, Secret};
@@ -0,0 +1,431 @@
+// tokn-config/src/loader.rs
+
+use figment::{
+    error::Kind,
+    providers::{Env, Format, Serialized, Toml, Yaml},
+    value::Value,
+    Figment,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::{Path, PathBuf};
+
+// ---
+
+use crate::{secret_weakness, ConfigError, ConfigProblem, Profile, PROFILE_ENV};
+
+// ---
+
+/// Environment variable naming an optional TOML/YAML config file.
+pub const CONFIG_FILE_ENV: &str = "TOKN_CONFIG";
+
+/// Suffix of the variable naming a file that holds a declared variable's
+/// value: `JWT_SECRET_FILE` for `JWT_SECRET`.
+pub const FILE_ENV_SUFFIX: &str = "_FILE";
+
+// ---
+
+type Check = Box<dyn Fn(&Figment) -> Option<ConfigProblem>>;
+
+// ---
+
+/// Layered configuration loader: defaults → config file → environment.
+///
+/// Each service declares its keys once, as dotted paths into its `Config`
+/// struct (`server.port`), together with the environment variable that
+/// overrides it (`JWT_SERVICE_PORT`). [`load`](Self::load) then checks every
+/// key and rule before deserializing, so a misconfigured deployment reports
+/// all of its problems in one error.
+///
+/// # Layers
+///
+/// 1. Defaults given to [`optional`](Self::optional)
+/// 2. The file named by `TOKN_CONFIG` (or [`file`](Self::file)), if any;
+///    format chosen by extension (`.toml`, `.yaml`, `.yml`)
+/// 3. The declared environment variables (a `.env` file is loaded first)
+///
+/// Every declared variable can instead be read from a file named by the same
+/// variable with a `_FILE` suffix (`JWT_SECRET_FILE=/run/secrets/jwt`), so
+/// Docker and Kubernetes secrets can be mounted without exposing them in the
+/// environment. A trailing newline is dropped; setting both variables, or
+/// naming an unreadable file, is reported like any other invalid key.
+///
+/// Every loader also declares `profile` (env `TOKN_ENV`, default `dev`; see
+/// [`Profile`]), which decides how strictly [`secret`](Self::secret),
+/// [`prod_rule`](Self::prod_rule), and [`forbid_in_prod`](Self::forbid_in_prod)
+/// are enforced.
+///
+/// # Example
+///
+/// ```no_run
+/// # #[derive(serde::Deserialize)] struct Config;
+/// let config: Config = tokn_config::ConfigLoader::new("jwt-service")
+///     .optional("server.port", "JWT_SERVICE_PORT", 8083u16)
+///     .required::<String>("jwt.secret", "JWT_SECRET")
+///     .rule("jwt.secret", |secret: &String| {
+///         if secret.len() >= 32 { Ok(()) } else { Err("must be at least 32 characters".into()) }
+///     })
+///     .load()?;
+/// # Ok::<(), tokn_config::ConfigError>(())
+/// ```
+pub struct ConfigLoader {
+    // ---
+    service: String,
+    file: Option<PathBuf>,
+    defaults: Figment,
+    env: Vec<(&'static str, &'static str)>,
+    checks: Vec<Check>,
+}
+
+// ---
+
+impl ConfigLoader {
+    // ---
+    /// Create a loader for `service` (used in error messages).
+    ///
+    /// Loads `.env` and picks up the config file path from `TOKN_CONFIG`.
+    pub fn new(service: &str) -> Self {
+        // ---
+        dotenvy::dotenv().ok();
+
+        Self {
+            service: service.to_string(),
+            file: std::env::var_os(CONFIG_FILE_ENV)
+                .filter(|path| !path.is_empty())
+                .map(PathBuf::from),
+            defaults: Figment::new(),
+            env: Vec::new(),
+            checks: Vec::new(),
+        }
+        .optional("profile", PROFILE_ENV, Profile::Dev)
+    }
+
+    /// Read this config file instead of the one named by `TOKN_CONFIG`.
+    pub fn file(mut self, path: impl AsRef<Path>) -> Self {
+        // ---
+        self.file = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Declare a key that must be provided by the file or environment.
+    pub fn required<T: DeserializeOwned>(mut self, key: &'static str, env: &'static str) -> Self {
+        // ---
+        self.env.push((env, key));
+        self.checks
+            .push(Box::new(move |figment| match figment.find_value(key) {
+                Err(_) => Some(ConfigProblem::Missing {
+                    key: key.to_string(),
+                    env: Some(env.to_string()),
+                }),
+                Ok(_) => type_check::<T>(figment, key, env),
+            }));
+        self
+    }
+
+    /// Declare a key with a default value.
+    pub fn optional<T>(mut self, key: &'static str, env: &'static str, default: T) -> Self
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        // ---
+        self.defaults = self.defaults.merge(Serialized::default(key, default));
+        self.env.push((env, key));
+        self.checks
+            .push(Box::new(move |figment| type_check::<T>(figment, key, env)));
+        self
+    }
+
+    /// Declare a key with no default: absent unless the file or environment
+    /// sets it (for `Option` fields in the config struct).
+    pub fn key<T: DeserializeOwned>(mut self, key: &'static str, env: &'static str) -> Self {
+        // ---
+        self.env.push((env, key));
+        self.checks
+            .push(Box::new(move |figment| type_check::<T>(figment, key, env)));
+        self
+    }
+
+    /// Add a validation rule for `key`. Skipped when the key is missing or has
+    /// the wrong type (those are already reported).
+    pub fn rule<T, F>(mut self, key: &'static str, check: F) -> Self
+    where
+        T: DeserializeOwned,
+        F: Fn(&T) -> Result<(), String> + 'static,
+    {
+        // ---
+        let env = self.env_for(key);
+
+        self.checks.push(Box::new(move |figment| {
+            let value = figment.extract_inner::<T>(key).ok()?;
+            check(&value).err().map(|reason| ConfigProblem::Invalid {
+                key: key.to_string(),
+                env: env.clone(),
+                reason,
+            })
+        }));
+        self
+    }
+
+    /// Mark `key` as a secret and check it with [`secret_weakness`]. A weak
+    /// value, or one left at the default given to [`optional`](Self::optional),
+    /// is an error under the `staging` and `prod` profiles and a logged
+    /// warning under `dev`. Skipped when the key is missing.
+    pub fn secret(self, key: &'static str) -> Self {
+        // ---
+        self.screen_secret(key, None)
+    }
+
+    /// Mark `key` as a secret like [`secret`](Self::secret), but accept a
+    /// weak value under every profile while the boolean key `allow` is true,
+    /// logging a warning that names the override. Only the weakness check is
+    /// waived; rules added for `key` still apply.
+    pub fn secret_unless(self, key: &'static str, allow: &'static str) -> Self {
+        // ---
+        self.screen_secret(key, Some(allow))
+    }
+
+    /// Add the weakness check of [`secret`](Self::secret) and
+    /// [`secret_unless`](Self::secret_unless).
+    fn screen_secret(mut self, key: &'static str, allow: Option<&'static str>) -> Self {
+        // ---
+        let env = self.env_for(key);
+        // The switch, and how to name it in the warning
+        let allow = allow.map(|allow| {
+            let name = self.env_for(allow).unwrap_or_else(|| allow.to_string());
+            (allow, name)
+        });
+        let default = self.defaults.extract_inner::<String>(key).ok();
+
+        self.checks.push(Box::new(move |figment| {
+            let value = figment.extract_inner::<String>(key).ok()?;
+            let reason = if default.as_ref() == Some(&value) {
+                "is the built-in default".to_string()
+            } else {
+                secret_weakness(&value)?
+            };
+            let env_note = env
+                .as_deref()
+                .map(|env| format!(" (env {env})"))
+                .unwrap_or_default();
+
+            let allowed_by = allow
+                .as_ref()
+                .filter(|(allow, _)| figment.extract_inner::<bool>(allow).unwrap_or(false));
+            if let Some((_, allow_env)) = allowed_by {
+                tracing::warn!(
+                    "INSECURE: accepting weak secret {key}{env_note} because \
+                     {allow_env}=true: {reason}; never set {allow_env} outside local development"
+                );
+                return None;
+            }
+
+            if profile(figment).is_strict() {
+                return Some(ConfigProblem::Invalid {
+                    key: key.to_string(),
+                    env: env.clone(),
+                    reason: format!("is a weak secret: {reason}"),
+                });
+            }
+
+            tracing::warn!(
+                "Weak secret {key}{env_note}: {reason}; refused when {PROFILE_ENV} is staging or prod"
+            );
+            None
+        }));
+        self
+    }
+
+    /// Add a validation rule for `key` that applies only under the `prod`
+    /// profile, such as requiring TLS. Skipped, like [`rule`](Self::rule),
+    /// when the key is missing or has the wrong type.
+    pub fn prod_rule<T, F>(mut self, key: &'static str, check: F) -> Self
+    where
+        T: DeserializeOwned,
+        F: Fn(&T) -> Result<(), String> + 'static,
+    {
+        // ---
+        let env = self.env_for(key);
+
+        self.checks.push(Box::new(move |figment| {
+            if !profile(figment).is_prod() {
+                return None;
+            }
+            let value = figment.extract_inner::<T>(key).ok()?;
+            check(&value).err().map(|reason| ConfigProblem::Invalid {
+                key: key.to_string(),
+                env: env.clone(),
+                reason: format!("{reason} when {PROFILE_ENV}=prod"),
+            })
+        }));
+        self
+    }
+
+    /// Refuse the boolean switch `key` being on under the `prod` profile, for
+    /// development conveniences (debug endpoints, open sign-up) that must
+    /// never reach production. Allowed under `dev` and `staging`.
+    pub fn forbid_in_prod(self, key: &'static str) -> Self {
+        // ---
+        self.prod_rule(key, |enabled: &bool| {
+            if *enabled {
+                Err("must be off".to_string())
+            } else {
+                Ok(())
+            }
+        })
+    }
+
+    /// Merge all layers, run every check, and deserialize into `C`.
+    ///
+    /// # Errors
+    ///
+    /// - [`ConfigError::FileNotFound`] / [`ConfigError::UnsupportedFormat`] for a bad config file path
+    /// - [`ConfigError::Parse`] if the config file is malformed
+    /// - [`ConfigError::Invalid`] listing every missing or invalid key
+    pub fn load<C: DeserializeOwned>(self) -> Result<C, ConfigError> {
+        // ---
+        let (figment, mut problems) = self.figment()?;
+
+        // Surface file syntax errors before per-key checks (which would
+        // otherwise all fail with the same parse error)
+        figment
+            .extract::<figment::value::Value>()
+            .map_err(|e| ConfigError::Parse(Box::new(e)))?;
+
+        // ---
+        let file_problems: Vec<String> = problems.iter().map(|p| p.key().to_string()).collect();
+        for check in &self.checks {
+            if let Some(problem) = check(&figment) {
+                // A key whose `_FILE` variable failed is already reported
+                let unread = matches!(problem, ConfigProblem::Missing { .. })
+                    && file_problems.iter().any(|key| key == problem.key());
+                if !unread && !problems.contains(&problem) {
+                    problems.push(problem);
+                }
+            }
+        }
+        if !problems.is_empty() {
+            return Err(ConfigError::Invalid {
+                service: self.service,
+                problems,
+            });
+        }
+
+        // ---
+        figment.extract().map_err(|e| ConfigError::Invalid {
+            service: self.service,
+            problems: vec![ConfigProblem::Invalid {
+                key: e.path.join("."),
+                env: None,
+                reason: e.kind.to_string(),
+            }],
+        })
+    }
+
+    // ---
+    /// Environment variable declared for `key`, if any.
+    fn env_for(&self, key: &str) -> Option<String> {
+        // ---
+        self.env
+            .iter()
+            .find(|(_, k)| *k == key)
+            .map(|(env, _)| env.to_string())
+    }
+
+    /// Merge every layer, returning problems with `_FILE` variables
+    /// alongside.
+    fn figment(&self) -> Result<(Figment, Vec<ConfigProblem>), ConfigError> {
+        // ---
+        let mut figment = self.defaults.clone();
+
+        if let Some(path) = &self.file {
+            if !path.exists() {
+                return Err(ConfigError::FileNotFound(path.clone()));
+            }
+            figment = match path.extension().and_then(|ext| ext.to_str()) {
+                Some("toml") => figment.merge(Toml::file_exact(path)),
+                Some("yaml" | "yml") => figment.merge(Yaml::file_exact(path)),
+                _ => return Err(ConfigError::UnsupportedFormat(path.clone())),
+            };
+        }
+
+        // ---
+        let mapping = self.env.clone();
+        let env = Env::raw().filter_map(move |name| {
+            mapping
+                .iter()
+                .find(|(env, _)| name == *env)
+                .map(|(_, key)| (*key).into())
+        });
+
+        let (files, problems) = self.env_files();
+        Ok((figment.merge(env).merge(files), problems))
+    }
+
+    /// Values of declared variables read from the files named by their
+    /// `_FILE` variants.
+    fn env_files(&self) -> (Figment, Vec<ConfigProblem>) {
+        // ---
+        let mut files = Figment::new();
+        let mut problems = Vec::new();
+
+        for (env, key) in &self.env {
+            let file_env = format!("{env}{FILE_ENV_SUFFIX}");
+            let Some(path) = std::env::var_os(&file_env).filter(|path| !path.is_empty()) else {
+                continue;
+            };
+            let problem = |reason: String| ConfigProblem::Invalid {
+                key: key.to_string(),
+                env: Some(file_env.clone()),
+                reason,
+            };
+
+            if std::env::var_os(env).is_some() {
+                problems.push(problem(format!("conflicts with {env}; set only one")));
+                continue;
+            }
+            match std::fs::read_to_string(&path) {
+                Ok(contents) => {
+                    // Parsed like an environment value, so numbers and
+                    // booleans keep their types
+                    let value: Value = contents
+                        .trim_end_matches(['\r', '\n'])
+                        .parse()
+                        .unwrap_or_else(|never| match never {});
+                    files = files.merge(Serialized::default(key, value));
+                }
+                Err(e) => problems.push(problem(format!(
+                    "cannot read {}: {e}",
+                    Path::new(&path).display()
+                ))),
+            }
+        }
+
+        (files, problems)
+    }
+}
+
+// ---
+
+/// The profile being loaded; `dev` when it is invalid (already reported).
+fn profile(figment: &Figment) -> Profile {
+    // ---
+    figment
+        .extract_inner::<Profile>("profile")
+        .unwrap_or_default()
+}
+
+fn type_check<T: DeserializeOwned>(
+    figment: &Figment,
+    key: &'static str,
+    env: &'static str,
+) -> Option<ConfigProblem> {
+    // ---
+    match figment.extract_inner::<T>(key) {
+        Ok(_) => None,
+        Err(e) if matches!(e.kind, Kind::MissingField(_)) => None,
+        Err(e) => Some(ConfigProblem::Invalid {
+            key: key.to_string(),
+            env: Some(env.to_string()),
+            reason: e.kind.to_string(),
+        }),
+    }
+}
@@ -0,0 +1,72 @@
+// tokn-config/src/profile.rs
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// ---
+
+/// Environment variable selecting the deployment [`Profile`].
+pub const PROFILE_ENV: &str = "TOKN_ENV";
+
+// ---
+
+/// Deployment profile, from `TOKN_ENV` (`dev`, `staging`, or `prod`; default
+/// `dev`).
+///
+/// Decides which questionable settings are tolerated with a warning and which
+/// refuse startup:
+///
+/// | Setting                                   | `dev`   | `staging` | `prod`  |
+/// |-------------------------------------------|---------|-----------|---------|
+/// | Weak or built-in default secret           | warning | refused   | refused |
+/// | [`prod_rule`] (e.g. no TLS)               | allowed | allowed   | refused |
+/// | [`forbid_in_prod`] switch turned on       | allowed | allowed   | refused |
+///
+/// [`prod_rule`]: crate::ConfigLoader::prod_rule
+/// [`forbid_in_prod`]: crate::ConfigLoader::forbid_in_prod
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Profile {
+    // ---
+    /// Local development: weak settings are logged, not fatal.
+    #[default]
+    #[serde(alias = "development")]
+    Dev,
+
+    /// Pre-production: real secrets, but development conveniences allowed.
+    #[serde(alias = "stage")]
+    Staging,
+
+    /// Production: weak settings are configuration errors.
+    #[serde(alias = "production")]
+    Prod,
+}
+
+// ---
+
+impl Profile {
+    // ---
+    /// Whether this is the production profile.
+    pub fn is_prod(self) -> bool {
+        // ---
+        self == Profile::Prod
+    }
+
+    /// Whether weak secrets are refused (`staging` and `prod`).
+    pub fn is_strict(self) -> bool {
+        // ---
+        self != Profile::Dev
+    }
+}
+
+impl fmt::Display for Profile {
+    // ---
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // ---
+        f.write_str(match self {
+            Profile::Dev => "dev",
+            Profile::Staging => "staging",
+            Profile::Prod => "prod",
+        })
+    }
+}
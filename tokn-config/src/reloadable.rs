@@ -0,0 +1,66 @@
+// tokn-config/src/reloadable.rs
+
+use std::sync::{Arc, RwLock};
+
+// ---
+
+/// Shared value that can be swapped at runtime, e.g. on configuration reload.
+///
+/// Readers take a cheap snapshot with [`get`](Self::get) and keep using it for
+/// the rest of a request, so one request never sees a half-applied reload.
+/// Cloning is cheap; clones share the value.
+///
+/// # Example
+///
+/// ```
+/// use tokn_config::Reloadable;
+///
+/// let ttl = Reloadable::new(900);
+/// let snapshot = ttl.get();
+/// ttl.set(300);
+/// assert_eq!((*snapshot, *ttl.get()), (900, 300));
+/// ```
+#[derive(Debug)]
+pub struct Reloadable<T>(Arc<RwLock<Arc<T>>>);
+
+// ---
+
+impl<T> Reloadable<T> {
+    // ---
+    /// Wrap an initial value.
+    pub fn new(value: T) -> Self {
+        // ---
+        Self(Arc::new(RwLock::new(Arc::new(value))))
+    }
+
+    // ---
+    /// Snapshot of the current value.
+    pub fn get(&self) -> Arc<T> {
+        // ---
+        self.0.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    // ---
+    /// Replace the value; returns the previous one.
+    pub fn set(&self, value: T) -> Arc<T> {
+        // ---
+        let mut current = self.0.write().unwrap_or_else(|e| e.into_inner());
+        std::mem::replace(&mut *current, Arc::new(value))
+    }
+}
+
+impl<T> Clone for Reloadable<T> {
+    // ---
+    fn clone(&self) -> Self {
+        // ---
+        Self(self.0.clone())
+    }
+}
+
+impl<T> From<T> for Reloadable<T> {
+    // ---
+    fn from(value: T) -> Self {
+        // ---
+        Self::new(value)
+    }
+}
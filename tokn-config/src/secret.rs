@@ -0,0 +1,210 @@
+// tokn-config/src/secret.rs
+
+use secrecy::{ExposeSecret, SecretString};
+use serde::{Deserialize, Deserializer};
+use std::collections::HashMap;
+use std::fmt;
+use zeroize::Zeroize;
+
+// ---
+
+/// Lower-cased fragments that mark a secret as a placeholder or a common
+/// password, wherever they appear in it.
+const DENYLIST: &[&str] = &[
+    "password",
+    "passwd",
+    "secret",
+    "changeme",
+    "change-me",
+    "change_me",
+    "example",
+    "default",
+    "letmein",
+    "qwerty",
+    "123456",
+    "abcdef",
+    "admin",
+    "welcome",
+    "iloveyou",
+    "monkey",
+    "dragon",
+];
+
+/// Fewest distinct characters a secret may use.
+const MIN_DISTINCT_CHARS: usize = 10;
+
+/// Lowest Shannon entropy per character accepted (random hex is ~4.0).
+const MIN_BITS_PER_CHAR: f64 = 3.0;
+
+// ---
+
+/// A secret configuration value: a signing key, client secret, or token.
+///
+/// The value is zeroed when dropped and never shown by `Debug`, so a config
+/// struct holding one can be logged or dropped without leaking it. Read it
+/// with [`expose`](Self::expose) only where it is used.
+///
+/// # Security
+///
+/// Zeroing covers this value and the string it was deserialized from. Copies
+/// made elsewhere (the environment, figment's merged layers, or a library's
+/// own key type such as `jsonwebtoken::EncodingKey`) are outside its control;
+/// keep those short-lived.
+///
+/// # Example
+///
+/// ```
+/// use tokn_config::Secret;
+///
+/// let secret = Secret::from("s3cret-value");
+/// assert_eq!(format!("{secret:?}"), "Secret([REDACTED])");
+/// assert_eq!(secret.expose(), "s3cret-value");
+/// ```
+#[derive(Clone)]
+pub struct Secret(SecretString);
+
+impl Secret {
+    // ---
+    /// Borrow the secret value.
+    pub fn expose(&self) -> &str {
+        // ---
+        self.0.expose_secret()
+    }
+}
+
+impl From<String> for Secret {
+    // ---
+    fn from(mut value: String) -> Self {
+        // ---
+        // Copy into an exactly-sized box, then wipe the original buffer
+        let secret = Secret(SecretString::from(value.as_str()));
+        value.zeroize();
+        secret
+    }
+}
+
+impl From<&str> for Secret {
+    // ---
+    fn from(value: &str) -> Self {
+        // ---
+        Secret(SecretString::from(value))
+    }
+}
+
+impl fmt::Debug for Secret {
+    // ---
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // ---
+        f.write_str("Secret([REDACTED])")
+    }
+}
+
+/// Compares values so reload diffs can tell a changed secret from an
+/// unchanged one. Not constant-time; do not use it to authenticate callers.
+impl PartialEq for Secret {
+    // ---
+    fn eq(&self, other: &Self) -> bool {
+        // ---
+        self.expose() == other.expose()
+    }
+}
+
+impl Eq for Secret {}
+
+impl<'de> Deserialize<'de> for Secret {
+    // ---
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // ---
+        String::deserialize(deserializer).map(Secret::from)
+    }
+}
+
+// ---
+
+/// Check `secret` against weak-secret heuristics, returning why it is weak.
+///
+/// Length is left to each key's own rule. A secret is weak when it:
+/// - contains a common password or placeholder word (`secret`, `changeme`, ...)
+/// - uses fewer than 10 distinct characters (`aaaa...`, `abab...`)
+/// - repeats a shorter pattern or is mostly an ascending/descending run
+/// - has under 3 bits of Shannon entropy per character
+///
+/// These catch obvious mistakes, not every guessable value; generate secrets
+/// with e.g. `openssl rand -base64 48`.
+///
+/// # Example
+///
+/// ```
+/// use tokn_config::secret_weakness;
+///
+/// assert!(secret_weakness(&"a".repeat(40)).is_some());
+/// assert!(secret_weakness("your-secret-key-must-be-at-least-32-characters-long").is_some());
+/// assert!(secret_weakness("Jx4q9Lr2vTz7Wm1Kp8Ns3Hd6Bf0Gc5Ye").is_none());
+/// ```
+pub fn secret_weakness(secret: &str) -> Option<String> {
+    // ---
+    let lower = secret.to_lowercase();
+    if let Some(word) = DENYLIST.iter().find(|word| lower.contains(*word)) {
+        return Some(format!("contains the common word or placeholder '{word}'"));
+    }
+
+    let chars: Vec<char> = secret.chars().collect();
+    if chars.is_empty() {
+        return Some("is empty".into());
+    }
+
+    // ---
+    let mut counts = HashMap::new();
+    for c in &chars {
+        *counts.entry(*c).or_insert(0usize) += 1;
+    }
+    if counts.len() < MIN_DISTINCT_CHARS {
+        return Some(format!(
+            "uses only {} distinct characters (at least {MIN_DISTINCT_CHARS} required)",
+            counts.len()
+        ));
+    }
+
+    if repeats_pattern(&chars) {
+        return Some("repeats a shorter pattern".into());
+    }
+
+    if is_sequential(&chars) {
+        return Some("is mostly a sequential run of characters".into());
+    }
+
+    // ---
+    let len = chars.len() as f64;
+    let bits_per_char: f64 = counts
+        .values()
+        .map(|&n| {
+            let p = n as f64 / len;
+            -p * p.log2()
+        })
+        .sum();
+    if bits_per_char < MIN_BITS_PER_CHAR {
+        return Some(format!(
+            "has low entropy ({bits_per_char:.1} bits per character, at least {MIN_BITS_PER_CHAR:.1} required)"
+        ));
+    }
+
+    None
+}
+
+// ---
+
+/// Whether `chars` is a shorter unit repeated (the last copy may be partial).
+fn repeats_pattern(chars: &[char]) -> bool {
+    // ---
+    (1..=chars.len() / 2).any(|period| (period..chars.len()).all(|i| chars[i] == chars[i - period]))
+}
+
+/// Whether most neighbouring characters step by +1 or -1 (`abcdef`, `987654`).
+fn is_sequential(chars: &[char]) -> bool {
+    // ---
+    let steps = chars
+        .windows(2)
+        .filter(|pair| (pair[1] as i64 - pair[0] as i64).abs() == 1)
+        .count();
+    steps * 2 > chars.len() - 1
+}
@@ -0,0 +1,211 @@
+// tokn-conformance/src/authorize.rs
+
+//! RFC 6749 authorization endpoint checks
+
+use reqwest::{Response, Url};
+
+// ---
+
+use crate::client::{location, query, with, Client};
+use crate::{Check, Group, Report};
+
+// ---
+
+/// A redirect URI never registered for any client.
+const FOREIGN_REDIRECT_URI: &str = "https://attacker.example/callback";
+
+/// A `state` value that only survives if the server encodes it.
+const AWKWARD_STATE: &str = "a b&c=d/e?f";
+
+const UNKNOWN_CLIENT: Check = Check {
+    id: "authorize.unknown-client",
+    group: Group::Rfc6749,
+    section: "§4.1.2.1",
+    title: "an unknown client_id gets an error page, not a redirect",
+};
+
+const FOREIGN_REDIRECT: Check = Check {
+    id: "authorize.unregistered-redirect",
+    group: Group::Rfc6749,
+    section: "§4.1.2.1",
+    title: "an unregistered redirect_uri gets an error page, not a redirect",
+};
+
+const CONSENT_FOREIGN_REDIRECT: Check = Check {
+    id: "authorize.consent-unregistered-redirect",
+    group: Group::Rfc6749,
+    section: "§10.6",
+    title: "approving consent never redirects to an unregistered redirect_uri",
+};
+
+const UNSUPPORTED_RESPONSE_TYPE: Check = Check {
+    id: "authorize.unsupported-response-type",
+    group: Group::Rfc6749,
+    section: "§4.1.2.1",
+    title: "an unsupported response_type redirects with error=unsupported_response_type",
+};
+
+const CODE_AND_STATE: Check = Check {
+    id: "authorize.code-and-state",
+    group: Group::Rfc6749,
+    section: "§4.1.2",
+    title: "approval redirects to the registered redirect_uri with code and state",
+};
+
+const STATE_ROUND_TRIP: Check = Check {
+    id: "authorize.state-round-trip",
+    group: Group::Rfc6749,
+    section: "§4.1.2",
+    title: "state with reserved characters comes back unchanged",
+};
+
+const ACCESS_DENIED: Check = Check {
+    id: "authorize.access-denied",
+    group: Group::Rfc6749,
+    section: "§4.1.2.1",
+    title: "denial redirects with error=access_denied and state",
+};
+
+// ---
+
+pub(crate) async fn run(client: &Client, report: &mut Report) {
+    // ---
+    report.record(UNKNOWN_CLIENT, unknown_client(client).await);
+    report.record(FOREIGN_REDIRECT, foreign_redirect(client).await);
+    report.record(
+        CONSENT_FOREIGN_REDIRECT,
+        consent_foreign_redirect(client).await,
+    );
+    report.record(
+        UNSUPPORTED_RESPONSE_TYPE,
+        unsupported_response_type(client).await,
+    );
+    report.record(CODE_AND_STATE, code_and_state(client).await);
+    report.record(STATE_ROUND_TRIP, state_round_trip(client).await);
+    report.record(ACCESS_DENIED, access_denied(client).await);
+}
+
+// ---
+
+async fn unknown_client(client: &Client) -> Result<(), String> {
+    // ---
+    let request = with(
+        client.authorize_query(),
+        "client_id",
+        "conformance_unknown_client",
+    );
+    error_page(client.authorize(&request).await?)
+}
+
+async fn foreign_redirect(client: &Client) -> Result<(), String> {
+    // ---
+    let request = with(
+        client.authorize_query(),
+        "redirect_uri",
+        FOREIGN_REDIRECT_URI,
+    );
+    error_page(client.authorize(&request).await?)
+}
+
+async fn consent_foreign_redirect(client: &Client) -> Result<(), String> {
+    // ---
+    let form = with(
+        client.consent_form("approve"),
+        "redirect_uri",
+        FOREIGN_REDIRECT_URI,
+    );
+    let response = client.consent(&form).await?;
+    match location(&response) {
+        Some(url) if url.as_str().starts_with(FOREIGN_REDIRECT_URI) => {
+            Err(format!("redirected to {url}"))
+        }
+        _ => Ok(()),
+    }
+}
+
+async fn unsupported_response_type(client: &Client) -> Result<(), String> {
+    // ---
+    let request = with(client.authorize_query(), "response_type", "token");
+    let response = client.authorize(&request).await?;
+    let status = response.status().as_u16();
+
+    let url = registered_redirect(client, &response)
+        .ok_or_else(|| format!("got HTTP {status}, expected a redirect to the client"))?;
+    expect_param(&url, "error", "unsupported_response_type")?;
+    expect_param(&url, "state", "conformance")
+}
+
+async fn code_and_state(client: &Client) -> Result<(), String> {
+    // ---
+    let response = client.consent(&client.consent_form("approve")).await?;
+    let status = response.status().as_u16();
+
+    let url = registered_redirect(client, &response)
+        .ok_or_else(|| format!("got HTTP {status}, expected a redirect to the client"))?;
+    if query(&url, "code").unwrap_or_default().is_empty() {
+        return Err(format!("redirected to {url} without a code"));
+    }
+    expect_param(&url, "state", "conformance")
+}
+
+async fn state_round_trip(client: &Client) -> Result<(), String> {
+    // ---
+    let form = with(client.consent_form("approve"), "state", AWKWARD_STATE);
+    let response = client.consent(&form).await?;
+    let status = response.status().as_u16();
+
+    let url = registered_redirect(client, &response)
+        .ok_or_else(|| format!("got HTTP {status}, expected a redirect to the client"))?;
+    expect_param(&url, "state", AWKWARD_STATE)
+}
+
+async fn access_denied(client: &Client) -> Result<(), String> {
+    // ---
+    let response = client.consent(&client.consent_form("deny")).await?;
+    let status = response.status().as_u16();
+
+    let url = registered_redirect(client, &response)
+        .ok_or_else(|| format!("got HTTP {status}, expected a redirect to the client"))?;
+    expect_param(&url, "error", "access_denied")?;
+    expect_param(&url, "state", "conformance")
+}
+
+// ---
+
+/// The response must be a 4xx page, not a redirect.
+fn error_page(response: Response) -> Result<(), String> {
+    // ---
+    let status = response.status();
+    if let Some(url) = location(&response) {
+        return Err(format!("redirected to {url}"));
+    }
+    if !status.is_client_error() {
+        return Err(format!(
+            "got HTTP {}, expected a 4xx error page",
+            status.as_u16()
+        ));
+    }
+    Ok(())
+}
+
+/// The redirect target, if the response redirects to the registered URI.
+fn registered_redirect(client: &Client, response: &Response) -> Option<Url> {
+    // ---
+    location(response).filter(|url| {
+        let mut bare = url.clone();
+        bare.set_query(None);
+        bare.set_fragment(None);
+        bare.as_str() == client.target.redirect_uri
+    })
+}
+
+fn expect_param(url: &Url, name: &str, expected: &str) -> Result<(), String> {
+    // ---
+    match query(url, name) {
+        Some(value) if value == expected => Ok(()),
+        Some(value) => Err(format!(
+            "{name} came back as {value:?}, expected {expected:?}"
+        )),
+        None => Err(format!("redirected to {url} without {name}")),
+    }
+}
@@ -0,0 +1,85 @@
+// tokn-conformance/src/cli.rs
+
+//! Command-line options
+
+use clap::Parser;
+use std::time::Duration;
+use tokn_conformance::{Group, Target};
+
+// ---
+
+/// OAuth2 protocol conformance checks for oauth2-server.
+///
+/// Runs RFC 6749, 7636, 7009, and 7662 checks against a running server and
+/// prints a pass/fail report. Exits non-zero when any check fails. RFC 7636,
+/// 7009, and 7662 checks are skipped unless their option below is given.
+#[derive(Debug, Parser)]
+#[command(name = "tokn-conformance", version)]
+pub struct Args {
+    // ---
+    /// oauth2-server base URL
+    #[arg(long, env = "TOKN_OAUTH2_URL", default_value = "http://127.0.0.1:8082")]
+    pub url: String,
+
+    /// OAuth2 client ID registered with the server
+    #[arg(long, default_value = "demo_client")]
+    pub client_id: String,
+
+    /// OAuth2 client secret for --client-id
+    #[arg(long, default_value = "demo_secret")]
+    pub client_secret: String,
+
+    /// Redirect URI registered for --client-id
+    #[arg(long, default_value = "http://127.0.0.1:8081/callback")]
+    pub redirect_uri: String,
+
+    /// Run the RFC 7636 checks; the server must enforce PKCE
+    #[arg(long)]
+    pub pkce: bool,
+
+    /// RFC 7009 revocation endpoint URL
+    #[arg(long)]
+    pub revocation_url: Option<String>,
+
+    /// RFC 7662 introspection endpoint URL
+    #[arg(long)]
+    pub introspection_url: Option<String>,
+
+    /// Only run this group: rfc6749, rfc7636, rfc7009, or rfc7662 (repeatable)
+    #[arg(long = "only")]
+    pub groups: Vec<Group>,
+
+    /// Per-request timeout in seconds
+    #[arg(long, default_value = "10", value_parser = parse_seconds)]
+    pub timeout: Duration,
+}
+
+impl Args {
+    // ---
+    pub fn target(&self) -> Target {
+        // ---
+        Target {
+            client_id: self.client_id.clone(),
+            client_secret: self.client_secret.clone(),
+            redirect_uri: self.redirect_uri.clone(),
+            pkce: self.pkce,
+            revocation_url: self.revocation_url.clone(),
+            introspection_url: self.introspection_url.clone(),
+            groups: self.groups.clone(),
+            timeout: self.timeout,
+            ..Target::new(&self.url)
+        }
+    }
+}
+
+// ---
+
+fn parse_seconds(value: &str) -> Result<Duration, String> {
+    // ---
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|secs| secs.is_finite() && *secs > 0.0)
+        .map(Duration::from_secs_f64)
+        .ok_or_else(|| format!("expected a positive number of seconds, got '{value}'"))
+}
@@ -0,0 +1,261 @@
+// tokn-conformance/src/client.rs
+
+//! HTTP helpers shared by the checks: the consent form, the token endpoint,
+//! and reading redirects and error responses
+
+use anyhow::{Context, Result};
+use reqwest::header::{HeaderMap, LOCATION};
+use reqwest::{Response, Url};
+use serde_json::Value;
+
+// ---
+
+use crate::Target;
+
+// ---
+
+/// Form or query fields, in order.
+pub(crate) type Form = Vec<(&'static str, String)>;
+
+/// `form` with `key` set to `value`, replacing any previous value.
+pub(crate) fn with(mut form: Form, key: &'static str, value: impl Into<String>) -> Form {
+    // ---
+    form.retain(|(k, _)| *k != key);
+    form.push((key, value.into()));
+    form
+}
+
+/// `form` without `key`.
+pub(crate) fn without(mut form: Form, key: &'static str) -> Form {
+    // ---
+    form.retain(|(k, _)| *k != key);
+    form
+}
+
+// ---
+
+/// Client for the server under test. Redirects are never followed, so
+/// checks can see where the server sends the user agent.
+pub(crate) struct Client {
+    // ---
+    http: reqwest::Client,
+    pub target: Target,
+}
+
+/// A response read to the end; `body` is `Null` unless it was JSON.
+pub(crate) struct Reply {
+    // ---
+    pub status: u16,
+    pub headers: HeaderMap,
+    pub body: Value,
+}
+
+// ---
+
+impl Client {
+    // ---
+    pub fn new(target: &Target) -> Result<Self> {
+        // ---
+        let http = reqwest::Client::builder()
+            .timeout(target.timeout)
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .context("Failed to build HTTP client")?;
+
+        Ok(Self {
+            http,
+            target: target.clone(),
+        })
+    }
+
+    // ---
+    /// A valid authorization request for the registered client.
+    pub fn authorize_query(&self) -> Form {
+        // ---
+        vec![
+            ("response_type", "code".to_string()),
+            ("client_id", self.target.client_id.clone()),
+            ("redirect_uri", self.target.redirect_uri.clone()),
+            ("scope", "profile".to_string()),
+            ("state", "conformance".to_string()),
+        ]
+    }
+
+    /// The consent form as submitted with `action` (`approve` or `deny`).
+    pub fn consent_form(&self, action: &str) -> Form {
+        // ---
+        vec![
+            ("client_id", self.target.client_id.clone()),
+            ("redirect_uri", self.target.redirect_uri.clone()),
+            ("scope", "profile".to_string()),
+            ("state", "conformance".to_string()),
+            ("action", action.to_string()),
+        ]
+    }
+
+    /// A valid token request exchanging `code`.
+    pub fn token_form(&self, code: &str) -> Form {
+        // ---
+        vec![
+            ("grant_type", "authorization_code".to_string()),
+            ("code", code.to_string()),
+            ("redirect_uri", self.target.redirect_uri.clone()),
+            ("client_id", self.target.client_id.clone()),
+            ("client_secret", self.target.client_secret.clone()),
+        ]
+    }
+
+    // ---
+    /// GET the authorization endpoint.
+    pub async fn authorize(&self, query: &Form) -> Result<Response, String> {
+        // ---
+        self.http
+            .get(format!("{}/v1/oauth/authorize", self.target.base_url))
+            .query(query)
+            .send()
+            .await
+            .map_err(describe)
+    }
+
+    /// Submit the consent form.
+    pub async fn consent(&self, form: &Form) -> Result<Response, String> {
+        // ---
+        self.http
+            .post(format!("{}/v1/oauth/authorize", self.target.base_url))
+            .form(form)
+            .send()
+            .await
+            .map_err(describe)
+    }
+
+    /// POST `form` to the token endpoint.
+    pub async fn token(&self, form: &Form) -> Result<Reply, String> {
+        // ---
+        let url = format!("{}/v1/oauth/token", self.target.base_url);
+        self.post(&url, form).await
+    }
+
+    /// POST `form` to any endpoint.
+    pub async fn post(&self, url: &str, form: &Form) -> Result<Reply, String> {
+        // ---
+        let response = self
+            .http
+            .post(url)
+            .form(form)
+            .send()
+            .await
+            .map_err(describe)?;
+        Reply::read(response).await
+    }
+
+    /// GET userinfo with `access_token`.
+    pub async fn userinfo(&self, access_token: &str) -> Result<Reply, String> {
+        // ---
+        let response = self
+            .http
+            .get(format!("{}/v1/oauth/userinfo", self.target.base_url))
+            .bearer_auth(access_token)
+            .send()
+            .await
+            .map_err(describe)?;
+        Reply::read(response).await
+    }
+
+    // ---
+    /// Approve the consent form with `extra` fields and return the code.
+    pub async fn issue_code(&self, extra: Form) -> Result<String, String> {
+        // ---
+        let mut form = self.consent_form("approve");
+        for (key, value) in extra {
+            form = with(form, key, value);
+        }
+
+        let response = self.consent(&form).await?;
+        let status = response.status().as_u16();
+        location(&response)
+            .and_then(|url| query(&url, "code"))
+            .ok_or_else(|| format!("approving consent gave HTTP {status} without a code"))
+    }
+
+    /// Obtain a fresh access token for the registered client.
+    pub async fn access_token(&self) -> Result<String, String> {
+        // ---
+        let code = self.issue_code(Vec::new()).await?;
+        let reply = self.token(&self.token_form(&code)).await?;
+        match reply.body["access_token"].as_str() {
+            Some(token) if reply.status == 200 => Ok(token.to_string()),
+            _ => Err(format!(
+                "exchanging a fresh code gave HTTP {} {}",
+                reply.status, reply.body
+            )),
+        }
+    }
+}
+
+// ---
+
+impl Reply {
+    // ---
+    pub async fn read(response: Response) -> Result<Self, String> {
+        // ---
+        let status = response.status().as_u16();
+        let headers = response.headers().clone();
+        let bytes = response.bytes().await.map_err(describe)?;
+
+        Ok(Self {
+            status,
+            headers,
+            body: serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+        })
+    }
+
+    // ---
+    /// An RFC 6749 §5.2 style error: one of `statuses` with `"error": error`.
+    pub fn expect_error(&self, statuses: &[u16], error: &str) -> Result<(), String> {
+        // ---
+        if statuses.contains(&self.status) && self.body["error"] == error {
+            return Ok(());
+        }
+        Err(format!(
+            "got HTTP {} {}, expected HTTP {} with error={error}",
+            self.status,
+            self.body,
+            statuses
+                .iter()
+                .map(u16::to_string)
+                .collect::<Vec<_>>()
+                .join(" or ")
+        ))
+    }
+}
+
+// ---
+
+/// Where a redirect response points, resolved against the request URL.
+pub(crate) fn location(response: &Response) -> Option<Url> {
+    // ---
+    if !response.status().is_redirection() {
+        return None;
+    }
+    let value = response.headers().get(LOCATION)?.to_str().ok()?;
+    response.url().join(value).ok()
+}
+
+/// First value of a query parameter.
+pub(crate) fn query(url: &Url, name: &str) -> Option<String> {
+    // ---
+    url.query_pairs()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.into_owned())
+}
+
+fn describe(e: reqwest::Error) -> String {
+    // ---
+    if e.is_timeout() {
+        "request timed out".to_string()
+    } else if e.is_connect() {
+        "could not connect to the server".to_string()
+    } else {
+        format!("request failed: {e}")
+    }
+}
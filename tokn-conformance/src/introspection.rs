@@ -0,0 +1,99 @@
+// tokn-conformance/src/introspection.rs
+
+//! RFC 7662 token introspection checks
+
+use crate::client::{with, Client, Form};
+use crate::{Check, Group, Report};
+
+// ---
+
+const ACTIVE: Check = Check {
+    id: "introspection.active",
+    group: Group::Rfc7662,
+    section: "§2.2",
+    title: "a live access token is reported with \"active\": true",
+};
+
+const INACTIVE: Check = Check {
+    id: "introspection.inactive",
+    group: Group::Rfc7662,
+    section: "§2.2",
+    title: "an unknown token is reported with \"active\": false and HTTP 200",
+};
+
+const CLIENT_AUTH: Check = Check {
+    id: "introspection.client-auth",
+    group: Group::Rfc7662,
+    section: "§2.1",
+    title: "a wrong client_secret is refused with HTTP 401",
+};
+
+/// Every check in this group, for reporting skips.
+pub(crate) const CHECKS: &[Check] = &[ACTIVE, INACTIVE, CLIENT_AUTH];
+
+// ---
+
+pub(crate) async fn run(client: &Client, url: &str, report: &mut Report) {
+    // ---
+    report.record(ACTIVE, active(client, url).await);
+    report.record(INACTIVE, inactive(client, url).await);
+    report.record(CLIENT_AUTH, client_auth(client, url).await);
+}
+
+// ---
+
+async fn active(client: &Client, url: &str) -> Result<(), String> {
+    // ---
+    let token = client.access_token().await?;
+    let reply = client.post(url, &introspect_form(client, &token)).await?;
+    if reply.status != 200 || reply.body["active"] != true {
+        return Err(format!(
+            "got HTTP {} {}, expected HTTP 200 with \"active\": true",
+            reply.status, reply.body
+        ));
+    }
+    Ok(())
+}
+
+async fn inactive(client: &Client, url: &str) -> Result<(), String> {
+    // ---
+    let form = introspect_form(client, "conformance-unknown-token");
+    let reply = client.post(url, &form).await?;
+    if reply.status != 200 || reply.body["active"] != false {
+        return Err(format!(
+            "got HTTP {} {}, expected HTTP 200 with \"active\": false",
+            reply.status, reply.body
+        ));
+    }
+    Ok(())
+}
+
+async fn client_auth(client: &Client, url: &str) -> Result<(), String> {
+    // ---
+    let token = client.access_token().await?;
+    let form = with(
+        introspect_form(client, &token),
+        "client_secret",
+        "conformance-wrong-secret",
+    );
+    let reply = client.post(url, &form).await?;
+    if reply.status != 401 {
+        return Err(format!(
+            "got HTTP {} {}, expected HTTP 401",
+            reply.status, reply.body
+        ));
+    }
+    Ok(())
+}
+
+// ---
+
+fn introspect_form(client: &Client, token: &str) -> Form {
+    // ---
+    vec![
+        ("token", token.to_string()),
+        ("token_type_hint", "access_token".to_string()),
+        ("client_id", client.target.client_id.clone()),
+        ("client_secret", client.target.client_secret.clone()),
+    ]
+}
@@ -0,0 +1,95 @@
+// tokn-conformance/src/lib.rs
+
+//! tokn-conformance - OAuth2 protocol conformance checks for oauth2-server
+//!
+//! Runs behavioral checks against a running oauth2-server and reports each as
+//! passed, failed, or skipped:
+//!
+//! - RFC 6749: authorization errors, redirect URI validation, `state`
+//!   round-trips, single-use codes, and token endpoint error codes
+//! - RFC 7636: PKCE enforcement (only with [`Target::pkce`])
+//! - RFC 7009: token revocation (only with [`Target::revocation_url`])
+//! - RFC 7662: token introspection (only with [`Target::introspection_url`])
+//!
+//! Checks drive the consent form directly, so codes are issued for the user
+//! oauth2-server approves as; each check obtains its own codes and tokens.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # async fn example() -> anyhow::Result<()> {
+//! let report = tokn_conformance::run(&tokn_conformance::Target::new("http://127.0.0.1:8082")).await?;
+//! println!("{report}");
+//! assert_eq!(report.failed().count(), 0);
+//! # Ok(())
+//! # }
+//! ```
+
+mod authorize;
+mod client;
+mod introspection;
+mod pkce;
+mod report;
+mod revocation;
+mod target;
+mod token;
+
+use anyhow::Result;
+
+// ---
+
+pub use report::{Check, CheckResult, Outcome, Report};
+pub use target::{Group, Target};
+
+// ---
+
+use client::Client;
+
+// ---
+
+/// Run every check of the selected groups against `target`.
+///
+/// # Errors
+///
+/// Returns an error if the HTTP client cannot be built. Failed checks,
+/// including an unreachable server, are reported, not returned.
+pub async fn run(target: &Target) -> Result<Report> {
+    // ---
+    let client = Client::new(target)?;
+    let mut report = Report::new(&target.base_url);
+
+    if target.runs(Group::Rfc6749) {
+        authorize::run(&client, &mut report).await;
+        token::run(&client, &mut report).await;
+    }
+
+    if target.runs(Group::Rfc7636) {
+        if target.pkce {
+            pkce::run(&client, &mut report).await;
+        } else {
+            report.skip_all(pkce::CHECKS, "PKCE not enabled (--pkce)");
+        }
+    }
+
+    if target.runs(Group::Rfc7009) {
+        match &target.revocation_url {
+            Some(url) => revocation::run(&client, url, &mut report).await,
+            None => report.skip_all(
+                revocation::CHECKS,
+                "no revocation endpoint (--revocation-url)",
+            ),
+        }
+    }
+
+    if target.runs(Group::Rfc7662) {
+        match &target.introspection_url {
+            Some(url) => introspection::run(&client, url, &mut report).await,
+            None => report.skip_all(
+                introspection::CHECKS,
+                "no introspection endpoint (--introspection-url)",
+            ),
+        }
+    }
+
+    Ok(report)
+}
@@ -0,0 +1,43 @@
+// tokn-conformance/src/main.rs
+
+//! tokn-conformance - OAuth2 protocol conformance runner for oauth2-server
+//!
+//! Runs RFC 6749/7636/7009/7662 behavioral checks against a running
+//! oauth2-server, prints a pass/fail report, and exits non-zero when any
+//! check fails.
+//!
+//! # Example
+//!
+//! ```bash
+//! # Core RFC 6749 checks against a local server
+//! cargo run -p tokn-conformance
+//!
+//! # Everything, against a server with PKCE and revocation enabled
+//! cargo run -p tokn-conformance -- --url https://auth.example.com --pkce \
+//!     --revocation-url https://auth.example.com/v1/oauth/revoke
+//! ```
+
+mod cli;
+
+use anyhow::Result;
+use clap::Parser;
+use std::process::ExitCode;
+
+// ---
+
+use cli::Args;
+
+// ---
+
+#[tokio::main]
+async fn main() -> Result<ExitCode> {
+    // ---
+    let args = Args::parse();
+    let report = tokn_conformance::run(&args.target()).await?;
+    println!("{report}");
+
+    if report.failed().count() > 0 {
+        return Ok(ExitCode::FAILURE);
+    }
+    Ok(ExitCode::SUCCESS)
+}
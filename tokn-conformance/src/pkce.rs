@@ -0,0 +1,93 @@
+// tokn-conformance/src/pkce.rs
+
+//! RFC 7636 PKCE enforcement checks
+
+use crate::client::{with, Client, Form};
+use crate::{Check, Group, Report};
+
+// ---
+
+/// The code verifier and S256 challenge from RFC 7636 Appendix B.
+const VERIFIER: &str = "dBjftJeZ4CVP-mJ92K1qW8-2y4Edry9xXNC6J5HJ0RQ";
+const CHALLENGE: &str = "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM";
+
+const MISSING_VERIFIER: Check = Check {
+    id: "pkce.missing-verifier",
+    group: Group::Rfc7636,
+    section: "§4.6",
+    title: "a code issued with a challenge is refused without code_verifier",
+};
+
+const WRONG_VERIFIER: Check = Check {
+    id: "pkce.wrong-verifier",
+    group: Group::Rfc7636,
+    section: "§4.6",
+    title: "a code_verifier not matching the challenge is refused with invalid_grant",
+};
+
+const S256: Check = Check {
+    id: "pkce.s256",
+    group: Group::Rfc7636,
+    section: "§4.6",
+    title: "the matching code_verifier for an S256 challenge is accepted",
+};
+
+/// Every check in this group, for reporting skips.
+pub(crate) const CHECKS: &[Check] = &[MISSING_VERIFIER, WRONG_VERIFIER, S256];
+
+// ---
+
+pub(crate) async fn run(client: &Client, report: &mut Report) {
+    // ---
+    report.record(MISSING_VERIFIER, missing_verifier(client).await);
+    report.record(WRONG_VERIFIER, wrong_verifier(client).await);
+    report.record(S256, s256(client).await);
+}
+
+// ---
+
+async fn missing_verifier(client: &Client) -> Result<(), String> {
+    // ---
+    let code = client.issue_code(challenge()).await?;
+    let form = client.token_form(&code);
+    client
+        .token(&form)
+        .await?
+        .expect_error(&[400], "invalid_grant")
+}
+
+async fn wrong_verifier(client: &Client) -> Result<(), String> {
+    // ---
+    let code = client.issue_code(challenge()).await?;
+    let form = with(
+        client.token_form(&code),
+        "code_verifier",
+        VERIFIER.chars().rev().collect::<String>(),
+    );
+    client
+        .token(&form)
+        .await?
+        .expect_error(&[400], "invalid_grant")
+}
+
+async fn s256(client: &Client) -> Result<(), String> {
+    // ---
+    let code = client.issue_code(challenge()).await?;
+    let form = with(client.token_form(&code), "code_verifier", VERIFIER);
+    let reply = client.token(&form).await?;
+
+    if reply.status != 200 {
+        return Err(format!("got HTTP {} {}", reply.status, reply.body));
+    }
+    Ok(())
+}
+
+// ---
+
+fn challenge() -> Form {
+    // ---
+    vec![
+        ("code_challenge", CHALLENGE.to_string()),
+        ("code_challenge_method", "S256".to_string()),
+    ]
+}
@@ -0,0 +1,163 @@
+// tokn-conformance/src/report.rs
+
+//! Check descriptions, outcomes, and the printed report
+
+use std::fmt;
+
+// ---
+
+use crate::Group;
+
+// ---
+
+/// One conformance check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Check {
+    // ---
+    /// Stable identifier, e.g. `token.single-use-code`
+    pub id: &'static str,
+    pub group: Group,
+
+    /// Section of the RFC the behavior comes from
+    pub section: &'static str,
+
+    /// What a conforming server does
+    pub title: &'static str,
+}
+
+/// Result of one check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    // ---
+    Pass,
+
+    /// The server misbehaved; the string says how
+    Fail(String),
+
+    /// Not run; the string says why
+    Skip(String),
+}
+
+/// A check and its outcome.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckResult {
+    // ---
+    pub check: Check,
+    pub outcome: Outcome,
+}
+
+// ---
+
+/// Outcomes of a conformance run, in the order the checks ran.
+#[derive(Debug, Clone)]
+pub struct Report {
+    // ---
+    base_url: String,
+    results: Vec<CheckResult>,
+}
+
+impl Report {
+    // ---
+    pub(crate) fn new(base_url: &str) -> Self {
+        // ---
+        Self {
+            base_url: base_url.to_string(),
+            results: Vec::new(),
+        }
+    }
+
+    // ---
+    /// Record the result of running `check`.
+    pub(crate) fn record(&mut self, check: Check, result: Result<(), String>) {
+        // ---
+        let outcome = match result {
+            Ok(()) => Outcome::Pass,
+            Err(reason) => Outcome::Fail(reason),
+        };
+        self.results.push(CheckResult { check, outcome });
+    }
+
+    /// Record every one of `checks` as skipped.
+    pub(crate) fn skip_all(&mut self, checks: &[Check], reason: &str) {
+        // ---
+        self.results.extend(checks.iter().map(|&check| CheckResult {
+            check,
+            outcome: Outcome::Skip(reason.to_string()),
+        }));
+    }
+
+    // ---
+    pub fn results(&self) -> &[CheckResult] {
+        // ---
+        &self.results
+    }
+
+    pub fn passed(&self) -> impl Iterator<Item = &CheckResult> {
+        // ---
+        self.results
+            .iter()
+            .filter(|result| result.outcome == Outcome::Pass)
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = &CheckResult> {
+        // ---
+        self.results
+            .iter()
+            .filter(|result| matches!(result.outcome, Outcome::Fail(_)))
+    }
+
+    pub fn skipped(&self) -> impl Iterator<Item = &CheckResult> {
+        // ---
+        self.results
+            .iter()
+            .filter(|result| matches!(result.outcome, Outcome::Skip(_)))
+    }
+}
+
+// ---
+
+impl fmt::Display for Report {
+    // ---
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // ---
+        writeln!(f, "tokn-conformance: {}", self.base_url)?;
+
+        let width = self
+            .results
+            .iter()
+            .map(|result| result.check.id.len())
+            .max()
+            .unwrap_or(0);
+
+        let mut group = None;
+        for result in &self.results {
+            let check = &result.check;
+            if group != Some(check.group) {
+                group = Some(check.group);
+                writeln!(f, "\n{}", check.group.title())?;
+            }
+
+            let (label, detail) = match &result.outcome {
+                Outcome::Pass => ("PASS", None),
+                Outcome::Fail(reason) => ("FAIL", Some(reason)),
+                Outcome::Skip(reason) => ("SKIP", Some(reason)),
+            };
+            writeln!(
+                f,
+                "  {label}  {:<9} {:<width$}  {}",
+                check.section, check.id, check.title
+            )?;
+            if let Some(detail) = detail {
+                writeln!(f, "        -> {detail}")?;
+            }
+        }
+
+        write!(
+            f,
+            "\n{} passed, {} failed, {} skipped",
+            self.passed().count(),
+            self.failed().count(),
+            self.skipped().count()
+        )
+    }
+}
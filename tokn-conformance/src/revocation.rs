@@ -0,0 +1,97 @@
+// tokn-conformance/src/revocation.rs
+
+//! RFC 7009 token revocation checks
+
+use crate::client::{with, Client, Form};
+use crate::{Check, Group, Report};
+
+// ---
+
+const VALID_TOKEN: Check = Check {
+    id: "revocation.valid-token",
+    group: Group::Rfc7009,
+    section: "§2.2",
+    title: "revoking an access token returns 200 and the token stops working",
+};
+
+const UNKNOWN_TOKEN: Check = Check {
+    id: "revocation.unknown-token",
+    group: Group::Rfc7009,
+    section: "§2.2",
+    title: "revoking an unknown token still returns 200",
+};
+
+const CLIENT_AUTH: Check = Check {
+    id: "revocation.client-auth",
+    group: Group::Rfc7009,
+    section: "§2.1",
+    title: "a wrong client_secret is refused with invalid_client",
+};
+
+/// Every check in this group, for reporting skips.
+pub(crate) const CHECKS: &[Check] = &[VALID_TOKEN, UNKNOWN_TOKEN, CLIENT_AUTH];
+
+// ---
+
+pub(crate) async fn run(client: &Client, url: &str, report: &mut Report) {
+    // ---
+    report.record(VALID_TOKEN, valid_token(client, url).await);
+    report.record(UNKNOWN_TOKEN, unknown_token(client, url).await);
+    report.record(CLIENT_AUTH, client_auth(client, url).await);
+}
+
+// ---
+
+async fn valid_token(client: &Client, url: &str) -> Result<(), String> {
+    // ---
+    let token = client.access_token().await?;
+    let reply = client.post(url, &revoke_form(client, &token)).await?;
+    if reply.status != 200 {
+        return Err(format!("got HTTP {} {}", reply.status, reply.body));
+    }
+
+    let userinfo = client.userinfo(&token).await?;
+    if userinfo.status != 401 {
+        return Err(format!(
+            "userinfo with the revoked token got HTTP {}, expected 401",
+            userinfo.status
+        ));
+    }
+    Ok(())
+}
+
+async fn unknown_token(client: &Client, url: &str) -> Result<(), String> {
+    // ---
+    let form = revoke_form(client, "conformance-unknown-token");
+    let reply = client.post(url, &form).await?;
+    if reply.status != 200 {
+        return Err(format!("got HTTP {} {}", reply.status, reply.body));
+    }
+    Ok(())
+}
+
+async fn client_auth(client: &Client, url: &str) -> Result<(), String> {
+    // ---
+    let token = client.access_token().await?;
+    let form = with(
+        revoke_form(client, &token),
+        "client_secret",
+        "conformance-wrong-secret",
+    );
+    client
+        .post(url, &form)
+        .await?
+        .expect_error(&[400, 401], "invalid_client")
+}
+
+// ---
+
+fn revoke_form(client: &Client, token: &str) -> Form {
+    // ---
+    vec![
+        ("token", token.to_string()),
+        ("token_type_hint", "access_token".to_string()),
+        ("client_id", client.target.client_id.clone()),
+        ("client_secret", client.target.client_secret.clone()),
+    ]
+}
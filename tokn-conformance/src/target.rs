@@ -0,0 +1,133 @@
+// tokn-conformance/src/target.rs
+
+//! The server under test and which checks to run against it
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+// ---
+
+/// The oauth2-server under test and the client registered with it.
+#[derive(Debug, Clone)]
+pub struct Target {
+    // ---
+    /// oauth2-server base URL
+    pub base_url: String,
+
+    /// Registered client used for every check
+    pub client_id: String,
+    pub client_secret: String,
+
+    /// Redirect URI registered for `client_id`
+    pub redirect_uri: String,
+
+    /// The server enforces PKCE; RFC 7636 checks are skipped otherwise
+    pub pkce: bool,
+
+    /// RFC 7009 revocation endpoint; its checks are skipped when unset
+    pub revocation_url: Option<String>,
+
+    /// RFC 7662 introspection endpoint; its checks are skipped when unset
+    pub introspection_url: Option<String>,
+
+    /// Groups to run; empty runs all
+    pub groups: Vec<Group>,
+
+    /// Per-request timeout
+    pub timeout: Duration,
+}
+
+impl Target {
+    // ---
+    /// The seeded `demo_client` registration at `base_url`, with the
+    /// optional RFCs off.
+    pub fn new(base_url: impl Into<String>) -> Self {
+        // ---
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            client_id: "demo_client".to_string(),
+            client_secret: "demo_secret".to_string(),
+            redirect_uri: "http://127.0.0.1:8081/callback".to_string(),
+            pkce: false,
+            revocation_url: None,
+            introspection_url: None,
+            groups: Vec::new(),
+            timeout: Duration::from_secs(10),
+        }
+    }
+
+    /// True when checks in `group` are selected.
+    pub fn runs(&self, group: Group) -> bool {
+        // ---
+        self.groups.is_empty() || self.groups.contains(&group)
+    }
+}
+
+// ---
+
+/// The specification a check comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Group {
+    // ---
+    /// The OAuth 2.0 Authorization Framework
+    Rfc6749,
+
+    /// Proof Key for Code Exchange
+    Rfc7636,
+
+    /// Token Revocation
+    Rfc7009,
+
+    /// Token Introspection
+    Rfc7662,
+}
+
+impl Group {
+    // ---
+    pub const ALL: [Group; 4] = [
+        Group::Rfc6749,
+        Group::Rfc7636,
+        Group::Rfc7009,
+        Group::Rfc7662,
+    ];
+
+    /// Heading used in the report.
+    pub fn title(self) -> &'static str {
+        // ---
+        match self {
+            Group::Rfc6749 => "RFC 6749 (OAuth 2.0)",
+            Group::Rfc7636 => "RFC 7636 (PKCE)",
+            Group::Rfc7009 => "RFC 7009 (Token Revocation)",
+            Group::Rfc7662 => "RFC 7662 (Token Introspection)",
+        }
+    }
+}
+
+impl fmt::Display for Group {
+    // ---
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // ---
+        f.write_str(match self {
+            Group::Rfc6749 => "rfc6749",
+            Group::Rfc7636 => "rfc7636",
+            Group::Rfc7009 => "rfc7009",
+            Group::Rfc7662 => "rfc7662",
+        })
+    }
+}
+
+impl FromStr for Group {
+    // ---
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // ---
+        Group::ALL
+            .into_iter()
+            .find(|group| group.to_string() == s.to_ascii_lowercase())
+            .ok_or_else(|| {
+                format!("unknown group '{s}' (expected rfc6749, rfc7636, rfc7009, or rfc7662)")
+            })
+    }
+}
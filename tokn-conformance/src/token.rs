@@ -0,0 +1,182 @@
+// tokn-conformance/src/token.rs
+
+//! RFC 6749 token endpoint checks
+
+use reqwest::header::CACHE_CONTROL;
+
+// ---
+
+use crate::client::{with, without, Client};
+use crate::{Check, Group, Report};
+
+// ---
+
+const RESPONSE: Check = Check {
+    id: "token.response",
+    group: Group::Rfc6749,
+    section: "§5.1",
+    title: "a valid exchange returns a Bearer access_token with Cache-Control: no-store",
+};
+
+const SINGLE_USE_CODE: Check = Check {
+    id: "token.single-use-code",
+    group: Group::Rfc6749,
+    section: "§4.1.2",
+    title: "a code exchanged once is refused with invalid_grant",
+};
+
+const UNKNOWN_CODE: Check = Check {
+    id: "token.unknown-code",
+    group: Group::Rfc6749,
+    section: "§5.2",
+    title: "an unknown code is refused with invalid_grant",
+};
+
+const REDIRECT_MISMATCH: Check = Check {
+    id: "token.redirect-mismatch",
+    group: Group::Rfc6749,
+    section: "§4.1.3",
+    title: "a redirect_uri differing from the authorization request is refused with invalid_grant",
+};
+
+const INVALID_CLIENT: Check = Check {
+    id: "token.invalid-client",
+    group: Group::Rfc6749,
+    section: "§5.2",
+    title: "a wrong client_secret is refused with invalid_client",
+};
+
+const UNSUPPORTED_GRANT_TYPE: Check = Check {
+    id: "token.unsupported-grant-type",
+    group: Group::Rfc6749,
+    section: "§5.2",
+    title: "an unknown grant_type is refused with unsupported_grant_type",
+};
+
+const MISSING_PARAMETER: Check = Check {
+    id: "token.missing-parameter",
+    group: Group::Rfc6749,
+    section: "§5.2",
+    title: "a request without code is refused with invalid_request",
+};
+
+// ---
+
+pub(crate) async fn run(client: &Client, report: &mut Report) {
+    // ---
+    report.record(RESPONSE, response(client).await);
+    report.record(SINGLE_USE_CODE, single_use_code(client).await);
+    report.record(UNKNOWN_CODE, unknown_code(client).await);
+    report.record(REDIRECT_MISMATCH, redirect_mismatch(client).await);
+    report.record(INVALID_CLIENT, invalid_client(client).await);
+    report.record(UNSUPPORTED_GRANT_TYPE, unsupported_grant_type(client).await);
+    report.record(MISSING_PARAMETER, missing_parameter(client).await);
+}
+
+// ---
+
+async fn response(client: &Client) -> Result<(), String> {
+    // ---
+    let code = client.issue_code(Vec::new()).await?;
+    let reply = client.token(&client.token_form(&code)).await?;
+
+    if reply.status != 200 {
+        return Err(format!("got HTTP {} {}", reply.status, reply.body));
+    }
+    if !reply.body["access_token"].is_string() {
+        return Err(format!("no access_token in {}", reply.body));
+    }
+    let token_type = reply.body["token_type"].as_str().unwrap_or_default();
+    if !token_type.eq_ignore_ascii_case("bearer") {
+        return Err(format!("token_type is {:?}, expected Bearer", token_type));
+    }
+
+    let no_store = reply
+        .headers
+        .get_all(CACHE_CONTROL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .any(|value| value.to_ascii_lowercase().contains("no-store"));
+    if !no_store {
+        return Err("response lacks Cache-Control: no-store".to_string());
+    }
+    Ok(())
+}
+
+async fn single_use_code(client: &Client) -> Result<(), String> {
+    // ---
+    let code = client.issue_code(Vec::new()).await?;
+    let form = client.token_form(&code);
+
+    let first = client.token(&form).await?;
+    if first.status != 200 {
+        return Err(format!(
+            "first exchange got HTTP {} {}",
+            first.status, first.body
+        ));
+    }
+    client
+        .token(&form)
+        .await?
+        .expect_error(&[400], "invalid_grant")
+}
+
+async fn unknown_code(client: &Client) -> Result<(), String> {
+    // ---
+    let form = client.token_form("conformance-unknown-code");
+    client
+        .token(&form)
+        .await?
+        .expect_error(&[400], "invalid_grant")
+}
+
+async fn redirect_mismatch(client: &Client) -> Result<(), String> {
+    // ---
+    let code = client.issue_code(Vec::new()).await?;
+    let form = with(
+        client.token_form(&code),
+        "redirect_uri",
+        format!("{}/elsewhere", client.target.redirect_uri),
+    );
+    client
+        .token(&form)
+        .await?
+        .expect_error(&[400], "invalid_grant")
+}
+
+async fn invalid_client(client: &Client) -> Result<(), String> {
+    // ---
+    let code = client.issue_code(Vec::new()).await?;
+    let form = with(
+        client.token_form(&code),
+        "client_secret",
+        "conformance-wrong-secret",
+    );
+    client
+        .token(&form)
+        .await?
+        .expect_error(&[400, 401], "invalid_client")
+}
+
+async fn unsupported_grant_type(client: &Client) -> Result<(), String> {
+    // ---
+    let code = client.issue_code(Vec::new()).await?;
+    let form = with(
+        client.token_form(&code),
+        "grant_type",
+        "urn:tokn:conformance",
+    );
+    client
+        .token(&form)
+        .await?
+        .expect_error(&[400], "unsupported_grant_type")
+}
+
+async fn missing_parameter(client: &Client) -> Result<(), String> {
+    // ---
+    let form = without(client.token_form(""), "code");
+    client
+        .token(&form)
+        .await?
+        .expect_error(&[400], "invalid_request")
+}
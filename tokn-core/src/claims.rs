@@ -0,0 +1,414 @@
+// tokn-core/src/claims.rs
+
+//! JWT claims structures
+//!
+//! Defines the payload that will be encoded in JWT tokens.
+
+use crate::clock::Clock;
+use crate::error::{ReservedClaim, TokenError};
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use uuid::Uuid;
+
+// ---
+
+/// Claim names [`Claims::with_custom`] refuses: the RFC 7519 registered
+/// claims and the ones tokn sets itself.
+pub const RESERVED_CLAIMS: &[&str] = &[
+    "iss",
+    "sub",
+    "aud",
+    "exp",
+    "nbf",
+    "iat",
+    "jti",
+    "email",
+    "roles",
+    "scope",
+    "cnf",
+    "ver",
+    "purpose",
+    "client_id",
+];
+
+// ---
+
+/// JWT Claims following RFC 7519 standard claims.
+///
+/// # Standard Claims
+///
+/// - `iss` (issuer) - Who issued the token, omitted unless set (see
+///   [`with_issuer`](Claims::with_issuer))
+/// - `sub` (subject) - User identifier
+/// - `aud` (audience) - The services the token is meant for, omitted when
+///   unrestricted; a string for one, an array for several
+/// - `exp` (expiration) - When the token expires (Unix timestamp)
+/// - `iat` (issued at) - When the token was created (Unix timestamp)
+/// - `jti` (JWT ID) - Unique token identifier for revocation
+///
+/// # Custom Claims
+///
+/// - `email` - User email address (application-specific)
+/// - `roles` - Roles granted to the user (omitted when empty)
+/// - `scope` - Space-delimited scopes granted to the token (RFC 8693 §4.2),
+///   omitted when none
+/// - `cnf` - The DPoP key the token is bound to (RFC 9449 §6), omitted for
+///   bearer tokens
+/// - `ver` - The user's token version at issue; the issuer refuses tokens
+///   older than the current version, omitted when not tracked
+/// - `client_id` - The client the token was issued to (RFC 9068 §2.2),
+///   omitted unless set
+/// - `purpose` - What a single-purpose token (email verification, ...) is
+///   for; access-token verification refuses tokens that carry one
+/// - `custom` - Caller-supplied claims (tenant ID, plan, ...), flattened into
+///   the payload next to the ones above
+///
+/// # Security
+///
+/// - Never put sensitive data (passwords, credit cards) in claims
+/// - Claims are Base64-encoded, NOT encrypted, unless the token is a JWE
+///   (`JwtKeys::with_encryption_key`, `jwe` feature)
+/// - Anyone can decode and read the claims (signature only proves authenticity)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    // ---
+    /// Subject - User ID this token represents
+    pub sub: String,
+
+    /// Email - Custom claim for user email
+    pub email: String,
+
+    /// Expiration time (Unix timestamp)
+    pub exp: usize,
+
+    /// Issued at time (Unix timestamp)
+    pub iat: usize,
+
+    /// JWT ID - Unique identifier for this token (used for revocation)
+    pub jti: String,
+
+    /// Issuer - Who issued the token (see [`with_issuer`](Self::with_issuer))
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+
+    /// Audience - The services the token is meant for (see
+    /// [`with_audience`](Self::with_audience)); empty when unrestricted
+    #[serde(default, skip_serializing_if = "Vec::is_empty", with = "audience")]
+    pub aud: Vec<String>,
+
+    /// Roles granted to the user
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roles: Vec<String>,
+
+    /// Space-delimited scopes granted to the token (see [`scopes`](Self::scopes))
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+
+    /// Confirmation: the key a DPoP-bound token must be presented with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cnf: Option<Confirmation>,
+
+    /// Token version of the user when the token was issued (see
+    /// [`with_version`](Self::with_version))
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ver: Option<u64>,
+
+    /// The client the token was issued to (see
+    /// [`with_client_id`](Self::with_client_id))
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+
+    /// What the token is for, when it is not an access token (see
+    /// [`with_purpose`](Self::with_purpose))
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub purpose: Option<String>,
+
+    /// Any other claims in the payload; never one of [`RESERVED_CLAIMS`]
+    /// when set through [`with_custom`](Self::with_custom)
+    #[serde(flatten)]
+    pub custom: Map<String, Value>,
+}
+
+/// The `cnf` (confirmation) claim of a DPoP-bound token.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Confirmation {
+    // ---
+    /// RFC 7638 thumbprint of the client's public key (see
+    /// [`jwk_thumbprint`](crate::jwk_thumbprint))
+    pub jkt: String,
+}
+
+// ---
+
+impl Claims {
+    // ---
+    /// Create new claims with standard expiration time.
+    ///
+    /// # Arguments
+    ///
+    /// - `user_id` - Unique identifier for the user
+    /// - `email` - User's email address
+    /// - `expiry_seconds` - Token expiry duration in seconds (e.g., 900 = 15 minutes)
+    /// - `clock` - Time source for `iat` and `exp`
+    ///
+    /// # Returns
+    ///
+    /// Claims with:
+    /// - `sub` set to user_id
+    /// - `email` set to provided email
+    /// - `iat` set to `clock`'s current time
+    /// - `exp` set to `clock`'s current time + expiry_seconds
+    /// - `jti` set to random UUID v4
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use tokn_core::{Claims, SystemClock};
+    ///
+    /// let claims = Claims::new(
+    ///     "user_12345".to_string(),
+    ///     "john@example.com".to_string(),
+    ///     900, // 15 minutes
+    ///     &SystemClock,
+    /// );
+    /// ```
+    pub fn new(user_id: String, email: String, expiry_seconds: i64, clock: &dyn Clock) -> Self {
+        // ---
+        let now = clock.now();
+        let exp_time = now + Duration::seconds(expiry_seconds);
+
+        Self {
+            sub: user_id,
+            email,
+            iat: now.timestamp() as usize,
+            exp: exp_time.timestamp() as usize,
+            jti: Uuid::new_v4().to_string(),
+            iss: None,
+            aud: Vec::new(),
+            roles: Vec::new(),
+            scope: None,
+            cnf: None,
+            ver: None,
+            client_id: None,
+            purpose: None,
+            custom: Map::new(),
+        }
+    }
+
+    /// Add `custom` claims to the payload, replacing any added before.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ReservedClaim`] if `custom` sets one of [`RESERVED_CLAIMS`],
+    /// which would override the claims tokn manages (`exp`, `jti`, ...).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use serde_json::json;
+    /// use tokn_core::{Claims, SystemClock};
+    ///
+    /// let custom = json!({ "tenant_id": "acme", "roles": ["admin"] });
+    /// let claims = Claims::new("user_1".into(), "u@example.com".into(), 900, &SystemClock)
+    ///     .with_custom(custom.as_object().unwrap().clone())?;
+    /// assert_eq!(claims.custom["tenant_id"], "acme");
+    ///
+    /// let exp = json!({ "exp": 0 });
+    /// let claims = Claims::new("user_1".into(), "u@example.com".into(), 900, &SystemClock);
+    /// assert!(claims.with_custom(exp.as_object().unwrap().clone()).is_err());
+    /// # Ok::<(), tokn_core::ReservedClaim>(())
+    /// ```
+    pub fn with_custom(mut self, custom: Map<String, Value>) -> Result<Self, ReservedClaim> {
+        // ---
+        if let Some(name) = custom
+            .keys()
+            .find(|name| RESERVED_CLAIMS.contains(&name.as_str()))
+        {
+            return Err(ReservedClaim(name.clone()));
+        }
+        self.custom = custom;
+        Ok(self)
+    }
+
+    /// Grant `roles` and `scope` (space-delimited; blank means none).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tokn_core::{Claims, SystemClock};
+    ///
+    /// let claims = Claims::new("user_1".into(), "u@example.com".into(), 900, &SystemClock)
+    ///     .with_access(vec!["admin".into()], Some("orders:read orders:write".into()));
+    /// assert!(claims.has_role("admin"));
+    /// assert!(claims.has_scope("orders:write"));
+    /// assert!(!claims.has_scope("orders"));
+    /// ```
+    pub fn with_access(mut self, roles: Vec<String>, scope: Option<String>) -> Self {
+        // ---
+        self.roles = roles;
+        self.scope = scope.filter(|scope| !scope.trim().is_empty());
+        self
+    }
+
+    /// Bind the token to the DPoP key with thumbprint `jkt` (or, with `None`,
+    /// issue a bearer token).
+    pub fn with_dpop_key(mut self, jkt: Option<String>) -> Self {
+        // ---
+        self.cnf = jkt.map(|jkt| Confirmation { jkt });
+        self
+    }
+
+    /// Stamp the token with the user's token `version` (or, with `None`,
+    /// leave it unversioned). Bumping the user's version invalidates every
+    /// token stamped with an older one; unversioned tokens count as version 0.
+    pub fn with_version(mut self, version: Option<u64>) -> Self {
+        // ---
+        self.ver = version;
+        self
+    }
+
+    /// Restrict the token to `purpose` (e.g. `verify_email`). Access-token
+    /// verification ([`JwtKeys::verify`](crate::JwtKeys::verify)) refuses it;
+    /// only [`JwtKeys::verify_purpose`](crate::JwtKeys::verify_purpose) with
+    /// the same purpose accepts it.
+    pub fn with_purpose(mut self, purpose: impl Into<String>) -> Self {
+        // ---
+        self.purpose = Some(purpose.into());
+        self
+    }
+
+    /// Restrict the token to the services in `audience` (blank entries are
+    /// dropped; empty leaves it unrestricted). Verifiers expecting an
+    /// audience refuse tokens that do not list it (see [`check_audience`]).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tokn_core::{Claims, SystemClock};
+    ///
+    /// let claims = Claims::new("user_1".into(), "u@example.com".into(), 900, &SystemClock)
+    ///     .with_audience(vec!["orders-api".into(), "billing-api".into()]);
+    /// assert!(claims.has_audience("orders-api"));
+    /// assert!(!claims.has_audience("admin-api"));
+    /// ```
+    pub fn with_audience(mut self, audience: Vec<String>) -> Self {
+        // ---
+        self.aud = audience
+            .into_iter()
+            .filter(|aud| !aud.trim().is_empty())
+            .collect();
+        self
+    }
+
+    /// Name the token's `issuer` (or, with `None`, leave `iss` out).
+    pub fn with_issuer(mut self, issuer: Option<String>) -> Self {
+        // ---
+        self.iss = issuer;
+        self
+    }
+
+    /// Name the OAuth client the token is issued to (or, with `None`, leave
+    /// `client_id` out).
+    pub fn with_client_id(mut self, client_id: Option<String>) -> Self {
+        // ---
+        self.client_id = client_id;
+        self
+    }
+
+    /// Whether the token is meant for `audience`: it lists it in `aud`.
+    /// A token without `aud` is meant for no audience in particular.
+    pub fn has_audience(&self, audience: &str) -> bool {
+        // ---
+        self.aud.iter().any(|aud| aud == audience)
+    }
+
+    /// The thumbprint of the DPoP key the token is bound to, if any.
+    pub fn dpop_jkt(&self) -> Option<&str> {
+        // ---
+        self.cnf.as_ref().map(|cnf| cnf.jkt.as_str())
+    }
+
+    /// The scopes in the `scope` claim.
+    pub fn scopes(&self) -> impl Iterator<Item = &str> {
+        // ---
+        self.scope.as_deref().unwrap_or_default().split_whitespace()
+    }
+
+    /// Whether the token was granted exactly `scope`.
+    pub fn has_scope(&self, scope: &str) -> bool {
+        // ---
+        self.scopes().any(|granted| granted == scope)
+    }
+
+    /// Whether the user holds `role`.
+    pub fn has_role(&self, role: &str) -> bool {
+        // ---
+        self.roles.iter().any(|granted| granted == role)
+    }
+}
+
+// ---
+
+/// Refuse `claims` not meant for `expected`: with an expected audience, the
+/// token's `aud` must list it, and a token without `aud` is refused.
+///
+/// # Errors
+///
+/// Returns [`TokenError::WrongAudience`] if the token does not list
+/// `expected`.
+pub fn check_audience(claims: &Claims, expected: &str) -> Result<(), TokenError> {
+    // ---
+    if !claims.has_audience(expected) {
+        return Err(TokenError::WrongAudience);
+    }
+    Ok(())
+}
+
+// ---
+
+/// Serde `with` module for the `aud` claim: a single audience is written as
+/// a string and several as an array (RFC 7519 §4.1.3); either is read.
+///
+/// ```
+/// #[derive(serde::Deserialize)]
+/// struct Request {
+///     #[serde(default, with = "tokn_core::audience")]
+///     audience: Vec<String>,
+/// }
+///
+/// let one: Request = serde_json::from_str(r#"{ "audience": "orders-api" }"#).unwrap();
+/// assert_eq!(one.audience, ["orders-api"]);
+/// ```
+pub mod audience {
+    // ---
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    /// Write `audience` as a string if it has one entry, else an array.
+    pub fn serialize<S: Serializer>(audience: &[String], serializer: S) -> Result<S::Ok, S::Error> {
+        // ---
+        match audience {
+            [one] => one.serialize(serializer),
+            many => many.serialize(serializer),
+        }
+    }
+
+    /// Read a string or an array of strings; `null` is no audience.
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<String>, D::Error> {
+        // ---
+        Ok(match Option::<OneOrMany>::deserialize(deserializer)? {
+            Some(OneOrMany::One(one)) => vec![one],
+            Some(OneOrMany::Many(many)) => many,
+            None => Vec::new(),
+        })
+    }
+}
@@ -0,0 +1,24 @@
+// tokn-core/src/compare.rs
+
+//! Constant-time comparison of secrets (admin tokens, CSRF tokens, one-time
+//! code hashes)
+
+// ---
+
+/// Whether `a` equals `b`, compared without short-circuiting on the first
+/// differing byte, so response timing does not reveal how much of a secret
+/// was guessed correctly. Only the length is compared in variable time.
+///
+/// # Example
+///
+/// ```
+/// use tokn_core::constant_time_eq;
+///
+/// assert!(constant_time_eq(b"s3cret-token", b"s3cret-token"));
+/// assert!(!constant_time_eq(b"s3cret-token", b"s3cret-tokeN"));
+/// assert!(!constant_time_eq(b"s3cret", b"s3cret-token"));
+/// ```
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    // ---
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
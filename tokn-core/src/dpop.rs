@@ -0,0 +1,233 @@
+// tokn-core/src/dpop.rs
+
+//! DPoP proof-of-possession (RFC 9449)
+//!
+//! A DPoP client signs a short-lived proof JWT for every request with a key
+//! pair of its own, sending the public key in the proof header. An access
+//! token issued against a proof is bound to that key by its `cnf.jkt` claim
+//! (the key's RFC 7638 thumbprint), so a stolen token is useless without the
+//! private key.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use jsonwebtoken::jwk::{AlgorithmParameters, EllipticCurve, Jwk};
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+// ---
+
+use crate::clock::Clock;
+use crate::error::DpopError;
+
+// ---
+
+/// Header carrying the DPoP proof.
+pub const DPOP_HEADER: &str = "dpop";
+
+/// The `typ` of a proof JWT.
+const DPOP_PROOF_TYPE: &str = "dpop+jwt";
+
+/// How long a proof is accepted after its `iat`.
+pub const DPOP_PROOF_MAX_AGE_SECONDS: i64 = 300;
+
+/// Seconds a proof's `iat` may lie in the future, absorbing clock skew
+/// between client and server.
+const DPOP_PROOF_FUTURE_LEEWAY_SECONDS: i64 = 60;
+
+// ---
+
+/// The request a proof must have been made for.
+#[derive(Debug, Clone, Copy)]
+pub struct DpopRequest<'a> {
+    // ---
+    /// HTTP method (`htm`), e.g. `POST`
+    pub method: &'a str,
+
+    /// `Host` the request was sent to; when set, the authority of `htu`
+    /// must match it
+    pub host: Option<&'a str>,
+
+    /// Request path; `htu` must name it (its query and fragment are ignored)
+    pub path: &'a str,
+
+    /// Access token sent with the proof, whose hash the proof's `ath` must
+    /// carry; `None` at the token endpoint
+    pub access_token: Option<&'a str>,
+}
+
+/// A verified proof.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DpopProof {
+    // ---
+    /// Thumbprint of the proof's key, for the token's `cnf.jkt`
+    pub jkt: String,
+
+    /// Unique ID of the proof, for replay detection
+    pub jti: String,
+
+    /// When the proof was created (Unix timestamp)
+    pub iat: i64,
+}
+
+impl DpopProof {
+    // ---
+    /// When the proof stops being accepted (Unix timestamp), and so how long
+    /// its `jti` must be remembered.
+    pub fn expires_at(&self) -> i64 {
+        // ---
+        self.iat + DPOP_PROOF_MAX_AGE_SECONDS
+    }
+}
+
+/// The claims of a proof JWT.
+#[derive(Debug, Deserialize)]
+struct ProofClaims {
+    // ---
+    jti: String,
+    htm: String,
+    htu: String,
+    iat: i64,
+    #[serde(default)]
+    ath: Option<String>,
+}
+
+// ---
+
+/// Verify the DPoP `proof` sent with `request`.
+///
+/// The proof must be a JWT of type `dpop+jwt`, signed with an asymmetric
+/// algorithm by the public key in its `jwk` header, for this method and URL,
+/// issued within the last [`DPOP_PROOF_MAX_AGE_SECONDS`] of `clock`, and,
+/// when the request carries an access token, hash that token in `ath`.
+///
+/// The scheme of `htu` is not compared: behind a TLS-terminating proxy the
+/// service cannot tell which one the client used.
+///
+/// Replay detection is the caller's: remember [`DpopProof::jti`] until
+/// [`DpopProof::expires_at`] and refuse a proof seen before.
+///
+/// # Errors
+///
+/// Returns [`DpopError::Invalid`] naming the check the proof failed.
+pub fn verify_dpop_proof(
+    proof: &str,
+    request: &DpopRequest<'_>,
+    clock: &dyn Clock,
+) -> Result<DpopProof, DpopError> {
+    // ---
+    let header = decode_header(proof).map_err(|_| DpopError::Invalid("malformed"))?;
+    if header.typ.as_deref() != Some(DPOP_PROOF_TYPE) {
+        return Err(DpopError::Invalid("typ must be dpop+jwt"));
+    }
+    if matches!(
+        header.alg,
+        Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+    ) {
+        return Err(DpopError::Invalid("alg must be asymmetric"));
+    }
+    let jwk = header.jwk.ok_or(DpopError::Invalid("jwk header missing"))?;
+    let jkt = jwk_thumbprint(&jwk).ok_or(DpopError::Invalid("unsupported jwk"))?;
+
+    // The proof carries no `exp`; its age is checked against `iat` below
+    let key = DecodingKey::from_jwk(&jwk).map_err(|_| DpopError::Invalid("unusable jwk"))?;
+    let mut validation = Validation::new(header.alg);
+    validation.validate_exp = false;
+    validation.validate_aud = false;
+    validation.required_spec_claims.clear();
+    let claims = decode::<ProofClaims>(proof, &key, &validation)
+        .map_err(|_| DpopError::Invalid("signature or claims"))?
+        .claims;
+
+    if !claims.htm.eq_ignore_ascii_case(request.method) {
+        return Err(DpopError::Invalid("htm does not match the request method"));
+    }
+    if !htu_matches(&claims.htu, request.host, request.path) {
+        return Err(DpopError::Invalid("htu does not match the request URL"));
+    }
+
+    let now = clock.timestamp();
+    if claims.iat + DPOP_PROOF_MAX_AGE_SECONDS < now
+        || claims.iat > now + DPOP_PROOF_FUTURE_LEEWAY_SECONDS
+    {
+        return Err(DpopError::Invalid("iat is outside the accepted window"));
+    }
+
+    if let Some(token) = request.access_token {
+        if claims.ath.as_deref() != Some(access_token_hash(token).as_str()) {
+            return Err(DpopError::Invalid("ath does not match the access token"));
+        }
+    }
+
+    Ok(DpopProof {
+        jkt,
+        jti: claims.jti,
+        iat: claims.iat,
+    })
+}
+
+// ---
+
+/// The RFC 7638 thumbprint of `jwk`: base64url SHA-256 of its required
+/// members in lexicographic order. `None` for symmetric keys.
+pub fn jwk_thumbprint(jwk: &Jwk) -> Option<String> {
+    // ---
+    // Members are base64url or fixed names, so none needs JSON escaping
+    let canonical = match &jwk.algorithm {
+        AlgorithmParameters::EllipticCurve(params) => format!(
+            r#"{{"crv":"{}","kty":"EC","x":"{}","y":"{}"}}"#,
+            curve_name(&params.curve),
+            params.x,
+            params.y
+        ),
+        AlgorithmParameters::RSA(params) => {
+            format!(r#"{{"e":"{}","kty":"RSA","n":"{}"}}"#, params.e, params.n)
+        }
+        AlgorithmParameters::OctetKeyPair(params) => format!(
+            r#"{{"crv":"{}","kty":"OKP","x":"{}"}}"#,
+            curve_name(&params.curve),
+            params.x
+        ),
+        AlgorithmParameters::OctetKey(_) => return None,
+    };
+    Some(URL_SAFE_NO_PAD.encode(Sha256::digest(canonical.as_bytes())))
+}
+
+/// The `ath` of a proof sent with `access_token`: its base64url SHA-256.
+pub fn access_token_hash(access_token: &str) -> String {
+    // ---
+    URL_SAFE_NO_PAD.encode(Sha256::digest(access_token.as_bytes()))
+}
+
+// ---
+
+/// The JWK `crv` name of `curve`.
+fn curve_name(curve: &EllipticCurve) -> &'static str {
+    // ---
+    match curve {
+        EllipticCurve::P256 => "P-256",
+        EllipticCurve::P384 => "P-384",
+        EllipticCurve::P521 => "P-521",
+        EllipticCurve::Ed25519 => "Ed25519",
+    }
+}
+
+/// Whether `htu` names `path` on `host`, ignoring scheme, query, and
+/// fragment.
+fn htu_matches(htu: &str, host: Option<&str>, path: &str) -> bool {
+    // ---
+    let Some(rest) = htu
+        .strip_prefix("https://")
+        .or_else(|| htu.strip_prefix("http://"))
+    else {
+        return false;
+    };
+    let rest = rest.split(['?', '#']).next().unwrap_or_default();
+    let (authority, htu_path) = match rest.find('/') {
+        Some(slash) => rest.split_at(slash),
+        None => (rest, "/"),
+    };
+
+    let host_matches = host.is_none_or(|host| authority.eq_ignore_ascii_case(host));
+    host_matches && htu_path == path
+}
@@ -0,0 +1,151 @@
+// tokn-core/src/error.rs
+
+//! Error types shared across tokn services
+
+use thiserror::Error;
+
+// ---
+
+/// Errors produced while generating or validating JWTs.
+///
+/// The `Display` output of each variant is safe to return to API callers: it
+/// describes *why* a token was rejected without echoing the token itself.
+#[derive(Debug, Error)]
+pub enum TokenError {
+    // ---
+    /// The token's `exp` claim is in the past.
+    #[error("Token has expired")]
+    Expired,
+
+    /// The signature does not match (tampered token or wrong secret).
+    #[error("Invalid token signature")]
+    InvalidSignature,
+
+    /// The token was signed with an algorithm other than the one we accept.
+    #[error("Invalid token algorithm")]
+    InvalidAlgorithm,
+
+    /// No key in the JWKS or key ring matches the token's `kid`, or the
+    /// matching key is unusable.
+    #[error("Unknown token signing key")]
+    UnknownKey,
+
+    /// The token is for another purpose: a single-purpose token presented
+    /// as an access token, or the other way round.
+    #[error("Token is not valid for this purpose")]
+    WrongPurpose,
+
+    /// The token is not meant for the audience it was presented to: its
+    /// `aud` claim does not list it.
+    #[error("Token is not valid for this audience")]
+    WrongAudience,
+
+    /// The token's `typ` header is not `at+jwt`, where the JWT access token
+    /// profile (RFC 9068) is required.
+    #[error("Token type is not at+jwt")]
+    WrongType,
+
+    /// The token could not be parsed (bad structure, encoding, or claims).
+    #[error("Malformed token")]
+    Malformed,
+
+    /// Encoding a token failed (e.g. unusable signing key).
+    #[error("Failed to encode JWT token")]
+    Encoding(#[source] jsonwebtoken::errors::Error),
+
+    /// A signing or verification key could not be loaded, or cannot sign.
+    #[error("Invalid signing key: {0}")]
+    InvalidKey(String),
+}
+
+// ---
+
+impl From<jsonwebtoken::errors::Error> for TokenError {
+    // ---
+    fn from(err: jsonwebtoken::errors::Error) -> Self {
+        // ---
+        use jsonwebtoken::errors::ErrorKind;
+
+        match err.kind() {
+            ErrorKind::ExpiredSignature => TokenError::Expired,
+            ErrorKind::InvalidSignature => TokenError::InvalidSignature,
+            ErrorKind::InvalidAlgorithm => TokenError::InvalidAlgorithm,
+            _ => TokenError::Malformed,
+        }
+    }
+}
+
+// ---
+
+/// A custom claim uses a name tokn manages itself (see
+/// [`RESERVED_CLAIMS`](crate::RESERVED_CLAIMS)).
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("'{0}' is a reserved claim and cannot be set")]
+pub struct ReservedClaim(pub String);
+
+// ---
+
+/// Errors produced while extracting a Bearer token from the `Authorization` header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum AuthHeaderError {
+    // ---
+    /// No `Authorization` header was sent.
+    #[error("Missing Authorization header")]
+    Missing,
+
+    /// The header is not valid ASCII or does not use the `Bearer` (or, where
+    /// accepted, `DPoP`) scheme.
+    #[error("Invalid Authorization header format")]
+    InvalidFormat,
+}
+
+// ---
+
+/// Errors produced while checking DPoP proof-of-possession (RFC 9449).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum DpopError {
+    // ---
+    /// A DPoP-bound token was sent without a `DPoP` proof header.
+    #[error("Missing DPoP proof")]
+    Missing,
+
+    /// The proof failed the named check.
+    #[error("Invalid DPoP proof: {0}")]
+    Invalid(&'static str),
+
+    /// The proof's `jti` was seen before.
+    #[error("DPoP proof has already been used")]
+    Replayed,
+
+    /// The proof was signed by a key other than the one the token is bound to.
+    #[error("DPoP proof key does not match the token")]
+    KeyMismatch,
+
+    /// A DPoP-bound token was sent with the `Bearer` scheme, or a bearer
+    /// token with the `DPoP` scheme.
+    #[error("Authorization scheme does not match the token binding")]
+    SchemeMismatch,
+}
+
+// ---
+
+/// Errors produced while checking PKCE parameters (RFC 7636).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum PkceError {
+    // ---
+    /// `code_challenge_method` is not `S256` (missing means `plain`).
+    #[error("code_challenge_method must be S256")]
+    UnsupportedMethod,
+
+    /// `code_challenge` is not a base64url SHA-256 digest.
+    #[error("code_challenge must be 43 base64url characters")]
+    InvalidChallenge,
+
+    /// `code_verifier` is not 43 to 128 unreserved characters.
+    #[error("code_verifier must be 43 to 128 unreserved characters")]
+    InvalidVerifier,
+
+    /// `code_verifier` does not hash to the stored challenge.
+    #[error("code_verifier does not match the code_challenge")]
+    Mismatch,
+}
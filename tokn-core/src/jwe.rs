@@ -0,0 +1,146 @@
+// tokn-core/src/jwe.rs
+
+//! Encrypted access tokens (`jwe` feature)
+//!
+//! A signed JWT nested in a compact JWE (RFC 7516) with direct key agreement
+//! (`alg: dir`) and AES-256-GCM (`enc: A256GCM`), so holders of the token
+//! cannot read its claims. The inner JWT is signed as usual; decryption only
+//! uncovers it for the normal signature and expiry checks.
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::Deserialize;
+
+// ---
+
+use crate::error::TokenError;
+
+// ---
+
+/// Length of an A256GCM content encryption key, in bytes.
+pub const JWE_KEY_LENGTH: usize = 32;
+
+/// Protected header of every token we encrypt. `cty: JWT` marks the payload
+/// as a nested JWT (RFC 7519 §5.2).
+const JWE_HEADER: &str = r#"{"alg":"dir","enc":"A256GCM","cty":"JWT"}"#;
+
+const NONCE_LENGTH: usize = 12;
+const TAG_LENGTH: usize = 16;
+
+// ---
+
+/// The header fields decryption depends on.
+#[derive(Deserialize)]
+struct Header {
+    // ---
+    alg: String,
+    enc: String,
+}
+
+/// An A256GCM key shared by the issuer and every verifier.
+#[derive(Clone)]
+pub(crate) struct JweKey {
+    // ---
+    cipher: Aes256Gcm,
+}
+
+impl JweKey {
+    // ---
+    /// A key from [`JWE_KEY_LENGTH`] raw bytes.
+    pub(crate) fn new(key: &[u8]) -> Result<Self, TokenError> {
+        // ---
+        let cipher = Aes256Gcm::new_from_slice(key).map_err(|_| {
+            TokenError::InvalidKey(format!(
+                "encryption key must be {JWE_KEY_LENGTH} bytes, not {}",
+                key.len()
+            ))
+        })?;
+        Ok(Self { cipher })
+    }
+
+    // ---
+    /// Encrypt the signed JWT `jws` into a compact JWE.
+    pub(crate) fn encrypt(&self, jws: &str) -> Result<String, TokenError> {
+        // ---
+        let header = URL_SAFE_NO_PAD.encode(JWE_HEADER);
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let sealed = self
+            .cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: jws.as_bytes(),
+                    aad: header.as_bytes(),
+                },
+            )
+            .map_err(|_| TokenError::InvalidKey("encryption failed".into()))?;
+
+        // aes-gcm appends the tag to the ciphertext; JWE carries it apart
+        let (ciphertext, tag) = sealed.split_at(sealed.len() - TAG_LENGTH);
+        Ok(format!(
+            "{header}..{}.{}.{}",
+            URL_SAFE_NO_PAD.encode(nonce),
+            URL_SAFE_NO_PAD.encode(ciphertext),
+            URL_SAFE_NO_PAD.encode(tag)
+        ))
+    }
+
+    /// Decrypt the compact JWE `token` into the signed JWT it carries.
+    ///
+    /// Returns [`TokenError::InvalidAlgorithm`] for a JWE other than
+    /// `dir`/`A256GCM`, [`TokenError::InvalidSignature`] if it does not
+    /// decrypt with this key, and [`TokenError::Malformed`] otherwise.
+    pub(crate) fn decrypt(&self, token: &str) -> Result<String, TokenError> {
+        // ---
+        let [header, encrypted_key, nonce, ciphertext, tag] = jwe_parts(token)?;
+
+        let decoded = URL_SAFE_NO_PAD
+            .decode(header)
+            .map_err(|_| TokenError::Malformed)?;
+        let Header { alg, enc } =
+            serde_json::from_slice(&decoded).map_err(|_| TokenError::Malformed)?;
+        if alg != "dir" || enc != "A256GCM" || !encrypted_key.is_empty() {
+            return Err(TokenError::InvalidAlgorithm);
+        }
+
+        let decode = |part: &str| {
+            URL_SAFE_NO_PAD
+                .decode(part)
+                .map_err(|_| TokenError::Malformed)
+        };
+        let nonce = decode(nonce)?;
+        let mut sealed = decode(ciphertext)?;
+        let tag = decode(tag)?;
+        if nonce.len() != NONCE_LENGTH || tag.len() != TAG_LENGTH {
+            return Err(TokenError::Malformed);
+        }
+        sealed.extend_from_slice(&tag);
+
+        let jws = self
+            .cipher
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &sealed,
+                    aad: header.as_bytes(),
+                },
+            )
+            .map_err(|_| TokenError::InvalidSignature)?;
+        String::from_utf8(jws).map_err(|_| TokenError::Malformed)
+    }
+}
+
+/// Whether `token` is a compact JWE (five parts) rather than a JWS (three).
+pub(crate) fn is_jwe(token: &str) -> bool {
+    // ---
+    token.split('.').count() == 5
+}
+
+/// The five parts of a compact JWE.
+fn jwe_parts(token: &str) -> Result<[&str; 5], TokenError> {
+    // ---
+    let parts: Vec<&str> = token.split('.').collect();
+    parts.try_into().map_err(|_| TokenError::Malformed)
+}
@@ -0,0 +1,136 @@
+// tokn-core/src/jwks.rs
+
+//! Token validation against a JSON Web Key Set (RFC 7517)
+//!
+//! For verifiers that hold a published key set rather than the signing
+//! secret, such as edge workers and browser tooling built for wasm32.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use jsonwebtoken::jwk::{AlgorithmParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm};
+use jsonwebtoken::{decode_header, Algorithm, DecodingKey};
+
+// ---
+
+use crate::claims::Claims;
+use crate::clock::Clock;
+use crate::error::TokenError;
+use crate::token::{check_purpose, decode_claims, DEFAULT_LEEWAY_SECONDS};
+
+// ---
+
+/// Validate and decode a JWT with a key from `jwks`.
+///
+/// The key is the one whose `kid` matches the token header's; a token
+/// without a `kid` is only accepted when the set holds exactly one key.
+///
+/// # Security
+///
+/// Validates the same as [`validate_token`](crate::validate_token), except
+/// that the algorithm is pinned by the key rather than fixed to HS256: the
+/// JWK's `alg` if it declares one, otherwise HS256 for `oct`, RS256 for
+/// `RSA`, ES256 or ES384 for `EC` (by curve), and EdDSA for `OKP`. A token
+/// whose header names any other algorithm is rejected, so an RSA public key
+/// can never be used as an HMAC secret.
+///
+/// An `oct` key *is* the HS256 signing secret: a set containing one must be
+/// distributed as privately as `JWT_SECRET` itself.
+///
+/// # Errors
+///
+/// Returns:
+/// - [`TokenError::UnknownKey`] if no key matches, or the matching key
+///   cannot be used
+/// - [`TokenError::InvalidAlgorithm`] if the token's algorithm is not the
+///   key's
+/// - any error [`validate_token`](crate::validate_token) returns
+///
+/// # Example
+///
+/// ```no_run
+/// use tokn_core::{validate_token_with_jwks, JwkSet, SystemClock};
+///
+/// let jwks: JwkSet = serde_json::from_str(r#"{"keys": []}"#)?;
+/// let token = "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9...";
+///
+/// match validate_token_with_jwks(token, &jwks, &SystemClock) {
+///     Ok(claims) => println!("Valid token for user: {}", claims.sub),
+///     Err(e) => println!("Invalid token: {}", e),
+/// }
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+pub fn validate_token_with_jwks(
+    token: &str,
+    jwks: &JwkSet,
+    clock: &dyn Clock,
+) -> Result<Claims, TokenError> {
+    // ---
+    let header = decode_header(token)?;
+    let jwk = match &header.kid {
+        Some(kid) => jwks.find(kid),
+        None => match jwks.keys.as_slice() {
+            [only] => Some(only),
+            _ => None,
+        },
+    }
+    .ok_or(TokenError::UnknownKey)?;
+
+    let algorithm = key_algorithm(jwk).ok_or(TokenError::UnknownKey)?;
+    let key = decoding_key(jwk).ok_or(TokenError::UnknownKey)?;
+
+    let claims = decode_claims(token, &key, algorithm, clock, DEFAULT_LEEWAY_SECONDS)?;
+    check_purpose(&claims, None)?;
+    Ok(claims)
+}
+
+// ---
+
+/// The verification key `jwk` carries.
+fn decoding_key(jwk: &Jwk) -> Option<DecodingKey> {
+    // ---
+    match &jwk.algorithm {
+        // `k` is base64url (RFC 7518 §6.4.1); `DecodingKey::from_jwk` expects
+        // standard base64
+        AlgorithmParameters::OctetKey(oct) => {
+            let secret = URL_SAFE_NO_PAD
+                .decode(oct.value.trim_end_matches('='))
+                .ok()?;
+            Some(DecodingKey::from_secret(&secret))
+        }
+        _ => DecodingKey::from_jwk(jwk).ok(),
+    }
+}
+
+/// The one signing algorithm `jwk` verifies, or `None` for encryption keys
+/// and unsupported curves.
+fn key_algorithm(jwk: &Jwk) -> Option<Algorithm> {
+    // ---
+    if let Some(declared) = jwk.common.key_algorithm {
+        return match declared {
+            KeyAlgorithm::HS256 => Some(Algorithm::HS256),
+            KeyAlgorithm::HS384 => Some(Algorithm::HS384),
+            KeyAlgorithm::HS512 => Some(Algorithm::HS512),
+            KeyAlgorithm::ES256 => Some(Algorithm::ES256),
+            KeyAlgorithm::ES384 => Some(Algorithm::ES384),
+            KeyAlgorithm::RS256 => Some(Algorithm::RS256),
+            KeyAlgorithm::RS384 => Some(Algorithm::RS384),
+            KeyAlgorithm::RS512 => Some(Algorithm::RS512),
+            KeyAlgorithm::PS256 => Some(Algorithm::PS256),
+            KeyAlgorithm::PS384 => Some(Algorithm::PS384),
+            KeyAlgorithm::PS512 => Some(Algorithm::PS512),
+            KeyAlgorithm::EdDSA => Some(Algorithm::EdDSA),
+            _ => None,
+        };
+    }
+
+    match &jwk.algorithm {
+        AlgorithmParameters::OctetKey(_) => Some(Algorithm::HS256),
+        AlgorithmParameters::RSA(_) => Some(Algorithm::RS256),
+        AlgorithmParameters::EllipticCurve(params) => match params.curve {
+            EllipticCurve::P256 => Some(Algorithm::ES256),
+            EllipticCurve::P384 => Some(Algorithm::ES384),
+            _ => None,
+        },
+        AlgorithmParameters::OctetKeyPair(_) => Some(Algorithm::EdDSA),
+    }
+}
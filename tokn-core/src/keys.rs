@@ -0,0 +1,215 @@
+// tokn-core/src/keys.rs
+
+//! Redis key naming conventions
+//!
+//! Every service that reads or writes token state in Redis must build its keys
+//! through these functions so the layout stays consistent across the workspace.
+//!
+//! # Key Layout
+//!
+//! | Key                                  | Value                                    | TTL                         |
+//! |--------------------------------------|------------------------------------------|-----------------------------|
+//! | `refresh_token:{sha256(token)}`      | JSON `RefreshTokenData`                  | refresh token lifetime      |
+//! | `used_refresh_token:{sha256(token)}` | JSON `RefreshTokenData`                  | remaining refresh token TTL |
+//! | `refresh_successor:{sha256(token)}`  | Successor token, sealed with the token   | refresh grace window        |
+//! | `blacklist:jti:{jti}`                | `"revoked"`                              | remaining access token TTL  |
+//! | `consumed:jti:{jti}`                 | `"consumed"`                             | remaining single-use TTL    |
+//! | `user_sessions:{user_id}`            | Hash: session ID → refresh token SHA-256 | longest refresh token TTL   |
+//! | `issuer_keys`                        | Hash: key SHA-256 → JSON entry           | none                        |
+//! | `dpop_proof:{jti}`                   | `"used"`                                 | remaining DPoP proof age    |
+//! | `token_version:{user_id}`            | Counter: the user's `ver` claim          | none                        |
+//! | `revoked_before:{user_id}`           | Unix timestamp: older `iat`s are revoked | none                        |
+//! | `ticket:{ticket}`                    | JSON `Claims`                            | ticket lifetime             |
+//! | `api_keys`                           | Hash: key SHA-256 → JSON entry           | none                        |
+//! | `user_keys:{hash}:{user_id}`         | Set: SHA-256s of the user's keys in hash | none                        |
+//! | `magic_link:{token}`                 | JSON `Claims`                            | magic link lifetime         |
+//!
+//! Refresh tokens are keyed by their hex SHA-256, so read access to Redis
+//! yields no token that can be replayed. Tokens stored before that, under
+//! `refresh_token:{token}` and indexed by the token itself, are still found
+//! through [`legacy_refresh_token`] and [`legacy_used_refresh_token`] until
+//! they expire.
+
+use sha2::{Digest, Sha256};
+
+// ---
+
+/// Prefix for refresh token entries.
+pub const REFRESH_TOKEN_PREFIX: &str = "refresh_token:";
+
+/// Prefix for refresh tokens already consumed by rotation.
+pub const USED_REFRESH_TOKEN_PREFIX: &str = "used_refresh_token:";
+
+/// Prefix for the tokens refresh tokens were just rotated into.
+pub const REFRESH_SUCCESSOR_PREFIX: &str = "refresh_successor:";
+
+/// Prefix for revoked (blacklisted) access token JTIs.
+pub const BLACKLIST_JTI_PREFIX: &str = "blacklist:jti:";
+
+/// Prefix for the JTIs of single-use tokens (email verification, password
+/// reset) already used; kept apart from the blacklist operators edit.
+pub const CONSUMED_JTI_PREFIX: &str = "consumed:jti:";
+
+/// Prefix for the per-user index of refresh token sessions.
+pub const USER_SESSIONS_PREFIX: &str = "user_sessions:";
+
+/// Hash of the API keys allowed to call jwt-service's token endpoint, by
+/// the SHA-256 of the key.
+pub const ISSUER_KEYS: &str = "issuer_keys";
+
+/// Prefix for the IDs of DPoP proofs already presented.
+pub const DPOP_PROOF_PREFIX: &str = "dpop_proof:";
+
+/// Prefix for the per-user token version counters.
+pub const TOKEN_VERSION_PREFIX: &str = "token_version:";
+
+/// Prefix for the per-user revocation cutoffs.
+pub const REVOKED_BEFORE_PREFIX: &str = "revoked_before:";
+
+/// Prefix for single-use WebSocket/SSE tickets.
+pub const TICKET_PREFIX: &str = "ticket:";
+
+/// Hash of the long-lived API keys machine clients authenticate with instead
+/// of access tokens, by the SHA-256 of the key.
+pub const API_KEYS: &str = "api_keys";
+
+/// Prefix for the per-user indexes of the keys in [`API_KEYS`] and
+/// [`ISSUER_KEYS`].
+pub const USER_KEYS_PREFIX: &str = "user_keys:";
+
+/// Prefix for unredeemed magic login links.
+pub const MAGIC_LINK_PREFIX: &str = "magic_link:";
+
+// ---
+
+/// Hex SHA-256 of a refresh token: all Redis keeps of it, in its key and the
+/// session index.
+pub fn refresh_token_hash(token: &str) -> String {
+    // ---
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// Whether `s` is a [`refresh_token_hash`] rather than a token stored before
+/// tokens were hashed (those are UUIDs).
+pub fn is_refresh_token_hash(s: &str) -> bool {
+    // ---
+    s.len() == 64 && s.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+// ---
+
+/// Redis key for a stored refresh token.
+pub fn refresh_token(token: &str) -> String {
+    // ---
+    format!("{REFRESH_TOKEN_PREFIX}{}", refresh_token_hash(token))
+}
+
+/// Redis key a refresh token was stored under before tokens were hashed; only
+/// read, never written.
+///
+/// `None` for a "token" shaped like a hash: its legacy key would be the
+/// hashed entry of another token, which the hash must not unlock.
+pub fn legacy_refresh_token(token: &str) -> Option<String> {
+    // ---
+    (!is_refresh_token_hash(token)).then(|| format!("{REFRESH_TOKEN_PREFIX}{token}"))
+}
+
+// ---
+
+/// Redis key remembering that a refresh token was rotated, so a replay can be
+/// told apart from an unknown token.
+pub fn used_refresh_token(token: &str) -> String {
+    // ---
+    format!("{USED_REFRESH_TOKEN_PREFIX}{}", refresh_token_hash(token))
+}
+
+/// Redis key a rotated refresh token was remembered under before tokens were
+/// hashed; only read, never written. `None` for a "token" shaped like a hash,
+/// as with [`legacy_refresh_token`].
+pub fn legacy_used_refresh_token(token: &str) -> Option<String> {
+    // ---
+    (!is_refresh_token_hash(token)).then(|| format!("{USED_REFRESH_TOKEN_PREFIX}{token}"))
+}
+
+/// Redis key holding the token a refresh token was rotated into, during the
+/// refresh grace window.
+pub fn refresh_successor(token: &str) -> String {
+    // ---
+    format!("{REFRESH_SUCCESSOR_PREFIX}{}", refresh_token_hash(token))
+}
+
+// ---
+
+/// Redis key marking an access token's JTI as revoked.
+pub fn blacklisted_jti(jti: &str) -> String {
+    // ---
+    format!("{BLACKLIST_JTI_PREFIX}{jti}")
+}
+
+/// Redis key marking a single-use token's JTI as used.
+pub fn consumed_jti(jti: &str) -> String {
+    // ---
+    format!("{CONSUMED_JTI_PREFIX}{jti}")
+}
+
+// ---
+
+/// Redis key of the hash indexing `user_id`'s sessions: session ID → current
+/// refresh token's hash.
+pub fn user_sessions(user_id: &str) -> String {
+    // ---
+    format!("{USER_SESSIONS_PREFIX}{user_id}")
+}
+
+// ---
+
+/// Redis key remembering that a DPoP proof was used, so it cannot be
+/// replayed.
+pub fn dpop_proof(jti: &str) -> String {
+    // ---
+    format!("{DPOP_PROOF_PREFIX}{jti}")
+}
+
+// ---
+
+/// Redis key counting `user_id`'s token version: access tokens stamped with
+/// an older `ver` are refused.
+pub fn token_version(user_id: &str) -> String {
+    // ---
+    format!("{TOKEN_VERSION_PREFIX}{user_id}")
+}
+
+/// Redis key holding `user_id`'s revocation cutoff: tokens issued before it
+/// are refused.
+pub fn revoked_before(user_id: &str) -> String {
+    // ---
+    format!("{REVOKED_BEFORE_PREFIX}{user_id}")
+}
+
+// ---
+
+/// Redis key holding the claims a single-use ticket stands for, until the
+/// ticket is redeemed.
+pub fn ticket(ticket: &str) -> String {
+    // ---
+    format!("{TICKET_PREFIX}{ticket}")
+}
+
+// ---
+
+/// Redis key of the set indexing the keys in the hash `keys` (e.g.
+/// [`API_KEYS`]) that act as `user_id`, by the SHA-256 of the key; it may
+/// still name keys deleted one by one.
+pub fn user_keys(keys: &str, user_id: &str) -> String {
+    // ---
+    format!("{USER_KEYS_PREFIX}{keys}:{user_id}")
+}
+
+// ---
+
+/// Redis key holding the claims a magic login link grants, until the link is
+/// redeemed.
+pub fn magic_link(token: &str) -> String {
+    // ---
+    format!("{MAGIC_LINK_PREFIX}{token}")
+}
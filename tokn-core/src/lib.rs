@@ -0,0 +1,70 @@
+// tokn-core/src/lib.rs
+
+//! Shared core types for the tokn workspace
+//!
+//! Provides the pieces every tokn service agrees on:
+//! - JWT claims and token generation/validation, with an HS256 secret or an
+//!   RS256, ES256, or EdDSA key pair ([`JwtKeys`]); PASETO v4 tokens with the
+//!   `paseto` feature ([`TokenFormat`]), and JWE-encrypted JWTs with the `jwe`
+//!   feature
+//! - Validation against a JSON Web Key Set, for verifiers without the secret
+//! - A [`Clock`] abstraction so expiry can be tested without sleeping
+//! - Typed token and Authorization-header errors
+//! - Bearer token extraction from HTTP headers
+//! - DPoP proof verification, binding tokens to a client key (RFC 9449)
+//! - PKCE S256 challenge and verifier checks (RFC 7636)
+//! - RFC 7807 problem details, the error body of the JSON APIs (`IntoResponse`
+//!   with the `axum` feature)
+//! - Redis key naming conventions
+//! - Constant-time comparison of secrets
+//! - The userinfo response contract between oauth2-server and oauth2-client
+//!
+//! Without the `axum` feature this crate has no runtime dependencies (no tokio,
+//! no Redis, no database) so it can be shared by every service without pulling
+//! in their infrastructure. It also builds for `wasm32-unknown-unknown`, taking
+//! time and randomness from the JavaScript host, so edge workers and browser
+//! tooling can validate tokens with the same code as the services:
+//!
+//! ```bash
+//! cargo build -p tokn-core --target wasm32-unknown-unknown
+//! ```
+
+mod bearer;
+mod claims;
+mod clock;
+mod compare;
+mod dpop;
+mod error;
+#[cfg(feature = "jwe")]
+mod jwe;
+mod jwks;
+#[cfg(feature = "paseto")]
+mod paseto;
+mod pkce;
+mod problem;
+mod signing;
+mod token;
+mod userinfo;
+
+pub mod keys;
+
+// ---
+
+pub use bearer::{authorization_token, bearer_token, cookie_value, AuthScheme};
+pub use claims::{audience, check_audience, Claims, Confirmation, RESERVED_CLAIMS};
+pub use clock::{Clock, SharedClock, SystemClock, TestClock};
+pub use compare::constant_time_eq;
+pub use dpop::{
+    access_token_hash, jwk_thumbprint, verify_dpop_proof, DpopProof, DpopRequest, DPOP_HEADER,
+    DPOP_PROOF_MAX_AGE_SECONDS,
+};
+pub use error::{AuthHeaderError, DpopError, PkceError, ReservedClaim, TokenError};
+pub use jsonwebtoken::jwk::JwkSet;
+#[cfg(feature = "jwe")]
+pub use jwe::JWE_KEY_LENGTH;
+pub use jwks::validate_token_with_jwks;
+pub use pkce::{check_code_challenge, pkce_challenge, verify_code_verifier, PKCE_S256};
+pub use problem::{Problem, ABOUT_BLANK, PROBLEM_JSON};
+pub use signing::{JwtKeys, SigningAlgorithm, AT_JWT_TYPE};
+pub use token::{generate_token, validate_token, TokenFormat, DEFAULT_LEEWAY_SECONDS};
+pub use userinfo::UserInfo;
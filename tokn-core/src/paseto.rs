@@ -0,0 +1,199 @@
+// tokn-core/src/paseto.rs
+
+//! PASETO v4 access tokens (`paseto` feature)
+//!
+//! The same [`Claims`] as the JWTs, in a PASETO envelope: `v4.local`
+//! (XChaCha20 encrypted, keyed from the HS256 secret) or `v4.public`
+//! (Ed25519 signed). The version and purpose fix the algorithm, so there is
+//! no header for a token to pick a weaker one with. `exp` and `iat` are
+//! RFC 3339 times in the payload, as PASETO registers them.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use pasetors::keys::{AsymmetricPublicKey, AsymmetricSecretKey, SymmetricKey};
+use pasetors::token::UntrustedToken;
+use pasetors::version4::{LocalToken, PublicToken, V4};
+use pasetors::{Local, Public};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+// ---
+
+use crate::claims::Claims;
+use crate::clock::Clock;
+use crate::error::TokenError;
+use crate::token::check_expiry;
+
+// ---
+
+/// Domain separation for the `v4.local` key, so the HS256 secret is never
+/// used as an encryption key as-is.
+const LOCAL_KEY_CONTEXT: &[u8] = b"tokn paseto v4.local key";
+
+/// Claims PASETO registers as RFC 3339 times rather than Unix timestamps.
+const TIME_CLAIMS: &[&str] = &["exp", "iat"];
+
+// ---
+
+/// A PASETO v4 key, its purpose fixed.
+#[derive(Clone)]
+pub(crate) enum PasetoKey {
+    // ---
+    /// `v4.local`: one key encrypts and decrypts
+    Local(SymmetricKey<V4>),
+
+    /// `v4.public`: Ed25519, signing only with the secret key
+    Public {
+        secret: Option<AsymmetricSecretKey<V4>>,
+        public: AsymmetricPublicKey<V4>,
+    },
+}
+
+impl PasetoKey {
+    // ---
+    /// A `v4.local` key derived from `secret`.
+    pub(crate) fn local(secret: &str) -> Self {
+        // ---
+        let key = Sha256::new()
+            .chain_update(LOCAL_KEY_CONTEXT)
+            .chain_update(secret.as_bytes())
+            .finalize();
+        Self::Local(SymmetricKey::<V4>::from(&key).expect("SHA-256 output is a 32-byte key"))
+    }
+
+    /// A `v4.public` key from an Ed25519 PEM public key and, to sign, the
+    /// PKCS#8 PEM private key.
+    pub(crate) fn public(
+        private_pem: Option<&[u8]>,
+        public_pem: &[u8],
+    ) -> Result<Self, TokenError> {
+        // ---
+        // SPKI: a 12-byte algorithm prefix, then the 32-byte key
+        let public_key = pem_tail(public_pem, 44, "Ed25519 public key")?;
+        let public = AsymmetricPublicKey::<V4>::from(&public_key)
+            .map_err(|e| TokenError::InvalidKey(format!("Ed25519 public key: {e}")))?;
+
+        let secret = match private_pem {
+            Some(pem) => {
+                // PKCS#8 v1: a 16-byte prefix, then the 32-byte seed. The V4
+                // secret key is the seed followed by the public key.
+                let mut keypair = pem_tail(pem, 48, "Ed25519 private key")?;
+                keypair.extend_from_slice(&public_key);
+                let secret = AsymmetricSecretKey::<V4>::from(&keypair)
+                    .map_err(|e| TokenError::InvalidKey(format!("Ed25519 private key: {e}")))?;
+                Some(secret)
+            }
+            None => None,
+        };
+
+        Ok(Self::Public { secret, public })
+    }
+
+    /// Whether this key can issue tokens.
+    pub(crate) fn can_sign(&self) -> bool {
+        // ---
+        match self {
+            Self::Local(_) => true,
+            Self::Public { secret, .. } => secret.is_some(),
+        }
+    }
+
+    // ---
+    /// Encrypt or sign `claims` into a token.
+    pub(crate) fn sign(&self, claims: &Claims) -> Result<String, TokenError> {
+        // ---
+        let payload = encode_payload(claims)?;
+        let token = match self {
+            Self::Local(key) => LocalToken::encrypt(key, &payload, None, None),
+            Self::Public { secret, .. } => {
+                let secret = secret
+                    .as_ref()
+                    .ok_or_else(|| TokenError::InvalidKey("no private key to sign with".into()))?;
+                PublicToken::sign(secret, &payload, None, None)
+            }
+        };
+        token.map_err(|e| TokenError::InvalidKey(format!("PASETO: {e}")))
+    }
+
+    /// Decrypt or verify `token` and check `exp` against `clock`.
+    ///
+    /// A JWT, or a PASETO of another version or purpose, is refused with
+    /// [`TokenError::InvalidAlgorithm`].
+    pub(crate) fn verify(
+        &self,
+        token: &str,
+        clock: &dyn Clock,
+        leeway: u64,
+    ) -> Result<Claims, TokenError> {
+        // ---
+        let trusted = match self {
+            Self::Local(key) => {
+                let token = UntrustedToken::<Local, V4>::try_from(token)
+                    .map_err(|_| TokenError::InvalidAlgorithm)?;
+                LocalToken::decrypt(key, &token, None, None)
+            }
+            Self::Public { public, .. } => {
+                let token = UntrustedToken::<Public, V4>::try_from(token)
+                    .map_err(|_| TokenError::InvalidAlgorithm)?;
+                PublicToken::verify(public, &token, None, None)
+            }
+        }
+        .map_err(|_| TokenError::InvalidSignature)?;
+
+        let claims = decode_payload(trusted.payload())?;
+        check_expiry(&claims, clock, leeway)?;
+        Ok(claims)
+    }
+}
+
+// ---
+
+/// `claims` as a PASETO payload, with [`TIME_CLAIMS`] as RFC 3339 times.
+fn encode_payload(claims: &Claims) -> Result<Vec<u8>, TokenError> {
+    // ---
+    let mut payload = serde_json::to_value(claims).map_err(|e| TokenError::Encoding(e.into()))?;
+    for name in TIME_CLAIMS {
+        if let Some(value) = payload.get_mut(*name) {
+            let time = value
+                .as_i64()
+                .and_then(|secs| DateTime::<Utc>::from_timestamp(secs, 0))
+                .ok_or(TokenError::Malformed)?;
+            *value = Value::String(time.to_rfc3339());
+        }
+    }
+    serde_json::to_vec(&payload).map_err(|e| TokenError::Encoding(e.into()))
+}
+
+/// The claims of a verified PASETO payload.
+fn decode_payload(payload: &str) -> Result<Claims, TokenError> {
+    // ---
+    let mut payload: Value = serde_json::from_str(payload).map_err(|_| TokenError::Malformed)?;
+    for name in TIME_CLAIMS {
+        if let Some(value) = payload.get_mut(*name) {
+            let time = value
+                .as_str()
+                .and_then(|time| DateTime::parse_from_rfc3339(time).ok())
+                .ok_or(TokenError::Malformed)?;
+            *value = time.timestamp().into();
+        }
+    }
+    serde_json::from_value(payload).map_err(|_| TokenError::Malformed)
+}
+
+/// The last 32 bytes of the `len`-byte DER in `pem`, a `what` Ed25519 key.
+fn pem_tail(pem: &[u8], len: usize, what: &str) -> Result<Vec<u8>, TokenError> {
+    // ---
+    let invalid = || TokenError::InvalidKey(format!("{what}: not an Ed25519 PEM key"));
+    let pem = std::str::from_utf8(pem).map_err(|_| invalid())?;
+    let base64: String = pem
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with("-----"))
+        .collect();
+    let der = STANDARD.decode(base64).map_err(|_| invalid())?;
+    if der.len() != len {
+        return Err(invalid());
+    }
+    Ok(der[len - 32..].to_vec())
+}
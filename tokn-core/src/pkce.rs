@@ -0,0 +1,81 @@
+// tokn-core/src/pkce.rs
+
+//! Proof Key for Code Exchange (RFC 7636)
+//!
+//! A client sends the `code_challenge` of a random `code_verifier` with its
+//! authorization request, and the verifier itself with the token request, so
+//! an intercepted authorization code cannot be exchanged without it. Only the
+//! `S256` method is accepted: `plain` sends the verifier in the clear.
+
+use crate::error::PkceError;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use sha2::{Digest, Sha256};
+
+// ---
+
+/// The one `code_challenge_method` accepted.
+pub const PKCE_S256: &str = "S256";
+
+/// Length of an S256 challenge: a base64url SHA-256 digest, unpadded.
+const CHALLENGE_LEN: usize = 43;
+
+// ---
+
+/// The S256 `code_challenge` of `verifier`: its base64url SHA-256.
+///
+/// # Example
+///
+/// ```
+/// use tokn_core::pkce_challenge;
+///
+/// // RFC 7636 Appendix B
+/// assert_eq!(
+///     pkce_challenge("dBjftJeZ4CVP-mJ92K1qW8-2y4Edry9xXNC6J5HJ0RQ"),
+///     "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+/// );
+/// ```
+pub fn pkce_challenge(verifier: &str) -> String {
+    // ---
+    URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+/// Check the `code_challenge` and `code_challenge_method` of an authorization
+/// request. A missing method means `plain` (RFC 7636 §4.3), which is refused.
+///
+/// # Errors
+///
+/// Returns [`PkceError::UnsupportedMethod`] unless `method` is `S256`, and
+/// [`PkceError::InvalidChallenge`] if `challenge` is not 43 base64url
+/// characters.
+pub fn check_code_challenge(challenge: &str, method: Option<&str>) -> Result<(), PkceError> {
+    // ---
+    if method != Some(PKCE_S256) {
+        return Err(PkceError::UnsupportedMethod);
+    }
+    let base64url = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_';
+    if challenge.len() != CHALLENGE_LEN || !challenge.chars().all(base64url) {
+        return Err(PkceError::InvalidChallenge);
+    }
+    Ok(())
+}
+
+/// Check the `code_verifier` of a token request against the S256
+/// `challenge` stored with the authorization code.
+///
+/// # Errors
+///
+/// Returns [`PkceError::InvalidVerifier`] if `verifier` is not 43 to 128
+/// unreserved characters (RFC 7636 §4.1), and [`PkceError::Mismatch`] if it
+/// does not hash to `challenge`.
+pub fn verify_code_verifier(verifier: &str, challenge: &str) -> Result<(), PkceError> {
+    // ---
+    let unreserved = |c: char| c.is_ascii_alphanumeric() || "-._~".contains(c);
+    if !(43..=128).contains(&verifier.len()) || !verifier.chars().all(unreserved) {
+        return Err(PkceError::InvalidVerifier);
+    }
+    if pkce_challenge(verifier) != challenge {
+        return Err(PkceError::Mismatch);
+    }
+    Ok(())
+}
@@ -0,0 +1,164 @@
+// tokn-core/src/problem.rs
+
+//! RFC 7807 problem details, the error body of every tokn JSON API
+
+use http::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+// ---
+
+/// Media type of a [`Problem`] body.
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// `type` of a problem that needs no more explanation than its status.
+pub const ABOUT_BLANK: &str = "about:blank";
+
+// ---
+
+/// An HTTP API error body per RFC 7807:
+///
+/// ```json
+/// {
+///   "type": "about:blank",
+///   "title": "Unauthorized",
+///   "status": 401,
+///   "detail": "Token has expired",
+///   "instance": "/v1/auth/validate",
+///   "request_id": "0b9c6f5e-3c1a-4d9e-9a43-1f0d3c8e7a21"
+/// }
+/// ```
+///
+/// `detail` is safe to show callers and never echoes credentials.
+/// `instance` and `request_id` are filled in as the response leaves the
+/// service (see `tokn_server::problem_details`), so handlers only state what
+/// went wrong. Extension members, such as jwt-service's `"valid": false`,
+/// serialize alongside the standard ones.
+///
+/// With the `axum` feature a `Problem` is also a response, sent with its
+/// status and `Content-Type: application/problem+json`.
+///
+/// OAuth2 token endpoint errors keep the RFC 6749 `{"error": ...}` shape
+/// that OAuth2 clients expect instead.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Problem {
+    // ---
+    /// URI identifying the problem type; [`ABOUT_BLANK`] when the status says
+    /// it all
+    #[serde(rename = "type", default = "about_blank")]
+    pub type_uri: String,
+
+    /// Short summary of the problem type; the status's reason phrase for
+    /// [`ABOUT_BLANK`]
+    pub title: String,
+
+    /// HTTP status code
+    pub status: u16,
+
+    /// Explanation of this occurrence
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+
+    /// URI reference of this occurrence (the request path)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+
+    /// ID of the request, as logged, for support and log correlation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+
+    /// Extension members
+    #[serde(flatten)]
+    pub extensions: Map<String, Value>,
+}
+
+fn about_blank() -> String {
+    // ---
+    ABOUT_BLANK.to_string()
+}
+
+impl Problem {
+    // ---
+    /// An [`ABOUT_BLANK`] problem for `status`, titled with its reason phrase.
+    pub fn new(status: StatusCode) -> Self {
+        // ---
+        Self {
+            type_uri: about_blank(),
+            title: status.canonical_reason().unwrap_or("Error").to_string(),
+            status: status.as_u16(),
+            detail: None,
+            instance: None,
+            request_id: None,
+            extensions: Map::new(),
+        }
+    }
+
+    /// Identify the problem type with `type_uri` and `title`.
+    pub fn with_type(mut self, type_uri: impl Into<String>, title: impl Into<String>) -> Self {
+        // ---
+        self.type_uri = type_uri.into();
+        self.title = title.into();
+        self
+    }
+
+    /// Explain this occurrence.
+    pub fn detail(mut self, detail: impl Into<String>) -> Self {
+        // ---
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// Set the URI reference of this occurrence.
+    pub fn instance(mut self, instance: impl Into<String>) -> Self {
+        // ---
+        self.instance = Some(instance.into());
+        self
+    }
+
+    /// Set the ID of the request that failed.
+    pub fn request_id(mut self, request_id: impl Into<String>) -> Self {
+        // ---
+        self.request_id = Some(request_id.into());
+        self
+    }
+
+    /// Add the extension member `name`.
+    pub fn extension(mut self, name: &str, value: impl Into<Value>) -> Self {
+        // ---
+        self.extensions.insert(name.to_string(), value.into());
+        self
+    }
+
+    /// The HTTP status, or 500 if `status` is not a valid code.
+    pub fn status_code(&self) -> StatusCode {
+        // ---
+        StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    /// The JSON body.
+    pub fn to_json(&self) -> Vec<u8> {
+        // ---
+        // A map of strings and JSON values always serializes
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+}
+
+// ---
+
+#[cfg(feature = "axum")]
+impl axum::response::IntoResponse for Problem {
+    // ---
+    /// The problem as a response. The `Problem` is also kept in the
+    /// response's extensions so middleware can complete it.
+    fn into_response(self) -> axum::response::Response {
+        // ---
+        let mut response = (
+            self.status_code(),
+            [(http::header::CONTENT_TYPE, PROBLEM_JSON)],
+            self.to_json(),
+        )
+            .into_response();
+        response.extensions_mut().insert(self);
+        response
+    }
+}
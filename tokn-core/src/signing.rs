@@ -0,0 +1,804 @@
+// tokn-core/src/signing.rs
+
+//! Access-token signing keys: an HMAC secret, an RSA, P-256, or Ed25519 key
+//! pair, or a ring of them identified by `kid` for rotation; or, with the
+//! `paseto` feature, a PASETO v4 key. With the `jwe` feature, JWTs can also
+//! be encrypted.
+
+use jsonwebtoken::{decode_header, encode, Algorithm, DecodingKey, EncodingKey, Header};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+// ---
+
+use crate::claims::Claims;
+use crate::clock::{Clock, SystemClock};
+use crate::error::TokenError;
+#[cfg(feature = "jwe")]
+use crate::jwe::{is_jwe, JweKey};
+#[cfg(feature = "paseto")]
+use crate::paseto::PasetoKey;
+use crate::token::{check_purpose, decode_claims, TokenFormat, DEFAULT_LEEWAY_SECONDS};
+
+// ---
+
+/// The `typ` header of access tokens under the JWT access token profile
+/// (RFC 9068 §2.1; see [`JwtKeys::with_access_token_profile`]).
+pub const AT_JWT_TYPE: &str = "at+jwt";
+
+/// Algorithm access tokens are signed with (`JWT_ALGORITHM`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum SigningAlgorithm {
+    // ---
+    /// HMAC-SHA256 with a shared secret; anyone who can verify can also mint
+    #[default]
+    #[serde(rename = "HS256", alias = "hs256")]
+    Hs256,
+
+    /// HMAC-SHA384 with a shared secret of at least 48 bytes
+    #[serde(rename = "HS384", alias = "hs384")]
+    Hs384,
+
+    /// HMAC-SHA512 with a shared secret of at least 64 bytes
+    #[serde(rename = "HS512", alias = "hs512")]
+    Hs512,
+
+    /// RSASSA-PKCS1-v1_5 with SHA-256; verifiers only need the public key
+    #[serde(rename = "RS256", alias = "rs256")]
+    Rs256,
+
+    /// ECDSA on P-256 with SHA-256; smaller keys and signatures than RS256
+    #[serde(rename = "ES256", alias = "es256")]
+    Es256,
+
+    /// EdDSA with Ed25519; fast, deterministic signatures
+    #[serde(
+        rename = "EdDSA",
+        alias = "eddsa",
+        alias = "Ed25519",
+        alias = "ed25519"
+    )]
+    EdDsa,
+}
+
+impl SigningAlgorithm {
+    // ---
+    /// The JWS `alg` name (`HS256`, `RS256`, `ES256`, `EdDSA`, ...).
+    pub fn as_str(self) -> &'static str {
+        // ---
+        match self {
+            SigningAlgorithm::Hs256 => "HS256",
+            SigningAlgorithm::Hs384 => "HS384",
+            SigningAlgorithm::Hs512 => "HS512",
+            SigningAlgorithm::Rs256 => "RS256",
+            SigningAlgorithm::Es256 => "ES256",
+            SigningAlgorithm::EdDsa => "EdDSA",
+        }
+    }
+
+    /// Whether keys are PEM key pairs rather than a shared secret.
+    pub fn is_asymmetric(self) -> bool {
+        // ---
+        self.min_secret_len().is_none()
+    }
+
+    /// The shortest secret an HMAC algorithm accepts, in bytes: its hash's
+    /// output length (RFC 7518 §3.2). `None` for the key pair algorithms.
+    pub fn min_secret_len(self) -> Option<usize> {
+        // ---
+        match self {
+            SigningAlgorithm::Hs256 => Some(32),
+            SigningAlgorithm::Hs384 => Some(48),
+            SigningAlgorithm::Hs512 => Some(64),
+            _ => None,
+        }
+    }
+
+    fn jwt(self) -> Algorithm {
+        // ---
+        match self {
+            SigningAlgorithm::Hs256 => Algorithm::HS256,
+            SigningAlgorithm::Hs384 => Algorithm::HS384,
+            SigningAlgorithm::Hs512 => Algorithm::HS512,
+            SigningAlgorithm::Rs256 => Algorithm::RS256,
+            SigningAlgorithm::Es256 => Algorithm::ES256,
+            SigningAlgorithm::EdDsa => Algorithm::EdDSA,
+        }
+    }
+
+    /// The kind of PEM key this algorithm takes, for error messages.
+    fn key_type(self) -> &'static str {
+        // ---
+        match self {
+            SigningAlgorithm::Hs256 | SigningAlgorithm::Hs384 | SigningAlgorithm::Hs512 => "HMAC",
+            SigningAlgorithm::Rs256 => "RSA",
+            SigningAlgorithm::Es256 => "EC P-256",
+            SigningAlgorithm::EdDsa => "Ed25519",
+        }
+    }
+}
+
+impl FromStr for SigningAlgorithm {
+    // ---
+    type Err = String;
+
+    /// Parse a JWS `alg` name, case-insensitively (`Ed25519` for EdDSA too).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // ---
+        match s.to_ascii_lowercase().as_str() {
+            "hs256" => Ok(SigningAlgorithm::Hs256),
+            "hs384" => Ok(SigningAlgorithm::Hs384),
+            "hs512" => Ok(SigningAlgorithm::Hs512),
+            "rs256" => Ok(SigningAlgorithm::Rs256),
+            "es256" => Ok(SigningAlgorithm::Es256),
+            "eddsa" | "ed25519" => Ok(SigningAlgorithm::EdDsa),
+            _ => Err(format!(
+                "unknown signing algorithm '{s}' (expected HS256, HS384, HS512, RS256, ES256, or EdDSA)"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for SigningAlgorithm {
+    // ---
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // ---
+        f.write_str(self.as_str())
+    }
+}
+
+// ---
+
+/// One signing/verification key, with its algorithm pinned.
+#[derive(Clone)]
+struct Key {
+    // ---
+    kid: Option<String>,
+    algorithm: SigningAlgorithm,
+    encoding: Option<EncodingKey>,
+    decoding: DecodingKey,
+}
+
+/// The key of HMAC `algorithm` with `secret`, signing and verifying.
+fn hmac_key(algorithm: SigningAlgorithm, secret: &str) -> Key {
+    // ---
+    Key {
+        kid: None,
+        algorithm,
+        encoding: Some(EncodingKey::from_secret(secret.as_bytes())),
+        decoding: DecodingKey::from_secret(secret.as_bytes()),
+    }
+}
+
+// ---
+
+/// Keys that sign and verify access tokens.
+///
+/// Usually a single key: an HS256, HS384, or HS512 secret, or an RS256,
+/// ES256, or EdDSA key pair. A key built from a public key alone verifies but cannot sign; that
+/// is all a resource server needs. Each key accepts only its own algorithm, so a token cannot
+/// choose a different one (algorithm confusion).
+///
+/// For rotation, [`ring`](Self::ring) combines keys under IDs: tokens are
+/// signed with the current key and carry its ID in the `kid` header, and
+/// verification picks the key the token's `kid` names. Tokens without a
+/// `kid` (issued before rotation was set up) are checked against the current
+/// key.
+///
+/// With the `paseto` feature, [`paseto_local`](Self::paseto_local) and
+/// [`paseto_public`](Self::paseto_public) issue PASETO v4 tokens instead
+/// ([`TokenFormat::Paseto`]); such keys refuse JWTs, and JWT keys refuse
+/// PASETOs.
+///
+/// With [`with_access_token_profile`](Self::with_access_token_profile),
+/// access tokens follow the JWT access token profile (RFC 9068): they carry
+/// `typ: at+jwt`, and access tokens without it are refused.
+///
+/// With the `jwe` feature, [`with_encryption_key`](Self::with_encryption_key)
+/// wraps each signed JWT in a JWE so its claims cannot be read without the
+/// key; verification decrypts transparently.
+///
+/// # Example
+///
+/// ```no_run
+/// use tokn_core::{Claims, JwtKeys, SystemClock};
+///
+/// let keys = JwtKeys::rs256(
+///     &std::fs::read("jwt-private.pem")?,
+///     &std::fs::read("jwt-public.pem")?,
+/// )?;
+/// let claims = Claims::new("user_123".into(), "user@example.com".into(), 900, &SystemClock);
+/// let token = keys.sign(&claims)?;
+///
+/// // A resource server holding only the public key
+/// let verifier = JwtKeys::rs256_public(&std::fs::read("jwt-public.pem")?)?;
+/// assert_eq!(verifier.verify(&token, &SystemClock)?.sub, "user_123");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone)]
+pub struct JwtKeys {
+    // ---
+    keys: Vec<Key>,
+    /// Index of the key new tokens are signed with
+    current: usize,
+    /// Verify-only key for `kid`-less tokens signed before a secret rotation
+    previous: Option<Key>,
+    /// Seconds past `exp` a token is still accepted
+    leeway: u64,
+    /// Issue access tokens as `typ: at+jwt`, and refuse ones without it
+    at_jwt: bool,
+    /// Issue and accept PASETOs with this key instead of JWTs
+    #[cfg(feature = "paseto")]
+    paseto: Option<PasetoKey>,
+    /// Encrypt signed JWTs with this key, and decrypt JWEs before verifying
+    #[cfg(feature = "jwe")]
+    encryption: Option<JweKey>,
+}
+
+impl JwtKeys {
+    // ---
+    /// HS256 keys from a shared secret (at least 32 bytes).
+    pub fn hs256(secret: &str) -> Self {
+        // ---
+        Self::single(hmac_key(SigningAlgorithm::Hs256, secret))
+    }
+
+    /// Keys for an HMAC `algorithm` (HS256, HS384, or HS512) from a shared
+    /// secret, which should be at least
+    /// [`min_secret_len`](SigningAlgorithm::min_secret_len) bytes long.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TokenError::InvalidKey`] if `algorithm` takes a key pair.
+    pub fn hmac(algorithm: SigningAlgorithm, secret: &str) -> Result<Self, TokenError> {
+        // ---
+        if algorithm.is_asymmetric() {
+            return Err(TokenError::InvalidKey(format!(
+                "{algorithm} uses a PEM key pair, not a secret"
+            )));
+        }
+        Ok(Self::single(hmac_key(algorithm, secret)))
+    }
+
+    /// RS256 keys from a PEM private key (PKCS#1 or PKCS#8) and the matching
+    /// PEM public key.
+    ///
+    /// # Errors
+    ///
+    /// As [`from_pem`](Self::from_pem).
+    pub fn rs256(private_pem: &[u8], public_pem: &[u8]) -> Result<Self, TokenError> {
+        // ---
+        Self::from_pem(SigningAlgorithm::Rs256, private_pem, public_pem)
+    }
+
+    /// Verify-only RS256 keys from a PEM public key.
+    ///
+    /// # Errors
+    ///
+    /// As [`from_public_pem`](Self::from_public_pem).
+    pub fn rs256_public(public_pem: &[u8]) -> Result<Self, TokenError> {
+        // ---
+        Self::from_public_pem(SigningAlgorithm::Rs256, public_pem)
+    }
+
+    /// Keys for an asymmetric `algorithm` from a PEM private key and the
+    /// matching PEM public key: RSA (PKCS#1 or PKCS#8) for RS256, P-256
+    /// (PKCS#8) for ES256, Ed25519 (PKCS#8) for EdDSA.
+    ///
+    /// The pair signs and verifies a probe token, so a key of the wrong type
+    /// or a mismatched pair fails here rather than on the first request.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TokenError::InvalidKey`] if `algorithm` is an HMAC one, either PEM
+    /// does not hold a key of the type `algorithm` needs, or the public key
+    /// does not verify the private key's signatures.
+    pub fn from_pem(
+        algorithm: SigningAlgorithm,
+        private_pem: &[u8],
+        public_pem: &[u8],
+    ) -> Result<Self, TokenError> {
+        // ---
+        let encoding = match algorithm {
+            SigningAlgorithm::Rs256 => EncodingKey::from_rsa_pem(private_pem),
+            SigningAlgorithm::Es256 => EncodingKey::from_ec_pem(private_pem),
+            SigningAlgorithm::EdDsa => EncodingKey::from_ed_pem(private_pem),
+            hmac => {
+                return Err(TokenError::InvalidKey(format!(
+                    "{hmac} uses a secret, not a PEM key"
+                )))
+            }
+        }
+        .map_err(|e| {
+            TokenError::InvalidKey(format!("{} private key: {e}", algorithm.key_type()))
+        })?;
+        let mut keys = Self::from_public_pem(algorithm, public_pem)?;
+        keys.keys[0].encoding = Some(encoding);
+
+        // A mismatched pair would issue tokens nobody can verify
+        let probe = Claims::new("key-check".into(), String::new(), 60, &SystemClock);
+        let token = keys
+            .sign(&probe)
+            .map_err(|_| TokenError::InvalidKey(format!("private key cannot sign {algorithm}")))?;
+        keys.verify(&token, &SystemClock)
+            .map_err(|_| TokenError::InvalidKey("public key does not match private key".into()))?;
+
+        Ok(keys)
+    }
+
+    /// Verify-only keys for an asymmetric `algorithm` from a PEM public key.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TokenError::InvalidKey`] if `algorithm` is an HMAC one or the PEM
+    /// does not hold a public key of the type `algorithm` needs.
+    pub fn from_public_pem(
+        algorithm: SigningAlgorithm,
+        public_pem: &[u8],
+    ) -> Result<Self, TokenError> {
+        // ---
+        let decoding = match algorithm {
+            SigningAlgorithm::Rs256 => DecodingKey::from_rsa_pem(public_pem),
+            SigningAlgorithm::Es256 => DecodingKey::from_ec_pem(public_pem),
+            SigningAlgorithm::EdDsa => DecodingKey::from_ed_pem(public_pem),
+            hmac => {
+                return Err(TokenError::InvalidKey(format!(
+                    "{hmac} uses a secret, not a PEM key"
+                )))
+            }
+        }
+        .map_err(|e| TokenError::InvalidKey(format!("{} public key: {e}", algorithm.key_type())))?;
+
+        Ok(Self::single(Key {
+            kid: None,
+            algorithm,
+            encoding: None,
+            decoding,
+        }))
+    }
+
+    /// A rotation ring of `keys`, each under its ID, signing with the one
+    /// whose ID is `current`.
+    ///
+    /// Each entry contributes its current key; retired keys may be verify-only.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TokenError::InvalidKey`] if an ID is empty or repeated, no key
+    /// has the ID `current`, or that key cannot sign.
+    pub fn ring<I>(current: &str, keys: I) -> Result<Self, TokenError>
+    where
+        I: IntoIterator<Item = (String, JwtKeys)>,
+    {
+        // ---
+        let mut ring: Vec<Key> = Vec::new();
+        for (kid, keys) in keys {
+            if kid.is_empty() {
+                return Err(TokenError::InvalidKey("empty key ID".into()));
+            }
+            if ring
+                .iter()
+                .any(|key| key.kid.as_deref() == Some(kid.as_str()))
+            {
+                return Err(TokenError::InvalidKey(format!("duplicate key ID '{kid}'")));
+            }
+            let mut key = keys.keys[keys.current].clone();
+            key.kid = Some(kid);
+            ring.push(key);
+        }
+
+        let current = ring
+            .iter()
+            .position(|key| key.kid.as_deref() == Some(current))
+            .ok_or_else(|| TokenError::InvalidKey(format!("no key with ID '{current}'")))?;
+        if ring[current].encoding.is_none() {
+            return Err(TokenError::InvalidKey(format!(
+                "current key '{}' has no private key to sign with",
+                ring[current].kid.as_deref().unwrap_or_default()
+            )));
+        }
+
+        Ok(Self {
+            keys: ring,
+            current,
+            previous: None,
+            leeway: DEFAULT_LEEWAY_SECONDS,
+            at_jwt: false,
+            #[cfg(feature = "paseto")]
+            paseto: None,
+            #[cfg(feature = "jwe")]
+            encryption: None,
+        })
+    }
+
+    /// Also accept tokens without a `kid` signed with `secret`, the HMAC
+    /// secret the current key replaced, in the current key's HMAC algorithm
+    /// (HS256 for a key pair); new tokens are still signed with the current
+    /// key. [`with_previous_hmac_secret`](Self::with_previous_hmac_secret)
+    /// names the previous algorithm instead.
+    ///
+    /// Keeps access tokens issued before a secret rotation valid until they
+    /// expire. Drop the previous secret once they have.
+    pub fn with_previous_secret(mut self, secret: &str) -> Self {
+        // ---
+        let algorithm = match self.algorithm() {
+            algorithm if !algorithm.is_asymmetric() => algorithm,
+            _ => SigningAlgorithm::Hs256,
+        };
+        self.previous = Some(Key {
+            encoding: None,
+            ..hmac_key(algorithm, secret)
+        });
+        self
+    }
+
+    /// Also accept tokens without a `kid` signed with `secret` in HMAC
+    /// `algorithm`, as [`with_previous_secret`](Self::with_previous_secret)
+    /// does; for a rotation that changed the algorithm too, such as HS256 to
+    /// HS512 or to a key pair.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TokenError::InvalidKey`] if `algorithm` takes a key pair.
+    pub fn with_previous_hmac_secret(
+        mut self,
+        algorithm: SigningAlgorithm,
+        secret: &str,
+    ) -> Result<Self, TokenError> {
+        // ---
+        if algorithm.is_asymmetric() {
+            return Err(TokenError::InvalidKey(format!(
+                "{algorithm} uses a PEM key pair, not a secret"
+            )));
+        }
+        self.previous = Some(Key {
+            encoding: None,
+            ..hmac_key(algorithm, secret)
+        });
+        Ok(self)
+    }
+
+    /// Accept tokens up to `seconds` past their `exp`, instead of
+    /// [`DEFAULT_LEEWAY_SECONDS`], to absorb clock skew between the issuing
+    /// and validating hosts.
+    pub fn with_leeway(mut self, seconds: u64) -> Self {
+        // ---
+        self.leeway = seconds;
+        self
+    }
+
+    /// Follow the JWT access token profile (RFC 9068): sign access tokens
+    /// with the `typ: at+jwt` header, and have [`verify`](Self::verify)
+    /// refuse tokens without it, so no other kind of JWT signed with the same
+    /// key passes as an access token. Single-purpose tokens (see
+    /// [`Claims::with_purpose`]) are not access tokens and keep the plain
+    /// `JWT` type. JWTs only; PASETOs carry no header.
+    ///
+    /// The profile also requires the `iss`, `aud`, and `client_id` claims;
+    /// setting them is up to the issuer (see [`Claims::with_issuer`]).
+    pub fn with_access_token_profile(mut self) -> Self {
+        // ---
+        self.at_jwt = true;
+        self
+    }
+
+    /// Encrypt issued JWTs with `key` ([`JWE_KEY_LENGTH`](crate::JWE_KEY_LENGTH)
+    /// bytes), nesting each in a JWE (`dir`, `A256GCM`), and decrypt JWEs
+    /// before verifying them.
+    ///
+    /// Unencrypted JWTs signed with these keys are still accepted, so tokens
+    /// issued before encryption was turned on stay valid until they expire.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TokenError::InvalidKey`] if `key` is not 32 bytes long.
+    #[cfg(feature = "jwe")]
+    pub fn with_encryption_key(mut self, key: &[u8]) -> Result<Self, TokenError> {
+        // ---
+        self.encryption = Some(JweKey::new(key)?);
+        Ok(self)
+    }
+
+    /// PASETO `v4.local` keys from a shared secret (at least 32 bytes).
+    ///
+    /// Tokens are encrypted (XChaCha20, with a key derived from `secret`) as
+    /// well as authenticated, so only holders of the secret can read their
+    /// claims.
+    #[cfg(feature = "paseto")]
+    pub fn paseto_local(secret: &str) -> Self {
+        // ---
+        let mut keys = Self::hs256(secret);
+        keys.paseto = Some(PasetoKey::local(secret));
+        keys
+    }
+
+    /// PASETO `v4.public` keys from an Ed25519 PEM private key (PKCS#8) and
+    /// the matching PEM public key.
+    ///
+    /// # Errors
+    ///
+    /// As [`from_pem`](Self::from_pem) with [`SigningAlgorithm::EdDsa`].
+    #[cfg(feature = "paseto")]
+    pub fn paseto_public(private_pem: &[u8], public_pem: &[u8]) -> Result<Self, TokenError> {
+        // ---
+        // The JWT probe has already checked the pair matches
+        let mut keys = Self::from_pem(SigningAlgorithm::EdDsa, private_pem, public_pem)?;
+        keys.paseto = Some(PasetoKey::public(Some(private_pem), public_pem)?);
+        Ok(keys)
+    }
+
+    /// Verify-only PASETO `v4.public` keys from an Ed25519 PEM public key.
+    ///
+    /// # Errors
+    ///
+    /// As [`from_public_pem`](Self::from_public_pem) with
+    /// [`SigningAlgorithm::EdDsa`].
+    #[cfg(feature = "paseto")]
+    pub fn paseto_public_verifier(public_pem: &[u8]) -> Result<Self, TokenError> {
+        // ---
+        let mut keys = Self::from_public_pem(SigningAlgorithm::EdDsa, public_pem)?;
+        keys.paseto = Some(PasetoKey::public(None, public_pem)?);
+        Ok(keys)
+    }
+
+    fn single(key: Key) -> Self {
+        // ---
+        Self {
+            keys: vec![key],
+            current: 0,
+            previous: None,
+            leeway: DEFAULT_LEEWAY_SECONDS,
+            at_jwt: false,
+            #[cfg(feature = "paseto")]
+            paseto: None,
+            #[cfg(feature = "jwe")]
+            encryption: None,
+        }
+    }
+
+    // ---
+    /// The algorithm new tokens are signed with.
+    pub fn algorithm(&self) -> SigningAlgorithm {
+        // ---
+        self.keys[self.current].algorithm
+    }
+
+    /// The wire format of the tokens these keys issue and accept.
+    pub fn format(&self) -> TokenFormat {
+        // ---
+        #[cfg(feature = "paseto")]
+        if self.paseto.is_some() {
+            return TokenFormat::Paseto;
+        }
+        TokenFormat::Jwt
+    }
+
+    /// Whether issued JWTs are encrypted (see
+    /// [`with_encryption_key`](Self::with_encryption_key)).
+    pub fn is_encrypted(&self) -> bool {
+        // ---
+        #[cfg(feature = "jwe")]
+        if self.encryption.is_some() {
+            return true;
+        }
+        false
+    }
+
+    /// Whether access tokens follow the JWT access token profile (see
+    /// [`with_access_token_profile`](Self::with_access_token_profile)).
+    pub fn is_access_token_profile(&self) -> bool {
+        // ---
+        self.at_jwt
+    }
+
+    /// Seconds past `exp` a token is still accepted (see
+    /// [`with_leeway`](Self::with_leeway)).
+    pub fn leeway(&self) -> u64 {
+        // ---
+        self.leeway
+    }
+
+    /// The ID new tokens carry in their `kid` header (`None` outside a ring).
+    pub fn current_kid(&self) -> Option<&str> {
+        // ---
+        self.keys[self.current].kid.as_deref()
+    }
+
+    /// IDs of every key tokens are verified against, in ring order.
+    pub fn kids(&self) -> Vec<&str> {
+        // ---
+        self.keys
+            .iter()
+            .filter_map(|key| key.kid.as_deref())
+            .collect()
+    }
+
+    /// Whether these keys can sign (false for a public key alone).
+    pub fn can_sign(&self) -> bool {
+        // ---
+        #[cfg(feature = "paseto")]
+        if let Some(paseto) = &self.paseto {
+            return paseto.can_sign();
+        }
+        self.keys[self.current].encoding.is_some()
+    }
+
+    // ---
+    /// Sign `claims` into a JWT with the current key, naming it in the `kid`
+    /// header when it has an ID (and typing an access token `at+jwt` under
+    /// the [access token profile](Self::with_access_token_profile)), and
+    /// encrypt it when these keys have an encryption key (into a PASETO with
+    /// PASETO keys).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TokenError::Encoding`] if encoding fails, or
+    /// [`TokenError::InvalidKey`] if these keys are verify-only.
+    pub fn sign(&self, claims: &Claims) -> Result<String, TokenError> {
+        // ---
+        #[cfg(feature = "paseto")]
+        if let Some(paseto) = &self.paseto {
+            return paseto.sign(claims);
+        }
+
+        let key = &self.keys[self.current];
+        let encoding = key
+            .encoding
+            .as_ref()
+            .ok_or_else(|| TokenError::InvalidKey("no private key to sign with".into()))?;
+
+        let mut header = Header::new(key.algorithm.jwt());
+        header.kid = key.kid.clone();
+        if self.at_jwt && claims.purpose.is_none() {
+            header.typ = Some(AT_JWT_TYPE.into());
+        }
+        let token = encode(&header, claims, encoding).map_err(TokenError::Encoding)?;
+
+        #[cfg(feature = "jwe")]
+        if let Some(encryption) = &self.encryption {
+            return encryption.encrypt(&token);
+        }
+        Ok(token)
+    }
+
+    /// Verify `token`'s signature with the key its `kid` names (the current
+    /// key if it names none, then any [previous secret](Self::with_previous_secret))
+    /// and check `exp` against `clock`, with the [leeway](Self::with_leeway). Returns
+    /// the claims if valid. With an [encryption key](Self::with_encryption_key)
+    /// an encrypted token is decrypted first. Single-purpose tokens are not
+    /// access tokens, and are refused; so, under the
+    /// [access token profile](Self::with_access_token_profile), are tokens
+    /// not typed `at+jwt`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`validate_token`](crate::validate_token);
+    /// [`TokenError::UnknownKey`] if the `kid` names no key in the ring, and
+    /// [`TokenError::InvalidAlgorithm`] if the token is signed with an
+    /// algorithm other than its key's (or is a JWT presented to PASETO keys,
+    /// or a JWE other than `dir`/`A256GCM`); [`TokenError::WrongType`] for a
+    /// token not typed `at+jwt` under the access token profile.
+    pub fn verify(&self, token: &str, clock: &dyn Clock) -> Result<Claims, TokenError> {
+        // ---
+        let claims = self.verify_any(token, clock, self.at_jwt)?;
+        check_purpose(&claims, None)?;
+        Ok(claims)
+    }
+
+    /// Verify a single-purpose token, as [`verify`](Self::verify) does an
+    /// access token: `token` must carry the `purpose` claim `purpose` (see
+    /// [`Claims::with_purpose`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`verify`](Self::verify), with
+    /// [`TokenError::WrongPurpose`] for a token without that purpose.
+    pub fn verify_purpose(
+        &self,
+        token: &str,
+        purpose: &str,
+        clock: &dyn Clock,
+    ) -> Result<Claims, TokenError> {
+        // ---
+        let claims = self.verify_any(token, clock, false)?;
+        check_purpose(&claims, Some(purpose))?;
+        Ok(claims)
+    }
+
+    /// Verify `token` whatever its purpose, requiring the `at+jwt` type if
+    /// `at_jwt`.
+    fn verify_any(
+        &self,
+        token: &str,
+        clock: &dyn Clock,
+        at_jwt: bool,
+    ) -> Result<Claims, TokenError> {
+        // ---
+        #[cfg(feature = "paseto")]
+        if let Some(paseto) = &self.paseto {
+            return paseto.verify(token, clock, self.leeway);
+        }
+
+        #[cfg(feature = "jwe")]
+        let decrypted;
+        #[cfg(feature = "jwe")]
+        let token = match &self.encryption {
+            Some(encryption) if is_jwe(token) => {
+                decrypted = encryption.decrypt(token)?;
+                decrypted.as_str()
+            }
+            _ => token,
+        };
+
+        let header = decode_header(token)?;
+        let key = match header.kid.as_deref() {
+            // Outside a ring a `kid` names nothing; the one key decides
+            Some(kid) if self.keys.iter().any(|key| key.kid.is_some()) => self
+                .keys
+                .iter()
+                .find(|key| key.kid.as_deref() == Some(kid))
+                .ok_or(TokenError::UnknownKey)?,
+            _ => &self.keys[self.current],
+        };
+
+        // A `kid`-less token in the previous secret's algorithm, not the
+        // current key's, was signed before the secret was rotated
+        let previous = self.previous.as_ref().filter(|_| header.kid.is_none());
+        let key = match previous {
+            Some(previous)
+                if header.alg != key.algorithm.jwt() && header.alg == previous.algorithm.jwt() =>
+            {
+                previous
+            }
+            _ => key,
+        };
+
+        let claims = match decode_claims(
+            token,
+            &key.decoding,
+            key.algorithm.jwt(),
+            clock,
+            self.leeway,
+        ) {
+            // Signed before the secret was rotated, in the same algorithm
+            Err(TokenError::InvalidSignature) => match previous {
+                Some(previous) if !std::ptr::eq(key, previous) => decode_claims(
+                    token,
+                    &previous.decoding,
+                    previous.algorithm.jwt(),
+                    clock,
+                    self.leeway,
+                ),
+                _ => Err(TokenError::InvalidSignature),
+            },
+            result => result,
+        }?;
+
+        // RFC 9068 §4: `application/at+jwt` is the same type spelled out
+        let typ = header.typ.as_deref().unwrap_or_default();
+        let typ = typ.strip_prefix("application/").unwrap_or(typ);
+        if at_jwt && !typ.eq_ignore_ascii_case(AT_JWT_TYPE) {
+            return Err(TokenError::WrongType);
+        }
+        Ok(claims)
+    }
+}
+
+impl fmt::Debug for JwtKeys {
+    // ---
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // ---
+        f.debug_struct("JwtKeys")
+            .field("format", &self.format())
+            .field("algorithm", &self.algorithm())
+            .field("current_kid", &self.current_kid())
+            .field("kids", &self.kids())
+            .field("can_sign", &self.can_sign())
+            .field("accepts_previous_secret", &self.previous.is_some())
+            .field("leeway", &self.leeway)
+            .field("access_token_profile", &self.at_jwt)
+            .field("encrypted", &self.is_encrypted())
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,242 @@
+// tokn-core/src/token.rs
+
+//! JWT token generation and encoding
+//!
+//! Provides functions to generate and validate signed JWT tokens using HS256
+//! algorithm; [`JwtKeys`] covers the other signing algorithms and, with the
+//! `paseto` feature, PASETO v4 tokens ([`TokenFormat`]).
+
+use crate::claims::Claims;
+use crate::clock::Clock;
+use crate::error::TokenError;
+use crate::signing::JwtKeys;
+use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+// ---
+
+/// Seconds past `exp` a token is still accepted unless
+/// [`JwtKeys::with_leeway`] says otherwise, absorbing clock skew between the
+/// issuing and validating hosts (the `jsonwebtoken` default).
+pub const DEFAULT_LEEWAY_SECONDS: u64 = 60;
+
+// ---
+
+/// Wire format of access tokens (`TOKEN_FORMAT`).
+///
+/// Either carries the same [`Claims`]; only the envelope differs, so
+/// endpoints and middleware work unchanged.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenFormat {
+    // ---
+    /// JSON Web Tokens (RFC 7519), signed with the key's algorithm
+    #[default]
+    Jwt,
+
+    /// PASETO v4 (`paseto` feature): `v4.local` (encrypted with a key
+    /// derived from the HS256 secret) or `v4.public` (signed with an Ed25519
+    /// key pair). No algorithm header to confuse, and no `none`.
+    Paseto,
+}
+
+impl TokenFormat {
+    // ---
+    /// The `TOKEN_FORMAT` name (`jwt`, `paseto`).
+    pub fn as_str(self) -> &'static str {
+        // ---
+        match self {
+            TokenFormat::Jwt => "jwt",
+            TokenFormat::Paseto => "paseto",
+        }
+    }
+}
+
+impl FromStr for TokenFormat {
+    // ---
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // ---
+        match s.to_ascii_lowercase().as_str() {
+            "jwt" => Ok(TokenFormat::Jwt),
+            "paseto" => Ok(TokenFormat::Paseto),
+            _ => Err(format!(
+                "unknown token format '{s}' (expected jwt or paseto)"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for TokenFormat {
+    // ---
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // ---
+        f.write_str(self.as_str())
+    }
+}
+
+// ---
+
+/// Generate a signed JWT token from claims.
+///
+/// Uses HS256 (HMAC-SHA256) for signing. The secret key must be at least 256 bits (32 bytes).
+///
+/// # Arguments
+///
+/// - `claims` - The claims to encode in the token
+/// - `secret` - Secret key for HMAC signing (minimum 32 bytes)
+///
+/// # Returns
+///
+/// A signed JWT string in the format: `header.payload.signature`
+///
+/// # Security
+///
+/// - Secret must be kept confidential
+/// - Secret should be at least 256 bits (32 bytes) for HS256
+/// - Rotate secrets periodically
+/// - Use environment variables, never hardcode
+///
+/// # Errors
+///
+/// Returns [`TokenError::Encoding`] if JWT encoding fails.
+///
+/// # Example
+///
+/// ```no_run
+/// use tokn_core::{Claims, SystemClock, generate_token};
+///
+/// let claims = Claims::new(
+///     "user_123".to_string(),
+///     "user@example.com".to_string(),
+///     900,
+///     &SystemClock,
+/// );
+///
+/// let secret = "your-secret-key-at-least-32-characters";
+/// let token = generate_token(&claims, secret)?;
+/// # Ok::<(), tokn_core::TokenError>(())
+/// ```
+pub fn generate_token(claims: &Claims, secret: &str) -> Result<String, TokenError> {
+    // ---
+    JwtKeys::hs256(secret).sign(claims)
+}
+
+// ---
+
+/// Validate and decode a JWT token.
+///
+/// Verifies the token signature and checks expiration. Returns the decoded claims if valid.
+///
+/// # Arguments
+///
+/// - `token` - The JWT string to validate
+/// - `secret` - Secret key used to sign the token (must match generation secret)
+/// - `clock` - Time source `exp` is checked against
+///
+/// # Returns
+///
+/// The decoded `Claims` if the token is valid.
+///
+/// # Security
+///
+/// Validates:
+/// - **Signature** - Token hasn't been tampered with (HS256 verification)
+/// - **Expiration** - Token hasn't expired (checks `exp` against `clock`, with
+///   [`DEFAULT_LEEWAY_SECONDS`] of leeway for clock skew; see
+///   [`JwtKeys::with_leeway`] for another)
+/// - **Algorithm** - Only HS256 is accepted (prevents algorithm confusion attacks)
+/// - **Purpose** - Single-purpose tokens (email verification, ...) are not
+///   access tokens
+///
+/// Does NOT validate:
+/// - Token revocation (check blacklist separately)
+/// - Issuer or audience (see [`check_audience`](crate::check_audience))
+///
+/// Encrypted tokens need the encryption key too: verify them with
+/// `JwtKeys::with_encryption_key` (`jwe` feature), which decrypts before
+/// these checks.
+///
+/// # Errors
+///
+/// Returns:
+/// - [`TokenError::InvalidSignature`] if the token was tampered with or signed with another secret
+/// - [`TokenError::Expired`] if the `exp` claim is in the past
+/// - [`TokenError::Malformed`] if the token format is invalid
+/// - [`TokenError::InvalidAlgorithm`] if the algorithm is not HS256
+/// - [`TokenError::WrongPurpose`] if the token has a `purpose` claim
+///
+/// # Example
+///
+/// ```no_run
+/// use tokn_core::{validate_token, SystemClock};
+///
+/// let secret = "your-secret-key-at-least-32-characters";
+/// let token = "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9...";
+///
+/// match validate_token(token, secret, &SystemClock) {
+///     Ok(claims) => println!("Valid token for user: {}", claims.sub),
+///     Err(e) => println!("Invalid token: {}", e),
+/// }
+/// # Ok::<(), tokn_core::TokenError>(())
+/// ```
+pub fn validate_token(token: &str, secret: &str, clock: &dyn Clock) -> Result<Claims, TokenError> {
+    // ---
+    JwtKeys::hs256(secret).verify(token, clock)
+}
+
+// ---
+
+/// Verify `token`'s signature with `key`, accepting only `algorithm`, and
+/// check `exp` against `clock` with `leeway` seconds of leeway.
+pub(crate) fn decode_claims(
+    token: &str,
+    key: &DecodingKey,
+    algorithm: Algorithm,
+    clock: &dyn Clock,
+    leeway: u64,
+) -> Result<Claims, TokenError> {
+    // ---
+    // Configure validation rules; `exp` must be present but is checked below
+    // against `clock` rather than the system time
+    let mut validation = Validation::new(algorithm);
+    validation.validate_exp = false;
+    validation.leeway = leeway;
+    // `aud` is checked by callers expecting an audience (`check_audience`)
+    validation.validate_aud = false;
+
+    // Decode and validate token
+    let token_data = decode::<Claims>(token, key, &validation)?;
+
+    check_expiry(&token_data.claims, clock, leeway)?;
+    Ok(token_data.claims)
+}
+
+/// Refuse `claims` whose `purpose` is not `expected`; `None` expects an
+/// access token, which has none.
+pub(crate) fn check_purpose(claims: &Claims, expected: Option<&str>) -> Result<(), TokenError> {
+    // ---
+    if claims.purpose.as_deref() != expected {
+        return Err(TokenError::WrongPurpose);
+    }
+    Ok(())
+}
+
+/// Refuse `claims` whose `exp` is more than `leeway` seconds before
+/// `clock`'s time.
+pub(crate) fn check_expiry(
+    claims: &Claims,
+    clock: &dyn Clock,
+    leeway: u64,
+) -> Result<(), TokenError> {
+    // ---
+    let exp = i64::try_from(claims.exp).map_err(|_| TokenError::Malformed)?;
+    let leeway = i64::try_from(leeway).map_err(|_| TokenError::Malformed)?;
+    if exp.saturating_add(leeway) < clock.timestamp() {
+        return Err(TokenError::Expired);
+    }
+    Ok(())
+}
@@ -0,0 +1,30 @@
+// tokn-core/src/userinfo.rs
+
+//! UserInfo response contract
+
+use serde::{Deserialize, Serialize};
+
+// ---
+
+/// User information returned by the oauth2-server UserInfo endpoint.
+///
+/// Serialized by oauth2-server at `GET /v1/oauth/userinfo` and deserialized by
+/// oauth2-client after the token exchange, so both sides share one definition.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserInfo {
+    // ---
+    /// Subject - User ID
+    pub sub: String,
+
+    /// Display name of the user
+    pub username: String,
+
+    /// Verified phone number in E.164 form; only with the `phone` scope
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phone_number: Option<String>,
+
+    /// Whether `phone_number` was confirmed by a one-time code; present
+    /// whenever `phone_number` is
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phone_number_verified: Option<bool>,
+}
@@ -0,0 +1,43 @@
+// tokn-demo/src/lib.rs
+
+//! tokn-demo - The whole tokn stack in one process, with nothing to install
+//!
+//! Runs jwt-service, oauth2-server, and oauth2-client on their usual ports
+//! from a single binary:
+//!
+//! - oauth2-server stores its data in a throwaway Postgres
+//!   ([`EmbeddedPostgres`]), migrated and seeded with the demo client and
+//!   user; the Postgres binaries are downloaded on first run
+//! - jwt-service runs stateless (no Redis): access tokens only, no refresh
+//!   tokens or revocation
+//! - events, email, SMS, and rate limiting keep their disabled or
+//!   log-only defaults
+//!
+//! oauth2-server's queries are written for Postgres (advisory locks,
+//! `sha256()`, `strpos()`), so the demo embeds Postgres rather than
+//! swapping in SQLite. Everything is lost on exit, and the JWT secret is
+//! [`DEMO_JWT_SECRET`]: never expose a demo to a network.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # async fn example() -> anyhow::Result<()> {
+//! let postgres = tokn_demo::EmbeddedPostgres::start().await?;
+//! let pool = oauth2_server::create_pool(postgres.url()).await?;
+//! oauth2_server::run_migrations(&pool).await?;
+//!
+//! let app = tokn_demo::oauth2_server(std::sync::Arc::new(pool))?;
+//! # Ok(())
+//! # }
+//! ```
+
+mod postgres;
+mod stack;
+
+// ---
+
+pub use postgres::EmbeddedPostgres;
+pub use stack::{
+    jwt_service, oauth2_client, oauth2_server, DEMO_CLIENT_ID, DEMO_CLIENT_SECRET, DEMO_JWT_SECRET,
+    DEMO_REDIRECT_URI, JWT_SERVICE_ADDR, OAUTH2_CLIENT_ADDR, OAUTH2_SERVER_ADDR,
+};
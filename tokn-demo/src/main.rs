@@ -0,0 +1,90 @@
+// tokn-demo/src/main.rs
+
+//! tokn-demo - jwt-service, oauth2-server, and oauth2-client in one process
+//!
+//! ```bash
+//! cargo run -p tokn-demo
+//! # then open http://127.0.0.1:8081 and sign in
+//! ```
+//!
+//! Needs ports 8081-8083 free (stop any services started from `.env`) and,
+//! on first run only, network access to download Postgres.
+
+use anyhow::Result;
+use axum::Router;
+use std::sync::Arc;
+use std::time::Duration;
+use tokn_demo::{EmbeddedPostgres, JWT_SERVICE_ADDR, OAUTH2_CLIENT_ADDR, OAUTH2_SERVER_ADDR};
+use tokn_server::{Bind, Shutdown};
+use tokn_telemetry::TelemetryConfig;
+use tracing::info;
+
+// ---
+
+/// Log filter when `RUST_LOG` is unset: every service, but not per-request
+/// traces.
+const DEFAULT_FILTER: &str =
+    "tokn_demo=info,jwt_service=info,oauth2_server=info,oauth2_client=info,tokn_server=info";
+
+// ---
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // ---
+    // Initialize logging
+    let mut telemetry = TelemetryConfig::from_env("tokn-demo")?;
+    telemetry.default_filter = DEFAULT_FILTER.to_string();
+    let _telemetry = tokn_telemetry::init(&telemetry)?;
+
+    // ---
+    // Throwaway Postgres with the demo client and user
+    info!("Starting embedded Postgres (downloaded on first run)");
+    let postgres = EmbeddedPostgres::start().await?;
+
+    let result = run(&postgres).await;
+    postgres.stop().await?;
+    result
+}
+
+// ---
+
+/// Serve the three services until Ctrl-C or until one fails.
+async fn run(postgres: &EmbeddedPostgres) -> Result<()> {
+    // ---
+    let pool = oauth2_server::create_pool(postgres.url()).await?;
+    oauth2_server::run_migrations(&pool).await?;
+
+    let jwt_service = tokn_demo::jwt_service()?;
+    let oauth2_server = tokn_demo::oauth2_server(Arc::new(pool))?;
+    let oauth2_client = tokn_demo::oauth2_client(&format!("http://{OAUTH2_SERVER_ADDR}"))?;
+
+    info!("tokn demo running (Ctrl-C to stop):");
+    info!("  http://{OAUTH2_CLIENT_ADDR}/ - Sign in with the demo client");
+    info!("  http://{OAUTH2_SERVER_ADDR}/docs - API docs for all three services");
+    info!(
+        "  http://{JWT_SERVICE_ADDR}/v1/auth/token - Mint JWTs (stateless: no refresh or revoke)"
+    );
+
+    let services = async {
+        tokio::try_join!(
+            serve(jwt_service, JWT_SERVICE_ADDR),
+            serve(oauth2_server, OAUTH2_SERVER_ADDR),
+            serve(oauth2_client, OAUTH2_CLIENT_ADDR),
+        )
+    };
+
+    tokio::select! {
+        result = services => result.map(|_| ()),
+        _ = tokio::signal::ctrl_c() => {
+            info!("Shutting down");
+            Ok(())
+        }
+    }
+}
+
+async fn serve(app: Router, addr: &str) -> Result<()> {
+    // ---
+    // Ctrl-C stops the whole demo above; nothing to drain
+    let shutdown = Shutdown::new(Duration::ZERO);
+    tokn_server::serve(app, &Bind::Tcp(addr.to_string()), None, None, &shutdown).await
+}
@@ -0,0 +1,70 @@
+// tokn-demo/src/postgres.rs
+
+use anyhow::{Context, Result};
+use postgresql_embedded::{PostgreSQL, Settings};
+
+// ---
+
+/// Database the demo migrates and serves from.
+const DATABASE: &str = "tokn_demo";
+
+// ---
+
+/// A Postgres server running from a temporary directory on a free port.
+///
+/// The data directory is removed when the server stops. Call
+/// [`stop`](Self::stop) before exiting: a process killed without it leaves
+/// the server running.
+pub struct EmbeddedPostgres {
+    // ---
+    server: PostgreSQL,
+    url: String,
+}
+
+impl EmbeddedPostgres {
+    // ---
+    /// Install Postgres if needed, start it, and create the demo database.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the binaries cannot be downloaded (first run
+    /// only) or the server fails to start.
+    pub async fn start() -> Result<Self> {
+        // ---
+        let mut server = PostgreSQL::new(Settings::default());
+        server
+            .setup()
+            .await
+            .context("Failed to install embedded Postgres")?;
+        server
+            .start()
+            .await
+            .context("Failed to start embedded Postgres")?;
+        server
+            .create_database(DATABASE)
+            .await
+            .context("Failed to create the demo database")?;
+
+        let url = server.settings().url(DATABASE);
+        Ok(Self { server, url })
+    }
+
+    /// Connection URL for the demo database.
+    pub fn url(&self) -> &str {
+        // ---
+        &self.url
+    }
+
+    /// Stop the server and remove its data.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the server does not shut down cleanly.
+    pub async fn stop(self) -> Result<()> {
+        // ---
+        self.server
+            .stop()
+            .await
+            .context("Failed to stop embedded Postgres")
+    }
+}
@@ -0,0 +1,171 @@
+// tokn-demo/src/stack.rs
+
+use anyhow::Result;
+use axum::Router;
+use sqlx::PgPool;
+use std::sync::Arc;
+use tokn_core::SystemClock;
+
+// ---
+
+/// Where each service listens. Fixed, because the seeded client's redirect
+/// URI ([`DEMO_REDIRECT_URI`]) names oauth2-client's address.
+pub const JWT_SERVICE_ADDR: &str = "127.0.0.1:8083";
+pub const OAUTH2_SERVER_ADDR: &str = "127.0.0.1:8082";
+pub const OAUTH2_CLIENT_ADDR: &str = "127.0.0.1:8081";
+
+/// Signing secret for demo tokens. Public, so demo tokens prove nothing.
+pub const DEMO_JWT_SECRET: &str = "tokn-demo-secret-published-in-the-source-code";
+
+/// Demo client seeded by the oauth2-server migrations.
+pub const DEMO_CLIENT_ID: &str = "demo_client";
+
+/// Secret for [`DEMO_CLIENT_ID`] seeded by the oauth2-server migrations.
+pub const DEMO_CLIENT_SECRET: &str = "demo_secret";
+
+/// Redirect URI registered for [`DEMO_CLIENT_ID`].
+pub const DEMO_REDIRECT_URI: &str = "http://127.0.0.1:8081/callback";
+
+/// Placeholder for the Redis URL the configurations require; nothing in the
+/// demo connects to it.
+const UNUSED_REDIS_URL: &str = "redis://127.0.0.1:6379";
+
+// ---
+
+/// jwt-service, stateless, signing with [`DEMO_JWT_SECRET`].
+///
+/// # Errors
+///
+/// Returns an error if the signing keys cannot be built.
+pub fn jwt_service() -> Result<Router> {
+    // ---
+    let config = jwt_service::Config {
+        profile: Default::default(),
+        server: jwt_service::ServerConfig {
+            host: "127.0.0.1".to_string(),
+            port: 8083,
+            bind: None,
+            socket_mode: None,
+            tls: None,
+            compression: Default::default(),
+            cors: Default::default(),
+            security_headers: Default::default(),
+            grpc_addr: None,
+            drain_timeout_seconds: 30,
+        },
+        redis: jwt_service::RedisConfig {
+            url: UNUSED_REDIS_URL.to_string(),
+        },
+        jwt: jwt_service::JwtConfig {
+            algorithm: Default::default(),
+            format: Default::default(),
+            issuer: None,
+            access_token_profile: false,
+            secret: Some(DEMO_JWT_SECRET.into()),
+            previous_secret: None,
+            previous_algorithm: None,
+            secret_rotated_at: None,
+            allow_weak_secret: false,
+            private_key_path: None,
+            public_key_path: None,
+            access_token_expiry_seconds: 900,
+            refresh_token_expiry_seconds: 604800,
+            refresh_token_sliding: true,
+            max_session_seconds: None,
+            refresh_bind_device: false,
+            refresh_grace_seconds: 0,
+            cookie_mode: false,
+            validation_leeway_seconds: tokn_core::DEFAULT_LEEWAY_SECONDS,
+            ticket_ttl_seconds: 30,
+            magic_link_ttl_seconds: 900,
+            magic_link_url: None,
+            verify_email_ttl_seconds: 86_400,
+            verify_email_url: None,
+            password_reset_ttl_seconds: 900,
+            password_reset_url: None,
+            stateless: true,
+            keys: Vec::new(),
+            current_kid: None,
+            encryption_key: None,
+        },
+        store: Default::default(),
+        scheduler: Default::default(),
+        startup: Default::default(),
+        circuit_breaker: Default::default(),
+        redis_retry: Default::default(),
+        log: Default::default(),
+        admin: Default::default(),
+        api: Default::default(),
+        events: Default::default(),
+        audit: Default::default(),
+        mail: Default::default(),
+        rate_limit: Default::default(),
+        service_auth: Default::default(),
+        issuer: Default::default(),
+        chaos: Default::default(),
+    };
+
+    let state = jwt_service::AppState::stateless(config, SystemClock::shared())?;
+    Ok(jwt_service::build_router(state))
+}
+
+// ---
+
+/// oauth2-server on `pool` (migrated), with the API docs at `/docs`.
+///
+/// # Errors
+///
+/// Returns an error if the bundled API specs cannot be merged.
+pub fn oauth2_server(pool: Arc<PgPool>) -> Result<Router> {
+    // ---
+    let state = oauth2_server::AppState::new(pool, Default::default());
+
+    Ok(oauth2_server::build_router(state).merge(tokn_portal::portal_router(&Default::default())?))
+}
+
+// ---
+
+/// oauth2-client signing in as [`DEMO_CLIENT_ID`] at the oauth2-server at
+/// `server_url`.
+///
+/// # Errors
+///
+/// Returns an error if the built-in translations or themes fail to load.
+pub fn oauth2_client(server_url: &str) -> Result<Router> {
+    // ---
+    let config = oauth2_client::Config {
+        profile: Default::default(),
+        server: oauth2_client::ServerConfig {
+            host: "127.0.0.1".to_string(),
+            port: 8081,
+            bind: None,
+            socket_mode: None,
+            tls: None,
+            compression: Default::default(),
+            cors: Default::default(),
+            security_headers: Default::default(),
+            drain_timeout_seconds: 30,
+        },
+        redis: oauth2_client::RedisConfig {
+            url: UNUSED_REDIS_URL.to_string(),
+        },
+        oauth2: oauth2_client::OAuth2Config {
+            client_id: DEMO_CLIENT_ID.to_string(),
+            client_secret: DEMO_CLIENT_SECRET.into(),
+            redirect_uri: DEMO_REDIRECT_URI.to_string(),
+            authorize_url: format!("{server_url}/v1/oauth/authorize"),
+            token_url: format!("{server_url}/v1/oauth/token"),
+            userinfo_url: format!("{server_url}/v1/oauth/userinfo"),
+        },
+        circuit_breaker: Default::default(),
+        log: Default::default(),
+        admin: Default::default(),
+        i18n: Default::default(),
+        theme: Default::default(),
+        rate_limit: Default::default(),
+        service_auth: Default::default(),
+        chaos: Default::default(),
+    };
+
+    oauth2_client::build_router(Arc::new(config))
+}
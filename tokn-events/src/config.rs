@@ -0,0 +1,95 @@
+// tokn-events/src/config.rs
+
+use serde::Deserialize;
+use std::fmt;
+
+// ---
+
+/// Broker that auth events are published to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventsBackend {
+    // ---
+    /// Events are discarded (default)
+    #[default]
+    None,
+
+    /// Kafka; `url` lists bootstrap brokers as comma-separated `host:port`
+    Kafka,
+
+    /// NATS core publish; `url` is the server URL (`nats://host:4222`)
+    Nats,
+}
+
+impl fmt::Display for EventsBackend {
+    // ---
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // ---
+        f.write_str(match self {
+            EventsBackend::None => "none",
+            EventsBackend::Kafka => "kafka",
+            EventsBackend::Nats => "nats",
+        })
+    }
+}
+
+// ---
+
+/// Service configuration section for event publishing.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct EventsConfig {
+    // ---
+    /// Broker to publish to (env `EVENTS_BACKEND`: `none`, `kafka`, or `nats`)
+    pub backend: EventsBackend,
+
+    /// Broker address (env `EVENTS_URL`); required unless `backend` is `none`
+    pub url: Option<String>,
+
+    /// Kafka topic or NATS subject (env `EVENTS_TOPIC`, default `tokn.auth`)
+    pub topic: String,
+
+    /// Events queued while the broker is slow; further events are dropped
+    /// (default: 1024)
+    pub buffer: usize,
+
+    /// Events queued for each live subscriber (`/admin/events`); one that
+    /// falls further behind skips the oldest (env `EVENTS_LIVE_BUFFER`,
+    /// default: 256)
+    pub live_buffer: usize,
+}
+
+// ---
+
+impl Default for EventsConfig {
+    // ---
+    fn default() -> Self {
+        // ---
+        Self {
+            backend: EventsBackend::None,
+            url: None,
+            topic: "tokn.auth".to_string(),
+            buffer: 1024,
+            live_buffer: 256,
+        }
+    }
+}
+
+// ---
+
+/// Config rule for the `events` section: a broker backend needs a `url`.
+///
+/// # Errors
+///
+/// Returns a message for `tokn_config::ConfigLoader::rule` when `backend` is
+/// `kafka` or `nats` and `url` is unset.
+pub fn validate_events_config(config: &EventsConfig) -> Result<(), String> {
+    // ---
+    if config.backend != EventsBackend::None && config.url.is_none() {
+        return Err(format!(
+            "backend '{}' requires a broker url (EVENTS_URL)",
+            config.backend
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,33 @@
+// tokn-events/src/error.rs
+
+use crate::EventsBackend;
+
+// ---
+
+/// Errors connecting to or publishing on an event broker.
+#[derive(Debug, thiserror::Error)]
+pub enum EventsError {
+    // ---
+    /// A backend was selected without a broker address
+    #[error("events backend '{0}' requires EVENTS_URL")]
+    MissingUrl(EventsBackend),
+
+    /// The backend was compiled out (see the crate's `kafka`/`nats` features)
+    #[error("events backend '{0}' is not enabled in this build")]
+    Unsupported(EventsBackend),
+
+    /// An event kind name that is not an [`AuthEventKind`](crate::AuthEventKind)
+    #[error("unknown event kind '{0}'")]
+    UnknownKind(String),
+
+    /// The event could not be serialized
+    #[error("failed to serialize event: {0}")]
+    Serialize(#[from] serde_json::Error),
+
+    /// The broker client reported an error
+    #[error("{backend} error: {message}")]
+    Broker {
+        backend: EventsBackend,
+        message: String,
+    },
+}
@@ -0,0 +1,169 @@
+// tokn-events/src/event.rs
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use uuid::Uuid;
+
+// ---
+
+use crate::EventsError;
+
+// ---
+
+/// What happened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthEventKind {
+    // ---
+    /// A user authenticated and approved a client (oauth2-server consent)
+    Login,
+
+    /// An access token was issued (any grant, including refresh)
+    TokenIssued,
+
+    /// A refresh token that had already been rotated was presented again,
+    /// which usually means it was stolen
+    RefreshReuse,
+
+    /// An access or refresh token was revoked before it expired
+    TokenRevoked,
+
+    /// Too many wrong one-time codes locked a user out of phone
+    /// verification until a new code is sent (emitted for every refused try)
+    Lockout,
+}
+
+impl AuthEventKind {
+    // ---
+    /// Wire name, as used in the JSON `kind` field and metric labels.
+    pub fn as_str(self) -> &'static str {
+        // ---
+        match self {
+            AuthEventKind::Login => "login",
+            AuthEventKind::TokenIssued => "token_issued",
+            AuthEventKind::RefreshReuse => "refresh_reuse",
+            AuthEventKind::TokenRevoked => "token_revoked",
+            AuthEventKind::Lockout => "lockout",
+        }
+    }
+}
+
+impl FromStr for AuthEventKind {
+    // ---
+    type Err = EventsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // ---
+        match s {
+            "login" => Ok(AuthEventKind::Login),
+            "token_issued" => Ok(AuthEventKind::TokenIssued),
+            "refresh_reuse" => Ok(AuthEventKind::RefreshReuse),
+            "token_revoked" => Ok(AuthEventKind::TokenRevoked),
+            "lockout" => Ok(AuthEventKind::Lockout),
+            other => Err(EventsError::UnknownKind(other.to_string())),
+        }
+    }
+}
+
+// ---
+
+/// A structured auth event, published as one JSON object.
+///
+/// # Security
+///
+/// Events identify users and tokens but never carry token values, secrets,
+/// or passwords; `jti` is the token's ID claim, not the token.
+///
+/// # Example
+///
+/// ```json
+/// {
+///   "id": "0b6f2d9e-4b3c-4e8f-9a51-3f1c2a7d8e90",
+///   "kind": "token_issued",
+///   "service": "oauth2-server",
+///   "occurred_at": "2026-01-05T12:00:00Z",
+///   "subject": "user_001",
+///   "client_id": "demo_client",
+///   "grant_type": "authorization_code"
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthEvent {
+    // ---
+    /// Unique event ID, for consumer-side deduplication
+    pub id: Uuid,
+
+    /// Event type
+    pub kind: AuthEventKind,
+
+    /// Emitting service; filled in by [`Events::emit`](crate::Events::emit)
+    pub service: String,
+
+    /// When the action happened
+    pub occurred_at: DateTime<Utc>,
+
+    /// User the event concerns
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+
+    /// OAuth2 client involved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+
+    /// ID claim of the access token involved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
+
+    /// Grant that produced the token (`authorization_code`, `refresh_token`, ...)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grant_type: Option<String>,
+}
+
+// ---
+
+impl AuthEvent {
+    // ---
+    /// New event of `kind`, timestamped now.
+    pub fn new(kind: AuthEventKind) -> Self {
+        // ---
+        Self {
+            id: Uuid::new_v4(),
+            kind,
+            service: String::new(),
+            occurred_at: Utc::now(),
+            subject: None,
+            client_id: None,
+            jti: None,
+            grant_type: None,
+        }
+    }
+
+    /// Set the user the event concerns.
+    pub fn subject(mut self, subject: impl Into<String>) -> Self {
+        // ---
+        self.subject = Some(subject.into());
+        self
+    }
+
+    /// Set the OAuth2 client involved.
+    pub fn client_id(mut self, client_id: impl Into<String>) -> Self {
+        // ---
+        self.client_id = Some(client_id.into());
+        self
+    }
+
+    /// Set the access token's ID claim.
+    pub fn jti(mut self, jti: impl Into<String>) -> Self {
+        // ---
+        self.jti = Some(jti.into());
+        self
+    }
+
+    /// Set the grant that produced the token.
+    pub fn grant_type(mut self, grant_type: impl Into<String>) -> Self {
+        // ---
+        self.grant_type = Some(grant_type.into());
+        self
+    }
+}
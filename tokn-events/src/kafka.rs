@@ -0,0 +1,102 @@
+// tokn-events/src/kafka.rs
+
+use chrono::Utc;
+use rskafka::client::{
+    partition::{Compression, PartitionClient, UnknownTopicHandling},
+    ClientBuilder,
+};
+use rskafka::record::Record;
+use rskafka::BackoffConfig;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+// ---
+
+use crate::{EventPublisher, EventsBackend, EventsError};
+
+// ---
+
+/// How long the client keeps retrying a connection or produce call before
+/// reporting an error. rskafka retries forever by default, which would stall
+/// startup retries and the publish queue behind an unreachable broker.
+const RETRY_DEADLINE: Duration = Duration::from_secs(10);
+
+// ---
+
+/// Publishes events to a Kafka topic.
+///
+/// Every event goes to partition 0 and is keyed by its subject, so a
+/// consumer sees each user's events in order. The topic must already exist.
+pub struct KafkaPublisher {
+    // ---
+    partition: PartitionClient,
+}
+
+// ---
+
+impl KafkaPublisher {
+    // ---
+    /// Connect to `brokers` (comma-separated `host:port` bootstrap list) and
+    /// publish to `topic`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no broker can be reached or the topic does not exist.
+    pub async fn connect(brokers: &str, topic: &str) -> Result<Self, EventsError> {
+        // ---
+        let brokers = brokers
+            .split(',')
+            .map(str::trim)
+            .filter(|broker| !broker.is_empty())
+            .map(String::from)
+            .collect();
+
+        let client = ClientBuilder::new(brokers)
+            .client_id("tokn")
+            .backoff_config(BackoffConfig {
+                deadline: Some(RETRY_DEADLINE),
+                ..BackoffConfig::default()
+            })
+            .build()
+            .await
+            .map_err(broker_error)?;
+        let partition = client
+            .partition_client(topic, 0, UnknownTopicHandling::Error)
+            .await
+            .map_err(broker_error)?;
+
+        Ok(Self { partition })
+    }
+}
+
+// ---
+
+impl EventPublisher for KafkaPublisher {
+    // ---
+    async fn publish(&self, key: Option<&str>, payload: Vec<u8>) -> Result<(), EventsError> {
+        // ---
+        let record = Record {
+            key: key.map(|key| key.as_bytes().to_vec()),
+            value: Some(payload),
+            headers: BTreeMap::new(),
+            timestamp: Utc::now(),
+        };
+
+        self.partition
+            .produce(vec![record], Compression::NoCompression)
+            .await
+            .map_err(broker_error)?;
+
+        Ok(())
+    }
+}
+
+// ---
+
+fn broker_error(e: rskafka::client::error::Error) -> EventsError {
+    // ---
+    EventsError::Broker {
+        backend: EventsBackend::Kafka,
+        message: e.to_string(),
+    }
+}
@@ -0,0 +1,50 @@
+// tokn-events/src/lib.rs
+
+//! Auth event publishing for tokn services
+//!
+//! Services describe security-relevant actions (logins, token issuance,
+//! refresh-token reuse, revocations) as [`AuthEvent`]s and hand them to an
+//! [`Events`] handle. A background task serializes each event to JSON and
+//! sends it to Kafka or NATS, so fraud and analytics pipelines can consume
+//! them without touching the request path.
+//!
+//! Publishing is best-effort: a slow or unreachable broker never delays or
+//! fails a request. Events that cannot be queued or sent are dropped, logged,
+//! and counted in `tokn_events_dropped_total` / `tokn_events_failed_total`.
+//!
+//! Within a process, [`LiveEvents`] additionally fans events out to live
+//! subscribers (the admin event stream), independent of the broker.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), tokn_events::EventsError> {
+//! use tokn_events::{AuthEvent, AuthEventKind, Events, EventsConfig};
+//!
+//! let events = Events::connect(&EventsConfig::default(), "jwt-service").await?;
+//! events.emit(AuthEvent::new(AuthEventKind::TokenIssued).subject("user_123"));
+//! # Ok(())
+//! # }
+//! ```
+
+mod config;
+mod error;
+mod event;
+#[cfg(feature = "kafka")]
+mod kafka;
+mod live;
+#[cfg(feature = "nats")]
+mod nats;
+mod publisher;
+
+// ---
+
+pub use config::{validate_events_config, EventsBackend, EventsConfig};
+pub use error::EventsError;
+pub use event::{AuthEvent, AuthEventKind};
+#[cfg(feature = "kafka")]
+pub use kafka::KafkaPublisher;
+pub use live::LiveEvents;
+#[cfg(feature = "nats")]
+pub use nats::NatsPublisher;
+pub use publisher::{EventPublisher, Events};
@@ -0,0 +1,68 @@
+// tokn-events/src/live.rs
+
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+// ---
+
+use crate::AuthEvent;
+
+// ---
+
+/// In-process fan-out of auth events to live subscribers, such as the
+/// `/admin/events` stream behind admin dashboards.
+///
+/// Attach it to an [`Events`](crate::Events) handle with
+/// [`Events::with_live`](crate::Events::with_live); every emitted event is
+/// then also delivered here, whether or not a broker is configured.
+///
+/// Each subscriber gets its own queue of `capacity` events. Sending never
+/// waits: a subscriber that falls further behind skips the oldest events and
+/// learns how many it missed from its next receive, so one slow dashboard
+/// cannot hold up requests or other subscribers. Clones share the channel.
+#[derive(Clone)]
+pub struct LiveEvents {
+    // ---
+    service: Arc<str>,
+    sender: broadcast::Sender<AuthEvent>,
+}
+
+impl LiveEvents {
+    // ---
+    /// A channel for events from `service`, queueing up to `capacity` events
+    /// per subscriber.
+    pub fn new(service: &str, capacity: usize) -> Self {
+        // ---
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self {
+            service: service.into(),
+            sender,
+        }
+    }
+
+    /// Receive every event sent from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<AuthEvent> {
+        // ---
+        self.sender.subscribe()
+    }
+
+    /// Number of live subscribers.
+    pub fn subscribers(&self) -> usize {
+        // ---
+        self.sender.receiver_count()
+    }
+
+    // ---
+    /// Deliver `event`, stamped with this channel's service, to the current
+    /// subscribers; a no-op when there are none.
+    pub(crate) fn send(&self, event: &AuthEvent) {
+        // ---
+        if self.sender.receiver_count() == 0 {
+            return;
+        }
+        let mut event = event.clone();
+        event.service = self.service.to_string();
+        // Only fails when the last subscriber left since the check above
+        let _ = self.sender.send(event);
+    }
+}
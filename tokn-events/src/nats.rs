@@ -0,0 +1,59 @@
+// tokn-events/src/nats.rs
+
+use crate::{EventPublisher, EventsBackend, EventsError};
+
+// ---
+
+/// Publishes events on a NATS subject (core NATS, at-most-once).
+///
+/// Use a JetStream stream bound to the subject for durable delivery.
+pub struct NatsPublisher {
+    // ---
+    client: async_nats::Client,
+    subject: String,
+}
+
+// ---
+
+impl NatsPublisher {
+    // ---
+    /// Connect to the NATS server at `url` and publish on `subject`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the server cannot be reached.
+    pub async fn connect(url: &str, subject: &str) -> Result<Self, EventsError> {
+        // ---
+        let client = async_nats::connect(url)
+            .await
+            .map_err(|e| broker_error(e.to_string()))?;
+
+        Ok(Self {
+            client,
+            subject: subject.to_string(),
+        })
+    }
+}
+
+// ---
+
+impl EventPublisher for NatsPublisher {
+    // ---
+    async fn publish(&self, _key: Option<&str>, payload: Vec<u8>) -> Result<(), EventsError> {
+        // ---
+        self.client
+            .publish(self.subject.clone(), payload.into())
+            .await
+            .map_err(|e| broker_error(e.to_string()))
+    }
+}
+
+// ---
+
+fn broker_error(message: String) -> EventsError {
+    // ---
+    EventsError::Broker {
+        backend: EventsBackend::Nats,
+        message,
+    }
+}
@@ -0,0 +1,183 @@
+// tokn-events/src/publisher.rs
+
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::mpsc::{self, error::TrySendError};
+
+// ---
+
+use crate::{AuthEvent, EventsBackend, EventsConfig, EventsError, LiveEvents};
+
+// ---
+
+/// A broker connection that auth events are sent through.
+///
+/// Implemented by [`KafkaPublisher`](crate::KafkaPublisher) and
+/// [`NatsPublisher`](crate::NatsPublisher); implement it to route events
+/// elsewhere (or to capture them in tests) and hand it to [`Events::start`].
+pub trait EventPublisher: Send + Sync + 'static {
+    // ---
+    /// Send one JSON-encoded event. `key` is the event's subject, when it has
+    /// one, so brokers that partition by key keep a user's events in order.
+    fn publish(
+        &self,
+        key: Option<&str>,
+        payload: Vec<u8>,
+    ) -> impl Future<Output = Result<(), EventsError>> + Send;
+}
+
+// ---
+
+/// Handle for emitting auth events; cheap to clone into handler state.
+///
+/// The default handle is disabled and discards every event.
+#[derive(Clone, Default)]
+pub struct Events {
+    // ---
+    inner: Option<Arc<Inner>>,
+    live: Option<LiveEvents>,
+}
+
+struct Inner {
+    // ---
+    service: String,
+    queue: mpsc::Sender<AuthEvent>,
+}
+
+// ---
+
+impl Events {
+    // ---
+    /// A handle that discards every event.
+    pub fn disabled() -> Self {
+        // ---
+        Self::default()
+    }
+
+    // ---
+    /// Connect to the broker selected by `config` and start publishing events
+    /// stamped with `service`. Returns a disabled handle for
+    /// [`EventsBackend::None`].
+    ///
+    /// Must be called within a Tokio runtime.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `url` is missing, the backend is compiled out, or
+    /// the broker cannot be reached.
+    #[cfg_attr(not(any(feature = "kafka", feature = "nats")), allow(unused_variables))]
+    pub async fn connect(config: &EventsConfig, service: &str) -> Result<Self, EventsError> {
+        // ---
+        match config.backend {
+            EventsBackend::None => Ok(Self::disabled()),
+            #[cfg(feature = "kafka")]
+            EventsBackend::Kafka => {
+                let publisher = crate::KafkaPublisher::connect(url(config)?, &config.topic).await?;
+                Ok(Self::start(publisher, service, config.buffer))
+            }
+            #[cfg(feature = "nats")]
+            EventsBackend::Nats => {
+                let publisher = crate::NatsPublisher::connect(url(config)?, &config.topic).await?;
+                Ok(Self::start(publisher, service, config.buffer))
+            }
+            #[allow(unreachable_patterns)]
+            backend => Err(EventsError::Unsupported(backend)),
+        }
+    }
+
+    // ---
+    /// Start publishing through `publisher` on a background task, queueing at
+    /// most `buffer` events.
+    ///
+    /// Must be called within a Tokio runtime.
+    pub fn start<P: EventPublisher>(publisher: P, service: &str, buffer: usize) -> Self {
+        // ---
+        let (queue, events) = mpsc::channel(buffer.max(1));
+        tokio::spawn(publish_loop(publisher, events));
+
+        Self {
+            inner: Some(Arc::new(Inner {
+                service: service.to_string(),
+                queue,
+            })),
+            live: None,
+        }
+    }
+
+    // ---
+    /// Also deliver every event to `live`'s subscribers, with or without a
+    /// broker.
+    pub fn with_live(mut self, live: LiveEvents) -> Self {
+        // ---
+        self.live = Some(live);
+        self
+    }
+
+    // ---
+    /// Whether events are published to a broker.
+    pub fn is_enabled(&self) -> bool {
+        // ---
+        self.inner.is_some()
+    }
+
+    // ---
+    /// Queue `event` for publishing, stamped with this service's name, and
+    /// hand it to any live subscribers.
+    ///
+    /// Never blocks: when the queue is full the event is dropped and counted
+    /// in `tokn_events_dropped_total`.
+    pub fn emit(&self, mut event: AuthEvent) {
+        // ---
+        if let Some(live) = &self.live {
+            live.send(&event);
+        }
+        let Some(inner) = &self.inner else {
+            return;
+        };
+        event.service.clone_from(&inner.service);
+        let kind = event.kind.as_str();
+
+        if let Err(e) = inner.queue.try_send(event) {
+            let reason = match e {
+                TrySendError::Full(_) => "queue full",
+                TrySendError::Closed(_) => "publisher stopped",
+            };
+            metrics::counter!("tokn_events_dropped_total", "kind" => kind).increment(1);
+            tracing::warn!("Dropped {kind} event ({reason})");
+        }
+    }
+}
+
+// ---
+
+#[cfg(any(feature = "kafka", feature = "nats"))]
+fn url(config: &EventsConfig) -> Result<&str, EventsError> {
+    // ---
+    config
+        .url
+        .as_deref()
+        .ok_or(EventsError::MissingUrl(config.backend))
+}
+
+// ---
+
+async fn publish_loop<P: EventPublisher>(publisher: P, mut events: mpsc::Receiver<AuthEvent>) {
+    // ---
+    while let Some(event) = events.recv().await {
+        let kind = event.kind.as_str();
+        let result = match serde_json::to_vec(&event) {
+            Ok(payload) => publisher.publish(event.subject.as_deref(), payload).await,
+            Err(e) => Err(e.into()),
+        };
+
+        match result {
+            Ok(()) => {
+                metrics::counter!("tokn_events_published_total", "kind" => kind).increment(1);
+            }
+            Err(e) => {
+                metrics::counter!("tokn_events_failed_total", "kind" => kind).increment(1);
+                tracing::warn!("Failed to publish {kind} event {}: {e}", event.id);
+            }
+        }
+    }
+}
@@ -0,0 +1,55 @@
+/* tokn-ffi/include/tokn.h
+ *
+ * C ABI for validating jwt-service access tokens in-process.
+ *
+ * Link against libtokn_ffi (cargo build --release -p tokn-ffi). Validation
+ * checks the HS256 signature and `exp` (60 seconds of leeway) against the
+ * system clock; revocation is NOT checked. All functions are thread-safe.
+ *
+ * Keep in sync with tokn-ffi/src/.
+ */
+
+#ifndef TOKN_H
+#define TOKN_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Status codes */
+#define TOKN_OK                0 /* token is valid */
+#define TOKN_EXPIRED           1 /* `exp` is in the past */
+#define TOKN_INVALID_SIGNATURE 2 /* tampered token or wrong secret */
+#define TOKN_INVALID_ALGORITHM 3 /* not signed with HS256 */
+#define TOKN_MALFORMED         4 /* unparseable token or claims */
+#define TOKN_INVALID_ARGUMENT  5 /* null pointer or non-UTF-8 string */
+#define TOKN_INTERNAL          6 /* bug in tokn-ffi */
+
+/* Claims of a validated token. The strings belong to tokn-ffi: release
+ * them with tokn_claims_free(), never free(). */
+typedef struct tokn_claims {
+    char *sub;   /* subject (user ID) */
+    char *email; /* user email */
+    char *jti;   /* token ID */
+    int64_t exp; /* expiry, Unix seconds */
+    int64_t iat; /* issued at, Unix seconds */
+} tokn_claims;
+
+/* Validate `token`, signed with `secret` (jwt-service's JWT_SECRET).
+ * Returns TOKN_OK and fills in `claims` (if not NULL), or the reason the
+ * token was rejected, leaving `claims` untouched. */
+int tokn_validate_token(const char *token, const char *secret, tokn_claims *claims);
+
+/* Free the strings in `claims` and set them to NULL. NULL is a no-op. */
+void tokn_claims_free(tokn_claims *claims);
+
+/* Static description of `status`; never free it. */
+const char *tokn_status_message(int status);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* TOKN_H */
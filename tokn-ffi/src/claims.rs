@@ -0,0 +1,70 @@
+// tokn-ffi/src/claims.rs
+
+use std::ffi::{c_char, CString};
+use std::ptr;
+use tokn_core::Claims;
+
+// ---
+
+/// Claims of a validated token (`tokn_claims` in `tokn.h`).
+///
+/// The strings are owned by tokn-ffi: release them with
+/// [`tokn_claims_free`], never with `free()`.
+#[repr(C)]
+#[derive(Debug)]
+pub struct ToknClaims {
+    // ---
+    /// Subject (user ID)
+    pub sub: *mut c_char,
+    /// User email
+    pub email: *mut c_char,
+    /// Token ID, the key jwt-service revokes by
+    pub jti: *mut c_char,
+    /// Expiry, Unix seconds
+    pub exp: i64,
+    /// Issued at, Unix seconds
+    pub iat: i64,
+}
+
+impl ToknClaims {
+    // ---
+    /// C copies of `claims`, or `None` if a string contains a NUL byte.
+    pub(crate) fn new(claims: &Claims) -> Option<Self> {
+        // ---
+        let sub = CString::new(claims.sub.as_str()).ok()?;
+        let email = CString::new(claims.email.as_str()).ok()?;
+        let jti = CString::new(claims.jti.as_str()).ok()?;
+
+        Some(Self {
+            sub: sub.into_raw(),
+            email: email.into_raw(),
+            jti: jti.into_raw(),
+            exp: claims.exp as i64,
+            iat: claims.iat as i64,
+        })
+    }
+}
+
+// ---
+
+/// Free the strings in `claims` and null them out, so a second call is
+/// harmless. Does nothing if `claims` is null.
+///
+/// # Safety
+///
+/// `claims` must be null or point to a `tokn_claims` filled in by
+/// `tokn_validate_token` (or already freed by this function).
+#[no_mangle]
+pub unsafe extern "C" fn tokn_claims_free(claims: *mut ToknClaims) {
+    // ---
+    let Some(claims) = claims.as_mut() else {
+        return;
+    };
+
+    for field in [&mut claims.sub, &mut claims.email, &mut claims.jti] {
+        if !field.is_null() {
+            drop(CString::from_raw(*field));
+            *field = ptr::null_mut();
+        }
+    }
+}
@@ -0,0 +1,44 @@
+// tokn-ffi/src/lib.rs
+
+//! tokn-ffi - C ABI for validating jwt-service tokens in-process
+//!
+//! Lets C and C++ services (such as a gateway) check a jwt-service access
+//! token with the same code jwt-service uses, instead of calling
+//! `POST /v1/auth/validate` per request. The declarations are in
+//! `include/tokn.h`; link `libtokn_ffi.so` (or `.dylib`) or, statically,
+//! `libtokn_ffi.a`.
+//!
+//! Validation checks the HS256 signature and `exp` (with 60 seconds of
+//! leeway) against the system clock, exactly as `tokn_core::validate_token`.
+//! Revocation is not checked: that needs jwt-service's Redis blacklist.
+//!
+//! Every function is thread-safe and keeps no global state. Panics never
+//! cross the ABI; they are reported as `TOKN_INTERNAL`.
+//!
+//! # Example
+//!
+//! ```c
+//! #include "tokn.h"
+//!
+//! tokn_claims claims;
+//! int status = tokn_validate_token(token, secret, &claims);
+//! if (status == TOKN_OK) {
+//!     printf("user %s\n", claims.sub);
+//!     tokn_claims_free(&claims);
+//! } else {
+//!     printf("rejected: %s\n", tokn_status_message(status));
+//! }
+//! ```
+
+mod claims;
+mod status;
+mod validate;
+
+// ---
+
+pub use claims::{tokn_claims_free, ToknClaims};
+pub use status::{
+    tokn_status_message, TOKN_EXPIRED, TOKN_INTERNAL, TOKN_INVALID_ALGORITHM,
+    TOKN_INVALID_ARGUMENT, TOKN_INVALID_SIGNATURE, TOKN_MALFORMED, TOKN_OK,
+};
+pub use validate::tokn_validate_token;
@@ -0,0 +1,68 @@
+// tokn-ffi/src/status.rs
+
+use std::ffi::{c_char, c_int};
+use tokn_core::TokenError;
+
+// ---
+
+/// The token is valid.
+pub const TOKN_OK: c_int = 0;
+
+/// The token's `exp` is in the past.
+pub const TOKN_EXPIRED: c_int = 1;
+
+/// The signature does not match (tampered token or wrong secret).
+pub const TOKN_INVALID_SIGNATURE: c_int = 2;
+
+/// The token is signed with an algorithm other than HS256.
+pub const TOKN_INVALID_ALGORITHM: c_int = 3;
+
+/// The token could not be parsed, or its claims cannot be represented as C
+/// strings.
+pub const TOKN_MALFORMED: c_int = 4;
+
+/// A required pointer was null, or a string was not valid UTF-8.
+pub const TOKN_INVALID_ARGUMENT: c_int = 5;
+
+/// A bug in tokn-ffi (e.g. a caught panic).
+pub const TOKN_INTERNAL: c_int = 6;
+
+// ---
+
+/// The status a validation error is reported as.
+pub(crate) fn status_of(error: &TokenError) -> c_int {
+    // ---
+    match error {
+        TokenError::Expired => TOKN_EXPIRED,
+        TokenError::InvalidSignature => TOKN_INVALID_SIGNATURE,
+        TokenError::InvalidAlgorithm => TOKN_INVALID_ALGORITHM,
+        // A single-purpose token's claims are not an access token's
+        TokenError::Malformed
+        | TokenError::WrongPurpose
+        | TokenError::WrongAudience
+        | TokenError::WrongType => TOKN_MALFORMED,
+        TokenError::UnknownKey | TokenError::Encoding(_) | TokenError::InvalidKey(_) => {
+            TOKN_INTERNAL
+        }
+    }
+}
+
+// ---
+
+/// A static, NUL-terminated description of `status`, safe to log and to
+/// return to API callers. Never free it.
+#[no_mangle]
+pub extern "C" fn tokn_status_message(status: c_int) -> *const c_char {
+    // ---
+    let message = match status {
+        TOKN_OK => c"Token is valid",
+        TOKN_EXPIRED => c"Token has expired",
+        TOKN_INVALID_SIGNATURE => c"Invalid token signature",
+        TOKN_INVALID_ALGORITHM => c"Invalid token algorithm",
+        TOKN_MALFORMED => c"Malformed token",
+        TOKN_INVALID_ARGUMENT => c"Invalid argument",
+        TOKN_INTERNAL => c"Internal error",
+        _ => c"Unknown status",
+    };
+    message.as_ptr()
+}
@@ -0,0 +1,69 @@
+// tokn-ffi/src/validate.rs
+
+use std::ffi::{c_char, c_int, CStr};
+use std::panic;
+use tokn_core::SystemClock;
+
+// ---
+
+use crate::claims::ToknClaims;
+use crate::status::{status_of, TOKN_INTERNAL, TOKN_INVALID_ARGUMENT, TOKN_MALFORMED, TOKN_OK};
+
+// ---
+
+/// Validate a jwt-service access token signed with `secret` (jwt-service's
+/// `JWT_SECRET`), writing its claims to `claims` on success.
+///
+/// Returns `TOKN_OK` or the reason the token was rejected (see
+/// `tokn_status_message`). On success, `claims` (if not null) owns three
+/// strings the caller must release with `tokn_claims_free`; on failure it is
+/// left untouched.
+///
+/// # Safety
+///
+/// `token` and `secret` must be null or NUL-terminated strings, and `claims`
+/// null or valid for writing one `tokn_claims`.
+#[no_mangle]
+pub unsafe extern "C" fn tokn_validate_token(
+    token: *const c_char,
+    secret: *const c_char,
+    claims: *mut ToknClaims,
+) -> c_int {
+    // ---
+    let (Some(token), Some(secret)) = (str_arg(token), str_arg(secret)) else {
+        return TOKN_INVALID_ARGUMENT;
+    };
+
+    let result = panic::catch_unwind(|| tokn_core::validate_token(token, secret, &SystemClock));
+    let decoded = match result {
+        Ok(Ok(decoded)) => decoded,
+        Ok(Err(e)) => return status_of(&e),
+        Err(_) => return TOKN_INTERNAL,
+    };
+
+    if claims.is_null() {
+        return TOKN_OK;
+    }
+    match ToknClaims::new(&decoded) {
+        Some(copied) => {
+            claims.write(copied);
+            TOKN_OK
+        }
+        None => TOKN_MALFORMED,
+    }
+}
+
+// ---
+
+/// `ptr` as a `&str`, or `None` if it is null or not UTF-8.
+///
+/// # Safety
+///
+/// `ptr` must be null or a NUL-terminated string outliving `'a`.
+unsafe fn str_arg<'a>(ptr: *const c_char) -> Option<&'a str> {
+    // ---
+    if ptr.is_null() {
+        return None;
+    }
+    CStr::from_ptr(ptr).to_str().ok()
+}
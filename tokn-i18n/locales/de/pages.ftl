@@ -0,0 +1,31 @@
+# tokn-i18n/locales/de/pages.ftl
+
+## oauth2-server consent page
+
+consent-title = Autorisierungsanfrage
+consent-intro = Die Anwendung <strong>{ $client }</strong> möchte auf Ihr Konto zugreifen.
+consent-scopes = Berechtigungen: { $scopes }
+consent-approve = Zulassen
+consent-deny = Ablehnen
+
+## Error pages
+
+error-title = Etwas ist schiefgelaufen
+error-invalid-authorize-request = Dieser Autorisierungsanfrage fehlen erforderliche Parameter oder sie enthält ungültige. Kehren Sie zur Anwendung zurück und versuchen Sie es erneut.
+
+## oauth2-client demo pages
+
+client-home-title = OAuth2-Client-Demo
+client-home-intro = Diese Demo zeigt den OAuth2-Autorisierungscode-Ablauf.
+client-home-login = Mit OAuth2 anmelden
+client-back-home = Zurück zur Startseite
+
+client-callback-title = Angemeldet - OAuth2-Client-Demo
+client-callback-heading = Erfolgreich authentifiziert!
+client-callback-welcome = Willkommen, <strong>{ $username }</strong>
+client-callback-token = Zugriffstoken: <code>{ $token }</code>
+
+client-profile-title = Profil - OAuth2-Client-Demo
+client-profile-heading = Profil
+client-profile-authenticated = Sie sind authentifiziert!
+client-profile-todo = <em>TODO: Echte Benutzerdaten vom Userinfo-Endpunkt anzeigen</em>
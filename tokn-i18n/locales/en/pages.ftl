@@ -0,0 +1,34 @@
+# tokn-i18n/locales/en/pages.ftl
+#
+# Messages may contain HTML; arguments ($client, $username, ...) are escaped
+# before they are substituted.
+
+## oauth2-server consent page
+
+consent-title = Authorization Request
+consent-intro = Application <strong>{ $client }</strong> wants to access your account.
+consent-scopes = Scopes: { $scopes }
+consent-approve = Approve
+consent-deny = Deny
+
+## Error pages
+
+error-title = Something went wrong
+error-invalid-authorize-request = This authorization request is missing required parameters or has invalid ones. Return to the application and try again.
+
+## oauth2-client demo pages
+
+client-home-title = OAuth2 Client Demo
+client-home-intro = This demo shows OAuth2 authorization code flow.
+client-home-login = Login with OAuth2
+client-back-home = Back to Home
+
+client-callback-title = Logged In - OAuth2 Client Demo
+client-callback-heading = Successfully Authenticated!
+client-callback-welcome = Welcome, <strong>{ $username }</strong>
+client-callback-token = Access Token: <code>{ $token }</code>
+
+client-profile-title = Profile - OAuth2 Client Demo
+client-profile-heading = Profile
+client-profile-authenticated = You are authenticated!
+client-profile-todo = <em>TODO: Display actual user information from userinfo endpoint</em>
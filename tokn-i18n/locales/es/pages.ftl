@@ -0,0 +1,31 @@
+# tokn-i18n/locales/es/pages.ftl
+
+## oauth2-server consent page
+
+consent-title = Solicitud de autorización
+consent-intro = La aplicación <strong>{ $client }</strong> quiere acceder a tu cuenta.
+consent-scopes = Permisos: { $scopes }
+consent-approve = Aprobar
+consent-deny = Denegar
+
+## Error pages
+
+error-title = Algo salió mal
+error-invalid-authorize-request = A esta solicitud de autorización le faltan parámetros obligatorios o tiene parámetros no válidos. Vuelve a la aplicación e inténtalo de nuevo.
+
+## oauth2-client demo pages
+
+client-home-title = Demo de cliente OAuth2
+client-home-intro = Esta demo muestra el flujo de código de autorización de OAuth2.
+client-home-login = Iniciar sesión con OAuth2
+client-back-home = Volver al inicio
+
+client-callback-title = Sesión iniciada - Demo de cliente OAuth2
+client-callback-heading = ¡Autenticación correcta!
+client-callback-welcome = Hola, <strong>{ $username }</strong>
+client-callback-token = Token de acceso: <code>{ $token }</code>
+
+client-profile-title = Perfil - Demo de cliente OAuth2
+client-profile-heading = Perfil
+client-profile-authenticated = ¡Has iniciado sesión!
+client-profile-todo = <em>TODO: mostrar la información real del usuario del endpoint userinfo</em>
@@ -0,0 +1,31 @@
+# tokn-i18n/locales/fr/pages.ftl
+
+## oauth2-server consent page
+
+consent-title = Demande d’autorisation
+consent-intro = L’application <strong>{ $client }</strong> souhaite accéder à votre compte.
+consent-scopes = Autorisations : { $scopes }
+consent-approve = Autoriser
+consent-deny = Refuser
+
+## Error pages
+
+error-title = Une erreur est survenue
+error-invalid-authorize-request = Cette demande d’autorisation comporte des paramètres manquants ou invalides. Revenez à l’application et réessayez.
+
+## oauth2-client demo pages
+
+client-home-title = Démo client OAuth2
+client-home-intro = Cette démo illustre le flux OAuth2 par code d’autorisation.
+client-home-login = Se connecter avec OAuth2
+client-back-home = Retour à l’accueil
+
+client-callback-title = Connecté - Démo client OAuth2
+client-callback-heading = Authentification réussie !
+client-callback-welcome = Bienvenue, <strong>{ $username }</strong>
+client-callback-token = Jeton d’accès : <code>{ $token }</code>
+
+client-profile-title = Profil - Démo client OAuth2
+client-profile-heading = Profil
+client-profile-authenticated = Vous êtes authentifié !
+client-profile-todo = <em>TODO : afficher les informations réelles de l’utilisateur (endpoint userinfo)</em>
@@ -0,0 +1,38 @@
+// tokn-i18n/src/config.rs
+
+use serde::Deserialize;
+use std::path::PathBuf;
+
+// ---
+
+/// Service configuration section for page localization.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct I18nConfig {
+    // ---
+    /// Locale used when `Accept-Language` matches none of the translations
+    /// (env `I18N_DEFAULT_LOCALE`, default `en`)
+    pub default_locale: String,
+
+    /// Serve every page in this locale, ignoring `Accept-Language`
+    /// (env `I18N_LOCALE`)
+    pub locale: Option<String>,
+
+    /// Directory of `<locale>/*.ftl` files overriding or adding to the
+    /// built-in translations (env `I18N_DIR`)
+    pub dir: Option<PathBuf>,
+}
+
+// ---
+
+impl Default for I18nConfig {
+    // ---
+    fn default() -> Self {
+        // ---
+        Self {
+            default_locale: "en".to_string(),
+            locale: None,
+            dir: None,
+        }
+    }
+}
@@ -0,0 +1,29 @@
+// tokn-i18n/src/error.rs
+
+use std::path::PathBuf;
+
+// ---
+
+/// Errors building a [`Localizer`](crate::Localizer) from its configuration.
+#[derive(Debug, thiserror::Error)]
+pub enum I18nError {
+    // ---
+    /// A configured locale or locale directory name is not a language tag
+    #[error("invalid locale '{0}'")]
+    InvalidLocale(String),
+
+    /// The default or forced locale has no messages
+    #[error("locale '{0}' has no translations (available: {1})")]
+    UnknownLocale(String, String),
+
+    /// An override directory or file could not be read
+    #[error("failed to read {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    /// A Fluent (`.ftl`) file has syntax errors
+    #[error("invalid Fluent file {path}: {message}")]
+    Parse { path: PathBuf, message: String },
+}
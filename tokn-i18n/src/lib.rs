@@ -0,0 +1,36 @@
+// tokn-i18n/src/lib.rs
+
+//! Localization of tokn's user-facing HTML pages
+//!
+//! Page text lives in [Fluent](https://projectfluent.org) files, one set per
+//! locale (`en`, `es`, `fr`, `de` are built in). Each request is served in the
+//! best match for its `Accept-Language` header; a deployment can change the
+//! fallback locale, force a single locale, or override and add translations
+//! from a directory of `.ftl` files (see [`I18nConfig`]).
+//!
+//! # Example
+//!
+//! ```
+//! use axum::response::Response;
+//! use tokn_i18n::Messages;
+//!
+//! // In a router whose state provides an `Arc<Localizer>` via `FromRef`
+//! async fn page(messages: Messages) -> Response {
+//!     let html = format!(
+//!         "<html lang=\"{}\"><h1>{}</h1></html>",
+//!         messages.lang(),
+//!         messages.text("client-home-title"),
+//!     );
+//!     messages.html(html)
+//! }
+//! ```
+
+mod config;
+mod error;
+mod localizer;
+
+// ---
+
+pub use config::I18nConfig;
+pub use error::I18nError;
+pub use localizer::{Localizer, Messages};
@@ -0,0 +1,349 @@
+// tokn-i18n/src/localizer.rs
+
+use axum::{
+    extract::{FromRef, FromRequestParts},
+    http::{header, request::Parts, HeaderValue},
+    response::{Html, IntoResponse, Response},
+};
+use fluent_bundle::{concurrent::FluentBundle, FluentArgs, FluentResource};
+use fluent_langneg::{negotiate_languages, NegotiationStrategy};
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::path::Path;
+use std::sync::Arc;
+use unic_langid::LanguageIdentifier;
+
+// ---
+
+use crate::{I18nConfig, I18nError};
+
+// ---
+
+/// Translations compiled into the binary, as `(locale, Fluent source)`.
+const BUILTIN: &[(&str, &str)] = &[
+    ("en", include_str!("../locales/en/pages.ftl")),
+    ("es", include_str!("../locales/es/pages.ftl")),
+    ("fr", include_str!("../locales/fr/pages.ftl")),
+    ("de", include_str!("../locales/de/pages.ftl")),
+];
+
+type Bundle = FluentBundle<FluentResource>;
+
+// ---
+
+/// Translations for every available locale, and the rules for picking one
+/// per request.
+///
+/// Built from the built-in `en`, `es`, `fr`, and `de` messages plus any
+/// deployment overrides in [`I18nConfig::dir`]. A message missing from the
+/// chosen locale falls back to the default locale, then to its id.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Arc;
+/// use tokn_i18n::Localizer;
+///
+/// let localizer = Arc::new(Localizer::builtin());
+/// let messages = localizer.negotiate(Some("fr-CA, en;q=0.5"));
+/// assert_eq!(messages.lang(), "fr");
+/// assert_eq!(messages.text("consent-approve"), "Autoriser");
+/// ```
+pub struct Localizer {
+    // ---
+    bundles: BTreeMap<LanguageIdentifier, Bundle>,
+    available: Vec<LanguageIdentifier>,
+    default: LanguageIdentifier,
+    forced: Option<LanguageIdentifier>,
+}
+
+impl Localizer {
+    // ---
+    /// Load the built-in translations, apply the overrides in `config.dir`,
+    /// and check that the default and forced locales exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`I18nError`] when a locale is not a valid language tag, an
+    /// override file cannot be read or parsed, or `default_locale`/`locale`
+    /// names a locale with no translations.
+    pub fn new(config: &I18nConfig) -> Result<Self, I18nError> {
+        // ---
+        let mut bundles = BTreeMap::new();
+        for (locale, source) in BUILTIN {
+            let path = Path::new("locales").join(locale).join("pages.ftl");
+            let resource = parse_resource(source.to_string(), &path)?;
+            bundle_for(&mut bundles, parse_locale(locale)?).add_resource_overriding(resource);
+        }
+
+        if let Some(dir) = &config.dir {
+            load_overrides(&mut bundles, dir)?;
+        }
+
+        let available: Vec<LanguageIdentifier> = bundles.keys().cloned().collect();
+        let require = |locale: &str| {
+            let locale = parse_locale(locale)?;
+            if bundles.contains_key(&locale) {
+                Ok(locale)
+            } else {
+                let names: Vec<String> = available.iter().map(|l| l.to_string()).collect();
+                Err(I18nError::UnknownLocale(
+                    locale.to_string(),
+                    names.join(", "),
+                ))
+            }
+        };
+
+        Ok(Self {
+            default: require(&config.default_locale)?,
+            forced: config.locale.as_deref().map(require).transpose()?,
+            available,
+            bundles,
+        })
+    }
+
+    /// The built-in translations with English as the default.
+    pub fn builtin() -> Self {
+        // ---
+        Self::new(&I18nConfig::default()).expect("built-in translations are valid")
+    }
+
+    /// Locales with translations, in tag order.
+    pub fn available(&self) -> &[LanguageIdentifier] {
+        // ---
+        &self.available
+    }
+
+    /// Pick the locale for a request from its `Accept-Language` header.
+    ///
+    /// The forced locale wins when configured; otherwise the highest-weighted
+    /// language with translations (`de-AT` matches `de`), else the default.
+    pub fn negotiate(self: &Arc<Self>, accept_language: Option<&str>) -> Messages {
+        // ---
+        let locale = self.forced.clone().unwrap_or_else(|| {
+            let requested = accept_language.map(requested_locales).unwrap_or_default();
+            negotiate_languages(
+                &requested,
+                &self.available,
+                Some(&self.default),
+                NegotiationStrategy::Lookup,
+            )
+            .first()
+            .map(|locale| (*locale).clone())
+            .unwrap_or_else(|| self.default.clone())
+        });
+
+        Messages {
+            localizer: self.clone(),
+            locale,
+        }
+    }
+
+    // ---
+    fn format(&self, locale: &LanguageIdentifier, id: &str, args: Option<&FluentArgs>) -> String {
+        // ---
+        [locale, &self.default]
+            .into_iter()
+            .filter_map(|locale| self.bundles.get(locale))
+            .find_map(|bundle| {
+                let pattern = bundle.get_message(id)?.value()?;
+                let mut errors = Vec::new();
+                let text = bundle.format_pattern(pattern, args, &mut errors);
+                if !errors.is_empty() {
+                    tracing::warn!("Message '{id}' ({locale}) formatted with errors: {errors:?}");
+                }
+                Some(text.into_owned())
+            })
+            .unwrap_or_else(|| {
+                tracing::warn!("Message '{id}' missing from locale '{locale}' and the default");
+                id.to_string()
+            })
+    }
+}
+
+// ---
+
+/// Messages in the locale chosen for one request.
+///
+/// Also an axum extractor for any state that provides an `Arc<Localizer>`
+/// via [`FromRef`], negotiating from the request's `Accept-Language` header.
+///
+/// # Security
+///
+/// Message text is trusted HTML (shipped with tokn or by the deployment).
+/// Arguments passed to [`format`](Self::format) are HTML-escaped, so request
+/// data such as a client id cannot inject markup.
+pub struct Messages {
+    // ---
+    localizer: Arc<Localizer>,
+    locale: LanguageIdentifier,
+}
+
+impl Messages {
+    // ---
+    /// The chosen locale's language tag, for `<html lang="...">`.
+    pub fn lang(&self) -> String {
+        // ---
+        self.locale.to_string()
+    }
+
+    /// The message `id`, which takes no arguments.
+    pub fn text(&self, id: &str) -> String {
+        // ---
+        self.localizer.format(&self.locale, id, None)
+    }
+
+    /// The message `id` with `args` (HTML-escaped) substituted.
+    pub fn format(&self, id: &str, args: &[(&str, &str)]) -> String {
+        // ---
+        let mut fluent_args = FluentArgs::new();
+        for (name, value) in args {
+            fluent_args.set(*name, escape_html(value));
+        }
+        self.localizer.format(&self.locale, id, Some(&fluent_args))
+    }
+
+    /// Respond with the HTML page `body`, marked with its `Content-Language`
+    /// and `Vary: Accept-Language` so caches keep one copy per language.
+    pub fn html(&self, body: String) -> Response {
+        // ---
+        let mut response = Html(body).into_response();
+        let headers = response.headers_mut();
+        if let Ok(lang) = HeaderValue::from_str(&self.lang()) {
+            headers.insert(header::CONTENT_LANGUAGE, lang);
+        }
+        headers.append(header::VARY, HeaderValue::from_static("accept-language"));
+        response
+    }
+}
+
+impl<S> FromRequestParts<S> for Messages
+where
+    Arc<Localizer>: FromRef<S>,
+    S: Send + Sync,
+{
+    // ---
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        // ---
+        let accept_language = parts
+            .headers
+            .get(header::ACCEPT_LANGUAGE)
+            .and_then(|value| value.to_str().ok());
+        Ok(Arc::<Localizer>::from_ref(state).negotiate(accept_language))
+    }
+}
+
+// ---
+
+fn bundle_for(
+    bundles: &mut BTreeMap<LanguageIdentifier, Bundle>,
+    locale: LanguageIdentifier,
+) -> &mut Bundle {
+    // ---
+    bundles.entry(locale.clone()).or_insert_with(|| {
+        let mut bundle = Bundle::new_concurrent(vec![locale]);
+        // Unicode isolation marks around arguments would show up in HTML attributes
+        bundle.set_use_isolating(false);
+        bundle
+    })
+}
+
+/// Add every `<dir>/<locale>/*.ftl` file, replacing built-in messages with
+/// the same id.
+fn load_overrides(
+    bundles: &mut BTreeMap<LanguageIdentifier, Bundle>,
+    dir: &Path,
+) -> Result<(), I18nError> {
+    // ---
+    let io_error = |path: &Path| {
+        let path = path.to_path_buf();
+        move |source| I18nError::Io { path, source }
+    };
+
+    let mut locale_dirs: Vec<_> = std::fs::read_dir(dir)
+        .map_err(io_error(dir))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_dir())
+        .collect();
+    locale_dirs.sort();
+
+    for locale_dir in locale_dirs {
+        let name = locale_dir.file_name().unwrap_or_default().to_string_lossy();
+        let locale = parse_locale(&name)?;
+
+        let mut files: Vec<_> = std::fs::read_dir(&locale_dir)
+            .map_err(io_error(&locale_dir))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "ftl"))
+            .collect();
+        files.sort();
+
+        for path in files {
+            let source = std::fs::read_to_string(&path).map_err(io_error(&path))?;
+            let resource = parse_resource(source, &path)?;
+            bundle_for(bundles, locale.clone()).add_resource_overriding(resource);
+            tracing::info!("Loaded translations for '{locale}' from {}", path.display());
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_resource(source: String, path: &Path) -> Result<FluentResource, I18nError> {
+    // ---
+    FluentResource::try_new(source).map_err(|(_, errors)| I18nError::Parse {
+        path: path.to_path_buf(),
+        message: errors
+            .iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>()
+            .join("; "),
+    })
+}
+
+fn parse_locale(tag: &str) -> Result<LanguageIdentifier, I18nError> {
+    // ---
+    tag.parse()
+        .map_err(|_| I18nError::InvalidLocale(tag.to_string()))
+}
+
+/// Languages from an `Accept-Language` header, most preferred first.
+///
+/// Orders by `q` weight (ties keep header order) and drops `*`, `q=0`, and
+/// tags that do not parse.
+fn requested_locales(header: &str) -> Vec<LanguageIdentifier> {
+    // ---
+    let mut weighted: Vec<(f32, LanguageIdentifier)> = header
+        .split(',')
+        .filter_map(|item| {
+            let mut parts = item.split(';').map(str::trim);
+            let tag = parts.next()?;
+            let q = parts
+                .find_map(|param| param.strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.parse::<f32>().ok())?;
+            (q > 0.0 && tag != "*")
+                .then(|| tag.parse().ok().map(|locale| (q, locale)))
+                .flatten()
+        })
+        .collect();
+    weighted.sort_by(|a, b| b.0.total_cmp(&a.0));
+    weighted.into_iter().map(|(_, locale)| locale).collect()
+}
+
+fn escape_html(value: &str) -> String {
+    // ---
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,109 @@
+// tokn-load/src/cli.rs
+
+//! Command-line options
+
+use clap::{Parser, ValueEnum};
+use std::time::Duration;
+
+// ---
+
+/// Concurrent load generator for the tokn token endpoints.
+///
+/// Runs each selected scenario in turn and prints latency percentiles,
+/// throughput, and error rates. With no `--scenario`, all scenarios run.
+#[derive(Debug, Parser)]
+#[command(name = "tokn-load", version)]
+pub struct Args {
+    // ---
+    /// Scenario to run (repeatable)
+    #[arg(short, long = "scenario", value_enum)]
+    pub scenarios: Vec<Scenario>,
+
+    /// Number of concurrent workers
+    #[arg(short, long, default_value_t = 32)]
+    pub concurrency: usize,
+
+    /// Total requests per scenario (ignored when --duration is set)
+    #[arg(short = 'n', long, default_value_t = 1000)]
+    pub requests: u64,
+
+    /// Run each scenario for this many seconds instead of a fixed request count
+    #[arg(short, long, value_parser = parse_seconds)]
+    pub duration: Option<Duration>,
+
+    /// Per-request timeout in seconds
+    #[arg(long, default_value = "10", value_parser = parse_seconds)]
+    pub timeout: Duration,
+
+    /// jwt-service base URL
+    #[arg(long, env = "TOKN_JWT_URL", default_value = "http://127.0.0.1:8083")]
+    pub jwt_url: String,
+
+    /// oauth2-server base URL
+    #[arg(long, env = "TOKN_OAUTH2_URL", default_value = "http://127.0.0.1:8082")]
+    pub oauth2_url: String,
+
+    /// OAuth2 client ID used for the /v1/oauth/token scenario
+    #[arg(long, default_value = "demo_client")]
+    pub client_id: String,
+
+    /// OAuth2 client secret used for the /v1/oauth/token scenario
+    #[arg(long, default_value = "demo_secret")]
+    pub client_secret: String,
+
+    /// Redirect URI registered for --client-id
+    #[arg(long, default_value = "http://127.0.0.1:8081/callback")]
+    pub redirect_uri: String,
+}
+
+// ---
+
+/// Endpoint under load.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Scenario {
+    // ---
+    /// POST /v1/auth/token on jwt-service (signing + Redis write)
+    JwtToken,
+
+    /// POST /v1/auth/validate on jwt-service (signature check + blacklist lookup)
+    JwtValidate,
+
+    /// POST /v1/oauth/token on oauth2-server (code exchange against Postgres).
+    /// Each request first obtains a fresh code via the consent endpoint;
+    /// only the exchange itself is timed.
+    OauthToken,
+}
+
+// ---
+
+impl Scenario {
+    // ---
+    pub const ALL: [Scenario; 3] = [
+        //
+        Scenario::JwtToken,
+        Scenario::JwtValidate,
+        Scenario::OauthToken,
+    ];
+
+    /// Endpoint label used in the report.
+    pub fn endpoint(self) -> &'static str {
+        // ---
+        match self {
+            Scenario::JwtToken => "POST /v1/auth/token",
+            Scenario::JwtValidate => "POST /v1/auth/validate",
+            Scenario::OauthToken => "POST /v1/oauth/token",
+        }
+    }
+}
+
+// ---
+
+fn parse_seconds(value: &str) -> Result<Duration, String> {
+    // ---
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|secs| secs.is_finite() && *secs > 0.0)
+        .map(Duration::from_secs_f64)
+        .ok_or_else(|| format!("expected a positive number of seconds, got '{value}'"))
+}
@@ -0,0 +1,65 @@
+// tokn-load/src/main.rs
+
+//! tokn-load - Concurrent load generator for the tokn token endpoints
+//!
+//! Drives `/v1/auth/token` and `/v1/auth/validate` on jwt-service and `/v1/oauth/token`
+//! on oauth2-server with a configurable number of concurrent workers, then
+//! reports latency percentiles, throughput, and error rates per endpoint.
+//!
+//! # Example
+//!
+//! ```bash
+//! # 5000 requests per scenario, 64 concurrent workers
+//! cargo run --release -p tokn-load -- -c 64 -n 5000
+//!
+//! # 30-second soak of token validation only
+//! cargo run --release -p tokn-load -- -s jwt-validate -d 30
+//! ```
+
+mod cli;
+mod report;
+mod runner;
+
+use anyhow::Result;
+use clap::Parser;
+use std::sync::Arc;
+
+// ---
+
+use cli::{Args, Scenario};
+
+// ---
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // ---
+    let args = Args::parse();
+    let scenarios = if args.scenarios.is_empty() {
+        Scenario::ALL.to_vec()
+    } else {
+        args.scenarios.clone()
+    };
+
+    let client = runner::http_client(&args)?;
+    let args = Arc::new(args);
+
+    match args.duration {
+        Some(duration) => println!(
+            "tokn-load: {} workers, {:.1}s per scenario\n",
+            args.concurrency,
+            duration.as_secs_f64()
+        ),
+        None => println!(
+            "tokn-load: {} workers, {} requests per scenario\n",
+            args.concurrency, args.requests
+        ),
+    }
+
+    // ---
+    for scenario in scenarios {
+        let report = runner::run(scenario, client.clone(), args.clone()).await?;
+        println!("{report}");
+    }
+
+    Ok(())
+}
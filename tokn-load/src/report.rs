@@ -0,0 +1,142 @@
+// tokn-load/src/report.rs
+
+//! Latency samples, error tallies, and the per-scenario summary
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
+
+// ---
+
+use crate::cli::Scenario;
+
+// ---
+
+/// Raw measurements collected by one worker (merged across workers at the end).
+#[derive(Debug, Default)]
+pub struct Samples {
+    // ---
+    latencies: Vec<Duration>,
+    errors: BTreeMap<String, u64>,
+}
+
+// ---
+
+impl Samples {
+    // ---
+    pub fn record_success(&mut self, latency: Duration) {
+        // ---
+        self.latencies.push(latency);
+    }
+
+    /// Tally a failed request under a short, human-readable reason
+    /// (e.g. `HTTP 503`, `timeout`).
+    pub fn record_error(&mut self, reason: impl Into<String>) {
+        // ---
+        *self.errors.entry(reason.into()).or_default() += 1;
+    }
+
+    pub fn merge(&mut self, other: Samples) {
+        // ---
+        self.latencies.extend(other.latencies);
+        for (reason, count) in other.errors {
+            *self.errors.entry(reason).or_default() += count;
+        }
+    }
+}
+
+// ---
+
+/// Summary of one scenario run.
+///
+/// Latency percentiles are computed over successful requests only; failures
+/// are reported separately so a fast-failing service does not look fast.
+pub struct Report {
+    // ---
+    scenario: Scenario,
+    elapsed: Duration,
+    latencies: Vec<Duration>,
+    errors: BTreeMap<String, u64>,
+}
+
+// ---
+
+impl Report {
+    // ---
+    pub fn new(scenario: Scenario, elapsed: Duration, samples: Samples) -> Self {
+        // ---
+        let mut latencies = samples.latencies;
+        latencies.sort_unstable();
+
+        Self {
+            scenario,
+            elapsed,
+            latencies,
+            errors: samples.errors,
+        }
+    }
+
+    fn successes(&self) -> u64 {
+        // ---
+        self.latencies.len() as u64
+    }
+
+    fn failures(&self) -> u64 {
+        // ---
+        self.errors.values().sum()
+    }
+
+    /// Nearest-rank percentile (`p` in 0..=100) of successful latencies.
+    fn percentile(&self, p: f64) -> Option<Duration> {
+        // ---
+        if self.latencies.is_empty() {
+            return None;
+        }
+        let rank = ((p / 100.0) * self.latencies.len() as f64).ceil() as usize;
+        Some(self.latencies[rank.clamp(1, self.latencies.len()) - 1])
+    }
+}
+
+// ---
+
+impl fmt::Display for Report {
+    // ---
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // ---
+        let total = self.successes() + self.failures();
+        let secs = self.elapsed.as_secs_f64();
+        let error_rate = if total == 0 {
+            0.0
+        } else {
+            self.failures() as f64 * 100.0 / total as f64
+        };
+
+        writeln!(f, "{}", self.scenario.endpoint())?;
+        writeln!(
+            f,
+            "  requests   {total} in {secs:.2}s ({:.1} req/s)",
+            total as f64 / secs.max(f64::EPSILON)
+        )?;
+        writeln!(f, "  errors     {} ({error_rate:.2}%)", self.failures())?;
+        for (reason, count) in &self.errors {
+            writeln!(f, "    {count:>8}  {reason}")?;
+        }
+
+        // ---
+        let Some(max) = self.latencies.last() else {
+            return writeln!(f, "  latency    n/a (no successful requests)");
+        };
+        let ms = |d: Option<Duration>| d.unwrap_or_default().as_secs_f64() * 1000.0;
+        let mean = self.latencies.iter().sum::<Duration>() / self.latencies.len() as u32;
+
+        writeln!(
+            f,
+            "  latency    p50 {:.2}ms  p90 {:.2}ms  p99 {:.2}ms  max {:.2}ms  mean {:.2}ms",
+            ms(self.percentile(50.0)),
+            ms(self.percentile(90.0)),
+            ms(self.percentile(99.0)),
+            ms(Some(*max)),
+            ms(Some(mean)),
+        )
+    }
+}
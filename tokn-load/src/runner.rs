@@ -0,0 +1,272 @@
+// tokn-load/src/runner.rs
+
+//! Worker pool and per-scenario request logic
+
+use anyhow::{bail, Context, Result};
+use reqwest::{header::LOCATION, Client, StatusCode, Url};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// ---
+
+use crate::cli::{Args, Scenario};
+use crate::report::{Report, Samples};
+
+// ---
+
+/// Shared, read-only state handed to every worker.
+struct Target {
+    // ---
+    scenario: Scenario,
+    client: Client,
+    args: Arc<Args>,
+
+    /// Pre-issued access token for [`Scenario::JwtValidate`].
+    access_token: Option<String>,
+}
+
+// ---
+
+/// How long a scenario runs: a fixed request count or a wall-clock deadline.
+enum Budget {
+    // ---
+    Requests(u64),
+    Until(Instant),
+}
+
+// ---
+
+/// Build the HTTP client used for every scenario.
+///
+/// Redirects are not followed: the oauth2 consent endpoint answers with a
+/// redirect carrying the authorization code, which we read from `Location`.
+pub fn http_client(args: &Args) -> Result<Client> {
+    // ---
+    Client::builder()
+        .timeout(args.timeout)
+        .redirect(reqwest::redirect::Policy::none())
+        .pool_max_idle_per_host(args.concurrency)
+        .build()
+        .context("Failed to build HTTP client")
+}
+
+// ---
+
+/// Run one scenario to completion and summarize it.
+///
+/// # Errors
+///
+/// Returns an error if scenario setup fails (e.g. the token used by
+/// `jwt-validate` cannot be issued). Failures of individual load requests are
+/// counted in the report, not returned.
+pub async fn run(scenario: Scenario, client: Client, args: Arc<Args>) -> Result<Report> {
+    // ---
+    let access_token = match scenario {
+        Scenario::JwtValidate => Some(issue_access_token(&client, &args).await?),
+        _ => None,
+    };
+
+    let budget = Arc::new(match args.duration {
+        Some(duration) => Budget::Until(Instant::now() + duration),
+        None => Budget::Requests(args.requests),
+    });
+    let target = Arc::new(Target {
+        scenario,
+        client,
+        args: args.clone(),
+        access_token,
+    });
+    let sequence = Arc::new(AtomicU64::new(0));
+
+    // ---
+    let started = Instant::now();
+    let workers: Vec<_> = (0..args.concurrency.max(1))
+        .map(|_| {
+            let target = target.clone();
+            let budget = budget.clone();
+            let sequence = sequence.clone();
+            tokio::spawn(async move {
+                // ---
+                let mut samples = Samples::default();
+                loop {
+                    let n = sequence.fetch_add(1, Ordering::Relaxed);
+                    let more = match *budget {
+                        Budget::Requests(total) => n < total,
+                        Budget::Until(deadline) => Instant::now() < deadline,
+                    };
+                    if !more {
+                        break;
+                    }
+                    match target.request(n).await {
+                        Ok(latency) => samples.record_success(latency),
+                        Err(reason) => samples.record_error(reason),
+                    }
+                }
+                samples
+            })
+        })
+        .collect();
+
+    let mut samples = Samples::default();
+    for worker in workers {
+        samples.merge(worker.await.context("Load worker panicked")?);
+    }
+
+    Ok(Report::new(scenario, started.elapsed(), samples))
+}
+
+// ---
+
+impl Target {
+    // ---
+    /// Perform request number `n` and return its latency, or a failure reason.
+    async fn request(&self, n: u64) -> Result<Duration, String> {
+        // ---
+        match self.scenario {
+            Scenario::JwtToken => self.jwt_token(n).await,
+            Scenario::JwtValidate => self.jwt_validate().await,
+            Scenario::OauthToken => self.oauth_token().await,
+        }
+    }
+
+    async fn jwt_token(&self, n: u64) -> Result<Duration, String> {
+        // ---
+        let request = self
+            .client
+            .post(format!("{}/v1/auth/token", self.args.jwt_url))
+            .json(&json!({ "user_id": format!("load_user_{n}"), "email": "load@example.com" }));
+
+        let started = Instant::now();
+        let response = request.send().await.map_err(describe)?;
+        let latency = started.elapsed();
+
+        expect_success(response.status())?;
+        Ok(latency)
+    }
+
+    async fn jwt_validate(&self) -> Result<Duration, String> {
+        // ---
+        let request = self
+            .client
+            .post(format!("{}/v1/auth/validate", self.args.jwt_url))
+            .json(&json!({ "token": self.access_token }));
+
+        let started = Instant::now();
+        let response = request.send().await.map_err(describe)?;
+        let latency = started.elapsed();
+
+        expect_success(response.status())?;
+        let body: Value = response.json().await.map_err(describe)?;
+        if body["valid"] != true {
+            return Err("token reported invalid".to_string());
+        }
+        Ok(latency)
+    }
+
+    async fn oauth_token(&self) -> Result<Duration, String> {
+        // ---
+        let args = &self.args;
+        let code = self
+            .authorization_code()
+            .await
+            .map_err(|reason| format!("authorize: {reason}"))?;
+
+        let request = self
+            .client
+            .post(format!("{}/v1/oauth/token", args.oauth2_url))
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", code.as_str()),
+                ("redirect_uri", args.redirect_uri.as_str()),
+                ("client_id", args.client_id.as_str()),
+                ("client_secret", args.client_secret.as_str()),
+            ]);
+
+        let started = Instant::now();
+        let response = request.send().await.map_err(describe)?;
+        let latency = started.elapsed();
+
+        expect_success(response.status())?;
+        Ok(latency)
+    }
+
+    /// Approve the consent form to mint a single-use authorization code (untimed).
+    async fn authorization_code(&self) -> Result<String, String> {
+        // ---
+        let args = &self.args;
+        let response = self
+            .client
+            .post(format!("{}/v1/oauth/authorize", args.oauth2_url))
+            .form(&[
+                ("client_id", args.client_id.as_str()),
+                ("redirect_uri", args.redirect_uri.as_str()),
+                ("scope", "profile"),
+                ("state", "tokn-load"),
+                ("action", "approve"),
+            ])
+            .send()
+            .await
+            .map_err(describe)?;
+
+        let location = response
+            .headers()
+            .get(LOCATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| Url::parse(value).ok())
+            .ok_or_else(|| format!("HTTP {} without redirect", response.status().as_u16()))?;
+
+        location
+            .query_pairs()
+            .find(|(key, _)| key == "code")
+            .map(|(_, code)| code.into_owned())
+            .ok_or_else(|| "redirect without code".to_string())
+    }
+}
+
+// ---
+
+/// Issue the access token the `jwt-validate` scenario validates repeatedly.
+async fn issue_access_token(client: &Client, args: &Args) -> Result<String> {
+    // ---
+    let body: Value = client
+        .post(format!("{}/v1/auth/token", args.jwt_url))
+        .json(&json!({ "user_id": "load_user", "email": "load@example.com" }))
+        .send()
+        .await
+        .with_context(|| format!("Failed to reach jwt-service at {}", args.jwt_url))?
+        .error_for_status()
+        .context("jwt-service rejected setup token request")?
+        .json()
+        .await?;
+
+    match body["access_token"].as_str() {
+        Some(token) => Ok(token.to_string()),
+        None => bail!("jwt-service response has no access_token: {body}"),
+    }
+}
+
+// ---
+
+fn expect_success(status: StatusCode) -> Result<(), String> {
+    // ---
+    if status.is_success() {
+        Ok(())
+    } else {
+        Err(format!("HTTP {}", status.as_u16()))
+    }
+}
+
+fn describe(e: reqwest::Error) -> String {
+    // ---
+    if e.is_timeout() {
+        "timeout".to_string()
+    } else if e.is_connect() {
+        "connection error".to_string()
+    } else if e.is_decode() {
+        "invalid response body".to_string()
+    } else {
+        "transport error".to_string()
+    }
+}
@@ -0,0 +1,108 @@
+// tokn-mail/src/config.rs
+
+use serde::Deserialize;
+use std::fmt;
+use std::path::PathBuf;
+use tokn_config::Secret;
+use tokn_resilience::RetryPolicy;
+
+// ---
+
+/// Where outgoing email is delivered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MailBackend {
+    // ---
+    /// Messages are written to the log instead of being sent (default)
+    #[default]
+    Log,
+
+    /// An SMTP relay; `smtp_url` is `smtp://` (STARTTLS optional),
+    /// `smtp://...?tls=required`, or `smtps://` (implicit TLS)
+    Smtp,
+
+    /// Amazon SES (v2 API); credentials come from the standard AWS chain
+    Ses,
+}
+
+impl fmt::Display for MailBackend {
+    // ---
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // ---
+        f.write_str(match self {
+            MailBackend::Log => "log",
+            MailBackend::Smtp => "smtp",
+            MailBackend::Ses => "ses",
+        })
+    }
+}
+
+// ---
+
+/// Service configuration section for email delivery.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct MailConfig {
+    // ---
+    /// Delivery backend (env `MAIL_BACKEND`: `log`, `smtp`, or `ses`)
+    pub backend: MailBackend,
+
+    /// Sender address, optionally with a display name (env `MAIL_FROM`,
+    /// default `tokn <no-reply@localhost>`)
+    pub from: String,
+
+    /// SMTP relay URL, usually carrying credentials (env `MAIL_SMTP_URL`);
+    /// required for the `smtp` backend
+    pub smtp_url: Option<Secret>,
+
+    /// SES region (env `MAIL_SES_REGION`); the AWS default chain when unset
+    pub ses_region: Option<String>,
+
+    /// Directory of template overrides, `<template>.txt` and
+    /// `<template>.html` (env `MAIL_TEMPLATE_DIR`)
+    pub template_dir: Option<PathBuf>,
+
+    /// Backoff between delivery attempts; permanent rejections are not
+    /// retried (default: 500ms doubling to 5s, giving up after 30s)
+    pub retry: RetryPolicy,
+}
+
+// ---
+
+impl Default for MailConfig {
+    // ---
+    fn default() -> Self {
+        // ---
+        Self {
+            backend: MailBackend::Log,
+            from: "tokn <no-reply@localhost>".to_string(),
+            smtp_url: None,
+            ses_region: None,
+            template_dir: None,
+            retry: RetryPolicy {
+                initial_delay_ms: 500,
+                max_delay_ms: 5_000,
+                max_wait_seconds: 30,
+            },
+        }
+    }
+}
+
+// ---
+
+/// Config rule for the `mail` section: the `smtp` backend needs a relay URL.
+///
+/// # Errors
+///
+/// Returns a message for `tokn_config::ConfigLoader::rule` when `backend` is
+/// `smtp` and `smtp_url` is unset, or when `from` is empty.
+pub fn validate_mail_config(config: &MailConfig) -> Result<(), String> {
+    // ---
+    if config.backend == MailBackend::Smtp && config.smtp_url.is_none() {
+        return Err("backend 'smtp' requires a relay url (MAIL_SMTP_URL)".to_string());
+    }
+    if config.from.trim().is_empty() {
+        return Err("sender address (MAIL_FROM) must not be empty".to_string());
+    }
+    Ok(())
+}
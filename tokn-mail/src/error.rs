@@ -0,0 +1,59 @@
+// tokn-mail/src/error.rs
+
+use std::path::PathBuf;
+
+// ---
+
+use crate::MailBackend;
+
+// ---
+
+/// Errors building, rendering, or delivering an email.
+#[derive(Debug, thiserror::Error)]
+pub enum MailError {
+    // ---
+    /// The `smtp` backend was selected without a relay URL
+    #[error("mail backend 'smtp' requires MAIL_SMTP_URL")]
+    MissingSmtpUrl,
+
+    /// The backend was compiled out (see the crate's `smtp`/`ses` features)
+    #[error("mail backend '{0}' is not enabled in this build")]
+    Unsupported(MailBackend),
+
+    /// A sender or recipient is not a valid email address
+    #[error("invalid email address '{0}'")]
+    InvalidAddress(String),
+
+    /// A template override could not be read or lacks its `Subject:` line
+    #[error("invalid mail template {path}: {message}")]
+    Template { path: PathBuf, message: String },
+
+    /// The provider refused the message; retrying will not help
+    #[error("{backend} rejected the message: {message}")]
+    Rejected {
+        backend: MailBackend,
+        message: String,
+    },
+
+    /// Delivery failed in a way that may succeed later (connection refused,
+    /// throttling, a 4xx SMTP reply)
+    #[error("{backend} error: {message}")]
+    Transport {
+        backend: MailBackend,
+        message: String,
+    },
+}
+
+// ---
+
+impl MailError {
+    // ---
+    /// Whether sending the same message again cannot succeed.
+    ///
+    /// [`Mail::send`](crate::Mail::send) retries only errors for which this
+    /// is `false`.
+    pub fn is_permanent(&self) -> bool {
+        // ---
+        !matches!(self, MailError::Transport { .. })
+    }
+}
@@ -0,0 +1,60 @@
+// tokn-mail/src/lib.rs
+
+//! Email delivery for tokn services
+//!
+//! Features that email users (address verification, password reset, magic
+//! sign-in links, and security notifications) render a [`Template`] and hand
+//! it to a [`Mail`] handle, which sends it through the configured [`Mailer`]
+//! backend:
+//!
+//! - [`LogMailer`]: writes messages to the log (the default, for development)
+//! - [`SmtpMailer`]: any SMTP relay, via lettre (`smtp` feature)
+//! - [`SesMailer`]: Amazon SES (`ses` feature)
+//!
+//! Transport failures are retried with backoff ([`MailConfig::retry`]);
+//! rejections (a bad address, an unverified sender) fail at once. Sends are
+//! counted in `tokn_mail_sent_total` / `tokn_mail_failed_total` by template.
+//!
+//! Operators reword messages by dropping `<template>.txt` and
+//! `<template>.html` files into `MAIL_TEMPLATE_DIR` (see [`Templates`]).
+//!
+//! # Example
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), tokn_mail::MailError> {
+//! use tokn_mail::{Mail, MailConfig, Template};
+//!
+//! let mail = Mail::connect(&MailConfig::default()).await?;
+//! mail.send(
+//!     Template::Verification,
+//!     "ann@example.com",
+//!     &[("link", "https://id.example.com/verify?t=..."), ("expires_in", "24 hours")],
+//! )
+//! .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+mod config;
+mod error;
+mod log;
+mod mailer;
+mod message;
+#[cfg(feature = "ses")]
+mod ses;
+#[cfg(feature = "smtp")]
+mod smtp;
+mod template;
+
+// ---
+
+pub use config::{validate_mail_config, MailBackend, MailConfig};
+pub use error::MailError;
+pub use log::LogMailer;
+pub use mailer::{Mail, Mailer};
+pub use message::Email;
+#[cfg(feature = "ses")]
+pub use ses::SesMailer;
+#[cfg(feature = "smtp")]
+pub use smtp::SmtpMailer;
+pub use template::{Rendered, Template, Templates};
@@ -0,0 +1,30 @@
+// tokn-mail/src/log.rs
+
+use crate::{Email, MailError, Mailer};
+
+// ---
+
+/// Writes each email to the log at `info` instead of sending it.
+///
+/// The development default (`MAIL_BACKEND=log`): verification and reset
+/// links can be copied from the service output. Log redaction still masks
+/// token-shaped values, so query parameters named `token` or `code` show as
+/// `[REDACTED]`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogMailer;
+
+// ---
+
+impl Mailer for LogMailer {
+    // ---
+    async fn send(&self, email: &Email) -> Result<(), MailError> {
+        // ---
+        tracing::info!(
+            to = %email.to,
+            subject = %email.subject,
+            "Email not sent (MAIL_BACKEND=log):\n{}",
+            email.text
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,231 @@
+// tokn-mail/src/mailer.rs
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokn_resilience::RetryPolicy;
+
+// ---
+
+use crate::{Email, LogMailer, MailBackend, MailConfig, MailError, Template, Templates};
+
+// ---
+
+/// A delivery backend that rendered emails are sent through.
+///
+/// Implemented by [`LogMailer`], [`SmtpMailer`](crate::SmtpMailer), and
+/// [`SesMailer`](crate::SesMailer); implement it to deliver elsewhere (or to
+/// capture messages in tests) and hand it to [`Mail::with_mailer`].
+pub trait Mailer: Send + Sync + 'static {
+    // ---
+    /// Deliver one email. Return [`MailError::Transport`] for failures worth
+    /// retrying and [`MailError::Rejected`] for ones that are not.
+    fn send(&self, email: &Email) -> impl Future<Output = Result<(), MailError>> + Send;
+}
+
+// ---
+
+/// Object-safe adapter over [`Mailer`], so [`Mail`] is not generic.
+trait DynMailer: Send + Sync {
+    // ---
+    fn send_boxed<'a>(
+        &'a self,
+        email: &'a Email,
+    ) -> Pin<Box<dyn Future<Output = Result<(), MailError>> + Send + 'a>>;
+}
+
+impl<M: Mailer> DynMailer for M {
+    // ---
+    fn send_boxed<'a>(
+        &'a self,
+        email: &'a Email,
+    ) -> Pin<Box<dyn Future<Output = Result<(), MailError>> + Send + 'a>> {
+        // ---
+        Box::pin(self.send(email))
+    }
+}
+
+// ---
+
+/// Handle for sending templated email; cheap to clone into handler state.
+///
+/// The default handle writes every message to the log.
+#[derive(Clone)]
+pub struct Mail {
+    // ---
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    // ---
+    backend: String,
+    mailer: Box<dyn DynMailer>,
+    from: String,
+    templates: Templates,
+    retry: RetryPolicy,
+}
+
+// ---
+
+impl Default for Mail {
+    // ---
+    fn default() -> Self {
+        // ---
+        Self::with_mailer(
+            LogMailer,
+            MailBackend::Log,
+            &MailConfig::default(),
+            Templates::builtin(),
+        )
+    }
+}
+
+// ---
+
+impl Mail {
+    // ---
+    /// Set up the backend selected by `config`, with the built-in templates
+    /// overridden by any in `config.template_dir`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a template override is invalid, the SMTP URL is
+    /// missing or malformed, or the backend is compiled out.
+    pub async fn connect(config: &MailConfig) -> Result<Self, MailError> {
+        // ---
+        let templates = Templates::load(config.template_dir.as_deref())?;
+
+        match config.backend {
+            MailBackend::Log => Ok(Self::with_mailer(
+                LogMailer,
+                MailBackend::Log,
+                config,
+                templates,
+            )),
+            #[cfg(feature = "smtp")]
+            MailBackend::Smtp => {
+                let url = config.smtp_url.as_ref().ok_or(MailError::MissingSmtpUrl)?;
+                let mailer = crate::SmtpMailer::new(url.expose())?;
+                Ok(Self::with_mailer(
+                    mailer,
+                    MailBackend::Smtp,
+                    config,
+                    templates,
+                ))
+            }
+            #[cfg(feature = "ses")]
+            MailBackend::Ses => {
+                let mailer = crate::SesMailer::connect(config.ses_region.as_deref()).await;
+                Ok(Self::with_mailer(
+                    mailer,
+                    MailBackend::Ses,
+                    config,
+                    templates,
+                ))
+            }
+            #[allow(unreachable_patterns)]
+            backend => Err(MailError::Unsupported(backend)),
+        }
+    }
+
+    // ---
+    /// Send through `mailer`, using the sender and retry policy from `config`.
+    /// `backend` names the mailer in logs and metrics.
+    pub fn with_mailer<M: Mailer>(
+        mailer: M,
+        backend: impl ToString,
+        config: &MailConfig,
+        templates: Templates,
+    ) -> Self {
+        // ---
+        Self {
+            inner: Arc::new(Inner {
+                backend: backend.to_string(),
+                mailer: Box::new(mailer),
+                from: config.from.clone(),
+                templates,
+                retry: config.retry,
+            }),
+        }
+    }
+
+    // ---
+    /// Render `template` with `vars` and send it to `to`.
+    ///
+    /// `to` is added to `vars` as the `to` variable. Transport failures are
+    /// retried with the configured backoff; rejections are not.
+    ///
+    /// # Errors
+    ///
+    /// Returns the permanent error, or the last transient one once the retry
+    /// budget is spent. Either is also logged and counted in
+    /// `tokn_mail_failed_total`.
+    pub async fn send(
+        &self,
+        template: Template,
+        to: &str,
+        vars: &[(&str, &str)],
+    ) -> Result<(), MailError> {
+        // ---
+        let inner = &self.inner;
+        let mut vars = vars.to_vec();
+        vars.push(("to", to));
+        let rendered = inner.templates.render(template, &vars);
+        let email = Email {
+            from: inner.from.clone(),
+            to: to.to_string(),
+            subject: rendered.subject,
+            text: rendered.text,
+            html: rendered.html,
+        };
+
+        let what = format!("{} mail backend", inner.backend);
+        let (mailer, email) = (&inner.mailer, &email);
+        let result = tokn_resilience::retry(&inner.retry, &what, || async move {
+            // Stop retrying on a rejection, but still report it
+            match mailer.send_boxed(email).await {
+                Err(e) if e.is_permanent() => Ok(Err(e)),
+                result => result.map(Ok),
+            }
+        })
+        .await
+        .and_then(|result| result);
+
+        let kind = template.as_str();
+        match &result {
+            Ok(()) => {
+                metrics::counter!("tokn_mail_sent_total", "template" => kind).increment(1);
+            }
+            Err(e) => {
+                metrics::counter!("tokn_mail_failed_total", "template" => kind).increment(1);
+                tracing::warn!("Failed to send {kind} email via {}: {e}", inner.backend);
+            }
+        }
+        result
+    }
+
+    // ---
+    /// Send in the background, for callers that must not wait on delivery
+    /// (e.g. a security notification from a request handler). Failures are
+    /// logged and counted as for [`send`](Self::send).
+    ///
+    /// Must be called within a Tokio runtime.
+    pub fn notify(&self, template: Template, to: &str, vars: &[(&str, &str)]) {
+        // ---
+        let mail = self.clone();
+        let to = to.to_string();
+        let vars: Vec<(String, String)> = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+
+        tokio::spawn(async move {
+            let vars: Vec<(&str, &str)> = vars
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.as_str()))
+                .collect();
+            // Already logged and counted by `send`
+            let _ = mail.send(template, &to, &vars).await;
+        });
+    }
+}
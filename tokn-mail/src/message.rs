@@ -0,0 +1,25 @@
+// tokn-mail/src/message.rs
+
+/// One outgoing email, addressed and rendered.
+///
+/// `from` and `to` are RFC 5322 addresses, optionally with a display name
+/// (`tokn <no-reply@example.com>`); backends reject ones they cannot parse
+/// with [`MailError::InvalidAddress`](crate::MailError::InvalidAddress).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Email {
+    // ---
+    /// Sender, from `MAIL_FROM`
+    pub from: String,
+
+    /// Single recipient
+    pub to: String,
+
+    /// Subject line (a single line)
+    pub subject: String,
+
+    /// Plain-text body, always sent
+    pub text: String,
+
+    /// HTML alternative to `text`, when the template has one
+    pub html: Option<String>,
+}
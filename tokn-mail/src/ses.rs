@@ -0,0 +1,124 @@
+// tokn-mail/src/ses.rs
+
+use aws_config::{BehaviorVersion, Region};
+use aws_sdk_sesv2::error::{DisplayErrorContext, SdkError};
+use aws_sdk_sesv2::operation::send_email::SendEmailError;
+use aws_sdk_sesv2::types::{Body, Content, Destination, EmailContent, Message};
+use aws_sdk_sesv2::Client;
+
+// ---
+
+use crate::{Email, MailBackend, MailError, Mailer};
+
+// ---
+
+/// Sends email through the Amazon SES v2 `SendEmail` API.
+///
+/// Credentials come from the standard AWS chain (environment, profile,
+/// instance or task role); the sender must be a verified SES identity.
+pub struct SesMailer {
+    // ---
+    client: Client,
+}
+
+// ---
+
+impl SesMailer {
+    // ---
+    /// Load AWS configuration, using `region` when given instead of the
+    /// chain's default.
+    pub async fn connect(region: Option<&str>) -> Self {
+        // ---
+        let mut loader = aws_config::defaults(BehaviorVersion::latest());
+        if let Some(region) = region {
+            loader = loader.region(Region::new(region.to_string()));
+        }
+        let sdk_config = loader.load().await;
+
+        Self {
+            client: Client::new(&sdk_config),
+        }
+    }
+}
+
+// ---
+
+impl Mailer for SesMailer {
+    // ---
+    async fn send(&self, email: &Email) -> Result<(), MailError> {
+        // ---
+        self.client
+            .send_email()
+            .from_email_address(&email.from)
+            .destination(Destination::builder().to_addresses(&email.to).build())
+            .content(
+                EmailContent::builder()
+                    .simple(build_message(email)?)
+                    .build(),
+            )
+            .send()
+            .await
+            .map_err(send_error)?;
+
+        Ok(())
+    }
+}
+
+// ---
+
+fn build_message(email: &Email) -> Result<Message, MailError> {
+    // ---
+    let content = |data: &str| Content::builder().data(data).charset("UTF-8").build();
+    let body = Body::builder()
+        .text(content(&email.text).map_err(build_error)?)
+        .set_html(
+            email
+                .html
+                .as_deref()
+                .map(content)
+                .transpose()
+                .map_err(build_error)?,
+        )
+        .build();
+
+    Message::builder()
+        .subject(content(&email.subject).map_err(build_error)?)
+        .body(body)
+        .build()
+        .map_err(build_error)
+}
+
+fn build_error(e: aws_sdk_sesv2::error::BuildError) -> MailError {
+    // ---
+    MailError::Rejected {
+        backend: MailBackend::Ses,
+        message: e.to_string(),
+    }
+}
+
+/// Rejections of the message or the account are permanent; throttling,
+/// network, and service errors are retried.
+fn send_error(e: SdkError<SendEmailError>) -> MailError {
+    // ---
+    let permanent = e.as_service_error().is_some_and(|e| {
+        e.is_message_rejected()
+            || e.is_mail_from_domain_not_verified_exception()
+            || e.is_account_suspended_exception()
+            || e.is_sending_paused_exception()
+            || e.is_bad_request_exception()
+            || e.is_not_found_exception()
+    });
+    let message = DisplayErrorContext(&e).to_string();
+
+    if permanent {
+        MailError::Rejected {
+            backend: MailBackend::Ses,
+            message,
+        }
+    } else {
+        MailError::Transport {
+            backend: MailBackend::Ses,
+            message,
+        }
+    }
+}
@@ -0,0 +1,114 @@
+// tokn-portal/src/config.rs
+
+use serde::de::{self, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use std::collections::BTreeMap;
+use std::fmt;
+
+// ---
+
+use crate::spec::SERVICES;
+
+// ---
+
+/// Service configuration section for the developer portal.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct PortalConfig {
+    // ---
+    /// Serve the portal under `/docs` (env `PORTAL_ENABLED`, default: true)
+    pub enabled: bool,
+
+    /// Public base URL of each service, replacing the local development
+    /// addresses in the specs
+    /// (env `PORTAL_SERVERS`, e.g. `jwt-service=https://jwt.example.com`)
+    pub servers: ServiceUrls,
+}
+
+impl Default for PortalConfig {
+    // ---
+    fn default() -> Self {
+        // ---
+        Self {
+            enabled: true,
+            servers: ServiceUrls::default(),
+        }
+    }
+}
+
+// ---
+
+/// Base URL by service name.
+///
+/// Deserializes from a table (`[portal.servers]` in a config file) or from a
+/// comma-separated `service=url` list (the `PORTAL_SERVERS` variable).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ServiceUrls(pub BTreeMap<String, String>);
+
+impl<'de> Deserialize<'de> for ServiceUrls {
+    // ---
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // ---
+        deserializer.deserialize_any(ServiceUrlsVisitor)
+    }
+}
+
+struct ServiceUrlsVisitor;
+
+impl<'de> Visitor<'de> for ServiceUrlsVisitor {
+    // ---
+    type Value = ServiceUrls;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // ---
+        f.write_str("a table or a comma-separated list of service=url pairs")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        // ---
+        let mut servers = BTreeMap::new();
+        for pair in value.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (service, url) = pair
+                .split_once('=')
+                .ok_or_else(|| E::custom(format!("expected service=url, got '{pair}'")))?;
+            servers.insert(service.trim().to_string(), url.trim().to_string());
+        }
+        Ok(ServiceUrls(servers))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        // ---
+        let mut servers = BTreeMap::new();
+        while let Some((service, url)) = map.next_entry::<String, String>()? {
+            servers.insert(service, url);
+        }
+        Ok(ServiceUrls(servers))
+    }
+}
+
+// ---
+
+/// Config rule for the `portal` section: server overrides must name a tokn
+/// service and give an `http` or `https` URL.
+///
+/// # Errors
+///
+/// Returns a message for `tokn_config::ConfigLoader::rule` naming the first
+/// invalid override.
+pub fn validate_portal_config(config: &PortalConfig) -> Result<(), String> {
+    // ---
+    for (service, url) in &config.servers.0 {
+        if !SERVICES.contains(&service.as_str()) {
+            return Err(format!(
+                "PORTAL_SERVERS names unknown service '{service}' (expected one of: {})",
+                SERVICES.join(", ")
+            ));
+        }
+        if !(url.starts_with("http://") || url.starts_with("https://")) {
+            return Err(format!(
+                "PORTAL_SERVERS URL for {service} must start with http:// or https://"
+            ));
+        }
+    }
+    Ok(())
+}
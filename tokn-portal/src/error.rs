@@ -0,0 +1,28 @@
+// tokn-portal/src/error.rs
+
+// ---
+
+/// Errors building the developer portal from the service specs.
+#[derive(Debug, thiserror::Error)]
+pub enum PortalError {
+    // ---
+    /// A spec is not JSON or lacks a required OpenAPI field
+    #[error("invalid OpenAPI spec '{spec}': {reason}")]
+    InvalidSpec { spec: String, reason: String },
+
+    /// Two specs define the same path, or the same component differently
+    #[error("specs conflict on {what} '{name}'")]
+    Conflict { what: &'static str, name: String },
+
+    /// An operation states no auth requirement, not even `[]` for public
+    #[error("{method} {path} in spec '{spec}' does not declare its security")]
+    MissingSecurity {
+        spec: String,
+        method: String,
+        path: String,
+    },
+
+    /// A server URL override names no service in the specs
+    #[error("unknown service '{0}' in PORTAL_SERVERS")]
+    UnknownService(String),
+}
@@ -0,0 +1,35 @@
+// tokn-portal/src/lib.rs
+
+//! Developer portal for the tokn platform
+//!
+//! Each service's HTTP API is described by an OpenAPI 3.1 spec under
+//! `tokn-portal/specs/`, compiled into the binary. [`merge`] combines them
+//! into one document for the whole platform, and [`portal_router`] serves
+//! it under `/docs` as a Swagger UI page that can switch between the
+//! platform and each service.
+//!
+//! Every operation states what it requires: `security: []` for public
+//! endpoints, or one of the schemes below. A spec that leaves an operation
+//! unstated fails the merge, so a new endpoint cannot be documented without
+//! saying how it is protected.
+//!
+//! | Scheme             | Credential                                            |
+//! |--------------------|-------------------------------------------------------|
+//! | `jwtBearer`        | jwt-service access token (`POST /v1/auth/token`)      |
+//! | `oauth2`           | oauth2-server access token, with the listed scopes    |
+//! | `adminToken`       | the service's `ADMIN_TOKEN`                           |
+//! | `serviceSignature` | a request signed by another tokn service              |
+//!
+//! When editing a service's routes, update its spec in the same change.
+
+mod config;
+mod error;
+mod portal;
+mod spec;
+
+// ---
+
+pub use config::{validate_portal_config, PortalConfig, ServiceUrls};
+pub use error::PortalError;
+pub use portal::portal_router;
+pub use spec::{builtin_specs, merge, Spec, PLATFORM_TITLE, SERVICES};
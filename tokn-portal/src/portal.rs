@@ -0,0 +1,98 @@
+// tokn-portal/src/portal.rs
+
+use axum::{
+    body::Bytes,
+    http::header,
+    response::{Html, IntoResponse},
+    routing::{get, MethodRouter},
+    Router,
+};
+use serde_json::{json, Value};
+
+// ---
+
+use crate::spec::{builtin_specs, merge, PLATFORM_TITLE};
+use crate::{PortalConfig, PortalError};
+
+// ---
+
+/// Swagger UI page, with `{{URLS}}` and `{{PRIMARY}}` to fill in.
+const PAGE: &str = include_str!("portal.html");
+
+/// `Content-Security-Policy` of the page: Swagger UI comes from unpkg and is
+/// started by an inline script, and "Try it out" calls each service's URL.
+const PAGE_CSP: &str = "default-src 'self'; \
+                        script-src 'self' 'unsafe-inline' https://unpkg.com; \
+                        style-src 'self' 'unsafe-inline' https://unpkg.com; \
+                        img-src 'self' data: https://unpkg.com; connect-src *; \
+                        frame-ancestors 'none'; object-src 'none'; base-uri 'none'";
+
+// ---
+
+/// Build the developer portal routes, or an empty router when
+/// `config.enabled` is false:
+///
+/// - `GET /docs`: Swagger UI, with a selector between the merged platform
+///   spec (shown first) and each service's own spec
+/// - `GET /docs/openapi.json`: the merged spec
+/// - `GET /docs/specs/<name>.json`: one service's spec, e.g. `jwt-service`
+///
+/// The specs are merged once, here, with `config.servers` applied, so a
+/// broken spec stops the service at startup rather than on first view.
+///
+/// # Errors
+///
+/// - [`PortalError::UnknownService`] when `config.servers` names a service
+///   no spec describes
+/// - any error from [`merge`]
+pub fn portal_router(config: &PortalConfig) -> Result<Router, PortalError> {
+    // ---
+    if !config.enabled {
+        return Ok(Router::new());
+    }
+
+    let mut specs = builtin_specs()?;
+    let unknown = config.servers.0.keys().find(|service| {
+        !specs
+            .iter()
+            .any(|spec| spec.services().any(|s| s == *service))
+    });
+    if let Some(service) = unknown {
+        return Err(PortalError::UnknownService(service.clone()));
+    }
+    for spec in &mut specs {
+        spec.set_server_urls(&config.servers);
+    }
+    let merged = merge(&specs)?;
+
+    // ---
+    let mut urls = vec![json!({ "name": PLATFORM_TITLE, "url": "/docs/openapi.json" })];
+    let mut router = Router::new().route("/docs/openapi.json", json_route(&merged));
+    for spec in &specs {
+        let url = format!("/docs/specs/{}.json", spec.name);
+        urls.push(json!({ "name": spec.name, "url": url }));
+        router = router.route(&url, json_route(&spec.document));
+    }
+
+    let page = PAGE
+        .replace("{{URLS}}", &Value::Array(urls).to_string())
+        .replace("{{PRIMARY}}", &Value::from(PLATFORM_TITLE).to_string());
+    tracing::info!(
+        "Serving the developer portal at /docs ({} specs)",
+        specs.len()
+    );
+
+    Ok(router.route(
+        "/docs",
+        get(move || async move { ([(header::CONTENT_SECURITY_POLICY, PAGE_CSP)], Html(page)) }),
+    ))
+}
+
+// ---
+
+/// A GET route answering with `document`, serialized once.
+fn json_route(document: &Value) -> MethodRouter {
+    // ---
+    let body = Bytes::from(document.to_string());
+    get(move || async move { ([(header::CONTENT_TYPE, "application/json")], body).into_response() })
+}
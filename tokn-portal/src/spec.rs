@@ -0,0 +1,227 @@
+// tokn-portal/src/spec.rs
+
+use serde_json::{json, Map, Value};
+
+// ---
+
+use crate::{PortalError, ServiceUrls};
+
+// ---
+
+/// Services whose server URLs can be overridden, as named in the specs'
+/// `servers[].description`.
+pub const SERVICES: [&str; 3] = ["jwt-service", "oauth2-server", "oauth2-client"];
+
+/// Title of the merged spec, which the portal selects first.
+pub const PLATFORM_TITLE: &str = "tokn platform";
+
+/// HTTP methods that hold an operation in an OpenAPI path item.
+const METHODS: [&str; 8] = [
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// Specs compiled into the binary, by name.
+const BUILTIN: [(&str, &str); 4] = [
+    ("jwt-service", include_str!("../specs/jwt-service.json")),
+    ("oauth2-server", include_str!("../specs/oauth2-server.json")),
+    ("oauth2-client", include_str!("../specs/oauth2-client.json")),
+    ("admin", include_str!("../specs/admin.json")),
+];
+
+// ---
+
+/// One OpenAPI 3.1 document, such as a single service's API.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spec {
+    // ---
+    /// Short name, used in the portal's spec URL (`/docs/specs/<name>.json`)
+    pub name: String,
+
+    /// The OpenAPI document
+    pub document: Value,
+}
+
+impl Spec {
+    // ---
+    /// Parse the OpenAPI JSON document `json` as the spec `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PortalError::InvalidSpec`] when `json` is not a JSON object
+    /// with `openapi`, `info`, and `paths`.
+    pub fn parse(name: &str, json: &str) -> Result<Self, PortalError> {
+        // ---
+        let invalid = |reason: String| PortalError::InvalidSpec {
+            spec: name.to_string(),
+            reason,
+        };
+
+        let document: Value = serde_json::from_str(json).map_err(|e| invalid(e.to_string()))?;
+        for field in ["openapi", "info", "paths"] {
+            if document.get(field).is_none() {
+                return Err(invalid(format!("missing '{field}'")));
+            }
+        }
+        Ok(Self {
+            name: name.to_string(),
+            document,
+        })
+    }
+
+    /// Services this spec's servers are described as.
+    pub fn services(&self) -> impl Iterator<Item = &str> {
+        // ---
+        let servers = self.document.get("servers").and_then(Value::as_array);
+        servers
+            .into_iter()
+            .flatten()
+            .filter_map(|server| server.get("description")?.as_str())
+    }
+
+    /// Point each server described as a service in `urls` at that
+    /// service's URL.
+    pub fn set_server_urls(&mut self, urls: &ServiceUrls) {
+        // ---
+        let servers = self
+            .document
+            .get_mut("servers")
+            .and_then(Value::as_array_mut);
+        for server in servers.into_iter().flatten() {
+            let service = server.get("description").and_then(Value::as_str);
+            if let Some(url) = service.and_then(|service| urls.0.get(service)) {
+                server["url"] = Value::String(url.clone());
+            }
+        }
+    }
+}
+
+// ---
+
+/// The specs for every tokn service, plus the admin endpoints they share.
+///
+/// # Errors
+///
+/// Returns [`PortalError::InvalidSpec`] if a compiled-in spec is malformed.
+pub fn builtin_specs() -> Result<Vec<Spec>, PortalError> {
+    // ---
+    BUILTIN
+        .iter()
+        .map(|(name, json)| Spec::parse(name, json))
+        .collect()
+}
+
+/// Merge `specs` into one OpenAPI 3.1 document describing the platform.
+///
+/// Each spec's `servers` move onto its path items, so every operation
+/// still targets the service that serves it, and each spec's top-level
+/// `security` moves onto the operations that do not set their own. Tags,
+/// servers, and components are combined.
+///
+/// # Errors
+///
+/// - [`PortalError::Conflict`] when two specs define the same path, or the
+///   same component name with different contents
+/// - [`PortalError::MissingSecurity`] when an operation has no `security`,
+///   either its own or its spec's; public operations declare `[]`
+pub fn merge(specs: &[Spec]) -> Result<Value, PortalError> {
+    // ---
+    let mut paths = Map::new();
+    let mut components = Map::new();
+    let mut tags: Vec<Value> = Vec::new();
+    let mut servers: Vec<Value> = Vec::new();
+
+    for spec in specs {
+        // ---
+        let document = &spec.document;
+        let spec_servers = document.get("servers").cloned();
+        let spec_security = document.get("security");
+
+        let Some(spec_paths) = document["paths"].as_object() else {
+            return Err(PortalError::InvalidSpec {
+                spec: spec.name.clone(),
+                reason: "'paths' is not an object".to_string(),
+            });
+        };
+        for (path, item) in spec_paths {
+            // ---
+            if paths.contains_key(path) {
+                return Err(PortalError::Conflict {
+                    what: "path",
+                    name: path.clone(),
+                });
+            }
+
+            let mut item = item.clone();
+            if let (Some(servers), None) = (&spec_servers, item.get("servers")) {
+                item["servers"] = servers.clone();
+            }
+            for method in METHODS {
+                let Some(operation) = item.get_mut(method) else {
+                    continue;
+                };
+                if operation.get("security").is_some() {
+                    continue;
+                }
+                match spec_security {
+                    Some(security) => operation["security"] = security.clone(),
+                    None => {
+                        return Err(PortalError::MissingSecurity {
+                            spec: spec.name.clone(),
+                            method: method.to_uppercase(),
+                            path: path.clone(),
+                        })
+                    }
+                }
+            }
+            paths.insert(path.clone(), item);
+        }
+
+        // ---
+        let spec_components = document.get("components").and_then(Value::as_object);
+        for (section, entries) in spec_components.into_iter().flatten() {
+            let merged = components
+                .entry(section.clone())
+                .or_insert_with(|| Value::Object(Map::new()));
+            for (name, entry) in entries.as_object().into_iter().flatten() {
+                match merged.get(name) {
+                    Some(existing) if existing != entry => {
+                        return Err(PortalError::Conflict {
+                            what: "component",
+                            name: format!("{section}/{name}"),
+                        })
+                    }
+                    Some(_) => {}
+                    None => merged[name] = entry.clone(),
+                }
+            }
+        }
+
+        // ---
+        let spec_tags = document.get("tags").and_then(Value::as_array);
+        for tag in spec_tags.into_iter().flatten() {
+            if !tags.iter().any(|t| t["name"] == tag["name"]) {
+                tags.push(tag.clone());
+            }
+        }
+        let spec_servers = spec_servers.as_ref().and_then(Value::as_array);
+        for server in spec_servers.into_iter().flatten() {
+            if !servers.contains(server) {
+                servers.push(server.clone());
+            }
+        }
+    }
+
+    Ok(json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": PLATFORM_TITLE,
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "Every tokn service in one document. Each operation lists \
+                the server that serves it and the credentials it requires.",
+        },
+        "servers": servers,
+        "tags": tags,
+        "paths": paths,
+        "components": components,
+    }))
+}
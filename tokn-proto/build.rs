@@ -0,0 +1,17 @@
+// tokn-proto/build.rs
+
+//! Generates the gRPC code from `proto/` with a vendored `protoc`, so building
+//! does not require protobuf tooling on the host.
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // ---
+    let protoc = protoc_bin_vendored::protoc_bin_path()?;
+    std::env::set_var("PROTOC", protoc);
+
+    tonic_prost_build::configure().compile_protos(
+        &["proto/tokn/introspection/v1/introspection.proto"],
+        &["proto"],
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,30 @@
+// tokn-proto/examples/introspect.rs
+
+//! Introspect a token against a tokn gRPC endpoint.
+//!
+//! ```bash
+//! cargo run -p tokn-proto --example introspect -- http://127.0.0.1:50083 "$TOKEN"
+//! ```
+
+use tokn_proto::{IntrospectRequest, TokenIntrospectionClient};
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let mut args = std::env::args().skip(1);
+    let (Some(endpoint), Some(token)) = (args.next(), args.next()) else {
+        eprintln!("usage: introspect <endpoint> <token>");
+        std::process::exit(2);
+    };
+
+    let mut client = TokenIntrospectionClient::connect(endpoint).await?;
+    let response = client
+        .introspect(IntrospectRequest {
+            token,
+            ..Default::default()
+        })
+        .await?
+        .into_inner();
+
+    println!("{response:#?}");
+    Ok(())
+}
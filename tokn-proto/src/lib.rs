@@ -0,0 +1,59 @@
+// tokn-proto/src/lib.rs
+
+//! gRPC contracts shared across tokn services
+//!
+//! `tokn.introspection.v1.TokenIntrospection` lets internal resource services
+//! validate any tokn token through one RPC. jwt-service answers for JWT access
+//! tokens and oauth2-server for opaque OAuth2 access tokens; each reports
+//! `active: false` for tokens it did not issue. Both serve it when their
+//! `*_GRPC_ADDR` is set.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # async fn example() -> anyhow::Result<()> {
+//! use tokn_proto::{IntrospectRequest, TokenIntrospectionClient};
+//!
+//! let mut client = TokenIntrospectionClient::connect("http://127.0.0.1:50083").await?;
+//! let response = client
+//!     .introspect(IntrospectRequest {
+//!         token: "eyJhbGciOiJIUzI1NiJ9...".into(),
+//!         ..Default::default()
+//!     })
+//!     .await?
+//!     .into_inner();
+//!
+//! if response.active {
+//!     println!("token for {:?}", response.sub);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+/// Generated code for `tokn.introspection.v1`.
+pub mod introspection {
+    // ---
+    pub mod v1 {
+        // ---
+        tonic::include_proto!("tokn.introspection.v1");
+    }
+}
+
+// ---
+
+pub use introspection::v1::token_introspection_client::TokenIntrospectionClient;
+pub use introspection::v1::token_introspection_server::{
+    TokenIntrospection, TokenIntrospectionServer,
+};
+pub use introspection::v1::{IntrospectRequest, IntrospectResponse};
+
+// ---
+
+impl IntrospectResponse {
+    // ---
+    /// Response for a token that is unknown, expired, or revoked.
+    pub fn inactive() -> Self {
+        // ---
+        Self::default()
+    }
+}
@@ -0,0 +1,197 @@
+// tokn-python/src/lib.rs
+
+//! tokn - Python bindings for tokn-core's token rules
+//!
+//! Python services validate jwt-service tokens with the exact code the Rust
+//! services use (HS256 only, `exp` checked with 60 seconds of leeway):
+//!
+//! - `validate_token(token, secret)` returns the `Claims` or raises a
+//!   `TokenError` subclass saying why the token was rejected
+//! - `inspect_claims(token)` reads claims without verifying them, for
+//!   logging and debugging only
+//! - `generate_token(sub, email, secret, expiry_seconds=900)` mints tokens
+//!   for development and tests; production tokens come from jwt-service
+//!
+//! Revocation is not checked: that needs jwt-service's Redis blacklist.
+
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use tokn_core::SystemClock;
+
+// ---
+
+create_exception!(tokn, TokenError, PyException, "A token was rejected.");
+create_exception!(
+    tokn,
+    ExpiredTokenError,
+    TokenError,
+    "The token has expired."
+);
+create_exception!(
+    tokn,
+    InvalidSignatureError,
+    TokenError,
+    "The signature does not match (tampered token or wrong secret)."
+);
+create_exception!(
+    tokn,
+    InvalidAlgorithmError,
+    TokenError,
+    "The token is not signed with HS256."
+);
+create_exception!(
+    tokn,
+    MalformedTokenError,
+    TokenError,
+    "The token could not be parsed."
+);
+
+/// The Python exception for `error`, with tokn-core's message.
+fn py_err(error: tokn_core::TokenError) -> PyErr {
+    // ---
+    let message = error.to_string();
+    match error {
+        tokn_core::TokenError::Expired => ExpiredTokenError::new_err(message),
+        tokn_core::TokenError::InvalidSignature => InvalidSignatureError::new_err(message),
+        tokn_core::TokenError::InvalidAlgorithm => InvalidAlgorithmError::new_err(message),
+        tokn_core::TokenError::Malformed => MalformedTokenError::new_err(message),
+        _ => TokenError::new_err(message),
+    }
+}
+
+// ---
+
+/// The claims of a jwt-service token.
+#[pyclass(frozen, module = "tokn", name = "Claims")]
+struct Claims {
+    // ---
+    /// Subject (user ID)
+    #[pyo3(get)]
+    sub: String,
+    /// User email
+    #[pyo3(get)]
+    email: String,
+    /// Expiry, Unix seconds
+    #[pyo3(get)]
+    exp: usize,
+    /// Issued at, Unix seconds
+    #[pyo3(get)]
+    iat: usize,
+    /// Token ID, the key jwt-service revokes by
+    #[pyo3(get)]
+    jti: String,
+}
+
+impl From<tokn_core::Claims> for Claims {
+    // ---
+    fn from(claims: tokn_core::Claims) -> Self {
+        // ---
+        Self {
+            sub: claims.sub,
+            email: claims.email,
+            exp: claims.exp,
+            iat: claims.iat,
+            jti: claims.jti,
+        }
+    }
+}
+
+#[pymethods]
+impl Claims {
+    // ---
+    /// The claims as a dict, as they appear in the token payload.
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        // ---
+        let dict = PyDict::new(py);
+        dict.set_item("sub", &self.sub)?;
+        dict.set_item("email", &self.email)?;
+        dict.set_item("exp", self.exp)?;
+        dict.set_item("iat", self.iat)?;
+        dict.set_item("jti", &self.jti)?;
+        Ok(dict)
+    }
+
+    fn __repr__(&self) -> String {
+        // ---
+        format!(
+            "Claims(sub={:?}, email={:?}, exp={}, iat={}, jti={:?})",
+            self.sub, self.email, self.exp, self.iat, self.jti
+        )
+    }
+}
+
+// ---
+
+/// Validate `token`, signed with `secret` (jwt-service's `JWT_SECRET`), and
+/// return its claims.
+///
+/// Raises a `TokenError` subclass if the token is expired, tampered with,
+/// not HS256, or malformed.
+#[pyfunction]
+fn validate_token(token: &str, secret: &str) -> PyResult<Claims> {
+    // ---
+    tokn_core::validate_token(token, secret, &SystemClock)
+        .map(Claims::from)
+        .map_err(py_err)
+}
+
+/// Read `token`'s claims WITHOUT checking its signature or expiry.
+///
+/// Anyone can forge what this returns: use it to log or display a token,
+/// never to decide access. Raises `MalformedTokenError` if the token does
+/// not parse.
+#[pyfunction]
+fn inspect_claims(token: &str) -> PyResult<Claims> {
+    // ---
+    let mut validation = Validation::new(Algorithm::HS256);
+    validation.insecure_disable_signature_validation();
+    validation.validate_exp = false;
+
+    jsonwebtoken::decode::<tokn_core::Claims>(token, &DecodingKey::from_secret(&[]), &validation)
+        .map(|data| Claims::from(data.claims))
+        .map_err(|_| MalformedTokenError::new_err("Malformed token"))
+}
+
+/// Mint an HS256 token for `sub`, valid for `expiry_seconds`, as
+/// jwt-service would. For development and tests only.
+#[pyfunction]
+#[pyo3(signature = (sub, email, secret, expiry_seconds = 900))]
+fn generate_token(
+    sub: String,
+    email: String,
+    secret: &str,
+    expiry_seconds: i64,
+) -> PyResult<String> {
+    // ---
+    let claims = tokn_core::Claims::new(sub, email, expiry_seconds, &SystemClock);
+    tokn_core::generate_token(&claims, secret).map_err(py_err)
+}
+
+// ---
+
+#[pymodule]
+fn tokn(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    // ---
+    let py = m.py();
+
+    m.add_class::<Claims>()?;
+    m.add_function(wrap_pyfunction!(validate_token, m)?)?;
+    m.add_function(wrap_pyfunction!(inspect_claims, m)?)?;
+    m.add_function(wrap_pyfunction!(generate_token, m)?)?;
+
+    m.add("TokenError", py.get_type::<TokenError>())?;
+    m.add("ExpiredTokenError", py.get_type::<ExpiredTokenError>())?;
+    m.add(
+        "InvalidSignatureError",
+        py.get_type::<InvalidSignatureError>(),
+    )?;
+    m.add(
+        "InvalidAlgorithmError",
+        py.get_type::<InvalidAlgorithmError>(),
+    )?;
+    m.add("MalformedTokenError", py.get_type::<MalformedTokenError>())?;
+    Ok(())
+}
@@ -0,0 +1,49 @@
+# tokn-python/tests/test_tokn.py
+#
+# Run after `maturin develop`: pytest tokn-python/tests
+
+import pytest
+
+import tokn
+
+SECRET = "python-test-secret-at-least-32-characters"
+
+
+def test_validate_returns_claims():
+    token = tokn.generate_token("user_1", "u@example.com", SECRET)
+
+    claims = tokn.validate_token(token, SECRET)
+
+    assert claims.sub == "user_1"
+    assert claims.email == "u@example.com"
+    assert claims.exp - claims.iat == 900
+    assert claims.to_dict()["jti"] == claims.jti
+
+
+def test_rejections_raise_specific_errors():
+    token = tokn.generate_token("user_1", "u@example.com", SECRET)
+
+    with pytest.raises(tokn.InvalidSignatureError):
+        tokn.validate_token(token, "some-other-secret-at-least-32-characters")
+    with pytest.raises(tokn.MalformedTokenError):
+        tokn.validate_token("not.a.jwt", SECRET)
+
+    # 60 seconds of leeway past `exp`, as in the Rust services
+    expired = tokn.generate_token("user_1", "u@example.com", SECRET, expiry_seconds=-61)
+    with pytest.raises(tokn.ExpiredTokenError, match="expired"):
+        tokn.validate_token(expired, SECRET)
+    within_leeway = tokn.generate_token("user_1", "u@example.com", SECRET, expiry_seconds=-30)
+    tokn.validate_token(within_leeway, SECRET)
+
+
+def test_errors_share_a_base_class():
+    assert issubclass(tokn.ExpiredTokenError, tokn.TokenError)
+    assert issubclass(tokn.MalformedTokenError, tokn.TokenError)
+
+
+def test_inspect_skips_verification():
+    expired = tokn.generate_token("user_1", "u@example.com", SECRET, expiry_seconds=-3600)
+
+    assert tokn.inspect_claims(expired).sub == "user_1"
+    with pytest.raises(tokn.MalformedTokenError):
+        tokn.inspect_claims("garbage")
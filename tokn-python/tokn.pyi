@@ -0,0 +1,38 @@
+# tokn-python/tokn.pyi
+#
+# Type stubs for the `tokn` extension module (src/lib.rs).
+
+class Claims:
+    """The claims of a jwt-service token."""
+
+    sub: str
+    email: str
+    exp: int
+    iat: int
+    jti: str
+
+    def to_dict(self) -> dict[str, str | int]: ...
+
+class TokenError(Exception):
+    """A token was rejected."""
+
+class ExpiredTokenError(TokenError):
+    """The token has expired."""
+
+class InvalidSignatureError(TokenError):
+    """The signature does not match (tampered token or wrong secret)."""
+
+class InvalidAlgorithmError(TokenError):
+    """The token is not signed with HS256."""
+
+class MalformedTokenError(TokenError):
+    """The token could not be parsed."""
+
+def validate_token(token: str, secret: str) -> Claims:
+    """Validate `token`, signed with `secret`, and return its claims."""
+
+def inspect_claims(token: str) -> Claims:
+    """Read `token`'s claims WITHOUT checking its signature or expiry."""
+
+def generate_token(sub: str, email: str, secret: str, expiry_seconds: int = 900) -> str:
+    """Mint an HS256 token as jwt-service would. Development and tests only."""
@@ -0,0 +1,176 @@
+// tokn-ratelimit/src/config.rs
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+
+// ---
+
+/// How requests are counted against the limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Algorithm {
+    // ---
+    /// At most `requests` in any rolling `window_seconds` (default)
+    #[default]
+    SlidingWindow,
+
+    /// Bursts of up to `burst` requests, refilled at `requests` per
+    /// `window_seconds`
+    TokenBucket,
+}
+
+impl fmt::Display for Algorithm {
+    // ---
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // ---
+        f.write_str(match self {
+            Algorithm::SlidingWindow => "sliding_window",
+            Algorithm::TokenBucket => "token_bucket",
+        })
+    }
+}
+
+// ---
+
+/// What a request is counted against.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyBy {
+    // ---
+    /// The client's IP address (default)
+    #[default]
+    Ip,
+
+    /// The authenticated user, or the IP address for anonymous requests
+    User,
+
+    /// Both: a request must fit the IP address's limit and the user's
+    IpAndUser,
+}
+
+impl fmt::Display for KeyBy {
+    // ---
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // ---
+        f.write_str(match self {
+            KeyBy::Ip => "ip",
+            KeyBy::User => "user",
+            KeyBy::IpAndUser => "ip_and_user",
+        })
+    }
+}
+
+// ---
+
+/// Service configuration section for request rate limiting.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    // ---
+    /// Limit requests per client (env `RATE_LIMIT_ENABLED`, default: false)
+    pub enabled: bool,
+
+    /// Counting algorithm (env `RATE_LIMIT_ALGORITHM`: `sliding_window` or
+    /// `token_bucket`)
+    pub algorithm: Algorithm,
+
+    /// Requests allowed per window (env `RATE_LIMIT_REQUESTS`, default: 100)
+    pub requests: u32,
+
+    /// Window length in seconds (env `RATE_LIMIT_WINDOW_SECONDS`, default: 60)
+    pub window_seconds: u64,
+
+    /// Token bucket capacity (env `RATE_LIMIT_BURST`, default: `requests`)
+    pub burst: Option<u32>,
+
+    /// Identify clients by the last `X-Forwarded-For` address instead of the
+    /// peer address; only behind a proxy that sets it
+    /// (env `RATE_LIMIT_TRUST_FORWARDED_FOR`, default: false)
+    pub trust_forwarded_for: bool,
+
+    /// What requests are counted against (env `RATE_LIMIT_KEY_BY`: `ip`,
+    /// `user`, or `ip_and_user`, default: `ip`); users are identified by the
+    /// service, see `RateLimitLayer::identify_users`
+    pub key_by: KeyBy,
+
+    /// Requests per window for specific paths, counted separately from the
+    /// rest of the service; with the token bucket this is also the capacity
+    /// (env `RATE_LIMIT_ENDPOINTS` as an inline table, usually set in the
+    /// config file instead)
+    pub endpoints: BTreeMap<String, u32>,
+}
+
+impl Default for RateLimitConfig {
+    // ---
+    fn default() -> Self {
+        // ---
+        Self {
+            enabled: false,
+            algorithm: Algorithm::default(),
+            requests: 100,
+            window_seconds: 60,
+            burst: None,
+            trust_forwarded_for: false,
+            key_by: KeyBy::default(),
+            endpoints: BTreeMap::new(),
+        }
+    }
+}
+
+impl RateLimitConfig {
+    // ---
+    /// Token bucket capacity: `burst`, or `requests` when unset.
+    pub fn capacity(&self) -> u32 {
+        // ---
+        self.burst.unwrap_or(self.requests)
+    }
+
+    /// Requests per window and token bucket capacity for requests to
+    /// `path`: its entry in `endpoints`, else the service-wide limit.
+    pub fn limit_for(&self, path: &str) -> (u32, u32) {
+        // ---
+        match self.endpoints.get(path) {
+            Some(&requests) => (requests, requests),
+            None => (self.requests, self.capacity()),
+        }
+    }
+}
+
+// ---
+
+/// Config rule for the `rate_limit` section: the limits and window must be
+/// positive, endpoint paths must start with `/`, and `burst` only applies to
+/// the token bucket.
+///
+/// # Errors
+///
+/// Returns a message for `tokn_config::ConfigLoader::rule` naming the first
+/// invalid setting.
+pub fn validate_rate_limit_config(config: &RateLimitConfig) -> Result<(), String> {
+    // ---
+    if config.requests == 0 || config.window_seconds == 0 {
+        return Err(
+            "RATE_LIMIT_REQUESTS and RATE_LIMIT_WINDOW_SECONDS must be positive".to_string(),
+        );
+    }
+    for (path, &requests) in &config.endpoints {
+        if !path.starts_with('/') {
+            return Err(format!(
+                "RATE_LIMIT_ENDPOINTS path '{path}' must start with '/'"
+            ));
+        }
+        if requests == 0 {
+            return Err(format!(
+                "RATE_LIMIT_ENDPOINTS limit for '{path}' must be positive"
+            ));
+        }
+    }
+    match (config.algorithm, config.burst) {
+        (Algorithm::TokenBucket, Some(0)) => Err("RATE_LIMIT_BURST must be positive".to_string()),
+        (Algorithm::SlidingWindow, Some(_)) => {
+            Err("RATE_LIMIT_BURST applies only to the token_bucket algorithm".to_string())
+        }
+        _ => Ok(()),
+    }
+}
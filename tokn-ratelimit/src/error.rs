@@ -0,0 +1,18 @@
+// tokn-ratelimit/src/error.rs
+
+/// Errors checking a rate limit.
+///
+/// The layer lets requests through on any of these (failing open), so an
+/// outage of the limit store never takes the service down with it.
+#[derive(Debug, thiserror::Error)]
+pub enum RateLimitError {
+    // ---
+    /// Redis could not be reached, or rejected the script or its reply
+    #[error("rate limit store error: {0}")]
+    Redis(#[from] redis::RedisError),
+
+    /// Redis has failed repeatedly and is not being called for now, or the
+    /// call timed out
+    #[error("rate limit store unavailable: {0}")]
+    Unavailable(String),
+}
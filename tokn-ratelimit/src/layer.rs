@@ -0,0 +1,268 @@
+// tokn-ratelimit/src/layer.rs
+
+use axum::extract::{ConnectInfo, Request};
+use axum::http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tower::{Layer, Service};
+
+// ---
+
+use crate::{Decision, KeyBy, RateLimiter};
+
+// ---
+
+/// Requests allowed in the current window.
+pub const RATELIMIT_LIMIT: HeaderName = HeaderName::from_static("ratelimit-limit");
+
+/// Requests left before the client is limited.
+pub const RATELIMIT_REMAINING: HeaderName = HeaderName::from_static("ratelimit-remaining");
+
+/// Seconds until the quota recovers.
+pub const RATELIMIT_RESET: HeaderName = HeaderName::from_static("ratelimit-reset");
+
+/// Identifies the authenticated user of a request from its headers.
+type IdentifyUser = Arc<dyn Fn(&HeaderMap) -> Option<String> + Send + Sync>;
+
+// ---
+
+/// Tower layer limiting requests per client through a [`RateLimiter`].
+///
+/// Clients are identified by peer IP address, which requires serving with
+/// `into_make_service_with_connect_info::<SocketAddr>()` (as
+/// `tokn_server::serve` does on TCP), or by the last `X-Forwarded-For`
+/// address when the limiter trusts it. Requests from a client that cannot
+/// be identified (e.g. over a Unix socket without `X-Forwarded-For`) are not
+/// limited.
+///
+/// With [`KeyBy::User`] or [`KeyBy::IpAndUser`], requests are also counted
+/// per user as identified by [`identify_users`](Self::identify_users); a
+/// service that cannot identify users keeps counting by IP address. Paths
+/// listed in `endpoints` are counted against their own limit, matched
+/// exactly as requested.
+///
+/// Every limited response carries `RateLimit-Limit`, `RateLimit-Remaining`,
+/// and `RateLimit-Reset` (seconds); a refused request gets
+/// `429 Too Many Requests` with `Retry-After` and an OAuth-style JSON error.
+/// If the limiter fails (Redis down), requests are let through.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example(app: axum::Router) -> anyhow::Result<()> {
+/// use tokn_core::SystemClock;
+/// use tokn_ratelimit::{RateLimitConfig, RateLimitLayer, RateLimiter};
+///
+/// let config = RateLimitConfig { enabled: true, ..Default::default() };
+/// let limiter = RateLimiter::connect(
+///     "jwt-service",
+///     &config,
+///     "redis://127.0.0.1:6379",
+///     Default::default(),
+///     SystemClock::shared(),
+/// )
+/// .await?;
+/// let app = app.layer(RateLimitLayer::new(limiter).exempt("/health"));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct RateLimitLayer {
+    // ---
+    limiter: RateLimiter,
+    exempt: Arc<[&'static str]>,
+    identify_user: Option<IdentifyUser>,
+}
+
+impl RateLimitLayer {
+    // ---
+    /// Limit every request through `limiter`.
+    pub fn new(limiter: RateLimiter) -> Self {
+        // ---
+        Self {
+            limiter,
+            exempt: Arc::new([]),
+            identify_user: None,
+        }
+    }
+
+    /// Never limit requests for exactly `path` (e.g. a health check).
+    pub fn exempt(mut self, path: &'static str) -> Self {
+        // ---
+        let mut exempt = self.exempt.to_vec();
+        exempt.push(path);
+        self.exempt = exempt.into();
+        self
+    }
+
+    /// Identify the authenticated user of a request (e.g. the subject of a
+    /// verified bearer token) for limits keyed by user; `None` for anonymous
+    /// requests.
+    pub fn identify_users<F>(mut self, identify: F) -> Self
+    where
+        F: Fn(&HeaderMap) -> Option<String> + Send + Sync + 'static,
+    {
+        // ---
+        self.identify_user = Some(Arc::new(identify));
+        self
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    // ---
+    type Service = RateLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        // ---
+        RateLimitService {
+            inner,
+            limiter: self.limiter.clone(),
+            exempt: self.exempt.clone(),
+            identify_user: self.identify_user.clone(),
+        }
+    }
+}
+
+// ---
+
+/// Service produced by [`RateLimitLayer`].
+#[derive(Clone)]
+pub struct RateLimitService<S> {
+    // ---
+    inner: S,
+    limiter: RateLimiter,
+    exempt: Arc<[&'static str]>,
+    identify_user: Option<IdentifyUser>,
+}
+
+impl<S> Service<Request> for RateLimitService<S>
+where
+    S: Service<Request, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    // ---
+    type Response = Response;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // ---
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        // ---
+        // Keep the instance that was polled ready; leave a fresh clone behind
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        let exempt = self.exempt.contains(&req.uri().path());
+        if exempt || !self.limiter.is_enabled() {
+            return Box::pin(inner.call(req));
+        }
+        let limiter = self.limiter.clone();
+        let user = match (limiter.key_by(), &self.identify_user) {
+            (KeyBy::User | KeyBy::IpAndUser, Some(identify)) => identify(req.headers()),
+            _ => None,
+        };
+
+        Box::pin(async move {
+            let ip = client_key(&req, limiter.trusts_forwarded_for());
+            let user = user.map(|user| format!("user:{user}"));
+            let clients: Vec<String> = match limiter.key_by() {
+                KeyBy::Ip => ip.into_iter().collect(),
+                KeyBy::User => user.or(ip).into_iter().collect(),
+                KeyBy::IpAndUser => ip.into_iter().chain(user).collect(),
+            };
+            if clients.is_empty() {
+                tracing::debug!("Not rate limiting a request with no client address");
+                return inner.call(req).await;
+            }
+
+            // Every key must allow the request; report the closest to its limit
+            let path = req.uri().path().to_string();
+            let mut tightest: Option<Decision> = None;
+            for client in &clients {
+                match limiter.check_path(client, &path).await {
+                    Ok(Some(decision)) if !decision.allowed => {
+                        return Ok(too_many_requests(&decision))
+                    }
+                    Ok(Some(decision)) => match tightest {
+                        Some(current) if current.remaining <= decision.remaining => {}
+                        _ => tightest = Some(decision),
+                    },
+                    Ok(None) => {}
+                    Err(e) => {
+                        tracing::warn!("Rate limit check failed; allowing the request: {e}");
+                        return inner.call(req).await;
+                    }
+                }
+            }
+
+            let mut response = inner.call(req).await?;
+            if let Some(decision) = &tightest {
+                set_headers(response.headers_mut(), decision);
+            }
+            Ok(response)
+        })
+    }
+}
+
+// ---
+
+/// The client's IP address: the last `X-Forwarded-For` entry (the address
+/// the nearest proxy saw) when trusted, else the peer address.
+fn client_key(req: &Request, trust_forwarded_for: bool) -> Option<String> {
+    // ---
+    if trust_forwarded_for {
+        let forwarded = req
+            .headers()
+            .get_all("x-forwarded-for")
+            .iter()
+            .next_back()
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.rsplit(',').map(str::trim).find(|a| !a.is_empty()));
+        if let Some(addr) = forwarded {
+            return Some(addr.to_string());
+        }
+    }
+
+    req.extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip().to_string())
+}
+
+fn set_headers(headers: &mut HeaderMap, decision: &Decision) {
+    // ---
+    headers.insert(RATELIMIT_LIMIT, HeaderValue::from(decision.limit));
+    headers.insert(RATELIMIT_REMAINING, HeaderValue::from(decision.remaining));
+    headers.insert(RATELIMIT_RESET, HeaderValue::from(seconds(decision.reset)));
+}
+
+fn too_many_requests(decision: &Decision) -> Response {
+    // ---
+    let retry_after = seconds(decision.retry_after.unwrap_or(decision.reset)).max(1);
+    let body = serde_json::json!({
+        "error": "rate_limited",
+        "error_description": format!("Too many requests; retry in {retry_after} seconds"),
+    });
+
+    let mut response = (StatusCode::TOO_MANY_REQUESTS, Json(body)).into_response();
+    let headers = response.headers_mut();
+    set_headers(headers, decision);
+    headers.insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+    response
+}
+
+/// Whole seconds, rounded up so a client never retries early.
+fn seconds(duration: Duration) -> u64 {
+    // ---
+    duration.as_millis().div_ceil(1000) as u64
+}
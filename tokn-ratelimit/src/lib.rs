@@ -0,0 +1,34 @@
+// tokn-ratelimit/src/lib.rs
+
+//! Distributed request rate limiting for tokn services
+//!
+//! A [`RateLimiter`] counts requests per client in Redis, so every replica
+//! of a service enforces one shared limit, using either algorithm from
+//! [`RateLimitConfig`]:
+//!
+//! - sliding window: at most `requests` in any rolling `window_seconds`
+//! - token bucket: bursts of up to `burst`, refilled at `requests` per
+//!   `window_seconds`
+//!
+//! Each check is a single Lua script call, so concurrent requests cannot
+//! race past the limit. Paths can have limits of their own
+//! (`endpoints`), and requests can be counted per IP address, per
+//! authenticated user, or both ([`KeyBy`]). [`RateLimitLayer`] applies a
+//! limiter to a router, identifying clients and answering with the same
+//! `RateLimit-*` headers, `429` body, and `tokn_ratelimit_checks_total`
+//! metric in every service. When Redis is unavailable requests are let
+//! through rather than refused.
+
+mod config;
+mod error;
+mod layer;
+mod limiter;
+
+// ---
+
+pub use config::{validate_rate_limit_config, Algorithm, KeyBy, RateLimitConfig};
+pub use error::RateLimitError;
+pub use layer::{
+    RateLimitLayer, RateLimitService, RATELIMIT_LIMIT, RATELIMIT_REMAINING, RATELIMIT_RESET,
+};
+pub use limiter::{Decision, RateLimiter};
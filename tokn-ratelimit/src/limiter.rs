@@ -0,0 +1,245 @@
+// tokn-ratelimit/src/limiter.rs
+
+use redis::aio::ConnectionManager;
+use redis::{Client, Script};
+use std::sync::Arc;
+use std::time::Duration;
+use tokn_core::SharedClock;
+use tokn_resilience::{CircuitBreaker, CircuitBreakerConfig, CircuitBreakerError};
+
+// ---
+
+use crate::{Algorithm, KeyBy, RateLimitConfig, RateLimitError};
+
+// ---
+
+/// Prefix of every bucket key in Redis.
+const KEY_PREFIX: &str = "tokn:ratelimit";
+
+// ---
+
+/// Outcome of one rate limit check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Decision {
+    // ---
+    /// Whether the request may proceed
+    pub allowed: bool,
+    /// Requests per window (sliding window) or bucket capacity (token bucket)
+    pub limit: u32,
+    /// Requests left before the client is limited
+    pub remaining: u32,
+    /// Until the next slot frees (sliding window) or the bucket is full again
+    /// (token bucket)
+    pub reset: Duration,
+    /// For a refused request, how long until one would be allowed
+    pub retry_after: Option<Duration>,
+}
+
+// ---
+
+/// Distributed request rate limiter over Redis.
+///
+/// Each check runs one Lua script (sliding window log or token bucket, see
+/// [`Algorithm`]) against the client's bucket, so every replica of a service
+/// shares the same counts and a check is a single round trip. Buckets are
+/// keyed `tokn:ratelimit:<service>:<algorithm>:<client>`, or
+/// `tokn:ratelimit:<service>:<algorithm>:<path>:<client>` for paths with a
+/// limit of their own, and expire once idle. Calls go through a [`CircuitBreaker`] named `ratelimit`, so a dead
+/// Redis fails checks immediately instead of adding latency to every
+/// request.
+///
+/// Clones share the connection and breaker. Checks are counted in
+/// `tokn_ratelimit_checks_total` by service and result (`allowed`,
+/// `limited`, `error`).
+#[derive(Clone)]
+pub struct RateLimiter {
+    // ---
+    inner: Option<Arc<Inner>>,
+}
+
+struct Inner {
+    // ---
+    service: &'static str,
+    config: RateLimitConfig,
+    redis: ConnectionManager,
+    breaker: CircuitBreaker,
+    clock: SharedClock,
+    /// Script for `config.algorithm`, run by SHA after the first call
+    script: Script,
+}
+
+impl RateLimiter {
+    // ---
+    /// A limiter for `service` connected to `redis_url`, or a disabled one
+    /// (without connecting) when `config.enabled` is false.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RateLimitError::Redis`] if the URL is invalid or Redis
+    /// cannot be reached.
+    pub async fn connect(
+        service: &'static str,
+        config: &RateLimitConfig,
+        redis_url: &str,
+        circuit_breaker: CircuitBreakerConfig,
+        clock: SharedClock,
+    ) -> Result<Self, RateLimitError> {
+        // ---
+        if !config.enabled {
+            return Ok(Self::disabled());
+        }
+
+        let redis = ConnectionManager::new(Client::open(redis_url)?).await?;
+        let script = match config.algorithm {
+            Algorithm::SlidingWindow => include_str!("scripts/sliding_window.lua"),
+            Algorithm::TokenBucket => include_str!("scripts/token_bucket.lua"),
+        };
+        Ok(Self {
+            inner: Some(Arc::new(Inner {
+                service,
+                config: config.clone(),
+                redis,
+                breaker: CircuitBreaker::new("ratelimit", circuit_breaker),
+                clock,
+                script: Script::new(script),
+            })),
+        })
+    }
+
+    /// A limiter that allows everything.
+    pub fn disabled() -> Self {
+        // ---
+        Self { inner: None }
+    }
+
+    // ---
+    /// Whether requests are being limited.
+    pub fn is_enabled(&self) -> bool {
+        // ---
+        self.inner.is_some()
+    }
+
+    /// The breaker guarding the Redis calls, or `None` when disabled.
+    pub fn breaker(&self) -> Option<&CircuitBreaker> {
+        // ---
+        self.inner.as_ref().map(|inner| &inner.breaker)
+    }
+
+    /// Whether clients are identified by `X-Forwarded-For`.
+    pub fn trusts_forwarded_for(&self) -> bool {
+        // ---
+        self.inner
+            .as_ref()
+            .is_some_and(|inner| inner.config.trust_forwarded_for)
+    }
+
+    /// What requests are counted against; [`KeyBy::Ip`] when disabled.
+    pub fn key_by(&self) -> KeyBy {
+        // ---
+        self.inner
+            .as_ref()
+            .map_or(KeyBy::Ip, |inner| inner.config.key_by)
+    }
+
+    /// Count one request from `client` (e.g. its IP address), or `None` when
+    /// the limiter is disabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RateLimitError::Redis`] if the script fails, or
+    /// [`RateLimitError::Unavailable`] while the breaker is open or the call
+    /// times out.
+    pub async fn check(&self, client: &str) -> Result<Option<Decision>, RateLimitError> {
+        // ---
+        self.check_path(client, "").await
+    }
+
+    /// Count one request from `client` to `path`, against the path's own
+    /// limit if `endpoints` has one and the service-wide limit otherwise.
+    ///
+    /// # Errors
+    ///
+    /// As for [`check`](Self::check).
+    pub async fn check_path(
+        &self,
+        client: &str,
+        path: &str,
+    ) -> Result<Option<Decision>, RateLimitError> {
+        // ---
+        let Some(inner) = &self.inner else {
+            return Ok(None);
+        };
+
+        let result = inner.check(client, path).await;
+        let outcome = match &result {
+            Ok(decision) if decision.allowed => "allowed",
+            Ok(_) => "limited",
+            Err(_) => "error",
+        };
+        metrics::counter!(
+            "tokn_ratelimit_checks_total",
+            "service" => inner.service,
+            "result" => outcome
+        )
+        .increment(1);
+
+        result.map(Some)
+    }
+}
+
+impl Inner {
+    // ---
+    async fn check(&self, client: &str, path: &str) -> Result<Decision, RateLimitError> {
+        // ---
+        let config = &self.config;
+        let (requests, capacity) = config.limit_for(path);
+        let key = if config.endpoints.contains_key(path) {
+            format!(
+                "{KEY_PREFIX}:{}:{}:{path}:{client}",
+                self.service, config.algorithm
+            )
+        } else {
+            format!(
+                "{KEY_PREFIX}:{}:{}:{client}",
+                self.service, config.algorithm
+            )
+        };
+        let now = self.clock.now().timestamp_millis();
+        let window_ms = config.window_seconds.saturating_mul(1000);
+
+        let mut invocation = self.script.key(&key);
+        invocation.arg(now);
+        let limit = match config.algorithm {
+            Algorithm::SlidingWindow => {
+                // Members must be unique even for requests in the same millisecond
+                let member = format!("{now}-{:016x}", rand::random::<u64>());
+                invocation.arg(window_ms).arg(requests).arg(member);
+                requests
+            }
+            Algorithm::TokenBucket => {
+                let rate = f64::from(requests) / window_ms as f64;
+                invocation.arg(capacity).arg(rate.to_string());
+                capacity
+            }
+        };
+
+        let mut redis = self.redis.clone();
+        let (allowed, remaining, reset, retry_after): (i64, i64, i64, i64) = self
+            .breaker
+            .call(invocation.invoke_async(&mut redis))
+            .await
+            .map_err(|e| match e {
+                CircuitBreakerError::Inner(e) => RateLimitError::Redis(e),
+                other => RateLimitError::Unavailable(other.to_string()),
+            })?;
+
+        let millis = |ms: i64| Duration::from_millis(ms.max(0) as u64);
+        Ok(Decision {
+            allowed: allowed == 1,
+            limit,
+            remaining: u32::try_from(remaining.max(0)).unwrap_or(u32::MAX),
+            reset: millis(reset),
+            retry_after: (allowed != 1).then(|| millis(retry_after)),
+        })
+    }
+}
@@ -0,0 +1,39 @@
+-- Sliding window log: one sorted-set member per allowed request, scored by
+-- its time in milliseconds. A request is allowed while fewer than `limit`
+-- members fall inside the last `window` milliseconds.
+--
+-- KEYS[1]  bucket key
+-- ARGV[1]  now (ms)    ARGV[2] window (ms)    ARGV[3] limit
+-- ARGV[4]  unique member for this request
+--
+-- Returns {allowed (0/1), remaining, reset (ms), retry_after (ms)}
+
+local key = KEYS[1]
+local now = tonumber(ARGV[1])
+local window = tonumber(ARGV[2])
+local limit = tonumber(ARGV[3])
+
+redis.call('ZREMRANGEBYSCORE', key, '-inf', now - window)
+local count = redis.call('ZCARD', key)
+
+local allowed = 0
+if count < limit then
+    redis.call('ZADD', key, now, ARGV[4])
+    redis.call('PEXPIRE', key, window)
+    count = count + 1
+    allowed = 1
+end
+
+-- The oldest request leaving the window frees the next slot
+local reset = 0
+local oldest = redis.call('ZRANGE', key, 0, 0, 'WITHSCORES')
+if oldest[2] then
+    reset = math.max(tonumber(oldest[2]) + window - now, 0)
+end
+
+local retry_after = 0
+if allowed == 0 then
+    retry_after = reset
+end
+
+return {allowed, math.max(limit - count, 0), reset, retry_after}
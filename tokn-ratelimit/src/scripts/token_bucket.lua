@@ -0,0 +1,38 @@
+-- Token bucket: a hash of {tokens, ts}. Tokens refill continuously at `rate`
+-- per millisecond up to `capacity`; each allowed request takes one.
+--
+-- KEYS[1]  bucket key
+-- ARGV[1]  now (ms)    ARGV[2] capacity    ARGV[3] rate (tokens per ms)
+--
+-- Returns {allowed (0/1), remaining, reset (ms), retry_after (ms)}
+
+local key = KEYS[1]
+local now = tonumber(ARGV[1])
+local capacity = tonumber(ARGV[2])
+local rate = tonumber(ARGV[3])
+
+local state = redis.call('HMGET', key, 'tokens', 'ts')
+local tokens = tonumber(state[1]) or capacity
+local ts = tonumber(state[2]) or now
+
+-- A replica whose clock lags never drains tokens it did not add
+if now > ts then
+    tokens = math.min(capacity, tokens + (now - ts) * rate)
+    ts = now
+end
+
+local allowed = 0
+local retry_after = 0
+if tokens >= 1 then
+    tokens = tokens - 1
+    allowed = 1
+else
+    retry_after = math.ceil((1 - tokens) / rate)
+end
+
+-- Until the bucket is full again; a full bucket needs no state
+local reset = math.ceil((capacity - tokens) / rate)
+redis.call('HSET', key, 'tokens', tostring(tokens), 'ts', tostring(ts))
+redis.call('PEXPIRE', key, math.max(reset, 1))
+
+return {allowed, math.floor(tokens), reset, retry_after}
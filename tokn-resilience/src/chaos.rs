@@ -0,0 +1,243 @@
+// tokn-resilience/src/chaos.rs
+
+//! Fault injection for testing breakers, retries, and degraded modes.
+//!
+//! Only builds with the `chaos` feature inject anything; without it
+//! [`install_faults`] is a no-op and [`validate_chaos_config`] refuses any
+//! non-zero probability, so a forgotten `CHAOS_*` variable cannot silently do
+//! nothing.
+
+use serde::Deserialize;
+use std::str::FromStr;
+
+// ---
+
+/// Service configuration section for fault injection.
+///
+/// Faults are injected into calls made through a [`crate::CircuitBreaker`]
+/// (Redis, Postgres, and outbound HTTP to other tokn services), so they count
+/// towards opening the breaker like real failures.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ChaosConfig {
+    // ---
+    /// Percentage of calls failed without reaching the dependency
+    /// (env `CHAOS_FAILURE_PERCENT`, default: 0)
+    pub failure_percent: u8,
+
+    /// Percentage of calls delayed by `delay_ms` before they run
+    /// (env `CHAOS_DELAY_PERCENT`, default: 0)
+    pub delay_percent: u8,
+
+    /// Injected delay in milliseconds; longer than the breaker's
+    /// `call_timeout_ms` turns a delay into a timeout
+    /// (env `CHAOS_DELAY_MS`, default: 1000)
+    pub delay_ms: u64,
+
+    /// Breakers to inject faults into, e.g. `redis,postgres`
+    /// (env `CHAOS_TARGETS`, default: all)
+    pub targets: ChaosTargets,
+}
+
+impl Default for ChaosConfig {
+    // ---
+    fn default() -> Self {
+        // ---
+        Self {
+            failure_percent: 0,
+            delay_percent: 0,
+            delay_ms: 1_000,
+            targets: ChaosTargets::default(),
+        }
+    }
+}
+
+impl ChaosConfig {
+    // ---
+    /// True when any fault has a non-zero probability.
+    pub fn is_active(&self) -> bool {
+        // ---
+        self.failure_percent > 0 || self.delay_percent > 0
+    }
+}
+
+// ---
+
+/// Breaker names faults are injected into.
+///
+/// Parsed from a comma-separated list of breaker names (`redis`, `postgres`,
+/// `ratelimit`, `oauth2-server`); empty or `all` targets every breaker.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct ChaosTargets(pub Vec<String>);
+
+impl ChaosTargets {
+    // ---
+    /// True when faults apply to the breaker called `name`.
+    pub fn includes(&self, name: &str) -> bool {
+        // ---
+        self.0.is_empty() || self.0.iter().any(|target| target == name)
+    }
+}
+
+impl FromStr for ChaosTargets {
+    // ---
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // ---
+        let names: Vec<String> = s
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect();
+
+        if names.iter().any(|name| name == "all") {
+            return Ok(Self::default());
+        }
+        Ok(Self(names))
+    }
+}
+
+impl TryFrom<String> for ChaosTargets {
+    // ---
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        // ---
+        s.parse()
+    }
+}
+
+// ---
+
+/// Config rule for the `chaos` section: percentages are at most 100, and
+/// faults need a build with the `chaos` feature.
+///
+/// # Errors
+///
+/// Returns a message for `tokn_config::ConfigLoader::rule` naming the first
+/// invalid setting.
+pub fn validate_chaos_config(config: &ChaosConfig) -> Result<(), String> {
+    // ---
+    if config.failure_percent > 100 || config.delay_percent > 100 {
+        return Err("CHAOS_FAILURE_PERCENT and CHAOS_DELAY_PERCENT must be 0-100".to_string());
+    }
+    if config.is_active() && !cfg!(feature = "chaos") {
+        return Err("fault injection needs a build with the `chaos` feature".to_string());
+    }
+    Ok(())
+}
+
+/// Config rule refusing active fault injection, for `ConfigLoader::prod_rule`.
+///
+/// # Errors
+///
+/// Returns a message when any fault has a non-zero probability.
+pub fn forbid_chaos(config: &ChaosConfig) -> Result<(), String> {
+    // ---
+    if config.is_active() {
+        return Err("fault injection must be off".to_string());
+    }
+    Ok(())
+}
+
+// ---
+
+/// Start injecting the faults in `config` into every [`crate::CircuitBreaker`]
+/// in the process, replacing any previous configuration. An inactive config
+/// stops injection.
+///
+/// A no-op without the `chaos` feature.
+pub fn install_faults(config: &ChaosConfig) {
+    // ---
+    #[cfg(feature = "chaos")]
+    {
+        let mut installed = injector::INSTALLED
+            .write()
+            .unwrap_or_else(|e| e.into_inner());
+
+        if config.is_active() {
+            tracing::warn!(
+                "Fault injection active: failing {}% and delaying {}% ({}ms) of calls to {}",
+                config.failure_percent,
+                config.delay_percent,
+                config.delay_ms,
+                if config.targets.0.is_empty() {
+                    "all dependencies".to_string()
+                } else {
+                    config.targets.0.join(", ")
+                }
+            );
+            *installed = Some(config.clone());
+        } else {
+            *installed = None;
+        }
+    }
+
+    #[cfg(not(feature = "chaos"))]
+    let _ = config;
+}
+
+// ---
+
+#[cfg(feature = "chaos")]
+pub(crate) use injector::{draw, Fault};
+
+#[cfg(feature = "chaos")]
+mod injector {
+    // ---
+    use super::ChaosConfig;
+    use rand::Rng;
+    use std::sync::RwLock;
+    use std::time::Duration;
+
+    // ---
+
+    pub(super) static INSTALLED: RwLock<Option<ChaosConfig>> = RwLock::new(None);
+
+    /// A fault to inject into one call.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub(crate) enum Fault {
+        // ---
+        /// Fail without calling the dependency
+        Fail,
+
+        /// Wait this long, then make the call
+        Delay(Duration),
+    }
+
+    impl Fault {
+        // ---
+        /// Label for the `tokn_chaos_faults_total` counter.
+        pub(crate) fn label(self) -> &'static str {
+            // ---
+            match self {
+                Fault::Fail => "fail",
+                Fault::Delay(_) => "delay",
+            }
+        }
+    }
+
+    // ---
+
+    /// Roll for a fault on a call through the breaker called `breaker`.
+    pub(crate) fn draw(breaker: &str) -> Option<Fault> {
+        // ---
+        let installed = INSTALLED.read().unwrap_or_else(|e| e.into_inner());
+        let config = installed.as_ref()?;
+        if !config.targets.includes(breaker) {
+            return None;
+        }
+
+        let mut rng = rand::thread_rng();
+        if rng.gen_range(0..100) < config.failure_percent {
+            return Some(Fault::Fail);
+        }
+        if rng.gen_range(0..100) < config.delay_percent {
+            return Some(Fault::Delay(Duration::from_millis(config.delay_ms)));
+        }
+        None
+    }
+}
@@ -0,0 +1,350 @@
+// tokn-resilience/src/circuit_breaker.rs
+
+use serde::Deserialize;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+// ---
+
+/// Circuit breaker thresholds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct CircuitBreakerConfig {
+    // ---
+    /// Consecutive failures that open the breaker (default: 5)
+    pub failure_threshold: u32,
+
+    /// How long an open breaker rejects calls before letting a trial call
+    /// through, in seconds (default: 30)
+    pub open_seconds: u64,
+
+    /// Calls running longer than this many milliseconds are abandoned and count
+    /// as failures (default: 5000)
+    pub call_timeout_ms: u64,
+}
+
+// ---
+
+impl Default for CircuitBreakerConfig {
+    // ---
+    fn default() -> Self {
+        // ---
+        Self {
+            failure_threshold: 5,
+            open_seconds: 30,
+            call_timeout_ms: 5_000,
+        }
+    }
+}
+
+// ---
+
+/// Error returned by calls made through a [`CircuitBreaker`].
+#[derive(Debug, thiserror::Error)]
+pub enum CircuitBreakerError<E> {
+    // ---
+    /// The breaker is open; the call was not attempted
+    #[error("circuit breaker '{0}' is open")]
+    Open(&'static str),
+
+    /// The call exceeded `call_timeout_ms` and was abandoned
+    #[error("call through circuit breaker '{name}' timed out after {}ms", after.as_millis())]
+    Timeout { name: &'static str, after: Duration },
+
+    /// The call was failed by fault injection (only with the `chaos` feature)
+    #[error("injected fault in call through circuit breaker '{0}'")]
+    Injected(&'static str),
+
+    /// The call itself failed
+    #[error(transparent)]
+    Inner(E),
+}
+
+impl<E> CircuitBreakerError<E> {
+    // ---
+    /// True when the call was rejected without reaching the dependency.
+    pub fn is_open(&self) -> bool {
+        // ---
+        matches!(self, CircuitBreakerError::Open(_))
+    }
+}
+
+// ---
+
+/// Breaker state as exported in the `tokn_circuit_breaker_state` gauge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakerState {
+    // ---
+    /// Calls pass through (gauge value 0)
+    Closed,
+
+    /// Calls are rejected (gauge value 1)
+    Open,
+
+    /// One trial call is allowed through to probe recovery (gauge value 2)
+    HalfOpen,
+}
+
+impl BreakerState {
+    // ---
+    /// Lowercase name, as shown on `/debug`.
+    pub fn as_str(self) -> &'static str {
+        // ---
+        match self {
+            BreakerState::Closed => "closed",
+            BreakerState::Open => "open",
+            BreakerState::HalfOpen => "half_open",
+        }
+    }
+}
+
+// ---
+
+#[derive(Debug)]
+enum State {
+    // ---
+    Closed {
+        failures: u32,
+    },
+    Open {
+        until: Instant,
+    },
+    /// A trial call is in flight; if it never reports back (the caller was
+    /// cancelled), another trial is allowed after `until`
+    HalfOpen {
+        until: Instant,
+    },
+}
+
+// ---
+
+/// Circuit breaker guarding calls to one dependency.
+///
+/// After `failure_threshold` consecutive failures (errors or timeouts) the
+/// breaker opens and rejects calls immediately with [`CircuitBreakerError::Open`],
+/// so requests fail fast instead of queueing behind a dead or slow dependency.
+/// After `open_seconds` a single trial call is let through: success closes the
+/// breaker, failure re-opens it.
+///
+/// Cloning is cheap; clones share state. State changes are logged and exported
+/// as the `tokn_circuit_breaker_state{breaker="<name>"}` gauge, and rejected
+/// calls are counted in `tokn_circuit_breaker_rejected_total`.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn ping() -> Result<(), std::io::Error> { Ok(()) }
+/// # async fn example() {
+/// use tokn_resilience::{CircuitBreaker, CircuitBreakerConfig};
+///
+/// let breaker = CircuitBreaker::new("redis", CircuitBreakerConfig::default());
+/// match breaker.call(ping()).await {
+///     Ok(()) => {}
+///     Err(e) if e.is_open() => tracing::warn!("Redis unavailable, failing fast"),
+///     Err(e) => tracing::error!("Redis error: {e}"),
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    // ---
+    name: &'static str,
+    config: CircuitBreakerConfig,
+    state: Arc<Mutex<State>>,
+}
+
+// ---
+
+impl CircuitBreaker {
+    // ---
+    /// Create a closed breaker; `name` labels its logs and metrics.
+    pub fn new(name: &'static str, config: CircuitBreakerConfig) -> Self {
+        // ---
+        let breaker = Self {
+            name,
+            config,
+            state: Arc::new(Mutex::new(State::Closed { failures: 0 })),
+        };
+        breaker.export(BreakerState::Closed);
+        breaker
+    }
+
+    // ---
+    /// Name used in logs and metric labels.
+    pub fn name(&self) -> &'static str {
+        // ---
+        self.name
+    }
+
+    // ---
+    /// Current state. An open breaker whose wait has elapsed still reports
+    /// `Open` until the next call turns it half-open.
+    pub fn state(&self) -> BreakerState {
+        // ---
+        match *self.lock() {
+            State::Closed { .. } => BreakerState::Closed,
+            State::Open { .. } => BreakerState::Open,
+            State::HalfOpen { .. } => BreakerState::HalfOpen,
+        }
+    }
+
+    // ---
+    /// Run `fut` through the breaker.
+    ///
+    /// # Errors
+    ///
+    /// - [`CircuitBreakerError::Open`] if the breaker rejected the call
+    /// - [`CircuitBreakerError::Timeout`] if `fut` ran past `call_timeout_ms`
+    /// - [`CircuitBreakerError::Inner`] with the error `fut` returned
+    /// - [`CircuitBreakerError::Injected`] if fault injection failed the call
+    ///   (see [`crate::install_faults`])
+    pub async fn call<T, E, Fut>(&self, fut: Fut) -> Result<T, CircuitBreakerError<E>>
+    where
+        Fut: Future<Output = Result<T, E>>,
+    {
+        // ---
+        if !self.admit() {
+            metrics::counter!("tokn_circuit_breaker_rejected_total", "breaker" => self.name)
+                .increment(1);
+            return Err(CircuitBreakerError::Open(self.name));
+        }
+
+        let timeout = Duration::from_millis(self.config.call_timeout_ms);
+
+        #[cfg(feature = "chaos")]
+        let fut = {
+            let fault = crate::chaos::draw(self.name);
+            if let Some(fault) = fault {
+                metrics::counter!(
+                    "tokn_chaos_faults_total",
+                    "breaker" => self.name,
+                    "fault" => fault.label()
+                )
+                .increment(1);
+            }
+            if fault == Some(crate::chaos::Fault::Fail) {
+                self.record_failure();
+                return Err(CircuitBreakerError::Injected(self.name));
+            }
+            async move {
+                if let Some(crate::chaos::Fault::Delay(delay)) = fault {
+                    tokio::time::sleep(delay).await;
+                }
+                fut.await
+            }
+        };
+
+        match tokio::time::timeout(timeout, fut).await {
+            Ok(Ok(value)) => {
+                self.record_success();
+                Ok(value)
+            }
+            Ok(Err(error)) => {
+                self.record_failure();
+                Err(CircuitBreakerError::Inner(error))
+            }
+            Err(_) => {
+                self.record_failure();
+                Err(CircuitBreakerError::Timeout {
+                    name: self.name,
+                    after: timeout,
+                })
+            }
+        }
+    }
+
+    // ---
+    /// Decide whether a call may proceed, moving an expired open breaker to
+    /// half-open.
+    fn admit(&self) -> bool {
+        // ---
+        let mut state = self.lock();
+        let now = Instant::now();
+
+        match *state {
+            State::Closed { .. } => true,
+            State::Open { until } | State::HalfOpen { until } if now >= until => {
+                *state = State::HalfOpen {
+                    until: now + self.open_duration(),
+                };
+                drop(state);
+                tracing::info!(
+                    "Circuit breaker '{}' half-open, sending trial call",
+                    self.name
+                );
+                self.export(BreakerState::HalfOpen);
+                true
+            }
+            State::Open { .. } | State::HalfOpen { .. } => false,
+        }
+    }
+
+    // ---
+    fn record_success(&self) {
+        // ---
+        let mut state = self.lock();
+        let recovered = !matches!(*state, State::Closed { .. });
+        *state = State::Closed { failures: 0 };
+        drop(state);
+
+        if recovered {
+            tracing::info!("Circuit breaker '{}' closed", self.name);
+            self.export(BreakerState::Closed);
+        }
+    }
+
+    // ---
+    fn record_failure(&self) {
+        // ---
+        let mut state = self.lock();
+        let reason = match *state {
+            State::Closed { failures } if failures + 1 < self.config.failure_threshold => {
+                *state = State::Closed {
+                    failures: failures + 1,
+                };
+                return;
+            }
+            State::Closed { failures } => format!("{} consecutive failures", failures + 1),
+            State::HalfOpen { .. } => "failed trial call".to_string(),
+            // Late failure of a call admitted before the breaker opened
+            State::Open { .. } => return,
+        };
+
+        *state = State::Open {
+            until: Instant::now() + self.open_duration(),
+        };
+        drop(state);
+
+        tracing::warn!(
+            "Circuit breaker '{}' open after {reason}; rejecting calls for {}s",
+            self.name,
+            self.config.open_seconds
+        );
+        self.export(BreakerState::Open);
+    }
+
+    // ---
+    fn open_duration(&self) -> Duration {
+        // ---
+        Duration::from_secs(self.config.open_seconds)
+    }
+
+    fn export(&self, state: BreakerState) {
+        // ---
+        let value = match state {
+            BreakerState::Closed => 0.0,
+            BreakerState::Open => 1.0,
+            BreakerState::HalfOpen => 2.0,
+        };
+        metrics::gauge!("tokn_circuit_breaker_state", "breaker" => self.name).set(value);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        // ---
+        // The state is a plain enum; a panic while holding the lock cannot leave
+        // it half-updated, so recover from poisoning
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,102 @@
+// tokn-resilience/src/layer.rs
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+
+// ---
+
+use crate::{CircuitBreaker, CircuitBreakerError};
+
+// ---
+
+/// Tower layer routing every call of the wrapped service through a
+/// [`CircuitBreaker`].
+///
+/// Intended for outbound clients (HTTP, gRPC, or a `service_fn` around any
+/// async call): an `Err` from the inner service counts as a failure, and while
+/// the breaker is open calls fail with [`CircuitBreakerError::Open`] without
+/// reaching the inner service.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn fetch(url: &'static str) -> Result<String, std::io::Error> { Ok(url.into()) }
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// use tokn_resilience::{CircuitBreaker, CircuitBreakerConfig, CircuitBreakerLayer};
+/// use tower::{ServiceBuilder, ServiceExt};
+///
+/// let breaker = CircuitBreaker::new("upstream", CircuitBreakerConfig::default());
+/// let service = ServiceBuilder::new()
+///     .layer(CircuitBreakerLayer::new(breaker))
+///     .service_fn(fetch);
+///
+/// let body = service.oneshot("http://upstream.internal/status").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerLayer {
+    // ---
+    breaker: CircuitBreaker,
+}
+
+impl CircuitBreakerLayer {
+    // ---
+    /// Wrap services with `breaker`; all wrapped services share its state.
+    pub fn new(breaker: CircuitBreaker) -> Self {
+        // ---
+        Self { breaker }
+    }
+}
+
+impl<S> Layer<S> for CircuitBreakerLayer {
+    // ---
+    type Service = CircuitBreakerService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        // ---
+        CircuitBreakerService {
+            inner,
+            breaker: self.breaker.clone(),
+        }
+    }
+}
+
+// ---
+
+/// Service produced by [`CircuitBreakerLayer`].
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerService<S> {
+    // ---
+    inner: S,
+    breaker: CircuitBreaker,
+}
+
+impl<S, Req> Service<Req> for CircuitBreakerService<S>
+where
+    S: Service<Req>,
+    S::Future: Send + 'static,
+{
+    // ---
+    type Response = S::Response;
+    type Error = CircuitBreakerError<S::Error>;
+    type Future = Pin<Box<dyn Future<Output = Result<S::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // ---
+        self.inner
+            .poll_ready(cx)
+            .map_err(CircuitBreakerError::Inner)
+    }
+
+    fn call(&mut self, req: Req) -> Self::Future {
+        // ---
+        // A rejected call drops the inner future unpolled; for lazy futures
+        // (reqwest, `service_fn`) that means nothing is sent
+        let breaker = self.breaker.clone();
+        let fut = self.inner.call(req);
+        Box::pin(async move { breaker.call(fut).await })
+    }
+}
@@ -0,0 +1,31 @@
+// tokn-resilience/src/lib.rs
+
+//! Resilience utilities shared by tokn services
+//!
+//! - [`retry`]: retry a dependency connection (Postgres, Redis) with exponential
+//!   backoff and jitter until it succeeds or [`RetryPolicy::max_wait_seconds`]
+//!   elapses, so a service started alongside its dependencies waits for them
+//!   instead of crashing
+//! - [`retry_call`]: retry one call on the request path a bounded number of
+//!   times ([`CallRetryPolicy`]), so a transient hiccup does not fail the
+//!   request
+//! - [`CircuitBreaker`]: fail fast while a dependency is down or slow instead of
+//!   letting every request queue behind it; [`CircuitBreakerLayer`] applies one
+//!   to any tower service
+//! - [`install_faults`]: with the `chaos` feature, randomly fail or delay calls
+//!   through circuit breakers to exercise breakers, retries, and degraded
+//!   modes without breaking real infrastructure
+
+mod chaos;
+mod circuit_breaker;
+mod layer;
+mod retry;
+
+// ---
+
+pub use chaos::{forbid_chaos, install_faults, validate_chaos_config, ChaosConfig, ChaosTargets};
+pub use circuit_breaker::{
+    BreakerState, CircuitBreaker, CircuitBreakerConfig, CircuitBreakerError,
+};
+pub use layer::{CircuitBreakerLayer, CircuitBreakerService};
+pub use retry::{retry, retry_call, CallRetryPolicy, RetryPolicy};
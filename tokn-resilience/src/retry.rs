@@ -0,0 +1,237 @@
+// tokn-resilience/src/retry.rs
+
+use rand::Rng;
+use serde::Deserialize;
+use std::fmt::Display;
+use std::future::Future;
+use std::time::Duration;
+use tokio::time::Instant;
+
+// ---
+
+/// Exponential backoff settings for [`retry`].
+///
+/// Each delay doubles from `initial_delay_ms` up to `max_delay_ms`, with half of
+/// it randomized (equal jitter) so replicas restarted together do not retry in
+/// lockstep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    // ---
+    /// Delay before the second attempt, in milliseconds (default: 250)
+    pub initial_delay_ms: u64,
+
+    /// Upper bound for a single delay, in milliseconds (default: 10000)
+    pub max_delay_ms: u64,
+
+    /// Stop retrying once this many seconds have passed since the first attempt
+    /// (default: 60; 0 tries exactly once)
+    pub max_wait_seconds: u64,
+}
+
+// ---
+
+impl Default for RetryPolicy {
+    // ---
+    fn default() -> Self {
+        // ---
+        Self {
+            initial_delay_ms: 250,
+            max_delay_ms: 10_000,
+            max_wait_seconds: 60,
+        }
+    }
+}
+
+// ---
+
+impl RetryPolicy {
+    // ---
+    /// Jittered delay to sleep after failed attempt number `attempt` (1-based).
+    fn delay(&self, attempt: u32) -> Duration {
+        // ---
+        backoff(self.initial_delay_ms, self.max_delay_ms, attempt)
+    }
+}
+
+// ---
+
+/// Bounded retries for a single call on the request path (e.g. one Redis
+/// command), for [`retry_call`].
+///
+/// Unlike [`RetryPolicy`], which waits out a dependency at startup, this gives
+/// up after `max_attempts` short, jittered delays, so a transient hiccup is
+/// absorbed without holding the request for long.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct CallRetryPolicy {
+    // ---
+    /// Attempts in all, the first included (default: 3; 1 disables retries)
+    pub max_attempts: u32,
+
+    /// Delay before the second attempt, in milliseconds (default: 20)
+    pub initial_delay_ms: u64,
+
+    /// Upper bound for a single delay, in milliseconds (default: 200)
+    pub max_delay_ms: u64,
+}
+
+// ---
+
+impl Default for CallRetryPolicy {
+    // ---
+    fn default() -> Self {
+        // ---
+        Self {
+            max_attempts: 3,
+            initial_delay_ms: 20,
+            max_delay_ms: 200,
+        }
+    }
+}
+
+// ---
+
+/// Run `op` until it succeeds or `policy.max_wait_seconds` runs out.
+///
+/// Every failure is logged at `warn` with the attempt number and the next delay;
+/// `what` names the dependency in those messages (e.g. `"Redis"`). A delay never
+/// sleeps past the deadline: the final attempt happens right at it.
+///
+/// # Errors
+///
+/// Returns the error from the last attempt once the deadline has passed.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn connect(url: &str) -> anyhow::Result<()> { Ok(()) }
+/// # async fn example() -> anyhow::Result<()> {
+/// use tokn_resilience::RetryPolicy;
+///
+/// let policy = RetryPolicy::default();
+/// tokn_resilience::retry(&policy, "Postgres", || connect("postgres://...")).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn retry<T, E, F, Fut>(policy: &RetryPolicy, what: &str, mut op: F) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    E: Display,
+{
+    // ---
+    let deadline = Instant::now() + Duration::from_secs(policy.max_wait_seconds);
+    let mut attempt = 1;
+
+    loop {
+        let error = match op().await {
+            Ok(value) => {
+                if attempt > 1 {
+                    tracing::info!("{what} available after {attempt} attempts");
+                }
+                return Ok(value);
+            }
+            Err(error) => error,
+        };
+
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            tracing::error!("{what} unavailable after {attempt} attempts, giving up: {error:#}");
+            return Err(error);
+        }
+
+        let delay = policy.delay(attempt).min(remaining);
+        tracing::warn!(
+            "{what} unavailable (attempt {attempt}): {error:#}; retrying in {}ms",
+            delay.as_millis()
+        );
+
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// Run `op` until it succeeds, fails with an error `retryable` rejects, or
+/// `policy.max_attempts` is used up.
+///
+/// Only retry errors after which repeating `op` is safe: a command that may
+/// have been applied before its connection dropped must not run twice unless
+/// it is idempotent. Each retry is logged at `warn` and counted in
+/// `tokn_call_retries_total{call="<what>"}`.
+///
+/// # Errors
+///
+/// Returns the first error `retryable` rejects, or the last one.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn get(key: &str) -> Result<String, std::io::Error> { Ok(String::new()) }
+/// # async fn example() -> Result<(), std::io::Error> {
+/// use tokn_resilience::CallRetryPolicy;
+///
+/// let policy = CallRetryPolicy::default();
+/// let value = tokn_resilience::retry_call(
+///     &policy,
+///     "redis",
+///     |e: &std::io::Error| e.kind() == std::io::ErrorKind::ConnectionRefused,
+///     || get("key"),
+/// )
+/// .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn retry_call<T, E, F, Fut, R>(
+    policy: &CallRetryPolicy,
+    what: &'static str,
+    retryable: R,
+    mut op: F,
+) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    R: Fn(&E) -> bool,
+    E: Display,
+{
+    // ---
+    let mut attempt = 1;
+
+    loop {
+        let error = match op().await {
+            Ok(value) => return Ok(value),
+            Err(error) => error,
+        };
+        if attempt >= policy.max_attempts || !retryable(&error) {
+            return Err(error);
+        }
+
+        let delay = backoff(policy.initial_delay_ms, policy.max_delay_ms, attempt);
+        tracing::warn!(
+            "{what} call failed (attempt {attempt}/{}): {error:#}; retrying in {}ms",
+            policy.max_attempts,
+            delay.as_millis()
+        );
+        metrics::counter!("tokn_call_retries_total", "call" => what).increment(1);
+
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+// ---
+
+/// Exponential delay after failed attempt number `attempt` (1-based),
+/// doubling from `initial_ms` up to `max_ms`, with half of it randomized
+/// (equal jitter).
+fn backoff(initial_ms: u64, max_ms: u64, attempt: u32) -> Duration {
+    // ---
+    let exponential = initial_ms
+        .saturating_mul(1u64 << attempt.saturating_sub(1).min(32))
+        .min(max_ms);
+
+    let half = exponential / 2;
+    let jitter = rand::thread_rng().gen_range(0..=exponential - half);
+
+    Duration::from_millis(half + jitter)
+}
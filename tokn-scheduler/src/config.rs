@@ -0,0 +1,35 @@
+// tokn-scheduler/src/config.rs
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+// ---
+
+use crate::Schedule;
+
+// ---
+
+/// Service configuration section for recurring background jobs.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct SchedulerConfig {
+    // ---
+    /// Run jobs on their schedules (env `SCHEDULER_ENABLED`, default: true);
+    /// when false, jobs only run through [`Scheduler::run_now`](crate::Scheduler::run_now)
+    pub enabled: bool,
+
+    /// Schedule overrides by job name (`[scheduler.jobs]` in a config file,
+    /// or one `JOB_<NAME>` variable per job declared by the service)
+    pub jobs: BTreeMap<String, Schedule>,
+}
+
+impl Default for SchedulerConfig {
+    // ---
+    fn default() -> Self {
+        // ---
+        Self {
+            enabled: true,
+            jobs: BTreeMap::new(),
+        }
+    }
+}
@@ -0,0 +1,54 @@
+// tokn-scheduler/src/election.rs
+
+use std::future::Future;
+use std::pin::Pin;
+
+// ---
+
+/// Decides which replica runs singleton jobs.
+///
+/// Implement it over a lock every replica can see (a Postgres advisory lock,
+/// a Redis key with a lease) and hand it to
+/// [`Scheduler::with_election`](crate::Scheduler::with_election). Only the
+/// instance that currently leads runs jobs registered with
+/// [`Scheduler::singleton_job`](crate::Scheduler::singleton_job).
+pub trait Election: Send + Sync + 'static {
+    // ---
+    /// Whether this instance leads right now, taking over if no instance
+    /// does. Called each time a singleton job comes due; an instance that
+    /// cannot tell (e.g. the lock store is down) should answer `false`.
+    fn is_leader(&self) -> impl Future<Output = bool> + Send;
+}
+
+// ---
+
+/// Always leads: every singleton job runs on this instance.
+///
+/// The default, for deployments with a single replica.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Standalone;
+
+impl Election for Standalone {
+    // ---
+    async fn is_leader(&self) -> bool {
+        // ---
+        true
+    }
+}
+
+// ---
+
+/// Object-safe adapter over [`Election`], so [`Scheduler`](crate::Scheduler)
+/// is not generic.
+pub(crate) trait DynElection: Send + Sync {
+    // ---
+    fn is_leader_boxed(&self) -> Pin<Box<dyn Future<Output = bool> + Send + '_>>;
+}
+
+impl<E: Election> DynElection for E {
+    // ---
+    fn is_leader_boxed(&self) -> Pin<Box<dyn Future<Output = bool> + Send + '_>> {
+        // ---
+        Box::pin(self.is_leader())
+    }
+}
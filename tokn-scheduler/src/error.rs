@@ -0,0 +1,24 @@
+// tokn-scheduler/src/error.rs
+
+// ---
+
+/// Errors configuring or running scheduled jobs.
+#[derive(Debug, thiserror::Error)]
+pub enum SchedulerError {
+    // ---
+    /// A schedule expression could not be parsed
+    #[error("invalid schedule '{expression}': {reason}")]
+    InvalidSchedule { expression: String, reason: String },
+
+    /// No job is registered under this name
+    #[error("unknown job '{0}'")]
+    UnknownJob(String),
+
+    /// The job returned an error
+    #[error("job '{job}' failed: {message}")]
+    Failed { job: String, message: String },
+
+    /// The job panicked
+    #[error("job '{0}' panicked")]
+    Panicked(String),
+}
@@ -0,0 +1,35 @@
+// tokn-scheduler/src/lib.rs
+
+//! Recurring background jobs for tokn services
+//!
+//! A service registers its housekeeping jobs (expired token cleanup, key
+//! rotation, retention pruning, retry sweeps) on a [`Scheduler`], each with
+//! a default [`Schedule`] that deployments can override per job in the
+//! `scheduler` config section:
+//!
+//! - cron expressions (`*/15 * * * *`, evaluated in UTC)
+//! - `@hourly`, `@daily`, `@weekly`, `@monthly`, `@yearly`
+//! - fixed intervals (`@every 10m`)
+//! - `off`
+//!
+//! The scheduler runs each job in its own task, never lets a job overlap its
+//! previous run, and reports runs, failures, and durations per job as
+//! metrics (see [`Scheduler`]).
+//!
+//! With several replicas, jobs that must happen once per due time (cleanup,
+//! rotation) are registered as singletons and run only on the replica an
+//! [`Election`] picks as leader.
+
+mod config;
+mod election;
+mod error;
+mod schedule;
+mod scheduler;
+
+// ---
+
+pub use config::SchedulerConfig;
+pub use election::{Election, Standalone};
+pub use error::SchedulerError;
+pub use schedule::Schedule;
+pub use scheduler::{Job, JobResult, JobStatus, RunOutcome, Scheduler};
@@ -0,0 +1,331 @@
+// tokn-scheduler/src/schedule.rs
+
+use chrono::{DateTime, Datelike, Duration, DurationRound, NaiveDate, Timelike, Utc};
+use serde::de::{self, Deserializer, Visitor};
+use serde::Deserialize;
+use std::fmt;
+use std::str::FromStr;
+
+// ---
+
+use crate::SchedulerError;
+
+// ---
+
+const MONTHS: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAYS: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// How far ahead [`Schedule::next_after`] searches before giving up on an
+/// expression that never matches (e.g. `0 0 31 2 *`).
+const SEARCH_YEARS: i32 = 5;
+
+// ---
+
+/// When a job runs.
+///
+/// Parsed from one of:
+///
+/// - a five-field cron expression, `minute hour day-of-month month
+///   day-of-week`, evaluated in UTC. Fields take `*`, numbers, ranges
+///   (`1-5`), steps (`*/15`, `0-30/10`), and lists (`1,15`); months and
+///   weekdays also take names (`jan`, `mon`), and Sunday is `0` or `7`. As in
+///   cron, a job whose day-of-month and day-of-week are both restricted runs
+///   when either matches.
+/// - `@hourly`, `@daily`, `@weekly`, `@monthly`, or `@yearly`
+/// - `@every <n><unit>` with unit `s`, `m`, `h`, or `d` (e.g. `@every 90s`),
+///   measured from when the scheduler started or the previous run was due
+/// - `off`, to disable the job
+///
+/// # Example
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use tokn_scheduler::Schedule;
+///
+/// let schedule: Schedule = "*/15 9-17 * * mon-fri".parse().unwrap();
+/// let friday_evening = Utc.with_ymd_and_hms(2026, 10, 16, 17, 50, 0).unwrap();
+/// assert_eq!(
+///     schedule.next_after(friday_evening),
+///     Some(Utc.with_ymd_and_hms(2026, 10, 19, 9, 0, 0).unwrap()),
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    // ---
+    source: String,
+    kind: Kind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Kind {
+    // ---
+    Cron(Cron),
+    Every(Duration),
+    Off,
+}
+
+// ---
+
+impl Schedule {
+    // ---
+    /// A schedule that never fires.
+    pub fn off() -> Self {
+        // ---
+        Self {
+            source: "off".to_string(),
+            kind: Kind::Off,
+        }
+    }
+
+    /// Whether the schedule never fires.
+    pub fn is_off(&self) -> bool {
+        // ---
+        self.kind == Kind::Off
+    }
+
+    /// First time strictly after `after` that the schedule fires, or `None`
+    /// for `off` and for expressions with no match in the next five years.
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        // ---
+        match &self.kind {
+            Kind::Cron(cron) => cron.next_after(after),
+            Kind::Every(interval) => Some(after + *interval),
+            Kind::Off => None,
+        }
+    }
+}
+
+impl fmt::Display for Schedule {
+    // ---
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // ---
+        f.write_str(&self.source)
+    }
+}
+
+impl FromStr for Schedule {
+    // ---
+    type Err = SchedulerError;
+
+    fn from_str(expression: &str) -> Result<Self, Self::Err> {
+        // ---
+        let source = expression.trim();
+        let invalid = |reason: String| SchedulerError::InvalidSchedule {
+            expression: source.to_string(),
+            reason,
+        };
+
+        let kind = match source.to_ascii_lowercase().as_str() {
+            "off" => Kind::Off,
+            "@hourly" => Kind::Cron(Cron::parse("0 * * * *").map_err(invalid)?),
+            "@daily" | "@midnight" => Kind::Cron(Cron::parse("0 0 * * *").map_err(invalid)?),
+            "@weekly" => Kind::Cron(Cron::parse("0 0 * * 0").map_err(invalid)?),
+            "@monthly" => Kind::Cron(Cron::parse("0 0 1 * *").map_err(invalid)?),
+            "@yearly" | "@annually" => Kind::Cron(Cron::parse("0 0 1 1 *").map_err(invalid)?),
+            lower => match lower.strip_prefix("@every") {
+                Some(interval) => Kind::Every(parse_interval(interval.trim()).map_err(invalid)?),
+                None if lower.starts_with('@') => {
+                    return Err(invalid("unknown shorthand".to_string()))
+                }
+                None => Kind::Cron(Cron::parse(lower).map_err(invalid)?),
+            },
+        };
+
+        Ok(Self {
+            source: source.to_string(),
+            kind,
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for Schedule {
+    // ---
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // ---
+        deserializer.deserialize_str(ScheduleVisitor)
+    }
+}
+
+struct ScheduleVisitor;
+
+impl Visitor<'_> for ScheduleVisitor {
+    // ---
+    type Value = Schedule;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // ---
+        f.write_str("a cron expression, @daily-style shorthand, @every <interval>, or off")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        // ---
+        value.parse().map_err(E::custom)
+    }
+}
+
+// ---
+
+/// `<n><unit>` with unit `s`, `m`, `h`, or `d`.
+fn parse_interval(interval: &str) -> Result<Duration, String> {
+    // ---
+    let split = interval
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or("interval needs a unit (s, m, h, or d)")?;
+    let (count, unit) = interval.split_at(split);
+    let count: i64 = count
+        .parse()
+        .map_err(|_| format!("invalid interval '{interval}'"))?;
+    if count == 0 {
+        return Err("interval must be positive".to_string());
+    }
+
+    match unit {
+        "s" => Ok(Duration::seconds(count)),
+        "m" => Ok(Duration::minutes(count)),
+        "h" => Ok(Duration::hours(count)),
+        "d" => Ok(Duration::days(count)),
+        _ => Err(format!("unknown interval unit '{unit}'")),
+    }
+}
+
+// ---
+
+/// A parsed five-field expression, one bit per allowed value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Cron {
+    // ---
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// Both day fields are restricted, so either may match
+    either_day: bool,
+}
+
+impl Cron {
+    // ---
+    fn parse(expression: &str) -> Result<Self, String> {
+        // ---
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!("expected 5 fields, got {}", fields.len()));
+        };
+
+        // Sunday may be written as 7
+        let mut weekdays = parse_field(weekday, 0, 7, &WEEKDAYS, "day-of-week")?;
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59, &[], "minute")?,
+            hours: parse_field(hour, 0, 23, &[], "hour")?,
+            days: parse_field(day, 1, 31, &[], "day-of-month")?,
+            months: parse_field(month, 1, 12, &MONTHS, "month")?,
+            weekdays,
+            either_day: !day.starts_with('*') && !weekday.starts_with('*'),
+        })
+    }
+
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        // ---
+        let day = self.days & (1 << date.day()) != 0;
+        let weekday = self.weekdays & (1 << date.weekday().num_days_from_sunday()) != 0;
+        if self.either_day {
+            day || weekday
+        } else {
+            day && weekday
+        }
+    }
+
+    fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        // ---
+        let start = after.duration_trunc(Duration::minutes(1)).ok()? + Duration::minutes(1);
+        let limit = start.year() + SEARCH_YEARS;
+        let mut t = start.naive_utc();
+
+        // Skip whole months, days, and hours that cannot match before
+        // stepping through minutes
+        while t.year() <= limit {
+            if self.months & (1 << t.month()) == 0 {
+                let (year, month) = match t.month() {
+                    12 => (t.year() + 1, 1),
+                    month => (t.year(), month + 1),
+                };
+                t = NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            if !self.day_matches(t.date()) {
+                t = t.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            if self.hours & (1 << t.hour()) == 0 {
+                t = t.with_minute(0)? + Duration::hours(1);
+                continue;
+            }
+            if self.minutes & (1 << t.minute()) == 0 {
+                t += Duration::minutes(1);
+                continue;
+            }
+            return Some(t.and_utc());
+        }
+        None
+    }
+}
+
+/// Parse one cron field into a bitmask of the values in `min..=max`.
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str], what: &str) -> Result<u64, String> {
+    // ---
+    let value = |s: &str| -> Result<u32, String> {
+        let n = match names.iter().position(|name| *name == s) {
+            Some(index) => index as u32 + if min == 1 { 1 } else { 0 },
+            None => s
+                .parse()
+                .map_err(|_| format!("invalid {what} value '{s}'"))?,
+        };
+        if !(min..=max).contains(&n) {
+            return Err(format!("{what} value {n} is outside {min}-{max}"));
+        }
+        Ok(n)
+    };
+
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .map_err(|_| format!("invalid {what} step '{step}'"))?;
+                if step == 0 {
+                    return Err(format!("{what} step must be positive"));
+                }
+                (range, Some(step))
+            }
+            None => (part, None),
+        };
+
+        let (first, last) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((first, last)) => (value(first)?, value(last)?),
+                // `5/10` means every 10th value from 5
+                None if step.is_some() => (value(range)?, max),
+                None => {
+                    let n = value(range)?;
+                    (n, n)
+                }
+            },
+        };
+        if first > last {
+            return Err(format!("{what} range '{range}' is backwards"));
+        }
+
+        for n in (first..=last).step_by(step.unwrap_or(1) as usize) {
+            mask |= 1 << n;
+        }
+    }
+    Ok(mask)
+}
@@ -0,0 +1,457 @@
+// tokn-scheduler/src/scheduler.rs
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokn_core::SharedClock;
+
+// ---
+
+use crate::election::DynElection;
+use crate::{Election, Schedule, SchedulerConfig, SchedulerError, Standalone};
+
+// ---
+
+/// What a job run returns; the error is logged and counted, never retried
+/// before the next scheduled run.
+pub type JobResult = Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+/// Work run on a schedule.
+///
+/// Implemented for async closures returning [`JobResult`], which covers
+/// most jobs:
+///
+/// ```
+/// use tokn_core::SystemClock;
+/// use tokn_scheduler::{JobResult, Schedule, Scheduler, SchedulerConfig};
+///
+/// let scheduler = Scheduler::new(&SchedulerConfig::default(), SystemClock::shared()).job(
+///     "heartbeat",
+///     "@every 30s".parse().unwrap(),
+///     || async {
+///         tracing::info!("still here");
+///         JobResult::Ok(())
+///     },
+/// );
+/// ```
+pub trait Job: Send + Sync + 'static {
+    // ---
+    /// Run the job once.
+    fn run(&self) -> impl Future<Output = JobResult> + Send;
+}
+
+impl<F, Fut> Job for F
+where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = JobResult> + Send,
+{
+    // ---
+    fn run(&self) -> impl Future<Output = JobResult> + Send {
+        // ---
+        self()
+    }
+}
+
+// ---
+
+/// Object-safe adapter over [`Job`], so [`Scheduler`] can hold mixed jobs.
+trait DynJob: Send + Sync {
+    // ---
+    fn run_boxed(&self) -> Pin<Box<dyn Future<Output = JobResult> + Send + '_>>;
+}
+
+impl<J: Job> DynJob for J {
+    // ---
+    fn run_boxed(&self) -> Pin<Box<dyn Future<Output = JobResult> + Send + '_>> {
+        // ---
+        Box::pin(self.run())
+    }
+}
+
+// ---
+
+/// How a requested run ended, when it did not fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunOutcome {
+    // ---
+    /// The job ran and returned `Ok`
+    Completed,
+
+    /// The previous run was still in progress, so this one did not start
+    Skipped,
+}
+
+/// A registered job and its most recent run on this instance, as reported
+/// by [`Scheduler::status`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct JobStatus {
+    // ---
+    pub name: String,
+
+    /// Effective schedule, as written in the config
+    pub schedule: String,
+
+    /// Runs only on the elected leader
+    pub singleton: bool,
+
+    /// A run is in progress
+    pub running: bool,
+
+    /// When the last completed run on this instance started
+    pub last_run: Option<DateTime<Utc>>,
+
+    /// How the last run ended: `success`, `failure`, or `panic`
+    pub last_result: Option<&'static str>,
+
+    /// When the last successful run started
+    pub last_success: Option<DateTime<Utc>>,
+
+    /// Error of the last failed run, cleared by the next success
+    pub last_error: Option<String>,
+
+    /// Next due time; `None` when the scheduler is disabled or the job is off
+    pub next_run: Option<DateTime<Utc>>,
+}
+
+// ---
+
+struct Entry {
+    // ---
+    name: String,
+    schedule: Schedule,
+    job: Box<dyn DynJob>,
+    /// Runs only on the elected leader
+    singleton: bool,
+    running: AtomicBool,
+    last: Mutex<LastRun>,
+}
+
+/// The most recent run of an [`Entry`] on this instance.
+#[derive(Debug, Default)]
+struct LastRun {
+    // ---
+    started: Option<DateTime<Utc>>,
+    result: Option<&'static str>,
+    succeeded: Option<DateTime<Utc>>,
+    error: Option<String>,
+}
+
+impl Entry {
+    // ---
+    fn last(&self) -> std::sync::MutexGuard<'_, LastRun> {
+        // ---
+        // Plain fields; a panic while holding the lock cannot leave them torn
+        self.last.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Clears [`Entry::running`] when a run ends, however it ends.
+struct Running(Arc<Entry>);
+
+impl Drop for Running {
+    // ---
+    fn drop(&mut self) {
+        // ---
+        self.0.running.store(false, Ordering::Release);
+    }
+}
+
+// ---
+
+/// Runs registered jobs on their schedules; cheap to clone.
+///
+/// A job never overlaps itself: a run that comes due (or is requested with
+/// [`run_now`](Self::run_now)) while the previous one is still going is
+/// skipped and counted in `tokn_job_skipped_total{reason="overlap"}`. Each
+/// run executes in its own task, so a panicking job is reported without
+/// stopping the scheduler.
+///
+/// Every replica runs jobs registered with [`job`](Self::job). Jobs that must
+/// run once per due time across the fleet are registered with
+/// [`singleton_job`](Self::singleton_job) and run only where the
+/// [`Election`] says this instance leads; elsewhere the due run is counted in
+/// `tokn_job_skipped_total{reason="follower"}`, and `tokn_scheduler_leader`
+/// is 1 on the leader and 0 on the others.
+///
+/// Runs are counted in `tokn_job_runs_total` by job and result (`success`,
+/// `failure`, `panic`) and timed in `tokn_job_duration_seconds`;
+/// `tokn_job_last_success_timestamp_seconds` records the last successful run
+/// of each job, for alerting on jobs that have stopped succeeding.
+#[derive(Clone)]
+pub struct Scheduler {
+    // ---
+    config: Arc<SchedulerConfig>,
+    clock: SharedClock,
+    election: Arc<dyn DynElection>,
+    jobs: Vec<Arc<Entry>>,
+}
+
+impl Scheduler {
+    // ---
+    /// A scheduler with no jobs yet, leading on its own ([`Standalone`]);
+    /// `clock` stamps run times.
+    pub fn new(config: &SchedulerConfig, clock: SharedClock) -> Self {
+        // ---
+        Self {
+            config: Arc::new(config.clone()),
+            clock,
+            election: Arc::new(Standalone),
+            jobs: Vec::new(),
+        }
+    }
+
+    // ---
+    /// Run singleton jobs only while `election` says this instance leads.
+    pub fn with_election<E: Election>(mut self, election: E) -> Self {
+        // ---
+        self.election = Arc::new(election);
+        self
+    }
+
+    // ---
+    /// Register `job` under `name`, running on every replica on `schedule`
+    /// unless the config overrides it in `scheduler.jobs.<name>`.
+    ///
+    /// # Panics
+    ///
+    /// If a job named `name` is already registered.
+    pub fn job<J: Job>(self, name: &str, schedule: Schedule, job: J) -> Self {
+        // ---
+        self.register(name, schedule, job, false)
+    }
+
+    /// Register `job` like [`job`](Self::job), but run it only on the elected
+    /// leader, so each due run happens once across the fleet.
+    ///
+    /// # Panics
+    ///
+    /// If a job named `name` is already registered.
+    pub fn singleton_job<J: Job>(self, name: &str, schedule: Schedule, job: J) -> Self {
+        // ---
+        self.register(name, schedule, job, true)
+    }
+
+    fn register<J: Job>(mut self, name: &str, schedule: Schedule, job: J, singleton: bool) -> Self {
+        // ---
+        assert!(
+            self.jobs.iter().all(|entry| entry.name != name),
+            "job '{name}' registered twice"
+        );
+
+        let schedule = self.config.jobs.get(name).cloned().unwrap_or(schedule);
+        self.jobs.push(Arc::new(Entry {
+            name: name.to_string(),
+            schedule,
+            job: Box::new(job),
+            singleton,
+            running: AtomicBool::new(false),
+            last: Mutex::new(LastRun::default()),
+        }));
+        self
+    }
+
+    // ---
+    /// Registered jobs and their effective schedules, in registration order.
+    pub fn jobs(&self) -> impl Iterator<Item = (&str, &Schedule)> {
+        // ---
+        self.jobs
+            .iter()
+            .map(|entry| (entry.name.as_str(), &entry.schedule))
+    }
+
+    // ---
+    /// Every registered job with its last run on this instance, in
+    /// registration order.
+    pub fn status(&self) -> Vec<JobStatus> {
+        // ---
+        let now = self.clock.now();
+        self.jobs
+            .iter()
+            .map(|entry| {
+                let last = entry.last();
+                JobStatus {
+                    name: entry.name.clone(),
+                    schedule: entry.schedule.to_string(),
+                    singleton: entry.singleton,
+                    running: entry.running.load(Ordering::Acquire),
+                    last_run: last.started,
+                    last_result: last.result,
+                    last_success: last.succeeded,
+                    last_error: last.error.clone(),
+                    next_run: self
+                        .config
+                        .enabled
+                        .then(|| entry.schedule.next_after(now))
+                        .flatten(),
+                }
+            })
+            .collect()
+    }
+
+    // ---
+    /// Run the job named `name` now, outside its schedule, and wait for it.
+    /// Runs here even for a singleton job on a follower; only overlap with a
+    /// run on this instance is prevented.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SchedulerError::UnknownJob`] for an unregistered name, and
+    /// [`SchedulerError::Failed`] or [`SchedulerError::Panicked`] when the
+    /// run does not succeed.
+    pub async fn run_now(&self, name: &str) -> Result<RunOutcome, SchedulerError> {
+        // ---
+        let entry = self
+            .jobs
+            .iter()
+            .find(|entry| entry.name == name)
+            .ok_or_else(|| SchedulerError::UnknownJob(name.to_string()))?;
+
+        execute(entry, &self.clock).await
+    }
+
+    // ---
+    /// Start running every job on its schedule in background tasks, which
+    /// live as long as the Tokio runtime. Does nothing when
+    /// `scheduler.enabled` is false.
+    ///
+    /// Must be called within a Tokio runtime.
+    pub fn start(&self) {
+        // ---
+        if !self.config.enabled {
+            tracing::info!(
+                "Scheduler disabled (SCHEDULER_ENABLED=false); jobs run only on request"
+            );
+            return;
+        }
+
+        for entry in &self.jobs {
+            if entry.schedule.is_off() {
+                tracing::info!("Job {} is off", entry.name);
+                continue;
+            }
+            tracing::info!("Scheduled job {} ({})", entry.name, entry.schedule);
+            tokio::spawn(run_loop(
+                entry.clone(),
+                self.clock.clone(),
+                self.election.clone(),
+            ));
+        }
+    }
+}
+
+// ---
+
+async fn run_loop(entry: Arc<Entry>, clock: SharedClock, election: Arc<dyn DynElection>) {
+    // ---
+    let mut last = clock.now();
+
+    loop {
+        // Never fire twice for the same due time, even if the sleep
+        // returned a little early by the clock's reckoning
+        let from = last.max(clock.now());
+        let Some(next) = entry.schedule.next_after(from) else {
+            tracing::warn!(
+                "Job {} has no upcoming runs ({})",
+                entry.name,
+                entry.schedule
+            );
+            return;
+        };
+
+        let wait = (next - from).to_std().unwrap_or_default();
+        tokio::time::sleep(wait).await;
+        last = next;
+
+        let (entry, clock, election) = (entry.clone(), clock.clone(), election.clone());
+        tokio::spawn(async move {
+            if entry.singleton && !lead(&election).await {
+                let name = entry.name.clone();
+                metrics::counter!("tokn_job_skipped_total", "job" => name.clone(), "reason" => "follower")
+                    .increment(1);
+                tracing::debug!("Skipped job {name}: another instance leads");
+                return;
+            }
+            // Already logged and counted by `execute`
+            let _ = execute(&entry, &clock).await;
+        });
+    }
+}
+
+/// Ask `election` whether this instance leads, recording the answer in
+/// `tokn_scheduler_leader`.
+async fn lead(election: &Arc<dyn DynElection>) -> bool {
+    // ---
+    let leader = election.is_leader_boxed().await;
+    metrics::gauge!("tokn_scheduler_leader").set(if leader { 1.0 } else { 0.0 });
+    leader
+}
+
+async fn execute(entry: &Arc<Entry>, clock: &SharedClock) -> Result<RunOutcome, SchedulerError> {
+    // ---
+    let name = entry.name.clone();
+    if entry.running.swap(true, Ordering::AcqRel) {
+        metrics::counter!("tokn_job_skipped_total", "job" => name.clone(), "reason" => "overlap")
+            .increment(1);
+        tracing::warn!("Skipped job {name}: the previous run is still in progress");
+        return Ok(RunOutcome::Skipped);
+    }
+
+    // The run gets its own task so a panic is contained, and holds the guard
+    // so the job stays marked running until it really ends
+    let running = Running(entry.clone());
+    let started_at = clock.now();
+    let started = Instant::now();
+    let result = tokio::spawn(async move {
+        let result = running.0.job.run_boxed().await;
+        drop(running);
+        result
+    })
+    .await;
+
+    metrics::histogram!("tokn_job_duration_seconds", "job" => name.clone())
+        .record(started.elapsed().as_secs_f64());
+
+    let (label, result) = match result {
+        Ok(Ok(())) => {
+            metrics::gauge!("tokn_job_last_success_timestamp_seconds", "job" => name.clone())
+                .set(clock.timestamp() as f64);
+            tracing::debug!("Job {name} completed in {:?}", started.elapsed());
+            ("success", Ok(RunOutcome::Completed))
+        }
+        Ok(Err(e)) => {
+            tracing::warn!("Job {name} failed: {e}");
+            let message = e.to_string();
+            (
+                "failure",
+                Err(SchedulerError::Failed {
+                    job: name.clone(),
+                    message,
+                }),
+            )
+        }
+        Err(_) => {
+            tracing::error!("Job {name} panicked");
+            ("panic", Err(SchedulerError::Panicked(name.clone())))
+        }
+    };
+
+    {
+        let mut last = entry.last();
+        last.started = Some(started_at);
+        last.result = Some(label);
+        match &result {
+            Ok(_) => {
+                last.succeeded = Some(started_at);
+                last.error = None;
+            }
+            Err(e) => last.error = Some(e.to_string()),
+        }
+    }
+
+    metrics::counter!("tokn_job_runs_total", "job" => name, "result" => label).increment(1);
+    result
+}
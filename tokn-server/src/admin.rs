@@ -0,0 +1,164 @@
+// tokn-server/src/admin.rs
+
+use axum::{
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::post,
+    Json, Router,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use tokn_config::Secret;
+use tokn_core::constant_time_eq;
+
+// ---
+
+use crate::{CallingService, ReloadFn};
+
+// ---
+
+/// Operator endpoints under `/admin`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct AdminConfig {
+    // ---
+    /// Bearer token required on every `/admin` request (env `ADMIN_TOKEN`).
+    /// When unset the admin endpoints are not mounted at all.
+    pub token: Option<Secret>,
+}
+
+// ---
+
+/// Minimum admin token length, matching the 256-bit floor used for JWT secrets.
+const MIN_ADMIN_TOKEN_LEN: usize = 32;
+
+/// Config rule for `admin.token`: reject tokens short enough to brute-force.
+///
+/// # Errors
+///
+/// Returns a message for `tokn_config::ConfigLoader::rule` when `token` is
+/// shorter than 32 characters.
+pub fn validate_admin_token(token: &str) -> Result<(), String> {
+    // ---
+    if token.len() < MIN_ADMIN_TOKEN_LEN {
+        return Err(format!(
+            "must be at least {MIN_ADMIN_TOKEN_LEN} characters (256 bits) for security"
+        ));
+    }
+    Ok(())
+}
+
+// ---
+
+/// Build the `/admin` routes, or an empty router when no admin token is
+/// configured.
+///
+/// Routes:
+/// - `POST /admin/reload` - run `reload` and return its [`ReloadReport`](crate::ReloadReport)
+///
+/// # Security
+///
+/// - Every route requires `Authorization: Bearer <ADMIN_TOKEN>`, or a request
+///   signed by another tokn service (see
+///   [`verify_service_signature`](crate::verify_service_signature)); anything
+///   else gets 401
+/// - The token is compared in constant time
+/// - Keep these routes off the public network (e.g. block `/admin` at the
+///   ingress) even with a strong token
+///
+/// # Example
+///
+/// ```no_run
+/// # fn example(app: axum::Router, reload: tokn_server::ReloadFn) {
+/// use tokn_server::AdminConfig;
+///
+/// let admin = AdminConfig { token: Some("change-me-to-32-plus-random-characters".into()) };
+/// let app = app.merge(tokn_server::admin_router(&admin, reload));
+/// # }
+/// ```
+pub fn admin_router(config: &AdminConfig, reload: ReloadFn) -> Router {
+    // ---
+    let Some(token) = config.token.clone() else {
+        return Router::new();
+    };
+
+    Router::new()
+        .route("/admin/reload", post(reload_handler))
+        .with_state(reload)
+        .route_layer(middleware::from_fn_with_state(
+            Arc::new(token),
+            require_admin_token,
+        ))
+}
+
+/// Guard a service's own `/admin` routes with the same check as
+/// [`admin_router`], or return an empty router when no admin token is
+/// configured.
+pub fn admin_routes<S>(config: &AdminConfig, routes: Router<S>) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    // ---
+    let Some(token) = config.token.clone() else {
+        return Router::new();
+    };
+
+    routes.route_layer(middleware::from_fn_with_state(
+        Arc::new(token),
+        require_admin_token,
+    ))
+}
+
+// ---
+
+async fn reload_handler(State(reload): State<ReloadFn>) -> Response {
+    // ---
+    tracing::info!("Configuration reload requested via /admin/reload");
+
+    match reload() {
+        Ok(report) => {
+            report.log();
+            Json(report).into_response()
+        }
+        Err(e) => {
+            tracing::error!("Configuration reload failed, keeping previous settings: {e:#}");
+            (StatusCode::UNPROCESSABLE_ENTITY, format!("{e:#}")).into_response()
+        }
+    }
+}
+
+// ---
+
+pub(crate) async fn require_admin_token(
+    State(token): State<Arc<Secret>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    // ---
+    if let Some(CallingService(service)) = req.extensions().get::<CallingService>() {
+        tracing::info!(
+            service,
+            "Admin request to {} from a tokn service",
+            req.uri().path()
+        );
+        return next.run(req).await;
+    }
+
+    let presented = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+
+    match presented {
+        Some(presented) if constant_time_eq(presented.as_bytes(), token.expose().as_bytes()) => {
+            next.run(req).await
+        }
+        _ => {
+            tracing::warn!("Rejected unauthenticated request to {}", req.uri().path());
+            StatusCode::UNAUTHORIZED.into_response()
+        }
+    }
+}
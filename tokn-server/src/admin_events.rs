@@ -0,0 +1,140 @@
+// tokn-server/src/admin_events.rs
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    middleware,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::get,
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream};
+use tokio_stream::StreamExt;
+use tokn_events::{AuthEvent, AuthEventKind, LiveEvents};
+
+// ---
+
+use crate::admin::require_admin_token;
+use crate::AdminConfig;
+
+// ---
+
+/// Query string for `GET /admin/events`.
+#[derive(Debug, Default, Deserialize)]
+struct EventsQuery {
+    // ---
+    /// Comma-separated event kinds to stream; every kind when unset
+    kind: Option<String>,
+}
+
+/// Data of a `lagged` event.
+#[derive(Debug, Serialize)]
+struct Lagged {
+    // ---
+    /// Events skipped because the subscriber fell behind
+    missed: u64,
+}
+
+// ---
+
+/// Build the `GET /admin/events` route streaming `live`'s auth events as
+/// server-sent events, or an empty router when no admin token is configured.
+///
+/// Each event is sent with its kind as the SSE event name, its ID as the SSE
+/// `id`, and the [`AuthEvent`] JSON as data. `?kind=login,lockout` limits the
+/// stream to those kinds (an unknown kind gets 400). A subscriber that falls
+/// more than the channel's capacity behind receives a `lagged` event with
+/// `{"missed": n}` in place of the skipped events, counted in
+/// `tokn_admin_events_lagged_total`; emitting never waits for subscribers.
+/// Idle streams get a keep-alive comment every 15 seconds.
+///
+/// # Security
+///
+/// Guarded by the same `Authorization: Bearer <ADMIN_TOKEN>` check as
+/// [`admin_router`](crate::admin_router). Events carry user and client IDs
+/// but never token values.
+///
+/// # Example
+///
+/// ```no_run
+/// # fn example(app: axum::Router, admin: tokn_server::AdminConfig) {
+/// use tokn_events::{Events, LiveEvents};
+///
+/// let live = LiveEvents::new("jwt-service", 256);
+/// let events = Events::disabled().with_live(live.clone());
+/// let app = app.merge(tokn_server::admin_events_router(&admin, live));
+/// # }
+/// ```
+pub fn admin_events_router(config: &AdminConfig, live: LiveEvents) -> Router {
+    // ---
+    let Some(token) = config.token.clone() else {
+        return Router::new();
+    };
+
+    Router::new()
+        .route("/admin/events", get(events_handler))
+        .with_state(live)
+        .route_layer(middleware::from_fn_with_state(
+            Arc::new(token),
+            require_admin_token,
+        ))
+}
+
+// ---
+
+async fn events_handler(
+    State(live): State<LiveEvents>,
+    Query(query): Query<EventsQuery>,
+) -> Response {
+    // ---
+    let kinds = match parse_kinds(query.kind.as_deref()) {
+        Ok(kinds) => kinds,
+        Err(e) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    };
+    let receiver = live.subscribe();
+    tracing::info!(
+        "Admin event stream opened ({} subscribers)",
+        live.subscribers()
+    );
+
+    let stream = BroadcastStream::new(receiver).filter_map(move |item| match item {
+        Ok(event) if kinds.is_empty() || kinds.contains(&event.kind) => Some(sse_event(&event)),
+        Ok(_) => None,
+        Err(BroadcastStreamRecvError::Lagged(missed)) => {
+            metrics::counter!("tokn_admin_events_lagged_total").increment(missed);
+            Some(
+                Event::default()
+                    .event("lagged")
+                    .json_data(Lagged { missed }),
+            )
+        }
+    });
+
+    Sse::new(stream)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+fn sse_event(event: &AuthEvent) -> Result<Event, axum::Error> {
+    // ---
+    Event::default()
+        .event(event.kind.as_str())
+        .id(event.id.to_string())
+        .json_data(event)
+}
+
+/// Kinds listed in `kind`, or none (meaning all) when unset or empty.
+fn parse_kinds(kind: Option<&str>) -> Result<Vec<AuthEventKind>, tokn_events::EventsError> {
+    // ---
+    kind.unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|kind| !kind.is_empty())
+        .map(str::parse)
+        .collect()
+}
@@ -0,0 +1,123 @@
+// tokn-server/src/bind.rs
+
+use serde::{de, Deserialize, Deserializer};
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+// ---
+
+/// Where a service listens.
+///
+/// Parsed from `host:port` (TCP) or `unix:/path/to.sock` (Unix domain socket).
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum Bind {
+    // ---
+    /// TCP `host:port`
+    Tcp(String),
+
+    /// Unix domain socket path (for sidecar deployments behind nginx et al.)
+    Unix(PathBuf),
+}
+
+// ---
+
+impl FromStr for Bind {
+    // ---
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // ---
+        match s.strip_prefix("unix:") {
+            Some("") => Err("unix socket path must not be empty".to_string()),
+            Some(path) => Ok(Bind::Unix(PathBuf::from(path))),
+            None if s.contains(':') => Ok(Bind::Tcp(s.to_string())),
+            None => Err(format!(
+                "expected 'host:port' or 'unix:/path/to.sock', got '{s}'"
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for Bind {
+    // ---
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        // ---
+        s.parse()
+    }
+}
+
+impl fmt::Display for Bind {
+    // ---
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // ---
+        match self {
+            Bind::Tcp(addr) => write!(f, "{addr}"),
+            Bind::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+// ---
+
+/// Unix socket file permissions, written in octal (`660`, `0660`, `0o660`).
+///
+/// Unquoted numbers in TOML/YAML or the environment are read as octal digits
+/// too, so `SOCKET_MODE=660` means `rw-rw----`, not decimal 660.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SocketMode(pub u32);
+
+// ---
+
+impl FromStr for SocketMode {
+    // ---
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // ---
+        let digits = s.trim_start_matches("0o");
+        u32::from_str_radix(digits, 8)
+            .ok()
+            .filter(|mode| *mode <= 0o777)
+            .map(SocketMode)
+            .ok_or_else(|| format!("expected an octal mode such as 660, got '{s}'"))
+    }
+}
+
+impl<'de> Deserialize<'de> for SocketMode {
+    // ---
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // ---
+        struct Visitor;
+
+        impl de::Visitor<'_> for Visitor {
+            // ---
+            type Value = SocketMode;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                // ---
+                f.write_str("an octal file mode such as 660")
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<SocketMode, E> {
+                // ---
+                v.to_string().parse().map_err(E::custom)
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<SocketMode, E> {
+                // ---
+                v.to_string().parse().map_err(E::custom)
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<SocketMode, E> {
+                // ---
+                v.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
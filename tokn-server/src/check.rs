@@ -0,0 +1,272 @@
+// tokn-server/src/check.rs
+
+use anyhow::Result;
+use serde::Serialize;
+use std::fmt;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+// ---
+
+use crate::TlsConfig;
+
+// ---
+
+/// Command-line flag that validates a service's configuration, prints a
+/// [`CheckReport`], and exits instead of serving.
+pub const CHECK_CONFIG_FLAG: &str = "--check-config";
+
+/// How long one connectivity probe may take before it counts as failed.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+// ---
+
+/// How `--check-config` prints its report: `--check-config` (or `=text`) for
+/// people, `--check-config=json` for deploy pipelines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckFormat {
+    // ---
+    Text,
+    Json,
+}
+
+impl CheckFormat {
+    // ---
+    /// The requested format if the process was started with `--check-config`.
+    pub fn from_args() -> Option<Self> {
+        // ---
+        Self::parse(std::env::args().skip(1))
+    }
+
+    /// The requested format if `args` contain `--check-config`. An unknown
+    /// format prints text rather than falling through to starting the server.
+    pub fn parse<I>(args: I) -> Option<Self>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        // ---
+        args.into_iter().find_map(|arg| {
+            let format = arg.as_ref().strip_prefix(CHECK_CONFIG_FLAG)?;
+            match format {
+                "=json" => Some(CheckFormat::Json),
+                "" => Some(CheckFormat::Text),
+                _ if format.starts_with('=') => Some(CheckFormat::Text),
+                _ => None,
+            }
+        })
+    }
+}
+
+// ---
+
+/// Outcome of one check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    // ---
+    /// Checked and fine
+    Pass,
+    /// Usable, but refused or discouraged under a stricter profile
+    Warn,
+    /// The service would not start, or would fail once serving
+    Fail,
+    /// Not applicable to this configuration
+    Skip,
+}
+
+impl CheckStatus {
+    // ---
+    fn label(self) -> &'static str {
+        // ---
+        match self {
+            CheckStatus::Pass => "pass",
+            CheckStatus::Warn => "warn",
+            CheckStatus::Fail => "FAIL",
+            CheckStatus::Skip => "skip",
+        }
+    }
+}
+
+// ---
+
+/// One line of a [`CheckReport`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Check {
+    // ---
+    /// What was checked: a config key or a dependency (`redis`, `postgres`)
+    pub name: String,
+    pub status: CheckStatus,
+    /// What was found, or why the check failed. Never includes secret values.
+    pub detail: String,
+}
+
+// ---
+
+/// Result of `--check-config`: the configuration load, secret screening, TLS
+/// files, and connectivity probes a service runs before it would serve.
+///
+/// The process exits non-zero if any check failed, so a deploy pipeline can
+/// stop before rolling out a broken configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CheckReport {
+    // ---
+    pub service: String,
+    pub checks: Vec<Check>,
+}
+
+impl CheckReport {
+    // ---
+    /// An empty report for `service`.
+    pub fn new(service: &str) -> Self {
+        // ---
+        Self {
+            service: service.to_string(),
+            checks: Vec::new(),
+        }
+    }
+
+    /// Record a check with `status`.
+    pub fn record(&mut self, name: &str, status: CheckStatus, detail: impl Into<String>) {
+        // ---
+        self.checks.push(Check {
+            name: name.to_string(),
+            status,
+            detail: detail.into(),
+        });
+    }
+
+    /// Record a passed check.
+    pub fn pass(&mut self, name: &str, detail: impl Into<String>) {
+        // ---
+        self.record(name, CheckStatus::Pass, detail);
+    }
+
+    /// Record a warning.
+    pub fn warn(&mut self, name: &str, detail: impl Into<String>) {
+        // ---
+        self.record(name, CheckStatus::Warn, detail);
+    }
+
+    /// Record a failed check.
+    pub fn fail(&mut self, name: &str, detail: impl Into<String>) {
+        // ---
+        self.record(name, CheckStatus::Fail, detail);
+    }
+
+    /// Record a check that does not apply.
+    pub fn skip(&mut self, name: &str, detail: impl Into<String>) {
+        // ---
+        self.record(name, CheckStatus::Skip, detail);
+    }
+
+    // ---
+    /// Run a connectivity probe, recording its detail on success and its
+    /// error (or a timeout) on failure, with the elapsed time.
+    pub async fn probe<F>(&mut self, name: &str, probe: F)
+    where
+        F: Future<Output = Result<String>>,
+    {
+        // ---
+        let started = Instant::now();
+        let outcome = tokio::time::timeout(PROBE_TIMEOUT, probe).await;
+        let elapsed = started.elapsed().as_millis();
+
+        match outcome {
+            Ok(Ok(detail)) => self.pass(name, format!("{detail} ({elapsed} ms)")),
+            Ok(Err(e)) => self.fail(name, format!("{e:#}")),
+            Err(_) => self.fail(
+                name,
+                format!("no response within {}s", PROBE_TIMEOUT.as_secs()),
+            ),
+        }
+    }
+
+    /// Screen an optional secret with [`tokn_config::secret_weakness`]. Weak
+    /// secrets only reach this point under the `dev` profile (the stricter
+    /// ones refuse them at load), so they are warnings.
+    pub fn secret(&mut self, key: &str, value: Option<&str>) {
+        // ---
+        match value {
+            None => self.skip(key, "not set"),
+            Some(value) => match tokn_config::secret_weakness(value) {
+                Some(reason) => self.warn(
+                    key,
+                    format!("weak secret: {reason}; refused when TOKN_ENV is staging or prod"),
+                ),
+                None => self.pass(key, "strong"),
+            },
+        }
+    }
+
+    /// Load the TLS certificate and key, if configured.
+    pub async fn tls(&mut self, tls: Option<&TlsConfig>) {
+        // ---
+        match tls {
+            None => self.skip("server.tls", "plain HTTP"),
+            Some(tls) => match tls.load().await {
+                Ok(_) => self.pass("server.tls", format!("loaded {}", tls.cert_path.display())),
+                Err(e) => self.fail("server.tls", format!("{e:#}")),
+            },
+        }
+    }
+
+    // ---
+    /// Whether no check failed.
+    pub fn passed(&self) -> bool {
+        // ---
+        self.checks
+            .iter()
+            .all(|check| check.status != CheckStatus::Fail)
+    }
+
+    /// Print the report in `format` and exit: status 0 if every check passed
+    /// or warned, 1 otherwise.
+    pub fn exit(&self, format: CheckFormat) -> ! {
+        // ---
+        match format {
+            CheckFormat::Text => print!("{self}"),
+            CheckFormat::Json => println!(
+                "{}",
+                serde_json::json!({
+                    "service": self.service,
+                    "passed": self.passed(),
+                    "checks": self.checks,
+                })
+            ),
+        }
+        std::process::exit(if self.passed() { 0 } else { 1 });
+    }
+}
+
+impl fmt::Display for CheckReport {
+    // ---
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // ---
+        writeln!(f, "{} configuration check", self.service)?;
+
+        let width = self.checks.iter().map(|c| c.name.len()).max().unwrap_or(0);
+        for check in &self.checks {
+            // Multi-line details (config problems) stay aligned under the first
+            let indent = format!("\n{:width$}", "", width = width + 11);
+            let detail = check.detail.replace('\n', &indent);
+            writeln!(
+                f,
+                "  [{}] {:width$}  {detail}",
+                check.status.label(),
+                check.name
+            )?;
+        }
+
+        let failed = self
+            .checks
+            .iter()
+            .filter(|check| check.status == CheckStatus::Fail)
+            .count();
+        if failed == 0 {
+            writeln!(f, "OK: no failures in {} checks", self.checks.len())
+        } else {
+            writeln!(f, "FAILED: {failed} of {} checks failed", self.checks.len())
+        }
+    }
+}
@@ -0,0 +1,180 @@
+// tokn-server/src/compression.rs
+
+use axum::http::{header, Response};
+use serde::Deserialize;
+use std::str::FromStr;
+use std::sync::Arc;
+use tower_http::compression::{
+    predicate::{NotForContentType, Predicate, SizeAbove},
+    CompressionLayer,
+};
+
+// ---
+
+/// Response compression settings.
+///
+/// # Security
+///
+/// Compressing a response that mixes secrets with attacker-influenced input
+/// over TLS enables BREACH-style length oracles. Token responses are therefore
+/// never compressed: anything sent with `Cache-Control: no-store` (which RFC 6749
+/// §5.1 requires on token responses) is skipped, as is every content type in
+/// `exclude_content_types`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct CompressionConfig {
+    // ---
+    /// Enabled encodings, e.g. `gzip,br` (default) or `off`
+    pub algorithms: CompressionAlgorithms,
+
+    /// Responses smaller than this many bytes are sent uncompressed
+    pub min_size: u16,
+
+    /// Content-type prefixes never compressed (default: `application/jwt`)
+    pub exclude_content_types: Vec<String>,
+}
+
+// ---
+
+impl Default for CompressionConfig {
+    // ---
+    fn default() -> Self {
+        // ---
+        Self {
+            algorithms: CompressionAlgorithms {
+                gzip: true,
+                br: true,
+            },
+            min_size: 256,
+            exclude_content_types: vec!["application/jwt".to_string()],
+        }
+    }
+}
+
+// ---
+
+/// Set of enabled compression encodings.
+///
+/// Parsed from a comma-separated list: `gzip`, `br` (or `brotli`), or
+/// `off`/`none` to disable compression entirely.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct CompressionAlgorithms {
+    // ---
+    pub gzip: bool,
+    pub br: bool,
+}
+
+// ---
+
+impl FromStr for CompressionAlgorithms {
+    // ---
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // ---
+        let mut algorithms = CompressionAlgorithms {
+            gzip: false,
+            br: false,
+        };
+
+        for name in s.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+            match name.to_ascii_lowercase().as_str() {
+                "gzip" => algorithms.gzip = true,
+                "br" | "brotli" => algorithms.br = true,
+                "off" | "none" => {}
+                other => {
+                    return Err(format!(
+                        "unknown compression '{other}' (expected gzip, br, or off)"
+                    ))
+                }
+            }
+        }
+
+        Ok(algorithms)
+    }
+}
+
+impl TryFrom<String> for CompressionAlgorithms {
+    // ---
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        // ---
+        s.parse()
+    }
+}
+
+// ---
+
+/// Skips responses that must not be compressed: `Cache-Control: no-store`
+/// (token responses) and configured content types.
+#[derive(Debug, Clone)]
+pub struct SkipSensitive {
+    // ---
+    exclude_content_types: Arc<[String]>,
+}
+
+// ---
+
+impl Predicate for SkipSensitive {
+    // ---
+    fn should_compress<B>(&self, response: &Response<B>) -> bool
+    where
+        B: http_body::Body,
+    {
+        // ---
+        let headers = response.headers();
+
+        let no_store = headers
+            .get_all(header::CACHE_CONTROL)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .any(|value| value.to_ascii_lowercase().contains("no-store"));
+        if no_store {
+            return false;
+        }
+
+        let content_type = headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+
+        !self
+            .exclude_content_types
+            .iter()
+            .any(|excluded| content_type.starts_with(excluded.as_str()))
+    }
+}
+
+// ---
+
+/// Build the response compression layer for a service router.
+///
+/// Applies the configured encodings to responses at least `min_size` bytes,
+/// except gRPC, images, server-sent events, and everything [`SkipSensitive`]
+/// rejects. With `algorithms = off` the layer is a pass-through.
+///
+/// # Example
+///
+/// ```
+/// # let app: axum::Router = axum::Router::new();
+/// use tokn_server::CompressionConfig;
+///
+/// let app = app.layer(tokn_server::compression_layer(&CompressionConfig::default()));
+/// ```
+pub fn compression_layer(config: &CompressionConfig) -> CompressionLayer<impl Predicate> {
+    // ---
+    let predicate = SizeAbove::new(config.min_size)
+        .and(NotForContentType::GRPC)
+        .and(NotForContentType::IMAGES)
+        .and(NotForContentType::SSE)
+        .and(SkipSensitive {
+            exclude_content_types: config.exclude_content_types.clone().into(),
+        });
+
+    CompressionLayer::new()
+        .gzip(config.algorithms.gzip)
+        .br(config.algorithms.br)
+        .compress_when(predicate)
+}
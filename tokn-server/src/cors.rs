@@ -0,0 +1,239 @@
+// tokn-server/src/cors.rs
+
+use axum::http::{header, HeaderName, HeaderValue, Method};
+use serde::Deserialize;
+use std::time::Duration;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+// ---
+
+use crate::REQUEST_ID;
+
+// ---
+
+/// Cross-origin resource sharing settings, letting browser apps served from
+/// other origins call the service.
+///
+/// # Security
+///
+/// No origin is allowed by default, so browsers keep the same-origin policy
+/// until origins are listed. `*` allows any origin but never with
+/// `allow_credentials`, which browsers refuse and which would let any site act
+/// with a user's cookies.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct CorsConfig {
+    // ---
+    /// Origins allowed to call the service, e.g. `https://app.example.com`, or
+    /// `*` for any; empty (default) disables CORS
+    pub allowed_origins: CorsList,
+
+    /// Methods cross-origin requests may use, in any case (default:
+    /// `GET,POST`)
+    pub allowed_methods: CorsList,
+
+    /// Request headers cross-origin requests may send (default:
+    /// `authorization,content-type,x-request-id`)
+    pub allowed_headers: CorsList,
+
+    /// Whether cross-origin requests may carry cookies and HTTP authentication
+    pub allow_credentials: bool,
+
+    /// Seconds browsers may cache a preflight response
+    pub max_age_seconds: u64,
+}
+
+// ---
+
+impl Default for CorsConfig {
+    // ---
+    fn default() -> Self {
+        // ---
+        Self {
+            allowed_origins: CorsList::default(),
+            allowed_methods: "GET,POST".into(),
+            allowed_headers: "authorization,content-type,x-request-id".into(),
+            allow_credentials: false,
+            max_age_seconds: 600,
+        }
+    }
+}
+
+// ---
+
+/// List of CORS origins, methods, or headers.
+///
+/// Parsed from a comma-separated list, e.g.
+/// `https://app.example.com,https://admin.example.com`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(from = "String")]
+pub struct CorsList(pub Vec<String>);
+
+// ---
+
+impl CorsList {
+    // ---
+    /// Whether the list has no entries.
+    pub fn is_empty(&self) -> bool {
+        // ---
+        self.0.is_empty()
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &str> {
+        // ---
+        self.0.iter().map(String::as_str)
+    }
+
+    fn is_any(&self) -> bool {
+        // ---
+        self.iter().any(|entry| entry == "*")
+    }
+}
+
+impl From<&str> for CorsList {
+    // ---
+    fn from(s: &str) -> Self {
+        // ---
+        Self(
+            s.split(',')
+                .map(str::trim)
+                .filter(|entry| !entry.is_empty())
+                .map(String::from)
+                .collect(),
+        )
+    }
+}
+
+impl From<String> for CorsList {
+    // ---
+    fn from(s: String) -> Self {
+        // ---
+        s.as_str().into()
+    }
+}
+
+// ---
+
+/// Config rule for a `cors` section: origins must be `scheme://host[:port]`
+/// or a lone `*` (not with credentials), and methods and headers must be
+/// valid HTTP tokens.
+///
+/// # Errors
+///
+/// Returns a message for `tokn_config::ConfigLoader::rule` naming the first
+/// invalid setting.
+pub fn validate_cors_config(config: &CorsConfig) -> Result<(), String> {
+    // ---
+    if config.allowed_origins.is_any() {
+        if config.allowed_origins.0.len() > 1 {
+            return Err("allowed_origins: `*` cannot be combined with other origins".to_string());
+        }
+        if config.allow_credentials {
+            return Err("allowed_origins: `*` cannot be used with allow_credentials".to_string());
+        }
+    } else if let Some(origin) = config.allowed_origins.iter().find(|o| !is_origin(o)) {
+        return Err(format!(
+            "allowed_origins: '{origin}' is not an origin (expected scheme://host[:port])"
+        ));
+    }
+    if let Some(method) = config
+        .allowed_methods
+        .iter()
+        .find(|m| parse_method(m).is_none())
+    {
+        return Err(format!("allowed_methods: '{method}' is not an HTTP method"));
+    }
+    if let Some(name) = config
+        .allowed_headers
+        .iter()
+        .find(|h| HeaderName::from_bytes(h.as_bytes()).is_err())
+    {
+        return Err(format!("allowed_headers: '{name}' is not a header name"));
+    }
+    Ok(())
+}
+
+/// Whether `s` is an origin as browsers send it: scheme, host, and optional
+/// port, with no path.
+fn is_origin(s: &str) -> bool {
+    // ---
+    let Some((scheme, host)) = s.split_once("://") else {
+        return false;
+    };
+    matches!(scheme, "http" | "https")
+        && !host.is_empty()
+        && !host.contains('/')
+        && HeaderValue::from_str(s).is_ok()
+}
+
+/// `s` as a method, case-insensitively (`get` is `GET`).
+fn parse_method(s: &str) -> Option<Method> {
+    // ---
+    Method::from_bytes(s.to_ascii_uppercase().as_bytes()).ok()
+}
+
+// ---
+
+/// Build the CORS layer for a service router, or `None` when no origin is
+/// allowed.
+///
+/// Preflight requests from an allowed origin are answered by the layer;
+/// other responses gain the `Access-Control-*` headers, exposing
+/// `X-Request-Id` and `Retry-After` to the calling script. Requests from other
+/// origins pass through unchanged, so browsers block their responses.
+///
+/// Expects a config accepted by [`validate_cors_config`]; invalid entries are
+/// skipped.
+///
+/// # Example
+///
+/// ```
+/// # let app: axum::Router = axum::Router::new();
+/// use tokn_server::CorsConfig;
+///
+/// let config = CorsConfig {
+///     allowed_origins: "https://app.example.com".into(),
+///     ..CorsConfig::default()
+/// };
+/// let app = match tokn_server::cors_layer(&config) {
+///     Some(cors) => app.layer(cors),
+///     None => app,
+/// };
+/// ```
+pub fn cors_layer(config: &CorsConfig) -> Option<CorsLayer> {
+    // ---
+    if config.allowed_origins.is_empty() {
+        return None;
+    }
+
+    let origins = if config.allowed_origins.is_any() {
+        AllowOrigin::any()
+    } else {
+        AllowOrigin::list(
+            config
+                .allowed_origins
+                .iter()
+                .filter_map(|origin| HeaderValue::from_str(origin).ok()),
+        )
+    };
+    let methods: Vec<Method> = config
+        .allowed_methods
+        .iter()
+        .filter_map(parse_method)
+        .collect();
+    let headers: Vec<HeaderName> = config
+        .allowed_headers
+        .iter()
+        .filter_map(|name| HeaderName::from_bytes(name.as_bytes()).ok())
+        .collect();
+
+    Some(
+        CorsLayer::new()
+            .allow_origin(origins)
+            .allow_methods(methods)
+            .allow_headers(headers)
+            .allow_credentials(config.allow_credentials)
+            .expose_headers([REQUEST_ID, header::RETRY_AFTER])
+            .max_age(Duration::from_secs(config.max_age_seconds)),
+    )
+}
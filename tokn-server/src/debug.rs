@@ -0,0 +1,256 @@
+// tokn-server/src/debug.rs
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    middleware,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+// ---
+
+use crate::admin::require_admin_token;
+use crate::AdminConfig;
+
+// ---
+
+/// Produces one section of the `/debug` report on each request.
+pub type DebugProbe = Arc<dyn Fn() -> ProbeFuture + Send + Sync>;
+
+/// The future a [`DebugProbe`] returns.
+pub type ProbeFuture = Pin<Box<dyn Future<Output = Value> + Send>>;
+
+// ---
+
+/// What a service reports on `/debug`: build info, uptime, and the sections
+/// and caches it registers.
+///
+/// Cloning is cheap; clones share the registered probes and counters.
+#[derive(Clone)]
+pub struct DebugInfo {
+    // ---
+    service: &'static str,
+    version: &'static str,
+    started: Instant,
+    sections: Vec<(&'static str, DebugProbe)>,
+    caches: Vec<(&'static str, CacheStats)>,
+}
+
+impl DebugInfo {
+    // ---
+    /// Report for `service` at `version` (pass `env!("CARGO_PKG_VERSION")`),
+    /// with uptime counted from now.
+    pub fn new(service: &'static str, version: &'static str) -> Self {
+        // ---
+        Self {
+            service,
+            version,
+            started: Instant::now(),
+            sections: Vec::new(),
+            caches: Vec::new(),
+        }
+    }
+
+    // ---
+    /// Add a section named `name`, filled in by `probe` on every request.
+    /// Probes should bound their own waits (e.g. go through a circuit
+    /// breaker), since the report waits for each in turn.
+    ///
+    /// # Panics
+    ///
+    /// If `name` is `build`, `caches`, or already registered.
+    pub fn section<F, Fut>(mut self, name: &'static str, probe: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Value> + Send + 'static,
+    {
+        // ---
+        assert!(
+            !matches!(name, "build" | "caches") && self.probe(name).is_none(),
+            "debug section '{name}' is reserved or already registered"
+        );
+        let probe: DebugProbe = Arc::new(move || -> ProbeFuture { Box::pin(probe()) });
+        self.sections.push((name, probe));
+        self
+    }
+
+    /// Report `stats` under `caches.<name>`.
+    pub fn cache(mut self, name: &'static str, stats: &CacheStats) -> Self {
+        // ---
+        self.caches.push((name, stats.clone()));
+        self
+    }
+
+    // ---
+    fn probe(&self, name: &str) -> Option<&DebugProbe> {
+        // ---
+        self.sections
+            .iter()
+            .find(|(section, _)| *section == name)
+            .map(|(_, probe)| probe)
+    }
+
+    fn build(&self) -> Value {
+        // ---
+        serde_json::json!({
+            "service": self.service,
+            "version": self.version,
+            "profile": if cfg!(debug_assertions) { "debug" } else { "release" },
+            "uptime_seconds": self.started.elapsed().as_secs(),
+        })
+    }
+
+    fn caches(&self) -> Value {
+        // ---
+        let caches: Map<String, Value> = self
+            .caches
+            .iter()
+            .map(|(name, stats)| (name.to_string(), serde_json::json!(stats.snapshot())))
+            .collect();
+        Value::Object(caches)
+    }
+
+    /// One section by name, or `None` if there is no such section.
+    async fn report_section(&self, name: &str) -> Option<Value> {
+        // ---
+        match name {
+            "build" => Some(self.build()),
+            "caches" => Some(self.caches()),
+            _ => {
+                let probe = self.probe(name)?;
+                Some(probe().await)
+            }
+        }
+    }
+
+    /// Every section, in the order `build`, registered sections, `caches`.
+    async fn report(&self) -> Value {
+        // ---
+        let mut report = Map::new();
+        report.insert("build".to_string(), self.build());
+        for (name, probe) in &self.sections {
+            report.insert(name.to_string(), probe().await);
+        }
+        report.insert("caches".to_string(), self.caches());
+        Value::Object(report)
+    }
+}
+
+// ---
+
+/// Hit and miss counters for an in-process cache, reported on `/debug`.
+///
+/// Clones share the counters.
+#[derive(Debug, Clone, Default)]
+pub struct CacheStats {
+    // ---
+    hits: Arc<AtomicU64>,
+    misses: Arc<AtomicU64>,
+}
+
+/// [`CacheStats`] at one point in time.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct CacheSnapshot {
+    // ---
+    pub hits: u64,
+    pub misses: u64,
+
+    /// `hits / (hits + misses)`, or `None` before the first lookup
+    pub hit_rate: Option<f64>,
+}
+
+impl CacheStats {
+    // ---
+    pub fn hit(&self) {
+        // ---
+        self.hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn miss(&self) {
+        // ---
+        self.misses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> CacheSnapshot {
+        // ---
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        let lookups = hits + misses;
+        CacheSnapshot {
+            hits,
+            misses,
+            hit_rate: (lookups > 0).then(|| hits as f64 / lookups as f64),
+        }
+    }
+}
+
+// ---
+
+/// Build the `/debug` routes reporting `debug`, or an empty router when no
+/// admin token is configured.
+///
+/// Routes:
+/// - `GET /debug` - every section as one JSON object
+/// - `GET /debug/{section}` - one section (`build`, `caches`, or one
+///   registered with [`DebugInfo::section`]); unknown sections get 404
+///
+/// # Security
+///
+/// Guarded by the same `Authorization: Bearer <ADMIN_TOKEN>` check as
+/// [`admin_router`](crate::admin_router). Sections describe capacity and
+/// health, never secrets or connection URLs; still, block `/debug` at the
+/// ingress along with `/admin`.
+///
+/// # Example
+///
+/// ```no_run
+/// # fn example(app: axum::Router, admin: tokn_server::AdminConfig) {
+/// use tokn_server::DebugInfo;
+///
+/// let debug = DebugInfo::new("jwt-service", env!("CARGO_PKG_VERSION"))
+///     .section("redis", || async { serde_json::json!({ "breaker": "closed" }) });
+/// let app = app.merge(tokn_server::debug_router(&admin, debug));
+/// # }
+/// ```
+pub fn debug_router(config: &AdminConfig, debug: DebugInfo) -> Router {
+    // ---
+    let Some(token) = config.token.clone() else {
+        return Router::new();
+    };
+
+    Router::new()
+        .route("/debug", get(report_handler))
+        .route("/debug/{section}", get(section_handler))
+        .with_state(Arc::new(debug))
+        .route_layer(middleware::from_fn_with_state(
+            Arc::new(token),
+            require_admin_token,
+        ))
+}
+
+// ---
+
+async fn report_handler(State(debug): State<Arc<DebugInfo>>) -> Json<Value> {
+    // ---
+    Json(debug.report().await)
+}
+
+async fn section_handler(
+    State(debug): State<Arc<DebugInfo>>,
+    Path(section): Path<String>,
+) -> Response {
+    // ---
+    match debug.report_section(&section).await {
+        Some(value) => Json(value).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
@@ -0,0 +1,306 @@
+// tokn-server/src/health.rs
+
+use axum::{
+    http::{header, StatusCode},
+    response::IntoResponse,
+    routing::get,
+    Json, Router,
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// ---
+
+/// Longest a single dependency probe may take before it counts as failed.
+pub const DEFAULT_PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+// ---
+
+/// Checks one dependency on each readiness request.
+pub type HealthProbe = Arc<dyn Fn() -> HealthFuture + Send + Sync>;
+
+/// The future a [`HealthProbe`] returns: `Err` carries why the dependency is
+/// unusable.
+pub type HealthFuture = Pin<Box<dyn Future<Output = Result<(), String>> + Send>>;
+
+/// Says why an answering dependency is degraded, if it is.
+type DegradedCheck = Arc<dyn Fn() -> Option<String> + Send + Sync>;
+
+// ---
+
+/// The dependencies a service needs to serve traffic, probed by
+/// `GET /health/ready`.
+///
+/// Cloning is cheap; clones share the registered probes.
+#[derive(Clone)]
+pub struct HealthChecks {
+    // ---
+    service: &'static str,
+    timeout: Duration,
+    probes: Vec<(&'static str, HealthProbe)>,
+    degraded: Vec<(&'static str, DegradedCheck)>,
+}
+
+impl HealthChecks {
+    // ---
+    /// Checks for `service`, with no dependencies yet and
+    /// [`DEFAULT_PROBE_TIMEOUT`] per probe.
+    pub fn new(service: &'static str) -> Self {
+        // ---
+        Self {
+            service,
+            timeout: DEFAULT_PROBE_TIMEOUT,
+            probes: Vec::new(),
+            degraded: Vec::new(),
+        }
+    }
+
+    // ---
+    /// Probe dependency `name` with `probe` (e.g. a Redis `PING` or a
+    /// Postgres `SELECT 1`) on every readiness request.
+    ///
+    /// # Panics
+    ///
+    /// If `name` is already registered.
+    pub fn check<F, Fut, E>(mut self, name: &'static str, probe: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: Display,
+    {
+        // ---
+        assert!(
+            self.probes.iter().all(|(probed, _)| *probed != name),
+            "health check '{name}' is already registered"
+        );
+        let probe: HealthProbe = Arc::new(move || -> HealthFuture {
+            let probe = probe();
+            Box::pin(async move { probe.await.map_err(|e| e.to_string()) })
+        });
+        self.probes.push((name, probe));
+        self
+    }
+
+    /// Report dependency `name` degraded, with the reason `check` returns,
+    /// whenever it answers its probe but `check` returns one (e.g. while a
+    /// circuit breaker around it is open).
+    ///
+    /// # Panics
+    ///
+    /// If `name` has no probe registered with [`check`](Self::check).
+    pub fn degraded_when<F>(mut self, name: &'static str, check: F) -> Self
+    where
+        F: Fn() -> Option<String> + Send + Sync + 'static,
+    {
+        // ---
+        assert!(
+            self.probes.iter().any(|(probed, _)| *probed == name),
+            "health check '{name}' is not registered"
+        );
+        self.degraded.push((name, Arc::new(check)));
+        self
+    }
+
+    /// Fail probes that take longer than `timeout`.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        // ---
+        self.timeout = timeout;
+        self
+    }
+
+    // ---
+    /// Run every probe concurrently.
+    async fn report(&self) -> HealthReport {
+        // ---
+        let runs = self.probes.iter().map(|(name, probe)| {
+            let (name, probe, timeout) = (*name, probe.clone(), self.timeout);
+            tokio::spawn(async move {
+                let started = Instant::now();
+                let result = match tokio::time::timeout(timeout, probe()).await {
+                    Ok(result) => result,
+                    Err(_) => Err(format!("no answer within {}ms", timeout.as_millis())),
+                };
+                (name, DependencyStatus::new(result, started.elapsed()))
+            })
+        });
+        let runs: Vec<_> = runs.collect();
+
+        let mut checks = BTreeMap::new();
+        for run in runs {
+            // Probes are never aborted, so this only fails on a panic
+            let (name, status) = match run.await {
+                Ok(checked) => checked,
+                Err(e) => std::panic::resume_unwind(e.into_panic()),
+            };
+            checks.insert(name, status);
+        }
+
+        for (name, check) in &self.degraded {
+            if let Some(status) = checks.get_mut(name) {
+                if status.status == Status::Ok {
+                    status.reason = check();
+                    if status.reason.is_some() {
+                        status.status = Status::Degraded;
+                    }
+                }
+            }
+        }
+
+        let status = if checks.values().any(|check| check.status == Status::Fail) {
+            Status::Fail
+        } else if checks
+            .values()
+            .any(|check| check.status == Status::Degraded)
+        {
+            Status::Degraded
+        } else {
+            Status::Ok
+        };
+        HealthReport {
+            status,
+            service: self.service,
+            checks,
+        }
+    }
+}
+
+// ---
+
+/// Outcome of a health check, or of one dependency probe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+    // ---
+    Ok,
+    /// Answering, but not at full strength; still ready
+    Degraded,
+    Fail,
+}
+
+/// One dependency on `/health/ready`.
+#[derive(Debug, Serialize)]
+struct DependencyStatus {
+    // ---
+    status: Status,
+
+    /// Round trip of the probe, in milliseconds
+    latency_ms: f64,
+
+    /// Why the probe failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+
+    /// Why the dependency is degraded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+}
+
+impl DependencyStatus {
+    // ---
+    fn new(result: Result<(), String>, latency: Duration) -> Self {
+        // ---
+        Self {
+            status: if result.is_ok() {
+                Status::Ok
+            } else {
+                Status::Fail
+            },
+            latency_ms: latency.as_secs_f64() * 1000.0,
+            error: result.err(),
+            reason: None,
+        }
+    }
+}
+
+/// Body of `/health/live` and `/health/ready`.
+#[derive(Debug, Serialize)]
+struct HealthReport {
+    // ---
+    status: Status,
+    service: &'static str,
+
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    checks: BTreeMap<&'static str, DependencyStatus>,
+}
+
+// ---
+
+/// Build the health check routes for `checks`.
+///
+/// Routes:
+/// - `GET /health/live` - 200 while the process serves requests; no
+///   dependencies are probed, so a Redis or Postgres outage never restarts
+///   the service
+/// - `GET /health/ready` - probes every dependency concurrently: 200 when all
+///   answer, 503 Service Unavailable when any fails or times out, with each
+///   dependency's status and latency. A dependency that answers but is
+///   [degraded](HealthChecks::degraded_when) is reported as `degraded`, with
+///   its reason, and the service as `degraded`, still with 200
+///
+/// Both answer `Cache-Control: no-store` and need no authentication; probe
+/// errors name the failure, never connection URLs or credentials.
+///
+/// # Example
+///
+/// ```no_run
+/// # fn example(app: axum::Router) {
+/// use tokn_server::HealthChecks;
+///
+/// let checks = HealthChecks::new("jwt-service").check("redis", || async {
+///     Ok::<_, std::io::Error>(())
+/// });
+/// let app = app.merge(tokn_server::health_router(checks));
+/// # }
+/// ```
+///
+/// ```json
+/// {
+///   "status": "fail",
+///   "service": "oauth2-server",
+///   "checks": {
+///     "postgres": { "status": "fail", "latency_ms": 2000.4, "error": "no answer within 2000ms" }
+///   }
+/// }
+/// ```
+pub fn health_router<S>(checks: HealthChecks) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    // ---
+    let service = checks.service;
+    let checks = Arc::new(checks);
+
+    Router::new()
+        .route(
+            "/health/live",
+            get(move || async move {
+                let report = HealthReport {
+                    status: Status::Ok,
+                    service,
+                    checks: BTreeMap::new(),
+                };
+                respond(report)
+            }),
+        )
+        .route(
+            "/health/ready",
+            get(move || {
+                let checks = checks.clone();
+                async move { respond(checks.report().await) }
+            }),
+        )
+}
+
+fn respond(report: HealthReport) -> impl IntoResponse {
+    // ---
+    let status = match report.status {
+        Status::Ok | Status::Degraded => StatusCode::OK,
+        Status::Fail => StatusCode::SERVICE_UNAVAILABLE,
+    };
+    (status, [(header::CACHE_CONTROL, "no-store")], Json(report))
+}
@@ -0,0 +1,82 @@
+// tokn-server/src/lib.rs
+
+//! Shared HTTP serving for tokn binaries
+//!
+//! Wraps `axum::serve` with the deployment options every service supports:
+//! - Plain HTTP on TCP (default)
+//! - Native TLS via rustls, with certificate reload on `SIGHUP`
+//! - Unix domain sockets with configurable file permissions, for sidecar
+//!   deployments behind a local reverse proxy
+//! - HTTP/2 (ALPN over TLS, prior-knowledge h2c over plain TCP)
+//! - Graceful shutdown on `SIGTERM`/`SIGINT`, draining in-flight requests
+//!   within a timeout
+//! - Response compression (gzip/br) that never touches token responses
+//! - CORS for browser apps on other origins, off until origins are listed
+//! - Security headers (CSP, `X-Frame-Options`, `Referrer-Policy`,
+//!   `X-Content-Type-Options`) on every response
+//! - Configuration reload on `SIGHUP` or `POST /admin/reload`, behind an
+//!   admin bearer token
+//! - Runtime diagnostics under `/debug` (build info, uptime, and
+//!   service-registered sections such as pool and job status), behind the
+//!   same admin token
+//! - A live `/admin/events` stream of auth events over server-sent events
+//!   (`events` feature)
+//! - Public API versioning under `/v1`, with the unversioned paths kept as a
+//!   deprecated alias
+//! - Request IDs: taken from `X-Request-Id` or generated, recorded on every
+//!   request's tracing span, and returned with every response
+//! - RFC 7807 error responses completed with the request path and ID
+//! - HMAC-signed requests between tokn services, accepted in place of the
+//!   admin token and required by internal-only routes
+//! - Liveness and readiness endpoints (`/health/live`, `/health/ready`)
+//!   probing each service's dependencies
+//! - A `--check-config` report (config, secrets, TLS files, dependency
+//!   probes) for deploy pipelines to run before rolling out
+
+mod admin;
+#[cfg(feature = "events")]
+mod admin_events;
+mod bind;
+mod check;
+mod compression;
+mod cors;
+mod debug;
+mod health;
+mod problem;
+mod reload;
+mod request_id;
+mod security_headers;
+mod serve;
+mod service_auth;
+mod shutdown;
+mod tls;
+mod unix;
+mod versioning;
+
+// ---
+
+pub use admin::{admin_router, admin_routes, validate_admin_token, AdminConfig};
+#[cfg(feature = "events")]
+pub use admin_events::admin_events_router;
+pub use bind::{Bind, SocketMode};
+pub use check::{Check, CheckFormat, CheckReport, CheckStatus, CHECK_CONFIG_FLAG};
+pub use compression::{compression_layer, CompressionAlgorithms, CompressionConfig, SkipSensitive};
+pub use cors::{cors_layer, validate_cors_config, CorsConfig, CorsList};
+pub use debug::{debug_router, CacheSnapshot, CacheStats, DebugInfo, DebugProbe, ProbeFuture};
+pub use health::{health_router, HealthChecks, HealthFuture, HealthProbe, DEFAULT_PROBE_TIMEOUT};
+pub use problem::problem_details;
+pub use reload::{reload_on_sighup, ReloadFn, ReloadReport};
+pub use request_id::{request_id, RequestId, REQUEST_ID};
+pub use security_headers::{
+    security_headers, validate_security_headers_config, SecurityHeaders, SecurityHeadersConfig,
+    DEFAULT_CONTENT_SECURITY_POLICY,
+};
+pub use serve::serve;
+pub use service_auth::{
+    validate_service_auth_config, verify_service_signature, CallingService, ServiceAuth,
+    ServiceAuthConfig, ServiceAuthError, NONCE_HEADER, SERVICE_HEADER, SIGNATURE_HEADER,
+    TIMESTAMP_HEADER,
+};
+pub use shutdown::{Shutdown, DEFAULT_DRAIN_TIMEOUT_SECONDS};
+pub use tls::{require_tls, TlsConfig};
+pub use versioning::{versioned, ApiConfig, API_VERSION_PREFIX};
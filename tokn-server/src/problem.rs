@@ -0,0 +1,87 @@
+// tokn-server/src/problem.rs
+
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{header, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use tokn_core::Problem;
+
+// ---
+
+use crate::REQUEST_ID;
+
+// ---
+
+/// Middleware completing [`Problem`] error responses with the request they
+/// answer: `instance` becomes the request path and `request_id` the
+/// `X-Request-Id` request header (set by [`request_id`](crate::request_id)
+/// when that layer is outside this one), or a new UUID when there is none.
+/// The ID is also returned in an `X-Request-Id` response header and logged
+/// with the problem (server errors at `warn`, others at `debug`), so a caller's report
+/// can be matched to the service's logs.
+///
+/// Other responses pass through untouched, as do problems that already set
+/// these fields.
+///
+/// # Example
+///
+/// ```
+/// use axum::{middleware, routing::get, Router};
+/// use axum::http::StatusCode;
+/// use tokn_core::Problem;
+///
+/// let app: Router = Router::new()
+///     .route("/", get(|| async { Problem::new(StatusCode::NOT_FOUND) }))
+///     .layer(middleware::from_fn(tokn_server::problem_details));
+/// ```
+pub async fn problem_details(request: Request, next: Next) -> Response {
+    // ---
+    let path = request.uri().path().to_string();
+    let request_id = request
+        .headers()
+        .get(&REQUEST_ID)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+
+    let response = next.run(request).await;
+    let Some(mut problem) = response.extensions().get::<Problem>().cloned() else {
+        return response;
+    };
+
+    let instance = problem.instance.take().unwrap_or(path);
+    let request_id = problem
+        .request_id
+        .take()
+        .or(request_id)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let problem = problem.instance(instance).request_id(&request_id);
+
+    let detail = problem.detail.as_deref().unwrap_or_default();
+    if problem.status_code().is_server_error() {
+        tracing::warn!(
+            request_id,
+            status = problem.status,
+            detail,
+            "Request failed"
+        );
+    } else {
+        tracing::debug!(
+            request_id,
+            status = problem.status,
+            detail,
+            "Request refused"
+        );
+    }
+
+    // Keep the handler's headers (Retry-After, ...) and replace only the body
+    let (mut parts, _) = response.into_parts();
+    parts.headers.remove(header::CONTENT_LENGTH);
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        parts.headers.insert(REQUEST_ID, value);
+    }
+    parts.extensions.insert(problem.clone());
+    Response::from_parts(parts, Body::from(problem.to_json()))
+}
@@ -0,0 +1,111 @@
+// tokn-server/src/reload.rs
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+// ---
+
+/// Re-reads a service's configuration and applies the settings that can change
+/// without a restart. Shared by the `SIGHUP` handler and `POST /admin/reload`.
+pub type ReloadFn = Arc<dyn Fn() -> Result<ReloadReport> + Send + Sync>;
+
+// ---
+
+/// What a configuration reload changed.
+///
+/// Serialized as the `POST /admin/reload` response body.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ReloadReport {
+    // ---
+    /// Applied changes, as `key: old -> new`
+    pub applied: Vec<String>,
+
+    /// Keys whose new value only takes effect after a restart. Values are
+    /// omitted because these sections may hold secrets.
+    pub restart_required: Vec<String>,
+}
+
+// ---
+
+impl ReloadReport {
+    // ---
+    /// Record `key` as applied if its value changed; returns whether it did.
+    pub fn reloadable<T: PartialEq + Debug>(&mut self, key: &str, old: &T, new: &T) -> bool {
+        // ---
+        if old == new {
+            return false;
+        }
+        self.applied.push(format!("{key}: {old:?} -> {new:?}"));
+        true
+    }
+
+    // ---
+    /// Record `key` as needing a restart if its value changed.
+    pub fn restart_required<T: PartialEq>(&mut self, key: &str, old: &T, new: &T) {
+        // ---
+        if old != new {
+            self.restart_required.push(key.to_string());
+        }
+    }
+
+    // ---
+    /// Log the outcome: one line per applied change, plus a warning listing
+    /// the keys that still need a restart.
+    pub fn log(&self) {
+        // ---
+        if self.applied.is_empty() && self.restart_required.is_empty() {
+            tracing::info!("Configuration reloaded, nothing changed");
+            return;
+        }
+        for change in &self.applied {
+            tracing::info!("Configuration reloaded, {change}");
+        }
+        if !self.restart_required.is_empty() {
+            tracing::warn!(
+                "Changed settings require a restart to take effect: {}",
+                self.restart_required.join(", ")
+            );
+        }
+    }
+}
+
+// ---
+
+/// Run `reload` each time the process receives `SIGHUP`. No-op on non-Unix
+/// platforms.
+///
+/// A failed reload is logged and leaves the running configuration untouched.
+///
+/// # Errors
+///
+/// Returns an error if the signal handler cannot be installed.
+pub fn reload_on_sighup(reload: ReloadFn) -> Result<()> {
+    // ---
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut hangup =
+            signal(SignalKind::hangup()).context("Failed to install SIGHUP handler")?;
+
+        tokio::spawn(async move {
+            // ---
+            while hangup.recv().await.is_some() {
+                tracing::info!("SIGHUP received, reloading configuration");
+                match reload() {
+                    Ok(report) => report.log(),
+                    Err(e) => tracing::error!(
+                        "Configuration reload failed, keeping previous settings: {e:#}"
+                    ),
+                }
+            }
+        });
+    }
+
+    #[cfg(not(unix))]
+    let _ = reload;
+
+    Ok(())
+}
@@ -0,0 +1,131 @@
+// tokn-server/src/request_id.rs
+
+use axum::{
+    extract::{FromRequestParts, Request},
+    http::{request::Parts, HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use std::convert::Infallible;
+use tracing::Instrument;
+
+// ---
+
+/// Request header carrying the caller's or proxy's request ID.
+pub const REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longest `X-Request-Id` accepted from a caller; longer ones are replaced.
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+// ---
+
+/// The ID of the request being handled, as set by [`request_id`].
+///
+/// Extract it in a handler to send it on in the [`REQUEST_ID`] header of
+/// calls to other services, so one caller action can be followed through
+/// every service's logs:
+///
+/// ```
+/// use axum::{middleware, routing::get, Router};
+/// use tokn_server::RequestId;
+///
+/// async fn handler(request_id: RequestId) -> String {
+///     format!("handling {}", request_id.as_str())
+/// }
+///
+/// let app: Router = Router::new()
+///     .route("/", get(handler))
+///     .layer(middleware::from_fn(tokn_server::request_id));
+/// ```
+///
+/// Behind no [`request_id`] layer it is the `X-Request-Id` header, or a new
+/// UUID when there is none.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(String);
+
+impl RequestId {
+    // ---
+    /// The ID, as sent in `X-Request-Id`.
+    pub fn as_str(&self) -> &str {
+        // ---
+        &self.0
+    }
+
+    /// The caller's ID from `parts`, if it is one worth keeping: at most 128
+    /// visible ASCII characters.
+    fn from_header(parts: &Parts) -> Option<Self> {
+        // ---
+        let value = parts.headers.get(&REQUEST_ID)?.to_str().ok()?;
+        let valid = !value.is_empty()
+            && value.len() <= MAX_REQUEST_ID_LEN
+            && value.bytes().all(|byte| byte.is_ascii_graphic());
+        valid.then(|| Self(value.to_string()))
+    }
+
+    fn generate() -> Self {
+        // ---
+        Self(uuid::Uuid::new_v4().to_string())
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for RequestId {
+    // ---
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        // ---
+        Ok(parts
+            .extensions
+            .get::<RequestId>()
+            .cloned()
+            .or_else(|| Self::from_header(parts))
+            .unwrap_or_else(Self::generate))
+    }
+}
+
+// ---
+
+/// Middleware giving every request an ID: the caller's `X-Request-Id` when it
+/// sends a usable one (at most 128 visible ASCII characters), else a new
+/// UUID.
+///
+/// The ID replaces the request's `X-Request-Id` header, so inner layers such
+/// as [`problem_details`](crate::problem_details) see it, is available to
+/// handlers as a [`RequestId`], is recorded as `request_id` on a `request`
+/// span around the rest of the stack (which the JSON log format copies onto
+/// every line), and is returned in the `X-Request-Id` response header.
+///
+/// Add it as the outermost layer, so the trace layer's spans and every
+/// response carry the ID.
+///
+/// # Example
+///
+/// ```
+/// use axum::{middleware, routing::get, Router};
+///
+/// let app: Router = Router::new()
+///     .route("/", get(|| async { "ok" }))
+///     .layer(middleware::from_fn(tokn_server::problem_details))
+///     .layer(middleware::from_fn(tokn_server::request_id));
+/// ```
+pub async fn request_id(request: Request, next: Next) -> Response {
+    // ---
+    let (mut parts, body) = request.into_parts();
+    let id = RequestId::from_header(&parts).unwrap_or_else(RequestId::generate);
+    let value = HeaderValue::from_str(id.as_str()).expect("request IDs are visible ASCII");
+    parts.headers.insert(REQUEST_ID, value.clone());
+    parts.extensions.insert(id.clone());
+
+    let span = tracing::info_span!(
+        "request",
+        request_id = id.as_str(),
+        method = %parts.method,
+        path = parts.uri.path(),
+    );
+    let mut response = next
+        .run(Request::from_parts(parts, body))
+        .instrument(span)
+        .await;
+    response.headers_mut().insert(REQUEST_ID, value);
+    response
+}
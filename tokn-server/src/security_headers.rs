@@ -0,0 +1,170 @@
+// tokn-server/src/security_headers.rs
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+
+// ---
+
+/// Default `Content-Security-Policy`: same-origin resources only, no
+/// framing, plugins, or `<base>` rewriting.
+///
+/// `form-action` is left out on purpose: browsers apply it to the redirect a
+/// form submission answers with, which would block the consent page sending
+/// the user back to a client's `redirect_uri`.
+pub const DEFAULT_CONTENT_SECURITY_POLICY: &str =
+    "default-src 'self'; frame-ancestors 'none'; object-src 'none'; base-uri 'none'";
+
+// ---
+
+/// Security headers added to every response, guarding the HTML pages (consent,
+/// login, admin UI) against framing, content sniffing, and leaking URLs
+/// through `Referer`.
+///
+/// Each value is sent as is; an empty value leaves that header out.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct SecurityHeadersConfig {
+    // ---
+    /// `Content-Security-Policy` (default: [`DEFAULT_CONTENT_SECURITY_POLICY`])
+    pub content_security_policy: String,
+
+    /// `X-Frame-Options`, `DENY` (default) or `SAMEORIGIN`
+    pub frame_options: String,
+
+    /// `Referrer-Policy` (default: `no-referrer`)
+    pub referrer_policy: String,
+
+    /// `X-Content-Type-Options` (default: `nosniff`)
+    pub content_type_options: String,
+}
+
+// ---
+
+impl Default for SecurityHeadersConfig {
+    // ---
+    fn default() -> Self {
+        // ---
+        Self {
+            content_security_policy: DEFAULT_CONTENT_SECURITY_POLICY.to_string(),
+            frame_options: "DENY".to_string(),
+            referrer_policy: "no-referrer".to_string(),
+            content_type_options: "nosniff".to_string(),
+        }
+    }
+}
+
+impl SecurityHeadersConfig {
+    // ---
+    /// The configured headers, by name, skipping empty values.
+    fn entries(&self) -> impl Iterator<Item = (HeaderName, &str)> {
+        // ---
+        [
+            (
+                header::CONTENT_SECURITY_POLICY,
+                &self.content_security_policy,
+            ),
+            (header::X_FRAME_OPTIONS, &self.frame_options),
+            (header::REFERRER_POLICY, &self.referrer_policy),
+            (header::X_CONTENT_TYPE_OPTIONS, &self.content_type_options),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name, value.trim()))
+        .filter(|(_, value)| !value.is_empty())
+    }
+}
+
+// ---
+
+/// Config rule for a `security_headers` section: every value must be sendable
+/// as a header, and `frame_options` must be `DENY` or `SAMEORIGIN`.
+///
+/// # Errors
+///
+/// Returns a message for `tokn_config::ConfigLoader::rule` naming the first
+/// invalid setting.
+pub fn validate_security_headers_config(config: &SecurityHeadersConfig) -> Result<(), String> {
+    // ---
+    if let Some((name, _)) = config
+        .entries()
+        .find(|(_, value)| HeaderValue::from_str(value).is_err())
+    {
+        return Err(format!("{name} is not a valid header value"));
+    }
+    let frame_options = config.frame_options.trim();
+    if !frame_options.is_empty()
+        && !frame_options.eq_ignore_ascii_case("DENY")
+        && !frame_options.eq_ignore_ascii_case("SAMEORIGIN")
+    {
+        return Err(format!(
+            "x-frame-options must be DENY or SAMEORIGIN, not '{frame_options}'"
+        ));
+    }
+    Ok(())
+}
+
+// ---
+
+/// The header set [`security_headers`] adds, built once from a
+/// [`SecurityHeadersConfig`].
+#[derive(Debug, Clone)]
+pub struct SecurityHeaders {
+    // ---
+    headers: Arc<[(HeaderName, HeaderValue)]>,
+}
+
+impl SecurityHeaders {
+    // ---
+    /// The headers `config` sets. Values rejected by
+    /// [`validate_security_headers_config`] are skipped.
+    pub fn new(config: &SecurityHeadersConfig) -> Self {
+        // ---
+        let headers = config
+            .entries()
+            .filter_map(|(name, value)| Some((name, HeaderValue::from_str(value).ok()?)))
+            .collect();
+        Self { headers }
+    }
+}
+
+// ---
+
+/// Middleware adding the configured security headers to every response.
+///
+/// A header the handler already set is kept, so a page needing a looser
+/// policy (e.g. the developer portal loading Swagger UI from a CDN) sends its
+/// own `Content-Security-Policy`.
+///
+/// Apply it outside every router, like the compression layer, so the admin
+/// UI and portal are covered too.
+///
+/// # Example
+///
+/// ```
+/// use axum::{middleware, Router};
+/// use tokn_server::{SecurityHeaders, SecurityHeadersConfig};
+///
+/// let headers = SecurityHeaders::new(&SecurityHeadersConfig::default());
+/// let app: Router = Router::new()
+///     .layer(middleware::from_fn_with_state(headers, tokn_server::security_headers));
+/// ```
+pub async fn security_headers(
+    State(headers): State<SecurityHeaders>,
+    request: Request,
+    next: Next,
+) -> Response {
+    // ---
+    let mut response = next.run(request).await;
+    for (name, value) in headers.headers.iter() {
+        response
+            .headers_mut()
+            .entry(name.clone())
+            .or_insert_with(|| value.clone());
+    }
+    response
+}
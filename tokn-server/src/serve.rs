@@ -0,0 +1,101 @@
+// tokn-server/src/serve.rs
+
+use anyhow::{Context, Result};
+use axum::Router;
+use std::net::SocketAddr;
+use tokio::net::TcpListener;
+
+// ---
+
+use crate::{Bind, Shutdown, SocketMode, TlsConfig};
+
+// ---
+
+/// Serve `app` on `bind`, over HTTPS when `tls` is given.
+///
+/// - `Bind::Tcp` without TLS is plain `axum::serve`
+/// - On TCP, handlers can extract the peer address as
+///   `ConnectInfo<SocketAddr>` (used to rate limit per client)
+/// - `Bind::Tcp` with TLS loads the certificate up front (so a bad path fails
+///   startup) and reloads it on `SIGHUP`
+/// - `Bind::Unix` serves plain HTTP on a Unix domain socket, applying
+///   `socket_mode` to the socket file; TLS is left to the fronting proxy
+/// - Once `shutdown` is triggered, no new connections are accepted and
+///   in-flight requests get its drain timeout to finish; then this returns
+///
+/// # Errors
+///
+/// Returns an error if the address cannot be bound, the certificate or key
+/// cannot be loaded, TLS is combined with a Unix socket, or the server fails
+/// while running.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example(app: axum::Router) -> anyhow::Result<()> {
+/// use std::time::Duration;
+/// use tokn_server::{Bind, Shutdown, SocketMode};
+///
+/// let bind: Bind = "unix:/run/tokn/jwt.sock".parse().unwrap();
+/// let shutdown = Shutdown::on_signals(Duration::from_secs(30))?;
+/// tokn_server::serve(app, &bind, None, Some(SocketMode(0o660)), &shutdown).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn serve(
+    app: Router,
+    bind: &Bind,
+    tls: Option<&TlsConfig>,
+    socket_mode: Option<SocketMode>,
+    shutdown: &Shutdown,
+) -> Result<()> {
+    // ---
+    let addr = match bind {
+        Bind::Tcp(addr) => addr,
+        Bind::Unix(path) => {
+            anyhow::ensure!(
+                tls.is_none(),
+                "TLS is not supported on Unix socket {}; terminate TLS at the proxy",
+                path.display()
+            );
+            return crate::unix::serve(app, path, socket_mode, shutdown).await;
+        }
+    };
+
+    // ---
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind {addr}"))?;
+
+    let Some(tls) = tls else {
+        tracing::info!("Listening on http://{addr}");
+        let signal = shutdown.clone();
+        let server = axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .with_graceful_shutdown(async move { signal.signalled().await });
+        return shutdown.drain(server).await;
+    };
+
+    // ---
+    let rustls = tls.load().await?;
+    tls.reload_on_sighup(rustls.clone())?;
+
+    // axum-server drains connections itself, given the timeout
+    let handle = axum_server::Handle::new();
+    let signal = shutdown.clone();
+    let draining = handle.clone();
+    tokio::spawn(async move {
+        signal.signalled().await;
+        draining.graceful_shutdown(Some(signal.drain_timeout()));
+    });
+
+    tracing::info!("Listening on https://{addr}");
+    axum_server::from_tcp_rustls(listener.into_std()?, rustls)
+        .handle(handle)
+        .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+        .await?;
+
+    Ok(())
+}
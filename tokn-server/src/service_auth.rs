@@ -0,0 +1,406 @@
+// tokn-server/src/service_auth.rs
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{FromRequestParts, Request, State},
+    http::{request::Parts, HeaderMap, HeaderName, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use tokn_config::Secret;
+use tokn_core::{Problem, SharedClock};
+
+// ---
+
+/// Request header naming the workspace service that signed the request.
+pub const SERVICE_HEADER: HeaderName = HeaderName::from_static("x-tokn-service");
+
+/// Request header carrying the Unix time the request was signed at.
+pub const TIMESTAMP_HEADER: HeaderName = HeaderName::from_static("x-tokn-timestamp");
+
+/// Request header carrying a value unique to each signed request.
+pub const NONCE_HEADER: HeaderName = HeaderName::from_static("x-tokn-nonce");
+
+/// Request header carrying the hex HMAC-SHA256 signature.
+pub const SIGNATURE_HEADER: HeaderName = HeaderName::from_static("x-tokn-signature");
+
+/// Largest request body buffered to check its signature.
+const MAX_SIGNED_BODY: usize = 1024 * 1024;
+
+/// Minimum signing key length, matching the 256-bit floor used for JWT secrets.
+const MIN_SERVICE_AUTH_KEY_LEN: usize = 32;
+
+// ---
+
+/// Service configuration section for signed requests between tokn services.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ServiceAuthConfig {
+    // ---
+    /// Key shared by every workspace service (env `SERVICE_AUTH_KEY`). When
+    /// unset requests are neither signed nor verified.
+    pub key: Option<Secret>,
+
+    /// How far a signed request's timestamp may be from this service's clock,
+    /// in seconds (env `SERVICE_AUTH_MAX_AGE_SECONDS`, default: 300)
+    pub max_age_seconds: u64,
+}
+
+impl Default for ServiceAuthConfig {
+    // ---
+    fn default() -> Self {
+        // ---
+        Self {
+            key: None,
+            max_age_seconds: 300,
+        }
+    }
+}
+
+// ---
+
+/// Config rule for the `service_auth` section: the key must be long enough
+/// not to be brute-forced and the window must be positive.
+///
+/// # Errors
+///
+/// Returns a message for `tokn_config::ConfigLoader::rule` naming the first
+/// invalid setting.
+pub fn validate_service_auth_config(config: &ServiceAuthConfig) -> Result<(), String> {
+    // ---
+    if let Some(key) = &config.key {
+        if key.expose().len() < MIN_SERVICE_AUTH_KEY_LEN {
+            return Err(format!(
+                "SERVICE_AUTH_KEY must be at least {MIN_SERVICE_AUTH_KEY_LEN} characters (256 bits) for security"
+            ));
+        }
+    }
+    if config.max_age_seconds == 0 {
+        return Err("SERVICE_AUTH_MAX_AGE_SECONDS must be positive".to_string());
+    }
+    Ok(())
+}
+
+// ---
+
+/// Why a signed request was refused.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ServiceAuthError {
+    // ---
+    #[error("Missing or malformed {0} header")]
+    MissingHeader(HeaderName),
+
+    #[error("Request was signed too long ago or too far in the future")]
+    Expired,
+
+    #[error("Request has already been received")]
+    Replayed,
+
+    #[error("Invalid request signature")]
+    BadSignature,
+}
+
+// ---
+
+/// Signs outgoing requests to, and verifies incoming requests from, the other
+/// tokn services.
+///
+/// A signature is HMAC-SHA256, keyed with `SERVICE_AUTH_KEY`, over the
+/// calling service's name, the timestamp, a nonce, the method, the path and
+/// query, and the SHA-256 of the body. Verification rejects timestamps more
+/// than `max_age_seconds` from this service's clock and nonces already seen
+/// within that window, so a captured request cannot be replayed.
+///
+/// Every service holding the key is trusted equally: the service name says
+/// which one called, for logs, but does not limit what it may call.
+///
+/// Clones share the same replay window. A disabled handle (no key) signs
+/// nothing and verifies nothing.
+#[derive(Clone, Default)]
+pub struct ServiceAuth {
+    // ---
+    inner: Option<Arc<Inner>>,
+}
+
+struct Inner {
+    // ---
+    service: String,
+    key: Secret,
+    max_age: u64,
+    clock: SharedClock,
+    /// Nonces seen within the window, with their timestamps
+    seen: Mutex<HashMap<String, i64>>,
+}
+
+impl ServiceAuth {
+    // ---
+    /// Sign and verify as `service` with the configured key, or a disabled
+    /// handle when `config.key` is unset.
+    pub fn new(service: &str, config: &ServiceAuthConfig, clock: SharedClock) -> Self {
+        // ---
+        let Some(key) = config.key.clone() else {
+            return Self::disabled();
+        };
+
+        Self {
+            inner: Some(Arc::new(Inner {
+                service: service.to_string(),
+                key,
+                max_age: config.max_age_seconds,
+                clock,
+                seen: Mutex::new(HashMap::new()),
+            })),
+        }
+    }
+
+    /// A handle that neither signs nor verifies.
+    pub fn disabled() -> Self {
+        // ---
+        Self { inner: None }
+    }
+
+    /// Whether a key is configured.
+    pub fn is_enabled(&self) -> bool {
+        // ---
+        self.inner.is_some()
+    }
+
+    /// The headers to add to a request for `method` `path_and_query` with
+    /// `body`; none when disabled.
+    ///
+    /// Clients built on another `http` version can add them by
+    /// `name.as_str()`.
+    pub fn sign(
+        &self,
+        method: &str,
+        path_and_query: &str,
+        body: &[u8],
+    ) -> Vec<(HeaderName, String)> {
+        // ---
+        let Some(inner) = &self.inner else {
+            return Vec::new();
+        };
+
+        let timestamp = inner.clock.timestamp().to_string();
+        let nonce = uuid::Uuid::new_v4().simple().to_string();
+        let mac = inner.mac(
+            &inner.service,
+            &timestamp,
+            &nonce,
+            method,
+            path_and_query,
+            body,
+        );
+
+        vec![
+            (SERVICE_HEADER, inner.service.clone()),
+            (TIMESTAMP_HEADER, timestamp),
+            (NONCE_HEADER, nonce),
+            (SIGNATURE_HEADER, hex::encode(mac.finalize().into_bytes())),
+        ]
+    }
+
+    /// Check the signature headers of a request for `method`
+    /// `path_and_query` with `body`, returning the calling service's name.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ServiceAuthError`] when a header is missing, the timestamp
+    /// is outside the window, the nonce was already used, or the signature
+    /// does not match. A disabled handle refuses every request with
+    /// [`ServiceAuthError::BadSignature`].
+    pub fn verify(
+        &self,
+        method: &str,
+        path_and_query: &str,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> Result<String, ServiceAuthError> {
+        // ---
+        let Some(inner) = &self.inner else {
+            return Err(ServiceAuthError::BadSignature);
+        };
+
+        let header = |name: &HeaderName| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .filter(|value| !value.is_empty())
+                .ok_or_else(|| ServiceAuthError::MissingHeader(name.clone()))
+        };
+        let service = header(&SERVICE_HEADER)?;
+        let timestamp = header(&TIMESTAMP_HEADER)?;
+        let nonce = header(&NONCE_HEADER)?;
+        let signature = hex::decode(header(&SIGNATURE_HEADER)?)
+            .map_err(|_| ServiceAuthError::MissingHeader(SIGNATURE_HEADER))?;
+        let signed_at: i64 = timestamp
+            .parse()
+            .map_err(|_| ServiceAuthError::MissingHeader(TIMESTAMP_HEADER))?;
+
+        let now = inner.clock.timestamp();
+        if now.abs_diff(signed_at) > inner.max_age {
+            return Err(ServiceAuthError::Expired);
+        }
+
+        // Compared in constant time by the MAC itself
+        inner
+            .mac(service, timestamp, nonce, method, path_and_query, body)
+            .verify_slice(&signature)
+            .map_err(|_| ServiceAuthError::BadSignature)?;
+
+        // Only remember nonces of genuine requests, so forged ones cannot
+        // fill the map
+        let mut seen = inner.seen.lock().unwrap_or_else(|e| e.into_inner());
+        seen.retain(|_, at| now.abs_diff(*at) <= inner.max_age);
+        if seen
+            .insert(format!("{service}:{nonce}"), signed_at)
+            .is_some()
+        {
+            return Err(ServiceAuthError::Replayed);
+        }
+
+        Ok(service.to_string())
+    }
+}
+
+impl Inner {
+    // ---
+    fn mac(
+        &self,
+        service: &str,
+        timestamp: &str,
+        nonce: &str,
+        method: &str,
+        path_and_query: &str,
+        body: &[u8],
+    ) -> Hmac<Sha256> {
+        // ---
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.key.expose().as_bytes())
+            .expect("HMAC accepts keys of any length");
+        let body_hash = hex::encode(Sha256::digest(body));
+        let canonical = [
+            service,
+            timestamp,
+            nonce,
+            &method.to_ascii_uppercase(),
+            path_and_query,
+            &body_hash,
+        ]
+        .join("\n");
+        mac.update(canonical.as_bytes());
+        mac
+    }
+}
+
+impl fmt::Debug for ServiceAuth {
+    // ---
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // ---
+        match &self.inner {
+            Some(inner) => f
+                .debug_struct("ServiceAuth")
+                .field("service", &inner.service)
+                .field("max_age", &inner.max_age)
+                .finish_non_exhaustive(),
+            None => f.write_str("ServiceAuth(disabled)"),
+        }
+    }
+}
+
+// ---
+
+/// The workspace service that signed the current request, set by
+/// [`verify_service_signature`].
+///
+/// As an extractor it refuses unsigned requests with 401, so a route taking
+/// `CallingService` is reachable only by the other tokn services (for
+/// internal APIs and callbacks).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallingService(pub String);
+
+impl<S> FromRequestParts<S> for CallingService
+where
+    S: Send + Sync,
+{
+    // ---
+    type Rejection = Problem;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        // ---
+        parts
+            .extensions
+            .get::<CallingService>()
+            .cloned()
+            .ok_or_else(|| {
+                Problem::new(StatusCode::UNAUTHORIZED)
+                    .detail("Only signed requests from tokn services are accepted")
+            })
+    }
+}
+
+// ---
+
+/// Middleware verifying requests signed by another tokn service.
+///
+/// A request carrying `X-Tokn-Signature` is checked against `auth` and, when
+/// valid, marked with its [`CallingService`]; an invalid signature gets a 401
+/// problem. Unsigned requests pass through unmarked, as does everything when
+/// `auth` is disabled.
+///
+/// Apply it outside every router that should see the caller, including
+/// [`admin_router`](crate::admin_router), which accepts a signed request in
+/// place of the admin token.
+///
+/// # Example
+///
+/// ```
+/// use axum::{middleware, Router};
+/// use tokn_core::SystemClock;
+/// use tokn_server::{ServiceAuth, ServiceAuthConfig};
+///
+/// let auth = ServiceAuth::new("oauth2-server", &ServiceAuthConfig::default(), SystemClock::shared());
+/// let app: Router = Router::new()
+///     .layer(middleware::from_fn_with_state(auth, tokn_server::verify_service_signature));
+/// ```
+pub async fn verify_service_signature(
+    State(auth): State<ServiceAuth>,
+    request: Request,
+    next: Next,
+) -> Response {
+    // ---
+    if !auth.is_enabled() || !request.headers().contains_key(&SIGNATURE_HEADER) {
+        return next.run(request).await;
+    }
+
+    let (mut parts, body) = request.into_parts();
+    let Ok(body) = to_bytes(body, MAX_SIGNED_BODY).await else {
+        return Problem::new(StatusCode::PAYLOAD_TOO_LARGE)
+            .detail("Request body too large to verify")
+            .into_response();
+    };
+
+    let path_and_query = parts
+        .uri
+        .path_and_query()
+        .map(|pq| pq.as_str())
+        .unwrap_or_else(|| parts.uri.path());
+    match auth.verify(parts.method.as_str(), path_and_query, &parts.headers, &body) {
+        Ok(service) => {
+            tracing::debug!(service, "Verified signed request to {}", parts.uri.path());
+            parts.extensions.insert(CallingService(service));
+            next.run(Request::from_parts(parts, Body::from(body))).await
+        }
+        Err(e) => {
+            tracing::warn!("Rejected signed request to {}: {e}", parts.uri.path());
+            Problem::new(StatusCode::UNAUTHORIZED)
+                .detail(e.to_string())
+                .into_response()
+        }
+    }
+}
@@ -0,0 +1,167 @@
+// tokn-server/src/shutdown.rs
+
+use anyhow::Result;
+use std::future::IntoFuture;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+
+// ---
+
+/// Default time in-flight requests get to finish once shutdown begins.
+pub const DEFAULT_DRAIN_TIMEOUT_SECONDS: u64 = 30;
+
+// ---
+
+/// Shutdown of a service process, shared by all of its listeners.
+///
+/// Once triggered (by `SIGTERM`/`SIGINT` with [`Shutdown::on_signals`], or by
+/// [`Shutdown::trigger`]), listeners stop accepting connections and in-flight
+/// requests get up to the drain timeout to complete; connections still open
+/// after that are closed.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example(app: axum::Router, bind: tokn_server::Bind) -> anyhow::Result<()> {
+/// use std::time::Duration;
+/// use tokn_server::Shutdown;
+///
+/// let shutdown = Shutdown::on_signals(Duration::from_secs(30))?;
+/// tokn_server::serve(app, &bind, None, None, &shutdown).await?;
+/// // Listeners have drained: close pools and connections here
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Shutdown {
+    // ---
+    triggered: Arc<watch::Sender<bool>>,
+    drain_timeout: Duration,
+}
+
+impl Shutdown {
+    // ---
+    /// A shutdown that begins only when [`Shutdown::trigger`] is called.
+    pub fn new(drain_timeout: Duration) -> Self {
+        // ---
+        Self {
+            triggered: Arc::new(watch::Sender::new(false)),
+            drain_timeout,
+        }
+    }
+
+    // ---
+    /// A shutdown that begins on `SIGTERM` or `SIGINT` (Ctrl-C; the only
+    /// signal on non-Unix platforms).
+    ///
+    /// Must be called within a Tokio runtime.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the signal handlers cannot be installed.
+    pub fn on_signals(drain_timeout: Duration) -> Result<Self> {
+        // ---
+        let shutdown = Self::new(drain_timeout);
+
+        #[cfg(unix)]
+        let mut terminate = {
+            use anyhow::Context;
+            use tokio::signal::unix::{signal, SignalKind};
+
+            signal(SignalKind::terminate()).context("Failed to install SIGTERM handler")?
+        };
+
+        let trigger = shutdown.clone();
+        tokio::spawn(async move {
+            // ---
+            #[cfg(unix)]
+            let signal = tokio::select! {
+                _ = terminate.recv() => "SIGTERM",
+                _ = tokio::signal::ctrl_c() => "SIGINT",
+            };
+            #[cfg(not(unix))]
+            let signal = match tokio::signal::ctrl_c().await {
+                Ok(()) => "Ctrl-C",
+                Err(e) => {
+                    tracing::error!("Failed to listen for Ctrl-C: {e}");
+                    return;
+                }
+            };
+
+            tracing::info!(
+                "{signal} received, draining in-flight requests (up to {}s)",
+                trigger.drain_timeout.as_secs()
+            );
+            trigger.trigger();
+        });
+
+        Ok(shutdown)
+    }
+
+    // ---
+    /// Begin shutting down.
+    pub fn trigger(&self) {
+        // ---
+        self.triggered.send_replace(true);
+    }
+
+    // ---
+    /// Whether shutdown has begun.
+    pub fn is_triggered(&self) -> bool {
+        // ---
+        *self.triggered.borrow()
+    }
+
+    // ---
+    /// Time in-flight requests get to finish once shutdown begins.
+    pub fn drain_timeout(&self) -> Duration {
+        // ---
+        self.drain_timeout
+    }
+
+    // ---
+    /// Resolve once shutdown has begun; pass to a server's graceful shutdown
+    /// hook so it stops accepting connections.
+    pub async fn signalled(&self) {
+        // ---
+        let mut triggered = self.triggered.subscribe();
+        // The sender lives as long as `self`, so this only returns on trigger
+        let _ = triggered.wait_for(|triggered| *triggered).await;
+    }
+
+    // ---
+    /// Run `server` (already wired to stop accepting on [`Shutdown::signalled`])
+    /// to completion, allowing it at most the drain timeout once shutdown has
+    /// begun.
+    ///
+    /// # Errors
+    ///
+    /// Returns the server's error if it fails; running out of drain time is
+    /// logged, not an error.
+    pub async fn drain<F, E>(&self, server: F) -> Result<()>
+    where
+        F: IntoFuture<Output = Result<(), E>>,
+        E: Into<anyhow::Error>,
+    {
+        // ---
+        let server = server.into_future();
+        tokio::pin!(server);
+
+        tokio::select! {
+            result = &mut server => return result.map_err(Into::into),
+            () = self.signalled() => {}
+        }
+
+        match tokio::time::timeout(self.drain_timeout, server).await {
+            Ok(result) => result.map_err(Into::into),
+            Err(_) => {
+                tracing::warn!(
+                    "Drain timeout ({}s) elapsed; closing remaining connections",
+                    self.drain_timeout.as_secs()
+                );
+                Ok(())
+            }
+        }
+    }
+}
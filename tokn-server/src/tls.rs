@@ -0,0 +1,107 @@
+// tokn-server/src/tls.rs
+
+use anyhow::{Context, Result};
+use axum_server::tls_rustls::RustlsConfig;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+// ---
+
+use crate::Bind;
+
+// ---
+
+/// PEM certificate chain and private key for native TLS serving.
+///
+/// # Security
+///
+/// - The key file should be readable only by the service user
+/// - Send `SIGHUP` after rotating the files; the new pair is loaded without a
+///   restart and existing connections are unaffected
+/// - If a reload fails (missing file, bad PEM, mismatched key) the previous
+///   certificate stays in service and the error is logged
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct TlsConfig {
+    // ---
+    /// PEM certificate chain (leaf first)
+    pub cert_path: PathBuf,
+
+    /// PEM private key (PKCS#8, PKCS#1, or SEC1)
+    pub key_path: PathBuf,
+}
+
+// ---
+
+/// Production config rule for a service's listener: it must serve HTTPS
+/// itself, or listen on a Unix socket behind a local TLS-terminating proxy.
+///
+/// # Errors
+///
+/// Returns a message for `tokn_config::ConfigLoader::prod_rule` when `tls` is
+/// unset and `bind` is not a Unix socket.
+pub fn require_tls(tls: Option<&TlsConfig>, bind: Option<&Bind>) -> Result<(), String> {
+    // ---
+    match (tls, bind) {
+        (Some(_), _) | (None, Some(Bind::Unix(_))) => Ok(()),
+        _ => Err(
+            "must serve HTTPS (set the TLS certificate and key) or bind a Unix socket behind a TLS-terminating proxy"
+                .to_string(),
+        ),
+    }
+}
+
+// ---
+
+impl TlsConfig {
+    // ---
+    /// Load the certificate and key into a reloadable rustls configuration.
+    pub(crate) async fn load(&self) -> Result<RustlsConfig> {
+        // ---
+        RustlsConfig::from_pem_file(&self.cert_path, &self.key_path)
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to load TLS certificate {} / key {}",
+                    self.cert_path.display(),
+                    self.key_path.display()
+                )
+            })
+    }
+
+    /// Reload the certificate and key from disk each time the process receives
+    /// `SIGHUP`. No-op on non-Unix platforms.
+    pub(crate) fn reload_on_sighup(&self, rustls: RustlsConfig) -> Result<()> {
+        // ---
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+
+            let mut hangup =
+                signal(SignalKind::hangup()).context("Failed to install SIGHUP handler")?;
+            let tls = self.clone();
+
+            tokio::spawn(async move {
+                // ---
+                while hangup.recv().await.is_some() {
+                    match rustls
+                        .reload_from_pem_file(&tls.cert_path, &tls.key_path)
+                        .await
+                    {
+                        Ok(()) => tracing::info!(
+                            "Reloaded TLS certificate from {}",
+                            tls.cert_path.display()
+                        ),
+                        Err(e) => {
+                            tracing::error!("TLS reload failed, keeping previous certificate: {e}")
+                        }
+                    }
+                }
+            });
+        }
+
+        #[cfg(not(unix))]
+        let _ = rustls;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,74 @@
+// tokn-server/src/unix.rs
+
+use anyhow::Result;
+use axum::Router;
+use std::path::Path;
+
+// ---
+
+use crate::{Shutdown, SocketMode};
+
+// ---
+
+/// Serve plain HTTP on a Unix domain socket.
+///
+/// A leftover socket file from a previous run is removed, but only if nothing
+/// is accepting connections on it, so two instances cannot silently steal the
+/// same path.
+#[cfg(unix)]
+pub(crate) async fn serve(
+    app: Router,
+    path: &Path,
+    mode: Option<SocketMode>,
+    shutdown: &Shutdown,
+) -> Result<()> {
+    // ---
+    use anyhow::Context;
+    use std::os::unix::fs::PermissionsExt;
+    use tokio::net::{UnixListener, UnixStream};
+
+    if path.exists() {
+        anyhow::ensure!(
+            UnixStream::connect(path).await.is_err(),
+            "Unix socket {} is already in use",
+            path.display()
+        );
+        std::fs::remove_file(path)
+            .with_context(|| format!("Failed to remove stale socket {}", path.display()))?;
+    }
+
+    let listener = UnixListener::bind(path)
+        .with_context(|| format!("Failed to bind Unix socket {}", path.display()))?;
+
+    // ---
+    if let Some(SocketMode(mode)) = mode {
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+            .with_context(|| format!("Failed to set mode {mode:o} on {}", path.display()))?;
+    }
+
+    tracing::info!("Listening on unix:{}", path.display());
+    let signal = shutdown.clone();
+    let server =
+        axum::serve(listener, app).with_graceful_shutdown(async move { signal.signalled().await });
+    shutdown.drain(server).await?;
+
+    // The socket file outlives the listener; leave nothing stale behind
+    let _ = std::fs::remove_file(path);
+    Ok(())
+}
+
+// ---
+
+#[cfg(not(unix))]
+pub(crate) async fn serve(
+    _app: Router,
+    path: &Path,
+    _mode: Option<SocketMode>,
+    _shutdown: &Shutdown,
+) -> Result<()> {
+    // ---
+    anyhow::bail!(
+        "Unix sockets are not supported on this platform ({})",
+        path.display()
+    )
+}
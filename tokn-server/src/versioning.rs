@@ -0,0 +1,92 @@
+// tokn-server/src/versioning.rs
+
+use axum::{
+    extract::Request,
+    http::HeaderValue,
+    middleware::{self, Next},
+    response::Response,
+    Router,
+};
+use serde::Deserialize;
+
+// ---
+
+/// Path prefix of the current public API version.
+pub const API_VERSION_PREFIX: &str = "/v1";
+
+// ---
+
+/// Public API routing settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ApiConfig {
+    // ---
+    /// Also serve the API at its unversioned paths (`/auth/...`, `/oauth/...`),
+    /// marked deprecated (env `API_LEGACY_PATHS`, default `true`)
+    pub legacy_paths: bool,
+}
+
+// ---
+
+impl Default for ApiConfig {
+    // ---
+    fn default() -> Self {
+        // ---
+        Self { legacy_paths: true }
+    }
+}
+
+// ---
+
+/// Mount `api` under [`API_VERSION_PREFIX`], keeping the old paths as a
+/// deprecated alias while `legacy_paths` is on.
+///
+/// Requests to a legacy path are handled exactly as before, but every response
+/// carries `Deprecation: true` and a `Link: </v1/...>; rel="successor-version"`
+/// header pointing at the versioned path, so integrators can find and migrate
+/// their remaining calls before `API_LEGACY_PATHS=false` removes them.
+///
+/// Only the versioned API belongs in `api`; service banners, `/health`, and
+/// `/admin` stay unversioned.
+///
+/// # Example
+///
+/// ```
+/// use axum::{routing::get, Router};
+/// use tokn_server::ApiConfig;
+///
+/// let api: Router = Router::new().route("/auth/ping", get(|| async { "pong" }));
+/// // Serves /v1/auth/ping, plus /auth/ping with deprecation headers
+/// let app = tokn_server::versioned(api, &ApiConfig::default());
+/// ```
+pub fn versioned<S>(api: Router<S>, config: &ApiConfig) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    // ---
+    let router = Router::new().nest(API_VERSION_PREFIX, api.clone());
+
+    if config.legacy_paths {
+        router.merge(api.layer(middleware::from_fn(mark_deprecated)))
+    } else {
+        router
+    }
+}
+
+// ---
+
+async fn mark_deprecated(req: Request, next: Next) -> Response {
+    // ---
+    let path = req.uri().path().to_string();
+    tracing::debug!("Deprecated unversioned API path used: {path}");
+
+    let mut response = next.run(req).await;
+    let headers = response.headers_mut();
+    headers.insert("deprecation", HeaderValue::from_static("true"));
+    let successor = format!("<{API_VERSION_PREFIX}{path}>; rel=\"successor-version\"");
+    if let Ok(link) = HeaderValue::from_str(&successor) {
+        headers.append(axum::http::header::LINK, link);
+    }
+
+    response
+}
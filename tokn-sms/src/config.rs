@@ -0,0 +1,133 @@
+// tokn-sms/src/config.rs
+
+use serde::Deserialize;
+use std::fmt;
+use tokn_config::Secret;
+
+// ---
+
+/// Where outgoing text messages are delivered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmsBackend {
+    // ---
+    /// Messages are written to the log instead of being sent (default)
+    #[default]
+    Console,
+
+    /// Twilio Programmable Messaging
+    Twilio,
+}
+
+impl fmt::Display for SmsBackend {
+    // ---
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // ---
+        f.write_str(match self {
+            SmsBackend::Console => "console",
+            SmsBackend::Twilio => "twilio",
+        })
+    }
+}
+
+// ---
+
+/// Service configuration section for SMS delivery and one-time codes.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize)]
+#[serde(default)]
+pub struct SmsConfig {
+    // ---
+    /// Delivery backend (env `SMS_BACKEND`: `console` or `twilio`)
+    pub backend: SmsBackend,
+
+    /// Twilio account SID (env `SMS_TWILIO_ACCOUNT_SID`)
+    pub twilio_account_sid: Option<String>,
+
+    /// Twilio auth token (env `SMS_TWILIO_AUTH_TOKEN`)
+    pub twilio_auth_token: Option<Secret>,
+
+    /// Sending number in E.164 form, or a messaging service SID (`MG...`)
+    /// (env `SMS_TWILIO_FROM`)
+    pub twilio_from: Option<String>,
+
+    /// Code length, lifetime, and rate limits
+    pub otp: OtpPolicy,
+}
+
+// ---
+
+/// How one-time codes are issued and checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct OtpPolicy {
+    // ---
+    /// Digits per code, 4 to 10 (default: 6)
+    pub digits: u32,
+
+    /// Seconds a code stays valid (env `OTP_TTL_SECONDS`, default: 300)
+    pub ttl_seconds: u64,
+
+    /// Wrong guesses allowed before the code is burned
+    /// (env `OTP_MAX_ATTEMPTS`, default: 5)
+    pub max_attempts: u32,
+
+    /// Seconds before another code can be sent to the same number
+    /// (env `OTP_RESEND_SECONDS`, default: 30)
+    pub resend_seconds: u64,
+
+    /// Codes sent to one number per rolling hour
+    /// (env `OTP_MAX_SENDS_PER_HOUR`, default: 5)
+    pub max_sends_per_hour: u32,
+}
+
+impl Default for OtpPolicy {
+    // ---
+    fn default() -> Self {
+        // ---
+        Self {
+            digits: 6,
+            ttl_seconds: 300,
+            max_attempts: 5,
+            resend_seconds: 30,
+            max_sends_per_hour: 5,
+        }
+    }
+}
+
+// ---
+
+/// Config rule for the `sms` section: the `twilio` backend needs its
+/// credentials, and the code policy must be usable.
+///
+/// # Errors
+///
+/// Returns a message for `tokn_config::ConfigLoader::rule` naming the first
+/// missing Twilio setting or out-of-range policy value.
+pub fn validate_sms_config(config: &SmsConfig) -> Result<(), String> {
+    // ---
+    if config.backend == SmsBackend::Twilio {
+        let missing = [
+            (
+                "SMS_TWILIO_ACCOUNT_SID",
+                config.twilio_account_sid.is_none(),
+            ),
+            ("SMS_TWILIO_AUTH_TOKEN", config.twilio_auth_token.is_none()),
+            ("SMS_TWILIO_FROM", config.twilio_from.is_none()),
+        ];
+        if let Some((env, _)) = missing.iter().find(|(_, missing)| *missing) {
+            return Err(format!("backend 'twilio' requires {env}"));
+        }
+    }
+
+    let otp = &config.otp;
+    if !(4..=10).contains(&otp.digits) {
+        return Err("otp.digits must be between 4 and 10".to_string());
+    }
+    if otp.ttl_seconds == 0 || otp.max_attempts == 0 || otp.max_sends_per_hour == 0 {
+        return Err(
+            "OTP_TTL_SECONDS, OTP_MAX_ATTEMPTS, and OTP_MAX_SENDS_PER_HOUR must be positive"
+                .to_string(),
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,79 @@
+// tokn-sms/src/error.rs
+
+use crate::SmsBackend;
+
+// ---
+
+/// Errors delivering a text message.
+#[derive(Debug, thiserror::Error)]
+pub enum SmsError {
+    // ---
+    /// The backend was compiled out (see the crate's `twilio` feature)
+    #[error("sms backend '{0}' is not enabled in this build")]
+    Unsupported(SmsBackend),
+
+    /// A required provider setting is missing
+    #[error("sms backend '{backend}' requires {env}")]
+    MissingSetting {
+        backend: SmsBackend,
+        env: &'static str,
+    },
+
+    /// The provider refused the message (unreachable or invalid number,
+    /// bad credentials)
+    #[error("{backend} rejected the message: {message}")]
+    Rejected {
+        backend: SmsBackend,
+        message: String,
+    },
+
+    /// The provider could not be reached or failed temporarily
+    #[error("{backend} error: {message}")]
+    Transport {
+        backend: SmsBackend,
+        message: String,
+    },
+}
+
+// ---
+
+/// Why a one-time code was not sent or not accepted.
+#[derive(Debug, thiserror::Error)]
+pub enum OtpError {
+    // ---
+    /// The phone number is not in E.164 form (`+` and 8 to 15 digits)
+    #[error("invalid phone number")]
+    InvalidPhoneNumber,
+
+    /// A code was sent to this number too recently
+    #[error("a code was sent recently; retry in {retry_after} seconds")]
+    ResendTooSoon { retry_after: u64 },
+
+    /// This number has been sent its hourly allowance of codes
+    #[error("too many codes sent to this number; retry in {retry_after} seconds")]
+    RateLimited { retry_after: u64 },
+
+    /// No code is outstanding for this number (never sent, or already used)
+    #[error("no pending code for this number")]
+    NoPendingCode,
+
+    /// The code's lifetime has passed
+    #[error("code has expired")]
+    Expired,
+
+    /// Too many wrong guesses; a new code must be requested
+    #[error("too many failed attempts")]
+    TooManyAttempts,
+
+    /// The code does not match
+    #[error("incorrect code")]
+    Mismatch,
+
+    /// The message could not be delivered
+    #[error(transparent)]
+    Send(#[from] SmsError),
+
+    /// The code store failed
+    #[error("otp store error: {0}")]
+    Store(String),
+}
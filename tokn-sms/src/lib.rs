@@ -0,0 +1,37 @@
+// tokn-sms/src/lib.rs
+
+//! SMS delivery and one-time codes for tokn services
+//!
+//! Phone verification and SMS-based MFA hand a [`PhoneNumber`] to an
+//! [`Otp`] handle, which sends a code through the configured
+//! [`SmsSender`] and later checks what the user typed back:
+//!
+//! - [`ConsoleSender`]: writes messages to the log (the default, for
+//!   development)
+//! - [`TwilioSender`]: Twilio Programmable Messaging (`twilio` feature)
+//!
+//! Code expiry, the wrong-guess limit, the resend interval, and the hourly
+//! send cap per number come from [`OtpPolicy`] and are enforced by [`Otp`]
+//! itself, not by each caller. Codes are stored only as hashes, in an
+//! [`OtpStore`] the service provides ([`MemoryOtpStore`] for one instance).
+//!
+//! Sends are counted in `tokn_otp_sent_total` / `tokn_otp_send_failed_total`
+//! by backend, checks in `tokn_otp_verified_total` by result.
+
+mod config;
+mod error;
+mod otp;
+mod phone;
+mod sender;
+#[cfg(feature = "twilio")]
+mod twilio;
+
+// ---
+
+pub use config::{validate_sms_config, OtpPolicy, SmsBackend, SmsConfig};
+pub use error::{OtpError, SmsError};
+pub use otp::{MemoryOtpStore, Otp, OtpRecord, OtpStore};
+pub use phone::PhoneNumber;
+pub use sender::{ConsoleSender, SmsSender};
+#[cfg(feature = "twilio")]
+pub use twilio::TwilioSender;
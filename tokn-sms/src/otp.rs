@@ -0,0 +1,451 @@
+// tokn-sms/src/otp.rs
+
+use chrono::{DateTime, Duration, Utc};
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use tokn_core::{constant_time_eq, SharedClock, SystemClock};
+
+// ---
+
+use crate::{
+    ConsoleSender, OtpError, OtpPolicy, PhoneNumber, SmsBackend, SmsConfig, SmsError, SmsSender,
+};
+
+// ---
+
+/// Length of the window `max_sends_per_hour` applies to, in seconds.
+const SEND_WINDOW_SECONDS: i64 = 3600;
+
+// ---
+
+/// State kept per phone number between sending a code and checking it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OtpRecord {
+    // ---
+    /// SHA-256 of the outstanding code (hex); `None` once it has been used
+    pub code_hash: Option<String>,
+
+    /// When the outstanding code stops being accepted
+    pub expires_at: DateTime<Utc>,
+
+    /// Wrong guesses against the outstanding code
+    pub attempts: u32,
+
+    /// When the last code was sent
+    pub last_sent_at: DateTime<Utc>,
+
+    /// Start of the current hourly send window
+    pub window_started_at: DateTime<Utc>,
+
+    /// Codes sent in the current window
+    pub sends_in_window: u32,
+}
+
+// ---
+
+/// Where [`OtpRecord`]s are kept.
+///
+/// [`MemoryOtpStore`] suits a single instance and tests; a service running
+/// several replicas keeps records in its database so a code sent by one
+/// replica can be checked by another.
+pub trait OtpStore: Send + Sync + 'static {
+    // ---
+    /// The record for `phone`, if any.
+    fn load(
+        &self,
+        phone: &PhoneNumber,
+    ) -> impl Future<Output = Result<Option<OtpRecord>, OtpError>> + Send;
+
+    /// Replace the record for `phone`.
+    fn save(
+        &self,
+        phone: &PhoneNumber,
+        record: &OtpRecord,
+    ) -> impl Future<Output = Result<(), OtpError>> + Send;
+}
+
+// ---
+
+/// In-process [`OtpStore`]; clones share the same records.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryOtpStore {
+    // ---
+    records: Arc<Mutex<HashMap<PhoneNumber, OtpRecord>>>,
+}
+
+impl MemoryOtpStore {
+    // ---
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<PhoneNumber, OtpRecord>> {
+        // ---
+        self.records.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl OtpStore for MemoryOtpStore {
+    // ---
+    async fn load(&self, phone: &PhoneNumber) -> Result<Option<OtpRecord>, OtpError> {
+        // ---
+        Ok(self.lock().get(phone).cloned())
+    }
+
+    async fn save(&self, phone: &PhoneNumber, record: &OtpRecord) -> Result<(), OtpError> {
+        // ---
+        self.lock().insert(phone.clone(), record.clone());
+        Ok(())
+    }
+}
+
+// ---
+
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Object-safe adapters over [`SmsSender`] and [`OtpStore`], so [`Otp`] is
+/// not generic.
+trait DynSender: Send + Sync {
+    // ---
+    fn send_boxed<'a>(
+        &'a self,
+        to: &'a PhoneNumber,
+        body: &'a str,
+    ) -> BoxFuture<'a, Result<(), SmsError>>;
+}
+
+impl<S: SmsSender> DynSender for S {
+    // ---
+    fn send_boxed<'a>(
+        &'a self,
+        to: &'a PhoneNumber,
+        body: &'a str,
+    ) -> BoxFuture<'a, Result<(), SmsError>> {
+        // ---
+        Box::pin(self.send(to, body))
+    }
+}
+
+trait DynStore: Send + Sync {
+    // ---
+    fn load_boxed<'a>(
+        &'a self,
+        phone: &'a PhoneNumber,
+    ) -> BoxFuture<'a, Result<Option<OtpRecord>, OtpError>>;
+
+    fn save_boxed<'a>(
+        &'a self,
+        phone: &'a PhoneNumber,
+        record: &'a OtpRecord,
+    ) -> BoxFuture<'a, Result<(), OtpError>>;
+}
+
+impl<T: OtpStore> DynStore for T {
+    // ---
+    fn load_boxed<'a>(
+        &'a self,
+        phone: &'a PhoneNumber,
+    ) -> BoxFuture<'a, Result<Option<OtpRecord>, OtpError>> {
+        // ---
+        Box::pin(self.load(phone))
+    }
+
+    fn save_boxed<'a>(
+        &'a self,
+        phone: &'a PhoneNumber,
+        record: &'a OtpRecord,
+    ) -> BoxFuture<'a, Result<(), OtpError>> {
+        // ---
+        Box::pin(self.save(phone, record))
+    }
+}
+
+// ---
+
+/// Issues and checks one-time codes sent by SMS; cheap to clone into
+/// handler state.
+///
+/// Expiry, the wrong-guess limit, the resend interval, and the hourly send
+/// cap are all enforced here from the [`OtpPolicy`], so every caller gets
+/// the same protection against guessing and SMS pumping. Only a hash of each
+/// code is stored, and a code is consumed by its first successful check.
+///
+/// The default handle logs codes to the console and keeps records in memory.
+///
+/// # Example
+///
+/// ```
+/// # async fn example() -> Result<(), tokn_sms::OtpError> {
+/// use tokn_sms::{Otp, PhoneNumber};
+///
+/// let otp = Otp::default();
+/// let phone: PhoneNumber = "+14155550123".parse()?;
+/// otp.send_code(&phone).await?;
+/// assert!(otp.verify(&phone, "not-the-code").await.is_err());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Otp {
+    // ---
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    // ---
+    backend: String,
+    sender: Box<dyn DynSender>,
+    store: Box<dyn DynStore>,
+    policy: OtpPolicy,
+    clock: SharedClock,
+}
+
+// ---
+
+impl Default for Otp {
+    // ---
+    fn default() -> Self {
+        // ---
+        Self::new(
+            ConsoleSender,
+            SmsBackend::Console,
+            MemoryOtpStore::default(),
+            OtpPolicy::default(),
+            SystemClock::shared(),
+        )
+    }
+}
+
+// ---
+
+impl Otp {
+    // ---
+    /// Send through the backend selected by `config`, keeping records in
+    /// `store`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Twilio settings are incomplete or the backend
+    /// is compiled out.
+    pub fn connect<T: OtpStore>(
+        config: &SmsConfig,
+        store: T,
+        clock: SharedClock,
+    ) -> Result<Self, SmsError> {
+        // ---
+        match config.backend {
+            SmsBackend::Console => Ok(Self::new(
+                ConsoleSender,
+                SmsBackend::Console,
+                store,
+                config.otp,
+                clock,
+            )),
+            #[cfg(feature = "twilio")]
+            SmsBackend::Twilio => Ok(Self::new(
+                crate::TwilioSender::new(config)?,
+                SmsBackend::Twilio,
+                store,
+                config.otp,
+                clock,
+            )),
+            #[allow(unreachable_patterns)]
+            backend => Err(SmsError::Unsupported(backend)),
+        }
+    }
+
+    // ---
+    /// Send through `sender` and keep records in `store`. `backend` names
+    /// the sender in logs and metrics.
+    pub fn new<S: SmsSender, T: OtpStore>(
+        sender: S,
+        backend: impl ToString,
+        store: T,
+        policy: OtpPolicy,
+        clock: SharedClock,
+    ) -> Self {
+        // ---
+        Self {
+            inner: Arc::new(Inner {
+                backend: backend.to_string(),
+                sender: Box::new(sender),
+                store: Box::new(store),
+                policy,
+                clock,
+            }),
+        }
+    }
+
+    /// The policy codes are issued under.
+    pub fn policy(&self) -> &OtpPolicy {
+        // ---
+        &self.inner.policy
+    }
+
+    // ---
+    /// Send a new code to `phone`, replacing any outstanding one.
+    ///
+    /// The send counts against the resend interval and hourly cap even if
+    /// delivery then fails, so a failing provider cannot be retried in a
+    /// tight loop.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OtpError::ResendTooSoon`] or [`OtpError::RateLimited`] when
+    /// a limit applies, or the store or delivery failure.
+    pub async fn send_code(&self, phone: &PhoneNumber) -> Result<(), OtpError> {
+        // ---
+        let inner = &self.inner;
+        let policy = &inner.policy;
+        let now = inner.clock.now();
+
+        let (window_started_at, sends_in_window) = match inner.store.load_boxed(phone).await? {
+            Some(previous) => {
+                let resend_at =
+                    previous.last_sent_at + Duration::seconds(policy.resend_seconds as i64);
+                if now < resend_at {
+                    return Err(OtpError::ResendTooSoon {
+                        retry_after: seconds_until(now, resend_at),
+                    });
+                }
+
+                let window_ends_at =
+                    previous.window_started_at + Duration::seconds(SEND_WINDOW_SECONDS);
+                if now >= window_ends_at {
+                    (now, 1)
+                } else if previous.sends_in_window >= policy.max_sends_per_hour {
+                    metrics::counter!("tokn_otp_rate_limited_total").increment(1);
+                    return Err(OtpError::RateLimited {
+                        retry_after: seconds_until(now, window_ends_at),
+                    });
+                } else {
+                    (previous.window_started_at, previous.sends_in_window + 1)
+                }
+            }
+            None => (now, 1),
+        };
+
+        let code = generate_code(policy.digits);
+        let record = OtpRecord {
+            code_hash: Some(hash_code(phone, &code)),
+            expires_at: now + Duration::seconds(policy.ttl_seconds as i64),
+            attempts: 0,
+            last_sent_at: now,
+            window_started_at,
+            sends_in_window,
+        };
+        inner.store.save_boxed(phone, &record).await?;
+
+        let body = format!(
+            "Your verification code is {code}. It expires in {}.",
+            describe_ttl(policy.ttl_seconds)
+        );
+        let backend = inner.backend.clone();
+        match inner.sender.send_boxed(phone, &body).await {
+            Ok(()) => {
+                metrics::counter!("tokn_otp_sent_total", "backend" => backend).increment(1);
+                Ok(())
+            }
+            Err(e) => {
+                metrics::counter!("tokn_otp_send_failed_total", "backend" => backend).increment(1);
+                tracing::warn!(
+                    "Failed to send code to {} via {}: {e}",
+                    phone.masked(),
+                    inner.backend
+                );
+                Err(e.into())
+            }
+        }
+    }
+
+    // ---
+    /// Check `code` against the one outstanding for `phone`, consuming it on
+    /// success.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OtpError::NoPendingCode`], [`OtpError::Expired`],
+    /// [`OtpError::TooManyAttempts`], or [`OtpError::Mismatch`] when the code
+    /// is not accepted, or the store failure.
+    pub async fn verify(&self, phone: &PhoneNumber, code: &str) -> Result<(), OtpError> {
+        // ---
+        let result = self.check(phone, code).await;
+
+        let outcome = match &result {
+            Ok(()) => "ok",
+            Err(OtpError::NoPendingCode) => "no_pending_code",
+            Err(OtpError::Expired) => "expired",
+            Err(OtpError::TooManyAttempts) => "too_many_attempts",
+            Err(OtpError::Mismatch) => "mismatch",
+            Err(_) => "error",
+        };
+        metrics::counter!("tokn_otp_verified_total", "result" => outcome).increment(1);
+        result
+    }
+
+    async fn check(&self, phone: &PhoneNumber, code: &str) -> Result<(), OtpError> {
+        // ---
+        let inner = &self.inner;
+        let now = inner.clock.now();
+
+        let mut record = inner
+            .store
+            .load_boxed(phone)
+            .await?
+            .ok_or(OtpError::NoPendingCode)?;
+        let Some(expected) = record.code_hash.clone() else {
+            return Err(OtpError::NoPendingCode);
+        };
+        if now >= record.expires_at {
+            return Err(OtpError::Expired);
+        }
+        if record.attempts >= inner.policy.max_attempts {
+            return Err(OtpError::TooManyAttempts);
+        }
+
+        let presented = hash_code(phone, code.trim());
+        if constant_time_eq(presented.as_bytes(), expected.as_bytes()) {
+            record.code_hash = None;
+            inner.store.save_boxed(phone, &record).await?;
+            return Ok(());
+        }
+
+        record.attempts += 1;
+        inner.store.save_boxed(phone, &record).await?;
+        if record.attempts >= inner.policy.max_attempts {
+            return Err(OtpError::TooManyAttempts);
+        }
+        Err(OtpError::Mismatch)
+    }
+}
+
+// ---
+
+/// Uniformly random code of `digits` decimal digits, zero-padded.
+fn generate_code(digits: u32) -> String {
+    // ---
+    let n = rand::thread_rng().gen_range(0..10u64.pow(digits));
+    format!("{n:0width$}", width = digits as usize)
+}
+
+/// Hash binding a code to the number it was sent to.
+fn hash_code(phone: &PhoneNumber, code: &str) -> String {
+    // ---
+    hex::encode(Sha256::digest(format!("{phone}:{code}")))
+}
+
+/// Whole seconds from `now` until `then`, rounded up.
+fn seconds_until(now: DateTime<Utc>, then: DateTime<Utc>) -> u64 {
+    // ---
+    let millis = (then - now).num_milliseconds().max(0) as u64;
+    millis.div_ceil(1000)
+}
+
+fn describe_ttl(seconds: u64) -> String {
+    // ---
+    match seconds {
+        s if s % 60 == 0 && s >= 120 => format!("{} minutes", s / 60),
+        60 => "1 minute".to_string(),
+        s => format!("{s} seconds"),
+    }
+}
@@ -0,0 +1,86 @@
+// tokn-sms/src/phone.rs
+
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+// ---
+
+use crate::OtpError;
+
+// ---
+
+/// A phone number in E.164 form (`+14155550123`).
+///
+/// Parsing drops the spaces, dashes, dots, and parentheses people type, then
+/// requires a `+`, a non-zero country code digit, and 8 to 15 digits in all.
+///
+/// # Example
+///
+/// ```
+/// use tokn_sms::PhoneNumber;
+///
+/// let phone: PhoneNumber = "+1 (415) 555-0123".parse().unwrap();
+/// assert_eq!(phone.as_str(), "+14155550123");
+/// assert!("4155550123".parse::<PhoneNumber>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct PhoneNumber(String);
+
+impl PhoneNumber {
+    // ---
+    /// The number as `+` followed by digits.
+    pub fn as_str(&self) -> &str {
+        // ---
+        &self.0
+    }
+
+    /// The number with all but the last two digits masked (`+*********23`),
+    /// for logs.
+    pub fn masked(&self) -> String {
+        // ---
+        let digits = self.0.len() - 1;
+        format!("+{}{}", "*".repeat(digits - 2), &self.0[self.0.len() - 2..])
+    }
+}
+
+impl FromStr for PhoneNumber {
+    // ---
+    type Err = OtpError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        // ---
+        let compact: String = value
+            .chars()
+            .filter(|c| !matches!(c, ' ' | '-' | '.' | '(' | ')'))
+            .collect();
+        let digits = compact
+            .strip_prefix('+')
+            .ok_or(OtpError::InvalidPhoneNumber)?;
+
+        let valid = (8..=15).contains(&digits.len())
+            && digits.bytes().all(|b| b.is_ascii_digit())
+            && !digits.starts_with('0');
+        if !valid {
+            return Err(OtpError::InvalidPhoneNumber);
+        }
+        Ok(Self(compact))
+    }
+}
+
+impl fmt::Display for PhoneNumber {
+    // ---
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // ---
+        f.write_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for PhoneNumber {
+    // ---
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // ---
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
@@ -0,0 +1,42 @@
+// tokn-sms/src/sender.rs
+
+use std::future::Future;
+
+// ---
+
+use crate::{PhoneNumber, SmsError};
+
+// ---
+
+/// A provider that text messages are sent through.
+///
+/// Implemented by [`ConsoleSender`] and [`TwilioSender`](crate::TwilioSender);
+/// implement it to use another provider (or to capture messages in tests)
+/// and hand it to [`Otp::new`](crate::Otp::new).
+pub trait SmsSender: Send + Sync + 'static {
+    // ---
+    /// Deliver `body` to `to`.
+    fn send(
+        &self,
+        to: &PhoneNumber,
+        body: &str,
+    ) -> impl Future<Output = Result<(), SmsError>> + Send;
+}
+
+// ---
+
+/// Writes each message to the log at `info` instead of sending it.
+///
+/// The development default (`SMS_BACKEND=console`): codes can be read from
+/// the service output.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConsoleSender;
+
+impl SmsSender for ConsoleSender {
+    // ---
+    async fn send(&self, to: &PhoneNumber, body: &str) -> Result<(), SmsError> {
+        // ---
+        tracing::info!(to = %to, "SMS not sent (SMS_BACKEND=console): {body}");
+        Ok(())
+    }
+}
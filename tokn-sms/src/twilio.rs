@@ -0,0 +1,126 @@
+// tokn-sms/src/twilio.rs
+
+use reqwest::StatusCode;
+use std::time::Duration;
+use tokn_config::Secret;
+
+// ---
+
+use crate::{PhoneNumber, SmsBackend, SmsConfig, SmsError, SmsSender};
+
+// ---
+
+/// Twilio REST API base URL.
+const TWILIO_API: &str = "https://api.twilio.com/2010-04-01";
+
+/// Upper bound on one send request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+// ---
+
+/// Sends text messages through Twilio Programmable Messaging.
+pub struct TwilioSender {
+    // ---
+    http: reqwest::Client,
+    account_sid: String,
+    auth_token: Secret,
+    from: String,
+}
+
+// ---
+
+impl TwilioSender {
+    // ---
+    /// Build a sender from the `SMS_TWILIO_*` settings in `config`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SmsError::MissingSetting`] if any of them is unset.
+    pub fn new(config: &SmsConfig) -> Result<Self, SmsError> {
+        // ---
+        let missing = |env| SmsError::MissingSetting {
+            backend: SmsBackend::Twilio,
+            env,
+        };
+        let http = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| transport_error(e.to_string()))?;
+
+        Ok(Self {
+            http,
+            account_sid: config
+                .twilio_account_sid
+                .clone()
+                .ok_or_else(|| missing("SMS_TWILIO_ACCOUNT_SID"))?,
+            auth_token: config
+                .twilio_auth_token
+                .clone()
+                .ok_or_else(|| missing("SMS_TWILIO_AUTH_TOKEN"))?,
+            from: config
+                .twilio_from
+                .clone()
+                .ok_or_else(|| missing("SMS_TWILIO_FROM"))?,
+        })
+    }
+}
+
+// ---
+
+impl SmsSender for TwilioSender {
+    // ---
+    async fn send(&self, to: &PhoneNumber, body: &str) -> Result<(), SmsError> {
+        // ---
+        let sender = if self.from.starts_with("MG") {
+            "MessagingServiceSid"
+        } else {
+            "From"
+        };
+        let url = format!("{TWILIO_API}/Accounts/{}/Messages.json", self.account_sid);
+
+        let response = self
+            .http
+            .post(url)
+            .basic_auth(&self.account_sid, Some(self.auth_token.expose()))
+            .form(&[("To", to.as_str()), (sender, &self.from), ("Body", body)])
+            .send()
+            .await
+            .map_err(|e| transport_error(e.without_url().to_string()))?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+
+        // Twilio explains rejections in a JSON `message` field
+        let message = response
+            .json::<TwilioError>()
+            .await
+            .map(|body| body.message)
+            .unwrap_or_else(|_| status.to_string());
+        if status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+            return Err(transport_error(message));
+        }
+        Err(SmsError::Rejected {
+            backend: SmsBackend::Twilio,
+            message,
+        })
+    }
+}
+
+// ---
+
+/// Error body of a failed Twilio request.
+#[derive(serde::Deserialize)]
+struct TwilioError {
+    // ---
+    message: String,
+}
+
+fn transport_error(message: String) -> SmsError {
+    // ---
+    SmsError::Transport {
+        backend: SmsBackend::Twilio,
+        message,
+    }
+}
@@ -0,0 +1,140 @@
+// tokn-telemetry/src/config.rs
+
+use anyhow::{Context, Result};
+use std::env;
+use std::io::IsTerminal;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use tokn_config::Secret;
+
+// ---
+
+/// Log line format for the stdout subscriber.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    // ---
+    /// Human-readable single-line output (default)
+    #[default]
+    Text,
+
+    /// One JSON object per event in the versioned tokn log schema, for log
+    /// aggregators (see [`JsonLogLayer`](crate::JsonLogLayer))
+    Json,
+}
+
+// ---
+
+impl FromStr for LogFormat {
+    // ---
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        // ---
+        match s.to_ascii_lowercase().as_str() {
+            "text" | "pretty" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            other => anyhow::bail!("Unknown log format '{other}' (expected 'text' or 'json')"),
+        }
+    }
+}
+
+// ---
+
+/// Telemetry settings shared by every tokn binary.
+///
+/// Normally built with [`TelemetryConfig::from_env`]; construct directly in tests
+/// or tools that should not read the environment.
+#[derive(Debug, Clone)]
+pub struct TelemetryConfig {
+    // ---
+    /// Service name reported in OTLP resources and the `tokn_build_info` metric
+    pub service_name: String,
+
+    /// Filter used when `RUST_LOG` is unset
+    pub default_filter: String,
+
+    /// Stdout log format
+    pub log_format: LogFormat,
+
+    /// Emit ANSI colors (text format only)
+    pub ansi: bool,
+
+    /// Key for hashing `user_id` fields into `user_hash` (JSON format only);
+    /// plain SHA-256 when unset
+    pub user_hash_key: Option<Secret>,
+
+    /// OTLP/gRPC collector endpoint; spans are exported only when set
+    pub otlp_endpoint: Option<String>,
+
+    /// Address for the Prometheus scrape endpoint; metrics are served only when set
+    pub metrics_addr: Option<SocketAddr>,
+}
+
+// ---
+
+impl TelemetryConfig {
+    // ---
+    /// Defaults for `service_name`: text logs at `debug` for the service crate
+    /// and `tower_http`, no OTLP export, no metrics endpoint.
+    pub fn new(service_name: &str) -> Self {
+        // ---
+        let crate_name = service_name.replace('-', "_");
+
+        Self {
+            service_name: service_name.to_string(),
+            default_filter: format!(
+                "{crate_name}=debug,tokn_config=info,tokn_server=info,tokn_resilience=info,tokn_events=info,tokn_telemetry=info,tower_http=debug"
+            ),
+            log_format: LogFormat::Text,
+            ansi: std::io::stdout().is_terminal(),
+            user_hash_key: None,
+            otlp_endpoint: None,
+            metrics_addr: None,
+        }
+    }
+
+    /// Load telemetry settings from environment variables (and `.env`).
+    ///
+    /// | Variable                      | Effect                                    |
+    /// |-------------------------------|-------------------------------------------|
+    /// | `RUST_LOG`                    | Log filter (falls back to the default)    |
+    /// | `LOG_FORMAT`                  | `text` (default) or `json`                |
+    /// | `LOG_USER_HASH_KEY`           | HMAC key for `user_hash` in JSON logs     |
+    /// | `OTEL_EXPORTER_OTLP_ENDPOINT` | Enables OTLP span export to this endpoint |
+    /// | `OTEL_SERVICE_NAME`           | Overrides the reported service name       |
+    /// | `METRICS_ADDR`                | Serves Prometheus metrics on this address |
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `LOG_FORMAT` or `METRICS_ADDR` cannot be parsed.
+    pub fn from_env(service_name: &str) -> Result<Self> {
+        // ---
+        dotenvy::dotenv().ok();
+
+        let mut config = Self::new(service_name);
+
+        if let Some(name) = non_empty("OTEL_SERVICE_NAME") {
+            config.service_name = name;
+        }
+        if let Some(format) = non_empty("LOG_FORMAT") {
+            config.log_format = format.parse()?;
+        }
+        config.user_hash_key = non_empty("LOG_USER_HASH_KEY").map(Secret::from);
+        config.otlp_endpoint = non_empty("OTEL_EXPORTER_OTLP_ENDPOINT");
+        config.metrics_addr = non_empty("METRICS_ADDR")
+            .map(|addr| {
+                addr.parse()
+                    .context("METRICS_ADDR must be a socket address")
+            })
+            .transpose()?;
+
+        Ok(config)
+    }
+}
+
+// ---
+
+fn non_empty(key: &str) -> Option<String> {
+    // ---
+    env::var(key).ok().filter(|value| !value.trim().is_empty())
+}
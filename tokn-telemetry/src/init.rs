@@ -0,0 +1,142 @@
+// tokn-telemetry/src/init.rs
+
+use anyhow::{Context, Result};
+use tracing_subscriber::{
+    fmt, layer::SubscriberExt, reload, util::SubscriberInitExt, EnvFilter, Layer, Registry,
+};
+
+// ---
+
+use crate::{JsonLogLayer, LogFilter, LogFormat, RedactingWriter, TelemetryConfig};
+
+// ---
+
+/// Keeps exporters alive for the life of the process.
+///
+/// Hold this in `main` (`let _telemetry = ...`); dropping it flushes and shuts
+/// down the OTLP pipeline so buffered spans are not lost on exit.
+#[must_use = "dropping the guard shuts down span export immediately"]
+pub struct TelemetryGuard {
+    // ---
+    log_filter: LogFilter,
+
+    #[cfg(feature = "otlp")]
+    tracer_provider: Option<opentelemetry_sdk::trace::SdkTracerProvider>,
+}
+
+// ---
+
+impl TelemetryGuard {
+    // ---
+    /// Handle for changing the active log filter at runtime.
+    pub fn log_filter(&self) -> LogFilter {
+        // ---
+        self.log_filter.clone()
+    }
+}
+
+// ---
+
+impl Drop for TelemetryGuard {
+    // ---
+    fn drop(&mut self) {
+        // ---
+        #[cfg(feature = "otlp")]
+        if let Some(provider) = self.tracer_provider.take() {
+            if let Err(e) = provider.shutdown() {
+                eprintln!("Failed to flush OTLP spans: {e}");
+            }
+        }
+    }
+}
+
+// ---
+
+/// Install the global tracing subscriber, optional OTLP export, and optional
+/// Prometheus metrics endpoint.
+///
+/// Call once, early in `main`, from within the Tokio runtime.
+///
+/// # Errors
+///
+/// Returns an error if a global subscriber is already installed, or if the
+/// OTLP exporter or metrics listener cannot be created.
+///
+/// # Example
+///
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() -> anyhow::Result<()> {
+/// use tokn_telemetry::TelemetryConfig;
+///
+/// let _telemetry = tokn_telemetry::init(&TelemetryConfig::from_env("jwt-service")?)?;
+/// tracing::info!("telemetry ready");
+/// # Ok(())
+/// # }
+/// ```
+pub fn init(config: &TelemetryConfig) -> Result<TelemetryGuard> {
+    // ---
+    let directives = std::env::var(EnvFilter::DEFAULT_ENV)
+        .ok()
+        .filter(|d| EnvFilter::try_new(d).is_ok())
+        .unwrap_or_else(|| config.default_filter.clone());
+    let (filter, handle) = reload::Layer::<EnvFilter, Registry>::new(EnvFilter::new(&directives));
+
+    let fmt_layer = match config.log_format {
+        LogFormat::Text => fmt::layer()
+            .with_ansi(config.ansi)
+            .with_writer(RedactingWriter(std::io::stdout))
+            .boxed(),
+        LogFormat::Json => {
+            let layer = JsonLogLayer::new(&config.service_name);
+            match &config.user_hash_key {
+                Some(key) => layer.with_user_hash_key(key.expose()).boxed(),
+                None => layer.boxed(),
+            }
+        }
+    };
+
+    // ---
+    #[cfg(feature = "otlp")]
+    let (otlp_layer, tracer_provider) = match &config.otlp_endpoint {
+        Some(endpoint) => {
+            let (layer, provider) = crate::otlp::layer(&config.service_name, endpoint)?;
+            (Some(layer), Some(provider))
+        }
+        None => (None, None),
+    };
+
+    let registry = tracing_subscriber::registry().with(filter).with(fmt_layer);
+
+    #[cfg(feature = "otlp")]
+    let registry = registry.with(otlp_layer);
+
+    registry
+        .try_init()
+        .context("A global tracing subscriber is already installed")?;
+
+    // ---
+    #[cfg(not(feature = "otlp"))]
+    if config.otlp_endpoint.is_some() {
+        tracing::warn!("OTEL_EXPORTER_OTLP_ENDPOINT is set but tokn-telemetry was built without the `otlp` feature");
+    }
+
+    match config.metrics_addr {
+        #[cfg(feature = "metrics")]
+        Some(addr) => {
+            crate::prometheus::install(&config.service_name, addr)?;
+            tracing::info!("Serving Prometheus metrics on http://{addr}/metrics");
+        }
+        #[cfg(not(feature = "metrics"))]
+        Some(_) => tracing::warn!(
+            "METRICS_ADDR is set but tokn-telemetry was built without the `metrics` feature"
+        ),
+        None => {}
+    }
+
+    Ok(TelemetryGuard {
+        log_filter: LogFilter::new(handle, directives, config.default_filter.clone()),
+        #[cfg(feature = "otlp")]
+        tracer_provider,
+    })
+}
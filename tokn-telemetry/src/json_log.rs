@@ -0,0 +1,351 @@
+// tokn-telemetry/src/json_log.rs
+
+use chrono::{SecondsFormat, Utc};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::io::{self, Write};
+use std::sync::Arc;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Record};
+use tracing::{Event, Id, Subscriber};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+use zeroize::Zeroizing;
+
+// ---
+
+/// Version of the JSON log line schema, emitted as `schema_version`.
+///
+/// Bumped only for breaking changes (a field renamed, removed, or retyped);
+/// new optional fields do not change it.
+pub const LOG_SCHEMA_VERSION: u32 = 1;
+
+/// Hex characters of the user hash kept in `user_hash` (64 bits).
+const USER_HASH_LEN: usize = 16;
+
+// ---
+
+/// `tracing` layer writing one JSON object per event in the tokn log schema
+/// (see `docs/development-setup.md`, "Log Schema").
+///
+/// Every line carries `schema_version`, `timestamp`, `level`, `service`,
+/// `target`, and `message`. `event`, `request_id`, `client_id`, and
+/// `user_hash` are added when the event or an enclosing span records an
+/// `event`, `request_id`, `client_id`, or `user_id` field; the innermost value
+/// wins. Any other event fields go under `fields`.
+///
+/// # Security
+///
+/// Each line is passed through [`redact`](crate::redact) before it is
+/// written, so secrets, codes, and tokens in messages or fields are masked.
+///
+/// `user_id` is never written as-is: it is replaced by a truncated SHA-256
+/// (HMAC-SHA256 when a key is set with [`with_user_hash_key`](Self::with_user_hash_key))
+/// so log readers can correlate a user's lines without learning who the user
+/// is. Without a key, short or guessable IDs can be recovered by hashing
+/// candidates, so set one in production.
+///
+/// # Example
+///
+/// ```no_run
+/// use tracing_subscriber::layer::SubscriberExt;
+/// use tracing_subscriber::util::SubscriberInitExt;
+///
+/// tracing_subscriber::registry()
+///     .with(tokn_telemetry::JsonLogLayer::new("jwt-service"))
+///     .init();
+///
+/// tracing::info!(event = "token_revoked", user_id = "user_123", "Token revoked");
+/// ```
+pub struct JsonLogLayer<W = fn() -> io::Stdout> {
+    // ---
+    service: String,
+    user_hash_key: Option<Arc<Zeroizing<Vec<u8>>>>,
+    make_writer: W,
+}
+
+// ---
+
+impl JsonLogLayer {
+    // ---
+    /// Layer writing to stdout, stamping every line with `service`.
+    pub fn new(service: &str) -> Self {
+        // ---
+        Self {
+            service: service.to_string(),
+            user_hash_key: None,
+            make_writer: io::stdout,
+        }
+    }
+}
+
+impl<W> JsonLogLayer<W> {
+    // ---
+    /// Hash user IDs with HMAC-SHA256 under `key` instead of plain SHA-256.
+    pub fn with_user_hash_key(mut self, key: impl AsRef<[u8]>) -> Self {
+        // ---
+        self.user_hash_key = Some(Arc::new(Zeroizing::new(key.as_ref().to_vec())));
+        self
+    }
+
+    /// Write lines to `make_writer` instead of stdout.
+    pub fn with_writer<W2>(self, make_writer: W2) -> JsonLogLayer<W2>
+    where
+        W2: for<'a> MakeWriter<'a> + 'static,
+    {
+        // ---
+        JsonLogLayer {
+            service: self.service,
+            user_hash_key: self.user_hash_key,
+            make_writer,
+        }
+    }
+
+    // ---
+    fn user_hash(&self, user_id: &str) -> String {
+        // ---
+        let digest = match &self.user_hash_key {
+            Some(key) => {
+                let mut mac =
+                    Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+                mac.update(user_id.as_bytes());
+                mac.finalize().into_bytes()
+            }
+            None => Sha256::digest(user_id.as_bytes()),
+        };
+
+        let mut hash = hex::encode(digest);
+        hash.truncate(USER_HASH_LEN);
+        hash
+    }
+}
+
+// ---
+
+impl<S, W> Layer<S> for JsonLogLayer<W>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'a> MakeWriter<'a> + 'static,
+{
+    // ---
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        // ---
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut fields = SchemaFields::default();
+        attrs.record(&mut fields);
+        span.extensions_mut().insert(fields);
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        // ---
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut extensions = span.extensions_mut();
+        if let Some(fields) = extensions.get_mut::<SchemaFields>() {
+            values.record(fields);
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        // ---
+        let mut visitor = EventVisitor::default();
+        event.record(&mut visitor);
+        let mut schema = visitor.schema;
+
+        // Inherit correlation fields from enclosing spans, innermost first
+        let mut span_name = None;
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope {
+                span_name.get_or_insert(span.name());
+                if let Some(fields) = span.extensions().get::<SchemaFields>() {
+                    schema.inherit(fields);
+                }
+            }
+        }
+
+        let metadata = event.metadata();
+        let line = LogLine {
+            schema_version: LOG_SCHEMA_VERSION,
+            timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Micros, true),
+            level: metadata.level().as_str(),
+            service: &self.service,
+            target: metadata.target(),
+            message: visitor.message,
+            event: schema.event,
+            request_id: schema.request_id,
+            client_id: schema.client_id,
+            user_hash: schema.user_id.as_deref().map(|id| self.user_hash(id)),
+            span: span_name,
+            fields: visitor.fields,
+        };
+
+        let Ok(json) = serde_json::to_string(&line) else {
+            return;
+        };
+        let mut buf = crate::redact(&json).into_owned();
+        buf.push('\n');
+        let _ = self.make_writer.make_writer().write_all(buf.as_bytes());
+    }
+}
+
+// ---
+
+/// One log line, in schema field order.
+#[derive(Serialize)]
+struct LogLine<'a> {
+    // ---
+    schema_version: u32,
+    timestamp: String,
+    level: &'static str,
+    service: &'a str,
+    target: &'a str,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    event: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    user_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    span: Option<&'static str>,
+    #[serde(skip_serializing_if = "Map::is_empty")]
+    fields: Map<String, Value>,
+}
+
+// ---
+
+/// Fields promoted to top-level schema keys, recorded on events and spans.
+#[derive(Default)]
+struct SchemaFields {
+    // ---
+    event: Option<String>,
+    request_id: Option<String>,
+    client_id: Option<String>,
+    user_id: Option<String>,
+}
+
+impl SchemaFields {
+    // ---
+    /// Store `value` if `name` is a schema field; false otherwise.
+    fn set(&mut self, name: &str, value: impl FnOnce() -> String) -> bool {
+        // ---
+        let slot = match name {
+            "event" => &mut self.event,
+            "request_id" => &mut self.request_id,
+            "client_id" => &mut self.client_id,
+            "user_id" => &mut self.user_id,
+            _ => return false,
+        };
+        *slot = Some(value());
+        true
+    }
+
+    /// Fill fields still unset from an enclosing span.
+    fn inherit(&mut self, outer: &SchemaFields) {
+        // ---
+        for (slot, value) in [
+            (&mut self.event, &outer.event),
+            (&mut self.request_id, &outer.request_id),
+            (&mut self.client_id, &outer.client_id),
+            (&mut self.user_id, &outer.user_id),
+        ] {
+            if slot.is_none() {
+                slot.clone_from(value);
+            }
+        }
+    }
+}
+
+impl Visit for SchemaFields {
+    // ---
+    fn record_str(&mut self, field: &Field, value: &str) {
+        // ---
+        self.set(field.name(), || value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        // ---
+        self.set(field.name(), || format!("{value:?}"));
+    }
+}
+
+// ---
+
+/// Collects an event's message, schema fields, and remaining fields.
+#[derive(Default)]
+struct EventVisitor {
+    // ---
+    message: String,
+    schema: SchemaFields,
+    fields: Map<String, Value>,
+}
+
+impl EventVisitor {
+    // ---
+    fn insert(&mut self, field: &Field, value: Value) {
+        // ---
+        let name = field.name();
+        if name == "message" {
+            self.message = match value {
+                Value::String(s) => s,
+                other => other.to_string(),
+            };
+            return;
+        }
+        let promoted = self.schema.set(name, || match &value {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        });
+        if !promoted {
+            self.fields.insert(name.to_string(), value);
+        }
+    }
+}
+
+impl Visit for EventVisitor {
+    // ---
+    fn record_str(&mut self, field: &Field, value: &str) {
+        // ---
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        // ---
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        // ---
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        // ---
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        // ---
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_error(&mut self, field: &Field, value: &(dyn std::error::Error + 'static)) {
+        // ---
+        self.insert(field, Value::from(value.to_string()));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        // ---
+        self.insert(field, Value::from(format!("{value:?}")));
+    }
+}
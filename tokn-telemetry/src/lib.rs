@@ -0,0 +1,40 @@
+// tokn-telemetry/src/lib.rs
+
+//! Shared telemetry initialization for tokn binaries
+//!
+//! One call sets up everything observability-related, identically in every
+//! service:
+//! - `tracing` subscriber with `RUST_LOG` filtering and text or JSON output; the
+//!   filter can be swapped at runtime through [`LogFilter`], and JSON lines follow
+//!   a versioned schema (see [`JsonLogLayer`]); secrets, codes, and tokens are
+//!   masked in every line before it is written (see [`redact`])
+//! - OTLP/gRPC span export when `OTEL_EXPORTER_OTLP_ENDPOINT` is set (`otlp` feature)
+//! - Prometheus scrape endpoint when `METRICS_ADDR` is set (`metrics` feature)
+//!
+//! ```no_run
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! use tokn_telemetry::TelemetryConfig;
+//!
+//! let _telemetry = tokn_telemetry::init(&TelemetryConfig::from_env("oauth2-server")?)?;
+//! # Ok(())
+//! # }
+//! ```
+
+mod config;
+mod init;
+mod json_log;
+mod log_filter;
+#[cfg(feature = "otlp")]
+mod otlp;
+#[cfg(feature = "metrics")]
+mod prometheus;
+mod redact;
+
+// ---
+
+pub use config::{LogFormat, TelemetryConfig};
+pub use init::{init, TelemetryGuard};
+pub use json_log::{JsonLogLayer, LOG_SCHEMA_VERSION};
+pub use log_filter::{LogConfig, LogFilter};
+pub use redact::{redact, Redacting, RedactingWriter, REDACTED};
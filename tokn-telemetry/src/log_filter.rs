@@ -0,0 +1,114 @@
+// tokn-telemetry/src/log_filter.rs
+
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use std::sync::{Arc, Mutex};
+use tracing_subscriber::{reload, EnvFilter, Registry};
+
+// ---
+
+/// Service configuration section for logging.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct LogConfig {
+    // ---
+    /// Filter directives (key `log.filter`, env `RUST_LOG`); the service
+    /// default when unset. Reloadable at runtime.
+    pub filter: Option<String>,
+}
+
+// ---
+
+/// Handle to the active `RUST_LOG`-style filter, obtained from
+/// [`TelemetryGuard::log_filter`](crate::TelemetryGuard::log_filter).
+///
+/// Cloning is cheap; clones change the same filter.
+///
+/// # Example
+///
+/// ```no_run
+/// # fn example(telemetry: &tokn_telemetry::TelemetryGuard) -> anyhow::Result<()> {
+/// let log_filter = telemetry.log_filter();
+/// log_filter.set(Some("jwt_service=trace,tower_http=info"))?;
+/// log_filter.set(None)?; // back to the service default
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct LogFilter {
+    // ---
+    handle: reload::Handle<EnvFilter, Registry>,
+    current: Arc<Mutex<String>>,
+    default: Arc<str>,
+}
+
+// ---
+
+impl LogFilter {
+    // ---
+    pub(crate) fn new(
+        handle: reload::Handle<EnvFilter, Registry>,
+        current: String,
+        default: String,
+    ) -> Self {
+        // ---
+        Self {
+            handle,
+            current: Arc::new(Mutex::new(current)),
+            default: default.into(),
+        }
+    }
+
+    // ---
+    /// Directives currently in effect.
+    pub fn current(&self) -> String {
+        // ---
+        self.lock().clone()
+    }
+
+    // ---
+    /// Replace the filter with `directives`, or with the service default when
+    /// `None`. Returns the directives that were in effect before.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `directives` do not parse; the active filter is left
+    /// unchanged.
+    pub fn set(&self, directives: Option<&str>) -> Result<String> {
+        // ---
+        let directives = directives.unwrap_or(&self.default);
+        // The parse error repeats its message as its source, so format it
+        // directly rather than chaining it as context
+        let filter = EnvFilter::try_new(directives)
+            .map_err(|e| anyhow!("Invalid log filter '{directives}': {e}"))?;
+
+        let mut current = self.lock();
+        self.handle
+            .reload(filter)
+            .context("Failed to swap the log filter")?;
+
+        Ok(std::mem::replace(&mut *current, directives.to_string()))
+    }
+
+    // ---
+    /// Apply a service's `log` section at startup, when the filter comes from
+    /// the config file rather than `RUST_LOG`. An invalid filter is logged and
+    /// the current one kept.
+    pub fn apply(&self, config: &LogConfig) {
+        // ---
+        let Some(directives) = config.filter.as_deref() else {
+            return;
+        };
+        if *self.lock() == directives {
+            return;
+        }
+        if let Err(e) = self.set(Some(directives)) {
+            tracing::warn!("{e:#}; keeping '{}'", self.current());
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, String> {
+        // ---
+        self.current.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
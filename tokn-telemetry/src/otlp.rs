@@ -0,0 +1,43 @@
+// tokn-telemetry/src/otlp.rs
+
+use anyhow::{Context, Result};
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::{trace::SdkTracerProvider, Resource};
+use tracing::Subscriber;
+use tracing_subscriber::{registry::LookupSpan, Layer};
+
+// ---
+
+/// Build the OTLP/gRPC tracer provider and the `tracing` layer that feeds it.
+///
+/// Must be called from within a Tokio runtime (the gRPC channel is created
+/// eagerly). The returned provider must be shut down on exit to flush spans.
+pub(crate) fn layer<S>(
+    service_name: &str,
+    endpoint: &str,
+) -> Result<(impl Layer<S>, SdkTracerProvider)>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    // ---
+    let exporter = SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(endpoint)
+        .build()
+        .with_context(|| format!("Failed to create OTLP exporter for {endpoint}"))?;
+
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(
+            Resource::builder()
+                .with_service_name(service_name.to_string())
+                .build(),
+        )
+        .build();
+
+    opentelemetry::global::set_tracer_provider(provider.clone());
+    let tracer = provider.tracer(service_name.to_string());
+
+    Ok((tracing_opentelemetry::layer().with_tracer(tracer), provider))
+}
@@ -0,0 +1,32 @@
+// tokn-telemetry/src/prometheus.rs
+
+use anyhow::{Context, Result};
+use metrics_exporter_prometheus::PrometheusBuilder;
+use std::net::SocketAddr;
+
+// ---
+
+/// Install the global Prometheus recorder and serve it on `addr`.
+///
+/// Also registers `tokn_build_info{service, version}` (always 1) so every
+/// service exposes at least one series identifying itself.
+pub(crate) fn install(service_name: &str, addr: SocketAddr) -> Result<()> {
+    // ---
+    PrometheusBuilder::new()
+        .with_http_listener(addr)
+        .install()
+        .with_context(|| format!("Failed to start metrics endpoint on {addr}"))?;
+
+    metrics::describe_gauge!(
+        "tokn_build_info",
+        "Build information for the running tokn service (always 1)"
+    );
+    metrics::gauge!(
+        "tokn_build_info",
+        "service" => service_name.to_string(),
+        "version" => env!("CARGO_PKG_VERSION"),
+    )
+    .set(1.0);
+
+    Ok(())
+}
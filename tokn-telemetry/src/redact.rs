@@ -0,0 +1,133 @@
+// tokn-telemetry/src/redact.rs
+
+use regex::{Regex, RegexBuilder};
+use std::borrow::Cow;
+use std::io::{self, Write};
+use std::sync::LazyLock;
+use tracing_subscriber::fmt::MakeWriter;
+
+// ---
+
+/// Replacement text for masked values.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Names whose values are masked wherever they appear as `name=value`,
+/// `"name": "value"`, or `name: "value"` (Debug output). Matched whole and
+/// case-insensitively, so `token_type` and `error_code` are left alone.
+const SENSITIVE_KEYS: &str = "client_secret|password|passwd|secret|api_key|authorization|\
+                              token|access_token|refresh_token|id_token|code|code_verifier|assertion";
+
+// ---
+
+/// Ordered masking rules: (pattern, replacement).
+static RULES: LazyLock<Vec<(Regex, &'static str)>> = LazyLock::new(|| {
+    // ---
+    let rule = |pattern: String, replacement| {
+        let regex = RegexBuilder::new(&pattern)
+            .case_insensitive(true)
+            .build()
+            .expect("redaction patterns are valid");
+        (regex, replacement)
+    };
+
+    vec![
+        // JSON: "client_secret": "..."
+        rule(
+            format!(r#""({SENSITIVE_KEYS})"(\s*:\s*)"(?:[^"\\]|\\.)*""#),
+            r#""$1"$2"[REDACTED]""#,
+        ),
+        // Debug structs: client_secret: "..." or refresh_token: Some("...")
+        rule(
+            format!(r#"\b({SENSITIVE_KEYS}): (Some\()?"(?:[^"\\]|\\.)*""#),
+            r#"$1: $2"[REDACTED]""#,
+        ),
+        // Form bodies, query strings, and fmt fields: client_secret=... or ="..."
+        rule(
+            format!(r#"\b({SENSITIVE_KEYS})=(?:"(?:[^"\\]|\\.)*"|[^&\s"',]+)"#),
+            "$1=[REDACTED]",
+        ),
+        // Authorization headers; a following key=value is not a token
+        rule(
+            r"\bBearer\s+[A-Za-z0-9\-._~+/]+=*([^A-Za-z0-9\-._~+/=]|$)".to_string(),
+            "Bearer [REDACTED]$1",
+        ),
+        // Anything shaped like a JWT
+        rule(
+            r"\beyJ[A-Za-z0-9_-]*\.[A-Za-z0-9_-]+\.[A-Za-z0-9_-]*".to_string(),
+            REDACTED,
+        ),
+    ]
+});
+
+// ---
+
+/// Mask secrets, authorization codes, and tokens in `text`.
+///
+/// Used by every log sink `tokn_telemetry::init` installs; call it directly
+/// before putting untrusted or credential-bearing text anywhere else (error
+/// responses, span attributes exported over OTLP).
+///
+/// # Example
+///
+/// ```
+/// let body = "grant_type=authorization_code&code=abc123&client_secret=s3cret";
+/// assert_eq!(
+///     tokn_telemetry::redact(body),
+///     "grant_type=authorization_code&code=[REDACTED]&client_secret=[REDACTED]"
+/// );
+/// ```
+pub fn redact(text: &str) -> Cow<'_, str> {
+    // ---
+    let mut text = Cow::Borrowed(text);
+    for (regex, replacement) in RULES.iter() {
+        if let Cow::Owned(replaced) = regex.replace_all(&text, *replacement) {
+            text = Cow::Owned(replaced);
+        }
+    }
+    text
+}
+
+// ---
+
+/// [`MakeWriter`] that passes every log line through [`redact`] before
+/// writing it to the inner writer.
+///
+/// Relies on the formatter handing over each event in a single write, as
+/// `tracing_subscriber::fmt` and [`JsonLogLayer`](crate::JsonLogLayer) do.
+#[derive(Debug, Clone)]
+pub struct RedactingWriter<W>(pub W);
+
+/// Writer produced by [`RedactingWriter`].
+pub struct Redacting<W>(W);
+
+// ---
+
+impl<'a, M> MakeWriter<'a> for RedactingWriter<M>
+where
+    M: MakeWriter<'a>,
+{
+    // ---
+    type Writer = Redacting<M::Writer>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        // ---
+        Redacting(self.0.make_writer())
+    }
+}
+
+impl<W: Write> Write for Redacting<W> {
+    // ---
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // ---
+        match std::str::from_utf8(buf) {
+            Ok(text) => self.0.write_all(redact(text).as_bytes())?,
+            Err(_) => self.0.write_all(buf)?,
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        // ---
+        self.0.flush()
+    }
+}
@@ -0,0 +1,90 @@
+// tokn-theme/src/config.rs
+
+use serde::de::{self, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+
+// ---
+
+/// Service configuration section for page theming.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ThemeConfig {
+    // ---
+    /// Directory of named themes, one subdirectory each (env `THEME_DIR`)
+    pub dir: Option<PathBuf>,
+
+    /// Theme for every page without a client-specific one
+    /// (env `THEME_NAME`, default `default`)
+    pub name: String,
+
+    /// Per-client themes for the consent and error pages, by `client_id`
+    /// (env `THEME_CLIENTS`, e.g. `acme_web=acme,beta_app=beta`)
+    pub clients: ClientThemes,
+}
+
+// ---
+
+impl Default for ThemeConfig {
+    // ---
+    fn default() -> Self {
+        // ---
+        Self {
+            dir: None,
+            name: "default".to_string(),
+            clients: ClientThemes::default(),
+        }
+    }
+}
+
+// ---
+
+/// Theme name by OAuth2 `client_id`.
+///
+/// Deserializes from a table (`[theme.clients]` in a config file) or from a
+/// comma-separated `client=theme` list (the `THEME_CLIENTS` variable).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientThemes(pub BTreeMap<String, String>);
+
+impl<'de> Deserialize<'de> for ClientThemes {
+    // ---
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // ---
+        deserializer.deserialize_any(ClientThemesVisitor)
+    }
+}
+
+struct ClientThemesVisitor;
+
+impl<'de> Visitor<'de> for ClientThemesVisitor {
+    // ---
+    type Value = ClientThemes;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // ---
+        f.write_str("a table or a comma-separated list of client=theme pairs")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        // ---
+        let mut clients = BTreeMap::new();
+        for pair in value.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (client, theme) = pair
+                .split_once('=')
+                .ok_or_else(|| E::custom(format!("expected client=theme, got '{pair}'")))?;
+            clients.insert(client.trim().to_string(), theme.trim().to_string());
+        }
+        Ok(ClientThemes(clients))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        // ---
+        let mut clients = BTreeMap::new();
+        while let Some((client, theme)) = map.next_entry::<String, String>()? {
+            clients.insert(client, theme);
+        }
+        Ok(ClientThemes(clients))
+    }
+}
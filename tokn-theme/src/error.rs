@@ -0,0 +1,25 @@
+// tokn-theme/src/error.rs
+
+use std::path::PathBuf;
+
+// ---
+
+/// Errors loading the configured themes.
+#[derive(Debug, thiserror::Error)]
+pub enum ThemeError {
+    // ---
+    /// A theme name is not a plain directory name (`[A-Za-z0-9_-]+`)
+    #[error("invalid theme name '{0}'")]
+    InvalidName(String),
+
+    /// The deployment or a client was assigned a theme that does not exist
+    #[error("theme '{0}' not found (available: {1})")]
+    UnknownTheme(String, String),
+
+    /// A theme directory or template could not be read
+    #[error("failed to read {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+}
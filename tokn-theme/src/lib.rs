@@ -0,0 +1,30 @@
+// tokn-theme/src/lib.rs
+
+//! Theming for tokn's rendered HTML pages
+//!
+//! Pages supply only their body content; a [`Theme`] wraps it in layout,
+//! head, header, and footer partials and serves the stylesheets and images
+//! those reference. Operators brand the consent, error, and demo pages by
+//! dropping replacement partials and assets into a theme directory instead
+//! of forking the templates compiled into the binaries, and can give
+//! individual OAuth2 clients their own theme (see [`ThemeConfig`]).
+//!
+//! A theme directory looks like:
+//!
+//! ```text
+//! $THEME_DIR/acme/
+//! ├── header.html      # any of layout/head/header/footer.html; the rest are built in
+//! └── static/          # served at /theme/acme/static/
+//!     ├── theme.css    # replaces the built-in stylesheet
+//!     └── logo.svg
+//! ```
+
+mod config;
+mod error;
+mod theme;
+
+// ---
+
+pub use config::{ClientThemes, ThemeConfig};
+pub use error::ThemeError;
+pub use theme::{Page, Theme, Themes, DEFAULT_THEME};
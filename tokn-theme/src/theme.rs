@@ -0,0 +1,317 @@
+// tokn-theme/src/theme.rs
+
+use axum::{http::header, routing::get, Router};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tower_http::services::ServeDir;
+
+// ---
+
+use crate::{ThemeConfig, ThemeError};
+
+// ---
+
+/// Name of the built-in theme, used unless `THEME_NAME` selects another.
+pub const DEFAULT_THEME: &str = "default";
+
+/// Built-in partials, as `(file stem, template)`; a theme directory may
+/// replace any of them with `<stem>.html`.
+const PARTIALS: [(&str, &str); 4] = [
+    ("layout", include_str!("../templates/layout.html")),
+    ("head", include_str!("../templates/head.html")),
+    ("header", include_str!("../templates/header.html")),
+    ("footer", include_str!("../templates/footer.html")),
+];
+
+/// Built-in stylesheet, served as `theme.css` by every theme that lacks one.
+const THEME_CSS: &str = include_str!("../static/theme.css");
+
+// ---
+
+/// One rendered page: the body content plus what the layout needs around it.
+#[derive(Debug, Clone, Copy)]
+pub struct Page<'a> {
+    // ---
+    /// Language tag for `<html lang>`
+    pub lang: &'a str,
+
+    /// Page title (trusted text; escape request data before passing it)
+    pub title: &'a str,
+
+    /// Body HTML placed inside the layout's `<main>`
+    pub content: &'a str,
+}
+
+// ---
+
+/// A set of page partials (`layout`, `head`, `header`, `footer`) and the
+/// directory of static assets they reference.
+///
+/// Templates substitute `{{ name }}` placeholders in a single pass, so text
+/// inserted for one placeholder is never expanded again. Every partial sees
+/// `lang`, `title`, and `static` (this theme's asset URL prefix); `layout`
+/// also gets `head`, `header`, `content`, and `footer`.
+#[derive(Debug, Clone)]
+pub struct Theme {
+    // ---
+    name: String,
+    partials: BTreeMap<&'static str, String>,
+    static_dir: Option<PathBuf>,
+}
+
+impl Theme {
+    // ---
+    /// The theme's name (its directory name under `THEME_DIR`).
+    pub fn name(&self) -> &str {
+        // ---
+        &self.name
+    }
+
+    /// URL prefix the theme's static assets are served under.
+    pub fn static_url(&self) -> String {
+        // ---
+        format!("/theme/{}/static", self.name)
+    }
+
+    /// Render `page` into a complete HTML document.
+    pub fn render(&self, page: &Page<'_>) -> String {
+        // ---
+        let static_url = self.static_url();
+        let common = [
+            ("lang", page.lang),
+            ("title", page.title),
+            ("static", static_url.as_str()),
+        ];
+        let head = substitute(&self.partials["head"], &common);
+        let header = substitute(&self.partials["header"], &common);
+        let footer = substitute(&self.partials["footer"], &common);
+
+        let mut vars = common.to_vec();
+        vars.extend([
+            ("head", head.as_str()),
+            ("header", header.as_str()),
+            ("content", page.content),
+            ("footer", footer.as_str()),
+        ]);
+        substitute(&self.partials["layout"], &vars)
+    }
+
+    // ---
+    fn builtin(name: &str) -> Self {
+        // ---
+        Self {
+            name: name.to_string(),
+            partials: PARTIALS
+                .iter()
+                .map(|(stem, template)| (*stem, template.to_string()))
+                .collect(),
+            static_dir: None,
+        }
+    }
+
+    /// Built-in partials overridden by the `<stem>.html` files in `dir`.
+    fn load(name: &str, dir: &Path) -> Result<Self, ThemeError> {
+        // ---
+        let mut theme = Self::builtin(name);
+        for (stem, _) in PARTIALS {
+            let path = dir.join(format!("{stem}.html"));
+            if path.is_file() {
+                let template = std::fs::read_to_string(&path)
+                    .map_err(|source| ThemeError::Io { path, source })?;
+                theme.partials.insert(stem, template);
+            }
+        }
+
+        let static_dir = dir.join("static");
+        theme.static_dir = static_dir.is_dir().then_some(static_dir);
+        Ok(theme)
+    }
+}
+
+// ---
+
+/// Every configured theme, and which one each page uses.
+///
+/// Always contains the built-in [`DEFAULT_THEME`]; each subdirectory of
+/// `THEME_DIR` adds a theme of that name (a `default/` directory customizes
+/// the built-in one). Pages for a client listed in `THEME_CLIENTS` use its
+/// theme, everything else the deployment's `THEME_NAME`.
+///
+/// # Example
+///
+/// ```
+/// use tokn_theme::{Page, Themes};
+///
+/// let themes = Themes::builtin();
+/// let html = themes.for_client(Some("any_client")).render(&Page {
+///     lang: "en",
+///     title: "Hello",
+///     content: "<p>Hi</p>",
+/// });
+/// assert!(html.contains("<title>Hello</title>"));
+/// assert!(html.contains(r#"href="/theme/default/static/theme.css""#));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Themes {
+    // ---
+    themes: BTreeMap<String, Theme>,
+    default: String,
+    clients: BTreeMap<String, String>,
+}
+
+impl Themes {
+    // ---
+    /// Load the themes in `config.dir` and check every assigned theme exists.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ThemeError`] when a theme directory or template cannot be
+    /// read, a theme name is not a plain directory name, or `THEME_NAME` or
+    /// `THEME_CLIENTS` names a theme that does not exist.
+    pub fn new(config: &ThemeConfig) -> Result<Self, ThemeError> {
+        // ---
+        let mut themes = BTreeMap::new();
+        themes.insert(DEFAULT_THEME.to_string(), Theme::builtin(DEFAULT_THEME));
+
+        if let Some(dir) = &config.dir {
+            let io_error = |source| ThemeError::Io {
+                path: dir.clone(),
+                source,
+            };
+            let mut theme_dirs: Vec<PathBuf> = std::fs::read_dir(dir)
+                .map_err(io_error)?
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.is_dir())
+                .collect();
+            theme_dirs.sort();
+
+            for theme_dir in theme_dirs {
+                let name = theme_dir
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string();
+                validate_name(&name)?;
+                themes.insert(name.clone(), Theme::load(&name, &theme_dir)?);
+                tracing::info!("Loaded theme '{name}' from {}", theme_dir.display());
+            }
+        }
+
+        let require = |name: &str| {
+            validate_name(name)?;
+            if themes.contains_key(name) {
+                Ok(())
+            } else {
+                let names: Vec<&str> = themes.keys().map(String::as_str).collect();
+                Err(ThemeError::UnknownTheme(name.to_string(), names.join(", ")))
+            }
+        };
+        require(&config.name)?;
+        for theme in config.clients.0.values() {
+            require(theme)?;
+        }
+
+        Ok(Self {
+            default: config.name.clone(),
+            clients: config.clients.0.clone(),
+            themes,
+        })
+    }
+
+    /// Only the built-in theme.
+    pub fn builtin() -> Self {
+        // ---
+        Self::new(&ThemeConfig::default()).expect("built-in theme is valid")
+    }
+
+    /// The deployment's theme, for pages not tied to a client.
+    pub fn default_theme(&self) -> &Theme {
+        // ---
+        &self.themes[&self.default]
+    }
+
+    /// The theme for pages shown on behalf of `client_id`.
+    pub fn for_client(&self, client_id: Option<&str>) -> &Theme {
+        // ---
+        client_id
+            .and_then(|client_id| self.clients.get(client_id))
+            .and_then(|name| self.themes.get(name))
+            .unwrap_or_else(|| self.default_theme())
+    }
+
+    /// Routes serving each theme's static assets at
+    /// `/theme/<name>/static/...`.
+    ///
+    /// Files come from the theme's `static/` directory; the built-in
+    /// `theme.css` is served when the theme does not provide its own.
+    pub fn static_router<S>(&self) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        // ---
+        self.themes
+            .values()
+            .fold(Router::new(), |router, theme| match &theme.static_dir {
+                Some(dir) => router.nest_service(
+                    &theme.static_url(),
+                    ServeDir::new(dir).fallback(builtin_assets()),
+                ),
+                None => router.nest_service(&theme.static_url(), builtin_assets()),
+            })
+    }
+}
+
+// ---
+
+fn builtin_assets() -> Router {
+    // ---
+    Router::new().route(
+        "/theme.css",
+        get(|| async {
+            (
+                [(header::CONTENT_TYPE, "text/css; charset=utf-8")],
+                THEME_CSS,
+            )
+        }),
+    )
+}
+
+fn validate_name(name: &str) -> Result<(), ThemeError> {
+    // ---
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(ThemeError::InvalidName(name.to_string()))
+    }
+}
+
+/// Replace each `{{ name }}` in `template` with its value from `vars`, in a
+/// single left-to-right pass. Unknown placeholders are left as they are.
+fn substitute(template: &str, vars: &[(&str, &str)]) -> String {
+    // ---
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            rest = &rest[start..];
+            break;
+        };
+
+        let name = after[..end].trim();
+        match vars.iter().find(|(var, _)| *var == name) {
+            Some((_, value)) => out.push_str(value),
+            None => out.push_str(&rest[start..start + 2 + end + 2]),
+        }
+        rest = &after[end + 2..];
+    }
+
+    out.push_str(rest);
+    out
+}